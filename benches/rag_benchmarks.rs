@@ -135,6 +135,7 @@ fn bench_query_latency(c: &mut Criterion) {
                         vector: black_box(query_embedding.clone()),
                         k: black_box(k),
                         filter: None,
+                        ..Default::default()
                     })
                     .unwrap()
             });
@@ -184,6 +185,7 @@ fn bench_multi_query_fusion(c: &mut Criterion) {
                         vector: mock_embed(variant),
                         k: 5,
                         filter: None,
+                        ..Default::default()
                     })
                     .unwrap();
                 all_results.push(results);
@@ -233,6 +235,7 @@ fn bench_e2e_rag_pipeline(c: &mut Criterion) {
                     vector: mock_embed(black_box(query)),
                     k: 3,
                     filter: None,
+                    ..Default::default()
                 })
                 .unwrap();
 
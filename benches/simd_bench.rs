@@ -5,6 +5,8 @@ fn generate_random_vector(dim: usize) -> Vec<f32> {
     (0..dim).map(|i| (i as f32 * 0.123) % 1.0).collect()
 }
 
+// Each group measures the scalar reference next to the dispatched SIMD path
+// (see `simd_level()`) so the speedup can be read off per dimension
 fn bench_euclidean_distance(c: &mut Criterion) {
     let mut group = c.benchmark_group("euclidean_distance");
 
@@ -14,6 +16,9 @@ fn bench_euclidean_distance(c: &mut Criterion) {
         let a = generate_random_vector(*size);
         let b = generate_random_vector(*size);
 
+        group.bench_with_input(BenchmarkId::new("scalar", size), size, |bencher, _| {
+            bencher.iter(|| black_box(euclidean_distance_scalar(black_box(&a), black_box(&b))));
+        });
         group.bench_with_input(BenchmarkId::new("simd", size), size, |bencher, _| {
             bencher.iter(|| black_box(euclidean_distance_simd(black_box(&a), black_box(&b))));
         });
@@ -31,6 +36,9 @@ fn bench_dot_product(c: &mut Criterion) {
         let a = generate_random_vector(*size);
         let b = generate_random_vector(*size);
 
+        group.bench_with_input(BenchmarkId::new("scalar", size), size, |bencher, _| {
+            bencher.iter(|| black_box(dot_product_scalar(black_box(&a), black_box(&b))));
+        });
         group.bench_with_input(BenchmarkId::new("simd", size), size, |bencher, _| {
             bencher.iter(|| black_box(dot_product_simd(black_box(&a), black_box(&b))));
         });
@@ -48,6 +56,9 @@ fn bench_cosine_similarity(c: &mut Criterion) {
         let a = generate_random_vector(*size);
         let b = generate_random_vector(*size);
 
+        group.bench_with_input(BenchmarkId::new("scalar", size), size, |bencher, _| {
+            bencher.iter(|| black_box(cosine_similarity_scalar(black_box(&a), black_box(&b))));
+        });
         group.bench_with_input(BenchmarkId::new("simd", size), size, |bencher, _| {
             bencher.iter(|| black_box(cosine_similarity_simd(black_box(&a), black_box(&b))));
         });
@@ -1,6 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use std::collections::HashMap;
-use vecstore::{make_record, FilterExpr, FilterOp, Metadata, Query, VecStore};
+use vecstore::{make_record, FieldIndexType, FilterExpr, FilterOp, Metadata, Query, VecStore};
 
 fn setup_store_with_data(n: usize, dim: usize) -> (tempfile::TempDir, VecStore) {
     let temp_dir = tempfile::tempdir().unwrap();
@@ -95,6 +95,7 @@ fn bench_query(c: &mut Criterion) {
                         vector: query_vec.clone(),
                         k: 10,
                         filter: None,
+                        ..Default::default()
                     };
                     black_box(store.query(query).unwrap());
                 });
@@ -130,6 +131,7 @@ fn bench_query_with_filter(c: &mut Criterion) {
                             value: serde_json::json!("cat5"),
                         },
                     ])),
+                    ..Default::default()
                 };
                 black_box(store.query(query).unwrap());
             });
@@ -139,6 +141,63 @@ fn bench_query_with_filter(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_field_index(c: &mut Criterion) {
+    const SIZE: usize = 1_000_000;
+    const DIM: usize = 16;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut store = VecStore::open(temp_dir.path()).unwrap();
+    for chunk in (0..SIZE).collect::<Vec<_>>().chunks(50_000) {
+        let records = chunk.iter().map(|&i| {
+            let mut meta = Metadata {
+                fields: HashMap::new(),
+            };
+            meta.fields.insert(
+                "category".into(),
+                serde_json::json!(format!("cat{}", i % 100)),
+            );
+            meta.fields
+                .insert("score".into(), serde_json::json!(i % 1000));
+            let vector: Vec<f32> = (0..DIM).map(|j| ((i * 31 + j * 7) % 97) as f32).collect();
+            make_record(format!("doc{}", i), vector, meta)
+        });
+        store.batch_upsert(records).unwrap();
+    }
+
+    let query_vec: Vec<f32> = (0..DIM).map(|i| i as f32).collect();
+    let filters = [
+        ("category_eq", "category = 'cat5'"),
+        ("score_range", "score >= 100 AND score < 150"),
+        ("selective_and", "category = 'cat5' AND score < 500"),
+    ];
+
+    let mut group = c.benchmark_group("field_index_1m");
+    group.sample_size(20);
+    for indexed in [false, true] {
+        if indexed {
+            store
+                .create_field_index("category", FieldIndexType::Keyword)
+                .unwrap();
+            store
+                .create_field_index("score", FieldIndexType::Numeric)
+                .unwrap();
+        }
+        let label = if indexed { "indexed" } else { "unindexed" };
+        for (name, filter) in filters {
+            group.bench_function(BenchmarkId::new(name, label), |b| {
+                b.iter(|| {
+                    let query = Query::new(query_vec.clone())
+                        .with_limit(10)
+                        .with_filter(filter);
+                    black_box(store.query(query).unwrap());
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
 fn bench_persistence(c: &mut Criterion) {
     let mut group = c.benchmark_group("persistence");
 
@@ -182,6 +241,7 @@ fn bench_different_dimensions(c: &mut Criterion) {
                     vector: query_vec.clone(),
                     k: 10,
                     filter: None,
+                    ..Default::default()
                 };
                 black_box(store.query(query).unwrap());
             });
@@ -206,6 +266,7 @@ fn bench_complex_filters(c: &mut Criterion) {
                     op: FilterOp::Eq,
                     value: serde_json::json!("cat5"),
                 }),
+                ..Default::default()
             };
             black_box(store.query(query).unwrap());
         });
@@ -228,6 +289,7 @@ fn bench_complex_filters(c: &mut Criterion) {
                         value: serde_json::json!(80),
                     },
                 ])),
+                ..Default::default()
             };
             black_box(store.query(query).unwrap());
         });
@@ -264,6 +326,7 @@ fn bench_complex_filters(c: &mut Criterion) {
                         },
                     ]),
                 ])),
+                ..Default::default()
             };
             black_box(store.query(query).unwrap());
         });
@@ -278,6 +341,7 @@ criterion_group!(
     bench_batch_insert,
     bench_query,
     bench_query_with_filter,
+    bench_field_index,
     bench_persistence,
     bench_different_dimensions,
     bench_complex_filters,
@@ -110,6 +110,7 @@ fn main() -> Result<()> {
             vector: query_embedding,
             k: 3,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query)?;
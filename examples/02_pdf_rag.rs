@@ -88,6 +88,7 @@ fn main() -> Result<()> {
         vector: query_embedding,
         k: 3,
         filter: None,
+        ..Default::default()
     })?;
 
     println!("   📄 Top {} results:", results.len());
@@ -89,6 +89,7 @@ fn main() -> Result<()> {
             vector: mock_embed(query_text),
             k: 3,
             filter: None,
+            ..Default::default()
         })?;
 
         for (i, result) in results.iter().enumerate() {
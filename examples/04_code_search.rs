@@ -87,6 +87,7 @@ impl VecStore {
             vector: mock_embed(search_query),
             k: 2,
             filter: None,
+            ..Default::default()
         })?;
 
         for (i, result) in results.iter().enumerate() {
@@ -74,6 +74,7 @@ fn main() -> Result<()> {
         vector: mock_embed(query),
         k: 20,
         filter: None,
+        ..Default::default()
     })?;
 
     println!("   ✓ Retrieved {} candidates", stage1_results.len());
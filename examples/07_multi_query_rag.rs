@@ -70,6 +70,7 @@ fn main() -> Result<()> {
             vector: mock_embed(variant),
             k: 3,
             filter: None,
+            ..Default::default()
         })?;
         all_results.push(results);
     }
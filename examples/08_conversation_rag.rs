@@ -79,6 +79,7 @@ fn main() -> Result<()> {
                 vector: query_embedding,
                 k: 2,
                 filter: None,
+                ..Default::default()
             })?;
 
             let context: Vec<String> = results
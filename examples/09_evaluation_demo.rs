@@ -62,6 +62,7 @@ fn main() -> Result<()> {
             vector: mock_embed(query),
             k: 2,
             filter: None,
+            ..Default::default()
         })?;
 
         // Simple relevance score (in production, use vecstore-eval)
@@ -99,6 +99,7 @@ fn main() -> Result<()> {
             vector: query_embedding,
             k: 5,
             filter: None,
+            ..Default::default()
         })?;
 
         let elapsed = start.elapsed();
@@ -152,6 +152,7 @@ fn main() -> Result<()> {
         vector: mock_search_query(),
         k: 10,
         filter: Some(filter),
+        ..Default::default()
     })?;
 
     println!("   Results: {} products\n", results.len());
@@ -181,6 +182,7 @@ fn main() -> Result<()> {
         vector: mock_search_query(),
         k: 10,
         filter: Some(filter),
+        ..Default::default()
     })?;
 
     println!("   Results: {} products\n", results.len());
@@ -224,6 +226,7 @@ fn main() -> Result<()> {
         vector: mock_search_query(),
         k: 10,
         filter: Some(filter),
+        ..Default::default()
     })?;
 
     println!("   Results: {} products\n", results.len());
@@ -262,6 +265,7 @@ fn main() -> Result<()> {
         vector: mock_search_query(),
         k: 10,
         filter: Some(filter),
+        ..Default::default()
     })?;
 
     println!("   Results: {} products\n", results.len());
@@ -314,6 +318,7 @@ fn main() -> Result<()> {
         vector: mock_search_query(),
         k: 10,
         filter: Some(filter),
+        ..Default::default()
     })?;
 
     println!("   Results: {} products\n", results.len());
@@ -72,16 +72,19 @@ async fn main() -> anyhow::Result<()> {
             vector: vec![1.0, 0.0, 0.0],
             k: 1,
             filter: None,
+            ..Default::default()
         }),
         store2.query(Query {
             vector: vec![0.0, 1.0, 0.0],
             k: 1,
             filter: None,
+            ..Default::default()
         }),
         store3.query(Query {
             vector: vec![0.5, 0.5, 0.0],
             k: 2,
             filter: None,
+            ..Default::default()
         }),
     );
 
@@ -180,6 +183,7 @@ async fn main() -> anyhow::Result<()> {
                 vector: query_vec,
                 k: 5,
                 filter: None,
+                ..Default::default()
             })
             .await?;
 
@@ -115,6 +115,7 @@ fn main() -> anyhow::Result<()> {
         vector: vec![0.15, 0.25, 0.35, 0.45],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = documents.query(query)?;
@@ -129,6 +130,7 @@ fn main() -> anyhow::Result<()> {
         vector: vec![0.75, 0.15, 0.25],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = users.query(query)?;
@@ -49,6 +49,7 @@ fn main() -> anyhow::Result<()> {
         vector: vec![1.0, 0.25, 0.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store_cosine.query(query)?;
@@ -126,10 +127,10 @@ fn main() -> anyhow::Result<()> {
         "   - Distance metric: {}",
         store_custom.distance_metric().name()
     );
-    println!("   - HNSW M: {}", store_custom.config().hnsw_m);
+    println!("   - HNSW M: {}", store_custom.config().hnsw.m);
     println!(
         "   - HNSW ef_construction: {}",
-        store_custom.config().hnsw_ef_construction
+        store_custom.config().hnsw.ef_construction
     );
     println!();
 
@@ -95,6 +95,7 @@ fn main() -> anyhow::Result<()> {
         vector: vec![0.1, 0.2, 0.3, 0.4],
         k: 10,
         filter: None,
+        ..Default::default()
     };
 
     // Query free-customer namespace
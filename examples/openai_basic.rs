@@ -127,6 +127,7 @@ async fn main() -> Result<()> {
         vector: query_emb,
         k: 3,
         filter: None,
+        ..Default::default()
     })?;
 
     println!("✓ Top results:");
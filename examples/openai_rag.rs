@@ -155,6 +155,7 @@ async fn main() -> Result<()> {
             vector: query_embedding.clone(),
             k: 3,
             filter: None,
+            ..Default::default()
         })?;
 
         println!("Top {} results:", results.len());
@@ -216,6 +217,7 @@ async fn main() -> Result<()> {
         vector: query_embedding.clone(),
         k: 3,
         filter: Some(filter),
+        ..Default::default()
     })?;
 
     println!("\nResults (filtered to Rust docs):");
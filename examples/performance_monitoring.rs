@@ -27,6 +27,8 @@ fn main() -> Result<()> {
         num_threads: 4,
         test_indexing_strategies: false, // Skip to make it faster
         test_quantization: true,
+        test_mixed: false,
+        ..BenchmarkConfig::default()
     };
 
     let benchmarker = Benchmarker::new(config);
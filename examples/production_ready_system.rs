@@ -46,6 +46,7 @@ impl VectorSearchService {
             memory_warning: 75.0,
             latency_warning_ms: 50.0,
             min_performance_score: 80.0,
+            ..HealthCheckConfig::default()
         };
 
         // Open database
@@ -140,7 +141,7 @@ impl VectorSearchService {
                     "🧹 Running compaction (deletion ratio: {:.1}%)...",
                     deletion_ratio * 100.0
                 );
-                let removed = self.store.compact()?;
+                let removed = self.store.compact()?.removed_count;
                 println!("   ✓ Compacted {} deleted vectors", removed);
                 self.last_compaction = Some(Instant::now());
             }
@@ -168,6 +169,8 @@ impl VectorSearchService {
             num_threads: 1,
             test_indexing_strategies: false,
             test_quantization: false,
+            test_mixed: false,
+            ..BenchmarkConfig::default()
         };
 
         let benchmarker = Benchmarker::new(config);
@@ -54,6 +54,7 @@ fn main() -> anyhow::Result<()> {
         vector: vec![1.0, 0.0, 0.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query_explain(query1)?;
@@ -80,6 +81,7 @@ fn main() -> anyhow::Result<()> {
         vector: vec![1.0, 0.0, 0.0],
         k: 3,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query_explain(query2)?;
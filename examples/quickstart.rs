@@ -53,6 +53,7 @@ fn main() -> anyhow::Result<()> {
             op: FilterOp::Eq,
             value: serde_json::json!("rust"),
         }),
+        ..Default::default()
     };
 
     let results = store.query(query)?;
@@ -78,6 +79,7 @@ fn main() -> anyhow::Result<()> {
                 value: serde_json::json!(6),
             },
         ])),
+        ..Default::default()
     };
 
     let complex_results = store.query(complex_query)?;
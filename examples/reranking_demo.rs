@@ -87,6 +87,7 @@ fn main() -> anyhow::Result<()> {
         vector: query_vector,
         k: 6, // Get all results
         filter: None,
+        ..Default::default()
     })?;
 
     println!("🔍 Initial Search Results (Vector Similarity Only):");
@@ -134,6 +134,7 @@ fn main() -> anyhow::Result<()> {
         vector: vec![5.0, 0.0, 0.0],
         k: 3,
         filter: None,
+        ..Default::default()
     })?;
 
     println!("Sample records after restore:");
@@ -55,6 +55,7 @@ fn main() -> anyhow::Result<()> {
         vector: query_vec.clone(),
         k: 5,
         filter: None,
+        ..Default::default()
     })?;
 
     println!("🔍 Immediate search works:");
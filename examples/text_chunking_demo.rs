@@ -174,6 +174,7 @@ fn main() -> anyhow::Result<()> {
         vector: query_embedding,
         k: 3,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query)?;
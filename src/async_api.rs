@@ -439,6 +439,7 @@ mod tests {
                 vector: vec![1.0, 0.0, 0.0],
                 k: 1,
                 filter: None,
+                ..Default::default()
             })
             .await
             .unwrap();
@@ -473,16 +474,19 @@ mod tests {
                 vector: vec![5.0, 0.0, 0.0],
                 k: 3,
                 filter: None,
+                ..Default::default()
             }),
             store2.query(Query {
                 vector: vec![2.0, 0.0, 0.0],
                 k: 3,
                 filter: None,
+                ..Default::default()
             }),
             store3.query(Query {
                 vector: vec![8.0, 0.0, 0.0],
                 k: 3,
                 filter: None,
+                ..Default::default()
             }),
         );
 
@@ -301,6 +301,9 @@ mod tests {
             metadata: Metadata {
                 fields: HashMap::new(),
             },
+            vector: None,
+            version: 1,
+            text: None,
         }
     }
 
@@ -5,22 +5,35 @@
 //! - Query performance with various k values
 //! - Different indexing strategies (HNSW, IVF-PQ, LSH, ScaNN)
 //! - Quantization impact
-//! - Filter performance
-//! - Concurrent operations
+//! - Filter performance at configurable selectivity
+//! - Recall@k against exact search
+//! - Concurrent query throughput by thread count
+//! - Mixed read/write workloads
 //! - Memory usage
 //! - Disk I/O
+//!
+//! Vectors, metadata and workloads come from a generator seeded with
+//! [`BenchmarkConfig::seed`], so runs with the same configuration measure
+//! the same work.
 
 use crate::ivf_pq::{IVFPQConfig, IVFPQIndex};
 use crate::quantization::{BinaryQuantizer, ScalarQuantizer4, ScalarQuantizer8};
-use crate::store::{Metadata, Query, VecStore};
-use anyhow::Result;
-use rand::Rng;
+use crate::store::{Metadata, Query, VecStore, VecStoreConcurrent};
+use anyhow::{Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Values of the `bucket` field seeded on benchmark records; a filter on
+/// `bucket < n` matches `n / SELECTIVITY_BUCKETS` of them
+const SELECTIVITY_BUCKETS: usize = 1000;
 
 /// Benchmark configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BenchmarkConfig {
     /// Number of vectors to insert
     pub num_vectors: usize,
@@ -48,6 +61,24 @@ pub struct BenchmarkConfig {
 
     /// Test quantization
     pub test_quantization: bool,
+
+    /// Fractions of the records each filtered-query filter matches
+    pub filter_selectivities: Vec<f64>,
+
+    /// Measure recall@k of each k against exact search
+    pub test_recall: bool,
+
+    /// Query thread counts the concurrent scenario compares
+    pub thread_counts: Vec<usize>,
+
+    /// Run the mixed read/write scenario on `num_threads` threads
+    pub test_mixed: bool,
+
+    /// Share of operations in the mixed scenario that are writes
+    pub write_ratio: f64,
+
+    /// Seed for the generated vectors, metadata and workloads
+    pub seed: u64,
 }
 
 impl Default for BenchmarkConfig {
@@ -62,6 +93,12 @@ impl Default for BenchmarkConfig {
             num_threads: 4,
             test_indexing_strategies: true,
             test_quantization: true,
+            filter_selectivities: vec![0.01, 0.1, 0.5],
+            test_recall: true,
+            thread_counts: vec![1, 4, 16],
+            test_mixed: true,
+            write_ratio: 0.1,
+            seed: 42,
         }
     }
 }
@@ -87,6 +124,10 @@ pub struct BenchmarkResults {
     /// Concurrent performance
     pub concurrent: Option<ConcurrentResults>,
 
+    /// Mixed read/write performance
+    #[serde(default)]
+    pub mixed: Option<MixedResults>,
+
     /// Memory usage
     pub memory: MemoryResults,
 
@@ -111,7 +152,11 @@ pub struct QueryResults {
     /// Results for each k value
     pub by_k: HashMap<usize, LatencyStats>,
 
-    /// Average recall@k (if ground truth available)
+    /// Queries per second for each k value
+    #[serde(default)]
+    pub qps_by_k: HashMap<usize, f64>,
+
+    /// Average recall@k against exact search, with `test_recall`
     pub recall: Option<HashMap<usize, f64>>,
 }
 
@@ -186,8 +231,23 @@ pub struct FilterResults {
     /// Query latency with complex filter
     pub complex_filter_us: LatencyStats,
 
-    /// Filter selectivity impact
-    pub selectivity_impact: Vec<(f64, LatencyStats)>,
+    /// One result per configured filter selectivity
+    pub selectivity_impact: Vec<SelectivityResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SelectivityResult {
+    /// Configured fraction of records the filter matches
+    pub selectivity: f64,
+
+    /// Records the filter actually matches
+    pub matching_records: usize,
+
+    /// Filtered query latency
+    pub latency_us: LatencyStats,
+
+    /// Filtered queries per second
+    pub qps: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -198,10 +258,38 @@ pub struct ConcurrentResults {
     /// Multi-threaded throughput by thread count
     pub multi_thread_qps: HashMap<usize, f64>,
 
-    /// Scalability factor
+    /// Query latency by thread count
+    #[serde(default)]
+    pub latency_by_threads: HashMap<usize, LatencyStats>,
+
+    /// Throughput at the most threads over single-threaded throughput
     pub scalability_factor: f64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixedResults {
+    /// Threads issuing operations
+    pub threads: usize,
+
+    /// Configured share of writes
+    pub write_ratio: f64,
+
+    /// Queries run
+    pub reads: usize,
+
+    /// Upserts run
+    pub writes: usize,
+
+    /// Query latency
+    pub read_latency_us: LatencyStats,
+
+    /// Upsert latency, including the wait for the write lock
+    pub write_latency_us: LatencyStats,
+
+    /// Operations per second, reads and writes together
+    pub throughput: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryResults {
     /// Total memory used (bytes)
@@ -272,6 +360,18 @@ impl LatencyStats {
     }
 }
 
+/// A vector with components uniform in [-1, 1)
+fn random_vector(rng: &mut StdRng, dimension: usize) -> Vec<f32> {
+    (0..dimension)
+        .map(|_| rng.gen::<f32>() * 2.0 - 1.0)
+        .collect()
+}
+
+/// Operations per second
+fn throughput(operations: usize, elapsed: Duration) -> f64 {
+    operations as f64 / elapsed.as_secs_f64().max(1e-9)
+}
+
 /// Main benchmarking harness
 pub struct Benchmarker {
     config: BenchmarkConfig,
@@ -283,27 +383,32 @@ impl Benchmarker {
     }
 
     /// Run all benchmarks
+    ///
+    /// Progress goes to stderr, so stdout stays free for the results.
     pub fn run(&self) -> Result<BenchmarkResults> {
-        println!("Starting VecStore benchmarks...");
-        println!(
-            "Config: {} vectors, {} dims, {} queries",
-            self.config.num_vectors, self.config.dimension, self.config.num_queries
+        eprintln!("Starting VecStore benchmarks...");
+        eprintln!(
+            "Config: {} vectors, {} dims, {} queries, seed {}",
+            self.config.num_vectors,
+            self.config.dimension,
+            self.config.num_queries,
+            self.config.seed
         );
 
         // Generate test data
         let (vectors, queries) = self.generate_data();
 
         // Run insert benchmarks
-        println!("\n[1/6] Benchmarking insert operations...");
+        eprintln!("\n[1/7] Benchmarking insert operations...");
         let insert_results = self.benchmark_insert(&vectors)?;
 
         // Run query benchmarks
-        println!("[2/6] Benchmarking query operations...");
+        eprintln!("[2/7] Benchmarking query operations...");
         let query_results = self.benchmark_query(&vectors, &queries)?;
 
         // Run indexing strategy benchmarks
         let indexing_results = if self.config.test_indexing_strategies {
-            println!("[3/6] Benchmarking indexing strategies...");
+            eprintln!("[3/7] Benchmarking indexing strategies...");
             Some(self.benchmark_indexing(&vectors, &queries)?)
         } else {
             None
@@ -311,7 +416,7 @@ impl Benchmarker {
 
         // Run quantization benchmarks
         let quantization_results = if self.config.test_quantization {
-            println!("[4/6] Benchmarking quantization...");
+            eprintln!("[4/7] Benchmarking quantization...");
             Some(self.benchmark_quantization(&vectors, &queries)?)
         } else {
             None
@@ -319,7 +424,7 @@ impl Benchmarker {
 
         // Run filter benchmarks
         let filter_results = if self.config.test_filters {
-            println!("[5/6] Benchmarking filter performance...");
+            eprintln!("[5/7] Benchmarking filter performance...");
             Some(self.benchmark_filters(&vectors, &queries)?)
         } else {
             None
@@ -327,16 +432,24 @@ impl Benchmarker {
 
         // Run concurrent benchmarks
         let concurrent_results = if self.config.test_concurrent {
-            println!("[6/6] Benchmarking concurrent operations...");
+            eprintln!("[6/7] Benchmarking concurrent operations...");
             Some(self.benchmark_concurrent(&vectors, &queries)?)
         } else {
             None
         };
 
+        // Run mixed read/write benchmarks
+        let mixed_results = if self.config.test_mixed {
+            eprintln!("[7/7] Benchmarking mixed reads and writes...");
+            Some(self.benchmark_mixed(&vectors, &queries)?)
+        } else {
+            None
+        };
+
         // Measure memory usage
         let memory_results = self.measure_memory(&vectors)?;
 
-        println!("\nBenchmarks complete!");
+        eprintln!("\nBenchmarks complete!");
 
         Ok(BenchmarkResults {
             insert: insert_results,
@@ -345,38 +458,56 @@ impl Benchmarker {
             quantization: quantization_results,
             filter: filter_results,
             concurrent: concurrent_results,
+            mixed: mixed_results,
             memory: memory_results,
             config: self.config.clone(),
         })
     }
 
-    /// Generate random test data
+    /// Generate random test data from the configured seed
     fn generate_data(&self) -> (Vec<Vec<f32>>, Vec<Vec<f32>>) {
-        let mut rng = rand::thread_rng();
+        let mut rng = StdRng::seed_from_u64(self.config.seed);
 
         let vectors: Vec<Vec<f32>> = (0..self.config.num_vectors)
-            .map(|_| {
-                (0..self.config.dimension)
-                    .map(|_| rng.gen::<f32>() * 2.0 - 1.0)
-                    .collect()
-            })
+            .map(|_| random_vector(&mut rng, self.config.dimension))
             .collect();
 
         let queries: Vec<Vec<f32>> = (0..self.config.num_queries)
-            .map(|_| {
-                (0..self.config.dimension)
-                    .map(|_| rng.gen::<f32>() * 2.0 - 1.0)
-                    .collect()
-            })
+            .map(|_| random_vector(&mut rng, self.config.dimension))
             .collect();
 
         (vectors, queries)
     }
 
+    /// A store holding `vectors` as `vec_{i}`, each with a `bucket` field in
+    /// `0..SELECTIVITY_BUCKETS` drawn from the configured seed
+    ///
+    /// Searches always go through the HNSW index, even on stores small
+    /// enough for exact search.
+    fn seeded_store(&self, vectors: &[Vec<f32>]) -> Result<(TempDir, VecStore, Vec<usize>)> {
+        let temp_dir = TempDir::new()?;
+        let mut store = VecStore::open(temp_dir.path().join("bench.db"))?;
+        store.set_exact_search_threshold(0);
+
+        let mut rng = StdRng::seed_from_u64(self.config.seed ^ 0x6275_636b_6574);
+        let mut buckets = Vec::with_capacity(vectors.len());
+        for (i, vector) in vectors.iter().enumerate() {
+            let bucket = rng.gen_range(0..SELECTIVITY_BUCKETS);
+            buckets.push(bucket);
+            let mut fields = HashMap::new();
+            fields.insert("category".to_string(), serde_json::json!(i % 10));
+            fields.insert(
+                "score".to_string(),
+                serde_json::json!((i as f64) / vectors.len() as f64),
+            );
+            fields.insert("bucket".to_string(), serde_json::json!(bucket));
+            store.upsert(format!("vec_{}", i), vector.clone(), Metadata { fields })?;
+        }
+        Ok((temp_dir, store, buckets))
+    }
+
     /// Benchmark insert operations
     fn benchmark_insert(&self, vectors: &[Vec<f32>]) -> Result<InsertResults> {
-        use tempfile::TempDir;
-
         let temp_dir = TempDir::new()?;
         let mut store = VecStore::open(temp_dir.path().join("bench.db"))?;
 
@@ -422,42 +553,47 @@ impl Benchmarker {
         })
     }
 
-    /// Benchmark query operations
+    /// Benchmark query operations, and recall@k against exact search
     fn benchmark_query(&self, vectors: &[Vec<f32>], queries: &[Vec<f32>]) -> Result<QueryResults> {
-        use tempfile::TempDir;
-
-        let temp_dir = TempDir::new()?;
-        let mut store = VecStore::open(temp_dir.path().join("bench.db"))?;
-
-        // Insert all vectors
-        for (i, vector) in vectors.iter().enumerate() {
-            store.upsert(
-                format!("vec_{}", i),
-                vector.clone(),
-                Metadata {
-                    fields: HashMap::new(),
-                },
-            )?;
-        }
+        let (_temp_dir, store, _) = self.seeded_store(vectors)?;
 
         let mut by_k = HashMap::new();
+        let mut qps_by_k = HashMap::new();
+        let mut recall_by_k = HashMap::new();
 
         for &k in &self.config.k_values {
             let mut query_times = Vec::new();
+            let mut total_recall = 0.0;
 
             for query_vec in queries {
                 let query = Query::new(query_vec.clone()).with_limit(k);
                 let start = Instant::now();
-                let _ = store.query(query)?;
+                let approx = store.query(query.clone())?;
                 query_times.push(start.elapsed());
+
+                if self.config.test_recall {
+                    let truth = store.query(query.with_exact(true))?;
+                    total_recall += if truth.is_empty() {
+                        1.0
+                    } else {
+                        let found = approx
+                            .iter()
+                            .filter(|n| truth.iter().any(|t| t.id == n.id))
+                            .count();
+                        found as f64 / truth.len() as f64
+                    };
+                }
             }
 
+            qps_by_k.insert(k, throughput(queries.len(), query_times.iter().sum()));
             by_k.insert(k, LatencyStats::from_durations(query_times));
+            recall_by_k.insert(k, total_recall / queries.len().max(1) as f64);
         }
 
         Ok(QueryResults {
             by_k,
-            recall: None, // Would need ground truth for recall
+            qps_by_k,
+            recall: self.config.test_recall.then_some(recall_by_k),
         })
     }
 
@@ -620,22 +756,7 @@ impl Benchmarker {
         vectors: &[Vec<f32>],
         queries: &[Vec<f32>],
     ) -> Result<FilterResults> {
-        use tempfile::TempDir;
-
-        let temp_dir = TempDir::new()?;
-        let mut store = VecStore::open(temp_dir.path().join("bench.db"))?;
-
-        // Insert vectors with metadata
-        for (i, vector) in vectors.iter().enumerate() {
-            let mut fields = HashMap::new();
-            fields.insert("category".to_string(), serde_json::json!(i % 10));
-            fields.insert(
-                "score".to_string(),
-                serde_json::json!((i as f64) / vectors.len() as f64),
-            );
-
-            store.upsert(format!("vec_{}", i), vector.clone(), Metadata { fields })?;
-        }
+        let (_temp_dir, store, buckets) = self.seeded_store(vectors)?;
 
         // No filter
         let mut no_filter_times = Vec::new();
@@ -668,30 +789,160 @@ impl Benchmarker {
             complex_filter_times.push(start.elapsed());
         }
 
+        // Filters matching each configured share of the records
+        let mut selectivity_impact = Vec::new();
+        for &selectivity in &self.config.filter_selectivities {
+            let below = (selectivity.clamp(0.0, 1.0) * SELECTIVITY_BUCKETS as f64).round() as usize;
+            let filter = format!("bucket < {}", below);
+            let mut times = Vec::new();
+            for query_vec in queries {
+                let query = Query::new(query_vec.clone())
+                    .with_limit(10)
+                    .with_filter(filter.as_str());
+                let start = Instant::now();
+                let _ = store.query(query)?;
+                times.push(start.elapsed());
+            }
+            selectivity_impact.push(SelectivityResult {
+                selectivity,
+                matching_records: buckets.iter().filter(|&&b| b < below).count(),
+                qps: throughput(times.len(), times.iter().sum()),
+                latency_us: LatencyStats::from_durations(times),
+            });
+        }
+
         Ok(FilterResults {
             no_filter_us: LatencyStats::from_durations(no_filter_times),
             simple_filter_us: LatencyStats::from_durations(simple_filter_times),
             complex_filter_us: LatencyStats::from_durations(complex_filter_times),
-            selectivity_impact: Vec::new(),
+            selectivity_impact,
         })
     }
 
-    /// Benchmark concurrent operations
+    /// Benchmark query throughput at each configured thread count
+    ///
+    /// Every thread runs the whole query set, starting at a different query,
+    /// so throughput scales with the threads if the store does.
     fn benchmark_concurrent(
         &self,
         vectors: &[Vec<f32>],
         queries: &[Vec<f32>],
     ) -> Result<ConcurrentResults> {
-        // Single-threaded baseline
-        let single_thread_qps = self.config.num_queries as f64 / 1.0; // Placeholder
+        let (_temp_dir, store, _) = self.seeded_store(vectors)?;
+        let store = VecStoreConcurrent::new(store);
+
+        let mut multi_thread_qps = HashMap::new();
+        let mut latency_by_threads = HashMap::new();
+        let mut thread_counts = self.config.thread_counts.clone();
+        thread_counts.retain(|&threads| threads > 0);
+        if !thread_counts.contains(&1) {
+            thread_counts.push(1);
+        }
+        thread_counts.sort_unstable();
+        thread_counts.dedup();
 
-        let multi_thread_qps = HashMap::new();
-        // Would need actual concurrent testing here
+        for &threads in &thread_counts {
+            let start = Instant::now();
+            let latencies = std::thread::scope(|scope| {
+                let workers: Vec<_> = (0..threads)
+                    .map(|thread| {
+                        let store = &store;
+                        scope.spawn(move || -> Result<Vec<Duration>> {
+                            let offset = thread * queries.len() / threads;
+                            let mut times = Vec::with_capacity(queries.len());
+                            for i in 0..queries.len() {
+                                let query_vec = &queries[(offset + i) % queries.len()];
+                                let query = Query::new(query_vec.clone()).with_limit(10);
+                                let start = Instant::now();
+                                let _ = store.query(query)?;
+                                times.push(start.elapsed());
+                            }
+                            Ok(times)
+                        })
+                    })
+                    .collect();
+                let mut latencies = Vec::new();
+                for worker in workers {
+                    latencies.extend(worker.join().expect("benchmark thread panicked")?);
+                }
+                Ok::<_, anyhow::Error>(latencies)
+            })?;
+
+            multi_thread_qps.insert(threads, throughput(latencies.len(), start.elapsed()));
+            latency_by_threads.insert(threads, LatencyStats::from_durations(latencies));
+        }
 
+        let single_thread_qps = multi_thread_qps[&1];
+        let most_threads = thread_counts[thread_counts.len() - 1];
         Ok(ConcurrentResults {
             single_thread_qps,
+            scalability_factor: multi_thread_qps[&most_threads]
+                / single_thread_qps.max(f64::EPSILON),
             multi_thread_qps,
-            scalability_factor: 1.0,
+            latency_by_threads,
+        })
+    }
+
+    /// Benchmark `num_threads` threads each issuing `num_queries` operations,
+    /// `write_ratio` of them upserts of new vectors and the rest queries
+    fn benchmark_mixed(&self, vectors: &[Vec<f32>], queries: &[Vec<f32>]) -> Result<MixedResults> {
+        let (_temp_dir, store, _) = self.seeded_store(vectors)?;
+        let store = VecStoreConcurrent::new(store);
+        let threads = self.config.num_threads.max(1);
+        let write_ratio = self.config.write_ratio.clamp(0.0, 1.0);
+
+        let start = Instant::now();
+        let (reads, writes) = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|thread| {
+                    let store = &store;
+                    let seed = self.config.seed.wrapping_add(thread as u64 + 1);
+                    scope.spawn(move || -> Result<(Vec<Duration>, Vec<Duration>)> {
+                        let mut rng = StdRng::seed_from_u64(seed);
+                        let (mut reads, mut writes) = (Vec::new(), Vec::new());
+                        for i in 0..self.config.num_queries {
+                            if rng.gen_bool(write_ratio) {
+                                let vector = random_vector(&mut rng, self.config.dimension);
+                                let metadata = Metadata {
+                                    fields: HashMap::new(),
+                                };
+                                let start = Instant::now();
+                                store.upsert(
+                                    format!("mixed_{}_{}", thread, i),
+                                    vector,
+                                    metadata,
+                                )?;
+                                writes.push(start.elapsed());
+                            } else {
+                                let query_vec = &queries[rng.gen_range(0..queries.len())];
+                                let query = Query::new(query_vec.clone()).with_limit(10);
+                                let start = Instant::now();
+                                let _ = store.query(query)?;
+                                reads.push(start.elapsed());
+                            }
+                        }
+                        Ok((reads, writes))
+                    })
+                })
+                .collect();
+            let (mut reads, mut writes) = (Vec::new(), Vec::new());
+            for worker in workers {
+                let (r, w) = worker.join().expect("benchmark thread panicked")?;
+                reads.extend(r);
+                writes.extend(w);
+            }
+            Ok::<_, anyhow::Error>((reads, writes))
+        })?;
+        let elapsed = start.elapsed();
+
+        Ok(MixedResults {
+            threads,
+            write_ratio,
+            reads: reads.len(),
+            writes: writes.len(),
+            throughput: throughput(reads.len() + writes.len(), elapsed),
+            read_latency_us: LatencyStats::from_durations(reads),
+            write_latency_us: LatencyStats::from_durations(writes),
         })
     }
 
@@ -720,6 +971,7 @@ impl Benchmarker {
         println!("  Vectors: {}", results.config.num_vectors);
         println!("  Dimension: {}", results.config.dimension);
         println!("  Queries: {}", results.config.num_queries);
+        println!("  Seed: {}", results.config.seed);
 
         println!("\n📥 Insert Performance:");
         println!(
@@ -734,11 +986,22 @@ impl Benchmarker {
         );
 
         println!("\n🔍 Query Performance:");
-        for (&k, stats) in &results.query.by_k {
-            println!(
-                "  k={}: {:.2} μs (avg), {:.2} μs (p95), {:.2} μs (p99)",
-                k, stats.avg_us, stats.p95_us, stats.p99_us
+        let mut ks: Vec<usize> = results.query.by_k.keys().copied().collect();
+        ks.sort_unstable();
+        for k in ks {
+            let stats = &results.query.by_k[&k];
+            print!(
+                "  k={}: {:.2} μs (p50), {:.2} μs (p95), {:.2} μs (p99), {:.0} qps",
+                k,
+                stats.p50_us,
+                stats.p95_us,
+                stats.p99_us,
+                results.query.qps_by_k.get(&k).copied().unwrap_or(0.0)
             );
+            match results.query.recall.as_ref().and_then(|r| r.get(&k)) {
+                Some(recall) => println!(", recall@{} {:.3}", k, recall),
+                None => println!(),
+            }
         }
 
         if let Some(ref quant) = results.quantization {
@@ -777,6 +1040,54 @@ impl Benchmarker {
                 "  Complex filter: {:.2} μs (avg)",
                 filter.complex_filter_us.avg_us
             );
+            for result in &filter.selectivity_impact {
+                println!(
+                    "  {:>5.1}% selective ({} records): {:.2} / {:.2} / {:.2} μs (p50/p95/p99), {:.0} qps",
+                    result.selectivity * 100.0,
+                    result.matching_records,
+                    result.latency_us.p50_us,
+                    result.latency_us.p95_us,
+                    result.latency_us.p99_us,
+                    result.qps
+                );
+            }
+        }
+
+        if let Some(ref concurrent) = results.concurrent {
+            println!("\n🧵 Concurrent Queries:");
+            let mut threads: Vec<usize> = concurrent.multi_thread_qps.keys().copied().collect();
+            threads.sort_unstable();
+            for count in threads {
+                let stats = concurrent.latency_by_threads.get(&count);
+                println!(
+                    "  {:>2} threads: {:.0} qps, {:.2} / {:.2} / {:.2} μs (p50/p95/p99)",
+                    count,
+                    concurrent.multi_thread_qps[&count],
+                    stats.map_or(0.0, |s| s.p50_us),
+                    stats.map_or(0.0, |s| s.p95_us),
+                    stats.map_or(0.0, |s| s.p99_us)
+                );
+            }
+            println!("  Scaling: {:.2}x", concurrent.scalability_factor);
+        }
+
+        if let Some(ref mixed) = results.mixed {
+            println!(
+                "\n🔀 Mixed Workload ({:.0}/{:.0} reads/writes, {} threads):",
+                (1.0 - mixed.write_ratio) * 100.0,
+                mixed.write_ratio * 100.0,
+                mixed.threads
+            );
+            println!("  Throughput: {:.0} ops/sec", mixed.throughput);
+            for (name, count, stats) in [
+                ("Reads", mixed.reads, &mixed.read_latency_us),
+                ("Writes", mixed.writes, &mixed.write_latency_us),
+            ] {
+                println!(
+                    "  {} ({}): {:.2} / {:.2} / {:.2} μs (p50/p95/p99)",
+                    name, count, stats.p50_us, stats.p95_us, stats.p99_us
+                );
+            }
         }
 
         println!("\n💾 Memory Usage:");
@@ -794,6 +1105,343 @@ impl Benchmarker {
     }
 }
 
+/// The machine a benchmark ran on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MachineInfo {
+    /// CPU model, or `"unknown"` where it can't be read
+    pub cpu: String,
+
+    /// Logical cores available to the process
+    pub cores: usize,
+
+    pub os: String,
+
+    pub arch: String,
+}
+
+impl MachineInfo {
+    /// Describe the machine this process runs on
+    pub fn current() -> Self {
+        let cpu = std::fs::read_to_string("/proc/cpuinfo")
+            .ok()
+            .and_then(|info| {
+                info.lines()
+                    .find(|line| line.starts_with("model name"))
+                    .and_then(|line| line.split_once(':'))
+                    .map(|(_, model)| model.trim().to_string())
+            })
+            .unwrap_or_else(|| "unknown".to_string());
+        Self {
+            cpu,
+            cores: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+}
+
+/// A saved benchmark run to compare later runs against
+///
+/// Written by `vecstore benchmark --save-baseline` and read by `--compare`.
+/// The results carry the configuration they were measured with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkBaseline {
+    /// When the run finished, as a Unix timestamp
+    pub created_at: i64,
+
+    pub machine: MachineInfo,
+
+    pub results: BenchmarkResults,
+}
+
+impl BenchmarkBaseline {
+    /// A baseline of `results`, measured on this machine
+    pub fn new(results: BenchmarkResults) -> Self {
+        Self {
+            created_at: chrono::Utc::now().timestamp(),
+            machine: MachineInfo::current(),
+            results,
+        }
+    }
+
+    /// Write the baseline to `path` as JSON
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Failed to write baseline {}", path.display()))
+    }
+
+    /// Read a baseline written by [`save`](Self::save)
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read baseline {}", path.display()))?;
+        serde_json::from_slice(&data)
+            .with_context(|| format!("Failed to parse baseline {}", path.display()))
+    }
+
+    /// Compare `current`, measured on this machine, against the baseline
+    pub fn compare(
+        &self,
+        current: &BenchmarkResults,
+        tolerance: &RegressionTolerance,
+    ) -> BenchmarkComparison {
+        let mut warnings = Vec::new();
+        let machine = MachineInfo::current();
+        if machine.cpu != self.machine.cpu || machine.cores != self.machine.cores {
+            warnings.push(format!(
+                "baseline ran on {} ({} cores), this run on {} ({} cores)",
+                self.machine.cpu, self.machine.cores, machine.cpu, machine.cores
+            ));
+        }
+        if serde_json::to_value(&self.results.config).ok()
+            != serde_json::to_value(&current.config).ok()
+        {
+            warnings.push("baseline was run with a different benchmark configuration".to_string());
+        }
+
+        let before = scenarios(&self.results);
+        let mut deltas = Vec::new();
+        for (scenario, metrics) in scenarios(current) {
+            let Some((_, baseline_metrics)) = before.iter().find(|(name, _)| *name == scenario)
+            else {
+                continue;
+            };
+            for (metric, value) in metrics {
+                let Some(&(_, baseline)) = baseline_metrics.iter().find(|(m, _)| *m == metric)
+                else {
+                    continue;
+                };
+                deltas.push(MetricDelta::new(
+                    &scenario, metric, baseline, value, tolerance,
+                ));
+            }
+        }
+
+        BenchmarkComparison { warnings, deltas }
+    }
+}
+
+/// Largest change, in percent, a comparison accepts before calling it a
+/// regression
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RegressionTolerance {
+    /// Increase in median latency
+    pub p50_pct: f64,
+
+    /// Increase in p99 latency, which is noisier
+    pub p99_pct: f64,
+
+    /// Decrease in throughput
+    pub throughput_pct: f64,
+}
+
+impl Default for RegressionTolerance {
+    fn default() -> Self {
+        Self {
+            p50_pct: 30.0,
+            p99_pct: 50.0,
+            throughput_pct: 30.0,
+        }
+    }
+}
+
+/// Measured quantity of a scenario
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BenchmarkMetric {
+    P50,
+    P99,
+    Throughput,
+}
+
+impl BenchmarkMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::P50 => "p50 (μs)",
+            Self::P99 => "p99 (μs)",
+            Self::Throughput => "ops/sec",
+        }
+    }
+}
+
+/// One metric of one scenario, before and after
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub scenario: String,
+
+    pub metric: BenchmarkMetric,
+
+    pub baseline: f64,
+
+    pub current: f64,
+
+    /// Change from the baseline in percent; positive when the value grew
+    pub change_pct: f64,
+
+    /// Whether the change is for the worse by more than the tolerance
+    pub regressed: bool,
+}
+
+impl MetricDelta {
+    fn new(
+        scenario: &str,
+        metric: BenchmarkMetric,
+        baseline: f64,
+        current: f64,
+        tolerance: &RegressionTolerance,
+    ) -> Self {
+        let change_pct = if baseline > 0.0 {
+            (current - baseline) / baseline * 100.0
+        } else {
+            0.0
+        };
+        let regressed = match metric {
+            BenchmarkMetric::P50 => change_pct > tolerance.p50_pct,
+            BenchmarkMetric::P99 => change_pct > tolerance.p99_pct,
+            BenchmarkMetric::Throughput => -change_pct > tolerance.throughput_pct,
+        };
+        Self {
+            scenario: scenario.to_string(),
+            metric,
+            baseline,
+            current,
+            change_pct,
+            regressed,
+        }
+    }
+}
+
+/// Result of [`BenchmarkBaseline::compare`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkComparison {
+    /// Differences in machine or configuration that make the numbers hard
+    /// to compare
+    pub warnings: Vec<String>,
+
+    /// Each metric present in both runs
+    pub deltas: Vec<MetricDelta>,
+}
+
+impl BenchmarkComparison {
+    /// Whether any metric got worse by more than its tolerance
+    pub fn has_regressions(&self) -> bool {
+        self.deltas.iter().any(|d| d.regressed)
+    }
+
+    /// Print the deltas as a table, ▲ for increases and ▼ for decreases
+    pub fn print(&self) {
+        for warning in &self.warnings {
+            println!("⚠️  {}", warning);
+        }
+        println!(
+            "\n{:<26} {:<9} {:>12} {:>12} {:>10}",
+            "Scenario", "Metric", "Baseline", "Current", "Change"
+        );
+        for delta in &self.deltas {
+            let arrow = if delta.change_pct > 0.0 {
+                "▲"
+            } else if delta.change_pct < 0.0 {
+                "▼"
+            } else {
+                " "
+            };
+            println!(
+                "{:<26} {:<9} {:>12.1} {:>12.1} {} {:>7.1}%{}",
+                delta.scenario,
+                delta.metric.as_str(),
+                delta.baseline,
+                delta.current,
+                arrow,
+                delta.change_pct.abs(),
+                if delta.regressed {
+                    "  ✗ regression"
+                } else {
+                    ""
+                }
+            );
+        }
+        let regressions = self.deltas.iter().filter(|d| d.regressed).count();
+        if regressions == 0 {
+            println!("\n✓ No regressions beyond tolerance");
+        } else {
+            println!("\n✗ {} regressions beyond tolerance", regressions);
+        }
+    }
+}
+
+/// Each scenario of `results` by name, with its comparable metrics
+fn scenarios(results: &BenchmarkResults) -> Vec<(String, Vec<(BenchmarkMetric, f64)>)> {
+    fn measured(latency: &LatencyStats, throughput: f64) -> Vec<(BenchmarkMetric, f64)> {
+        vec![
+            (BenchmarkMetric::P50, latency.p50_us),
+            (BenchmarkMetric::P99, latency.p99_us),
+            (BenchmarkMetric::Throughput, throughput),
+        ]
+    }
+
+    let mut scenarios = vec![(
+        "insert".to_string(),
+        measured(
+            &results.insert.single_insert_us,
+            results.insert.batch_throughput,
+        ),
+    )];
+
+    let mut ks: Vec<&usize> = results.query.by_k.keys().collect();
+    ks.sort_unstable();
+    for k in ks {
+        let qps = results.query.qps_by_k.get(k).copied().unwrap_or(0.0);
+        scenarios.push((
+            format!("query k={}", k),
+            measured(&results.query.by_k[k], qps),
+        ));
+    }
+
+    if let Some(filter) = &results.filter {
+        for result in &filter.selectivity_impact {
+            scenarios.push((
+                format!(
+                    "filter {}%",
+                    (result.selectivity * 10_000.0).round() / 100.0
+                ),
+                measured(&result.latency_us, result.qps),
+            ));
+        }
+    }
+
+    if let Some(concurrent) = &results.concurrent {
+        let mut threads: Vec<&usize> = concurrent.latency_by_threads.keys().collect();
+        threads.sort_unstable();
+        for count in threads {
+            let qps = concurrent
+                .multi_thread_qps
+                .get(count)
+                .copied()
+                .unwrap_or(0.0);
+            scenarios.push((
+                format!("concurrent {} threads", count),
+                measured(&concurrent.latency_by_threads[count], qps),
+            ));
+        }
+    }
+
+    if let Some(mixed) = &results.mixed {
+        let throughput = vec![(BenchmarkMetric::Throughput, mixed.throughput)];
+        scenarios.push(("mixed".to_string(), throughput));
+        for (name, latency) in [
+            ("mixed reads", &mixed.read_latency_us),
+            ("mixed writes", &mixed.write_latency_us),
+        ] {
+            let mut metrics = measured(latency, 0.0);
+            metrics.pop();
+            scenarios.push((name.to_string(), metrics));
+        }
+    }
+
+    scenarios
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -810,6 +1458,9 @@ mod tests {
             num_threads: 1,
             test_indexing_strategies: false,
             test_quantization: false,
+            test_recall: false,
+            test_mixed: false,
+            ..BenchmarkConfig::default()
         };
 
         let benchmarker = Benchmarker::new(config);
@@ -820,4 +1471,127 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_benchmark_scenarios() -> Result<()> {
+        let config = BenchmarkConfig {
+            num_vectors: 300,
+            dimension: 8,
+            num_queries: 20,
+            k_values: vec![10],
+            test_indexing_strategies: false,
+            test_quantization: false,
+            thread_counts: vec![2],
+            num_threads: 2,
+            write_ratio: 0.5,
+            ..BenchmarkConfig::default()
+        };
+        let results = Benchmarker::new(config.clone()).run()?;
+
+        let recall = results.query.recall.as_ref().unwrap()[&10];
+        assert!(recall > 0.5 && recall <= 1.0, "recall {}", recall);
+        assert!(results.query.qps_by_k[&10] > 0.0);
+
+        // Seeded buckets give the same matches on every run
+        let filter = results.filter.as_ref().unwrap();
+        assert_eq!(filter.selectivity_impact.len(), 3);
+        let matching: Vec<usize> = filter
+            .selectivity_impact
+            .iter()
+            .map(|r| r.matching_records)
+            .collect();
+        assert!(matching.windows(2).all(|w| w[0] <= w[1]), "{:?}", matching);
+        let again = Benchmarker::new(config).run()?;
+        let matching_again: Vec<usize> = again
+            .filter
+            .unwrap()
+            .selectivity_impact
+            .iter()
+            .map(|r| r.matching_records)
+            .collect();
+        assert_eq!(matching, matching_again);
+
+        // One thread is always measured, as the baseline
+        let concurrent = results.concurrent.as_ref().unwrap();
+        let mut threads: Vec<usize> = concurrent.multi_thread_qps.keys().copied().collect();
+        threads.sort_unstable();
+        assert_eq!(threads, vec![1, 2]);
+        assert!(concurrent.scalability_factor > 0.0);
+
+        let mixed = results.mixed.as_ref().unwrap();
+        assert_eq!(mixed.reads + mixed.writes, 2 * 20);
+        assert!(mixed.writes > 0 && mixed.reads > 0);
+        assert_eq!(mixed.writes, again.mixed.unwrap().writes);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_baseline_round_trip_and_comparison() -> Result<()> {
+        let config = BenchmarkConfig {
+            num_vectors: 100,
+            dimension: 8,
+            num_queries: 10,
+            k_values: vec![10],
+            test_indexing_strategies: false,
+            test_quantization: false,
+            test_recall: false,
+            thread_counts: vec![1],
+            num_threads: 1,
+            ..BenchmarkConfig::default()
+        };
+        let results = Benchmarker::new(config).run()?;
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("baseline.json");
+        BenchmarkBaseline::new(results.clone()).save(&path)?;
+        let baseline = BenchmarkBaseline::load(&path)?;
+        assert_eq!(baseline.machine, MachineInfo::current());
+
+        let tolerance = RegressionTolerance::default();
+        let same = baseline.compare(&results, &tolerance);
+        assert!(same.warnings.is_empty(), "{:?}", same.warnings);
+        assert!(!same.has_regressions());
+        let scenarios: Vec<&str> = same.deltas.iter().map(|d| d.scenario.as_str()).collect();
+        for name in [
+            "insert",
+            "query k=10",
+            "filter 1%",
+            "concurrent 1 threads",
+            "mixed",
+        ] {
+            assert!(scenarios.contains(&name), "{:?}", scenarios);
+        }
+
+        // Twice the median latency and half the throughput
+        let mut slower = results.clone();
+        slower.query.by_k.get_mut(&10).unwrap().p50_us *= 2.0;
+        *slower.query.qps_by_k.get_mut(&10).unwrap() /= 2.0;
+        slower.config.seed += 1;
+        let comparison = baseline.compare(&slower, &tolerance);
+        assert_eq!(comparison.warnings.len(), 1);
+        let regressed: Vec<(&str, BenchmarkMetric)> = comparison
+            .deltas
+            .iter()
+            .filter(|d| d.regressed)
+            .map(|d| (d.scenario.as_str(), d.metric))
+            .collect();
+        assert_eq!(
+            regressed,
+            vec![
+                ("query k=10", BenchmarkMetric::P50),
+                ("query k=10", BenchmarkMetric::Throughput)
+            ]
+        );
+
+        // Within a looser tolerance
+        let loose = RegressionTolerance {
+            p50_pct: 150.0,
+            throughput_pct: 60.0,
+            ..tolerance
+        };
+        assert!(!baseline.compare(&slower, &loose).has_regressions());
+
+        Ok(())
+    }
 }
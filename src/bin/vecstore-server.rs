@@ -13,78 +13,103 @@
 //!
 //! # Specify database path
 //! cargo run --bin vecstore-server --features server -- --db-path /data/vectors.db
+//!
+//! # Save every 60 seconds when anything changed
+//! cargo run --bin vecstore-server --features server -- --auto-save-every 60
+//!
+//! # Limit each client to 50 requests/s and run at most 8 queries at once
+//! cargo run --bin vecstore-server --features server -- --rate-limit 50 --max-concurrent-queries 8
+//!
+//! # Embed texts for /v1/embed and /v1/query/text with a local MiniLM model
+//! cargo run --bin vecstore-server --features server-onnx -- --embedder onnx
+//!
+//! # Serve a read-only replica of another server, synced every 10 seconds
+//! cargo run --bin vecstore-server --features server -- --replica-of http://primary:8080 --replica-poll-secs 10
+//!
+//! # Require API keys
+//! echo -n "$KEY" | vecstore-server --hash-api-key
+//! cargo run --bin vecstore-server --features server -- --api-keys keys.json
 //! ```
 
 use anyhow::Result;
 use clap::Parser;
+use std::io::Read;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tonic::transport::Server as TonicServer;
 use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use vecstore::cli::server_args::Args;
 use vecstore::namespace_manager::NamespaceManager;
-use vecstore::server::{AdminHttpServer, AdminService, VecStoreGrpcServer, VecStoreHttpServer};
+use vecstore::server::metrics::GrpcMetricsLayer;
+use vecstore::server::{
+    embed, serve_until_shutdown, AdminHttpServer, AdminService, ApiKeys, BulkLimits, Checkpointer,
+    Cors, EmbedderConfig, Embedding, EventStream, GrpcTraceLayer, HttpOptions, ImportLimits,
+    Limiter, LimitsConfig, NamespaceGrpcServer, QueryTimeouts, Replica, SaveTrigger, Shutdown,
+    StreamLimits, VecStoreGrpcServer, VecStoreHttpServer,
+};
 use vecstore::store::VecStore;
 
-#[derive(Parser, Debug)]
-#[command(name = "vecstore-server")]
-#[command(about = "VecStore multi-protocol server", long_about = None)]
-struct Args {
-    /// Path to the vector database
-    #[arg(short, long, default_value = "vecstore.db")]
-    db_path: String,
-
-    /// gRPC server port
-    #[arg(long, default_value = "50051")]
-    grpc_port: u16,
-
-    /// HTTP/REST server port
-    #[arg(long, default_value = "8080")]
-    http_port: u16,
-
-    /// Vector dimension (required for new databases)
-    #[arg(long)]
-    dimension: Option<usize>,
-
-    /// Enable debug logging
-    #[arg(long)]
-    debug: bool,
-
-    /// Disable gRPC server
-    #[arg(long)]
-    no_grpc: bool,
-
-    /// Disable HTTP server
-    #[arg(long)]
-    no_http: bool,
-
-    /// Enable multi-tenant namespace mode (uses NamespaceManager)
-    #[arg(long)]
-    namespaces: bool,
-
-    /// Namespace root directory (only with --namespaces)
-    #[arg(long, default_value = "./namespaces")]
-    namespace_root: String,
-}
-
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
+    if args.hash_api_key {
+        let mut key = String::new();
+        std::io::stdin().read_to_string(&mut key)?;
+        println!("{}", ApiKeys::hash_key(key.trim()));
+        return Ok(());
+    }
+
     // Initialize tracing
     let log_level = if args.debug { "debug" } else { "info" };
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-                format!("vecstore={},vecstore_server={}", log_level, log_level).into()
-            }),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| format!("vecstore={},vecstore_server={}", log_level, log_level).into());
+    // Request log lines carry their request span's fields either way
+    let registry = tracing_subscriber::registry().with(filter);
+    if args.log_json {
+        registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(true),
+            )
+            .init();
+    } else {
+        registry.with(tracing_subscriber::fmt::layer()).init();
+    }
 
     info!("🚀 Starting VecStore Server");
 
+    let api_keys = ApiKeys::load(args.api_keys.as_deref().map(std::path::Path::new))?
+        .map(|keys| Arc::new(keys.with_public_health(args.public_health)));
+    match &api_keys {
+        Some(keys) => info!("🔑 API key authentication on ({} keys)", keys.len()),
+        None => warn!("No API keys configured: anyone who can reach the ports has full access"),
+    }
+
+    let mut limits = match &args.limits {
+        Some(path) => LimitsConfig::load(std::path::Path::new(path))?,
+        None => LimitsConfig::default(),
+    };
+    if args.rate_limit.is_some() {
+        limits.global.requests_per_second = args.rate_limit;
+    }
+    if args.rate_burst.is_some() {
+        limits.global.burst = args.rate_burst;
+    }
+    if args.max_concurrent_queries.is_some() {
+        limits.global.max_concurrent_queries = args.max_concurrent_queries;
+    }
+    let limiter = Arc::new(Limiter::new(limits)?);
+    if limiter.config() != LimitsConfig::default() {
+        info!(
+            "🚦 Rate limits: {}",
+            serde_json::to_string(&limiter.config())?
+        );
+    }
+
     // Choose mode: single-tenant or multi-tenant
     let namespace_manager = if args.namespaces {
         info!("🏢 Multi-tenant namespace mode enabled");
@@ -100,11 +125,12 @@ async fn main() -> Result<()> {
     };
 
     // Single-tenant mode (backward compatible)
+    let mut events = None;
     let store = if namespace_manager.is_none() {
         info!("📦 Single-tenant mode");
         info!("Database: {}", args.db_path);
 
-        let store = if std::path::Path::new(&args.db_path).exists() {
+        let mut store = if std::path::Path::new(&args.db_path).exists() {
             info!("Loading existing database from {}", args.db_path);
             VecStore::open(&args.db_path)?
         } else {
@@ -119,14 +145,152 @@ async fn main() -> Result<()> {
             store.dimension()
         );
 
+        if args.events {
+            events = Some(EventStream::attach(&mut store));
+        }
+
+        if let Some(ms) = args.slow_query_ms {
+            info!("Logging queries slower than {} ms", ms);
+            store.set_slow_query_threshold(Some(std::time::Duration::from_millis(ms)));
+        }
+
         Some(Arc::new(RwLock::new(store)))
     } else {
         None
     };
 
+    // Server-side embedding, checked against the store's dimension so a
+    // mismatched model fails here rather than on every request
+    let embedding = match (args.embedder.as_deref(), &store) {
+        (Some(kind), Some(store)) => {
+            let config = match kind {
+                "onnx" => EmbedderConfig::Onnx {
+                    model: args
+                        .embed_model
+                        .clone()
+                        .unwrap_or_else(|| embed::DEFAULT_ONNX_MODEL.to_string()),
+                },
+                _ => EmbedderConfig::OpenAi {
+                    base_url: args.embed_url.clone(),
+                    model: args
+                        .embed_model
+                        .clone()
+                        .unwrap_or_else(|| embed::DEFAULT_OPENAI_MODEL.to_string()),
+                    api_key: std::env::var(&args.embed_api_key_env).ok(),
+                },
+            };
+            let embedder = config.build()?;
+            embed::check_dimension(embedder.as_ref(), store.read().await.dimension())?;
+            info!(
+                "🔤 Embedding with {} ({} dimensions)",
+                embedder.model(),
+                embedder.dimension()
+            );
+            Some(Embedding::new(embedder).with_max_batch(args.embed_max_batch))
+        }
+        (Some(_), None) => {
+            warn!("--embedder is ignored in namespace mode");
+            None
+        }
+        (None, _) => None,
+    };
+
+    // Background TTL sweep; expired records are already hidden from reads,
+    // this marks them for the next compaction. The same tick rebuilds the
+    // index once removals have fragmented it, so writes never pay for that.
+    if let (Some(secs), Some(store)) = (args.expiry_sweep_secs, store.clone()) {
+        info!("⏱️  Purging expired records every {}s", secs);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(secs.max(1)));
+            loop {
+                interval.tick().await;
+                let mut store = store.write().await;
+                match store.purge_expired() {
+                    Ok(0) => {}
+                    Ok(purged) => info!("Purged {} expired records", purged),
+                    Err(e) => warn!("Failed to purge expired records: {}", e),
+                }
+                if let Err(e) = store.defragment_if_needed() {
+                    warn!("Failed to rebuild the fragmented index: {}", e);
+                }
+            }
+        });
+    }
+
     // Start servers
+    let shutdown = Shutdown::new();
     let mut handles = vec![];
 
+    // Saves go through one checkpointer, so the background task, POST
+    // /admin/save and the save on exit never write at the same time
+    let checkpointer = store
+        .clone()
+        .map(|store| Arc::new(Checkpointer::new(store)));
+    match (args.auto_save_every, &checkpointer) {
+        (Some(secs), Some(checkpointer)) => {
+            info!("💾 Saving every {}s when there are unsaved changes", secs);
+            checkpointer.clone().spawn(
+                std::time::Duration::from_secs(secs.max(1)),
+                shutdown.clone(),
+            );
+        }
+        (Some(_), None) => {
+            warn!("--auto-save-every is ignored in namespace mode, which saves on every write")
+        }
+        (None, _) => {}
+    }
+
+    // Replica mode: poll the primary and swap in its snapshots; writes are
+    // refused by both servers
+    let replica = match (&args.replica_of, &store) {
+        (Some(primary), Some(store)) => {
+            let replica = Replica::new(primary.clone(), store.clone())
+                .with_api_key(std::env::var(&args.replica_api_key_env).ok());
+            let replica = Arc::new(replica);
+            info!(
+                "🔁 Read-only replica of {}, polling every {}s",
+                replica.primary(),
+                args.replica_poll_secs
+            );
+            replica.clone().spawn(
+                std::time::Duration::from_secs(args.replica_poll_secs.max(1)),
+                shutdown.clone(),
+            );
+            Some(replica)
+        }
+        (Some(_), None) => anyhow::bail!("--replica-of needs single-tenant mode"),
+        (None, _) => None,
+    };
+
+    let import_limits = ImportLimits {
+        max_body_bytes: args.import_max_bytes,
+        max_rows: args.import_max_rows,
+        ..ImportLimits::default()
+    };
+    let bulk_limits = BulkLimits {
+        max_batch_size: args.max_batch_size,
+        max_delete_matches: args.max_delete_matches,
+    };
+    let query_timeouts = QueryTimeouts {
+        default: std::time::Duration::from_millis(args.query_timeout_ms),
+        max: std::time::Duration::from_millis(args.max_query_timeout_ms),
+    };
+    let stream_limits = StreamLimits {
+        // A client answering pings stays well inside the idle timeout
+        ping_interval: std::time::Duration::from_secs((args.ws_idle_timeout_secs / 4).max(1)),
+        idle_timeout: std::time::Duration::from_secs(args.ws_idle_timeout_secs),
+        max_queries: args.ws_max_queries,
+        max_subscriptions: args.ws_max_subscriptions,
+        ..StreamLimits::default()
+    };
+    let http_options = HttpOptions {
+        cors: Cors::parse(&args.cors_origins, &args.cors_methods, &args.cors_headers)?,
+        compression: !args.no_compression,
+        max_body_bytes: args.max_body_bytes,
+        swagger_ui: args.swagger_ui,
+    };
+
     // Start gRPC server
     if !args.no_grpc {
         let grpc_addr: SocketAddr = format!("0.0.0.0:{}", args.grpc_port).parse()?;
@@ -134,30 +298,84 @@ async fn main() -> Result<()> {
         info!("🔌 Starting gRPC server on {}", grpc_addr);
 
         let grpc_handle = if let Some(ref manager) = namespace_manager {
-            // Multi-tenant mode: Admin service only (use namespaces for vector ops)
-            let admin_server = AdminService::new(manager.clone());
+            // Multi-tenant mode: Admin service, and vector ops routed by namespace
+            let mut admin_server = AdminService::new(manager.clone()).with_limits(limiter.clone());
+            let mut namespace_server = NamespaceGrpcServer::new(manager.clone())
+                .with_limits(limiter.clone())
+                .with_query_timeouts(query_timeouts);
+            if let Some(keys) = api_keys.clone() {
+                admin_server = admin_server.with_auth(keys.clone());
+                namespace_server = namespace_server.with_auth(keys);
+            }
+            let keys = api_keys.clone();
+            let stopped = shutdown.signalled();
 
             info!("   Admin API: grpc://{}/ (VecStoreAdminService)", grpc_addr);
+            info!(
+                "   Vector API: grpc://{}/ (VecStoreService, per-namespace)",
+                grpc_addr
+            );
 
             tokio::spawn(async move {
                 use vecstore::server::types::pb::vec_store_admin_service_server::VecStoreAdminServiceServer;
+                use vecstore::server::types::pb::vec_store_service_server::VecStoreServiceServer;
 
-                TonicServer::builder()
-                    .add_service(VecStoreAdminServiceServer::new(admin_server))
-                    .serve(grpc_addr)
+                let mut builder = TonicServer::builder()
+                    .layer(GrpcTraceLayer)
+                    .layer(GrpcMetricsLayer);
+                let router = match keys {
+                    Some(keys) => builder
+                        .add_service(VecStoreAdminServiceServer::with_interceptor(
+                            admin_server,
+                            keys.clone().interceptor(),
+                        ))
+                        .add_service(VecStoreServiceServer::with_interceptor(
+                            namespace_server,
+                            keys.interceptor(),
+                        )),
+                    None => builder
+                        .add_service(VecStoreAdminServiceServer::new(admin_server))
+                        .add_service(VecStoreServiceServer::new(namespace_server)),
+                };
+                router
+                    .serve_with_shutdown(grpc_addr, stopped)
                     .await
                     .map_err(|e| anyhow::anyhow!("gRPC server error: {}", e))
             })
         } else {
             // Single-tenant mode: VecStore service only
-            let grpc_server = VecStoreGrpcServer::with_store(store.clone().unwrap());
+            let mut grpc_server = VecStoreGrpcServer::with_store(store.clone().unwrap())
+                .with_import_limits(import_limits)
+                .with_bulk_limits(bulk_limits)
+                .with_query_timeouts(query_timeouts)
+                .with_limits(limiter.clone());
+            if let Some(events) = events.clone() {
+                grpc_server = grpc_server.with_events(events);
+            }
+            if let Some(keys) = api_keys.clone() {
+                grpc_server = grpc_server.with_auth(keys);
+            }
+            if let Some(replica) = replica.clone() {
+                grpc_server = grpc_server.with_replica(replica);
+            }
+            let keys = api_keys.clone();
+            let stopped = shutdown.signalled();
 
             tokio::spawn(async move {
                 use vecstore::server::types::pb::vec_store_service_server::VecStoreServiceServer;
 
-                TonicServer::builder()
-                    .add_service(VecStoreServiceServer::new(grpc_server))
-                    .serve(grpc_addr)
+                let mut builder = TonicServer::builder()
+                    .layer(GrpcTraceLayer)
+                    .layer(GrpcMetricsLayer);
+                let router = match keys {
+                    Some(keys) => builder.add_service(VecStoreServiceServer::with_interceptor(
+                        grpc_server,
+                        keys.interceptor(),
+                    )),
+                    None => builder.add_service(VecStoreServiceServer::new(grpc_server)),
+                };
+                router
+                    .serve_with_shutdown(grpc_addr, stopped)
                     .await
                     .map_err(|e| anyhow::anyhow!("gRPC server error: {}", e))
             })
@@ -176,32 +394,75 @@ async fn main() -> Result<()> {
 
         let app = if let Some(ref manager) = namespace_manager {
             // Multi-tenant mode: Admin API
-            let admin_server = AdminHttpServer::new(manager.clone());
+            let mut admin_server = AdminHttpServer::new(manager.clone())
+                .with_limits(limiter.clone())
+                .with_query_timeouts(query_timeouts)
+                .with_http_options(http_options.clone());
+            if let Some(keys) = api_keys.clone() {
+                admin_server = admin_server.with_auth(keys);
+            }
 
             info!("   Admin API: http://{}/admin/namespaces", http_addr);
+            info!(
+                "   REST API: http://{}/v1/ns/{{namespace}}/query",
+                http_addr
+            );
             info!("   Stats: http://{}/admin/stats", http_addr);
             info!("   Health: http://{}/health", http_addr);
+            info!("   OpenAPI: http://{}/openapi.json", http_addr);
 
             admin_server.router()
         } else {
             // Single-tenant mode: VecStore API
-            let http_server = VecStoreHttpServer::with_store(store.clone().unwrap());
+            let mut http_server = VecStoreHttpServer::with_store(store.clone().unwrap())
+                .with_import_limits(import_limits)
+                .with_bulk_limits(bulk_limits)
+                .with_query_timeouts(query_timeouts)
+                .with_stream_limits(stream_limits)
+                .with_limits(limiter.clone())
+                .with_http_options(http_options.clone())
+                .with_checkpointer(checkpointer.clone().unwrap());
 
             info!("   REST API: http://{}/v1/query", http_addr);
+            if let Some(embedding) = embedding.clone() {
+                info!("   Embeddings: http://{}/v1/embed", http_addr);
+                http_server = http_server.with_embedder(embedding);
+            }
             info!("   WebSocket: ws://{}/ws/query-stream", http_addr);
+            if let Some(events) = events.clone() {
+                info!("   Events: ws://{}/ws/events", http_addr);
+                http_server = http_server.with_events(events);
+            }
+            if let Some(keys) = api_keys.clone() {
+                http_server = http_server.with_auth(keys);
+            }
+            if let Some(replica) = replica.clone() {
+                http_server = http_server.with_replica(replica);
+            }
             info!("   Health: http://{}/health", http_addr);
             info!("   Metrics: http://{}/metrics", http_addr);
+            info!("   OpenAPI: http://{}/openapi.json", http_addr);
 
             http_server.router()
         };
 
+        if args.swagger_ui {
+            info!("   Swagger UI: http://{}/docs", http_addr);
+        }
+
+        let stopped = shutdown.signalled();
         let http_handle = tokio::spawn(async move {
             let listener = tokio::net::TcpListener::bind(http_addr)
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to bind HTTP server: {}", e))?;
-            axum::serve(listener, app)
-                .await
-                .map_err(|e| anyhow::anyhow!("HTTP server error: {}", e))
+            // Peer addresses key the rate limits of requests without an API key
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(stopped)
+            .await
+            .map_err(|e| anyhow::anyhow!("HTTP server error: {}", e))
         });
 
         handles.push(http_handle);
@@ -216,10 +477,23 @@ async fn main() -> Result<()> {
     info!("✅ VecStore Server running");
     info!("   Press Ctrl+C to stop");
 
-    // Wait for all servers
-    for handle in handles {
-        handle.await??;
+    shutdown.trigger_on_signal();
+    let deadline = std::time::Duration::from_secs(args.shutdown_timeout_secs);
+    let served = serve_until_shutdown(handles, &shutdown, deadline).await;
+
+    // Writes since the last save are only in memory (and the WAL, if on)
+    info!("💾 Saving before exit");
+    if let Some(checkpointer) = &checkpointer {
+        let report = checkpointer.checkpoint(SaveTrigger::Shutdown, true).await;
+        if let Some(error) = report.error {
+            anyhow::bail!("Failed to save before exit: {}", error);
+        }
+    }
+    if let Some(manager) = &namespace_manager {
+        manager.write().await.save_all()?;
     }
+    served?;
+    info!("👋 Shutdown complete");
 
     Ok(())
 }
@@ -1,700 +1,5 @@
-use anyhow::{Context, Result};
-use clap::{Parser, Subcommand, ValueEnum};
-use std::collections::HashMap;
-use std::fs;
-use std::path::PathBuf;
-use std::time::Instant;
-use vecstore::{FilterExpr, Metadata, Query, Record, VecDatabase, VecStore};
+//! `vecstore` command-line tool; the commands live in [`vecstore::cli`]
 
-#[derive(Parser)]
-#[command(name = "vecstore")]
-#[command(version = "1.1.0")]
-#[command(about = "Production-ready vector database CLI", long_about = None)]
-struct Cli {
-    #[command(subcommand)]
-    command: Commands,
-}
-
-#[derive(Subcommand)]
-enum Commands {
-    /// Initialize a new vector store
-    Init {
-        /// Directory to store data
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-
-        /// Vector dimension
-        #[arg(short = 'D', long)]
-        dimension: Option<usize>,
-    },
-
-    /// Ingest a single vector
-    Ingest {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-        /// Record ID
-        #[arg(short, long)]
-        id: String,
-        /// Path to vector JSON file (array of f32)
-        #[arg(short, long)]
-        vec: PathBuf,
-        /// Path to metadata JSON file
-        #[arg(short, long)]
-        meta: PathBuf,
-    },
-
-    /// Ingest batch of vectors from JSONL file
-    IngestBatch {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-        /// Path to JSONL file
-        #[arg(short, long)]
-        jsonl: PathBuf,
-    },
-
-    /// Query the vector store
-    Query {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-        /// Path to query vector JSON file
-        #[arg(short, long)]
-        vec: PathBuf,
-        /// Number of results
-        #[arg(short, long, default_value = "5")]
-        k: usize,
-        /// Filter expression (JSON)
-        #[arg(short, long)]
-        filter: Option<String>,
-        /// Output as JSON
-        #[arg(long)]
-        json_out: bool,
-    },
-
-    /// Show store statistics
-    Stats {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-
-        /// Show detailed statistics
-        #[arg(long)]
-        detailed: bool,
-    },
-
-    /// Export vectors to various formats
-    Export {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-
-        /// Output file path
-        #[arg(short, long)]
-        output: PathBuf,
-
-        /// Export format
-        #[arg(short, long, value_enum, default_value = "jsonl")]
-        format: ExportFormat,
-    },
-
-    /// Import vectors from other formats
-    Import {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-
-        /// Input file path
-        #[arg(short, long)]
-        input: PathBuf,
-
-        /// Import format
-        #[arg(short, long, value_enum)]
-        format: ImportFormat,
-    },
-
-    /// Migrate from other vector databases
-    Migrate {
-        /// Source database type
-        #[arg(short, long, value_enum)]
-        source: MigrationSource,
-
-        /// Source connection string or file path
-        #[arg(short = 'c', long)]
-        source_path: String,
-
-        /// Destination directory
-        #[arg(short, long, default_value = "./data")]
-        dest: PathBuf,
-    },
-
-    /// Backup the vector store
-    Backup {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-
-        /// Backup file path
-        #[arg(short, long)]
-        output: PathBuf,
-
-        /// Compress backup
-        #[arg(long)]
-        compress: bool,
-    },
-
-    /// Restore from backup
-    Restore {
-        /// Backup file path
-        #[arg(short, long)]
-        backup: PathBuf,
-
-        /// Destination directory
-        #[arg(short, long, default_value = "./data")]
-        dest: PathBuf,
-    },
-
-    /// Optimize the index
-    Optimize {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-
-        /// Force full rebuild
-        #[arg(long)]
-        rebuild: bool,
-    },
-
-    /// Benchmark search performance
-    Benchmark {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-
-        /// Number of queries to run
-        #[arg(short, long, default_value = "1000")]
-        queries: usize,
-
-        /// Number of results per query
-        #[arg(short, long, default_value = "10")]
-        k: usize,
-    },
-
-    /// Health check
-    Health {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-    },
-
-    /// Collection management commands
-    #[command(subcommand)]
-    Collection(CollectionCommands),
-
-    /// Delete vectors by ID or filter
-    Delete {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-
-        /// Vector ID to delete
-        #[arg(short, long)]
-        id: Option<String>,
-
-        /// Filter expression to delete matching vectors
-        #[arg(short, long)]
-        filter: Option<String>,
-    },
-
-    /// Compact the store (remove deleted vectors)
-    Compact {
-        /// Directory containing the store
-        #[arg(short, long, default_value = "./data")]
-        dir: PathBuf,
-    },
-}
-
-#[derive(Subcommand)]
-enum CollectionCommands {
-    /// List all collections
-    List {
-        /// Database directory
-        #[arg(short, long, default_value = "./db")]
-        dir: PathBuf,
-    },
-
-    /// Create a new collection
-    Create {
-        /// Database directory
-        #[arg(short, long, default_value = "./db")]
-        dir: PathBuf,
-
-        /// Collection name
-        #[arg(short, long)]
-        name: String,
-
-        /// Vector dimension
-        #[arg(short = 'D', long)]
-        dimension: usize,
-    },
-
-    /// Drop a collection
-    Drop {
-        /// Database directory
-        #[arg(short, long, default_value = "./db")]
-        dir: PathBuf,
-
-        /// Collection name
-        #[arg(short, long)]
-        name: String,
-    },
-
-    /// Show collection info
-    Info {
-        /// Database directory
-        #[arg(short, long, default_value = "./db")]
-        dir: PathBuf,
-
-        /// Collection name
-        #[arg(short, long)]
-        name: String,
-    },
-}
-
-#[derive(ValueEnum, Clone, Copy)]
-enum ExportFormat {
-    Jsonl,
-    Csv,
-    Parquet,
-    Npy,
-}
-
-#[derive(Debug, ValueEnum, Clone, Copy)]
-enum ImportFormat {
-    Jsonl,
-    Csv,
-    Npy,
-    Pinecone,
-    Weaviate,
-    Qdrant,
-}
-
-#[derive(Debug, ValueEnum, Clone, Copy)]
-enum MigrationSource {
-    Pinecone,
-    Weaviate,
-    Qdrant,
-    ChromaDB,
-    Milvus,
-}
-
-fn main() -> Result<()> {
-    vecstore::init_tracing();
-
-    let cli = Cli::parse();
-
-    match cli.command {
-        Commands::Init { dir, dimension } => {
-            let store = VecStore::open(&dir)?;
-            store.save()?;
-            println!("✓ Initialized vector store at: {:?}", dir);
-            if let Some(dim) = dimension {
-                println!(
-                    "  Note: Dimension ({}) will be auto-detected from first insert",
-                    dim
-                );
-            } else {
-                println!("  Dimension will be auto-detected from first insert");
-            }
-        }
-
-        Commands::Ingest { dir, id, vec, meta } => {
-            let mut store = VecStore::open(&dir)?;
-
-            let vector_data = fs::read_to_string(&vec)
-                .with_context(|| format!("Failed to read vector file: {:?}", vec))?;
-            let vector: Vec<f32> = serde_json::from_str(&vector_data)
-                .with_context(|| "Failed to parse vector JSON")?;
-
-            let meta_data = fs::read_to_string(&meta)
-                .with_context(|| format!("Failed to read metadata file: {:?}", meta))?;
-            let fields: HashMap<String, serde_json::Value> = serde_json::from_str(&meta_data)
-                .with_context(|| "Failed to parse metadata JSON")?;
-            let metadata = Metadata { fields };
-
-            store.upsert(id.clone(), vector, metadata)?;
-            store.save()?;
-
-            println!("✓ Ingested record: {}", id);
-        }
-
-        Commands::IngestBatch { dir, jsonl } => {
-            let mut store = VecStore::open(&dir)?;
-
-            let content = fs::read_to_string(&jsonl)
-                .with_context(|| format!("Failed to read JSONL file: {:?}", jsonl))?;
-
-            let mut records = Vec::new();
-            for (line_num, line) in content.lines().enumerate() {
-                if line.trim().is_empty() {
-                    continue;
-                }
-
-                let record: Record = serde_json::from_str(line)
-                    .with_context(|| format!("Failed to parse line {}", line_num + 1))?;
-                records.push(record);
-            }
-
-            let count = records.len();
-            println!("Ingesting {} records...", count);
-
-            let start = Instant::now();
-            store.batch_upsert(records)?;
-            store.save()?;
-            let elapsed = start.elapsed();
-
-            println!(
-                "✓ Ingested {} records in {:.2}s ({:.0} vec/s)",
-                count,
-                elapsed.as_secs_f64(),
-                count as f64 / elapsed.as_secs_f64()
-            );
-        }
-
-        Commands::Query {
-            dir,
-            vec,
-            k,
-            filter,
-            json_out,
-        } => {
-            let store = VecStore::open(&dir)?;
-
-            let vector_data = fs::read_to_string(&vec)
-                .with_context(|| format!("Failed to read vector file: {:?}", vec))?;
-            let vector: Vec<f32> = serde_json::from_str(&vector_data)
-                .with_context(|| "Failed to parse vector JSON")?;
-
-            let filter_expr = if let Some(f) = filter {
-                let expr: FilterExpr =
-                    serde_json::from_str(&f).with_context(|| "Failed to parse filter JSON")?;
-                Some(expr)
-            } else {
-                None
-            };
-
-            let query = Query {
-                vector,
-                k,
-                filter: filter_expr,
-            };
-
-            let start = Instant::now();
-            let results = store.query(query)?;
-            let elapsed = start.elapsed();
-
-            if json_out {
-                println!("{}", serde_json::to_string_pretty(&results)?);
-            } else {
-                println!(
-                    "✓ Found {} results in {:.2}ms:",
-                    results.len(),
-                    elapsed.as_millis()
-                );
-                for (i, neighbor) in results.iter().enumerate() {
-                    println!("{}. {} (score: {:.4})", i + 1, neighbor.id, neighbor.score);
-                    if !neighbor.metadata.fields.is_empty() {
-                        println!("   {:?}", neighbor.metadata.fields);
-                    }
-                }
-            }
-        }
-
-        Commands::Stats { dir, detailed } => {
-            let store = VecStore::open(&dir)?;
-            println!("📊 Vector Store Statistics");
-            println!("==========================");
-            println!("Location:  {:?}", dir);
-            println!("Records:   {}", store.count());
-            println!("Dimension: {}", store.dimension());
-
-            if detailed {
-                println!("\nDetailed Statistics:");
-                println!("  Distance metric: {:?}", store.distance_metric());
-                println!(
-                    "  Memory usage:    ~{} MB",
-                    (store.count() * store.dimension() * 4) / 1_048_576
-                );
-            }
-        }
-
-        Commands::Export {
-            dir,
-            output,
-            format,
-        } => {
-            let store = VecStore::open(&dir)?;
-
-            println!("Exporting {} vectors to {:?}...", store.count(), output);
-
-            match format {
-                ExportFormat::Jsonl => {
-                    // Export as JSONL
-                    let mut lines: Vec<String> = Vec::new();
-                    // Implementation would iterate and export
-                    println!("✓ Exported to JSONL format");
-                }
-                ExportFormat::Csv => {
-                    println!("✓ Exported to CSV format");
-                }
-                ExportFormat::Parquet => {
-                    println!("✓ Exported to Parquet format");
-                }
-                ExportFormat::Npy => {
-                    println!("✓ Exported to NumPy format");
-                }
-            }
-        }
-
-        Commands::Import { dir, input, format } => {
-            println!("Importing from {:?} ({:?} format)...", input, format);
-
-            match format {
-                ImportFormat::Jsonl => {
-                    println!("✓ Imported from JSONL");
-                }
-                ImportFormat::Csv => {
-                    println!("✓ Imported from CSV");
-                }
-                ImportFormat::Npy => {
-                    println!("✓ Imported from NumPy");
-                }
-                ImportFormat::Pinecone => {
-                    println!("✓ Imported from Pinecone export");
-                }
-                ImportFormat::Weaviate => {
-                    println!("✓ Imported from Weaviate export");
-                }
-                ImportFormat::Qdrant => {
-                    println!("✓ Imported from Qdrant export");
-                }
-            }
-        }
-
-        Commands::Migrate {
-            source,
-            source_path,
-            dest,
-        } => {
-            println!("🔄 Migrating from {:?}...", source);
-            println!("   Source: {}", source_path);
-            println!("   Destination: {:?}", dest);
-
-            match source {
-                MigrationSource::Pinecone => {
-                    println!("   Connecting to Pinecone...");
-                    println!("✓ Migration complete!");
-                }
-                MigrationSource::Weaviate => {
-                    println!("   Connecting to Weaviate...");
-                    println!("✓ Migration complete!");
-                }
-                MigrationSource::Qdrant => {
-                    println!("   Connecting to Qdrant...");
-                    println!("✓ Migration complete!");
-                }
-                MigrationSource::ChromaDB => {
-                    println!("   Connecting to ChromaDB...");
-                    println!("✓ Migration complete!");
-                }
-                MigrationSource::Milvus => {
-                    println!("   Connecting to Milvus...");
-                    println!("✓ Migration complete!");
-                }
-            }
-        }
-
-        Commands::Backup {
-            dir,
-            output,
-            compress,
-        } => {
-            eprintln!("❌ Backup feature not yet implemented");
-            eprintln!(
-                "   Workaround: Copy the entire directory {:?} to create a backup",
-                dir
-            );
-            eprintln!("   Requested output: {:?}", output);
-            if compress {
-                eprintln!("   Compression requested: {}", compress);
-            }
-            std::process::exit(1);
-        }
-
-        Commands::Restore { backup, dest } => {
-            eprintln!("❌ Restore feature not yet implemented");
-            eprintln!("   Workaround: Copy the backup directory to {:?}", dest);
-            eprintln!("   Requested backup: {:?}", backup);
-            std::process::exit(1);
-        }
-
-        Commands::Optimize { dir, rebuild } => {
-            let mut store = VecStore::open(&dir)?;
-
-            println!("⚡ Optimizing index...");
-            let start = Instant::now();
-
-            if rebuild {
-                println!("  Rebuilding from scratch...");
-            }
-
-            store.optimize()?;
-            store.save()?;
-
-            let elapsed = start.elapsed();
-            println!("✓ Optimization complete in {:.2}s", elapsed.as_secs_f64());
-        }
-
-        Commands::Benchmark { dir, queries, k } => {
-            let store = VecStore::open(&dir)?;
-
-            println!("🔥 Running benchmark...");
-            println!("   Queries: {}", queries);
-            println!("   Top-k: {}", k);
-
-            // Generate random query vectors
-            let dim = store.dimension();
-            let mut total_time = 0.0;
-
-            for i in 0..queries {
-                let query_vec: Vec<f32> = (0..dim).map(|_| rand::random::<f32>()).collect();
-                let query = Query {
-                    vector: query_vec,
-                    k,
-                    filter: None,
-                };
-
-                let start = Instant::now();
-                let _ = store.query(query)?;
-                total_time += start.elapsed().as_secs_f64();
-
-                if (i + 1) % 100 == 0 {
-                    print!(".");
-                    use std::io::{self, Write};
-                    io::stdout().flush()?;
-                }
-            }
-
-            println!();
-            println!("✓ Benchmark complete!");
-            println!(
-                "   Average latency: {:.2}ms",
-                (total_time / queries as f64) * 1000.0
-            );
-            println!(
-                "   Throughput: {:.0} queries/sec",
-                queries as f64 / total_time
-            );
-        }
-
-        Commands::Health { dir } => {
-            let store = VecStore::open(&dir)?;
-
-            println!("💚 Health Check");
-            println!("==============");
-            println!("Status: ✓ HEALTHY");
-            println!("Records: {}", store.count());
-            println!("Dimension: {}", store.dimension());
-
-            // Check for issues
-            if store.count() == 0 {
-                println!("⚠️  Warning: Store is empty");
-            }
-        }
-
-        Commands::Collection(cmd) => match cmd {
-            CollectionCommands::List { dir } => {
-                let db = VecDatabase::open(&dir)?;
-                let collections = db.list_collections()?;
-
-                println!("📁 Collections ({})", collections.len());
-                println!("=================");
-                for name in collections {
-                    println!("  - {}", name);
-                }
-            }
-
-            CollectionCommands::Create {
-                dir,
-                name,
-                dimension: _,
-            } => {
-                let mut db = VecDatabase::open(&dir)?;
-                db.create_collection(&name)?;
-
-                println!("✓ Created collection '{}'", name);
-            }
-
-            CollectionCommands::Drop { dir, name } => {
-                let mut db = VecDatabase::open(&dir)?;
-                db.delete_collection(&name)?;
-
-                println!("✓ Dropped collection '{}'", name);
-            }
-
-            CollectionCommands::Info { dir, name } => {
-                let db = VecDatabase::open(&dir)?;
-                let collection = db.get_collection(&name)?;
-
-                if let Some(coll) = collection {
-                    let stats = coll.stats()?;
-                    println!("📊 Collection: {}", name);
-                    println!("====================");
-                    println!("Records: {}", stats.vector_count);
-                    println!("Dimension: {}", stats.dimension);
-                } else {
-                    println!("Collection '{}' not found", name);
-                }
-            }
-        },
-
-        Commands::Delete { dir, id, filter } => {
-            let mut store = VecStore::open(&dir)?;
-
-            if let Some(id) = id {
-                store.delete(&id)?;
-                store.save()?;
-                println!("✓ Deleted vector: {}", id);
-            } else if let Some(filter_str) = filter {
-                let filter_expr: FilterExpr = serde_json::from_str(&filter_str)?;
-                // Delete by filter
-                println!("✓ Deleted vectors matching filter");
-            } else {
-                eprintln!("Error: Must specify either --id or --filter");
-                std::process::exit(1);
-            }
-        }
-
-        Commands::Compact { dir } => {
-            let mut store = VecStore::open(&dir)?;
-
-            println!("🗜️  Compacting store...");
-            let before = store.count();
-
-            store.compact()?;
-            store.save()?;
-
-            let after = store.count();
-            println!("✓ Compaction complete");
-            println!("  Before: {} vectors", before);
-            println!("  After: {} vectors", after);
-            println!(
-                "  Removed: {} deleted vectors",
-                before.saturating_sub(after)
-            );
-        }
-    }
-
-    Ok(())
+fn main() -> anyhow::Result<()> {
+    vecstore::cli::run()
 }
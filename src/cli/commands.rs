@@ -1,471 +1,2533 @@
-//! CLI command implementations
+//! What each `vecstore` command does
 
+use super::{
+    config, diff, server_args, Cli, CollectionCommands, Commands, ConfigCommands, CsvVectorFormat,
+    DiffFormat, ExportFormat, ImportFormat, MigrationSource,
+};
+use crate::import_export::{CsvOptions, FileFormat, ImportReport, Importer, VectorFormat};
+use crate::{
+    make_record, print_health_report, CheckSeverity, CheckStatus, CollectionConfig, Distance,
+    DuplicateKeep, FilterExpr, HealthChecker, IntegrityReport, Metadata,
+    OptimizationRecommendation, Query, QueryOptimizer, QueryOptions, Record,
+    StoreOptimizationSummary, UpsertPolicy, UpsertSummary, VecDatabase, VecStore,
+};
 use anyhow::{Context, Result};
-use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::Path;
+use clap::CommandFactory;
+use indicatif::{HumanDuration, ProgressBar, ProgressState, ProgressStyle};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::{BufRead, BufReader, BufWriter, IsTerminal, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
-use crate::cli::{
-    parse_ids, parse_vector, ExportFormat, ImportFormat, OutputFormat, VectorData,
-};
-use crate::store::{Metadata, Query, VecStore};
-use crate::health::{HealthChecker, HealthCheckConfig};
+/// Run the parsed command line
+pub(super) fn execute(cli: Cli, config: &config::Config) -> Result<()> {
+    let quiet = cli.quiet;
+
+    match cli.command {
+        Commands::Init {
+            dir,
+            dimension,
+            encrypt,
+        } => {
+            let store = if encrypt {
+                open_encrypted(&dir)?
+            } else {
+                VecStore::open(&dir)?
+            };
+            store.save()?;
+            println!("✓ Initialized vector store at: {:?}", dir);
+            if store.is_encrypted() {
+                println!(
+                    "  Encrypted at rest; keep the key, the data can't be recovered without it"
+                );
+            }
+            if let Some(dim) = dimension {
+                println!(
+                    "  Note: Dimension ({}) will be auto-detected from first insert",
+                    dim
+                );
+            } else {
+                println!("  Dimension will be auto-detected from first insert");
+            }
+        }
+
+        Commands::Ingest {
+            dir,
+            id,
+            vec,
+            meta,
+            text,
+            parent,
+            chunk_index,
+        } => {
+            let mut store = VecStore::open(&dir)?;
+
+            let vector_data = fs::read_to_string(&vec)
+                .with_context(|| format!("Failed to read vector file: {:?}", vec))?;
+            let vector: Vec<f32> = serde_json::from_str(&vector_data)
+                .with_context(|| "Failed to parse vector JSON")?;
 
-/// Show database information
-pub fn info(store: &VecStore, detailed: bool) -> Result<()> {
-    println!("📊 VecStore Database Information\n");
-    println!("Database Path: {:?}", store.path());
-    println!("Total Vectors: {}", store.len());
+            let meta_data = fs::read_to_string(&meta)
+                .with_context(|| format!("Failed to read metadata file: {:?}", meta))?;
+            let fields: HashMap<String, serde_json::Value> = serde_json::from_str(&meta_data)
+                .with_context(|| "Failed to parse metadata JSON")?;
+            let metadata = Metadata { fields };
 
-    if detailed {
-        println!("\nDetailed Statistics:");
-        let stats = store.stats()?;
-        println!("  Memory Usage: {} bytes", stats.memory_usage);
-        println!("  Average Vector Size: {} bytes", stats.avg_vector_size);
-        println!("  Index Type: HNSW");
+            store.upsert_record(Record {
+                text,
+                parent_id: parent,
+                chunk_index,
+                ..make_record(id.clone(), vector, metadata)
+            })?;
+            store.save()?;
 
-        if let Some(config) = stats.hnsw_config {
-            println!("\nHNSW Configuration:");
-            println!("  M (connections): {}", config.m);
-            println!("  ef_construction: {}", config.ef_construction);
+            println!("✓ Ingested record: {}", id);
         }
-    }
 
-    Ok(())
-}
+        Commands::IngestBatch {
+            dir,
+            jsonl,
+            on_conflict,
+            dry_run,
+            partial,
+        } => {
+            let mut store = VecStore::open(&dir)?;
 
-/// Query for similar vectors
-pub fn query(
-    store: &VecStore,
-    vector_str: &str,
-    limit: usize,
-    filter: Option<&str>,
-    format: OutputFormat,
-) -> Result<()> {
-    // Parse vector
-    let vector = parse_vector(vector_str)?;
+            let file = fs::File::open(&jsonl)
+                .with_context(|| format!("Failed to read JSONL file: {:?}", jsonl))?;
 
-    // Build query
-    let mut q = Query::new(vector).with_limit(limit);
-    if let Some(f) = filter {
-        q = q.with_filter(f);
-    }
+            let mut records = Vec::new();
+            let mut line_nums = Vec::new();
+            for (line_num, line) in BufReader::new(file).lines().enumerate() {
+                let line = line.with_context(|| format!("Failed to read line {}", line_num + 1))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+
+                let record: Record = serde_json::from_str(&line)
+                    .with_context(|| format!("Failed to parse line {}", line_num + 1))?;
+                records.push(record);
+                line_nums.push(line_num + 1);
+            }
+
+            let failed = store.validate_batch(&records);
+            if !failed.is_empty() {
+                eprintln!("⚠️  {} invalid record(s):", failed.len());
+                for item in &failed {
+                    eprintln!(
+                        "   line {} ('{}'): {}",
+                        line_nums[item.index], item.id, item.reason
+                    );
+                }
+                if !partial && !dry_run {
+                    return Err(anyhow::anyhow!(
+                        "No records ingested; fix the lines above or pass --partial to ingest the rest"
+                    ));
+                }
+                let mut rejected = failed.iter().map(|e| e.index).peekable();
+                records = records
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(index, _)| rejected.next_if_eq(index).is_none())
+                    .map(|(_, record)| record)
+                    .collect();
+            }
 
-    // Execute query
-    let results = store.query(q)?;
+            let count = records.len();
+            if dry_run {
+                println!("Checking {} records (dry run)...", count);
+                let summary = store.dry_run_upsert(records, on_conflict.into())?;
+                print_upsert_summary(&summary, on_conflict.into(), true);
+                return Ok(());
+            }
+            println!("Ingesting {} records...", count);
+
+            // Nothing is saved unless every batch is written
+            let start = Instant::now();
+            let mut progress = Progress::new("Ingesting", "records", Some(count), quiet);
+            let mut summary = UpsertSummary::default();
+            let mut records = records.into_iter().peekable();
+            while records.peek().is_some() {
+                let batch: Vec<Record> = records.by_ref().take(INGEST_BATCH_SIZE).collect();
+                let written = batch.len();
+                summary += store.batch_upsert_with_policy(batch, on_conflict.into())?;
+                progress.inc(written);
+            }
+            progress.finish();
+            store.save()?;
+            let elapsed = start.elapsed();
 
-    // Format output
-    match format {
-        OutputFormat::Json => {
-            let json = serde_json::to_string_pretty(&results)?;
-            println!("{}", json);
+            println!(
+                "✓ Ingested {} records in {:.2}s ({:.0} vec/s)",
+                count,
+                elapsed.as_secs_f64(),
+                count as f64 / elapsed.as_secs_f64()
+            );
+            print_upsert_summary(&summary, on_conflict.into(), false);
         }
-        OutputFormat::Table => {
-            println!("\n🔍 Query Results (top {}):\n", limit);
-            println!("{:<20} {:<15} {:<10}", "ID", "Score", "Distance");
-            println!("{}", "-".repeat(45));
-            for result in results {
+
+        Commands::Query {
+            dir,
+            vec,
+            text,
+            embed_model,
+            k,
+            filter,
+            show_text,
+            json_out,
+        } => {
+            let store = VecStore::open(&dir)?;
+
+            let vector = match (vec, text) {
+                (Some(vec), _) => {
+                    let vector_data = fs::read_to_string(&vec)
+                        .with_context(|| format!("Failed to read vector file: {:?}", vec))?;
+                    serde_json::from_str(&vector_data)
+                        .with_context(|| "Failed to parse vector JSON")?
+                }
+                (None, Some(text)) => embed_query_text(&store, embed_model.as_deref(), &text)?,
+                (None, None) => unreachable!("clap requires --vec or --text"),
+            };
+
+            let filter_expr = filter.as_deref().map(parse_filter_arg).transpose()?;
+
+            let query = Query {
+                vector,
+                k,
+                filter: filter_expr,
+                options: QueryOptions {
+                    include_text: show_text,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            let start = Instant::now();
+            let results = store.query(query)?;
+            let elapsed = start.elapsed();
+
+            if json_out {
+                println!("{}", serde_json::to_string_pretty(&results)?);
+            } else {
                 println!(
-                    "{:<20} {:<15.6} {:<10.6}",
-                    result.id, result.score, result.distance
+                    "✓ Found {} results in {:.2}ms:",
+                    results.len(),
+                    elapsed.as_millis()
                 );
+                for (i, neighbor) in results.iter().enumerate() {
+                    println!("{}. {} (score: {:.4})", i + 1, neighbor.id, neighbor.score);
+                    if !neighbor.metadata.fields.is_empty() {
+                        println!("   {:?}", neighbor.metadata.fields);
+                    }
+                    if let Some(text) = &neighbor.text {
+                        println!("   {}", text);
+                    }
+                }
             }
         }
-        OutputFormat::Simple => {
-            for result in results {
-                println!("{}\t{:.6}", result.id, result.score);
+
+        Commands::IngestText {
+            dir,
+            file,
+            chunk_size,
+            chunk_overlap,
+            embed_model,
+        } => ingest_text(
+            &dir,
+            &file,
+            chunk_size,
+            chunk_overlap,
+            embed_model.as_deref(),
+            quiet,
+        )?,
+
+        Commands::Get { dir, id, json_out } => {
+            let store = VecStore::open(&dir)?;
+            let records = store.get_many(&id);
+
+            if json_out {
+                println!("{}", serde_json::to_string_pretty(&records)?);
+            } else {
+                for (id, record) in id.iter().zip(&records) {
+                    match record {
+                        Some(record) => {
+                            println!("✓ {}", record.id);
+                            println!("   vector: {:?}", record.vector);
+                            if !record.metadata.fields.is_empty() {
+                                println!("   {:?}", record.metadata.fields);
+                            }
+                            print_chunk_details(record);
+                        }
+                        None => println!("✗ {} not found", id),
+                    }
+                }
+            }
+
+            if records.iter().any(Option::is_none) {
+                std::process::exit(1);
             }
         }
-    }
 
-    Ok(())
-}
+        Commands::ParentWindow {
+            dir,
+            id,
+            n,
+            json_out,
+        } => {
+            let store = VecStore::open(&dir)?;
+            let records = store.fetch_parent_window(&id, n)?;
 
-/// Insert a vector
-pub fn insert(
-    store: &mut VecStore,
-    id: &str,
-    vector_str: &str,
-    metadata_str: Option<&str>,
-) -> Result<()> {
-    let vector = parse_vector(vector_str)?;
+            if json_out {
+                println!("{}", serde_json::to_string_pretty(&records)?);
+            } else {
+                for record in &records {
+                    let marker = if record.id == id { "▶" } else { " " };
+                    println!("{} {}", marker, record.id);
+                    print_chunk_details(record);
+                }
+            }
+        }
 
-    let metadata = if let Some(json_str) = metadata_str {
-        serde_json::from_str(json_str).context("Invalid metadata JSON")?
-    } else {
-        serde_json::json!({})
-    };
+        Commands::UpdateMeta {
+            dir,
+            id,
+            set,
+            replace,
+        } => {
+            let mut store = VecStore::open(&dir)?;
 
-    store.upsert(id.to_string(), vector, metadata)?;
-    println!("✅ Inserted vector '{}'", id);
+            let fields = set
+                .iter()
+                .map(|s| parse_assignment(s))
+                .collect::<Result<HashMap<_, _>>>()?;
 
-    Ok(())
-}
+            if replace {
+                store.replace_metadata(&id, Metadata { fields })?;
+            } else {
+                store.update_metadata(&id, fields)?;
+            }
+            store.save()?;
 
-/// Delete vectors
-pub fn delete(
-    store: &mut VecStore,
-    ids: Option<&str>,
-    filter: Option<&str>,
-    yes: bool,
-) -> Result<()> {
-    if ids.is_none() && filter.is_none() {
-        anyhow::bail!("Must specify either --ids or --filter");
-    }
+            println!("✓ Updated metadata for: {}", id);
+        }
 
-    let to_delete = if let Some(ids_str) = ids {
-        parse_ids(ids_str)
-    } else if let Some(filter_expr) = filter {
-        // Query to find matching IDs
-        let q = Query::new(vec![0.0; 128]).with_filter(filter_expr).with_limit(10000);
-        let results = store.query(q)?;
-        results.into_iter().map(|r| r.id).collect()
-    } else {
-        vec![]
-    };
+        Commands::List {
+            dir,
+            filter,
+            offset,
+            limit,
+            show_vectors,
+            json_out,
+        } => {
+            let store = VecStore::open(&dir)?;
 
-    if to_delete.is_empty() {
-        println!("⚠️  No vectors found to delete");
-        return Ok(());
-    }
+            let filter_expr = filter.as_deref().map(parse_filter_arg).transpose()?;
+
+            let records = store.scan(filter_expr, offset, limit);
 
-    if !yes {
-        println!("⚠️  About to delete {} vectors:", to_delete.len());
-        for id in to_delete.iter().take(10) {
-            println!("  - {}", id);
+            if json_out {
+                println!("{}", serde_json::to_string_pretty(&records)?);
+            } else {
+                println!("📋 Vectors ({} shown):", records.len());
+                print_records(&records, show_vectors);
+            }
         }
-        if to_delete.len() > 10 {
-            println!("  ... and {} more", to_delete.len() - 10);
+
+        Commands::Sample {
+            dir,
+            filter,
+            n,
+            seed,
+            show_vectors,
+            json_out,
+        } => {
+            let store = VecStore::open(&dir)?;
+            let filter_expr = filter.as_deref().map(parse_filter_arg).transpose()?;
+            let seed = seed.unwrap_or_else(rand::random);
+            let records = store.sample(n, filter_expr, seed);
+
+            if json_out {
+                print_records_json(&records, show_vectors)?;
+            } else {
+                println!("🎲 {} random records (seed {}):", records.len(), seed);
+                print_records(&records, show_vectors);
+            }
         }
-        println!("\nProceed? (y/N)");
-        let mut input = String::new();
-        std::io::stdin().read_line(&mut input)?;
-        if !input.trim().eq_ignore_ascii_case("y") {
-            println!("Cancelled");
-            return Ok(());
+
+        Commands::Head {
+            dir,
+            filter,
+            n,
+            show_vectors,
+            json_out,
+        } => {
+            let store = VecStore::open(&dir)?;
+            let filter_expr = filter.as_deref().map(parse_filter_arg).transpose()?;
+            let records = store.scan(filter_expr, 0, n);
+
+            if json_out {
+                print_records_json(&records, show_vectors)?;
+            } else {
+                println!("📋 First {} records:", records.len());
+                print_records(&records, show_vectors);
+            }
         }
-    }
 
-    for id in &to_delete {
-        store.delete(id)?;
-    }
+        Commands::Tail {
+            dir,
+            filter,
+            n,
+            show_vectors,
+            json_out,
+        } => {
+            let store = VecStore::open(&dir)?;
+            let filter_expr = filter.as_deref().map(parse_filter_arg).transpose()?;
+            let matching = store.count_where(filter_expr.clone());
+            let records = store.scan(filter_expr, matching.saturating_sub(n), n);
+
+            if json_out {
+                print_records_json(&records, show_vectors)?;
+            } else {
+                println!("📋 Last {} records:", records.len());
+                print_records(&records, show_vectors);
+            }
+        }
 
-    println!("✅ Deleted {} vectors", to_delete.len());
+        Commands::Stats {
+            dir,
+            detailed,
+            group_by,
+            filter,
+            json_out,
+        } => {
+            let store = VecStore::open(&dir)?;
+            let filter_expr = filter.as_deref().map(parse_filter_arg).transpose()?;
 
-    Ok(())
-}
+            if json_out {
+                let mut json = serde_json::to_value(store.stats())?;
+                if filter_expr.is_some() {
+                    json["matching"] = store.count_where(filter_expr.clone()).into();
+                }
+                if let Some(field) = group_by {
+                    json["groups"] = serde_json::to_value(store.aggregate(filter_expr, &field))?;
+                }
+                println!("{}", serde_json::to_string_pretty(&json)?);
+                return Ok(());
+            }
 
-/// Import vectors from file
-pub fn import(
-    store: &mut VecStore,
-    file: &Path,
-    format: ImportFormat,
-    batch_size: usize,
-    no_validate: bool,
-) -> Result<()> {
-    let format = if format == ImportFormat::Auto {
-        // Detect from extension
-        match file.extension().and_then(|s| s.to_str()) {
-            Some("json") => ImportFormat::Json,
-            Some("jsonl") | Some("ndjson") => ImportFormat::Jsonl,
-            Some("csv") => ImportFormat::Csv,
-            _ => anyhow::bail!("Cannot auto-detect format, please specify --format"),
+            println!("📊 Vector Store Statistics");
+            println!("==========================");
+            println!("Location:  {:?}", dir);
+            println!("Records:   {}", store.count());
+            if filter_expr.is_some() {
+                println!("Matching:  {}", store.count_where(filter_expr.clone()));
+            }
+            println!("Dimension: {}", store.dimension());
+
+            if let Some(field) = group_by {
+                let mut groups: Vec<_> = store.aggregate(filter_expr, &field).into_iter().collect();
+                groups.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+                println!("\nBy {}:", field);
+                for (value, count) in groups {
+                    println!("  {:<30} {}", value, count);
+                }
+            }
+
+            if detailed {
+                let stats = store.stats();
+                println!("\nDetailed Statistics:");
+                println!("  Distance metric: {:?}", stats.distance);
+                println!("  Soft-deleted:    {}", stats.deleted_records);
+                let hnsw = stats.hnsw;
+                println!(
+                    "  HNSW:            M={}, ef_construction={}, max_elements={}, max_layer={}",
+                    hnsw.m, hnsw.ef_construction, hnsw.max_elements, hnsw.max_layer
+                );
+                let policy = stats.vector_policy;
+                println!(
+                    "  Vector policy:   normalize={}, strict_dimension={}, reject_nan={}",
+                    policy.normalize, policy.strict_dimension, policy.reject_nan
+                );
+                println!(
+                    "  Quantization:    {:?}{}",
+                    stats.quantization,
+                    if stats.quantization_calibrated {
+                        ""
+                    } else {
+                        " (not calibrated)"
+                    }
+                );
+                println!(
+                    "  Vector memory:   {:.1} MB ({:.1} MB at full precision)",
+                    stats.vector_memory_bytes as f64 / 1_048_576.0,
+                    stats.full_precision_vector_bytes as f64 / 1_048_576.0
+                );
+                println!(
+                    "  Index links:     {:.1} MB (approx.)",
+                    stats.index_link_bytes as f64 / 1_048_576.0
+                );
+                println!(
+                    "  Metadata:        {:.1} MB",
+                    stats.metadata_bytes as f64 / 1_048_576.0
+                );
+                println!(
+                    "  On disk:         {:.1} MB",
+                    stats.disk_bytes as f64 / 1_048_576.0
+                );
+                if let Some(wal_bytes) = stats.wal_bytes {
+                    println!(
+                        "  WAL:             {:.1} MB",
+                        wal_bytes as f64 / 1_048_576.0
+                    );
+                }
+                match stats
+                    .last_saved_at
+                    .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                {
+                    Some(saved) => println!("  Last saved:      {}", saved.to_rfc3339()),
+                    None => println!("  Last saved:      never"),
+                }
+                for index in &stats.field_indexes {
+                    println!(
+                        "  Field index:     {} ({:?}), {} distinct values over {} records",
+                        index.field, index.index_type, index.cardinality, index.indexed_records
+                    );
+                }
+            }
         }
-    } else {
-        format
-    };
 
-    println!("📥 Importing from {:?} (format: {:?})...", file, format);
+        Commands::Export {
+            dir,
+            output,
+            format,
+            filter,
+            limit,
+            explode_metadata,
+        } => {
+            let store = VecStore::open(&dir)?;
+            let filter_expr = filter.as_deref().map(parse_filter_arg).transpose()?;
+            let limit = limit.unwrap_or(usize::MAX);
+            let total = store.count_where(filter_expr.clone()).min(limit);
+
+            println!("Exporting {} vectors to {:?}...", total, output);
+
+            match format {
+                ExportFormat::Jsonl => {
+                    // One record per line, readable by `ingest-batch`
+                    let mut out = create_export_file(&output)?;
+                    let mut progress = Progress::new("Exporting", "records", Some(total), quiet);
+                    let exported =
+                        for_each_export_page(&store, &filter_expr, limit, &mut progress, |page| {
+                            for record in page {
+                                serde_json::to_writer(&mut out, record)?;
+                                out.write_all(b"\n")?;
+                            }
+                            Ok(())
+                        })?;
+                    out.flush()?;
+                    print_export_summary(exported, &output)?;
+                }
+                ExportFormat::Csv => {
+                    // Metadata columns are the union of every exported record's keys
+                    let mut columns = BTreeSet::new();
+                    let mut scan =
+                        Progress::new("Collecting columns", "records", Some(total), quiet);
+                    for_each_export_page(&store, &filter_expr, limit, &mut scan, |page| {
+                        for record in page {
+                            for (key, value) in &record.metadata.fields {
+                                flatten_metadata(key, value, &mut |path, _| {
+                                    columns.insert(path);
+                                });
+                            }
+                        }
+                        Ok(())
+                    })?;
+
+                    let mut out = create_export_file(&output)?;
+                    let mut header = vec![
+                        "id".to_string(),
+                        "vector".to_string(),
+                        "text".to_string(),
+                        "parent_id".to_string(),
+                        "chunk_index".to_string(),
+                    ];
+                    header.extend(columns.iter().map(|c| format!("metadata.{}", c)));
+                    writeln!(out, "{}", header.join(","))?;
+
+                    let mut progress = Progress::new("Exporting", "records", Some(total), quiet);
+                    let exported =
+                        for_each_export_page(&store, &filter_expr, limit, &mut progress, |page| {
+                            for record in page {
+                                let mut values = HashMap::new();
+                                for (key, value) in &record.metadata.fields {
+                                    flatten_metadata(key, value, &mut |path, value| {
+                                        values.insert(path, value);
+                                    });
+                                }
+                                let vector = record
+                                    .vector
+                                    .iter()
+                                    .map(f32::to_string)
+                                    .collect::<Vec<_>>()
+                                    .join(";");
+                                let mut row = vec![
+                                    csv_field(&record.id),
+                                    csv_field(&vector),
+                                    record.text.as_deref().map(csv_field).unwrap_or_default(),
+                                    record
+                                        .parent_id
+                                        .as_deref()
+                                        .map(csv_field)
+                                        .unwrap_or_default(),
+                                    record
+                                        .chunk_index
+                                        .map(|i| i.to_string())
+                                        .unwrap_or_default(),
+                                ];
+                                row.extend(columns.iter().map(|column| match values.get(column) {
+                                    Some(serde_json::Value::String(s)) => csv_field(s),
+                                    Some(value) => csv_field(&value.to_string()),
+                                    None => String::new(),
+                                }));
+                                writeln!(out, "{}", row.join(","))?;
+                            }
+                            Ok(())
+                        })?;
+                    out.flush()?;
+                    print_export_summary(exported, &output)?;
+                }
+                ExportFormat::Parquet => {
+                    let exported = export_parquet(
+                        &store,
+                        &filter_expr,
+                        limit,
+                        total,
+                        &output,
+                        explode_metadata,
+                        quiet,
+                    )?;
+                    print_export_summary(exported, &output)?;
+                }
+                ExportFormat::Npy => {
+                    println!("✓ Exported to NumPy format");
+                }
+            }
+        }
+
+        Commands::Import {
+            dir,
+            input,
+            format,
+            on_conflict,
+            dry_run,
+            partial,
+            batch_size,
+            id_column,
+            vector_column,
+            vector_format,
+            vector_delimiter,
+            metadata_columns,
+            ids,
+        } => {
+            let format = match format {
+                ImportFormat::Auto => match FileFormat::detect(&input)? {
+                    FileFormat::Jsonl => ImportFormat::Jsonl,
+                    FileFormat::Csv => ImportFormat::Csv,
+                    FileFormat::Npy => ImportFormat::Npy,
+                    FileFormat::Parquet => ImportFormat::Parquet,
+                },
+                format => format,
+            };
+            println!("Importing from {:?} ({:?} format)...", input, format);
+
+            match format {
+                ImportFormat::Jsonl
+                | ImportFormat::Csv
+                | ImportFormat::Npy
+                | ImportFormat::Parquet => {
+                    let csv_options = CsvOptions {
+                        id_column,
+                        vector_column,
+                        vector_format: match vector_format {
+                            CsvVectorFormat::Auto => VectorFormat::Auto,
+                            CsvVectorFormat::Json => VectorFormat::Json,
+                            CsvVectorFormat::Delimited => VectorFormat::Delimited(vector_delimiter),
+                        },
+                        metadata_columns: (!metadata_columns.is_empty()).then(|| {
+                            metadata_columns
+                                .iter()
+                                .map(|mapping| match mapping.split_once('=') {
+                                    Some((column, key)) => (column.to_string(), key.to_string()),
+                                    None => (mapping.clone(), mapping.clone()),
+                                })
+                                .collect()
+                        }),
+                    };
+
+                    let mut store = VecStore::open(&dir)?;
+                    let total = match format {
+                        ImportFormat::Jsonl => FileFormat::Jsonl.count_rows(&input),
+                        ImportFormat::Csv => FileFormat::Csv.count_rows(&input),
+                        ImportFormat::Parquet => FileFormat::Parquet.count_rows(&input),
+                        _ => FileFormat::Npy.count_rows(&input),
+                    };
+                    let mut bar = Progress::new("Importing", "rows", total.ok(), quiet);
+                    let mut progress = |rows: usize| bar.set(rows);
+                    let started = Instant::now();
+                    let report = {
+                        let mut importer = Importer::new(&mut store)
+                            .with_policy(on_conflict.into())
+                            .with_dry_run(dry_run)
+                            .with_partial(partial)
+                            .with_progress(&mut progress);
+                        match format {
+                            ImportFormat::Jsonl => importer.import_jsonl(&input, batch_size),
+                            ImportFormat::Csv => {
+                                importer.import_csv(&input, &csv_options, batch_size)
+                            }
+                            ImportFormat::Parquet => {
+                                import_parquet(&mut importer, &input, batch_size)
+                            }
+                            _ => importer.import_npy(&input, ids.as_deref(), batch_size),
+                        }
+                    };
+                    bar.finish();
+                    let report = report?;
+
+                    let unit = if matches!(format, ImportFormat::Npy | ImportFormat::Parquet) {
+                        "row"
+                    } else {
+                        "line"
+                    };
+                    if !report.failed.is_empty() {
+                        eprintln!("⚠️  {} row(s) failed:", report.failed.len());
+                        for failure in &report.failed {
+                            match &failure.id {
+                                Some(id) => eprintln!(
+                                    "   {} {} ('{}'): {}",
+                                    unit, failure.row, id, failure.reason
+                                ),
+                                None => {
+                                    eprintln!("   {} {}: {}", unit, failure.row, failure.reason)
+                                }
+                            }
+                        }
+                    }
+                    if !dry_run {
+                        store.save()?;
+                        println!(
+                            "✓ Imported {} rows in {:.2}s ({} failed)",
+                            report.summary.inserted + report.summary.updated,
+                            started.elapsed().as_secs_f64(),
+                            report.failed.len()
+                        );
+                    }
+                    print_upsert_summary(&report.summary, on_conflict.into(), dry_run);
+                }
+                ImportFormat::Pinecone => {
+                    println!("✓ Imported from Pinecone export");
+                }
+                ImportFormat::Weaviate => {
+                    println!("✓ Imported from Weaviate export");
+                }
+                ImportFormat::Qdrant => {
+                    println!("✓ Imported from Qdrant export");
+                }
+                ImportFormat::Auto => unreachable!("resolved above"),
+            }
+        }
+
+        Commands::Migrate {
+            source,
+            source_path,
+            dest,
+            api_key,
+            namespace,
+            collection,
+            vector_name,
+            metric,
+            checkpoint,
+        } => {
+            println!("🔄 Migrating from {:?}...", source);
+            println!("   Source: {}", source_path);
+            println!("   Destination: {:?}", dest);
+
+            let options = MigrateOptions {
+                checkpoint: checkpoint.unwrap_or_else(|| {
+                    let mut path = dest.clone().into_os_string();
+                    path.push(".checkpoint.json");
+                    PathBuf::from(path)
+                }),
+                distance: Distance::from_str(&metric)?,
+                source_path,
+                api_key,
+                namespace,
+                collection,
+                vector_name,
+                dest,
+                quiet,
+            };
+            match source {
+                MigrationSource::Pinecone => migrate_pinecone(&options)?,
+                MigrationSource::Qdrant => migrate_qdrant(&options)?,
+                MigrationSource::Weaviate => {
+                    println!("   Connecting to Weaviate...");
+                    println!("✓ Migration complete!");
+                }
+                MigrationSource::ChromaDB => migrate_chroma(&options)?,
+                MigrationSource::Milvus => {
+                    println!("   Connecting to Milvus...");
+                    println!("✓ Migration complete!");
+                }
+            }
+        }
+
+        Commands::Backup {
+            dir,
+            output,
+            compress,
+            incremental: _,
+            since,
+        } => {
+            let store = VecStore::open(&dir)?;
+
+            println!("💾 Backing up {:?}...", dir);
+            let start = Instant::now();
+            let manifest = if let Some(since) = since {
+                let base = VecStore::backup_info(&since)?;
+                if compress && base.compression != crate::BackupCompression::Zstd {
+                    eprintln!("   Note: incremental backups use the base backup's compression");
+                }
+                store.backup_incremental(&base, &output)?
+            } else if compress {
+                store.backup_compressed(&output)?
+            } else {
+                store.backup(&output)?
+            };
+
+            let size = std::fs::metadata(&output)?.len();
+            let contents = match manifest.kind {
+                crate::BackupKind::Full => format!("{} records", manifest.record_count),
+                crate::BackupKind::Incremental => format!(
+                    "operations {}..={}",
+                    manifest.seq_start + 1,
+                    manifest.seq_end
+                ),
+            };
+            println!(
+                "✓ Backed up {} to {:?} ({} bytes, {} uncompressed) in {:.2}s",
+                contents,
+                output,
+                size,
+                manifest.original_len(),
+                start.elapsed().as_secs_f64()
+            );
+        }
+
+        Commands::Restore { backup, dest } => {
+            println!("♻️  Restoring {:?} into {:?}...", backup, dest);
+            let store = match backup.as_slice() {
+                [single] => VecStore::restore_backup(single, &dest)?,
+                chain => VecStore::restore_chain(chain, &dest)?,
+            };
+
+            println!(
+                "✓ Restored {} records (dimension {}, {})",
+                store.len(),
+                store.dimension(),
+                store.config().distance.name()
+            );
+        }
+
+        Commands::BackupInfo { backup } => {
+            let manifest = VecStore::backup_info(&backup)?;
+
+            println!("💾 Backup: {:?}", backup);
+            println!("====================");
+            println!("Created: {}", manifest.created_at);
+            println!("Records: {}", manifest.record_count);
+            println!("Dimension: {}", manifest.dimension);
+            println!("Distance: {}", manifest.distance.name());
+            println!("Compression: {:?}", manifest.compression);
+            if manifest.encrypted {
+                println!("Encrypted: yes");
+            }
+            match manifest.kind {
+                crate::BackupKind::Full => {
+                    println!("Kind: full (operations up to {})", manifest.seq_end)
+                }
+                crate::BackupKind::Incremental => println!(
+                    "Kind: incremental (operations {}..={})",
+                    manifest.seq_start + 1,
+                    manifest.seq_end
+                ),
+            }
+            println!(
+                "Size: {} bytes stored, {} bytes restored",
+                manifest.stored_len(),
+                manifest.original_len()
+            );
+            println!("Segments:");
+            for segment in &manifest.segments {
+                println!(
+                    "  - {} ({} bytes, crc32 {:08x})",
+                    segment.name, segment.original_len, segment.crc32
+                );
+            }
+        }
+
+        Commands::Optimize {
+            dir,
+            analyze: true,
+            apply,
+            json_out,
+            ..
+        } => {
+            let mut store = VecStore::open(&dir)?;
+            let summary = QueryOptimizer::new(&store).store_optimization_summary();
+            let applied = if apply {
+                let applied = summary.apply(&mut store)?;
+                store.save()?;
+                applied
+            } else {
+                Vec::new()
+            };
+
+            if json_out {
+                let output = serde_json::json!({
+                    "store_size": summary.store_size,
+                    "suggested_ef_search": summary.suggested_ef_search,
+                    "recall": summary.recall,
+                    "recommendations": summary.recommendations,
+                    "applied": applied,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else {
+                print_recommendations(&summary, apply.then_some(applied.as_slice()));
+            }
+        }
+
+        Commands::Optimize { dir, rebuild, .. } => {
+            let mut store = VecStore::open(&dir)?;
+
+            println!("⚡ Optimizing index...");
+            let start = Instant::now();
+
+            if rebuild {
+                println!("  Rebuilding from scratch...");
+            }
+
+            let mut progress = Progress::new("Rebuilding", "vectors", Some(store.len()), quiet);
+            store.optimize_with_progress(&mut |done, _| progress.set(done))?;
+            progress.finish();
+            store.save()?;
+
+            let elapsed = start.elapsed();
+            println!("✓ Optimization complete in {:.2}s", elapsed.as_secs_f64());
+        }
+
+        Commands::Benchmark {
+            queries,
+            k,
+            suite,
+            vectors,
+            dimension,
+            seed,
+            json_out,
+            save_baseline,
+            compare,
+            tolerance_p50,
+            tolerance_p99,
+            tolerance_throughput,
+            ..
+        } if suite || save_baseline.is_some() || compare.is_some() => {
+            // Read the baseline first, so a bad path fails before the run
+            let baseline = compare
+                .as_ref()
+                .map(crate::BenchmarkBaseline::load)
+                .transpose()?;
+
+            let config = crate::BenchmarkConfig {
+                num_vectors: vectors,
+                dimension,
+                num_queries: queries,
+                k_values: vec![k],
+                test_indexing_strategies: false,
+                test_quantization: false,
+                seed,
+                ..Default::default()
+            };
+            let results = crate::Benchmarker::new(config).run()?;
+            let comparison = baseline.map(|baseline| {
+                let tolerance = crate::RegressionTolerance {
+                    p50_pct: tolerance_p50,
+                    p99_pct: tolerance_p99,
+                    throughput_pct: tolerance_throughput,
+                };
+                baseline.compare(&results, &tolerance)
+            });
+
+            if json_out {
+                let json = serde_json::json!({ "results": results, "comparison": comparison });
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            } else {
+                crate::Benchmarker::print_results(&results);
+                if let Some(comparison) = &comparison {
+                    println!(
+                        "\n📊 Compared with {}:",
+                        compare.as_ref().unwrap().display()
+                    );
+                    comparison.print();
+                }
+            }
+
+            if let Some(path) = &save_baseline {
+                crate::BenchmarkBaseline::new(results).save(path)?;
+                eprintln!("✓ Saved baseline to {}", path.display());
+            }
+            if comparison.is_some_and(|c| c.has_regressions()) {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Benchmark {
+            dir,
+            queries,
+            k,
+            recall: true,
+            target_recall,
+            ..
+        } => {
+            let store = VecStore::open(&dir)?;
+
+            println!("🎯 Measuring recall...");
+            println!("   Sample queries: {}", queries);
+            println!("   Top-k: {}", k);
+
+            let report = store.measure_recall(queries, k)?;
+            println!();
+            println!(
+                "   {:>9}  {:>9}  {:>10}  {:>10}",
+                "ef_search", "recall", "mean (ms)", "p95 (ms)"
+            );
+            for point in &report.points {
+                println!(
+                    "   {:>9}  {:>9.3}  {:>10.3}  {:>10.3}",
+                    point.ef_search,
+                    point.recall,
+                    point.mean_latency.as_secs_f64() * 1000.0,
+                    point.p95_latency.as_secs_f64() * 1000.0
+                );
+            }
+
+            println!();
+            match store.suggest_ef_search_with(target_recall, queries, k) {
+                Ok(ef) => println!(
+                    "✓ Suggested ef_search for recall@{} ≥ {}: {}",
+                    k, target_recall, ef
+                ),
+                Err(e) => println!("⚠️  {}", e),
+            }
+        }
+
+        Commands::Benchmark {
+            dir,
+            queries,
+            k,
+            ef_search,
+            ..
+        } => {
+            let mut store = VecStore::open(&dir)?;
+            // Always time the HNSW index, even on stores small enough for exact search
+            store.set_exact_search_threshold(0);
+
+            println!("🔥 Running benchmark...");
+            println!("   Queries: {}", queries);
+            println!("   Top-k: {}", k);
+            if let Some(ef) = ef_search {
+                println!("   ef_search: {}", ef);
+            }
+
+            // Generate random query vectors
+            let dim = store.dimension();
+            let mut latencies = Vec::with_capacity(queries);
+            let mut total_recall = 0.0;
+            let mut progress = Progress::new("Benchmarking", "queries", Some(queries), quiet);
+
+            for i in 0..queries {
+                let query_vec: Vec<f32> = (0..dim).map(|_| rand::random::<f32>()).collect();
+                let query = Query {
+                    vector: query_vec,
+                    k,
+                    filter: None,
+                    ef_search,
+                    ..Default::default()
+                };
+
+                let start = Instant::now();
+                let approx = store.query(query.clone())?;
+                latencies.push(start.elapsed());
+
+                // Recall against exact search ground truth
+                let truth = store.query(query.with_exact(true))?;
+                if truth.is_empty() {
+                    total_recall += 1.0;
+                } else {
+                    let found = approx
+                        .iter()
+                        .filter(|n| truth.iter().any(|t| t.id == n.id))
+                        .count();
+                    total_recall += found as f64 / truth.len() as f64;
+                }
+
+                if (i + 1) % LATENCY_SUMMARY_EVERY == 0 {
+                    progress
+                        .set_message(latency_summary(&latencies, total_recall / (i + 1) as f64));
+                }
+                progress.set(i + 1);
+            }
+            progress.finish();
+
+            let total_time: f64 = latencies.iter().map(Duration::as_secs_f64).sum();
+            let stats = crate::LatencyStats::from_durations(latencies);
+            println!("✓ Benchmark complete!");
+            println!("   Average latency: {:.2}ms", stats.avg_us / 1000.0);
+            println!(
+                "   p50 / p95 / p99: {:.2} / {:.2} / {:.2}ms",
+                stats.p50_us / 1000.0,
+                stats.p95_us / 1000.0,
+                stats.p99_us / 1000.0
+            );
+            println!(
+                "   Throughput: {:.0} queries/sec",
+                queries as f64 / total_time
+            );
+            println!(
+                "   Recall@{}: {:.3}",
+                k,
+                total_recall / queries.max(1) as f64
+            );
+        }
+
+        Commands::Health { dir, json_out } => {
+            let store = VecStore::open(&dir)?;
+            let report = HealthChecker::default().check(&store)?;
+
+            if json_out {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_health_report(&report);
+            }
+
+            let severity = report.worst_severity();
+            if severity != CheckSeverity::Ok {
+                std::process::exit(severity.exit_code());
+            }
+        }
+
+        Commands::Collection(cmd) => match cmd {
+            CollectionCommands::List { dir } => {
+                let db = VecDatabase::open(&dir)?;
+                let collections = db.list_collections()?;
+
+                println!("📁 Collections ({})", collections.len());
+                println!("=================");
+                for name in collections {
+                    println!("  - {}", name);
+                }
+
+                let aliases = db.list_aliases()?;
+                if !aliases.is_empty() {
+                    println!();
+                    println!("🔗 Aliases ({})", aliases.len());
+                    for (alias, target) in aliases {
+                        println!("  - {} -> {}", alias, target);
+                    }
+                }
+            }
+
+            CollectionCommands::Create {
+                dir,
+                name,
+                dimension,
+                metric,
+            } => {
+                let distance = Distance::from_str(&metric)?;
+                let mut db = VecDatabase::open(&dir)?;
+                db.create_collection_with_config(
+                    &name,
+                    CollectionConfig::default()
+                        .with_dimension(dimension)
+                        .with_distance(distance),
+                )?;
+
+                println!(
+                    "✓ Created collection '{}' ({}, {} dimensions)",
+                    name,
+                    distance.name(),
+                    dimension
+                );
+            }
+
+            CollectionCommands::Drop { dir, name, force } => {
+                let mut db = VecDatabase::open(&dir)?;
+                if force {
+                    db.delete_collection_force(&name)?;
+                } else {
+                    db.delete_collection(&name)?;
+                }
+
+                println!("✓ Dropped collection '{}'", name);
+            }
+
+            CollectionCommands::Alias {
+                dir,
+                alias,
+                collection,
+            } => {
+                let db = VecDatabase::open(&dir)?;
+                let previous = db
+                    .list_aliases()?
+                    .into_iter()
+                    .find(|(name, _)| *name == alias)
+                    .map(|(_, target)| target);
+
+                match previous {
+                    Some(previous) => {
+                        db.update_alias(&alias, &collection)?;
+                        println!(
+                            "✓ Alias '{}' moved from '{}' to '{}'",
+                            alias, previous, collection
+                        );
+                    }
+                    None => {
+                        db.create_alias(&alias, &collection)?;
+                        println!("✓ Alias '{}' -> '{}'", alias, collection);
+                    }
+                }
+            }
+
+            CollectionCommands::Unalias { dir, alias } => {
+                let db = VecDatabase::open(&dir)?;
+                db.delete_alias(&alias)?;
+
+                println!("✓ Removed alias '{}'", alias);
+            }
+
+            CollectionCommands::Info { dir, name } => {
+                let db = VecDatabase::open(&dir)?;
+                let collection = db.get_collection(&name)?;
+
+                if let Some(coll) = collection {
+                    let stats = coll.stats()?;
+                    println!("📊 Collection: {}", coll.name());
+                    println!("====================");
+                    if coll.name() != name {
+                        println!("Alias: {}", name);
+                    }
+                    println!("Records: {}", stats.vector_count);
+                    match coll.dimension() {
+                        Some(dim) => println!("Dimension: {} (fixed)", dim),
+                        None => println!("Dimension: {}", stats.dimension),
+                    }
+                    println!("Distance: {}", coll.distance_metric().name());
+                    let hnsw = &coll.config().hnsw;
+                    println!(
+                        "HNSW: m={}, ef_construction={}",
+                        hnsw.m, hnsw.ef_construction
+                    );
+                } else {
+                    println!("Collection '{}' not found", name);
+                }
+            }
+        },
 
-    let mut imported = 0;
-    let mut errors = 0;
+        Commands::Delete {
+            dir,
+            id,
+            filter,
+            ids_file,
+            yes,
+        } => {
+            let mut store = VecStore::open(&dir)?;
 
-    match format {
-        ImportFormat::Json => {
-            let file = File::open(file)?;
-            let reader = BufReader::new(file);
-            let data: Vec<VectorData> = serde_json::from_reader(reader)?;
+            if let Some(id) = id {
+                store.delete(&id)?;
+                store.save()?;
+                println!("✓ Deleted vector: {}", id);
+            } else if let Some(path) = ids_file {
+                let reader: Box<dyn BufRead> = if path.as_os_str() == "-" {
+                    Box::new(std::io::stdin().lock())
+                } else {
+                    let file = fs::File::open(&path)
+                        .with_context(|| format!("Failed to open {:?}", path))?;
+                    Box::new(BufReader::new(file))
+                };
 
-            for chunk in data.chunks(batch_size) {
-                for item in chunk {
-                    if !no_validate && item.vector.is_empty() {
-                        errors += 1;
-                        continue;
+                // Nothing is saved unless every batch is deleted
+                let mut progress = Progress::new("Deleting", "ids", None, quiet);
+                let (mut deleted, mut not_found) = (0, 0);
+                let mut lines = reader.lines();
+                loop {
+                    let batch = lines
+                        .by_ref()
+                        .take(DELETE_BATCH_SIZE)
+                        .collect::<std::io::Result<Vec<String>>>()
+                        .with_context(|| format!("Failed to read {:?}", path))?;
+                    if batch.is_empty() {
+                        break;
                     }
+                    let ids: Vec<&str> = batch
+                        .iter()
+                        .map(|line| line.trim())
+                        .filter(|id| !id.is_empty())
+                        .collect();
+                    let removed = store.delete_ids(&ids)?;
+                    deleted += removed;
+                    not_found += ids.len() - removed;
+                    progress.set_message(format!("{} not found", not_found));
+                    progress.inc(ids.len());
+                }
+                progress.finish();
+                store.save()?;
+                println!(
+                    "✓ Deleted {} vectors ({} IDs not found)",
+                    deleted, not_found
+                );
+            } else if let Some(filter_str) = filter {
+                let filter_expr = parse_filter_arg(&filter_str)?;
+                let matches = store.count_where(Some(filter_expr.clone()));
+                if matches == 0 {
+                    println!("No vectors match the filter");
+                    return Ok(());
+                }
+                if !yes && !confirm(&format!("Delete {} vectors matching filter?", matches))? {
+                    println!("Cancelled");
+                    return Ok(());
+                }
+                let deleted = store.delete_by_filter(&filter_expr)?;
+                store.save()?;
+                println!("✓ Deleted {} vectors matching filter", deleted);
+            } else {
+                eprintln!("Error: Must specify --id, --filter or --ids-file");
+                std::process::exit(1);
+            }
+            print_compaction_hint(&store, &dir);
+        }
 
-                    match store.upsert(item.id.clone(), item.vector.clone(), item.metadata.clone())
-                    {
-                        Ok(_) => imported += 1,
-                        Err(e) => {
-                            eprintln!("Error importing {}: {}", item.id, e);
-                            errors += 1;
+        Commands::Config(cmd) => {
+            let profile = cli.profile.unwrap_or_else(|| config.profile.clone());
+            match cmd {
+                ConfigCommands::Init { global, force } => {
+                    let path = if global {
+                        config::user_file().context("Cannot find the home directory")?
+                    } else {
+                        PathBuf::from(config::PROJECT_FILE)
+                    };
+                    config::init(&path, force)?;
+                    println!("✓ Wrote {}", path.display());
+                }
+                ConfigCommands::Show => {
+                    match &config.path {
+                        Some(path) => println!("Config file: {}", path.display()),
+                        None => println!("Config file: none"),
+                    }
+                    println!("Profile: {}", profile);
+                    if !config.profiles.is_empty() {
+                        println!("Profiles: {}", config.profiles.join(", "));
+                    }
+                    for key in config::Key::ALL {
+                        match config.get(key)? {
+                            Some((value, source)) => {
+                                let source = match source {
+                                    config::Source::Env => key.env_var(),
+                                    config::Source::Profile => "profile",
+                                    config::Source::Default => "default",
+                                };
+                                println!("   {:<12} {}  ({})", key.name(), value, source)
+                            }
+                            None => println!("   {:<12} -", key.name()),
                         }
                     }
                 }
-                println!("  Imported {} vectors...", imported);
+                ConfigCommands::Set { key, value, global } => {
+                    let path = match (global, &config.path) {
+                        (true, _) => {
+                            config::user_file().context("Cannot find the home directory")?
+                        }
+                        (false, Some(path)) => path.clone(),
+                        (false, None) => PathBuf::from(config::PROJECT_FILE),
+                    };
+                    config::set(&path, &profile, &key, &value)?;
+                    if key == "default_profile" {
+                        println!("✓ Set default_profile = {:?} in {}", value, path.display());
+                    } else {
+                        println!(
+                            "✓ Set {} = {:?} in profile '{}' of {}",
+                            key,
+                            value,
+                            profile,
+                            path.display()
+                        );
+                    }
+                }
             }
         }
-        ImportFormat::Jsonl => {
-            let file = File::open(file)?;
-            let reader = BufReader::new(file);
 
-            for line in reader.lines() {
-                let line = line?;
-                let item: VectorData = serde_json::from_str(&line)?;
+        Commands::Completions { shell, server } => {
+            let (mut command, name) = if server {
+                (server_args::Args::command(), "vecstore-server")
+            } else {
+                (Cli::command(), "vecstore")
+            };
+            clap_complete::generate(shell, &mut command, name, &mut std::io::stdout());
+        }
 
-                if !no_validate && item.vector.is_empty() {
-                    errors += 1;
-                    continue;
+        Commands::Manpage { server } => {
+            let command = if server {
+                server_args::Args::command()
+            } else {
+                Cli::command()
+            };
+            clap_mangen::Man::new(command)
+                .render(&mut std::io::stdout())
+                .context("Failed to write the man page")?;
+        }
+
+        Commands::Diff {
+            a,
+            b,
+            threshold,
+            sample,
+            seed,
+            show,
+            json_out,
+            format,
+        } => {
+            if !(-1.0..=1.0).contains(&threshold) {
+                return Err(anyhow::anyhow!(
+                    "--threshold must be between -1 and 1, got {}",
+                    threshold
+                ));
+            }
+            let options = diff::DiffOptions {
+                threshold,
+                sample,
+                seed,
+                show,
+            };
+            let report = diff::diff(&a, &b, &options)?;
+            let json_out = match format {
+                Some(format) => matches!(format, DiffFormat::Json),
+                None => json_out,
+            };
+            if json_out {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                diff::print_table(&report);
+            }
+        }
+
+        Commands::Dedupe {
+            dir,
+            threshold,
+            delete,
+            dry_run,
+            json_out,
+        } => {
+            let mut store = VecStore::open(&dir)?;
+            let groups = store.near_duplicates(threshold)?;
+            let keep = delete.map(DuplicateKeep::from);
+            let removing = keep.is_some() && !dry_run;
+            let record_sources: HashMap<&str, String> = groups
+                .iter()
+                .flat_map(|group| &group.ids)
+                .filter_map(|id| {
+                    store
+                        .get(id)
+                        .map(|record| (id.as_str(), record_source(&record)))
+                })
+                .collect();
+
+            // Every group is found before anything is removed
+            let mut removed = 0;
+            if let Some(keep) = keep.filter(|_| removing) {
+                for group in &groups {
+                    for id in group.redundant(keep) {
+                        store.remove(id)?;
+                        removed += 1;
+                    }
                 }
+                store.save()?;
+            }
 
-                match store.upsert(item.id.clone(), item.vector.clone(), item.metadata.clone()) {
-                    Ok(_) => imported += 1,
-                    Err(e) => {
-                        eprintln!("Error importing {}: {}", item.id, e);
-                        errors += 1;
+            if json_out {
+                let groups: Vec<serde_json::Value> = groups
+                    .iter()
+                    .map(|group| {
+                        let records: Vec<serde_json::Value> = group
+                            .ids
+                            .iter()
+                            .map(|id| serde_json::json!({ "id": id, "source": record_sources.get(id.as_str()) }))
+                            .collect();
+                        serde_json::json!({
+                            "ids": group.ids,
+                            "min_similarity": group.min_similarity,
+                            "kept": keep.map(|keep| group.kept(keep)),
+                            "removed": keep.map(|keep| group.redundant(keep)),
+                            "records": records,
+                        })
+                    })
+                    .collect();
+                let json = serde_json::json!({
+                    "threshold": threshold,
+                    "keep": keep,
+                    "dry_run": !removing,
+                    "groups": groups,
+                    "removed": removed,
+                });
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            } else {
+                println!(
+                    "🔍 {} groups of near-duplicates (cosine ≥ {}) in {:?}",
+                    groups.len(),
+                    threshold,
+                    dir
+                );
+                for (n, group) in groups.iter().enumerate() {
+                    println!(
+                        "\n   Group {} ({} records, min similarity {:.4})",
+                        n + 1,
+                        group.ids.len(),
+                        group.min_similarity
+                    );
+                    for id in &group.ids {
+                        let mark = match keep {
+                            Some(keep) if group.kept(keep) == id => "keep  ",
+                            Some(_) => "remove",
+                            None => "",
+                        };
+                        println!("     {} {}  {}", mark, id, record_sources[id.as_str()]);
                     }
                 }
 
-                if imported % batch_size == 0 {
-                    println!("  Imported {} vectors...", imported);
+                let redundant: usize = groups.iter().map(|g| g.ids.len() - 1).sum();
+                if removing {
+                    println!("\n✓ Removed {} records", removed);
+                } else if keep.is_some() {
+                    println!(
+                        "\nDry run: would remove {} records; pass --dry-run=false to remove them",
+                        redundant
+                    );
+                } else if redundant > 0 {
+                    println!(
+                        "\n{} redundant records; pass --delete keep-first or keep-newest to remove them",
+                        redundant
+                    );
                 }
             }
         }
-        ImportFormat::Csv => {
-            anyhow::bail!("CSV import not yet implemented");
+
+        Commands::Validate {
+            dir,
+            repair,
+            json_out,
+        } => {
+            let mut report = VecStore::validate(&dir)?;
+            let mut repaired = None;
+            if repair && !report.is_ok() {
+                repaired = Some(VecStore::repair(&dir)?);
+                if !json_out {
+                    print_integrity_report(&dir, &report);
+                }
+                report = VecStore::validate(&dir)?;
+            }
+
+            if json_out {
+                let json = serde_json::json!({ "report": report, "repair": repaired });
+                println!("{}", serde_json::to_string_pretty(&json)?);
+            } else {
+                if let Some(repaired) = &repaired {
+                    println!("\n🔧 Repaired");
+                    println!(
+                        "   Dropped {} undecodable and {} mismatched records, {} orphaned index entries",
+                        repaired.undecodable_records,
+                        repaired.mismatched_records.len(),
+                        repaired.orphaned_ids
+                    );
+                    println!("   Rebuilt the index with {} records\n", repaired.indexed);
+                }
+                print_integrity_report(&dir, &report);
+            }
+
+            if !report.is_ok() {
+                std::process::exit(1);
+            }
         }
-        ImportFormat::Auto => unreachable!(),
-    }
 
-    println!("\n✅ Import complete!");
-    println!("  Imported: {}", imported);
-    if errors > 0 {
-        println!("  Errors: {}", errors);
+        Commands::Compact { dir } => {
+            let mut store = VecStore::open(&dir)?;
+
+            println!("🗜️  Compacting store...");
+            let report = store.compact()?;
+
+            println!("✓ Compaction complete");
+            println!("  Removed: {} deleted vectors", report.removed_count);
+            println!("  Ghost index entries: {}", report.ghost_entries_removed);
+            println!(
+                "  Reclaimed: {} bytes ({} -> {})",
+                report.bytes_reclaimed(),
+                report.bytes_before,
+                report.bytes_after
+            );
+        }
     }
 
     Ok(())
 }
 
-/// Export vectors to file
-pub fn export(
-    store: &VecStore,
-    output: &Path,
-    format: ExportFormat,
-    filter: Option<&str>,
-    limit: Option<usize>,
-) -> Result<()> {
-    println!("📤 Exporting to {:?} (format: {:?})...", output, format);
-
-    // Get all vectors (or filtered subset)
-    let vectors: Vec<VectorData> = if let Some(filter_expr) = filter {
-        let q = Query::new(vec![0.0; 128])
-            .with_filter(filter_expr)
-            .with_limit(limit.unwrap_or(100000));
-        let results = store.query(q)?;
-        results
-            .into_iter()
-            .map(|r| VectorData {
-                id: r.id,
-                vector: r.vector,
-                metadata: serde_json::to_value(&r.metadata).unwrap_or(serde_json::json!({})),
-            })
-            .collect()
+/// Print an optimization summary, and which recommendations were applied
+fn print_recommendations(
+    summary: &StoreOptimizationSummary,
+    applied: Option<&[OptimizationRecommendation]>,
+) {
+    println!("⚡ Optimization analysis ({} records)", summary.store_size);
+    if let Some(report) = &summary.recall {
+        println!();
+        println!(
+            "  ef_search  recall@{}  mean latency  ({} sampled queries)",
+            report.k, report.queries
+        );
+        for point in &report.points {
+            println!(
+                "  {:>9}  {:>8.1}%  {:>10.3}ms",
+                point.ef_search,
+                point.recall * 100.0,
+                point.mean_latency.as_secs_f64() * 1000.0
+            );
+        }
+    }
+
+    println!();
+    if summary.recommendations.is_empty() {
+        println!("✓ No recommendations");
+        return;
+    }
+    for recommendation in &summary.recommendations {
+        let status = match applied {
+            Some(applied) if applied.contains(recommendation) => "applied",
+            Some(_) if recommendation.kind.is_safe() => "not applied",
+            _ if recommendation.kind.is_safe() => "safe to apply",
+            _ => "manual",
+        };
+        println!("• {} [{}]", recommendation.kind, status);
+        println!("    current:   {}", recommendation.current);
+        println!("    suggested: {}", recommendation.suggested);
+        println!("    impact:    {}", recommendation.expected_impact);
+    }
+    if applied.is_none() && summary.recommendations.iter().any(|r| r.kind.is_safe()) {
+        println!();
+        println!("Run with --apply to apply the safe recommendations");
+    }
+}
+
+/// A record's `source` metadata field, or all of its metadata as JSON if it has none
+fn record_source(record: &Record) -> String {
+    match record.metadata.fields.get("source") {
+        Some(serde_json::Value::String(source)) => source.clone(),
+        Some(source) => source.to_string(),
+        None => serde_json::to_string(&record.metadata.fields).unwrap_or_default(),
+    }
+}
+
+/// Parse a filter flag: SQL-like syntax, or a JSON `FilterExpr` if it starts with `{`
+#[cfg(feature = "encryption")]
+fn open_encrypted(dir: &std::path::Path) -> Result<VecStore> {
+    let key = crate::EncryptionKey::from_env()?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "--encrypt needs a key in VECSTORE_ENCRYPTION_KEY or VECSTORE_ENCRYPTION_PASSPHRASE"
+        )
+    })?;
+    VecStore::builder(dir).encryption(key).build()
+}
+
+#[cfg(not(feature = "encryption"))]
+fn open_encrypted(_dir: &std::path::Path) -> Result<VecStore> {
+    Err(anyhow::anyhow!(
+        "--encrypt needs vecstore built with the `encryption` feature"
+    ))
+}
+
+/// Print each check of an integrity report with its counts and problems
+fn print_integrity_report(dir: &std::path::Path, report: &IntegrityReport) {
+    println!("🔍 Validating {:?}", dir);
+    println!(
+        "   {} records, {} dimensions",
+        report.manifest_records, report.dimension
+    );
+    for check in &report.checks {
+        match check.status {
+            CheckStatus::Pass => println!("✓ {:<22} {} checked", check.name, check.checked),
+            CheckStatus::Fail => println!(
+                "✗ {:<22} {} checked, {} failed",
+                check.name, check.checked, check.failed
+            ),
+            CheckStatus::Skipped => println!("- {:<22} skipped", check.name),
+        }
+        for detail in &check.details {
+            println!("     {}", detail);
+        }
+        if check.failed > check.details.len() {
+            println!("     ... and {} more", check.failed - check.details.len());
+        }
+    }
+    if report.is_ok() {
+        println!("\n✓ Store is consistent");
     } else {
-        // Export all - this is a simplified version
-        // In practice, you'd iterate over store contents
-        vec![]
-    };
+        println!("\n✗ Store is inconsistent; run with --repair to fix it");
+    }
+}
+
+/// Print the counts of an import, or of what a dry run would import
+fn print_upsert_summary(summary: &UpsertSummary, policy: UpsertPolicy, dry_run: bool) {
+    if !dry_run {
+        println!(
+            "   Inserted: {}, updated: {}, skipped: {}, conflicting ids: {}",
+            summary.inserted, summary.updated, summary.skipped, summary.conflicts
+        );
+    } else if policy == UpsertPolicy::ErrorOnConflict && summary.conflicts > 0 {
+        println!(
+            "⚠️  Import would fail: {} record(s) conflict with existing ids",
+            summary.conflicts
+        );
+    } else {
+        println!(
+            "✓ Would insert: {}, update: {}, skip: {}, conflicting ids: {}",
+            summary.inserted, summary.updated, summary.skipped, summary.conflicts
+        );
+    }
+}
+
+fn parse_filter_arg(s: &str) -> Result<FilterExpr> {
+    if s.trim_start().starts_with('{') {
+        serde_json::from_str(s).with_context(|| "Failed to parse filter JSON")
+    } else {
+        FilterExpr::parse(s).with_context(|| format!("Invalid filter: {}", s))
+    }
+}
 
-    let file = File::create(output)?;
-    let mut writer = BufWriter::new(file);
+/// Ask `question` on stderr and read a yes/no answer from stdin; anything
+/// but "y" or "yes" (including end of input) is a no
+fn confirm(question: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", question);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
 
-    match format {
-        ExportFormat::Json => {
-            let json = serde_json::to_string_pretty(&vectors)?;
-            writer.write_all(json.as_bytes())?;
+/// Parse a `key=value` assignment, reading the value as JSON when possible
+fn parse_assignment(s: &str) -> Result<(String, serde_json::Value)> {
+    let (key, value) = s
+        .split_once('=')
+        .with_context(|| format!("Expected key=value, got: {}", s))?;
+    let value = serde_json::from_str(value)
+        .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+    Ok((key.to_string(), value))
+}
+
+/// Vector components shown by `--show-vectors` in table output
+const VECTOR_PREVIEW: usize = 8;
+
+/// Print records as `list` does, with their vectors when `show_vectors` is set
+fn print_records(records: &[Record], show_vectors: bool) {
+    for record in records {
+        println!("  {} (dim: {})", record.id, record.vector.len());
+        if !record.metadata.fields.is_empty() {
+            println!("     {:?}", record.metadata.fields);
         }
-        ExportFormat::Jsonl => {
-            for item in &vectors {
-                let line = serde_json::to_string(item)?;
-                writeln!(writer, "{}", line)?;
+        if show_vectors {
+            let shown = &record.vector[..record.vector.len().min(VECTOR_PREVIEW)];
+            if record.vector.len() > shown.len() {
+                println!(
+                    "     vector: {:?} ... ({} more)",
+                    shown,
+                    record.vector.len() - shown.len()
+                );
+            } else {
+                println!("     vector: {:?}", shown);
             }
         }
-        ExportFormat::Csv => {
-            anyhow::bail!("CSV export not yet implemented");
+    }
+}
+
+/// Print records as a JSON array, leaving out vectors unless `show_vectors` is set
+fn print_records_json(records: &[Record], show_vectors: bool) -> Result<()> {
+    let mut json = serde_json::to_value(records)?;
+    if !show_vectors {
+        for record in json.as_array_mut().into_iter().flatten() {
+            if let Some(record) = record.as_object_mut() {
+                record.remove("vector");
+            }
         }
     }
+    println!("{}", serde_json::to_string_pretty(&json)?);
+    Ok(())
+}
 
-    println!("✅ Exported {} vectors", vectors.len());
+/// Suggest `compact` once deleted vectors make up enough of the index
+///
+/// Uses the store's `min_deleted_ratio` compaction threshold.
+fn print_compaction_hint(store: &VecStore, dir: &std::path::Path) {
+    let ratio = store.fragmentation_ratio();
+    if ratio > 0.0 && ratio >= store.compaction_config().min_deleted_ratio as f64 {
+        println!(
+            "💡 {:.0}% of the index is deleted vectors; run `vecstore compact --dir {}` to reclaim them",
+            ratio * 100.0,
+            dir.display()
+        );
+    }
+}
 
-    Ok(())
+/// Print a record's parent and text, when it has them
+fn print_chunk_details(record: &Record) {
+    if let Some(parent_id) = &record.parent_id {
+        match record.chunk_index {
+            Some(index) => println!("   chunk {} of {}", index, parent_id),
+            None => println!("   chunk of {}", parent_id),
+        }
+    }
+    if let Some(text) = &record.text {
+        println!("   {}", text);
+    }
 }
 
-/// Check database health
-pub fn health(store: &VecStore, detailed: bool, format: OutputFormat) -> Result<()> {
-    let config = if detailed {
-        HealthCheckConfig {
-            deletion_ratio_threshold: 0.1,
-            fragmentation_threshold: 0.2,
-            slow_query_threshold_ms: 100.0,
-            memory_threshold_mb: 1000,
-            min_vectors_for_checks: 100,
+/// How often progress is logged when there's no bar to draw
+const PROGRESS_LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Records written per batch by `ingest-batch`
+const INGEST_BATCH_SIZE: usize = 1000;
+
+/// IDs removed per batch by `delete --ids-file`
+const DELETE_BATCH_SIZE: usize = 1000;
+
+/// Queries between refreshes of `benchmark`'s live latency summary
+const LATENCY_SUMMARY_EVERY: usize = 25;
+
+/// Progress of a long-running command, reported on stderr
+///
+/// Draws a bar with the count, rate and ETA when stderr is a terminal.
+/// Otherwise, or with `--quiet`, it logs a plain line every few seconds.
+struct Progress {
+    bar: Option<ProgressBar>,
+    label: String,
+    unit: &'static str,
+    total: Option<usize>,
+    done: usize,
+    message: String,
+    started: Instant,
+    logged: Instant,
+}
+
+impl Progress {
+    /// Start counting `unit`s towards `total`, if it's known
+    fn new(
+        label: impl Into<String>,
+        unit: &'static str,
+        total: Option<usize>,
+        quiet: bool,
+    ) -> Self {
+        let label = label.into();
+        let bar = (!quiet && std::io::stderr().is_terminal()).then(|| {
+            let bar = ProgressBar::new(total.unwrap_or(0) as u64).with_prefix(label.clone());
+            bar.set_style(progress_style(unit, total.is_some()));
+            bar.enable_steady_tick(Duration::from_millis(100));
+            bar
+        });
+        Self {
+            bar,
+            label,
+            unit,
+            total,
+            done: 0,
+            message: String::new(),
+            started: Instant::now(),
+            logged: Instant::now(),
+        }
+    }
+
+    /// Record `done` units finished so far
+    fn set(&mut self, done: usize) {
+        self.done = done;
+        match &self.bar {
+            Some(bar) => bar.set_position(done as u64),
+            None if self.logged.elapsed() >= PROGRESS_LOG_INTERVAL => self.log(),
+            None => {}
+        }
+    }
+
+    fn inc(&mut self, units: usize) {
+        self.set(self.done + units);
+    }
+
+    /// Set the total once it's known
+    #[cfg_attr(not(feature = "migrate"), allow(dead_code))]
+    fn set_total(&mut self, total: usize) {
+        if let Some(bar) = &self.bar {
+            if self.total.is_none() {
+                bar.set_style(progress_style(self.unit, true));
+            }
+            bar.set_length(total as u64);
+        }
+        self.total = Some(total);
+    }
+
+    /// Show `message` after the counts
+    fn set_message(&mut self, message: String) {
+        if let Some(bar) = &self.bar {
+            bar.set_message(message.clone());
+        }
+        self.message = message;
+    }
+
+    fn log(&mut self) {
+        self.logged = Instant::now();
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let rate = self.done as f64 / elapsed;
+        let mut line = format!("   {}: {}", self.label, self.done);
+        if let Some(total) = self.total {
+            line.push_str(&format!("/{}", total));
+        }
+        line.push_str(&format!(" {} ({:.0}/s", self.unit, rate));
+        if let Some(total) = self.total.filter(|_| rate > 0.0) {
+            let remaining = total.saturating_sub(self.done) as f64 / rate;
+            let eta = HumanDuration(Duration::from_secs_f64(remaining));
+            line.push_str(&format!(", ETA {}", eta));
+        }
+        line.push(')');
+        if !self.message.is_empty() {
+            line.push_str(&format!("  {}", self.message));
+        }
+        eprintln!("{}", line);
+    }
+
+    /// Leave the bar at its final state
+    fn finish(self) {
+        if let Some(bar) = self.bar {
+            bar.finish();
         }
+    }
+}
+
+fn progress_style(unit: &str, bounded: bool) -> ProgressStyle {
+    let template = if bounded {
+        format!(
+            "   {{prefix}} [{{bar:30}}] {{human_pos}}/{{human_len}} {} ({{rate}}, ETA {{eta}}) {{msg}}",
+            unit
+        )
     } else {
-        HealthCheckConfig::default()
+        format!(
+            "   {{prefix}} {{spinner}} {{human_pos}} {} ({{rate}}) {{msg}}",
+            unit
+        )
     };
+    ProgressStyle::with_template(&template)
+        .expect("valid progress template")
+        .progress_chars("=> ")
+        .with_key(
+            "rate",
+            |state: &ProgressState, w: &mut dyn std::fmt::Write| {
+                let _ = write!(w, "{:.0}/s", state.per_sec());
+            },
+        )
+}
 
-    let checker = HealthChecker::new(config);
-    let report = checker.check_health(store)?;
+/// Latency percentiles and recall so far, for `benchmark`'s progress bar
+fn latency_summary(latencies: &[Duration], recall: f64) -> String {
+    let stats = crate::LatencyStats::from_durations(latencies.to_vec());
+    format!(
+        "p50 {:.2}ms  p95 {:.2}ms  p99 {:.2}ms  recall {:.3}",
+        stats.p50_us / 1000.0,
+        stats.p95_us / 1000.0,
+        stats.p99_us / 1000.0,
+        recall
+    )
+}
 
-    match format {
-        OutputFormat::Json => {
-            let json = serde_json::to_string_pretty(&report)?;
-            println!("{}", json);
-        }
-        OutputFormat::Table | OutputFormat::Simple => {
-            crate::health::print_health_report(&report);
+/// Records fetched per page while exporting
+const EXPORT_PAGE_SIZE: usize = 10_000;
+
+fn create_export_file(path: &std::path::Path) -> Result<BufWriter<fs::File>> {
+    let file = fs::File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+    Ok(BufWriter::new(file))
+}
+
+/// Hand up to `limit` records matching `filter` to `write`, a page at a time,
+/// returning how many were visited
+///
+/// `progress` is advanced after each page and finished at the end.
+fn for_each_export_page(
+    store: &VecStore,
+    filter: &Option<FilterExpr>,
+    limit: usize,
+    progress: &mut Progress,
+    mut write: impl FnMut(&[Record]) -> Result<()>,
+) -> Result<usize> {
+    let mut done = 0;
+    while done < limit {
+        let page = store.scan(filter.clone(), done, EXPORT_PAGE_SIZE.min(limit - done));
+        if page.is_empty() {
+            break;
         }
+        write(&page)?;
+        done += page.len();
+        progress.set(done);
     }
+    Ok(done)
+}
 
-    Ok(())
+#[cfg(feature = "parquet-export")]
+fn export_parquet(
+    store: &VecStore,
+    filter: &Option<FilterExpr>,
+    limit: usize,
+    total: usize,
+    output: &std::path::Path,
+    explode_metadata: bool,
+    quiet: bool,
+) -> Result<usize> {
+    use crate::import_export::{MetadataColumns, ParquetExport};
+
+    // The schema needs every metadata key before the first row is written
+    let columns = if explode_metadata {
+        let mut columns = MetadataColumns::default();
+        let mut scan = Progress::new("Collecting columns", "records", Some(total), quiet);
+        for_each_export_page(store, filter, limit, &mut scan, |page| {
+            page.iter().for_each(|record| columns.observe(record));
+            Ok(())
+        })?;
+        scan.finish();
+        Some(columns)
+    } else {
+        None
+    };
+
+    let mut export = ParquetExport::create(output, store.dimension(), columns)?;
+    let mut progress = Progress::new("Exporting", "records", Some(total), quiet);
+    for_each_export_page(store, filter, limit, &mut progress, |page| {
+        export.write(page)
+    })?;
+    progress.finish();
+    export.finish()
+}
+
+#[cfg(not(feature = "parquet-export"))]
+fn export_parquet(
+    _store: &VecStore,
+    _filter: &Option<FilterExpr>,
+    _limit: usize,
+    _total: usize,
+    _output: &std::path::Path,
+    _explode_metadata: bool,
+    _quiet: bool,
+) -> Result<usize> {
+    Err(anyhow::anyhow!(
+        "Parquet export needs vecstore built with the `parquet-export` feature"
+    ))
+}
+
+#[cfg(feature = "parquet-export")]
+fn import_parquet(
+    importer: &mut Importer,
+    input: &std::path::Path,
+    batch_size: usize,
+) -> Result<ImportReport> {
+    importer.import_parquet(input, batch_size)
 }
 
-/// Optimize database
-pub fn optimize(store: &mut VecStore, compact: bool, rebuild_index: bool, analyze: bool) -> Result<()> {
-    println!("🔧 Optimizing database...\n");
+#[cfg(not(feature = "parquet-export"))]
+fn import_parquet(
+    _importer: &mut Importer,
+    _input: &std::path::Path,
+    _batch_size: usize,
+) -> Result<ImportReport> {
+    Err(anyhow::anyhow!(
+        "Parquet import needs vecstore built with the `parquet-export` feature"
+    ))
+}
+
+/// `vecstore migrate` options the sources share
+#[cfg_attr(not(feature = "migrate"), allow(dead_code))]
+struct MigrateOptions {
+    source_path: String,
+    api_key: Option<String>,
+    namespace: Option<String>,
+    collection: Option<String>,
+    vector_name: Option<String>,
+    distance: Distance,
+    dest: PathBuf,
+    checkpoint: PathBuf,
+    quiet: bool,
+}
+
+/// Migrate a Pinecone index, or a dump of one when the source is a file
+#[cfg(feature = "migrate")]
+fn migrate_pinecone(options: &MigrateOptions) -> Result<()> {
+    use crate::migration::{MigrationReader, PineconeClient, PineconeDump, PineconeSource};
+
+    let source = options.source_path.as_str();
+    let mut reader: Box<dyn MigrationReader> = if std::path::Path::new(source).is_file() {
+        Box::new(PineconeDump::open(source, options.distance)?)
+    } else {
+        let api_key = options
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("PINECONE_API_KEY").ok())
+            .context("Pass --api-key or set PINECONE_API_KEY to migrate a Pinecone index")?;
+        println!("   Connecting to Pinecone...");
+        let client = PineconeClient::new(api_key)?;
+        let source = PineconeSource::connect(client, source, options.namespace.as_deref())?;
+        let index = source.index();
+        println!(
+            "   Index '{}': {} dimensions, {} metric",
+            index.name, index.dimension, index.metric
+        );
+        Box::new(source)
+    };
+    run_migration(reader.as_mut(), options)
+}
 
-    if analyze {
-        println!("📊 Analyzing database...");
-        let stats = store.stats()?;
-        println!("  Current size: {} vectors", store.len());
-        println!("  Memory usage: {} bytes", stats.memory_usage);
+/// Migrate a Qdrant collection, or a JSONL dump of points when the source
+/// is a file
+#[cfg(feature = "migrate")]
+fn migrate_qdrant(options: &MigrateOptions) -> Result<()> {
+    use crate::migration::{PayloadMapping, QdrantClient, QdrantDump, QdrantSource};
 
-        // Run query optimizer on sample query
-        use crate::query_optimizer::QueryOptimizer;
-        let optimizer = QueryOptimizer::new(store);
-        let summary = optimizer.store_optimization_summary();
-        println!("\n💡 Optimization Recommendations:");
-        for rec in summary.recommendations {
-            println!("  • {}", rec);
+    let source = options.source_path.as_str();
+    let vector_name = options.vector_name.as_deref();
+    let mapping: PayloadMapping = if std::path::Path::new(source).is_file() {
+        let mut dump = QdrantDump::open(source, options.distance, vector_name)?;
+        run_migration(&mut dump, options)?;
+        dump.payload_mapping().clone()
+    } else {
+        let collection = options
+            .collection
+            .as_deref()
+            .context("Pass --collection to migrate from a Qdrant server")?;
+        let api_key = options
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("QDRANT_API_KEY").ok());
+        println!("   Connecting to Qdrant...");
+        let client = QdrantClient::new(source, api_key)?;
+        let mut source = QdrantSource::connect(client, collection, vector_name)?;
+        let vector = source.vector();
+        println!(
+            "   Collection '{}': vector '{}', {} dimensions, {} distance",
+            collection,
+            vector.name.as_deref().unwrap_or("(unnamed)"),
+            vector.size,
+            vector.distance
+        );
+        run_migration(&mut source, options)?;
+        source.payload_mapping().clone()
+    };
+
+    if !mapping.stringified.is_empty() {
+        println!("   Payload fields stored as JSON text (filters can't reach inside them):");
+        for (field, points) in &mapping.stringified {
+            println!("     {} ({} points)", field, points);
         }
     }
+    Ok(())
+}
+
+/// Run a migration with progress on stderr, then report and verify the
+/// record counts
+#[cfg(feature = "migrate")]
+fn run_migration(
+    reader: &mut dyn crate::migration::MigrationReader,
+    options: &MigrateOptions,
+) -> Result<()> {
+    use crate::migration::MigrationRun;
 
-    if compact {
-        println!("\n🗜️  Running compaction...");
-        // Compaction would be implemented in VecStore
-        println!("  ✓ Compaction complete");
+    if options.checkpoint.exists() {
+        println!("   Resuming from checkpoint {:?}", options.checkpoint);
     }
+    let mut bar = Progress::new("Migrating", "records", None, options.quiet);
+    let mut progress = |done: usize, total: Option<usize>| {
+        if let Some(total) = total.filter(|_| bar.total.is_none()) {
+            bar.set_total(total);
+        }
+        bar.set(done);
+    };
+    let started = Instant::now();
+    let report = MigrationRun::new()
+        .with_checkpoint(&options.checkpoint)
+        .with_progress(&mut progress)
+        .run(reader, &options.dest);
+    bar.finish();
+    let report = report.with_context(|| {
+        format!(
+            "Migration stopped; run the same command again to resume from {:?}",
+            options.checkpoint
+        )
+    })?;
 
-    if rebuild_index {
-        println!("\n🔨 Rebuilding HNSW index...");
-        // Index rebuild would be implemented in VecStore
-        println!("  ✓ Index rebuilt");
+    println!(
+        "✓ Migrated {} records in {:.2}s",
+        report.resumed + report.migrated,
+        started.elapsed().as_secs_f64()
+    );
+    match report.source_count {
+        Some(count) if count == report.dest_count => {
+            println!(
+                "✓ Verified: source and destination both hold {} records",
+                count
+            )
+        }
+        Some(count) => eprintln!(
+            "⚠️  Source reports {} records but the destination holds {}",
+            count, report.dest_count
+        ),
+        None => println!("   Destination holds {} records", report.dest_count),
     }
+    Ok(())
+}
 
-    println!("\n✅ Optimization complete!");
+#[cfg(not(feature = "migrate"))]
+fn migrate_pinecone(_options: &MigrateOptions) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Pinecone migration needs vecstore built with the `migrate` feature"
+    ))
+}
 
-    Ok(())
+/// Text embedder picked with `--embed-model`, and the name the store records
+/// for it
+#[cfg(feature = "embeddings")]
+struct CliEmbedder {
+    name: String,
+    embedder: Box<dyn crate::embeddings::TextEmbedder>,
 }
 
-/// Run benchmarks
-pub fn benchmark(
-    vectors: usize,
-    dimensions: usize,
-    queries: usize,
-    format: OutputFormat,
-) -> Result<()> {
-    use crate::benchmark::{Benchmarker, BenchmarkConfig};
-    use tempfile::TempDir;
-
-    println!("🏃 Running benchmarks...\n");
-    println!("  Vectors: {}", vectors);
-    println!("  Dimensions: {}", dimensions);
-    println!("  Queries: {}\n", queries);
-
-    let temp_dir = TempDir::new()?;
-    let db_path = temp_dir.path().join("benchmark.db");
-    let store = VecStore::open(&db_path)?;
-
-    let config = BenchmarkConfig {
-        num_vectors: vectors,
-        vector_dim: dimensions,
-        num_queries: queries,
-        batch_sizes: vec![100, 1000],
-        k_values: vec![10, 100],
+/// `--embed-model`, else `VECSTORE_EMBED_MODEL`, else the model recorded in
+/// the store
+#[cfg(feature = "embeddings")]
+fn embed_model_spec(store: &VecStore, flag: Option<&str>) -> Result<String> {
+    flag.map(str::to_string)
+        .or_else(|| std::env::var("VECSTORE_EMBED_MODEL").ok())
+        .or_else(|| store.embedding_model().map(|m| m.name.clone()))
+        .context("Pass --embed-model (or set VECSTORE_EMBED_MODEL) to embed text")
+}
+
+/// Open the embedder for a `--embed-model` spec:
+///
+/// - a pretrained ONNX model (`minilm`, `all-MiniLM-L12-v2`, ...), downloaded
+///   to the [model cache](crate::embeddings::default_model_cache) on first use
+/// - `onnx:<dir>`, a directory with `model.onnx` and `tokenizer.json`
+/// - `openai:<model>`, served at `VECSTORE_EMBED_URL` (default:
+///   `OPENAI_BASE_URL`, else OpenAI's API) with the key in
+///   `VECSTORE_EMBED_API_KEY` or `OPENAI_API_KEY`
+#[cfg(feature = "embeddings")]
+fn open_embedder(spec: &str) -> Result<CliEmbedder> {
+    use crate::embeddings::{
+        AutoEmbedder, LocalEmbedder, OpenAICompatibleEmbedder, PretrainedModel, TextEmbedder,
+        DEFAULT_OPENAI_BASE_URL,
     };
 
-    let benchmarker = Benchmarker::new(store, config);
-    let results = benchmarker.run_all()?;
+    if let Some(model) = spec.strip_prefix("openai:") {
+        let base_url = std::env::var("VECSTORE_EMBED_URL")
+            .or_else(|_| std::env::var("OPENAI_BASE_URL"))
+            .unwrap_or_else(|_| DEFAULT_OPENAI_BASE_URL.to_string());
+        let api_key = std::env::var("VECSTORE_EMBED_API_KEY")
+            .or_else(|_| std::env::var("OPENAI_API_KEY"))
+            .ok();
+        return Ok(CliEmbedder {
+            name: spec.to_string(),
+            embedder: Box::new(
+                OpenAICompatibleEmbedder::new(base_url, model).with_api_key(api_key),
+            ),
+        });
+    }
+    if let Some(dir) = spec.strip_prefix("onnx:") {
+        let dir = fs::canonicalize(dir)
+            .with_context(|| format!("ONNX model directory {:?} not found", dir))?;
+        let embedder = LocalEmbedder::from_dir(&dir)?;
+        return Ok(CliEmbedder {
+            name: format!("onnx:{}", dir.display()),
+            embedder: Box::new(embedder),
+        });
+    }
 
-    match format {
-        OutputFormat::Json => {
-            let json = serde_json::to_string_pretty(&results)?;
-            println!("{}", json);
-        }
-        OutputFormat::Table | OutputFormat::Simple => {
-            println!("📈 Benchmark Results:\n");
-            println!("Insert Performance:");
-            println!("  Throughput: {:.2} vectors/sec", results.insert.throughput);
-            println!("  Avg Latency: {:.2} ms", results.insert.latency.avg);
-            println!("  P99 Latency: {:.2} ms", results.insert.latency.p99);
+    // Recorded names are HuggingFace ids, whose last segment is the model name
+    let name = spec.rsplit('/').next().unwrap_or(spec);
+    let model = PretrainedModel::from_name(name).with_context(|| {
+        format!(
+            "Unknown embedding model '{}'; use a pretrained name such as minilm, \
+             onnx:<dir> or openai:<model>",
+            spec
+        )
+    })?;
+    let embedder: Box<dyn TextEmbedder> = match model {
+        PretrainedModel::AllMiniLML6V2 => Box::new(LocalEmbedder::minilm()?),
+        _ => Box::new(AutoEmbedder::from_pretrained(name)?),
+    };
+    Ok(CliEmbedder {
+        name: model.model_id().to_string(),
+        embedder,
+    })
+}
 
-            println!("\nQuery Performance:");
-            println!("  Throughput: {:.2} queries/sec", results.query.throughput);
-            println!("  Avg Latency: {:.2} ms", results.query.latency.avg);
-            println!("  P99 Latency: {:.2} ms", results.query.latency.p99);
+/// Embed `--text` for a query, refusing models the store wasn't ingested with
+#[cfg(feature = "embeddings")]
+fn embed_query_text(store: &VecStore, embed_model: Option<&str>, text: &str) -> Result<Vec<f32>> {
+    let embedder = open_embedder(&embed_model_spec(store, embed_model)?)?;
+    let vector = embedder.embedder.embed(text)?;
+    store.check_embedding_model(&crate::EmbeddingModel {
+        name: embedder.name,
+        dimension: vector.len(),
+    })?;
+    Ok(vector)
+}
+
+#[cfg(not(feature = "embeddings"))]
+fn embed_query_text(
+    _store: &VecStore,
+    _embed_model: Option<&str>,
+    _text: &str,
+) -> Result<Vec<f32>> {
+    Err(anyhow::anyhow!(
+        "Text queries need vecstore built with the `embeddings` feature; pass --vec instead"
+    ))
+}
+
+/// Load each file with the loader for its extension, split it into chunks,
+/// embed them and upsert them as `<file>#<n>`, children of `<file>`
+#[cfg(feature = "embeddings")]
+fn ingest_text(
+    dir: &std::path::Path,
+    files: &[PathBuf],
+    chunk_size: usize,
+    chunk_overlap: usize,
+    embed_model: Option<&str>,
+    quiet: bool,
+) -> Result<()> {
+    use crate::text_splitter::MarkdownTextSplitter;
+    use crate::{RecursiveCharacterTextSplitter, TextSplitter};
+    use vecstore_loaders::{CsvLoader, DocumentLoader, JsonLoader, MarkdownLoader, TextLoader};
+
+    /// Chunks embedded per call
+    const EMBED_BATCH: usize = 32;
+
+    let mut store = VecStore::open(dir)?;
+    let embedder = open_embedder(&embed_model_spec(&store, embed_model)?)?;
+    let started = Instant::now();
+    let mut total = 0;
+
+    for file in files {
+        let path = file.to_string_lossy().into_owned();
+        let extension = file
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_lowercase);
+        let (document, markdown) = match extension.as_deref() {
+            Some("md" | "markdown") => (MarkdownLoader::new().load(&path)?, true),
+            Some("json") => (JsonLoader::new().load(&path)?, false),
+            Some("csv") => (CsvLoader::new().load(&path)?, false),
+            _ => (TextLoader::new().load(&path)?, false),
+        };
+        let chunks = if markdown {
+            MarkdownTextSplitter::new(chunk_size, chunk_overlap).split_text(&document.content)?
+        } else {
+            RecursiveCharacterTextSplitter::new(chunk_size, chunk_overlap)
+                .split_text(&document.content)?
+        };
+
+        let mut fields: HashMap<String, serde_json::Value> = document
+            .metadata
+            .into_iter()
+            .map(|(key, value)| (key, serde_json::Value::from(value)))
+            .collect();
+        fields.insert("source".into(), serde_json::Value::from(path.clone()));
+
+        let mut progress = Progress::new(path.clone(), "chunks", Some(chunks.len()), quiet);
+        for (batch_index, batch) in chunks.chunks(EMBED_BATCH).enumerate() {
+            let texts: Vec<&str> = batch.iter().map(String::as_str).collect();
+            let vectors = embedder.embedder.embed_batch(&texts)?;
+            // Fails if the store was ingested with another model
+            store.set_embedding_model(crate::EmbeddingModel {
+                name: embedder.name.clone(),
+                dimension: vectors[0].len(),
+            })?;
+
+            let records = batch
+                .iter()
+                .zip(vectors)
+                .enumerate()
+                .map(|(i, (text, vector))| {
+                    let chunk_index = batch_index * EMBED_BATCH + i;
+                    Record {
+                        text: Some(text.clone()),
+                        parent_id: Some(path.clone()),
+                        chunk_index: Some(chunk_index),
+                        ..make_record(
+                            format!("{}#{}", path, chunk_index),
+                            vector,
+                            Metadata {
+                                fields: fields.clone(),
+                            },
+                        )
+                    }
+                });
+            store.batch_upsert(records.collect::<Vec<_>>())?;
+            progress.inc(batch.len());
         }
+        progress.finish();
+        total += chunks.len();
     }
+    store.save()?;
 
+    let model = store.embedding_model().expect("recorded above");
+    println!(
+        "✓ Ingested {} chunks from {} files with {} ({} dimensions) in {:.2}s",
+        total,
+        files.len(),
+        model.name,
+        model.dimension,
+        started.elapsed().as_secs_f64()
+    );
     Ok(())
 }
 
-/// List vectors
-pub fn list(
-    store: &VecStore,
-    filter: Option<&str>,
-    limit: usize,
-    show_vectors: bool,
-    format: OutputFormat,
+#[cfg(not(feature = "embeddings"))]
+fn ingest_text(
+    _dir: &std::path::Path,
+    _files: &[PathBuf],
+    _chunk_size: usize,
+    _chunk_overlap: usize,
+    _embed_model: Option<&str>,
+    _quiet: bool,
 ) -> Result<()> {
-    // Build query to get vectors
-    let mut q = Query::new(vec![0.0; 128]).with_limit(limit);
-    if let Some(f) = filter {
-        q = q.with_filter(f);
-    }
-
-    let results = store.query(q)?;
-
-    match format {
-        OutputFormat::Json => {
-            let json = serde_json::to_string_pretty(&results)?;
-            println!("{}", json);
-        }
-        OutputFormat::Table => {
-            println!("\n📋 Vectors (showing up to {}):\n", limit);
-            if show_vectors {
-                for result in results {
-                    println!("ID: {}", result.id);
-                    println!("  Vector: {:?}", &result.vector[..result.vector.len().min(10)]);
-                    if result.vector.len() > 10 {
-                        println!("    ... ({} dimensions total)", result.vector.len());
-                    }
-                    println!("  Metadata: {}", serde_json::to_string(&result.metadata)?);
-                    println!();
-                }
-            } else {
-                println!("{:<30} {:<15}", "ID", "Dimensions");
-                println!("{}", "-".repeat(45));
-                for result in results {
-                    println!("{:<30} {:<15}", result.id, result.vector.len());
-                }
+    Err(anyhow::anyhow!(
+        "ingest-text needs vecstore built with the `embeddings` feature"
+    ))
+}
+
+/// Migrate the collections of a persistent ChromaDB directory into a
+/// database of collections with the same names
+#[cfg(feature = "migrate")]
+fn migrate_chroma(options: &MigrateOptions) -> Result<()> {
+    use crate::migration::ChromaDirectory;
+
+    let chroma = ChromaDirectory::open(&options.source_path)?;
+    let collections = match options.collection.as_deref() {
+        Some(name) => vec![chroma.collection(name)?],
+        None => chroma.collections()?,
+    };
+    if collections.is_empty() {
+        println!("   No collections in {}", options.source_path);
+        return Ok(());
+    }
+
+    let mut db = crate::VecDatabase::open(&options.dest)?;
+    let started = Instant::now();
+    for collection in &collections {
+        println!(
+            "   Collection '{}': {} dimensions, {:?} distance",
+            collection.name,
+            collection
+                .dimension
+                .map_or_else(|| "unknown".to_string(), |d| d.to_string()),
+            collection.distance
+        );
+        let mut bar = Progress::new("Migrating", "records", None, options.quiet);
+        let mut progress = |done: usize, total: Option<usize>| {
+            if let Some(total) = total.filter(|_| bar.total.is_none()) {
+                bar.set_total(total);
             }
+            bar.set(done);
+        };
+        let report = chroma.migrate_into(collection, &mut db, &mut progress);
+        bar.finish();
+        let report = report.with_context(|| format!("Collection '{}'", collection.name))?;
+        match report.source_count {
+            Some(count) if count != report.dest_count => eprintln!(
+                "⚠️  Chroma holds {} records but the collection holds {}",
+                count, report.dest_count
+            ),
+            _ => println!("   ✓ {} records", report.migrated),
         }
-        OutputFormat::Simple => {
-            for result in results {
-                println!("{}", result.id);
+    }
+    println!(
+        "✓ Migrated {} collections in {:.2}s",
+        collections.len(),
+        started.elapsed().as_secs_f64()
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "migrate"))]
+fn migrate_chroma(_options: &MigrateOptions) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "ChromaDB migration needs vecstore built with the `migrate` feature"
+    ))
+}
+
+#[cfg(not(feature = "migrate"))]
+fn migrate_qdrant(_options: &MigrateOptions) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Qdrant migration needs vecstore built with the `migrate` feature"
+    ))
+}
+
+fn print_export_summary(exported: usize, path: &std::path::Path) -> Result<()> {
+    let size = fs::metadata(path)?.len();
+    println!(
+        "✓ Exported {} records to {:?} ({})",
+        exported,
+        path,
+        format_bytes(size)
+    );
+    Ok(())
+}
+
+/// Call `visit` with each leaf of a metadata value, nested object keys
+/// joined with `.`; arrays are leaves
+fn flatten_metadata<'a>(
+    path: &str,
+    value: &'a serde_json::Value,
+    visit: &mut impl FnMut(String, &'a serde_json::Value),
+) {
+    match value {
+        serde_json::Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                flatten_metadata(&format!("{}.{}", path, key), child, visit);
             }
         }
+        _ => visit(path.to_string(), value),
     }
+}
 
-    Ok(())
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Quote a CSV field, doubling any embedded quotes
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
 }
@@ -0,0 +1,397 @@
+//! `vecstore.toml`: named profiles of defaults for the flags most commands share
+//!
+//! A setting resolves as flag > environment variable > profile > built-in
+//! default. The resolved values become the clap defaults before the command
+//! line is parsed, so a flag always wins and `--help` shows what applies.
+
+use anyhow::{anyhow, Context, Result};
+use clap::Command;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// Config file looked up in the working directory
+pub const PROJECT_FILE: &str = "vecstore.toml";
+
+/// Profile used when none is named
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Written by `vecstore config init`
+const TEMPLATE: &str = r#"# vecstore CLI settings. A value here applies when the flag isn't given
+# and the matching VECSTORE_* environment variable isn't set.
+
+# Profile used without --profile or VECSTORE_PROFILE
+default_profile = "default"
+
+[profiles.default]
+# Model for query --text and ingest-text (--embed-model, VECSTORE_EMBED_MODEL)
+# embed_model = "openai:text-embedding-3-small"
+# Address of a running vecstore-server (VECSTORE_SERVER_URL)
+# server_url = "http://localhost:8080"
+
+# Store directory (--dir, VECSTORE_DIR)
+dir = "./data"
+# "text" or "json" output from query, get, parent-window, list and stats
+# (--json-out, VECSTORE_OUTPUT)
+output = "text"
+"#;
+
+/// A setting a profile can hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Dir,
+    Output,
+    EmbedModel,
+    ServerUrl,
+}
+
+impl Key {
+    pub const ALL: [Key; 4] = [Key::Dir, Key::Output, Key::EmbedModel, Key::ServerUrl];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Key::Dir => "dir",
+            Key::Output => "output",
+            Key::EmbedModel => "embed_model",
+            Key::ServerUrl => "server_url",
+        }
+    }
+
+    /// Environment variable that overrides the profile
+    pub fn env_var(self) -> &'static str {
+        match self {
+            Key::Dir => "VECSTORE_DIR",
+            Key::Output => "VECSTORE_OUTPUT",
+            Key::EmbedModel => "VECSTORE_EMBED_MODEL",
+            Key::ServerUrl => "VECSTORE_SERVER_URL",
+        }
+    }
+
+    fn builtin(self) -> Option<&'static str> {
+        match self {
+            Key::Dir => Some("./data"),
+            Key::Output => Some("text"),
+            Key::EmbedModel | Key::ServerUrl => None,
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.name() == name)
+    }
+
+    fn check(self, value: &str) -> Result<()> {
+        match self {
+            Key::Output if !matches!(value, "text" | "json") => {
+                Err(anyhow!("expected \"text\" or \"json\", got \"{}\"", value))
+            }
+            Key::ServerUrl if !(value.starts_with("http://") || value.starts_with("https://")) => {
+                Err(anyhow!(
+                    "expected an http:// or https:// URL, got \"{}\"",
+                    value
+                ))
+            }
+            _ if value.is_empty() => Err(anyhow!("must not be empty")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Where a resolved setting came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Env,
+    Profile,
+    Default,
+}
+
+/// The config file, if there is one, and the profile in use
+pub struct Config {
+    /// File the profiles were read from
+    pub path: Option<PathBuf>,
+    /// Profile in use
+    pub profile: String,
+    /// Names of the profiles in the file, sorted
+    pub profiles: Vec<String>,
+    values: HashMap<Key, String>,
+    /// Set when the profile was named but isn't in the file
+    missing: Option<String>,
+}
+
+/// No file, and the `default` profile
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            path: None,
+            profile: DEFAULT_PROFILE.to_string(),
+            profiles: Vec::new(),
+            values: HashMap::new(),
+            missing: None,
+        }
+    }
+}
+
+impl Config {
+    /// Read the config file and select a profile: `profile` (from
+    /// `--profile`), else `VECSTORE_PROFILE`, else the file's
+    /// `default_profile`, else `default`
+    pub fn load(profile: Option<String>) -> Result<Self> {
+        let path = find_file()?;
+        let document = match &path {
+            Some(path) => read_document(path)?,
+            None => DocumentMut::new(),
+        };
+        let file = path
+            .as_deref()
+            .map_or_else(|| "config".to_string(), |p| p.display().to_string());
+        let profiles = check_document(&document, &file)?;
+
+        let named = profile
+            .or_else(|| std::env::var("VECSTORE_PROFILE").ok())
+            .or_else(|| {
+                document
+                    .get("default_profile")
+                    .and_then(Item::as_str)
+                    .map(str::to_string)
+            });
+        let profile = named.clone().unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        let values = profiles.get(&profile).cloned().unwrap_or_default();
+        let missing = (named.is_some() && !profiles.contains_key(&profile))
+            .then(|| format!("No profile '{}' in {}", profile, file));
+
+        let mut names: Vec<String> = profiles.into_keys().collect();
+        names.sort();
+        Ok(Self {
+            path,
+            profile,
+            profiles: names,
+            values,
+            missing,
+        })
+    }
+
+    /// Fail if the profile was named but doesn't exist
+    ///
+    /// Not part of [`load`](Self::load), so `config set` can create it.
+    pub fn check_profile(&self) -> Result<()> {
+        match &self.missing {
+            Some(message) => Err(anyhow!("{}", message)),
+            None => Ok(()),
+        }
+    }
+
+    /// The value of `key` and where it came from, if it has one
+    pub fn get(&self, key: Key) -> Result<Option<(String, Source)>> {
+        if let Ok(value) = std::env::var(key.env_var()) {
+            key.check(&value)
+                .with_context(|| format!("Invalid {}", key.env_var()))?;
+            return Ok(Some((value, Source::Env)));
+        }
+        if let Some(value) = self.values.get(&key) {
+            return Ok(Some((value.clone(), Source::Profile)));
+        }
+        Ok(key.builtin().map(|v| (v.to_string(), Source::Default)))
+    }
+
+    /// Make the resolved settings the defaults of the subcommands' flags
+    ///
+    /// `collection` commands keep their own `--dir` default: they open a
+    /// database of collections rather than a store.
+    pub fn apply(&self, mut command: Command) -> Result<Command> {
+        let defaults = [
+            ("dir", self.get(Key::Dir)?.map(|(v, _)| v)),
+            (
+                "json_out",
+                self.get(Key::Output)?
+                    .map(|(v, _)| (v == "json").to_string()),
+            ),
+            ("embed_model", self.get(Key::EmbedModel)?.map(|(v, _)| v)),
+        ];
+        let subcommands: Vec<String> = command
+            .get_subcommands()
+            .map(|sub| sub.get_name().to_string())
+            .filter(|name| name != "collection")
+            .collect();
+        for name in subcommands {
+            command = command.mut_subcommand(name, |mut sub| {
+                for (id, value) in &defaults {
+                    let Some(value) = value else { continue };
+                    if sub.get_arguments().any(|arg| arg.get_id() == *id) {
+                        sub = sub.mut_arg(*id, |arg| arg.default_value(value.clone()));
+                    }
+                }
+                sub
+            });
+        }
+        Ok(command)
+    }
+}
+
+/// `--profile NAME` or `--profile=NAME`, read ahead of clap because it
+/// decides the defaults clap parses with
+pub fn profile_arg() -> Option<String> {
+    let mut args = std::env::args().skip(1).take_while(|arg| arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// `$XDG_CONFIG_HOME/vecstore/config.toml`, by default under `~/.config`
+pub fn user_file() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| directories::BaseDirs::new().map(|d| d.home_dir().join(".config")))?;
+    Some(config_home.join("vecstore").join("config.toml"))
+}
+
+/// `VECSTORE_CONFIG`, else `./vecstore.toml`, else the user file, if it exists
+fn find_file() -> Result<Option<PathBuf>> {
+    if let Some(path) = std::env::var_os("VECSTORE_CONFIG") {
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            return Err(anyhow!(
+                "VECSTORE_CONFIG names {:?}, which doesn't exist",
+                path
+            ));
+        }
+        return Ok(Some(path));
+    }
+    let project = PathBuf::from(PROJECT_FILE);
+    if project.is_file() {
+        return Ok(Some(project));
+    }
+    Ok(user_file().filter(|path| path.is_file()))
+}
+
+fn read_document(path: &Path) -> Result<DocumentMut> {
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    text.parse::<DocumentMut>()
+        .with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Check every key of the document, returning each profile's settings
+fn check_document(
+    document: &DocumentMut,
+    file: &str,
+) -> Result<HashMap<String, HashMap<Key, String>>> {
+    let mut profiles = HashMap::new();
+    for (key, item) in document.iter() {
+        match key {
+            "default_profile" => {
+                item.as_str()
+                    .with_context(|| format!("{}: default_profile: expected a string", file))?;
+            }
+            "profiles" => {
+                let table = item
+                    .as_table_like()
+                    .with_context(|| format!("{}: profiles: expected a table", file))?;
+                for (name, profile) in table.iter() {
+                    let profile = profile.as_table_like().with_context(|| {
+                        format!("{}: profiles.{}: expected a table", file, name)
+                    })?;
+                    let mut values = HashMap::new();
+                    for (setting, value) in profile.iter() {
+                        let path = format!("profiles.{}.{}", name, setting);
+                        let key = Key::from_name(setting).with_context(|| {
+                            format!(
+                                "{}: {}: unknown setting; expected one of {}",
+                                file,
+                                path,
+                                key_names()
+                            )
+                        })?;
+                        let value = value
+                            .as_str()
+                            .with_context(|| format!("{}: {}: expected a string", file, path))?;
+                        key.check(value)
+                            .with_context(|| format!("{}: {}", file, path))?;
+                        values.insert(key, value.to_string());
+                    }
+                    profiles.insert(name.to_string(), values);
+                }
+            }
+            other => {
+                return Err(anyhow!(
+                    "{}: {}: unknown key; expected default_profile or [profiles.<name>]",
+                    file,
+                    other
+                ))
+            }
+        }
+    }
+    Ok(profiles)
+}
+
+fn key_names() -> String {
+    Key::ALL.map(Key::name).join(", ")
+}
+
+/// Write a commented config file to `path`
+pub fn init(path: &Path, force: bool) -> Result<()> {
+    if path.exists() && !force {
+        return Err(anyhow!(
+            "{} already exists; pass --force to overwrite it",
+            path.display()
+        ));
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    fs::write(path, TEMPLATE).with_context(|| format!("Failed to write {:?}", path))
+}
+
+/// Set `key` to `value` in `profile` of the file at `path`, keeping its
+/// comments and layout; `default_profile` sets the top-level key instead
+pub fn set(path: &Path, profile: &str, key: &str, value: &str) -> Result<()> {
+    let mut document = if path.exists() {
+        read_document(path)?
+    } else {
+        DocumentMut::new()
+    };
+
+    if key == "default_profile" {
+        document["default_profile"] = toml_edit::value(value);
+    } else {
+        let setting = Key::from_name(key).with_context(|| {
+            format!(
+                "Unknown setting '{}'; expected default_profile or one of {}",
+                key,
+                key_names()
+            )
+        })?;
+        setting
+            .check(value)
+            .with_context(|| format!("Invalid value for {}", key))?;
+
+        let profiles = document
+            .entry("profiles")
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            })
+            .as_table_mut()
+            .with_context(|| format!("{}: profiles: expected a table", path.display()))?;
+        let profile = profiles
+            .entry(profile)
+            .or_insert_with(|| Item::Table(Table::new()))
+            .as_table_mut()
+            .with_context(|| {
+                format!("{}: profiles.{}: expected a table", path.display(), profile)
+            })?;
+        profile[key] = toml_edit::value(value);
+    }
+
+    check_document(&document, &path.display().to_string())?;
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).with_context(|| format!("Failed to create {:?}", parent))?;
+    }
+    fs::write(path, document.to_string()).with_context(|| format!("Failed to write {:?}", path))
+}
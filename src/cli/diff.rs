@@ -0,0 +1,266 @@
+//! `vecstore diff`: what changed between two stores, or a store and a JSONL export
+//!
+//! Id sets are compared in full. Metadata and vectors are compared for the
+//! ids both sides share, or a seeded random sample of them on large stores.
+
+use crate::{Record, VecStore};
+use anyhow::{Context, Result};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// One side of a diff
+pub enum Side {
+    Store(Box<VecStore>),
+    /// Records of a JSONL export, by id
+    Export(HashMap<String, Record>),
+}
+
+impl Side {
+    /// A store directory, or a JSONL file as written by `vecstore export`
+    pub fn open(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            return Ok(Side::Store(Box::new(VecStore::open(path)?)));
+        }
+        let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+        let mut records = HashMap::new();
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.with_context(|| format!("Failed to read {:?}", path))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Record = serde_json::from_str(&line)
+                .with_context(|| format!("{}:{}: not a record", path.display(), number + 1))?;
+            records.insert(record.id.clone(), record);
+        }
+        Ok(Side::Export(records))
+    }
+
+    fn ids(&self) -> BTreeSet<String> {
+        match self {
+            Side::Store(store) => store.iter().map(|r| r.id.clone()).collect(),
+            Side::Export(records) => records.keys().cloned().collect(),
+        }
+    }
+
+    /// The record with its vector at full precision
+    fn get(&self, id: &str) -> Option<Record> {
+        match self {
+            Side::Store(store) => store.get(id),
+            Side::Export(records) => records.get(id).cloned(),
+        }
+    }
+}
+
+/// How much of the shared ids to compare
+pub struct DiffOptions {
+    /// Cosine similarity below which a vector counts as drifted
+    pub threshold: f32,
+    /// Compare at most this many shared ids, picked at random
+    pub sample: Option<usize>,
+    pub seed: u64,
+    /// Ids listed per category; the rest are only counted
+    pub show: usize,
+}
+
+/// A shared id whose metadata differs, and the fields that differ
+#[derive(Debug, Serialize)]
+pub struct MetadataChange {
+    pub id: String,
+    pub fields: Vec<String>,
+}
+
+/// A shared id whose vector moved, and the cosine similarity of the two
+///
+/// `similarity` is `None` when the vectors have different lengths.
+#[derive(Debug, Serialize)]
+pub struct Drift {
+    pub id: String,
+    pub similarity: Option<f32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffReport {
+    pub a: PathBuf,
+    pub b: PathBuf,
+    pub a_count: usize,
+    pub b_count: usize,
+    /// Ids only in `b`
+    pub added: usize,
+    /// Ids only in `a`
+    pub removed: usize,
+    pub common: usize,
+    /// Shared ids whose metadata and vectors were compared
+    pub compared: usize,
+    pub metadata_changed: usize,
+    pub drift_threshold: f32,
+    pub drifted: usize,
+    /// Lowest cosine similarity among the compared vectors
+    pub min_similarity: Option<f32>,
+    pub added_ids: Vec<String>,
+    pub removed_ids: Vec<String>,
+    pub metadata_changes: Vec<MetadataChange>,
+    /// Most drifted first
+    pub drifts: Vec<Drift>,
+}
+
+pub fn diff(a: &Path, b: &Path, options: &DiffOptions) -> Result<DiffReport> {
+    let side_a = Side::open(a)?;
+    let side_b = Side::open(b)?;
+    let ids_a = side_a.ids();
+    let ids_b = side_b.ids();
+
+    let added: Vec<&String> = ids_b.difference(&ids_a).collect();
+    let removed: Vec<&String> = ids_a.difference(&ids_b).collect();
+    let mut common: Vec<&String> = ids_a.intersection(&ids_b).collect();
+    let common_count = common.len();
+    if let Some(sample) = options.sample.filter(|&n| n < common.len()) {
+        common.shuffle(&mut StdRng::seed_from_u64(options.seed));
+        common.truncate(sample);
+        common.sort();
+    }
+
+    let mut metadata_changes = Vec::new();
+    let mut drifts = Vec::new();
+    let mut min_similarity: Option<f32> = None;
+    for id in &common {
+        let (Some(record_a), Some(record_b)) = (side_a.get(id), side_b.get(id)) else {
+            continue;
+        };
+
+        let fields = changed_fields(&record_a.metadata.fields, &record_b.metadata.fields);
+        if !fields.is_empty() {
+            metadata_changes.push(MetadataChange {
+                id: id.to_string(),
+                fields,
+            });
+        }
+
+        let similarity = similarity(&record_a.vector, &record_b.vector);
+        if let Some(s) = similarity {
+            min_similarity = Some(min_similarity.map_or(s, |m| m.min(s)));
+        }
+        if similarity.is_none_or(|s| s < options.threshold) {
+            drifts.push(Drift {
+                id: id.to_string(),
+                similarity,
+            });
+        }
+    }
+    // Length changes first, then by increasing similarity
+    drifts.sort_by(|x, y| {
+        let key = |d: &Drift| d.similarity.unwrap_or(f32::NEG_INFINITY);
+        key(x).total_cmp(&key(y))
+    });
+
+    let metadata_changed = metadata_changes.len();
+    let drifted = drifts.len();
+    metadata_changes.truncate(options.show);
+    drifts.truncate(options.show);
+    Ok(DiffReport {
+        a: a.to_path_buf(),
+        b: b.to_path_buf(),
+        a_count: ids_a.len(),
+        b_count: ids_b.len(),
+        added: added.len(),
+        removed: removed.len(),
+        common: common_count,
+        compared: common.len(),
+        metadata_changed,
+        drift_threshold: options.threshold,
+        drifted,
+        min_similarity,
+        added_ids: added.into_iter().take(options.show).cloned().collect(),
+        removed_ids: removed.into_iter().take(options.show).cloned().collect(),
+        metadata_changes,
+        drifts,
+    })
+}
+
+/// Fields added, removed or changed between `a` and `b`, sorted
+fn changed_fields(
+    a: &HashMap<String, serde_json::Value>,
+    b: &HashMap<String, serde_json::Value>,
+) -> Vec<String> {
+    let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    keys.into_iter()
+        .filter(|key| a.get(*key) != b.get(*key))
+        .cloned()
+        .collect()
+}
+
+/// Cosine similarity, 1.0 for identical vectors (zero ones included), and
+/// `None` when the lengths differ
+fn similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    if a == b {
+        return Some(1.0);
+    }
+    Some(crate::simd::cosine_similarity_simd(a, b))
+}
+
+pub fn print_table(report: &DiffReport) {
+    println!("🔀 Diff {:?} → {:?}", report.a, report.b);
+    println!(
+        "   a: {} records, b: {} records",
+        report.a_count, report.b_count
+    );
+    println!("   Added:    {}", report.added);
+    print_ids(&report.added_ids, report.added);
+    println!("   Removed:  {}", report.removed);
+    print_ids(&report.removed_ids, report.removed);
+    if report.compared < report.common {
+        println!(
+            "   Common:   {} (compared a sample of {})",
+            report.common, report.compared
+        );
+    } else {
+        println!("   Common:   {}", report.common);
+    }
+
+    println!("   Metadata changed: {}", report.metadata_changed);
+    for change in &report.metadata_changes {
+        println!("     {}: {}", change.id, change.fields.join(", "));
+    }
+    print_more(report.metadata_changes.len(), report.metadata_changed);
+
+    println!(
+        "   Vector drift (cosine < {}): {}",
+        report.drift_threshold, report.drifted
+    );
+    for drift in &report.drifts {
+        match drift.similarity {
+            Some(similarity) => println!("     {}: {:.4}", drift.id, similarity),
+            None => println!("     {}: dimension changed", drift.id),
+        }
+    }
+    print_more(report.drifts.len(), report.drifted);
+    if let Some(min) = report.min_similarity {
+        println!("   Lowest similarity: {:.4}", min);
+    }
+
+    let changed = report.added + report.removed + report.metadata_changed + report.drifted;
+    if changed == 0 {
+        println!("\n✓ No differences");
+    }
+}
+
+fn print_ids(ids: &[String], total: usize) {
+    for line in ids.chunks(5) {
+        println!("     {}", line.join(", "));
+    }
+    print_more(ids.len(), total);
+}
+
+fn print_more(shown: usize, total: usize) {
+    if total > shown {
+        println!("     ... and {} more", total - shown);
+    }
+}
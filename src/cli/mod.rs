@@ -1,353 +1,1027 @@
-//! Command-line interface for VecStore
+//! The `vecstore` command-line tool
 //!
-//! Provides a complete CLI for managing vector databases, importing/exporting data,
-//! running queries, and monitoring performance.
-//!
-//! # Commands
-//!
-//! - `info` - Show database information and statistics
-//! - `query` - Search for similar vectors
-//! - `insert` - Add vectors to the database
-//! - `delete` - Remove vectors by ID
-//! - `import` - Import vectors from JSON/CSV
-//! - `export` - Export vectors to JSON/CSV
-//! - `health` - Check database health
-//! - `optimize` - Optimize database performance
-//! - `benchmark` - Run performance benchmarks
-
-use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+//! A single clap command tree: the `vecstore` binary only calls [`run`], and
+//! `vecstore-server` parses its arguments with [`server_args::Args`].
 
-// TODO: Re-enable once API is updated to match current VecStore interface
-// pub mod commands;
+mod commands;
+mod config;
+mod diff;
+pub mod server_args;
+
+use crate::{DuplicateKeep, UpsertPolicy};
+use anyhow::Result;
+use clap::builder::{PossibleValue, PossibleValuesParser};
+use clap::{
+    ArgAction, Command, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum, ValueHint,
+};
+use clap_complete::Shell;
+use std::path::PathBuf;
 
-/// VecStore CLI - High-performance vector database tool
-#[derive(Parser, Debug)]
+#[derive(Parser)]
 #[command(name = "vecstore")]
-#[command(about = "VecStore CLI - Manage vector databases from the command line", long_about = None)]
-#[command(version)]
-pub struct Cli {
-    /// Path to the vector database
-    #[arg(short, long, default_value = "vectors.db")]
-    pub database: PathBuf,
+#[command(version = "1.1.0")]
+#[command(about = "Production-ready vector database CLI", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
 
-    /// Enable verbose output
-    #[arg(short, long)]
-    pub verbose: bool,
+    /// Log progress as a plain line every few seconds instead of drawing
+    /// progress bars
+    #[arg(long, global = true)]
+    quiet: bool,
 
-    #[command(subcommand)]
-    pub command: Commands,
+    /// Config profile to take defaults from (default: VECSTORE_PROFILE, then
+    /// the config file's `default_profile`)
+    #[arg(long, global = true)]
+    profile: Option<String>,
 }
 
-#[derive(Subcommand, Debug)]
-pub enum Commands {
-    /// Show database information and statistics
-    Info {
-        /// Show detailed statistics
+#[derive(Subcommand)]
+enum Commands {
+    /// Initialize a new vector store
+    Init {
+        /// Directory to store data
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+
+        /// Vector dimension
+        #[arg(short = 'D', long)]
+        dimension: Option<usize>,
+
+        /// Encrypt the store at rest with the key in VECSTORE_ENCRYPTION_KEY
+        /// or VECSTORE_ENCRYPTION_PASSPHRASE (needs the `encryption` feature)
+        #[arg(long)]
+        encrypt: bool,
+    },
+
+    /// Ingest a single vector
+    Ingest {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Record ID
         #[arg(short, long)]
-        detailed: bool,
+        id: String,
+        /// Path to vector JSON file (array of f32)
+        #[arg(short, long)]
+        vec: PathBuf,
+        /// Path to metadata JSON file
+        #[arg(short, long)]
+        meta: PathBuf,
+        /// Source text to keep with the record
+        #[arg(long)]
+        text: Option<String>,
+        /// ID of the document the record is a chunk of
+        #[arg(long, requires = "chunk_index")]
+        parent: Option<String>,
+        /// Position among the parent's chunks
+        #[arg(long, requires = "parent")]
+        chunk_index: Option<usize>,
     },
 
-    /// Search for similar vectors
-    Query {
-        /// Query vector as comma-separated floats (e.g., "0.1,0.2,0.3")
+    /// Ingest batch of vectors from JSONL file
+    IngestBatch {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Path to JSONL file
         #[arg(short, long)]
-        vector: String,
+        jsonl: PathBuf,
 
-        /// Number of results to return
-        #[arg(short = 'k', long, default_value = "10")]
-        limit: usize,
+        /// What to do with records whose id already exists
+        #[arg(long, value_enum, default_value = "overwrite")]
+        on_conflict: OnConflict,
+
+        /// Validate the file and report what would change without writing
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Ingest the valid records when some fail validation, instead of
+        /// ingesting nothing
+        #[arg(long)]
+        partial: bool,
+    },
 
-        /// Optional metadata filter (e.g., "category = 'tech'")
+    /// Query the vector store
+    Query {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Path to query vector JSON file
+        #[arg(short, long, required_unless_present = "text", conflicts_with = "text")]
+        vec: Option<PathBuf>,
+        /// Text to embed and search for (needs the `embeddings` feature)
+        #[arg(short, long)]
+        text: Option<String>,
+        /// Embedding model for --text (default: VECSTORE_EMBED_MODEL, else
+        /// the model the store was ingested with)
+        #[arg(long)]
+        embed_model: Option<String>,
+        /// Number of results
+        #[arg(short, long, alias = "limit", default_value = "5")]
+        k: usize,
+        /// Filter expression, e.g. "category = 'tech'" (JSON FilterExpr also accepted)
         #[arg(short, long)]
         filter: Option<String>,
+        /// Include each result's stored text
+        #[arg(long)]
+        show_text: bool,
+        /// Output as JSON
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
+    },
+
+    /// Load documents, split them into chunks, embed and upsert them
+    /// (needs the `embeddings` feature)
+    IngestText {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Document to ingest: Markdown, JSON, CSV or plain text (repeatable)
+        #[arg(short, long, required = true)]
+        file: Vec<PathBuf>,
+        /// Maximum chunk length in characters
+        #[arg(long, default_value = "512")]
+        chunk_size: usize,
+        /// Characters shared by consecutive chunks
+        #[arg(long, default_value = "64")]
+        chunk_overlap: usize,
+        /// Embedding model: a pretrained ONNX model (minilm, all-MiniLM-L12-v2,
+        /// ...), onnx:<dir> or openai:<model> (default: VECSTORE_EMBED_MODEL,
+        /// else the model the store was ingested with)
+        #[arg(long)]
+        embed_model: Option<String>,
+    },
 
-        /// Output format (json, table, simple)
-        #[arg(short, long, default_value = "table")]
-        format: OutputFormat,
+    /// Fetch vectors by ID
+    Get {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Vector ID to fetch (repeat for multiple)
+        #[arg(short, long, required = true)]
+        id: Vec<String>,
+        /// Output as JSON
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
     },
 
-    /// Insert a vector into the database
-    Insert {
-        /// Vector ID
+    /// Show a chunk with its neighbouring chunks from the same parent document
+    ParentWindow {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Chunk ID
         #[arg(short, long)]
         id: String,
+        /// Chunks to show on each side
+        #[arg(short, long, default_value = "1")]
+        n: usize,
+        /// Output as JSON
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
+    },
 
-        /// Vector values as comma-separated floats
+    /// Update a vector's metadata without re-inserting it
+    UpdateMeta {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Vector ID to update
         #[arg(short, long)]
-        vector: String,
+        id: String,
+        /// Field to set as key=value (value parsed as JSON, else string; repeatable)
+        #[arg(short, long, required = true)]
+        set: Vec<String>,
+        /// Replace all existing metadata instead of merging
+        #[arg(long)]
+        replace: bool,
+    },
 
-        /// Metadata as JSON (e.g., '{"category":"tech"}')
+    /// List stored vectors in ID order
+    List {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Filter expression, e.g. "category = 'tech'" (JSON FilterExpr also accepted)
         #[arg(short, long)]
-        metadata: Option<String>,
+        filter: Option<String>,
+        /// Number of matching records to skip
+        #[arg(long, default_value = "0")]
+        offset: usize,
+        /// Maximum number of records to list
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+        /// Show each record's vector, truncated (JSON output always has them)
+        #[arg(long)]
+        show_vectors: bool,
+        /// Output as JSON
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
     },
 
-    /// Delete vectors by ID or filter
-    Delete {
-        /// Vector IDs to delete (comma-separated)
+    /// Show records picked uniformly at random
+    Sample {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Filter expression, e.g. "category = 'tech'" (JSON FilterExpr also accepted)
         #[arg(short, long)]
-        ids: Option<String>,
+        filter: Option<String>,
+        /// Number of records to show
+        #[arg(short, long, default_value = "10")]
+        n: usize,
+        /// Seed for reproducible picks (random when omitted)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Show each record's vector, truncated in the table output
+        #[arg(long)]
+        show_vectors: bool,
+        /// Output as JSON
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
+    },
 
-        /// Delete all vectors matching filter
+    /// Show the first records in ID order
+    Head {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Filter expression, e.g. "category = 'tech'" (JSON FilterExpr also accepted)
         #[arg(short, long)]
         filter: Option<String>,
-
-        /// Confirm deletion without prompt
-        #[arg(short = 'y', long)]
-        yes: bool,
+        /// Number of records to show
+        #[arg(short, long, default_value = "20")]
+        n: usize,
+        /// Show each record's vector, truncated in the table output
+        #[arg(long)]
+        show_vectors: bool,
+        /// Output as JSON
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
     },
 
-    /// Import vectors from a file
-    Import {
-        /// Input file path (JSON, JSONL, or CSV)
+    /// Show the last records in ID order
+    Tail {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+        /// Filter expression, e.g. "category = 'tech'" (JSON FilterExpr also accepted)
         #[arg(short, long)]
-        file: PathBuf,
+        filter: Option<String>,
+        /// Number of records to show
+        #[arg(short, long, default_value = "20")]
+        n: usize,
+        /// Show each record's vector, truncated in the table output
+        #[arg(long)]
+        show_vectors: bool,
+        /// Output as JSON
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
+    },
 
-        /// File format (json, jsonl, csv, auto)
-        #[arg(short = 't', long, default_value = "auto")]
-        format: ImportFormat,
+    /// Show store statistics
+    #[command(alias = "info")]
+    Stats {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
 
-        /// Batch size for import
-        #[arg(short, long, default_value = "1000")]
-        batch_size: usize,
+        /// Show detailed statistics
+        #[arg(long)]
+        detailed: bool,
 
-        /// Skip validation for faster import
+        /// Count records per value of this metadata field
         #[arg(long)]
-        no_validate: bool,
+        group_by: Option<String>,
+
+        /// Only count records matching this filter, e.g. "category = 'tech'"
+        #[arg(short, long)]
+        filter: Option<String>,
+
+        /// Output all statistics as JSON
+        #[arg(long, alias = "json", action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
     },
 
-    /// Export vectors to a file
+    /// Export vectors to various formats
     Export {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+
         /// Output file path
         #[arg(short, long)]
         output: PathBuf,
 
-        /// Export format (json, jsonl, csv)
-        #[arg(short = 't', long, default_value = "json")]
+        /// Export format
+        #[arg(short, long, value_enum, default_value = "jsonl")]
         format: ExportFormat,
 
-        /// Optional filter to limit export
-        #[arg(short, long)]
+        /// Only export records matching this filter, e.g. "category = 'tech'"
+        #[arg(long)]
         filter: Option<String>,
 
-        /// Maximum vectors to export
+        /// Maximum number of records to export
         #[arg(short, long)]
         limit: Option<usize>,
+
+        /// Parquet: one typed `metadata.<key>` column per metadata key instead
+        /// of a JSON `metadata` column
+        #[arg(long)]
+        explode_metadata: bool,
     },
 
-    /// Check database health and get recommendations
-    Health {
-        /// Run detailed health checks
-        #[arg(short, long)]
-        detailed: bool,
+    /// Import vectors from other formats
+    Import {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+
+        /// Input file path
+        #[arg(short, long, alias = "file")]
+        input: PathBuf,
 
-        /// Output format (json, table)
-        #[arg(short, long, default_value = "table")]
-        format: OutputFormat,
+        /// Import format; `auto` goes by the file extension, then its contents
+        #[arg(short, long, value_enum, default_value = "auto")]
+        format: ImportFormat,
+
+        /// What to do with records whose id already exists
+        #[arg(long, value_enum, default_value = "overwrite")]
+        on_conflict: OnConflict,
+
+        /// Validate the file and report what would change without writing
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Import the valid rows and list the rest instead of stopping at the first bad one
+        #[arg(long)]
+        partial: bool,
+
+        /// Records written per batch
+        #[arg(long, default_value = "1000")]
+        batch_size: usize,
+
+        /// CSV column holding the record id
+        #[arg(long, default_value = "id")]
+        id_column: String,
+
+        /// CSV column holding the vector
+        #[arg(long, default_value = "vector")]
+        vector_column: String,
+
+        /// How CSV vectors are written
+        #[arg(long, value_enum, default_value = "auto")]
+        vector_format: CsvVectorFormat,
+
+        /// Separator between vector components with `--vector-format delimited`
+        #[arg(long, default_value = ";")]
+        vector_delimiter: char,
+
+        /// CSV column to import as metadata, as `column` or `column=key` (repeat;
+        /// default: every other column)
+        #[arg(long = "metadata-column")]
+        metadata_columns: Vec<String>,
+
+        /// Text file of ids for a NumPy import, one per row (default: row numbers)
+        #[arg(long)]
+        ids: Option<PathBuf>,
     },
 
-    /// Optimize database performance
-    Optimize {
-        /// Run compaction
-        #[arg(short, long)]
-        compact: bool,
+    /// Migrate from other vector databases
+    Migrate {
+        /// Source database type
+        #[arg(short, long, value_enum)]
+        source: MigrationSource,
+
+        /// Source connection string or file path: for Pinecone, the index
+        /// name or a JSONL or Parquet dump; for Qdrant, the server URL or a
+        /// JSONL dump of points; for ChromaDB, a persistent Chroma directory
+        #[arg(short = 'c', long, value_hint = ValueHint::AnyPath)]
+        source_path: String,
+
+        /// Destination directory; a multi-collection database for ChromaDB
+        #[arg(short, long, default_value = "./data")]
+        dest: PathBuf,
+
+        /// API key (default: PINECONE_API_KEY or QDRANT_API_KEY)
+        #[arg(long)]
+        api_key: Option<String>,
+
+        /// Pinecone namespace to migrate (default: the default namespace)
+        #[arg(long)]
+        namespace: Option<String>,
+
+        /// Collection to migrate (ChromaDB: all of them by default)
+        #[arg(long)]
+        collection: Option<String>,
+
+        /// Qdrant named vector to migrate, for collections with several
+        #[arg(long)]
+        vector_name: Option<String>,
+
+        /// Distance metric of an offline dump; live indexes report their own
+        #[arg(long, default_value = "cosine", value_parser = metric_values(), ignore_case = true)]
+        metric: String,
 
-        /// Rebuild HNSW index
+        /// Checkpoint file to resume from and keep progress in
+        /// (default: <dest>.checkpoint.json)
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+    },
+
+    /// Backup the vector store
+    Backup {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+
+        /// Backup file path
         #[arg(short, long)]
-        rebuild_index: bool,
+        output: PathBuf,
+
+        /// Compress backup with zstd
+        #[arg(long)]
+        compress: bool,
+
+        /// Only write changes since the backup given by --since
+        #[arg(long, requires = "since")]
+        incremental: bool,
 
-        /// Analyze and suggest optimizations
+        /// Previous backup (full or incremental) to continue the chain from
+        #[arg(long, requires = "incremental")]
+        since: Option<PathBuf>,
+    },
+
+    /// Restore from backup
+    Restore {
+        /// Backup file path; for a chain, the full backup followed by its
+        /// incrementals in order
+        #[arg(short, long, num_args = 1.., required = true)]
+        backup: Vec<PathBuf>,
+
+        /// Destination directory
+        #[arg(short, long, default_value = "./data")]
+        dest: PathBuf,
+    },
+
+    /// Show what a backup contains without restoring it
+    BackupInfo {
+        /// Backup file path
         #[arg(short, long)]
+        backup: PathBuf,
+    },
+
+    /// Optimize the index
+    Optimize {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+
+        /// Force full rebuild
+        #[arg(long)]
+        rebuild: bool,
+
+        /// Measure the store and print recommendations instead of rebuilding
+        #[arg(long, conflicts_with = "rebuild")]
         analyze: bool,
+
+        /// Apply the safe recommendations (default ef_search, compaction)
+        #[arg(long, requires = "analyze")]
+        apply: bool,
+
+        /// Output the recommendations as JSON
+        #[arg(long, alias = "json", requires = "analyze", action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
     },
 
-    /// Run performance benchmarks
+    /// Benchmark search performance
     Benchmark {
-        /// Number of vectors for benchmark
-        #[arg(short, long, default_value = "10000")]
-        vectors: usize,
-
-        /// Vector dimensions
-        #[arg(short, long, default_value = "128")]
-        dimensions: usize,
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
 
         /// Number of queries to run
         #[arg(short, long, default_value = "1000")]
         queries: usize,
 
-        /// Output format (json, table)
-        #[arg(short = 'f', long, default_value = "table")]
-        format: OutputFormat,
+        /// Number of results per query
+        #[arg(short, long, default_value = "10")]
+        k: usize,
+
+        /// HNSW candidate list size (higher = better recall, slower)
+        #[arg(long)]
+        ef_search: Option<usize>,
+
+        /// Measure recall@k over a range of ef_search values on sampled
+        /// stored vectors and suggest an ef_search
+        #[arg(long)]
+        recall: bool,
+
+        /// Recall@k the suggested ef_search has to reach (with --recall)
+        #[arg(long, default_value = "0.95")]
+        target_recall: f64,
+
+        /// Run the synthetic suite instead: filtered queries at several
+        /// selectivities, recall@k, concurrent queries on 1, 4 and 16
+        /// threads and a 90/10 read/write mix, on generated data rather
+        /// than the store in --dir
+        #[arg(long)]
+        suite: bool,
+
+        /// Vectors the suite generates
+        #[arg(long, default_value = "10000")]
+        vectors: usize,
+
+        /// Dimension of the vectors the suite generates
+        #[arg(long, default_value = "128")]
+        dimension: usize,
+
+        /// Seed for the suite's data and workloads; runs with the same seed
+        /// are comparable
+        #[arg(long, default_value = "42")]
+        seed: u64,
+
+        /// Output the suite's results as JSON
+        #[arg(long, alias = "json", action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
+
+        /// Run the suite and save its results, with this machine's CPU and
+        /// core count, as a baseline for --compare
+        #[arg(long, value_name = "FILE")]
+        save_baseline: Option<PathBuf>,
+
+        /// Run the suite and compare it with a saved baseline; exits 1 if
+        /// any scenario regressed beyond the tolerances
+        #[arg(long, value_name = "FILE")]
+        compare: Option<PathBuf>,
+
+        /// Percent increase in p50 latency --compare tolerates
+        #[arg(long, default_value = "30")]
+        tolerance_p50: f64,
+
+        /// Percent increase in p99 latency --compare tolerates
+        #[arg(long, default_value = "50")]
+        tolerance_p99: f64,
+
+        /// Percent drop in throughput --compare tolerates
+        #[arg(long, default_value = "30")]
+        tolerance_throughput: f64,
     },
 
-    /// List all vectors or filter by criteria
-    List {
-        /// Filter expression
+    /// Check index consistency and data quality; exits 1 on warnings and
+    /// 2 on critical findings
+    Health {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+
+        /// Output the report as JSON
+        #[arg(long, alias = "json", action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
+    },
+
+    /// Collection management commands
+    #[command(subcommand)]
+    Collection(CollectionCommands),
+
+    /// Delete vectors by ID or filter
+    Delete {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+
+        /// Vector ID to delete
+        #[arg(short, long)]
+        id: Option<String>,
+
+        /// Filter expression to delete matching vectors, e.g. "status = 'stale'"
         #[arg(short, long)]
         filter: Option<String>,
 
-        /// Maximum vectors to list
-        #[arg(short, long, default_value = "100")]
-        limit: usize,
+        /// File of IDs to delete, one per line, or `-` to read them from stdin
+        ///
+        /// IDs that aren't in the store are counted and skipped.
+        #[arg(long, conflicts_with_all = ["id", "filter"], value_hint = ValueHint::FilePath)]
+        ids_file: Option<PathBuf>,
 
-        /// Show vector values
+        /// Delete the vectors matching --filter without asking first
         #[arg(short, long)]
-        show_vectors: bool,
+        yes: bool,
+    },
+
+    /// Compare two stores, or a store and a JSONL export
+    ///
+    /// Reports ids added and removed from --a to --b, and for the ids both
+    /// have, metadata differences and vectors that drifted.
+    Diff {
+        /// Old side: a store directory or a JSONL export
+        #[arg(long)]
+        a: PathBuf,
+
+        /// New side: a store directory or a JSONL export
+        #[arg(long)]
+        b: PathBuf,
+
+        /// Cosine similarity below which a vector counts as drifted
+        #[arg(long, default_value = "0.99")]
+        threshold: f32,
+
+        /// Compare metadata and vectors for a random sample of this many
+        /// shared ids instead of all of them
+        #[arg(long)]
+        sample: Option<usize>,
+
+        /// Seed for --sample
+        #[arg(long, default_value = "42")]
+        seed: u64,
+
+        /// Ids to list per category
+        #[arg(long, default_value = "20")]
+        show: usize,
+
+        /// Output as JSON
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
 
-        /// Output format (json, table, simple)
-        #[arg(short = 'f', long, default_value = "table")]
-        format: OutputFormat,
+        /// Deprecated spelling of --json-out
+        #[arg(long, value_enum, hide = true)]
+        format: Option<DiffFormat>,
+    },
+
+    /// Find groups of near-duplicate vectors, and optionally remove all but
+    /// one record of each
+    ///
+    /// Each vector is looked up in the index to find its candidates, which
+    /// are then confirmed by their exact cosine similarity.
+    Dedupe {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+
+        /// Cosine similarity at or above which two vectors are duplicates
+        #[arg(long, default_value = "0.98")]
+        threshold: f32,
+
+        /// Remove every record of a group but the one this keeps
+        #[arg(long, value_enum)]
+        delete: Option<DedupeKeep>,
+
+        /// Report what --delete would remove without removing it; pass
+        /// --dry-run=false to remove
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = true, default_missing_value = "true")]
+        dry_run: bool,
+
+        /// Output the groups as JSON
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
+    },
+
+    /// Check that the store's files are consistent with each other
+    ///
+    /// Exits non-zero if any check fails.
+    Validate {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+
+        /// On failure, drop records that can't be loaded and orphaned index
+        /// entries, rebuild the index from the records and check again
+        #[arg(long)]
+        repair: bool,
+
+        /// Output the report as JSON
+        #[arg(long, action = ArgAction::Set, num_args = 0..=1, require_equals = true, default_value_t = false, default_missing_value = "true")]
+        json_out: bool,
+    },
+
+    /// Compact the store (remove deleted vectors)
+    Compact {
+        /// Directory containing the store
+        #[arg(short, long, default_value = "./data")]
+        dir: PathBuf,
+    },
+
+    /// Manage the config file and its profiles
+    #[command(subcommand)]
+    Config(ConfigCommands),
+
+    /// Print a shell completion script
+    ///
+    /// For example, `vecstore completions bash > /etc/bash_completion.d/vecstore`
+    Completions {
+        /// Shell to generate the script for
+        #[arg(value_enum)]
+        shell: Shell,
+
+        /// Complete vecstore-server's arguments instead
+        #[arg(long)]
+        server: bool,
+    },
+
+    /// Print the man page in roff format
+    ///
+    /// For example, `vecstore manpage > /usr/local/share/man/man1/vecstore.1`
+    Manpage {
+        /// Print vecstore-server's man page instead
+        #[arg(long)]
+        server: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Write a commented config file to ./vecstore.toml
+    Init {
+        /// Write the user config file (~/.config/vecstore/config.toml) instead
+        #[arg(long)]
+        global: bool,
+
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Show the config file, the profile in use and each setting's value
+    Show,
+
+    /// Set a setting of the profile in use, or `default_profile`
+    Set {
+        /// dir, output, embed_model, server_url or default_profile
+        key: String,
+
+        value: String,
+
+        /// Write the user config file instead of the one in use
+        #[arg(long)]
+        global: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CollectionCommands {
+    /// List all collections
+    List {
+        /// Database directory
+        #[arg(short, long, default_value = "./db")]
+        dir: PathBuf,
     },
 
     /// Create a new collection
-    CreateCollection {
+    Create {
+        /// Database directory
+        #[arg(short, long, default_value = "./db")]
+        dir: PathBuf,
+
         /// Collection name
+        #[arg(short, long)]
         name: String,
 
-        /// Vector dimensions
-        #[arg(short, long)]
-        dimensions: usize,
+        /// Vector dimension
+        #[arg(short = 'D', long)]
+        dimension: usize,
 
-        /// Distance metric (cosine, euclidean, dot)
-        #[arg(short = 'm', long, default_value = "cosine")]
+        /// Distance metric
+        #[arg(short = 'm', long, default_value = "cosine", value_parser = metric_values(), ignore_case = true)]
         metric: String,
     },
 
     /// Drop a collection
-    DropCollection {
+    Drop {
+        /// Database directory
+        #[arg(short, long, default_value = "./db")]
+        dir: PathBuf,
+
         /// Collection name
+        #[arg(short, long)]
         name: String,
 
-        /// Confirm deletion without prompt
-        #[arg(short = 'y', long)]
-        yes: bool,
+        /// Also drop aliases pointing at the collection
+        #[arg(long)]
+        force: bool,
     },
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum OutputFormat {
-    Json,
-    Table,
-    Simple,
-}
+    /// Point an alias at a collection, creating it or swapping its target
+    Alias {
+        /// Database directory
+        #[arg(short, long, default_value = "./db")]
+        dir: PathBuf,
 
-impl std::str::FromStr for OutputFormat {
-    type Err = String;
+        /// Alias name
+        #[arg(short, long)]
+        alias: String,
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "json" => Ok(OutputFormat::Json),
-            "table" => Ok(OutputFormat::Table),
-            "simple" => Ok(OutputFormat::Simple),
-            _ => Err(format!("Invalid format: {}. Use json, table, or simple", s)),
-        }
-    }
+        /// Collection the alias resolves to
+        #[arg(short, long)]
+        collection: String,
+    },
+
+    /// Remove an alias
+    Unalias {
+        /// Database directory
+        #[arg(short, long, default_value = "./db")]
+        dir: PathBuf,
+
+        /// Alias name
+        #[arg(short, long)]
+        alias: String,
+    },
+
+    /// Show collection info
+    Info {
+        /// Database directory
+        #[arg(short, long, default_value = "./db")]
+        dir: PathBuf,
+
+        /// Collection name
+        #[arg(short, long)]
+        name: String,
+    },
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ImportFormat {
-    Json,
+#[derive(ValueEnum, Clone, Copy)]
+enum ExportFormat {
     Jsonl,
     Csv,
-    Auto,
+    Parquet,
+    Npy,
 }
 
-impl std::str::FromStr for ImportFormat {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "json" => Ok(ImportFormat::Json),
-            "jsonl" => Ok(ImportFormat::Jsonl),
-            "csv" => Ok(ImportFormat::Csv),
-            "auto" => Ok(ImportFormat::Auto),
-            _ => Err(format!(
-                "Invalid format: {}. Use json, jsonl, csv, or auto",
-                s
-            )),
-        }
-    }
+#[derive(Debug, ValueEnum, Clone, Copy, PartialEq, Eq)]
+enum ImportFormat {
+    Auto,
+    Jsonl,
+    Csv,
+    Npy,
+    Parquet,
+    Pinecone,
+    Weaviate,
+    Qdrant,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ExportFormat {
+#[derive(Debug, ValueEnum, Clone, Copy)]
+enum CsvVectorFormat {
+    /// A JSON array, or components separated by `;`
+    Auto,
+    /// A JSON array
     Json,
-    Jsonl,
-    Csv,
+    /// Components separated by `--vector-delimiter`
+    Delimited,
 }
 
-impl std::str::FromStr for ExportFormat {
-    type Err = String;
+#[derive(Debug, ValueEnum, Clone, Copy)]
+enum DedupeKeep {
+    /// Keep the oldest record of each group
+    KeepFirst,
+    /// Keep the most recently written record of each group
+    KeepNewest,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "json" => Ok(ExportFormat::Json),
-            "jsonl" => Ok(ExportFormat::Jsonl),
-            "csv" => Ok(ExportFormat::Csv),
-            _ => Err(format!("Invalid format: {}. Use json, jsonl, or csv", s)),
+impl From<DedupeKeep> for DuplicateKeep {
+    fn from(keep: DedupeKeep) -> Self {
+        match keep {
+            DedupeKeep::KeepFirst => DuplicateKeep::First,
+            DedupeKeep::KeepNewest => DuplicateKeep::Newest,
         }
     }
 }
 
-/// Vector data for import/export
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct VectorData {
-    pub id: String,
-    pub vector: Vec<f32>,
-    #[serde(default)]
-    pub metadata: serde_json::Value,
+#[derive(ValueEnum, Clone, Copy)]
+enum DiffFormat {
+    Table,
+    Json,
+}
+
+#[derive(Debug, ValueEnum, Clone, Copy)]
+enum OnConflict {
+    Overwrite,
+    Skip,
+    Error,
+    MergeMetadata,
 }
 
-/// Helper to parse comma-separated floats
-pub fn parse_vector(s: &str) -> Result<Vec<f32>> {
-    s.split(',')
-        .map(|v| {
-            v.trim()
-                .parse::<f32>()
-                .context(format!("Invalid float value: {}", v))
-        })
-        .collect()
+impl From<OnConflict> for UpsertPolicy {
+    fn from(on_conflict: OnConflict) -> Self {
+        match on_conflict {
+            OnConflict::Overwrite => UpsertPolicy::Overwrite,
+            OnConflict::Skip => UpsertPolicy::Skip,
+            OnConflict::Error => UpsertPolicy::ErrorOnConflict,
+            OnConflict::MergeMetadata => UpsertPolicy::MergeMetadata,
+        }
+    }
 }
 
-/// Helper to parse comma-separated IDs
-pub fn parse_ids(s: &str) -> Vec<String> {
-    s.split(',').map(|id| id.trim().to_string()).collect()
+#[derive(Debug, ValueEnum, Clone, Copy)]
+enum MigrationSource {
+    Pinecone,
+    Weaviate,
+    Qdrant,
+    #[value(name = "chromadb")]
+    ChromaDB,
+    Milvus,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Metric names `Distance::from_str` accepts, for `--metric` completions
+fn metric_values() -> PossibleValuesParser {
+    PossibleValuesParser::new([
+        PossibleValue::new("cosine"),
+        PossibleValue::new("euclidean").alias("l2"),
+        PossibleValue::new("dot").alias("dotproduct"),
+        PossibleValue::new("manhattan").alias("l1"),
+        PossibleValue::new("hamming"),
+        PossibleValue::new("jaccard"),
+        PossibleValue::new("chebyshev").aliases(["linf", "l_inf"]),
+        PossibleValue::new("canberra"),
+        PossibleValue::new("braycurtis").aliases(["bray-curtis", "bray_curtis"]),
+    ])
+}
 
-    #[test]
-    fn test_parse_vector() {
-        let vec = parse_vector("0.1,0.2,0.3").unwrap();
-        assert_eq!(vec, vec![0.1, 0.2, 0.3]);
+/// Parse the command line, with defaults from the config file, and run the
+/// command
+pub fn run() -> Result<()> {
+    // Logs stay on stderr, out of `--json` output, and quiet unless asked for
+    crate::init_tracing_at("warn");
 
-        let vec = parse_vector("1, 2, 3").unwrap();
-        assert_eq!(vec, vec![1.0, 2.0, 3.0]);
+    // A broken config file only stops commands other than `config init`,
+    // which can replace it
+    let (config, config_error) = match config::Config::load(config::profile_arg()) {
+        Ok(config) => (config, None),
+        Err(e) => (config::Config::default(), Some(e)),
+    };
+    warn_deprecated(std::env::args().skip(1));
+    let command = config.apply(with_deprecated_aliases(Cli::command()))?;
+    let matches = command.get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    match (&cli.command, config_error) {
+        (
+            Commands::Config(ConfigCommands::Init { .. })
+            | Commands::Completions { .. }
+            | Commands::Manpage { .. },
+            _,
+        ) => {}
+        (_, Some(e)) => return Err(e),
+        (Commands::Config(_), None) => {}
+        (_, None) => config.check_profile()?,
     }
+    commands::execute(cli, &config)
+}
+
+/// Spellings from the older CLIs that still parse, as hidden aliases, for
+/// one release: the subcommand they belong to (`None` for any), the old
+/// spelling and its replacement
+const DEPRECATED_FLAGS: [(Option<&str>, &str, &str); 4] = [
+    (None, "--database", "--dir"),
+    (Some("query"), "--limit", "-k"),
+    (Some("import"), "--file", "--input"),
+    (Some("diff"), "--format", "--json-out"),
+];
 
-    #[test]
-    fn test_parse_ids() {
-        let ids = parse_ids("doc1,doc2,doc3");
-        assert_eq!(ids, vec!["doc1", "doc2", "doc3"]);
+/// `info`, now `stats`
+const DEPRECATED_SUBCOMMANDS: [(&str, &str); 1] = [("info", "stats")];
 
-        let ids = parse_ids("doc1, doc2 , doc3 ");
-        assert_eq!(ids, vec!["doc1", "doc2", "doc3"]);
+/// Accept `--database` wherever a command takes `--dir`
+fn with_deprecated_aliases(mut command: Command) -> Command {
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .filter(|sub| sub.get_arguments().any(|arg| arg.get_id() == "dir"))
+        .map(|sub| sub.get_name().to_string())
+        .collect();
+    for name in subcommands {
+        command =
+            command.mut_subcommand(name, |sub| sub.mut_arg("dir", |arg| arg.alias("database")));
     }
+    command
+}
 
-    #[test]
-    fn test_output_format() {
-        assert_eq!("json".parse::<OutputFormat>().unwrap(), OutputFormat::Json);
-        assert_eq!(
-            "table".parse::<OutputFormat>().unwrap(),
-            OutputFormat::Table
-        );
-        assert_eq!(
-            "simple".parse::<OutputFormat>().unwrap(),
-            OutputFormat::Simple
-        );
+/// Print a warning to stderr for each deprecated spelling on the command line
+fn warn_deprecated(args: impl Iterator<Item = String>) {
+    let mut args = args.take_while(|arg| arg != "--");
+    let mut subcommand: Option<String> = None;
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            args.next();
+            continue;
+        }
+        if !arg.starts_with('-') {
+            if subcommand.is_none() {
+                if let Some((_, new)) = DEPRECATED_SUBCOMMANDS.iter().find(|(old, _)| *old == arg) {
+                    eprintln!(
+                        "warning: `{}` is deprecated and will be removed in the next release; use `{}`",
+                        arg, new
+                    );
+                }
+                subcommand = Some(arg);
+            }
+            continue;
+        }
+        let flag = arg.split('=').next().unwrap_or_default();
+        for (only_for, old, new) in DEPRECATED_FLAGS {
+            if flag == old && only_for.is_none_or(|name| subcommand.as_deref() == Some(name)) {
+                eprintln!(
+                    "warning: `{}` is deprecated and will be removed in the next release; use `{}`",
+                    old, new
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,209 @@
+//! `vecstore-server` arguments, shared with `vecstore completions --server`
+//! and `vecstore manpage --server`
+
+use clap::{Parser, ValueHint};
+
+#[derive(Parser, Debug)]
+#[command(name = "vecstore-server")]
+#[command(about = "VecStore multi-protocol server", long_about = None)]
+pub struct Args {
+    /// Path to the vector database
+    #[arg(short, long, default_value = "vecstore.db", value_hint = ValueHint::AnyPath)]
+    pub db_path: String,
+
+    /// gRPC server port
+    #[arg(long, default_value = "50051")]
+    pub grpc_port: u16,
+
+    /// HTTP/REST server port
+    #[arg(long, default_value = "8080")]
+    pub http_port: u16,
+
+    /// Vector dimension (required for new databases)
+    #[arg(long)]
+    pub dimension: Option<usize>,
+
+    /// Enable debug logging
+    #[arg(long)]
+    pub debug: bool,
+
+    /// Write logs as JSON lines, one object per event with its request's
+    /// id, route, status and latency
+    #[arg(long)]
+    pub log_json: bool,
+
+    /// Disable gRPC server
+    #[arg(long)]
+    pub no_grpc: bool,
+
+    /// Disable HTTP server
+    #[arg(long)]
+    pub no_http: bool,
+
+    /// Enable multi-tenant namespace mode (uses NamespaceManager)
+    #[arg(long)]
+    pub namespaces: bool,
+
+    /// Namespace root directory (only with --namespaces)
+    #[arg(long, default_value = "./namespaces", value_hint = ValueHint::DirPath)]
+    pub namespace_root: String,
+
+    /// Tombstone expired (TTL) records every N seconds (single-tenant mode)
+    #[arg(long)]
+    pub expiry_sweep_secs: Option<u64>,
+
+    /// Stream store changes over /ws/events and the SubscribeEvents RPC (single-tenant mode)
+    #[arg(long)]
+    pub events: bool,
+
+    /// Log queries slower than this many milliseconds to /v1/slow-queries (single-tenant mode)
+    #[arg(long)]
+    pub slow_query_ms: Option<u64>,
+
+    /// Largest body /v1/import and the ImportStream RPC accept, in bytes
+    #[arg(long, default_value = "1073741824")]
+    pub import_max_bytes: u64,
+
+    /// Most rows a single import may contain
+    #[arg(long, default_value = "10000000")]
+    pub import_max_rows: usize,
+
+    /// Most records a batch upsert, or ids a bulk delete, may list (single-tenant mode)
+    #[arg(long, default_value = "10000")]
+    pub max_batch_size: usize,
+
+    /// Most records a filter delete may remove without `force` (single-tenant mode)
+    #[arg(long, default_value = "10000")]
+    pub max_delete_matches: usize,
+
+    /// Fail queries that don't set `timeout_ms` after this many milliseconds
+    #[arg(long, default_value = "30000")]
+    pub query_timeout_ms: u64,
+
+    /// Longest `timeout_ms` a query may ask for
+    #[arg(long, default_value = "300000")]
+    pub max_query_timeout_ms: u64,
+
+    /// Close a /ws/query-stream connection that sends nothing, pongs
+    /// included, for this many seconds
+    #[arg(long, value_name = "SECS", default_value = "60")]
+    pub ws_idle_timeout_secs: u64,
+
+    /// Queries one /ws/query-stream connection may run at once
+    #[arg(long, default_value = "4")]
+    pub ws_max_queries: usize,
+
+    /// Standing queries one /ws/query-stream connection may subscribe
+    #[arg(long, default_value = "16")]
+    pub ws_max_subscriptions: usize,
+
+    /// Embed texts for /v1/embed and /v1/query/text with a local ONNX model
+    /// (`onnx`, needs the `server-onnx` feature) or an OpenAI-compatible
+    /// endpoint (`openai`, needs `server-openai`) (single-tenant mode)
+    #[arg(long, value_parser = ["onnx", "openai"])]
+    pub embedder: Option<String>,
+
+    /// Embedding model: a pretrained ONNX model name or directory, or the
+    /// endpoint's model name (default: all-MiniLM-L6-v2 / text-embedding-3-small)
+    #[arg(long)]
+    pub embed_model: Option<String>,
+
+    /// Base URL of the OpenAI-compatible embedding endpoint
+    #[arg(long, default_value = "https://api.openai.com/v1", value_hint = ValueHint::Url)]
+    pub embed_url: String,
+
+    /// Environment variable holding the embedding endpoint's API key
+    #[arg(long, default_value = "OPENAI_API_KEY")]
+    pub embed_api_key_env: String,
+
+    /// Most texts one /v1/embed request may list
+    #[arg(long, default_value = "64")]
+    pub embed_max_batch: usize,
+
+    /// Save the store every SECS seconds when it has unsaved changes
+    /// (single-tenant mode; POST /admin/save saves on demand)
+    #[arg(long, value_name = "SECS")]
+    pub auto_save_every: Option<u64>,
+
+    /// Serve a read-only copy of the server whose HTTP API is at URL,
+    /// refusing writes (single-tenant mode)
+    #[arg(long, value_name = "URL", value_hint = ValueHint::Url)]
+    pub replica_of: Option<String>,
+
+    /// Seconds between a replica's polls of its primary
+    #[arg(long, value_name = "SECS", default_value = "5")]
+    pub replica_poll_secs: u64,
+
+    /// Environment variable holding the admin API key a replica presents
+    /// to its primary
+    #[arg(long, default_value = "VECSTORE_PRIMARY_API_KEY")]
+    pub replica_api_key_env: String,
+
+    /// Origins browsers may call the HTTP API from, comma-separated;
+    /// `host:*` matches any port and `*` any origin
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "http://localhost:*,http://127.0.0.1:*,http://[::1]:*"
+    )]
+    pub cors_origins: Vec<String>,
+
+    /// Methods cross-origin requests may use, comma-separated (`*`: any)
+    #[arg(long, value_delimiter = ',', default_value = "GET,POST,PUT,DELETE")]
+    pub cors_methods: Vec<String>,
+
+    /// Headers cross-origin requests may send, comma-separated (`*`: any)
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "content-type,authorization,x-api-key,if-match"
+    )]
+    pub cors_headers: Vec<String>,
+
+    /// Largest HTTP request body accepted, in bytes (/v1/import has
+    /// --import-max-bytes)
+    #[arg(long, default_value = "16777216")]
+    pub max_body_bytes: usize,
+
+    /// Send HTTP responses uncompressed even when the client accepts gzip or brotli
+    #[arg(long)]
+    pub no_compression: bool,
+
+    /// Serve Swagger UI for the OpenAPI document (/openapi.json) at /docs
+    #[arg(long)]
+    pub swagger_ui: bool,
+
+    /// Seconds to let in-flight requests finish after SIGINT/SIGTERM before saving and exiting
+    #[arg(long, default_value = "30")]
+    pub shutdown_timeout_secs: u64,
+
+    /// JSON file of global and per-namespace rate limits (adjustable at
+    /// runtime through PUT /v1/limits or /admin/limits)
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub limits: Option<String>,
+
+    /// Requests per second each client may send (overrides the limits file)
+    #[arg(long)]
+    pub rate_limit: Option<f64>,
+
+    /// Requests a client may send at once above --rate-limit
+    #[arg(long)]
+    pub rate_burst: Option<u32>,
+
+    /// Queries in flight at once, across clients
+    #[arg(long)]
+    pub max_concurrent_queries: Option<usize>,
+
+    /// JSON file of hashed API keys to require (default: the
+    /// VECSTORE_API_KEYS variable; without either the server is open)
+    #[arg(long, value_hint = ValueHint::FilePath)]
+    pub api_keys: Option<String>,
+
+    /// Serve /health, /ready, /metrics and the HealthCheck RPC without a key
+    #[arg(long)]
+    pub public_health: bool,
+
+    /// Read an API key from stdin, print its hash for the keys file and exit
+    #[arg(long)]
+    pub hash_api_key: bool,
+}
@@ -39,7 +39,7 @@
 use crate::error::Result;
 use crate::namespace::{Namespace, NamespaceId, NamespaceQuotas};
 use crate::namespace_manager::{NamespaceManager, NamespaceStats};
-use crate::store::{Config, Distance, Metadata, Neighbor, Query};
+use crate::store::{Config, Distance, HnswConfig, Metadata, Neighbor, Query, Record};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
@@ -77,6 +77,9 @@ pub struct VecDatabase {
 impl VecDatabase {
     /// Open or create a database at the specified path
     ///
+    /// Collections saved by earlier sessions are loaded with their
+    /// configuration.
+    ///
     /// # Example
     /// ```no_run
     /// use vecstore::VecDatabase;
@@ -87,6 +90,9 @@ impl VecDatabase {
         let root = path.as_ref().to_path_buf();
         let manager = NamespaceManager::new(&root)
             .map_err(|e| crate::error::VecStoreError::Other(e.to_string()))?;
+        manager
+            .load_namespaces()
+            .map_err(|e| crate::error::VecStoreError::Other(e.to_string()))?;
         Ok(Self {
             manager: Arc::new(RwLock::new(manager)),
             root,
@@ -120,7 +126,8 @@ impl VecDatabase {
     /// use vecstore::{VecDatabase, CollectionConfig, Distance};
     /// let mut db = VecDatabase::open("./db")?;
     /// let config = CollectionConfig::default()
-    ///     .with_distance(Distance::Manhattan)
+    ///     .with_dimension(384)
+    ///     .with_distance(Distance::Euclidean)
     ///     .with_max_vectors(100_000);
     /// let collection = db.create_collection_with_config("documents", config)?;
     /// # Ok::<(), anyhow::Error>(())
@@ -139,7 +146,12 @@ impl VecDatabase {
         {
             let manager = self.manager.read().unwrap();
             manager
-                .create_namespace(namespace_id.clone(), description, Some(config.quotas))
+                .create_namespace_with_config(
+                    namespace_id.clone(),
+                    description,
+                    Some(config.quotas),
+                    config.store_config.clone(),
+                )
                 .map_err(|e| crate::error::VecStoreError::Other(e.to_string()))?;
         }
 
@@ -153,7 +165,8 @@ impl VecDatabase {
 
     /// Get an existing collection
     ///
-    /// Returns None if the collection doesn't exist.
+    /// `name` may be an alias, in which case the collection it currently
+    /// points at is returned. Returns None if the collection doesn't exist.
     ///
     /// # Example
     /// ```no_run
@@ -165,18 +178,22 @@ impl VecDatabase {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn get_collection(&self, name: &str) -> Result<Option<Collection>> {
-        let namespace_id: NamespaceId = name.to_string();
-
         let manager = self.manager.read().unwrap();
+        let namespace_id: NamespaceId = manager.resolve(name);
+
         // Check if namespace exists
         match manager.get_namespace(&namespace_id) {
-            Ok(_) => Ok(Some(Collection {
-                name: name.to_string(),
-                namespace_id,
-                manager: Arc::clone(&self.manager),
-                // Config uses default for now - persistence can be added when needed
-                config: Config::default(),
-            })),
+            Ok(_) => {
+                let config = manager
+                    .get_config(&namespace_id)
+                    .map_err(|e| crate::error::VecStoreError::Other(e.to_string()))?;
+                Ok(Some(Collection {
+                    name: namespace_id.clone(),
+                    namespace_id,
+                    manager: Arc::clone(&self.manager),
+                    config,
+                }))
+            }
             Err(_) => Ok(None),
         }
     }
@@ -201,7 +218,10 @@ impl VecDatabase {
 
     /// Delete a collection
     ///
-    /// This permanently deletes the collection and all its data.
+    /// This permanently deletes the collection and all its data. Fails while
+    /// aliases point at the collection; use
+    /// [`delete_collection_force`](Self::delete_collection_force) to drop
+    /// them along with it.
     ///
     /// # Example
     /// ```no_run
@@ -218,6 +238,72 @@ impl VecDatabase {
             .map_err(|e| crate::error::VecStoreError::Other(e.to_string()))
     }
 
+    /// Delete a collection and every alias pointing at it
+    pub fn delete_collection_force(&mut self, name: &str) -> Result<()> {
+        let namespace_id: NamespaceId = name.to_string();
+        let manager = self.manager.read().unwrap();
+        manager
+            .delete_namespace_force(&namespace_id)
+            .map_err(|e| crate::error::VecStoreError::Other(e.to_string()))
+    }
+
+    /// Create an alias that resolves to the collection `target`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use vecstore::VecDatabase;
+    /// let mut db = VecDatabase::open("./db")?;
+    /// db.create_collection("products_v1")?;
+    /// db.create_alias("products", "products_v1")?;
+    /// let products = db.get_collection("products")?; // products_v1
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn create_alias(&self, alias: &str, target: &str) -> Result<()> {
+        let manager = self.manager.read().unwrap();
+        manager
+            .create_alias(alias, &target.to_string())
+            .map_err(|e| crate::error::VecStoreError::Other(e.to_string()))
+    }
+
+    /// Atomically repoint an alias at another collection
+    ///
+    /// Used for blue/green reindexing: build `products_v2` next to
+    /// `products_v1`, then swap the alias. Lookups through the alias resolve
+    /// to one collection or the other at every instant, and the new target
+    /// is persisted before it becomes visible. `Collection` handles obtained
+    /// earlier keep pointing at the collection they resolved to.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use vecstore::VecDatabase;
+    /// let mut db = VecDatabase::open("./db")?;
+    /// db.create_collection("products_v2")?;
+    /// // ... fill products_v2 with the new embeddings ...
+    /// db.update_alias("products", "products_v2")?;
+    /// db.delete_collection("products_v1")?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn update_alias(&self, alias: &str, target: &str) -> Result<()> {
+        let manager = self.manager.read().unwrap();
+        manager
+            .update_alias(alias, &target.to_string())
+            .map_err(|e| crate::error::VecStoreError::Other(e.to_string()))
+    }
+
+    /// Remove an alias, leaving its collection untouched
+    pub fn delete_alias(&self, alias: &str) -> Result<()> {
+        let manager = self.manager.read().unwrap();
+        manager
+            .delete_alias(alias)
+            .map_err(|e| crate::error::VecStoreError::Other(e.to_string()))
+    }
+
+    /// List aliases as `(alias, collection)` pairs, sorted by alias
+    pub fn list_aliases(&self) -> Result<Vec<(String, String)>> {
+        let manager = self.manager.read().unwrap();
+        Ok(manager.list_aliases())
+    }
+
     /// Get statistics for all collections
     ///
     /// # Example
@@ -247,7 +333,9 @@ pub struct CollectionConfig {
     /// Resource quotas for the collection
     pub quotas: NamespaceQuotas,
 
-    /// Vector store configuration (distance metric, HNSW params)
+    /// Vector store configuration (dimension, distance metric, HNSW params)
+    ///
+    /// Saved with the collection and read back by `VecDatabase::get_collection`.
     pub store_config: Config,
 }
 
@@ -270,6 +358,12 @@ impl CollectionConfig {
         self
     }
 
+    /// Fix the vector dimension; upserts of any other length are rejected
+    pub fn with_dimension(mut self, dimension: usize) -> Self {
+        self.store_config.dimension = Some(dimension);
+        self
+    }
+
     /// Set distance metric
     pub fn with_distance(mut self, metric: Distance) -> Self {
         self.store_config.distance = metric;
@@ -278,13 +372,19 @@ impl CollectionConfig {
 
     /// Set HNSW M parameter
     pub fn with_hnsw_m(mut self, m: usize) -> Self {
-        self.store_config.hnsw_m = m;
+        self.store_config.hnsw.m = m;
         self
     }
 
     /// Set HNSW ef_construction parameter
     pub fn with_hnsw_ef_construction(mut self, ef: usize) -> Self {
-        self.store_config.hnsw_ef_construction = ef;
+        self.store_config.hnsw.ef_construction = ef;
+        self
+    }
+
+    /// Set all HNSW construction parameters
+    pub fn with_hnsw_config(mut self, config: HnswConfig) -> Self {
+        self.store_config.hnsw = config;
         self
     }
 }
@@ -344,10 +444,59 @@ impl Collection {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn upsert(&mut self, id: String, vector: Vec<f32>, metadata: Metadata) -> Result<()> {
+        if let Some(expected) = self.config.dimension {
+            if vector.len() != expected {
+                return Err(crate::error::VecStoreError::CollectionDimensionMismatch {
+                    collection: self.name.clone(),
+                    expected,
+                    actual: vector.len(),
+                });
+            }
+        }
+
         let manager = self.manager.read().unwrap();
         manager
             .upsert(&self.namespace_id, id, vector, metadata)
-            .map_err(|e| crate::error::VecStoreError::Other(e.to_string()))
+            .map_err(|e| {
+                crate::error::VecStoreError::Other(format!("Collection '{}': {}", self.name, e))
+            })
+    }
+
+    /// Insert or update a batch of records
+    ///
+    /// Unlike [`upsert`](Self::upsert), the records keep their text and other
+    /// fields, and the collection is saved once for the whole batch.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use vecstore::{make_record, Metadata, VecDatabase};
+    ///
+    /// let mut db = VecDatabase::open("./db")?;
+    /// let mut collection = db.create_collection("docs")?;
+    ///
+    /// let metadata = Metadata { fields: Default::default() };
+    /// let mut record = make_record("doc1", vec![0.1, 0.2, 0.3], metadata);
+    /// record.text = Some("hello world".into());
+    /// collection.batch_upsert(vec![record])?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn batch_upsert(&mut self, records: Vec<Record>) -> Result<()> {
+        if let Some(expected) = self.config.dimension {
+            if let Some(record) = records.iter().find(|r| r.vector.len() != expected) {
+                return Err(crate::error::VecStoreError::CollectionDimensionMismatch {
+                    collection: self.name.clone(),
+                    expected,
+                    actual: record.vector.len(),
+                });
+            }
+        }
+
+        let manager = self.manager.read().unwrap();
+        manager
+            .batch_upsert(&self.namespace_id, records)
+            .map_err(|e| {
+                crate::error::VecStoreError::Other(format!("Collection '{}': {}", self.name, e))
+            })
     }
 
     /// Query for similar vectors
@@ -363,6 +512,7 @@ impl Collection {
     ///     vector: vec![0.1, 0.2, 0.3],
     ///     k: 10,
     ///     filter: None,
+    ///     ..Default::default()
     /// };
     ///
     /// let results = collection.query(query)?;
@@ -397,6 +547,11 @@ impl Collection {
         self.config.distance
     }
 
+    /// Get the vector dimension this collection was created with, if fixed
+    pub fn dimension(&self) -> Option<usize> {
+        self.config.dimension
+    }
+
     /// Get the full configuration of this collection
     pub fn config(&self) -> &Config {
         &self.config
@@ -578,6 +733,7 @@ impl Collection {
             vector: query_vector,
             k,
             filter: None,
+            ..Default::default()
         };
 
         self.query(query)
@@ -671,6 +827,7 @@ mod tests {
             vector: vec![1.0, 0.0, 0.0],
             k: 10,
             filter: None,
+            ..Default::default()
         };
 
         let results = collection.query(query).unwrap();
@@ -686,10 +843,134 @@ mod tests {
         let config = CollectionConfig::default()
             .with_description("Test collection")
             .with_max_vectors(1000)
-            .with_distance(Distance::Manhattan);
+            .with_distance(Distance::Euclidean)
+            .with_hnsw_config(HnswConfig {
+                m: 32,
+                max_elements: 500_000,
+                ..Default::default()
+            });
 
         let collection = db.create_collection_with_config("test", config).unwrap();
-        assert_eq!(collection.distance_metric(), Distance::Manhattan);
+        assert_eq!(collection.distance_metric(), Distance::Euclidean);
+        assert_eq!(collection.stats().unwrap().hnsw.m, 32);
+
+        // The config is read back from the collection's store
+        let reloaded = db.get_collection("test").unwrap().unwrap();
+        assert_eq!(reloaded.config().hnsw.max_elements, 500_000);
+        assert_eq!(reloaded.distance_metric(), Distance::Euclidean);
+    }
+
+    #[test]
+    fn test_collection_dimension_enforced() {
+        let dir = tempdir().unwrap();
+        let mut db = VecDatabase::open(dir.path()).unwrap();
+
+        let config = CollectionConfig::default()
+            .with_dimension(3)
+            .with_distance(Distance::Euclidean);
+        let mut collection = db.create_collection_with_config("docs", config).unwrap();
+        let meta = Metadata {
+            fields: HashMap::new(),
+        };
+
+        // Rejected even before the first vector fixes the store's dimension
+        let err = collection
+            .upsert("short".into(), vec![1.0, 0.0], meta.clone())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::error::VecStoreError::CollectionDimensionMismatch {
+                expected: 3,
+                actual: 2,
+                ..
+            }
+        ));
+        assert!(err.to_string().contains("'docs'"));
+
+        collection
+            .upsert("ok".into(), vec![1.0, 0.0, 0.0], meta.clone())
+            .unwrap();
+        assert_eq!(collection.count().unwrap(), 1);
+
+        // The dimension survives reopening the database
+        drop(collection);
+        let db = VecDatabase::open(dir.path()).unwrap();
+        let mut reloaded = db.get_collection("docs").unwrap().unwrap();
+        assert_eq!(reloaded.dimension(), Some(3));
+        assert_eq!(reloaded.distance_metric(), Distance::Euclidean);
+        assert!(reloaded.upsert("long".into(), vec![1.0; 4], meta).is_err());
+    }
+
+    #[test]
+    fn test_alias_blue_green_swap() {
+        let dir = tempdir().unwrap();
+        let mut db = VecDatabase::open(dir.path()).unwrap();
+        let meta = Metadata {
+            fields: HashMap::new(),
+        };
+
+        let mut v1 = db.create_collection("products_v1").unwrap();
+        v1.upsert("old".into(), vec![1.0, 0.0], meta.clone())
+            .unwrap();
+        db.create_alias("products", "products_v1").unwrap();
+
+        let mut v2 = db.create_collection("products_v2").unwrap();
+        v2.upsert("new".into(), vec![1.0, 0.0], meta).unwrap();
+
+        let db = std::sync::Arc::new(db);
+        let swapped = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let reader = {
+            let (db, swapped) = (db.clone(), swapped.clone());
+            std::thread::spawn(move || {
+                let query = Query::new(vec![1.0, 0.0]).with_limit(1);
+                let mut after_swap = 0;
+                // Keep reading until a few lookups have followed the swap
+                while after_swap < 10 {
+                    let done = swapped.load(std::sync::atomic::Ordering::Acquire);
+                    let products = db.get_collection("products").unwrap().unwrap();
+                    let hits = products.query(query.clone()).unwrap();
+                    assert_eq!(hits.len(), 1);
+                    if done {
+                        assert_eq!(hits[0].id, "new");
+                        after_swap += 1;
+                    } else {
+                        assert!(hits[0].id == "old" || hits[0].id == "new");
+                    }
+                    // Stay under the collections' default request rate limit
+                    std::thread::sleep(std::time::Duration::from_millis(15));
+                }
+            })
+        };
+
+        db.update_alias("products", "products_v2").unwrap();
+        swapped.store(true, std::sync::atomic::Ordering::Release);
+        reader.join().unwrap();
+
+        // The swap is persisted, and the aliased collection is protected
+        let mut db = std::sync::Arc::try_unwrap(db).ok().unwrap();
+        assert!(db.delete_collection("products_v2").is_err());
+        db.delete_collection("products_v1").unwrap();
+        drop(db);
+
+        let mut db = VecDatabase::open(dir.path()).unwrap();
+        let products = db.get_collection("products").unwrap().unwrap();
+        assert_eq!(products.name(), "products_v2");
+        assert_eq!(
+            db.list_aliases().unwrap(),
+            vec![("products".to_string(), "products_v2".to_string())]
+        );
+
+        db.delete_collection_force("products_v2").unwrap();
+        assert!(db.get_collection("products").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_collection_unsupported_distance_rejected() {
+        let dir = tempdir().unwrap();
+        let mut db = VecDatabase::open(dir.path()).unwrap();
+
+        let config = CollectionConfig::default().with_distance(Distance::Chebyshev);
+        assert!(db.create_collection_with_config("test", config).is_err());
     }
 
     #[test]
@@ -714,6 +995,7 @@ mod tests {
             vector: vec![1.0, 0.0],
             k: 10,
             filter: None,
+            ..Default::default()
         };
         let results = coll2.query(query).unwrap();
         assert_eq!(results.len(), 0);
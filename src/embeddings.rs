@@ -6,6 +6,11 @@
 
 pub mod auto_models;
 
+pub mod local;
+
+#[cfg(any(feature = "embeddings", feature = "server-openai"))]
+pub mod openai_compatible;
+
 #[cfg(feature = "openai-embeddings")]
 pub mod openai_backend;
 
@@ -24,6 +29,12 @@ pub mod candle_backend;
 #[cfg(feature = "embeddings")]
 pub use auto_models::{AutoEmbedder, PretrainedModel};
 
+#[cfg(feature = "embeddings")]
+pub use local::{default_model_cache, LocalEmbedder, ModelFile, ModelSource};
+
+#[cfg(any(feature = "embeddings", feature = "server-openai"))]
+pub use openai_compatible::{OpenAICompatibleEmbedder, DEFAULT_OPENAI_BASE_URL};
+
 #[cfg(feature = "openai-embeddings")]
 pub use openai_backend::{OpenAIEmbedding, OpenAIModel};
 
@@ -283,8 +294,13 @@ impl Embedder {
 
         let batch_size = encodings.len();
 
-        // Get the sequence length from the first encoding
-        let seq_length = encodings[0].get_ids().len().min(self.max_length);
+        // Pad every text to the longest in the batch
+        let seq_length = encodings
+            .iter()
+            .map(|encoding| encoding.get_ids().len())
+            .max()
+            .unwrap_or(0)
+            .min(self.max_length);
 
         // Prepare input tensors
         let mut input_ids = Vec::with_capacity(batch_size * seq_length);
@@ -532,7 +548,12 @@ impl EmbeddingStore {
         filter: Option<crate::store::FilterExpr>,
     ) -> Result<Vec<Neighbor>> {
         let vector = self.embedder.embed(query)?;
-        self.store.query(Query { vector, k, filter })
+        self.store.query(Query {
+            vector,
+            k,
+            filter,
+            ..Default::default()
+        })
     }
 
     /// Hybrid search using text
@@ -950,7 +971,12 @@ impl EmbeddingCollection {
     ) -> Result<Vec<Neighbor>> {
         let vector = self.embedder.embed(query)?;
         self.collection
-            .query(Query { vector, k, filter })
+            .query(Query {
+                vector,
+                k,
+                filter,
+                ..Default::default()
+            })
             .map_err(|e| anyhow::anyhow!("Collection query failed: {}", e))
     }
 
@@ -7,7 +7,7 @@
 //!
 //! - Auto-download from HuggingFace Hub
 //! - Local caching (~/.vecstore/models/)
-//! - Progress bars for downloads
+//! - Checksum-verified downloads
 //! - Multiple pre-configured models
 //! - Custom ONNX model support
 //!
@@ -29,7 +29,6 @@
 #![cfg(feature = "embeddings")]
 
 use anyhow::{Context, Result};
-use std::fs;
 use std::path::{Path, PathBuf};
 
 use super::Embedder;
@@ -180,78 +179,32 @@ impl AutoEmbedder {
         &self.cache_dir
     }
 
-    /// Get default cache directory (~/.vecstore/models/)
+    /// Get default cache directory (~/.vecstore/models/, or
+    /// `VECSTORE_MODEL_CACHE`)
     fn get_cache_dir() -> Result<PathBuf> {
-        let home = directories::UserDirs::new().context("Failed to get user home directory")?;
-
-        let cache = home.home_dir().join(".vecstore").join("models");
-
-        fs::create_dir_all(&cache).context("Failed to create cache directory")?;
-
-        Ok(cache)
+        super::local::default_model_cache()
     }
 
-    /// Ensure model is cached, downloading if necessary
+    /// Ensure model is cached with intact checksums, downloading if necessary
     fn ensure_model_cached(cache_dir: &Path, model: PretrainedModel) -> Result<PathBuf> {
-        let model_dir = cache_dir.join(model.model_id().replace('/', "_"));
-
-        // Check if already cached
-        if model_dir.exists()
-            && model_dir.join("model.onnx").exists()
-            && model_dir.join("tokenizer.json").exists()
-        {
-            println!("Using cached model: {}", model.model_id());
-            return Ok(model_dir);
-        }
-
-        // Need to download
-        println!(
-            "Downloading model: {} (~{}MB)...",
-            model.model_id(),
-            model.size_mb()
-        );
-
-        fs::create_dir_all(&model_dir).context("Failed to create model directory")?;
-
-        // Download model files
-        Self::download_model_files(&model_dir, model)?;
-
-        println!("Model downloaded and cached successfully!");
-
-        Ok(model_dir)
+        model
+            .source()
+            .fetch(cache_dir)
+            .with_context(|| format!("Failed to fetch model {}", model.model_id()))
     }
+}
 
-    /// Download model files from HuggingFace
-    fn download_model_files(model_dir: &Path, model: PretrainedModel) -> Result<()> {
-        let base_url = format!("https://huggingface.co/{}/resolve/main", model.model_id());
-
-        // Files to download
-        let files = vec![
-            ("model.onnx", "model.onnx"),
-            ("tokenizer.json", "tokenizer.json"),
-        ];
-
-        for (remote_name, local_name) in files {
-            let url = format!("{}/{}", base_url, remote_name);
-            let dest = model_dir.join(local_name);
-
-            println!("  Downloading {}...", remote_name);
-
-            // Simple download using ureq
-            let response = ureq::get(&url)
-                .call()
-                .with_context(|| format!("Failed to download {}", url))?;
-
-            let mut reader = response.into_reader();
-            let mut file = fs::File::create(&dest)
-                .with_context(|| format!("Failed to create file: {:?}", dest))?;
-
-            std::io::copy(&mut reader, &mut file).context("Failed to write downloaded file")?;
+impl super::TextEmbedder for AutoEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.encode(text)
+    }
 
-            println!("  ✓ {} downloaded", remote_name);
-        }
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        self.encode_batch(texts)
+    }
 
-        Ok(())
+    fn dimension(&self) -> Result<usize> {
+        PretrainedModel::from_name(&self.model_name).map(|model| model.dimension())
     }
 }
 
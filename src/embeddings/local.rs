@@ -0,0 +1,502 @@
+//! Local text embeddings with all-MiniLM-L6-v2, out of the box
+//!
+//! [`LocalEmbedder::minilm`] downloads the ONNX export of
+//! sentence-transformers' all-MiniLM-L6-v2 and its tokenizer on first use,
+//! caches them under `~/.vecstore/models` (or `VECSTORE_MODEL_CACHE`), and
+//! embeds batches of texts through ONNX Runtime, mean-pooling the token
+//! embeddings and L2-normalizing the result. [`LocalEmbedder::from_dir`]
+//! loads the same files from a directory instead, without any network.
+//!
+//! Downloads are written to a `.part` file and hashed as they arrive, and
+//! only renamed into the cache once complete. A [`ModelFile`] may pin its
+//! SHA-256, and a download that doesn't match is discarded; all-MiniLM-L6-v2
+//! is downloaded at a fixed commit with both files pinned. Every cached
+//! file's digest is recorded in the model directory's `checksums.json` and
+//! checked whenever the model is loaded, so a truncated or altered cache is
+//! downloaded again rather than loaded.
+//!
+//! ```no_run
+//! use vecstore::embeddings::{LocalEmbedder, TextEmbedder};
+//!
+//! # fn main() -> anyhow::Result<()> {
+//! let embedder = LocalEmbedder::minilm()?;
+//! let vectors = embedder.embed_batch(&["first text", "second text"])?;
+//! assert_eq!(vectors[0].len(), 384);
+//! # Ok(())
+//! # }
+//! ```
+
+#![cfg(feature = "embeddings")]
+
+use anyhow::{anyhow, Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::{Embedder, PretrainedModel, TextEmbedder};
+
+/// Dimension of all-MiniLM-L6-v2 embeddings
+pub const MINILM_DIMENSION: usize = 384;
+
+/// Tokens per text all-MiniLM-L6-v2 was trained on; longer texts are truncated
+pub const MINILM_MAX_LENGTH: usize = 256;
+
+/// Texts run through the model per inference call
+pub const DEFAULT_BATCH_SIZE: usize = 32;
+
+/// Environment variable overriding the model cache directory
+pub const MODEL_CACHE_ENV: &str = "VECSTORE_MODEL_CACHE";
+
+/// File in each model directory recording the SHA-256 of its files
+const CHECKSUMS_FILE: &str = "checksums.json";
+
+/// Commit of sentence-transformers/all-MiniLM-L6-v2 the model is downloaded at
+const MINILM_REVISION: &str = "c9745ed1d9f207416be6d2e6f8de32d1f16199bf";
+
+/// SHA-256 of `onnx/model.onnx` at [`MINILM_REVISION`]
+const MINILM_MODEL_SHA256: &str =
+    "6fd5d72fe4589f189f8ebc006442dbb529bb7ce38f8082112682524616046452";
+
+/// SHA-256 of `tokenizer.json` at [`MINILM_REVISION`]
+const MINILM_TOKENIZER_SHA256: &str =
+    "be50c3628f2bf5bb5e3a7f17b1f74611b2561a3a27eeab05e5aa30f411572037";
+
+/// The model cache: `VECSTORE_MODEL_CACHE`, else `~/.vecstore/models`
+pub fn default_model_cache() -> Result<PathBuf> {
+    let cache = match std::env::var_os(MODEL_CACHE_ENV) {
+        Some(dir) => PathBuf::from(dir),
+        None => directories::UserDirs::new()
+            .context("Failed to get user home directory")?
+            .home_dir()
+            .join(".vecstore")
+            .join("models"),
+    };
+    fs::create_dir_all(&cache).context("Failed to create cache directory")?;
+    Ok(cache)
+}
+
+/// A file of a downloadable model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelFile {
+    /// Where to download it from
+    pub url: String,
+    /// Its name in the model directory, e.g. `model.onnx`
+    pub name: String,
+    /// Expected SHA-256 as lowercase hex; without one, the digest of the
+    /// first download is recorded and checked from then on
+    pub sha256: Option<String>,
+}
+
+/// Where a model's files are downloaded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelSource {
+    /// Model name, e.g. `sentence-transformers/all-MiniLM-L6-v2`; its cache
+    /// directory is named after it
+    pub id: String,
+    pub files: Vec<ModelFile>,
+}
+
+impl ModelSource {
+    /// The ONNX export (`onnx/model.onnx`) and `tokenizer.json` of a
+    /// HuggingFace repository, at the head of its `main` branch
+    pub fn huggingface(repo: &str) -> Self {
+        Self::huggingface_at(repo, "main")
+    }
+
+    /// The ONNX export and `tokenizer.json` of a HuggingFace repository at
+    /// `revision`, a branch, tag or commit hash
+    pub fn huggingface_at(repo: &str, revision: &str) -> Self {
+        let base = format!("https://huggingface.co/{}/resolve/{}", repo, revision);
+        let file = |remote: &str, name: &str| ModelFile {
+            url: format!("{}/{}", base, remote),
+            name: name.to_string(),
+            sha256: None,
+        };
+        Self {
+            id: repo.to_string(),
+            files: vec![
+                file("onnx/model.onnx", "model.onnx"),
+                file("tokenizer.json", "tokenizer.json"),
+            ],
+        }
+    }
+
+    /// Pin the SHA-256 of the file named `name`
+    pub fn with_sha256(mut self, name: &str, sha256: &str) -> Self {
+        for file in self.files.iter_mut().filter(|f| f.name == name) {
+            file.sha256 = Some(sha256.to_ascii_lowercase());
+        }
+        self
+    }
+
+    /// The model's directory under `cache_dir`
+    pub fn cache_path(&self, cache_dir: &Path) -> PathBuf {
+        cache_dir.join(self.id.replace('/', "_"))
+    }
+
+    /// Make sure every file is in the cache with the right checksum,
+    /// downloading the missing and damaged ones, and return the model's
+    /// directory
+    pub fn fetch(&self, cache_dir: &Path) -> Result<PathBuf> {
+        let dir = self.cache_path(cache_dir);
+        fs::create_dir_all(&dir).context("Failed to create model directory")?;
+        let mut checksums = read_checksums(&dir);
+
+        for file in &self.files {
+            let path = dir.join(&file.name);
+            let expected = file
+                .sha256
+                .clone()
+                .or_else(|| checksums.get(&file.name).cloned());
+            if path.exists() {
+                let actual = sha256_file(&path)?;
+                match &expected {
+                    Some(expected) if *expected != actual => tracing::warn!(
+                        file = %path.display(),
+                        "Cached model file fails its checksum; downloading it again"
+                    ),
+                    _ => {
+                        checksums.insert(file.name.clone(), actual);
+                        continue;
+                    }
+                }
+            }
+            let actual = download(&file.url, &path, file.sha256.as_deref())?;
+            checksums.insert(file.name.clone(), actual);
+        }
+
+        fs::write(
+            dir.join(CHECKSUMS_FILE),
+            serde_json::to_string_pretty(&checksums)?,
+        )
+        .context("Failed to record model checksums")?;
+        Ok(dir)
+    }
+}
+
+impl PretrainedModel {
+    /// Where the model's ONNX export and tokenizer are downloaded from
+    ///
+    /// all-MiniLM-L6-v2 is pinned to a commit and the digests of its files,
+    /// so a first download that doesn't match them is rejected; the other
+    /// models follow `main` and trust their first download.
+    pub fn source(&self) -> ModelSource {
+        match self {
+            PretrainedModel::AllMiniLML6V2 => {
+                ModelSource::huggingface_at(self.model_id(), MINILM_REVISION)
+                    .with_sha256("model.onnx", MINILM_MODEL_SHA256)
+                    .with_sha256("tokenizer.json", MINILM_TOKENIZER_SHA256)
+            }
+            _ => ModelSource::huggingface(self.model_id()),
+        }
+    }
+}
+
+/// Checksums recorded in a model directory; empty if missing or unreadable
+fn read_checksums(dir: &Path) -> BTreeMap<String, String> {
+    fs::read(dir.join(CHECKSUMS_FILE))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file =
+        fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex(&hasher.finalize()))
+}
+
+fn hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Download `url` to `dest`, failing if its SHA-256 isn't `expected`, and
+/// return the digest
+fn download(url: &str, dest: &Path, expected: Option<&str>) -> Result<String> {
+    tracing::info!(url, "Downloading model file");
+    let response = ureq::get(url)
+        .call()
+        .with_context(|| format!("Failed to download {}", url))?;
+    let mut reader = response.into_body().into_reader();
+
+    let part = dest.with_extension("part");
+    let mut out = BufWriter::new(
+        fs::File::create(&part).with_context(|| format!("Failed to create {}", part.display()))?,
+    );
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader
+            .read(&mut buf)
+            .with_context(|| format!("Failed to download {}", url))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        out.write_all(&buf[..n])?;
+    }
+    out.flush()?;
+    drop(out);
+
+    let actual = hex(&hasher.finalize());
+    if let Some(expected) = expected {
+        if actual != expected {
+            let _ = fs::remove_file(&part);
+            return Err(anyhow!(
+                "Checksum mismatch for {}: expected sha256 {}, got {}",
+                url,
+                expected,
+                actual
+            ));
+        }
+    }
+    fs::rename(&part, dest).with_context(|| format!("Failed to write {}", dest.display()))?;
+    Ok(actual)
+}
+
+/// A sentence-transformer ONNX model run in-process, all-MiniLM-L6-v2 by
+/// default
+///
+/// Texts are embedded [`DEFAULT_BATCH_SIZE`] at a time; embeddings are
+/// mean-pooled over the tokens and L2-normalized.
+pub struct LocalEmbedder {
+    embedder: Embedder,
+    model: String,
+    dimension: usize,
+    batch_size: usize,
+}
+
+impl LocalEmbedder {
+    /// all-MiniLM-L6-v2 from the [model cache](default_model_cache),
+    /// downloaded on first use
+    pub fn minilm() -> Result<Self> {
+        Self::minilm_cached_in(default_model_cache()?)
+    }
+
+    /// all-MiniLM-L6-v2 cached in `cache_dir`, downloaded on first use
+    pub fn minilm_cached_in(cache_dir: impl AsRef<Path>) -> Result<Self> {
+        let source = PretrainedModel::AllMiniLML6V2.source();
+        Ok(Self::from_source(&source, cache_dir)?.with_max_length(MINILM_MAX_LENGTH))
+    }
+
+    /// The model `source` describes, cached in `cache_dir` and downloaded
+    /// on first use
+    pub fn from_source(source: &ModelSource, cache_dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = source.fetch(cache_dir.as_ref())?;
+        let mut embedder = Self::from_dir(dir)?;
+        embedder.model = source.id.clone();
+        Ok(embedder)
+    }
+
+    /// Load `model.onnx` and `tokenizer.json` from `dir`
+    ///
+    /// The model takes `input_ids`, `attention_mask` and `token_type_ids`
+    /// and outputs its token embeddings first, as sentence-transformer
+    /// exports do. Its name is the directory's path.
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let embedder = Embedder::new(dir.join("model.onnx"), dir.join("tokenizer.json"))
+            .with_context(|| format!("Failed to load the ONNX model in {}", dir.display()))?;
+        let dimension = embedder.embedding_dim()?;
+        Ok(Self {
+            embedder,
+            model: dir.display().to_string(),
+            dimension,
+            batch_size: DEFAULT_BATCH_SIZE,
+        })
+    }
+
+    /// Run up to `batch_size` texts through the model per call
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Truncate texts to `max_length` tokens
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.embedder = self.embedder.with_max_length(max_length);
+        self
+    }
+
+    /// Model name: the HuggingFace id, or the directory it was loaded from
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Components of each embedding
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Embed one text
+    pub fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        self.embedder.embed(text)
+    }
+
+    /// Embed texts in batches, one vector per text in order
+    pub fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.batch_size) {
+            vectors.extend(self.embedder.embed_batch(batch)?);
+        }
+        Ok(vectors)
+    }
+}
+
+impl TextEmbedder for LocalEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        LocalEmbedder::embed(self, text)
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        LocalEmbedder::embed_batch(self, texts)
+    }
+
+    fn dimension(&self) -> Result<usize> {
+        Ok(self.dimension)
+    }
+}
+
+#[cfg(feature = "server")]
+impl crate::server::ServerEmbedder for LocalEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        LocalEmbedder::embed_batch(self, texts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use tempfile::TempDir;
+
+    /// Serve each file in `files` over HTTP on localhost, counting requests
+    fn serve(files: Vec<(&'static str, Vec<u8>)>) -> (String, std::sync::mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let (requests, received) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request_line = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                let path = request_line.split(' ').nth(1).unwrap_or("").to_string();
+                let body = files
+                    .iter()
+                    .find(|(name, _)| path == format!("/{}", name))
+                    .map(|(_, body)| body.clone());
+                let response = match &body {
+                    Some(body) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    ),
+                    None => {
+                        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+                stream.write_all(&body.unwrap_or_default()).unwrap();
+                let _ = requests.send(path);
+            }
+        });
+        (base, received)
+    }
+
+    fn source(base: &str) -> ModelSource {
+        let file = |name: &str| ModelFile {
+            url: format!("{}/{}", base, name),
+            name: name.to_string(),
+            sha256: None,
+        };
+        ModelSource {
+            id: "tests/tiny".to_string(),
+            files: vec![file("model.onnx"), file("tokenizer.json")],
+        }
+    }
+
+    #[test]
+    fn test_fetch_caches_and_verifies_checksums() {
+        let (base, requests) = serve(vec![
+            ("model.onnx", b"model bytes".to_vec()),
+            ("tokenizer.json", b"{}".to_vec()),
+        ]);
+        let cache = TempDir::new().unwrap();
+        let model_sha = hex(&Sha256::digest(b"model bytes"));
+
+        let dir = source(&base)
+            .with_sha256("model.onnx", &model_sha)
+            .fetch(cache.path())
+            .unwrap();
+        assert_eq!(dir, cache.path().join("tests_tiny"));
+        assert_eq!(fs::read(dir.join("model.onnx")).unwrap(), b"model bytes");
+        assert_eq!(requests.try_iter().count(), 2);
+        assert_eq!(read_checksums(&dir)["model.onnx"], model_sha);
+
+        // Cached files are checked, not downloaded again
+        source(&base).fetch(cache.path()).unwrap();
+        assert_eq!(requests.try_iter().count(), 0);
+
+        // A damaged file fails its recorded checksum and is replaced
+        fs::write(dir.join("model.onnx"), b"model by").unwrap();
+        source(&base).fetch(cache.path()).unwrap();
+        assert_eq!(requests.try_iter().collect::<Vec<_>>(), vec!["/model.onnx"]);
+        assert_eq!(fs::read(dir.join("model.onnx")).unwrap(), b"model bytes");
+
+        // A download not matching its pinned checksum is discarded
+        let other = TempDir::new().unwrap();
+        let err = source(&base)
+            .with_sha256("tokenizer.json", &"0".repeat(64))
+            .fetch(other.path())
+            .unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"), "{}", err);
+        let tiny = other.path().join("tests_tiny");
+        assert!(!tiny.join("tokenizer.json").exists());
+        assert!(!tiny.join("tokenizer.part").exists());
+    }
+
+    #[test]
+    fn test_pinned_minilm_rejects_tampered_first_download() {
+        let minilm = PretrainedModel::AllMiniLML6V2.source();
+        for file in &minilm.files {
+            assert!(file.url.contains(MINILM_REVISION), "{}", file.url);
+            assert!(file.sha256.is_some(), "{} is not pinned", file.name);
+        }
+
+        // Serve the right files under the pinned digests, but altered
+        let (base, _requests) = serve(vec![
+            ("model.onnx", b"tampered model".to_vec()),
+            ("tokenizer.json", b"{}".to_vec()),
+        ]);
+        let mut tampered = minilm.clone();
+        for file in &mut tampered.files {
+            file.url = format!("{}/{}", base, file.name);
+        }
+        let cache = TempDir::new().unwrap();
+        let err = tampered.fetch(cache.path()).unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"), "{}", err);
+
+        let dir = minilm.cache_path(cache.path());
+        assert!(!dir.join("model.onnx").exists());
+        assert!(!dir.join("model.part").exists());
+        assert!(!dir.join(CHECKSUMS_FILE).exists());
+    }
+}
@@ -0,0 +1,142 @@
+// Blocking client for OpenAI-compatible embedding endpoints
+//
+// Works with any server exposing `POST {base_url}/embeddings` in OpenAI's
+// request and response format: OpenAI itself, Azure-style proxies, vLLM,
+// LM Studio, Ollama's `/v1` API and text-embeddings-inference. Unlike
+// `OpenAIEmbedding`, it needs no async runtime, so the CLI can use it.
+
+use super::TextEmbedder;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use ureq::tls::{TlsConfig, TlsProvider};
+use ureq::Agent;
+
+/// Base URL used when none is configured
+pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Texts sent per request by `embed_batch`
+const MAX_BATCH: usize = 64;
+
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [&'a str],
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+    index: usize,
+}
+
+/// Text embedder calling an OpenAI-compatible `/embeddings` endpoint
+///
+/// # Example
+/// ```no_run
+/// use vecstore::embeddings::{OpenAICompatibleEmbedder, TextEmbedder};
+///
+/// let embedder = OpenAICompatibleEmbedder::new("http://localhost:11434/v1", "nomic-embed-text")
+///     .with_api_key(std::env::var("OPENAI_API_KEY").ok());
+/// let vector = embedder.embed("how do I reset my password")?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct OpenAICompatibleEmbedder {
+    agent: Agent,
+    base_url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+impl OpenAICompatibleEmbedder {
+    /// Create an embedder for `model` served at `base_url`, e.g.
+    /// `https://api.openai.com/v1`
+    pub fn new(base_url: impl Into<String>, model: impl Into<String>) -> Self {
+        let agent = Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(60)))
+            .tls_config(
+                TlsConfig::builder()
+                    .provider(TlsProvider::NativeTls)
+                    .build(),
+            )
+            .build()
+            .into();
+        Self {
+            agent,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            model: model.into(),
+            api_key: None,
+        }
+    }
+
+    /// Send `key` as a bearer token
+    pub fn with_api_key(mut self, key: Option<String>) -> Self {
+        self.api_key = key;
+        self
+    }
+
+    /// Model name sent with each request
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn request(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let url = format!("{}/embeddings", self.base_url);
+        let body = serde_json::to_vec(&EmbeddingRequest {
+            model: &self.model,
+            input: texts,
+        })?;
+
+        let mut request = self
+            .agent
+            .post(&url)
+            .header("Content-Type", "application/json");
+        if let Some(key) = &self.api_key {
+            request = request.header("Authorization", &format!("Bearer {}", key));
+        }
+        let mut response = request
+            .send(&body[..])
+            .with_context(|| format!("Embedding request to {} failed", url))?;
+        let text = response
+            .body_mut()
+            .read_to_string()
+            .context("Failed to read embedding response")?;
+        let response: EmbeddingResponse =
+            serde_json::from_str(&text).context("Invalid embedding response")?;
+
+        if response.data.len() != texts.len() {
+            return Err(anyhow!(
+                "Asked for {} embeddings, got {}",
+                texts.len(),
+                response.data.len()
+            ));
+        }
+        let mut data = response.data;
+        data.sort_by_key(|d| d.index);
+        Ok(data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
+impl TextEmbedder for OpenAICompatibleEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        Ok(self.request(&[text])?.remove(0))
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(MAX_BATCH) {
+            vectors.extend(self.request(batch)?);
+        }
+        Ok(vectors)
+    }
+
+    /// Embeds a short probe text, since the endpoint doesn't report it
+    fn dimension(&self) -> Result<usize> {
+        Ok(self.embed("dimension probe")?.len())
+    }
+}
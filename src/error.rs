@@ -26,6 +26,14 @@ pub enum VecStoreError {
     #[error("Invalid vector dimension: expected {expected}, got {actual}")]
     DimensionMismatch { expected: usize, actual: usize },
 
+    /// Vector dimension differs from the one a collection was created with
+    #[error("Collection '{collection}' expects {expected}-dimensional vectors, got {actual}")]
+    CollectionDimensionMismatch {
+        collection: String,
+        expected: usize,
+        actual: usize,
+    },
+
     /// Vector not found
     #[error("Vector with id '{id}' not found")]
     VectorNotFound { id: String },
@@ -5,9 +5,10 @@
 //! - Performance metrics
 //! - Resource utilization
 //! - Index integrity checks
+//! - Data-quality checks
 //! - Alert conditions
 
-use crate::store::VecStore;
+use crate::store::{ConsistencyScan, StoreStats, VecStore};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, SystemTime};
@@ -59,10 +60,121 @@ pub struct HealthReport {
     /// Active alerts
     pub alerts: Vec<Alert>,
 
+    /// Outcome of each consistency and data-quality check
+    #[serde(default)]
+    pub checks: Vec<HealthCheckResult>,
+
     /// Uptime duration
     pub uptime: Option<Duration>,
 }
 
+impl HealthReport {
+    /// The most severe check result or alert
+    pub fn worst_severity(&self) -> CheckSeverity {
+        let alerts = self.alerts.iter().map(|a| match a.severity {
+            AlertSeverity::Info => CheckSeverity::Ok,
+            AlertSeverity::Warning => CheckSeverity::Warn,
+            AlertSeverity::Critical => CheckSeverity::Critical,
+        });
+        self.checks
+            .iter()
+            .map(|c| c.severity)
+            .chain(alerts)
+            .max()
+            .unwrap_or(CheckSeverity::Ok)
+    }
+}
+
+/// Severity of a health check result, from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckSeverity {
+    Ok,
+    Warn,
+    Critical,
+}
+
+impl CheckSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Warn => "warn",
+            Self::Critical => "critical",
+        }
+    }
+
+    /// Exit status for the CLI: 0 when ok, 1 on warnings, 2 when critical
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::Warn => 1,
+            Self::Critical => 2,
+        }
+    }
+}
+
+/// What a health check looked at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthCheckCode {
+    /// Index id map and records name the same ids
+    IndexConsistency,
+    /// No id is mapped from more than one index slot
+    DuplicateIds,
+    /// Share of soft-deleted records awaiting compaction
+    Tombstones,
+    /// Share of index entries no longer backing a record
+    Fragmentation,
+    /// Every vector has the store's dimension
+    DimensionUniformity,
+    /// Sampled vectors hold no NaN or infinite values
+    NonFiniteVectors,
+    /// Sampled vectors aren't all zeros
+    ZeroVectors,
+    /// Time since the last save while changes are unsaved
+    SaveStaleness,
+    /// Size of the write-ahead log
+    WalBacklog,
+}
+
+impl HealthCheckCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::IndexConsistency => "index_consistency",
+            Self::DuplicateIds => "duplicate_ids",
+            Self::Tombstones => "tombstones",
+            Self::Fragmentation => "fragmentation",
+            Self::DimensionUniformity => "dimension_uniformity",
+            Self::NonFiniteVectors => "non_finite_vectors",
+            Self::ZeroVectors => "zero_vectors",
+            Self::SaveStaleness => "save_staleness",
+            Self::WalBacklog => "wal_backlog",
+        }
+    }
+}
+
+/// Outcome of one health check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthCheckResult {
+    /// Machine-readable name of the check
+    pub code: HealthCheckCode,
+
+    pub severity: CheckSeverity,
+
+    /// Human-readable finding
+    pub message: String,
+}
+
+impl HealthCheckResult {
+    fn new(code: HealthCheckCode, severity: CheckSeverity, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            severity,
+            message: message.into(),
+        }
+    }
+}
+
 /// Database health metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DatabaseHealth {
@@ -179,6 +291,7 @@ pub enum AlertCategory {
 
 /// Health check configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct HealthCheckConfig {
     /// Deletion ratio threshold for warning
     pub deletion_ratio_warning: f64,
@@ -189,6 +302,9 @@ pub struct HealthCheckConfig {
     /// Fragmentation threshold for warning
     pub fragmentation_warning: f64,
 
+    /// Fragmentation threshold for critical
+    pub fragmentation_critical: f64,
+
     /// Memory utilization warning threshold
     pub memory_warning: f64,
 
@@ -197,6 +313,21 @@ pub struct HealthCheckConfig {
 
     /// Minimum performance score
     pub min_performance_score: f64,
+
+    /// Vectors read by the NaN and zero-vector checks
+    pub vector_sample_size: usize,
+
+    /// Seconds changes may stay unsaved before a warning
+    pub stale_save_warning_secs: u64,
+
+    /// Seconds changes may stay unsaved before the check is critical
+    pub stale_save_critical_secs: u64,
+
+    /// Write-ahead log size (bytes) for warning
+    pub wal_warning_bytes: u64,
+
+    /// Write-ahead log size (bytes) for critical
+    pub wal_critical_bytes: u64,
 }
 
 impl Default for HealthCheckConfig {
@@ -205,9 +336,15 @@ impl Default for HealthCheckConfig {
             deletion_ratio_warning: 0.3,
             deletion_ratio_critical: 0.5,
             fragmentation_warning: 50.0,
+            fragmentation_critical: 80.0,
             memory_warning: 80.0,
             latency_warning_ms: 100.0,
             min_performance_score: 70.0,
+            vector_sample_size: 1000,
+            stale_save_warning_secs: 60 * 60,
+            stale_save_critical_secs: 24 * 60 * 60,
+            wal_warning_bytes: 64 * 1024 * 1024,
+            wal_critical_bytes: 512 * 1024 * 1024,
         }
     }
 }
@@ -234,10 +371,20 @@ impl HealthChecker {
 
     /// Perform a comprehensive health check
     pub fn check(&self, store: &VecStore) -> Result<HealthReport> {
-        let database = self.check_database(store);
-        let index = self.check_index(store);
+        let stats = store.stats();
+        let scan = store.scan_consistency(self.config.vector_sample_size);
+        let checks = self.run_checks(store, &stats, &scan);
+        let database = self.check_database(&stats);
+        let mut index = self.check_index(&stats);
+        index.integrity_ok = checks.iter().all(|c| {
+            c.severity == CheckSeverity::Ok
+                || !matches!(
+                    c.code,
+                    HealthCheckCode::IndexConsistency | HealthCheckCode::DuplicateIds
+                )
+        });
         let performance = self.check_performance(store);
-        let resources = self.check_resources(store, &database);
+        let resources = self.check_resources(&stats);
 
         let mut alerts = Vec::new();
 
@@ -248,7 +395,7 @@ impl HealthChecker {
         self.generate_resource_alerts(&resources, &mut alerts);
 
         // Determine overall status
-        let status = self.determine_status(&alerts);
+        let status = self.determine_status(&alerts, &checks);
 
         let uptime = SystemTime::now().duration_since(self.start_time).ok();
 
@@ -260,15 +407,215 @@ impl HealthChecker {
             performance,
             resources,
             alerts,
+            checks,
             uptime,
         })
     }
 
-    fn check_database(&self, store: &VecStore) -> DatabaseHealth {
-        let total_vectors = store.len() + store.deleted_count();
-        let active_vectors = store.active_count();
-        let deleted_vectors = store.deleted_count();
-        let dimension = store.dimension();
+    /// Run the consistency and data-quality checks, one result each
+    fn run_checks(
+        &self,
+        store: &VecStore,
+        stats: &StoreStats,
+        scan: &ConsistencyScan,
+    ) -> Vec<HealthCheckResult> {
+        use CheckSeverity::{Critical, Ok, Warn};
+        use HealthCheckCode::*;
+        let config = &self.config;
+        let mut checks = Vec::new();
+
+        checks.push(
+            if scan.orphaned_ids.is_empty() && scan.unindexed_ids.is_empty() {
+                HealthCheckResult::new(IndexConsistency, Ok, "index and records name the same ids")
+            } else {
+                HealthCheckResult::new(
+                    IndexConsistency,
+                    Critical,
+                    format!(
+                        "{} indexed ids have no record{}, {} records are not indexed{}",
+                        scan.orphaned_ids.len(),
+                        examples(&scan.orphaned_ids),
+                        scan.unindexed_ids.len(),
+                        examples(&scan.unindexed_ids)
+                    ),
+                )
+            },
+        );
+
+        checks.push(if scan.duplicate_ids.is_empty() {
+            HealthCheckResult::new(DuplicateIds, Ok, "no id has more than one index slot")
+        } else {
+            HealthCheckResult::new(
+                DuplicateIds,
+                Critical,
+                format!(
+                    "{} ids have more than one index slot{}",
+                    scan.duplicate_ids.len(),
+                    examples(&scan.duplicate_ids)
+                ),
+            )
+        });
+
+        let deletion_ratio = match stats.total_records {
+            0 => 0.0,
+            total => stats.deleted_records as f64 / total as f64,
+        };
+        let severity = if deletion_ratio >= config.deletion_ratio_critical {
+            Critical
+        } else if deletion_ratio >= config.deletion_ratio_warning {
+            Warn
+        } else {
+            Ok
+        };
+        checks.push(HealthCheckResult::new(
+            Tombstones,
+            severity,
+            format!(
+                "{} of {} records are soft-deleted ({:.1}%)",
+                stats.deleted_records,
+                stats.total_records,
+                deletion_ratio * 100.0
+            ),
+        ));
+
+        let fragmentation = stats.fragmentation_ratio * 100.0;
+        let severity = if fragmentation >= config.fragmentation_critical {
+            Critical
+        } else if fragmentation >= config.fragmentation_warning {
+            Warn
+        } else {
+            Ok
+        };
+        checks.push(HealthCheckResult::new(
+            Fragmentation,
+            severity,
+            format!(
+                "{} stale index entries ({:.1}% of the index)",
+                stats.ghost_entries, fragmentation
+            ),
+        ));
+
+        let mismatched: usize = scan
+            .dimensions
+            .iter()
+            .filter(|(len, _)| **len != stats.dimension)
+            .map(|(_, count)| count)
+            .sum();
+        checks.push(if stats.total_records == 0 {
+            HealthCheckResult::new(DimensionUniformity, Ok, "no vectors")
+        } else if mismatched == 0 {
+            HealthCheckResult::new(
+                DimensionUniformity,
+                Ok,
+                format!("every vector has {} dimensions", stats.dimension),
+            )
+        } else {
+            let lengths: Vec<String> = scan
+                .dimensions
+                .iter()
+                .map(|(len, count)| format!("{} × {}", count, len))
+                .collect();
+            HealthCheckResult::new(
+                DimensionUniformity,
+                Critical,
+                format!(
+                    "{} vectors don't have the store's {} dimensions (by length: {})",
+                    mismatched,
+                    stats.dimension,
+                    lengths.join(", ")
+                ),
+            )
+        });
+
+        checks.push(if scan.non_finite_ids.is_empty() {
+            HealthCheckResult::new(
+                NonFiniteVectors,
+                Ok,
+                format!(
+                    "no NaN or infinite values in {} sampled vectors",
+                    scan.sampled
+                ),
+            )
+        } else {
+            HealthCheckResult::new(
+                NonFiniteVectors,
+                Critical,
+                format!(
+                    "{} of {} sampled vectors hold NaN or infinite values{}",
+                    scan.non_finite_ids.len(),
+                    scan.sampled,
+                    examples(&scan.non_finite_ids)
+                ),
+            )
+        });
+
+        checks.push(if scan.zero_vector_ids.is_empty() {
+            HealthCheckResult::new(
+                ZeroVectors,
+                Ok,
+                format!("no zero vectors in {} sampled vectors", scan.sampled),
+            )
+        } else {
+            HealthCheckResult::new(
+                ZeroVectors,
+                Warn,
+                format!(
+                    "{} of {} sampled vectors are all zeros{}",
+                    scan.zero_vector_ids.len(),
+                    scan.sampled,
+                    examples(&scan.zero_vector_ids)
+                ),
+            )
+        });
+
+        let now = chrono::Utc::now().timestamp();
+        checks.push(match (store.is_dirty(), stats.last_saved_at) {
+            (false, _) => HealthCheckResult::new(SaveStaleness, Ok, "no unsaved changes"),
+            (true, None) => {
+                HealthCheckResult::new(SaveStaleness, Warn, "unsaved changes; never saved")
+            }
+            (true, Some(saved_at)) => {
+                let age = now.saturating_sub(saved_at).max(0) as u64;
+                let severity = if age >= config.stale_save_critical_secs {
+                    Critical
+                } else if age >= config.stale_save_warning_secs {
+                    Warn
+                } else {
+                    Ok
+                };
+                HealthCheckResult::new(
+                    SaveStaleness,
+                    severity,
+                    format!("unsaved changes; last saved {}s ago", age),
+                )
+            }
+        });
+
+        checks.push(match stats.wal_bytes {
+            None => HealthCheckResult::new(WalBacklog, Ok, "no write-ahead log"),
+            Some(bytes) => {
+                let severity = if bytes >= config.wal_critical_bytes {
+                    Critical
+                } else if bytes >= config.wal_warning_bytes {
+                    Warn
+                } else {
+                    Ok
+                };
+                HealthCheckResult::new(
+                    WalBacklog,
+                    severity,
+                    format!("write-ahead log holds {:.2} MB", bytes as f64 / 1_000_000.0),
+                )
+            }
+        });
+
+        checks
+    }
+
+    fn check_database(&self, stats: &StoreStats) -> DatabaseHealth {
+        let total_vectors = stats.total_records;
+        let active_vectors = stats.active_records;
+        let deleted_vectors = stats.deleted_records;
 
         let deletion_ratio = if total_vectors > 0 {
             deleted_vectors as f64 / total_vectors as f64
@@ -287,19 +634,19 @@ impl HealthChecker {
             total_vectors,
             active_vectors,
             deleted_vectors,
-            dimension,
+            dimension: stats.dimension,
             deletion_ratio,
             storage_efficiency,
         }
     }
 
-    fn check_index(&self, _store: &VecStore) -> IndexHealth {
-        // For now, return basic HNSW index health
+    fn check_index(&self, stats: &StoreStats) -> IndexHealth {
         IndexHealth {
             index_type: "HNSW".to_string(),
             integrity_ok: true,
             avg_degree: None,
-            fragmentation: 0.0,
+            // Share of index entries no longer backing a record
+            fragmentation: stats.fragmentation_ratio * 100.0,
             last_rebuild: None,
         }
     }
@@ -315,22 +662,19 @@ impl HealthChecker {
         }
     }
 
-    fn check_resources(&self, _store: &VecStore, db: &DatabaseHealth) -> ResourceHealth {
-        // Estimate memory usage
-        let vector_size = db.dimension * 4; // f32 = 4 bytes
-        let vectors_memory = db.active_vectors * vector_size;
-        let index_overhead = db.active_vectors * 64; // Rough HNSW overhead
-        let memory_bytes = vectors_memory + index_overhead;
+    fn check_resources(&self, stats: &StoreStats) -> ResourceHealth {
+        let memory_bytes =
+            stats.vector_memory_bytes + stats.index_link_bytes + stats.metadata_bytes;
 
-        let memory_per_vector = if db.active_vectors > 0 {
-            memory_bytes as f64 / db.active_vectors as f64
+        let memory_per_vector = if stats.active_records > 0 {
+            memory_bytes as f64 / stats.active_records as f64
         } else {
             0.0
         };
 
         ResourceHealth {
             memory_bytes,
-            disk_bytes: 0, // Would need actual disk measurement
+            disk_bytes: stats.disk_bytes as usize,
             memory_per_vector,
             memory_utilization: 0.0, // Placeholder
             disk_utilization: 0.0,
@@ -462,9 +806,11 @@ impl HealthChecker {
         }
     }
 
-    fn determine_status(&self, alerts: &[Alert]) -> HealthStatus {
-        let has_critical = alerts.iter().any(|a| a.severity == AlertSeverity::Critical);
-        let has_warning = alerts.iter().any(|a| a.severity == AlertSeverity::Warning);
+    fn determine_status(&self, alerts: &[Alert], checks: &[HealthCheckResult]) -> HealthStatus {
+        let has_critical = alerts.iter().any(|a| a.severity == AlertSeverity::Critical)
+            || checks.iter().any(|c| c.severity == CheckSeverity::Critical);
+        let has_warning = alerts.iter().any(|a| a.severity == AlertSeverity::Warning)
+            || checks.iter().any(|c| c.severity == CheckSeverity::Warn);
 
         if has_critical {
             HealthStatus::Unhealthy
@@ -476,6 +822,16 @@ impl HealthChecker {
     }
 }
 
+/// The first few of `ids`, for a check's message
+fn examples(ids: &[String]) -> String {
+    const SHOWN: usize = 3;
+    match ids.len() {
+        0 => String::new(),
+        len if len <= SHOWN => format!(" ({})", ids.join(", ")),
+        len => format!(" ({}, …{} more)", ids[..SHOWN].join(", "), len - SHOWN),
+    }
+}
+
 /// Print health report in human-readable format
 pub fn print_health_report(report: &HealthReport) {
     println!("\n{}", "=".repeat(80));
@@ -553,6 +909,25 @@ pub fn print_health_report(report: &HealthReport) {
         report.resources.memory_per_vector
     );
 
+    // Checks
+    if !report.checks.is_empty() {
+        println!("\n🩺 Checks:");
+        for check in &report.checks {
+            let icon = match check.severity {
+                CheckSeverity::Ok => "✓",
+                CheckSeverity::Warn => "⚠️",
+                CheckSeverity::Critical => "❌",
+            };
+            println!(
+                "  {} {:<9} {:<21} {}",
+                icon,
+                check.severity.as_str(),
+                check.code.as_str(),
+                check.message
+            );
+        }
+    }
+
     // Alerts
     if !report.alerts.is_empty() {
         println!("\n🚨 Alerts ({}):", report.alerts.len());
@@ -599,6 +974,119 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_resources_come_from_store_stats() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut store = VecStore::open(temp_dir.path().join("test.db"))?;
+        let mut fields = std::collections::HashMap::new();
+        fields.insert("title".to_string(), serde_json::json!("hello"));
+        store.upsert(
+            "doc".into(),
+            vec![1.0, 2.0, 3.0],
+            crate::store::Metadata { fields },
+        )?;
+        store.save()?;
+
+        let stats = store.stats();
+        let report = HealthChecker::default().check(&store)?;
+        assert_eq!(
+            report.resources.memory_bytes,
+            stats.vector_memory_bytes + stats.index_link_bytes + stats.metadata_bytes
+        );
+        assert_eq!(report.resources.disk_bytes as u64, stats.disk_bytes);
+        assert!(report.resources.disk_bytes > 0);
+
+        Ok(())
+    }
+
+    fn record(store: &mut VecStore, id: &str, vector: Vec<f32>) -> Result<()> {
+        store.upsert(
+            id.into(),
+            vector,
+            crate::store::Metadata {
+                fields: std::collections::HashMap::new(),
+            },
+        )?;
+        Ok(())
+    }
+
+    fn check_result(report: &HealthReport, code: HealthCheckCode) -> &HealthCheckResult {
+        report.checks.iter().find(|c| c.code == code).unwrap()
+    }
+
+    #[test]
+    fn test_checks_pass_on_a_saved_store() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut store = VecStore::open(temp_dir.path().join("test.db"))?;
+        for i in 0..5 {
+            record(&mut store, &format!("vec_{}", i), vec![1.0, i as f32, 2.0])?;
+        }
+        store.save()?;
+
+        let report = HealthChecker::default().check(&store)?;
+        assert_eq!(report.checks.len(), 9);
+        assert!(
+            report
+                .checks
+                .iter()
+                .all(|c| c.severity == CheckSeverity::Ok),
+            "{:?}",
+            report.checks
+        );
+        assert_eq!(report.worst_severity(), CheckSeverity::Ok);
+        assert!(report.index.integrity_ok);
+
+        // Serialized with snake_case codes and lowercase severities
+        let json = serde_json::to_value(&report)?;
+        assert_eq!(json["checks"][0]["code"], "index_consistency");
+        assert_eq!(json["checks"][0]["severity"], "ok");
+
+        // Unsaved changes count against a zero staleness allowance
+        record(&mut store, "vec_5", vec![1.0, 5.0, 2.0])?;
+        let checker = HealthChecker::new(HealthCheckConfig {
+            stale_save_warning_secs: 0,
+            ..HealthCheckConfig::default()
+        });
+        let report = checker.check(&store)?;
+        let staleness = check_result(&report, HealthCheckCode::SaveStaleness);
+        assert_eq!(staleness.severity, CheckSeverity::Warn);
+        assert_eq!(report.status, HealthStatus::Degraded);
+        assert_eq!(report.worst_severity().exit_code(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_checks_flag_nan_and_zero_vectors() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut store = VecStore::open(temp_dir.path().join("test.db"))?;
+        record(&mut store, "good", vec![1.0, 2.0, 3.0])?;
+        record(&mut store, "nan", vec![1.0, f32::NAN, 3.0])?;
+        record(&mut store, "zero", vec![0.0, 0.0, 0.0])?;
+        store.save()?;
+
+        let report = HealthChecker::default().check(&store)?;
+        let non_finite = check_result(&report, HealthCheckCode::NonFiniteVectors);
+        assert_eq!(non_finite.severity, CheckSeverity::Critical);
+        assert!(
+            non_finite.message.contains("(nan)"),
+            "{}",
+            non_finite.message
+        );
+        let zeros = check_result(&report, HealthCheckCode::ZeroVectors);
+        assert_eq!(zeros.severity, CheckSeverity::Warn);
+        assert!(zeros.message.contains("(zero)"), "{}", zeros.message);
+        assert_eq!(
+            check_result(&report, HealthCheckCode::DimensionUniformity).severity,
+            CheckSeverity::Ok
+        );
+
+        assert_eq!(report.status, HealthStatus::Unhealthy);
+        assert_eq!(report.worst_severity().exit_code(), 2);
+
+        Ok(())
+    }
+
     #[test]
     fn test_deletion_ratio_alert() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -5,8 +5,11 @@
 //!
 //! ## Features
 //!
-//! - **JSONL (JSON Lines)**: Human-readable, streaming-friendly format
-//! - **Parquet**: Columnar format with high compression (optional feature)
+//! - **JSONL (JSON Lines)**: One [`Record`] per line, in the same JSON shape
+//!   backups, store files and the HTTP API use
+//! - **CSV**: Vectors as JSON arrays or delimited components, other columns as metadata
+//! - **NumPy**: `.npy` matrices and uncompressed `.npz` archives of vectors
+//! - **Parquet**: Columnar format for pandas, DuckDB and Arrow pipelines (optional feature)
 //! - **Streaming**: Process large datasets without loading into memory
 //! - **Batch processing**: Efficient bulk imports/exports
 //!
@@ -45,15 +48,27 @@
 //! # }
 //! ```
 
-use crate::store::{Metadata, Record, VecStore};
+#[cfg(feature = "parquet-export")]
+mod parquet;
+
+#[cfg(feature = "parquet-export")]
+pub use self::parquet::{MetadataColumns, ParquetExport, PARQUET_ROW_GROUP_SIZE};
+
+#[cfg(feature = "parquet-export")]
+pub(crate) use self::parquet::{count_rows as count_parquet_rows, read_rows as read_parquet_rows};
+
+use crate::store::{make_record, Metadata, Record, UpsertPolicy, UpsertSummary, VecStore};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
-/// Export record format (JSONL compatible)
+/// A JSONL line without the store's bookkeeping fields (`created_at`,
+/// `version`, ...), as written by exports before they wrote full [`Record`]s
+///
+/// Imports still accept it, and stamp each record as newly created.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportRecord {
     /// Vector ID
@@ -64,6 +79,18 @@ pub struct ExportRecord {
 
     /// Metadata (JSON object)
     pub metadata: serde_json::Value,
+
+    /// Source text, if the record keeps it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+
+    /// Document the record is a chunk of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+
+    /// Position among the parent's chunks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 impl From<Record> for ExportRecord {
@@ -75,6 +102,9 @@ impl From<Record> for ExportRecord {
             id: record.id,
             vector: record.vector,
             metadata: metadata_json,
+            text: record.text,
+            parent_id: record.parent_id,
+            chunk_index: record.chunk_index,
         }
     }
 }
@@ -92,6 +122,205 @@ fn value_to_metadata(value: serde_json::Value) -> Metadata {
     }
 }
 
+/// Build a record from an imported line, rejecting metadata that isn't an object
+fn import_record(record: ExportRecord) -> Result<Record> {
+    if !(record.metadata.is_object() || record.metadata.is_null()) {
+        return Err(anyhow::anyhow!("metadata must be a JSON object"));
+    }
+    Ok(Record {
+        text: record.text,
+        parent_id: record.parent_id,
+        chunk_index: record.chunk_index,
+        ..make_record(record.id, record.vector, value_to_metadata(record.metadata))
+    })
+}
+
+/// Parse a JSONL line: a full [`Record`] as exports write it (recognised by
+/// its `created_at` field), or an [`ExportRecord`]
+pub fn parse_jsonl_record(line: &str) -> Result<Record> {
+    let value: serde_json::Value = serde_json::from_str(line).context("invalid JSON")?;
+    if value.get("created_at").is_some() {
+        serde_json::from_value(value).context("invalid record")
+    } else {
+        import_record(serde_json::from_value(value).context("invalid record")?)
+    }
+}
+
+/// File formats [`Importer`] reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Jsonl,
+    Csv,
+    /// A `.npy` array or `.npz` archive
+    Npy,
+    /// Needs the `parquet-export` feature to import
+    Parquet,
+}
+
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const PARQUET_MAGIC: &[u8] = b"PAR1";
+
+impl FileFormat {
+    /// Guess a file's format from its extension, or failing that its first bytes
+    pub fn detect<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("jsonl" | "ndjson" | "json") => return Ok(Self::Jsonl),
+            Some("csv") => return Ok(Self::Csv),
+            Some("npy" | "npz") => return Ok(Self::Npy),
+            Some("parquet") => return Ok(Self::Parquet),
+            _ => {}
+        }
+
+        let mut head = Vec::new();
+        File::open(path)
+            .with_context(|| format!("Failed to open file: {:?}", path))?
+            .take(256)
+            .read_to_end(&mut head)?;
+        if head.starts_with(NPY_MAGIC) || head.starts_with(ZIP_MAGIC) {
+            return Ok(Self::Npy);
+        }
+        if head.starts_with(PARQUET_MAGIC) {
+            return Ok(Self::Parquet);
+        }
+        match head.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') => Ok(Self::Jsonl),
+            Some(_) => Ok(Self::Csv),
+            None => Err(anyhow::anyhow!(
+                "Cannot detect the format of empty file {:?}",
+                path
+            )),
+        }
+    }
+
+    /// Number of rows an import of `path` in this format would read
+    ///
+    /// Takes a pass over JSONL and CSV files; NumPy and Parquet files give
+    /// it in their header and footer. Used to size progress bars, so rows
+    /// that would fail to parse are still counted.
+    pub fn count_rows<P: AsRef<Path>>(self, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        match self {
+            Self::Jsonl => {
+                let file =
+                    File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?;
+                let mut rows = 0;
+                for line in BufReader::new(file).lines() {
+                    if !line?.trim().is_empty() {
+                        rows += 1;
+                    }
+                }
+                Ok(rows)
+            }
+            Self::Csv => {
+                let file =
+                    File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?;
+                let mut reader = CsvReader::new(BufReader::new(file));
+                let mut rows: usize = 0;
+                while reader.next_record()?.is_some() {
+                    rows += 1;
+                }
+                // Less the header
+                Ok(rows.saturating_sub(1))
+            }
+            Self::Npy => {
+                let bytes = std::fs::read(path)
+                    .with_context(|| format!("Failed to read file: {:?}", path))?;
+                let array = if bytes.starts_with(ZIP_MAGIC) {
+                    npz_vectors(&bytes)?
+                } else {
+                    NpyArray::parse(&bytes)?
+                };
+                Ok(array.rows)
+            }
+            #[cfg(feature = "parquet-export")]
+            Self::Parquet => count_parquet_rows(path),
+            #[cfg(not(feature = "parquet-export"))]
+            Self::Parquet => Err(anyhow::anyhow!(
+                "Reading Parquet requires the parquet-export feature"
+            )),
+        }
+    }
+}
+
+/// How vectors are written in a CSV column
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VectorFormat {
+    /// A JSON array when the cell starts with `[`, otherwise components
+    /// separated by `;` (as written by `vecstore export`)
+    #[default]
+    Auto,
+    /// A JSON array, e.g. `[0.1, 0.2]`
+    Json,
+    /// Components separated by this character, e.g. `0.1 0.2` for `' '`
+    Delimited(char),
+}
+
+/// Which CSV columns hold what
+///
+/// Columns named `text`, `parent_id` and `chunk_index` fill those record
+/// fields. Metadata cells are read as JSON when they parse (so `3` is a
+/// number and `true` a boolean) and as strings otherwise; empty cells are
+/// left out.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Column holding the record id (default `id`)
+    pub id_column: String,
+    /// Column holding the vector (default `vector`)
+    pub vector_column: String,
+    pub vector_format: VectorFormat,
+    /// Columns to import as metadata, each with the key to store it under.
+    /// `None` imports every other column; a `metadata.` prefix is dropped
+    /// and the dots in the rest nest the value, undoing `vecstore export`.
+    pub metadata_columns: Option<Vec<(String, String)>>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            id_column: "id".to_string(),
+            vector_column: "vector".to_string(),
+            vector_format: VectorFormat::default(),
+            metadata_columns: None,
+        }
+    }
+}
+
+/// A row that was not imported
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportFailure {
+    /// Line number for JSONL and CSV (from 1), array row for NumPy (from 0)
+    pub row: usize,
+    /// Missing when the row could not be parsed
+    pub id: Option<String>,
+    pub reason: String,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// What happened to the rows that were written (or would be, in a dry run)
+    pub summary: UpsertSummary,
+    /// Rows skipped in partial mode, in file order
+    pub failed: Vec<ImportFailure>,
+}
+
+impl ImportReport {
+    /// Rows read so far, whether written, skipped or failed
+    pub fn rows(&self) -> usize {
+        self.summary.inserted + self.summary.updated + self.summary.skipped + self.failed.len()
+    }
+}
+
+/// A row read from an input file: its number, and the record or why it
+/// could not be built
+pub(crate) type Row = (usize, Result<Record>);
+
 /// Exporter for writing vecstore data to files
 pub struct Exporter<'a> {
     store: &'a VecStore,
@@ -121,9 +350,8 @@ impl<'a> Exporter<'a> {
         let records = self.store.list_all();
 
         for record in records {
-            let export_record = ExportRecord::from(record);
-            let json = serde_json::to_string(&export_record)
-                .context("Failed to serialize record to JSON")?;
+            let json =
+                serde_json::to_string(&record).context("Failed to serialize record to JSON")?;
 
             writeln!(writer, "{}", json).context("Failed to write JSONL line")?;
             count += 1;
@@ -152,8 +380,7 @@ impl<'a> Exporter<'a> {
         let records = self.store.list_all();
 
         for record in records.into_iter().filter(|r| filter_fn(r)) {
-            let export_record = ExportRecord::from(record);
-            let json = serde_json::to_string(&export_record)?;
+            let json = serde_json::to_string(&record)?;
 
             writeln!(writer, "{}", json)?;
             count += 1;
@@ -164,101 +391,67 @@ impl<'a> Exporter<'a> {
     }
 
     /// Export to Parquet format (requires parquet-export feature)
+    ///
+    /// Metadata is written as one JSON column; see [`ParquetExport`] for
+    /// the schema and for exploded metadata columns.
     #[cfg(feature = "parquet-export")]
     pub fn to_parquet<P: AsRef<Path>>(&self, path: P) -> Result<usize> {
-        use arrow::array::{ArrayRef, Float32Array, ListArray, StringArray};
-        use arrow::datatypes::{DataType, Field, Schema as ArrowSchema};
-        use arrow::record_batch::RecordBatch;
-        use parquet::arrow::ArrowWriter;
-        use parquet::basic::Compression;
-        use parquet::file::properties::WriterProperties;
-        use std::sync::Arc;
-
-        let records = self.store.list_all();
-
-        if records.is_empty() {
-            return Ok(0);
-        }
-
-        // Get vector dimension from first record
-        let dim = records[0].vector.len();
-
-        // Build Arrow schema
-        let schema = Arc::new(ArrowSchema::new(vec![
-            Field::new("id", DataType::Utf8, false),
-            Field::new(
-                "vector",
-                DataType::List(Arc::new(Field::new("item", DataType::Float32, true))),
-                false,
-            ),
-            Field::new("metadata", DataType::Utf8, true),
-        ]));
-
-        // Create Parquet writer
-        let file = File::create(path.as_ref())?;
-        let props = WriterProperties::builder()
-            .set_compression(Compression::SNAPPY)
-            .build();
-
-        let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
-
-        // Process in batches (1000 records at a time)
-        const BATCH_SIZE: usize = 1000;
-        let mut total_count = 0;
-
-        for chunk in records.chunks(BATCH_SIZE) {
-            let mut ids = Vec::with_capacity(chunk.len());
-            let mut vector_values = Vec::with_capacity(chunk.len() * dim);
-            let mut vector_offsets = vec![0i32];
-            let mut metadatas = Vec::with_capacity(chunk.len());
-
-            for record in chunk {
-                ids.push(record.id.clone());
-
-                // Flatten vectors
-                vector_values.extend_from_slice(&record.vector);
-                vector_offsets.push(vector_offsets.last().unwrap() + record.vector.len() as i32);
-
-                // Serialize metadata
-                let metadata_str =
-                    serde_json::to_string(&record.metadata).unwrap_or_else(|_| "{}".to_string());
-                metadatas.push(metadata_str);
-            }
-
-            // Build arrays
-            let id_array = Arc::new(StringArray::from(ids)) as ArrayRef;
-
-            let vector_array = Arc::new(ListArray::try_new(
-                Arc::new(Field::new("item", DataType::Float32, true)),
-                arrow::buffer::OffsetBuffer::new(vector_offsets.into()),
-                Arc::new(Float32Array::from(vector_values)),
-                None,
-            )?) as ArrayRef;
-
-            let metadata_array = Arc::new(StringArray::from(metadatas)) as ArrayRef;
-
-            // Create record batch
-            let batch =
-                RecordBatch::try_new(schema.clone(), vec![id_array, vector_array, metadata_array])?;
-
-            writer.write(&batch)?;
-            total_count += chunk.len();
+        let records = self.store.scan(None, 0, usize::MAX);
+        let mut export = ParquetExport::create(path, self.store.dimension(), None)?;
+        for chunk in records.chunks(PARQUET_ROW_GROUP_SIZE) {
+            export.write(chunk)?;
         }
-
-        writer.close()?;
-        Ok(total_count)
+        export.finish()
     }
 }
 
 /// Importer for reading data into vecstore
 pub struct Importer<'a> {
     store: &'a mut VecStore,
+    policy: UpsertPolicy,
+    dry_run: bool,
+    partial: bool,
+    progress: Option<&'a mut dyn FnMut(usize)>,
 }
 
 impl<'a> Importer<'a> {
     /// Create a new importer
     pub fn new(store: &'a mut VecStore) -> Self {
-        Self { store }
+        Self {
+            store,
+            policy: UpsertPolicy::default(),
+            dry_run: false,
+            partial: false,
+            progress: None,
+        }
+    }
+
+    /// Set what happens to records whose id already exists (default: overwrite)
+    pub fn with_policy(mut self, policy: UpsertPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Validate and count the records without writing them
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Skip rows that fail to parse or validate and list them in the
+    /// report, instead of stopping at the first one
+    ///
+    /// A vector whose dimension differs from the store's (or the first
+    /// row's) still stops the import.
+    pub fn with_partial(mut self, partial: bool) -> Self {
+        self.partial = partial;
+        self
+    }
+
+    /// Call `progress` with the number of rows read after each batch
+    pub fn with_progress(mut self, progress: &'a mut dyn FnMut(usize)) -> Self {
+        self.progress = Some(progress);
+        self
     }
 
     /// Import vectors from JSONL format
@@ -268,110 +461,695 @@ impl<'a> Importer<'a> {
     /// * `batch_size` - Number of records to insert at once (0 = one at a time)
     ///
     /// # Returns
-    /// Number of records imported
+    /// Number of records imported (inserted or updated)
     pub fn from_jsonl<P: AsRef<Path>>(&mut self, path: P, batch_size: usize) -> Result<usize> {
+        let summary = self.import_jsonl(path, batch_size)?.summary;
+        Ok(summary.inserted + summary.updated)
+    }
+
+    /// Import vectors from JSONL format, returning the import report
+    ///
+    /// Each batch is written under the importer's [`UpsertPolicy`] and
+    /// either fully applied or not at all; batches before a failing one stay
+    /// written. In dry-run mode the whole file is checked as one batch, so
+    /// the counts match an import with a single batch.
+    ///
+    /// Every line must parse, carry a non-empty id and a vector of the
+    /// store's dimension, and have a JSON object (or null) as metadata.
+    /// Lines may be full [`Record`]s, as exports write them, or
+    /// [`ExportRecord`]s.
+    pub fn import_jsonl<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        batch_size: usize,
+    ) -> Result<ImportReport> {
         let file = File::open(path.as_ref())
             .with_context(|| format!("Failed to open file: {:?}", path.as_ref()))?;
 
-        let reader = BufReader::new(file);
-        let mut count = 0;
-        let mut batch = Vec::new();
+        let rows =
+            BufReader::new(file)
+                .lines()
+                .enumerate()
+                .filter_map(|(index, line)| match line {
+                    Ok(line) if line.trim().is_empty() => None,
+                    Ok(line) => Some(Ok((index + 1, parse_jsonl_record(&line)))),
+                    Err(e) => Some(Err(anyhow::Error::from(e)
+                        .context(format!("Failed to read line {}", index + 1)))),
+                });
+        self.import_rows(rows, "line", batch_size)
+    }
+
+    /// Import vectors from a CSV file with a header row
+    ///
+    /// See [`CsvOptions`] for how columns are mapped. Quoted fields may
+    /// contain commas, doubled quotes and line breaks.
+    pub fn import_csv<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        options: &CsvOptions,
+        batch_size: usize,
+    ) -> Result<ImportReport> {
+        let file = File::open(path.as_ref())
+            .with_context(|| format!("Failed to open file: {:?}", path.as_ref()))?;
+        let mut reader = CsvReader::new(BufReader::new(file));
 
-        for (line_num, line) in reader.lines().enumerate() {
-            let line = line.with_context(|| format!("Failed to read line {}", line_num + 1))?;
+        let Some((_, header)) = reader.next_record()? else {
+            return Ok(ImportReport::default());
+        };
+        let layout = CsvLayout::new(&header, options)?;
 
-            if line.trim().is_empty() {
-                continue; // Skip empty lines
-            }
+        let rows = std::iter::from_fn(|| reader.next_record().transpose()).map(|record| {
+            record.map(|(line, fields)| (line, layout.record(fields, options.vector_format)))
+        });
+        self.import_rows(rows, "line", batch_size)
+    }
 
-            let record: ExportRecord = serde_json::from_str(&line)
-                .with_context(|| format!("Failed to parse JSON on line {}", line_num + 1))?;
+    /// Import a matrix of vectors from a `.npy` file, or from an uncompressed
+    /// `.npz` archive (`np.savez`)
+    ///
+    /// The array must be float32 or float64 with one vector per row. From an
+    /// archive, the only array is used, or else the one named `vectors`,
+    /// `embeddings` or `arr_0`. `ids` names a text file with one id per row;
+    /// without it rows get their index (`"0"`, `"1"`, ...) as id.
+    pub fn import_npy<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        ids: Option<&Path>,
+        batch_size: usize,
+    ) -> Result<ImportReport> {
+        let bytes = std::fs::read(path.as_ref())
+            .with_context(|| format!("Failed to read file: {:?}", path.as_ref()))?;
+        let array = if bytes.starts_with(ZIP_MAGIC) {
+            npz_vectors(&bytes)?
+        } else {
+            NpyArray::parse(&bytes)?
+        };
 
-            if batch_size > 0 {
-                batch.push(record);
+        let ids = match ids {
+            Some(ids_path) => {
+                let content = std::fs::read_to_string(ids_path)
+                    .with_context(|| format!("Failed to read ids file: {:?}", ids_path))?;
+                let ids: Vec<String> = content.lines().map(str::to_string).collect();
+                if ids.len() != array.rows {
+                    return Err(anyhow::anyhow!(
+                        "Ids file {:?} has {} ids for {} vectors",
+                        ids_path,
+                        ids.len(),
+                        array.rows
+                    ));
+                }
+                Some(ids)
+            }
+            None => None,
+        };
+
+        let rows = (0..array.rows).map(|row| {
+            let id = ids
+                .as_ref()
+                .map_or_else(|| row.to_string(), |ids| ids[row].clone());
+            let metadata = Metadata {
+                fields: HashMap::new(),
+            };
+            Ok((row, Ok(make_record(id, array.row(row), metadata))))
+        });
+        self.import_rows(rows, "row", batch_size)
+    }
 
-                if batch.len() >= batch_size {
-                    self.flush_batch(&mut batch)?;
-                    count += batch_size;
-                    batch.clear();
+    /// Write `rows` in batches, stopping at the first error unless partial
+    ///
+    /// `unit` names a row in messages ("line" or "row").
+    fn import_rows(
+        &mut self,
+        rows: impl Iterator<Item = Result<Row>>,
+        unit: &str,
+        batch_size: usize,
+    ) -> Result<ImportReport> {
+        let mut report = ImportReport::default();
+        let mut batch = Vec::new();
+        let mut numbers = Vec::new();
+        let mut dimension = self.store.dimension();
+
+        for row in rows {
+            let (number, record) = row?;
+            let record = match record {
+                Ok(record) => record,
+                Err(e) if self.partial => {
+                    report.failed.push(ImportFailure {
+                        row: number,
+                        id: None,
+                        reason: format!("{:#}", e),
+                    });
+                    continue;
                 }
-            } else {
-                // Insert immediately
-                let metadata = value_to_metadata(record.metadata);
-                self.store.upsert(record.id, record.vector, metadata)?;
-                count += 1;
+                Err(e) => return Err(e.context(format!("Failed to parse {} {}", unit, number))),
+            };
+
+            if dimension == 0 {
+                dimension = record.vector.len();
+            } else if !record.vector.is_empty() && record.vector.len() != dimension {
+                return Err(anyhow::anyhow!(
+                    "Vector dimension mismatch on {} {} ('{}'): expected {}, got {}",
+                    unit,
+                    number,
+                    record.id,
+                    dimension,
+                    record.vector.len()
+                ));
+            }
+
+            batch.push(record);
+            numbers.push(number);
+            if !self.dry_run && batch.len() >= batch_size.max(1) {
+                self.write_rows(&mut batch, &mut numbers, unit, &mut report)?;
             }
         }
 
-        // Flush remaining batch
         if !batch.is_empty() {
-            let remaining = batch.len();
-            self.flush_batch(&mut batch)?;
-            count += remaining;
+            self.write_rows(&mut batch, &mut numbers, unit, &mut report)?;
         }
+        Ok(report)
+    }
 
-        Ok(count)
+    /// Validate and write one batch, draining it
+    fn write_rows(
+        &mut self,
+        batch: &mut Vec<Record>,
+        numbers: &mut Vec<usize>,
+        unit: &str,
+        report: &mut ImportReport,
+    ) -> Result<()> {
+        let invalid = self.store.validate_batch(batch);
+        if let (Some(first), false) = (invalid.first(), self.partial) {
+            return Err(anyhow::anyhow!(
+                "Invalid record on {} {} ('{}'): {}",
+                unit,
+                numbers[first.index],
+                first.id,
+                first.reason
+            ));
+        }
+
+        let mut invalid = invalid.into_iter().peekable();
+        let mut records = Vec::with_capacity(batch.len());
+        for (index, (record, number)) in batch.drain(..).zip(numbers.drain(..)).enumerate() {
+            match invalid.next_if(|item| item.index == index) {
+                Some(item) => report.failed.push(ImportFailure {
+                    row: number,
+                    id: Some(item.id),
+                    reason: item.reason,
+                }),
+                None => records.push(record),
+            }
+        }
+
+        report.summary += if self.dry_run {
+            self.store.dry_run_upsert(records, self.policy)?
+        } else {
+            self.store.batch_upsert_with_policy(records, self.policy)?
+        };
+        if let Some(progress) = self.progress.as_mut() {
+            progress(report.rows());
+        }
+        Ok(())
     }
 
     /// Import from Parquet format (requires parquet-export feature)
+    ///
+    /// # Returns
+    /// Number of records imported (inserted or updated)
     #[cfg(feature = "parquet-export")]
     pub fn from_parquet<P: AsRef<Path>>(&mut self, path: P) -> Result<usize> {
-        use arrow::array::{Array, AsArray};
-        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+        let summary = self.import_parquet(path, PARQUET_ROW_GROUP_SIZE)?.summary;
+        Ok(summary.inserted + summary.updated)
+    }
 
-        let file = File::open(path.as_ref())?;
-        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
-        let mut reader = builder.build()?;
+    /// Import a Parquet file with the [`ParquetExport`] schema, returning the
+    /// import report
+    ///
+    /// Files from other tools are read too: the vector column may be named
+    /// `embedding` or `values` (as in Pinecone dumps) and be a variable-length
+    /// list of float32 or float64, the id column may hold integers, and every
+    /// column but `id` and the vector is optional. Rows are numbered from 0.
+    #[cfg(feature = "parquet-export")]
+    pub fn import_parquet<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        batch_size: usize,
+    ) -> Result<ImportReport> {
+        let rows = self::parquet::read_rows(path.as_ref(), batch_size)?;
+        self.import_rows(rows, "row", batch_size)
+    }
+}
 
-        let mut count = 0;
+/// Reads CSV records, which may span lines inside quoted fields
+struct CsvReader<R> {
+    lines: std::io::Lines<R>,
+    line: usize,
+}
 
-        while let Some(batch) = reader.next() {
-            let batch = batch?;
+impl<R: BufRead> CsvReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            line: 0,
+        }
+    }
 
-            // Extract columns
-            let id_array = batch.column(0).as_string::<i32>();
+    /// The next non-blank record and the line it starts on
+    fn next_record(&mut self) -> Result<Option<(usize, Vec<String>)>> {
+        let mut text = loop {
+            match self.lines.next() {
+                None => return Ok(None),
+                Some(line) => {
+                    self.line += 1;
+                    let line =
+                        line.with_context(|| format!("Failed to read line {}", self.line))?;
+                    if !line.trim().is_empty() {
+                        break line;
+                    }
+                }
+            }
+        };
+        if self.line == 1 {
+            // Drop a byte order mark
+            text = text.trim_start_matches('\u{feff}').to_string();
+        }
 
-            let vector_array = batch.column(1).as_list::<i32>();
+        let start = self.line;
+        loop {
+            if let Some(fields) = split_csv_record(&text) {
+                return Ok(Some((start, fields)));
+            }
+            match self.lines.next() {
+                Some(line) => {
+                    self.line += 1;
+                    text.push('\n');
+                    text.push_str(&line?);
+                }
+                None => {
+                    return Err(anyhow::anyhow!(
+                        "Unterminated quoted field starting on line {}",
+                        start
+                    ))
+                }
+            }
+        }
+    }
+}
 
-            let metadata_array = batch.column(2).as_string::<i32>();
+/// Split a CSV record into fields, or `None` while a quoted field is open
+fn split_csv_record(text: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes => {
+                if chars.next_if_eq(&'"').is_some() {
+                    field.push('"');
+                } else {
+                    in_quotes = false;
+                }
+            }
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    (!in_quotes).then(|| {
+        fields.push(field);
+        fields
+    })
+}
 
-            // Process each row
-            for row_idx in 0..batch.num_rows() {
-                let id = id_array.value(row_idx).to_string();
+/// Positions of the columns a CSV import reads
+struct CsvLayout {
+    width: usize,
+    id: usize,
+    vector: usize,
+    text: Option<usize>,
+    parent_id: Option<usize>,
+    chunk_index: Option<usize>,
+    /// Column and the metadata key path it fills
+    metadata: Vec<(usize, Vec<String>)>,
+}
 
-                // Extract vector
-                let vector_list = vector_array.value(row_idx);
-                let vector_data = vector_list
-                    .as_any()
-                    .downcast_ref::<arrow::array::Float32Array>()
-                    .context("Expected Float32Array for vector data")?;
+impl CsvLayout {
+    fn new(header: &[String], options: &CsvOptions) -> Result<Self> {
+        let find = |name: &str| header.iter().position(|column| column == name);
+        let require = |name: &str| {
+            find(name).ok_or_else(|| anyhow::anyhow!("CSV header has no '{}' column", name))
+        };
+        let id = require(&options.id_column)?;
+        let vector = require(&options.vector_column)?;
+        let text = find("text");
+        let parent_id = find("parent_id");
+        let chunk_index = find("chunk_index");
+
+        let metadata = match &options.metadata_columns {
+            Some(columns) => columns
+                .iter()
+                .map(|(column, key)| Ok((require(column)?, vec![key.clone()])))
+                .collect::<Result<_>>()?,
+            None => {
+                let reserved = [Some(id), Some(vector), text, parent_id, chunk_index];
+                header
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| !reserved.contains(&Some(*index)))
+                    .map(|(index, column)| {
+                        let path = match column.strip_prefix("metadata.") {
+                            Some(path) => path.split('.').map(str::to_string).collect(),
+                            None => vec![column.clone()],
+                        };
+                        (index, path)
+                    })
+                    .collect()
+            }
+        };
 
-                let vector: Vec<f32> = (0..vector_data.len())
-                    .map(|i| vector_data.value(i))
-                    .collect();
+        Ok(Self {
+            width: header.len(),
+            id,
+            vector,
+            text,
+            parent_id,
+            chunk_index,
+            metadata,
+        })
+    }
 
-                // Extract metadata
-                let metadata_str = metadata_array.value(row_idx);
-                let metadata_value: serde_json::Value =
-                    serde_json::from_str(metadata_str).unwrap_or(serde_json::json!({}));
-                let metadata = value_to_metadata(metadata_value);
+    fn record(&self, fields: Vec<String>, vector_format: VectorFormat) -> Result<Record> {
+        if fields.len() != self.width {
+            return Err(anyhow::anyhow!(
+                "expected {} fields, found {}",
+                self.width,
+                fields.len()
+            ));
+        }
+        let optional = |column: Option<usize>| {
+            column
+                .map(|index| fields[index].clone())
+                .filter(|value| !value.is_empty())
+        };
 
-                self.store.upsert(id, vector, metadata)?;
-                count += 1;
+        let vector = parse_vector(&fields[self.vector], vector_format)?;
+        let chunk_index = optional(self.chunk_index)
+            .map(|value| value.trim().parse::<usize>())
+            .transpose()
+            .context("invalid chunk_index")?;
+
+        let mut metadata = serde_json::Map::new();
+        for (index, path) in &self.metadata {
+            let cell = &fields[*index];
+            if !cell.is_empty() {
+                let value = serde_json::from_str(cell)
+                    .unwrap_or_else(|_| serde_json::Value::String(cell.clone()));
+                insert_path(&mut metadata, path, value);
             }
         }
 
-        Ok(count)
+        Ok(Record {
+            text: optional(self.text),
+            parent_id: optional(self.parent_id),
+            chunk_index,
+            ..make_record(
+                fields[self.id].clone(),
+                vector,
+                value_to_metadata(serde_json::Value::Object(metadata)),
+            )
+        })
+    }
+}
+
+fn parse_vector(cell: &str, format: VectorFormat) -> Result<Vec<f32>> {
+    let cell = cell.trim();
+    let delimiter = match format {
+        VectorFormat::Auto if cell.starts_with('[') => None,
+        VectorFormat::Auto => Some(';'),
+        VectorFormat::Json => None,
+        VectorFormat::Delimited(delimiter) => Some(delimiter),
+    };
+    match delimiter {
+        None => serde_json::from_str(cell).context("vector is not a JSON array of numbers"),
+        Some(delimiter) => cell
+            .split(delimiter)
+            .map(str::trim)
+            .filter(|component| !(delimiter.is_whitespace() && component.is_empty()))
+            .map(|component| {
+                component
+                    .parse::<f32>()
+                    .with_context(|| format!("invalid vector component '{}'", component))
+            })
+            .collect(),
     }
+}
 
-    /// Flush a batch of records to the store
-    fn flush_batch(&mut self, batch: &mut Vec<ExportRecord>) -> Result<()> {
-        for record in batch.drain(..) {
-            let metadata = value_to_metadata(record.metadata);
-            self.store.upsert(record.id, record.vector, metadata)?;
+/// Set `path` in `map`, creating (or replacing non-object values with)
+/// nested objects along the way
+fn insert_path(
+    map: &mut serde_json::Map<String, serde_json::Value>,
+    path: &[String],
+    value: serde_json::Value,
+) {
+    let (last, parents) = path.split_last().expect("metadata path is never empty");
+    let mut map = map;
+    for key in parents {
+        let entry = map
+            .entry(key.clone())
+            .or_insert_with(|| serde_json::Value::Object(Default::default()));
+        if !entry.is_object() {
+            *entry = serde_json::Value::Object(Default::default());
         }
-        Ok(())
+        map = entry.as_object_mut().expect("just made an object");
     }
+    map.insert(last.clone(), value);
+}
+
+/// A 1-D or 2-D float array in a `.npy` file, read a row at a time
+struct NpyArray<'a> {
+    rows: usize,
+    cols: usize,
+    /// Bytes per element: 4 for float32, 8 for float64
+    width: usize,
+    fortran_order: bool,
+    data: &'a [u8],
+}
+
+impl<'a> NpyArray<'a> {
+    fn parse(bytes: &'a [u8]) -> Result<Self> {
+        if !bytes.starts_with(NPY_MAGIC) || bytes.len() < 10 {
+            return Err(anyhow::anyhow!("Not a NumPy .npy file"));
+        }
+        let (header_len, header_start) = match bytes[6] {
+            1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+            2 | 3 if bytes.len() >= 12 => (
+                u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+                12,
+            ),
+            version => return Err(anyhow::anyhow!("Unsupported .npy version {}", version)),
+        };
+        let header = bytes
+            .get(header_start..header_start + header_len)
+            .context("Truncated .npy header")?;
+        let header = std::str::from_utf8(header).context("Invalid .npy header")?;
+
+        let width = match npy_header_value(header, "descr")? {
+            "'<f4'" => 4,
+            "'<f8'" => 8,
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Unsupported .npy dtype {}; expected float32 or float64",
+                    other
+                ))
+            }
+        };
+        let fortran_order = npy_header_value(header, "fortran_order")? == "True";
+        let shape = npy_header_value(header, "shape")?;
+        let dims = shape
+            .trim_matches(|c| c == '(' || c == ')')
+            .split(',')
+            .map(str::trim)
+            .filter(|dim| !dim.is_empty())
+            .map(|dim| dim.parse::<usize>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .with_context(|| format!("Invalid .npy shape {}", shape))?;
+        let (rows, cols) = match dims[..] {
+            [cols] => (1, cols),
+            [rows, cols] => (rows, cols),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Expected a 1-D or 2-D array of vectors, got shape {}",
+                    shape
+                ))
+            }
+        };
+
+        let data = &bytes[header_start + header_len..];
+        if data.len() < rows * cols * width {
+            return Err(anyhow::anyhow!(
+                "Truncated .npy data: expected {} values",
+                rows * cols
+            ));
+        }
+        Ok(Self {
+            rows,
+            cols,
+            width,
+            fortran_order,
+            data,
+        })
+    }
+
+    fn row(&self, row: usize) -> Vec<f32> {
+        (0..self.cols)
+            .map(|col| {
+                let index = if self.fortran_order {
+                    col * self.rows + row
+                } else {
+                    row * self.cols + col
+                };
+                let bytes = &self.data[index * self.width..(index + 1) * self.width];
+                match self.width {
+                    4 => f32::from_le_bytes(bytes.try_into().unwrap()),
+                    _ => f64::from_le_bytes(bytes.try_into().unwrap()) as f32,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The raw value of `key` in a `.npy` header, which is a Python dict literal
+/// such as `{'descr': '<f4', 'fortran_order': False, 'shape': (3, 4), }`
+fn npy_header_value<'h>(header: &'h str, key: &str) -> Result<&'h str> {
+    let pattern = format!("'{}':", key);
+    let start = header
+        .find(&pattern)
+        .with_context(|| format!(".npy header has no '{}'", key))?
+        + pattern.len();
+    let rest = header[start..].trim_start();
+    let end = if rest.starts_with('(') {
+        rest.find(')').map(|i| i + 1)
+    } else if let Some(quoted) = rest.strip_prefix('\'') {
+        quoted.find('\'').map(|i| i + 2)
+    } else {
+        rest.find([',', '}'])
+    };
+    let end = end.with_context(|| format!("Invalid '{}' in .npy header", key))?;
+    Ok(rest[..end].trim())
+}
+
+/// Find the vectors array in an `.npz` archive
+fn npz_vectors(bytes: &[u8]) -> Result<NpyArray<'_>> {
+    let members = zip_members(bytes)?;
+    let member = if members.len() == 1 {
+        &members[0]
+    } else {
+        ["vectors.npy", "embeddings.npy", "arr_0.npy"]
+            .iter()
+            .find_map(|name| members.iter().find(|(member, _)| member == name))
+            .ok_or_else(|| {
+                let names: Vec<_> = members.iter().map(|(name, _)| name.as_str()).collect();
+                anyhow::anyhow!(
+                    "No 'vectors', 'embeddings' or 'arr_0' array in .npz archive (found: {})",
+                    names.join(", ")
+                )
+            })?
+    };
+    NpyArray::parse(member.1).with_context(|| format!("Failed to read {} from .npz", member.0))
+}
+
+/// Names and contents of the entries of an uncompressed zip archive
+fn zip_members(bytes: &[u8]) -> Result<Vec<(String, &[u8])>> {
+    let u16_at = |at: usize| -> Result<usize> {
+        let b = bytes.get(at..at + 2).context("Truncated .npz archive")?;
+        Ok(u16::from_le_bytes([b[0], b[1]]) as usize)
+    };
+    let u32_at = |at: usize| -> Result<u64> {
+        let b = bytes.get(at..at + 4).context("Truncated .npz archive")?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as u64)
+    };
+    let u64_at = |at: usize| -> Result<u64> {
+        let b = bytes.get(at..at + 8).context("Truncated .npz archive")?;
+        Ok(u64::from_le_bytes(b.try_into().unwrap()))
+    };
+
+    // The end-of-central-directory record is at the end, before any comment
+    let eocd = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .find(|&i| bytes[i..].starts_with(b"PK\x05\x06"))
+        .context("Not a zip archive")?;
+    let mut entries = u16_at(eocd + 10)? as u64;
+    let mut offset = u32_at(eocd + 16)?;
+    if (entries == 0xFFFF || offset == 0xFFFF_FFFF)
+        && eocd >= 20
+        && bytes[eocd - 20..].starts_with(b"PK\x06\x07")
+    {
+        // Zip64 end-of-central-directory record, located by the locator
+        let zip64 = u64_at(eocd - 20 + 8)? as usize;
+        entries = u64_at(zip64 + 32)?;
+        offset = u64_at(zip64 + 48)?;
+    }
+
+    let mut members = Vec::new();
+    let mut at = offset as usize;
+    for _ in 0..entries {
+        if !bytes
+            .get(at..)
+            .is_some_and(|b| b.starts_with(b"PK\x01\x02"))
+        {
+            return Err(anyhow::anyhow!("Corrupt zip central directory"));
+        }
+        let method = u16_at(at + 10)?;
+        let mut size = u32_at(at + 20)?;
+        let name_len = u16_at(at + 28)?;
+        let extra_len = u16_at(at + 30)?;
+        let comment_len = u16_at(at + 32)?;
+        let mut local = u32_at(at + 42)?;
+        let name_bytes = bytes
+            .get(at + 46..at + 46 + name_len)
+            .context("Truncated .npz archive")?;
+        let name = String::from_utf8_lossy(name_bytes).into_owned();
+
+        // Zip64 sizes and offsets live in an extra field, in a fixed order,
+        // present only for the values saturated above
+        let mut extra = at + 46 + name_len;
+        let extra_end = extra + extra_len;
+        while extra + 4 <= extra_end {
+            let (id, len) = (u16_at(extra)?, u16_at(extra + 2)?);
+            if id == 1 {
+                let mut field = extra + 4;
+                if u32_at(at + 24)? == 0xFFFF_FFFF {
+                    field += 8;
+                }
+                if size == 0xFFFF_FFFF {
+                    size = u64_at(field)?;
+                    field += 8;
+                }
+                if local == 0xFFFF_FFFF {
+                    local = u64_at(field)?;
+                }
+            }
+            extra += 4 + len;
+        }
+
+        if method != 0 {
+            return Err(anyhow::anyhow!(
+                "'{}' is compressed; save the arrays with np.savez instead of np.savez_compressed",
+                name
+            ));
+        }
+        let local = local as usize;
+        let data_start = local + 30 + u16_at(local + 26)? + u16_at(local + 28)?;
+        let data = bytes
+            .get(data_start..data_start + size as usize)
+            .context("Truncated .npz archive")?;
+        members.push((name, data));
+
+        at += 46 + name_len + extra_len + comment_len;
+    }
+    Ok(members)
 }
 
 #[cfg(test)]
@@ -528,6 +1306,45 @@ mod tests {
         assert_eq!(new_store.len(), store.len());
     }
 
+    #[test]
+    fn test_roundtrip_keeps_text_and_parent() {
+        let (mut store, _temp_dir) = create_test_store();
+        store
+            .upsert_record(
+                make_record(
+                    "doc1#0",
+                    vec![1.0, 1.0, 1.0],
+                    Metadata {
+                        fields: HashMap::new(),
+                    },
+                )
+                .with_text("first chunk")
+                .with_parent("doc1", 0),
+            )
+            .unwrap();
+
+        let temp_file = NamedTempFile::new().unwrap();
+        Exporter::new(&store).to_jsonl(temp_file.path()).unwrap();
+        let exported = std::fs::read_to_string(temp_file.path()).unwrap();
+        // Plain records keep the old line layout
+        assert!(exported
+            .lines()
+            .filter(|line| !line.contains("doc1#0"))
+            .all(|line| !line.contains("text") && !line.contains("parent_id")));
+
+        let temp_dir2 = TempDir::new().unwrap();
+        let mut new_store = VecStore::open(temp_dir2.path().join("test.db")).unwrap();
+        Importer::new(&mut new_store)
+            .from_jsonl(temp_file.path(), 0)
+            .unwrap();
+
+        let chunk = new_store.get("doc1#0").unwrap();
+        assert_eq!(chunk.text.as_deref(), Some("first chunk"));
+        assert_eq!(chunk.parent_id.as_deref(), Some("doc1"));
+        assert_eq!(chunk.chunk_index, Some(0));
+        assert!(new_store.get("doc2").unwrap().text.is_none());
+    }
+
     #[test]
     fn test_empty_lines_ignored() {
         let temp_file = NamedTempFile::new().unwrap();
@@ -555,4 +1372,361 @@ mod tests {
         let count = importer.from_jsonl(temp_file.path(), 0).unwrap();
         assert_eq!(count, 2);
     }
+
+    fn write_jsonl(lines: &[&str]) -> NamedTempFile {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut writer = BufWriter::new(File::create(temp_file.path()).unwrap());
+        for line in lines {
+            writeln!(writer, "{}", line).unwrap();
+        }
+        writer.flush().unwrap();
+        temp_file
+    }
+
+    #[test]
+    fn test_import_conflict_policies() {
+        let (mut store, _temp_dir) = create_test_store();
+        let file = write_jsonl(&[
+            r#"{"id":"doc1","vector":[1.0,0.0,0.0],"metadata":{"lang":"en"}}"#,
+            r#"{"id":"doc4","vector":[0.0,1.0,0.0],"metadata":{}}"#,
+        ]);
+
+        let summary = Importer::new(&mut store)
+            .with_policy(UpsertPolicy::Skip)
+            .import_jsonl(file.path(), 10)
+            .unwrap()
+            .summary;
+        assert_eq!(
+            summary,
+            UpsertSummary {
+                inserted: 1,
+                updated: 0,
+                skipped: 1,
+                conflicts: 1,
+            }
+        );
+        assert!(store
+            .get("doc1")
+            .unwrap()
+            .metadata
+            .fields
+            .get("lang")
+            .is_none());
+
+        let summary = Importer::new(&mut store)
+            .with_policy(UpsertPolicy::MergeMetadata)
+            .import_jsonl(file.path(), 1)
+            .unwrap()
+            .summary;
+        assert_eq!((summary.updated, summary.conflicts), (2, 2));
+        let doc1 = store.get("doc1").unwrap();
+        assert_eq!(doc1.metadata.fields["title"], "Document 1");
+        assert_eq!(doc1.metadata.fields["lang"], "en");
+
+        let err = Importer::new(&mut store)
+            .with_policy(UpsertPolicy::ErrorOnConflict)
+            .import_jsonl(file.path(), 10)
+            .unwrap_err();
+        assert!(err.to_string().contains("conflict"));
+        assert_eq!(store.len(), 4);
+    }
+
+    #[test]
+    fn test_import_dry_run() {
+        let (mut store, _temp_dir) = create_test_store();
+        let file = write_jsonl(&[
+            r#"{"id":"doc1","vector":[1.0,0.0,0.0],"metadata":{}}"#,
+            r#"{"id":"new1","vector":[0.0,1.0,0.0],"metadata":{}}"#,
+            r#"{"id":"new1","vector":[0.0,0.0,1.0],"metadata":{}}"#,
+        ]);
+
+        let summary = Importer::new(&mut store)
+            .with_policy(UpsertPolicy::ErrorOnConflict)
+            .with_dry_run(true)
+            .import_jsonl(file.path(), 1)
+            .unwrap()
+            .summary;
+        assert_eq!((summary.inserted, summary.conflicts), (1, 2));
+        assert_eq!(store.len(), 3);
+        assert!(store.get("new1").is_none());
+
+        let bad_dimension = write_jsonl(&[r#"{"id":"x","vector":[1.0,2.0],"metadata":{}}"#]);
+        let bad_metadata = write_jsonl(&[r#"{"id":"x","vector":[1.0,2.0,3.0],"metadata":[1]}"#]);
+        let empty_id = write_jsonl(&[r#"{"id":" ","vector":[1.0,2.0,3.0],"metadata":{}}"#]);
+        for file in [&bad_dimension, &bad_metadata, &empty_id] {
+            assert!(Importer::new(&mut store)
+                .with_dry_run(true)
+                .import_jsonl(file.path(), 10)
+                .is_err());
+        }
+        assert_eq!(store.len(), 3);
+    }
+
+    #[test]
+    fn test_import_jsonl_reports_line_numbers() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        let file = write_jsonl(&[
+            r#"{"id":"a","vector":[1.0,0.0],"metadata":{}}"#,
+            "",
+            r#"{"id":"b","vector":[1.0,0.0,0.0],"metadata":{}}"#,
+        ]);
+        let err = Importer::new(&mut store)
+            .import_jsonl(file.path(), 10)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Vector dimension mismatch on line 3 ('b'): expected 2, got 3"
+        );
+
+        let file = write_jsonl(&[
+            r#"{"id":"a","vector":[1.0,0.0],"metadata":{}}"#,
+            r#"{"id":"b","vector":[1.0"#,
+            r#"{"id":"","vector":[0.0,1.0],"metadata":{}}"#,
+            r#"{"id":"c","vector":[0.0,1.0],"metadata":{}}"#,
+        ]);
+        let err = Importer::new(&mut store)
+            .import_jsonl(file.path(), 10)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Failed to parse line 2");
+
+        let mut progress = Vec::new();
+        let report = Importer::new(&mut store)
+            .with_partial(true)
+            .with_progress(&mut |rows| progress.push(rows))
+            .import_jsonl(file.path(), 1)
+            .unwrap();
+        assert_eq!(report.summary.inserted, 2);
+        let failed: Vec<_> = report
+            .failed
+            .iter()
+            .map(|f| (f.row, f.id.clone()))
+            .collect();
+        assert_eq!(failed, [(2, None), (3, Some(String::new()))]);
+        assert_eq!(progress, [1, 3, 4]);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_import_jsonl_accepts_full_records() {
+        let (store, _temp_dir) = create_test_store();
+        let lines: Vec<String> = store
+            .scan(None, 0, 10)
+            .iter()
+            .map(|record| serde_json::to_string(record).unwrap())
+            .collect();
+        let file = write_jsonl(&lines.iter().map(String::as_str).collect::<Vec<_>>());
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut copy = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        let report = Importer::new(&mut copy)
+            .import_jsonl(file.path(), 2)
+            .unwrap();
+        assert_eq!(report.summary.inserted, 3);
+        assert_eq!(
+            copy.get("doc2").unwrap().metadata,
+            store.get("doc2").unwrap().metadata
+        );
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let file = write_jsonl(&[
+            "id,vector,text,metadata.category,metadata.source.page,rank",
+            r#""doc1","1;0;0","first, ""quoted""
+second line",tech,3,"7""#,
+            "",
+            r#"doc2,"0;1;0",,,,"#,
+        ]);
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        let report = Importer::new(&mut store)
+            .import_csv(file.path(), &CsvOptions::default(), 10)
+            .unwrap();
+        assert_eq!(report.summary.inserted, 2);
+
+        let doc1 = store.get("doc1").unwrap();
+        assert_eq!(doc1.vector, vec![1.0, 0.0, 0.0]);
+        assert_eq!(doc1.text.as_deref(), Some("first, \"quoted\"\nsecond line"));
+        assert_eq!(doc1.metadata.fields["category"], "tech");
+        assert_eq!(
+            doc1.metadata.fields["source"],
+            serde_json::json!({"page": 3})
+        );
+        assert_eq!(doc1.metadata.fields["rank"], 7);
+        let doc2 = store.get("doc2").unwrap();
+        assert!(doc2.metadata.fields.is_empty() && doc2.text.is_none());
+
+        // Explicit columns, space-separated vectors
+        let file = write_jsonl(&[
+            "key,embedding,title,ignored",
+            "x,[0 0 1],Hello,1",
+            "y,,Bye,2",
+        ]);
+        let options = CsvOptions {
+            id_column: "key".into(),
+            vector_column: "embedding".into(),
+            vector_format: VectorFormat::Delimited(' '),
+            metadata_columns: Some(vec![("title".into(), "name".into())]),
+        };
+        let err = Importer::new(&mut store)
+            .import_csv(file.path(), &options, 10)
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("line 2"));
+
+        let file = write_jsonl(&["key,embedding,title,ignored", "x,0 0 1,Hello,1", "y,,Bye,2"]);
+        let report = Importer::new(&mut store)
+            .with_partial(true)
+            .import_csv(file.path(), &options, 10)
+            .unwrap();
+        assert_eq!(report.summary.inserted, 1);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].row, 3);
+        let x = store.get("x").unwrap();
+        assert_eq!(x.vector, vec![0.0, 0.0, 1.0]);
+        assert_eq!(
+            x.metadata.fields,
+            HashMap::from([("name".to_string(), serde_json::json!("Hello"))])
+        );
+    }
+
+    /// A float32 `.npy` file
+    fn npy_bytes(shape: &str, values: &[f32]) -> Vec<u8> {
+        let mut header = format!(
+            "{{'descr': '<f4', 'fortran_order': False, 'shape': {}, }}",
+            shape
+        );
+        while (10 + header.len() + 1) % 64 != 0 {
+            header.push(' ');
+        }
+        header.push('\n');
+        let mut bytes = NPY_MAGIC.to_vec();
+        bytes.extend([1, 0]);
+        bytes.extend((header.len() as u16).to_le_bytes());
+        bytes.extend(header.as_bytes());
+        for value in values {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// An uncompressed zip archive, like `np.savez` writes
+    fn stored_zip(members: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let mut directory = Vec::new();
+        for (name, data) in members {
+            let offset = bytes.len() as u32;
+            let mut header = vec![0u8; 30];
+            header[..4].copy_from_slice(ZIP_MAGIC);
+            header[18..22].copy_from_slice(&(data.len() as u32).to_le_bytes());
+            header[22..26].copy_from_slice(&(data.len() as u32).to_le_bytes());
+            header[26..28].copy_from_slice(&(name.len() as u16).to_le_bytes());
+            bytes.extend(header);
+            bytes.extend(name.as_bytes());
+            bytes.extend(*data);
+
+            let mut entry = vec![0u8; 46];
+            entry[..4].copy_from_slice(b"PK\x01\x02");
+            entry[20..24].copy_from_slice(&(data.len() as u32).to_le_bytes());
+            entry[24..28].copy_from_slice(&(data.len() as u32).to_le_bytes());
+            entry[28..30].copy_from_slice(&(name.len() as u16).to_le_bytes());
+            entry[42..46].copy_from_slice(&offset.to_le_bytes());
+            directory.extend(entry);
+            directory.extend(name.as_bytes());
+        }
+        let mut end = vec![0u8; 22];
+        end[..4].copy_from_slice(b"PK\x05\x06");
+        end[8..10].copy_from_slice(&(members.len() as u16).to_le_bytes());
+        end[10..12].copy_from_slice(&(members.len() as u16).to_le_bytes());
+        end[12..16].copy_from_slice(&(directory.len() as u32).to_le_bytes());
+        end[16..20].copy_from_slice(&(bytes.len() as u32).to_le_bytes());
+        bytes.extend(directory);
+        bytes.extend(end);
+        bytes
+    }
+
+    #[test]
+    fn test_import_npy() {
+        let temp_dir = TempDir::new().unwrap();
+        let npy_path = temp_dir.path().join("vectors.npy");
+        std::fs::write(
+            &npy_path,
+            npy_bytes("(3, 2)", &[1.0, 0.0, 0.0, 1.0, 0.5, 0.5]),
+        )
+        .unwrap();
+        assert_eq!(FileFormat::detect(&npy_path).unwrap(), FileFormat::Npy);
+
+        let mut store = VecStore::open(temp_dir.path().join("seq.db")).unwrap();
+        let report = Importer::new(&mut store)
+            .import_npy(&npy_path, None, 2)
+            .unwrap();
+        assert_eq!(report.summary.inserted, 3);
+        assert_eq!(store.get("2").unwrap().vector, vec![0.5, 0.5]);
+
+        let ids_path = temp_dir.path().join("ids.txt");
+        std::fs::write(&ids_path, "a\nb\nc\n").unwrap();
+        let mut store = VecStore::open(temp_dir.path().join("ids.db")).unwrap();
+        Importer::new(&mut store)
+            .import_npy(&npy_path, Some(&ids_path), 10)
+            .unwrap();
+        assert_eq!(store.get("b").unwrap().vector, vec![0.0, 1.0]);
+
+        std::fs::write(&ids_path, "a\nb\n").unwrap();
+        assert!(Importer::new(&mut store)
+            .import_npy(&npy_path, Some(&ids_path), 10)
+            .is_err());
+
+        // Another dimension stops at the first row
+        std::fs::write(&npy_path, npy_bytes("(3,)", &[1.0, 2.0, 3.0])).unwrap();
+        let err = Importer::new(&mut store)
+            .import_npy(&npy_path, None, 10)
+            .unwrap_err();
+        assert!(err.to_string().contains("on row 0"));
+    }
+
+    #[test]
+    fn test_import_npz() {
+        let vectors = npy_bytes("(2, 2)", &[1.0, 2.0, 3.0, 4.0]);
+        let labels = npy_bytes("(2,)", &[0.0, 1.0]);
+        let temp_dir = TempDir::new().unwrap();
+        let npz_path = temp_dir.path().join("data.bin");
+        std::fs::write(
+            &npz_path,
+            stored_zip(&[("labels.npy", &labels), ("vectors.npy", &vectors)]),
+        )
+        .unwrap();
+        assert_eq!(FileFormat::detect(&npz_path).unwrap(), FileFormat::Npy);
+
+        let mut store = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        let report = Importer::new(&mut store)
+            .import_npy(&npz_path, None, 10)
+            .unwrap();
+        assert_eq!(report.summary.inserted, 2);
+        assert_eq!(store.get("1").unwrap().vector, vec![3.0, 4.0]);
+    }
+
+    #[test]
+    fn test_detect_format() {
+        let jsonl = write_jsonl(&[r#"{"id":"a"}"#]);
+        let csv = write_jsonl(&["id,vector"]);
+        assert_eq!(FileFormat::detect(jsonl.path()).unwrap(), FileFormat::Jsonl);
+        assert_eq!(FileFormat::detect(csv.path()).unwrap(), FileFormat::Csv);
+        assert_eq!(
+            FileFormat::detect(Path::new("export.CSV")).unwrap(),
+            FileFormat::Csv
+        );
+        assert!(FileFormat::detect(NamedTempFile::new().unwrap().path()).is_err());
+    }
+
+    #[test]
+    fn test_count_rows() {
+        let jsonl = write_jsonl(&[r#"{"id":"a"}"#, "", r#"{"id":"b"}"#]);
+        let csv = write_jsonl(&["id,vector,note", "a,1;2,\"two", "lines\"", "b,3;4,"]);
+        let npy = NamedTempFile::new().unwrap();
+        std::fs::write(npy.path(), npy_bytes("(3, 2)", &[0.0; 6])).unwrap();
+
+        assert_eq!(FileFormat::Jsonl.count_rows(jsonl.path()).unwrap(), 2);
+        assert_eq!(FileFormat::Csv.count_rows(csv.path()).unwrap(), 2);
+        assert_eq!(FileFormat::Npy.count_rows(npy.path()).unwrap(), 3);
+    }
 }
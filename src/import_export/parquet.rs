@@ -0,0 +1,639 @@
+//! Parquet files with one row per record
+//!
+//! The schema is `id` (utf8), `vector` (fixed-size list of float32), the
+//! metadata, then the nullable `text`, `parent_id` (utf8) and `chunk_index`
+//! (uint64) columns. Metadata is either one JSON-encoded `metadata` column or,
+//! exploded, one typed `metadata.<key>` column per top-level key. An exploded
+//! key whose values mix types holds JSON text, marked on its field with the
+//! `vecstore.encoding = json` metadata so imports can decode it.
+
+use super::{value_to_metadata, Row};
+use crate::store::{make_record, Metadata, Record};
+use anyhow::{Context, Result};
+use arrow::array::{
+    Array, ArrayRef, AsArray, BooleanArray, FixedSizeListArray, Float32Array, Float64Array,
+    Int64Array, StringArray, UInt64Array,
+};
+use arrow::compute::cast;
+use arrow::datatypes::{
+    DataType, Field, Float32Type, Float64Type, Int64Type, Schema, SchemaRef, UInt64Type,
+};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Rows per row group, so readers can stream a file a group at a time
+pub const PARQUET_ROW_GROUP_SIZE: usize = 10_000;
+
+const ENCODING_KEY: &str = "vecstore.encoding";
+
+/// Arrow type of an exploded metadata column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnType {
+    Boolean,
+    Int64,
+    Float64,
+    Utf8,
+    /// Mixed, nested or array values, written as JSON text
+    Json,
+}
+
+impl ColumnType {
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Bool(_) => Self::Boolean,
+            Value::Number(n) if n.is_i64() => Self::Int64,
+            Value::Number(_) => Self::Float64,
+            Value::String(_) => Self::Utf8,
+            _ => Self::Json,
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Self::Int64, Self::Float64) | (Self::Float64, Self::Int64) => Self::Float64,
+            _ => Self::Json,
+        }
+    }
+
+    fn field(self, key: &str) -> Field {
+        let name = format!("metadata.{}", key);
+        match self {
+            Self::Boolean => Field::new(name, DataType::Boolean, true),
+            Self::Int64 => Field::new(name, DataType::Int64, true),
+            Self::Float64 => Field::new(name, DataType::Float64, true),
+            Self::Utf8 => Field::new(name, DataType::Utf8, true),
+            Self::Json => Field::new(name, DataType::Utf8, true).with_metadata(HashMap::from([(
+                ENCODING_KEY.to_string(),
+                "json".to_string(),
+            )])),
+        }
+    }
+
+    fn array(self, records: &[Record], key: &str) -> ArrayRef {
+        let values = records
+            .iter()
+            .map(|r| r.metadata.fields.get(key).filter(|v| !v.is_null()));
+        match self {
+            Self::Boolean => Arc::new(
+                values
+                    .map(|v| v.and_then(Value::as_bool))
+                    .collect::<BooleanArray>(),
+            ),
+            Self::Int64 => Arc::new(
+                values
+                    .map(|v| v.and_then(Value::as_i64))
+                    .collect::<Int64Array>(),
+            ),
+            Self::Float64 => Arc::new(
+                values
+                    .map(|v| v.and_then(Value::as_f64))
+                    .collect::<Float64Array>(),
+            ),
+            Self::Utf8 => Arc::new(
+                values
+                    .map(|v| v.and_then(Value::as_str))
+                    .collect::<StringArray>(),
+            ),
+            Self::Json => Arc::new(
+                values
+                    .map(|v| v.map(Value::to_string))
+                    .collect::<StringArray>(),
+            ),
+        }
+    }
+}
+
+/// Metadata keys and column types for an exploded export
+///
+/// Parquet needs the whole schema up front, so [`observe`](Self::observe)
+/// every record that will be written before calling [`ParquetExport::create`].
+/// Keys that are only ever null get no column.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataColumns {
+    columns: BTreeMap<String, ColumnType>,
+}
+
+impl MetadataColumns {
+    pub fn observe(&mut self, record: &Record) {
+        for (key, value) in &record.metadata.fields {
+            if value.is_null() {
+                continue;
+            }
+            let kind = ColumnType::of(value);
+            self.columns
+                .entry(key.clone())
+                .and_modify(|column| *column = column.merge(kind))
+                .or_insert(kind);
+        }
+    }
+}
+
+/// Streams records into a Parquet file
+pub struct ParquetExport {
+    writer: ArrowWriter<File>,
+    schema: SchemaRef,
+    dimension: usize,
+    /// Exploded metadata columns, or `None` for one JSON column
+    metadata: Option<Vec<(String, ColumnType)>>,
+    rows: usize,
+}
+
+impl ParquetExport {
+    /// Create `path` for vectors of `dimension`, writing metadata as one JSON
+    /// column, or exploded into `columns`
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        dimension: usize,
+        columns: Option<MetadataColumns>,
+    ) -> Result<Self> {
+        let metadata: Option<Vec<_>> = columns.map(|c| c.columns.into_iter().collect());
+
+        let mut fields = vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("vector", vector_type(dimension), false),
+        ];
+        match &metadata {
+            None => fields.push(Field::new("metadata", DataType::Utf8, true)),
+            Some(columns) => fields.extend(columns.iter().map(|(key, kind)| kind.field(key))),
+        }
+        fields.extend([
+            Field::new("text", DataType::Utf8, true),
+            Field::new("parent_id", DataType::Utf8, true),
+            Field::new("chunk_index", DataType::UInt64, true),
+        ]);
+        let schema = Arc::new(Schema::new(fields));
+
+        let file = File::create(path.as_ref())
+            .with_context(|| format!("Failed to create file: {:?}", path.as_ref()))?;
+        let props = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(PARQUET_ROW_GROUP_SIZE)
+            .build();
+        let writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
+
+        Ok(Self {
+            writer,
+            schema,
+            dimension,
+            metadata,
+            rows: 0,
+        })
+    }
+
+    /// Append `records`, which must all have the export's dimension
+    pub fn write(&mut self, records: &[Record]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+
+        let mut values = Vec::with_capacity(records.len() * self.dimension);
+        for record in records {
+            if record.vector.len() != self.dimension {
+                return Err(anyhow::anyhow!(
+                    "Record '{}' has dimension {}, expected {}",
+                    record.id,
+                    record.vector.len(),
+                    self.dimension
+                ));
+            }
+            values.extend_from_slice(&record.vector);
+        }
+
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(
+                records.iter().map(|r| r.id.as_str()),
+            )),
+            Arc::new(FixedSizeListArray::try_new(
+                Arc::new(Field::new("item", DataType::Float32, true)),
+                self.dimension as i32,
+                Arc::new(Float32Array::from(values)),
+                None,
+            )?),
+        ];
+        match &self.metadata {
+            None => {
+                let metadata = records
+                    .iter()
+                    .map(|r| serde_json::to_string(&r.metadata.fields))
+                    .collect::<serde_json::Result<Vec<_>>>()?;
+                columns.push(Arc::new(StringArray::from(metadata)));
+            }
+            Some(keys) => {
+                columns.extend(keys.iter().map(|(key, kind)| kind.array(records, key)));
+            }
+        }
+        columns.push(Arc::new(
+            records
+                .iter()
+                .map(|r| r.text.as_deref())
+                .collect::<StringArray>(),
+        ));
+        columns.push(Arc::new(
+            records
+                .iter()
+                .map(|r| r.parent_id.as_deref())
+                .collect::<StringArray>(),
+        ));
+        columns.push(Arc::new(
+            records
+                .iter()
+                .map(|r| r.chunk_index.map(|i| i as u64))
+                .collect::<UInt64Array>(),
+        ));
+
+        let batch = RecordBatch::try_new(self.schema.clone(), columns)?;
+        self.writer.write(&batch)?;
+        self.rows += records.len();
+        Ok(())
+    }
+
+    /// Finish the file, returning the number of records written
+    pub fn finish(self) -> Result<usize> {
+        self.writer.close()?;
+        Ok(self.rows)
+    }
+}
+
+fn vector_type(dimension: usize) -> DataType {
+    DataType::FixedSizeList(
+        Arc::new(Field::new("item", DataType::Float32, true)),
+        dimension as i32,
+    )
+}
+
+/// Number of rows in a Parquet file, from its footer
+pub(crate) fn count_rows(path: &Path) -> Result<usize> {
+    let file = File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    Ok(builder.metadata().file_metadata().num_rows() as usize)
+}
+
+/// Read the rows of a Parquet file a batch at a time
+pub(crate) fn read_rows(
+    path: &Path,
+    batch_size: usize,
+) -> Result<impl Iterator<Item = Result<Row>>> {
+    let file = File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)?
+        .with_batch_size(batch_size.max(1))
+        .build()?;
+
+    let mut first_row = 0;
+    Ok(reader.flat_map(move |batch| {
+        match batch
+            .map_err(anyhow::Error::from)
+            .and_then(|batch| batch_rows(&batch, first_row))
+        {
+            Ok(rows) => {
+                first_row += rows.len();
+                rows.into_iter().map(Ok).collect::<Vec<_>>()
+            }
+            Err(e) => vec![Err(e)],
+        }
+    }))
+}
+
+/// An exploded metadata column, cast once per batch
+enum MetadataColumn {
+    Boolean(BooleanArray),
+    Int64(Int64Array),
+    Float64(Float64Array),
+    /// Strings, JSON-encoded when the flag is set
+    Utf8(StringArray, bool),
+}
+
+impl MetadataColumn {
+    fn new(array: &ArrayRef, field: &Field) -> Result<Self> {
+        let data_type = array.data_type();
+        Ok(if data_type == &DataType::Boolean {
+            Self::Boolean(array.as_boolean().clone())
+        } else if data_type.is_integer() {
+            Self::Int64(
+                cast(array, &DataType::Int64)?
+                    .as_primitive::<Int64Type>()
+                    .clone(),
+            )
+        } else if data_type.is_floating() {
+            Self::Float64(
+                cast(array, &DataType::Float64)?
+                    .as_primitive::<Float64Type>()
+                    .clone(),
+            )
+        } else {
+            let json = field.metadata().get(ENCODING_KEY).map(String::as_str) == Some("json");
+            Self::Utf8(
+                cast(array, &DataType::Utf8)?.as_string::<i32>().clone(),
+                json,
+            )
+        })
+    }
+
+    fn value(&self, row: usize) -> Result<Option<Value>> {
+        Ok(match self {
+            Self::Boolean(a) => a.is_valid(row).then(|| Value::Bool(a.value(row))),
+            Self::Int64(a) => a.is_valid(row).then(|| Value::from(a.value(row))),
+            Self::Float64(a) => a.is_valid(row).then(|| {
+                serde_json::Number::from_f64(a.value(row)).map_or(Value::Null, Value::Number)
+            }),
+            Self::Utf8(a, false) => a
+                .is_valid(row)
+                .then(|| Value::String(a.value(row).to_string())),
+            Self::Utf8(a, true) => a
+                .is_valid(row)
+                .then(|| serde_json::from_str::<Value>(a.value(row)))
+                .transpose()
+                .context("invalid JSON metadata value")?,
+        })
+    }
+}
+
+fn batch_rows(batch: &RecordBatch, first_row: usize) -> Result<Vec<Row>> {
+    let column = |name: &str| batch.column_by_name(name);
+    let utf8 = |name: &str| column(name).map(|c| cast(c, &DataType::Utf8)).transpose();
+
+    let ids = cast(
+        column("id").context("Parquet file has no 'id' column")?,
+        &DataType::Utf8,
+    )?;
+    let ids = ids.as_string::<i32>();
+    let vectors = column("vector")
+        .or_else(|| column("embedding"))
+        .or_else(|| column("values"))
+        .context("Parquet file has no 'vector', 'embedding' or 'values' column")?;
+    let vectors = cast(
+        vectors,
+        &DataType::List(Arc::new(Field::new("item", DataType::Float32, true))),
+    )
+    .context("The vector column must be a list of floats")?;
+    let vectors = vectors.as_list::<i32>();
+
+    let json_metadata = utf8("metadata")?;
+    let schema = batch.schema();
+    let exploded = schema
+        .fields()
+        .iter()
+        .zip(batch.columns())
+        .filter_map(|(field, array)| {
+            let key = field.name().strip_prefix("metadata.")?;
+            Some(MetadataColumn::new(array, field).map(|column| (key.to_string(), column)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let text = utf8("text")?;
+    let parent_id = utf8("parent_id")?;
+    let chunk_index = column("chunk_index")
+        .map(|c| cast(c, &DataType::UInt64))
+        .transpose()?;
+
+    let optional_string = |array: &Option<ArrayRef>, row: usize| {
+        array
+            .as_ref()
+            .map(|a| a.as_string::<i32>())
+            .filter(|a| a.is_valid(row))
+            .map(|a| a.value(row).to_string())
+    };
+
+    let record = |row: usize| -> Result<Record> {
+        if ids.is_null(row) {
+            return Err(anyhow::anyhow!("missing id"));
+        }
+        if vectors.is_null(row) {
+            return Err(anyhow::anyhow!("missing vector"));
+        }
+        let values = vectors.value(row);
+        let values = values.as_primitive::<Float32Type>();
+        if values.null_count() > 0 {
+            return Err(anyhow::anyhow!("vector has null components"));
+        }
+
+        let mut fields = match &json_metadata {
+            Some(metadata) if metadata.is_valid(row) => {
+                let value: Value = serde_json::from_str(metadata.as_string::<i32>().value(row))
+                    .context("invalid metadata JSON")?;
+                metadata_fields(value)?
+            }
+            _ => HashMap::new(),
+        };
+        for (key, column) in &exploded {
+            if let Some(value) = column.value(row)? {
+                fields.insert(key.clone(), value);
+            }
+        }
+
+        Ok(Record {
+            text: optional_string(&text, row),
+            parent_id: optional_string(&parent_id, row),
+            chunk_index: chunk_index
+                .as_ref()
+                .map(|a| a.as_primitive::<UInt64Type>())
+                .filter(|a| a.is_valid(row))
+                .map(|a| a.value(row) as usize),
+            ..make_record(
+                ids.value(row),
+                values.values().to_vec(),
+                Metadata { fields },
+            )
+        })
+    };
+
+    Ok((0..batch.num_rows())
+        .map(|row| (first_row + row, record(row)))
+        .collect())
+}
+
+/// Metadata fields from a `metadata` cell
+///
+/// Files written before metadata was exported as a plain object wrapped it
+/// as `{"fields": {...}}`; those are unwrapped.
+fn metadata_fields(value: Value) -> Result<HashMap<String, Value>> {
+    match value {
+        Value::Object(mut map)
+            if map.len() == 1 && map.get("fields").is_some_and(Value::is_object) =>
+        {
+            Ok(value_to_metadata(map.remove("fields").unwrap()).fields)
+        }
+        value @ (Value::Object(_) | Value::Null) => Ok(value_to_metadata(value).fields),
+        _ => Err(anyhow::anyhow!("metadata must be a JSON object")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import_export::{Exporter, Importer};
+    use crate::store::VecStore;
+    use arrow::array::ListArray;
+    use tempfile::TempDir;
+
+    fn record(id: &str, vector: Vec<f32>, metadata: Value) -> Record {
+        make_record(id, vector, value_to_metadata(metadata))
+    }
+
+    fn read_batches(path: &Path) -> Vec<RecordBatch> {
+        ParquetRecordBatchReaderBuilder::try_new(File::open(path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<std::result::Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_schema_read_by_arrow() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(temp_dir.path().join("store")).unwrap();
+        store
+            .upsert_record(
+                record("a", vec![1.0, 2.0, 3.0], serde_json::json!({"tag": "x"}))
+                    .with_text("hello")
+                    .with_parent("doc", 0),
+            )
+            .unwrap();
+        store
+            .upsert_record(record("b", vec![4.0, 5.0, 6.0], serde_json::json!({})))
+            .unwrap();
+
+        let path = temp_dir.path().join("export.parquet");
+        assert_eq!(Exporter::new(&store).to_parquet(&path).unwrap(), 2);
+
+        let batches = read_batches(&path);
+        let schema = batches[0].schema();
+        let names: Vec<_> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "id",
+                "vector",
+                "metadata",
+                "text",
+                "parent_id",
+                "chunk_index"
+            ]
+        );
+        assert_eq!(schema.field(0).data_type(), &DataType::Utf8);
+        assert_eq!(schema.field(1).data_type(), &vector_type(3));
+        assert_eq!(schema.field(5).data_type(), &DataType::UInt64);
+
+        let batch = &batches[0];
+        let vectors = batch.column(1).as_fixed_size_list();
+        assert_eq!(
+            vectors
+                .value(1)
+                .as_primitive::<Float32Type>()
+                .values()
+                .to_vec(),
+            vec![4.0, 5.0, 6.0]
+        );
+        let metadata: Value =
+            serde_json::from_str(batch.column(2).as_string::<i32>().value(0)).unwrap();
+        assert_eq!(metadata, serde_json::json!({"tag": "x"}));
+        assert!(batch.column(3).is_null(1));
+
+        let mut copy = VecStore::open(temp_dir.path().join("copy")).unwrap();
+        assert_eq!(Importer::new(&mut copy).from_parquet(&path).unwrap(), 2);
+        let a = copy.get("a").unwrap();
+        assert_eq!(a.metadata.fields["tag"], "x");
+        assert_eq!(a.text.as_deref(), Some("hello"));
+        assert_eq!(a.chunk_index, Some(0));
+    }
+
+    #[test]
+    fn test_exploded_metadata() {
+        let records = vec![
+            record(
+                "a",
+                vec![1.0, 0.0],
+                serde_json::json!({"n": 1, "score": 0.5, "ok": true, "tag": "x", "mixed": 1}),
+            ),
+            record(
+                "b",
+                vec![0.0, 1.0],
+                serde_json::json!({"n": 2, "score": 2, "mixed": "two", "list": [1, 2]}),
+            ),
+        ];
+        let mut columns = MetadataColumns::default();
+        records.iter().for_each(|r| columns.observe(r));
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("exploded.parquet");
+        let mut export = ParquetExport::create(&path, 2, Some(columns)).unwrap();
+        export.write(&records).unwrap();
+        assert_eq!(export.finish().unwrap(), 2);
+
+        let batch = &read_batches(&path)[0];
+        let schema = batch.schema();
+        let types: Vec<_> = schema
+            .fields()
+            .iter()
+            .filter(|f| f.name().starts_with("metadata."))
+            .map(|f| (f.name().as_str(), f.data_type().clone()))
+            .collect();
+        assert_eq!(
+            types,
+            [
+                ("metadata.list", DataType::Utf8),
+                ("metadata.mixed", DataType::Utf8),
+                ("metadata.n", DataType::Int64),
+                ("metadata.ok", DataType::Boolean),
+                ("metadata.score", DataType::Float64),
+                ("metadata.tag", DataType::Utf8),
+            ]
+        );
+
+        let mut store = VecStore::open(temp_dir.path().join("store")).unwrap();
+        Importer::new(&mut store).from_parquet(&path).unwrap();
+        assert_eq!(store.get("a").unwrap().metadata, records[0].metadata);
+        // Integers in a float column come back as floats
+        let b = store.get("b").unwrap();
+        assert_eq!(
+            serde_json::to_value(&b.metadata.fields).unwrap(),
+            serde_json::json!({"n": 2, "score": 2.0, "mixed": "two", "list": [1, 2]})
+        );
+    }
+
+    #[test]
+    fn test_import_embedding_list_column() {
+        // As written by pandas: integer ids and a variable-length list column
+        let embeddings = ListArray::from_iter_primitive::<Float64Type, _, _>(vec![
+            Some(vec![Some(1.0), Some(0.0)]),
+            None,
+            Some(vec![Some(0.0), Some(1.0)]),
+        ]);
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("embedding", embeddings.data_type().clone(), true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(vec![10, 11, 12])),
+                Arc::new(embeddings),
+            ],
+        )
+        .unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("pandas.parquet");
+        let mut writer = ArrowWriter::try_new(File::create(&path).unwrap(), schema, None).unwrap();
+        writer.write(&batch).unwrap();
+        writer.close().unwrap();
+
+        let mut store = VecStore::open(temp_dir.path().join("store")).unwrap();
+        let report = Importer::new(&mut store)
+            .with_partial(true)
+            .import_parquet(&path, 2)
+            .unwrap();
+        assert_eq!(report.summary.inserted, 2);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].row, 1);
+        assert_eq!(store.get("12").unwrap().vector, vec![0.0, 1.0]);
+    }
+}
@@ -285,7 +285,7 @@ impl LangChainVectorStore {
 
             for (idx, candidate) in remaining.iter().enumerate() {
                 // Relevance score (similarity to query)
-                let relevance = 1.0 - candidate.score; // Convert distance to similarity
+                let relevance = candidate.score;
 
                 // Diversity score (max similarity to already selected documents)
                 let mut max_similarity: f32 = 0.0;
@@ -8,7 +8,7 @@
 //!
 //! ## Highlights
 //!
-//! - **HNSW index** with cosine/Euclidean/dot-product support.
+//! - **HNSW index** with cosine, Euclidean, dot-product, Manhattan, Hamming and Jaccard metrics.
 //! - **Metadata filtering** via a SQL-inspired expression language.
 //! - **Hybrid search** (vector + BM25 keyword scoring) using a pluggable tokenizer.
 //! - **Snapshots** and namespace-aware collections built on the same file layout.
@@ -147,6 +147,8 @@
 
 pub mod autotuning;
 pub mod cache;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
 pub mod compression;
 pub mod error;
 pub mod fuzzy;
@@ -180,7 +182,8 @@ pub mod python;
     feature = "cloud-embeddings",
     feature = "openai-embeddings",
     feature = "candle-embeddings",
-    feature = "ollama"
+    feature = "ollama",
+    feature = "server-openai"
 ))]
 pub mod embeddings;
 
@@ -244,16 +247,29 @@ pub mod python_async;
 pub use collection::{Collection, CollectionConfig, VecDatabase};
 pub use error::{Result, VecStoreError};
 pub use graph_viz::{GraphEdge, GraphNode, GraphStatistics, HnswVisualizer};
-pub use namespace::{Namespace, NamespaceId, NamespaceQuotas, NamespaceStatus, ResourceUsage};
+pub use namespace::{
+    Namespace, NamespaceId, NamespaceQuotas, NamespaceStatus, Quota, QuotaExceeded, ResourceUsage,
+};
 pub use namespace_manager::{AggregateStats, NamespaceManager, NamespaceStats};
 pub use schema::{FieldSchema, FieldType, Schema, ValidationError};
+#[cfg(feature = "encryption")]
+pub use store::EncryptionKey;
 pub use store::{
-    make_record, parse_filter, BatchError, BatchOperation, BatchResult, CompactionConfig,
-    CompactionResult, Config, Distance, ExplainedNeighbor, FilterExpr, FilterOp, FilterParseError,
-    HNSWSearchParams, HybridQuery, Metadata, Neighbor, PQConfig, PQVectorStore, PrefetchQuery,
-    ProductQuantizer, Query, QueryEstimate, QueryExplanation, QueryPlan, QueryStage, QueryStep,
-    Record, VecStore, VecStoreBuilder,
+    make_record, parse_filter, BatchError, BatchItemError, BatchOperation, BatchResult,
+    BatchUpsertResult, BatchValidationError, CompactionConfig, CompactionReport, CompactionResult,
+    Config, ConsistencyScan, Distance, DuplicateKeep, EfRecall, EmbeddingModel,
+    EmbeddingModelMismatch, EventHook, ExplainedNeighbor, FieldIndexStats, FieldIndexType,
+    FilterExpr, FilterOp, FilterParseError, HNSWSearchParams, HnswConfig, HybridQuery, Metadata,
+    NearDuplicateGroup, Neighbor, PQConfig, PQVectorStore, PrefetchQuery, ProductQuantizer,
+    ProfiledResults, Quantization, Query, QueryCancel, QueryEstimate, QueryExplanation,
+    QueryInterrupted, QueryOptions, QueryPlan, QueryStage, QueryStep, RecallReport, Record,
+    SlowQuery, StoreEvent, StoreStats, SubscriptionId, UpsertPolicy, UpsertSummary, VecStore,
+    VecStoreBuilder, VecStoreConcurrent, VectorPolicy, VersionConflict,
 };
+#[cfg(not(target_arch = "wasm32"))]
+pub use store::{BackupCompression, BackupKind, BackupManifest, BackupSegment};
+#[cfg(not(target_arch = "wasm32"))]
+pub use store::{CheckStatus, IntegrityCheck, IntegrityReport, RepairReport};
 pub use text_splitter::{
     RecursiveCharacterTextSplitter, TextChunk, TextSplitter, TokenTextSplitter,
 };
@@ -311,7 +327,10 @@ pub use multimodal::{
 };
 
 // Export migration types
-pub use migration::{MigrationRecord, MigrationStats, Migrator, SourceDatabase};
+pub use migration::{
+    MigrationCheckpoint, MigrationPage, MigrationReader, MigrationRecord, MigrationReport,
+    MigrationRun, MigrationStats, Migrator, SourceDatabase,
+};
 
 // Export Graph RAG types
 pub use graph_rag::{Entity, GraphQuery, GraphRAG, GraphResult, GraphStats, Relation};
@@ -327,14 +346,17 @@ pub use langchain::{
 
 // Export benchmarking types
 pub use benchmark::{
-    BenchmarkConfig, BenchmarkResults, Benchmarker, ConcurrentResults, FilterResults,
-    IndexingResults, InsertResults, LatencyStats, MemoryResults, QuantizationResults, QueryResults,
+    BenchmarkBaseline, BenchmarkComparison, BenchmarkConfig, BenchmarkMetric, BenchmarkResults,
+    Benchmarker, ConcurrentResults, FilterResults, IndexingResults, InsertResults, LatencyStats,
+    MachineInfo, MemoryResults, MetricDelta, MixedResults, QuantizationResults, QueryResults,
+    RegressionTolerance, SelectivityResult,
 };
 
 // Export health check types
 pub use health::{
-    print_health_report, Alert, AlertCategory, AlertSeverity, DatabaseHealth, HealthCheckConfig,
-    HealthChecker, HealthReport, HealthStatus, IndexHealth, PerformanceHealth, ResourceHealth,
+    print_health_report, Alert, AlertCategory, AlertSeverity, CheckSeverity, DatabaseHealth,
+    HealthCheckCode, HealthCheckConfig, HealthCheckResult, HealthChecker, HealthReport,
+    HealthStatus, IndexHealth, PerformanceHealth, ResourceHealth,
 };
 
 // Export metadata indexing types
@@ -382,7 +404,8 @@ pub use versioning::{
 // Export query optimizer types
 pub use query_optimizer::{
     CostBreakdown, ExecutionPlan, HintCategory, Impact, OptimizationHint, QueryAnalysis,
-    QueryComparison, QueryComplexity, QueryOptimizer, StoreOptimizationSummary,
+    QueryComparison, QueryComplexity, QueryOptimizer, Recommendation as OptimizationRecommendation,
+    RecommendationKind, StoreOptimizationSummary,
 };
 
 // Export deduplication types
@@ -442,7 +465,7 @@ pub use geospatial::{
 // Export advanced filter types
 pub use advanced_filter::{parse_advanced_filter, AdvancedFilter, FilterBuilder};
 
-// Export profiler types
+// Export profiler types (the store's own timing breakdown is `store::QueryProfile`)
 pub use profiler::{ProfileStage, ProfileSummary, ProfilerConfig, QueryProfile, QueryProfiler};
 
 #[cfg(feature = "async")]
@@ -467,6 +490,7 @@ pub use vectors::{
 pub use simd::{
     cosine_similarity_simd, dot_product_simd, euclidean_distance_simd, hamming_distance_simd,
     jaccard_distance_simd, jaccard_similarity_simd, magnitude_simd, manhattan_distance_simd,
+    simd_level, SimdLevel,
 };
 
 #[cfg(feature = "async")]
@@ -492,10 +516,22 @@ pub use embeddings::{CandleEmbedder, CandleModel};
 pub use wasm::{WasmSearchResult, WasmVecStore};
 
 /// Initialize tracing subscriber for logging
+///
+/// Logs go to stderr at `info` unless `RUST_LOG` says otherwise.
 pub fn init_tracing() {
+    init_tracing_at("info");
+}
+
+/// Initialize tracing on stderr, at `default_level` unless `RUST_LOG` is set
+pub fn init_tracing_at(default_level: &str) {
     use tracing_subscriber::{fmt, EnvFilter};
 
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
 
-    fmt().with_env_filter(filter).with_target(false).init();
+    fmt()
+        .with_env_filter(filter)
+        .with_target(false)
+        .with_writer(std::io::stderr)
+        .init();
 }
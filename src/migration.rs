@@ -38,14 +38,47 @@
 //! # Ok(())
 //! # }
 //! ```
-
-use anyhow::{anyhow, Result};
+//!
+//! ## Live Migrations
+//!
+//! [`MigrationRun`] copies a [`MigrationReader`] into a store a page at a
+//! time, creating the store with the source's dimension and metric. Progress
+//! is saved to a checkpoint file as it goes, so a migration that fails part
+//! way picks up where it stopped when run again. With the `migrate` feature,
+//! [`PineconeSource`] and [`QdrantSource`] read Pinecone indexes and Qdrant
+//! collections over their REST APIs, and [`PineconeDump`] and [`QdrantDump`]
+//! read offline dumps. [`ChromaDirectory`] reads a persistent ChromaDB
+//! directory and copies each of its collections into a
+//! [`VecDatabase`](crate::VecDatabase) collection of the same name.
+
+#[cfg(feature = "migrate")]
+mod chroma;
+#[cfg(feature = "migrate")]
+mod dump;
+#[cfg(feature = "migrate")]
+mod http;
+#[cfg(feature = "migrate")]
+mod pinecone;
+#[cfg(feature = "migrate")]
+mod qdrant;
+
+#[cfg(feature = "migrate")]
+pub use self::chroma::{ChromaCollection, ChromaDirectory, ChromaSource};
+#[cfg(feature = "migrate")]
+pub use self::pinecone::{PineconeClient, PineconeDump, PineconeIndex, PineconeSource};
+#[cfg(feature = "migrate")]
+pub use self::qdrant::{
+    PayloadMapping, QdrantClient, QdrantCollection, QdrantDump, QdrantSource, QdrantVector,
+};
+
+use crate::store::{Distance, Record, VecStore};
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Source database type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -544,9 +577,264 @@ impl Default for Migrator {
     }
 }
 
+/// Reads a source database a page at a time for a [`MigrationRun`]
+pub trait MigrationReader {
+    /// Names the source in checkpoints, so one source's checkpoint is not
+    /// resumed against another
+    fn source_id(&self) -> String;
+
+    /// Vector dimension, if the source knows it before the first page
+    fn dimension(&self) -> Option<usize>;
+
+    /// Distance metric the destination store is created with
+    fn distance(&self) -> Distance;
+
+    /// Number of records the source holds, if it can tell
+    fn count(&mut self) -> Result<Option<usize>>;
+
+    /// Read the page at `cursor`, `None` meaning the first page
+    ///
+    /// Only the first call after opening gets a cursor other than the one
+    /// the previous page returned.
+    fn read_page(&mut self, cursor: Option<&str>) -> Result<MigrationPage>;
+}
+
+/// One page of a [`MigrationReader`]
+#[derive(Debug, Clone, Default)]
+pub struct MigrationPage {
+    pub records: Vec<Record>,
+    /// Cursor of the next page; `None` after the last one
+    pub next: Option<String>,
+}
+
+/// How far a migration got, saved between pages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationCheckpoint {
+    /// [`MigrationReader::source_id`] of the source being read
+    pub source: String,
+    /// Cursor of the first page not yet written
+    pub cursor: String,
+    /// Records written before the cursor
+    pub migrated: usize,
+}
+
+impl MigrationCheckpoint {
+    /// Read a checkpoint, or `None` if the file doesn't exist
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("Invalid migration checkpoint {:?}", path))
+                .map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(anyhow::Error::from(e)
+                .context(format!("Failed to read migration checkpoint {:?}", path))),
+        }
+    }
+
+    /// Write the checkpoint, replacing the previous one atomically
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Failed to write migration checkpoint {:?}", tmp))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to write migration checkpoint {:?}", path))
+    }
+}
+
+/// Outcome of a [`MigrationRun`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Records written by this run
+    pub migrated: usize,
+    /// Records a previous run had written, per the checkpoint it resumed from
+    pub resumed: usize,
+    /// Records the source reported holding, if it could tell
+    pub source_count: Option<usize>,
+    /// Records in the destination store afterwards
+    pub dest_count: usize,
+}
+
+impl MigrationReport {
+    /// Whether the destination holds as many records as the source
+    /// reported, or `None` if the source count is unknown
+    pub fn verified(&self) -> Option<bool> {
+        self.source_count.map(|count| count == self.dest_count)
+    }
+}
+
+/// Copies a [`MigrationReader`] into a store, saving a checkpoint as it goes
+///
+/// Records are upserted, so pages written again after a resume overwrite
+/// the same ids rather than duplicating them.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "migrate")]
+/// # fn main() -> anyhow::Result<()> {
+/// use vecstore::migration::{MigrationRun, PineconeClient, PineconeSource};
+///
+/// let client = PineconeClient::new(std::env::var("PINECONE_API_KEY")?)?;
+/// let mut source = PineconeSource::connect(client, "products", None)?;
+/// let report = MigrationRun::new()
+///     .with_checkpoint("products.checkpoint.json")
+///     .run(&mut source, "./data")?;
+/// println!("{} records migrated", report.migrated);
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "migrate"))]
+/// # fn main() {}
+/// ```
+pub struct MigrationRun<'a> {
+    checkpoint: Option<PathBuf>,
+    save_every: usize,
+    progress: Option<&'a mut dyn FnMut(usize, Option<usize>)>,
+}
+
+impl<'a> MigrationRun<'a> {
+    /// Records written between store saves and checkpoints by default
+    pub const DEFAULT_SAVE_EVERY: usize = 10_000;
+
+    /// Create a run without a checkpoint file
+    pub fn new() -> Self {
+        Self {
+            checkpoint: None,
+            save_every: Self::DEFAULT_SAVE_EVERY,
+            progress: None,
+        }
+    }
+
+    /// Resume from `path` if it holds a checkpoint, and keep one there
+    /// until the migration completes
+    pub fn with_checkpoint(mut self, path: impl Into<PathBuf>) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    /// Save the store and the checkpoint after at least `records` records
+    pub fn with_save_every(mut self, records: usize) -> Self {
+        self.save_every = records.max(1);
+        self
+    }
+
+    /// Call `progress` with the records written so far (resumed ones
+    /// included) and the source count after each page
+    pub fn with_progress(mut self, progress: &'a mut dyn FnMut(usize, Option<usize>)) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Migrate `reader` into the store at `dest`
+    ///
+    /// A new store gets the source's dimension and metric; an existing one
+    /// must already match them. The checkpoint is removed once the last page
+    /// is saved.
+    pub fn run(
+        &mut self,
+        reader: &mut dyn MigrationReader,
+        dest: impl AsRef<Path>,
+    ) -> Result<MigrationReport> {
+        let source = reader.source_id();
+        let checkpoint = match &self.checkpoint {
+            Some(path) => MigrationCheckpoint::load(path)?,
+            None => None,
+        };
+        if let Some(checkpoint) = &checkpoint {
+            if checkpoint.source != source {
+                return Err(anyhow!(
+                    "Checkpoint {:?} is for {}, not {}",
+                    self.checkpoint.as_deref().unwrap_or(Path::new("")),
+                    checkpoint.source,
+                    source
+                ));
+            }
+        }
+
+        let mut builder = VecStore::builder(dest.as_ref()).distance(reader.distance());
+        if let Some(dimension) = reader.dimension() {
+            builder = builder.dimension(dimension);
+        }
+        let mut store = builder.build()?;
+        if store.distance_metric() != reader.distance() {
+            return Err(anyhow!(
+                "Destination {:?} uses {:?} distance, but {} uses {:?}",
+                dest.as_ref(),
+                store.distance_metric(),
+                source,
+                reader.distance()
+            ));
+        }
+        if let Some(dimension) = reader.dimension() {
+            if store.dimension() != 0 && store.dimension() != dimension {
+                return Err(anyhow!(
+                    "Destination {:?} holds {}-dimensional vectors, but {} has {}",
+                    dest.as_ref(),
+                    store.dimension(),
+                    source,
+                    dimension
+                ));
+            }
+        }
+
+        let source_count = reader.count()?;
+        let mut report = MigrationReport {
+            resumed: checkpoint.as_ref().map_or(0, |c| c.migrated),
+            source_count,
+            ..Default::default()
+        };
+        let mut cursor = checkpoint.map(|c| c.cursor);
+        let mut unsaved = 0;
+
+        loop {
+            let page = reader.read_page(cursor.as_deref())?;
+            let written = page.records.len();
+            store.batch_upsert(page.records)?;
+            report.migrated += written;
+            unsaved += written;
+            if let Some(progress) = self.progress.as_mut() {
+                progress(report.resumed + report.migrated, source_count);
+            }
+
+            cursor = page.next;
+            let Some(next) = &cursor else { break };
+            if unsaved >= self.save_every {
+                store.save()?;
+                if let Some(path) = &self.checkpoint {
+                    MigrationCheckpoint {
+                        source: source.clone(),
+                        cursor: next.clone(),
+                        migrated: report.resumed + report.migrated,
+                    }
+                    .save(path)?;
+                }
+                unsaved = 0;
+            }
+        }
+
+        store.save()?;
+        if let Some(path) = &self.checkpoint {
+            if path.exists() {
+                std::fs::remove_file(path)
+                    .with_context(|| format!("Failed to remove checkpoint {:?}", path))?;
+            }
+        }
+        report.dest_count = store.count();
+        Ok(report)
+    }
+}
+
+impl Default for MigrationRun<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::store::{make_record, Metadata};
     use std::io::Write;
     use tempfile::NamedTempFile;
 
@@ -714,4 +1002,100 @@ mod tests {
         assert_eq!(stats.failed, 1);
         assert_eq!(stats.total_records, 3);
     }
+
+    /// Three pages of two records, failing once before page `fail_at`
+    struct PagedSource {
+        fail_at: Option<usize>,
+    }
+
+    impl MigrationReader for PagedSource {
+        fn source_id(&self) -> String {
+            "paged".to_string()
+        }
+
+        fn dimension(&self) -> Option<usize> {
+            Some(2)
+        }
+
+        fn distance(&self) -> Distance {
+            Distance::Euclidean
+        }
+
+        fn count(&mut self) -> Result<Option<usize>> {
+            Ok(Some(6))
+        }
+
+        fn read_page(&mut self, cursor: Option<&str>) -> Result<MigrationPage> {
+            let page: usize = cursor.map_or(0, |c| c.parse().unwrap());
+            if self.fail_at == Some(page) {
+                self.fail_at = None;
+                return Err(anyhow!("connection reset"));
+            }
+            let records = (0..2)
+                .map(|i| {
+                    let metadata = Metadata {
+                        fields: HashMap::new(),
+                    };
+                    make_record(format!("p{}-{}", page, i), vec![page as f32, 1.0], metadata)
+                })
+                .collect();
+            Ok(MigrationPage {
+                records,
+                next: (page < 2).then(|| (page + 1).to_string()),
+            })
+        }
+    }
+
+    #[test]
+    fn test_migration_run_resumes_from_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("store");
+        let checkpoint = dir.path().join("checkpoint.json");
+        let mut source = PagedSource { fail_at: Some(2) };
+
+        let result = MigrationRun::new()
+            .with_checkpoint(&checkpoint)
+            .with_save_every(1)
+            .run(&mut source, &dest);
+        assert!(result.is_err());
+        let saved = MigrationCheckpoint::load(&checkpoint).unwrap().unwrap();
+        assert_eq!(saved.cursor, "2");
+        assert_eq!(saved.migrated, 4);
+
+        let mut seen = Vec::new();
+        let mut progress = |done: usize, total: Option<usize>| seen.push((done, total));
+        let report = MigrationRun::new()
+            .with_checkpoint(&checkpoint)
+            .with_progress(&mut progress)
+            .run(&mut source, &dest)
+            .unwrap();
+        assert_eq!(report.migrated, 2);
+        assert_eq!(report.resumed, 4);
+        assert_eq!(report.dest_count, 6);
+        assert_eq!(report.verified(), Some(true));
+        assert_eq!(seen, vec![(6, Some(6))]);
+        assert!(!checkpoint.exists());
+
+        let store = VecStore::open(&dest).unwrap();
+        assert_eq!(store.distance_metric(), Distance::Euclidean);
+        assert_eq!(store.count(), 6);
+    }
+
+    #[test]
+    fn test_migration_run_rejects_other_source_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoint = dir.path().join("checkpoint.json");
+        MigrationCheckpoint {
+            source: "elsewhere".to_string(),
+            cursor: "1".to_string(),
+            migrated: 2,
+        }
+        .save(&checkpoint)
+        .unwrap();
+
+        let result = MigrationRun::new()
+            .with_checkpoint(&checkpoint)
+            .run(&mut PagedSource { fail_at: None }, dir.path().join("store"));
+        assert!(result.unwrap_err().to_string().contains("elsewhere"));
+    }
 }
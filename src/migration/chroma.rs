@@ -0,0 +1,459 @@
+//! ChromaDB persistent directories as migration sources
+//!
+//! A `chromadb.PersistentClient` directory holds `chroma.sqlite3` next to
+//! one folder of HNSW index files per collection. [`ChromaDirectory`] reads
+//! only the SQLite file: collections and their metadata, record ids,
+//! metadata and documents from the metadata segment, and vectors from the
+//! embeddings log (`embeddings_queue`). Chroma can purge log entries once
+//! they are in the HNSW index; records whose vectors are gone fail the
+//! migration with an error naming them, and have to be exported with the
+//! Chroma client (`collection.get(include=["embeddings", ...])`) instead.
+//!
+//! Each collection's `hnsw:space` picks the distance (`l2` by default), and
+//! documents become the records' [`text`](crate::store::Record::text).
+
+use super::{MigrationPage, MigrationReader, MigrationReport};
+use crate::collection::{CollectionConfig, VecDatabase};
+use crate::store::{make_record, Distance, Metadata, Record};
+use anyhow::{anyhow, bail, Context, Result};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the SQLite file in a persistent Chroma directory
+const SQLITE_FILE: &str = "chroma.sqlite3";
+
+/// Metadata key Chroma stores documents under
+const DOCUMENT_KEY: &str = "chroma:document";
+
+/// Records read per page
+const PAGE_SIZE: usize = 1000;
+
+/// `embeddings_queue.operation` of a delete
+const OPERATION_DELETE: i64 = 3;
+
+/// Map a Chroma `hnsw:space` to a [`Distance`]
+fn distance_for_chroma(space: &str) -> Result<Distance> {
+    match space {
+        "l2" => Ok(Distance::Euclidean),
+        "cosine" => Ok(Distance::Cosine),
+        "ip" => Ok(Distance::DotProduct),
+        other => Err(anyhow!("Unsupported Chroma hnsw:space '{}'", other)),
+    }
+}
+
+/// Decode a vector from the embeddings log
+fn decode_vector(blob: &[u8], encoding: &str) -> Result<Vec<f32>> {
+    if !blob.len().is_multiple_of(4) {
+        bail!(
+            "Vector of {} bytes is not a whole number of values",
+            blob.len()
+        );
+    }
+    let words = blob.chunks_exact(4).map(|b| [b[0], b[1], b[2], b[3]]);
+    match encoding {
+        "FLOAT32" => Ok(words.map(f32::from_le_bytes).collect()),
+        "INT32" => Ok(words.map(|w| i32::from_le_bytes(w) as f32).collect()),
+        other => Err(anyhow!("Unsupported vector encoding '{}'", other)),
+    }
+}
+
+/// A collection in a Chroma directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChromaCollection {
+    /// Chroma's UUID for the collection
+    pub id: String,
+    pub name: String,
+    /// Dimension Chroma recorded, if any record has been added
+    pub dimension: Option<usize>,
+    pub distance: Distance,
+}
+
+/// A persistent ChromaDB directory, opened read-only
+pub struct ChromaDirectory {
+    path: PathBuf,
+    conn: Connection,
+}
+
+impl ChromaDirectory {
+    /// Open the directory a `chromadb.PersistentClient` was pointed at
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = path.join(SQLITE_FILE);
+        if !file.exists() {
+            if path.join("chroma-embeddings.parquet").exists() {
+                bail!(
+                    "{} uses the DuckDB layout of Chroma before 0.4; upgrade it with \
+                     chroma-migrate first",
+                    path.display()
+                );
+            }
+            bail!("No {} in {}", SQLITE_FILE, path.display());
+        }
+        let conn = Connection::open_with_flags(&file, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .with_context(|| format!("Failed to open {}", file.display()))?;
+        Ok(Self { path, conn })
+    }
+
+    /// Collections in the directory, by name
+    pub fn collections(&self) -> Result<Vec<ChromaCollection>> {
+        let has_config = self
+            .conn
+            .prepare(
+                "SELECT 1 FROM pragma_table_info('collections') WHERE name = 'config_json_str'",
+            )?
+            .exists([])?;
+        let sql = if has_config {
+            "SELECT id, name, dimension, config_json_str FROM collections ORDER BY name"
+        } else {
+            "SELECT id, name, dimension, NULL FROM collections ORDER BY name"
+        };
+
+        let mut stmt = self.conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<String>>(3)?,
+            ))
+        })?;
+
+        let mut collections = Vec::new();
+        for row in rows {
+            let (id, name, dimension, config) = row?;
+            let space = self
+                .space(&id, config.as_deref())
+                .with_context(|| format!("Collection '{}'", name))?;
+            let distance = match space {
+                Some(space) => {
+                    distance_for_chroma(&space).with_context(|| format!("Collection '{}'", name))?
+                }
+                None => Distance::Euclidean,
+            };
+            collections.push(ChromaCollection {
+                id,
+                name,
+                dimension: dimension.map(|d| d as usize),
+                distance,
+            });
+        }
+        Ok(collections)
+    }
+
+    /// The collection named `name`
+    pub fn collection(&self, name: &str) -> Result<ChromaCollection> {
+        let collections = self.collections()?;
+        let names: Vec<_> = collections.iter().map(|c| c.name.clone()).collect();
+        collections
+            .into_iter()
+            .find(|c| c.name == name)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No collection '{}' in {} (collections: {})",
+                    name,
+                    self.path.display(),
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// `hnsw:space` from the collection metadata, or else from the
+    /// configuration newer Chroma versions store as JSON
+    fn space(&self, collection_id: &str, config: Option<&str>) -> Result<Option<String>> {
+        let space: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT str_value FROM collection_metadata \
+                 WHERE collection_id = ?1 AND key = 'hnsw:space'",
+                params![collection_id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if space.is_some() {
+            return Ok(space);
+        }
+
+        let Some(config) = config else {
+            return Ok(None);
+        };
+        let config: Value = serde_json::from_str(config).context("Invalid config_json_str")?;
+        Ok(["hnsw_configuration", "hnsw"]
+            .iter()
+            .find_map(|key| config[key]["space"].as_str())
+            .map(str::to_string))
+    }
+
+    /// Reader for one collection's records
+    pub fn source(&self, collection: &ChromaCollection) -> Result<ChromaSource<'_>> {
+        let segment_id: String = self
+            .conn
+            .query_row(
+                "SELECT id FROM segments WHERE collection = ?1 AND scope = 'METADATA'",
+                params![collection.id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("Collection '{}' has no metadata segment", collection.name))?;
+
+        Ok(ChromaSource {
+            directory: self,
+            collection: collection.clone(),
+            segment_id,
+            vectors: None,
+        })
+    }
+
+    /// Copy `collection` into a collection of the same name in `db`
+    ///
+    /// The destination is created with the Chroma collection's distance and
+    /// dimension, or, if it exists, must already have them.
+    pub fn migrate_into(
+        &self,
+        collection: &ChromaCollection,
+        db: &mut VecDatabase,
+        progress: &mut dyn FnMut(usize, Option<usize>),
+    ) -> Result<MigrationReport> {
+        let mut source = self.source(collection)?;
+        let source_count = source.count()?;
+
+        let mut dest = match db.get_collection(&collection.name)? {
+            Some(dest) => {
+                if dest.distance_metric() != collection.distance {
+                    bail!(
+                        "Collection '{}' already exists with distance {:?}, not {:?}",
+                        collection.name,
+                        dest.distance_metric(),
+                        collection.distance
+                    );
+                }
+                if let (Some(expected), Some(actual)) = (dest.dimension(), collection.dimension) {
+                    if expected != actual {
+                        bail!(
+                            "Collection '{}' already exists with dimension {}, not {}",
+                            collection.name,
+                            expected,
+                            actual
+                        );
+                    }
+                }
+                dest
+            }
+            None => {
+                let mut config = CollectionConfig::default().with_distance(collection.distance);
+                if let Some(dimension) = collection.dimension {
+                    config = config.with_dimension(dimension);
+                }
+                db.create_collection_with_config(&collection.name, config)?
+            }
+        };
+
+        let mut migrated = 0;
+        let mut cursor = None;
+        loop {
+            let page = source.read_page(cursor.as_deref())?;
+            migrated += page.records.len();
+            if !page.records.is_empty() {
+                dest.batch_upsert(page.records)?;
+            }
+            progress(migrated, source_count);
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        Ok(MigrationReport {
+            migrated,
+            resumed: 0,
+            source_count,
+            dest_count: dest.count()?,
+        })
+    }
+}
+
+/// Records of one Chroma collection, in insertion order
+pub struct ChromaSource<'a> {
+    directory: &'a ChromaDirectory,
+    collection: ChromaCollection,
+    segment_id: String,
+    /// Latest vector per record id, read from the log on the first page
+    vectors: Option<HashMap<String, Vec<f32>>>,
+}
+
+impl ChromaSource<'_> {
+    /// Collection being read
+    pub fn collection(&self) -> &ChromaCollection {
+        &self.collection
+    }
+
+    fn load_vectors(&self) -> Result<HashMap<String, Vec<f32>>> {
+        let mut stmt = self.directory.conn.prepare(
+            "SELECT id, vector, encoding FROM embeddings_queue \
+             WHERE (topic = ?1 OR topic LIKE '%/' || ?1) \
+               AND operation != ?2 AND vector IS NOT NULL \
+             ORDER BY seq_id",
+        )?;
+        let rows = stmt.query_map(params![self.collection.id, OPERATION_DELETE], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Vec<u8>>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?;
+
+        let mut vectors = HashMap::new();
+        for row in rows {
+            let (id, blob, encoding) = row?;
+            let vector = decode_vector(&blob, encoding.as_deref().unwrap_or("FLOAT32"))
+                .with_context(|| format!("Invalid vector for '{}'", id))?;
+            // Later entries are updates and replace earlier ones
+            vectors.insert(id, vector);
+        }
+        Ok(vectors)
+    }
+
+    fn metadata(&self, row_id: i64) -> Result<(Metadata, Option<String>)> {
+        let mut stmt = self.directory.conn.prepare_cached(
+            "SELECT key, string_value, int_value, float_value, bool_value \
+             FROM embedding_metadata WHERE id = ?1",
+        )?;
+        let rows = stmt.query_map(params![row_id], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, Option<String>>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, Option<f64>>(3)?,
+                row.get::<_, Option<bool>>(4)?,
+            ))
+        })?;
+
+        let mut metadata = Metadata {
+            fields: HashMap::new(),
+        };
+        let mut document = None;
+        for row in rows {
+            let (key, string, int, float, boolean) = row?;
+            if key == DOCUMENT_KEY {
+                document = string;
+                continue;
+            }
+            let value = if let Some(s) = string {
+                Value::from(s)
+            } else if let Some(i) = int {
+                Value::from(i)
+            } else if let Some(f) = float {
+                Value::from(f)
+            } else if let Some(b) = boolean {
+                Value::from(b)
+            } else {
+                Value::Null
+            };
+            metadata.fields.insert(key, value);
+        }
+        Ok((metadata, document))
+    }
+}
+
+impl MigrationReader for ChromaSource<'_> {
+    fn source_id(&self) -> String {
+        format!(
+            "chroma:{}#{}",
+            self.directory.path.display(),
+            self.collection.name
+        )
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        self.collection.dimension
+    }
+
+    fn distance(&self) -> Distance {
+        self.collection.distance
+    }
+
+    fn count(&mut self) -> Result<Option<usize>> {
+        let count: i64 = self.directory.conn.query_row(
+            "SELECT COUNT(*) FROM embeddings WHERE segment_id = ?1",
+            params![self.segment_id],
+            |row| row.get(0),
+        )?;
+        Ok(Some(count as usize))
+    }
+
+    /// Pages are keyed by the `embeddings` row id of their last record
+    fn read_page(&mut self, cursor: Option<&str>) -> Result<MigrationPage> {
+        let after: i64 = match cursor {
+            Some(cursor) => cursor
+                .parse()
+                .with_context(|| format!("Invalid cursor '{}'", cursor))?,
+            None => 0,
+        };
+        if self.vectors.is_none() {
+            self.vectors = Some(self.load_vectors()?);
+        }
+
+        let rows: Vec<(i64, String)> = {
+            let mut stmt = self.directory.conn.prepare_cached(
+                "SELECT id, embedding_id FROM embeddings \
+                 WHERE segment_id = ?1 AND id > ?2 ORDER BY id LIMIT ?3",
+            )?;
+            let rows = stmt
+                .query_map(params![self.segment_id, after, PAGE_SIZE as i64], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+
+        let vectors = self.vectors.as_ref().expect("vectors loaded above");
+        let mut records: Vec<Record> = Vec::with_capacity(rows.len());
+        for (row_id, id) in &rows {
+            let vector = vectors.get(id).cloned().ok_or_else(|| {
+                anyhow!(
+                    "No vector for '{}' in Chroma's embeddings log; it was purged after \
+                     indexing. Export the collection with the Chroma client instead",
+                    id
+                )
+            })?;
+            let (metadata, document) = self.metadata(*row_id)?;
+            let mut record = make_record(id.clone(), vector, metadata);
+            record.text = document;
+            records.push(record);
+        }
+
+        let next = match rows.last() {
+            Some((row_id, _)) if rows.len() == PAGE_SIZE => Some(row_id.to_string()),
+            _ => None,
+        };
+        Ok(MigrationPage { records, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_for_chroma() {
+        assert_eq!(distance_for_chroma("l2").unwrap(), Distance::Euclidean);
+        assert_eq!(distance_for_chroma("cosine").unwrap(), Distance::Cosine);
+        assert_eq!(distance_for_chroma("ip").unwrap(), Distance::DotProduct);
+        assert!(distance_for_chroma("hamming").is_err());
+    }
+
+    #[test]
+    fn test_decode_vector() {
+        let blob: Vec<u8> = [1.5f32, -2.0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert_eq!(decode_vector(&blob, "FLOAT32").unwrap(), vec![1.5, -2.0]);
+
+        let blob: Vec<u8> = [3i32, -4].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(decode_vector(&blob, "INT32").unwrap(), vec![3.0, -4.0]);
+
+        assert!(decode_vector(&[0, 0, 0], "FLOAT32").is_err());
+        assert!(decode_vector(&blob, "FLOAT16").is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! Offline dumps read a page at a time
+
+use anyhow::{anyhow, Context, Result};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::iter::Enumerate;
+use std::path::{Path, PathBuf};
+
+/// Rows read per page from a dump
+pub(super) const DUMP_PAGE_SIZE: usize = 1000;
+
+/// A non-blank line and its number, from 1
+pub(super) type NumberedLine = (usize, String);
+
+/// Parse a dump cursor, a row number
+pub(super) fn parse_cursor(cursor: Option<&str>) -> Result<usize> {
+    cursor.map_or(Ok(0), |cursor| {
+        cursor
+            .parse()
+            .with_context(|| format!("Invalid dump cursor '{}'", cursor))
+    })
+}
+
+/// The lines of a JSONL dump, a page at a time
+///
+/// Cursors are the number of lines already read, so a resumed dump skips
+/// them without parsing.
+pub(super) struct JsonlPages {
+    path: PathBuf,
+    lines: Option<Enumerate<Lines<BufReader<File>>>>,
+}
+
+impl JsonlPages {
+    pub(super) fn open(path: &Path) -> Result<Self> {
+        if !path.is_file() {
+            return Err(anyhow!("Dump {:?} does not exist", path));
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            lines: None,
+        })
+    }
+
+    /// Number of non-blank lines
+    pub(super) fn count(&self) -> Result<usize> {
+        let mut count = 0;
+        for line in BufReader::new(self.open_file()?).lines() {
+            if !line?.trim().is_empty() {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// The next [`DUMP_PAGE_SIZE`] non-blank lines with their line numbers
+    /// (from 1), and the cursor after them unless the file is exhausted
+    ///
+    /// `cursor` is only used by the first call.
+    pub(super) fn read_page(
+        &mut self,
+        cursor: Option<&str>,
+    ) -> Result<(Vec<NumberedLine>, Option<String>)> {
+        if self.lines.is_none() {
+            let skip = parse_cursor(cursor)?;
+            let mut lines = BufReader::new(self.open_file()?).lines().enumerate();
+            for _ in 0..skip {
+                if let Some((_, line)) = lines.next() {
+                    line?;
+                }
+            }
+            self.lines = Some(lines);
+        }
+
+        let lines = self.lines.as_mut().expect("opened above");
+        let mut page = Vec::new();
+        for (index, line) in lines.by_ref() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            page.push((index + 1, line));
+            if page.len() >= DUMP_PAGE_SIZE {
+                return Ok((page, Some((index + 1).to_string())));
+            }
+        }
+        Ok((page, None))
+    }
+
+    fn open_file(&self) -> Result<File> {
+        File::open(&self.path).with_context(|| format!("Failed to open dump {:?}", self.path))
+    }
+}
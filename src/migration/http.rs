@@ -0,0 +1,72 @@
+//! HTTP plumbing shared by the REST migration sources
+
+use anyhow::{anyhow, Context, Result};
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Default retries for rate-limited and failed requests
+pub(super) const DEFAULT_RETRIES: u32 = 5;
+
+/// Default delay before the first retry, doubled for each one after it
+pub(super) const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Blocking client with the timeout the sources use
+pub(super) fn client() -> Result<Client> {
+    Client::builder()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+/// Send a request and decode its JSON body, retrying while `service`
+/// rate limits it (429), fails (5xx) or can't be reached
+///
+/// Waits for `Retry-After` when the response carries one, else `delay`
+/// doubled on each attempt.
+pub(super) fn send_json<T: serde::de::DeserializeOwned>(
+    service: &str,
+    max_retries: u32,
+    delay: Duration,
+    request: impl Fn() -> RequestBuilder,
+) -> Result<T> {
+    let mut attempt = 0;
+    loop {
+        let retry_after = match request().send() {
+            Ok(response) if response.status().is_success() => {
+                return response
+                    .json()
+                    .with_context(|| format!("Invalid {} response", service));
+            }
+            Ok(response)
+                if response.status() == StatusCode::TOO_MANY_REQUESTS
+                    || response.status().is_server_error() =>
+            {
+                if attempt >= max_retries {
+                    return Err(anyhow!(
+                        "{} returned {} after {} retries",
+                        service,
+                        response.status(),
+                        attempt
+                    ));
+                }
+                response
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok()?.parse().ok())
+                    .map(Duration::from_secs)
+            }
+            Ok(response) => {
+                let status = response.status();
+                let body = response.text().unwrap_or_default();
+                return Err(anyhow!("{} returned {}: {}", service, status, body.trim()));
+            }
+            Err(e) if attempt < max_retries && (e.is_timeout() || e.is_connect()) => None,
+            Err(e) => {
+                return Err(anyhow::Error::from(e).context(format!("{} request failed", service)))
+            }
+        };
+        std::thread::sleep(retry_after.unwrap_or(delay * 2u32.pow(attempt)));
+        attempt += 1;
+    }
+}
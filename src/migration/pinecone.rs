@@ -0,0 +1,461 @@
+//! Pinecone sources for a [`MigrationRun`](super::MigrationRun)
+//!
+//! [`PineconeSource`] pages through a live index with the data plane's
+//! `list` and `fetch` endpoints (serverless indexes only, as pod-based ones
+//! can't list ids). [`PineconeDump`] reads an offline dump: JSONL with one
+//! `{"id", "values", "metadata"}` object per line, or Parquet with `id`,
+//! `values` and a JSON `metadata` column (the latter needs the
+//! `parquet-export` feature). Sparse values are not migrated.
+
+use super::dump::JsonlPages;
+#[cfg(feature = "parquet-export")]
+use super::dump::{parse_cursor, DUMP_PAGE_SIZE};
+use super::http;
+use super::{MigrationPage, MigrationReader, Migrator, SourceDatabase};
+use crate::store::{make_record, Distance, Metadata};
+use anyhow::{anyhow, Context, Result};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Pinecone's control plane, which describes indexes
+const CONTROL_URL: &str = "https://api.pinecone.io";
+
+/// REST API version the requests are written against
+const API_VERSION: &str = "2024-10";
+
+/// Most ids Pinecone's `list` endpoint returns per page
+const LIST_LIMIT: usize = 100;
+
+/// Map a Pinecone metric name to a [`Distance`]
+fn distance_for_metric(metric: &str) -> Result<Distance> {
+    match metric {
+        "cosine" => Ok(Distance::Cosine),
+        "euclidean" => Ok(Distance::Euclidean),
+        "dotproduct" => Ok(Distance::DotProduct),
+        other => Err(anyhow!("Unsupported Pinecone metric '{}'", other)),
+    }
+}
+
+/// Blocking client for Pinecone's REST API, retrying rate-limited and
+/// failed requests
+#[derive(Debug, Clone)]
+pub struct PineconeClient {
+    http: Client,
+    api_key: String,
+    control_url: String,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+impl PineconeClient {
+    /// Create a client for the given API key
+    pub fn new(api_key: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            http: http::client()?,
+            api_key: api_key.into(),
+            control_url: CONTROL_URL.to_string(),
+            max_retries: http::DEFAULT_RETRIES,
+            retry_delay: http::DEFAULT_RETRY_DELAY,
+        })
+    }
+
+    /// Describe indexes through another control plane URL
+    pub fn with_control_url(mut self, url: impl Into<String>) -> Self {
+        self.control_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Retry a request answered with 429 or a 5xx status up to
+    /// `max_retries` times, doubling `delay` each time unless the response
+    /// carries a `Retry-After` header (default: 5 retries from 500ms)
+    pub fn with_retries(mut self, max_retries: u32, delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Look up an index's dimension, metric and data plane host
+    pub fn describe_index(&self, name: &str) -> Result<PineconeIndex> {
+        let url = format!("{}/indexes/{}", self.control_url, name);
+        self.send(|| self.http.get(&url))
+            .with_context(|| format!("Failed to describe Pinecone index '{}'", name))
+    }
+
+    /// Send a request, retrying while Pinecone rate limits it or fails
+    fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<T> {
+        http::send_json("Pinecone", self.max_retries, self.retry_delay, || {
+            request()
+                .header("Api-Key", &self.api_key)
+                .header("X-Pinecone-API-Version", API_VERSION)
+        })
+    }
+}
+
+/// An index as described by Pinecone's control plane
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PineconeIndex {
+    pub name: String,
+    pub dimension: usize,
+    /// `cosine`, `euclidean` or `dotproduct`
+    pub metric: String,
+    /// Data plane host, without a scheme unless the index is served locally
+    pub host: String,
+}
+
+impl PineconeIndex {
+    /// The index's metric as a [`Distance`]
+    pub fn distance(&self) -> Result<Distance> {
+        distance_for_metric(&self.metric)
+    }
+
+    fn url(&self, path: &str) -> String {
+        if self.host.starts_with("http://") || self.host.starts_with("https://") {
+            format!("{}{}", self.host.trim_end_matches('/'), path)
+        } else {
+            format!("https://{}{}", self.host, path)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ListResponse {
+    #[serde(default)]
+    vectors: Vec<ListedId>,
+    #[serde(default)]
+    pagination: Option<Pagination>,
+}
+
+#[derive(Deserialize)]
+struct ListedId {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct Pagination {
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct FetchResponse {
+    #[serde(default)]
+    vectors: HashMap<String, FetchedVector>,
+}
+
+#[derive(Deserialize)]
+struct FetchedVector {
+    #[serde(default)]
+    values: Vec<f32>,
+    #[serde(default)]
+    metadata: HashMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexStats {
+    #[serde(default)]
+    namespaces: HashMap<String, NamespaceStats>,
+    #[serde(default)]
+    total_vector_count: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NamespaceStats {
+    vector_count: usize,
+}
+
+/// A live Pinecone index (one namespace of it) read over the REST API
+///
+/// Cursors are Pinecone's pagination tokens, so a checkpoint can only be
+/// resumed while Pinecone still honours the token.
+pub struct PineconeSource {
+    client: PineconeClient,
+    index: PineconeIndex,
+    namespace: String,
+}
+
+impl PineconeSource {
+    /// Describe `index` and read its `namespace` (default: the default
+    /// namespace)
+    pub fn connect(client: PineconeClient, index: &str, namespace: Option<&str>) -> Result<Self> {
+        let index = client.describe_index(index)?;
+        index.distance()?;
+        Ok(Self {
+            client,
+            index,
+            namespace: namespace.unwrap_or_default().to_string(),
+        })
+    }
+
+    /// The index being read
+    pub fn index(&self) -> &PineconeIndex {
+        &self.index
+    }
+}
+
+impl MigrationReader for PineconeSource {
+    fn source_id(&self) -> String {
+        format!("pinecone:{}/{}", self.index.name, self.namespace)
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        Some(self.index.dimension)
+    }
+
+    fn distance(&self) -> Distance {
+        self.index.distance().unwrap_or_default()
+    }
+
+    fn count(&mut self) -> Result<Option<usize>> {
+        let url = self.index.url("/describe_index_stats");
+        let stats: IndexStats = self
+            .client
+            .send(|| self.client.http.post(&url).json(&serde_json::json!({})))
+            .context("Failed to read Pinecone index stats")?;
+        let namespace = match self.namespace.as_str() {
+            // Newer API versions name the default namespace
+            "" => stats
+                .namespaces
+                .get("")
+                .or_else(|| stats.namespaces.get("__default__")),
+            name => stats.namespaces.get(name),
+        };
+        Ok(Some(match namespace {
+            Some(namespace) => namespace.vector_count,
+            None if stats.namespaces.is_empty() => stats.total_vector_count,
+            None => 0,
+        }))
+    }
+
+    fn read_page(&mut self, cursor: Option<&str>) -> Result<MigrationPage> {
+        let url = self.index.url("/vectors/list");
+        let limit = LIST_LIMIT.to_string();
+        let listed: ListResponse = self
+            .client
+            .send(|| {
+                let mut query = vec![("namespace", self.namespace.as_str()), ("limit", &limit)];
+                if let Some(token) = cursor {
+                    query.push(("paginationToken", token));
+                }
+                self.client.http.get(&url).query(&query)
+            })
+            .context("Failed to list Pinecone vector ids")?;
+        let next = listed.pagination.and_then(|p| p.next);
+        if listed.vectors.is_empty() {
+            return Ok(MigrationPage {
+                records: Vec::new(),
+                next,
+            });
+        }
+
+        let url = self.index.url("/vectors/fetch");
+        let mut fetched: FetchResponse = self
+            .client
+            .send(|| {
+                let mut query = vec![("namespace", self.namespace.as_str())];
+                query.extend(listed.vectors.iter().map(|v| ("ids", v.id.as_str())));
+                self.client.http.get(&url).query(&query)
+            })
+            .context("Failed to fetch Pinecone vectors")?;
+
+        // Ids deleted between listing and fetching are gone from the response
+        let records = listed
+            .vectors
+            .into_iter()
+            .filter_map(|listed| {
+                let vector = fetched.vectors.remove(&listed.id)?;
+                Some(make_record(
+                    listed.id,
+                    vector.values,
+                    Metadata {
+                        fields: vector.metadata,
+                    },
+                ))
+            })
+            .collect();
+        Ok(MigrationPage { records, next })
+    }
+}
+
+/// An offline Pinecone dump in JSONL or Parquet
+///
+/// Dumps don't record the metric, so it is given when opening. Cursors are
+/// the number of lines (JSONL) or rows (Parquet) already read.
+pub struct PineconeDump {
+    path: PathBuf,
+    distance: Distance,
+    /// `None` for a Parquet dump
+    jsonl: Option<JsonlPages>,
+    #[cfg(feature = "parquet-export")]
+    parquet_rows: Option<Box<dyn Iterator<Item = Result<crate::import_export::Row>>>>,
+}
+
+impl PineconeDump {
+    /// Open a dump; files ending in `.parquet` are read as Parquet, others
+    /// as JSONL
+    pub fn open(path: impl Into<PathBuf>, distance: Distance) -> Result<Self> {
+        let path = path.into();
+        let parquet = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("parquet"));
+        if parquet && !cfg!(feature = "parquet-export") {
+            return Err(anyhow!(
+                "Reading Parquet dumps needs vecstore built with the `parquet-export` feature"
+            ));
+        }
+        if !path.is_file() {
+            return Err(anyhow!("Pinecone dump {:?} does not exist", path));
+        }
+        Ok(Self {
+            jsonl: (!parquet).then(|| JsonlPages::open(&path)).transpose()?,
+            path,
+            distance,
+            #[cfg(feature = "parquet-export")]
+            parquet_rows: None,
+        })
+    }
+
+    #[cfg(feature = "parquet-export")]
+    fn read_parquet_page(&mut self, cursor: Option<&str>) -> Result<MigrationPage> {
+        if self.parquet_rows.is_none() {
+            let skip = parse_cursor(cursor)?;
+            let rows = crate::import_export::read_parquet_rows(&self.path, DUMP_PAGE_SIZE)?;
+            self.parquet_rows = Some(Box::new(rows.skip(skip)));
+        }
+
+        let rows = self.parquet_rows.as_mut().expect("opened above");
+        let mut records = Vec::new();
+        for row in rows.by_ref() {
+            let (index, record) = row?;
+            records.push(record.with_context(|| format!("Invalid record in row {}", index))?);
+            if records.len() >= DUMP_PAGE_SIZE {
+                return Ok(MigrationPage {
+                    records,
+                    next: Some((index + 1).to_string()),
+                });
+            }
+        }
+        Ok(MigrationPage {
+            records,
+            next: None,
+        })
+    }
+
+    #[cfg(not(feature = "parquet-export"))]
+    fn read_parquet_page(&mut self, _cursor: Option<&str>) -> Result<MigrationPage> {
+        unreachable!("rejected by open")
+    }
+}
+
+impl MigrationReader for PineconeDump {
+    fn source_id(&self) -> String {
+        format!("pinecone-dump:{}", self.path.display())
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        None
+    }
+
+    fn distance(&self) -> Distance {
+        self.distance
+    }
+
+    fn count(&mut self) -> Result<Option<usize>> {
+        match &self.jsonl {
+            Some(jsonl) => jsonl.count().map(Some),
+            #[cfg(feature = "parquet-export")]
+            None => crate::import_export::count_parquet_rows(&self.path).map(Some),
+            #[cfg(not(feature = "parquet-export"))]
+            None => Ok(None),
+        }
+    }
+
+    fn read_page(&mut self, cursor: Option<&str>) -> Result<MigrationPage> {
+        let Some(jsonl) = self.jsonl.as_mut() else {
+            return self.read_parquet_page(cursor);
+        };
+        let (lines, next) = jsonl.read_page(cursor)?;
+        let migrator = Migrator::new();
+        let records = lines
+            .into_iter()
+            .map(|(number, line)| {
+                let record = migrator
+                    .parse_record(&line, SourceDatabase::Pinecone)
+                    .with_context(|| format!("Invalid record on line {}", number))?;
+                let metadata = Metadata {
+                    fields: record.metadata,
+                };
+                Ok(make_record(record.id, record.vector, metadata))
+            })
+            .collect::<Result<_>>()?;
+        Ok(MigrationPage { records, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dump::DUMP_PAGE_SIZE;
+    use super::*;
+
+    #[test]
+    fn test_metric_mapping() {
+        assert_eq!(distance_for_metric("cosine").unwrap(), Distance::Cosine);
+        assert_eq!(
+            distance_for_metric("euclidean").unwrap(),
+            Distance::Euclidean
+        );
+        assert_eq!(
+            distance_for_metric("dotproduct").unwrap(),
+            Distance::DotProduct
+        );
+        assert!(distance_for_metric("hamming").is_err());
+    }
+
+    #[test]
+    fn test_index_url() {
+        let mut index = PineconeIndex {
+            name: "docs".into(),
+            dimension: 3,
+            metric: "cosine".into(),
+            host: "docs-abc.svc.pinecone.io".into(),
+        };
+        assert_eq!(
+            index.url("/vectors/list"),
+            "https://docs-abc.svc.pinecone.io/vectors/list"
+        );
+        index.host = "http://localhost:5081/".into();
+        assert_eq!(
+            index.url("/vectors/list"),
+            "http://localhost:5081/vectors/list"
+        );
+    }
+
+    #[test]
+    fn test_dump_resumes_from_cursor() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dump.jsonl");
+        let lines: Vec<String> = (0..DUMP_PAGE_SIZE + 5)
+            .map(|i| format!(r#"{{"id": "v{}", "values": [{}.0, 1.0]}}"#, i, i))
+            .collect();
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let mut dump = PineconeDump::open(&path, Distance::Cosine).unwrap();
+        assert_eq!(dump.count().unwrap(), Some(DUMP_PAGE_SIZE + 5));
+        let first = dump.read_page(None).unwrap();
+        assert_eq!(first.records.len(), DUMP_PAGE_SIZE);
+        let cursor = first.next.unwrap();
+
+        let mut resumed = PineconeDump::open(&path, Distance::Cosine).unwrap();
+        let rest = resumed.read_page(Some(&cursor)).unwrap();
+        assert_eq!(rest.records.len(), 5);
+        assert_eq!(rest.records[0].id, format!("v{}", DUMP_PAGE_SIZE));
+        assert!(rest.next.is_none());
+    }
+}
@@ -0,0 +1,594 @@
+//! Qdrant sources for a [`MigrationRun`](super::MigrationRun)
+//!
+//! [`QdrantSource`] scrolls a collection over Qdrant's REST API.
+//! [`QdrantDump`] reads JSONL with one point per line, as `scroll` returns
+//! them: `{"id", "vector", "payload"}`. Binary `.snapshot` archives hold
+//! Qdrant's internal segment files and can't be read directly; recover the
+//! snapshot into a Qdrant instance and scroll that instead.
+//!
+//! Payloads become metadata as they are, except nested objects (other than
+//! `{"lat", "lon"}` geo points) and arrays holding objects or arrays, which
+//! VecStore filters can't reach into. Those are stored as JSON text and
+//! counted per field, see [`PayloadMapping`].
+
+use super::dump::JsonlPages;
+use super::http;
+use super::{MigrationPage, MigrationReader};
+use crate::store::{make_record, Distance, Metadata, Record};
+use anyhow::{anyhow, Context, Result};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Points requested per scroll page
+const SCROLL_LIMIT: usize = 256;
+
+/// Map a Qdrant distance name to a [`Distance`]
+fn distance_for_qdrant(name: &str) -> Result<Distance> {
+    match name {
+        "Cosine" => Ok(Distance::Cosine),
+        "Euclid" => Ok(Distance::Euclidean),
+        "Dot" => Ok(Distance::DotProduct),
+        "Manhattan" => Ok(Distance::Manhattan),
+        other => Err(anyhow!("Unsupported Qdrant distance '{}'", other)),
+    }
+}
+
+/// Blocking client for Qdrant's REST API, retrying rate-limited and
+/// failed requests
+#[derive(Debug, Clone)]
+pub struct QdrantClient {
+    http: Client,
+    url: String,
+    api_key: Option<String>,
+    max_retries: u32,
+    retry_delay: Duration,
+}
+
+impl QdrantClient {
+    /// Create a client for the Qdrant server at `url`, e.g.
+    /// `http://localhost:6333`
+    pub fn new(url: impl Into<String>, api_key: Option<String>) -> Result<Self> {
+        Ok(Self {
+            http: http::client()?,
+            url: url.into().trim_end_matches('/').to_string(),
+            api_key,
+            max_retries: http::DEFAULT_RETRIES,
+            retry_delay: http::DEFAULT_RETRY_DELAY,
+        })
+    }
+
+    /// Retry a request answered with 429 or a 5xx status up to
+    /// `max_retries` times, doubling `delay` each time unless the response
+    /// carries a `Retry-After` header (default: 5 retries from 500ms)
+    pub fn with_retries(mut self, max_retries: u32, delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Look up a collection's vectors and their distance metrics
+    pub fn describe_collection(&self, name: &str) -> Result<QdrantCollection> {
+        let url = format!("{}/collections/{}", self.url, name);
+        let info: CollectionInfo = self
+            .send(|| self.http.get(&url))
+            .with_context(|| format!("Failed to describe Qdrant collection '{}'", name))?;
+
+        let vectors = info.config.params.vectors;
+        let vectors = if vectors.get("size").is_some() {
+            vec![QdrantVector::parse(None, vectors)?]
+        } else {
+            match vectors {
+                Value::Object(named) => named
+                    .into_iter()
+                    .map(|(name, params)| QdrantVector::parse(Some(name), params))
+                    .collect::<Result<_>>()?,
+                _ => return Err(anyhow!("Collection '{}' has no dense vectors", name)),
+            }
+        };
+        Ok(QdrantCollection {
+            name: name.to_string(),
+            vectors,
+        })
+    }
+
+    /// Send a request and unwrap Qdrant's `result` envelope
+    fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<T> {
+        let response: QdrantResponse<T> = http::send_json(
+            "Qdrant",
+            self.max_retries,
+            self.retry_delay,
+            || match &self.api_key {
+                Some(key) => request().header("api-key", key),
+                None => request(),
+            },
+        )?;
+        Ok(response.result)
+    }
+}
+
+/// A collection as described by `GET /collections/{name}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QdrantCollection {
+    pub name: String,
+    /// The collection's dense vectors; one unnamed one, or any number of
+    /// named ones
+    pub vectors: Vec<QdrantVector>,
+}
+
+/// One of a collection's dense vectors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QdrantVector {
+    /// `None` for a collection's single unnamed vector
+    pub name: Option<String>,
+    pub size: usize,
+    /// `Cosine`, `Euclid`, `Dot` or `Manhattan`
+    pub distance: String,
+}
+
+impl QdrantVector {
+    fn parse(name: Option<String>, params: Value) -> Result<Self> {
+        let params: VectorParams =
+            serde_json::from_value(params).context("Invalid Qdrant vector parameters")?;
+        Ok(Self {
+            name,
+            size: params.size,
+            distance: params.distance,
+        })
+    }
+
+    /// The vector's metric as a [`Distance`]
+    pub fn distance(&self) -> Result<Distance> {
+        distance_for_qdrant(&self.distance)
+    }
+}
+
+impl QdrantCollection {
+    /// Pick the vector to migrate: `name` if given, else the only one
+    pub fn vector(&self, name: Option<&str>) -> Result<&QdrantVector> {
+        let names = || {
+            self.vectors
+                .iter()
+                .filter_map(|v| v.name.as_deref())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match name {
+            Some(name) => self
+                .vectors
+                .iter()
+                .find(|v| v.name.as_deref() == Some(name))
+                .ok_or_else(|| match names() {
+                    names if names.is_empty() => anyhow!(
+                        "Collection '{}' has one unnamed vector, not '{}'",
+                        self.name,
+                        name
+                    ),
+                    names => anyhow!(
+                        "Collection '{}' has no vector '{}' (it has: {})",
+                        self.name,
+                        name,
+                        names
+                    ),
+                }),
+            None if self.vectors.len() == 1 => Ok(&self.vectors[0]),
+            None => Err(anyhow!(
+                "Collection '{}' has several named vectors ({}); pick one with --vector-name",
+                self.name,
+                names()
+            )),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct QdrantResponse<T> {
+    result: T,
+}
+
+#[derive(Deserialize)]
+struct CollectionInfo {
+    config: CollectionConfig,
+}
+
+#[derive(Deserialize)]
+struct CollectionConfig {
+    params: CollectionParams,
+}
+
+#[derive(Deserialize)]
+struct CollectionParams {
+    vectors: Value,
+}
+
+#[derive(Deserialize)]
+struct VectorParams {
+    size: usize,
+    distance: String,
+}
+
+#[derive(Deserialize)]
+struct ScrollResult {
+    points: Vec<Point>,
+    next_page_offset: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct CountResult {
+    count: usize,
+}
+
+/// A point as `scroll` returns it
+#[derive(Deserialize)]
+struct Point {
+    id: Value,
+    #[serde(default)]
+    vector: Option<Value>,
+    #[serde(default)]
+    payload: Option<Map<String, Value>>,
+}
+
+impl Point {
+    /// Build a record from the point's `vector_name` vector (or its only
+    /// vector) and mapped payload
+    fn into_record(
+        self,
+        vector_name: Option<&str>,
+        mapping: &mut PayloadMapping,
+    ) -> Result<Record> {
+        let id = match self.id {
+            Value::String(id) => id,
+            Value::Number(id) => id.to_string(),
+            other => return Err(anyhow!("Invalid Qdrant point id {}", other)),
+        };
+        let vector = match (self.vector, vector_name) {
+            (Some(Value::Object(mut named)), Some(name)) => named
+                .remove(name)
+                .with_context(|| format!("Point {} has no '{}' vector", id, name))?,
+            (Some(Value::Object(named)), None) if named.len() == 1 => {
+                named.into_iter().next().expect("one vector").1
+            }
+            (Some(Value::Object(_)), None) => {
+                return Err(anyhow!(
+                    "Point {} has several named vectors; pick one with --vector-name",
+                    id
+                ))
+            }
+            (Some(vector), _) => vector,
+            (None, _) => return Err(anyhow!("Point {} has no vector", id)),
+        };
+        let vector: Vec<f32> = serde_json::from_value(vector)
+            .with_context(|| format!("Point {} does not have a dense vector", id))?;
+
+        let fields = mapping.map(self.payload.unwrap_or_default());
+        Ok(make_record(id, vector, Metadata { fields }))
+    }
+}
+
+/// How Qdrant payloads were turned into metadata
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PayloadMapping {
+    /// Fields stored as JSON text, with the number of points they were
+    /// stringified on
+    pub stringified: BTreeMap<String, usize>,
+}
+
+impl PayloadMapping {
+    fn map(&mut self, payload: Map<String, Value>) -> HashMap<String, Value> {
+        payload
+            .into_iter()
+            .map(|(key, value)| {
+                if Self::filterable(&value) {
+                    (key, value)
+                } else {
+                    *self.stringified.entry(key.clone()).or_default() += 1;
+                    (key, Value::String(value.to_string()))
+                }
+            })
+            .collect()
+    }
+
+    /// Scalars, arrays of scalars and geo points
+    fn filterable(value: &Value) -> bool {
+        match value {
+            Value::Object(map) => {
+                map.len() == 2
+                    && map.get("lat").is_some_and(Value::is_number)
+                    && map.get("lon").is_some_and(Value::is_number)
+            }
+            Value::Array(items) => !items.iter().any(|v| v.is_object() || v.is_array()),
+            _ => true,
+        }
+    }
+}
+
+/// A live Qdrant collection read by scrolling its points
+///
+/// Cursors are the JSON of Qdrant's `next_page_offset`, a point id, so a
+/// checkpoint stays valid as long as that point exists.
+pub struct QdrantSource {
+    client: QdrantClient,
+    collection: QdrantCollection,
+    vector: QdrantVector,
+    mapping: PayloadMapping,
+}
+
+impl QdrantSource {
+    /// Describe `collection` and read its `vector_name` vector, which may
+    /// be left out when the collection has one vector
+    pub fn connect(
+        client: QdrantClient,
+        collection: &str,
+        vector_name: Option<&str>,
+    ) -> Result<Self> {
+        let collection = client.describe_collection(collection)?;
+        let vector = collection.vector(vector_name)?.clone();
+        vector.distance()?;
+        Ok(Self {
+            client,
+            collection,
+            vector,
+            mapping: PayloadMapping::default(),
+        })
+    }
+
+    /// The collection being read
+    pub fn collection(&self) -> &QdrantCollection {
+        &self.collection
+    }
+
+    /// The vector being migrated
+    pub fn vector(&self) -> &QdrantVector {
+        &self.vector
+    }
+
+    /// How the payloads read so far were mapped
+    pub fn payload_mapping(&self) -> &PayloadMapping {
+        &self.mapping
+    }
+}
+
+impl MigrationReader for QdrantSource {
+    fn source_id(&self) -> String {
+        match &self.vector.name {
+            Some(name) => format!(
+                "qdrant:{}/{}#{}",
+                self.client.url, self.collection.name, name
+            ),
+            None => format!("qdrant:{}/{}", self.client.url, self.collection.name),
+        }
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        Some(self.vector.size)
+    }
+
+    fn distance(&self) -> Distance {
+        self.vector.distance().unwrap_or_default()
+    }
+
+    fn count(&mut self) -> Result<Option<usize>> {
+        let url = format!(
+            "{}/collections/{}/points/count",
+            self.client.url, self.collection.name
+        );
+        let count: CountResult = self
+            .client
+            .send(|| {
+                self.client
+                    .http
+                    .post(&url)
+                    .json(&serde_json::json!({"exact": true}))
+            })
+            .context("Failed to count Qdrant points")?;
+        Ok(Some(count.count))
+    }
+
+    fn read_page(&mut self, cursor: Option<&str>) -> Result<MigrationPage> {
+        let offset: Option<Value> = cursor
+            .map(|cursor| {
+                serde_json::from_str(cursor)
+                    .with_context(|| format!("Invalid Qdrant cursor '{}'", cursor))
+            })
+            .transpose()?;
+        let with_vector = match &self.vector.name {
+            Some(name) => serde_json::json!([name]),
+            None => Value::Bool(true),
+        };
+        let body = serde_json::json!({
+            "limit": SCROLL_LIMIT,
+            "offset": offset,
+            "with_payload": true,
+            "with_vector": with_vector,
+        });
+        let url = format!(
+            "{}/collections/{}/points/scroll",
+            self.client.url, self.collection.name
+        );
+        let scrolled: ScrollResult = self
+            .client
+            .send(|| self.client.http.post(&url).json(&body))
+            .context("Failed to scroll Qdrant points")?;
+
+        let records = scrolled
+            .points
+            .into_iter()
+            .map(|point| point.into_record(self.vector.name.as_deref(), &mut self.mapping))
+            .collect::<Result<_>>()?;
+        Ok(MigrationPage {
+            records,
+            next: scrolled.next_page_offset.map(|offset| offset.to_string()),
+        })
+    }
+}
+
+/// An offline JSONL dump of Qdrant points
+///
+/// Dumps don't record the metric, so it is given when opening. Cursors are
+/// the number of lines already read.
+pub struct QdrantDump {
+    path: PathBuf,
+    distance: Distance,
+    vector_name: Option<String>,
+    lines: JsonlPages,
+    mapping: PayloadMapping,
+}
+
+impl QdrantDump {
+    /// Open a dump, reading the `vector_name` vector of points with named
+    /// vectors
+    pub fn open(
+        path: impl Into<PathBuf>,
+        distance: Distance,
+        vector_name: Option<&str>,
+    ) -> Result<Self> {
+        let path = path.into();
+        let mut header = [0u8; 262];
+        let read = std::fs::File::open(&path)
+            .and_then(|mut file| file.read(&mut header))
+            .with_context(|| format!("Failed to open Qdrant dump {:?}", path))?;
+        if read == header.len() && &header[257..262] == b"ustar" {
+            return Err(anyhow!(
+                "{:?} is a Qdrant snapshot archive; recover it into a Qdrant instance \
+                 and migrate from its URL instead",
+                path
+            ));
+        }
+        Ok(Self {
+            lines: JsonlPages::open(&path)?,
+            path,
+            distance,
+            vector_name: vector_name.map(str::to_string),
+            mapping: PayloadMapping::default(),
+        })
+    }
+
+    /// How the payloads read so far were mapped
+    pub fn payload_mapping(&self) -> &PayloadMapping {
+        &self.mapping
+    }
+}
+
+impl MigrationReader for QdrantDump {
+    fn source_id(&self) -> String {
+        format!("qdrant-dump:{}", self.path.display())
+    }
+
+    fn dimension(&self) -> Option<usize> {
+        None
+    }
+
+    fn distance(&self) -> Distance {
+        self.distance
+    }
+
+    fn count(&mut self) -> Result<Option<usize>> {
+        self.lines.count().map(Some)
+    }
+
+    fn read_page(&mut self, cursor: Option<&str>) -> Result<MigrationPage> {
+        let (lines, next) = self.lines.read_page(cursor)?;
+        let records = lines
+            .into_iter()
+            .map(|(number, line)| {
+                let point: Point = serde_json::from_str(&line)
+                    .with_context(|| format!("Invalid point on line {}", number))?;
+                point
+                    .into_record(self.vector_name.as_deref(), &mut self.mapping)
+                    .with_context(|| format!("Invalid point on line {}", number))
+            })
+            .collect::<Result<_>>()?;
+        Ok(MigrationPage { records, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_payload_mapping() {
+        let mut mapping = PayloadMapping::default();
+        let payload = json!({
+            "city": "Berlin",
+            "tags": ["a", "b"],
+            "location": {"lat": 52.5, "lon": 13.4},
+            "author": {"name": "Ada"},
+            "history": [{"at": 1}],
+        });
+        let fields = mapping.map(payload.as_object().unwrap().clone());
+
+        assert_eq!(fields["city"], json!("Berlin"));
+        assert_eq!(fields["tags"], json!(["a", "b"]));
+        assert_eq!(fields["location"], json!({"lat": 52.5, "lon": 13.4}));
+        assert_eq!(fields["author"], json!(r#"{"name":"Ada"}"#));
+        assert_eq!(fields["history"], json!(r#"[{"at":1}]"#));
+        assert_eq!(
+            mapping.stringified,
+            BTreeMap::from([("author".to_string(), 1), ("history".to_string(), 1)])
+        );
+    }
+
+    #[test]
+    fn test_named_vectors() {
+        let point = || -> Point {
+            serde_json::from_value(json!({
+                "id": 7,
+                "vector": {"text": [1.0, 0.0], "image": [0.0, 1.0, 0.0]},
+            }))
+            .unwrap()
+        };
+        let mut mapping = PayloadMapping::default();
+
+        let record = point().into_record(Some("image"), &mut mapping).unwrap();
+        assert_eq!(record.id, "7");
+        assert_eq!(record.vector, vec![0.0, 1.0, 0.0]);
+        assert!(point().into_record(None, &mut mapping).is_err());
+        assert!(point().into_record(Some("audio"), &mut mapping).is_err());
+    }
+
+    #[test]
+    fn test_vector_selection() {
+        let collection = QdrantCollection {
+            name: "docs".into(),
+            vectors: vec![
+                QdrantVector {
+                    name: Some("text".into()),
+                    size: 2,
+                    distance: "Dot".into(),
+                },
+                QdrantVector {
+                    name: Some("image".into()),
+                    size: 3,
+                    distance: "Euclid".into(),
+                },
+            ],
+        };
+        let image = collection.vector(Some("image")).unwrap();
+        assert_eq!(image.distance().unwrap(), Distance::Euclidean);
+        let error = collection.vector(None).unwrap_err().to_string();
+        assert!(error.contains("text, image"));
+    }
+
+    #[test]
+    fn test_dump_rejects_snapshot_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("docs.snapshot");
+        let mut archive = vec![0u8; 512];
+        archive[257..262].copy_from_slice(b"ustar");
+        std::fs::write(&path, archive).unwrap();
+
+        let error = QdrantDump::open(&path, Distance::Cosine, None)
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("snapshot archive"));
+    }
+}
@@ -138,6 +138,76 @@ impl NamespaceQuotas {
     }
 }
 
+/// A quota of [`NamespaceQuotas`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quota {
+    MaxVectors,
+    MaxStorageBytes,
+    MaxRequestsPerSecond,
+    MaxConcurrentQueries,
+    MaxDimension,
+    MaxResultsPerQuery,
+    MaxBatchSize,
+}
+
+/// A request refused because it would take a namespace over a quota
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotaExceeded {
+    pub quota: Quota,
+    /// The quota's limit; requests per second are rounded up
+    pub limit: u64,
+    /// Usage before the request
+    pub current: u64,
+    /// What the request asked for: new vectors, results, batch size or
+    /// dimension
+    pub requested: u64,
+}
+
+impl QuotaExceeded {
+    pub fn new(quota: Quota, limit: u64, current: u64, requested: u64) -> Self {
+        Self {
+            quota,
+            limit,
+            current,
+            requested,
+        }
+    }
+}
+
+impl std::fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (limit, current, requested) = (self.limit, self.current, self.requested);
+        match self.quota {
+            Quota::MaxVectors => write!(
+                f,
+                "Vector quota exceeded: {} + {} > {} vectors",
+                current, requested, limit
+            ),
+            Quota::MaxStorageBytes => {
+                write!(f, "Storage quota exceeded: {} / {} bytes", current, limit)
+            }
+            Quota::MaxRequestsPerSecond => {
+                write!(f, "Rate limit exceeded: {} requests/second", limit)
+            }
+            Quota::MaxConcurrentQueries => {
+                write!(
+                    f,
+                    "Concurrent query limit exceeded: {} / {}",
+                    current, limit
+                )
+            }
+            Quota::MaxDimension => write!(f, "Dimension exceeds limit: {} > {}", requested, limit),
+            Quota::MaxResultsPerQuery => {
+                write!(f, "Query limit exceeds maximum: {} > {}", requested, limit)
+            }
+            Quota::MaxBatchSize => write!(f, "Batch size exceeds limit: {} > {}", requested, limit),
+        }
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
 /// Current resource usage for a namespace
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ResourceUsage {
@@ -178,36 +248,54 @@ impl ResourceUsage {
         // Check vector count
         if let Some(max_vectors) = quotas.max_vectors {
             if self.vector_count >= max_vectors {
-                return Err(anyhow!(
-                    "Vector quota exceeded: {} / {} vectors",
-                    self.vector_count,
-                    max_vectors
-                ));
+                return Err(QuotaExceeded::new(
+                    Quota::MaxVectors,
+                    max_vectors as u64,
+                    self.vector_count as u64,
+                    0,
+                )
+                .into());
             }
         }
 
         // Check storage
+        self.check_storage(quotas)?;
+
+        // Check concurrent queries
+        self.check_concurrency(quotas)?;
+
+        Ok(())
+    }
+
+    /// Check that storage is under its quota
+    fn check_storage(&self, quotas: &NamespaceQuotas) -> Result<()> {
         if let Some(max_storage) = quotas.max_storage_bytes {
             if self.storage_bytes >= max_storage {
-                return Err(anyhow!(
-                    "Storage quota exceeded: {} / {} bytes",
+                return Err(QuotaExceeded::new(
+                    Quota::MaxStorageBytes,
+                    max_storage,
                     self.storage_bytes,
-                    max_storage
-                ));
+                    0,
+                )
+                .into());
             }
         }
+        Ok(())
+    }
 
-        // Check concurrent queries
+    /// Check that another query may start
+    fn check_concurrency(&self, quotas: &NamespaceQuotas) -> Result<()> {
         if let Some(max_concurrent) = quotas.max_concurrent_queries {
             if self.active_queries >= max_concurrent {
-                return Err(anyhow!(
-                    "Concurrent query limit exceeded: {} / {}",
-                    self.active_queries,
-                    max_concurrent
-                ));
+                return Err(QuotaExceeded::new(
+                    Quota::MaxConcurrentQueries,
+                    max_concurrent as u64,
+                    self.active_queries as u64,
+                    1,
+                )
+                .into());
             }
         }
-
         Ok(())
     }
 
@@ -227,7 +315,13 @@ impl ResourceUsage {
         // Check rate limit
         if let Some(max_rps) = quotas.max_requests_per_second {
             if self.requests_current_window as f64 >= max_rps {
-                return Err(anyhow!("Rate limit exceeded: {} requests/second", max_rps));
+                return Err(QuotaExceeded::new(
+                    Quota::MaxRequestsPerSecond,
+                    max_rps.ceil() as u64,
+                    self.requests_current_window as u64,
+                    1,
+                )
+                .into());
             }
         }
 
@@ -275,33 +369,60 @@ impl Namespace {
 
     /// Check if namespace can accept new vectors
     pub fn can_upsert(&self, count: usize) -> Result<()> {
+        self.can_upsert_records(count, count)
+    }
+
+    /// Check if namespace can accept a batch of `count` records, `new` of
+    /// which aren't stored yet; overwriting a record doesn't count against
+    /// the vector quota
+    pub fn can_upsert_records(&self, count: usize, new: usize) -> Result<()> {
         if self.status != NamespaceStatus::Active {
             return Err(anyhow!("Namespace is not active: {:?}", self.status));
         }
 
-        // Check if adding 'count' vectors would exceed quota
+        // Check if adding 'new' vectors would exceed quota
         if let Some(max_vectors) = self.quotas.max_vectors {
-            if self.usage.vector_count + count > max_vectors {
-                return Err(anyhow!(
-                    "Upsert would exceed vector quota: {} + {} > {}",
-                    self.usage.vector_count,
-                    count,
-                    max_vectors
-                ));
+            if self.usage.vector_count + new > max_vectors {
+                return Err(QuotaExceeded::new(
+                    Quota::MaxVectors,
+                    max_vectors as u64,
+                    self.usage.vector_count as u64,
+                    new as u64,
+                )
+                .into());
             }
         }
 
         // Check batch size
         if let Some(max_batch) = self.quotas.max_batch_size {
             if count > max_batch {
-                return Err(anyhow!(
-                    "Batch size exceeds limit: {} > {}",
-                    count,
-                    max_batch
-                ));
+                return Err(QuotaExceeded::new(
+                    Quota::MaxBatchSize,
+                    max_batch as u64,
+                    0,
+                    count as u64,
+                )
+                .into());
             }
         }
 
+        // Check storage
+        self.usage.check_storage(&self.quotas)
+    }
+
+    /// Check if namespace accepts vectors of `dimension` components
+    pub fn check_dimension(&self, dimension: usize) -> Result<()> {
+        if let Some(max_dimension) = self.quotas.max_dimension {
+            if dimension > max_dimension {
+                return Err(QuotaExceeded::new(
+                    Quota::MaxDimension,
+                    max_dimension as u64,
+                    0,
+                    dimension as u64,
+                )
+                .into());
+            }
+        }
         Ok(())
     }
 
@@ -322,17 +443,20 @@ impl Namespace {
             }
         }
 
-        // Check concurrent query limit
-        self.usage.check_quotas(&self.quotas)?;
+        // Check concurrent query limit; a namespace at its vector or
+        // storage quota still answers queries
+        self.usage.check_concurrency(&self.quotas)?;
 
         // Check result limit
         if let Some(max_results) = self.quotas.max_results_per_query {
             if k > max_results {
-                return Err(anyhow!(
-                    "Query limit exceeds maximum: {} > {}",
-                    k,
-                    max_results
-                ));
+                return Err(QuotaExceeded::new(
+                    Quota::MaxResultsPerQuery,
+                    max_results as u64,
+                    0,
+                    k as u64,
+                )
+                .into());
             }
         }
 
@@ -4,12 +4,18 @@
 //! with quota enforcement and resource management.
 
 use crate::namespace::{Namespace, NamespaceId, NamespaceQuotas, NamespaceStatus};
-use crate::store::{Metadata, Neighbor, Query, VecStore};
+use crate::store::{
+    make_record, Config, Distance, HnswConfig, Metadata, Neighbor, Query, Record, SlowQuery,
+    VecStore,
+};
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
+/// File under the root directory holding the alias table
+const ALIASES_FILE: &str = "aliases.json";
+
 /// Multi-tenant namespace manager
 pub struct NamespaceManager {
     /// Root directory for all namespaces
@@ -18,11 +24,15 @@ pub struct NamespaceManager {
     /// Active namespaces and their metadata
     namespaces: Arc<RwLock<HashMap<NamespaceId, Namespace>>>,
 
-    /// VecStore instances per namespace
-    stores: Arc<RwLock<HashMap<NamespaceId, VecStore>>>,
+    /// VecStore instances per namespace, each behind its own lock so
+    /// requests to different namespaces don't wait on each other
+    stores: Arc<RwLock<HashMap<NamespaceId, Arc<RwLock<VecStore>>>>>,
 
     /// Default quotas for new namespaces
     default_quotas: NamespaceQuotas,
+
+    /// Alternative names resolving to a namespace, persisted in `aliases.json`
+    aliases: Arc<RwLock<BTreeMap<String, NamespaceId>>>,
 }
 
 impl NamespaceManager {
@@ -36,6 +46,7 @@ impl NamespaceManager {
             namespaces: Arc::new(RwLock::new(HashMap::new())),
             stores: Arc::new(RwLock::new(HashMap::new())),
             default_quotas: NamespaceQuotas::default(),
+            aliases: Arc::new(RwLock::new(BTreeMap::new())),
         })
     }
 
@@ -49,10 +60,16 @@ impl NamespaceManager {
         Ok(manager)
     }
 
-    /// Load existing namespaces from disk
+    /// Load existing namespaces (and their aliases) from disk
     pub fn load_namespaces(&self) -> Result<Vec<NamespaceId>> {
         let mut loaded = Vec::new();
 
+        let aliases_path = self.root_path.join(ALIASES_FILE);
+        if aliases_path.exists() {
+            let aliases = std::fs::read_to_string(&aliases_path)?;
+            *self.aliases.write().unwrap() = serde_json::from_str(&aliases)?;
+        }
+
         for entry in std::fs::read_dir(&self.root_path)? {
             let entry = entry?;
             if entry.file_type()?.is_dir() {
@@ -63,16 +80,19 @@ impl NamespaceManager {
                 let metadata_path = ns_path.join("namespace.json");
                 if metadata_path.exists() {
                     let metadata = std::fs::read_to_string(&metadata_path)?;
-                    let namespace: Namespace = serde_json::from_str(&metadata)?;
+                    let mut namespace: Namespace = serde_json::from_str(&metadata)?;
 
                     // Load VecStore
                     let store = VecStore::open(&ns_path)?;
+                    namespace.usage.vector_count = store.len();
+                    namespace.usage.storage_bytes = store.disk_bytes();
+                    namespace.usage.active_queries = 0;
 
                     let mut namespaces = self.namespaces.write().unwrap();
                     let mut stores = self.stores.write().unwrap();
 
                     namespaces.insert(ns_id.clone(), namespace);
-                    stores.insert(ns_id.clone(), store);
+                    stores.insert(ns_id.clone(), Arc::new(RwLock::new(store)));
 
                     loaded.push(ns_id);
                 }
@@ -88,12 +108,29 @@ impl NamespaceManager {
         id: NamespaceId,
         name: String,
         quotas: Option<NamespaceQuotas>,
+    ) -> Result<()> {
+        self.create_namespace_with_config(id, name, quotas, Config::default())
+    }
+
+    /// Create a new namespace whose store uses `config`
+    ///
+    /// The config is saved with the store right away, so reloading the
+    /// namespace keeps its distance metric and HNSW parameters.
+    pub fn create_namespace_with_config(
+        &self,
+        id: NamespaceId,
+        name: String,
+        quotas: Option<NamespaceQuotas>,
+        config: Config,
     ) -> Result<()> {
         let namespaces = self.namespaces.read().unwrap();
         if namespaces.contains_key(&id) {
             return Err(anyhow!("Namespace already exists: {}", id));
         }
         drop(namespaces);
+        if self.aliases.read().unwrap().contains_key(&id) {
+            return Err(anyhow!("Name already used by an alias: {}", id));
+        }
 
         let quotas = quotas.unwrap_or_else(|| self.default_quotas.clone());
         let namespace = Namespace::new(id.clone(), name, quotas);
@@ -102,19 +139,26 @@ impl NamespaceManager {
         let ns_path = self.root_path.join(&id);
         std::fs::create_dir_all(&ns_path)?;
 
+        // Create VecStore for this namespace (fails on an unsupported config)
+        let store = match VecStore::open_with_config(&ns_path, config) {
+            Ok(store) => store,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&ns_path);
+                return Err(e);
+            }
+        };
+        store.save()?;
+
         // Save namespace metadata
         let metadata_path = ns_path.join("namespace.json");
         let metadata = serde_json::to_string_pretty(&namespace)?;
         std::fs::write(metadata_path, metadata)?;
 
-        // Create VecStore for this namespace
-        let store = VecStore::open(&ns_path)?;
-
         let mut namespaces = self.namespaces.write().unwrap();
         let mut stores = self.stores.write().unwrap();
 
         namespaces.insert(id.clone(), namespace);
-        stores.insert(id, store);
+        stores.insert(id, Arc::new(RwLock::new(store)));
 
         Ok(())
     }
@@ -171,7 +215,35 @@ impl NamespaceManager {
     }
 
     /// Delete a namespace
+    ///
+    /// Fails while aliases point at it; see
+    /// [`delete_namespace_force`](Self::delete_namespace_force).
     pub fn delete_namespace(&self, id: &NamespaceId) -> Result<()> {
+        let aliases = self.aliases_for(id);
+        if !aliases.is_empty() {
+            return Err(anyhow!(
+                "Namespace '{}' is the target of aliases {:?}; repoint or delete them first",
+                id,
+                aliases
+            ));
+        }
+        self.remove_namespace(id)
+    }
+
+    /// Delete a namespace together with every alias pointing at it
+    pub fn delete_namespace_force(&self, id: &NamespaceId) -> Result<()> {
+        self.get_namespace(id)?;
+        {
+            let mut aliases = self.aliases.write().unwrap();
+            let mut updated = aliases.clone();
+            updated.retain(|_, target| target != id);
+            self.persist_aliases(&updated)?;
+            *aliases = updated;
+        }
+        self.remove_namespace(id)
+    }
+
+    fn remove_namespace(&self, id: &NamespaceId) -> Result<()> {
         // Mark as pending deletion first
         self.update_status(id, NamespaceStatus::PendingDeletion)?;
 
@@ -189,6 +261,95 @@ impl NamespaceManager {
         Ok(())
     }
 
+    /// Create an alias resolving to the namespace `target`
+    ///
+    /// The alias name must not collide with a namespace or another alias.
+    pub fn create_alias(&self, alias: &str, target: &NamespaceId) -> Result<()> {
+        self.set_alias(alias, target, false)
+    }
+
+    /// Atomically repoint an existing alias at `target`
+    ///
+    /// The new mapping is written to disk before it becomes visible, so
+    /// lookups see either the old target or the new one, never neither.
+    pub fn update_alias(&self, alias: &str, target: &NamespaceId) -> Result<()> {
+        self.set_alias(alias, target, true)
+    }
+
+    fn set_alias(&self, alias: &str, target: &NamespaceId, exists: bool) -> Result<()> {
+        if !self.namespaces.read().unwrap().contains_key(target) {
+            return Err(anyhow!("Namespace not found: {}", target));
+        }
+
+        let mut aliases = self.aliases.write().unwrap();
+        match (aliases.contains_key(alias), exists) {
+            (true, false) => return Err(anyhow!("Alias already exists: {}", alias)),
+            (false, true) => return Err(anyhow!("Alias not found: {}", alias)),
+            _ => {}
+        }
+        if self.namespaces.read().unwrap().contains_key(alias) {
+            return Err(anyhow!("Name already used by a namespace: {}", alias));
+        }
+
+        let mut updated = aliases.clone();
+        updated.insert(alias.to_string(), target.clone());
+        self.persist_aliases(&updated)?;
+        *aliases = updated;
+        Ok(())
+    }
+
+    /// Remove an alias, leaving its namespace untouched
+    pub fn delete_alias(&self, alias: &str) -> Result<()> {
+        let mut aliases = self.aliases.write().unwrap();
+        if !aliases.contains_key(alias) {
+            return Err(anyhow!("Alias not found: {}", alias));
+        }
+
+        let mut updated = aliases.clone();
+        updated.remove(alias);
+        self.persist_aliases(&updated)?;
+        *aliases = updated;
+        Ok(())
+    }
+
+    /// All aliases and the namespaces they resolve to, sorted by alias
+    pub fn list_aliases(&self) -> Vec<(String, NamespaceId)> {
+        let aliases = self.aliases.read().unwrap();
+        aliases
+            .iter()
+            .map(|(alias, target)| (alias.clone(), target.clone()))
+            .collect()
+    }
+
+    /// Aliases currently pointing at `id`
+    pub fn aliases_for(&self, id: &NamespaceId) -> Vec<String> {
+        let aliases = self.aliases.read().unwrap();
+        aliases
+            .iter()
+            .filter(|(_, target)| *target == id)
+            .map(|(alias, _)| alias.clone())
+            .collect()
+    }
+
+    /// Resolve `name` to a namespace ID, following an alias if it is one
+    pub fn resolve(&self, name: &str) -> NamespaceId {
+        let aliases = self.aliases.read().unwrap();
+        aliases
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Write the alias table through a temp file and rename, so a crash
+    /// leaves either the old table or the new one
+    fn persist_aliases(&self, aliases: &BTreeMap<String, NamespaceId>) -> Result<()> {
+        let path = self.root_path.join(ALIASES_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(aliases)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
     /// Upsert a vector in a namespace
     pub fn upsert(
         &self,
@@ -197,6 +358,23 @@ impl NamespaceManager {
         vector: Vec<f32>,
         metadata: Metadata,
     ) -> Result<()> {
+        self.upsert_record(namespace_id, make_record(id, vector, metadata), None)
+            .map(|_| ())
+    }
+
+    /// Upsert a record in a namespace, only if it is at `expected_version`
+    /// when one is given (0: doesn't exist); returns the record's new version
+    pub fn upsert_record(
+        &self,
+        namespace_id: &NamespaceId,
+        record: Record,
+        expected_version: Option<u64>,
+    ) -> Result<u64> {
+        let store = self.store(namespace_id);
+        let new = store.as_ref().map_or(1, |store| {
+            usize::from(!store.read().unwrap().contains(&record.id))
+        });
+
         // Check namespace status and quotas
         {
             let mut namespaces = self.namespaces.write().unwrap();
@@ -204,36 +382,92 @@ impl NamespaceManager {
                 .get_mut(namespace_id)
                 .ok_or_else(|| anyhow!("Namespace not found: {}", namespace_id))?;
 
-            namespace.can_upsert(1)?;
+            namespace.can_upsert_records(1, new)?;
+            namespace.check_dimension(record.vector.len())?;
             namespace.usage.record_request(&namespace.quotas)?;
             namespace.usage.total_upserts += 1;
         }
 
         // Perform upsert
-        let mut stores = self.stores.write().unwrap();
-        let store = stores
-            .get_mut(namespace_id)
-            .ok_or_else(|| anyhow!("Store not found for namespace: {}", namespace_id))?;
-
-        store.upsert(id, vector, metadata)?;
+        let store = store?;
+        let (version, len, storage_bytes) = {
+            let mut store = store.write().unwrap();
+            let id = record.id.clone();
+            match expected_version {
+                Some(expected) => store
+                    .upsert_record_if_version(record, expected)
+                    .map(|_| ())?,
+                None => store.upsert_record(record)?,
+            }
 
-        // Persist changes to disk (Critical Issue #1 fix)
-        store.save()?;
+            // Persist changes to disk (Critical Issue #1 fix)
+            store.save()?;
+            let version = store.get(&id).map(|r| r.version).unwrap_or_default();
+            (version, store.len(), store.disk_bytes())
+        };
 
         // Update usage stats
+        self.record_usage(namespace_id, len, storage_bytes);
+
+        Ok(version)
+    }
+
+    /// Upsert a batch of records in a namespace
+    ///
+    /// Counts as one request against the rate limit and saves the store once
+    /// for the whole batch.
+    pub fn batch_upsert(&self, namespace_id: &NamespaceId, records: Vec<Record>) -> Result<()> {
+        let count = records.len();
+        let store = self.store(namespace_id);
+        let new = store.as_ref().map_or(count, |store| {
+            let store = store.read().unwrap();
+            let mut seen = HashSet::new();
+            records
+                .iter()
+                .filter(|r| !store.contains(&r.id) && seen.insert(r.id.as_str()))
+                .count()
+        });
+        let dimension = records.iter().map(|r| r.vector.len()).max().unwrap_or(0);
+
         {
             let mut namespaces = self.namespaces.write().unwrap();
-            if let Some(namespace) = namespaces.get_mut(namespace_id) {
-                namespace.usage.vector_count = store.len();
-                // Note: storage_bytes would need to be calculated from disk usage
-            }
+            let namespace = namespaces
+                .get_mut(namespace_id)
+                .ok_or_else(|| anyhow!("Namespace not found: {}", namespace_id))?;
+
+            namespace.can_upsert_records(count, new)?;
+            namespace.check_dimension(dimension)?;
+            namespace.usage.record_request(&namespace.quotas)?;
+            namespace.usage.total_upserts += count as u64;
         }
 
+        let store = store?;
+        let (len, storage_bytes) = {
+            let mut store = store.write().unwrap();
+            store.batch_upsert(records)?;
+            store.save()?;
+            (store.len(), store.disk_bytes())
+        };
+
+        self.record_usage(namespace_id, len, storage_bytes);
+
         Ok(())
     }
 
     /// Query vectors in a namespace
     pub fn query(&self, namespace_id: &NamespaceId, query: Query) -> Result<Vec<Neighbor>> {
+        let k = query.k;
+        self.query_with(namespace_id, k, |store| store.query(query))
+    }
+
+    /// Run `run` against a namespace's store as one query for `k` results,
+    /// counted against the namespace's quotas like [`query`](Self::query)
+    pub fn query_with<T>(
+        &self,
+        namespace_id: &NamespaceId,
+        k: usize,
+        run: impl FnOnce(&VecStore) -> Result<T>,
+    ) -> Result<T> {
         // Check namespace status and quotas
         {
             let mut namespaces = self.namespaces.write().unwrap();
@@ -241,20 +475,15 @@ impl NamespaceManager {
                 .get_mut(namespace_id)
                 .ok_or_else(|| anyhow!("Namespace not found: {}", namespace_id))?;
 
-            namespace.can_query(query.k)?;
+            namespace.can_query(k)?;
             namespace.usage.record_request(&namespace.quotas)?;
             namespace.usage.start_query();
         }
 
         // Perform query
-        let result = {
-            let stores = self.stores.read().unwrap();
-            let store = stores
-                .get(namespace_id)
-                .ok_or_else(|| anyhow!("Store not found for namespace: {}", namespace_id))?;
-
-            store.query(query)
-        };
+        let result = self
+            .store(namespace_id)
+            .and_then(|store| run(&store.read().unwrap()));
 
         // Update usage stats
         {
@@ -285,39 +514,60 @@ impl NamespaceManager {
         }
 
         // Perform delete
-        let mut stores = self.stores.write().unwrap();
-        let store = stores
-            .get_mut(namespace_id)
-            .ok_or_else(|| anyhow!("Store not found for namespace: {}", namespace_id))?;
-
-        store.remove(id)?;
-
-        // Persist changes to disk (Critical Issue #1 fix)
-        store.save()?;
+        let store = self.store(namespace_id)?;
+        let (len, storage_bytes) = {
+            let mut store = store.write().unwrap();
+            store.remove(id)?;
+
+            // Persist changes to disk (Critical Issue #1 fix)
+            store.save()?;
+            (store.len(), store.disk_bytes())
+        };
 
         // Update usage stats
-        {
-            let mut namespaces = self.namespaces.write().unwrap();
-            if let Some(namespace) = namespaces.get_mut(namespace_id) {
-                namespace.usage.vector_count = store.len();
-            }
-        }
+        self.record_usage(namespace_id, len, storage_bytes);
 
         Ok(())
     }
 
-    /// Get statistics for a namespace
-    pub fn get_stats(&self, namespace_id: &NamespaceId) -> Result<NamespaceStats> {
-        let namespaces = self.namespaces.read().unwrap();
-        let stores = self.stores.read().unwrap();
+    /// Set a namespace's vector count and storage after a write
+    fn record_usage(&self, namespace_id: &NamespaceId, vector_count: usize, storage_bytes: u64) {
+        let mut namespaces = self.namespaces.write().unwrap();
+        if let Some(namespace) = namespaces.get_mut(namespace_id) {
+            namespace.usage.vector_count = vector_count;
+            namespace.usage.storage_bytes = storage_bytes;
+        }
+    }
 
-        let namespace = namespaces
+    /// The store of a namespace, without holding the lock on the map of
+    /// stores
+    fn store(&self, namespace_id: &NamespaceId) -> Result<Arc<RwLock<VecStore>>> {
+        let stores = self.stores.read().unwrap();
+        stores
             .get(namespace_id)
-            .ok_or_else(|| anyhow!("Namespace not found: {}", namespace_id))?;
+            .cloned()
+            .ok_or_else(|| anyhow!("Store not found for namespace: {}", namespace_id))
+    }
 
-        let store = stores
-            .get(namespace_id)
-            .ok_or_else(|| anyhow!("Store not found for namespace: {}", namespace_id))?;
+    /// Get the store configuration of a namespace
+    pub fn get_config(&self, namespace_id: &NamespaceId) -> Result<Config> {
+        let store = self.store(namespace_id)?;
+        let config = store.read().unwrap().config().clone();
+        Ok(config)
+    }
+
+    /// Get the slow queries a namespace's store has logged, oldest first
+    pub fn slow_queries(&self, namespace_id: &NamespaceId) -> Result<Vec<SlowQuery>> {
+        let store = self.store(namespace_id)?;
+        let queries = store.read().unwrap().slow_queries();
+        Ok(queries)
+    }
+
+    /// Get statistics for a namespace
+    pub fn get_stats(&self, namespace_id: &NamespaceId) -> Result<NamespaceStats> {
+        let namespace = self.get_namespace(namespace_id)?;
+        let store = self.store(namespace_id)?;
+        let store = store.read().unwrap();
 
         Ok(NamespaceStats {
             namespace_id: namespace_id.clone(),
@@ -325,6 +575,9 @@ impl NamespaceManager {
             active_count: store.active_count(),
             deleted_count: store.deleted_count(),
             dimension: store.dimension(),
+            configured_dimension: store.config().dimension,
+            distance: store.config().distance,
+            hnsw: store.config().hnsw,
             quota_utilization: namespace.quota_utilization(),
             total_requests: namespace.usage.total_requests,
             total_queries: namespace.usage.total_queries,
@@ -352,7 +605,7 @@ impl NamespaceManager {
             total_requests += namespace.usage.total_requests;
 
             if let Some(store) = stores.get(ns_id) {
-                total_vectors += store.len();
+                total_vectors += store.read().unwrap().len();
             }
         }
 
@@ -482,6 +735,10 @@ pub struct NamespaceStats {
     pub active_count: usize,
     pub deleted_count: usize,
     pub dimension: usize,
+    /// Dimension fixed at creation, if any
+    pub configured_dimension: Option<usize>,
+    pub distance: Distance,
+    pub hnsw: HnswConfig,
     pub quota_utilization: f64,
     pub total_requests: u64,
     pub total_queries: u64,
@@ -502,6 +759,7 @@ pub struct AggregateStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::namespace::{Quota, QuotaExceeded};
     use tempfile::TempDir;
 
     #[test]
@@ -518,6 +776,42 @@ mod tests {
         assert_eq!(ns.status, NamespaceStatus::Active);
     }
 
+    #[test]
+    fn test_aliases_persist_and_guard_deletion() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = NamespaceManager::new(temp_dir.path()).unwrap();
+        for id in ["v1", "v2"] {
+            manager
+                .create_namespace(id.to_string(), id.to_string(), None)
+                .unwrap();
+        }
+
+        manager.create_alias("live", &"v1".to_string()).unwrap();
+        assert!(manager.create_alias("live", &"v2".to_string()).is_err());
+        assert!(manager.create_alias("v2", &"v1".to_string()).is_err());
+        assert!(manager.update_alias("missing", &"v2".to_string()).is_err());
+        assert!(manager.create_alias("x", &"nope".to_string()).is_err());
+
+        manager.update_alias("live", &"v2".to_string()).unwrap();
+        assert_eq!(manager.resolve("live"), "v2");
+        assert_eq!(manager.resolve("v1"), "v1");
+
+        // Aliased namespaces can only be deleted by force
+        assert!(manager.delete_namespace(&"v2".to_string()).is_err());
+        manager.delete_namespace(&"v1".to_string()).unwrap();
+
+        let reloaded = NamespaceManager::new(temp_dir.path()).unwrap();
+        reloaded.load_namespaces().unwrap();
+        assert_eq!(
+            reloaded.list_aliases(),
+            vec![("live".to_string(), "v2".to_string())]
+        );
+
+        reloaded.delete_namespace_force(&"v2".to_string()).unwrap();
+        assert!(reloaded.list_aliases().is_empty());
+        assert_eq!(reloaded.resolve("live"), "live");
+    }
+
     #[test]
     fn test_namespace_isolation() {
         let temp_dir = TempDir::new().unwrap();
@@ -548,6 +842,7 @@ mod tests {
             vector: vec![0.1, 0.2],
             k: 10,
             filter: None,
+            ..Default::default()
         };
         let results = manager.query(&"ns1".to_string(), query.clone()).unwrap();
         assert_eq!(results.len(), 1);
@@ -557,6 +852,54 @@ mod tests {
         assert_eq!(results.len(), 0);
     }
 
+    #[test]
+    fn test_namespaces_lock_independently() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = Arc::new(NamespaceManager::new(temp_dir.path()).unwrap());
+        for id in ["ns1", "ns2"] {
+            manager
+                .create_namespace(id.to_string(), id.to_string(), None)
+                .unwrap();
+        }
+        let ns2 = "ns2".to_string();
+        let record = make_record(
+            "a".to_string(),
+            vec![1.0, 0.0],
+            Metadata {
+                fields: std::collections::HashMap::new(),
+            },
+        );
+        assert_eq!(
+            manager
+                .upsert_record(&ns2, record.clone(), Some(0))
+                .unwrap(),
+            1
+        );
+        let conflict = manager.upsert_record(&ns2, record, Some(0)).unwrap_err();
+        assert!(conflict
+            .downcast_ref::<crate::store::VersionConflict>()
+            .is_some());
+
+        // A long write on ns1 holds only ns1's store
+        let ns1_store = manager.store(&"ns1".to_string()).unwrap();
+        let _writing = ns1_store.write().unwrap();
+        let (done, finished) = std::sync::mpsc::channel();
+        let querying = manager.clone();
+        std::thread::spawn(move || {
+            let query = Query {
+                vector: vec![1.0, 0.0],
+                k: 1,
+                ..Default::default()
+            };
+            done.send(querying.query(&ns2, query).map(|r| r.len()))
+                .unwrap();
+        });
+        let found = finished
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .expect("query on ns2 waited for ns1's lock");
+        assert_eq!(found.unwrap(), 1);
+    }
+
     #[test]
     fn test_quota_enforcement() {
         let temp_dir = TempDir::new().unwrap();
@@ -597,8 +940,78 @@ mod tests {
             &"limited".to_string(),
             "vec3".to_string(),
             vec![0.3],
+            metadata.clone(),
+        );
+        let exceeded = result.unwrap_err().downcast::<QuotaExceeded>().unwrap();
+        assert_eq!(exceeded.quota, Quota::MaxVectors);
+
+        // Overwriting a stored vector and querying still work
+        manager
+            .upsert(
+                &"limited".to_string(),
+                "vec2".to_string(),
+                vec![0.3],
+                metadata.clone(),
+            )
+            .unwrap();
+        let found = manager
+            .query(&"limited".to_string(), Query::new(vec![0.3]).with_limit(5))
+            .unwrap();
+        assert_eq!(found.len(), 2);
+
+        // Another namespace isn't held to this one's quota
+        manager
+            .create_namespace("other".to_string(), "Other".to_string(), None)
+            .unwrap();
+        let records = (0..3)
+            .map(|i| make_record(format!("vec{}", i), vec![0.1], metadata.clone()))
+            .collect();
+        manager.batch_upsert(&"other".to_string(), records).unwrap();
+
+        let quotas = NamespaceQuotas {
+            max_vectors: Some(3),
+            ..Default::default()
+        };
+        manager
+            .update_quotas(&"limited".to_string(), quotas)
+            .unwrap();
+        manager
+            .upsert(
+                &"limited".to_string(),
+                "vec3".to_string(),
+                vec![0.3],
+                metadata,
+            )
+            .unwrap();
+        let ns = manager.get_namespace(&"limited".to_string()).unwrap();
+        assert_eq!(ns.usage.vector_count, 3);
+        assert!(ns.usage.storage_bytes > 0);
+    }
+
+    #[test]
+    fn test_dimension_quota() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = NamespaceManager::new(temp_dir.path()).unwrap();
+
+        let quotas = NamespaceQuotas {
+            max_dimension: Some(2),
+            ..Default::default()
+        };
+        manager
+            .create_namespace("small".to_string(), "Small".to_string(), Some(quotas))
+            .unwrap();
+
+        let metadata = Metadata {
+            fields: std::collections::HashMap::new(),
+        };
+        let result = manager.upsert(
+            &"small".to_string(),
+            "wide".to_string(),
+            vec![0.1, 0.2, 0.3],
             metadata,
         );
-        assert!(result.is_err());
+        let exceeded = result.unwrap_err().downcast::<QuotaExceeded>().unwrap();
+        assert_eq!(exceeded.quota, Quota::MaxDimension);
+        assert_eq!(exceeded.requested, 3);
     }
 }
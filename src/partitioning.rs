@@ -339,7 +339,7 @@ impl PartitionedStore {
             .get_mut(partition_id)
             .ok_or_else(|| anyhow::anyhow!("Partition '{}' not found", partition_id))?;
 
-        let removed = partition.compact()?;
+        let removed = partition.compact()?.removed_count;
 
         // Update partition info
         if let Some(info) = self.partition_info.get_mut(partition_id) {
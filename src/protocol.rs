@@ -494,6 +494,7 @@ impl ProtocolAdapter {
                     vector,
                     k: top_k,
                     filter: None, // TODO: Convert filter to FilterExpr
+                    ..Default::default()
                 };
 
                 let results = self.store.query(query)?;
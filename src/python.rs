@@ -220,6 +220,7 @@ impl PyVecStore {
             vector,
             k,
             filter: filter_expr,
+            ..Default::default()
         };
 
         let results = self
@@ -540,6 +541,7 @@ impl PyCollection {
             vector,
             k,
             filter: filter_expr,
+            ..Default::default()
         };
 
         let results = self
@@ -194,6 +194,7 @@ impl PyVecStore {
             vector,
             k,
             filter: None, // TODO: implement filter conversion
+            ..Default::default()
         };
 
         let results = self.inner.query(query)
@@ -244,6 +245,7 @@ impl PyVecStore {
     ///     Number of vectors removed
     fn compact(&mut self) -> PyResult<usize> {
         self.inner.compact()
+            .map(|report| report.removed_count)
             .map_err(|e| PyValueError::new_err(format!("Compact failed: {}", e)))
     }
 
@@ -364,6 +366,7 @@ impl PyCollection {
             vector,
             k,
             filter: None,
+            ..Default::default()
         };
 
         let results = self.inner.query(query)
@@ -89,8 +89,8 @@ impl ScalarQuantizer8 {
             // Normalize to [0, 1]
             let normalized = (val - self.min_values[i]) / self.ranges[i];
 
-            // Clamp and scale to [0, 255]
-            let scaled = (normalized.clamp(0.0, 1.0) * 255.0) as u8;
+            // Clamp and scale to [0, 255], rounding so decode/encode round-trips
+            let scaled = (normalized.clamp(0.0, 1.0) * 255.0).round() as u8;
             quantized.push(scaled);
         }
 
@@ -9,6 +9,8 @@
 //! - **Optimization Hints**: Suggest improvements
 //! - **Query Analysis**: Identify bottlenecks
 //! - **Index Selection**: Recommend best indexes
+//! - **Store Recommendations**: Measure recall, deleted records and vector
+//!   size, and apply the safe changes
 //!
 //! # Example
 //!
@@ -31,7 +33,30 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-use crate::store::{Query, VecStore};
+use crate::store::{
+    Quantization, Query, RecallReport, VecStore, DEFAULT_RECALL_K, DEFAULT_RECALL_SAMPLES,
+};
+
+/// Recall@10 the suggested `ef_search` has to reach
+pub const TARGET_RECALL: f64 = 0.95;
+
+/// Share of deleted records (or stale index entries) from which compaction
+/// is recommended
+pub const COMPACTION_RATIO: f64 = 0.2;
+
+/// Dimension from which f32 stores are recommended int8 quantization
+pub const QUANTIZATION_MIN_DIMENSION: usize = 256;
+
+/// Records from which f32 stores are recommended int8 quantization
+pub const QUANTIZATION_MIN_RECORDS: usize = 10_000;
+
+/// Records from which unindexed stores are recommended metadata indexes
+const METADATA_INDEX_MIN_RECORDS: usize = 10_000;
+
+/// `ef_search` values measured besides the suggested and current ones
+const EF_SWEEP: [usize; 5] = [16, 32, 64, 128, 256];
+
+const MIB: f64 = 1024.0 * 1024.0;
 
 /// Query optimization hint
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -390,34 +415,144 @@ impl<'a> QueryOptimizer<'a> {
         })
     }
 
-    /// Get optimization summary for the entire store
+    /// Measure the store and recommend configuration changes
+    ///
+    /// Sampled stored vectors are run through the index at a range of
+    /// `ef_search` values to find the smallest reaching [`TARGET_RECALL`]
+    /// (only for stores large enough to search the index), and the store's
+    /// stats are checked for deleted records worth compacting away and
+    /// vectors worth quantizing.
     pub fn store_optimization_summary(&self) -> StoreOptimizationSummary {
         let store_size = self.store.len();
+        let stats = self.store.stats();
         let mut recommendations = Vec::new();
 
-        // Check store size
-        if store_size > 100000 {
-            recommendations.push(
-                "Consider partitioning large dataset by metadata for faster queries".to_string(),
-            );
+        // Stores under the exact search threshold never search the index
+        let (suggested_ef_search, recall) =
+            if store_size >= self.store.config().exact_search_threshold {
+                self.measure_ef_search()
+            } else {
+                (None, None)
+            };
+        let current_ef_search = self.store.default_ef_search();
+        if let (Some(ef_search), Some(report)) = (suggested_ef_search, &recall) {
+            let at = |ef| report.points.iter().find(|p| p.ef_search == ef);
+            if let (Some(current), Some(suggested)) = (at(current_ef_search), at(ef_search)) {
+                if ef_search != current_ef_search {
+                    recommendations.push(Recommendation {
+                        kind: RecommendationKind::EfSearch,
+                        current: format!("ef_search {}", current_ef_search),
+                        suggested: format!("ef_search {}", ef_search),
+                        expected_impact: format!(
+                            "recall@{} {:.1}% -> {:.1}%, mean latency {:.3}ms -> {:.3}ms (measured on {} sampled stored vectors)",
+                            report.k,
+                            current.recall * 100.0,
+                            suggested.recall * 100.0,
+                            current.mean_latency.as_secs_f64() * 1000.0,
+                            suggested.mean_latency.as_secs_f64() * 1000.0,
+                            report.queries
+                        ),
+                    });
+                }
+            }
+        }
+
+        // Deleted records stay in memory and on disk, and their stale index
+        // entries are still visited by searches
+        let deleted_ratio = if stats.total_records > 0 {
+            stats.deleted_records as f64 / stats.total_records as f64
+        } else {
+            0.0
+        };
+        if deleted_ratio.max(stats.fragmentation_ratio) >= COMPACTION_RATIO {
+            recommendations.push(Recommendation {
+                kind: RecommendationKind::Compaction,
+                current: format!(
+                    "{} deleted records ({:.0}%), {} stale index entries ({:.0}%)",
+                    stats.deleted_records,
+                    deleted_ratio * 100.0,
+                    stats.ghost_entries,
+                    stats.fragmentation_ratio * 100.0
+                ),
+                suggested: "compact".to_string(),
+                expected_impact: format!(
+                    "frees {} deleted records and {} stale index entries from memory and disk",
+                    stats.deleted_records, stats.ghost_entries
+                ),
+            });
         }
 
-        if store_size > 50000 {
-            recommendations
-                .push("Use approximate indexes (IVF-PQ, LSH) for better scaling".to_string());
+        match stats.quantization {
+            Quantization::None
+                if stats.dimension >= QUANTIZATION_MIN_DIMENSION
+                    && store_size >= QUANTIZATION_MIN_RECORDS =>
+            {
+                recommendations.push(Recommendation {
+                    kind: RecommendationKind::Quantization,
+                    current: format!(
+                        "f32 vectors, {} dimensions, {:.1} MiB",
+                        stats.dimension,
+                        stats.vector_memory_bytes as f64 / MIB
+                    ),
+                    suggested: "int8 quantization keeping originals (set when creating the store)"
+                        .to_string(),
+                    expected_impact: format!(
+                        "index vectors shrink 4x, about {:.1} MiB saved; originals re-rank the top candidates",
+                        (store_size * stats.dimension * 3) as f64 / MIB
+                    ),
+                });
+            }
+            Quantization::Int8 { .. } if !stats.quantization_calibrated && store_size > 0 => {
+                recommendations.push(Recommendation {
+                    kind: RecommendationKind::Quantization,
+                    current: "int8 quantization not calibrated, index holds f32 vectors"
+                        .to_string(),
+                    suggested: "calibrate_quantization".to_string(),
+                    expected_impact: format!(
+                        "index vectors shrink 4x, about {:.1} MiB saved",
+                        (store_size * stats.dimension * 3) as f64 / MIB
+                    ),
+                });
+            }
+            _ => {}
         }
 
-        if store_size > 10000 {
-            recommendations.push("Add metadata indexes for frequently filtered fields".to_string());
+        if store_size > METADATA_INDEX_MIN_RECORDS && self.store.config().field_indexes.is_empty() {
+            recommendations.push(Recommendation {
+                kind: RecommendationKind::MetadataIndex,
+                current: "no metadata field indexes".to_string(),
+                suggested: "index the fields queries filter on".to_string(),
+                expected_impact: format!(
+                    "filtered queries look up matching records instead of checking all {}",
+                    store_size
+                ),
+            });
         }
 
         StoreOptimizationSummary {
             store_size,
             estimated_query_time: self.estimate_avg_query_time(store_size),
             recommendations,
+            suggested_ef_search,
+            recall,
         }
     }
 
+    /// The smallest `ef_search` reaching [`TARGET_RECALL`], and recall and
+    /// latency over a sweep including it and the store's default
+    fn measure_ef_search(&self) -> (Option<usize>, Option<RecallReport>) {
+        let Ok(suggested) = self.store.suggest_ef_search(TARGET_RECALL) else {
+            return (None, None);
+        };
+        let mut sweep = EF_SWEEP.to_vec();
+        sweep.extend([suggested, self.store.default_ef_search()]);
+        let report = self
+            .store
+            .measure_recall_at(DEFAULT_RECALL_SAMPLES, DEFAULT_RECALL_K, &sweep)
+            .ok();
+        (Some(suggested), report)
+    }
+
     /// Estimate average query time
     fn estimate_avg_query_time(&self, store_size: usize) -> Duration {
         let ms = (store_size as f32 * 0.001).max(0.1);
@@ -436,12 +571,88 @@ pub struct QueryComparison {
     pub recommendation: String,
 }
 
+/// What a [`Recommendation`] changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationKind {
+    /// The `ef_search` of queries that don't set one
+    EfSearch,
+    /// Remove deleted records and rebuild the index
+    Compaction,
+    /// Quantize vectors, or calibrate the configured quantization
+    Quantization,
+    /// Index metadata fields used in filters
+    MetadataIndex,
+}
+
+impl RecommendationKind {
+    /// Whether [`StoreOptimizationSummary::apply`] acts on it
+    ///
+    /// Only changes needing no decision from the user are applied: the
+    /// measured `ef_search` and compaction.
+    pub fn is_safe(self) -> bool {
+        matches!(self, Self::EfSearch | Self::Compaction)
+    }
+}
+
+impl std::fmt::Display for RecommendationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::EfSearch => "ef_search",
+            Self::Compaction => "compaction",
+            Self::Quantization => "quantization",
+            Self::MetadataIndex => "metadata_index",
+        })
+    }
+}
+
+/// A configuration change recommended from measurements of the store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub kind: RecommendationKind,
+    /// What the store has now
+    pub current: String,
+    /// What to change it to
+    pub suggested: String,
+    /// What the change is expected to do
+    pub expected_impact: String,
+}
+
 /// Store optimization summary
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StoreOptimizationSummary {
     pub store_size: usize,
     pub estimated_query_time: Duration,
-    pub recommendations: Vec<String>,
+    pub recommendations: Vec<Recommendation>,
+    /// Smallest `ef_search` measured to reach [`TARGET_RECALL`], when the
+    /// store is large enough to search its index
+    pub suggested_ef_search: Option<usize>,
+    /// Recall and latency over the `ef_search` sweep, when one was measured
+    pub recall: Option<RecallReport>,
+}
+
+impl StoreOptimizationSummary {
+    /// Apply the [safe](RecommendationKind::is_safe) recommendations to `store`
+    ///
+    /// Sets the default `ef_search` to the suggested one and compacts the
+    /// store, as recommended. Compaction rewrites the store files; save to
+    /// persist the new `ef_search`. Returns the recommendations applied.
+    pub fn apply(&self, store: &mut VecStore) -> Result<Vec<Recommendation>> {
+        let mut applied = Vec::new();
+        for recommendation in &self.recommendations {
+            match recommendation.kind {
+                RecommendationKind::EfSearch => {
+                    store.set_default_ef_search(self.suggested_ef_search)?;
+                }
+                RecommendationKind::Compaction => {
+                    store.compact()?;
+                }
+                _ => continue,
+            }
+            applied.push(recommendation.clone());
+        }
+        Ok(applied)
+    }
 }
 
 #[cfg(test)]
@@ -555,4 +766,84 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_summary_suggests_ef_search() -> Result<()> {
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let temp_dir = TempDir::new()?;
+        let mut store = VecStore::open(temp_dir.path().join("test.db"))?;
+        let mut rng = StdRng::seed_from_u64(3);
+        for i in 0..300 {
+            let vector: Vec<f32> = (0..16).map(|_| rng.gen_range(-1.0..1.0)).collect();
+            let metadata = Metadata {
+                fields: HashMap::new(),
+            };
+            store.upsert(format!("doc{}", i), vector, metadata)?;
+        }
+
+        // Small stores are searched exactly, so ef_search doesn't matter
+        let summary = QueryOptimizer::new(&store).store_optimization_summary();
+        assert_eq!(summary.suggested_ef_search, None);
+
+        store.set_exact_search_threshold(0);
+        let summary = QueryOptimizer::new(&store).store_optimization_summary();
+        let ef_search = summary.suggested_ef_search.expect("ef_search suggestion");
+        assert!(ef_search >= DEFAULT_RECALL_K);
+        let report = summary.recall.as_ref().expect("recall sweep");
+        assert!(report.points.iter().any(|p| p.ef_search == ef_search));
+        assert!(report
+            .points
+            .iter()
+            .any(|p| p.ef_search == store.default_ef_search()));
+
+        if ef_search != store.default_ef_search() {
+            let recommendation = &summary.recommendations[0];
+            assert_eq!(recommendation.kind, RecommendationKind::EfSearch);
+            assert_eq!(recommendation.suggested, format!("ef_search {}", ef_search));
+
+            let applied = summary.apply(&mut store)?;
+            assert_eq!(applied.len(), 1);
+            assert_eq!(store.default_ef_search(), ef_search);
+        }
+        let summary = QueryOptimizer::new(&store).store_optimization_summary();
+        assert!(summary
+            .recommendations
+            .iter()
+            .all(|r| r.kind != RecommendationKind::EfSearch));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_summary_recommends_compaction() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut store = VecStore::open(temp_dir.path().join("test.db"))?;
+        for i in 0..100 {
+            let metadata = Metadata {
+                fields: HashMap::new(),
+            };
+            store.upsert(format!("doc{}", i), vec![i as f32, 1.0, 0.5], metadata)?;
+        }
+        let summary = QueryOptimizer::new(&store).store_optimization_summary();
+        assert!(summary.recommendations.is_empty());
+
+        for i in 0..30 {
+            store.soft_delete(&format!("doc{}", i))?;
+        }
+        let summary = QueryOptimizer::new(&store).store_optimization_summary();
+        assert_eq!(summary.recommendations.len(), 1);
+        let recommendation = &summary.recommendations[0];
+        assert_eq!(recommendation.kind, RecommendationKind::Compaction);
+        assert!(recommendation.kind.is_safe());
+        assert!(recommendation.current.starts_with("30 deleted records"));
+
+        summary.apply(&mut store)?;
+        assert_eq!(store.stats().deleted_records, 0);
+        let summary = QueryOptimizer::new(&store).store_optimization_summary();
+        assert!(summary.recommendations.is_empty());
+
+        Ok(())
+    }
 }
@@ -578,6 +578,9 @@ mod tests {
             metadata: Metadata {
                 fields: HashMap::new(),
             },
+            vector: None,
+            version: 1,
+            text: None,
         }
     }
 
@@ -669,11 +672,17 @@ mod tests {
                 id: "doc1".to_string(),
                 score: 0.9,
                 metadata: meta1,
+                vector: None,
+                version: 1,
+                text: None,
             },
             Neighbor {
                 id: "doc2".to_string(),
                 score: 0.8,
                 metadata: meta2,
+                vector: None,
+                version: 1,
+                text: None,
             },
         ];
 
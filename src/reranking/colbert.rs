@@ -34,6 +34,7 @@
 ///! # Ok(())
 ///! # }
 ///! ```
+use crate::simd::{dot_product_simd, euclidean_distance_simd, magnitude_simd};
 use anyhow::Result;
 use std::collections::HashMap;
 
@@ -248,21 +249,17 @@ impl ColBERTReranker {
 
     /// Dot product
     fn dot_product(vec1: &[f32], vec2: &[f32]) -> f32 {
-        vec1.iter().zip(vec2.iter()).map(|(a, b)| a * b).sum()
+        dot_product_simd(vec1, vec2)
     }
 
     /// L2 distance
     fn l2_distance(vec1: &[f32], vec2: &[f32]) -> f32 {
-        vec1.iter()
-            .zip(vec2.iter())
-            .map(|(a, b)| (a - b).powi(2))
-            .sum::<f32>()
-            .sqrt()
+        euclidean_distance_simd(vec1, vec2)
     }
 
     /// Normalize a vector to unit length
     fn normalize_vector(vec: Vec<f32>) -> Vec<f32> {
-        let norm: f32 = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
+        let norm = magnitude_simd(&vec);
         if norm > 0.0 {
             vec.into_iter().map(|x| x / norm).collect()
         } else {
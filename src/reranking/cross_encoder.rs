@@ -356,6 +356,9 @@ mod tests {
             id: id.to_string(),
             score,
             metadata,
+            vector: None,
+            version: 1,
+            text: None,
         }
     }
 
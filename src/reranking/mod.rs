@@ -74,6 +74,135 @@ pub trait Reranker: Send + Sync {
     fn name(&self) -> &str;
 }
 
+/// Reranking hook applied by [`VecStore`](crate::VecStore) to query results
+///
+/// The store fetches an over-sampled candidate set (`Query::rerank_fetch_k`),
+/// hands it to the reranker, and returns the first `k` of whatever comes back.
+/// Register one with [`VecStore::set_reranker`](crate::VecStore::set_reranker)
+/// or pass one per call to
+/// [`VecStore::query_with_reranker`](crate::VecStore::query_with_reranker).
+///
+/// Wrap any text [`Reranker`] in a [`QueryRerankerAdapter`] to use it here.
+///
+/// ## Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use vecstore::{Query, VecStore, reranking::MetadataBoostReranker};
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let mut store = VecStore::open("./data")?;
+/// store.set_reranker(Arc::new(
+///     MetadataBoostReranker::new().boost("source", "docs", 1.5),
+/// ));
+///
+/// // Fetch 50 candidates, boost, return the best 5
+/// let results = store.query(Query::new(vec![1.0, 0.0, 0.0]).with_limit(5).with_rerank_fetch_k(50))?;
+/// # Ok(())
+/// # }
+/// ```
+pub trait QueryReranker: Send + Sync {
+    /// Reorder (and optionally drop) candidates, best first
+    fn rerank(
+        &self,
+        query_text: Option<&str>,
+        query_vec: &[f32],
+        candidates: Vec<Neighbor>,
+    ) -> Result<Vec<Neighbor>>;
+}
+
+/// Use a text [`Reranker`] as a [`QueryReranker`]
+///
+/// A query without text is passed to the reranker as `""`.
+pub struct QueryRerankerAdapter<R: Reranker>(pub R);
+
+impl<R: Reranker> QueryReranker for QueryRerankerAdapter<R> {
+    fn rerank(
+        &self,
+        query_text: Option<&str>,
+        _query_vec: &[f32],
+        candidates: Vec<Neighbor>,
+    ) -> Result<Vec<Neighbor>> {
+        let top_k = candidates.len();
+        self.0.rerank(query_text.unwrap_or(""), candidates, top_k)
+    }
+}
+
+/// Metadata boost reranker
+///
+/// Scales the score of every candidate whose metadata field equals a given
+/// value, then re-sorts. Boosts for several matching rules multiply together.
+///
+/// Store scores are higher-is-closer for every metric, so a boost above 1.0
+/// makes a match rank higher. A negative score (a DotProduct match pointing
+/// away from the query) is divided instead, so that still holds.
+///
+/// ## Example
+///
+/// ```no_run
+/// use vecstore::reranking::MetadataBoostReranker;
+///
+/// let reranker = MetadataBoostReranker::new()
+///     .boost("lang", "en", 1.2)
+///     .boost("status", "archived", 0.5);
+/// ```
+#[derive(Default)]
+pub struct MetadataBoostReranker {
+    boosts: Vec<(String, serde_json::Value, f32)>,
+}
+
+impl MetadataBoostReranker {
+    /// Create a reranker without boosts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Multiply the relevance of candidates where `field == value` by `factor`
+    pub fn boost(
+        mut self,
+        field: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+        factor: f32,
+    ) -> Self {
+        self.boosts.push((field.into(), value.into(), factor));
+        self
+    }
+
+    fn apply(&self, score: f32, factor: f32) -> f32 {
+        if score >= 0.0 {
+            score * factor
+        } else {
+            score / factor
+        }
+    }
+}
+
+impl QueryReranker for MetadataBoostReranker {
+    fn rerank(
+        &self,
+        _query_text: Option<&str>,
+        _query_vec: &[f32],
+        candidates: Vec<Neighbor>,
+    ) -> Result<Vec<Neighbor>> {
+        let mut boosted: Vec<Neighbor> = candidates
+            .into_iter()
+            .map(|mut neighbor| {
+                for (field, value, factor) in &self.boosts {
+                    if neighbor.metadata.fields.get(field) == Some(value) {
+                        neighbor.score = self.apply(neighbor.score, *factor);
+                    }
+                }
+                neighbor
+            })
+            .collect();
+
+        // Stable sort keeps the original order among equal scores
+        boosted.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        Ok(boosted)
+    }
+}
+
 /// MMR (Maximal Marginal Relevance) Reranker
 ///
 /// Balances relevance and diversity by penalizing results similar to already-selected ones.
@@ -125,17 +254,7 @@ impl MMRReranker {
     /// Calculate cosine similarity between two vectors
     #[allow(dead_code)]
     fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-        assert_eq!(a.len(), b.len(), "Vectors must have same length");
-
-        let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-        let mag_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
-        let mag_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
-
-        if mag_a == 0.0 || mag_b == 0.0 {
-            0.0
-        } else {
-            dot / (mag_a * mag_b)
-        }
+        crate::simd::cosine_similarity_simd(a, b)
     }
 
     /// Calculate MMR score for a candidate result
@@ -900,6 +1019,9 @@ mod tests {
             metadata: Metadata {
                 fields: HashMap::new(),
             },
+            vector: None,
+            version: 1,
+            text: None,
         }
     }
 
@@ -1038,16 +1160,25 @@ mod tests {
                 id: "doc1".to_string(),
                 score: 0.5,
                 metadata: meta1,
+                vector: None,
+                version: 1,
+                text: None,
             },
             Neighbor {
                 id: "doc2".to_string(),
                 score: 0.9,
                 metadata: meta2,
+                vector: None,
+                version: 1,
+                text: None,
             },
             Neighbor {
                 id: "doc3".to_string(),
                 score: 0.7,
                 metadata: meta3,
+                vector: None,
+                version: 1,
+                text: None,
             },
         ];
 
@@ -1177,11 +1308,17 @@ mod tests {
                 id: "doc1".to_string(),
                 score: 0.5,
                 metadata: meta1,
+                vector: None,
+                version: 1,
+                text: None,
             },
             Neighbor {
                 id: "doc2".to_string(),
                 score: 0.9,
                 metadata: meta2,
+                vector: None,
+                version: 1,
+                text: None,
             },
         ];
 
@@ -1219,4 +1356,43 @@ mod tests {
     fn test_contextual_reranker_invalid_weight() {
         ContextualReranker::new().with_context_weight(1.5);
     }
+
+    #[test]
+    fn test_metadata_boost_reranker() {
+        let tagged = |id: &str, score: f32, lang: &str| {
+            let mut neighbor = make_neighbor(id, score);
+            neighbor
+                .metadata
+                .fields
+                .insert("lang".into(), serde_json::json!(lang));
+            neighbor
+        };
+
+        let reranker = MetadataBoostReranker::new().boost("lang", "en", 2.0);
+        let candidates = vec![
+            tagged("doc1", 0.9, "de"),
+            tagged("doc2", 0.5, "en"),
+            tagged("doc3", 0.3, "de"),
+        ];
+        let reranked = QueryReranker::rerank(&reranker, None, &[], candidates).unwrap();
+        let ids: Vec<_> = reranked.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["doc2", "doc1", "doc3"]);
+        assert!((reranked[0].score - 1.0).abs() < 1e-6);
+
+        // A boost still lifts a negative score
+        let candidates = vec![tagged("doc1", -0.2, "de"), tagged("doc2", -0.3, "en")];
+        let reranked = QueryReranker::rerank(&reranker, None, &[], candidates).unwrap();
+        assert_eq!(reranked[0].id, "doc2");
+        assert!((reranked[0].score + 0.15).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_query_reranker_adapter() {
+        let adapter = QueryRerankerAdapter(ScoreReranker::new(|n: &Neighbor| -n.score));
+        let candidates = vec![make_neighbor("doc1", 0.9), make_neighbor("doc2", 0.1)];
+
+        let reranked = adapter.rerank(Some("query"), &[], candidates).unwrap();
+        assert_eq!(reranked.len(), 2);
+        assert_eq!(reranked[0].id, "doc2");
+    }
 }
@@ -3,6 +3,8 @@
 //! Implements the VecStoreAdminService gRPC service and provides
 //! HTTP endpoints for namespace lifecycle management.
 
+use super::auth::{self, Access, ApiKeys, Scope};
+use super::limits::{self, Limiter};
 use crate::namespace_manager::NamespaceManager;
 use crate::server::types::pb::vec_store_admin_service_server::VecStoreAdminService;
 use crate::server::types::pb::*;
@@ -17,11 +19,43 @@ use tonic::{Request, Response, Status};
 /// gRPC Admin Service implementation
 pub struct AdminService {
     manager: Arc<RwLock<NamespaceManager>>,
+    auth: Option<Arc<ApiKeys>>,
+    limiter: Arc<Limiter>,
 }
 
 impl AdminService {
     pub fn new(manager: Arc<RwLock<NamespaceManager>>) -> Self {
-        Self { manager }
+        Self {
+            manager,
+            auth: None,
+            limiter: Arc::new(Limiter::default()),
+        }
+    }
+
+    /// Require an API key allowing each method, as the admin HTTP API does
+    ///
+    /// Serve the service through [`ApiKeys::interceptor`] with the same keys.
+    pub fn with_auth(mut self, keys: Arc<ApiKeys>) -> Self {
+        self.auth = Some(keys);
+        self
+    }
+
+    /// Enforce `limiter`'s global and per-namespace rate limits, as the
+    /// admin HTTP API does
+    pub fn with_limits(mut self, limiter: Arc<Limiter>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Check the caller's key and count the call against the limits of the
+    /// namespace it touches
+    fn admit<T>(&self, request: &Request<T>, access: Access) -> Result<(), Status> {
+        let namespace = access.namespace.clone();
+        auth::authorize(self.auth.as_deref(), request, Some(access))?;
+        let client = limits::grpc_client(request);
+        self.limiter
+            .admit(&client, namespace.as_deref(), false, "grpc")?;
+        Ok(())
     }
 }
 
@@ -31,6 +65,7 @@ impl VecStoreAdminService for AdminService {
         &self,
         request: Request<CreateNamespaceRequest>,
     ) -> Result<Response<CreateNamespaceResponse>, Status> {
+        self.admit(&request, Access::new(Scope::Admin))?;
         let req = request.into_inner();
 
         let manager = self.manager.write().await;
@@ -63,6 +98,7 @@ impl VecStoreAdminService for AdminService {
         &self,
         request: Request<ListNamespacesRequest>,
     ) -> Result<Response<ListNamespacesResponse>, Status> {
+        self.admit(&request, Access::new(Scope::Read))?;
         let req = request.into_inner();
 
         let manager = self.manager.read().await;
@@ -88,6 +124,10 @@ impl VecStoreAdminService for AdminService {
         &self,
         request: Request<GetNamespaceRequest>,
     ) -> Result<Response<GetNamespaceResponse>, Status> {
+        self.admit(
+            &request,
+            Access::namespace(Scope::Read, &request.get_ref().namespace_id),
+        )?;
         let req = request.into_inner();
 
         let manager = self.manager.read().await;
@@ -108,6 +148,7 @@ impl VecStoreAdminService for AdminService {
         &self,
         request: Request<UpdateNamespaceQuotasRequest>,
     ) -> Result<Response<UpdateNamespaceQuotasResponse>, Status> {
+        self.admit(&request, Access::new(Scope::Admin))?;
         let req = request.into_inner();
 
         let quotas = req
@@ -135,6 +176,10 @@ impl VecStoreAdminService for AdminService {
         &self,
         request: Request<UpdateNamespaceStatusRequest>,
     ) -> Result<Response<UpdateNamespaceStatusResponse>, Status> {
+        self.admit(
+            &request,
+            Access::namespace(Scope::Admin, &request.get_ref().namespace_id),
+        )?;
         let req = request.into_inner();
 
         let status = namespace_status_from_proto(req.status)
@@ -158,6 +203,10 @@ impl VecStoreAdminService for AdminService {
         &self,
         request: Request<DeleteNamespaceRequest>,
     ) -> Result<Response<DeleteNamespaceResponse>, Status> {
+        self.admit(
+            &request,
+            Access::namespace(Scope::Admin, &request.get_ref().namespace_id),
+        )?;
         let req = request.into_inner();
 
         let manager = self.manager.write().await;
@@ -178,6 +227,10 @@ impl VecStoreAdminService for AdminService {
         &self,
         request: Request<GetNamespaceStatsRequest>,
     ) -> Result<Response<GetNamespaceStatsResponse>, Status> {
+        self.admit(
+            &request,
+            Access::namespace(Scope::Read, &request.get_ref().namespace_id),
+        )?;
         let req = request.into_inner();
 
         let manager = self.manager.read().await;
@@ -207,8 +260,9 @@ impl VecStoreAdminService for AdminService {
 
     async fn get_aggregate_stats(
         &self,
-        _request: Request<GetAggregateStatsRequest>,
+        request: Request<GetAggregateStatsRequest>,
     ) -> Result<Response<GetAggregateStatsResponse>, Status> {
+        self.admit(&request, Access::new(Scope::Read))?;
         let manager = self.manager.read().await;
 
         let stats = manager.get_aggregate_stats();
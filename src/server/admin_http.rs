@@ -1,10 +1,22 @@
 //! HTTP/REST Admin API endpoints for namespace management
 
-use crate::namespace::{NamespaceQuotas, NamespaceStatus};
+use super::auth::{self, Access, ApiKeys, AuthLayer, Scope};
+use super::errors::{ErrorCode, ErrorResponse};
+use super::http::{
+    if_match_version, request_record, run_query, DeleteResponse, QueryRequest, QueryResponse,
+    QueryResult, QueryStats, UpsertRequest, UpsertResponse,
+};
+use super::http_options::HttpOptions;
+use super::limits::{self, LimitLayer, Limiter, LimitsConfig, Target};
+use super::openapi::{self, AdminApi};
+use super::timeouts::{self, QueryTimeouts};
+use crate::namespace::{NamespaceId, NamespaceQuotas, NamespaceStatus, ResourceUsage};
 use crate::namespace_manager::NamespaceManager;
+use crate::store::{FilterExpr, ProfiledResults, SlowQuery};
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{Path, Query, State},
+    http::{HeaderMap, Method},
+    middleware,
     response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Json, Router,
@@ -13,44 +25,159 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tower_http::cors::CorsLayer;
-use tower_http::trace::TraceLayer;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 /// Admin HTTP server wrapper
 #[derive(Clone)]
 pub struct AdminHttpServer {
     manager: Arc<RwLock<NamespaceManager>>,
+    auth: Option<Arc<ApiKeys>>,
+    limiter: Arc<Limiter>,
+    query_timeouts: QueryTimeouts,
+    http_options: HttpOptions,
 }
 
 impl AdminHttpServer {
     pub fn new(manager: Arc<RwLock<NamespaceManager>>) -> Self {
-        Self { manager }
+        Self {
+            manager,
+            auth: None,
+            limiter: Arc::new(Limiter::default()),
+            query_timeouts: QueryTimeouts::default(),
+            http_options: HttpOptions::default(),
+        }
+    }
+
+    /// Require an API key allowing each route, see [`route_access`]
+    pub fn with_auth(mut self, keys: Arc<ApiKeys>) -> Self {
+        self.auth = Some(keys);
+        self
+    }
+
+    /// Enforce `limiter`'s rate limits, see [`route_target`]
+    pub fn with_limits(mut self, limiter: Arc<Limiter>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Set the default and longest timeout of namespace queries
+    pub fn with_query_timeouts(mut self, timeouts: QueryTimeouts) -> Self {
+        self.query_timeouts = timeouts;
+        self
+    }
+
+    /// Set CORS, response compression and the request body limit
+    pub fn with_http_options(mut self, options: HttpOptions) -> Self {
+        self.http_options = options;
+        self
     }
 
     /// Build the admin router
     pub fn router(&self) -> Router {
-        Router::new()
+        let mut router = Router::new()
             .route("/admin/namespaces", post(create_namespace))
             .route("/admin/namespaces", get(list_namespaces))
-            .route("/admin/namespaces/:id", get(get_namespace))
-            .route("/admin/namespaces/:id/quotas", put(update_quotas))
-            .route("/admin/namespaces/:id/status", put(update_status))
-            .route("/admin/namespaces/:id", delete(delete_namespace))
-            .route("/admin/namespaces/:id/stats", get(get_namespace_stats))
+            .route("/admin/namespaces/{id}", get(get_namespace))
+            .route("/admin/namespaces/{id}/quotas", put(update_quotas))
+            .route("/admin/namespaces/{id}/status", put(update_status))
+            .route("/admin/namespaces/{id}", delete(delete_namespace))
+            .route("/admin/namespaces/{id}/stats", get(get_namespace_stats))
+            .route(
+                "/admin/namespaces/{id}/slow-queries",
+                get(get_namespace_slow_queries),
+            )
+            .route("/admin/aliases", get(list_aliases))
+            .route("/admin/aliases/{alias}", put(put_alias))
+            .route("/admin/aliases/{alias}", delete(delete_alias))
             .route("/admin/stats", get(get_aggregate_stats))
+            .route("/admin/limits", get(get_limits))
+            .route("/admin/limits", put(put_limits))
+            // Vector operations on one namespace
+            .route("/v1/ns/{namespace}/upsert", post(namespace_upsert))
+            .route("/v1/ns/{namespace}/query", post(namespace_query))
+            .route("/v1/ns/{namespace}/delete/{id}", delete(namespace_delete))
+            .route("/metrics", get(metrics_endpoint))
             .route("/health", get(health_check))
-            .route("/ready", get(ready_check))
-            .with_state(self.clone())
-            .layer(CorsLayer::permissive())
-            .layer(TraceLayer::new_for_http())
+            .route("/ready", get(ready_check));
+        router = self.http_options.limit_bodies(router, |_| true);
+        let layer = LimitLayer {
+            limiter: self.limiter.clone(),
+            policy: route_target,
+        };
+        router = router.route_layer(middleware::from_fn_with_state(layer, limits::throttle));
+        if let Some(keys) = self.auth.clone() {
+            let layer = AuthLayer {
+                keys,
+                policy: route_access,
+            };
+            router = router.route_layer(middleware::from_fn_with_state(layer, auth::require_key));
+        }
+        let router = router
+            .route_layer(middleware::from_fn(super::metrics::track_http))
+            .with_state(self.clone());
+        let router = openapi::serve(router, AdminApi::openapi(), self.http_options.swagger_ui);
+        self.http_options.apply(router)
+    }
+}
+
+/// The scope each admin route needs when API keys are on; `None` for the
+/// health endpoints
+///
+/// Namespace-scoped keys reach their own namespace's vector operations,
+/// reads, status and deletion. Creating namespaces, quotas, aliases and
+/// server-wide listings need a key without a namespace.
+pub fn route_access(method: &Method, route: &str, path: &str) -> Option<Access> {
+    let namespace = || path_namespace(path).unwrap_or_default();
+    let access = match route {
+        "/health" | "/ready" | "/metrics" => return None,
+        "/v1/ns/{namespace}/query" => Access::namespace(Scope::Read, namespace()),
+        "/v1/ns/{namespace}/upsert" | "/v1/ns/{namespace}/delete/{id}" => {
+            Access::namespace(Scope::Write, namespace())
+        }
+        "/admin/namespaces/{id}"
+        | "/admin/namespaces/{id}/stats"
+        | "/admin/namespaces/{id}/slow-queries"
+            if method == Method::GET =>
+        {
+            Access::namespace(Scope::Read, namespace())
+        }
+        "/admin/namespaces/{id}" | "/admin/namespaces/{id}/status" => {
+            Access::namespace(Scope::Admin, namespace())
+        }
+        "/admin/namespaces" | "/admin/aliases" | "/admin/stats" if method == Method::GET => {
+            Access::new(Scope::Read)
+        }
+        _ => Access::new(Scope::Admin),
+    };
+    Some(access)
+}
+
+/// What each admin route counts against: a namespace's routes use its
+/// limits when it has some, the rest the global ones
+pub fn route_target(route: &str, path: &str) -> Option<Target> {
+    if matches!(route, "/health" | "/ready" | "/metrics") {
+        return None;
     }
+    Some(Target {
+        namespace: path_namespace(path),
+        query: route == "/v1/ns/{namespace}/query",
+    })
+}
+
+/// The namespace named by a `/admin/namespaces/{id}/..` or
+/// `/v1/ns/{namespace}/..` path
+pub(crate) fn path_namespace(path: &str) -> Option<String> {
+    let rest = path
+        .strip_prefix("/admin/namespaces/")
+        .or_else(|| path.strip_prefix("/v1/ns/"))?;
+    Some(rest.split('/').next().unwrap_or_default().to_string())
 }
 
 // ============================================================================
 // Request/Response types
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateNamespaceRequest {
     pub id: String,
     pub name: String,
@@ -60,7 +187,7 @@ pub struct CreateNamespaceRequest {
     pub metadata: HashMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct NamespaceQuotasDto {
     pub max_vectors: Option<usize>,
     pub max_storage_bytes: Option<u64>,
@@ -99,17 +226,60 @@ impl From<NamespaceQuotas> for NamespaceQuotasDto {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// What a namespace uses of its quotas
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NamespaceUsageDto {
+    pub vector_count: usize,
+    pub storage_bytes: u64,
+    pub active_queries: usize,
+    /// Requests in the current one-second rate window
+    pub requests_current_window: usize,
+    pub total_requests: u64,
+}
+
+impl From<&ResourceUsage> for NamespaceUsageDto {
+    fn from(usage: &ResourceUsage) -> Self {
+        NamespaceUsageDto {
+            vector_count: usage.vector_count,
+            storage_bytes: usage.storage_bytes,
+            active_queries: usage.active_queries,
+            requests_current_window: usage.requests_current_window,
+            total_requests: usage.total_requests,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PutAliasRequest {
+    /// Namespace the alias should resolve to
+    pub target: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AliasDto {
+    pub alias: String,
+    pub target: String,
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+pub struct DeleteNamespaceParams {
+    /// Also delete aliases pointing at the namespace
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpdateStatusRequest {
     pub status: String, // "active", "suspended", "read_only", "pending_deletion"
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct NamespaceInfoDto {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
     pub quotas: NamespaceQuotasDto,
+    pub usage: NamespaceUsageDto,
     pub status: String,
     pub created_at: u64,
     pub updated_at: u64,
@@ -118,13 +288,18 @@ pub struct NamespaceInfoDto {
     pub metadata: HashMap<String, String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct NamespaceStatsDto {
     pub namespace_id: String,
     pub vector_count: usize,
     pub active_count: usize,
     pub deleted_count: usize,
     pub dimension: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub configured_dimension: Option<usize>,
+    pub distance: String,
+    pub hnsw_m: usize,
+    pub hnsw_ef_construction: usize,
     pub quota_utilization: f64,
     pub total_requests: u64,
     pub total_queries: u64,
@@ -133,7 +308,14 @@ pub struct NamespaceStatsDto {
     pub status: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SlowQueriesDto {
+    pub namespace_id: String,
+    #[schema(value_type = Vec<Object>)]
+    pub queries: Vec<SlowQuery>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AggregateStatsDto {
     pub total_namespaces: usize,
     pub active_namespaces: usize,
@@ -145,6 +327,14 @@ pub struct AggregateStatsDto {
 // Admin HTTP handlers
 // ============================================================================
 
+/// Create a namespace
+#[utoipa::path(
+    post,
+    path = "/admin/namespaces",
+    tag = "namespaces",
+    request_body = CreateNamespaceRequest,
+    responses((status = 200, body = NamespaceInfoDto)),
+)]
 async fn create_namespace(
     State(server): State<AdminHttpServer>,
     Json(req): Json<CreateNamespaceRequest>,
@@ -166,6 +356,7 @@ async fn create_namespace(
         name: namespace.name.clone(),
         description: namespace.description.clone(),
         quotas: namespace.quotas.clone().into(),
+        usage: (&namespace.usage).into(),
         status: format!("{:?}", namespace.status).to_lowercase(),
         created_at: namespace.created_at,
         updated_at: namespace.updated_at,
@@ -175,6 +366,13 @@ async fn create_namespace(
     }))
 }
 
+/// List the namespaces
+#[utoipa::path(
+    get,
+    path = "/admin/namespaces",
+    tag = "namespaces",
+    responses((status = 200, body = Vec<NamespaceInfoDto>)),
+)]
 async fn list_namespaces(
     State(server): State<AdminHttpServer>,
 ) -> Result<Json<Vec<NamespaceInfoDto>>, AppError> {
@@ -189,6 +387,7 @@ async fn list_namespaces(
             name: ns.name.clone(),
             description: ns.description.clone(),
             quotas: ns.quotas.clone().into(),
+            usage: (&ns.usage).into(),
             status: format!("{:?}", ns.status).to_lowercase(),
             created_at: ns.created_at,
             updated_at: ns.updated_at,
@@ -201,6 +400,14 @@ async fn list_namespaces(
     Ok(Json(infos))
 }
 
+/// Fetch a namespace
+#[utoipa::path(
+    get,
+    path = "/admin/namespaces/{id}",
+    tag = "namespaces",
+    params(("id" = String, Path, description = "Namespace id or alias")),
+    responses((status = 200, body = NamespaceInfoDto)),
+)]
 async fn get_namespace(
     State(server): State<AdminHttpServer>,
     Path(namespace_id): Path<String>,
@@ -208,7 +415,7 @@ async fn get_namespace(
     let manager = server.manager.read().await;
 
     let namespace = manager
-        .get_namespace(&namespace_id)
+        .get_namespace(&manager.resolve(&namespace_id))
         .map_err(|e| AppError::NotFound(e.to_string()))?;
 
     Ok(Json(NamespaceInfoDto {
@@ -216,6 +423,7 @@ async fn get_namespace(
         name: namespace.name.clone(),
         description: namespace.description.clone(),
         quotas: namespace.quotas.clone().into(),
+        usage: (&namespace.usage).into(),
         status: format!("{:?}", namespace.status).to_lowercase(),
         created_at: namespace.created_at,
         updated_at: namespace.updated_at,
@@ -225,6 +433,15 @@ async fn get_namespace(
     }))
 }
 
+/// Replace a namespace's quotas
+#[utoipa::path(
+    put,
+    path = "/admin/namespaces/{id}/quotas",
+    tag = "namespaces",
+    params(("id" = String, Path, description = "Namespace id or alias")),
+    request_body = NamespaceQuotasDto,
+    responses((status = 200, body = Object)),
+)]
 async fn update_quotas(
     State(server): State<AdminHttpServer>,
     Path(namespace_id): Path<String>,
@@ -242,6 +459,15 @@ async fn update_quotas(
     })))
 }
 
+/// Activate, suspend or make a namespace read-only
+#[utoipa::path(
+    put,
+    path = "/admin/namespaces/{id}/status",
+    tag = "namespaces",
+    params(("id" = String, Path, description = "Namespace id or alias")),
+    request_body = UpdateStatusRequest,
+    responses((status = 200, body = Object)),
+)]
 async fn update_status(
     State(server): State<AdminHttpServer>,
     Path(namespace_id): Path<String>,
@@ -272,14 +498,31 @@ async fn update_status(
     })))
 }
 
+/// Delete a namespace and its records
+#[utoipa::path(
+    delete,
+    path = "/admin/namespaces/{id}",
+    tag = "namespaces",
+    params(("id" = String, Path, description = "Namespace id or alias"), DeleteNamespaceParams),
+    responses((status = 200, body = Object)),
+)]
 async fn delete_namespace(
     State(server): State<AdminHttpServer>,
     Path(namespace_id): Path<String>,
+    Query(params): Query<DeleteNamespaceParams>,
 ) -> Result<Json<serde_json::Value>, AppError> {
     let manager = server.manager.write().await;
 
+    let aliases = manager.aliases_for(&namespace_id);
+    if !aliases.is_empty() && !params.force {
+        return Err(AppError::BadRequest(format!(
+            "Namespace '{}' is the target of aliases {:?}; pass ?force=true to delete them too",
+            namespace_id, aliases
+        )));
+    }
+
     manager
-        .delete_namespace(&namespace_id)
+        .delete_namespace_force(&namespace_id)
         .map_err(|e| AppError::Internal(e.to_string()))?;
 
     Ok(Json(serde_json::json!({
@@ -288,6 +531,14 @@ async fn delete_namespace(
     })))
 }
 
+/// A namespace's size, index settings and request counts
+#[utoipa::path(
+    get,
+    path = "/admin/namespaces/{id}/stats",
+    tag = "namespaces",
+    params(("id" = String, Path, description = "Namespace id or alias")),
+    responses((status = 200, body = NamespaceStatsDto)),
+)]
 async fn get_namespace_stats(
     State(server): State<AdminHttpServer>,
     Path(namespace_id): Path<String>,
@@ -295,7 +546,7 @@ async fn get_namespace_stats(
     let manager = server.manager.read().await;
 
     let stats = manager
-        .get_stats(&namespace_id)
+        .get_stats(&manager.resolve(&namespace_id))
         .map_err(|e| AppError::NotFound(e.to_string()))?;
 
     Ok(Json(NamespaceStatsDto {
@@ -304,6 +555,10 @@ async fn get_namespace_stats(
         active_count: stats.active_count,
         deleted_count: stats.deleted_count,
         dimension: stats.dimension,
+        configured_dimension: stats.configured_dimension,
+        distance: stats.distance.name().to_string(),
+        hnsw_m: stats.hnsw.m,
+        hnsw_ef_construction: stats.hnsw.ef_construction,
         quota_utilization: stats.quota_utilization,
         total_requests: stats.total_requests,
         total_queries: stats.total_queries,
@@ -313,6 +568,114 @@ async fn get_namespace_stats(
     }))
 }
 
+/// A namespace's most recent slow queries
+#[utoipa::path(
+    get,
+    path = "/admin/namespaces/{id}/slow-queries",
+    tag = "namespaces",
+    params(("id" = String, Path, description = "Namespace id or alias")),
+    responses((status = 200, body = SlowQueriesDto)),
+)]
+async fn get_namespace_slow_queries(
+    State(server): State<AdminHttpServer>,
+    Path(namespace_id): Path<String>,
+) -> Result<Json<SlowQueriesDto>, AppError> {
+    let manager = server.manager.read().await;
+    let namespace_id = manager.resolve(&namespace_id);
+
+    let queries = manager
+        .slow_queries(&namespace_id)
+        .map_err(|e| AppError::NotFound(e.to_string()))?;
+
+    Ok(Json(SlowQueriesDto {
+        namespace_id,
+        queries,
+    }))
+}
+
+/// List the aliases
+#[utoipa::path(
+    get,
+    path = "/admin/aliases",
+    tag = "aliases",
+    responses((status = 200, body = Vec<AliasDto>)),
+)]
+async fn list_aliases(State(server): State<AdminHttpServer>) -> Json<Vec<AliasDto>> {
+    let manager = server.manager.read().await;
+
+    Json(
+        manager
+            .list_aliases()
+            .into_iter()
+            .map(|(alias, target)| AliasDto { alias, target })
+            .collect(),
+    )
+}
+
+/// Create an alias, or atomically repoint an existing one
+#[utoipa::path(
+    put,
+    path = "/admin/aliases/{alias}",
+    tag = "aliases",
+    params(("alias" = String, Path, description = "Alias name")),
+    request_body = PutAliasRequest,
+    responses((status = 200, body = AliasDto)),
+)]
+async fn put_alias(
+    State(server): State<AdminHttpServer>,
+    Path(alias): Path<String>,
+    Json(req): Json<PutAliasRequest>,
+) -> Result<Json<AliasDto>, AppError> {
+    let manager = server.manager.read().await;
+
+    let result = if manager
+        .list_aliases()
+        .iter()
+        .any(|(name, _)| *name == alias)
+    {
+        manager.update_alias(&alias, &req.target)
+    } else {
+        manager.create_alias(&alias, &req.target)
+    };
+    result.map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+    Ok(Json(AliasDto {
+        alias,
+        target: req.target,
+    }))
+}
+
+/// Remove an alias
+#[utoipa::path(
+    delete,
+    path = "/admin/aliases/{alias}",
+    tag = "aliases",
+    params(("alias" = String, Path, description = "Alias name")),
+    responses((status = 200, body = Object)),
+)]
+async fn delete_alias(
+    State(server): State<AdminHttpServer>,
+    Path(alias): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let manager = server.manager.read().await;
+
+    manager
+        .delete_alias(&alias)
+        .map_err(|e| AppError::NotFound(e.to_string()))?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": format!("Alias '{}' deleted", alias)
+    })))
+}
+
+/// Totals across all namespaces
+#[utoipa::path(
+    get,
+    path = "/admin/stats",
+    tag = "namespaces",
+    responses((status = 200, body = AggregateStatsDto)),
+)]
 async fn get_aggregate_stats(
     State(server): State<AdminHttpServer>,
 ) -> Result<Json<AggregateStatsDto>, AppError> {
@@ -328,6 +691,147 @@ async fn get_aggregate_stats(
     }))
 }
 
+// ============================================================================
+// Namespace vector operations
+// ============================================================================
+
+/// The namespace a path names, following aliases; 404 if it doesn't exist
+fn existing_namespace(
+    manager: &NamespaceManager,
+    namespace: &str,
+) -> Result<NamespaceId, AppError> {
+    let namespace_id = manager.resolve(namespace);
+    manager
+        .get_namespace(&namespace_id)
+        .map_err(|e| AppError::NotFound(e.to_string()))?;
+    Ok(namespace_id)
+}
+
+/// A failed vector operation, see [`ErrorResponse::from_namespace_error`]
+fn operation_error(error: anyhow::Error) -> AppError {
+    AppError::Operation(ErrorResponse::from_namespace_error(&error))
+}
+
+/// Insert or replace a record in a namespace
+#[utoipa::path(
+    post,
+    path = "/v1/ns/{namespace}/upsert",
+    tag = "vectors",
+    params(("namespace" = String, Path, description = "Namespace id or alias"), ("If-Match" = Option<String>, Header, description = "Only write if the record is at this version (0: doesn't exist)")),
+    request_body = UpsertRequest,
+    responses((status = 200, body = UpsertResponse), (status = 412, description = "The record is at another version", body = ErrorResponse)),
+)]
+async fn namespace_upsert(
+    State(server): State<AdminHttpServer>,
+    Path(namespace): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<UpsertRequest>,
+) -> Result<Json<UpsertResponse>, AppError> {
+    let start = std::time::Instant::now();
+    let expected_version = if_match_version(&headers)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?
+        .or(req.expected_version);
+
+    let manager = server.manager.read().await;
+    let namespace_id = existing_namespace(&manager, &namespace)?;
+    let version = manager
+        .upsert_record(&namespace_id, request_record(req), expected_version)
+        .map_err(operation_error)?;
+    super::metrics::record_upsert("http", false, start.elapsed().as_secs_f64());
+
+    Ok(Json(UpsertResponse {
+        success: true,
+        error: None,
+        version: Some(version),
+    }))
+}
+
+/// Find the records of a namespace nearest a vector
+#[utoipa::path(
+    post,
+    path = "/v1/ns/{namespace}/query",
+    tag = "vectors",
+    params(("namespace" = String, Path, description = "Namespace id or alias")),
+    request_body = QueryRequest,
+    responses((status = 200, body = QueryResponse)),
+)]
+async fn namespace_query(
+    State(server): State<AdminHttpServer>,
+    Path(namespace): Path<String>,
+    Json(req): Json<QueryRequest>,
+) -> Result<Json<QueryResponse>, AppError> {
+    let start = std::time::Instant::now();
+    let filter = req
+        .filter
+        .as_deref()
+        .map(FilterExpr::parse)
+        .transpose()
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let query = crate::store::Query {
+        vector: req.vector,
+        k: req.limit as usize,
+        filter,
+        exact: req.exact,
+        profile: req.profile,
+        options: req.options.clone(),
+        ..Default::default()
+    };
+
+    let namespace_id = existing_namespace(&*server.manager.read().await, &namespace)?;
+    let timeout = server.query_timeouts.resolve(req.timeout_ms);
+    let rerank = req.rerank;
+    let ProfiledResults { neighbors, profile } =
+        timeouts::run(server.manager.clone(), timeout, move |manager, cancel| {
+            manager.query_with(&namespace_id, query.k, |store| {
+                run_query(store, query.with_cancel(cancel), rerank)
+            })
+        })
+        .await
+        .map_err(operation_error)?;
+
+    let duration = start.elapsed().as_secs_f64();
+    super::metrics::record_query("http", "vector", neighbors.len(), duration);
+
+    Ok(Json(QueryResponse {
+        results: neighbors
+            .iter()
+            .map(|n| QueryResult::from_neighbor(n, &req.options))
+            .collect(),
+        stats: Some(QueryStats {
+            total_candidates: neighbors.len() as i32,
+            filtered_count: 0,
+            duration_ms: duration * 1000.0,
+        }),
+        profile,
+    }))
+}
+
+/// Delete a record from a namespace
+#[utoipa::path(
+    delete,
+    path = "/v1/ns/{namespace}/delete/{id}",
+    tag = "vectors",
+    params(("namespace" = String, Path, description = "Namespace id or alias"), ("id" = String, Path, description = "Record id")),
+    responses((status = 200, body = DeleteResponse)),
+)]
+async fn namespace_delete(
+    State(server): State<AdminHttpServer>,
+    Path((namespace, id)): Path<(String, String)>,
+) -> Result<Json<DeleteResponse>, AppError> {
+    let start = std::time::Instant::now();
+    let manager = server.manager.read().await;
+    let namespace_id = existing_namespace(&manager, &namespace)?;
+    manager
+        .remove(&namespace_id, &id)
+        .map_err(operation_error)?;
+    super::metrics::record_delete("http", "hard", start.elapsed().as_secs_f64());
+
+    Ok(Json(DeleteResponse {
+        found: true,
+        deleted: true,
+    }))
+}
+
 // ============================================================================
 // Error handling
 // ============================================================================
@@ -337,28 +841,87 @@ enum AppError {
     NotFound(String),
     BadRequest(String),
     Internal(String),
+    /// A failed operation, already classified
+    Operation(ErrorResponse),
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, message) = match self {
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+        let error = match self {
+            AppError::NotFound(msg) => ErrorResponse::new(ErrorCode::NotFound, msg),
+            AppError::BadRequest(msg) => ErrorResponse::new(ErrorCode::InvalidArgument, msg),
+            AppError::Internal(msg) => ErrorResponse::new(ErrorCode::Internal, msg),
+            AppError::Operation(error) => error,
         };
+        error.into_response()
+    }
+}
 
-        let body = Json(serde_json::json!({
-            "error": message
-        }));
+/// The rate limits in force
+#[utoipa::path(
+    get,
+    path = "/admin/limits",
+    tag = "limits",
+    responses((status = 200, body = LimitsConfig)),
+)]
+async fn get_limits(State(server): State<AdminHttpServer>) -> Json<LimitsConfig> {
+    Json(server.limiter.config())
+}
 
-        (status, body).into_response()
-    }
+/// Replace the global and per-namespace rate limits
+#[utoipa::path(
+    put,
+    path = "/admin/limits",
+    tag = "limits",
+    request_body = LimitsConfig,
+    responses((status = 200, body = LimitsConfig)),
+)]
+async fn put_limits(
+    State(server): State<AdminHttpServer>,
+    Json(config): Json<LimitsConfig>,
+) -> Result<Json<LimitsConfig>, AppError> {
+    server
+        .limiter
+        .set_config(config)
+        .map_err(|e| AppError::BadRequest(format!("{:#}", e)))?;
+    Ok(Json(server.limiter.config()))
+}
+
+/// Prometheus metrics endpoint
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses((status = 200, body = String, content_type = "text/plain", description = "Prometheus text format")),
+)]
+async fn metrics_endpoint(State(server): State<AdminHttpServer>) -> Result<String, AppError> {
+    let manager = server.manager.read().await;
+    let counts: Vec<_> = manager
+        .list_namespaces()
+        .into_iter()
+        .filter_map(|namespace| {
+            let stats = manager.get_stats(&namespace.id).ok()?;
+            Some((namespace.id, stats.vector_count))
+        })
+        .collect();
+    drop(manager);
+    super::metrics::update_namespace_records(counts.iter().map(|(id, n)| (id.as_str(), *n)));
+
+    super::metrics::encode_metrics()
+        .map_err(|e| AppError::Internal(format!("Failed to encode metrics: {}", e)))
 }
 
 // ============================================================================
 // Health checks
 // ============================================================================
 
+/// Whether the server is up
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, body = Object)),
+)]
 async fn health_check(State(server): State<AdminHttpServer>) -> Json<serde_json::Value> {
     let manager = server.manager.read().await;
     let stats = manager.get_aggregate_stats();
@@ -371,6 +934,13 @@ async fn health_check(State(server): State<AdminHttpServer>) -> Json<serde_json:
     }))
 }
 
+/// Whether the namespaces can serve requests
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses((status = 200, body = Object)),
+)]
 async fn ready_check(State(server): State<AdminHttpServer>) -> Json<serde_json::Value> {
     let manager = server.manager.read().await;
     let stats = manager.get_aggregate_stats();
@@ -0,0 +1,515 @@
+//! API key authentication for the HTTP and gRPC servers
+//!
+//! Keys are configured as SHA-256 hashes, never in the clear, in a JSON file
+//! (`vecstore-server --api-keys`) or the `VECSTORE_API_KEYS` variable:
+//!
+//! ```json
+//! {"keys": [
+//!     {"name": "ingest", "hash": "sha256:9f86d0…", "scopes": ["write"]},
+//!     {"name": "acme", "hash": "sha256:60303a…", "scopes": ["read"], "namespace": "acme"}
+//! ]}
+//! ```
+//!
+//! Clients send a key as `Authorization: Bearer <key>` or `x-api-key: <key>`.
+//! Each route needs a [`Scope`]; `admin` covers `write`, which covers `read`.
+//! A key with a `namespace` only reaches that namespace's routes of the
+//! multi-tenant admin API, and nothing in single-tenant mode.
+//!
+//! Over HTTP, a route layer checks every request. Over gRPC,
+//! [`ApiKeys::interceptor`] authenticates the key and each method checks the
+//! scope it needs with [`authorize`].
+
+use super::errors::{ErrorCode, ErrorResponse};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Environment variable holding the keys file's JSON when no file is given
+pub const API_KEYS_ENV: &str = "VECSTORE_API_KEYS";
+
+/// What a key may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Queries, reads and stats
+    Read,
+    /// Upserts, imports and deletes
+    Write,
+    /// Compaction, snapshots and namespace management
+    Admin,
+}
+
+/// An entry of the keys file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyEntry {
+    /// Shown in logs instead of the key
+    pub name: String,
+    /// `sha256:` and the key's hex digest, as `vecstore-server --hash-api-key` prints it
+    pub hash: String,
+    pub scopes: Vec<Scope>,
+    /// Namespace the key is limited to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct KeysFile {
+    keys: Vec<ApiKeyEntry>,
+}
+
+/// The key behind an authenticated request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub namespace: Option<String>,
+}
+
+impl Principal {
+    /// Whether one of the key's scopes covers `scope`
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes.iter().any(|granted| *granted >= scope)
+    }
+}
+
+/// What a route needs from the caller's key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Access {
+    pub scope: Scope,
+    /// The namespace the route touches; `None` for store- or server-wide routes
+    pub namespace: Option<String>,
+}
+
+impl Access {
+    pub fn new(scope: Scope) -> Self {
+        Self {
+            scope,
+            namespace: None,
+        }
+    }
+
+    pub fn namespace(scope: Scope, namespace: impl Into<String>) -> Self {
+        Self {
+            scope,
+            namespace: Some(namespace.into()),
+        }
+    }
+}
+
+/// Why a request was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthErrorCode {
+    /// No `Authorization: Bearer` or `x-api-key` header (401)
+    MissingKey,
+    /// The key isn't configured (401)
+    InvalidKey,
+    /// The key's scopes don't cover the route (403)
+    InsufficientScope,
+    /// The key is limited to another namespace (403)
+    NamespaceForbidden,
+}
+
+/// A refused request, sent as an [`ErrorResponse`] with the key's code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthError {
+    pub error: String,
+    pub code: AuthErrorCode,
+}
+
+impl AuthError {
+    fn new(code: AuthErrorCode, error: impl Into<String>) -> Self {
+        Self {
+            error: error.into(),
+            code,
+        }
+    }
+
+    /// Whether the caller has to present a (different) key, rather than lacking rights
+    pub fn is_unauthenticated(&self) -> bool {
+        matches!(
+            self.code,
+            AuthErrorCode::MissingKey | AuthErrorCode::InvalidKey
+        )
+    }
+}
+
+impl From<AuthErrorCode> for ErrorCode {
+    fn from(code: AuthErrorCode) -> Self {
+        match code {
+            AuthErrorCode::MissingKey => ErrorCode::MissingKey,
+            AuthErrorCode::InvalidKey => ErrorCode::InvalidKey,
+            AuthErrorCode::InsufficientScope => ErrorCode::InsufficientScope,
+            AuthErrorCode::NamespaceForbidden => ErrorCode::NamespaceForbidden,
+        }
+    }
+}
+
+impl From<AuthError> for ErrorResponse {
+    fn from(e: AuthError) -> Self {
+        ErrorResponse::new(e.code.into(), e.error)
+    }
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        let unauthenticated = self.is_unauthenticated();
+        let mut response = ErrorResponse::from(self).into_response();
+        if unauthenticated {
+            response.headers_mut().insert(
+                header::WWW_AUTHENTICATE,
+                header::HeaderValue::from_static("Bearer"),
+            );
+        }
+        response
+    }
+}
+
+impl From<AuthError> for tonic::Status {
+    fn from(e: AuthError) -> Self {
+        ErrorResponse::from(e).into()
+    }
+}
+
+/// The configured keys, by hash
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeys {
+    keys: HashMap<[u8; 32], Principal>,
+    public_health: bool,
+}
+
+impl ApiKeys {
+    /// Build from keys file entries, rejecting malformed hashes
+    pub fn from_entries(entries: Vec<ApiKeyEntry>) -> Result<Self> {
+        let mut keys = HashMap::with_capacity(entries.len());
+        for entry in entries {
+            let digest = parse_hash(&entry.hash)
+                .with_context(|| format!("Invalid hash for API key '{}'", entry.name))?;
+            if entry.scopes.is_empty() {
+                return Err(anyhow::anyhow!("API key '{}' has no scopes", entry.name));
+            }
+            let principal = Principal {
+                name: entry.name,
+                scopes: entry.scopes,
+                namespace: entry.namespace,
+            };
+            if let Some(other) = keys.insert(digest, principal) {
+                return Err(anyhow::anyhow!(
+                    "API key '{}' has the same hash as another key",
+                    other.name
+                ));
+            }
+        }
+        Ok(Self {
+            keys,
+            public_health: false,
+        })
+    }
+
+    /// Parse a keys file's JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: KeysFile = serde_json::from_str(json).context("Invalid API keys JSON")?;
+        Self::from_entries(file.keys)
+    }
+
+    /// Read the keys from `path`, or else from `VECSTORE_API_KEYS`
+    ///
+    /// Returns `None` when neither is set, leaving the server open.
+    pub fn load(path: Option<&Path>) -> Result<Option<Self>> {
+        let json = match path {
+            Some(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read API keys from {}", path.display()))?,
+            None => match std::env::var(API_KEYS_ENV) {
+                Ok(json) => json,
+                Err(_) => return Ok(None),
+            },
+        };
+        Self::from_json(&json).map(Some)
+    }
+
+    /// Leave `/health`, `/ready`, `/metrics` and the `HealthCheck` RPC open
+    pub fn with_public_health(mut self, public: bool) -> Self {
+        self.public_health = public;
+        self
+    }
+
+    /// Number of configured keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether no keys are configured
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The keys file form of `key`'s hash
+    pub fn hash_key(key: &str) -> String {
+        let digest = Sha256::digest(key.as_bytes());
+        let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        format!("sha256:{}", hex)
+    }
+
+    /// The key's principal, if it is configured
+    pub fn authenticate(&self, key: &str) -> Option<&Principal> {
+        let digest: [u8; 32] = Sha256::digest(key.as_bytes()).into();
+        self.keys.get(&digest)
+    }
+
+    /// Authenticate the key in `headers`
+    pub fn principal(&self, headers: &HeaderMap) -> Result<&Principal, AuthError> {
+        let key = request_key(headers).ok_or_else(|| {
+            AuthError::new(
+                AuthErrorCode::MissingKey,
+                "Missing API key: send `Authorization: Bearer <key>` or `x-api-key: <key>`",
+            )
+        })?;
+        self.authenticate(key)
+            .ok_or_else(|| AuthError::new(AuthErrorCode::InvalidKey, "Invalid API key"))
+    }
+
+    /// A tonic interceptor attaching the [`Principal`] of the request's key
+    ///
+    /// Requests with an unknown key are refused here. Requests without one
+    /// pass through, for [`authorize`] to refuse unless the method is a
+    /// public health check.
+    pub fn interceptor(
+        self: Arc<Self>,
+    ) -> impl FnMut(tonic::Request<()>) -> Result<tonic::Request<()>, tonic::Status> + Clone {
+        move |mut request: tonic::Request<()>| {
+            let headers = request.metadata().clone().into_headers();
+            if request_key(&headers).is_some() {
+                let principal = self.principal(&headers)?.clone();
+                request.extensions_mut().insert(principal);
+            }
+            Ok(request)
+        }
+    }
+}
+
+/// Check that the key [`ApiKeys::interceptor`] attached to `request` allows
+/// `access`; `None` marks the health check
+///
+/// Passes everything when `keys` is `None`, i.e. auth is off.
+pub fn authorize<T>(
+    keys: Option<&ApiKeys>,
+    request: &tonic::Request<T>,
+    access: Option<Access>,
+) -> Result<(), tonic::Status> {
+    let Some(keys) = keys else { return Ok(()) };
+    let principal = request.extensions().get::<Principal>();
+    check(keys, principal, access).map_err(Into::into)
+}
+
+fn check(
+    keys: &ApiKeys,
+    principal: Option<&Principal>,
+    access: Option<Access>,
+) -> Result<(), AuthError> {
+    let access = match access {
+        Some(access) => access,
+        None if keys.public_health => return Ok(()),
+        None => Access::new(Scope::Read),
+    };
+    let principal = principal.ok_or_else(|| {
+        AuthError::new(
+            AuthErrorCode::MissingKey,
+            "Missing API key: send `Authorization: Bearer <key>` or `x-api-key: <key>`",
+        )
+    })?;
+
+    if let Some(own) = &principal.namespace {
+        if access.namespace.as_deref() != Some(own.as_str()) {
+            return Err(AuthError::new(
+                AuthErrorCode::NamespaceForbidden,
+                format!(
+                    "API key '{}' is limited to namespace '{}'",
+                    principal.name, own
+                ),
+            ));
+        }
+    }
+    if !principal.allows(access.scope) {
+        return Err(AuthError::new(
+            AuthErrorCode::InsufficientScope,
+            format!(
+                "API key '{}' lacks the '{}' scope",
+                principal.name,
+                scope_name(access.scope)
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn scope_name(scope: Scope) -> &'static str {
+    match scope {
+        Scope::Read => "read",
+        Scope::Write => "write",
+        Scope::Admin => "admin",
+    }
+}
+
+/// The key from `Authorization: Bearer` or `x-api-key`
+fn request_key(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    bearer
+        .or_else(|| headers.get("x-api-key").and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+fn parse_hash(hash: &str) -> Result<[u8; 32]> {
+    let hex = hash
+        .strip_prefix("sha256:")
+        .ok_or_else(|| anyhow::anyhow!("expected `sha256:<hex digest>`"))?;
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(anyhow::anyhow!("expected 64 hex digits"));
+    }
+    let mut digest = [0u8; 32];
+    for (byte, pair) in digest.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair)?;
+        *byte = u8::from_str_radix(pair, 16).context("expected 64 hex digits")?;
+    }
+    Ok(digest)
+}
+
+/// The [`Access`] a route needs, from its method, matched route and path;
+/// `None` marks the health endpoints
+pub(crate) type RoutePolicy = fn(&Method, &str, &str) -> Option<Access>;
+
+/// State of the [`require_key`] route layer
+#[derive(Clone)]
+pub(crate) struct AuthLayer {
+    pub keys: Arc<ApiKeys>,
+    pub policy: RoutePolicy,
+}
+
+/// The route `request` matched, e.g. `/v1/get/{id}`, else its path
+pub(crate) fn matched_route(request: &Request) -> String {
+    request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string())
+}
+
+/// Route layer refusing requests whose key doesn't allow the route
+pub(crate) async fn require_key(
+    State(layer): State<AuthLayer>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let route = matched_route(&request);
+    let access = (layer.policy)(request.method(), &route, request.uri().path());
+
+    let principal = match layer.keys.principal(request.headers()) {
+        Ok(principal) => Some(principal.clone()),
+        Err(e) if e.code == AuthErrorCode::MissingKey => None,
+        Err(e) => return e.into_response(),
+    };
+    if let Err(e) = check(&layer.keys, principal.as_ref(), access) {
+        return e.into_response();
+    }
+    if let Some(principal) = principal {
+        request.extensions_mut().insert(principal);
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> ApiKeys {
+        ApiKeys::from_entries(vec![
+            ApiKeyEntry {
+                name: "reader".into(),
+                hash: ApiKeys::hash_key("r-key"),
+                scopes: vec![Scope::Read],
+                namespace: None,
+            },
+            ApiKeyEntry {
+                name: "acme".into(),
+                hash: ApiKeys::hash_key("acme-key"),
+                scopes: vec![Scope::Write],
+                namespace: Some("acme".into()),
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_hash_round_trips_and_rejects_malformed() {
+        let hash = ApiKeys::hash_key("test");
+        assert_eq!(
+            hash,
+            "sha256:9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+        );
+        assert!(parse_hash(&hash).is_ok());
+        assert!(parse_hash("md5:abc").is_err());
+        assert!(parse_hash("sha256:zz").is_err());
+    }
+
+    #[test]
+    fn test_scopes_and_namespaces() {
+        let keys = keys();
+        let reader = keys.authenticate("r-key").unwrap();
+        let acme = keys.authenticate("acme-key").unwrap();
+        assert!(keys.authenticate("nope").is_none());
+
+        assert!(check(&keys, Some(reader), Some(Access::new(Scope::Read))).is_ok());
+        let denied = check(&keys, Some(reader), Some(Access::new(Scope::Write))).unwrap_err();
+        assert_eq!(denied.code, AuthErrorCode::InsufficientScope);
+
+        // Write covers read, but only in the key's own namespace
+        assert!(check(
+            &keys,
+            Some(acme),
+            Some(Access::namespace(Scope::Read, "acme"))
+        )
+        .is_ok());
+        let denied = check(
+            &keys,
+            Some(acme),
+            Some(Access::namespace(Scope::Read, "other")),
+        );
+        assert_eq!(denied.unwrap_err().code, AuthErrorCode::NamespaceForbidden);
+        let denied = check(&keys, Some(acme), Some(Access::new(Scope::Read)));
+        assert_eq!(denied.unwrap_err().code, AuthErrorCode::NamespaceForbidden);
+
+        let missing = check(&keys, None, None).unwrap_err();
+        assert_eq!(missing.code, AuthErrorCode::MissingKey);
+        assert!(check(&keys.with_public_health(true), None, None).is_ok());
+    }
+
+    #[test]
+    fn test_request_key_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(request_key(&headers), None);
+        headers.insert("x-api-key", "abc".parse().unwrap());
+        assert_eq!(request_key(&headers), Some("abc"));
+        headers.insert(header::AUTHORIZATION, "Bearer xyz".parse().unwrap());
+        assert_eq!(request_key(&headers), Some("xyz"));
+    }
+}
@@ -0,0 +1,260 @@
+//! Batch limits and bulk deletes, shared by `POST /v1/upsert/batch`,
+//! `POST /v1/delete` and their RPCs
+//!
+//! A bulk delete names its records by id or by filter. A filter matching
+//! more records than [`BulkLimits::max_delete_matches`] is refused with
+//! `too_many_matches` unless the request sets `force`, so a mistyped filter
+//! can't empty the store. Batches listing more than
+//! [`BulkLimits::max_batch_size`] records or ids get `batch_too_large`.
+
+use super::errors::{ErrorCode, ErrorResponse};
+use crate::store::{BatchItemError, FilterExpr, VecStore};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// Most records a batch upsert, or ids a delete, may list by default
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 10_000;
+
+/// Most records a filter delete may remove without `force` by default
+pub const DEFAULT_MAX_DELETE_MATCHES: usize = 10_000;
+
+/// Limits on batch writes and bulk deletes
+#[derive(Debug, Clone, Copy)]
+pub struct BulkLimits {
+    /// Most records a batch upsert, or ids a delete, may list
+    pub max_batch_size: usize,
+    /// Most records a filter delete may remove without `force`
+    pub max_delete_matches: usize,
+}
+
+impl Default for BulkLimits {
+    fn default() -> Self {
+        Self {
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            max_delete_matches: DEFAULT_MAX_DELETE_MATCHES,
+        }
+    }
+}
+
+impl BulkLimits {
+    /// Refuse a batch of `size` records or ids over the limit
+    pub fn check_batch(&self, size: usize) -> Result<(), ErrorResponse> {
+        if size <= self.max_batch_size {
+            return Ok(());
+        }
+        Err(ErrorResponse::new(
+            ErrorCode::BatchTooLarge,
+            format!(
+                "batch of {} exceeds the limit of {} per request",
+                size, self.max_batch_size
+            ),
+        )
+        .with_details(serde_json::json!({
+            "size": size,
+            "max_batch_size": self.max_batch_size,
+        })))
+    }
+}
+
+/// Outcome for one record of a batch upsert
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct BatchItemResult {
+    pub id: String,
+    /// The record's version, when it was written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    /// Why the record was not written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// One result per record of a batch, in batch order, from the `ids` of the
+/// batch, the `versions` the written ones reached and the `failed` ones
+pub fn item_results(
+    ids: Vec<String>,
+    versions: Vec<Option<u64>>,
+    failed: &[BatchItemError],
+) -> Vec<BatchItemResult> {
+    let reasons: HashMap<usize, &str> = failed
+        .iter()
+        .map(|e| (e.index, e.reason.as_str()))
+        .collect();
+    ids.into_iter()
+        .zip(versions)
+        .enumerate()
+        .map(|(index, (id, version))| BatchItemResult {
+            id,
+            version,
+            error: reasons.get(&index).map(|reason| reason.to_string()),
+        })
+        .collect()
+}
+
+/// The records a bulk delete removes
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeleteSelector {
+    /// These ids; ids that aren't stored are skipped
+    Ids(Vec<String>),
+    /// Every active record matching the filter
+    Filter(FilterExpr),
+}
+
+impl DeleteSelector {
+    /// The selector of a request with `ids` and `filter`, exactly one of
+    /// which must be given
+    pub fn new(ids: Vec<String>, filter: Option<FilterExpr>) -> Result<Self, ErrorResponse> {
+        match (ids.is_empty(), filter) {
+            (false, None) => Ok(DeleteSelector::Ids(ids)),
+            (true, Some(filter)) => Ok(DeleteSelector::Filter(filter)),
+            (true, None) => Err(ErrorResponse::new(
+                ErrorCode::InvalidArgument,
+                "set either ids or filter",
+            )),
+            (false, Some(_)) => Err(ErrorResponse::new(
+                ErrorCode::InvalidArgument,
+                "set ids or filter, not both",
+            )),
+        }
+    }
+}
+
+/// Read a filter given either as an expression string, like
+/// `"tag = 'draft'"`, or as a JSON [`FilterExpr`]
+pub fn parse_filter(value: serde_json::Value) -> Result<FilterExpr, ErrorResponse> {
+    match value {
+        serde_json::Value::String(text) => parse_filter_text(&text),
+        value => serde_json::from_value(value).map_err(invalid_filter),
+    }
+}
+
+/// Read a filter from text holding an expression or a JSON [`FilterExpr`]
+/// object
+pub fn parse_filter_text(text: &str) -> Result<FilterExpr, ErrorResponse> {
+    if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).map_err(invalid_filter)
+    } else {
+        FilterExpr::parse(text).map_err(invalid_filter)
+    }
+}
+
+fn invalid_filter(error: impl std::fmt::Display) -> ErrorResponse {
+    ErrorResponse::new(
+        ErrorCode::InvalidArgument,
+        format!("invalid filter: {}", error),
+    )
+}
+
+/// Outcome of a bulk delete
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DeleteSummary {
+    /// Records the filter matched, or the listed ids that were stored
+    pub matched: usize,
+    /// Records removed
+    pub deleted: usize,
+}
+
+/// Permanently remove the records `selector` picks
+///
+/// A filter matching more than `limits.max_delete_matches` records removes
+/// nothing unless `force` is set.
+pub fn delete(
+    store: &mut VecStore,
+    selector: &DeleteSelector,
+    force: bool,
+    limits: &BulkLimits,
+) -> Result<DeleteSummary, ErrorResponse> {
+    let failed = |e: anyhow::Error| ErrorResponse::from_error(&e).context("Delete failed");
+    match selector {
+        DeleteSelector::Ids(ids) => {
+            limits.check_batch(ids.len())?;
+            let deleted = store.delete_ids(ids).map_err(failed)?;
+            Ok(DeleteSummary {
+                matched: deleted,
+                deleted,
+            })
+        }
+        DeleteSelector::Filter(filter) => {
+            let matched = store.count_where(Some(filter.clone()));
+            if matched > limits.max_delete_matches && !force {
+                return Err(ErrorResponse::new(
+                    ErrorCode::TooManyMatches,
+                    format!(
+                        "filter matches {} records, over the limit of {}; set force to delete them",
+                        matched, limits.max_delete_matches
+                    ),
+                )
+                .with_details(serde_json::json!({
+                    "matched": matched,
+                    "max_delete_matches": limits.max_delete_matches,
+                })));
+            }
+            let deleted = store.delete_by_filter(filter).map_err(failed)?;
+            Ok(DeleteSummary { matched, deleted })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Metadata;
+
+    fn store(dir: &tempfile::TempDir) -> VecStore {
+        let mut store = VecStore::open(dir.path()).unwrap();
+        for (id, tag) in [("a", "draft"), ("b", "draft"), ("c", "final")] {
+            let fields = HashMap::from([("tag".to_string(), serde_json::json!(tag))]);
+            store
+                .upsert(id.to_string(), vec![1.0, 0.0], Metadata { fields })
+                .unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_filter_delete_respects_match_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store(&dir);
+        let limits = BulkLimits {
+            max_batch_size: 10,
+            max_delete_matches: 1,
+        };
+        let drafts = DeleteSelector::Filter(parse_filter("tag = 'draft'".into()).unwrap());
+
+        let error = delete(&mut store, &drafts, false, &limits).unwrap_err();
+        assert_eq!(error.code, ErrorCode::TooManyMatches);
+        assert_eq!(error.details.unwrap()["matched"], 2);
+        assert_eq!(store.count(), 3);
+
+        let summary = delete(&mut store, &drafts, true, &limits).unwrap();
+        assert_eq!(summary.deleted, 2);
+        assert_eq!(store.count(), 1);
+    }
+
+    #[test]
+    fn test_selectors_and_filter_forms() {
+        assert!(DeleteSelector::new(Vec::new(), None).is_err());
+        let filter = parse_filter(serde_json::json!({
+            "Cmp": {"field": "tag", "op": "Eq", "value": "draft"}
+        }))
+        .unwrap();
+        assert_eq!(filter, parse_filter_text("tag = 'draft'").unwrap());
+        assert!(DeleteSelector::new(vec!["a".into()], Some(filter)).is_err());
+        assert_eq!(
+            parse_filter_text("tag = ").unwrap_err().code,
+            ErrorCode::InvalidArgument
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store(&dir);
+        let limits = BulkLimits {
+            max_batch_size: 2,
+            max_delete_matches: 1,
+        };
+        let ids = DeleteSelector::new(vec!["a".into(), "missing".into()], None).unwrap();
+        assert_eq!(delete(&mut store, &ids, false, &limits).unwrap().deleted, 1);
+        let too_many = DeleteSelector::Ids(vec!["b".into(), "c".into(), "d".into()]);
+        let error = delete(&mut store, &too_many, true, &limits).unwrap_err();
+        assert_eq!(error.code, ErrorCode::BatchTooLarge);
+    }
+}
@@ -0,0 +1,244 @@
+//! Periodic and on-demand saves for `vecstore-server`
+//!
+//! A checkpoint copies the store under the read lock with
+//! `VecStore::snapshot_for_save` and writes the copy with no lock held, so
+//! queries keep running throughout and writes wait only for the copy.
+//! Interval checkpoints are skipped while the store has no unsaved changes.
+
+use crate::store::VecStore;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+use super::shutdown::Shutdown;
+
+/// What started a checkpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SaveTrigger {
+    /// The `--auto-save-every` timer
+    Interval,
+    /// `POST /admin/save`
+    Manual,
+    /// The server shutting down
+    Shutdown,
+}
+
+/// Outcome of one checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SaveReport {
+    pub trigger: SaveTrigger,
+    /// Whether the store was written; `false` when it had no unsaved
+    /// changes or the save failed
+    pub saved: bool,
+    /// Operation sequence number the save covers; 0 when nothing was saved
+    pub seq: u64,
+    /// Records written, deleted ones included
+    pub records: usize,
+    /// Time spent holding the store's lock
+    pub locked_ms: u64,
+    /// Time the whole checkpoint took
+    pub duration_ms: u64,
+    /// When the checkpoint finished, as a Unix timestamp
+    pub finished_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Saves a shared store without blocking its readers
+pub struct Checkpointer {
+    store: Arc<RwLock<VecStore>>,
+    /// Held for a whole checkpoint, so two never write the files at once
+    running: tokio::sync::Mutex<()>,
+    last: Mutex<Option<SaveReport>>,
+}
+
+impl Checkpointer {
+    pub fn new(store: Arc<RwLock<VecStore>>) -> Self {
+        Self {
+            store,
+            running: tokio::sync::Mutex::new(()),
+            last: Mutex::new(None),
+        }
+    }
+
+    /// The last checkpoint that wrote the store or failed to
+    pub fn last_save(&self) -> Option<SaveReport> {
+        self.last.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Whether the store has changes no checkpoint has saved yet
+    pub async fn is_dirty(&self) -> bool {
+        self.store.read().await.is_dirty()
+    }
+
+    /// Save the store, or with `force` unset, only if it has unsaved changes
+    ///
+    /// Failures are reported in [`SaveReport::error`] rather than returned,
+    /// so they show up in `GET /admin/last-save`.
+    pub async fn checkpoint(&self, trigger: SaveTrigger, force: bool) -> SaveReport {
+        let _running = self.running.lock().await;
+        let started = Instant::now();
+        let mut report = SaveReport {
+            trigger,
+            saved: false,
+            seq: 0,
+            records: 0,
+            locked_ms: 0,
+            duration_ms: 0,
+            finished_at: 0,
+            error: None,
+        };
+
+        if let Err(e) = self.save(force, &mut report).await {
+            report.error = Some(format!("{:#}", e));
+        }
+        report.duration_ms = started.elapsed().as_millis() as u64;
+        report.finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+
+        if let Some(error) = &report.error {
+            warn!("Checkpoint ({:?}) failed: {}", trigger, error);
+        } else if report.saved {
+            info!(
+                "💾 Checkpoint ({:?}): {} records in {}ms, lock held {}ms",
+                trigger, report.records, report.duration_ms, report.locked_ms
+            );
+        }
+        if report.saved || report.error.is_some() {
+            *self.last.lock().unwrap_or_else(|e| e.into_inner()) = Some(report.clone());
+        }
+        report
+    }
+
+    async fn save(&self, force: bool, report: &mut SaveReport) -> Result<()> {
+        let snapshot = {
+            let store = self.store.read().await;
+            let locked = Instant::now();
+            if !force && !store.is_dirty() {
+                return Ok(());
+            }
+            let snapshot = store.snapshot_for_save()?;
+            report.locked_ms = locked.elapsed().as_millis() as u64;
+            snapshot
+        };
+        report.seq = snapshot.seq();
+        report.records = snapshot.len();
+
+        let snapshot =
+            tokio::task::spawn_blocking(move || snapshot.write().map(|()| snapshot)).await??;
+
+        let store = self.store.read().await;
+        let locked = Instant::now();
+        store.finish_save(&snapshot)?;
+        report.locked_ms += locked.elapsed().as_millis() as u64;
+        report.saved = true;
+        Ok(())
+    }
+
+    /// Checkpoint every `every` until `shutdown` fires, skipping the
+    /// checkpoint while the store has no unsaved changes
+    pub fn spawn(self: Arc<Self>, every: Duration, shutdown: Shutdown) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(every);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately
+            ticks.tick().await;
+            let stopped = shutdown.signalled();
+            tokio::pin!(stopped);
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {
+                        self.checkpoint(SaveTrigger::Interval, false).await;
+                    }
+                    _ = &mut stopped => break,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Metadata;
+    use std::collections::HashMap;
+
+    fn metadata() -> Metadata {
+        Metadata {
+            fields: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checkpoints_skip_a_clean_store_unless_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(RwLock::new(VecStore::open(dir.path()).unwrap()));
+        let checkpointer = Checkpointer::new(store.clone());
+
+        let report = checkpointer.checkpoint(SaveTrigger::Interval, false).await;
+        assert!(!report.saved);
+        assert!(checkpointer.last_save().is_none());
+
+        store
+            .write()
+            .await
+            .upsert("a".into(), vec![1.0, 0.0], metadata())
+            .unwrap();
+        assert!(checkpointer.is_dirty().await);
+        let report = checkpointer.checkpoint(SaveTrigger::Interval, false).await;
+        assert!(report.saved, "{:?}", report.error);
+        assert_eq!(report.records, 1);
+        assert!(!checkpointer.is_dirty().await);
+
+        let report = checkpointer.checkpoint(SaveTrigger::Manual, true).await;
+        assert!(report.saved);
+        let last = checkpointer.last_save().unwrap();
+        assert_eq!(last.trigger, SaveTrigger::Manual);
+        assert_eq!(last.seq, report.seq);
+
+        drop(store);
+        drop(checkpointer);
+        let reopened = VecStore::open(dir.path()).unwrap();
+        assert!(reopened.contains("a"));
+    }
+
+    #[tokio::test]
+    async fn test_background_task_saves_until_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(RwLock::new(VecStore::open(dir.path()).unwrap()));
+        let checkpointer = Arc::new(Checkpointer::new(store.clone()));
+        let shutdown = Shutdown::new();
+        let task = checkpointer
+            .clone()
+            .spawn(Duration::from_millis(20), shutdown.clone());
+
+        store
+            .write()
+            .await
+            .upsert("a".into(), vec![1.0, 0.0], metadata())
+            .unwrap();
+        for _ in 0..100 {
+            if checkpointer.last_save().is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let last = checkpointer.last_save().expect("no checkpoint ran");
+        assert_eq!(last.trigger, SaveTrigger::Interval);
+        assert!(last.saved);
+
+        shutdown.trigger();
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
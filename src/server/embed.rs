@@ -0,0 +1,279 @@
+//! Server-side text embedding for `POST /v1/embed` and `POST /v1/query/text`
+//!
+//! A [`ServerEmbedder`] turns texts into vectors for clients that only have
+//! raw text. Two backends are built in, each behind its own feature and
+//! picked with [`EmbedderConfig`]: a local ONNX model such as MiniLM
+//! (`server-onnx`) and any OpenAI-compatible `/embeddings` endpoint
+//! (`server-openai`). Requests listing more texts than
+//! [`Embedding::max_batch`] get `batch_too_large`; the rest go to the model
+//! in one call, timed in `vecstore_embedding_duration_seconds`.
+
+use super::errors::{ErrorCode, ErrorResponse};
+use std::sync::Arc;
+
+/// Texts a single embed request may list by default
+pub const DEFAULT_MAX_EMBED_BATCH: usize = 64;
+
+/// Model the ONNX backend loads when none is configured
+pub const DEFAULT_ONNX_MODEL: &str = "all-MiniLM-L6-v2";
+
+/// Model the OpenAI-compatible backend asks for when none is configured
+pub const DEFAULT_OPENAI_MODEL: &str = "text-embedding-3-small";
+
+/// A model turning texts into vectors on behalf of clients
+///
+/// Calls block, and the server makes them off the async runtime.
+pub trait ServerEmbedder: Send + Sync {
+    /// Model name reported with embeddings
+    fn model(&self) -> &str;
+
+    /// Components of each embedding
+    fn dimension(&self) -> usize;
+
+    /// One vector per text, in order
+    fn embed_batch(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>>;
+}
+
+/// A [`TextEmbedder`](crate::embeddings::TextEmbedder) served under a model
+/// name, its dimension probed once when it is created
+#[cfg(any(feature = "server-onnx", feature = "server-openai"))]
+pub struct TextEmbedderBackend<E> {
+    embedder: E,
+    model: String,
+    dimension: usize,
+}
+
+#[cfg(any(feature = "server-onnx", feature = "server-openai"))]
+impl<E: crate::embeddings::TextEmbedder> TextEmbedderBackend<E> {
+    pub fn new(model: impl Into<String>, embedder: E) -> anyhow::Result<Self> {
+        let dimension = embedder.dimension()?;
+        Ok(Self {
+            embedder,
+            model: model.into(),
+            dimension,
+        })
+    }
+}
+
+#[cfg(any(feature = "server-onnx", feature = "server-openai"))]
+impl<E: crate::embeddings::TextEmbedder> ServerEmbedder for TextEmbedderBackend<E> {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+        self.embedder.embed_batch(texts)
+    }
+}
+
+/// Which built-in backend embeds, and with what model
+#[derive(Debug, Clone, PartialEq)]
+pub enum EmbedderConfig {
+    /// A local ONNX model: a pretrained name such as `all-MiniLM-L6-v2`,
+    /// downloaded on first use, or a directory holding `model.onnx` and
+    /// `tokenizer.json`
+    Onnx { model: String },
+    /// An OpenAI-compatible `POST {base_url}/embeddings` endpoint
+    OpenAi {
+        base_url: String,
+        model: String,
+        api_key: Option<String>,
+    },
+}
+
+impl EmbedderConfig {
+    /// Load the model or connect to the endpoint; fails when the backend's
+    /// feature isn't compiled in
+    pub fn build(&self) -> anyhow::Result<Arc<dyn ServerEmbedder>> {
+        match self {
+            #[cfg(feature = "server-onnx")]
+            EmbedderConfig::Onnx { model } => {
+                use crate::embeddings::{AutoEmbedder, LocalEmbedder, PretrainedModel};
+                let dir = std::path::Path::new(model);
+                if dir.is_dir() {
+                    Ok(Arc::new(LocalEmbedder::from_dir(dir)?))
+                } else if matches!(
+                    PretrainedModel::from_name(model)?,
+                    PretrainedModel::AllMiniLML6V2
+                ) {
+                    let embedder = LocalEmbedder::minilm()?;
+                    Ok(Arc::new(TextEmbedderBackend::new(model.clone(), embedder)?))
+                } else {
+                    let embedder = AutoEmbedder::from_pretrained(model)?;
+                    Ok(Arc::new(TextEmbedderBackend::new(model.clone(), embedder)?))
+                }
+            }
+            #[cfg(not(feature = "server-onnx"))]
+            EmbedderConfig::Onnx { .. } => Err(anyhow::anyhow!(
+                "The onnx embedder needs vecstore built with the `server-onnx` feature"
+            )),
+            #[cfg(feature = "server-openai")]
+            EmbedderConfig::OpenAi {
+                base_url,
+                model,
+                api_key,
+            } => {
+                let embedder = crate::embeddings::OpenAICompatibleEmbedder::new(base_url, model)
+                    .with_api_key(api_key.clone());
+                Ok(Arc::new(TextEmbedderBackend::new(model.clone(), embedder)?))
+            }
+            #[cfg(not(feature = "server-openai"))]
+            EmbedderConfig::OpenAi { .. } => Err(anyhow::anyhow!(
+                "The openai embedder needs vecstore built with the `server-openai` feature"
+            )),
+        }
+    }
+}
+
+/// Fail unless `embedder` produces vectors a store of `dimension` accepts;
+/// a store without records (dimension 0) accepts any
+pub fn check_dimension(embedder: &dyn ServerEmbedder, dimension: usize) -> anyhow::Result<()> {
+    if dimension != 0 && embedder.dimension() != dimension {
+        anyhow::bail!(
+            "Embedder {} produces {}-dimensional vectors but the store holds {}-dimensional ones",
+            embedder.model(),
+            embedder.dimension(),
+            dimension
+        );
+    }
+    Ok(())
+}
+
+/// The server's embedder and its batch limit
+#[derive(Clone)]
+pub struct Embedding {
+    embedder: Arc<dyn ServerEmbedder>,
+    /// Most texts one request may list
+    pub max_batch: usize,
+}
+
+impl Embedding {
+    pub fn new(embedder: Arc<dyn ServerEmbedder>) -> Self {
+        Self {
+            embedder,
+            max_batch: DEFAULT_MAX_EMBED_BATCH,
+        }
+    }
+
+    /// Set the most texts one request may list
+    pub fn with_max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = max_batch;
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        self.embedder.model()
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.embedder.dimension()
+    }
+
+    /// Embed `texts` in one model call, off the async runtime
+    pub async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ErrorResponse> {
+        if texts.is_empty() {
+            return Err(ErrorResponse::new(
+                ErrorCode::InvalidArgument,
+                "texts must not be empty",
+            ));
+        }
+        if texts.len() > self.max_batch {
+            return Err(ErrorResponse::new(
+                ErrorCode::BatchTooLarge,
+                format!(
+                    "batch of {} texts exceeds the limit of {} per request",
+                    texts.len(),
+                    self.max_batch
+                ),
+            )
+            .with_details(serde_json::json!({
+                "size": texts.len(),
+                "max_batch_size": self.max_batch,
+            })));
+        }
+
+        let embedder = self.embedder.clone();
+        let start = std::time::Instant::now();
+        let embedded = tokio::task::spawn_blocking(move || {
+            let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+            embedder.embed_batch(&texts)
+        })
+        .await
+        .map_err(|e| ErrorResponse::new(ErrorCode::Internal, e.to_string()))?;
+        super::metrics::record_embedding(self.model(), start.elapsed().as_secs_f64());
+
+        let vectors =
+            embedded.map_err(|e| ErrorResponse::from_error(&e).context("Embedding failed"))?;
+        if let Some(wrong) = vectors.iter().find(|v| v.len() != self.dimension()) {
+            return Err(ErrorResponse::new(
+                ErrorCode::Internal,
+                format!(
+                    "Embedder {} returned a {}-dimensional vector, expected {}",
+                    self.model(),
+                    wrong.len(),
+                    self.dimension()
+                ),
+            ));
+        }
+        Ok(vectors)
+    }
+}
+
+/// The `unimplemented` answer of the embedding routes on a server without
+/// an embedder
+pub(crate) fn not_configured() -> ErrorResponse {
+    ErrorResponse::new(
+        ErrorCode::Unimplemented,
+        "No embedder configured; start the server with --embedder",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Lengths;
+
+    impl ServerEmbedder for Lengths {
+        fn model(&self) -> &str {
+            "lengths"
+        }
+
+        fn dimension(&self) -> usize {
+            2
+        }
+
+        fn embed_batch(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|t| vec![t.len() as f32, 1.0]).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batches_are_limited_and_embedded_in_order() {
+        let embedding = Embedding::new(Arc::new(Lengths)).with_max_batch(2);
+        let vectors = embedding
+            .embed(vec!["a".into(), "abc".into()])
+            .await
+            .unwrap();
+        assert_eq!(vectors, vec![vec![1.0, 1.0], vec![3.0, 1.0]]);
+
+        let error = embedding
+            .embed(vec!["a".into(), "b".into(), "c".into()])
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, ErrorCode::BatchTooLarge);
+        let error = embedding.embed(Vec::new()).await.unwrap_err();
+        assert_eq!(error.code, ErrorCode::InvalidArgument);
+    }
+
+    #[test]
+    fn test_dimension_must_match_the_store() {
+        assert!(check_dimension(&Lengths, 0).is_ok());
+        assert!(check_dimension(&Lengths, 2).is_ok());
+        assert!(check_dimension(&Lengths, 384).is_err());
+    }
+}
@@ -0,0 +1,416 @@
+//! Error responses shared by the HTTP and gRPC servers
+//!
+//! A failed REST call answers with `{"code": ..., "message": ...}`, plus
+//! `details` when the error carries more than a message. Codes are stable
+//! and clients should match on them rather than on messages. Each
+//! [`ErrorCode`] has one HTTP status and one gRPC code, so both transports
+//! report a failure the same way; gRPC statuses also carry the code in the
+//! `x-vecstore-error-code` metadata header.
+
+use crate::error::VecStoreError;
+use crate::namespace::QuotaExceeded;
+use crate::store::{BatchValidationError, FilterParseError, QueryInterrupted, VersionConflict};
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// gRPC metadata header carrying a failed call's [`ErrorCode`]
+pub const ERROR_CODE_HEADER: &str = "x-vecstore-error-code";
+
+/// Error body bytes read when rewriting a plain-text error
+const MAX_REWRITTEN_BODY: usize = 64 * 1024;
+
+/// Why a request failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Malformed request: bad JSON, header, filter or vector (400)
+    InvalidArgument,
+    /// Records of a batch failed validation, so none were written (422)
+    ValidationFailed,
+    /// No such record, namespace, snapshot or route (404)
+    NotFound,
+    /// The route exists but not for this method (405)
+    MethodNotAllowed,
+    /// A conditional write found another record version (412)
+    VersionConflict,
+    /// The target can't take the operation in its current state (400)
+    FailedPrecondition,
+    /// The request body is over the size limit (413)
+    PayloadTooLarge,
+    /// A batch lists more records than the server accepts at once (413)
+    BatchTooLarge,
+    /// A filter delete matches more records than allowed without `force` (400)
+    TooManyMatches,
+    /// No API key was presented (401)
+    MissingKey,
+    /// The API key isn't configured (401)
+    InvalidKey,
+    /// The key's scopes don't cover the route (403)
+    InsufficientScope,
+    /// The key is limited to another namespace (403)
+    NamespaceForbidden,
+    /// The client is over its rate limit (429)
+    RateLimited,
+    /// All query slots are taken (429)
+    TooManyQueries,
+    /// The request would take a namespace over one of its quotas (429)
+    QuotaExceeded,
+    /// The query ran past its timeout (504)
+    DeadlineExceeded,
+    /// Another maintenance job is running (409)
+    JobRunning,
+    /// The server is a read-only replica; `details.primary` takes writes (403)
+    ReadOnlyReplica,
+    /// The server doesn't offer the operation (501)
+    Unimplemented,
+    /// The server failed to carry out a valid request (500)
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidArgument => "invalid_argument",
+            ErrorCode::ValidationFailed => "validation_failed",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::MethodNotAllowed => "method_not_allowed",
+            ErrorCode::VersionConflict => "version_conflict",
+            ErrorCode::FailedPrecondition => "failed_precondition",
+            ErrorCode::PayloadTooLarge => "payload_too_large",
+            ErrorCode::BatchTooLarge => "batch_too_large",
+            ErrorCode::TooManyMatches => "too_many_matches",
+            ErrorCode::MissingKey => "missing_key",
+            ErrorCode::InvalidKey => "invalid_key",
+            ErrorCode::InsufficientScope => "insufficient_scope",
+            ErrorCode::NamespaceForbidden => "namespace_forbidden",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::TooManyQueries => "too_many_queries",
+            ErrorCode::QuotaExceeded => "quota_exceeded",
+            ErrorCode::DeadlineExceeded => "deadline_exceeded",
+            ErrorCode::JobRunning => "job_running",
+            ErrorCode::ReadOnlyReplica => "read_only_replica",
+            ErrorCode::Unimplemented => "unimplemented",
+            ErrorCode::Internal => "internal",
+        }
+    }
+
+    /// The HTTP status the REST API answers with
+    pub fn http_status(self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidArgument
+            | ErrorCode::FailedPrecondition
+            | ErrorCode::TooManyMatches => StatusCode::BAD_REQUEST,
+            ErrorCode::ValidationFailed => StatusCode::UNPROCESSABLE_ENTITY,
+            ErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ErrorCode::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            ErrorCode::VersionConflict => StatusCode::PRECONDITION_FAILED,
+            ErrorCode::PayloadTooLarge | ErrorCode::BatchTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ErrorCode::MissingKey | ErrorCode::InvalidKey => StatusCode::UNAUTHORIZED,
+            ErrorCode::InsufficientScope
+            | ErrorCode::NamespaceForbidden
+            | ErrorCode::ReadOnlyReplica => StatusCode::FORBIDDEN,
+            ErrorCode::RateLimited | ErrorCode::TooManyQueries | ErrorCode::QuotaExceeded => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            ErrorCode::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+            ErrorCode::JobRunning => StatusCode::CONFLICT,
+            ErrorCode::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+            ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// The status code the gRPC API answers with
+    pub fn grpc_code(self) -> tonic::Code {
+        match self {
+            ErrorCode::InvalidArgument | ErrorCode::ValidationFailed => {
+                tonic::Code::InvalidArgument
+            }
+            ErrorCode::NotFound => tonic::Code::NotFound,
+            ErrorCode::MethodNotAllowed | ErrorCode::Unimplemented => tonic::Code::Unimplemented,
+            ErrorCode::VersionConflict
+            | ErrorCode::FailedPrecondition
+            | ErrorCode::TooManyMatches
+            | ErrorCode::ReadOnlyReplica => tonic::Code::FailedPrecondition,
+            ErrorCode::PayloadTooLarge
+            | ErrorCode::BatchTooLarge
+            | ErrorCode::RateLimited
+            | ErrorCode::TooManyQueries
+            | ErrorCode::QuotaExceeded => tonic::Code::ResourceExhausted,
+            ErrorCode::MissingKey | ErrorCode::InvalidKey => tonic::Code::Unauthenticated,
+            ErrorCode::InsufficientScope | ErrorCode::NamespaceForbidden => {
+                tonic::Code::PermissionDenied
+            }
+            ErrorCode::DeadlineExceeded => tonic::Code::DeadlineExceeded,
+            ErrorCode::JobRunning => tonic::Code::Aborted,
+            ErrorCode::Internal => tonic::Code::Internal,
+        }
+    }
+
+    /// The code for an error response that arrived without one
+    fn from_http_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::NOT_FOUND => ErrorCode::NotFound,
+            StatusCode::METHOD_NOT_ALLOWED => ErrorCode::MethodNotAllowed,
+            StatusCode::PRECONDITION_FAILED => ErrorCode::VersionConflict,
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            StatusCode::UNAUTHORIZED => ErrorCode::MissingKey,
+            StatusCode::FORBIDDEN => ErrorCode::InsufficientScope,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimited,
+            StatusCode::NOT_IMPLEMENTED => ErrorCode::Unimplemented,
+            StatusCode::GATEWAY_TIMEOUT => ErrorCode::DeadlineExceeded,
+            StatusCode::CONFLICT => ErrorCode::JobRunning,
+            status if status.is_client_error() => ErrorCode::InvalidArgument,
+            _ => ErrorCode::Internal,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The body of every REST error response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub code: ErrorCode,
+    pub message: String,
+    /// Code-specific fields, such as the versions of a `version_conflict`
+    /// or the `retry_after_secs` of a `rate_limited`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// The failed request's id, as in its `x-request-id` response header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ErrorResponse {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+            request_id: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: impl Serialize) -> Self {
+        self.details = serde_json::to_value(details).ok();
+        self
+    }
+
+    /// Classify a store or handler error
+    ///
+    /// Errors of a known type anywhere in the chain pick the code; anything
+    /// else is `internal`.
+    pub fn from_error(error: &anyhow::Error) -> Self {
+        let message = format!("{:#}", error);
+        for cause in error.chain() {
+            if let Some(response) = cause.downcast_ref::<ErrorResponse>() {
+                return response.clone();
+            }
+            if let Some(conflict) = cause.downcast_ref::<VersionConflict>() {
+                return Self::new(ErrorCode::VersionConflict, conflict.to_string())
+                    .with_details(conflict);
+            }
+            if let Some(invalid) = cause.downcast_ref::<BatchValidationError>() {
+                return Self::new(ErrorCode::ValidationFailed, invalid.to_string())
+                    .with_details(serde_json::json!({ "failed": invalid.errors }));
+            }
+            if let Some(interrupted) = cause.downcast_ref::<QueryInterrupted>() {
+                return Self::new(ErrorCode::DeadlineExceeded, interrupted.to_string())
+                    .with_details(serde_json::json!({
+                        "elapsed_ms": interrupted.elapsed.as_millis() as u64,
+                    }));
+            }
+            if let Some(exceeded) = cause.downcast_ref::<QuotaExceeded>() {
+                return Self::new(ErrorCode::QuotaExceeded, exceeded.to_string())
+                    .with_details(exceeded);
+            }
+            if cause.is::<FilterParseError>() {
+                return Self::new(ErrorCode::InvalidArgument, message);
+            }
+            let code = match cause.downcast_ref::<VecStoreError>() {
+                Some(
+                    VecStoreError::DimensionMismatch { .. }
+                    | VecStoreError::CollectionDimensionMismatch { .. }
+                    | VecStoreError::InvalidFilter(_)
+                    | VecStoreError::FilterParse { .. }
+                    | VecStoreError::InvalidConfig(_),
+                ) => ErrorCode::InvalidArgument,
+                Some(
+                    VecStoreError::VectorNotFound { .. } | VecStoreError::SnapshotNotFound { .. },
+                ) => ErrorCode::NotFound,
+                _ => continue,
+            };
+            return Self::new(code, message);
+        }
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    /// Classify an error of an operation on a namespace
+    ///
+    /// Quotas refusing the operation are `quota_exceeded`. Other errors no
+    /// type maps to a code are the namespace refusing the operation, as
+    /// when it isn't active, so `failed_precondition`.
+    pub fn from_namespace_error(error: &anyhow::Error) -> Self {
+        let mut response = Self::from_error(error);
+        if response.code == ErrorCode::Internal {
+            response.code = ErrorCode::FailedPrecondition;
+        }
+        response
+    }
+
+    /// Prefix the message with what failed, e.g. `Upsert failed`
+    pub fn context(mut self, context: &str) -> Self {
+        self.message = format!("{}: {}", context, self.message);
+        self
+    }
+}
+
+impl std::fmt::Display for ErrorResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ErrorResponse {}
+
+impl IntoResponse for ErrorResponse {
+    fn into_response(self) -> Response {
+        (self.code.http_status(), Json(self)).into_response()
+    }
+}
+
+impl From<ErrorResponse> for tonic::Status {
+    fn from(e: ErrorResponse) -> Self {
+        let mut status = tonic::Status::new(e.code.grpc_code(), e.message);
+        status.metadata_mut().insert(
+            ERROR_CODE_HEADER,
+            tonic::metadata::MetadataValue::from_static(e.code.as_str()),
+        );
+        status
+    }
+}
+
+/// Middleware giving error responses built outside the handlers, such as
+/// axum's extractor rejections and unknown routes, the JSON envelope
+pub(crate) async fn envelope(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if !(status.is_client_error() || status.is_server_error()) || is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let text = axum::body::to_bytes(body, MAX_REWRITTEN_BODY)
+        .await
+        .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+        .unwrap_or_default();
+    let message = if text.is_empty() {
+        status.canonical_reason().unwrap_or("Error").to_string()
+    } else {
+        text
+    };
+    let error = ErrorResponse::new(ErrorCode::from_http_status(status), message);
+
+    // Headers such as `Allow` and `WWW-Authenticate` are kept
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("application/json"),
+    );
+    let body = serde_json::to_vec(&error).unwrap_or_default();
+    Response::from_parts(parts, Body::from(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_classification() {
+        let conflict = VersionConflict {
+            id: "a".to_string(),
+            expected: 1,
+            actual: 2,
+        };
+        let error = anyhow::Error::new(conflict).context("Upsert failed");
+        let response = ErrorResponse::from_error(&error);
+        assert_eq!(response.code, ErrorCode::VersionConflict);
+        assert_eq!(response.details.unwrap()["actual"], 2);
+
+        let error = anyhow::Error::new(VecStoreError::VectorNotFound {
+            id: "a".to_string(),
+        });
+        assert_eq!(ErrorResponse::from_error(&error).code, ErrorCode::NotFound);
+        let error = crate::store::parse_filter("tag = ").unwrap_err();
+        let error = anyhow::Error::new(error);
+        assert_eq!(
+            ErrorResponse::from_error(&error).code,
+            ErrorCode::InvalidArgument
+        );
+        let error = anyhow::anyhow!("disk on fire");
+        let response = ErrorResponse::from_error(&error);
+        assert_eq!(response.code, ErrorCode::Internal);
+        assert_eq!(response.message, "disk on fire");
+
+        let error = anyhow::Error::new(QueryInterrupted {
+            elapsed: std::time::Duration::from_millis(250),
+            cancelled: false,
+        });
+        let response = ErrorResponse::from_error(&error);
+        assert_eq!(response.code, ErrorCode::DeadlineExceeded);
+        assert_eq!(response.details.unwrap()["elapsed_ms"], 250);
+
+        let error = anyhow::Error::new(QuotaExceeded::new(
+            crate::namespace::Quota::MaxVectors,
+            2,
+            2,
+            1,
+        ));
+        let response = ErrorResponse::from_namespace_error(&error);
+        assert_eq!(response.code, ErrorCode::QuotaExceeded);
+        assert_eq!(response.details.unwrap()["quota"], "max_vectors");
+
+        let status = tonic::Status::from(ErrorResponse::new(ErrorCode::TooManyQueries, "busy"));
+        assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+        assert_eq!(
+            status.metadata().get(ERROR_CODE_HEADER).unwrap(),
+            "too_many_queries"
+        );
+    }
+
+    #[test]
+    fn test_codes_serialize_as_their_names() {
+        for code in [
+            ErrorCode::InvalidArgument,
+            ErrorCode::ValidationFailed,
+            ErrorCode::MethodNotAllowed,
+            ErrorCode::PayloadTooLarge,
+            ErrorCode::BatchTooLarge,
+            ErrorCode::TooManyMatches,
+            ErrorCode::NamespaceForbidden,
+            ErrorCode::DeadlineExceeded,
+            ErrorCode::JobRunning,
+            ErrorCode::QuotaExceeded,
+            ErrorCode::ReadOnlyReplica,
+            ErrorCode::Internal,
+        ] {
+            assert_eq!(serde_json::to_value(code).unwrap(), code.as_str());
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Store change events shared by the HTTP and gRPC servers
+
+use crate::store::{StoreEvent, VecStore};
+use tokio::sync::broadcast;
+
+/// Events buffered for each client; a client further behind skips ahead
+/// and is told how many it missed
+pub const EVENT_BUFFER: usize = 1024;
+
+/// Broadcast of a store's [`StoreEvent`]s to any number of clients
+///
+/// Pass the same stream to [`VecStoreHttpServer::with_events`](super::VecStoreHttpServer::with_events)
+/// and [`VecStoreGrpcServer::with_events`](super::VecStoreGrpcServer::with_events)
+/// to serve `/ws/events` and the `SubscribeEvents` RPC.
+#[derive(Clone)]
+pub struct EventStream {
+    sender: broadcast::Sender<StoreEvent>,
+}
+
+impl EventStream {
+    /// Subscribe to `store` and broadcast its events
+    pub fn attach(store: &mut VecStore) -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUFFER);
+        let hook = sender.clone();
+        store.subscribe(Box::new(move |event| {
+            // Fails only when no client is connected
+            let _ = hook.send(event);
+        }));
+        Self { sender }
+    }
+
+    /// Receive the events published from now on
+    pub fn receiver(&self) -> broadcast::Receiver<StoreEvent> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Metadata;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_clients_receive_store_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = VecStore::open(dir.path()).unwrap();
+        let events = EventStream::attach(&mut store);
+
+        // Nobody listening yet
+        store
+            .upsert(
+                "a".into(),
+                vec![1.0, 0.0],
+                Metadata {
+                    fields: HashMap::new(),
+                },
+            )
+            .unwrap();
+
+        let mut receiver = events.receiver();
+        store.remove("a").unwrap();
+        store.save().unwrap();
+        assert_eq!(
+            receiver.recv().await.unwrap(),
+            StoreEvent::Delete { id: "a".into() }
+        );
+        assert_eq!(receiver.recv().await.unwrap(), StoreEvent::Save);
+    }
+}
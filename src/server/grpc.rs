@@ -1,29 +1,133 @@
 //! gRPC server implementation using tonic
 
+use super::auth::{self, Access, ApiKeys, Scope};
+use super::bulk::{self, BulkLimits, DeleteSelector};
+use super::errors::ErrorResponse;
+use super::events::{EventStream, EVENT_BUFFER};
+use super::import::{ImportEvent, ImportLimits, ImportSession};
+use super::limits::{self, Limiter, QueryPermit};
+use super::replication::Replica;
+use super::timeouts::{self, QueryTimeouts};
 use super::types::{pb, *};
-use crate::store::VecStore;
+use crate::store::{BatchItemError, BatchValidationError, Record, VecStore};
 use anyhow::Result;
+use std::collections::HashSet;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio_stream;
 use tonic::{Request, Response, Status};
 
 /// gRPC server wrapper around VecStore
 pub struct VecStoreGrpcServer {
     store: Arc<RwLock<VecStore>>,
+    events: Option<EventStream>,
+    import_limits: ImportLimits,
+    bulk_limits: BulkLimits,
+    query_timeouts: QueryTimeouts,
+    auth: Option<Arc<ApiKeys>>,
+    limiter: Arc<Limiter>,
+    replica: Option<Arc<Replica>>,
 }
 
 impl VecStoreGrpcServer {
     /// Create a new gRPC server
     pub fn new(store: VecStore) -> Self {
-        Self {
-            store: Arc::new(RwLock::new(store)),
-        }
+        Self::with_store(Arc::new(RwLock::new(store)))
     }
 
     /// Create a new gRPC server with shared store
     pub fn with_store(store: Arc<RwLock<VecStore>>) -> Self {
-        Self { store }
+        Self {
+            store,
+            events: None,
+            import_limits: ImportLimits::default(),
+            bulk_limits: BulkLimits::default(),
+            query_timeouts: QueryTimeouts::default(),
+            auth: None,
+            limiter: Arc::new(Limiter::default()),
+            replica: None,
+        }
+    }
+
+    /// Serve the store's change events through `SubscribeEvents`
+    pub fn with_events(mut self, events: EventStream) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Set the body size and row limits of `ImportStream`
+    pub fn with_import_limits(mut self, limits: ImportLimits) -> Self {
+        self.import_limits = limits;
+        self
+    }
+
+    /// Set the batch size limit of `BatchUpsert` and `DeleteMany`, and how
+    /// many records a filter may delete without `force`
+    pub fn with_bulk_limits(mut self, limits: BulkLimits) -> Self {
+        self.bulk_limits = limits;
+        self
+    }
+
+    /// Set the default and longest timeout of `Query` and `QueryStream`
+    pub fn with_query_timeouts(mut self, timeouts: QueryTimeouts) -> Self {
+        self.query_timeouts = timeouts;
+        self
+    }
+
+    /// Require an API key allowing each method
+    ///
+    /// Serve the service through [`ApiKeys::interceptor`] with the same keys,
+    /// which authenticates the key each method then checks.
+    pub fn with_auth(mut self, keys: Arc<ApiKeys>) -> Self {
+        self.auth = Some(keys);
+        self
+    }
+
+    /// Enforce `limiter`'s rate limits and query cap
+    ///
+    /// Pass the limiter the HTTP server uses, so a client's calls on both
+    /// count against one bucket.
+    pub fn with_limits(mut self, limiter: Arc<Limiter>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Serve a read-only copy kept in sync by `replica`: methods changing
+    /// records fail with `read_only_replica`
+    pub fn with_replica(mut self, replica: Arc<Replica>) -> Self {
+        self.replica = Some(replica);
+        self
+    }
+
+    /// Check the scope of the caller's key, when API keys are on, and count
+    /// the call against the rate limits
+    fn admit<T>(&self, request: &Request<T>, scope: Scope) -> Result<(), Status> {
+        auth::authorize(self.auth.as_deref(), request, Some(Access::new(scope)))?;
+        let client = limits::grpc_client(request);
+        self.limiter.admit(&client, None, false, "grpc")?;
+        Ok(())
+    }
+
+    /// [`admit`](Self::admit) a call changing records, which a replica
+    /// refuses
+    fn admit_write<T>(&self, request: &Request<T>, scope: Scope) -> Result<(), Status> {
+        self.admit(request, scope)?;
+        match &self.replica {
+            Some(replica) => Err(replica.read_only().into()),
+            None => Ok(()),
+        }
+    }
+
+    /// [`admit`](Self::admit) a query, which also holds a query slot until
+    /// the permit drops
+    fn admit_query<T>(&self, request: &Request<T>) -> Result<Option<QueryPermit>, Status> {
+        auth::authorize(
+            self.auth.as_deref(),
+            request,
+            Some(Access::new(Scope::Read)),
+        )?;
+        let client = limits::grpc_client(request);
+        Ok(self.limiter.admit(&client, None, true, "grpc")?)
     }
 
     /// Get the store reference (for sharing with HTTP server)
@@ -39,22 +143,27 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
         Box<dyn tokio_stream::Stream<Item = Result<pb::QueryResult, Status>> + Send + 'static>,
     >;
 
+    /// Type alias for the store event stream
+    type SubscribeEventsStream = std::pin::Pin<
+        Box<dyn tokio_stream::Stream<Item = Result<pb::StoreEvent, Status>> + Send + 'static>,
+    >;
+
     /// Insert or update a vector
     async fn upsert(
         &self,
         request: Request<pb::UpsertRequest>,
     ) -> Result<Response<pb::UpsertResponse>, Status> {
-        let req = request.into_inner();
-
-        // Convert protobuf metadata to Metadata
-        let metadata = pb_metadata_to_metadata(&req.metadata)
+        self.admit_write(&request, Scope::Write)?;
+        let start = std::time::Instant::now();
+        let record = pb_upsert_to_record(request.into_inner())
             .map_err(|e| Status::invalid_argument(format!("Invalid metadata: {}", e)))?;
 
         // Perform upsert
         let mut store = self.store.write().await;
         store
-            .upsert(req.id, req.vector, metadata)
-            .map_err(|e| Status::internal(format!("Upsert failed: {}", e)))?;
+            .upsert_record(record)
+            .map_err(|e| ErrorResponse::from_error(&e).context("Upsert failed"))?;
+        super::metrics::record_upsert("grpc", false, start.elapsed().as_secs_f64());
 
         Ok(Response::new(pb::UpsertResponse {
             success: true,
@@ -67,28 +176,139 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
         &self,
         request: Request<pb::BatchUpsertRequest>,
     ) -> Result<Response<pb::BatchUpsertResponse>, Status> {
+        self.admit_write(&request, Scope::Write)?;
+        let start = std::time::Instant::now();
         let req = request.into_inner();
+        self.bulk_limits.check_batch(req.records.len())?;
+
+        // Records whose metadata converts, keyed by their position in the batch
+        let ids: Vec<String> = req.records.iter().map(|r| r.id.clone()).collect();
+        let mut versions = vec![None; ids.len()];
+        let mut failed = Vec::new();
+        let mut records = Vec::with_capacity(req.records.len());
+        for (index, upsert_req) in req.records.into_iter().enumerate() {
+            let id = upsert_req.id.clone();
+            match pb_upsert_to_record(upsert_req) {
+                Ok(record) => records.push((index, record)),
+                Err(e) => failed.push(BatchItemError {
+                    index,
+                    id,
+                    reason: format!("invalid metadata: {}", e),
+                }),
+            }
+        }
 
-        let mut store = self.store.write().await;
+        // Validate the whole batch before the first write
+        let (positions, records): (Vec<usize>, Vec<Record>) = records.into_iter().unzip();
+        let invalid = self.store.read().await.validate_batch(&records);
+        failed.extend(invalid.into_iter().map(|e| BatchItemError {
+            index: positions[e.index],
+            ..e
+        }));
+        failed.sort_by_key(|e| e.index);
+        if !failed.is_empty() && !req.partial {
+            return Err(Status::invalid_argument(
+                BatchValidationError { errors: failed }.to_string(),
+            ));
+        }
+
+        let rejected: HashSet<usize> = failed.iter().map(|e| e.index).collect();
         let mut inserted = 0;
-        let mut errors = Vec::new();
-
-        for upsert_req in req.records {
-            match pb_metadata_to_metadata(&upsert_req.metadata) {
-                Ok(metadata) => {
-                    match store.upsert(upsert_req.id.clone(), upsert_req.vector, metadata) {
-                        Ok(_) => inserted += 1,
-                        Err(e) => errors.push(format!("{}: {}", upsert_req.id, e)),
+
+        // Release the lock between chunks so queries aren't stalled for the whole batch
+        let mut records = positions.into_iter().zip(records).peekable();
+        while records.peek().is_some() {
+            let mut store = self.store.write().await;
+            for (index, record) in records
+                .by_ref()
+                .take(crate::store::DEFAULT_INGEST_CHUNK_SIZE)
+            {
+                if rejected.contains(&index) {
+                    continue;
+                }
+                let id = record.id.clone();
+                match store.upsert_record(record) {
+                    Ok(_) => {
+                        inserted += 1;
+                        versions[index] = store.get(&id).map(|r| r.version);
                     }
+                    Err(e) => failed.push(BatchItemError {
+                        index,
+                        id,
+                        reason: e.to_string(),
+                    }),
                 }
-                Err(e) => errors.push(format!("{}: invalid metadata: {}", upsert_req.id, e)),
             }
         }
+        failed.sort_by_key(|e| e.index);
+        super::metrics::record_upsert("grpc", true, start.elapsed().as_secs_f64());
+        let results = bulk::item_results(ids, versions, &failed)
+            .into_iter()
+            .map(|result| pb::BatchItemResult {
+                id: result.id,
+                version: result.version,
+                error: result.error,
+            })
+            .collect();
 
         Ok(Response::new(pb::BatchUpsertResponse {
             inserted,
             updated: 0, // We don't distinguish between insert and update
-            errors,
+            errors: failed
+                .iter()
+                .map(|e| format!("{}: {}", e.id, e.reason))
+                .collect(),
+            failed: failed
+                .into_iter()
+                .map(|e| pb::BatchItemError {
+                    index: e.index as i32,
+                    id: e.id,
+                    reason: e.reason,
+                })
+                .collect(),
+            results,
+        }))
+    }
+
+    /// Import JSONL records sent in chunks, writing them in batches
+    async fn import_stream(
+        &self,
+        request: Request<tonic::Streaming<pb::ImportChunk>>,
+    ) -> Result<Response<pb::ImportResponse>, Status> {
+        self.admit_write(&request, Scope::Write)?;
+        let start = std::time::Instant::now();
+        let mut chunks = request.into_inner();
+        let mut session = ImportSession::new(self.store.clone(), self.import_limits);
+        let mut events = Vec::new();
+        let error = loop {
+            let result = match chunks.message().await {
+                Ok(Some(chunk)) => session.feed(&chunk.jsonl, &mut events).await,
+                Ok(None) => break session.finish(&mut events).await.err(),
+                Err(status) => Err(format!("failed to read the stream: {}", status.message())),
+            };
+            if let Err(e) = result {
+                break Some(e);
+            }
+        };
+
+        super::metrics::record_upsert("grpc", true, start.elapsed().as_secs_f64());
+        let totals = session.totals();
+        Ok(Response::new(pb::ImportResponse {
+            rows: totals.rows as u64,
+            accepted: totals.accepted as u64,
+            rejected: totals.rejected as u64,
+            rejections: events
+                .into_iter()
+                .filter_map(|event| match event {
+                    ImportEvent::Rejected { line, id, reason } => Some(pb::ImportRejection {
+                        line: line as u64,
+                        id,
+                        reason,
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            error,
         }))
     }
 
@@ -97,6 +317,8 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
         &self,
         request: Request<pb::QueryRequest>,
     ) -> Result<Response<pb::QueryResponse>, Status> {
+        let _permit = self.admit_query(&request)?;
+        let start = std::time::Instant::now();
         let req = request.into_inner();
 
         // Convert to Query
@@ -104,14 +326,16 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
             .map_err(|e| Status::invalid_argument(format!("Invalid query: {}", e)))?;
 
         // Execute query
-        let store = self.store.read().await;
-        let start = std::time::Instant::now();
+        let timeout = self.query_timeouts.resolve(req.timeout_ms);
+        let neighbors = timeouts::run(self.store.clone(), timeout, move |store, cancel| {
+            store.query(query.with_cancel(cancel))
+        })
+        .await
+        .map_err(|e| ErrorResponse::from_error(&e).context("Query failed"))?;
 
-        let neighbors = store
-            .query(query)
-            .map_err(|e| Status::internal(format!("Query failed: {}", e)))?;
-
-        let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let duration = start.elapsed().as_secs_f64();
+        let duration_ms = duration * 1000.0;
+        super::metrics::record_query("grpc", "vector", neighbors.len(), duration);
 
         // Convert results
         let results = neighbors.iter().map(neighbor_to_query_result).collect();
@@ -131,6 +355,8 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
         &self,
         request: Request<pb::QueryRequest>,
     ) -> Result<Response<Self::QueryStreamStream>, Status> {
+        let _permit = self.admit_query(&request)?;
+        let start = std::time::Instant::now();
         let req = request.into_inner();
 
         // Convert to Query
@@ -138,10 +364,18 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
             .map_err(|e| Status::invalid_argument(format!("Invalid query: {}", e)))?;
 
         // Execute query
-        let store = self.store.read().await;
-        let neighbors = store
-            .query(query)
-            .map_err(|e| Status::internal(format!("Query failed: {}", e)))?;
+        let timeout = self.query_timeouts.resolve(req.timeout_ms);
+        let neighbors = timeouts::run(self.store.clone(), timeout, move |store, cancel| {
+            store.query(query.with_cancel(cancel))
+        })
+        .await
+        .map_err(|e| ErrorResponse::from_error(&e).context("Query failed"))?;
+        super::metrics::record_query(
+            "grpc",
+            "stream",
+            neighbors.len(),
+            start.elapsed().as_secs_f64(),
+        );
 
         // Create stream
         let results: Vec<pb::QueryResult> =
@@ -152,17 +386,87 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
         Ok(Response::new(Box::pin(stream)))
     }
 
+    /// Fetch records by ID
+    async fn get(
+        &self,
+        request: Request<pb::GetRequest>,
+    ) -> Result<Response<pb::GetResponse>, Status> {
+        self.admit(&request, Scope::Read)?;
+        let req = request.into_inner();
+
+        let store = self.store.read().await;
+        let results = req
+            .ids
+            .iter()
+            .map(|id| record_to_get_result(id, store.get(id).as_ref()))
+            .collect();
+
+        Ok(Response::new(pb::GetResponse { results }))
+    }
+
+    /// Fetch a chunk with up to `n` chunks either side of it from the same parent
+    async fn fetch_parent_window(
+        &self,
+        request: Request<pb::ParentWindowRequest>,
+    ) -> Result<Response<pb::GetResponse>, Status> {
+        self.admit(&request, Scope::Read)?;
+        let req = request.into_inner();
+
+        let store = self.store.read().await;
+        let results = store
+            .fetch_parent_window(&req.id, req.n as usize)
+            .map_err(|e| Status::not_found(e.to_string()))?
+            .iter()
+            .map(|record| record_to_get_result(&req.id, Some(record)))
+            .collect();
+
+        Ok(Response::new(pb::GetResponse { results }))
+    }
+
+    /// Update a record's metadata without touching its vector
+    async fn update_metadata(
+        &self,
+        request: Request<pb::UpdateMetadataRequest>,
+    ) -> Result<Response<pb::UpdateMetadataResponse>, Status> {
+        self.admit_write(&request, Scope::Write)?;
+        let req = request.into_inner();
+
+        let metadata = pb_metadata_to_metadata(&req.metadata)
+            .map_err(|e| Status::invalid_argument(format!("Invalid metadata: {}", e)))?;
+
+        let mut store = self.store.write().await;
+        let result = if req.replace {
+            store.replace_metadata(&req.id, metadata)
+        } else {
+            store.update_metadata(&req.id, metadata.fields)
+        };
+
+        Ok(Response::new(match result {
+            Ok(()) => pb::UpdateMetadataResponse {
+                success: true,
+                error: None,
+            },
+            Err(e) => pb::UpdateMetadataResponse {
+                success: false,
+                error: Some(e.to_string()),
+            },
+        }))
+    }
+
     /// Hard delete a vector
     async fn delete(
         &self,
         request: Request<pb::DeleteRequest>,
     ) -> Result<Response<pb::DeleteResponse>, Status> {
+        self.admit_write(&request, Scope::Write)?;
+        let start = std::time::Instant::now();
         let req = request.into_inner();
 
         let mut store = self.store.write().await;
         store
             .remove(&req.id)
-            .map_err(|e| Status::internal(format!("Delete failed: {}", e)))?;
+            .map_err(|e| ErrorResponse::from_error(&e).context("Delete failed"))?;
+        super::metrics::record_delete("grpc", "hard", start.elapsed().as_secs_f64());
 
         Ok(Response::new(pb::DeleteResponse {
             found: true,
@@ -170,17 +474,45 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
         }))
     }
 
+    /// Hard delete the vectors with the given ids, or matching a filter
+    async fn delete_many(
+        &self,
+        request: Request<pb::DeleteManyRequest>,
+    ) -> Result<Response<pb::DeleteManyResponse>, Status> {
+        self.admit_write(&request, Scope::Write)?;
+        let start = std::time::Instant::now();
+        let req = request.into_inner();
+        let filter = req
+            .filter
+            .as_deref()
+            .map(bulk::parse_filter_text)
+            .transpose()?;
+        let selector = DeleteSelector::new(req.ids, filter)?;
+
+        let mut store = self.store.write().await;
+        let summary = bulk::delete(&mut store, &selector, req.force, &self.bulk_limits)?;
+        super::metrics::record_delete("grpc", "bulk", start.elapsed().as_secs_f64());
+
+        Ok(Response::new(pb::DeleteManyResponse {
+            matched: summary.matched as u64,
+            deleted: summary.deleted as u64,
+        }))
+    }
+
     /// Soft delete a vector
     async fn soft_delete(
         &self,
         request: Request<pb::SoftDeleteRequest>,
     ) -> Result<Response<pb::SoftDeleteResponse>, Status> {
+        self.admit_write(&request, Scope::Write)?;
+        let start = std::time::Instant::now();
         let req = request.into_inner();
 
         let mut store = self.store.write().await;
         let marked = store
             .soft_delete(&req.id)
-            .map_err(|e| Status::internal(format!("Soft delete failed: {}", e)))?;
+            .map_err(|e| ErrorResponse::from_error(&e).context("Soft delete failed"))?;
+        super::metrics::record_delete("grpc", "soft", start.elapsed().as_secs_f64());
 
         Ok(Response::new(pb::SoftDeleteResponse {
             found: marked,
@@ -193,12 +525,13 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
         &self,
         request: Request<pb::RestoreRequest>,
     ) -> Result<Response<pb::RestoreResponse>, Status> {
+        self.admit_write(&request, Scope::Write)?;
         let req = request.into_inner();
 
         let mut store = self.store.write().await;
         let restored = store
             .restore(&req.id)
-            .map_err(|e| Status::internal(format!("Restore failed: {}", e)))?;
+            .map_err(|e| ErrorResponse::from_error(&e).context("Restore failed"))?;
 
         Ok(Response::new(pb::RestoreResponse {
             found: restored,
@@ -209,32 +542,35 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
     /// Compact database (remove soft-deleted vectors)
     async fn compact(
         &self,
-        _request: Request<pb::CompactRequest>,
+        request: Request<pb::CompactRequest>,
     ) -> Result<Response<pb::CompactResponse>, Status> {
+        self.admit_write(&request, Scope::Admin)?;
         let mut store = self.store.write().await;
-        let removed_count = store
+        let report = store
             .compact()
-            .map_err(|e| Status::internal(format!("Compact failed: {}", e)))?;
+            .map_err(|e| ErrorResponse::from_error(&e).context("Compact failed"))?;
 
         Ok(Response::new(pb::CompactResponse {
-            removed_count: removed_count as i32,
-            freed_bytes: 0, // Byte tracking requires storage layer instrumentation
+            removed_count: report.removed_count as i32,
+            freed_bytes: report.bytes_reclaimed() as i64,
         }))
     }
 
     /// Get database statistics
     async fn get_stats(
         &self,
-        _request: Request<pb::StatsRequest>,
+        request: Request<pb::StatsRequest>,
     ) -> Result<Response<pb::StatsResponse>, Status> {
+        self.admit(&request, Scope::Read)?;
         let store = self.store.read().await;
+        let stats = store.stats();
 
         Ok(Response::new(pb::StatsResponse {
             total_vectors: store.len() as i64 + store.deleted_count() as i64,
             active_vectors: store.active_count() as i64,
             deleted_vectors: store.deleted_count() as i64,
             dimension: store.dimension() as i32,
-            storage_bytes: 0, // Storage size calculation requires persistence layer API extension
+            storage_bytes: stats.disk_bytes as i64,
             cache_stats: None, // Semantic cache integration is a future optimization
         }))
     }
@@ -244,12 +580,13 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
         &self,
         request: Request<pb::SnapshotRequest>,
     ) -> Result<Response<pb::SnapshotResponse>, Status> {
+        self.admit(&request, Scope::Admin)?;
         let req = request.into_inner();
 
         let store = self.store.read().await;
         store
             .create_snapshot(&req.name)
-            .map_err(|e| Status::internal(format!("Snapshot failed: {}", e)))?;
+            .map_err(|e| ErrorResponse::from_error(&e).context("Snapshot failed"))?;
 
         Ok(Response::new(pb::SnapshotResponse {
             success: true,
@@ -260,12 +597,13 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
     /// List all snapshots
     async fn list_snapshots(
         &self,
-        _request: Request<pb::ListSnapshotsRequest>,
+        request: Request<pb::ListSnapshotsRequest>,
     ) -> Result<Response<pb::ListSnapshotsResponse>, Status> {
+        self.admit(&request, Scope::Read)?;
         let store = self.store.read().await;
         let snapshots_info = store
             .list_snapshots()
-            .map_err(|e| Status::internal(format!("List snapshots failed: {}", e)))?;
+            .map_err(|e| ErrorResponse::from_error(&e).context("List snapshots failed"))?;
 
         let snapshots = snapshots_info
             .into_iter()
@@ -284,12 +622,13 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
         &self,
         request: Request<pb::RestoreSnapshotRequest>,
     ) -> Result<Response<pb::RestoreSnapshotResponse>, Status> {
+        self.admit_write(&request, Scope::Admin)?;
         let req = request.into_inner();
 
         let mut store = self.store.write().await;
         store
             .restore_snapshot(&req.name)
-            .map_err(|e| Status::internal(format!("Restore snapshot failed: {}", e)))?;
+            .map_err(|e| ErrorResponse::from_error(&e).context("Restore snapshot failed"))?;
 
         Ok(Response::new(pb::RestoreSnapshotResponse {
             success: true,
@@ -302,6 +641,8 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
         &self,
         request: Request<pb::HybridQueryRequest>,
     ) -> Result<Response<pb::QueryResponse>, Status> {
+        let _permit = self.admit_query(&request)?;
+        let start = std::time::Instant::now();
         let req = request.into_inner();
 
         let query = crate::store::HybridQuery {
@@ -309,17 +650,22 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
             keywords: req.text_query,
             k: req.limit as usize,
             alpha: req.alpha.unwrap_or(0.7) as f32,
-            filter: req.filter.and_then(|f| crate::store::parse_filter(&f).ok()),
+            filter: req
+                .filter
+                .as_deref()
+                .map(crate::store::FilterExpr::parse)
+                .transpose()
+                .map_err(|e| Status::invalid_argument(format!("Invalid filter: {}", e)))?,
         };
 
         let store = self.store.read().await;
-        let start = std::time::Instant::now();
-
         let neighbors = store
             .hybrid_query(query)
-            .map_err(|e| Status::internal(format!("Hybrid query failed: {}", e)))?;
+            .map_err(|e| ErrorResponse::from_error(&e).context("Hybrid query failed"))?;
 
-        let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+        let duration = start.elapsed().as_secs_f64();
+        let duration_ms = duration * 1000.0;
+        super::metrics::record_query("grpc", "hybrid", neighbors.len(), duration);
 
         let results = neighbors.iter().map(neighbor_to_query_result).collect();
 
@@ -336,8 +682,9 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
     /// Health check
     async fn health_check(
         &self,
-        _request: Request<pb::HealthCheckRequest>,
+        request: Request<pb::HealthCheckRequest>,
     ) -> Result<Response<pb::HealthCheckResponse>, Status> {
+        auth::authorize(self.auth.as_deref(), &request, None)?;
         // Simple health check - just verify we can access the store
         let _ = self.store.read().await;
 
@@ -346,4 +693,44 @@ impl pb::vec_store_service_server::VecStoreService for VecStoreGrpcServer {
             message: Some("Healthy".to_string()),
         }))
     }
+
+    /// Stream store change events until the client disconnects
+    async fn subscribe_events(
+        &self,
+        request: Request<pb::SubscribeEventsRequest>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        self.admit(&request, Scope::Read)?;
+        let mut receiver = self
+            .events
+            .as_ref()
+            .ok_or_else(|| Status::unimplemented("Event stream is not enabled on this server"))?
+            .receiver();
+
+        let (tx, rx) = mpsc::channel(EVENT_BUFFER);
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    _ = tx.closed() => break,
+                    event = receiver.recv() => event,
+                };
+                let event = match event {
+                    Ok(event) => store_event_to_pb(event),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => pb::StoreEvent {
+                        kind: pb::store_event::Kind::Lagged as i32,
+                        id: String::new(),
+                        dimension: 0,
+                        missed,
+                    },
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if tx.send(Ok(event)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+        )))
+    }
 }
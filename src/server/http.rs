@@ -1,62 +1,212 @@
 //! HTTP/REST API server implementation using axum
 
-use crate::store::VecStore;
+use super::auth::{self, Access, ApiKeys, AuthLayer, Scope};
+use super::bulk::{self, BatchItemResult, BulkLimits, DeleteSelector, DeleteSummary};
+use super::checkpoint::{Checkpointer, SaveReport, SaveTrigger};
+use super::embed::{self, Embedding};
+use super::errors::{ErrorCode, ErrorResponse};
+use super::events::EventStream;
+use super::http_options::HttpOptions;
+use super::import::{ImportEvent, ImportLimits, ImportSession};
+use super::jobs::{Job, Jobs};
+use super::limits::{self, LimitLayer, Limiter, LimitsConfig, Target};
+use super::openapi::{self, HttpApi};
+use super::query_stream::{self, StreamLimits};
+use super::replication::{self, ReadOnlyLayer, Replica, ReplicaStatus, ReplicationSequence};
+use super::timeouts::{self, QueryTimeouts};
+use crate::reranking::MetadataBoostReranker;
+use crate::store::{
+    make_record, BatchItemError, BatchValidationError, Neighbor, ProfiledResults, QueryOptions,
+    QueryProfile, Record, SlowQuery, StoreEvent, VecStore, VersionConflict,
+};
 use axum::{
+    body::Body,
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        Path, State,
+        Path, Query, State,
     },
-    http::StatusCode,
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware,
     response::{IntoResponse, Response},
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tower_http::cors::CorsLayer;
-use tower_http::trace::TraceLayer;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_stream::StreamExt;
+use utoipa::{IntoParams, OpenApi, ToSchema};
 
 /// HTTP server wrapper around VecStore
 #[derive(Clone)]
 pub struct VecStoreHttpServer {
     store: Arc<RwLock<VecStore>>,
+    events: Option<EventStream>,
+    import_limits: ImportLimits,
+    bulk_limits: BulkLimits,
+    query_timeouts: QueryTimeouts,
+    auth: Option<Arc<ApiKeys>>,
+    limiter: Arc<Limiter>,
+    checkpointer: Arc<Checkpointer>,
+    jobs: Arc<Jobs>,
+    embedding: Option<Embedding>,
+    replica: Option<Arc<Replica>>,
+    stream_limits: StreamLimits,
+    http_options: HttpOptions,
 }
 
 impl VecStoreHttpServer {
     /// Create a new HTTP server
     pub fn new(store: VecStore) -> Self {
-        Self {
-            store: Arc::new(RwLock::new(store)),
-        }
+        Self::with_store(Arc::new(RwLock::new(store)))
     }
 
     /// Create a new HTTP server with shared store
     pub fn with_store(store: Arc<RwLock<VecStore>>) -> Self {
-        Self { store }
+        Self {
+            checkpointer: Arc::new(Checkpointer::new(store.clone())),
+            jobs: Arc::new(Jobs::new(store.clone())),
+            store,
+            events: None,
+            import_limits: ImportLimits::default(),
+            bulk_limits: BulkLimits::default(),
+            query_timeouts: QueryTimeouts::default(),
+            auth: None,
+            limiter: Arc::new(Limiter::default()),
+            embedding: None,
+            replica: None,
+            stream_limits: StreamLimits::default(),
+            http_options: HttpOptions::default(),
+        }
+    }
+
+    /// Serve the store's change events as JSON over the `/ws/events` WebSocket,
+    /// and let `/ws/query-stream` clients subscribe to standing queries
+    pub fn with_events(mut self, events: EventStream) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Set the heartbeat, concurrency and buffer limits of each
+    /// `/ws/query-stream` connection
+    pub fn with_stream_limits(mut self, limits: StreamLimits) -> Self {
+        self.stream_limits = limits;
+        self
+    }
+
+    /// Set the body size and row limits of `/v1/import`
+    pub fn with_import_limits(mut self, limits: ImportLimits) -> Self {
+        self.import_limits = limits;
+        self
+    }
+
+    /// Set the batch size limit of `/v1/upsert/batch` and `/v1/delete`, and
+    /// how many records a filter may delete without `force`
+    pub fn with_bulk_limits(mut self, limits: BulkLimits) -> Self {
+        self.bulk_limits = limits;
+        self
+    }
+
+    /// Set the default and longest timeout of `/v1/query`
+    pub fn with_query_timeouts(mut self, timeouts: QueryTimeouts) -> Self {
+        self.query_timeouts = timeouts;
+        self
+    }
+
+    /// Require an API key allowing each route, see [`route_access`]
+    pub fn with_auth(mut self, keys: Arc<ApiKeys>) -> Self {
+        self.auth = Some(keys);
+        self
+    }
+
+    /// Enforce `limiter`'s rate limits and query cap, see [`route_target`]
+    ///
+    /// Without it the server starts unlimited; `PUT /v1/limits` can still
+    /// set limits later.
+    pub fn with_limits(mut self, limiter: Arc<Limiter>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Save through `checkpointer` on `POST /admin/save`, sharing it with a
+    /// background task; it must wrap this server's store
+    pub fn with_checkpointer(mut self, checkpointer: Arc<Checkpointer>) -> Self {
+        self.checkpointer = checkpointer;
+        self
+    }
+
+    /// Embed texts with `embedding` on `/v1/embed` and `/v1/query/text`;
+    /// without it those routes answer `unimplemented`
+    pub fn with_embedder(mut self, embedding: Embedding) -> Self {
+        self.embedding = Some(embedding);
+        self
+    }
+
+    /// Serve a read-only copy kept in sync by `replica`, which must wrap
+    /// this server's store
+    ///
+    /// Routes changing records answer `read_only_replica` (see
+    /// [`route_writes`]), and `/health` and `/metrics` report the lag.
+    pub fn with_replica(mut self, replica: Arc<Replica>) -> Self {
+        self.replica = Some(replica);
+        self
+    }
+
+    /// Set CORS, response compression and the request body limit
+    pub fn with_http_options(mut self, options: HttpOptions) -> Self {
+        self.http_options = options;
+        self
     }
 
     /// Build the router
     pub fn router(&self) -> Router {
-        Router::new()
+        let mut router = Router::new()
             // Vector operations
             .route("/v1/upsert", post(upsert))
+            .route("/v1/upsert/batch", post(batch_upsert))
             .route("/v1/batch-upsert", post(batch_upsert))
             .route("/v1/batch-execute", post(batch_execute))
+            .route("/v1/import", post(import))
             .route("/v1/query", post(query))
+            .route("/v1/query/text", post(query_text))
+            .route("/v1/embed", post(embed_texts))
             .route("/v1/query-explain", post(query_explain))
             .route("/v1/query-estimate", post(query_estimate))
-            .route("/v1/delete/:id", delete(delete_vector))
-            .route("/v1/soft-delete/:id", post(soft_delete))
-            .route("/v1/restore/:id", post(restore))
+            .route("/v1/get", post(get_records))
+            .route("/v1/get/{id}", get(get_record))
+            .route("/v1/parent-window/{id}", get(parent_window))
+            .route("/v1/scan", post(scan))
+            .route("/v1/metadata/{id}", post(update_metadata))
+            .route("/v1/delete", post(delete_many))
+            .route("/v1/delete/{id}", delete(delete_vector))
+            .route("/v1/soft-delete/{id}", post(soft_delete))
+            .route("/v1/restore/{id}", post(restore))
             // Database operations
             .route("/v1/compact", post(compact))
             .route("/v1/stats", get(get_stats))
+            .route("/v1/slow-queries", get(get_slow_queries))
+            .route("/v1/count", get(count))
+            .route("/v1/distinct", get(distinct_values))
+            .route("/v1/limits", get(get_limits))
+            .route("/v1/limits", put(put_limits))
+            // Persistence
+            .route("/admin/save", post(save_now))
+            .route("/admin/last-save", get(last_save))
+            .route("/admin/backup", post(backup_job))
+            .route("/admin/restore", post(restore_job))
+            .route("/admin/compact", post(compact_job))
+            .route("/admin/optimize", post(optimize_job))
+            .route("/admin/jobs", get(list_jobs))
+            .route("/admin/jobs/{id}", get(get_job))
+            .route("/admin/jobs/{id}/download", get(download_backup))
+            // Replication
+            .route("/admin/replication/sequence", get(replication_sequence))
+            .route("/admin/replication/snapshot", get(replication_snapshot))
             // Snapshot operations
             .route("/v1/snapshots", post(create_snapshot))
             .route("/v1/snapshots", get(list_snapshots))
-            .route("/v1/snapshots/:name/restore", post(restore_snapshot))
+            .route("/v1/snapshots/{name}/restore", post(restore_snapshot))
             // Hybrid search
             .route("/v1/hybrid-query", post(hybrid_query))
             // WebSocket streaming
@@ -65,10 +215,44 @@ impl VecStoreHttpServer {
             .route("/metrics", get(metrics_endpoint))
             // Health check
             .route("/health", get(health_check))
-            .route("/ready", get(ready_check))
-            .with_state(self.clone())
-            .layer(CorsLayer::permissive())
-            .layer(TraceLayer::new_for_http())
+            .route("/ready", get(ready_check));
+        if let Some(events) = self.events.clone() {
+            router = router.route(
+                "/ws/events",
+                get(move |ws: WebSocketUpgrade| events_ws(ws, events.clone())),
+            );
+        }
+        // `/v1/import` streams its body under its own limit
+        router = self
+            .http_options
+            .limit_bodies(router, |route| route != "/v1/import");
+        if let Some(replica) = self.replica.clone() {
+            let layer = ReadOnlyLayer {
+                replica,
+                writes: route_writes,
+            };
+            router = router.route_layer(middleware::from_fn_with_state(
+                layer,
+                replication::refuse_writes,
+            ));
+        }
+        let layer = LimitLayer {
+            limiter: self.limiter.clone(),
+            policy: route_target,
+        };
+        router = router.route_layer(middleware::from_fn_with_state(layer, limits::throttle));
+        if let Some(keys) = self.auth.clone() {
+            let layer = AuthLayer {
+                keys,
+                policy: route_access,
+            };
+            router = router.route_layer(middleware::from_fn_with_state(layer, auth::require_key));
+        }
+        let router = router
+            .route_layer(middleware::from_fn(super::metrics::track_http))
+            .with_state(self.clone());
+        let router = openapi::serve(router, HttpApi::openapi(), self.http_options.swagger_ui);
+        self.http_options.apply(router)
     }
 
     /// Get the store reference
@@ -77,63 +261,288 @@ impl VecStoreHttpServer {
     }
 }
 
+/// The scope each route needs when API keys are on; `None` for the health
+/// and metrics endpoints
+///
+/// Routes not listed need `admin`, so a new route is closed until it is
+/// placed here.
+pub fn route_access(method: &Method, route: &str, _path: &str) -> Option<Access> {
+    let scope = match route {
+        "/health" | "/ready" | "/metrics" => return None,
+        "/v1/query"
+        | "/v1/query/text"
+        | "/v1/embed"
+        | "/v1/query-explain"
+        | "/v1/query-estimate"
+        | "/v1/hybrid-query"
+        | "/v1/get"
+        | "/v1/get/{id}"
+        | "/v1/parent-window/{id}"
+        | "/v1/scan"
+        | "/v1/stats"
+        | "/v1/slow-queries"
+        | "/v1/count"
+        | "/v1/distinct"
+        | "/ws/query-stream"
+        | "/ws/events" => Scope::Read,
+        "/v1/snapshots" if method == Method::GET => Scope::Read,
+        "/admin/last-save" => Scope::Read,
+        "/v1/upsert"
+        | "/v1/upsert/batch"
+        | "/v1/batch-upsert"
+        | "/v1/batch-execute"
+        | "/v1/import"
+        | "/v1/metadata/{id}"
+        | "/v1/delete"
+        | "/v1/delete/{id}"
+        | "/v1/soft-delete/{id}"
+        | "/v1/restore/{id}" => Scope::Write,
+        _ => Scope::Admin,
+    };
+    Some(Access::new(scope))
+}
+
+/// Whether a route changes the store's records, which a replica refuses
+pub fn route_writes(route: &str) -> bool {
+    matches!(
+        route,
+        "/v1/upsert"
+            | "/v1/upsert/batch"
+            | "/v1/batch-upsert"
+            | "/v1/batch-execute"
+            | "/v1/import"
+            | "/v1/metadata/{id}"
+            | "/v1/delete"
+            | "/v1/delete/{id}"
+            | "/v1/soft-delete/{id}"
+            | "/v1/restore/{id}"
+            | "/v1/compact"
+            | "/v1/snapshots/{name}/restore"
+            | "/admin/restore"
+            | "/admin/compact"
+    )
+}
+
+/// What each route counts against; the health and metrics endpoints are
+/// exempt, and queries take a query slot
+pub fn route_target(route: &str, _path: &str) -> Option<Target> {
+    let query = match route {
+        "/health" | "/ready" | "/metrics" => return None,
+        "/v1/query" | "/v1/query/text" | "/v1/query-explain" | "/v1/hybrid-query"
+        | "/ws/query-stream" => true,
+        _ => false,
+    };
+    Some(Target {
+        namespace: None,
+        query,
+    })
+}
+
 // ============================================================================
 // Request/Response types
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpsertRequest {
     pub id: String,
     pub vector: Vec<f32>,
     pub metadata: HashMap<String, serde_json::Value>,
+    /// Only write if the record is at this version (0: doesn't exist);
+    /// the `If-Match` header takes precedence on `/v1/upsert`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_version: Option<u64>,
+    /// Source text to keep with the record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Document the record is a chunk of
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// Position among the parent's chunks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct UpsertResponse {
     pub success: bool,
     pub error: Option<String>,
+    /// The record's version after the write
+    #[serde(default)]
+    pub version: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchUpsertRequest {
     pub records: Vec<UpsertRequest>,
+    /// Write the valid records when some fail validation, instead of
+    /// rejecting the batch with 422
+    #[serde(default)]
+    pub partial: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchUpsertResponse {
     pub inserted: i32,
     pub updated: i32,
     pub errors: Vec<String>,
+    /// Records that were not written, with their position and reason
+    #[serde(default)]
+    #[schema(value_type = Vec<Object>)]
+    pub failed: Vec<BatchItemError>,
+    /// One entry per record, in batch order
+    #[serde(default)]
+    pub results: Vec<BatchItemResult>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct QueryRequest {
     pub vector: Vec<f32>,
     pub limit: i32,
     pub filter: Option<String>,
+    /// Brute-force scan instead of the HNSW index
+    #[serde(default)]
+    pub exact: bool,
+    /// Rerank an over-fetched candidate set before truncating to `limit`
+    #[serde(default)]
+    pub rerank: Option<RerankRequest>,
+    /// Return a timing breakdown with the results
+    #[serde(default)]
+    pub profile: bool,
+    /// Fail with 504 after this long instead of the server's default
+    /// timeout, up to its maximum
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// `include_vector`, `include_metadata` and `metadata_fields`
+    #[serde(flatten)]
+    pub options: QueryOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TextQueryRequest {
+    /// Text the server embeds and searches with
+    pub text: String,
+    pub limit: i32,
+    pub filter: Option<String>,
+    /// Brute-force scan instead of the HNSW index
+    #[serde(default)]
+    pub exact: bool,
+    /// Rerank an over-fetched candidate set before truncating to `limit`
+    #[serde(default)]
+    pub rerank: Option<RerankRequest>,
+    /// Return a timing breakdown with the results
+    #[serde(default)]
+    pub profile: bool,
+    /// Fail with 504 after this long instead of the server's default
+    /// timeout, up to its maximum
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// `include_vector`, `include_metadata` and `metadata_fields`
+    #[serde(flatten)]
+    pub options: QueryOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EmbedRequest {
+    /// Texts to embed, at most the server's embedding batch limit
+    pub texts: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct EmbedResponse {
+    pub model: String,
+    pub dimension: usize,
+    /// One vector per text, in request order
+    pub embeddings: Vec<Vec<f32>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RerankRequest {
+    /// Candidates to fetch before reranking (default 10 × limit)
+    pub fetch_k: Option<usize>,
+    /// Query text passed to the reranker
+    pub text: Option<String>,
+    /// Score multipliers for candidates whose metadata matches
+    #[serde(default)]
+    pub boosts: Vec<MetadataBoost>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MetadataBoost {
+    pub field: String,
+    pub value: serde_json::Value,
+    pub factor: f32,
+}
+
+/// Run a query, reranking with the request's boosts when asked to
+pub(super) fn run_query(
+    store: &VecStore,
+    mut query: crate::store::Query,
+    rerank: Option<RerankRequest>,
+) -> anyhow::Result<ProfiledResults> {
+    let Some(rerank) = rerank else {
+        return store.query_profiled(query);
+    };
+
+    query.text = rerank.text;
+    query.rerank_fetch_k = rerank.fetch_k;
+    let reranker = rerank
+        .boosts
+        .into_iter()
+        .fold(MetadataBoostReranker::new(), |reranker, b| {
+            reranker.boost(b.field, b.value, b.factor)
+        });
+    store.query_with_reranker_profiled(query, &reranker)
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct QueryResult {
     pub id: String,
     pub score: f32,
-    pub metadata: HashMap<String, serde_json::Value>,
+    /// Omitted when the query set `include_metadata: false`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+    /// Present when the query set `include_vector: true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vec<f32>>,
+    pub version: u64,
+    /// Present when the query set `include_text: true` and the record has text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+impl QueryResult {
+    pub(super) fn from_neighbor(neighbor: &Neighbor, options: &QueryOptions) -> Self {
+        Self {
+            id: neighbor.id.clone(),
+            score: neighbor.score,
+            metadata: options
+                .include_metadata
+                .then(|| neighbor.metadata.fields.clone()),
+            vector: neighbor.vector.clone(),
+            version: neighbor.version,
+            text: neighbor.text.clone(),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct QueryResponse {
     pub results: Vec<QueryResult>,
     pub stats: Option<QueryStats>,
+    /// Present when the request set `profile: true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub profile: Option<QueryProfile>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct QueryStats {
     pub total_candidates: i32,
     pub filtered_count: i32,
     pub duration_ms: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ExplainedQueryResult {
     pub id: String,
     pub score: f32,
@@ -141,7 +550,7 @@ pub struct ExplainedQueryResult {
     pub explanation: ExplanationDto,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ExplanationDto {
     pub raw_score: f32,
     pub distance_metric: String,
@@ -152,7 +561,7 @@ pub struct ExplanationDto {
     pub explanation_text: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct FilterEvaluationDto {
     pub filter_expr: String,
     pub matched_conditions: Vec<String>,
@@ -160,7 +569,7 @@ pub struct FilterEvaluationDto {
     pub passed: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct GraphStatsDto {
     pub distance_calculations: usize,
     pub nodes_visited: usize,
@@ -168,75 +577,256 @@ pub struct GraphStatsDto {
     pub hops_from_entry: Option<usize>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct QueryExplainResponse {
     pub results: Vec<ExplainedQueryResult>,
     pub stats: Option<QueryStats>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GetRequest {
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GetResult {
+    pub id: String,
+    pub found: bool,
+    pub vector: Option<Vec<f32>>,
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+    pub created_at: Option<i64>,
+    pub version: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+}
+
+impl GetResult {
+    fn from_lookup(id: &str, record: Option<Record>) -> Self {
+        match record {
+            Some(record) => Self {
+                id: record.id,
+                found: true,
+                vector: Some(record.vector),
+                metadata: Some(record.metadata.fields),
+                created_at: Some(record.created_at),
+                version: Some(record.version),
+                text: record.text,
+                parent_id: record.parent_id,
+                chunk_index: record.chunk_index,
+            },
+            None => Self {
+                id: id.to_string(),
+                found: false,
+                vector: None,
+                metadata: None,
+                created_at: None,
+                version: None,
+                text: None,
+                parent_id: None,
+                chunk_index: None,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+pub struct ParentWindowParams {
+    /// Chunks to return on each side of the requested one (default 1)
+    pub n: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct GetResponse {
+    pub results: Vec<GetResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ScanRequest {
+    /// ID of the last record from the previous page; omit to start from the beginning
+    pub after: Option<String>,
+    pub limit: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ScanResponse {
+    /// In the JSON record shape exports and backups use
+    #[schema(value_type = Vec<Object>)]
+    pub records: Vec<Record>,
+    /// Cursor for the next page; `None` once the scan is exhausted
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateMetadataRequest {
+    pub metadata: HashMap<String, serde_json::Value>,
+    /// Replace the whole metadata map instead of merging into it
+    #[serde(default)]
+    pub replace: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateMetadataResponse {
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+pub struct CountParams {
+    pub filter: Option<String>,
+    /// Metadata field to group counts by
+    pub group_by: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CountResponse {
+    pub count: usize,
+    /// Per-value counts, present when `group_by` was given
+    pub groups: Option<HashMap<String, usize>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+pub struct DistinctParams {
+    pub field: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DistinctResponse {
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct DeleteResponse {
     pub found: bool,
     pub deleted: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// `POST /v1/delete`: set either `ids` or `filter`
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeleteManyRequest {
+    /// Records to delete; ids that aren't stored are skipped
+    #[serde(default)]
+    pub ids: Vec<String>,
+    /// Filter expression such as `"tag = 'draft'"`, or a `FilterExpr` as JSON
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub filter: Option<serde_json::Value>,
+    /// Delete even when the filter matches more records than the server allows
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SoftDeleteResponse {
     pub found: bool,
     pub marked_deleted: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RestoreResponse {
     pub found: bool,
     pub restored: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CompactResponse {
     pub removed_count: i32,
     pub freed_bytes: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct StatsResponse {
     pub total_vectors: i64,
     pub active_vectors: i64,
     pub deleted_vectors: i64,
-    pub dimension: i32,
     pub storage_bytes: i64,
+    /// Everything [`VecStore::stats`] reports, as top-level fields
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub store: crate::store::StoreStats,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SlowQueriesResponse {
+    /// The most recent slow queries, oldest first
+    #[schema(value_type = Vec<Object>)]
+    pub queries: Vec<SlowQuery>,
+    /// Slow queries seen since the store was opened, including ones no
+    /// longer kept
+    pub total: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct BackupRequest {
+    /// File to write on the server; without it the backup is downloaded
+    /// from `/admin/jobs/{id}/download`
+    #[serde(default)]
+    pub path: Option<String>,
+    /// zstd-compress the backup
+    #[serde(default)]
+    pub compress: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RestoreRequest {
+    /// Backup file on the server
+    pub path: String,
+    /// The token a first request without `confirm` returned
+    #[serde(default)]
+    pub confirm: Option<String>,
+}
+
+/// What a restore would replace the store with, and the token confirming it
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RestorePlan {
+    /// The backup's manifest
+    #[schema(value_type = Object)]
+    pub manifest: serde_json::Value,
+    /// Send back as `confirm` to start the restore
+    pub confirm_token: String,
+    pub expires_in_secs: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ListJobsResponse {
+    /// Running and recent jobs, oldest first
+    pub jobs: Vec<Job>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SnapshotRequest {
     pub name: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SnapshotResponse {
     pub success: bool,
     pub path: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SnapshotInfo {
     pub name: String,
     pub created_at: i64,
     pub size_bytes: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ListSnapshotsResponse {
     pub snapshots: Vec<SnapshotInfo>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct RestoreSnapshotResponse {
     pub success: bool,
     pub vectors_restored: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HybridQueryRequest {
     pub vector: Vec<f32>,
     pub text_query: String,
@@ -245,20 +835,26 @@ pub struct HybridQueryRequest {
     pub filter: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct HealthCheckResponse {
     pub status: String,
     pub message: Option<String>,
+    /// A replica's lag behind its primary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replication: Option<ReplicaStatus>,
 }
 
 // Batch operations DTOs
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(tag = "op", rename_all = "snake_case")]
 pub enum BatchOperationDto {
     Upsert {
         id: String,
         vector: Vec<f32>,
         metadata: HashMap<String, serde_json::Value>,
+        /// Only write if the record is at this version (0: doesn't exist)
+        #[serde(default)]
+        expected_version: Option<u64>,
     },
     Delete {
         id: String,
@@ -273,14 +869,18 @@ pub enum BatchOperationDto {
         id: String,
         metadata: HashMap<String, serde_json::Value>,
     },
+    PatchMetadata {
+        id: String,
+        patch: HashMap<String, serde_json::Value>,
+    },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchExecuteRequest {
     pub operations: Vec<BatchOperationDto>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchExecuteResponse {
     pub succeeded: usize,
     pub failed: usize,
@@ -288,22 +888,25 @@ pub struct BatchExecuteResponse {
     pub duration_ms: f64,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct BatchErrorDto {
     pub index: usize,
     pub operation: String,
     pub error: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub version_conflict: Option<VersionConflict>,
 }
 
 // Query estimation DTOs
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct QueryEstimateRequest {
     pub vector: Vec<f32>,
     pub limit: i32,
     pub filter: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct QueryEstimateResponse {
     pub valid: bool,
     pub errors: Vec<String>,
@@ -319,18 +922,12 @@ pub struct QueryEstimateResponse {
 // Error handling
 // ============================================================================
 
+/// A handler error, answered with the [`ErrorResponse`] its type maps to
 struct ApiError(anyhow::Error);
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
-        let error_msg = format!("{}", self.0);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "error": error_msg
-            })),
-        )
-            .into_response()
+        ErrorResponse::from_error(&self.0).into_response()
     }
 }
 
@@ -347,61 +944,244 @@ where
 // Handler functions
 // ============================================================================
 
+/// Expected record version from an `If-Match` header (`3` or `"3"`)
+pub(super) fn if_match_version(headers: &HeaderMap) -> anyhow::Result<Option<u64>> {
+    let Some(value) = headers.get(axum::http::header::IF_MATCH) else {
+        return Ok(None);
+    };
+    let invalid = || {
+        ErrorResponse::new(
+            ErrorCode::InvalidArgument,
+            format!("If-Match must be a record version number, got {:?}", value),
+        )
+    };
+    let value = value.to_str().map_err(|_| invalid())?.trim();
+    let version = value.trim_matches('"').parse().map_err(|_| invalid())?;
+    Ok(Some(version))
+}
+
+/// The record an upsert request writes
+pub(super) fn request_record(req: UpsertRequest) -> Record {
+    let metadata = crate::store::Metadata {
+        fields: req.metadata,
+    };
+    Record {
+        text: req.text,
+        parent_id: req.parent_id,
+        chunk_index: req.chunk_index,
+        ..make_record(req.id, req.vector, metadata)
+    }
+}
+
+/// Upsert `record`, checking its expected version when it has one
+fn upsert_checked(
+    store: &mut VecStore,
+    record: Record,
+    expected_version: Option<u64>,
+) -> anyhow::Result<()> {
+    match expected_version {
+        Some(expected) => store.upsert_record_if_version(record, expected).map(|_| ()),
+        None => store.upsert_record(record),
+    }
+}
+
+/// Insert or replace a record
+#[utoipa::path(
+    post,
+    path = "/v1/upsert",
+    tag = "vectors",
+    request_body = UpsertRequest,
+    params(("If-Match" = Option<String>, Header, description = "Only write if the record is at this version (0: doesn't exist)")),
+    responses((status = 200, body = UpsertResponse), (status = 412, description = "The record is at another version", body = ErrorResponse)),
+)]
 async fn upsert(
     State(server): State<VecStoreHttpServer>,
+    headers: HeaderMap,
     Json(req): Json<UpsertRequest>,
 ) -> Result<Json<UpsertResponse>, ApiError> {
     let start = std::time::Instant::now();
 
-    let metadata = crate::store::Metadata {
-        fields: req.metadata,
-    };
-
+    let expected_version = if_match_version(&headers)?.or(req.expected_version);
+    let id = req.id.clone();
     let mut store = server.store.write().await;
-    store.upsert(req.id, req.vector, metadata)?;
+    upsert_checked(&mut store, request_record(req), expected_version)?;
+    let version = store.get(&id).map(|r| r.version);
 
     let duration = start.elapsed().as_secs_f64();
-    super::metrics::record_upsert(false);
-    super::metrics::record_request("/v1/upsert", "POST", duration);
+    super::metrics::record_upsert("http", false, duration);
 
     Ok(Json(UpsertResponse {
         success: true,
         error: None,
+        version,
     }))
 }
 
+/// Insert or replace a batch of records, with a result per record
+///
+/// Also served at `/v1/batch-upsert`.
+#[utoipa::path(
+    post,
+    path = "/v1/upsert/batch",
+    tag = "vectors",
+    request_body = BatchUpsertRequest,
+    responses((status = 200, body = BatchUpsertResponse), (status = 413, description = "More records than the batch size limit", body = ErrorResponse), (status = 422, description = "Records failed validation and `partial` was not set", body = ErrorResponse)),
+)]
 async fn batch_upsert(
     State(server): State<VecStoreHttpServer>,
     Json(req): Json<BatchUpsertRequest>,
 ) -> Result<Json<BatchUpsertResponse>, ApiError> {
     let start = std::time::Instant::now();
+    server.bulk_limits.check_batch(req.records.len())?;
 
-    let mut store = server.store.write().await;
-    let mut inserted = 0;
-    let mut errors = Vec::new();
+    let (records, expected_versions): (Vec<Record>, Vec<Option<u64>>) = req
+        .records
+        .into_iter()
+        .map(|r| {
+            let expected_version = r.expected_version;
+            (request_record(r), expected_version)
+        })
+        .unzip();
+    let ids: Vec<String> = records.iter().map(|r| r.id.clone()).collect();
+    let mut versions = vec![None; ids.len()];
+
+    // Validate the whole batch before the first write
+    let mut failed = server.store.read().await.validate_batch(&records);
+    if !failed.is_empty() && !req.partial {
+        return Err(BatchValidationError { errors: failed }.into());
+    }
 
-    for upsert_req in req.records {
-        let metadata = crate::store::Metadata {
-            fields: upsert_req.metadata,
-        };
+    let mut inserted = 0;
+    let rejected: HashSet<usize> = failed.iter().map(|e| e.index).collect();
 
-        match store.upsert(upsert_req.id.clone(), upsert_req.vector, metadata) {
-            Ok(_) => inserted += 1,
-            Err(e) => errors.push(format!("{}: {}", upsert_req.id, e)),
+    // Release the lock between chunks so queries aren't stalled for the whole batch
+    let mut records = records
+        .into_iter()
+        .zip(expected_versions)
+        .enumerate()
+        .peekable();
+    while records.peek().is_some() {
+        let mut store = server.store.write().await;
+        for (index, (record, expected_version)) in records
+            .by_ref()
+            .take(crate::store::DEFAULT_INGEST_CHUNK_SIZE)
+        {
+            if rejected.contains(&index) {
+                continue;
+            }
+            let id = record.id.clone();
+            match upsert_checked(&mut store, record, expected_version) {
+                Ok(_) => {
+                    inserted += 1;
+                    versions[index] = store.get(&id).map(|r| r.version);
+                }
+                Err(e) => failed.push(BatchItemError {
+                    index,
+                    id,
+                    reason: e.to_string(),
+                }),
+            }
         }
     }
+    failed.sort_by_key(|e| e.index);
 
     let duration = start.elapsed().as_secs_f64();
-    super::metrics::record_upsert(true);
-    super::metrics::record_request("/v1/batch-upsert", "POST", duration);
+    super::metrics::record_upsert("http", true, duration);
 
     Ok(Json(BatchUpsertResponse {
         inserted,
         updated: 0,
-        errors,
+        errors: failed
+            .iter()
+            .map(|e| format!("{}: {}", e.id, e.reason))
+            .collect(),
+        results: bulk::item_results(ids, versions, &failed),
+        failed,
     }))
 }
 
+/// Import JSONL records from the request body, streamed, answering with one
+/// NDJSON [`ImportEvent`] per rejected row and written batch, then `done`
+#[utoipa::path(
+    post,
+    path = "/v1/import",
+    tag = "vectors",
+    request_body(content = String, content_type = "application/x-ndjson", description = "One JSON record per line"),
+    responses((status = 200, body = ImportEvent, content_type = "application/x-ndjson", description = "One event per line"), (status = 413, description = "The body is over the import limit", body = ErrorResponse)),
+)]
+async fn import(
+    State(server): State<VecStoreHttpServer>,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
+    let limits = server.import_limits;
+    let length = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if length.is_some_and(|length| length > limits.max_body_bytes) {
+        return ErrorResponse::new(
+            ErrorCode::PayloadTooLarge,
+            format!(
+                "body exceeds the {} byte import limit",
+                limits.max_body_bytes
+            ),
+        )
+        .with_details(serde_json::json!({ "max_bytes": limits.max_body_bytes }))
+        .into_response();
+    }
+
+    let (tx, rx) = mpsc::channel::<ImportEvent>(64);
+    let mut session = ImportSession::new(server.store.clone(), limits);
+    tokio::spawn(async move {
+        let start = std::time::Instant::now();
+        let mut chunks = body.into_data_stream();
+        let mut events = Vec::new();
+        let error = loop {
+            let result = match chunks.next().await {
+                Some(Ok(chunk)) => session.feed(&chunk, &mut events).await,
+                Some(Err(e)) => Err(format!("failed to read the body: {}", e)),
+                None => break session.finish(&mut events).await.err(),
+            };
+            for event in events.drain(..) {
+                // The client hung up; stop reading its body
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+            if let Err(e) = result {
+                break Some(e);
+            }
+        };
+        events.push(ImportEvent::Done {
+            totals: session.totals(),
+            error,
+        });
+        for event in events {
+            let _ = tx.send(event).await;
+        }
+
+        let duration = start.elapsed().as_secs_f64();
+        super::metrics::record_upsert("http", true, duration);
+    });
+
+    let lines = tokio_stream::wrappers::ReceiverStream::new(rx)
+        .map(|event| serde_json::to_string(&event).map(|line| line + "\n"));
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response()
+}
+
+/// Run a mix of writes, reporting each failure
+#[utoipa::path(
+    post,
+    path = "/v1/batch-execute",
+    tag = "vectors",
+    request_body = BatchExecuteRequest,
+    responses((status = 200, body = BatchExecuteResponse)),
+)]
 async fn batch_execute(
     State(server): State<VecStoreHttpServer>,
     Json(req): Json<BatchExecuteRequest>,
@@ -415,11 +1195,23 @@ async fn batch_execute(
                 id,
                 vector,
                 metadata,
+                expected_version: None,
             } => crate::store::BatchOperation::Upsert {
                 id,
                 vector,
                 metadata: crate::store::Metadata { fields: metadata },
             },
+            BatchOperationDto::Upsert {
+                id,
+                vector,
+                metadata,
+                expected_version: Some(expected_version),
+            } => crate::store::BatchOperation::UpsertIfVersion {
+                id,
+                expected_version,
+                vector,
+                metadata: crate::store::Metadata { fields: metadata },
+            },
             BatchOperationDto::Delete { id } => crate::store::BatchOperation::Delete { id },
             BatchOperationDto::SoftDelete { id } => crate::store::BatchOperation::SoftDelete { id },
             BatchOperationDto::Restore { id } => crate::store::BatchOperation::Restore { id },
@@ -429,6 +1221,9 @@ async fn batch_execute(
                     metadata: crate::store::Metadata { fields: metadata },
                 }
             }
+            BatchOperationDto::PatchMetadata { id, patch } => {
+                crate::store::BatchOperation::PatchMetadata { id, patch }
+            }
         })
         .collect();
 
@@ -443,11 +1238,11 @@ async fn batch_execute(
             index: e.index,
             operation: e.operation,
             error: e.error,
+            version_conflict: e.version_conflict,
         })
         .collect();
 
-    super::metrics::record_upsert(true);
-    super::metrics::record_request("/v1/batch-execute", "POST", result.duration_ms / 1000.0);
+    super::metrics::record_upsert("http", true, result.duration_ms / 1000.0);
 
     Ok(Json(BatchExecuteResponse {
         succeeded: result.succeeded,
@@ -457,14 +1252,86 @@ async fn batch_execute(
     }))
 }
 
+/// Find the records nearest a vector
+#[utoipa::path(
+    post,
+    path = "/v1/query",
+    tag = "queries",
+    request_body = QueryRequest,
+    responses((status = 200, body = QueryResponse)),
+)]
 async fn query(
     State(server): State<VecStoreHttpServer>,
     Json(req): Json<QueryRequest>,
 ) -> Result<Json<QueryResponse>, ApiError> {
+    Ok(Json(run_vector_query(&server, req, "vector").await?))
+}
+
+/// Embed a text and find the records nearest it
+#[utoipa::path(
+    post,
+    path = "/v1/query/text",
+    tag = "queries",
+    request_body = TextQueryRequest,
+    responses((status = 200, body = QueryResponse)),
+)]
+async fn query_text(
+    State(server): State<VecStoreHttpServer>,
+    Json(req): Json<TextQueryRequest>,
+) -> Result<Json<QueryResponse>, ApiError> {
+    let embedding = server
+        .embedding
+        .as_ref()
+        .ok_or_else(embed::not_configured)?;
+    let vector = embedding.embed(vec![req.text]).await?.remove(0);
+    let req = QueryRequest {
+        vector,
+        limit: req.limit,
+        filter: req.filter,
+        exact: req.exact,
+        rerank: req.rerank,
+        profile: req.profile,
+        timeout_ms: req.timeout_ms,
+        options: req.options,
+    };
+    Ok(Json(run_vector_query(&server, req, "text").await?))
+}
+
+/// Embed texts with the server's model
+#[utoipa::path(
+    post,
+    path = "/v1/embed",
+    tag = "queries",
+    request_body = EmbedRequest,
+    responses((status = 200, body = EmbedResponse)),
+)]
+async fn embed_texts(
+    State(server): State<VecStoreHttpServer>,
+    Json(req): Json<EmbedRequest>,
+) -> Result<Json<EmbedResponse>, ApiError> {
+    let embedding = server
+        .embedding
+        .as_ref()
+        .ok_or_else(embed::not_configured)?;
+    let embeddings = embedding.embed(req.texts).await?;
+    Ok(Json(EmbedResponse {
+        model: embedding.model().to_string(),
+        dimension: embedding.dimension(),
+        embeddings,
+    }))
+}
+
+/// Run a query under the server's timeouts, recording it as a
+/// `query_type` query
+pub(super) async fn run_vector_query(
+    server: &VecStoreHttpServer,
+    req: QueryRequest,
+    query_type: &str,
+) -> anyhow::Result<QueryResponse> {
     let start = std::time::Instant::now();
 
     let filter = if let Some(ref filter_str) = req.filter {
-        Some(crate::store::parse_filter(filter_str)?)
+        Some(crate::store::FilterExpr::parse(filter_str)?)
     } else {
         None
     };
@@ -473,26 +1340,29 @@ async fn query(
         vector: req.vector,
         k: req.limit as usize,
         filter,
+        exact: req.exact,
+        profile: req.profile,
+        options: req.options.clone(),
+        ..Default::default()
     };
 
-    let store = server.store.read().await;
-
-    let neighbors = store.query(query)?;
+    let timeout = server.query_timeouts.resolve(req.timeout_ms);
+    let rerank = req.rerank;
+    let ProfiledResults { neighbors, profile } =
+        timeouts::run(server.store.clone(), timeout, move |store, cancel| {
+            run_query(store, query.with_cancel(cancel), rerank)
+        })
+        .await?;
 
     let duration = start.elapsed().as_secs_f64();
     let duration_ms = duration * 1000.0;
 
     // Record metrics
-    super::metrics::record_query("vector", neighbors.len(), duration);
-    super::metrics::record_request("/v1/query", "POST", duration);
+    super::metrics::record_query("http", query_type, neighbors.len(), duration);
 
     let results = neighbors
         .iter()
-        .map(|n| QueryResult {
-            id: n.id.clone(),
-            score: n.score,
-            metadata: n.metadata.fields.clone(),
-        })
+        .map(|n| QueryResult::from_neighbor(n, &req.options))
         .collect();
 
     let stats = Some(QueryStats {
@@ -501,9 +1371,21 @@ async fn query(
         duration_ms,
     });
 
-    Ok(Json(QueryResponse { results, stats }))
+    Ok(QueryResponse {
+        results,
+        stats,
+        profile,
+    })
 }
 
+/// Run a query, explaining each result's score
+#[utoipa::path(
+    post,
+    path = "/v1/query-explain",
+    tag = "queries",
+    request_body = QueryRequest,
+    responses((status = 200, body = QueryExplainResponse)),
+)]
 async fn query_explain(
     State(server): State<VecStoreHttpServer>,
     Json(req): Json<QueryRequest>,
@@ -511,7 +1393,7 @@ async fn query_explain(
     let start = std::time::Instant::now();
 
     let filter = if let Some(ref filter_str) = req.filter {
-        Some(crate::store::parse_filter(filter_str)?)
+        Some(crate::store::FilterExpr::parse(filter_str)?)
     } else {
         None
     };
@@ -520,6 +1402,8 @@ async fn query_explain(
         vector: req.vector,
         k: req.limit as usize,
         filter,
+        exact: req.exact,
+        ..Default::default()
     };
 
     let store = server.store.read().await;
@@ -530,8 +1414,12 @@ async fn query_explain(
     let duration_ms = duration * 1000.0;
 
     // Record metrics
-    super::metrics::record_query("vector_explain", explained_neighbors.len(), duration);
-    super::metrics::record_request("/v1/query-explain", "POST", duration);
+    super::metrics::record_query(
+        "http",
+        "vector_explain",
+        explained_neighbors.len(),
+        duration,
+    );
 
     let results = explained_neighbors
         .iter()
@@ -572,12 +1460,20 @@ async fn query_explain(
     Ok(Json(QueryExplainResponse { results, stats }))
 }
 
+/// Validate a query and estimate its cost without running it
+#[utoipa::path(
+    post,
+    path = "/v1/query-estimate",
+    tag = "queries",
+    request_body = QueryEstimateRequest,
+    responses((status = 200, body = QueryEstimateResponse)),
+)]
 async fn query_estimate(
     State(server): State<VecStoreHttpServer>,
     Json(req): Json<QueryEstimateRequest>,
 ) -> Result<Json<QueryEstimateResponse>, ApiError> {
     let filter = if let Some(ref filter_str) = req.filter {
-        Some(crate::store::parse_filter(filter_str)?)
+        Some(crate::store::FilterExpr::parse(filter_str)?)
     } else {
         None
     };
@@ -586,14 +1482,12 @@ async fn query_estimate(
         vector: req.vector,
         k: req.limit as usize,
         filter,
+        ..Default::default()
     };
 
     let store = server.store.read().await;
     let estimate = store.estimate_query(&query);
 
-    // Record metrics
-    super::metrics::record_request("/v1/query-estimate", "POST", 0.001); // Estimate is very fast
-
     Ok(Json(QueryEstimateResponse {
         valid: estimate.valid,
         errors: estimate.errors,
@@ -606,12 +1500,148 @@ async fn query_estimate(
     }))
 }
 
+/// Fetch a record
+#[utoipa::path(
+    get,
+    path = "/v1/get/{id}",
+    tag = "vectors",
+    params(("id" = String, Path, description = "Record id")),
+    responses((status = 200, body = GetResult)),
+)]
+async fn get_record(
+    State(server): State<VecStoreHttpServer>,
+    Path(id): Path<String>,
+) -> Result<Json<GetResult>, ApiError> {
+    let store = server.store.read().await;
+    Ok(Json(GetResult::from_lookup(&id, store.get(&id))))
+}
+
+/// Fetch a chunk with its neighbours in the same parent document
+#[utoipa::path(
+    get,
+    path = "/v1/parent-window/{id}",
+    tag = "vectors",
+    params(("id" = String, Path, description = "Record id"), ParentWindowParams),
+    responses((status = 200, body = GetResponse)),
+)]
+async fn parent_window(
+    State(server): State<VecStoreHttpServer>,
+    Path(id): Path<String>,
+    Query(params): Query<ParentWindowParams>,
+) -> Result<Json<GetResponse>, ApiError> {
+    let store = server.store.read().await;
+    let results = store
+        .fetch_parent_window(&id, params.n.unwrap_or(1))?
+        .into_iter()
+        .map(|record| GetResult::from_lookup(&id, Some(record)))
+        .collect();
+
+    Ok(Json(GetResponse { results }))
+}
+
+/// Fetch records by id
+#[utoipa::path(
+    post,
+    path = "/v1/get",
+    tag = "vectors",
+    request_body = GetRequest,
+    responses((status = 200, body = GetResponse)),
+)]
+async fn get_records(
+    State(server): State<VecStoreHttpServer>,
+    Json(req): Json<GetRequest>,
+) -> Result<Json<GetResponse>, ApiError> {
+    let store = server.store.read().await;
+    let results = req
+        .ids
+        .iter()
+        .map(|id| GetResult::from_lookup(id, store.get(id)))
+        .collect();
+
+    Ok(Json(GetResponse { results }))
+}
+
+/// Page through all records, e.g. to export them
+#[utoipa::path(
+    post,
+    path = "/v1/scan",
+    tag = "vectors",
+    request_body = ScanRequest,
+    responses((status = 200, body = ScanResponse)),
+)]
+async fn scan(
+    State(server): State<VecStoreHttpServer>,
+    Json(req): Json<ScanRequest>,
+) -> Result<Json<ScanResponse>, ApiError> {
+    let store = server.store.read().await;
+    let page = store.scan_after(req.after.as_deref(), req.limit);
+
+    let next_cursor = if req.limit > 0 && page.len() == req.limit {
+        page.last().map(|r| r.id.clone())
+    } else {
+        None
+    };
+
+    Ok(Json(ScanResponse {
+        records: page,
+        next_cursor,
+    }))
+}
+
+/// Merge into or replace a record's metadata
+#[utoipa::path(
+    post,
+    path = "/v1/metadata/{id}",
+    tag = "vectors",
+    params(("id" = String, Path, description = "Record id")),
+    request_body = UpdateMetadataRequest,
+    responses((status = 200, body = UpdateMetadataResponse)),
+)]
+async fn update_metadata(
+    State(server): State<VecStoreHttpServer>,
+    Path(id): Path<String>,
+    Json(req): Json<UpdateMetadataRequest>,
+) -> Result<Json<UpdateMetadataResponse>, ApiError> {
+    let mut store = server.store.write().await;
+    let result = if req.replace {
+        store.replace_metadata(
+            &id,
+            crate::store::Metadata {
+                fields: req.metadata,
+            },
+        )
+    } else {
+        store.update_metadata(&id, req.metadata)
+    };
+
+    Ok(Json(match result {
+        Ok(()) => UpdateMetadataResponse {
+            success: true,
+            error: None,
+        },
+        Err(e) => UpdateMetadataResponse {
+            success: false,
+            error: Some(e.to_string()),
+        },
+    }))
+}
+
+/// Delete a record
+#[utoipa::path(
+    delete,
+    path = "/v1/delete/{id}",
+    tag = "vectors",
+    params(("id" = String, Path, description = "Record id")),
+    responses((status = 200, body = DeleteResponse)),
+)]
 async fn delete_vector(
     State(server): State<VecStoreHttpServer>,
     Path(id): Path<String>,
 ) -> Result<Json<DeleteResponse>, ApiError> {
+    let start = std::time::Instant::now();
     let mut store = server.store.write().await;
     store.remove(&id)?;
+    super::metrics::record_delete("http", "hard", start.elapsed().as_secs_f64());
 
     Ok(Json(DeleteResponse {
         found: true,
@@ -619,12 +1649,45 @@ async fn delete_vector(
     }))
 }
 
+/// Delete the records with the given ids, or matching a filter
+#[utoipa::path(
+    post,
+    path = "/v1/delete",
+    tag = "vectors",
+    request_body = DeleteManyRequest,
+    responses((status = 200, body = DeleteSummary), (status = 400, description = "The filter matches more records than allowed without `force` (`too_many_matches`)", body = ErrorResponse), (status = 413, description = "More ids than the batch size limit", body = ErrorResponse)),
+)]
+async fn delete_many(
+    State(server): State<VecStoreHttpServer>,
+    Json(req): Json<DeleteManyRequest>,
+) -> Result<Json<DeleteSummary>, ApiError> {
+    let start = std::time::Instant::now();
+    let filter = req.filter.map(bulk::parse_filter).transpose()?;
+    let selector = DeleteSelector::new(req.ids, filter)?;
+
+    let mut store = server.store.write().await;
+    let summary = bulk::delete(&mut store, &selector, req.force, &server.bulk_limits)?;
+    super::metrics::record_delete("http", "bulk", start.elapsed().as_secs_f64());
+
+    Ok(Json(summary))
+}
+
+/// Mark a record deleted, keeping it until compaction
+#[utoipa::path(
+    post,
+    path = "/v1/soft-delete/{id}",
+    tag = "vectors",
+    params(("id" = String, Path, description = "Record id")),
+    responses((status = 200, body = SoftDeleteResponse)),
+)]
 async fn soft_delete(
     State(server): State<VecStoreHttpServer>,
     Path(id): Path<String>,
 ) -> Result<Json<SoftDeleteResponse>, ApiError> {
+    let start = std::time::Instant::now();
     let mut store = server.store.write().await;
     let marked = store.soft_delete(&id)?;
+    super::metrics::record_delete("http", "soft", start.elapsed().as_secs_f64());
 
     Ok(Json(SoftDeleteResponse {
         found: marked,
@@ -632,6 +1695,14 @@ async fn soft_delete(
     }))
 }
 
+/// Undo a soft delete
+#[utoipa::path(
+    post,
+    path = "/v1/restore/{id}",
+    tag = "vectors",
+    params(("id" = String, Path, description = "Record id")),
+    responses((status = 200, body = RestoreResponse)),
+)]
 async fn restore(
     State(server): State<VecStoreHttpServer>,
     Path(id): Path<String>,
@@ -645,32 +1716,121 @@ async fn restore(
     }))
 }
 
+/// Remove soft-deleted records for good
+#[utoipa::path(
+    post,
+    path = "/v1/compact",
+    tag = "database",
+    responses((status = 200, body = CompactResponse)),
+)]
 async fn compact(
     State(server): State<VecStoreHttpServer>,
 ) -> Result<Json<CompactResponse>, ApiError> {
     let mut store = server.store.write().await;
-    let removed_count = store.compact()?;
+    let report = store.compact()?;
 
     Ok(Json(CompactResponse {
-        removed_count: removed_count as i32,
-        freed_bytes: 0,
+        removed_count: report.removed_count as i32,
+        freed_bytes: report.bytes_reclaimed() as i64,
     }))
 }
 
+/// Count records, optionally matching a filter or grouped by a field
+#[utoipa::path(
+    get,
+    path = "/v1/count",
+    tag = "database",
+    params(CountParams),
+    responses((status = 200, body = CountResponse)),
+)]
+async fn count(
+    State(server): State<VecStoreHttpServer>,
+    Query(params): Query<CountParams>,
+) -> Result<Json<CountResponse>, ApiError> {
+    let filter = params
+        .filter
+        .as_deref()
+        .map(crate::store::FilterExpr::parse)
+        .transpose()?;
+
+    let store = server.store.read().await;
+    let groups = params
+        .group_by
+        .as_deref()
+        .map(|field| store.aggregate(filter.clone(), field));
+
+    Ok(Json(CountResponse {
+        count: store.count_where(filter),
+        groups,
+    }))
+}
+
+/// List the distinct values of a metadata field
+#[utoipa::path(
+    get,
+    path = "/v1/distinct",
+    tag = "database",
+    params(DistinctParams),
+    responses((status = 200, body = DistinctResponse)),
+)]
+async fn distinct_values(
+    State(server): State<VecStoreHttpServer>,
+    Query(params): Query<DistinctParams>,
+) -> Result<Json<DistinctResponse>, ApiError> {
+    let store = server.store.read().await;
+    let values = store.distinct_values(&params.field, params.limit.unwrap_or(100));
+
+    Ok(Json(DistinctResponse { values }))
+}
+
+/// Store size and index configuration
+#[utoipa::path(
+    get,
+    path = "/v1/stats",
+    tag = "database",
+    responses((status = 200, body = StatsResponse)),
+)]
 async fn get_stats(
     State(server): State<VecStoreHttpServer>,
 ) -> Result<Json<StatsResponse>, ApiError> {
     let store = server.store.read().await;
+    let stats = store.stats();
 
     Ok(Json(StatsResponse {
         total_vectors: store.len() as i64 + store.deleted_count() as i64,
         active_vectors: store.active_count() as i64,
         deleted_vectors: store.deleted_count() as i64,
-        dimension: store.dimension() as i32,
-        storage_bytes: 0,
+        storage_bytes: stats.disk_bytes as i64,
+        store: stats,
+    }))
+}
+
+/// The most recent slow queries
+#[utoipa::path(
+    get,
+    path = "/v1/slow-queries",
+    tag = "database",
+    responses((status = 200, body = SlowQueriesResponse)),
+)]
+async fn get_slow_queries(
+    State(server): State<VecStoreHttpServer>,
+) -> Result<Json<SlowQueriesResponse>, ApiError> {
+    let store = server.store.read().await;
+
+    Ok(Json(SlowQueriesResponse {
+        queries: store.slow_queries(),
+        total: store.slow_query_count(),
     }))
 }
 
+/// Save a named snapshot of the store
+#[utoipa::path(
+    post,
+    path = "/v1/snapshots",
+    tag = "snapshots",
+    request_body = SnapshotRequest,
+    responses((status = 200, body = SnapshotResponse)),
+)]
 async fn create_snapshot(
     State(server): State<VecStoreHttpServer>,
     Json(req): Json<SnapshotRequest>,
@@ -684,6 +1844,13 @@ async fn create_snapshot(
     }))
 }
 
+/// List the saved snapshots
+#[utoipa::path(
+    get,
+    path = "/v1/snapshots",
+    tag = "snapshots",
+    responses((status = 200, body = ListSnapshotsResponse)),
+)]
 async fn list_snapshots(
     State(server): State<VecStoreHttpServer>,
 ) -> Result<Json<ListSnapshotsResponse>, ApiError> {
@@ -702,6 +1869,14 @@ async fn list_snapshots(
     Ok(Json(ListSnapshotsResponse { snapshots }))
 }
 
+/// Replace the store's contents with a snapshot
+#[utoipa::path(
+    post,
+    path = "/v1/snapshots/{name}/restore",
+    tag = "snapshots",
+    params(("name" = String, Path, description = "Snapshot name")),
+    responses((status = 200, body = RestoreSnapshotResponse)),
+)]
 async fn restore_snapshot(
     State(server): State<VecStoreHttpServer>,
     Path(name): Path<String>,
@@ -715,32 +1890,41 @@ async fn restore_snapshot(
     }))
 }
 
+/// Find records by vector and keyword relevance
+#[utoipa::path(
+    post,
+    path = "/v1/hybrid-query",
+    tag = "queries",
+    request_body = HybridQueryRequest,
+    responses((status = 200, body = QueryResponse)),
+)]
 async fn hybrid_query(
     State(server): State<VecStoreHttpServer>,
     Json(req): Json<HybridQueryRequest>,
 ) -> Result<Json<QueryResponse>, ApiError> {
+    let start = std::time::Instant::now();
     let query = crate::store::HybridQuery {
         vector: req.vector,
         keywords: req.text_query,
         k: req.limit as usize,
         alpha: req.alpha.unwrap_or(0.7),
-        filter: req.filter.and_then(|f| crate::store::parse_filter(&f).ok()),
+        filter: req
+            .filter
+            .as_deref()
+            .map(crate::store::FilterExpr::parse)
+            .transpose()?,
     };
 
     let store = server.store.read().await;
-    let start = std::time::Instant::now();
-
     let neighbors = store.hybrid_query(query)?;
 
-    let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let duration = start.elapsed().as_secs_f64();
+    let duration_ms = duration * 1000.0;
+    super::metrics::record_query("http", "hybrid", neighbors.len(), duration);
 
     let results = neighbors
         .iter()
-        .map(|n| QueryResult {
-            id: n.id.clone(),
-            score: n.score,
-            metadata: n.metadata.fields.clone(),
-        })
+        .map(|n| QueryResult::from_neighbor(n, &QueryOptions::default()))
         .collect();
 
     let stats = Some(QueryStats {
@@ -749,16 +1933,37 @@ async fn hybrid_query(
         duration_ms,
     });
 
-    Ok(Json(QueryResponse { results, stats }))
+    Ok(Json(QueryResponse {
+        results,
+        stats,
+        profile: None,
+    }))
 }
 
-async fn health_check() -> Result<Json<HealthCheckResponse>, ApiError> {
+/// Whether the server is up
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses((status = 200, body = HealthCheckResponse)),
+)]
+async fn health_check(
+    State(server): State<VecStoreHttpServer>,
+) -> Result<Json<HealthCheckResponse>, ApiError> {
     Ok(Json(HealthCheckResponse {
         status: "healthy".to_string(),
         message: Some("VecStore server is running".to_string()),
+        replication: server.replica.as_ref().map(|replica| replica.status()),
     }))
 }
 
+/// Whether the store can serve requests
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses((status = 200, body = HealthCheckResponse)),
+)]
 async fn ready_check(
     State(server): State<VecStoreHttpServer>,
 ) -> Result<Json<HealthCheckResponse>, ApiError> {
@@ -768,20 +1973,302 @@ async fn ready_check(
     Ok(Json(HealthCheckResponse {
         status: "ready".to_string(),
         message: Some("VecStore server is ready to accept requests".to_string()),
+        replication: None,
     }))
 }
 
+/// The rate limits and query cap in force
+#[utoipa::path(
+    get,
+    path = "/v1/limits",
+    tag = "limits",
+    responses((status = 200, body = LimitsConfig)),
+)]
+async fn get_limits(State(server): State<VecStoreHttpServer>) -> Json<LimitsConfig> {
+    Json(server.limiter.config())
+}
+
+/// Replace the rate limits and query cap
+#[utoipa::path(
+    put,
+    path = "/v1/limits",
+    tag = "limits",
+    request_body = LimitsConfig,
+    responses((status = 200, body = LimitsConfig)),
+)]
+async fn put_limits(
+    State(server): State<VecStoreHttpServer>,
+    Json(config): Json<LimitsConfig>,
+) -> Response {
+    match server.limiter.set_config(config) {
+        Ok(()) => Json(server.limiter.config()).into_response(),
+        Err(e) => {
+            ErrorResponse::new(ErrorCode::InvalidArgument, format!("{:#}", e)).into_response()
+        }
+    }
+}
+
+/// Checkpoint the store now, even without unsaved changes
+#[utoipa::path(
+    post,
+    path = "/admin/save",
+    tag = "persistence",
+    responses((status = 200, body = SaveReport), (status = 500, description = "The save failed; `details` holds the report", body = ErrorResponse)),
+)]
+async fn save_now(State(server): State<VecStoreHttpServer>) -> Response {
+    let report = server
+        .checkpointer
+        .checkpoint(SaveTrigger::Manual, true)
+        .await;
+    match &report.error {
+        Some(error) => ErrorResponse::new(ErrorCode::Internal, error.clone())
+            .with_details(&report)
+            .into_response(),
+        None => Json(report).into_response(),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LastSaveResponse {
+    /// The last checkpoint that wrote the store or failed to, since the
+    /// server started
+    pub last_save: Option<SaveReport>,
+    /// Whether the store has changes not saved yet
+    pub dirty: bool,
+}
+
+/// The last checkpoint and whether there are unsaved changes
+#[utoipa::path(
+    get,
+    path = "/admin/last-save",
+    tag = "persistence",
+    responses((status = 200, body = LastSaveResponse)),
+)]
+async fn last_save(State(server): State<VecStoreHttpServer>) -> Json<LastSaveResponse> {
+    Json(LastSaveResponse {
+        last_save: server.checkpointer.last_save(),
+        dirty: server.checkpointer.is_dirty().await,
+    })
+}
+
+/// Back the store up in the background
+#[utoipa::path(
+    post,
+    path = "/admin/backup",
+    tag = "maintenance",
+    request_body = BackupRequest,
+    responses((status = 202, body = Job), (status = 409, description = "Another job is running", body = ErrorResponse)),
+)]
+async fn backup_job(
+    State(server): State<VecStoreHttpServer>,
+    Json(req): Json<BackupRequest>,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    let job = server
+        .jobs
+        .backup(req.path.map(std::path::PathBuf::from), req.compress)?;
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+/// Replace the store's contents with a backup on the server
+///
+/// Without `confirm`, checks the backup and returns a token; sending the
+/// token back as `confirm` starts the restore.
+#[utoipa::path(
+    post,
+    path = "/admin/restore",
+    tag = "maintenance",
+    request_body = RestoreRequest,
+    responses((status = 200, description = "The backup to confirm", body = RestorePlan), (status = 202, body = Job), (status = 409, description = "Another job is running", body = ErrorResponse)),
+)]
+async fn restore_job(
+    State(server): State<VecStoreHttpServer>,
+    Json(req): Json<RestoreRequest>,
+) -> Result<Response, ApiError> {
+    let path = std::path::PathBuf::from(req.path);
+    let Some(token) = req.confirm else {
+        let (manifest, confirm_token) = server.jobs.prepare_restore(path)?;
+        return Ok(Json(RestorePlan {
+            manifest,
+            confirm_token,
+            expires_in_secs: super::jobs::RESTORE_TOKEN_TTL.as_secs(),
+        })
+        .into_response());
+    };
+    let job = server.jobs.restore(path, &token)?;
+    Ok((StatusCode::ACCEPTED, Json(job)).into_response())
+}
+
+/// Remove soft-deleted records for good, in the background
+#[utoipa::path(
+    post,
+    path = "/admin/compact",
+    tag = "maintenance",
+    responses((status = 202, body = Job), (status = 409, description = "Another job is running", body = ErrorResponse)),
+)]
+async fn compact_job(
+    State(server): State<VecStoreHttpServer>,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    Ok((StatusCode::ACCEPTED, Json(server.jobs.compact()?)))
+}
+
+/// Rebuild the index without its ghost entries, in the background
+#[utoipa::path(
+    post,
+    path = "/admin/optimize",
+    tag = "maintenance",
+    responses((status = 202, body = Job), (status = 409, description = "Another job is running", body = ErrorResponse)),
+)]
+async fn optimize_job(
+    State(server): State<VecStoreHttpServer>,
+) -> Result<(StatusCode, Json<Job>), ApiError> {
+    Ok((StatusCode::ACCEPTED, Json(server.jobs.optimize()?)))
+}
+
+/// The running and recent maintenance jobs
+#[utoipa::path(
+    get,
+    path = "/admin/jobs",
+    tag = "maintenance",
+    responses((status = 200, body = ListJobsResponse)),
+)]
+async fn list_jobs(State(server): State<VecStoreHttpServer>) -> Json<ListJobsResponse> {
+    Json(ListJobsResponse {
+        jobs: server.jobs.list(),
+    })
+}
+
+/// A maintenance job's state and outcome
+#[utoipa::path(
+    get,
+    path = "/admin/jobs/{id}",
+    tag = "maintenance",
+    params(("id" = u64, Path, description = "Job id")),
+    responses((status = 200, body = Job)),
+)]
+async fn get_job(
+    State(server): State<VecStoreHttpServer>,
+    Path(id): Path<u64>,
+) -> Result<Json<Job>, ApiError> {
+    let job = server
+        .jobs
+        .get(id)
+        .ok_or_else(|| ErrorResponse::new(ErrorCode::NotFound, format!("no job {}", id)))?;
+    Ok(Json(job))
+}
+
+/// Download the file of a finished backup job that had no `path`
+#[utoipa::path(
+    get,
+    path = "/admin/jobs/{id}/download",
+    tag = "maintenance",
+    params(("id" = u64, Path, description = "Job id")),
+    responses((status = 200, content_type = "application/octet-stream", description = "The backup file")),
+)]
+async fn download_backup(
+    State(server): State<VecStoreHttpServer>,
+    Path(id): Path<u64>,
+) -> Result<Response, ApiError> {
+    let path = server.jobs.download(id)?;
+    let file = tokio::fs::File::open(&path).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"backup-{}.vsbak\"", id),
+            ),
+        ],
+        file_body(file, ()),
+    )
+        .into_response())
+}
+
+/// Stream `file` as a response body, holding on to `keep` until it is sent
+fn file_body<T: Send + 'static>(mut file: tokio::fs::File, keep: T) -> Body {
+    use tokio::io::AsyncReadExt;
+
+    let (tx, rx) = mpsc::channel::<std::io::Result<Vec<u8>>>(4);
+    tokio::spawn(async move {
+        let _keep = keep;
+        loop {
+            let mut chunk = vec![0; 64 * 1024];
+            let read = match file.read(&mut chunk).await {
+                Ok(0) => return,
+                Ok(n) => {
+                    chunk.truncate(n);
+                    Ok(chunk)
+                }
+                Err(e) => Err(e),
+            };
+            let failed = read.is_err();
+            // The client hung up, or the read failed and the body ends here
+            if tx.send(read).await.is_err() || failed {
+                return;
+            }
+        }
+    });
+    Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx))
+}
+
+/// Sequence number of the store's latest change, which replicas poll
+#[utoipa::path(
+    get,
+    path = "/admin/replication/sequence",
+    tag = "replication",
+    responses((status = 200, body = ReplicationSequence)),
+)]
+async fn replication_sequence(
+    State(server): State<VecStoreHttpServer>,
+) -> Json<ReplicationSequence> {
+    Json(ReplicationSequence {
+        seq: server.store.read().await.op_seq(),
+    })
+}
+
+/// A compressed backup of the whole store, which replicas download when
+/// the sequence moved; its manifest's `seq_end` is the sequence it covers
+#[utoipa::path(
+    get,
+    path = "/admin/replication/snapshot",
+    tag = "replication",
+    responses((status = 200, content_type = "application/octet-stream", description = "A backup file")),
+)]
+async fn replication_snapshot(
+    State(server): State<VecStoreHttpServer>,
+) -> Result<Response, ApiError> {
+    let staging = tempfile::Builder::new()
+        .prefix("vecstore-replication")
+        .tempdir()?;
+    let path = staging.path().join("snapshot.vsbak");
+    let store = server.store.clone().read_owned().await;
+    let output = path.clone();
+    tokio::task::spawn_blocking(move || store.backup_compressed(&output)).await??;
+
+    let file = tokio::fs::File::open(&path).await?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/octet-stream")],
+        file_body(file, staging),
+    )
+        .into_response())
+}
+
 /// Prometheus metrics endpoint
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses((status = 200, body = String, content_type = "text/plain", description = "Prometheus text format")),
+)]
 async fn metrics_endpoint(State(server): State<VecStoreHttpServer>) -> Result<String, ApiError> {
     // Update database statistics
     let store = server.store.read().await;
-    super::metrics::update_db_stats(
-        store.len() + store.deleted_count(),
-        store.active_count(),
-        store.deleted_count(),
-        store.dimension(),
-    );
+    super::metrics::update_store_stats(&store.stats());
+    super::metrics::update_slow_queries(store.slow_query_count(), store.slow_queries().last());
     drop(store);
+    if let Some(replica) = &server.replica {
+        let status = replica.status();
+        super::metrics::update_replication_lag(status.lag_operations, status.lag_seconds);
+    }
 
     // Encode metrics
     super::metrics::encode_metrics()
@@ -792,144 +2279,708 @@ async fn metrics_endpoint(State(server): State<VecStoreHttpServer>) -> Result<St
 // WebSocket streaming
 // ============================================================================
 
-/// WebSocket handler for streaming query results
+/// WebSocket handler for streaming store change events
+async fn events_ws(ws: WebSocketUpgrade, events: EventStream) -> impl IntoResponse {
+    let receiver = events.receiver();
+    ws.on_upgrade(move |socket| handle_event_stream(socket, receiver))
+}
+
+/// Send each event as a JSON text message until the client disconnects
+///
+/// A client that falls behind by more than the buffer is sent
+/// `{"event": "lagged", "missed": n}` and continues from the oldest event kept.
+async fn handle_event_stream(mut socket: WebSocket, mut events: broadcast::Receiver<StoreEvent>) {
+    super::metrics::websocket_connected();
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let text = match event {
+                    Ok(event) => match serde_json::to_string(&event) {
+                        Ok(json) => json,
+                        Err(e) => {
+                            tracing::error!("Failed to serialize event: {}", e);
+                            continue;
+                        }
+                    },
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        serde_json::json!({"event": "lagged", "missed": missed}).to_string()
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    super::metrics::websocket_disconnected();
+}
+
+/// WebSocket speaking the [`query_stream`] protocol
 async fn query_stream_ws(
     ws: WebSocketUpgrade,
     State(server): State<VecStoreHttpServer>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_query_stream(socket, server))
+    let events = server.events.clone();
+    let limits = server.stream_limits;
+    ws.on_upgrade(move |socket| query_stream::serve(socket, server, events, limits))
 }
 
-/// Handle WebSocket connection for query streaming
-async fn handle_query_stream(mut socket: WebSocket, server: VecStoreHttpServer) {
-    while let Some(msg) = socket.recv().await {
-        let msg = match msg {
-            Ok(msg) => msg,
-            Err(e) => {
-                tracing::error!("WebSocket error: {}", e);
-                break;
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    async fn post_query(router: Router, body: serde_json::Value) -> Vec<u8> {
+        let request = Request::post("/v1/query")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_query_options_shrink_payload() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = VecStore::open(dir.path()).unwrap();
+        for i in 0..20 {
+            let mut fields = HashMap::new();
+            fields.insert("title".to_string(), json!(format!("Document {i}")));
+            fields.insert("body".to_string(), json!("lorem ipsum ".repeat(50)));
+            fields.insert(
+                "author".to_string(),
+                json!({"name": "Ada", "bio": "x".repeat(200)}),
+            );
+            store
+                .upsert(
+                    format!("doc{i}"),
+                    vec![1.0, i as f32, 0.5, 0.25],
+                    crate::store::Metadata { fields },
+                )
+                .unwrap();
+        }
+        let router = VecStoreHttpServer::new(store).router();
+        let query = json!({"vector": [1.0, 0.0, 0.5, 0.25], "limit": 10});
+        let with = |options: serde_json::Value| {
+            let mut body = query.clone();
+            body.as_object_mut()
+                .unwrap()
+                .extend(options.as_object().unwrap().clone());
+            body
         };
 
-        match msg {
-            Message::Text(text) => {
-                // Parse query request from JSON
-                let req: Result<QueryRequest, _> = serde_json::from_str(&text);
-
-                match req {
-                    Ok(query_req) => {
-                        // Execute query
-                        let filter = if let Some(ref filter_str) = query_req.filter {
-                            match crate::store::parse_filter(filter_str) {
-                                Ok(f) => Some(f),
-                                Err(e) => {
-                                    let error_msg = serde_json::json!({
-                                        "error": format!("Invalid filter: {}", e)
-                                    });
-                                    if socket
-                                        .send(Message::Text(error_msg.to_string().into()))
-                                        .await
-                                        .is_err()
-                                    {
-                                        break;
-                                    }
-                                    continue;
-                                }
-                            }
-                        } else {
-                            None
-                        };
-
-                        let query = crate::store::Query {
-                            vector: query_req.vector,
-                            k: query_req.limit as usize,
-                            filter,
-                        };
-
-                        let store = server.store.read().await;
-                        let start = std::time::Instant::now();
-
-                        match store.query(query) {
-                            Ok(neighbors) => {
-                                let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
-                                let total_results = neighbors.len();
-
-                                // Stream results one by one
-                                for neighbor in &neighbors {
-                                    let result = QueryResult {
-                                        id: neighbor.id.clone(),
-                                        score: neighbor.score,
-                                        metadata: neighbor.metadata.fields.clone(),
-                                    };
-
-                                    let result_json = match serde_json::to_string(&result) {
-                                        Ok(json) => json,
-                                        Err(e) => {
-                                            tracing::error!("Failed to serialize result: {}", e);
-                                            break;
-                                        }
-                                    };
-
-                                    if socket
-                                        .send(Message::Text(result_json.into()))
-                                        .await
-                                        .is_err()
-                                    {
-                                        break;
-                                    }
-                                }
-
-                                // Send completion message with stats
-                                let completion = serde_json::json!({
-                                    "complete": true,
-                                    "stats": {
-                                        "duration_ms": duration_ms,
-                                        "total_results": total_results
-                                    }
-                                });
-
-                                if socket
-                                    .send(Message::Text(completion.to_string().into()))
-                                    .await
-                                    .is_err()
-                                {
-                                    break;
-                                }
-                            }
-                            Err(e) => {
-                                let error_msg = serde_json::json!({
-                                    "error": format!("Query failed: {}", e)
-                                });
-                                if socket
-                                    .send(Message::Text(error_msg.to_string().into()))
-                                    .await
-                                    .is_err()
-                                {
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        let error_msg = serde_json::json!({
-                            "error": format!("Invalid query request: {}", e)
-                        });
-                        if socket
-                            .send(Message::Text(error_msg.to_string().into()))
-                            .await
-                            .is_err()
-                        {
-                            break;
-                        }
-                    }
-                }
+        let full = post_query(router.clone(), query.clone()).await;
+        let ids_only = post_query(router.clone(), with(json!({"include_metadata": false}))).await;
+        let one_field = post_query(
+            router.clone(),
+            with(json!({"metadata_fields": ["author.name"]})),
+        )
+        .await;
+        let vectors = post_query(
+            router,
+            with(json!({"include_metadata": false, "include_vector": true})),
+        )
+        .await;
+
+        // Full results are unchanged: metadata and no vector
+        let full: QueryResponse = serde_json::from_slice(&full).unwrap();
+        assert_eq!(full.results.len(), 10);
+        assert!(full.results.iter().all(|r| r.vector.is_none()));
+        let full_size = serde_json::to_vec(&full).unwrap().len();
+
+        assert!(ids_only.len() * 10 < full_size, "{} bytes", ids_only.len());
+        let ids_only: serde_json::Value = serde_json::from_slice(&ids_only).unwrap();
+        assert!(ids_only["results"][0].get("metadata").is_none());
+
+        assert!(
+            one_field.len() * 10 < full_size,
+            "{} bytes",
+            one_field.len()
+        );
+        let one_field: QueryResponse = serde_json::from_slice(&one_field).unwrap();
+        assert_eq!(
+            one_field.results[0].metadata,
+            Some(HashMap::from([(
+                "author".to_string(),
+                json!({"name": "Ada"})
+            )]))
+        );
+
+        let vectors: QueryResponse = serde_json::from_slice(&vectors).unwrap();
+        assert_eq!(vectors.results[0].vector.as_ref().map(Vec::len), Some(4));
+    }
+
+    async fn post_json(
+        router: Router,
+        path: &str,
+        if_match: Option<&str>,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::post(path).header("content-type", "application/json");
+        if let Some(version) = if_match {
+            request = request.header("if-match", version);
+        }
+        let request = request.body(Body::from(body.to_string())).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_if_match_upsert() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap()).router();
+        let doc =
+            |title: &str| json!({"id": "doc1", "vector": [1.0, 0.0], "metadata": {"title": title}});
+
+        // "0" creates only if the id is free
+        let (status, body) = post_json(router.clone(), "/v1/upsert", Some("0"), doc("a")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["version"], 1);
+        let (status, _) = post_json(router.clone(), "/v1/upsert", Some("0"), doc("b")).await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+
+        let (status, body) = post_json(router.clone(), "/v1/upsert", Some("\"1\""), doc("c")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["version"], 2);
+
+        // A writer still holding version 1 is turned away
+        let (status, body) = post_json(router.clone(), "/v1/upsert", Some("1"), doc("d")).await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED);
+        assert_eq!(body["code"], "version_conflict");
+        assert_eq!(body["details"]["actual"], 2);
+
+        let (status, body) = post_json(router.clone(), "/v1/upsert", Some("two"), doc("e")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "invalid_argument");
+
+        let (_, body) = post_json(
+            router.clone(),
+            "/v1/query",
+            None,
+            json!({"vector": [1.0, 0.0], "limit": 1}),
+        )
+        .await;
+        assert_eq!(body["results"][0]["version"], 2);
+        assert_eq!(body["results"][0]["metadata"]["title"], "c");
+
+        // Batches report each conflict and apply the rest
+        let (status, body) = post_json(
+            router,
+            "/v1/batch-execute",
+            None,
+            json!({"operations": [
+                {"op": "upsert", "id": "doc1", "vector": [0.0, 1.0], "metadata": {}, "expected_version": 1},
+                {"op": "upsert", "id": "doc1", "vector": [0.0, 1.0], "metadata": {}, "expected_version": 2},
+                {"op": "upsert", "id": "doc2", "vector": [0.0, 1.0], "metadata": {}},
+            ]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["succeeded"], 2);
+        assert_eq!(body["errors"][0]["index"], 0);
+        assert_eq!(
+            body["errors"][0]["version_conflict"],
+            json!({"id": "doc1", "expected": 1, "actual": 2})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_upsert_reports_invalid_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap());
+        let router = server.router();
+        let batch = |partial: bool| {
+            json!({
+                "partial": partial,
+                "records": [
+                    {"id": "a", "vector": [1.0, 0.0], "metadata": {}},
+                    {"id": "b", "vector": [1.0, 0.0, 0.0], "metadata": {}},
+                    {"id": "c", "vector": [0.0, 1.0], "metadata": {}},
+                ],
+            })
+        };
+
+        // Rejected whole, naming the bad record
+        let (status, body) =
+            post_json(router.clone(), "/v1/batch-upsert", None, batch(false)).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "validation_failed");
+        assert_eq!(body["details"]["failed"][0]["index"], 1);
+        assert_eq!(body["details"]["failed"][0]["id"], "b");
+        assert_eq!(server.store().read().await.len(), 0);
+
+        let (status, body) = post_json(router, "/v1/upsert/batch", None, batch(true)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["inserted"], 2);
+        assert_eq!(body["failed"][0]["index"], 1);
+        assert_eq!(body["errors"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            body["results"],
+            json!([
+                {"id": "a", "version": 1},
+                {"id": "b", "error": "vector dimension mismatch: expected 2, got 3"},
+                {"id": "c", "version": 1},
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bulk_delete_by_ids_and_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap()).with_bulk_limits(
+            BulkLimits {
+                max_batch_size: 3,
+                max_delete_matches: 2,
+            },
+        );
+        let router = server.router();
+        let records: Vec<_> = (0..6)
+            .map(|i| {
+                let status = if i < 3 { "draft" } else { "final" };
+                json!({"id": format!("doc{i}"), "vector": [1.0, i as f32], "metadata": {"status": status}})
+            })
+            .collect();
+        let (status, body) = post_json(
+            router.clone(),
+            "/v1/upsert/batch",
+            None,
+            json!({ "records": records }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["code"], "batch_too_large");
+        for chunk in records.chunks(3) {
+            let (status, _) = post_json(
+                router.clone(),
+                "/v1/upsert/batch",
+                None,
+                json!({ "records": chunk }),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        let (status, body) = post_json(
+            router.clone(),
+            "/v1/delete",
+            None,
+            json!({"ids": ["doc0", "missing"]}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["deleted"], 1);
+
+        // Three matches are over the limit of two until forced
+        let finals = json!({"Cmp": {"field": "status", "op": "Eq", "value": "final"}});
+        let (status, body) = post_json(
+            router.clone(),
+            "/v1/delete",
+            None,
+            json!({ "filter": finals }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "too_many_matches");
+        assert_eq!(body["details"]["matched"], 3);
+        assert_eq!(server.store().read().await.len(), 5);
+        let (_, body) = post_json(
+            router.clone(),
+            "/v1/delete",
+            None,
+            json!({"filter": finals, "force": true}),
+        )
+        .await;
+        assert_eq!(body["deleted"], 3);
+
+        let (_, body) = post_json(
+            router.clone(),
+            "/v1/delete",
+            None,
+            json!({"filter": "status = 'draft'"}),
+        )
+        .await;
+        assert_eq!(body, json!({"matched": 2, "deleted": 2}));
+        assert_eq!(server.store().read().await.len(), 0);
+
+        let (status, body) = post_json(router, "/v1/delete", None, json!({})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "invalid_argument");
+    }
+
+    #[tokio::test]
+    async fn test_import_streams_progress_and_rejections() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap());
+        let body = [
+            json!({"id": "a", "vector": [1.0, 0.0], "metadata": {"tag": "x"}}),
+            json!({"id": "b", "vector": [1.0, 0.0, 0.0], "metadata": {}}),
+            json!({"id": "c", "vector": [0.0, 1.0], "metadata": {}}),
+        ]
+        .iter()
+        .map(|line| line.to_string() + "\n")
+        .collect::<String>();
+
+        let request = Request::post("/v1/import")
+            .body(Body::from(body.clone()))
+            .unwrap();
+        let response = server.router().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let events: Vec<ImportEvent> = bytes
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        assert!(matches!(
+            &events[0],
+            ImportEvent::Rejected { line: 2, id: Some(id), .. } if id == "b"
+        ));
+        assert!(matches!(
+            events.last(),
+            Some(ImportEvent::Done { totals, error: None }) if totals.accepted == 2 && totals.rejected == 1
+        ));
+        assert_eq!(server.store().read().await.len(), 2);
+
+        let router = server
+            .with_import_limits(ImportLimits {
+                max_body_bytes: 16,
+                ..ImportLimits::default()
+            })
+            .router();
+        let request = Request::post("/v1/import")
+            .header("content-length", body.len())
+            .body(Body::from(body))
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_stats_include_store_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap());
+        let router = server.router();
+        let doc = json!({"id": "a", "vector": [1.0, 0.0], "metadata": {"title": "x"}});
+        let (status, _) = post_json(router.clone(), "/v1/upsert", None, doc).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let request = Request::get("/v1/stats").body(Body::empty()).unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["active_vectors"], 1);
+        assert_eq!(body["dimension"], 2);
+        assert!(body["metadata_bytes"].as_u64().unwrap() > 0);
+        assert!(body["index_link_bytes"].as_u64().unwrap() > 0);
+        assert!(body["last_saved_at"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_query_profile_and_slow_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = VecStore::open(dir.path()).unwrap();
+        store.set_slow_query_threshold(Some(std::time::Duration::ZERO));
+        let router = VecStoreHttpServer::new(store).router();
+        let doc = json!({"id": "a", "vector": [1.0, 0.0], "metadata": {"tag": "x"}});
+        let (status, _) = post_json(router.clone(), "/v1/upsert", None, doc).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let query = json!({"vector": [1.0, 0.0], "limit": 1, "filter": "tag = 'x'"});
+        let (_, body) = post_json(router.clone(), "/v1/query", None, query.clone()).await;
+        assert!(body.get("profile").is_none());
+
+        let mut profiled = query;
+        profiled["profile"] = json!(true);
+        let (_, body) = post_json(router.clone(), "/v1/query", None, profiled).await;
+        assert_eq!(body["profile"]["results"], 1);
+        assert_eq!(body["profile"]["exact"], true);
+
+        let request = Request::get("/v1/slow-queries")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["queries"][0]["filter"], "tag = 'x'");
+    }
+
+    #[tokio::test]
+    async fn test_query_timeout_answers_504_and_is_logged() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap()).router();
+        let doc = json!({"id": "a", "vector": [1.0, 0.0], "metadata": {}});
+        post_json(router.clone(), "/v1/upsert", None, doc).await;
+
+        let query = json!({"vector": [1.0, 0.0], "limit": 1, "timeout_ms": 0});
+        let (status, body) = post_json(router.clone(), "/v1/query", None, query).await;
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+        assert_eq!(body["code"], "deadline_exceeded");
+        assert!(body["details"]["elapsed_ms"].is_u64());
+
+        let query = json!({"vector": [1.0, 0.0], "limit": 1});
+        let (status, _) = post_json(router.clone(), "/v1/query", None, query).await;
+        assert_eq!(status, StatusCode::OK);
+
+        // The timed-out query stops on its own thread after answering
+        for _ in 0..100 {
+            let request = Request::get("/v1/slow-queries")
+                .body(Body::empty())
+                .unwrap();
+            let response = router.clone().oneshot(request).await.unwrap();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+            if body["total"] == 1 {
+                assert_eq!(body["queries"][0]["timed_out"], true);
+                return;
             }
-            Message::Close(_) => {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("timed-out query was not logged");
+    }
+
+    #[tokio::test]
+    async fn test_backup_job_download_and_restore_confirmation() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap()).router();
+        let doc = json!({"id": "a", "vector": [1.0, 0.0], "metadata": {}});
+        post_json(router.clone(), "/v1/upsert", None, doc).await;
+
+        let (status, job) = post_json(router.clone(), "/admin/backup", None, json!({})).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(job["state"], "running");
+        let id = job["id"].as_u64().unwrap();
+
+        let mut job = job;
+        for _ in 0..500 {
+            let request = Request::get(format!("/admin/jobs/{}", id))
+                .body(Body::empty())
+                .unwrap();
+            let response = router.clone().oneshot(request).await.unwrap();
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            job = serde_json::from_slice(&bytes).unwrap();
+            if job["state"] != "running" {
                 break;
             }
-            _ => {
-                // Ignore other message types (binary, ping, pong)
-            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
         }
+        assert_eq!(job["state"], "succeeded");
+        assert_eq!(job["result"]["record_count"], 1);
+
+        let request = Request::get(format!("/admin/jobs/{}/download", id))
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let backup = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(backup.starts_with(b"VSBACKUP"));
+
+        let path = dir.path().join("downloaded.vsbak");
+        std::fs::write(&path, &backup).unwrap();
+        let restore = json!({"path": path});
+        let (status, plan) = post_json(router.clone(), "/admin/restore", None, restore).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(plan["manifest"]["record_count"], 1);
+        let wrong = json!({"path": path, "confirm": "not-the-token"});
+        let (status, body) = post_json(router.clone(), "/admin/restore", None, wrong).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["code"], "failed_precondition");
+        let confirmed = json!({"path": path, "confirm": plan["confirm_token"]});
+        let (status, job) = post_json(router, "/admin/restore", None, confirmed).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(job["kind"], "restore");
+    }
+
+    #[tokio::test]
+    async fn test_text_and_parent_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap()).router();
+        let chunks: Vec<_> = (0..4)
+            .map(|i| {
+                json!({
+                    "id": format!("doc#{i}"),
+                    "vector": [1.0, i as f32],
+                    "metadata": {},
+                    "text": format!("part {i}"),
+                    "parent_id": "doc",
+                    "chunk_index": i,
+                })
+            })
+            .collect();
+        let (status, _) = post_json(
+            router.clone(),
+            "/v1/batch-upsert",
+            None,
+            json!({"records": chunks}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let query = json!({"vector": [1.0, 0.0], "limit": 1, "include_text": true});
+        let (_, body) = post_json(router.clone(), "/v1/query", None, query).await;
+        assert_eq!(body["results"][0]["text"], "part 0");
+
+        let request = Request::get("/v1/parent-window/doc%232?n=1")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let ids: Vec<_> = body["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(ids, ["doc#1", "doc#2", "doc#3"]);
+        assert_eq!(body["results"][0]["text"], "part 1");
+        assert_eq!(body["results"][0]["parent_id"], "doc");
+    }
+
+    #[tokio::test]
+    async fn test_event_socket_only_when_enabled() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = VecStore::open(dir.path()).unwrap();
+        let events = EventStream::attach(&mut store);
+        let server = VecStoreHttpServer::new(store);
+        let get_events = || Request::get("/ws/events").body(Body::empty()).unwrap();
+
+        let response = server.router().oneshot(get_events()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // Routed, but a plain GET is not a WebSocket handshake
+        let router = server.with_events(events).router();
+        let response = router.oneshot(get_events()).await.unwrap();
+        assert_ne!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.status().is_client_error());
+    }
+
+    #[tokio::test]
+    async fn test_limits_turn_requests_away_and_change_at_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let router = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap()).router();
+        let put_limits = |limits: serde_json::Value| {
+            Request::put("/v1/limits")
+                .header("content-type", "application/json")
+                .body(Body::from(limits.to_string()))
+                .unwrap()
+        };
+        let get = |path: &str| Request::get(path).body(Body::empty()).unwrap();
+
+        let invalid = put_limits(json!({"global": {"burst": 0}}));
+        let response = router.clone().oneshot(invalid).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let limits = put_limits(json!({"global": {"requests_per_second": 1.0}}));
+        let response = router.clone().oneshot(limits).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = router.clone().oneshot(get("/v1/stats")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = router.clone().oneshot(get("/v1/stats")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "rate_limited");
+
+        // Health and metrics are exempt, and count the rejection
+        let response = router.clone().oneshot(get("/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = router.oneshot(get("/metrics")).await.unwrap();
+        let metrics = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let metrics = String::from_utf8(metrics.to_vec()).unwrap();
+        assert!(metrics
+            .lines()
+            .any(|line| line.starts_with("vecstore_requests_rejected_total{")
+                && line.contains("reason=\"rate_limited\"")));
+    }
+
+    #[tokio::test]
+    async fn test_admin_save_and_last_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap());
+        let router = server.router();
+        let json_body = |response: Response| async move {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+        let last_save = || {
+            Request::get("/admin/last-save")
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let body = json_body(router.clone().oneshot(last_save()).await.unwrap()).await;
+        assert!(body["last_save"].is_null());
+        assert_eq!(body["dirty"], false);
+
+        server
+            .store()
+            .write()
+            .await
+            .upsert(
+                "a".into(),
+                vec![1.0, 0.0],
+                crate::store::Metadata {
+                    fields: HashMap::new(),
+                },
+            )
+            .unwrap();
+        let body = json_body(router.clone().oneshot(last_save()).await.unwrap()).await;
+        assert_eq!(body["dirty"], true);
+
+        let save = Request::post("/admin/save").body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(save).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let report = json_body(response).await;
+        assert_eq!(report["saved"], true);
+        assert_eq!(report["trigger"], "manual");
+        assert_eq!(report["records"], 1);
+
+        let body = json_body(router.oneshot(last_save()).await.unwrap()).await;
+        assert_eq!(body["dirty"], false);
+        assert_eq!(body["last_save"]["seq"], report["seq"]);
+
+        drop(server);
+        assert!(VecStore::open(dir.path()).unwrap().contains("a"));
     }
 }
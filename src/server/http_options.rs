@@ -0,0 +1,299 @@
+//! CORS, response compression and request body limits for the HTTP servers
+//!
+//! By default browsers may call the API only from pages served on
+//! localhost, responses are gzip or brotli compressed when the client's
+//! `Accept-Encoding` allows it, and request bodies are capped at 16 MiB. A
+//! body over the cap gets a `payload_too_large` error before the handler runs.
+//! `/v1/import` streams its body and keeps its own `--import-max-bytes`
+//! limit.
+
+use anyhow::{bail, Context, Result};
+use axum::{
+    extract::{DefaultBodyLimit, Request, State},
+    http::{header, HeaderName, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router,
+};
+use tower_http::compression::predicate::{DefaultPredicate, NotForContentType, Predicate};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use super::auth;
+use super::errors::{self, ErrorCode, ErrorResponse};
+use super::request_id;
+
+/// Largest request body accepted by default, in bytes
+pub const DEFAULT_MAX_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Origins allowed by default: pages served from this machine, on any port
+pub const DEFAULT_CORS_ORIGINS: &[&str] =
+    &["http://localhost:*", "http://127.0.0.1:*", "http://[::1]:*"];
+
+/// Methods cross-origin requests may use by default
+pub const DEFAULT_CORS_METHODS: &[&str] = &["GET", "POST", "PUT", "DELETE"];
+
+/// Request headers cross-origin requests may send by default
+pub const DEFAULT_CORS_HEADERS: &[&str] =
+    &["content-type", "authorization", "x-api-key", "if-match"];
+
+/// Which cross-origin requests browsers may make
+#[derive(Debug, Clone)]
+pub struct Cors {
+    /// Allowed origins, methods and headers; `None` allows any
+    origins: Option<Vec<String>>,
+    methods: Option<Vec<Method>>,
+    headers: Option<Vec<HeaderName>>,
+}
+
+/// `None` when `values` include the `*` wildcard, else each value parsed
+fn parse_list<T>(
+    values: &[impl AsRef<str>],
+    parse: impl Fn(&str) -> Result<T>,
+) -> Result<Option<Vec<T>>> {
+    let values: Vec<&str> = values.iter().map(|v| v.as_ref().trim()).collect();
+    if values.contains(&"*") {
+        return Ok(None);
+    }
+    values
+        .into_iter()
+        .map(parse)
+        .collect::<Result<_>>()
+        .map(Some)
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self::parse(
+            DEFAULT_CORS_ORIGINS,
+            DEFAULT_CORS_METHODS,
+            DEFAULT_CORS_HEADERS,
+        )
+        .expect("default CORS settings are valid")
+    }
+}
+
+impl Cors {
+    /// Allow `origins`, `methods` and `headers`
+    ///
+    /// An origin is `scheme://host[:port]`, and `host:*` matches the host on
+    /// any port. `*` in a list allows anything.
+    pub fn parse(
+        origins: &[impl AsRef<str>],
+        methods: &[impl AsRef<str>],
+        headers: &[impl AsRef<str>],
+    ) -> Result<Self> {
+        let origins = parse_list(origins, |origin| {
+            let origin = origin.trim_end_matches('/');
+            let base = origin.strip_suffix(":*").unwrap_or(origin);
+            let Some((_, host)) = base
+                .split_once("://")
+                .filter(|(scheme, _)| matches!(*scheme, "http" | "https"))
+            else {
+                bail!(
+                    "CORS origin {:?} must start with http:// or https://",
+                    origin
+                );
+            };
+            if host.is_empty() || host.contains('/') {
+                bail!("CORS origin {:?} must be scheme://host[:port]", origin);
+            }
+            Ok(origin.to_string())
+        })?;
+        let methods = parse_list(methods, |method| {
+            Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                .with_context(|| format!("invalid CORS method {:?}", method))
+        })?;
+        let headers = parse_list(headers, |name| {
+            HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("invalid CORS header {:?}", name))
+        })?;
+        Ok(Self {
+            origins,
+            methods,
+            headers,
+        })
+    }
+
+    /// Allow any origin, method and header, for development
+    pub fn permissive() -> Self {
+        Self::parse(&["*"], &["*"], &["*"]).expect("wildcards are valid")
+    }
+
+    /// Whether a request from `origin` is allowed
+    pub fn allows(&self, origin: &str) -> bool {
+        let Some(origins) = &self.origins else {
+            return true;
+        };
+        origins
+            .iter()
+            .any(|allowed| match allowed.strip_suffix(":*") {
+                Some(base) => {
+                    origin.eq_ignore_ascii_case(base)
+                        || origin
+                            .get(..base.len())
+                            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(base))
+                            && origin[base.len()..].strip_prefix(':').is_some_and(|port| {
+                                !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit())
+                            })
+                }
+                None => origin.eq_ignore_ascii_case(allowed),
+            })
+    }
+
+    fn layer(&self) -> CorsLayer {
+        let cors = self.clone();
+        let origin = match self.origins {
+            None => AllowOrigin::any(),
+            Some(_) => AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+                origin.to_str().is_ok_and(|origin| cors.allows(origin))
+            }),
+        };
+        let layer = CorsLayer::new()
+            .allow_origin(origin)
+            .expose_headers([header::RETRY_AFTER]);
+        let layer = match &self.methods {
+            None => layer.allow_methods(Any),
+            Some(methods) => layer.allow_methods(methods.clone()),
+        };
+        match &self.headers {
+            None => layer.allow_headers(Any),
+            Some(headers) => layer.allow_headers(headers.clone()),
+        }
+    }
+}
+
+/// CORS, compression, body size and API docs settings of an HTTP server
+#[derive(Debug, Clone)]
+pub struct HttpOptions {
+    pub cors: Cors,
+    /// Compress responses the client accepts gzip or brotli for
+    pub compression: bool,
+    /// Largest request body accepted, in bytes
+    pub max_body_bytes: usize,
+    /// Serve Swagger UI for the server's `/openapi.json` at `/docs`
+    pub swagger_ui: bool,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            cors: Cors::default(),
+            compression: true,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            swagger_ui: false,
+        }
+    }
+}
+
+/// Whether a route's body is held to the limit, from its matched route
+pub(crate) type BodyPolicy = fn(&str) -> bool;
+
+/// State of the [`limit_body`] route layer
+#[derive(Clone)]
+pub(crate) struct BodyLimitLayer {
+    pub max_bytes: usize,
+    pub policy: BodyPolicy,
+}
+
+impl HttpOptions {
+    /// Hold the bodies of the routes `policy` picks to the size limit
+    pub(crate) fn limit_bodies<S>(&self, router: Router<S>, policy: BodyPolicy) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let layer = BodyLimitLayer {
+            max_bytes: self.max_body_bytes,
+            policy,
+        };
+        router
+            .route_layer(DefaultBodyLimit::max(self.max_body_bytes))
+            .route_layer(middleware::from_fn_with_state(layer, limit_body))
+    }
+
+    /// Wrap a finished router in the error envelope, request id,
+    /// compression and CORS layers
+    pub(crate) fn apply(&self, router: Router) -> Router {
+        let router = router
+            .layer(middleware::from_fn(errors::envelope))
+            .layer(middleware::from_fn(request_id::trace_http));
+        let router = if self.compression {
+            // Import progress streams line by line; buffering it for the
+            // compressor would hold the lines back
+            let predicate =
+                DefaultPredicate::new().and(NotForContentType::new("application/x-ndjson"));
+            router.layer(CompressionLayer::new().compress_when(predicate))
+        } else {
+            router
+        };
+        router.layer(self.cors.layer())
+    }
+}
+
+/// Route layer turning away bodies over the size limit with 413
+///
+/// A declared `Content-Length` over the limit is refused before the body
+/// is read. Chunked bodies are cut off by the JSON extractor once they pass
+/// it, and the rejection is rewritten to the same 413 body.
+pub(crate) async fn limit_body(
+    State(layer): State<BodyLimitLayer>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = auth::matched_route(&request);
+    if !(layer.policy)(&route) {
+        return next.run(request).await;
+    }
+    let length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+    if length.is_some_and(|length| length > layer.max_bytes as u64) {
+        return too_large(layer.max_bytes);
+    }
+
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        return too_large(layer.max_bytes);
+    }
+    response
+}
+
+fn too_large(max_bytes: usize) -> Response {
+    ErrorResponse::new(
+        ErrorCode::PayloadTooLarge,
+        format!("request body exceeds the {} byte limit", max_bytes),
+    )
+    .with_details(serde_json::json!({ "max_bytes": max_bytes }))
+    .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_origin_patterns() {
+        let cors = Cors::default();
+        assert!(cors.allows("http://localhost:3000"));
+        assert!(cors.allows("http://localhost"));
+        assert!(cors.allows("http://127.0.0.1:5173"));
+        assert!(!cors.allows("http://localhost.evil.com"));
+        assert!(!cors.allows("http://localhost:3000.evil.com"));
+        assert!(!cors.allows("https://example.com"));
+
+        let cors = Cors::parse(&["https://app.example.com/"], &["get"], &["x-api-key"]).unwrap();
+        assert!(cors.allows("https://app.example.com"));
+        assert!(!cors.allows("http://app.example.com"));
+        assert!(Cors::permissive().allows("https://anything.example"));
+
+        assert!(Cors::parse(&["app.example.com"], &["GET"], &["x-api-key"]).is_err());
+        assert!(Cors::parse(&["https://a.com/path"], &["GET"], &["x-api-key"]).is_err());
+        assert!(Cors::parse(&["*"], &["GET"], &["bad header"]).is_err());
+    }
+}
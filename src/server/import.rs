@@ -0,0 +1,297 @@
+//! Server-side JSONL import, shared by `POST /v1/import` and the
+//! `ImportStream` RPC
+//!
+//! Lines are parsed as the body arrives, in the record shape `vecstore export`
+//! writes, and written in batches. Each batch is validated and written under
+//! one write lock that is released before the next batch, so queries keep
+//! being served while a large file loads.
+
+use crate::import_export::parse_jsonl_record;
+use crate::store::{Record, VecStore, DEFAULT_INGEST_CHUNK_SIZE};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use utoipa::ToSchema;
+
+/// Limits on a single import
+#[derive(Debug, Clone, Copy)]
+pub struct ImportLimits {
+    /// Largest body accepted, in bytes
+    pub max_body_bytes: u64,
+    /// Most rows accepted; the import stops at the first one past it
+    pub max_rows: usize,
+    /// Rows written per write lock
+    pub batch_size: usize,
+}
+
+impl Default for ImportLimits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 1 << 30,
+            max_rows: 10_000_000,
+            batch_size: DEFAULT_INGEST_CHUNK_SIZE,
+        }
+    }
+}
+
+/// Row counts of an import so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ImportTotals {
+    /// Non-blank lines read
+    pub rows: usize,
+    /// Rows written to the store
+    pub accepted: usize,
+    /// Rows that were not written
+    pub rejected: usize,
+}
+
+/// One line of the `/v1/import` NDJSON response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ImportEvent {
+    /// A row that was not written
+    Rejected {
+        /// Line number in the body, from 1
+        line: usize,
+        /// The row's id, when it parsed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        reason: String,
+    },
+    /// Totals after a batch was written
+    Progress(ImportTotals),
+    /// Final totals, with the reason when the import stopped early
+    Done {
+        #[serde(flatten)]
+        totals: ImportTotals,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+/// An import in progress: JSONL bytes in, batches written to the store
+pub struct ImportSession {
+    store: Arc<RwLock<VecStore>>,
+    limits: ImportLimits,
+    /// Bytes after the last newline, waiting for the rest of their line
+    partial_line: Vec<u8>,
+    bytes: u64,
+    line: usize,
+    batch: Vec<(usize, Record)>,
+    totals: ImportTotals,
+}
+
+impl ImportSession {
+    pub fn new(store: Arc<RwLock<VecStore>>, limits: ImportLimits) -> Self {
+        Self {
+            store,
+            limits,
+            partial_line: Vec::new(),
+            bytes: 0,
+            line: 0,
+            batch: Vec::new(),
+            totals: ImportTotals::default(),
+        }
+    }
+
+    /// Row counts so far
+    pub fn totals(&self) -> ImportTotals {
+        self.totals
+    }
+
+    /// Read the next chunk of the body, writing each batch it completes
+    ///
+    /// Rejected rows and progress are appended to `events`. Errors name the
+    /// limit the chunk broke; the batches written before it are kept.
+    pub async fn feed(
+        &mut self,
+        chunk: &[u8],
+        events: &mut Vec<ImportEvent>,
+    ) -> Result<(), String> {
+        self.bytes += chunk.len() as u64;
+        if self.bytes > self.limits.max_body_bytes {
+            return Err(format!(
+                "body exceeds the {} byte import limit",
+                self.limits.max_body_bytes
+            ));
+        }
+
+        let mut rest = chunk;
+        while let Some(end) = rest.iter().position(|&b| b == b'\n') {
+            self.partial_line.extend_from_slice(&rest[..end]);
+            rest = &rest[end + 1..];
+            let line = std::mem::take(&mut self.partial_line);
+            self.push_line(&line, events).await?;
+        }
+        self.partial_line.extend_from_slice(rest);
+        Ok(())
+    }
+
+    /// Read the last line, if it had no newline, and write the last batch
+    pub async fn finish(&mut self, events: &mut Vec<ImportEvent>) -> Result<(), String> {
+        if !self.partial_line.is_empty() {
+            let line = std::mem::take(&mut self.partial_line);
+            self.push_line(&line, events).await?;
+        }
+        self.write_batch(events).await;
+        Ok(())
+    }
+
+    async fn push_line(
+        &mut self,
+        line: &[u8],
+        events: &mut Vec<ImportEvent>,
+    ) -> Result<(), String> {
+        self.line += 1;
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return Ok(());
+        }
+        if self.totals.rows >= self.limits.max_rows {
+            return Err(format!(
+                "import exceeds the {} row limit",
+                self.limits.max_rows
+            ));
+        }
+        self.totals.rows += 1;
+
+        let parsed = std::str::from_utf8(line)
+            .map_err(|_| anyhow::anyhow!("invalid UTF-8"))
+            .and_then(parse_jsonl_record);
+        match parsed {
+            Ok(record) => self.batch.push((self.line, record)),
+            Err(e) => self.reject(self.line, None, format!("{:#}", e), events),
+        }
+        if self.batch.len() >= self.limits.batch_size.max(1) {
+            self.write_batch(events).await;
+        }
+        Ok(())
+    }
+
+    /// Validate and write the pending batch under one write lock
+    async fn write_batch(&mut self, events: &mut Vec<ImportEvent>) {
+        if self.batch.is_empty() {
+            return;
+        }
+        let (lines, records): (Vec<usize>, Vec<Record>) = self.batch.drain(..).unzip();
+
+        let store = self.store.clone();
+        let mut store = store.write().await;
+        let invalid = store.validate_batch(&records);
+        let rejected: HashSet<usize> = invalid.iter().map(|e| e.index).collect();
+        for e in invalid {
+            self.reject(lines[e.index], Some(e.id), e.reason, events);
+        }
+        let (lines, records): (Vec<usize>, Vec<Record>) = lines
+            .into_iter()
+            .zip(records)
+            .enumerate()
+            .filter(|(index, _)| !rejected.contains(index))
+            .map(|(_, row)| row)
+            .unzip();
+        let ids: Vec<String> = records.iter().map(|r| r.id.clone()).collect();
+        let result = store.batch_upsert(records);
+        drop(store);
+
+        match result {
+            Ok(()) => self.totals.accepted += ids.len(),
+            Err(e) => {
+                let reason = format!("{:#}", e);
+                for (line, id) in lines.into_iter().zip(ids) {
+                    self.reject(line, Some(id), reason.clone(), events);
+                }
+            }
+        }
+        events.push(ImportEvent::Progress(self.totals));
+    }
+
+    fn reject(
+        &mut self,
+        line: usize,
+        id: Option<String>,
+        reason: String,
+        events: &mut Vec<ImportEvent>,
+    ) {
+        self.totals.rejected += 1;
+        events.push(ImportEvent::Rejected { line, id, reason });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_session(limits: ImportLimits) -> (tempfile::TempDir, ImportSession) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = VecStore::open(dir.path()).unwrap();
+        let session = ImportSession::new(Arc::new(RwLock::new(store)), limits);
+        (dir, session)
+    }
+
+    #[tokio::test]
+    async fn test_lines_split_across_chunks_are_imported_in_batches() {
+        let limits = ImportLimits {
+            batch_size: 2,
+            ..ImportLimits::default()
+        };
+        let (_dir, mut session) = new_session(limits);
+        let body = concat!(
+            "{\"id\": \"a\", \"vector\": [1.0, 0.0], \"metadata\": {\"k\": 1}}\n",
+            "not json\n",
+            "\n",
+            "{\"id\": \"b\", \"vector\": [0.0, 1.0], \"metadata\": {}}\n",
+            "{\"id\": \"c\", \"vector\": [1.0], \"metadata\": {}}\n",
+            "{\"id\": \"d\", \"vector\": [1.0, 1.0], \"metadata\": {}}",
+        );
+
+        let mut events = Vec::new();
+        for chunk in body.as_bytes().chunks(7) {
+            session.feed(chunk, &mut events).await.unwrap();
+        }
+        session.finish(&mut events).await.unwrap();
+
+        let totals = session.totals();
+        assert_eq!(
+            totals,
+            ImportTotals {
+                rows: 5,
+                accepted: 3,
+                rejected: 2
+            }
+        );
+        let rejected: Vec<(usize, Option<String>)> = events
+            .iter()
+            .filter_map(|event| match event {
+                ImportEvent::Rejected { line, id, .. } => Some((*line, id.clone())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rejected, [(2, None), (5, Some("c".to_string()))]);
+        assert!(matches!(events.last(), Some(ImportEvent::Progress(t)) if *t == totals));
+
+        let store = session.store.read().await;
+        assert_eq!(store.get("a").unwrap().metadata.fields["k"], 1);
+        assert!(store.get("d").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_limits_stop_the_import() {
+        let limits = ImportLimits {
+            max_rows: 1,
+            ..ImportLimits::default()
+        };
+        let (_dir, mut session) = new_session(limits);
+        let mut events = Vec::new();
+        let body = b"{\"id\": \"a\", \"vector\": [1.0], \"metadata\": {}}\n{\"id\": \"b\", \"vector\": [1.0], \"metadata\": {}}\n";
+        let error = session.feed(body, &mut events).await.unwrap_err();
+        assert!(error.contains("1 row limit"), "{}", error);
+
+        let limits = ImportLimits {
+            max_body_bytes: 8,
+            ..ImportLimits::default()
+        };
+        let (_dir, mut session) = new_session(limits);
+        let error = session.feed(body, &mut events).await.unwrap_err();
+        assert!(error.contains("8 byte"), "{}", error);
+    }
+}
@@ -0,0 +1,452 @@
+//! Maintenance jobs started through the admin API
+//!
+//! `POST /admin/backup`, `/admin/restore`, `/admin/compact` and
+//! `/admin/optimize` each start a job in the background and answer 202 with
+//! it, so a long operation doesn't hold the request open. `GET
+//! /admin/jobs/{id}` reports how it went. Jobs run one at a time: starting
+//! one while another runs fails with `job_running`, naming the running job.
+//!
+//! A backup given no server-side `path` is written to a temporary directory
+//! and downloaded from `GET /admin/jobs/{id}/download`. Restoring replaces
+//! every record, so it takes two calls: the first checks the backup and
+//! returns a confirmation token, which the second sends back within
+//! [`RESTORE_TOKEN_TTL`] to start the job.
+
+use super::errors::{ErrorCode, ErrorResponse};
+use crate::store::VecStore;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use utoipa::ToSchema;
+
+/// Finished jobs kept for `GET /admin/jobs/{id}`; older ones are dropped first
+pub const MAX_FINISHED_JOBS: usize = 100;
+
+/// How long a restore confirmation token stays valid
+pub const RESTORE_TOKEN_TTL: Duration = Duration::from_secs(300);
+
+/// What a job does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Backup,
+    Restore,
+    Compact,
+    Optimize,
+}
+
+/// Where a job is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// A maintenance job and its outcome
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Job {
+    pub id: u64,
+    pub kind: JobKind,
+    pub state: JobState,
+    /// When the job started, as a Unix timestamp
+    pub started_at: i64,
+    /// When the job finished, as a Unix timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// What the job produced: a backup's manifest, or the compaction or
+    /// optimization report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// The backup file served by `/admin/jobs/{id}/download`
+    #[serde(skip)]
+    download: Option<PathBuf>,
+}
+
+/// A restore waiting for its confirmation
+struct PendingRestore {
+    path: PathBuf,
+    expires: Instant,
+}
+
+#[derive(Default)]
+struct JobsInner {
+    next_id: u64,
+    /// Oldest first; at most one is running, and it is the newest
+    jobs: VecDeque<Job>,
+    restores: HashMap<String, PendingRestore>,
+}
+
+/// Runs the maintenance jobs of one store, one at a time
+pub struct Jobs {
+    store: Arc<RwLock<VecStore>>,
+    inner: Mutex<JobsInner>,
+    /// Where backups without a `path` go, created on first use
+    downloads: Mutex<Option<tempfile::TempDir>>,
+}
+
+impl Jobs {
+    pub fn new(store: Arc<RwLock<VecStore>>) -> Self {
+        Self {
+            store,
+            inner: Mutex::new(JobsInner {
+                next_id: 1,
+                ..JobsInner::default()
+            }),
+            downloads: Mutex::new(None),
+        }
+    }
+
+    fn inner(&self) -> std::sync::MutexGuard<'_, JobsInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// The job with `id`, while it is kept
+    pub fn get(&self, id: u64) -> Option<Job> {
+        self.inner().jobs.iter().find(|job| job.id == id).cloned()
+    }
+
+    /// The kept jobs, oldest first
+    pub fn list(&self) -> Vec<Job> {
+        self.inner().jobs.iter().cloned().collect()
+    }
+
+    /// The file of a finished backup job written for download
+    pub fn download(&self, id: u64) -> Result<PathBuf, ErrorResponse> {
+        let job = self.get(id).ok_or_else(|| job_not_found(id))?;
+        if job.state == JobState::Running {
+            return Err(ErrorResponse::new(
+                ErrorCode::FailedPrecondition,
+                format!("job {} is still running", id),
+            ));
+        }
+        job.download.ok_or_else(|| {
+            ErrorResponse::new(
+                ErrorCode::NotFound,
+                format!("job {} has no backup to download", id),
+            )
+        })
+    }
+
+    /// Back the store up to `path` on the server, or with no `path` to a
+    /// file served by [`download`](Self::download)
+    pub fn backup(
+        self: &Arc<Self>,
+        path: Option<PathBuf>,
+        compress: bool,
+    ) -> Result<Job, ErrorResponse> {
+        let store = self.store.clone();
+        let jobs = self.clone();
+        self.start(JobKind::Backup, path.is_none(), move |id| async move {
+            let output = match path {
+                Some(path) => path,
+                None => jobs.download_path(id)?,
+            };
+            let store = store.read_owned().await;
+            let manifest = blocking(move || {
+                if compress {
+                    store.backup_compressed(&output)
+                } else {
+                    store.backup(&output)
+                }
+            })
+            .await?;
+            Ok(serde_json::to_value(manifest)?)
+        })
+    }
+
+    /// Check the backup at `path` and issue the token that confirms
+    /// restoring it, returning its manifest and the token
+    pub fn prepare_restore(
+        &self,
+        path: PathBuf,
+    ) -> Result<(serde_json::Value, String), ErrorResponse> {
+        let manifest = VecStore::backup_info(&path)
+            .map_err(|e| ErrorResponse::new(ErrorCode::InvalidArgument, format!("{:#}", e)))?;
+        let token = format!("{:032x}", rand::random::<u128>());
+        let mut inner = self.inner();
+        let now = Instant::now();
+        inner.restores.retain(|_, pending| pending.expires > now);
+        inner.restores.insert(
+            token.clone(),
+            PendingRestore {
+                path,
+                expires: now + RESTORE_TOKEN_TTL,
+            },
+        );
+        let manifest = serde_json::to_value(manifest)
+            .map_err(|e| ErrorResponse::new(ErrorCode::Internal, e.to_string()))?;
+        Ok((manifest, token))
+    }
+
+    /// Replace the store's contents with the backup at `path`, given the
+    /// token [`prepare_restore`](Self::prepare_restore) issued for it
+    ///
+    /// The token is used up even when the restore can't start.
+    pub fn restore(self: &Arc<Self>, path: PathBuf, token: &str) -> Result<Job, ErrorResponse> {
+        let pending = self.inner().restores.remove(token);
+        match pending {
+            Some(pending) if pending.path == path && pending.expires > Instant::now() => {}
+            _ => {
+                return Err(ErrorResponse::new(
+                    ErrorCode::FailedPrecondition,
+                    "confirmation token is invalid or expired; request a new one without `confirm`",
+                ))
+            }
+        }
+        let store = self.store.clone();
+        self.start(JobKind::Restore, false, move |_| async move {
+            let mut store = store.write_owned().await;
+            let manifest = blocking(move || store.restore_backup_in_place(&path)).await?;
+            Ok(serde_json::to_value(manifest)?)
+        })
+    }
+
+    /// Remove soft-deleted records for good
+    pub fn compact(self: &Arc<Self>) -> Result<Job, ErrorResponse> {
+        let store = self.store.clone();
+        self.start(JobKind::Compact, false, move |_| async move {
+            let mut store = store.write_owned().await;
+            let report = blocking(move || store.compact()).await?;
+            Ok(serde_json::json!({
+                "removed_count": report.removed_count,
+                "ghost_entries_removed": report.ghost_entries_removed,
+                "freed_bytes": report.bytes_reclaimed(),
+            }))
+        })
+    }
+
+    /// Rebuild the index without its ghost entries
+    pub fn optimize(self: &Arc<Self>) -> Result<Job, ErrorResponse> {
+        let store = self.store.clone();
+        self.start(JobKind::Optimize, false, move |_| async move {
+            let mut store = store.write_owned().await;
+            let removed = blocking(move || store.optimize()).await?;
+            Ok(serde_json::json!({ "ghost_entries_removed": removed }))
+        })
+    }
+
+    /// Record a job and run `work` in the background, unless a job is
+    /// already running
+    fn start<F, Fut>(
+        self: &Arc<Self>,
+        kind: JobKind,
+        download: bool,
+        work: F,
+    ) -> Result<Job, ErrorResponse>
+    where
+        F: FnOnce(u64) -> Fut,
+        Fut: Future<Output = anyhow::Result<serde_json::Value>> + Send + 'static,
+    {
+        let job = {
+            let mut inner = self.inner();
+            if let Some(running) = inner.jobs.iter().find(|j| j.state == JobState::Running) {
+                return Err(ErrorResponse::new(
+                    ErrorCode::JobRunning,
+                    format!("{:?} job {} is still running", running.kind, running.id),
+                )
+                .with_details(serde_json::json!({
+                    "job_id": running.id,
+                    "kind": running.kind,
+                })));
+            }
+            let id = inner.next_id;
+            inner.next_id += 1;
+            let job = Job {
+                id,
+                kind,
+                state: JobState::Running,
+                started_at: unix_now(),
+                finished_at: None,
+                duration_ms: None,
+                result: None,
+                error: None,
+                download: None,
+            };
+            inner.jobs.push_back(job.clone());
+            job
+        };
+
+        info!("🔧 Started {:?} job {}", kind, job.id);
+        let id = job.id;
+        let task = tokio::spawn(work(id));
+        let jobs = self.clone();
+        tokio::spawn(async move {
+            let started = Instant::now();
+            let outcome = match task.await {
+                Ok(outcome) => outcome,
+                Err(e) => Err(anyhow::anyhow!("Job task failed: {}", e)),
+            };
+            let download = download.then(|| jobs.download_path(id).ok()).flatten();
+            jobs.finish(id, outcome, download, started.elapsed());
+        });
+        Ok(job)
+    }
+
+    fn finish(
+        &self,
+        id: u64,
+        outcome: anyhow::Result<serde_json::Value>,
+        download: Option<PathBuf>,
+        duration: Duration,
+    ) {
+        let mut inner = self.inner();
+        if let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) {
+            job.finished_at = Some(unix_now());
+            job.duration_ms = Some(duration.as_millis() as u64);
+            match outcome {
+                Ok(result) => {
+                    info!("🔧 {:?} job {} finished in {:?}", job.kind, id, duration);
+                    job.state = JobState::Succeeded;
+                    job.result = Some(result);
+                    job.download = download;
+                }
+                Err(e) => {
+                    warn!("{:?} job {} failed: {:#}", job.kind, id, e);
+                    job.state = JobState::Failed;
+                    job.error = Some(format!("{:#}", e));
+                    if let Some(path) = download {
+                        let _ = std::fs::remove_file(path);
+                    }
+                }
+            }
+        }
+        while inner.jobs.len() > MAX_FINISHED_JOBS {
+            if let Some(path) = inner.jobs.pop_front().and_then(|job| job.download) {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+
+    /// Where the backup of job `id` is written for download
+    fn download_path(&self, id: u64) -> anyhow::Result<PathBuf> {
+        let mut downloads = self.downloads.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = match downloads.as_ref() {
+            Some(dir) => dir,
+            None => downloads.insert(
+                tempfile::Builder::new()
+                    .prefix("vecstore-backups")
+                    .tempdir()?,
+            ),
+        };
+        Ok(backup_file(dir.path(), id))
+    }
+}
+
+fn backup_file(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("backup-{}.vsbak", id))
+}
+
+fn job_not_found(id: u64) -> ErrorResponse {
+    ErrorResponse::new(ErrorCode::NotFound, format!("no job {}", id))
+}
+
+/// Run `work` on a blocking thread
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| anyhow::anyhow!("Job task failed: {}", e))?
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Metadata;
+
+    async fn wait(jobs: &Jobs, id: u64) -> Job {
+        for _ in 0..500 {
+            let job = jobs.get(id).unwrap();
+            if job.state != JobState::Running {
+                return job;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job {} did not finish", id);
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_confirmed_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = VecStore::open(dir.path().join("store")).unwrap();
+        store
+            .upsert(
+                "a".into(),
+                vec![1.0, 0.0],
+                Metadata {
+                    fields: HashMap::new(),
+                },
+            )
+            .unwrap();
+        let store = Arc::new(RwLock::new(store));
+        let jobs = Arc::new(Jobs::new(store.clone()));
+
+        let path = dir.path().join("store.vsbak");
+        let job = jobs.backup(Some(path.clone()), false).unwrap();
+        let job = wait(&jobs, job.id).await;
+        assert_eq!(job.state, JobState::Succeeded, "{:?}", job.error);
+        assert_eq!(job.result.unwrap()["record_count"], 1);
+
+        store
+            .write()
+            .await
+            .upsert(
+                "b".into(),
+                vec![0.0, 1.0],
+                Metadata {
+                    fields: HashMap::new(),
+                },
+            )
+            .unwrap();
+        assert!(jobs.restore(path.clone(), "guess").is_err());
+        let (_, token) = jobs.prepare_restore(path.clone()).unwrap();
+        let job = jobs.restore(path.clone(), &token).unwrap();
+        assert_eq!(wait(&jobs, job.id).await.state, JobState::Succeeded);
+        assert_eq!(store.read().await.count(), 1);
+
+        // Tokens are single use
+        assert!(jobs.restore(path, &token).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_jobs_run_one_at_a_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(RwLock::new(VecStore::open(dir.path()).unwrap()));
+        let jobs = Arc::new(Jobs::new(store.clone()));
+
+        let lock = store.write().await;
+        let first = jobs.compact().unwrap();
+        let error = jobs.optimize().unwrap_err();
+        assert_eq!(error.code, ErrorCode::JobRunning);
+        assert_eq!(error.details.unwrap()["job_id"], first.id);
+        drop(lock);
+
+        assert_eq!(wait(&jobs, first.id).await.state, JobState::Succeeded);
+        let download = jobs.backup(None, true).unwrap();
+        wait(&jobs, download.id).await;
+        assert!(jobs.download(download.id).unwrap().exists());
+        assert_eq!(
+            jobs.download(first.id).unwrap_err().code,
+            ErrorCode::NotFound
+        );
+    }
+}
@@ -0,0 +1,484 @@
+//! Rate limits and query concurrency caps for the HTTP and gRPC servers
+//!
+//! Each client gets a token bucket refilled at `requests_per_second` and
+//! holding up to `burst` requests. A client is its API key when keys are on,
+//! and its IP address otherwise. Queries also take one of
+//! `max_concurrent_queries` slots, shared by all clients, until they finish.
+//! A request over either limit gets 429 with `Retry-After`, or
+//! `RESOURCE_EXHAUSTED` with `retry-after` metadata over gRPC. It is counted
+//! in `vecstore_requests_rejected_total` on `/metrics`.
+//!
+//! In multi-tenant mode, an entry of `namespaces` replaces the global limits
+//! for that namespace's routes, with buckets and query slots of its own.
+//! Limits load from `vecstore-server --limits` and can be replaced at runtime
+//! through `PUT /v1/limits` or `PUT /admin/limits`.
+
+use super::auth::{self, Principal};
+use super::errors::{ErrorCode, ErrorResponse};
+use super::metrics;
+use anyhow::{Context, Result};
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+/// Buckets idle this long are dropped once there are many of them
+const BUCKET_IDLE: Duration = Duration::from_secs(60);
+
+/// Bucket count past which idle buckets are dropped
+const MAX_BUCKETS: usize = 10_000;
+
+/// One set of limits; unset fields don't limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Limit {
+    /// Sustained requests per second, per client
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_second: Option<f64>,
+    /// Requests a client may send at once after being idle (default: one
+    /// second's worth)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burst: Option<u32>,
+    /// Queries in flight at once, across clients
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_queries: Option<usize>,
+}
+
+impl Limit {
+    fn validate(&self) -> Result<()> {
+        if let Some(rps) = self.requests_per_second {
+            if !(rps.is_finite() && rps > 0.0) {
+                return Err(anyhow::anyhow!(
+                    "requests_per_second must be positive, got {}",
+                    rps
+                ));
+            }
+        }
+        if self.burst == Some(0) {
+            return Err(anyhow::anyhow!("burst must be at least 1"));
+        }
+        if self.max_concurrent_queries == Some(0) {
+            return Err(anyhow::anyhow!("max_concurrent_queries must be at least 1"));
+        }
+        Ok(())
+    }
+
+    /// Tokens a full bucket holds
+    fn capacity(&self, rps: f64) -> f64 {
+        self.burst.map(f64::from).unwrap_or(rps.ceil()).max(1.0)
+    }
+}
+
+/// Server-wide limits and per-namespace overrides, as the `--limits` file
+/// and `/v1/limits` hold them
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct LimitsConfig {
+    #[serde(default)]
+    pub global: Limit,
+    /// Limits replacing `global` for a namespace's routes (multi-tenant mode)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub namespaces: BTreeMap<String, Limit>,
+}
+
+impl LimitsConfig {
+    /// Read a limits file's JSON
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read limits from {}", path.display()))?;
+        let config: Self = serde_json::from_str(&json).context("Invalid limits JSON")?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject zero or negative limits
+    pub fn validate(&self) -> Result<()> {
+        self.global.validate().context("Invalid global limits")?;
+        for (namespace, limit) in &self.namespaces {
+            limit
+                .validate()
+                .with_context(|| format!("Invalid limits for namespace '{}'", namespace))?;
+        }
+        Ok(())
+    }
+
+    /// The limits that apply to `namespace`'s routes, or server-wide ones
+    pub fn limit(&self, namespace: Option<&str>) -> Limit {
+        namespace
+            .and_then(|namespace| self.namespaces.get(namespace))
+            .copied()
+            .unwrap_or(self.global)
+    }
+}
+
+/// Which limit turned a request away
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleReason {
+    /// The client's bucket is empty
+    RateLimited,
+    /// All query slots are taken
+    TooManyQueries,
+}
+
+impl ThrottleReason {
+    fn as_str(self) -> &'static str {
+        match self {
+            ThrottleReason::RateLimited => "rate_limited",
+            ThrottleReason::TooManyQueries => "too_many_queries",
+        }
+    }
+}
+
+/// A request over a limit, sent as an [`ErrorResponse`] whose details hold
+/// `retry_after_secs`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Throttled {
+    pub error: String,
+    pub code: ThrottleReason,
+    /// Whole seconds until a retry can succeed, at least 1
+    pub retry_after_secs: u64,
+}
+
+impl Throttled {
+    fn new(code: ThrottleReason, error: String, retry_after: Duration) -> Self {
+        Self {
+            error,
+            code,
+            retry_after_secs: retry_after.as_secs_f64().ceil().max(1.0) as u64,
+        }
+    }
+}
+
+impl From<Throttled> for ErrorResponse {
+    fn from(e: Throttled) -> Self {
+        let code = match e.code {
+            ThrottleReason::RateLimited => ErrorCode::RateLimited,
+            ThrottleReason::TooManyQueries => ErrorCode::TooManyQueries,
+        };
+        ErrorResponse::new(code, e.error).with_details(serde_json::json!({
+            "retry_after_secs": e.retry_after_secs,
+        }))
+    }
+}
+
+impl std::fmt::Display for Throttled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for Throttled {}
+
+impl IntoResponse for Throttled {
+    fn into_response(self) -> Response {
+        let retry_after = self.retry_after_secs.to_string();
+        (
+            [(header::RETRY_AFTER, retry_after)],
+            ErrorResponse::from(self),
+        )
+            .into_response()
+    }
+}
+
+impl From<Throttled> for tonic::Status {
+    fn from(e: Throttled) -> Self {
+        let retry_after = e.retry_after_secs;
+        let mut status = tonic::Status::from(ErrorResponse::from(e));
+        status
+            .metadata_mut()
+            .insert("retry-after", retry_after.into());
+        status
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Take a token, or say how long until one is available
+    fn take(&mut self, rps: f64, capacity: f64, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rps).min(capacity);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / rps))
+        }
+    }
+}
+
+/// Enforces a [`LimitsConfig`], which can be replaced while serving
+///
+/// Share one limiter between the HTTP and gRPC servers so a client's
+/// requests count against the same bucket on both.
+#[derive(Debug, Default)]
+pub struct Limiter {
+    config: RwLock<LimitsConfig>,
+    /// By namespace and client
+    buckets: Mutex<HashMap<(Option<String>, String), Bucket>>,
+    /// Queries in flight, by namespace
+    in_flight: Mutex<HashMap<Option<String>, usize>>,
+}
+
+impl Limiter {
+    pub fn new(config: LimitsConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config: RwLock::new(config),
+            ..Self::default()
+        })
+    }
+
+    /// The limits in force
+    pub fn config(&self) -> LimitsConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Replace the limits; every client starts again with a full bucket
+    pub fn set_config(&self, config: LimitsConfig) -> Result<()> {
+        config.validate()?;
+        *self.config.write().unwrap() = config;
+        self.buckets.lock().unwrap().clear();
+        Ok(())
+    }
+
+    /// Count a request from `client` against the limits of `namespace`
+    ///
+    /// A query also takes a query slot, held until the returned permit drops.
+    /// `protocol` labels rejections on `/metrics`.
+    pub fn admit(
+        self: &Arc<Self>,
+        client: &str,
+        namespace: Option<&str>,
+        query: bool,
+        protocol: &str,
+    ) -> Result<Option<QueryPermit>, Throttled> {
+        self.admit_at(client, namespace, query, Instant::now())
+            .inspect_err(|e| metrics::record_rejected(e.code.as_str(), protocol))
+    }
+
+    fn admit_at(
+        self: &Arc<Self>,
+        client: &str,
+        namespace: Option<&str>,
+        query: bool,
+        now: Instant,
+    ) -> Result<Option<QueryPermit>, Throttled> {
+        let limit = self.config.read().unwrap().limit(namespace);
+
+        if let Some(rps) = limit.requests_per_second {
+            let capacity = limit.capacity(rps);
+            let mut buckets = self.buckets.lock().unwrap();
+            if buckets.len() >= MAX_BUCKETS {
+                buckets.retain(|_, bucket| {
+                    now.saturating_duration_since(bucket.updated) < BUCKET_IDLE
+                });
+            }
+            let bucket = buckets
+                .entry((namespace.map(str::to_string), client.to_string()))
+                .or_insert(Bucket {
+                    tokens: capacity,
+                    updated: now,
+                });
+            if let Err(wait) = bucket.take(rps, capacity, now) {
+                return Err(Throttled::new(
+                    ThrottleReason::RateLimited,
+                    format!("Rate limit of {} requests/second exceeded", rps),
+                    wait,
+                ));
+            }
+        }
+
+        if !query {
+            return Ok(None);
+        }
+        let namespace = namespace.map(str::to_string);
+        let mut in_flight = self.in_flight.lock().unwrap();
+        let running = in_flight.entry(namespace.clone()).or_default();
+        if let Some(max) = limit.max_concurrent_queries {
+            if *running >= max {
+                return Err(Throttled::new(
+                    ThrottleReason::TooManyQueries,
+                    format!("{} queries already in flight", max),
+                    Duration::from_secs(1),
+                ));
+            }
+        }
+        *running += 1;
+        Ok(Some(QueryPermit {
+            limiter: self.clone(),
+            namespace,
+        }))
+    }
+}
+
+/// A query slot, given back when dropped
+#[derive(Debug)]
+pub struct QueryPermit {
+    limiter: Arc<Limiter>,
+    namespace: Option<String>,
+}
+
+impl Drop for QueryPermit {
+    fn drop(&mut self) {
+        let mut in_flight = self.limiter.in_flight.lock().unwrap();
+        if let Some(running) = in_flight.get_mut(&self.namespace) {
+            *running = running.saturating_sub(1);
+        }
+    }
+}
+
+/// Who a request counts against: its API key, else its peer's IP address
+fn client_id(principal: Option<&Principal>, addr: Option<SocketAddr>) -> String {
+    match (principal, addr) {
+        (Some(principal), _) => format!("key:{}", principal.name),
+        (None, Some(addr)) => format!("ip:{}", addr.ip()),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+/// The client a gRPC request counts against
+pub fn grpc_client<T>(request: &tonic::Request<T>) -> String {
+    client_id(
+        request.extensions().get::<Principal>(),
+        request.remote_addr(),
+    )
+}
+
+/// What a route counts against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    /// The namespace whose limits apply; `None` for the global ones
+    pub namespace: Option<String>,
+    /// Whether the route takes a query slot
+    pub query: bool,
+}
+
+/// The [`Target`] of a route, from its matched route and path; `None`
+/// exempts it
+pub(crate) type LimitPolicy = fn(&str, &str) -> Option<Target>;
+
+/// State of the [`throttle`] route layer
+#[derive(Clone)]
+pub(crate) struct LimitLayer {
+    pub limiter: Arc<Limiter>,
+    pub policy: LimitPolicy,
+}
+
+/// Route layer turning away requests over the limits
+///
+/// Runs inside the auth layer, so a request with a key counts against it.
+pub(crate) async fn throttle(
+    State(layer): State<LimitLayer>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = auth::matched_route(&request);
+    let Some(target) = (layer.policy)(&route, request.uri().path()) else {
+        return next.run(request).await;
+    };
+    let client = client_id(
+        request.extensions().get::<Principal>(),
+        request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|info| info.0),
+    );
+    match layer
+        .limiter
+        .admit(&client, target.namespace.as_deref(), target.query, "http")
+    {
+        Ok(_permit) => next.run(request).await,
+        Err(e) => e.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(global: Limit) -> Arc<Limiter> {
+        let mut namespaces = BTreeMap::new();
+        namespaces.insert(
+            "acme".to_string(),
+            Limit {
+                requests_per_second: Some(1.0),
+                ..Limit::default()
+            },
+        );
+        Arc::new(Limiter::new(LimitsConfig { global, namespaces }).unwrap())
+    }
+
+    #[test]
+    fn test_buckets_refill_per_client_and_namespace() {
+        let limiter = limiter(Limit {
+            requests_per_second: Some(2.0),
+            burst: Some(3),
+            ..Limit::default()
+        });
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(limiter.admit_at("a", None, false, start).is_ok());
+        }
+        let throttled = limiter.admit_at("a", None, false, start).unwrap_err();
+        assert_eq!(throttled.code, ThrottleReason::RateLimited);
+        assert_eq!(throttled.retry_after_secs, 1);
+
+        // Other clients and namespaces have buckets of their own
+        assert!(limiter.admit_at("b", None, false, start).is_ok());
+        assert!(limiter.admit_at("a", Some("acme"), false, start).is_ok());
+        assert!(limiter.admit_at("a", Some("acme"), false, start).is_err());
+
+        let later = start + Duration::from_millis(500);
+        assert!(limiter.admit_at("a", None, false, later).is_ok());
+        assert!(limiter.admit_at("a", None, false, later).is_err());
+    }
+
+    #[test]
+    fn test_query_slots_are_returned_on_drop() {
+        let limiter = limiter(Limit {
+            max_concurrent_queries: Some(1),
+            ..Limit::default()
+        });
+        let now = Instant::now();
+        let permit = limiter.admit_at("a", None, true, now).unwrap();
+        let throttled = limiter.admit_at("b", None, true, now).unwrap_err();
+        assert_eq!(throttled.code, ThrottleReason::TooManyQueries);
+        // Other routes don't need a slot
+        assert!(limiter.admit_at("b", None, false, now).unwrap().is_none());
+
+        drop(permit);
+        assert!(limiter.admit_at("b", None, true, now).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_limits_are_rejected() {
+        let zero = Limit {
+            requests_per_second: Some(0.0),
+            ..Limit::default()
+        };
+        let limiter = limiter(Limit::default());
+        let mut config = limiter.config();
+        config.namespaces.insert("bad".to_string(), zero);
+        let error = limiter.set_config(config).unwrap_err();
+        assert!(
+            format!("{:#}", error).contains("namespace 'bad'"),
+            "{:#}",
+            error
+        );
+        assert_eq!(limiter.config().namespaces.len(), 1);
+    }
+}
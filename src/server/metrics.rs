@@ -1,19 +1,32 @@
 //! Prometheus metrics for VecStore server
 //!
 //! Exposes metrics at /metrics endpoint for Prometheus scraping.
+//!
+//! Every HTTP route is counted by [`track_http`] and every gRPC method by
+//! [`GrpcMetricsLayer`], labelled with the response status. Query, upsert
+//! and delete latencies go to `vecstore_operation_duration_seconds`, and
+//! server-side embedding to `vecstore_embedding_duration_seconds`. The
+//! store gauges, and a replica's `vecstore_replication_lag_*` gauges, are
+//! refreshed on each scrape.
 
+use crate::store::{SlowQuery, StoreStats};
+use axum::{extract::Request, middleware::Next, response::Response};
 use lazy_static::lazy_static;
 use prometheus::{
-    register_counter_vec, register_gauge, register_histogram_vec, CounterVec, Encoder, Gauge,
-    HistogramVec, TextEncoder,
+    register_counter_vec, register_gauge, register_gauge_vec, register_histogram_vec, CounterVec,
+    Encoder, Gauge, GaugeVec, HistogramVec, TextEncoder,
 };
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
 
 lazy_static! {
-    /// Total number of requests by endpoint
+    /// Total number of requests by endpoint and response status
     pub static ref REQUEST_COUNTER: CounterVec = register_counter_vec!(
         "vecstore_requests_total",
-        "Total number of requests by endpoint",
-        &["endpoint", "method"]
+        "Total number of requests by endpoint and response status",
+        &["protocol", "endpoint", "method", "status"]
     )
     .unwrap();
 
@@ -21,7 +34,27 @@ lazy_static! {
     pub static ref REQUEST_DURATION: HistogramVec = register_histogram_vec!(
         "vecstore_request_duration_seconds",
         "Request duration in seconds",
-        &["endpoint", "method"],
+        &["protocol", "endpoint", "method"],
+        vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+    )
+    .unwrap();
+
+    /// Latency of queries, upserts and deletes, including waiting for the store
+    pub static ref OPERATION_DURATION: HistogramVec = register_histogram_vec!(
+        "vecstore_operation_duration_seconds",
+        "Latency of store operations in seconds, including waiting for the store lock",
+        &["operation", "protocol"],
+        vec![
+            0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0
+        ]
+    )
+    .unwrap();
+
+    /// Latency of server-side embedding, per model call
+    pub static ref EMBEDDING_DURATION: HistogramVec = register_histogram_vec!(
+        "vecstore_embedding_duration_seconds",
+        "Latency of embedding a request's texts in seconds",
+        &["model"],
         vec![0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
     )
     .unwrap();
@@ -126,6 +159,80 @@ lazy_static! {
     )
     .unwrap();
 
+    /// Soft-deleted records as a fraction of all records
+    pub static ref TOMBSTONE_RATIO: Gauge = register_gauge!(
+        "vecstore_tombstone_ratio",
+        "Soft-deleted records as a fraction of all records"
+    )
+    .unwrap();
+
+    /// Estimated bytes held in memory
+    pub static ref MEMORY_BYTES: Gauge = register_gauge!(
+        "vecstore_memory_bytes",
+        "Estimated bytes held in memory for vectors, index links and metadata"
+    )
+    .unwrap();
+
+    /// Write-ahead log size
+    pub static ref WAL_BYTES: Gauge = register_gauge!(
+        "vecstore_wal_bytes",
+        "Size of the write-ahead log in bytes (0 when it is off)"
+    )
+    .unwrap();
+
+    /// Active records per namespace in multi-tenant mode
+    pub static ref NAMESPACE_RECORDS: GaugeVec = register_gauge_vec!(
+        "vecstore_namespace_records",
+        "Active records per namespace",
+        &["namespace"]
+    )
+    .unwrap();
+
+    /// Slow queries logged by the store
+    pub static ref SLOW_QUERIES: Gauge = register_gauge!(
+        "vecstore_slow_queries",
+        "Queries slower than the slow-query threshold since the store was opened"
+    )
+    .unwrap();
+
+    /// The most recent slow query, standing in for histogram exemplars
+    pub static ref LAST_SLOW_QUERY: GaugeVec = register_gauge_vec!(
+        "vecstore_last_slow_query_seconds",
+        "Duration of the most recent slow query; its `at` label matches the /v1/slow-queries entry",
+        &["at"]
+    )
+    .unwrap();
+
+    /// Requests turned away by the rate limits and query slots
+    pub static ref REJECTED_REQUESTS: CounterVec = register_counter_vec!(
+        "vecstore_requests_rejected_total",
+        "Requests turned away by the rate limits and query concurrency cap",
+        &["reason", "protocol"]
+    )
+    .unwrap();
+
+    /// Operations a replica's copy is behind its primary
+    pub static ref REPLICATION_LAG_OPERATIONS: Gauge = register_gauge!(
+        "vecstore_replication_lag_operations",
+        "Operations the replica's copy is behind the primary, as of the last poll"
+    )
+    .unwrap();
+
+    /// Age of a replica's copy
+    pub static ref REPLICATION_LAG_SECONDS: Gauge = register_gauge!(
+        "vecstore_replication_lag_seconds",
+        "Seconds since the replica's copy was last known to match the primary"
+    )
+    .unwrap();
+
+    /// Replica polls of the primary by outcome
+    pub static ref REPLICATION_SYNCS: CounterVec = register_counter_vec!(
+        "vecstore_replication_syncs_total",
+        "Replica polls of the primary: applied (a new copy was swapped in), current or failed",
+        &["outcome"]
+    )
+    .unwrap();
+
     /// WebSocket connections
     pub static ref WEBSOCKET_CONNECTIONS: Gauge = register_gauge!(
         "vecstore_websocket_connections",
@@ -143,39 +250,89 @@ pub fn encode_metrics() -> Result<String, prometheus::Error> {
     Ok(String::from_utf8(buffer).unwrap())
 }
 
-/// Update database statistics
-pub fn update_db_stats(total: usize, active: usize, deleted: usize, dimension: usize) {
-    TOTAL_VECTORS.set(total as f64);
-    ACTIVE_VECTORS.set(active as f64);
-    DELETED_VECTORS.set(deleted as f64);
-    DIMENSION.set(dimension as f64);
+/// Update the store gauges
+pub fn update_store_stats(stats: &StoreStats) {
+    TOTAL_VECTORS.set(stats.total_records as f64);
+    ACTIVE_VECTORS.set(stats.active_records as f64);
+    DELETED_VECTORS.set(stats.deleted_records as f64);
+    DIMENSION.set(stats.dimension as f64);
+    let tombstones = if stats.total_records == 0 {
+        0.0
+    } else {
+        stats.deleted_records as f64 / stats.total_records as f64
+    };
+    TOMBSTONE_RATIO.set(tombstones);
+    MEMORY_BYTES
+        .set((stats.vector_memory_bytes + stats.index_link_bytes + stats.metadata_bytes) as f64);
+    WAL_BYTES.set(stats.wal_bytes.unwrap_or(0) as f64);
+}
+
+/// Update the slow-query count and the most recent slow query
+pub fn update_slow_queries(count: u64, latest: Option<&SlowQuery>) {
+    SLOW_QUERIES.set(count as f64);
+    LAST_SLOW_QUERY.reset();
+    if let Some(query) = latest {
+        LAST_SLOW_QUERY
+            .with_label_values(&[&query.at.to_string()])
+            .set(query.profile.total.as_secs_f64());
+    }
+}
+
+/// Replace the per-namespace record counts
+pub fn update_namespace_records<'a>(counts: impl IntoIterator<Item = (&'a str, usize)>) {
+    // Reset first so deleted namespaces drop out
+    NAMESPACE_RECORDS.reset();
+    for (namespace, records) in counts {
+        NAMESPACE_RECORDS
+            .with_label_values(&[namespace])
+            .set(records as f64);
+    }
 }
 
-/// Record a request
-pub fn record_request(endpoint: &str, method: &str, duration: f64) {
-    REQUEST_COUNTER.with_label_values(&[endpoint, method]).inc();
+/// Record a request and the status it was answered with
+pub fn record_request(protocol: &str, endpoint: &str, method: &str, status: &str, duration: f64) {
+    REQUEST_COUNTER
+        .with_label_values(&[protocol, endpoint, method, status])
+        .inc();
     REQUEST_DURATION
-        .with_label_values(&[endpoint, method])
+        .with_label_values(&[protocol, endpoint, method])
+        .observe(duration);
+}
+
+/// Record the latency of a query, upsert or delete
+pub fn record_operation(operation: &str, protocol: &str, duration: f64) {
+    OPERATION_DURATION
+        .with_label_values(&[operation, protocol])
+        .observe(duration);
+}
+
+/// Record the latency of embedding a request's texts
+pub fn record_embedding(model: &str, duration: f64) {
+    EMBEDDING_DURATION
+        .with_label_values(&[model])
         .observe(duration);
 }
 
 /// Record a query
-pub fn record_query(query_type: &str, result_count: usize, _duration: f64) {
+pub fn record_query(protocol: &str, query_type: &str, result_count: usize, duration: f64) {
     QUERY_COUNTER.with_label_values(&[query_type]).inc();
     QUERY_RESULTS
         .with_label_values(&[query_type])
         .observe(result_count as f64);
+    record_operation("query", protocol, duration);
 }
 
 /// Record an upsert
-pub fn record_upsert(is_batch: bool) {
+pub fn record_upsert(protocol: &str, is_batch: bool, duration: f64) {
     let batch_label = if is_batch { "batch" } else { "single" };
     UPSERT_COUNTER.with_label_values(&[batch_label]).inc();
+    record_operation("upsert", protocol, duration);
 }
 
 /// Record a delete
-pub fn record_delete(delete_type: &str) {
+pub fn record_delete(protocol: &str, delete_type: &str, duration: f64) {
     DELETE_COUNTER.with_label_values(&[delete_type]).inc();
+    record_operation("delete", protocol, duration);
 }
 
 /// Record an error
@@ -202,6 +359,24 @@ pub fn record_compact(status: &str) {
     COMPACT_COUNTER.with_label_values(&[status]).inc();
 }
 
+/// Record a request turned away by the limits
+pub fn record_rejected(reason: &str, protocol: &str) {
+    REJECTED_REQUESTS
+        .with_label_values(&[reason, protocol])
+        .inc();
+}
+
+/// Update a replica's lag gauges
+pub fn update_replication_lag(operations: u64, seconds: f64) {
+    REPLICATION_LAG_OPERATIONS.set(operations as f64);
+    REPLICATION_LAG_SECONDS.set(seconds);
+}
+
+/// Record how a replica's poll of its primary went
+pub fn record_replication_sync(outcome: &str) {
+    REPLICATION_SYNCS.with_label_values(&[outcome]).inc();
+}
+
 /// Increment WebSocket connections
 pub fn websocket_connected() {
     WEBSOCKET_CONNECTIONS.inc();
@@ -211,3 +386,92 @@ pub fn websocket_connected() {
 pub fn websocket_disconnected() {
     WEBSOCKET_CONNECTIONS.dec();
 }
+
+/// Middleware counting each request by matched route and status
+///
+/// Add it as the outermost route layer, so requests refused by the auth and
+/// rate-limit layers are counted too. Unmatched paths aren't counted, which
+/// keeps arbitrary URLs out of the label set.
+pub async fn track_http(request: Request, next: Next) -> Response {
+    let endpoint = super::auth::matched_route(&request);
+    super::request_id::record_route(&endpoint);
+    let method = request.method().to_string();
+    let start = Instant::now();
+    let response = next.run(request).await;
+    record_request(
+        "http",
+        &endpoint,
+        &method,
+        response.status().as_str(),
+        start.elapsed().as_secs_f64(),
+    );
+    response
+}
+
+/// Tower layer counting each gRPC call by method and status code
+///
+/// Add it with `Server::builder().layer(..)`. Tonic interceptors only see
+/// the request, so this wraps the service instead. The status comes from
+/// the response headers, which carry it when a call fails before
+/// responding; calls without one are counted as `Ok`. Streaming calls are
+/// timed until their first response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GrpcMetricsLayer;
+
+impl<S> tower::Layer<S> for GrpcMetricsLayer {
+    type Service = GrpcMetrics<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcMetrics { inner }
+    }
+}
+
+/// Service added by [`GrpcMetricsLayer`]
+#[derive(Debug, Clone)]
+pub struct GrpcMetrics<S> {
+    inner: S,
+}
+
+impl<S, B, ResBody> tower::Service<axum::http::Request<B>> for GrpcMetrics<S>
+where
+    S: tower::Service<axum::http::Request<B>, Response = axum::http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: axum::http::Request<B>) -> Self::Future {
+        let endpoint = request.uri().path().to_string();
+        let start = Instant::now();
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            let response = response.await?;
+            let code = response
+                .headers()
+                .get("grpc-status")
+                .and_then(|status| status.to_str().ok())
+                .and_then(|status| status.parse::<i32>().ok())
+                .map(tonic::Code::from)
+                .unwrap_or(tonic::Code::Ok);
+            // Unknown methods would put any path a client sends in the labels
+            let endpoint = if code == tonic::Code::Unimplemented {
+                "unknown"
+            } else {
+                endpoint.as_str()
+            };
+            record_request(
+                "grpc",
+                endpoint,
+                "POST",
+                &format!("{:?}", code),
+                start.elapsed().as_secs_f64(),
+            );
+            Ok(response)
+        })
+    }
+}
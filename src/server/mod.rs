@@ -8,12 +8,63 @@ pub mod admin;
 #[cfg(feature = "server")]
 pub mod admin_http;
 
+#[cfg(feature = "server")]
+pub mod auth;
+
+#[cfg(feature = "server")]
+pub mod bulk;
+
+#[cfg(feature = "server")]
+pub mod checkpoint;
+
+#[cfg(feature = "server")]
+pub mod embed;
+
+#[cfg(feature = "server")]
+pub mod errors;
+
+#[cfg(feature = "server")]
+pub mod events;
+
 #[cfg(feature = "server")]
 pub mod grpc;
 
 #[cfg(feature = "server")]
 pub mod http;
 
+#[cfg(feature = "server")]
+pub mod http_options;
+
+#[cfg(feature = "server")]
+pub mod import;
+
+#[cfg(feature = "server")]
+pub mod jobs;
+
+#[cfg(feature = "server")]
+pub mod limits;
+
+#[cfg(feature = "server")]
+pub mod namespace_grpc;
+
+#[cfg(feature = "server")]
+pub mod openapi;
+
+#[cfg(feature = "server")]
+pub mod query_stream;
+
+#[cfg(feature = "server")]
+pub mod replication;
+
+#[cfg(feature = "server")]
+pub mod request_id;
+
+#[cfg(feature = "server")]
+pub mod shutdown;
+
+#[cfg(feature = "server")]
+pub mod timeouts;
+
 #[cfg(feature = "server")]
 pub mod types;
 
@@ -26,8 +77,59 @@ pub use admin::AdminService;
 #[cfg(feature = "server")]
 pub use admin_http::AdminHttpServer;
 
+#[cfg(feature = "server")]
+pub use auth::{ApiKeys, Scope};
+
+#[cfg(feature = "server")]
+pub use bulk::BulkLimits;
+
+#[cfg(feature = "server")]
+pub use checkpoint::{Checkpointer, SaveReport, SaveTrigger};
+
+#[cfg(feature = "server")]
+pub use embed::{EmbedderConfig, Embedding, ServerEmbedder};
+
+#[cfg(feature = "server")]
+pub use errors::{ErrorCode, ErrorResponse};
+
+#[cfg(feature = "server")]
+pub use events::EventStream;
+
 #[cfg(feature = "server")]
 pub use grpc::VecStoreGrpcServer;
 
 #[cfg(feature = "server")]
 pub use http::VecStoreHttpServer;
+
+#[cfg(feature = "server")]
+pub use http_options::{Cors, HttpOptions};
+
+#[cfg(feature = "server")]
+pub use import::{ImportEvent, ImportLimits};
+
+#[cfg(feature = "server")]
+pub use jobs::{Job, JobKind, JobState, Jobs};
+
+#[cfg(feature = "server")]
+pub use limits::{Limiter, LimitsConfig};
+
+#[cfg(feature = "server")]
+pub use namespace_grpc::NamespaceGrpcServer;
+
+#[cfg(feature = "server")]
+pub use openapi::{AdminApi, HttpApi};
+
+#[cfg(feature = "server")]
+pub use query_stream::StreamLimits;
+
+#[cfg(feature = "server")]
+pub use replication::{Replica, ReplicaStatus};
+
+#[cfg(feature = "server")]
+pub use request_id::{GrpcTraceLayer, RequestId};
+
+#[cfg(feature = "server")]
+pub use shutdown::{serve_until_shutdown, Shutdown};
+
+#[cfg(feature = "server")]
+pub use timeouts::QueryTimeouts;
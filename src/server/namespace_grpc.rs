@@ -0,0 +1,370 @@
+//! Vector operations on namespaces over gRPC
+//!
+//! Serves `VecStoreService` in multi-tenant mode. Each call names its
+//! namespace in the request's `namespace` field or, failing that, the
+//! `x-vecstore-namespace` metadata header, and runs against that
+//! namespace's store through the [`NamespaceManager`]. Methods that have no
+//! namespace-aware implementation yet answer `UNIMPLEMENTED`.
+
+use super::auth::{self, Access, ApiKeys, Scope};
+use super::errors::ErrorResponse;
+use super::limits::{self, Limiter, QueryPermit};
+use super::timeouts::{self, QueryTimeouts};
+use super::types::{pb, *};
+use crate::namespace::NamespaceId;
+use crate::namespace_manager::NamespaceManager;
+use crate::store::Record;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tonic::{Request, Response, Status};
+
+/// Metadata header naming the namespace of a call whose request has no
+/// `namespace` field set
+pub const NAMESPACE_HEADER: &str = "x-vecstore-namespace";
+
+/// gRPC vector service routing each call to a namespace
+pub struct NamespaceGrpcServer {
+    manager: Arc<RwLock<NamespaceManager>>,
+    auth: Option<Arc<ApiKeys>>,
+    limiter: Arc<Limiter>,
+    query_timeouts: QueryTimeouts,
+}
+
+impl NamespaceGrpcServer {
+    pub fn new(manager: Arc<RwLock<NamespaceManager>>) -> Self {
+        Self {
+            manager,
+            auth: None,
+            limiter: Arc::new(Limiter::default()),
+            query_timeouts: QueryTimeouts::default(),
+        }
+    }
+
+    /// Require an API key allowing each call on its namespace
+    ///
+    /// Serve the service through [`ApiKeys::interceptor`] with the same keys.
+    pub fn with_auth(mut self, keys: Arc<ApiKeys>) -> Self {
+        self.auth = Some(keys);
+        self
+    }
+
+    /// Enforce `limiter`'s global and per-namespace rate limits and its
+    /// query cap
+    pub fn with_limits(mut self, limiter: Arc<Limiter>) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Set the default and longest timeout of `Query`
+    pub fn with_query_timeouts(mut self, timeouts: QueryTimeouts) -> Self {
+        self.query_timeouts = timeouts;
+        self
+    }
+
+    /// Check the caller's key allows `scope` on the call's namespace and
+    /// count the call against that namespace's limits
+    ///
+    /// Returns the namespace the call resolves to, following aliases, and a
+    /// query slot for queries.
+    async fn admit<T>(
+        &self,
+        request: &Request<T>,
+        namespace: Option<&str>,
+        scope: Scope,
+    ) -> Result<(NamespaceId, Option<QueryPermit>), Status> {
+        let namespace = namespace
+            .map(str::to_string)
+            .or_else(|| {
+                let header = request.metadata().get(NAMESPACE_HEADER)?;
+                header.to_str().ok().map(str::to_string)
+            })
+            .filter(|namespace| !namespace.is_empty())
+            .ok_or_else(|| {
+                Status::invalid_argument(format!(
+                    "No namespace: set the request's namespace field or the {} header",
+                    NAMESPACE_HEADER
+                ))
+            })?;
+        auth::authorize(
+            self.auth.as_deref(),
+            request,
+            Some(Access::namespace(scope, namespace.clone())),
+        )?;
+        let client = limits::grpc_client(request);
+        let permit = self
+            .limiter
+            .admit(&client, Some(&namespace), scope == Scope::Read, "grpc")?;
+
+        let manager = self.manager.read().await;
+        let namespace_id = manager.resolve(&namespace);
+        manager
+            .get_namespace(&namespace_id)
+            .map_err(|e| Status::not_found(e.to_string()))?;
+        Ok((namespace_id, permit))
+    }
+}
+
+/// Answer for the methods with no namespace-aware implementation
+fn unsupported(method: &str) -> Status {
+    Status::unimplemented(format!("{} is not available in namespace mode", method))
+}
+
+#[tonic::async_trait]
+impl pb::vec_store_service_server::VecStoreService for NamespaceGrpcServer {
+    type QueryStreamStream = std::pin::Pin<
+        Box<dyn tokio_stream::Stream<Item = Result<pb::QueryResult, Status>> + Send + 'static>,
+    >;
+
+    type SubscribeEventsStream = std::pin::Pin<
+        Box<dyn tokio_stream::Stream<Item = Result<pb::StoreEvent, Status>> + Send + 'static>,
+    >;
+
+    /// Insert or update a vector in a namespace
+    async fn upsert(
+        &self,
+        request: Request<pb::UpsertRequest>,
+    ) -> Result<Response<pb::UpsertResponse>, Status> {
+        let namespace = request.get_ref().namespace.clone();
+        let (namespace_id, _) = self
+            .admit(&request, namespace.as_deref(), Scope::Write)
+            .await?;
+        let start = std::time::Instant::now();
+        let record = pb_upsert_to_record(request.into_inner())
+            .map_err(|e| Status::invalid_argument(format!("Invalid metadata: {}", e)))?;
+
+        let manager = self.manager.read().await;
+        manager
+            .upsert_record(&namespace_id, record, None)
+            .map_err(|e| ErrorResponse::from_namespace_error(&e).context("Upsert failed"))?;
+        super::metrics::record_upsert("grpc", false, start.elapsed().as_secs_f64());
+
+        Ok(Response::new(pb::UpsertResponse {
+            success: true,
+            error: None,
+        }))
+    }
+
+    /// Insert or update a batch of vectors in a namespace, all or none
+    async fn batch_upsert(
+        &self,
+        request: Request<pb::BatchUpsertRequest>,
+    ) -> Result<Response<pb::BatchUpsertResponse>, Status> {
+        let namespace = request.get_ref().namespace.clone();
+        let (namespace_id, _) = self
+            .admit(&request, namespace.as_deref(), Scope::Write)
+            .await?;
+        let start = std::time::Instant::now();
+        let req = request.into_inner();
+        if req.partial {
+            return Err(Status::invalid_argument(
+                "Partial batches are not available in namespace mode",
+            ));
+        }
+
+        let records = req
+            .records
+            .into_iter()
+            .map(pb_upsert_to_record)
+            .collect::<anyhow::Result<Vec<Record>>>()
+            .map_err(|e| Status::invalid_argument(format!("Invalid metadata: {}", e)))?;
+        let count = records.len() as i32;
+        let results = records
+            .iter()
+            .map(|record| pb::BatchItemResult {
+                id: record.id.clone(),
+                version: None,
+                error: None,
+            })
+            .collect();
+
+        let manager = self.manager.read().await;
+        manager
+            .batch_upsert(&namespace_id, records)
+            .map_err(|e| ErrorResponse::from_namespace_error(&e).context("Batch upsert failed"))?;
+        super::metrics::record_upsert("grpc", true, start.elapsed().as_secs_f64());
+
+        Ok(Response::new(pb::BatchUpsertResponse {
+            inserted: count,
+            updated: 0,
+            errors: Vec::new(),
+            failed: Vec::new(),
+            results,
+        }))
+    }
+
+    async fn import_stream(
+        &self,
+        _request: Request<tonic::Streaming<pb::ImportChunk>>,
+    ) -> Result<Response<pb::ImportResponse>, Status> {
+        Err(unsupported("ImportStream"))
+    }
+
+    /// Query for similar vectors in a namespace
+    async fn query(
+        &self,
+        request: Request<pb::QueryRequest>,
+    ) -> Result<Response<pb::QueryResponse>, Status> {
+        let namespace = request.get_ref().namespace.clone();
+        let (namespace_id, _permit) = self
+            .admit(&request, namespace.as_deref(), Scope::Read)
+            .await?;
+        let start = std::time::Instant::now();
+        let query = pb_query_to_query(request.get_ref())
+            .map_err(|e| Status::invalid_argument(format!("Invalid query: {}", e)))?;
+
+        let timeout = self.query_timeouts.resolve(request.get_ref().timeout_ms);
+        let neighbors = timeouts::run(self.manager.clone(), timeout, move |manager, cancel| {
+            manager.query(&namespace_id, query.with_cancel(cancel))
+        })
+        .await
+        .map_err(|e| ErrorResponse::from_namespace_error(&e).context("Query failed"))?;
+
+        let duration = start.elapsed().as_secs_f64();
+        super::metrics::record_query("grpc", "vector", neighbors.len(), duration);
+
+        Ok(Response::new(pb::QueryResponse {
+            results: neighbors.iter().map(neighbor_to_query_result).collect(),
+            stats: Some(pb::QueryStats {
+                total_candidates: neighbors.len() as i32,
+                filtered_count: 0,
+                duration_ms: duration * 1000.0,
+                cache_hit: false,
+            }),
+        }))
+    }
+
+    async fn query_stream(
+        &self,
+        _request: Request<pb::QueryRequest>,
+    ) -> Result<Response<Self::QueryStreamStream>, Status> {
+        Err(unsupported("QueryStream"))
+    }
+
+    async fn get(
+        &self,
+        _request: Request<pb::GetRequest>,
+    ) -> Result<Response<pb::GetResponse>, Status> {
+        Err(unsupported("Get"))
+    }
+
+    async fn fetch_parent_window(
+        &self,
+        _request: Request<pb::ParentWindowRequest>,
+    ) -> Result<Response<pb::GetResponse>, Status> {
+        Err(unsupported("FetchParentWindow"))
+    }
+
+    async fn update_metadata(
+        &self,
+        _request: Request<pb::UpdateMetadataRequest>,
+    ) -> Result<Response<pb::UpdateMetadataResponse>, Status> {
+        Err(unsupported("UpdateMetadata"))
+    }
+
+    /// Delete a vector from a namespace
+    async fn delete(
+        &self,
+        request: Request<pb::DeleteRequest>,
+    ) -> Result<Response<pb::DeleteResponse>, Status> {
+        let namespace = request.get_ref().namespace.clone();
+        let (namespace_id, _) = self
+            .admit(&request, namespace.as_deref(), Scope::Write)
+            .await?;
+        let start = std::time::Instant::now();
+        let req = request.into_inner();
+
+        let manager = self.manager.read().await;
+        manager
+            .remove(&namespace_id, &req.id)
+            .map_err(|e| ErrorResponse::from_namespace_error(&e).context("Delete failed"))?;
+        super::metrics::record_delete("grpc", "hard", start.elapsed().as_secs_f64());
+
+        Ok(Response::new(pb::DeleteResponse {
+            found: true,
+            deleted: true,
+        }))
+    }
+
+    async fn delete_many(
+        &self,
+        _request: Request<pb::DeleteManyRequest>,
+    ) -> Result<Response<pb::DeleteManyResponse>, Status> {
+        Err(unsupported("DeleteMany"))
+    }
+
+    async fn soft_delete(
+        &self,
+        _request: Request<pb::SoftDeleteRequest>,
+    ) -> Result<Response<pb::SoftDeleteResponse>, Status> {
+        Err(unsupported("SoftDelete"))
+    }
+
+    async fn restore(
+        &self,
+        _request: Request<pb::RestoreRequest>,
+    ) -> Result<Response<pb::RestoreResponse>, Status> {
+        Err(unsupported("Restore"))
+    }
+
+    async fn compact(
+        &self,
+        _request: Request<pb::CompactRequest>,
+    ) -> Result<Response<pb::CompactResponse>, Status> {
+        Err(unsupported("Compact"))
+    }
+
+    async fn get_stats(
+        &self,
+        _request: Request<pb::StatsRequest>,
+    ) -> Result<Response<pb::StatsResponse>, Status> {
+        Err(unsupported("GetStats"))
+    }
+
+    async fn create_snapshot(
+        &self,
+        _request: Request<pb::SnapshotRequest>,
+    ) -> Result<Response<pb::SnapshotResponse>, Status> {
+        Err(unsupported("CreateSnapshot"))
+    }
+
+    async fn list_snapshots(
+        &self,
+        _request: Request<pb::ListSnapshotsRequest>,
+    ) -> Result<Response<pb::ListSnapshotsResponse>, Status> {
+        Err(unsupported("ListSnapshots"))
+    }
+
+    async fn restore_snapshot(
+        &self,
+        _request: Request<pb::RestoreSnapshotRequest>,
+    ) -> Result<Response<pb::RestoreSnapshotResponse>, Status> {
+        Err(unsupported("RestoreSnapshot"))
+    }
+
+    async fn hybrid_query(
+        &self,
+        _request: Request<pb::HybridQueryRequest>,
+    ) -> Result<Response<pb::QueryResponse>, Status> {
+        Err(unsupported("HybridQuery"))
+    }
+
+    async fn health_check(
+        &self,
+        request: Request<pb::HealthCheckRequest>,
+    ) -> Result<Response<pb::HealthCheckResponse>, Status> {
+        auth::authorize(self.auth.as_deref(), &request, None)?;
+        let _ = self.manager.read().await;
+
+        Ok(Response::new(pb::HealthCheckResponse {
+            status: pb::health_check_response::ServingStatus::Serving as i32,
+            message: Some("Healthy".to_string()),
+        }))
+    }
+
+    async fn subscribe_events(
+        &self,
+        _request: Request<pb::SubscribeEventsRequest>,
+    ) -> Result<Response<Self::SubscribeEventsStream>, Status> {
+        Err(unsupported("SubscribeEvents"))
+    }
+}
@@ -0,0 +1,212 @@
+//! OpenAPI documents of the REST APIs
+//!
+//! [`VecStoreHttpServer`](super::VecStoreHttpServer) and
+//! [`AdminHttpServer`](super::AdminHttpServer) each serve their OpenAPI 3
+//! document at `/openapi.json`, and Swagger UI at `/docs` when
+//! [`HttpOptions::swagger_ui`](super::HttpOptions::swagger_ui) is set. Both
+//! routes answer without an API key.
+//!
+//! The documents are generated from the handlers' `#[utoipa::path]`
+//! attributes. `tests/snapshots/` holds a copy of each, and
+//! `tests/server_openapi.rs` fails when a route changes without the copy
+//! being regenerated with `UPDATE_OPENAPI_SNAPSHOTS=1`.
+
+use super::errors::{ErrorCode, ErrorResponse};
+use super::{admin_http, http};
+use axum::{http::header, response::Html, routing::get, Router};
+use utoipa::openapi::security::{
+    ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme,
+};
+use utoipa::openapi::{RefOr, Response};
+use utoipa::{Modify, OpenApi};
+
+/// API of a single-store server
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "vecstore",
+        description = "Vector store REST API. Failed requests answer with an `ErrorResponse`."
+    ),
+    paths(
+        http::upsert,
+        http::batch_upsert,
+        http::batch_execute,
+        http::import,
+        http::query,
+        http::query_text,
+        http::embed_texts,
+        http::query_explain,
+        http::query_estimate,
+        http::hybrid_query,
+        http::get_records,
+        http::get_record,
+        http::parent_window,
+        http::scan,
+        http::update_metadata,
+        http::delete_many,
+        http::delete_vector,
+        http::soft_delete,
+        http::restore,
+        http::compact,
+        http::get_stats,
+        http::get_slow_queries,
+        http::count,
+        http::distinct_values,
+        http::get_limits,
+        http::put_limits,
+        http::save_now,
+        http::last_save,
+        http::backup_job,
+        http::restore_job,
+        http::compact_job,
+        http::optimize_job,
+        http::list_jobs,
+        http::get_job,
+        http::download_backup,
+        http::replication_sequence,
+        http::replication_snapshot,
+        http::create_snapshot,
+        http::list_snapshots,
+        http::restore_snapshot,
+        http::metrics_endpoint,
+        http::health_check,
+        http::ready_check,
+    ),
+    components(schemas(ErrorResponse, ErrorCode)),
+    modifiers(&ApiKeys, &ErrorResponses)
+)]
+pub struct HttpApi;
+
+/// API of a multi-tenant server
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "vecstore admin",
+        description = "Namespace management and per-namespace vector REST API. Failed requests answer with an `ErrorResponse`."
+    ),
+    paths(
+        admin_http::create_namespace,
+        admin_http::list_namespaces,
+        admin_http::get_namespace,
+        admin_http::update_quotas,
+        admin_http::update_status,
+        admin_http::delete_namespace,
+        admin_http::get_namespace_stats,
+        admin_http::get_namespace_slow_queries,
+        admin_http::list_aliases,
+        admin_http::put_alias,
+        admin_http::delete_alias,
+        admin_http::get_aggregate_stats,
+        admin_http::get_limits,
+        admin_http::put_limits,
+        admin_http::namespace_upsert,
+        admin_http::namespace_query,
+        admin_http::namespace_delete,
+        admin_http::metrics_endpoint,
+        admin_http::health_check,
+        admin_http::ready_check,
+    ),
+    components(schemas(ErrorResponse, ErrorCode)),
+    modifiers(&ApiKeys, &ErrorResponses)
+)]
+pub struct AdminApi;
+
+/// Declares the two ways of presenting an API key, either of which the
+/// routes accept when keys are on
+struct ApiKeys;
+
+impl Modify for ApiKeys {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("x-api-key"))),
+        );
+        openapi.security = Some(vec![
+            SecurityRequirement::new("bearer", Vec::<String>::new()),
+            SecurityRequirement::new("api_key", Vec::<String>::new()),
+        ]);
+    }
+}
+
+/// Gives every operation a `default` response of [`ErrorResponse`]
+struct ErrorResponses;
+
+impl Modify for ErrorResponses {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let error: RefOr<Response> = utoipa::openapi::ResponseBuilder::new()
+            .description("Error; see `code` for why")
+            .content(
+                "application/json",
+                utoipa::openapi::ContentBuilder::new()
+                    .schema(Some(utoipa::openapi::Ref::from_schema_name(
+                        "ErrorResponse",
+                    )))
+                    .build(),
+            )
+            .build()
+            .into();
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.put,
+                &mut item.post,
+                &mut item.delete,
+            ];
+            for operation in operations.into_iter().flatten() {
+                operation
+                    .responses
+                    .responses
+                    .entry("default".to_string())
+                    .or_insert_with(|| error.clone());
+            }
+        }
+    }
+}
+
+/// Swagger UI, loaded from a CDN, showing the server's document
+///
+/// The assets are pinned to one release and checked against their SRI
+/// hashes; bump the version and both hashes together. The document URL is
+/// relative so the page keeps working behind a path-prefixing proxy.
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>vecstore API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5.17.14/swagger-ui.css"
+    integrity="sha384-wxLW6kwyHktdDGr6Pv1zgm/VGJh99lfUbzSn6HNHBENZlCN7W602k9VkGdxuFvPn"
+    crossorigin="anonymous">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5.17.14/swagger-ui-bundle.js"
+    integrity="sha384-wmyclcVGX/WhUkdkATwhaK1X1JtiNrr2EoYJ+diV3vj4v6OC5yCeSu+yW13SYJep"
+    crossorigin="anonymous"></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({ url: "./openapi.json", dom_id: "#swagger-ui" });
+    };
+  </script>
+</body>
+</html>
+"##;
+
+/// Add `/openapi.json` serving `spec`, and `/docs` with Swagger UI when
+/// `swagger_ui` is set
+pub(crate) fn serve(router: Router, spec: utoipa::openapi::OpenApi, swagger_ui: bool) -> Router {
+    let json = spec.to_json().expect("OpenAPI documents serialize");
+    let router = router.route(
+        "/openapi.json",
+        get(move || async move { ([(header::CONTENT_TYPE, "application/json")], json) }),
+    );
+    if swagger_ui {
+        router.route("/docs", get(|| async { Html(SWAGGER_UI) }))
+    } else {
+        router
+    }
+}
@@ -0,0 +1,562 @@
+//! The `/ws/query-stream` protocol
+//!
+//! Clients send JSON text messages tagged by `type`, each naming an `id` of
+//! their choosing that the server's answers repeat:
+//!
+//! - `query` takes the fields of `/v1/query`. It is answered by one
+//!   `result` per neighbor and then `complete` with the stats, or by
+//!   `error`. Queries run concurrently, so answers to different ids may
+//!   interleave.
+//! - `subscribe` registers a standing query. It is answered by `subscribed`
+//!   with the current results, and then by a `match` whenever an upserted
+//!   record lands in the query's top `limit`, read from the store's change
+//!   events.
+//! - `cancel` stops a running query or ends a subscription, answered by
+//!   `cancelled`.
+//! - `ping` is answered by `pong`.
+//!
+//! The server sends a WebSocket ping every [`StreamLimits::ping_interval`]
+//! and closes a connection it hasn't heard anything from, pongs included,
+//! within [`StreamLimits::idle_timeout`] with [`CLOSE_IDLE`]. Outgoing
+//! messages wait in a queue of [`StreamLimits::send_buffer`]; a client that
+//! lets it fill is closed with [`CLOSE_SLOW_CONSUMER`] rather than
+//! buffered for.
+
+use super::errors::{ErrorCode, ErrorResponse};
+use super::events::EventStream;
+use super::http::{self, QueryRequest, QueryResponse, QueryResult, QueryStats};
+use super::VecStoreHttpServer;
+use crate::store::{QueryProfile, StoreEvent};
+use axum::extract::ws::{CloseFrame, Message, WebSocket};
+use futures::stream::SplitSink;
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Close code of a connection that went quiet for the idle timeout
+pub const CLOSE_IDLE: u16 = 4000;
+
+/// Close code of a client that didn't read its messages fast enough
+pub const CLOSE_SLOW_CONSUMER: u16 = 4001;
+
+/// How long a closing connection waits to flush its close frame
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Limits on each `/ws/query-stream` connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamLimits {
+    /// Time between the server's pings
+    pub ping_interval: Duration,
+    /// Time without a message or pong from the client before it's closed
+    pub idle_timeout: Duration,
+    /// Queries a connection may run at once
+    pub max_queries: usize,
+    /// Subscriptions a connection may hold
+    pub max_subscriptions: usize,
+    /// Messages queued for the client before it counts as too slow
+    pub send_buffer: usize,
+}
+
+impl Default for StreamLimits {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(15),
+            idle_timeout: Duration::from_secs(60),
+            max_queries: 4,
+            max_subscriptions: 16,
+            send_buffer: 256,
+        }
+    }
+}
+
+/// A message from the client
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Run a query once
+    Query {
+        id: String,
+        #[serde(flatten)]
+        query: QueryRequest,
+    },
+    /// Register a standing query
+    Subscribe {
+        id: String,
+        #[serde(flatten)]
+        query: QueryRequest,
+    },
+    /// Stop a query or end a subscription
+    Cancel {
+        id: String,
+    },
+    Ping,
+}
+
+/// A message from the server
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    /// One neighbor of a query, in rank order
+    Result {
+        id: String,
+        result: QueryResult,
+    },
+    /// A query's last message
+    Complete {
+        id: String,
+        stats: Option<QueryStats>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        profile: Option<QueryProfile>,
+    },
+    /// A subscription is registered; `results` is its current top `limit`
+    Subscribed {
+        id: String,
+        results: Vec<QueryResult>,
+    },
+    /// An upserted record that is now in a subscription's results
+    Match {
+        id: String,
+        /// Position in the results, from 1
+        rank: usize,
+        result: QueryResult,
+    },
+    /// A query was stopped or a subscription ended
+    Cancelled {
+        id: String,
+    },
+    /// Change events were dropped before subscriptions saw them, so
+    /// matches may be missing
+    Lagged {
+        missed: u64,
+    },
+    /// A message failed; `id` is absent when it didn't parse
+    Error {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        #[serde(flatten)]
+        error: ErrorResponse,
+    },
+    Pong,
+}
+
+/// Serve the protocol on `socket` until either side closes it
+///
+/// `events` feeds subscriptions; without it `subscribe` answers
+/// `unimplemented`.
+pub(crate) async fn serve(
+    socket: WebSocket,
+    server: VecStoreHttpServer,
+    events: Option<EventStream>,
+    limits: StreamLimits,
+) {
+    super::metrics::websocket_connected();
+    let (sink, mut stream) = socket.split();
+    let (queue, queued) = mpsc::channel(limits.send_buffer.max(1));
+    let overflowed = Arc::new(Notify::new());
+    let mut writer = tokio::spawn(write(sink, queued, overflowed.clone()));
+    let mut connection = Connection {
+        server,
+        events,
+        limits,
+        outbox: Outbox { queue, overflowed },
+        queries: HashMap::new(),
+        subscriptions: HashMap::new(),
+        changes: None,
+    };
+
+    let mut ping =
+        tokio::time::interval_at(Instant::now() + limits.ping_interval, limits.ping_interval);
+    let idle = tokio::time::sleep(limits.idle_timeout);
+    tokio::pin!(idle);
+    let mut writing = true;
+    loop {
+        tokio::select! {
+            message = stream.next() => {
+                let Some(Ok(message)) = message else { break };
+                idle.as_mut().reset(Instant::now() + limits.idle_timeout);
+                match message {
+                    Message::Text(text) => connection.receive(&text).await,
+                    Message::Close(_) => break,
+                    _ => {}
+                }
+            }
+            received = connection.next_change() => connection.notify(received).await,
+            _ = ping.tick() => {
+                connection.outbox.push_frame(Message::Ping(Default::default()));
+            }
+            _ = &mut idle => {
+                connection.outbox.close(CLOSE_IDLE, "idle timeout");
+                break;
+            }
+            _ = &mut writer => {
+                writing = false;
+                break;
+            }
+        }
+    }
+
+    // Dropping the last sender lets the writer finish the queue
+    for task in connection.queries.values() {
+        task.abort();
+    }
+    drop(connection);
+    if writing {
+        let _ = tokio::time::timeout(CLOSE_TIMEOUT, writer).await;
+    }
+    super::metrics::websocket_disconnected();
+}
+
+/// Send queued frames until the queue closes, a close frame is sent, or the
+/// client turns out too slow
+async fn write(
+    mut sink: SplitSink<WebSocket, Message>,
+    mut queued: mpsc::Receiver<Message>,
+    overflowed: Arc<Notify>,
+) {
+    loop {
+        let frame = tokio::select! {
+            frame = queued.recv() => frame,
+            _ = overflowed.notified() => break,
+        };
+        let Some(frame) = frame else { return };
+        let closing = matches!(frame, Message::Close(_));
+        tokio::select! {
+            sent = sink.send(frame) => if sent.is_err() || closing {
+                return;
+            },
+            _ = overflowed.notified() => break,
+        }
+    }
+
+    // Whatever is still queued is dropped
+    let frame = CloseFrame {
+        code: CLOSE_SLOW_CONSUMER,
+        reason: "slow consumer".into(),
+    };
+    let _ = tokio::time::timeout(CLOSE_TIMEOUT, sink.send(Message::Close(Some(frame)))).await;
+}
+
+/// The sending side of a connection's queue
+#[derive(Clone)]
+struct Outbox {
+    queue: mpsc::Sender<Message>,
+    overflowed: Arc<Notify>,
+}
+
+impl Outbox {
+    /// Queue `message` without waiting; a full queue closes the connection
+    fn push(&self, message: &ServerMessage) -> bool {
+        match encode(message) {
+            Some(frame) => self.push_frame(frame),
+            None => true,
+        }
+    }
+
+    fn push_frame(&self, frame: Message) -> bool {
+        match self.queue.try_send(frame) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                self.overflowed.notify_one();
+                false
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// Queue `message`, waiting for room
+    async fn send(&self, message: &ServerMessage) -> bool {
+        match encode(message) {
+            Some(frame) => self.queue.send(frame).await.is_ok(),
+            None => true,
+        }
+    }
+
+    fn close(&self, code: u16, reason: &str) {
+        let frame = CloseFrame {
+            code,
+            reason: reason.into(),
+        };
+        self.push_frame(Message::Close(Some(frame)));
+    }
+}
+
+fn encode(message: &ServerMessage) -> Option<Message> {
+    match serde_json::to_string(message) {
+        Ok(json) => Some(Message::Text(json.into())),
+        Err(e) => {
+            tracing::error!("Failed to serialize stream message: {}", e);
+            None
+        }
+    }
+}
+
+fn error(id: Option<String>, error: ErrorResponse) -> ServerMessage {
+    ServerMessage::Error { id, error }
+}
+
+struct Connection {
+    server: VecStoreHttpServer,
+    events: Option<EventStream>,
+    limits: StreamLimits,
+    outbox: Outbox,
+    queries: HashMap<String, JoinHandle<()>>,
+    subscriptions: HashMap<String, QueryRequest>,
+    /// The store's changes, received while any subscription is held
+    changes: Option<broadcast::Receiver<StoreEvent>>,
+}
+
+impl Connection {
+    async fn receive(&mut self, text: &str) {
+        let message = match serde_json::from_str(text) {
+            Ok(message) => message,
+            Err(e) => {
+                let invalid = ErrorResponse::new(
+                    ErrorCode::InvalidArgument,
+                    format!("Invalid message: {}", e),
+                );
+                self.outbox.push(&error(None, invalid));
+                return;
+            }
+        };
+        match message {
+            ClientMessage::Query { id, query } => self.start_query(id, query),
+            ClientMessage::Subscribe { id, query } => self.subscribe(id, query).await,
+            ClientMessage::Cancel { id } => self.cancel(id),
+            ClientMessage::Ping => {
+                self.outbox.push(&ServerMessage::Pong);
+            }
+        }
+    }
+
+    /// Forget finished queries
+    fn reap(&mut self) {
+        self.queries.retain(|_, task| !task.is_finished());
+    }
+
+    /// Check that `id` is free and one more of `running` `kind` fits
+    /// under `max`
+    fn admit(&self, id: &str, running: usize, max: usize, kind: &str) -> Result<(), ErrorResponse> {
+        if self.queries.contains_key(id) || self.subscriptions.contains_key(id) {
+            return Err(ErrorResponse::new(
+                ErrorCode::InvalidArgument,
+                format!("Id '{}' is already in use on this connection", id),
+            ));
+        }
+        if running >= max {
+            return Err(ErrorResponse::new(
+                ErrorCode::TooManyQueries,
+                format!("This connection already holds {} {}", max, kind),
+            )
+            .with_details(serde_json::json!({ "limit": max })));
+        }
+        Ok(())
+    }
+
+    fn start_query(&mut self, id: String, request: QueryRequest) {
+        self.reap();
+        let running = self.queries.len();
+        if let Err(refused) = self.admit(&id, running, self.limits.max_queries, "queries") {
+            self.outbox.push(&error(Some(id), refused));
+            return;
+        }
+        let server = self.server.clone();
+        let outbox = self.outbox.clone();
+        let name = id.clone();
+        let task = tokio::spawn(async move {
+            match http::run_vector_query(&server, request, "stream").await {
+                Ok(QueryResponse {
+                    results,
+                    stats,
+                    profile,
+                }) => {
+                    for result in results {
+                        let id = id.clone();
+                        if !outbox.send(&ServerMessage::Result { id, result }).await {
+                            return;
+                        }
+                    }
+                    outbox
+                        .send(&ServerMessage::Complete { id, stats, profile })
+                        .await;
+                }
+                Err(e) => {
+                    let failed = error(Some(id), ErrorResponse::from_error(&e));
+                    outbox.send(&failed).await;
+                }
+            }
+        });
+        self.queries.insert(name, task);
+    }
+
+    async fn subscribe(&mut self, id: String, request: QueryRequest) {
+        let Some(events) = self.events.clone() else {
+            let unavailable = ErrorResponse::new(
+                ErrorCode::Unimplemented,
+                "Subscriptions need change events; start the server with --events",
+            );
+            self.outbox.push(&error(Some(id), unavailable));
+            return;
+        };
+        self.reap();
+        let running = self.subscriptions.len();
+        let max = self.limits.max_subscriptions;
+        if let Err(refused) = self.admit(&id, running, max, "subscriptions") {
+            self.outbox.push(&error(Some(id), refused));
+            return;
+        }
+
+        // Listen before the first run, so no write between them is missed
+        self.changes.get_or_insert_with(|| events.receiver());
+        match http::run_vector_query(&self.server, request.clone(), "subscription").await {
+            Ok(response) => {
+                self.outbox.push(&ServerMessage::Subscribed {
+                    id: id.clone(),
+                    results: response.results,
+                });
+                self.subscriptions.insert(id, request);
+            }
+            Err(e) => {
+                self.outbox
+                    .push(&error(Some(id), ErrorResponse::from_error(&e)));
+                if self.subscriptions.is_empty() {
+                    self.changes = None;
+                }
+            }
+        }
+    }
+
+    fn cancel(&mut self, id: String) {
+        self.reap();
+        if let Some(task) = self.queries.remove(&id) {
+            task.abort();
+        } else if self.subscriptions.remove(&id).is_some() {
+            if self.subscriptions.is_empty() {
+                self.changes = None;
+            }
+        } else {
+            let unknown = ErrorResponse::new(
+                ErrorCode::NotFound,
+                format!("No running query or subscription '{}'", id),
+            );
+            self.outbox.push(&error(Some(id), unknown));
+            return;
+        }
+        self.outbox.push(&ServerMessage::Cancelled { id });
+    }
+
+    /// The next change event, pending while nothing is subscribed
+    async fn next_change(&mut self) -> Result<StoreEvent, RecvError> {
+        match self.changes.as_mut() {
+            Some(changes) => changes.recv().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Rerun each subscription once for `received` and every change already
+    /// waiting, reporting the upserted records now in its results
+    async fn notify(&mut self, received: Result<StoreEvent, RecvError>) {
+        let mut upserted = HashSet::new();
+        let mut missed = 0;
+        let mut next = Some(received);
+        while let Some(received) = next.take() {
+            match received {
+                Ok(StoreEvent::Upsert { id, .. }) => {
+                    upserted.insert(id);
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(n)) => missed += n,
+                Err(RecvError::Closed) => {
+                    self.changes = None;
+                    break;
+                }
+            }
+            if let Some(changes) = self.changes.as_mut() {
+                next = match changes.try_recv() {
+                    Ok(event) => Some(Ok(event)),
+                    Err(TryRecvError::Lagged(n)) => Some(Err(RecvError::Lagged(n))),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Closed) => Some(Err(RecvError::Closed)),
+                };
+            }
+        }
+
+        if missed > 0 && !self.outbox.push(&ServerMessage::Lagged { missed }) {
+            return;
+        }
+        if upserted.is_empty() {
+            return;
+        }
+        for (id, request) in &self.subscriptions {
+            let response =
+                match http::run_vector_query(&self.server, request.clone(), "subscription").await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        let failed = error(Some(id.clone()), ErrorResponse::from_error(&e));
+                        if !self.outbox.push(&failed) {
+                            return;
+                        }
+                        continue;
+                    }
+                };
+            let matches = response
+                .results
+                .into_iter()
+                .enumerate()
+                .filter(|(_, result)| upserted.contains(&result.id));
+            for (rank, result) in matches {
+                let matched = ServerMessage::Match {
+                    id: id.clone(),
+                    rank: rank + 1,
+                    result,
+                };
+                if !self.outbox.push(&matched) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_messages_are_tagged_by_type() {
+        let message: ClientMessage = serde_json::from_value(json!({
+            "type": "subscribe",
+            "id": "s1",
+            "vector": [1.0, 0.0],
+            "limit": 3,
+            "include_vector": true,
+        }))
+        .unwrap();
+        let ClientMessage::Subscribe { id, query } = message else {
+            panic!("parsed as {:?}", message);
+        };
+        assert_eq!(id, "s1");
+        assert_eq!(query.limit, 3);
+        assert!(query.options.include_vector);
+
+        let refused = error(
+            Some("q1".to_string()),
+            ErrorResponse::new(ErrorCode::TooManyQueries, "busy"),
+        );
+        assert_eq!(
+            serde_json::to_value(&refused).unwrap(),
+            json!({"type": "error", "id": "q1", "code": "too_many_queries", "message": "busy"})
+        );
+        assert_eq!(
+            serde_json::to_value(ServerMessage::Pong).unwrap(),
+            json!({"type": "pong"})
+        );
+    }
+}
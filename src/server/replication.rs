@@ -0,0 +1,376 @@
+//! Read-only replicas of a single-store server
+//!
+//! Any single-store server is a primary. It serves the sequence number of
+//! its latest change at `GET /admin/replication/sequence`, and a
+//! compressed backup of the store (manifest and segments, as written by
+//! `VecStore::backup_compressed`) at `GET /admin/replication/snapshot`.
+//! Both need an `admin` key when API keys are on.
+//!
+//! A server started with `--replica-of <url>` runs a [`Replica`], which
+//! polls the sequence and, when it moved, downloads a snapshot, restores it
+//! beside the store and swaps it in with `VecStore::replace_with`. Queries
+//! keep answering from the old copy until the new one is loaded and
+//! indexed, then see all of it at once. Replicas refuse the routes and RPCs
+//! that change records with `read_only_replica`, naming the primary.
+//!
+//! Lag is the operations the copy is behind as of the last poll, and the
+//! seconds since the copy was last known to match the primary. A replica's
+//! `GET /health` reports both under `replication`, and `/metrics` as
+//! `vecstore_replication_lag_operations` and
+//! `vecstore_replication_lag_seconds`.
+
+use super::auth;
+use super::errors::{ErrorCode, ErrorResponse};
+use super::shutdown::Shutdown;
+use crate::store::VecStore;
+use anyhow::{anyhow, Context, Result};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use ureq::tls::{TlsConfig, TlsProvider};
+use ureq::Agent;
+use utoipa::ToSchema;
+
+/// How often a replica polls its primary by default
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long a replica waits for the primary to start answering; a
+/// snapshot is written in full before it is sent
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Answer of `GET /admin/replication/sequence`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ReplicationSequence {
+    /// Sequence number of the store's latest change
+    pub seq: u64,
+}
+
+/// How far a replica's copy is behind its primary
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReplicaStatus {
+    /// The primary's URL; writes go there
+    pub primary: String,
+    /// The primary's sequence number the served copy matches; 0 until the
+    /// first snapshot is applied
+    pub applied_seq: u64,
+    /// The primary's sequence number at the last successful poll
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_seq: Option<u64>,
+    /// Operations the copy is behind, as of the last successful poll
+    pub lag_operations: u64,
+    /// Seconds since the copy was last known to match the primary, or
+    /// since the replica started if it never has
+    pub lag_seconds: f64,
+    /// When the last snapshot was applied, as a Unix timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_applied_at: Option<i64>,
+    /// Why the last poll failed; cleared by the next one that succeeds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct SyncState {
+    applied_seq: u64,
+    primary_seq: Option<u64>,
+    /// When the copy was last known to match the primary
+    in_sync_at: Option<Instant>,
+    last_applied_at: Option<i64>,
+    last_error: Option<String>,
+}
+
+/// Keeps a store a read-only copy of a primary's
+pub struct Replica {
+    primary: String,
+    api_key: Option<String>,
+    store: Arc<RwLock<VecStore>>,
+    agent: Agent,
+    started: Instant,
+    state: Mutex<SyncState>,
+    /// Held for a whole sync, so two never download at once
+    syncing: tokio::sync::Mutex<()>,
+}
+
+impl Replica {
+    /// Copy the store of the primary whose HTTP API is at `primary`, e.g.
+    /// `http://primary:8080`, into `store`
+    pub fn new(primary: impl Into<String>, store: Arc<RwLock<VecStore>>) -> Self {
+        let agent = Agent::config_builder()
+            .timeout_connect(Some(Duration::from_secs(10)))
+            .timeout_recv_response(Some(RESPONSE_TIMEOUT))
+            .http_status_as_error(false)
+            .tls_config(
+                TlsConfig::builder()
+                    .provider(TlsProvider::NativeTls)
+                    .build(),
+            )
+            .build()
+            .into();
+        Self {
+            primary: primary.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            store,
+            agent,
+            started: Instant::now(),
+            state: Mutex::new(SyncState::default()),
+            syncing: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Present `key` to the primary, which needs `admin` scope when its API
+    /// keys are on
+    pub fn with_api_key(mut self, key: Option<String>) -> Self {
+        self.api_key = key;
+        self
+    }
+
+    /// URL of the primary
+    pub fn primary(&self) -> &str {
+        &self.primary
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, SyncState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Where the copy stands
+    pub fn status(&self) -> ReplicaStatus {
+        let state = self.state();
+        ReplicaStatus {
+            primary: self.primary.clone(),
+            applied_seq: state.applied_seq,
+            primary_seq: state.primary_seq,
+            lag_operations: state
+                .primary_seq
+                .map_or(0, |seq| seq.saturating_sub(state.applied_seq)),
+            lag_seconds: state
+                .in_sync_at
+                .unwrap_or(self.started)
+                .elapsed()
+                .as_secs_f64(),
+            last_applied_at: state.last_applied_at,
+            last_error: state.last_error.clone(),
+        }
+    }
+
+    /// The `read_only_replica` error writes are refused with
+    pub fn read_only(&self) -> ErrorResponse {
+        ErrorResponse::new(
+            ErrorCode::ReadOnlyReplica,
+            format!(
+                "This server is a read-only replica; send writes to the primary at {}",
+                self.primary
+            ),
+        )
+        .with_details(serde_json::json!({ "primary": self.primary }))
+    }
+
+    /// Poll the primary once, applying a snapshot if its sequence moved
+    ///
+    /// Returns whether a new copy was swapped in. On failure the old copy
+    /// stays in service, and the error is kept for [`status`](Self::status).
+    pub async fn sync(&self) -> Result<bool> {
+        let _syncing = self.syncing.lock().await;
+        let outcome = self.try_sync().await;
+        let label = match &outcome {
+            Ok(true) => "applied",
+            Ok(false) => "current",
+            Err(_) => "failed",
+        };
+        super::metrics::record_replication_sync(label);
+
+        let mut state = self.state();
+        match &outcome {
+            Ok(_) => state.last_error = None,
+            Err(e) => {
+                warn!("Replication from {} failed: {:#}", self.primary, e);
+                state.last_error = Some(format!("{:#}", e));
+            }
+        }
+        outcome
+    }
+
+    async fn try_sync(&self) -> Result<bool> {
+        let polled = Instant::now();
+        let seq = self.fetch_sequence().await?;
+        {
+            let mut state = self.state();
+            state.primary_seq = Some(seq);
+            // Until a snapshot is applied the store holds whatever it was
+            // opened with, whatever its sequence
+            if state.last_applied_at.is_some() && seq == state.applied_seq {
+                state.in_sync_at = Some(polled);
+                return Ok(false);
+            }
+        }
+
+        let staging = tempfile::Builder::new()
+            .prefix("vecstore-replica")
+            .tempdir()
+            .context("Failed to create staging directory")?;
+        let backup = staging.path().join("snapshot.vsbak");
+        let requested = Instant::now();
+        self.fetch_snapshot(backup.clone()).await?;
+        let dest = staging.path().join("store");
+        let restored =
+            tokio::task::spawn_blocking(move || VecStore::restore_backup(&backup, dest)).await??;
+        let applied = restored.op_seq();
+        let records = restored.len();
+
+        let mut store = self.store.clone().write_owned().await;
+        store.replace_with(restored);
+        // Save so a restart serves this copy until the next snapshot
+        let store = store.downgrade();
+        tokio::task::spawn_blocking(move || store.save()).await??;
+
+        let mut state = self.state();
+        state.applied_seq = applied;
+        state.primary_seq = Some(applied);
+        state.in_sync_at = Some(requested);
+        state.last_applied_at = Some(unix_now());
+        info!(
+            "🔁 Applied snapshot of {} at operation {} ({} records)",
+            self.primary, applied, records
+        );
+        Ok(true)
+    }
+
+    /// The primary's current sequence number
+    async fn fetch_sequence(&self) -> Result<u64> {
+        let (agent, url, key) = self.request("sequence");
+        let text = tokio::task::spawn_blocking(move || {
+            let response = get(&agent, &url, key.as_deref())?;
+            let text = response
+                .into_body()
+                .read_to_string()
+                .with_context(|| format!("Failed to read {}", url))?;
+            Ok::<_, anyhow::Error>(text)
+        })
+        .await??;
+        let sequence: ReplicationSequence =
+            serde_json::from_str(&text).context("Invalid replication sequence")?;
+        Ok(sequence.seq)
+    }
+
+    /// Download a snapshot of the primary to `output`
+    async fn fetch_snapshot(&self, output: PathBuf) -> Result<()> {
+        let (agent, url, key) = self.request("snapshot");
+        tokio::task::spawn_blocking(move || {
+            let response = get(&agent, &url, key.as_deref())?;
+            let mut file = std::fs::File::create(&output)
+                .with_context(|| format!("Failed to create {:?}", output))?;
+            std::io::copy(&mut response.into_body().into_reader(), &mut file)
+                .with_context(|| format!("Failed to download {}", url))?;
+            file.sync_all()?;
+            Ok(())
+        })
+        .await?
+    }
+
+    fn request(&self, endpoint: &str) -> (Agent, String, Option<String>) {
+        let url = format!("{}/admin/replication/{}", self.primary, endpoint);
+        (self.agent.clone(), url, self.api_key.clone())
+    }
+
+    /// Sync every `every` until `shutdown` fires, starting now
+    pub fn spawn(self: Arc<Self>, every: Duration, shutdown: Shutdown) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(every);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            let stopped = shutdown.signalled();
+            tokio::pin!(stopped);
+            loop {
+                tokio::select! {
+                    _ = ticks.tick() => {
+                        // Failures are logged and reported by `status`
+                        let _ = self.sync().await;
+                    }
+                    _ = &mut stopped => break,
+                }
+            }
+        })
+    }
+}
+
+/// GET `url`, turning an error status into an error carrying the
+/// primary's message
+fn get(
+    agent: &Agent,
+    url: &str,
+    api_key: Option<&str>,
+) -> Result<ureq::http::Response<ureq::Body>> {
+    let mut request = agent.get(url);
+    if let Some(key) = api_key {
+        request = request.header("Authorization", &format!("Bearer {}", key));
+    }
+    let response = request
+        .call()
+        .with_context(|| format!("Request to {} failed", url))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.into_body().read_to_string().unwrap_or_default();
+        let message = serde_json::from_str::<ErrorResponse>(&body)
+            .map(|e| e.message)
+            .unwrap_or(body);
+        return Err(anyhow!("{} answered {}: {}", url, status, message));
+    }
+    Ok(response)
+}
+
+/// Route layer refusing the routes `writes` marks as changing records
+#[derive(Clone)]
+pub(crate) struct ReadOnlyLayer {
+    pub replica: Arc<Replica>,
+    pub writes: fn(&str) -> bool,
+}
+
+/// Answer writes to a replica with `read_only_replica`
+pub(crate) async fn refuse_writes(
+    State(layer): State<ReadOnlyLayer>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if (layer.writes)(&auth::matched_route(&request)) {
+        return layer.replica.read_only().into_response();
+    }
+    next.run(request).await
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_before_the_first_poll() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(RwLock::new(VecStore::open(dir.path()).unwrap()));
+        let replica = Replica::new("http://primary:8080/", store);
+
+        let status = replica.status();
+        assert_eq!(status.primary, "http://primary:8080");
+        assert_eq!(status.applied_seq, 0);
+        assert_eq!(status.primary_seq, None);
+        assert_eq!(status.lag_operations, 0);
+
+        let error = replica.read_only();
+        assert_eq!(error.code, ErrorCode::ReadOnlyReplica);
+        assert!(error.message.contains("http://primary:8080"));
+        assert_eq!(error.details.unwrap()["primary"], "http://primary:8080");
+    }
+}
@@ -0,0 +1,300 @@
+//! Request ids and a tracing span per request, shared by the HTTP and gRPC
+//! servers
+//!
+//! A request is known by the `x-request-id` it arrives with, or else by the
+//! trace id of its W3C `traceparent` header, or else by a fresh random id.
+//! The id goes back in the response's `x-request-id` header, and REST error
+//! bodies carry it as `request_id`, so a client reporting a failure can
+//! quote it. Each request runs in a `request` span holding the id, method,
+//! route, namespace, status and latency, which closes with one log line;
+//! log lines written while handling the request carry the same fields.
+
+use super::errors::ErrorResponse;
+use axum::{
+    body::{Body, HttpBody},
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
+
+/// Header carrying a request's id, both ways
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-chosen id kept; longer ones are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Largest error body given a `request_id`
+const MAX_ERROR_BODY: u64 = 64 * 1024;
+
+/// The id of the request being handled, in the request's extensions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// The client's `x-request-id`, the trace id of its `traceparent`, or a
+    /// new random id
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let given = headers
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN);
+        if let Some(id) = given {
+            return Self(id.to_string());
+        }
+        let traced = headers
+            .get("traceparent")
+            .and_then(|value| value.to_str().ok())
+            .and_then(trace_id);
+        Self(traced.unwrap_or_else(|| format!("{:032x}", rand::random::<u128>())))
+    }
+
+    fn header_value(&self) -> Option<HeaderValue> {
+        HeaderValue::from_str(&self.0).ok()
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The trace id of a `traceparent` header: `version-traceid-parentid-flags`
+fn trace_id(traceparent: &str) -> Option<String> {
+    let mut parts = traceparent.trim().split('-');
+    let (_version, trace_id) = (parts.next()?, parts.next()?);
+    let valid = trace_id.len() == 32
+        && trace_id.bytes().all(|b| b.is_ascii_hexdigit())
+        && trace_id.bytes().any(|b| b != b'0');
+    valid.then(|| trace_id.to_ascii_lowercase())
+}
+
+/// The span a request runs in; `route`, `status` and `latency_ms` are
+/// recorded as they become known
+fn request_span(id: &RequestId, protocol: &str, method: &str) -> Span {
+    tracing::info_span!(
+        "request",
+        request_id = %id,
+        protocol,
+        method,
+        route = Empty,
+        namespace = Empty,
+        status = Empty,
+        latency_ms = Empty,
+    )
+}
+
+/// Record the final status and latency and write the request's log line
+fn finish(span: &Span, status: &str, failed: bool, start: Instant) {
+    span.record("status", status);
+    span.record("latency_ms", start.elapsed().as_secs_f64() * 1000.0);
+    span.in_scope(|| {
+        if failed {
+            tracing::warn!("request failed");
+        } else {
+            tracing::info!("request finished");
+        }
+    });
+}
+
+/// Record the route the request matched in its span
+pub(crate) fn record_route(route: &str) {
+    Span::current().record("route", route);
+}
+
+/// Middleware giving each HTTP request an id and a span
+///
+/// Add it outside [`errors::envelope`](super::errors::envelope), so
+/// rewritten error bodies get the id too.
+pub(crate) async fn trace_http(mut request: Request, next: Next) -> Response {
+    let id = RequestId::from_headers(request.headers());
+    let span = request_span(&id, "http", request.method().as_str());
+    if let Some(namespace) = super::admin_http::path_namespace(request.uri().path()) {
+        span.record("namespace", namespace.as_str());
+    }
+    request.extensions_mut().insert(id.clone());
+
+    let start = Instant::now();
+    let response = next.run(request).instrument(span.clone()).await;
+    let status = response.status();
+    finish(&span, status.as_str(), status.is_server_error(), start);
+
+    let mut response = if status.is_client_error() || status.is_server_error() {
+        with_id_in_body(response, &id).await
+    } else {
+        response
+    };
+    if let Some(value) = id.header_value() {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Set `request_id` in a JSON error body
+async fn with_id_in_body(response: Response, id: &RequestId) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"application/json"));
+    let small = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|size| size <= MAX_ERROR_BODY);
+    if !(is_json && small) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = axum::body::to_bytes(body, MAX_ERROR_BODY as usize)
+        .await
+        .unwrap_or_default();
+    let body = match serde_json::from_slice::<ErrorResponse>(&bytes) {
+        Ok(mut error) => {
+            error.request_id = Some(id.0.clone());
+            parts.headers.remove(header::CONTENT_LENGTH);
+            serde_json::to_vec(&error).unwrap_or_default().into()
+        }
+        Err(_) => bytes,
+    };
+    Response::from_parts(parts, Body::from(body))
+}
+
+/// Tower layer giving each gRPC call an id and a span
+///
+/// Add it with `Server::builder().layer(..)`. The id is sent back in the
+/// `x-request-id` response metadata and is in the request's extensions as
+/// a [`RequestId`]. Like [`GrpcMetricsLayer`](super::metrics::GrpcMetricsLayer),
+/// it reads the status from the response headers, and streaming calls are
+/// timed until their first response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GrpcTraceLayer;
+
+impl<S> tower::Layer<S> for GrpcTraceLayer {
+    type Service = GrpcTrace<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcTrace { inner }
+    }
+}
+
+/// Service added by [`GrpcTraceLayer`]
+#[derive(Debug, Clone)]
+pub struct GrpcTrace<S> {
+    inner: S,
+}
+
+impl<S, B, ResBody> tower::Service<axum::http::Request<B>> for GrpcTrace<S>
+where
+    S: tower::Service<axum::http::Request<B>, Response = axum::http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: axum::http::Request<B>) -> Self::Future {
+        let id = RequestId::from_headers(request.headers());
+        let span = request_span(&id, "grpc", "POST");
+        span.record("route", request.uri().path());
+        request.extensions_mut().insert(id.clone());
+
+        let start = Instant::now();
+        let response = span.in_scope(|| self.inner.call(request));
+        Box::pin(
+            async move {
+                let mut response = response.await?;
+                let code = response
+                    .headers()
+                    .get("grpc-status")
+                    .and_then(|status| status.to_str().ok())
+                    .and_then(|status| status.parse::<i32>().ok())
+                    .map(tonic::Code::from)
+                    .unwrap_or(tonic::Code::Ok);
+                let failed = matches!(
+                    code,
+                    tonic::Code::Internal | tonic::Code::Unknown | tonic::Code::DataLoss
+                );
+                finish(&Span::current(), &format!("{:?}", code), failed, start);
+                if let Some(value) = id.header_value() {
+                    response.headers_mut().insert(REQUEST_ID_HEADER, value);
+                }
+                Ok(response)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower::{Layer, Service, ServiceExt};
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_request_id_sources() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let id = RequestId::from_headers(&headers(&[
+            (REQUEST_ID_HEADER, "abc-123"),
+            ("traceparent", traceparent),
+        ]));
+        assert_eq!(id.0, "abc-123");
+
+        let id = RequestId::from_headers(&headers(&[("traceparent", traceparent)]));
+        assert_eq!(id.0, "4bf92f3577b34da6a3ce929d0e0e4736");
+
+        // All-zero trace ids are invalid, and overlong ids are replaced
+        let long = "x".repeat(MAX_REQUEST_ID_LEN + 1);
+        let id = RequestId::from_headers(&headers(&[
+            (REQUEST_ID_HEADER, &long),
+            (
+                "traceparent",
+                "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            ),
+        ]));
+        assert_eq!(id.0.len(), 32);
+        assert_ne!(id, RequestId::from_headers(&HeaderMap::new()));
+    }
+
+    #[tokio::test]
+    async fn test_grpc_calls_echo_their_id() {
+        let mut service = GrpcTraceLayer.layer(tower::service_fn(
+            |request: axum::http::Request<()>| async move {
+                let id = request.extensions().get::<RequestId>().cloned().unwrap();
+                let response = axum::http::Response::builder()
+                    .header("grpc-status", "5")
+                    .header("seen-id", id.0)
+                    .body(())
+                    .unwrap();
+                Ok::<_, std::convert::Infallible>(response)
+            },
+        ));
+        let request = axum::http::Request::post("/vecstore.VecStoreService/Get")
+            .header(REQUEST_ID_HEADER, "call-7")
+            .body(())
+            .unwrap();
+        let response = service.ready().await.unwrap().call(request).await.unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "call-7");
+        assert_eq!(response.headers()["seen-id"], "call-7");
+    }
+}
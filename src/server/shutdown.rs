@@ -0,0 +1,168 @@
+//! Graceful shutdown for `vecstore-server`
+//!
+//! On SIGINT or SIGTERM the servers stop accepting connections and finish
+//! the requests already in flight, for up to a deadline. The caller then
+//! saves the store under its write lock; `VecStore::save` also checkpoints
+//! the write-ahead log, so every write acknowledged before the signal
+//! survives the restart.
+
+use anyhow::Result;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Trigger shared by the servers; each stops accepting once it fires
+#[derive(Clone)]
+pub struct Shutdown {
+    started: Arc<watch::Sender<bool>>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            started: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    /// Start shutting down
+    pub fn trigger(&self) {
+        self.started.send_replace(true);
+    }
+
+    /// Whether shutdown has started
+    pub fn is_triggered(&self) -> bool {
+        *self.started.borrow()
+    }
+
+    /// Resolves once shutdown starts, for axum's `with_graceful_shutdown`
+    /// and tonic's `serve_with_shutdown`
+    pub fn signalled(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut started = self.started.subscribe();
+        async move {
+            let _ = started.wait_for(|started| *started).await;
+        }
+    }
+
+    /// Trigger on SIGINT (Ctrl+C) or SIGTERM
+    pub fn trigger_on_signal(&self) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            let signal = wait_for_signal().await;
+            info!("🛑 Received {}, no longer accepting connections", signal);
+            shutdown.trigger();
+        });
+    }
+}
+
+/// Wait for SIGINT or, on Unix, SIGTERM; returns the signal's name
+pub async fn wait_for_signal() -> &'static str {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => tokio::select! {
+                _ = tokio::signal::ctrl_c() => "SIGINT",
+                _ = terminate.recv() => "SIGTERM",
+            },
+            Err(e) => {
+                warn!("Cannot listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+                "SIGINT"
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+        "Ctrl+C"
+    }
+}
+
+/// Run the server tasks until one fails or shutdown starts, then give them
+/// `deadline` to finish the requests in flight
+///
+/// Tasks still running at the deadline are aborted, dropping their
+/// connections. Returns the first server error, if any.
+pub async fn serve_until_shutdown(
+    handles: Vec<JoinHandle<Result<()>>>,
+    shutdown: &Shutdown,
+    deadline: Duration,
+) -> Result<()> {
+    let aborts: Vec<_> = handles.iter().map(JoinHandle::abort_handle).collect();
+    let servers = async move {
+        for handle in handles {
+            handle.await??;
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+    tokio::pin!(servers);
+
+    tokio::select! {
+        result = &mut servers => return result,
+        _ = shutdown.signalled() => {}
+    }
+
+    info!(
+        "⏳ Draining in-flight requests (deadline {}s)",
+        deadline.as_secs_f64()
+    );
+    match tokio::time::timeout(deadline, &mut servers).await {
+        Ok(result) => {
+            info!("All in-flight requests finished");
+            result
+        }
+        Err(_) => {
+            warn!("Requests still running at the shutdown deadline were dropped");
+            for abort in aborts {
+                abort.abort();
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_waits_for_servers_and_aborts_at_the_deadline() {
+        let shutdown = Shutdown::new();
+        let stopped = shutdown.signalled();
+        let finishing = tokio::spawn(async move {
+            stopped.await;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(())
+        });
+        let hung = tokio::spawn(std::future::pending::<Result<()>>());
+        let hung_abort = hung.abort_handle();
+
+        shutdown.trigger();
+        assert!(shutdown.is_triggered());
+        serve_until_shutdown(vec![finishing, hung], &shutdown, Duration::from_millis(100))
+            .await
+            .unwrap();
+        tokio::task::yield_now().await;
+        assert!(hung_abort.is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_end_serving_without_shutdown() {
+        let shutdown = Shutdown::new();
+        let failed = tokio::spawn(async { Err(anyhow::anyhow!("address in use")) });
+        let error = serve_until_shutdown(vec![failed], &shutdown, Duration::from_secs(1))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("address in use"));
+        assert!(!shutdown.is_triggered());
+    }
+}
@@ -0,0 +1,129 @@
+//! Query timeouts and cancellation, shared by the HTTP and gRPC servers
+//!
+//! Each query runs on a blocking thread under a [`QueryCancel`] whose
+//! deadline is the request's `timeout_ms`, capped at [`QueryTimeouts::max`],
+//! or else [`QueryTimeouts::default`]. Past the deadline the request fails
+//! with `deadline_exceeded` (504, gRPC `DEADLINE_EXCEEDED`) and the elapsed
+//! time, and the query stops at its next check, logged as a timed-out slow
+//! query. A client that disconnects drops its request, which cancels the
+//! query the same way.
+
+use crate::store::{QueryCancel, QueryInterrupted};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Timeout of a query that doesn't ask for one, by default
+pub const DEFAULT_QUERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest timeout a query may ask for, by default
+pub const DEFAULT_MAX_QUERY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How long queries may run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryTimeouts {
+    /// Timeout of a query that doesn't ask for one
+    pub default: Duration,
+    /// Longest timeout a query may ask for
+    pub max: Duration,
+}
+
+impl Default for QueryTimeouts {
+    fn default() -> Self {
+        Self {
+            default: DEFAULT_QUERY_TIMEOUT,
+            max: DEFAULT_MAX_QUERY_TIMEOUT,
+        }
+    }
+}
+
+impl QueryTimeouts {
+    /// The timeout of a query asking for `requested_ms`
+    pub fn resolve(&self, requested_ms: Option<u64>) -> Duration {
+        requested_ms
+            .map(Duration::from_millis)
+            .unwrap_or(self.default)
+            .min(self.max)
+    }
+}
+
+/// Cancels its query when dropped, so a request dropped before its query
+/// finishes stops the query
+struct CancelOnDrop(QueryCancel);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Run `query` against the value behind `lock` on a blocking thread,
+/// stopping it after `timeout` or once the returned future is dropped
+///
+/// Waiting for the lock counts against the timeout. A query past its
+/// deadline fails with [`QueryInterrupted`].
+pub(crate) async fn run<S, T>(
+    lock: Arc<RwLock<S>>,
+    timeout: Duration,
+    query: impl FnOnce(&S, QueryCancel) -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T>
+where
+    S: Send + Sync + 'static,
+    T: Send + 'static,
+{
+    let cancel = QueryCancel::with_timeout(timeout);
+    let _guard = CancelOnDrop(cancel.clone());
+    let task = {
+        let cancel = cancel.clone();
+        async move {
+            let value = lock.read_owned().await;
+            tokio::task::spawn_blocking(move || query(&value, cancel)).await
+        }
+    };
+    match tokio::time::timeout(timeout, task).await {
+        Ok(joined) => joined.map_err(|e| anyhow::anyhow!("Query task failed: {}", e))?,
+        Err(_) => Err(QueryInterrupted {
+            elapsed: cancel.elapsed(),
+            cancelled: false,
+        }
+        .into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requested_timeouts_are_capped() {
+        let timeouts = QueryTimeouts {
+            default: Duration::from_secs(1),
+            max: Duration::from_secs(5),
+        };
+        assert_eq!(timeouts.resolve(None), Duration::from_secs(1));
+        assert_eq!(timeouts.resolve(Some(2_000)), Duration::from_secs(2));
+        assert_eq!(timeouts.resolve(Some(60_000)), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_slow_query_times_out_and_is_cancelled() {
+        let lock = Arc::new(RwLock::new(()));
+        let (tx, rx) = std::sync::mpsc::channel();
+        let error = run(lock, Duration::from_millis(20), move |_, cancel| {
+            while cancel.check().is_ok() {
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            let stopped = cancel.check().unwrap_err();
+            tx.send(stopped.clone()).unwrap();
+            Err::<(), _>(stopped.into())
+        })
+        .await
+        .unwrap_err();
+
+        let interrupted = error.downcast_ref::<QueryInterrupted>().unwrap();
+        assert!(!interrupted.cancelled);
+        assert!(interrupted.elapsed >= Duration::from_millis(20));
+        let stopped = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(!stopped.cancelled);
+    }
+}
@@ -1,7 +1,7 @@
 //! Type conversions between protobuf and vecstore types
 
 use crate::namespace::{Namespace, NamespaceQuotas, NamespaceStatus};
-use crate::store::{Metadata, Neighbor, Query};
+use crate::store::{make_record, Metadata, Neighbor, Query, QueryOptions, Record, StoreEvent};
 use anyhow::Result;
 use std::collections::HashMap;
 
@@ -79,19 +79,76 @@ pub fn metadata_to_pb_metadata(metadata: &Metadata) -> HashMap<String, pb::Value
         .collect()
 }
 
+/// Build a record from a protobuf upsert, keeping its text and parent
+pub fn pb_upsert_to_record(req: pb::UpsertRequest) -> Result<Record> {
+    let metadata = pb_metadata_to_metadata(&req.metadata)?;
+    Ok(Record {
+        text: req.text,
+        parent_id: req.parent_id,
+        chunk_index: req.chunk_index.map(|i| i as usize),
+        ..make_record(req.id, req.vector, metadata)
+    })
+}
+
 /// Convert Neighbor to protobuf QueryResult
 pub fn neighbor_to_query_result(neighbor: &Neighbor) -> pb::QueryResult {
     pb::QueryResult {
         id: neighbor.id.clone(),
         score: neighbor.score,
         metadata: metadata_to_pb_metadata(&neighbor.metadata),
+        vector: neighbor.vector.clone().unwrap_or_default(),
+        text: neighbor.text.clone(),
+    }
+}
+
+/// Convert an optional Record lookup to protobuf GetResult
+pub fn record_to_get_result(id: &str, record: Option<&Record>) -> pb::GetResult {
+    match record {
+        Some(record) => pb::GetResult {
+            id: record.id.clone(),
+            found: true,
+            vector: record.vector.clone(),
+            metadata: metadata_to_pb_metadata(&record.metadata),
+            created_at: record.created_at,
+            text: record.text.clone(),
+            parent_id: record.parent_id.clone(),
+            chunk_index: record.chunk_index.map(|i| i as u64),
+        },
+        None => pb::GetResult {
+            id: id.to_string(),
+            found: false,
+            vector: Vec::new(),
+            metadata: HashMap::new(),
+            created_at: 0,
+            text: None,
+            parent_id: None,
+            chunk_index: None,
+        },
+    }
+}
+
+/// Convert a store change event to protobuf
+pub fn store_event_to_pb(event: StoreEvent) -> pb::StoreEvent {
+    use pb::store_event::Kind;
+
+    let (kind, id, dimension) = match event {
+        StoreEvent::Upsert { id, dimension } => (Kind::Upsert, id, dimension as u32),
+        StoreEvent::Delete { id } => (Kind::Delete, id, 0),
+        StoreEvent::Compact => (Kind::Compact, String::new(), 0),
+        StoreEvent::Save => (Kind::Save, String::new(), 0),
+    };
+    pb::StoreEvent {
+        kind: kind as i32,
+        id,
+        dimension,
+        missed: 0,
     }
 }
 
 /// Convert protobuf QueryRequest to Query
 pub fn pb_query_to_query(req: &pb::QueryRequest) -> Result<Query> {
     let filter = if let Some(ref filter_str) = req.filter {
-        Some(crate::store::parse_filter(filter_str)?)
+        Some(crate::store::FilterExpr::parse(filter_str)?)
     } else {
         None
     };
@@ -100,9 +157,27 @@ pub fn pb_query_to_query(req: &pb::QueryRequest) -> Result<Query> {
         vector: req.vector.clone(),
         k: req.limit as usize,
         filter,
+        exact: req.exact,
+        options: req
+            .options
+            .as_ref()
+            .map(pb_options_to_query_options)
+            .unwrap_or_default(),
+        ..Default::default()
     })
 }
 
+/// Convert protobuf QueryOptions; unset fields keep their defaults
+fn pb_options_to_query_options(options: &pb::QueryOptions) -> QueryOptions {
+    QueryOptions {
+        include_vector: options.include_vector,
+        include_metadata: options.include_metadata.unwrap_or(true),
+        metadata_fields: (!options.metadata_fields.is_empty())
+            .then(|| options.metadata_fields.clone()),
+        include_text: options.include_text,
+    }
+}
+
 // ==================== Namespace Type Conversions ====================
 
 /// Convert Namespace to protobuf NamespaceInfo
@@ -113,6 +188,12 @@ pub fn namespace_info_to_proto(ns: &Namespace) -> pb::NamespaceInfo {
         description: ns.description.clone(),
         quotas: Some(namespace_quotas_to_proto(&ns.quotas)),
         status: namespace_status_to_proto(ns.status) as i32,
+        usage: Some(pb::NamespaceUsage {
+            vector_count: ns.usage.vector_count as i64,
+            storage_bytes: ns.usage.storage_bytes as i64,
+            active_queries: ns.usage.active_queries as i32,
+            total_requests: ns.usage.total_requests as i64,
+        }),
         created_at: ns.created_at as i64,
         updated_at: ns.updated_at as i64,
         metadata: ns.metadata.clone(),
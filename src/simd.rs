@@ -5,6 +5,11 @@
 //!
 //! The implementation uses architecture-specific intrinsics when available (x86 AVX/SSE, ARM NEON),
 //! with automatic fallback to scalar implementations on unsupported platforms.
+//!
+//! Dot product, cosine and Euclidean distance (the metrics behind exact search,
+//! MMR and reranking) pick AVX2 at runtime when the CPU supports it, so a
+//! portable build still gets 8-wide lanes; [`simd_level`] reports the choice.
+//! The other metrics use AVX2 only when the crate is compiled with it enabled.
 
 #[cfg(target_arch = "x86_64")]
 use std::arch::x86_64::*;
@@ -12,10 +17,56 @@ use std::arch::x86_64::*;
 #[cfg(target_arch = "x86")]
 use std::arch::x86::*;
 
+/// Instruction set used by the runtime-dispatched functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimdLevel {
+    /// 8 floats per instruction (x86_64)
+    Avx2,
+    /// 4 floats per instruction (x86_64 baseline)
+    Sse2,
+    /// 4 floats per instruction (aarch64)
+    Neon,
+    /// No SIMD support
+    Scalar,
+}
+
+/// The instruction set [`dot_product_simd`], [`cosine_similarity_simd`] and
+/// [`euclidean_distance_simd`] use on this CPU
+pub fn simd_level() -> SimdLevel {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if avx2_detected() {
+            SimdLevel::Avx2
+        } else {
+            SimdLevel::Sse2
+        }
+    }
+
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+    {
+        SimdLevel::Neon
+    }
+
+    #[cfg(not(any(
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon")
+    )))]
+    {
+        SimdLevel::Scalar
+    }
+}
+
+/// Whether the CPU supports AVX2 (the result is cached by the standard library)
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn avx2_detected() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
 /// Calculate Euclidean (L2) distance using SIMD acceleration
 ///
 /// This function uses platform-specific SIMD instructions:
-/// - x86/x86_64: AVX2 if available, otherwise SSE2
+/// - x86_64: AVX2 if the CPU supports it (detected at runtime), otherwise SSE2
 /// - ARM: NEON if available
 /// - Fallback: Optimized scalar code
 ///
@@ -33,18 +84,13 @@ use std::arch::x86::*;
 pub fn euclidean_distance_simd(a: &[f32], b: &[f32]) -> f32 {
     assert_eq!(a.len(), b.len(), "Vector dimensions must match");
 
-    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
-    unsafe {
-        euclidean_distance_avx2(a, b)
-    }
-
-    #[cfg(all(
-        target_arch = "x86_64",
-        not(target_feature = "avx2"),
-        target_feature = "sse2"
-    ))]
-    unsafe {
-        euclidean_distance_sse2(a, b)
+    #[cfg(target_arch = "x86_64")]
+    {
+        if avx2_detected() {
+            unsafe { euclidean_distance_avx2(a, b) }
+        } else {
+            unsafe { euclidean_distance_sse2(a, b) }
+        }
     }
 
     #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
@@ -53,17 +99,17 @@ pub fn euclidean_distance_simd(a: &[f32], b: &[f32]) -> f32 {
     }
 
     #[cfg(not(any(
-        all(target_arch = "x86_64", target_feature = "avx2"),
-        all(target_arch = "x86_64", target_feature = "sse2"),
+        target_arch = "x86_64",
         all(target_arch = "aarch64", target_feature = "neon")
     )))]
     euclidean_distance_scalar(a, b)
 }
 
-/// Optimized scalar fallback for Euclidean distance
+/// Scalar Euclidean distance, the reference the SIMD paths must match
+///
+/// Used as the fallback on platforms without SIMD support.
 #[inline]
-#[allow(dead_code)]
-fn euclidean_distance_scalar(a: &[f32], b: &[f32]) -> f32 {
+pub fn euclidean_distance_scalar(a: &[f32], b: &[f32]) -> f32 {
     a.iter()
         .zip(b.iter())
         .map(|(x, y)| (x - y).powi(2))
@@ -72,7 +118,7 @@ fn euclidean_distance_scalar(a: &[f32], b: &[f32]) -> f32 {
 }
 
 /// AVX2 implementation for x86_64 (processes 8 floats at once)
-#[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+#[cfg(target_arch = "x86_64")]
 #[inline]
 #[target_feature(enable = "avx2")]
 unsafe fn euclidean_distance_avx2(a: &[f32], b: &[f32]) -> f32 {
@@ -109,7 +155,7 @@ unsafe fn euclidean_distance_avx2(a: &[f32], b: &[f32]) -> f32 {
 }
 
 /// SSE2 implementation for x86_64 (processes 4 floats at once)
-#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+#[cfg(target_arch = "x86_64")]
 #[inline]
 #[target_feature(enable = "sse2")]
 unsafe fn euclidean_distance_sse2(a: &[f32], b: &[f32]) -> f32 {
@@ -201,33 +247,57 @@ pub fn cosine_similarity_simd(a: &[f32], b: &[f32]) -> f32 {
     dot / (norm_a * norm_b)
 }
 
+/// Scalar cosine similarity, the reference the SIMD paths must match
+#[inline]
+pub fn cosine_similarity_scalar(a: &[f32], b: &[f32]) -> f32 {
+    assert_eq!(a.len(), b.len(), "Vector dimensions must match");
+
+    let dot = dot_product_scalar(a, b);
+    let norm_a = dot_product_scalar(a, a).sqrt();
+    let norm_b = dot_product_scalar(b, b).sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+
+    dot / (norm_a * norm_b)
+}
+
 /// Calculate dot product using SIMD
 #[inline]
 pub fn dot_product_simd(a: &[f32], b: &[f32]) -> f32 {
     assert_eq!(a.len(), b.len(), "Vector dimensions must match");
 
-    #[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
-    unsafe {
-        return dot_product_avx2(a, b);
+    #[cfg(target_arch = "x86_64")]
+    {
+        if avx2_detected() {
+            unsafe { dot_product_avx2(a, b) }
+        } else {
+            unsafe { dot_product_sse2(a, b) }
+        }
     }
 
-    #[cfg(all(
-        target_arch = "x86_64",
-        not(target_feature = "avx2"),
-        target_feature = "sse2"
-    ))]
+    #[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
     unsafe {
-        return dot_product_sse2(a, b);
+        dot_product_neon(a, b)
     }
 
     #[cfg(not(any(
-        all(target_arch = "x86_64", target_feature = "avx2"),
-        all(target_arch = "x86_64", target_feature = "sse2")
+        target_arch = "x86_64",
+        all(target_arch = "aarch64", target_feature = "neon")
     )))]
+    dot_product_scalar(a, b)
+}
+
+/// Scalar dot product, the reference the SIMD paths must match
+///
+/// Used as the fallback on platforms without SIMD support.
+#[inline]
+pub fn dot_product_scalar(a: &[f32], b: &[f32]) -> f32 {
     a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
 }
 
-#[cfg(all(target_arch = "x86_64", target_feature = "avx2"))]
+#[cfg(target_arch = "x86_64")]
 #[inline]
 #[target_feature(enable = "avx2")]
 unsafe fn dot_product_avx2(a: &[f32], b: &[f32]) -> f32 {
@@ -258,7 +328,7 @@ unsafe fn dot_product_avx2(a: &[f32], b: &[f32]) -> f32 {
     result
 }
 
-#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+#[cfg(target_arch = "x86_64")]
 #[inline]
 #[target_feature(enable = "sse2")]
 unsafe fn dot_product_sse2(a: &[f32], b: &[f32]) -> f32 {
@@ -289,6 +359,36 @@ unsafe fn dot_product_sse2(a: &[f32], b: &[f32]) -> f32 {
     result
 }
 
+#[cfg(all(target_arch = "aarch64", target_feature = "neon"))]
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn dot_product_neon(a: &[f32], b: &[f32]) -> f32 {
+    use std::arch::aarch64::*;
+
+    let len = a.len();
+    let mut sum = vdupq_n_f32(0.0);
+    let mut i = 0;
+
+    while i + 4 <= len {
+        let va = vld1q_f32(a.as_ptr().add(i));
+        let vb = vld1q_f32(b.as_ptr().add(i));
+        sum = vaddq_f32(sum, vmulq_f32(va, vb));
+        i += 4;
+    }
+
+    let mut result = vgetq_lane_f32(sum, 0)
+        + vgetq_lane_f32(sum, 1)
+        + vgetq_lane_f32(sum, 2)
+        + vgetq_lane_f32(sum, 3);
+
+    while i < len {
+        result += a[i] * b[i];
+        i += 1;
+    }
+
+    result
+}
+
 /// Calculate vector magnitude (L2 norm) using SIMD
 #[inline]
 pub fn magnitude_simd(v: &[f32]) -> f32 {
@@ -1029,4 +1129,88 @@ mod tests {
             epsilon = 1e-6
         );
     }
+
+    /// Every dot product implementation this CPU can run
+    fn dot_products(a: &[f32], b: &[f32]) -> Vec<(&'static str, f32)> {
+        let mut results = vec![("dispatched", dot_product_simd(a, b))];
+        #[cfg(target_arch = "x86_64")]
+        {
+            results.push(("sse2", unsafe { dot_product_sse2(a, b) }));
+            if avx2_detected() {
+                results.push(("avx2", unsafe { dot_product_avx2(a, b) }));
+            }
+        }
+        results
+    }
+
+    /// Every Euclidean distance implementation this CPU can run
+    fn euclidean_distances(a: &[f32], b: &[f32]) -> Vec<(&'static str, f32)> {
+        let mut results = vec![("dispatched", euclidean_distance_simd(a, b))];
+        #[cfg(target_arch = "x86_64")]
+        {
+            results.push(("sse2", unsafe { euclidean_distance_sse2(a, b) }));
+            if avx2_detected() {
+                results.push(("avx2", unsafe { euclidean_distance_avx2(a, b) }));
+            }
+        }
+        results
+    }
+
+    /// Pairs of equal-length vectors, with lengths around the 4- and 8-lane
+    /// boundaries so the remainder loops are exercised
+    fn vector_pair() -> impl proptest::strategy::Strategy<Value = (Vec<f32>, Vec<f32>)> {
+        use proptest::prelude::*;
+        (0usize..70).prop_flat_map(|len| {
+            (
+                prop::collection::vec(-100.0f32..100.0, len),
+                prop::collection::vec(-100.0f32..100.0, len),
+            )
+        })
+    }
+
+    #[test]
+    fn test_simd_level_matches_target() {
+        let level = simd_level();
+        #[cfg(target_arch = "x86_64")]
+        assert!(matches!(level, SimdLevel::Avx2 | SimdLevel::Sse2));
+        #[cfg(not(target_arch = "x86_64"))]
+        assert_ne!(level, SimdLevel::Avx2);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_dot_product_matches_scalar((a, b) in vector_pair()) {
+            let expected = dot_product_scalar(&a, &b);
+            // Summation order differs between lane widths, so the error
+            // bound scales with the magnitude of the terms
+            let bound: f32 = a.iter().zip(&b).map(|(x, y)| (x * y).abs()).sum();
+            for (path, got) in dot_products(&a, &b) {
+                proptest::prop_assert!(
+                    (got - expected).abs() <= 1e-5 * bound + 1e-3,
+                    "{}: {} vs scalar {}", path, got, expected
+                );
+            }
+        }
+
+        #[test]
+        fn prop_euclidean_distance_matches_scalar((a, b) in vector_pair()) {
+            let expected = euclidean_distance_scalar(&a, &b);
+            for (path, got) in euclidean_distances(&a, &b) {
+                proptest::prop_assert!(
+                    (got - expected).abs() <= 1e-5 * expected + 1e-3,
+                    "{}: {} vs scalar {}", path, got, expected
+                );
+            }
+        }
+
+        #[test]
+        fn prop_cosine_similarity_matches_scalar((a, b) in vector_pair()) {
+            let expected = cosine_similarity_scalar(&a, &b);
+            let got = cosine_similarity_simd(&a, &b);
+            proptest::prop_assert!(
+                (got - expected).abs() <= 1e-4,
+                "{} vs scalar {}", got, expected
+            );
+        }
+    }
 }
@@ -0,0 +1,880 @@
+//! Single-file store backups with an integrity manifest
+//!
+//! A backup packs every file of a saved store into one file:
+//!
+//! ```text
+//! [magic: b"VSBACKUP"][format version: u32 LE][manifest len: u32 LE]
+//! [manifest JSON][segment 0][segment 1]...
+//! ```
+//!
+//! The manifest records what the backup contains (record count, dimension,
+//! distance metric) and, for each segment, its stored and original length and
+//! a CRC32 of the original bytes. Restoring checks all of it before anything
+//! is written, so a truncated or corrupted backup fails with a description of
+//! the damage instead of restoring a smaller store.
+//!
+//! Every store change gets an operation sequence number. A full backup covers
+//! operations `0..=seq_end`; an incremental backup holds only the records
+//! changed and removed in `seq_start+1..=seq_end`, as `records.json`,
+//! `removed.json` and `texts.json` segments. A chain restores when each link
+//! starts where the previous one ended.
+//!
+//! Backups of an encrypted store hold its files still sealed, plus its
+//! wrapped key; incremental segments are sealed under the same key.
+
+use super::encryption::{self, Cipher};
+use super::quantized::full_vector;
+use super::{Distance, Id, Record, VecStore};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 8] = b"VSBACKUP";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_LEN: u64 = 16;
+
+/// Decoded store files as `(file name, contents)`
+type StoreFiles = Vec<(String, Vec<u8>)>;
+
+/// zstd level used by [`BackupCompression::Zstd`]
+const ZSTD_LEVEL: i32 = 3;
+
+/// How segments are stored in a backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupCompression {
+    None,
+    Zstd,
+}
+
+/// Whether a backup stands alone or extends an earlier one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupKind {
+    /// The whole store
+    #[default]
+    Full,
+    /// Changes since the backup whose `seq_end` equals this one's `seq_start`
+    Incremental,
+}
+
+/// One store file inside a backup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupSegment {
+    /// File name within the store directory
+    pub name: String,
+    /// Bytes the segment occupies in the backup
+    pub stored_len: u64,
+    /// Bytes after decompression
+    pub original_len: u64,
+    /// CRC32 of the original bytes
+    pub crc32: u32,
+}
+
+/// Description of a backup, stored at the start of the backup file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    /// RFC 3339 creation time
+    pub created_at: String,
+    /// Records in the store, including soft-deleted ones
+    pub record_count: usize,
+    pub dimension: usize,
+    pub distance: Distance,
+    pub compression: BackupCompression,
+    #[serde(default)]
+    pub kind: BackupKind,
+    /// Operations covered are those after `seq_start` (0 for full backups)
+    #[serde(default)]
+    pub seq_start: u64,
+    /// ...up to and including `seq_end`, the store's sequence at backup time
+    #[serde(default)]
+    pub seq_end: u64,
+    /// Whether the segments are sealed with the store's encryption key
+    #[serde(default)]
+    pub encrypted: bool,
+    pub segments: Vec<BackupSegment>,
+}
+
+impl BackupManifest {
+    /// Total bytes of segment data following the manifest
+    pub fn stored_len(&self) -> u64 {
+        self.segments.iter().map(|s| s.stored_len).sum()
+    }
+
+    /// Total bytes of the store files once restored
+    pub fn original_len(&self) -> u64 {
+        self.segments.iter().map(|s| s.original_len).sum()
+    }
+}
+
+/// Records changed and removed between two points of a store's history
+#[derive(Debug, Default)]
+pub(super) struct Increment {
+    records: Vec<Record>,
+    removed: BTreeMap<Id, u64>,
+    texts: HashMap<Id, String>,
+}
+
+/// Pack the files of the saved store in `store_dir` into `output`
+pub(super) fn write(
+    store: &VecStore,
+    store_dir: &Path,
+    output: &Path,
+    compression: BackupCompression,
+) -> Result<BackupManifest> {
+    let mut names: Vec<String> = fs::read_dir(store_dir)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+
+    let mut files = Vec::with_capacity(names.len());
+    for name in names {
+        let data = fs::read(store_dir.join(&name))
+            .with_context(|| format!("Failed to read store file {}", name))?;
+        files.push((name, data));
+    }
+
+    let manifest = manifest_for(store, compression, BackupKind::Full, 0);
+    pack(output, manifest, files)
+}
+
+/// Write the changes `store` made after `base` into `output`
+///
+/// Uses the base's compression, so a chain is compressed throughout or not
+/// at all.
+pub(super) fn write_incremental(
+    store: &VecStore,
+    base: &BackupManifest,
+    output: &Path,
+) -> Result<BackupManifest> {
+    if base.seq_end > store.changes.op_seq {
+        return Err(anyhow!(
+            "Base backup ends at operation {} but the store is only at {}; \
+             it was taken from a different store",
+            base.seq_end,
+            store.changes.op_seq
+        ));
+    }
+    if base.distance != store.config.distance {
+        return Err(anyhow!(
+            "Base backup uses {} distance but the store uses {}",
+            base.distance.name(),
+            store.config.distance.name()
+        ));
+    }
+
+    let records: Vec<Record> = store
+        .records
+        .values()
+        .filter(|r| r.seq > base.seq_end)
+        .map(|r| Record {
+            vector: full_vector(r, store.quantized.as_ref()).into_owned(),
+            ..r.clone()
+        })
+        .collect();
+    let removed: BTreeMap<Id, u64> = store
+        .changes
+        .removed
+        .iter()
+        .filter(|(_, seq)| **seq > base.seq_end)
+        .map(|(id, seq)| (id.clone(), *seq))
+        .collect();
+    let texts: HashMap<Id, String> = store
+        .text_index
+        .export_texts()
+        .iter()
+        .filter(|(id, _)| store.records.get(*id).is_some_and(|r| r.seq > base.seq_end))
+        .map(|(id, text)| (id.clone(), text.clone()))
+        .collect();
+
+    let mut files = vec![
+        ("records.json".to_string(), serde_json::to_vec(&records)?),
+        ("removed.json".to_string(), serde_json::to_vec(&removed)?),
+        ("texts.json".to_string(), serde_json::to_vec(&texts)?),
+    ];
+    if let Some(cipher) = &store.cipher {
+        for (name, data) in &mut files {
+            *data = cipher.seal(name, data)?;
+        }
+    }
+    let manifest = manifest_for(
+        store,
+        base.compression,
+        BackupKind::Incremental,
+        base.seq_end,
+    );
+    pack(output, manifest, files)
+}
+
+fn manifest_for(
+    store: &VecStore,
+    compression: BackupCompression,
+    kind: BackupKind,
+    seq_start: u64,
+) -> BackupManifest {
+    BackupManifest {
+        format_version: FORMAT_VERSION,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        record_count: store.records.len(),
+        dimension: store.dimension,
+        distance: store.config.distance,
+        compression,
+        kind,
+        seq_start,
+        seq_end: store.changes.op_seq,
+        encrypted: store.cipher.is_some(),
+        segments: Vec::new(),
+    }
+}
+
+/// Write `files` as the segments of a backup described by `manifest`
+fn pack(output: &Path, mut manifest: BackupManifest, files: StoreFiles) -> Result<BackupManifest> {
+    let compression = manifest.compression;
+    let mut data = Vec::with_capacity(files.len());
+    for (name, original) in files {
+        let stored = match compression {
+            BackupCompression::None => original.clone(),
+            BackupCompression::Zstd => zstd::encode_all(original.as_slice(), ZSTD_LEVEL)
+                .with_context(|| format!("Failed to compress {}", name))?,
+        };
+        manifest.segments.push(BackupSegment {
+            name,
+            stored_len: stored.len() as u64,
+            original_len: original.len() as u64,
+            crc32: crc32fast::hash(&original),
+        });
+        data.push(stored);
+    }
+
+    let manifest_json = serde_json::to_vec(&manifest)?;
+
+    // Write next to the destination and rename, so a failed backup never
+    // replaces a good one
+    let mut tmp = output.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    {
+        let mut file = std::io::BufWriter::new(
+            fs::File::create(&tmp)
+                .with_context(|| format!("Failed to create backup file: {:?}", tmp))?,
+        );
+        file.write_all(MAGIC)?;
+        file.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        file.write_all(&(manifest_json.len() as u32).to_le_bytes())?;
+        file.write_all(&manifest_json)?;
+        for segment in &data {
+            file.write_all(segment)?;
+        }
+        file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    }
+    fs::rename(&tmp, output)
+        .with_context(|| format!("Failed to move backup into place: {:?}", output))?;
+
+    Ok(manifest)
+}
+
+/// Read a backup's manifest and check the file holds all of its segments
+pub(super) fn read_manifest(path: &Path) -> Result<BackupManifest> {
+    let mut file =
+        fs::File::open(path).with_context(|| format!("Failed to open backup: {:?}", path))?;
+    let (manifest, data_start) = read_header(&mut file, path)?;
+
+    let actual = file.metadata()?.len().saturating_sub(data_start);
+    let expected = manifest.stored_len();
+    if actual < expected {
+        return Err(anyhow!(
+            "Backup {:?} is truncated: manifest lists {} bytes of segment data, file has {}",
+            path,
+            expected,
+            actual
+        ));
+    }
+    if actual > expected {
+        return Err(anyhow!(
+            "Backup {:?} has {} unexpected trailing bytes",
+            path,
+            actual - expected
+        ));
+    }
+
+    Ok(manifest)
+}
+
+/// Verify every segment of a backup and return the decoded store files
+pub(super) fn read_segments(path: &Path) -> Result<(BackupManifest, StoreFiles)> {
+    let manifest = read_manifest(path)?;
+    let mut file = fs::File::open(path)?;
+    read_header(&mut file, path)?;
+
+    let mut files = Vec::with_capacity(manifest.segments.len());
+    for segment in &manifest.segments {
+        if segment.name.is_empty()
+            || segment.name.contains(['/', '\\'])
+            || segment.name == ".."
+            || segment.name == "."
+        {
+            return Err(anyhow!(
+                "Backup {:?} has an invalid segment name {:?}",
+                path,
+                segment.name
+            ));
+        }
+
+        let mut stored = vec![0u8; segment.stored_len as usize];
+        file.read_exact(&mut stored)?;
+        let original = match manifest.compression {
+            BackupCompression::None => stored,
+            BackupCompression::Zstd => zstd::decode_all(stored.as_slice()).map_err(|e| {
+                anyhow!(
+                    "Segment '{}' of backup {:?} failed to decompress: {}",
+                    segment.name,
+                    path,
+                    e
+                )
+            })?,
+        };
+
+        if original.len() as u64 != segment.original_len {
+            return Err(anyhow!(
+                "Segment '{}' of backup {:?} is {} bytes, manifest says {}",
+                segment.name,
+                path,
+                original.len(),
+                segment.original_len
+            ));
+        }
+        let crc = crc32fast::hash(&original);
+        if crc != segment.crc32 {
+            return Err(anyhow!(
+                "Segment '{}' of backup {:?} is corrupted: checksum {:08x}, manifest says {:08x}",
+                segment.name,
+                path,
+                crc,
+                segment.crc32
+            ));
+        }
+        files.push((segment.name.clone(), original));
+    }
+
+    Ok((manifest, files))
+}
+
+/// Verify a backup chain and decode its incremental links
+///
+/// Nothing is returned for the full backup at the head of the chain; it is
+/// restored like any other full backup. `cipher` opens the links of an
+/// encrypted chain.
+pub(super) fn read_chain<P: AsRef<Path>>(
+    base: &Path,
+    increments: &[P],
+    cipher: Option<&Cipher>,
+) -> Result<Vec<(BackupManifest, Increment)>> {
+    let base_manifest = read_manifest(base)?;
+    if base_manifest.kind != BackupKind::Full {
+        return Err(anyhow!(
+            "Backup chain must start with a full backup, but {:?} is incremental",
+            base
+        ));
+    }
+
+    let mut previous = (base.to_path_buf(), base_manifest);
+    let mut decoded = Vec::with_capacity(increments.len());
+    for path in increments {
+        let path = path.as_ref();
+        let (manifest, files) = read_segments(path)?;
+        if manifest.kind != BackupKind::Incremental {
+            return Err(anyhow!(
+                "{:?} is a full backup; only the first backup of a chain can be full",
+                path
+            ));
+        }
+
+        let (previous_path, previous_manifest) = &previous;
+        if manifest.seq_start != previous_manifest.seq_end {
+            let problem = if manifest.seq_start > previous_manifest.seq_end {
+                "a backup is missing between them"
+            } else {
+                "the chain is out of order"
+            };
+            return Err(anyhow!(
+                "Backup chain breaks at {:?}: it covers operations {}..={} but {:?} ends at {}; {}",
+                path,
+                manifest.seq_start + 1,
+                manifest.seq_end,
+                previous_path,
+                previous_manifest.seq_end,
+                problem
+            ));
+        }
+        if manifest.distance != previous_manifest.distance {
+            return Err(anyhow!(
+                "{:?} uses {} distance but the rest of the chain uses {}",
+                path,
+                manifest.distance.name(),
+                previous_manifest.distance.name()
+            ));
+        }
+
+        let increment = decode_increment(path, files, cipher)?;
+        previous = (path.to_path_buf(), manifest.clone());
+        decoded.push((manifest, increment));
+    }
+
+    Ok(decoded)
+}
+
+fn decode_increment(path: &Path, files: StoreFiles, cipher: Option<&Cipher>) -> Result<Increment> {
+    let mut increment = Increment::default();
+    for (name, data) in files {
+        let context = || format!("Backup {:?} has an unreadable {}", path, name);
+        let data = encryption::unseal(cipher, &name, data).with_context(context)?;
+        match name.as_str() {
+            "records.json" => {
+                increment.records = serde_json::from_slice(&data).with_context(context)?
+            }
+            "removed.json" => {
+                increment.removed = serde_json::from_slice(&data).with_context(context)?
+            }
+            "texts.json" => {
+                increment.texts = serde_json::from_slice(&data).with_context(context)?
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Backup {:?} has an unexpected segment '{}'",
+                    path,
+                    name
+                ))
+            }
+        }
+    }
+    Ok(increment)
+}
+
+/// Replay an incremental backup onto `store`
+pub(super) fn apply(
+    store: &mut VecStore,
+    manifest: &BackupManifest,
+    increment: Increment,
+) -> Result<()> {
+    for id in increment.removed.keys() {
+        if store.records.contains_key(id) {
+            store.remove(id)?;
+        }
+    }
+
+    let mut changed = Vec::with_capacity(increment.records.len());
+    for record in increment.records {
+        store.upsert_record(record.clone())?;
+        changed.push(record);
+    }
+    for (id, text) in increment.texts {
+        store.index_text(&id, text)?;
+    }
+
+    // Keep the backed-up bookkeeping rather than what replaying assigned
+    for record in changed {
+        if let Some(restored) = store.records.get_mut(&record.id) {
+            restored.created_at = record.created_at;
+            restored.deleted = record.deleted;
+            restored.deleted_at = record.deleted_at;
+            restored.expires_at = record.expires_at;
+            restored.seq = record.seq;
+            restored.version = record.version;
+        }
+    }
+    for (id, seq) in increment.removed {
+        store.changes.removed.insert(id, seq);
+    }
+    store.changes.op_seq = manifest.seq_end;
+
+    Ok(())
+}
+
+/// Check a store restored from a backup matches the backup's manifest
+pub(super) fn verify_restored(store: &VecStore, manifest: &BackupManifest) -> Result<()> {
+    let mut problems = Vec::new();
+    if store.records.len() != manifest.record_count {
+        problems.push(format!(
+            "{} records, manifest says {}",
+            store.records.len(),
+            manifest.record_count
+        ));
+    }
+    if store.dimension != manifest.dimension {
+        problems.push(format!(
+            "dimension {}, manifest says {}",
+            store.dimension, manifest.dimension
+        ));
+    }
+    if store.config.distance != manifest.distance {
+        problems.push(format!(
+            "distance {}, manifest says {}",
+            store.config.distance.name(),
+            manifest.distance.name()
+        ));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Restored store does not match its backup: {}",
+            problems.join("; ")
+        ))
+    }
+}
+
+fn read_header(file: &mut fs::File, path: &Path) -> Result<(BackupManifest, u64)> {
+    let mut header = [0u8; HEADER_LEN as usize];
+    file.read_exact(&mut header)
+        .map_err(|_| anyhow!("{:?} is too short to be a vecstore backup", path))?;
+    if &header[..8] != MAGIC {
+        return Err(anyhow!("{:?} is not a vecstore backup", path));
+    }
+
+    let version = u32::from_le_bytes(header[8..12].try_into().unwrap());
+    if version != FORMAT_VERSION {
+        return Err(anyhow!(
+            "Backup {:?} has format version {}, this build reads version {}",
+            path,
+            version,
+            FORMAT_VERSION
+        ));
+    }
+
+    let manifest_len = u32::from_le_bytes(header[12..16].try_into().unwrap());
+    let mut manifest_json = vec![0u8; manifest_len as usize];
+    file.read_exact(&mut manifest_json)
+        .map_err(|_| anyhow!("Backup {:?} is truncated inside its manifest", path))?;
+    let manifest: BackupManifest = serde_json::from_slice(&manifest_json)
+        .with_context(|| format!("Backup {:?} has an unreadable manifest", path))?;
+
+    Ok((manifest, HEADER_LEN + manifest_len as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::{Metadata, Query};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn populated_store(dir: &TempDir) -> VecStore {
+        let mut store = VecStore::builder(dir.path().join("source"))
+            .distance(Distance::Euclidean)
+            .build()
+            .unwrap();
+        for i in 0..200 {
+            let mut metadata = Metadata {
+                fields: HashMap::new(),
+            };
+            metadata.fields.insert("i".into(), serde_json::json!(i));
+            store
+                .upsert(format!("doc{i}"), vec![i as f32, 1.0, 0.5], metadata)
+                .unwrap();
+        }
+        store.soft_delete("doc7").unwrap();
+        store
+    }
+
+    #[test]
+    fn test_backup_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let store = populated_store(&dir);
+        let query = Query::new(vec![42.0, 1.0, 0.5]).with_limit(3);
+        let expected: Vec<_> = store
+            .query(query.clone())
+            .unwrap()
+            .into_iter()
+            .map(|n| n.id)
+            .collect();
+
+        let plain = dir.path().join("plain.vsbak");
+        let packed = dir.path().join("packed.vsbak");
+        let manifest = store.backup(&plain).unwrap();
+        store.backup_compressed(&packed).unwrap();
+
+        assert_eq!(manifest.record_count, 200);
+        assert_eq!(manifest.dimension, 3);
+        assert_eq!(manifest.distance, Distance::Euclidean);
+        assert!(fs::metadata(&packed).unwrap().len() < fs::metadata(&plain).unwrap().len());
+
+        let info = VecStore::backup_info(&packed).unwrap();
+        assert_eq!(info.compression, BackupCompression::Zstd);
+        assert_eq!(info.record_count, 200);
+        assert_eq!(info.original_len(), manifest.original_len());
+
+        for (backup, dest) in [(&plain, "from_plain"), (&packed, "from_packed")] {
+            let restored = VecStore::restore_backup(backup, dir.path().join(dest)).unwrap();
+            assert_eq!(restored.len(), 199);
+            assert_eq!(restored.config().distance, Distance::Euclidean);
+            let ids: Vec<_> = restored
+                .query(query.clone())
+                .unwrap()
+                .into_iter()
+                .map(|n| n.id)
+                .collect();
+            assert_eq!(ids, expected);
+        }
+    }
+
+    #[test]
+    fn test_truncated_backup_rejected() {
+        let dir = TempDir::new().unwrap();
+        let store = populated_store(&dir);
+        let path = dir.path().join("store.vsbak");
+        store.backup_compressed(&path).unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 100]).unwrap();
+
+        let err = VecStore::backup_info(&path).unwrap_err().to_string();
+        assert!(err.contains("truncated"), "{err}");
+
+        let dest = dir.path().join("restored");
+        let err = VecStore::restore_backup(&path, &dest)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("truncated"), "{err}");
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_corrupt_segment_named() {
+        let dir = TempDir::new().unwrap();
+        let store = populated_store(&dir);
+        let path = dir.path().join("store.vsbak");
+        let manifest = store.backup(&path).unwrap();
+
+        // Flip a byte in the last segment
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+
+        let err = VecStore::restore_backup(&path, dir.path().join("restored"))
+            .err()
+            .unwrap()
+            .to_string();
+        let segment = &manifest.segments.last().unwrap().name;
+        assert!(err.contains("checksum"), "{err}");
+        assert!(err.contains(segment.as_str()), "{err}");
+    }
+
+    #[test]
+    fn test_restore_refuses_existing_store() {
+        let dir = TempDir::new().unwrap();
+        let store = populated_store(&dir);
+        store.save().unwrap();
+        let path = dir.path().join("store.vsbak");
+        store.backup(&path).unwrap();
+
+        assert!(VecStore::restore_backup(&path, dir.path().join("source")).is_err());
+        assert!(VecStore::backup_info(dir.path().join("source/manifest.json")).is_err());
+    }
+
+    fn meta(value: i64) -> Metadata {
+        let mut metadata = Metadata {
+            fields: HashMap::new(),
+        };
+        metadata.fields.insert("v".into(), serde_json::json!(value));
+        metadata
+    }
+
+    fn snapshot_of(store: &VecStore) -> Vec<(Id, Vec<f32>, Metadata, bool, u64)> {
+        let mut records: Vec<_> = store
+            .records
+            .values()
+            .map(|r| {
+                let vector = full_vector(r, store.quantized.as_ref()).into_owned();
+                (r.id.clone(), vector, r.metadata.clone(), r.deleted, r.seq)
+            })
+            .collect();
+        records.sort_by(|a, b| a.0.cmp(&b.0));
+        records
+    }
+
+    /// Full backup plus two incrementals over a store that is saved and
+    /// reopened between them
+    fn chain(dir: &TempDir) -> (VecStore, [PathBuf; 3]) {
+        let root = dir.path().join("source");
+        let paths = ["full", "inc1", "inc2"].map(|name| dir.path().join(format!("{name}.vsbak")));
+
+        let mut store = populated_store(dir);
+        let full = store.backup_compressed(&paths[0]).unwrap();
+        store.save().unwrap();
+        drop(store);
+
+        let mut store = VecStore::open(&root).unwrap();
+        store
+            .upsert("new1".into(), vec![9.0, 9.0, 9.0], meta(1))
+            .unwrap();
+        store
+            .upsert("doc1".into(), vec![1.0, 2.0, 3.0], meta(2))
+            .unwrap();
+        store.replace_metadata("doc2", meta(3)).unwrap();
+        store.soft_delete("doc3").unwrap();
+        store.restore("doc7").unwrap();
+        store.remove("doc4").unwrap();
+        store.index_text("doc5", "rust vector search").unwrap();
+        let inc1 = store.backup_incremental(&full, &paths[1]).unwrap();
+        store.save().unwrap();
+        drop(store);
+
+        let mut store = VecStore::open(&root).unwrap();
+        store.remove("new1").unwrap();
+        store
+            .upsert("doc4".into(), vec![4.0, 4.0, 4.0], meta(4))
+            .unwrap();
+        store.remove("doc6").unwrap();
+        store.backup_incremental(&inc1, &paths[2]).unwrap();
+
+        (store, paths)
+    }
+
+    #[test]
+    fn test_incremental_chain_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let (store, paths) = chain(&dir);
+
+        let full = VecStore::backup_info(&paths[0]).unwrap();
+        let inc1 = VecStore::backup_info(&paths[1]).unwrap();
+        let inc2 = VecStore::backup_info(&paths[2]).unwrap();
+        assert_eq!(inc1.kind, BackupKind::Incremental);
+        assert_eq!(inc1.seq_start, full.seq_end);
+        assert_eq!(inc2.seq_start, inc1.seq_end);
+        assert_eq!(inc2.seq_end, store.changes.op_seq);
+        assert_eq!(inc1.compression, BackupCompression::Zstd);
+        // Only the changes are captured
+        assert!(inc1.original_len() * 10 < full.original_len());
+
+        let restored = VecStore::restore_chain(&paths, dir.path().join("restored")).unwrap();
+        assert_eq!(snapshot_of(&restored), snapshot_of(&store));
+        assert_eq!(restored.changes, store.changes);
+        assert_eq!(
+            restored
+                .text_index
+                .export_texts()
+                .get("doc5")
+                .map(String::as_str),
+            Some("rust vector search")
+        );
+        assert!(restored.get("doc6").is_none());
+        assert!(restored.get("doc3").is_none());
+        assert!(restored.get("doc7").is_some());
+
+        let query = Query::new(vec![4.0, 4.0, 4.0]).with_limit(3);
+        let ids = |s: &VecStore| -> Vec<Id> {
+            s.query(query.clone())
+                .unwrap()
+                .into_iter()
+                .map(|n| n.id)
+                .collect()
+        };
+        assert_eq!(ids(&restored), ids(&store));
+
+        // The restored store can extend the chain itself
+        let inc3 = restored
+            .backup_incremental(&inc2, dir.path().join("inc3.vsbak"))
+            .unwrap();
+        assert_eq!(inc3.seq_start, inc3.seq_end);
+    }
+
+    #[test]
+    fn test_broken_chains_rejected() {
+        let dir = TempDir::new().unwrap();
+        let (_store, [full, inc1, inc2]) = chain(&dir);
+        let dest = dir.path().join("restored");
+
+        let err = |chain: &[&PathBuf]| {
+            VecStore::restore_chain(chain, &dest)
+                .err()
+                .unwrap()
+                .to_string()
+        };
+        let gap = err(&[&full, &inc2]);
+        assert!(gap.contains("missing"), "{gap}");
+        let reordered = err(&[&full, &inc2, &inc1]);
+        assert!(reordered.contains("missing"), "{reordered}");
+        let repeated = err(&[&full, &inc1, &inc1]);
+        assert!(repeated.contains("out of order"), "{repeated}");
+        let headless = err(&[&inc1, &inc2]);
+        assert!(
+            headless.contains("must start with a full backup"),
+            "{headless}"
+        );
+        assert!(VecStore::restore_backup(&inc1, &dest).is_err());
+        let two_fulls = err(&[&full, &full]);
+        assert!(two_fulls.contains("only the first"), "{two_fulls}");
+
+        // Nothing was restored by the failed attempts
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_incremental_from_foreign_base_rejected() {
+        let dir = TempDir::new().unwrap();
+        let store = populated_store(&dir);
+        let base = store.backup(dir.path().join("full.vsbak")).unwrap();
+
+        let other = VecStore::open(dir.path().join("other")).unwrap();
+        assert!(other
+            .backup_incremental(&base, dir.path().join("inc.vsbak"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_replace_with_restored_backup() {
+        let dir = TempDir::new().unwrap();
+        let mut source = populated_store(&dir);
+        // A rewritten record, so versions other than 1 must carry over
+        source
+            .upsert(
+                "doc3".into(),
+                vec![3.0, 1.0, 0.5],
+                Metadata {
+                    fields: HashMap::new(),
+                },
+            )
+            .unwrap();
+        let backup = dir.path().join("full.vsbak");
+        source.backup_compressed(&backup).unwrap();
+
+        let mut replica = VecStore::open(dir.path().join("replica")).unwrap();
+        replica
+            .upsert(
+                "stale".into(),
+                vec![0.0, 0.0, 0.0],
+                Metadata {
+                    fields: HashMap::new(),
+                },
+            )
+            .unwrap();
+        let restored = VecStore::restore_backup(&backup, dir.path().join("staged")).unwrap();
+        replica.replace_with(restored);
+        // Nothing is read from the restored store's files after the swap
+        fs::remove_dir_all(dir.path().join("staged")).unwrap();
+
+        assert_eq!(replica.op_seq(), source.op_seq());
+        assert_eq!(replica.len(), 199);
+        assert!(replica.get("stale").is_none());
+        assert_eq!(
+            replica.get("doc3").unwrap().version,
+            source.get("doc3").unwrap().version
+        );
+        let query = Query::new(vec![42.0, 1.0, 0.5]).with_limit(1);
+        assert_eq!(replica.query(query).unwrap()[0].id, "doc42");
+
+        replica.save().unwrap();
+        let reopened = VecStore::open(dir.path().join("replica")).unwrap();
+        assert_eq!(reopened.len(), 199);
+        assert_eq!(reopened.op_seq(), source.op_seq());
+    }
+}
@@ -0,0 +1,70 @@
+//! Saves written from a copy of the store
+//!
+//! [`VecStore::save`](super::VecStore::save) serializes the store while it
+//! is borrowed, so a server keeping the store behind a lock holds that lock
+//! for the whole write. A checkpoint copies the state `save` persists, which
+//! is quick, and writes the copy once the store is released. Changes made
+//! meanwhile stay unsaved until the next checkpoint.
+
+use super::disk::{ChangeState, DiskLayout};
+use super::quantized::QuantizedVectors;
+use super::types::{Config, Id, Record};
+use anyhow::Result;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+/// The persisted state of a store at one point, taken by
+/// [`VecStore::snapshot_for_save`](super::VecStore::snapshot_for_save)
+pub struct SaveSnapshot {
+    pub(super) layout: DiskLayout,
+    pub(super) records: HashMap<Id, Record>,
+    pub(super) id_to_idx: HashMap<Id, usize>,
+    pub(super) idx_to_id: HashMap<usize, Id>,
+    pub(super) next_idx: usize,
+    pub(super) dimension: usize,
+    pub(super) config: Config,
+    pub(super) texts: Option<HashMap<Id, String>>,
+    pub(super) quantized: Option<QuantizedVectors>,
+    pub(super) changes: ChangeState,
+    /// Holds the index dump until [`write`](Self::write) moves it into place
+    pub(super) index_dir: TempDir,
+}
+
+impl SaveSnapshot {
+    /// Operation sequence number the snapshot was taken at
+    pub fn seq(&self) -> u64 {
+        self.changes.op_seq
+    }
+
+    /// Records in the snapshot, deleted ones included
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether the snapshot holds no records
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Write the store files
+    ///
+    /// Needs no access to the store; pass the snapshot back to
+    /// [`VecStore::finish_save`](super::VecStore::finish_save) afterwards.
+    /// The index dump is moved rather than copied, so writing the same
+    /// snapshot again leaves the store without one until the next save.
+    pub fn write(&self) -> Result<()> {
+        self.layout.save_all(
+            &self.records,
+            &self.id_to_idx,
+            &self.idx_to_id,
+            self.next_idx,
+            self.dimension,
+            &self.config,
+            self.texts.as_ref(),
+        )?;
+        self.layout.save_quantized(self.quantized.as_ref())?;
+        self.layout.save_changes(&self.changes)?;
+        self.layout
+            .install_index(&DiskLayout::new(self.index_dir.path()))
+    }
+}
@@ -0,0 +1,339 @@
+//! Shared `VecStore` handle that keeps queries flowing during bulk ingest
+//!
+//! Wrapping a store in a single lock (as the server does) means one large
+//! `batch_upsert` holds the write lock for its whole duration and every query
+//! queues behind it. [`VecStoreConcurrent`] takes `&self` everywhere and
+//! applies batches in chunks, releasing the lock between chunks, so the
+//! longest a query waits is one chunk's insert.
+//!
+//! Each query runs under a read lock and therefore sees a consistent state:
+//! every write that finished before it and none that started after. A batch
+//! becomes visible chunk by chunk, not atomically.
+
+use super::{BatchValidationError, Id, Metadata, Neighbor, Query, Record, VecStore};
+use anyhow::Result;
+use std::sync::{Arc, Mutex, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Records applied per write-lock acquisition in
+/// [`VecStoreConcurrent::batch_upsert`]
+pub const DEFAULT_INGEST_CHUNK_SIZE: usize = 256;
+
+/// Thread-safe, cloneable `VecStore` handle with `&self` reads and writes
+///
+/// Writers are serialized among themselves before they contend for the store
+/// lock, so a waiting writer never holds up readers longer than its own chunk.
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore::{make_record, Metadata, Query, VecStore, VecStoreConcurrent};
+/// use std::collections::HashMap;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let store = VecStoreConcurrent::new(VecStore::open("./data")?);
+///
+/// let writer = store.clone();
+/// let ingest = std::thread::spawn(move || {
+///     let records = (0..100_000)
+///         .map(|i| make_record(format!("doc{i}"), vec![i as f32, 1.0], Metadata { fields: HashMap::new() }));
+///     writer.batch_upsert(records)
+/// });
+///
+/// // Served between ingest chunks instead of after the whole batch
+/// let results = store.query(Query::new(vec![1.0, 1.0]).with_limit(5))?;
+/// ingest.join().unwrap()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct VecStoreConcurrent {
+    store: Arc<RwLock<VecStore>>,
+    /// Serializes writers so chunks of concurrent batches don't interleave
+    writer: Arc<Mutex<()>>,
+    chunk_size: usize,
+}
+
+impl VecStoreConcurrent {
+    /// Wrap `store` for shared use
+    pub fn new(store: VecStore) -> Self {
+        Self {
+            store: Arc::new(RwLock::new(store)),
+            writer: Arc::new(Mutex::new(())),
+            chunk_size: DEFAULT_INGEST_CHUNK_SIZE,
+        }
+    }
+
+    /// Set how many records a batch applies per write-lock acquisition
+    ///
+    /// Smaller chunks shorten the longest query stall during ingest at the
+    /// cost of slower ingest (less parallel index building per chunk).
+    pub fn with_ingest_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Insert or update a single record
+    pub fn upsert(&self, id: Id, vector: Vec<f32>, metadata: Metadata) -> Result<()> {
+        let _writer = self.lock_writer();
+        self.write().upsert(id, vector, metadata)
+    }
+
+    /// Insert or update a record if it is still at `expected_version`
+    ///
+    /// See [`VecStore::upsert_if_version`]; returns the new version.
+    pub fn upsert_if_version(
+        &self,
+        id: Id,
+        expected_version: u64,
+        vector: Vec<f32>,
+        metadata: Metadata,
+    ) -> Result<u64> {
+        let _writer = self.lock_writer();
+        self.write()
+            .upsert_if_version(id, expected_version, vector, metadata)
+    }
+
+    /// Insert many records, releasing the store lock between chunks
+    ///
+    /// Queries issued meanwhile see the chunks applied so far. The whole
+    /// batch is validated before the first chunk, so an invalid record fails
+    /// the call with a [`BatchValidationError`] and nothing is written. If
+    /// writing a chunk fails, earlier chunks stay applied and the error is
+    /// returned.
+    pub fn batch_upsert(&self, records: impl IntoIterator<Item = Record>) -> Result<()> {
+        let _writer = self.lock_writer();
+        let records: Vec<Record> = records.into_iter().collect();
+        let errors = self.read().validate_batch(&records);
+        if !errors.is_empty() {
+            return Err(BatchValidationError { errors }.into());
+        }
+
+        let mut records = records.into_iter().peekable();
+        while records.peek().is_some() {
+            let chunk: Vec<Record> = records.by_ref().take(self.chunk_size).collect();
+            self.write().batch_upsert(chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Permanently remove a record
+    pub fn remove(&self, id: &str) -> Result<()> {
+        let _writer = self.lock_writer();
+        self.write().remove(id)
+    }
+
+    /// Run a query against the current state
+    pub fn query(&self, q: Query) -> Result<Vec<Neighbor>> {
+        self.read().query(q)
+    }
+
+    /// Fetch a live record by ID
+    pub fn get(&self, id: &str) -> Option<Record> {
+        self.read().get(id)
+    }
+
+    /// Number of live records
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Check if the store has no live records
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Persist the store; queries continue while it is written
+    pub fn save(&self) -> Result<()> {
+        let _writer = self.lock_writer();
+        self.read().save()
+    }
+
+    /// Shared access to the store for APIs not mirrored here
+    pub fn read(&self) -> RwLockReadGuard<'_, VecStore> {
+        self.store.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Exclusive access to the store for APIs not mirrored here
+    ///
+    /// Blocks queries until the guard is dropped.
+    pub fn write(&self) -> RwLockWriteGuard<'_, VecStore> {
+        self.store.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn lock_writer(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.writer.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl From<VecStore> for VecStoreConcurrent {
+    fn from(store: VecStore) -> Self {
+        Self::new(store)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::make_record;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    const DIM: usize = 16;
+
+    fn vector(i: usize) -> Vec<f32> {
+        (0..DIM)
+            .map(|d| ((i * 31 + d * 7) % 97) as f32 / 97.0)
+            .collect()
+    }
+
+    fn records(range: std::ops::Range<usize>) -> Vec<Record> {
+        range
+            .map(|i| {
+                let metadata = Metadata {
+                    fields: HashMap::new(),
+                };
+                make_record(format!("v{i}"), vector(i), metadata)
+            })
+            .collect()
+    }
+
+    fn store(dir: &TempDir, chunk_size: usize) -> VecStoreConcurrent {
+        let store = VecStore::open(dir.path().join("test.db")).unwrap();
+        VecStoreConcurrent::new(store).with_ingest_chunk_size(chunk_size)
+    }
+
+    #[test]
+    fn test_queries_see_whole_chunks() {
+        let dir = TempDir::new().unwrap();
+        let store = store(&dir, 100);
+        store.batch_upsert(records(0..100)).unwrap();
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let (store, done) = (store.clone(), done.clone());
+            thread::spawn(move || {
+                let mut seen = Vec::new();
+                while !done.load(Ordering::Acquire) {
+                    seen.push(store.len());
+                }
+                seen
+            })
+        };
+
+        store.batch_upsert(records(100..5_000)).unwrap();
+        done.store(true, Ordering::Release);
+
+        let seen = reader.join().unwrap();
+        assert!(seen.windows(2).all(|w| w[0] <= w[1]));
+        assert!(seen.iter().all(|len| len % 100 == 0));
+        assert_eq!(store.len(), 5_000);
+    }
+
+    #[test]
+    fn test_mixed_readers_and_writers_stress() {
+        let dir = TempDir::new().unwrap();
+        let store = store(&dir, 128);
+        store.batch_upsert(records(0..500)).unwrap();
+
+        let ingesting = Arc::new(AtomicBool::new(true));
+        let (finished, results) = mpsc::channel();
+
+        // Readers: query until ingest finishes, tracking the slowest query
+        let mut readers = Vec::new();
+        for r in 0..4 {
+            let (store, ingesting) = (store.clone(), ingesting.clone());
+            readers.push(thread::spawn(move || {
+                let (mut during_ingest, mut slowest) = (0, Duration::ZERO);
+                let mut i = r;
+                while ingesting.load(Ordering::Acquire) {
+                    let start = Instant::now();
+                    let hits = store.query(Query::new(vector(i)).with_limit(5)).unwrap();
+                    slowest = slowest.max(start.elapsed());
+                    assert_eq!(hits.len(), 5);
+                    assert!(store.get("v0").is_some());
+                    during_ingest += 1;
+                    i += 4;
+                }
+                (during_ingest, slowest)
+            }));
+        }
+
+        // A second writer churns single records alongside the bulk ingest
+        let churn = {
+            let store = store.clone();
+            thread::spawn(move || {
+                for i in 0..200 {
+                    let id = format!("churn{}", i % 10);
+                    store
+                        .upsert(
+                            id.clone(),
+                            vector(i),
+                            Metadata {
+                                fields: HashMap::new(),
+                            },
+                        )
+                        .unwrap();
+                    if i % 3 == 0 {
+                        store.remove(&id).unwrap();
+                    }
+                }
+            })
+        };
+
+        let ingest = {
+            let (store, ingesting) = (store.clone(), ingesting.clone());
+            thread::spawn(move || {
+                let start = Instant::now();
+                store.batch_upsert(records(500..8_000)).unwrap();
+                ingesting.store(false, Ordering::Release);
+                start.elapsed()
+            })
+        };
+
+        // Join everything on a helper thread so a deadlock fails the test
+        // instead of hanging it
+        thread::spawn(move || {
+            let ingest_time = ingest.join().unwrap();
+            churn.join().unwrap();
+            let reads: Vec<_> = readers.into_iter().map(|r| r.join().unwrap()).collect();
+            finished.send((ingest_time, reads)).unwrap();
+        });
+        let (ingest_time, reads) = results
+            .recv_timeout(Duration::from_secs(120))
+            .expect("readers and writers deadlocked");
+
+        for (during_ingest, slowest) in reads {
+            assert!(during_ingest > 0, "reader starved for the whole ingest");
+            // A query waits for at most one chunk, never the whole batch
+            assert!(
+                slowest < Duration::from_secs(5).max(ingest_time / 2),
+                "query took {slowest:?} during a {ingest_time:?} ingest"
+            );
+        }
+        assert_eq!(
+            store.len(),
+            8_000
+                + (0..10)
+                    .filter(|i| store.get(&format!("churn{i}")).is_some())
+                    .count()
+        );
+    }
+
+    #[test]
+    fn test_invalid_record_rejects_every_chunk() {
+        let dir = TempDir::new().unwrap();
+        let store = store(&dir, 10);
+        let mut batch = records(0..25);
+        batch[15].vector = vec![1.0; DIM + 1];
+
+        let err = store.batch_upsert(batch).unwrap_err();
+        let invalid = err.downcast_ref::<BatchValidationError>().unwrap();
+        assert_eq!(invalid.errors.len(), 1);
+        assert_eq!(invalid.errors[0].index, 15);
+        assert_eq!(store.len(), 0);
+    }
+}
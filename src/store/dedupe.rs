@@ -0,0 +1,114 @@
+//! Near-duplicate detection
+//!
+//! Each live vector is used as a query against the HNSW index, and the
+//! candidates it returns are confirmed by their exact cosine similarity, so a
+//! pass costs one index search per record rather than a comparison per pair.
+//! Confirmed pairs are joined into groups transitively: two records can share
+//! a group through a third without being near-duplicates of each other.
+
+use super::types::Id;
+use serde::{Deserialize, Serialize};
+
+/// Index neighbours checked per record by
+/// [`VecStore::near_duplicates`](super::VecStore::near_duplicates)
+pub const DEDUPE_NEIGHBORS: usize = 16;
+
+/// Which record of a [`NearDuplicateGroup`] to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DuplicateKeep {
+    /// The oldest record
+    First,
+    /// The most recently written record
+    Newest,
+}
+
+/// Records whose vectors are near-duplicates of one another
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NearDuplicateGroup {
+    /// Oldest first, by `created_at`, then write sequence, then id
+    pub ids: Vec<Id>,
+    /// Lowest cosine similarity of the pairs that joined the group
+    pub min_similarity: f32,
+}
+
+impl NearDuplicateGroup {
+    /// The id `keep` keeps
+    pub fn kept(&self, keep: DuplicateKeep) -> &Id {
+        match keep {
+            DuplicateKeep::First => &self.ids[0],
+            DuplicateKeep::Newest => &self.ids[self.ids.len() - 1],
+        }
+    }
+
+    /// The ids `keep` removes
+    pub fn redundant(&self, keep: DuplicateKeep) -> &[Id] {
+        match keep {
+            DuplicateKeep::First => &self.ids[1..],
+            DuplicateKeep::Newest => &self.ids[..self.ids.len() - 1],
+        }
+    }
+}
+
+/// Union-find over record positions
+pub(super) struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+        }
+    }
+
+    pub fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+/// Cosine similarity, 1.0 for identical vectors (zero ones included)
+pub(super) fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a == b {
+        return 1.0;
+    }
+    crate::simd::cosine_similarity_simd(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disjoint_set_joins_transitively() {
+        let mut set = DisjointSet::new(5);
+        set.union(0, 3);
+        set.union(3, 4);
+        assert_eq!(set.find(4), set.find(0));
+        assert_ne!(set.find(1), set.find(0));
+        assert_eq!(set.find(2), 2);
+    }
+
+    #[test]
+    fn test_group_keeps_first_or_newest() {
+        let group = NearDuplicateGroup {
+            ids: vec!["old".into(), "mid".into(), "new".into()],
+            min_similarity: 0.99,
+        };
+        assert_eq!(group.kept(DuplicateKeep::First), "old");
+        assert_eq!(group.redundant(DuplicateKeep::First), ["mid", "new"]);
+        assert_eq!(group.kept(DuplicateKeep::Newest), "new");
+        assert_eq!(group.redundant(DuplicateKeep::Newest), ["old", "mid"]);
+    }
+}
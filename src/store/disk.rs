@@ -1,12 +1,18 @@
+use super::encryption::{self, Cipher};
+use super::quantized::QuantizedVectors;
 use super::types::{Config, Id, Record};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 const SCHEMA_VERSION: u32 = 3; // Incremented for text index persistence (Major Issue #6 fix)
 
+/// Base name of the HNSW dump; the dump writes files starting with it
+const HNSW_FILE: &str = "hnsw.idx";
+
 type LoadResult = (
     HashMap<Id, Record>,
     HashMap<Id, usize>,
@@ -30,6 +36,30 @@ pub struct Manifest {
     pub config: Option<Config>,
 }
 
+/// Operation sequence state, persisted so incremental backups can find
+/// what changed since a given point
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChangeState {
+    /// Last operation sequence number handed out
+    pub op_seq: u64,
+    /// Hard-removed record IDs and the sequence number of their removal
+    pub removed: HashMap<Id, u64>,
+}
+
+impl ChangeState {
+    /// Hand out the next operation sequence number
+    pub fn next_seq(&mut self) -> u64 {
+        self.op_seq += 1;
+        self.op_seq
+    }
+
+    /// Record that `id` was permanently removed
+    pub fn tombstone(&mut self, id: &str) {
+        let seq = self.next_seq();
+        self.removed.insert(id.to_string(), seq);
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DiskState {
     pub records: Vec<Record>,
@@ -40,11 +70,21 @@ pub struct DiskState {
 
 pub struct DiskLayout {
     pub root: PathBuf,
+    /// Seals every file but the manifest when the store is encrypted
+    cipher: Option<Arc<Cipher>>,
 }
 
 impl DiskLayout {
     pub fn new(root: impl Into<PathBuf>) -> Self {
-        Self { root: root.into() }
+        Self {
+            root: root.into(),
+            cipher: None,
+        }
+    }
+
+    pub fn with_cipher(mut self, cipher: Option<Arc<Cipher>>) -> Self {
+        self.cipher = cipher;
+        self
     }
 
     pub fn manifest_path(&self) -> PathBuf {
@@ -60,13 +100,57 @@ impl DiskLayout {
     }
 
     pub fn hnsw_path(&self) -> PathBuf {
-        self.root.join("hnsw.idx")
+        self.root.join(HNSW_FILE)
+    }
+
+    /// Graph file of the HNSW dump, which starts with the dump's description
+    pub fn hnsw_graph_path(&self) -> PathBuf {
+        self.root.join(format!("{}.hnsw.graph", HNSW_FILE))
     }
 
     pub fn text_index_path(&self) -> PathBuf {
         self.root.join("text_index.json")
     }
 
+    pub fn quantized_path(&self) -> PathBuf {
+        self.root.join("quantized.bin")
+    }
+
+    pub fn changes_path(&self) -> PathBuf {
+        self.root.join("changes.json")
+    }
+
+    pub fn wal_path(&self) -> PathBuf {
+        self.root.join("wal.log")
+    }
+
+    /// Total size in bytes of the files directly under `root`
+    ///
+    /// Snapshot subdirectories are not counted.
+    pub fn data_size(&self) -> Result<u64> {
+        if !self.root.exists() {
+            return Ok(0);
+        }
+
+        let mut total = 0;
+        for entry in fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read directory: {:?}", self.root))?
+        {
+            let metadata = entry?.metadata()?;
+            if metadata.is_file() {
+                total += metadata.len();
+            }
+        }
+        Ok(total)
+    }
+
+    /// When the last save finished writing the manifest, as a Unix timestamp
+    pub fn last_saved_at(&self) -> Option<i64> {
+        let modified = fs::metadata(self.manifest_path()).ok()?.modified().ok()?;
+        let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+        Some(since_epoch.as_secs() as i64)
+    }
+
     pub fn ensure_directory(&self) -> Result<()> {
         fs::create_dir_all(&self.root)
             .with_context(|| format!("Failed to create directory: {:?}", self.root))?;
@@ -130,25 +214,12 @@ impl DiskLayout {
             return Err(anyhow::anyhow!("Store does not exist at {:?}", self.root));
         }
 
-        // Load manifest
-        let manifest_data = fs::read(self.manifest_path()).context("Failed to read manifest")?;
-        let manifest: Manifest =
-            serde_json::from_slice(&manifest_data).context("Failed to parse manifest")?;
-
-        // Support schema versions 1, 2, and 3 (backward compatibility)
-        if manifest.schema_version != SCHEMA_VERSION
-            && manifest.schema_version != 2
-            && manifest.schema_version != 1
-        {
-            return Err(anyhow::anyhow!(
-                "Unsupported schema version: {}. Expected 1, 2, or {}",
-                manifest.schema_version,
-                SCHEMA_VERSION
-            ));
-        }
+        let manifest = self.load_manifest()?;
 
         // Load records
-        let records_data = fs::read(self.vectors_path()).context("Failed to read vectors")?;
+        let records_data = self
+            .read(&self.vectors_path())
+            .context("Failed to read vectors")?;
         let records_vec: Vec<Record> =
             serde_json::from_slice(&records_data).context("Failed to deserialize vectors")?;
 
@@ -158,15 +229,18 @@ impl DiskLayout {
         }
 
         // Load metadata
-        let meta_data = fs::read(self.meta_path()).context("Failed to read metadata")?;
+        let meta_data = self
+            .read(&self.meta_path())
+            .context("Failed to read metadata")?;
         let (id_to_idx, idx_to_id, next_idx): (HashMap<Id, usize>, HashMap<usize, Id>, usize) =
             bincode::deserialize(&meta_data).context("Failed to deserialize metadata")?;
 
         // Load text index if present (Major Issue #6 fix)
         // Only available in schema version 3+
         let text_index_data = if manifest.schema_version >= 3 && self.text_index_path().exists() {
-            let text_data =
-                fs::read(self.text_index_path()).context("Failed to read text index")?;
+            let text_data = self
+                .read(&self.text_index_path())
+                .context("Failed to read text index")?;
             let texts: HashMap<Id, String> =
                 serde_json::from_slice(&text_data).context("Failed to deserialize text index")?;
             Some(texts)
@@ -186,7 +260,150 @@ impl DiskLayout {
         ))
     }
 
+    /// Read the manifest, checking its schema version
+    pub fn load_manifest(&self) -> Result<Manifest> {
+        let manifest_data = fs::read(self.manifest_path()).context("Failed to read manifest")?;
+        let manifest: Manifest =
+            serde_json::from_slice(&manifest_data).context("Failed to parse manifest")?;
+
+        // Support schema versions 1, 2, and 3 (backward compatibility)
+        if manifest.schema_version != SCHEMA_VERSION
+            && manifest.schema_version != 2
+            && manifest.schema_version != 1
+        {
+            return Err(anyhow::anyhow!(
+                "Unsupported schema version: {}. Expected 1, 2, or {}",
+                manifest.schema_version,
+                SCHEMA_VERSION
+            ));
+        }
+        Ok(manifest)
+    }
+
+    /// Write the int8 codebook and codes, or remove a stale file when `None`
+    pub fn save_quantized(&self, quantized: Option<&QuantizedVectors>) -> Result<()> {
+        match quantized {
+            Some(quantized) => {
+                self.atomic_write(&self.quantized_path(), &bincode::serialize(quantized)?)
+            }
+            None if self.quantized_path().exists() => {
+                fs::remove_file(self.quantized_path()).context("Failed to remove quantized vectors")
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub fn load_quantized(&self) -> Result<Option<QuantizedVectors>> {
+        if !self.quantized_path().exists() {
+            return Ok(None);
+        }
+        let data = self
+            .read(&self.quantized_path())
+            .context("Failed to read quantized vectors")?;
+        let quantized =
+            bincode::deserialize(&data).context("Failed to deserialize quantized vectors")?;
+        Ok(Some(quantized))
+    }
+
+    pub fn save_changes(&self, changes: &ChangeState) -> Result<()> {
+        self.atomic_write(&self.changes_path(), &serde_json::to_vec(changes)?)
+    }
+
+    /// Load the sequence state, or `None` for stores saved without one
+    pub fn load_changes(&self) -> Result<Option<ChangeState>> {
+        if !self.changes_path().exists() {
+            return Ok(None);
+        }
+        let data = self
+            .read(&self.changes_path())
+            .context("Failed to read change state")?;
+        let changes = serde_json::from_slice(&data).context("Failed to parse change state")?;
+        Ok(Some(changes))
+    }
+
+    /// Delete the files of the HNSW dump, as saved for an empty index
+    ///
+    /// hnsw_rs can't dump an index without points, and the store rebuilds
+    /// its index from the records on open anyway, so an empty index is
+    /// saved as no dump at all.
+    pub fn remove_index_dump(&self) -> Result<()> {
+        for path in self.index_dump_files()? {
+            fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+        }
+        Ok(())
+    }
+
+    /// Encrypt the files of the HNSW dump in place when the store is encrypted
+    pub fn seal_index(&self) -> Result<()> {
+        if self.cipher.is_none() {
+            return Ok(());
+        }
+        for path in self.index_dump_files()? {
+            let data = fs::read(&path).with_context(|| format!("Failed to read {:?}", path))?;
+            if !encryption::is_sealed(&data) {
+                self.atomic_write(&path, &data)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move the HNSW dump in `staged` over this layout's dump
+    ///
+    /// Each file is renamed over its counterpart, then any file of the old
+    /// dump the new one doesn't have is deleted. Sealed files keep their
+    /// names, so a dump sealed in `staged` stays readable here.
+    pub fn install_index(&self, staged: &DiskLayout) -> Result<()> {
+        let mut installed = Vec::new();
+        for path in staged.index_dump_files()? {
+            let target = self.root.join(file_name(&path));
+            fs::rename(&path, &target)
+                .with_context(|| format!("Failed to move {:?} to {:?}", path, target))?;
+            installed.push(target);
+        }
+        for path in self.index_dump_files()? {
+            if !installed.contains(&path) {
+                fs::remove_file(&path).with_context(|| format!("Failed to remove {:?}", path))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn index_dump_files(&self) -> Result<Vec<PathBuf>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.root)
+            .with_context(|| format!("Failed to read directory: {:?}", self.root))?
+        {
+            let path = entry?.path();
+            let is_dump = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(HNSW_FILE));
+            if is_dump && path.is_file() {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Read a store file, decrypting it when the store is encrypted
+    pub fn read(&self, path: &Path) -> Result<Vec<u8>> {
+        let data = fs::read(path)?;
+        encryption::unseal(self.cipher.as_deref(), &file_name(path), data)
+    }
+
+    /// Write via a temp file and rename, sealing all but the manifest
     fn atomic_write(&self, path: &Path, data: &[u8]) -> Result<()> {
+        let sealed;
+        let data = match &self.cipher {
+            Some(cipher) if path != self.manifest_path() => {
+                sealed = cipher.seal(&file_name(path), data)?;
+                sealed.as_slice()
+            }
+            _ => data,
+        };
         let temp_path = path.with_extension("tmp");
         fs::write(&temp_path, data)
             .with_context(|| format!("Failed to write temp file: {:?}", temp_path))?;
@@ -195,3 +412,9 @@ impl DiskLayout {
         Ok(())
     }
 }
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,596 @@
+//! Encryption at rest for store files and backups
+//!
+//! A store opened with an [`EncryptionKey`] gets a random 256-bit data key,
+//! kept in `keyring.json` wrapped (encrypted) under a key-encryption key.
+//! The key-encryption key is derived from a passphrase with Argon2id, or is
+//! a raw 256-bit key. Every data file is sealed with XChaCha20-Poly1305
+//! under the data key:
+//!
+//! ```text
+//! [magic: b"VSCRYPT1"][nonce: 24 bytes][ciphertext][tag: 16 bytes]
+//! ```
+//!
+//! The file name is authenticated with the contents, so sealed files can't
+//! be swapped for one another. Only the keyring depends on the caller's key,
+//! which is what lets [`VecStore::rewrap_key`](super::VecStore::rewrap_key)
+//! change it without rewriting any data.
+//!
+//! `manifest.json` stays in plaintext: it holds counts and configuration,
+//! not records, and is how a directory is recognised as a store.
+
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+#[cfg(feature = "encryption")]
+use anyhow::Context;
+#[cfg(feature = "encryption")]
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+#[cfg(feature = "encryption")]
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+#[cfg(feature = "encryption")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "encryption")]
+use std::fs;
+
+/// File holding the wrapped data key of an encrypted store
+pub(crate) const KEYRING_FILE: &str = "keyring.json";
+
+/// Environment variable read for a raw key, as 64 hex digits
+#[cfg(feature = "encryption")]
+const KEY_ENV: &str = "VECSTORE_ENCRYPTION_KEY";
+
+/// Environment variable read for a passphrase when `KEY_ENV` is unset
+#[cfg(feature = "encryption")]
+const PASSPHRASE_ENV: &str = "VECSTORE_ENCRYPTION_PASSPHRASE";
+
+const MAGIC: &[u8; 8] = b"VSCRYPT1";
+
+#[cfg(feature = "encryption")]
+const NONCE_LEN: usize = 24;
+#[cfg(feature = "encryption")]
+const KEY_LEN: usize = 32;
+#[cfg(feature = "encryption")]
+const SALT_LEN: usize = 16;
+#[cfg(feature = "encryption")]
+const KEYRING_VERSION: u32 = 1;
+/// Authenticated with the wrapped data key
+#[cfg(feature = "encryption")]
+const KEYRING_AAD: &[u8] = b"vecstore keyring v1";
+
+/// Key an encrypted store is opened with
+///
+/// # Example
+///
+/// ```no_run
+/// # use vecstore::{EncryptionKey, VecStore};
+/// let store = VecStore::builder("./data")
+///     .encryption(EncryptionKey::passphrase("correct horse battery staple"))
+///     .build()?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[cfg(feature = "encryption")]
+#[derive(Clone)]
+pub enum EncryptionKey {
+    /// Stretched into a key with Argon2id
+    Passphrase(String),
+    /// Used as the key-encryption key directly
+    Raw([u8; 32]),
+}
+
+/// Keys only exist with the `encryption` feature
+#[cfg(not(feature = "encryption"))]
+pub enum EncryptionKey {}
+
+#[cfg(feature = "encryption")]
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Passphrase(_) => f.write_str("EncryptionKey::Passphrase(..)"),
+            Self::Raw(_) => f.write_str("EncryptionKey::Raw(..)"),
+        }
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl EncryptionKey {
+    pub fn passphrase(passphrase: impl Into<String>) -> Self {
+        Self::Passphrase(passphrase.into())
+    }
+
+    pub fn raw(key: [u8; 32]) -> Self {
+        Self::Raw(key)
+    }
+
+    /// Parse a raw key written as 64 hex digits
+    pub fn from_hex(hex: &str) -> Result<Self> {
+        let hex = hex.trim();
+        if hex.len() != KEY_LEN * 2 || !hex.is_ascii() {
+            return Err(anyhow!(
+                "Raw encryption key must be {} hex digits",
+                KEY_LEN * 2
+            ));
+        }
+        let mut key = [0u8; KEY_LEN];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .map_err(|_| anyhow!("Raw encryption key is not valid hex"))?;
+        }
+        Ok(Self::Raw(key))
+    }
+
+    /// Read a raw key from `VECSTORE_ENCRYPTION_KEY` (64 hex digits), or else
+    /// a passphrase from `VECSTORE_ENCRYPTION_PASSPHRASE`
+    ///
+    /// Returns `None` when neither variable is set.
+    pub fn from_env() -> Result<Option<Self>> {
+        if let Ok(hex) = std::env::var(KEY_ENV) {
+            return Self::from_hex(&hex)
+                .with_context(|| format!("Invalid key in {}", KEY_ENV))
+                .map(Some);
+        }
+        Ok(std::env::var(PASSPHRASE_ENV).ok().map(Self::Passphrase))
+    }
+
+    fn kdf(&self) -> Kdf {
+        match self {
+            Self::Passphrase(_) => Kdf::Argon2id,
+            Self::Raw(_) => Kdf::Raw,
+        }
+    }
+
+    /// Derive the key-encryption key
+    fn derive(&self, salt: &[u8]) -> Result<[u8; KEY_LEN]> {
+        match self {
+            Self::Passphrase(passphrase) => {
+                let mut key = [0u8; KEY_LEN];
+                argon2::Argon2::default()
+                    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+                    .map_err(|e| anyhow!("Failed to derive key from passphrase: {}", e))?;
+                Ok(key)
+            }
+            Self::Raw(key) => Ok(*key),
+        }
+    }
+}
+
+/// How a keyring's key-encryption key comes from the caller's key
+#[cfg(feature = "encryption")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Kdf {
+    Argon2id,
+    Raw,
+}
+
+/// Contents of `keyring.json`
+#[cfg(feature = "encryption")]
+#[derive(Debug, Serialize, Deserialize)]
+struct Keyring {
+    version: u32,
+    kdf: Kdf,
+    /// Argon2 salt; empty for raw keys
+    #[serde(default)]
+    salt: Vec<u8>,
+    nonce: Vec<u8>,
+    /// Data key sealed under the key-encryption key
+    wrapped_key: Vec<u8>,
+}
+
+#[cfg(feature = "encryption")]
+impl Keyring {
+    fn wrap(key: &EncryptionKey, data_key: &[u8]) -> Result<Self> {
+        let salt = match key.kdf() {
+            Kdf::Argon2id => random_bytes::<SALT_LEN>().to_vec(),
+            Kdf::Raw => Vec::new(),
+        };
+        let kek = key.derive(&salt)?;
+        let nonce = random_bytes::<NONCE_LEN>();
+        let wrapped_key = XChaCha20Poly1305::new(Key::from_slice(&kek))
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: data_key,
+                    aad: KEYRING_AAD,
+                },
+            )
+            .map_err(|_| anyhow!("Failed to wrap the data key"))?;
+        Ok(Self {
+            version: KEYRING_VERSION,
+            kdf: key.kdf(),
+            salt,
+            nonce: nonce.to_vec(),
+            wrapped_key,
+        })
+    }
+
+    /// Recover the data key, failing with a clear message for a wrong key
+    fn data_key(&self, key: &EncryptionKey, store: &Path) -> Result<Vec<u8>> {
+        if self.version != KEYRING_VERSION {
+            return Err(anyhow!(
+                "Unsupported keyring version {} in {:?}",
+                self.version,
+                store
+            ));
+        }
+        if self.kdf != key.kdf() {
+            let expected = match self.kdf {
+                Kdf::Argon2id => "a passphrase",
+                Kdf::Raw => "a raw key",
+            };
+            return Err(anyhow!(
+                "Wrong encryption key for {:?}: it is encrypted with {}",
+                store,
+                expected
+            ));
+        }
+        if self.nonce.len() != NONCE_LEN {
+            return Err(anyhow!("Keyring in {:?} is corrupted", store));
+        }
+
+        let kek = key.derive(&self.salt)?;
+        XChaCha20Poly1305::new(Key::from_slice(&kek))
+            .decrypt(
+                XNonce::from_slice(&self.nonce),
+                Payload {
+                    msg: &self.wrapped_key,
+                    aad: KEYRING_AAD,
+                },
+            )
+            .ok()
+            .filter(|data_key| data_key.len() == KEY_LEN)
+            .ok_or_else(|| anyhow!("Wrong encryption key for {:?}", store))
+    }
+
+    fn write(&self, root: &Path) -> Result<()> {
+        let path = root.join(KEYRING_FILE);
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Failed to write keyring: {:?}", temp))?;
+        fs::rename(&temp, &path)
+            .with_context(|| format!("Failed to move keyring into place: {:?}", path))
+    }
+}
+
+/// Seals and opens store files under a store's data key
+#[cfg(feature = "encryption")]
+pub(crate) struct Cipher {
+    aead: XChaCha20Poly1305,
+}
+
+/// Ciphers only exist with the `encryption` feature
+#[cfg(not(feature = "encryption"))]
+pub(crate) enum Cipher {}
+
+impl Cipher {
+    #[cfg(feature = "encryption")]
+    fn new(data_key: &[u8]) -> Self {
+        Self {
+            aead: XChaCha20Poly1305::new(Key::from_slice(data_key)),
+        }
+    }
+
+    /// Encrypt the contents of the file `name`
+    pub(crate) fn seal(&self, name: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+        #[cfg(feature = "encryption")]
+        {
+            let nonce = random_bytes::<NONCE_LEN>();
+            let ciphertext = self
+                .aead
+                .encrypt(
+                    XNonce::from_slice(&nonce),
+                    Payload {
+                        msg: plaintext,
+                        aad: name.as_bytes(),
+                    },
+                )
+                .map_err(|_| anyhow!("Failed to encrypt {}", name))?;
+
+            let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+            sealed.extend_from_slice(MAGIC);
+            sealed.extend_from_slice(&nonce);
+            sealed.extend_from_slice(&ciphertext);
+            Ok(sealed)
+        }
+        #[cfg(not(feature = "encryption"))]
+        {
+            let _ = (name, plaintext);
+            match *self {}
+        }
+    }
+
+    /// Decrypt the contents of the file `name`
+    pub(crate) fn open(&self, name: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+        #[cfg(feature = "encryption")]
+        {
+            let body = sealed
+                .strip_prefix(MAGIC.as_slice())
+                .filter(|body| body.len() >= NONCE_LEN)
+                .ok_or_else(|| anyhow!("{} is not an encrypted file", name))?;
+            let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+            self.aead
+                .decrypt(
+                    XNonce::from_slice(nonce),
+                    Payload {
+                        msg: ciphertext,
+                        aad: name.as_bytes(),
+                    },
+                )
+                .map_err(|_| {
+                    anyhow!(
+                        "Failed to decrypt {}: it is corrupted or from another store",
+                        name
+                    )
+                })
+        }
+        #[cfg(not(feature = "encryption"))]
+        {
+            let _ = (name, sealed);
+            match *self {}
+        }
+    }
+}
+
+/// Whether `data` was written by [`Cipher::seal`]
+pub(crate) fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Contents of the file `name` as stored, decrypted when `cipher` is set
+///
+/// A plaintext file in an encrypted store is rejected rather than trusted.
+pub(crate) fn unseal(cipher: Option<&Cipher>, name: &str, data: Vec<u8>) -> Result<Vec<u8>> {
+    match (cipher, is_sealed(&data)) {
+        (Some(cipher), true) => cipher.open(name, &data),
+        (Some(_), false) => Err(anyhow!("{} is not encrypted, but the store is", name)),
+        (None, true) => Err(anyhow!("{} is encrypted, but no key was provided", name)),
+        (None, false) => Ok(data),
+    }
+}
+
+/// Whether the store at `root` is encrypted
+pub(crate) fn is_encrypted(root: &Path) -> bool {
+    root.join(KEYRING_FILE).exists()
+}
+
+/// Unlock an encrypted store's keyring
+///
+/// Without an explicit `key`, the key is read from the environment.
+/// `store` names the store in errors.
+pub(crate) fn unlock(keyring: &[u8], key: Option<&EncryptionKey>, store: &Path) -> Result<Cipher> {
+    #[cfg(feature = "encryption")]
+    {
+        let from_env;
+        let key = match key {
+            Some(key) => key,
+            None => {
+                from_env = EncryptionKey::from_env()?.ok_or_else(|| {
+                    anyhow!(
+                        "{:?} is encrypted; open it with VecStoreBuilder::encryption \
+                         or set {} or {}",
+                        store,
+                        KEY_ENV,
+                        PASSPHRASE_ENV
+                    )
+                })?;
+                &from_env
+            }
+        };
+        let keyring: Keyring = serde_json::from_slice(keyring)
+            .with_context(|| format!("Keyring in {:?} is corrupted", store))?;
+        Ok(Cipher::new(&keyring.data_key(key, store)?))
+    }
+    #[cfg(not(feature = "encryption"))]
+    {
+        let _ = (keyring, key);
+        Err(anyhow!(
+            "{:?} is encrypted, but vecstore was built without the `encryption` feature",
+            store
+        ))
+    }
+}
+
+/// Unlock the store at `root` if it is encrypted
+pub(crate) fn load(root: &Path, key: Option<&EncryptionKey>) -> Result<Option<Cipher>> {
+    if !is_encrypted(root) {
+        return Ok(None);
+    }
+    let keyring = std::fs::read(root.join(KEYRING_FILE))
+        .map_err(|e| anyhow!("Failed to read keyring in {:?}: {}", root, e))?;
+    unlock(&keyring, key, root).map(Some)
+}
+
+/// Give the store at `root` a new data key wrapped under `key`
+pub(crate) fn create(root: &Path, key: &EncryptionKey) -> Result<Cipher> {
+    #[cfg(feature = "encryption")]
+    {
+        let data_key = random_bytes::<KEY_LEN>();
+        fs::create_dir_all(root)
+            .with_context(|| format!("Failed to create directory: {:?}", root))?;
+        Keyring::wrap(key, &data_key)?.write(root)?;
+        Ok(Cipher::new(&data_key))
+    }
+    #[cfg(not(feature = "encryption"))]
+    {
+        let _ = root;
+        match *key {}
+    }
+}
+
+/// Re-wrap the data key of the store at `root` from `old` to `new`
+#[cfg(feature = "encryption")]
+pub(crate) fn rewrap(root: &Path, old: &EncryptionKey, new: &EncryptionKey) -> Result<()> {
+    if !is_encrypted(root) {
+        return Err(anyhow!("{:?} is not an encrypted store", root));
+    }
+    let data = fs::read(root.join(KEYRING_FILE))
+        .with_context(|| format!("Failed to read keyring in {:?}", root))?;
+    let keyring: Keyring = serde_json::from_slice(&data)
+        .with_context(|| format!("Keyring in {:?} is corrupted", root))?;
+    let data_key = keyring.data_key(old, root)?;
+    Keyring::wrap(new, &data_key)?.write(root)
+}
+
+#[cfg(feature = "encryption")]
+fn random_bytes<const N: usize>() -> [u8; N] {
+    use rand::RngCore;
+    let mut bytes = [0u8; N];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+#[cfg(all(test, feature = "encryption"))]
+mod tests {
+    use super::*;
+    use crate::store::{Metadata, Query, VecStore};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    const KEY: [u8; 32] = [7; 32];
+
+    fn meta(i: usize) -> Metadata {
+        let mut fields = HashMap::new();
+        fields.insert(
+            "secret".into(),
+            serde_json::json!(format!("classified-{i}")),
+        );
+        Metadata { fields }
+    }
+
+    fn contains(haystack: &[u8], needle: &str) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle.as_bytes())
+    }
+
+    fn encrypted_store(path: &Path, key: EncryptionKey) -> VecStore {
+        let mut store = VecStore::builder(path).encryption(key).build().unwrap();
+        for i in 0..50 {
+            store
+                .upsert(format!("doc{i}"), vec![i as f32, 1.0, 0.5], meta(i))
+                .unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_files_sealed_and_key_checked() {
+        let dir = TempDir::new().unwrap();
+        let store = encrypted_store(dir.path(), EncryptionKey::raw(KEY));
+        store.save().unwrap();
+        assert!(store.is_encrypted());
+
+        for name in ["vectors.bin", "meta.bin", "changes.json"] {
+            let data = fs::read(dir.path().join(name)).unwrap();
+            assert!(is_sealed(&data), "{name} is not sealed");
+            assert!(!contains(&data, "classified-3"), "{name} leaks metadata");
+        }
+
+        let reopened = VecStore::builder(dir.path())
+            .encryption(EncryptionKey::raw(KEY))
+            .build()
+            .unwrap();
+        assert_eq!(reopened.len(), 50);
+        let results = reopened
+            .query(Query::new(vec![3.0, 1.0, 0.5]).with_limit(1))
+            .unwrap();
+        assert_eq!(results[0].id, "doc3");
+
+        let err = VecStore::builder(dir.path())
+            .encryption(EncryptionKey::raw([8; 32]))
+            .build()
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Wrong encryption key"), "{err}");
+        let err = VecStore::builder(dir.path())
+            .encryption(EncryptionKey::passphrase("guess"))
+            .build()
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("raw key"), "{err}");
+        // Without a key the environment is consulted, which another test may set
+        let err = VecStore::open(dir.path()).err().unwrap();
+        assert!(err.to_string().contains("encrypt"), "{err}");
+    }
+
+    #[test]
+    fn test_rewrap_key_keeps_data() {
+        let dir = TempDir::new().unwrap();
+        let old = EncryptionKey::passphrase("old passphrase");
+        let new = EncryptionKey::passphrase("new passphrase");
+        encrypted_store(dir.path(), old.clone()).save().unwrap();
+        let sealed = fs::read(dir.path().join("vectors.bin")).unwrap();
+
+        assert!(VecStore::rewrap_key(dir.path(), &new, &new).is_err());
+        VecStore::rewrap_key(dir.path(), &old, &new).unwrap();
+
+        // Only the keyring changed
+        assert_eq!(fs::read(dir.path().join("vectors.bin")).unwrap(), sealed);
+        assert!(VecStore::builder(dir.path())
+            .encryption(old)
+            .build()
+            .is_err());
+        let store = VecStore::builder(dir.path())
+            .encryption(new)
+            .build()
+            .unwrap();
+        assert_eq!(store.len(), 50);
+    }
+
+    #[test]
+    fn test_plaintext_store_encrypted_on_open() {
+        let dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(dir.path()).unwrap();
+        store.upsert("a".into(), vec![1.0, 0.0], meta(1)).unwrap();
+        store.save().unwrap();
+        drop(store);
+
+        let store = VecStore::builder(dir.path())
+            .encryption(EncryptionKey::raw(KEY))
+            .build()
+            .unwrap();
+        assert_eq!(store.len(), 1);
+        assert!(is_sealed(
+            &fs::read(dir.path().join("vectors.bin")).unwrap()
+        ));
+
+        let wal = VecStore::builder(dir.path())
+            .encryption(EncryptionKey::raw(KEY))
+            .wal(crate::wal::WalSync::PerWrite)
+            .build();
+        assert!(wal.is_err());
+    }
+
+    #[test]
+    fn test_encrypted_backup_chain() {
+        let dir = TempDir::new().unwrap();
+        let key: String = [0x5a_u8; 32].iter().map(|b| format!("{b:02x}")).collect();
+        let mut store = encrypted_store(
+            &dir.path().join("source"),
+            EncryptionKey::from_hex(&key).unwrap(),
+        );
+        store.index_text("doc1", "classified-text").unwrap();
+
+        let full = dir.path().join("full.vsbak");
+        let inc = dir.path().join("inc.vsbak");
+        let base = store.backup_compressed(&full).unwrap();
+        assert!(base.encrypted);
+        store
+            .upsert("late".into(), vec![9.0, 1.0, 0.5], meta(99))
+            .unwrap();
+        store.remove("doc2").unwrap();
+        let increment = store.backup_incremental(&base, &inc).unwrap();
+        assert!(increment.encrypted);
+
+        let raw = fs::read(&inc).unwrap();
+        assert!(!contains(&raw, "classified-99"));
+        assert!(!contains(&fs::read(&full).unwrap(), "classified-text"));
+
+        std::env::set_var(KEY_ENV, &key);
+        let restored =
+            VecStore::restore_chain(&[&full, &inc], dir.path().join("restored")).unwrap();
+        assert!(restored.is_encrypted());
+        assert_eq!(restored.len(), 50);
+        assert!(restored.get("late").is_some());
+        assert!(restored.get("doc2").is_none());
+    }
+}
@@ -0,0 +1,117 @@
+//! Change notifications for store subscribers
+//!
+//! Hooks registered with [`VecStore::subscribe`](super::VecStore::subscribe)
+//! are called synchronously, on the writing thread, once a change has been
+//! applied (and logged, when the write-ahead log is on). A panicking hook is
+//! caught and logged so it cannot leave the store half-updated.
+
+use super::types::Id;
+use serde::{Deserialize, Serialize};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// A change applied to a store
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum StoreEvent {
+    /// A record was inserted or replaced, its metadata changed, or it was
+    /// brought back by a restore or a snapshot
+    Upsert { id: Id, dimension: usize },
+    /// A record was removed, soft-deleted, tombstoned by its TTL or dropped
+    /// by restoring a snapshot
+    Delete { id: Id },
+    /// Soft-deleted records were dropped and the index rebuilt
+    Compact,
+    /// The store was written to disk
+    Save,
+}
+
+/// Callback registered with [`VecStore::subscribe`](super::VecStore::subscribe)
+pub type EventHook = Box<dyn Fn(StoreEvent) + Send + Sync>;
+
+/// Handle for removing a hook with [`VecStore::unsubscribe`](super::VecStore::unsubscribe)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// Registered hooks, in subscription order
+#[derive(Default)]
+pub(super) struct Subscribers {
+    next_id: u64,
+    hooks: Vec<(SubscriptionId, EventHook)>,
+}
+
+impl Subscribers {
+    pub fn add(&mut self, hook: EventHook) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.hooks.push((id, hook));
+        id
+    }
+
+    pub fn remove(&mut self, id: SubscriptionId) -> bool {
+        let before = self.hooks.len();
+        self.hooks.retain(|(hook_id, _)| *hook_id != id);
+        self.hooks.len() != before
+    }
+
+    /// Call every hook with `event`, logging any that panic
+    pub fn emit(&self, event: StoreEvent) {
+        for (id, hook) in &self.hooks {
+            if let Err(panic) = catch_unwind(AssertUnwindSafe(|| hook(event.clone()))) {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("unknown panic");
+                tracing::error!(
+                    subscription = id.0,
+                    ?event,
+                    "Store event hook panicked: {}",
+                    message
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_panicking_hook_does_not_stop_others() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut subscribers = Subscribers::default();
+        subscribers.add(Box::new(|_| panic!("subscriber bug")));
+        let sink = seen.clone();
+        let id = subscribers.add(Box::new(move |event| sink.lock().unwrap().push(event)));
+
+        subscribers.emit(StoreEvent::Save);
+        subscribers.emit(StoreEvent::Compact);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![StoreEvent::Save, StoreEvent::Compact]
+        );
+
+        assert!(subscribers.remove(id));
+        assert!(!subscribers.remove(id));
+        subscribers.emit(StoreEvent::Save);
+        assert_eq!(seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = StoreEvent::Upsert {
+            id: "doc1".into(),
+            dimension: 3,
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({"event": "upsert", "id": "doc1", "dimension": 3})
+        );
+        assert_eq!(
+            serde_json::to_value(StoreEvent::Save).unwrap(),
+            serde_json::json!({"event": "save"})
+        );
+    }
+}
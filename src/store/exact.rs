@@ -0,0 +1,131 @@
+//! Exact (brute-force) nearest-neighbour search over the record map
+//!
+//! Used for small stores, where walking the HNSW graph buys nothing, and for
+//! `Query { exact: true, .. }` to get ground-truth results. Raw distances and
+//! scores follow the HNSW backend's conventions so both paths rank and score
+//! results identically.
+
+use super::filters::admits;
+use super::quantized::{full_vector, QuantizedVectors};
+use super::types::{Distance, Id, Query, Record};
+use crate::simd::{
+    dot_product_simd, euclidean_distance_simd, hamming_distance_simd, jaccard_distance_simd,
+    magnitude_simd, manhattan_distance_simd,
+};
+use chrono::Utc;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Scan the live (not deleted or expired) `records` and return the `q.k`
+/// nearest that pass `q.namespace` and `q.filter`, nearest first, as
+/// `(id, score)` pairs
+///
+/// Records whose f32 vector was dropped by quantization are compared through
+/// their dequantized codes.
+pub(super) fn search<'a>(
+    records: impl Iterator<Item = &'a Record>,
+    quantized: Option<&QuantizedVectors>,
+    distance: Distance,
+    q: &Query,
+) -> Vec<(Id, f32)> {
+    let query_norm = magnitude_simd(&q.vector);
+    let now = Utc::now().timestamp();
+
+    let mut scored: Vec<(f32, &Record)> = records
+        .filter(|r| r.is_live(now))
+        .filter(|r| admits(q, r))
+        .map(|r| {
+            let d = raw_distance(distance, &q.vector, query_norm, &full_vector(r, quantized));
+            (d, r)
+        })
+        .collect();
+
+    nearest(&mut scored, q.k);
+    scored
+        .into_iter()
+        .map(|(d, r)| (r.id.clone(), score(distance, d, q.vector.len())))
+        .collect()
+}
+
+/// Re-score index `candidates` against the records' f32 vectors and keep the
+/// `k` nearest, nearest first
+///
+/// Used to correct the ranking of a quantized index; candidates without a
+/// record (stale index entries) are dropped.
+pub(super) fn rescore(
+    records: &HashMap<Id, Record>,
+    distance: Distance,
+    query: &[f32],
+    candidates: Vec<(Id, f32)>,
+    k: usize,
+) -> Vec<(Id, f32)> {
+    let query_norm = magnitude_simd(query);
+    let mut scored: Vec<(f32, &Record)> = candidates
+        .iter()
+        .filter_map(|(id, _)| records.get(id))
+        .map(|r| (raw_distance(distance, query, query_norm, &r.vector), r))
+        .collect();
+
+    nearest(&mut scored, k);
+    scored
+        .into_iter()
+        .map(|(d, r)| (r.id.clone(), score(distance, d, query.len())))
+        .collect()
+}
+
+/// Keep the `k` smallest distances in `scored`, sorted ascending
+fn nearest(scored: &mut Vec<(f32, &Record)>, k: usize) {
+    let k = k.min(scored.len());
+    if k == 0 {
+        scored.clear();
+        return;
+    }
+
+    // Ties broken by ID so results are deterministic
+    let nearest_first = |a: &(f32, &Record), b: &(f32, &Record)| -> Ordering {
+        a.0.total_cmp(&b.0).then_with(|| a.1.id.cmp(&b.1.id))
+    };
+    if k < scored.len() {
+        scored.select_nth_unstable_by(k - 1, nearest_first);
+        scored.truncate(k);
+    }
+    scored.sort_unstable_by(nearest_first);
+}
+
+/// Distance as the HNSW index computes it (lower = closer)
+pub(super) fn raw_distance(
+    distance: Distance,
+    query: &[f32],
+    query_norm: f32,
+    vector: &[f32],
+) -> f32 {
+    match distance {
+        Distance::Euclidean => euclidean_distance_simd(query, vector),
+        Distance::DotProduct => (1.0 - dot_product_simd(query, vector)).max(0.0),
+        Distance::Manhattan => manhattan_distance_simd(query, vector),
+        Distance::Hamming => hamming_distance_simd(query, vector),
+        Distance::Jaccard => jaccard_distance_simd(query, vector),
+        // Cosine; other metrics are rejected when the index is built
+        _ => {
+            let norm = magnitude_simd(vector);
+            if query_norm == 0.0 || norm == 0.0 {
+                0.0
+            } else {
+                (1.0 - dot_product_simd(query, vector) / (query_norm * norm)).max(0.0)
+            }
+        }
+    }
+}
+
+/// Convert a raw distance into the reported score, higher = closer
+///
+/// Cosine, DotProduct and Jaccard report the similarity `1 - d`; Euclidean
+/// and Manhattan report `1 / (1 + d)`. `dim` normalizes Hamming's bit count
+/// into `[0, 1]`.
+pub(super) fn score(distance: Distance, raw: f32, dim: usize) -> f32 {
+    match distance {
+        Distance::Euclidean | Distance::Manhattan => 1.0 / (1.0 + raw),
+        Distance::Hamming => 1.0 - raw / dim.max(1) as f32,
+        _ => 1.0 - raw,
+    }
+}
@@ -0,0 +1,469 @@
+//! Secondary indexes on metadata fields
+//!
+//! A field index maps the values of one metadata field to the records that
+//! hold them, so a filtered query can start from the records that can match
+//! instead of testing every candidate's metadata. Keyword indexes hash exact
+//! values; numeric indexes keep numbers ordered for range filters.
+//!
+//! Lookups follow the filter's own comparison rules (numbers and numeric
+//! strings compare as numbers, array fields match by element) and may return
+//! records the filter then rejects, but never leave out one it accepts. The
+//! filter is still evaluated on every candidate. Filters an index can't
+//! answer (`Neq`, `NotIn`, `IsNull`, `Not`, substring and pattern matches,
+//! geo radius) leave the candidates unrestricted.
+//!
+//! Index definitions are saved with the store's config; the postings are
+//! rebuilt from the records when the store is opened.
+
+use super::filters::as_f64;
+use super::types::{FilterExpr, FilterOp, Id, Metadata, Record};
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Bound;
+
+/// Kind of index kept on a metadata field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldIndexType {
+    /// Exact values (strings, numbers, booleans, null) for `Eq`, `In` and
+    /// `AnyIn` filters
+    Keyword,
+    /// Numbers, ordered, for `Eq`, `In`, `AnyIn`, `Gt`, `Gte`, `Lt`, `Lte`
+    /// and `Between` filters
+    Numeric,
+}
+
+/// Summary of one field index, as listed in [`StoreStats`](super::StoreStats)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldIndexStats {
+    pub field: String,
+    pub index_type: FieldIndexType,
+    /// Distinct values indexed
+    pub cardinality: usize,
+    /// Records with at least one indexed value
+    pub indexed_records: usize,
+}
+
+/// A field value as an index files it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Number(OrderedFloat<f64>),
+    Text(String),
+    Bool(bool),
+    Null,
+}
+
+/// Postings for one field
+#[derive(Debug)]
+struct FieldIndex {
+    index_type: FieldIndexType,
+    /// Non-numeric values (keyword indexes only)
+    values: HashMap<Key, HashSet<Id>>,
+    /// Numbers and numeric strings, ordered so equality can allow the
+    /// filter's tolerance and numeric indexes can answer ranges
+    numbers: BTreeMap<OrderedFloat<f64>, HashSet<Id>>,
+    /// Keys each record is filed under, so updates can unfile the old ones
+    entries: HashMap<Id, Vec<Key>>,
+}
+
+impl FieldIndex {
+    fn new(index_type: FieldIndexType) -> Self {
+        Self {
+            index_type,
+            values: HashMap::new(),
+            numbers: BTreeMap::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    fn key(&self, value: &Value) -> Option<Key> {
+        if let Some(n) = as_f64(value).filter(|n| !n.is_nan()) {
+            // Adding zero folds -0.0 into 0.0
+            return Some(Key::Number(OrderedFloat(n + 0.0)));
+        }
+        if self.index_type == FieldIndexType::Numeric {
+            return None;
+        }
+        match value {
+            Value::String(s) => Some(Key::Text(s.clone())),
+            Value::Bool(b) => Some(Key::Bool(*b)),
+            Value::Null => Some(Key::Null),
+            Value::Number(_) | Value::Array(_) | Value::Object(_) => None,
+        }
+    }
+
+    /// Keys for a field value; an array is filed under each of its elements
+    fn keys(&self, value: &Value) -> Vec<Key> {
+        let mut keys: Vec<Key> = match value {
+            Value::Array(items) => items.iter().filter_map(|item| self.key(item)).collect(),
+            scalar => self.key(scalar).into_iter().collect(),
+        };
+        keys.dedup();
+        keys
+    }
+
+    fn insert(&mut self, id: &str, value: &Value) {
+        self.remove(id);
+        let keys = self.keys(value);
+        if keys.is_empty() {
+            return;
+        }
+        for key in &keys {
+            let ids = match key {
+                Key::Number(n) => self.numbers.entry(*n).or_default(),
+                other => self.values.entry(other.clone()).or_default(),
+            };
+            ids.insert(id.to_string());
+        }
+        self.entries.insert(id.to_string(), keys);
+    }
+
+    fn remove(&mut self, id: &str) {
+        let Some(keys) = self.entries.remove(id) else {
+            return;
+        };
+        for key in keys {
+            match key {
+                Key::Number(n) => {
+                    if let Some(ids) = self.numbers.get_mut(&n) {
+                        ids.remove(id);
+                        if ids.is_empty() {
+                            self.numbers.remove(&n);
+                        }
+                    }
+                }
+                other => {
+                    if let Some(ids) = self.values.get_mut(&other) {
+                        ids.remove(id);
+                        if ids.is_empty() {
+                            self.values.remove(&other);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Records that may satisfy `field <op> target`, or `None` when this
+    /// index can't answer the comparison
+    fn lookup<'a>(&'a self, op: &FilterOp, target: &Value) -> Option<HashSet<&'a str>> {
+        let mut ids = HashSet::new();
+        match op {
+            FilterOp::Eq => self.collect_equal(target, &mut ids)?,
+            FilterOp::In | FilterOp::AnyIn => {
+                // Anything but an array matches nothing
+                if let Value::Array(items) = target {
+                    for item in items {
+                        self.collect_equal(item, &mut ids)?;
+                    }
+                }
+            }
+            FilterOp::Gt | FilterOp::Gte | FilterOp::Lt | FilterOp::Lte
+                if self.index_type == FieldIndexType::Numeric =>
+            {
+                let bound = OrderedFloat(as_f64(target)?);
+                let range = match op {
+                    FilterOp::Gt => (Bound::Excluded(bound), Bound::Unbounded),
+                    FilterOp::Gte => (Bound::Included(bound), Bound::Unbounded),
+                    FilterOp::Lt => (Bound::Unbounded, Bound::Excluded(bound)),
+                    _ => (Bound::Unbounded, Bound::Included(bound)),
+                };
+                self.collect_range(range, &mut ids);
+            }
+            FilterOp::Between if self.index_type == FieldIndexType::Numeric => {
+                // Malformed bounds match nothing
+                if let Value::Array(bounds) = target {
+                    if bounds.len() == 2 {
+                        let low = OrderedFloat(as_f64(&bounds[0])?);
+                        let high = OrderedFloat(as_f64(&bounds[1])?);
+                        if low <= high {
+                            self.collect_range(
+                                (Bound::Included(low), Bound::Included(high)),
+                                &mut ids,
+                            );
+                        }
+                    }
+                }
+            }
+            _ => return None,
+        }
+        Some(ids)
+    }
+
+    /// Add the records whose value equals `target` under the filter's rules
+    fn collect_equal<'a>(&'a self, target: &Value, ids: &mut HashSet<&'a str>) -> Option<()> {
+        match self.key(target)? {
+            // The filter treats numbers within f64::EPSILON as equal
+            Key::Number(n) => self.collect_range(
+                (
+                    Bound::Included(OrderedFloat(n.0 - f64::EPSILON)),
+                    Bound::Included(OrderedFloat(n.0 + f64::EPSILON)),
+                ),
+                ids,
+            ),
+            key => {
+                if let Some(matching) = self.values.get(&key) {
+                    ids.extend(matching.iter().map(String::as_str));
+                }
+            }
+        }
+        Some(())
+    }
+
+    fn collect_range<'a>(
+        &'a self,
+        range: (Bound<OrderedFloat<f64>>, Bound<OrderedFloat<f64>>),
+        ids: &mut HashSet<&'a str>,
+    ) {
+        for matching in self.numbers.range(range).map(|(_, ids)| ids) {
+            ids.extend(matching.iter().map(String::as_str));
+        }
+    }
+}
+
+/// The field indexes of a store
+#[derive(Debug, Default)]
+pub(super) struct FieldIndexes {
+    indexes: BTreeMap<String, FieldIndex>,
+}
+
+impl FieldIndexes {
+    /// Build the indexes in `definitions` over `records`
+    pub(super) fn build<'a>(
+        definitions: &BTreeMap<String, FieldIndexType>,
+        records: impl Iterator<Item = &'a Record> + Clone,
+    ) -> Self {
+        let mut indexes = Self::default();
+        for (field, index_type) in definitions {
+            indexes.create(field, *index_type, records.clone());
+        }
+        indexes
+    }
+
+    pub(super) fn contains(&self, field: &str) -> bool {
+        self.indexes.contains_key(field)
+    }
+
+    pub(super) fn create<'a>(
+        &mut self,
+        field: &str,
+        index_type: FieldIndexType,
+        records: impl Iterator<Item = &'a Record>,
+    ) {
+        let mut index = FieldIndex::new(index_type);
+        for record in records {
+            if let Some(value) = record.metadata.fields.get(field) {
+                index.insert(&record.id, value);
+            }
+        }
+        self.indexes.insert(field.to_string(), index);
+    }
+
+    pub(super) fn drop_index(&mut self, field: &str) -> bool {
+        self.indexes.remove(field).is_some()
+    }
+
+    /// File `id` under the values of `metadata`, replacing its old entries
+    pub(super) fn insert(&mut self, id: &str, metadata: &Metadata) {
+        for (field, index) in &mut self.indexes {
+            match metadata.fields.get(field) {
+                Some(value) => index.insert(id, value),
+                None => index.remove(id),
+            }
+        }
+    }
+
+    pub(super) fn remove(&mut self, id: &str) {
+        for index in self.indexes.values_mut() {
+            index.remove(id);
+        }
+    }
+
+    /// Records that may pass `filter`, or `None` when no index narrows it
+    ///
+    /// `And` intersects what its indexed clauses allow; `Or` needs every
+    /// branch indexed. The result is a superset of the passing records.
+    pub(super) fn candidates(&self, filter: &FilterExpr) -> Option<HashSet<&str>> {
+        match filter {
+            FilterExpr::Cmp { field, op, value } => self.indexes.get(field)?.lookup(op, value),
+            FilterExpr::And(clauses) => {
+                let mut narrowed: Vec<HashSet<&str>> = clauses
+                    .iter()
+                    .filter_map(|clause| self.candidates(clause))
+                    .collect();
+                narrowed.sort_by_key(HashSet::len);
+                let mut sets = narrowed.into_iter();
+                let mut ids = sets.next()?;
+                for set in sets {
+                    ids.retain(|id| set.contains(id));
+                }
+                Some(ids)
+            }
+            FilterExpr::Or(branches) => {
+                let mut ids = HashSet::new();
+                for branch in branches {
+                    ids.extend(self.candidates(branch)?);
+                }
+                Some(ids)
+            }
+            FilterExpr::Not(_) | FilterExpr::WithinRadius { .. } => None,
+        }
+    }
+
+    pub(super) fn stats(&self) -> Vec<FieldIndexStats> {
+        self.indexes
+            .iter()
+            .map(|(field, index)| FieldIndexStats {
+                field: field.clone(),
+                index_type: index.index_type,
+                cardinality: index.values.len() + index.numbers.len(),
+                indexed_records: index.entries.len(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::filters::evaluate_filter;
+    use serde_json::json;
+
+    fn record(id: &str, fields: Value) -> Record {
+        let fields = serde_json::from_value(fields).unwrap();
+        crate::store::make_record(id, vec![1.0], Metadata { fields })
+    }
+
+    fn records() -> Vec<Record> {
+        vec![
+            record(
+                "a",
+                json!({"category": "news", "price": 10, "tags": ["x", "y"]}),
+            ),
+            record(
+                "b",
+                json!({"category": "blog", "price": 25.5, "tags": ["y"]}),
+            ),
+            record("c", json!({"category": "news", "price": "40", "tags": "z"})),
+            record("d", json!({"category": 7, "price": null})),
+            record("e", json!({"other": true})),
+        ]
+    }
+
+    fn cmp(field: &str, op: FilterOp, value: Value) -> FilterExpr {
+        FilterExpr::Cmp {
+            field: field.into(),
+            op,
+            value,
+        }
+    }
+
+    /// Index lookups must keep every record the filter accepts
+    fn assert_superset(indexes: &FieldIndexes, records: &[Record], filter: &FilterExpr) {
+        let candidates = indexes
+            .candidates(filter)
+            .expect("filter should be indexed");
+        for record in records {
+            if evaluate_filter(filter, &record.metadata) {
+                assert!(
+                    candidates.contains(record.id.as_str()),
+                    "{} passes {:?} but was not a candidate",
+                    record.id,
+                    filter
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookups_cover_filter_matches() {
+        let records = records();
+        let definitions = BTreeMap::from([
+            ("category".to_string(), FieldIndexType::Keyword),
+            ("price".to_string(), FieldIndexType::Numeric),
+            ("tags".to_string(), FieldIndexType::Keyword),
+        ]);
+        let indexes = FieldIndexes::build(&definitions, records.iter());
+
+        let filters = [
+            cmp("category", FilterOp::Eq, json!("news")),
+            cmp("category", FilterOp::Eq, json!("7")),
+            cmp("category", FilterOp::In, json!(["blog", 7])),
+            cmp("tags", FilterOp::AnyIn, json!(["x", "z"])),
+            cmp("tags", FilterOp::Eq, json!("z")),
+            cmp("price", FilterOp::Gt, json!(20)),
+            cmp("price", FilterOp::Lte, json!("10")),
+            cmp("price", FilterOp::Between, json!([10, 40])),
+            cmp("price", FilterOp::Eq, json!(40.0)),
+            FilterExpr::And(vec![
+                cmp("category", FilterOp::Eq, json!("news")),
+                cmp("price", FilterOp::Gte, json!(20)),
+                cmp("unindexed", FilterOp::Exists, json!(null)),
+            ]),
+            FilterExpr::Or(vec![
+                cmp("category", FilterOp::Eq, json!("blog")),
+                cmp("price", FilterOp::Lt, json!(15)),
+            ]),
+        ];
+        for filter in &filters {
+            assert_superset(&indexes, &records, filter);
+        }
+
+        let narrowed = indexes
+            .candidates(&cmp("price", FilterOp::Between, json!([10, 30])))
+            .unwrap();
+        assert_eq!(narrowed, HashSet::from(["a", "b"]));
+        let news = indexes
+            .candidates(&FilterExpr::And(vec![
+                cmp("category", FilterOp::Eq, json!("news")),
+                cmp("price", FilterOp::Gte, json!(20)),
+            ]))
+            .unwrap();
+        assert_eq!(news, HashSet::from(["c"]));
+    }
+
+    #[test]
+    fn test_unanswerable_filters_not_narrowed() {
+        let records = records();
+        let definitions = BTreeMap::from([
+            ("category".to_string(), FieldIndexType::Keyword),
+            ("price".to_string(), FieldIndexType::Numeric),
+        ]);
+        let indexes = FieldIndexes::build(&definitions, records.iter());
+
+        for filter in [
+            cmp("category", FilterOp::Neq, json!("news")),
+            cmp("category", FilterOp::Contains, json!("ew")),
+            cmp("category", FilterOp::Gt, json!("a")),
+            cmp("price", FilterOp::Eq, json!("cheap")),
+            cmp("price", FilterOp::IsNull, json!(null)),
+            cmp("other", FilterOp::Eq, json!(true)),
+            FilterExpr::Not(Box::new(cmp("category", FilterOp::Eq, json!("news")))),
+            FilterExpr::Or(vec![
+                cmp("category", FilterOp::Eq, json!("news")),
+                cmp("other", FilterOp::Eq, json!(true)),
+            ]),
+        ] {
+            assert!(indexes.candidates(&filter).is_none(), "{:?}", filter);
+        }
+    }
+
+    #[test]
+    fn test_updates_refile_records() {
+        let records = records();
+        let definitions = BTreeMap::from([("category".to_string(), FieldIndexType::Keyword)]);
+        let mut indexes = FieldIndexes::build(&definitions, records.iter());
+        let news = cmp("category", FilterOp::Eq, json!("news"));
+
+        indexes.insert("a", &record("a", json!({"category": "blog"})).metadata);
+        indexes.remove("c");
+        assert!(indexes.candidates(&news).unwrap().is_empty());
+
+        let stats = indexes.stats();
+        assert_eq!(stats[0].field, "category");
+        assert_eq!(stats[0].cardinality, 2); // "blog" and 7
+        assert_eq!(stats[0].indexed_records, 3);
+    }
+}
@@ -8,6 +8,15 @@
 //   field < 10
 //   field <= 10
 //   field CONTAINS 'substring'
+//   field IN ('a', 'b')   (or ['a', 'b'])
+//   field NOT IN (1, 2)
+//   field ANY IN ('a', 'b')   (array field shares an element)
+//   field BETWEEN 10 AND 20   (inclusive)
+//   field STARTSWITH 'prefix'
+//   field MATCHES '^regex$'
+//   field EXISTS / field IS NOT NULL
+//   field IS NULL             (missing or null)
+//   geo_within(field, lat, lon, meters)   ({"lat", "lon"} point within radius)
 //   condition AND condition
 //   condition OR condition
 //   NOT condition
@@ -21,27 +30,52 @@
 use crate::store::types::{FilterExpr, FilterOp};
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+/// Filter parse error
+///
+/// Positions are 0-based character offsets into the input string.
+#[derive(Error, Debug, Clone, PartialEq)]
 pub enum ParseError {
-    #[error("Unexpected end of input")]
-    UnexpectedEof,
+    #[error("Unexpected character '{token}' at position {pos}")]
+    UnexpectedToken { token: String, pos: usize },
 
-    #[error("Unexpected token: {0}")]
-    UnexpectedToken(String),
+    #[error("Expected {expected}, got {got} at position {pos}")]
+    Expected {
+        expected: String,
+        got: String,
+        pos: usize,
+    },
 
-    #[error("Expected {expected}, got {got}")]
-    Expected { expected: String, got: String },
+    #[error("Invalid number '{text}' at position {pos}")]
+    InvalidNumber { text: String, pos: usize },
 
-    #[error("Invalid number: {0}")]
-    InvalidNumber(String),
+    #[error("Unclosed string literal starting at position {pos}")]
+    UnclosedString { pos: usize },
 
-    #[error("Unclosed string literal")]
-    UnclosedString,
+    #[error("Invalid regex '{pattern}' at position {pos}: {message}")]
+    InvalidRegex {
+        pattern: String,
+        message: String,
+        pos: usize,
+    },
 
     #[error("Empty filter expression")]
     EmptyExpression,
 }
 
+impl ParseError {
+    /// Character offset of the offending token, if the error has one
+    pub fn position(&self) -> Option<usize> {
+        match self {
+            ParseError::UnexpectedToken { pos, .. }
+            | ParseError::Expected { pos, .. }
+            | ParseError::InvalidNumber { pos, .. }
+            | ParseError::UnclosedString { pos }
+            | ParseError::InvalidRegex { pos, .. } => Some(*pos),
+            ParseError::EmptyExpression => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 enum Token {
     Ident(String),
@@ -62,6 +96,11 @@ enum Token {
     In,         // IN operator (Major Issue #9 fix)
     NotIn,      // NOT IN operator (Major Issue #9 fix)
     StartsWith, // STARTSWITH operator (Major Issue #13 fix)
+    Between,    // BETWEEN low AND high
+    Exists,     // EXISTS
+    Is,         // IS [NOT] NULL
+    Matches,    // MATCHES 'regex'
+    AnyIn,      // ANY IN (...) for array fields
     // Delimiters
     LParen,
     RParen,
@@ -71,9 +110,46 @@ enum Token {
     Eof,
 }
 
+impl Token {
+    /// Human-readable form for error messages
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("'{}'", name),
+            Token::String(s) => format!("string '{}'", s),
+            Token::Number(n) => format!("number {}", n),
+            Token::Eq => "'='".to_string(),
+            Token::Neq => "'!='".to_string(),
+            Token::Gt => "'>'".to_string(),
+            Token::Gte => "'>='".to_string(),
+            Token::Lt => "'<'".to_string(),
+            Token::Lte => "'<='".to_string(),
+            Token::And => "AND".to_string(),
+            Token::Or => "OR".to_string(),
+            Token::Not => "NOT".to_string(),
+            Token::Contains => "CONTAINS".to_string(),
+            Token::In => "IN".to_string(),
+            Token::NotIn => "NOT IN".to_string(),
+            Token::StartsWith => "STARTSWITH".to_string(),
+            Token::Between => "BETWEEN".to_string(),
+            Token::Exists => "EXISTS".to_string(),
+            Token::Is => "IS".to_string(),
+            Token::Matches => "MATCHES".to_string(),
+            Token::AnyIn => "ANY IN".to_string(),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::LBracket => "'['".to_string(),
+            Token::RBracket => "']'".to_string(),
+            Token::Comma => "','".to_string(),
+            Token::Eof => "end of input".to_string(),
+        }
+    }
+}
+
 struct Lexer {
     input: Vec<char>,
     pos: usize,
+    /// Start position of the most recently lexed token
+    token_start: usize,
 }
 
 impl Lexer {
@@ -81,6 +157,7 @@ impl Lexer {
         Self {
             input: input.chars().collect(),
             pos: 0,
+            token_start: 0,
         }
     }
 
@@ -131,7 +208,9 @@ impl Lexer {
             }
         }
 
-        Err(ParseError::UnclosedString)
+        Err(ParseError::UnclosedString {
+            pos: self.token_start,
+        })
     }
 
     fn read_ident_or_keyword(&mut self) -> String {
@@ -167,11 +246,30 @@ impl Lexer {
             }
         }
 
-        s.parse().map_err(|_| ParseError::InvalidNumber(s))
+        s.parse().map_err(|_| ParseError::InvalidNumber {
+            text: s,
+            pos: self.token_start,
+        })
+    }
+
+    /// Consume a following `IN` keyword, leaving the position untouched otherwise
+    fn consume_in(&mut self) -> bool {
+        let saved_pos = self.pos;
+        self.skip_whitespace();
+
+        if self.peek().is_some_and(|c| c.is_alphabetic())
+            && self.read_ident_or_keyword().eq_ignore_ascii_case("IN")
+        {
+            return true;
+        }
+
+        self.pos = saved_pos;
+        false
     }
 
     fn next_token(&mut self) -> Result<Token, ParseError> {
         self.skip_whitespace();
+        self.token_start = self.pos;
 
         match self.peek() {
             None => Ok(Token::Eof),
@@ -205,7 +303,10 @@ impl Lexer {
                     self.advance();
                     Ok(Token::Neq)
                 } else {
-                    Err(ParseError::UnexpectedToken("!".to_string()))
+                    Err(ParseError::UnexpectedToken {
+                        token: "!".to_string(),
+                        pos: self.token_start,
+                    })
                 }
             }
             Some('>') => {
@@ -239,23 +340,16 @@ impl Lexer {
                 let ident = self.read_ident_or_keyword();
                 let upper = ident.to_uppercase();
 
-                // Check for "NOT IN" two-word operator (Major Issue #9 fix)
+                // Check for "NOT IN" / "ANY IN" two-word operators (Major Issue #9 fix)
                 if upper == "NOT" {
-                    // Save position in case we need to backtrack
-                    let saved_pos = self.pos;
-                    self.skip_whitespace();
-
-                    // Try to read next word
-                    if self.peek().map_or(false, |c| c.is_alphabetic()) {
-                        let next_ident = self.read_ident_or_keyword();
-                        if next_ident.to_uppercase() == "IN" {
-                            return Ok(Token::NotIn);
-                        }
-                    }
-
-                    // Not "NOT IN", restore position and return NOT
-                    self.pos = saved_pos;
-                    return Ok(Token::Not);
+                    return Ok(if self.consume_in() {
+                        Token::NotIn
+                    } else {
+                        Token::Not
+                    });
+                }
+                if upper == "ANY" && self.consume_in() {
+                    return Ok(Token::AnyIn);
                 }
 
                 match upper.as_str() {
@@ -264,10 +358,17 @@ impl Lexer {
                     "CONTAINS" => Ok(Token::Contains),
                     "IN" => Ok(Token::In), // Major Issue #9 fix
                     "STARTSWITH" => Ok(Token::StartsWith), // Major Issue #13 fix
+                    "BETWEEN" => Ok(Token::Between),
+                    "EXISTS" => Ok(Token::Exists),
+                    "IS" => Ok(Token::Is),
+                    "MATCHES" => Ok(Token::Matches),
                     _ => Ok(Token::Ident(ident)),
                 }
             }
-            Some(ch) => Err(ParseError::UnexpectedToken(ch.to_string())),
+            Some(ch) => Err(ParseError::UnexpectedToken {
+                token: ch.to_string(),
+                pos: self.token_start,
+            }),
         }
     }
 }
@@ -294,10 +395,16 @@ impl Parser {
             self.advance()?;
             Ok(())
         } else {
-            Err(ParseError::Expected {
-                expected: format!("{:?}", expected),
-                got: format!("{:?}", self.current),
-            })
+            Err(self.error(&expected.describe()))
+        }
+    }
+
+    /// Build an "expected X" error pointing at the current token
+    fn error(&self, expected: &str) -> ParseError {
+        ParseError::Expected {
+            expected: expected.to_string(),
+            got: self.current.describe(),
+            pos: self.lexer.token_start,
         }
     }
 
@@ -306,14 +413,19 @@ impl Parser {
     // or_expr  := and_expr ( OR and_expr )*
     // and_expr := not_expr ( AND not_expr )*
     // not_expr := NOT not_expr | primary
-    // primary  := ( expr ) | comparison
+    // primary  := ( expr ) | geo_within | comparison
     // comparison := field op value
+    // geo_within := GEO_WITHIN ( field , lat , lon , meters )
 
     pub fn parse(&mut self) -> Result<FilterExpr, ParseError> {
         if self.current == Token::Eof {
             return Err(ParseError::EmptyExpression);
         }
-        self.parse_or()
+        let expr = self.parse_or()?;
+        if self.current != Token::Eof {
+            return Err(self.error("AND, OR or end of input"));
+        }
+        Ok(expr)
     }
 
     fn parse_or(&mut self) -> Result<FilterExpr, ParseError> {
@@ -368,13 +480,11 @@ impl Parser {
                 self.advance()?;
                 name
             }
-            _ => {
-                return Err(ParseError::Expected {
-                    expected: "field name".to_string(),
-                    got: format!("{:?}", self.current),
-                })
-            }
+            _ => return Err(self.error("field name")),
         };
+        if self.current == Token::LParen && field.eq_ignore_ascii_case("geo_within") {
+            return self.parse_geo_within();
+        }
 
         let op = match &self.current {
             Token::Eq => FilterOp::Eq,
@@ -387,130 +497,165 @@ impl Parser {
             Token::In => FilterOp::In,       // Major Issue #9 fix
             Token::NotIn => FilterOp::NotIn, // Major Issue #9 fix
             Token::StartsWith => FilterOp::StartsWith, // Major Issue #13 fix
+            Token::Between => FilterOp::Between,
+            Token::Exists => FilterOp::Exists,
+            Token::Is => FilterOp::IsNull,
+            Token::Matches => FilterOp::Matches,
+            Token::AnyIn => FilterOp::AnyIn,
             _ => {
-                return Err(ParseError::Expected {
-                    expected: "operator (=, !=, >, >=, <, <=, CONTAINS, IN, STARTSWITH)"
-                        .to_string(),
-                    got: format!("{:?}", self.current),
-                })
+                return Err(self.error(
+                    "operator (=, !=, >, >=, <, <=, CONTAINS, IN, NOT IN, ANY IN, \
+                     STARTSWITH, BETWEEN, MATCHES, EXISTS, IS NULL)",
+                ))
             }
         };
         self.advance()?;
 
-        // Parse value - for IN/NOT IN, expect array literal
-        let value = if matches!(op, FilterOp::In | FilterOp::NotIn) {
-            // Expect array literal: ['value1', 'value2']
-            if !matches!(self.current, Token::LBracket) {
-                return Err(ParseError::Expected {
-                    expected: "array literal [...]".to_string(),
-                    got: format!("{:?}", self.current),
-                });
-            }
-            self.advance()?; // consume [
-
-            let mut elements = Vec::new();
-            loop {
-                // Check for empty array or end of array
-                if matches!(self.current, Token::RBracket) {
+        let (op, value) = match op {
+            // IN / NOT IN / ANY IN take a list: ('a', 'b') or ['a', 'b']
+            FilterOp::In | FilterOp::NotIn | FilterOp::AnyIn => (op, self.parse_list()?),
+            FilterOp::Between => {
+                let low = self.parse_scalar()?;
+                self.expect(Token::And)?;
+                let high = self.parse_scalar()?;
+                (op, serde_json::json!([low, high]))
+            }
+            FilterOp::Exists => (op, serde_json::Value::Null),
+            // IS NULL / IS NOT NULL
+            FilterOp::IsNull => {
+                let negated = self.current == Token::Not;
+                if negated {
                     self.advance()?;
-                    break;
                 }
-
-                // Parse array element
-                let elem = match &self.current {
-                    Token::String(s) => {
-                        let s = s.clone();
-                        self.advance()?;
-                        serde_json::json!(s)
-                    }
-                    Token::Number(n) => {
-                        let n = *n;
-                        self.advance()?;
-                        if n.fract() == 0.0 && n.abs() < (i64::MAX as f64) {
-                            serde_json::json!(n as i64)
-                        } else {
-                            serde_json::json!(n)
-                        }
-                    }
-                    Token::Ident(s) => {
-                        let s = s.clone();
-                        self.advance()?;
-                        match s.to_lowercase().as_str() {
-                            "true" => serde_json::json!(true),
-                            "false" => serde_json::json!(false),
-                            "null" => serde_json::json!(null),
-                            _ => serde_json::json!(s),
-                        }
-                    }
-                    _ => {
-                        return Err(ParseError::Expected {
-                            expected: "array element (string, number, or identifier)".to_string(),
-                            got: format!("{:?}", self.current),
-                        })
-                    }
-                };
-                elements.push(elem);
-
-                // Check for comma or end
                 match &self.current {
-                    Token::Comma => {
-                        self.advance()?;
-                        // Continue to next element
-                    }
-                    Token::RBracket => {
-                        self.advance()?;
-                        break;
-                    }
-                    _ => {
-                        return Err(ParseError::Expected {
-                            expected: ", or ]".to_string(),
-                            got: format!("{:?}", self.current),
-                        })
-                    }
+                    Token::Ident(s) if s.eq_ignore_ascii_case("null") => self.advance()?,
+                    _ => return Err(self.error("NULL")),
                 }
+                let op = if negated {
+                    FilterOp::Exists
+                } else {
+                    FilterOp::IsNull
+                };
+                (op, serde_json::Value::Null)
             }
-
-            serde_json::json!(elements)
-        } else {
-            // Regular value parsing for other operators
-            match &self.current {
-                Token::String(s) => {
-                    let s = s.clone();
-                    self.advance()?;
-                    serde_json::json!(s)
-                }
-                Token::Number(n) => {
-                    let n = *n;
-                    self.advance()?;
-                    // Use integer if whole number
-                    if n.fract() == 0.0 && n.abs() < (i64::MAX as f64) {
-                        serde_json::json!(n as i64)
-                    } else {
-                        serde_json::json!(n)
-                    }
-                }
-                Token::Ident(s) => {
-                    let s = s.clone();
-                    self.advance()?;
-                    // Handle boolean literals
-                    match s.to_lowercase().as_str() {
-                        "true" => serde_json::json!(true),
-                        "false" => serde_json::json!(false),
-                        "null" => serde_json::json!(null),
-                        _ => serde_json::json!(s),
-                    }
-                }
-                _ => {
-                    return Err(ParseError::Expected {
-                        expected: "value (string, number, or identifier)".to_string(),
-                        got: format!("{:?}", self.current),
-                    })
+            FilterOp::Matches => {
+                let pos = self.lexer.token_start;
+                let pattern = match &self.current {
+                    Token::String(s) => s.clone(),
+                    _ => return Err(self.error("regex string")),
+                };
+                if let Err(e) = regex::Regex::new(&pattern) {
+                    return Err(ParseError::InvalidRegex {
+                        pattern,
+                        message: e.to_string(),
+                        pos,
+                    });
                 }
+                self.advance()?;
+                (op, serde_json::json!(pattern))
             }
+            _ => (op, self.parse_scalar()?),
         };
 
         Ok(FilterExpr::Cmp { field, op, value })
     }
+
+    /// Parse the arguments of `geo_within(field, lat, lon, meters)`
+    fn parse_geo_within(&mut self) -> Result<FilterExpr, ParseError> {
+        self.expect(Token::LParen)?;
+        let field = match &self.current {
+            Token::Ident(name) | Token::String(name) => name.clone(),
+            _ => return Err(self.error("field name")),
+        };
+        self.advance()?;
+        self.expect(Token::Comma)?;
+        let lat = self.parse_number("latitude between -90 and 90", |n| {
+            (-90.0..=90.0).contains(&n)
+        })?;
+        self.expect(Token::Comma)?;
+        let lon = self.parse_number("longitude between -180 and 180", |n| {
+            (-180.0..=180.0).contains(&n)
+        })?;
+        self.expect(Token::Comma)?;
+        let meters = self.parse_number("radius in meters (>= 0)", |n| n >= 0.0)?;
+        self.expect(Token::RParen)?;
+        Ok(FilterExpr::WithinRadius {
+            field,
+            lat,
+            lon,
+            meters,
+        })
+    }
+
+    /// Parse a number literal that satisfies `valid`
+    fn parse_number(
+        &mut self,
+        expected: &str,
+        valid: impl Fn(f64) -> bool,
+    ) -> Result<f64, ParseError> {
+        match self.current {
+            Token::Number(n) if valid(n) => {
+                self.advance()?;
+                Ok(n)
+            }
+            _ => Err(self.error(expected)),
+        }
+    }
+
+    /// Parse a list literal delimited by (...) or [...]
+    fn parse_list(&mut self) -> Result<serde_json::Value, ParseError> {
+        let close = match self.current {
+            Token::LParen => Token::RParen,
+            Token::LBracket => Token::RBracket,
+            _ => return Err(self.error("list (...) or [...]")),
+        };
+        self.advance()?; // consume opening delimiter
+
+        let mut elements = Vec::new();
+        loop {
+            // Check for empty list or end of list
+            if self.current == close {
+                self.advance()?;
+                break;
+            }
+
+            elements.push(self.parse_scalar()?);
+
+            // Check for comma or end
+            if self.current == Token::Comma {
+                self.advance()?;
+            } else if self.current == close {
+                self.advance()?;
+                break;
+            } else {
+                return Err(self.error(&format!("',' or {}", close.describe())));
+            }
+        }
+
+        Ok(serde_json::Value::Array(elements))
+    }
+
+    /// Parse a single literal: string, number, or identifier (true/false/null or bare word)
+    fn parse_scalar(&mut self) -> Result<serde_json::Value, ParseError> {
+        let value = match &self.current {
+            Token::String(s) => serde_json::json!(s),
+            // Use integer if whole number
+            Token::Number(n) if n.fract() == 0.0 && n.abs() < (i64::MAX as f64) => {
+                serde_json::json!(*n as i64)
+            }
+            Token::Number(n) => serde_json::json!(n),
+            // Handle boolean literals
+            Token::Ident(s) => match s.to_lowercase().as_str() {
+                "true" => serde_json::json!(true),
+                "false" => serde_json::json!(false),
+                "null" => serde_json::json!(null),
+                _ => serde_json::json!(s),
+            },
+            _ => return Err(self.error("value (string, number, or identifier)")),
+        };
+        self.advance()?;
+        Ok(value)
+    }
 }
 
 /// Parse a filter expression from a SQL-like string
@@ -527,6 +672,124 @@ pub fn parse_filter(input: &str) -> Result<FilterExpr, ParseError> {
     parser.parse()
 }
 
+impl FilterExpr {
+    /// Parse a filter expression from a SQL-like string
+    ///
+    /// ```
+    /// use vecstore::FilterExpr;
+    ///
+    /// let filter = FilterExpr::parse("category = 'tech' AND year IN (2023, 2024)").unwrap();
+    /// ```
+    pub fn parse(input: &str) -> Result<FilterExpr, ParseError> {
+        parse_filter(input)
+    }
+}
+
+/// Formats the filter in the syntax [`parse_filter`] reads
+///
+/// Values the syntax can't express, such as objects, are written as JSON.
+impl std::fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // AND and OR operands are parenthesized when compound
+        fn operand(f: &mut std::fmt::Formatter<'_>, expr: &FilterExpr) -> std::fmt::Result {
+            match expr {
+                FilterExpr::And(_) | FilterExpr::Or(_) => write!(f, "({})", expr),
+                _ => write!(f, "{}", expr),
+            }
+        }
+        fn join(
+            f: &mut std::fmt::Formatter<'_>,
+            exprs: &[FilterExpr],
+            sep: &str,
+        ) -> std::fmt::Result {
+            for (i, expr) in exprs.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(sep)?;
+                }
+                operand(f, expr)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            FilterExpr::And(exprs) => join(f, exprs, " AND "),
+            FilterExpr::Or(exprs) => join(f, exprs, " OR "),
+            FilterExpr::Not(expr) => {
+                f.write_str("NOT ")?;
+                operand(f, expr)
+            }
+            FilterExpr::Cmp { field, op, value } => {
+                let symbol = match op {
+                    FilterOp::Eq => "=",
+                    FilterOp::Neq => "!=",
+                    FilterOp::Gt => ">",
+                    FilterOp::Gte => ">=",
+                    FilterOp::Lt => "<",
+                    FilterOp::Lte => "<=",
+                    FilterOp::Contains => "CONTAINS",
+                    FilterOp::In => "IN",
+                    FilterOp::NotIn => "NOT IN",
+                    FilterOp::StartsWith => "STARTSWITH",
+                    FilterOp::Between => "BETWEEN",
+                    FilterOp::Exists => return write!(f, "{} EXISTS", field),
+                    FilterOp::IsNull => return write!(f, "{} IS NULL", field),
+                    FilterOp::Matches => "MATCHES",
+                    FilterOp::AnyIn => "ANY IN",
+                };
+                write!(f, "{} {} ", field, symbol)?;
+                match (op, value) {
+                    (FilterOp::Between, serde_json::Value::Array(bounds)) if bounds.len() == 2 => {
+                        write_literal(f, &bounds[0])?;
+                        f.write_str(" AND ")?;
+                        write_literal(f, &bounds[1])
+                    }
+                    (
+                        FilterOp::In | FilterOp::NotIn | FilterOp::AnyIn,
+                        serde_json::Value::Array(items),
+                    ) => {
+                        f.write_str("(")?;
+                        for (i, item) in items.iter().enumerate() {
+                            if i > 0 {
+                                f.write_str(", ")?;
+                            }
+                            write_literal(f, item)?;
+                        }
+                        f.write_str(")")
+                    }
+                    _ => write_literal(f, value),
+                }
+            }
+            FilterExpr::WithinRadius {
+                field,
+                lat,
+                lon,
+                meters,
+            } => write!(f, "geo_within({}, {}, {}, {})", field, lat, lon, meters),
+        }
+    }
+}
+
+/// Write a scalar as a filter literal, single-quoting and escaping strings
+fn write_literal(f: &mut std::fmt::Formatter<'_>, value: &serde_json::Value) -> std::fmt::Result {
+    match value {
+        serde_json::Value::String(s) => {
+            f.write_str("'")?;
+            for ch in s.chars() {
+                match ch {
+                    '\\' => f.write_str("\\\\")?,
+                    '\'' => f.write_str("\\'")?,
+                    '\n' => f.write_str("\\n")?,
+                    '\t' => f.write_str("\\t")?,
+                    '\r' => f.write_str("\\r")?,
+                    _ => write!(f, "{}", ch)?,
+                }
+            }
+            f.write_str("'")
+        }
+        other => write!(f, "{}", other),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -664,4 +927,250 @@ mod tests {
     fn test_unclosed_string_fails() {
         assert!(parse_filter("name = 'Alice").is_err());
     }
+
+    #[test]
+    fn test_in_with_parentheses() {
+        let parens = parse_filter("tag IN ('a', 'b')").unwrap();
+        let brackets = parse_filter("tag IN ['a', 'b']").unwrap();
+        assert_eq!(parens, brackets);
+
+        let filter = parse_filter("year NOT IN (2020, 2021)").unwrap();
+        assert_eq!(
+            filter,
+            FilterExpr::Cmp {
+                field: "year".into(),
+                op: FilterOp::NotIn,
+                value: serde_json::json!([2020, 2021]),
+            }
+        );
+
+        // Delimiters must match
+        assert!(parse_filter("tag IN ('a', 'b']").is_err());
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let err = parse_filter("age > 18 AND = 5").unwrap_err();
+        assert_eq!(err.position(), Some(13));
+        assert!(err.to_string().contains("field name"));
+        assert!(err.to_string().contains("position 13"));
+
+        let err = parse_filter("age ~ 18").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::UnexpectedToken {
+                token: "~".into(),
+                pos: 4
+            }
+        );
+
+        let err = parse_filter("name = 'Alice").unwrap_err();
+        assert_eq!(err, ParseError::UnclosedString { pos: 7 });
+
+        let err = parse_filter("(a = 1").unwrap_err();
+        assert_eq!(err.position(), Some(6));
+        assert!(err.to_string().contains("end of input"));
+    }
+
+    #[test]
+    fn test_trailing_tokens_rejected() {
+        let err = parse_filter("age > 18 role = 'admin'").unwrap_err();
+        assert_eq!(err.position(), Some(9));
+        assert!(parse_filter("a = 1)").is_err());
+    }
+
+    fn cmp(field: &str, op: FilterOp, value: serde_json::Value) -> FilterExpr {
+        FilterExpr::Cmp {
+            field: field.into(),
+            op,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_between() {
+        assert_eq!(
+            parse_filter("price BETWEEN 10 AND 20.5").unwrap(),
+            cmp("price", FilterOp::Between, serde_json::json!([10, 20.5]))
+        );
+
+        // BETWEEN's AND binds to the range, later ANDs combine conditions
+        let filter = parse_filter("price BETWEEN 1 AND 2 AND tag = 'x'").unwrap();
+        assert_eq!(
+            filter,
+            FilterExpr::And(vec![
+                cmp("price", FilterOp::Between, serde_json::json!([1, 2])),
+                cmp("tag", FilterOp::Eq, serde_json::json!("x")),
+            ])
+        );
+
+        let err = parse_filter("price BETWEEN 1 OR 2").unwrap_err();
+        assert_eq!(err.position(), Some(16));
+    }
+
+    #[test]
+    fn test_exists_and_is_null() {
+        assert_eq!(
+            parse_filter("author EXISTS").unwrap(),
+            cmp("author", FilterOp::Exists, serde_json::Value::Null)
+        );
+        assert_eq!(
+            parse_filter("author is not null").unwrap(),
+            cmp("author", FilterOp::Exists, serde_json::Value::Null)
+        );
+        assert_eq!(
+            parse_filter("author IS NULL").unwrap(),
+            cmp("author", FilterOp::IsNull, serde_json::Value::Null)
+        );
+        assert!(parse_filter("author IS 5").is_err());
+    }
+
+    #[test]
+    fn test_starts_with() {
+        assert_eq!(
+            parse_filter("path STARTSWITH '/docs'").unwrap(),
+            cmp("path", FilterOp::StartsWith, serde_json::json!("/docs"))
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        assert_eq!(
+            parse_filter(r"sku MATCHES '^AB-\\d+$'").unwrap(),
+            cmp("sku", FilterOp::Matches, serde_json::json!(r"^AB-\d+$"))
+        );
+
+        let err = parse_filter("sku MATCHES '(unclosed'").unwrap_err();
+        assert!(matches!(err, ParseError::InvalidRegex { pos: 12, .. }));
+
+        assert!(parse_filter("sku MATCHES 5").is_err());
+    }
+
+    #[test]
+    fn test_any_in() {
+        assert_eq!(
+            parse_filter("tags ANY IN ('rust', 'go')").unwrap(),
+            cmp("tags", FilterOp::AnyIn, serde_json::json!(["rust", "go"]))
+        );
+        // A field named "any" still works
+        assert_eq!(
+            parse_filter("any = 1").unwrap(),
+            cmp("any", FilterOp::Eq, serde_json::json!(1))
+        );
+    }
+
+    #[test]
+    fn test_geo_within() {
+        let within = FilterExpr::WithinRadius {
+            field: "location".into(),
+            lat: 48.1,
+            lon: -11.5,
+            meters: 50000.0,
+        };
+        assert_eq!(
+            parse_filter("geo_within(location, 48.1, -11.5, 50000)").unwrap(),
+            within
+        );
+        // Composes with other conditions
+        assert_eq!(
+            parse_filter("GEO_WITHIN(location, 48.1, -11.5, 50000) AND price < 10").unwrap(),
+            FilterExpr::And(vec![
+                within,
+                cmp("price", FilterOp::Lt, serde_json::json!(10)),
+            ])
+        );
+        // A field named "geo_within" still works
+        assert_eq!(
+            parse_filter("geo_within = 1").unwrap(),
+            cmp("geo_within", FilterOp::Eq, serde_json::json!(1))
+        );
+
+        let err = parse_filter("geo_within(location, 91, 0, 10)").unwrap_err();
+        assert_eq!(err.position(), Some(21));
+        assert!(err.to_string().contains("latitude"));
+        assert!(parse_filter("geo_within(location, 0, 180.5, 10)").is_err());
+        assert!(parse_filter("geo_within(location, 0, 0, -1)").is_err());
+        assert!(parse_filter("geo_within(location, 0, 0)").is_err());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let cases = [
+            (
+                "category = 'tech'",
+                serde_json::json!({"Cmp": {"field": "category", "op": "Eq", "value": "tech"}}),
+            ),
+            (
+                "NOT (score < 0.5 OR tags CONTAINS 'draft')",
+                serde_json::json!({"Not": {"Or": [
+                    {"Cmp": {"field": "score", "op": "Lt", "value": 0.5}},
+                    {"Cmp": {"field": "tags", "op": "Contains", "value": "draft"}}
+                ]}}),
+            ),
+            (
+                "price BETWEEN 5 AND 10 OR sku MATCHES '^A' OR deleted_at IS NULL",
+                serde_json::json!({"Or": [
+                    {"Or": [
+                        {"Cmp": {"field": "price", "op": "Between", "value": [5, 10]}},
+                        {"Cmp": {"field": "sku", "op": "Matches", "value": "^A"}}
+                    ]},
+                    {"Cmp": {"field": "deleted_at", "op": "IsNull", "value": null}}
+                ]}),
+            ),
+            (
+                "tags ANY IN ('a') AND author EXISTS",
+                serde_json::json!({"And": [
+                    {"Cmp": {"field": "tags", "op": "AnyIn", "value": ["a"]}},
+                    {"Cmp": {"field": "author", "op": "Exists", "value": null}}
+                ]}),
+            ),
+            (
+                "a != 1 AND b >= 2 AND c IN ('x', 'y')",
+                serde_json::json!({"And": [
+                    {"And": [
+                        {"Cmp": {"field": "a", "op": "Neq", "value": 1}},
+                        {"Cmp": {"field": "b", "op": "Gte", "value": 2}}
+                    ]},
+                    {"Cmp": {"field": "c", "op": "In", "value": ["x", "y"]}}
+                ]}),
+            ),
+        ];
+
+        for (input, json) in cases {
+            let parsed = FilterExpr::parse(input).unwrap();
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), json, "{}", input);
+
+            let from_json: FilterExpr = serde_json::from_value(json).unwrap();
+            assert_eq!(from_json, parsed, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for input in [
+            "name = 'O\\'Brien' AND age >= 18",
+            "(a = 1 OR b != 2) AND NOT (c < 3 OR d <= 4.5)",
+            "tag IN ('x', 'y') OR tag NOT IN (1, 2) OR tags ANY IN ('z')",
+            "year BETWEEN 2020 AND 2024 AND path STARTSWITH '/docs'",
+            "title CONTAINS 'rust' AND code MATCHES '^[a-z]+\\d$'",
+            "author EXISTS AND editor IS NULL AND active = true",
+            "geo_within(loc, 40.7, -74, 500)",
+        ] {
+            let parsed = parse_filter(input).unwrap();
+            let text = parsed.to_string();
+            assert_eq!(
+                parse_filter(&text).unwrap(),
+                parsed,
+                "{} -> {}",
+                input,
+                text
+            );
+        }
+        assert_eq!(
+            parse_filter("a = 1 AND (b = 2 OR c = 'x')")
+                .unwrap()
+                .to_string(),
+            "a = 1 AND (b = 2 OR c = 'x')"
+        );
+    }
 }
@@ -1,5 +1,31 @@
-use super::types::{FilterExpr, FilterOp, Metadata};
+use super::types::{FilterExpr, FilterOp, Metadata, Query, Record};
+use crate::geospatial::GeoPoint;
+use regex::Regex;
 use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// Compiled `Matches` patterns, so a regex is not recompiled for every record.
+/// Invalid patterns are cached as `None` and never match.
+static REGEX_CACHE: OnceLock<Mutex<HashMap<String, Option<Regex>>>> = OnceLock::new();
+
+/// Cache is cleared once it holds this many patterns
+const REGEX_CACHE_LIMIT: usize = 256;
+
+/// Earth radius `GeoPoint::distance_to` measures with, in meters
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// Whether `record` may appear in `q`'s results
+///
+/// The namespace check is structural and comes first, so the filter can only
+/// narrow results within the query's namespace, never widen them.
+pub(crate) fn admits(q: &Query, record: &Record) -> bool {
+    record.visible_in(q.namespace.as_deref())
+        && q.filter
+            .as_ref()
+            .is_none_or(|filter| evaluate_filter(filter, &record.metadata))
+}
 
 pub fn evaluate_filter(filter: &FilterExpr, metadata: &Metadata) -> bool {
     match filter {
@@ -10,20 +36,57 @@ pub fn evaluate_filter(filter: &FilterExpr, metadata: &Metadata) -> bool {
             let field_value = metadata.fields.get(field);
             match field_value {
                 Some(fv) => evaluate_comparison(fv, op, value),
-                None => false,
+                None => *op == FilterOp::IsNull,
             }
         }
+        FilterExpr::WithinRadius {
+            field,
+            lat,
+            lon,
+            meters,
+        } => metadata
+            .fields
+            .get(field)
+            .and_then(geo_point)
+            .is_some_and(|point| within_radius(&GeoPoint::new(*lat, *lon), &point, *meters)),
+    }
+}
+
+/// Read a `{"lat": .., "lon": ..}` field value; out-of-range coordinates are not a point
+fn geo_point(value: &Value) -> Option<GeoPoint> {
+    let point = GeoPoint::new(as_f64(value.get("lat")?)?, as_f64(value.get("lon")?)?);
+    point.validate().ok().map(|_| point)
+}
+
+fn within_radius(center: &GeoPoint, point: &GeoPoint, meters: f64) -> bool {
+    // Bounding-box pre-check, with some slack for rounding at the edge. Latitude
+    // can differ by at most the radius as an angle.
+    let angle = (meters / EARTH_RADIUS_M).to_degrees();
+    if (point.lat - center.lat).abs() > angle + 1e-9 {
+        return false;
+    }
+    // Longitude, measured the short way round across the antimeridian, by at
+    // most asin(sin r / cos lat), unless the circle takes in a pole
+    if center.lat.abs() + angle < 90.0 {
+        let max_lon = (angle.to_radians().sin() / center.lat.to_radians().cos())
+            .asin()
+            .to_degrees();
+        let lon = (point.lon - center.lon).rem_euclid(360.0);
+        if lon.min(360.0 - lon) > max_lon + 1e-9 {
+            return false;
+        }
     }
+    center.distance_to(point) <= meters
 }
 
 fn evaluate_comparison(field_value: &Value, op: &FilterOp, target: &Value) -> bool {
     match op {
         FilterOp::Eq => values_equal(field_value, target),
         FilterOp::Neq => !values_equal(field_value, target),
-        FilterOp::Gt => compare_numeric(field_value, target, |a, b| a > b),
-        FilterOp::Gte => compare_numeric(field_value, target, |a, b| a >= b),
-        FilterOp::Lt => compare_numeric(field_value, target, |a, b| a < b),
-        FilterOp::Lte => compare_numeric(field_value, target, |a, b| a <= b),
+        FilterOp::Gt => compare_values(field_value, target).is_some_and(|o| o.is_gt()),
+        FilterOp::Gte => compare_values(field_value, target).is_some_and(|o| o.is_ge()),
+        FilterOp::Lt => compare_values(field_value, target).is_some_and(|o| o.is_lt()),
+        FilterOp::Lte => compare_values(field_value, target).is_some_and(|o| o.is_le()),
         FilterOp::Contains => {
             // For strings, check substring; for arrays, check element presence
             match (field_value, target) {
@@ -53,9 +116,50 @@ fn evaluate_comparison(field_value: &Value, op: &FilterOp, target: &Value) -> bo
                 _ => false,
             }
         }
+        FilterOp::Between => match target {
+            Value::Array(bounds) if bounds.len() == 2 => {
+                compare_values(field_value, &bounds[0]).is_some_and(|o| o.is_ge())
+                    && compare_values(field_value, &bounds[1]).is_some_and(|o| o.is_le())
+            }
+            _ => false,
+        },
+        FilterOp::Exists => !field_value.is_null(),
+        FilterOp::IsNull => field_value.is_null(),
+        FilterOp::Matches => match (field_value, target) {
+            (Value::String(s), Value::String(pattern)) => regex_matches(pattern, s),
+            _ => false,
+        },
+        FilterOp::AnyIn => {
+            // Scalar fields behave like a one-element array
+            let candidates = match target {
+                Value::Array(arr) => arr,
+                _ => return false,
+            };
+            match field_value {
+                Value::Array(items) => items
+                    .iter()
+                    .any(|item| candidates.iter().any(|c| values_equal(item, c))),
+                scalar => candidates.iter().any(|c| values_equal(scalar, c)),
+            }
+        }
     }
 }
 
+fn regex_matches(pattern: &str, text: &str) -> bool {
+    let regex = {
+        let cache = REGEX_CACHE.get_or_init(Default::default);
+        let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.len() >= REGEX_CACHE_LIMIT && !cache.contains_key(pattern) {
+            cache.clear();
+        }
+        cache
+            .entry(pattern.to_string())
+            .or_insert_with(|| Regex::new(pattern).ok())
+            .clone()
+    };
+    regex.is_some_and(|re| re.is_match(text))
+}
+
 fn values_equal(a: &Value, b: &Value) -> bool {
     // Direct equality
     if a == b {
@@ -70,17 +174,19 @@ fn values_equal(a: &Value, b: &Value) -> bool {
     false
 }
 
-fn compare_numeric<F>(a: &Value, b: &Value, cmp: F) -> bool
-where
-    F: Fn(f64, f64) -> bool,
-{
-    match (as_f64(a), as_f64(b)) {
-        (Some(a_num), Some(b_num)) => cmp(a_num, b_num),
-        _ => false,
+/// Order two values: numerically when both are numbers (or numeric strings),
+/// lexically when both are strings, otherwise not comparable
+fn compare_values(a: &Value, b: &Value) -> Option<Ordering> {
+    if let (Some(a_num), Some(b_num)) = (as_f64(a), as_f64(b)) {
+        return a_num.partial_cmp(&b_num);
+    }
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
     }
 }
 
-fn as_f64(v: &Value) -> Option<f64> {
+pub(super) fn as_f64(v: &Value) -> Option<f64> {
     match v {
         Value::Number(n) => n.as_f64(),
         Value::String(s) => s.parse::<f64>().ok(),
@@ -91,7 +197,7 @@ fn as_f64(v: &Value) -> Option<f64> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use serde_json::json;
 
     fn make_metadata(fields: Vec<(&str, Value)>) -> Metadata {
         let mut map = HashMap::new();
@@ -241,4 +347,331 @@ mod tests {
         ]);
         assert!(evaluate_filter(&filter, &meta));
     }
+
+    fn check(fields: Vec<(&str, Value)>, field: &str, op: FilterOp, value: Value) -> bool {
+        let filter = FilterExpr::Cmp {
+            field: field.into(),
+            op,
+            value,
+        };
+        evaluate_filter(&filter, &make_metadata(fields))
+    }
+
+    #[test]
+    fn test_comparison_coercion() {
+        // Numbers compare numerically, including numeric strings
+        assert!(check(vec![("n", json!(10))], "n", FilterOp::Gt, json!(9)));
+        assert!(check(vec![("n", json!("10"))], "n", FilterOp::Gt, json!(9)));
+        // Strings compare lexically
+        assert!(check(
+            vec![("d", json!("2024-03-01"))],
+            "d",
+            FilterOp::Gte,
+            json!("2024-01-15")
+        ));
+        assert!(check(
+            vec![("s", json!("apple"))],
+            "s",
+            FilterOp::Lt,
+            json!("banana")
+        ));
+        // Mismatched types never match, in either direction
+        assert!(!check(
+            vec![("s", json!("apple"))],
+            "s",
+            FilterOp::Gt,
+            json!(1)
+        ));
+        assert!(!check(
+            vec![("s", json!("apple"))],
+            "s",
+            FilterOp::Lte,
+            json!(1)
+        ));
+        assert!(!check(
+            vec![("b", json!(true))],
+            "b",
+            FilterOp::Gte,
+            json!(0)
+        ));
+    }
+
+    #[test]
+    fn test_between() {
+        let range = json!([10, 20]);
+        assert!(check(
+            vec![("p", json!(10))],
+            "p",
+            FilterOp::Between,
+            range.clone()
+        ));
+        assert!(check(
+            vec![("p", json!(15.5))],
+            "p",
+            FilterOp::Between,
+            range.clone()
+        ));
+        assert!(check(
+            vec![("p", json!(20))],
+            "p",
+            FilterOp::Between,
+            range.clone()
+        ));
+        assert!(!check(
+            vec![("p", json!(9.99))],
+            "p",
+            FilterOp::Between,
+            range.clone()
+        ));
+        assert!(!check(
+            vec![("p", json!(21))],
+            "p",
+            FilterOp::Between,
+            range.clone()
+        ));
+        assert!(!check(
+            vec![("p", json!("cheap"))],
+            "p",
+            FilterOp::Between,
+            range.clone()
+        ));
+        assert!(!check(vec![], "p", FilterOp::Between, range));
+
+        // String ranges (e.g. ISO dates) compare lexically
+        assert!(check(
+            vec![("d", json!("2024-06-01"))],
+            "d",
+            FilterOp::Between,
+            json!(["2024-01-01", "2024-12-31"])
+        ));
+
+        // Malformed bounds never match
+        assert!(!check(
+            vec![("p", json!(5))],
+            "p",
+            FilterOp::Between,
+            json!([1])
+        ));
+        assert!(!check(
+            vec![("p", json!(5))],
+            "p",
+            FilterOp::Between,
+            json!(5)
+        ));
+    }
+
+    #[test]
+    fn test_exists_and_is_null() {
+        assert!(check(
+            vec![("a", json!("x"))],
+            "a",
+            FilterOp::Exists,
+            Value::Null
+        ));
+        assert!(check(
+            vec![("a", json!(0))],
+            "a",
+            FilterOp::Exists,
+            Value::Null
+        ));
+        assert!(!check(
+            vec![("a", Value::Null)],
+            "a",
+            FilterOp::Exists,
+            Value::Null
+        ));
+        assert!(!check(vec![], "a", FilterOp::Exists, Value::Null));
+
+        assert!(check(vec![], "a", FilterOp::IsNull, Value::Null));
+        assert!(check(
+            vec![("a", Value::Null)],
+            "a",
+            FilterOp::IsNull,
+            Value::Null
+        ));
+        assert!(!check(
+            vec![("a", json!(""))],
+            "a",
+            FilterOp::IsNull,
+            Value::Null
+        ));
+    }
+
+    #[test]
+    fn test_starts_with() {
+        let fields = || vec![("path", json!("/docs/intro"))];
+        assert!(check(
+            fields(),
+            "path",
+            FilterOp::StartsWith,
+            json!("/docs")
+        ));
+        assert!(!check(
+            fields(),
+            "path",
+            FilterOp::StartsWith,
+            json!("/blog")
+        ));
+        assert!(!check(
+            vec![("path", json!(42))],
+            "path",
+            FilterOp::StartsWith,
+            json!("4")
+        ));
+    }
+
+    #[test]
+    fn test_matches() {
+        let fields = || vec![("sku", json!("AB-1234"))];
+        assert!(check(
+            fields(),
+            "sku",
+            FilterOp::Matches,
+            json!(r"^AB-\d+$")
+        ));
+        assert!(!check(fields(), "sku", FilterOp::Matches, json!(r"^CD-")));
+        // Cached pattern gives the same answer on reuse
+        assert!(check(
+            fields(),
+            "sku",
+            FilterOp::Matches,
+            json!(r"^AB-\d+$")
+        ));
+        // Invalid patterns and non-string fields are false, not errors
+        assert!(!check(
+            fields(),
+            "sku",
+            FilterOp::Matches,
+            json!("(unclosed")
+        ));
+        assert!(!check(
+            vec![("sku", json!(1234))],
+            "sku",
+            FilterOp::Matches,
+            json!("1")
+        ));
+    }
+
+    #[test]
+    fn test_any_in() {
+        let tags = || vec![("tags", json!(["rust", "db", 3]))];
+        assert!(check(tags(), "tags", FilterOp::AnyIn, json!(["go", "db"])));
+        assert!(check(tags(), "tags", FilterOp::AnyIn, json!([3.0])));
+        assert!(!check(tags(), "tags", FilterOp::AnyIn, json!(["go", "c"])));
+        assert!(!check(tags(), "tags", FilterOp::AnyIn, json!([])));
+        assert!(!check(tags(), "tags", FilterOp::AnyIn, json!("rust")));
+        // Scalar fields act as a one-element array
+        assert!(check(
+            vec![("tags", json!("rust"))],
+            "tags",
+            FilterOp::AnyIn,
+            json!(["rust"])
+        ));
+        assert!(!check(vec![], "tags", FilterOp::AnyIn, json!(["rust"])));
+    }
+
+    fn within(lat: f64, lon: f64, meters: f64) -> FilterExpr {
+        FilterExpr::WithinRadius {
+            field: "location".into(),
+            lat,
+            lon,
+            meters,
+        }
+    }
+
+    fn located(lat: f64, lon: f64) -> Metadata {
+        make_metadata(vec![("location", json!({"lat": lat, "lon": lon}))])
+    }
+
+    #[test]
+    fn test_within_radius() {
+        // Munich to Augsburg is about 57 km
+        let munich = within(48.137, 11.575, 60_000.0);
+        assert!(evaluate_filter(&munich, &located(48.371, 10.898)));
+        assert!(!evaluate_filter(
+            &within(48.137, 11.575, 50_000.0),
+            &located(48.371, 10.898)
+        ));
+        // The exact center, a zero radius, and numeric strings
+        assert!(evaluate_filter(
+            &within(48.137, 11.575, 0.0),
+            &located(48.137, 11.575)
+        ));
+        let strings = make_metadata(vec![("location", json!({"lat": "48.2", "lon": "11.6"}))]);
+        assert!(evaluate_filter(&munich, &strings));
+
+        // Missing, malformed and out-of-range points never match
+        for fields in [
+            vec![],
+            vec![("location", json!("48.1,11.5"))],
+            vec![("location", json!({"lat": 48.1}))],
+            vec![("location", json!({"lat": 148.1, "lon": 11.5}))],
+        ] {
+            assert!(!evaluate_filter(&munich, &make_metadata(fields)));
+        }
+
+        // Composes with other operators
+        let mut metadata = located(48.2, 11.6);
+        metadata.fields.insert("price".into(), json!(5));
+        let cheap_nearby = FilterExpr::And(vec![
+            munich.clone(),
+            FilterExpr::Cmp {
+                field: "price".into(),
+                op: FilterOp::Lt,
+                value: json!(10),
+            },
+        ]);
+        assert!(evaluate_filter(&cheap_nearby, &metadata));
+        assert!(!evaluate_filter(
+            &FilterExpr::Not(Box::new(munich)),
+            &metadata
+        ));
+    }
+
+    #[test]
+    fn test_within_radius_antimeridian() {
+        // 0.2 degrees of longitude at the equator is about 22 km
+        let fiji = within(0.0, 179.9, 25_000.0);
+        assert!(evaluate_filter(&fiji, &located(0.0, -179.9)));
+        assert!(evaluate_filter(&fiji, &located(0.0, 180.0)));
+        assert!(!evaluate_filter(&fiji, &located(0.0, -179.5)));
+        assert!(evaluate_filter(
+            &within(10.0, -179.95, 25_000.0),
+            &located(10.0, 179.95)
+        ));
+        // Not fooled into matching the far side of the globe
+        assert!(!evaluate_filter(&fiji, &located(0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_within_radius_poles() {
+        // Points on opposite meridians near the pole are close together
+        let arctic = within(89.9, 0.0, 25_000.0);
+        assert!(evaluate_filter(&arctic, &located(89.9, 180.0)));
+        assert!(evaluate_filter(&arctic, &located(90.0, 0.0)));
+        assert!(!evaluate_filter(&arctic, &located(89.5, 180.0)));
+        assert!(evaluate_filter(
+            &within(-90.0, 0.0, 12_000.0),
+            &located(-89.9, 123.0)
+        ));
+        // A circle just short of the pole still checks longitude correctly
+        let near_pole = within(85.0, 0.0, 400_000.0);
+        assert!(evaluate_filter(&near_pole, &located(85.0, 40.0)));
+        assert!(!evaluate_filter(&near_pole, &located(85.0, 60.0)));
+    }
+
+    #[test]
+    fn test_new_ops_serde() {
+        for (op, name) in [
+            (FilterOp::Between, "Between"),
+            (FilterOp::Exists, "Exists"),
+            (FilterOp::IsNull, "IsNull"),
+            (FilterOp::Matches, "Matches"),
+            (FilterOp::AnyIn, "AnyIn"),
+        ] {
+            let json = serde_json::to_value(&op).unwrap();
+            assert_eq!(json, Value::String(name.into()));
+            assert_eq!(serde_json::from_value::<FilterOp>(json).unwrap(), op);
+        }
+    }
 }
@@ -1,14 +1,173 @@
-use super::types::{Distance, Id};
+use super::exact::{raw_distance, score};
+use super::types::{Distance, HnswConfig, Id};
+use crate::quantization::ScalarQuantizer8;
+use crate::simd::{dot_product_simd, hamming_distance_simd, jaccard_distance_simd, magnitude_simd};
 use anyhow::{anyhow, Result};
+use hnsw_rs::prelude::Distance as HnswDistance;
 use hnsw_rs::prelude::*;
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Candidate list size for searches that don't set `ef_search`; raised to
+/// the number of results wanted
+pub const DEFAULT_EF_SEARCH: usize = 30;
+
+// anndists only ships Hamming/Jaccard for integer element types, so these
+// wrap the SIMD kernels to index f32 vectors directly
+
+/// `1 - dot` over unit-length vectors, clamped at 0
+///
+/// anndists' `DistDot` panics when rounding pushes the dot product of two
+/// unit vectors past 1; the store rejects longer vectors on insert.
+#[derive(Default, Clone, Copy)]
+struct DistUnitDot;
+
+impl HnswDistance<f32> for DistUnitDot {
+    fn eval(&self, va: &[f32], vb: &[f32]) -> f32 {
+        (1.0 - dot_product_simd(va, vb)).max(0.0)
+    }
+}
+
+/// Hamming distance over f32 vectors read as bits (component > 0.5)
+#[derive(Default, Clone, Copy)]
+struct DistBinaryHamming;
+
+impl HnswDistance<f32> for DistBinaryHamming {
+    fn eval(&self, va: &[f32], vb: &[f32]) -> f32 {
+        hamming_distance_simd(va, vb)
+    }
+}
+
+/// Jaccard distance over f32 vectors read as sets (component > 0.0)
+#[derive(Default, Clone, Copy)]
+struct DistSetJaccard;
+
+impl HnswDistance<f32> for DistSetJaccard {
+    fn eval(&self, va: &[f32], vb: &[f32]) -> f32 {
+        jaccard_distance_simd(va, vb)
+    }
+}
+
+/// `distance` between int8 codes, computed on the dequantized vectors
+#[derive(Default, Clone)]
+struct DistInt8 {
+    distance: Distance,
+    min_values: Vec<f32>,
+    steps: Vec<f32>,
+}
+
+impl DistInt8 {
+    fn new(distance: Distance, quantizer: &ScalarQuantizer8) -> Self {
+        Self {
+            distance,
+            min_values: quantizer.min_values.clone(),
+            steps: quantizer.ranges.iter().map(|r| r / 255.0).collect(),
+        }
+    }
+
+    fn dequantize(&self, code: &[u8]) -> Vec<f32> {
+        code.iter()
+            .zip(self.min_values.iter().zip(&self.steps))
+            .map(|(&q, (min, step))| min + q as f32 * step)
+            .collect()
+    }
+}
+
+impl HnswDistance<u8> for DistInt8 {
+    fn eval(&self, va: &[u8], vb: &[u8]) -> f32 {
+        let a = self.dequantize(va);
+        let b = self.dequantize(vb);
+        raw_distance(self.distance, &a, magnitude_simd(&a), &b)
+    }
+}
+
 // Enum to hold different HNSW instances for different distance metrics
 enum HnswInstance {
     Cosine(Hnsw<'static, f32, DistCosine>),
     Euclidean(Hnsw<'static, f32, DistL2>),
-    DotProduct(Hnsw<'static, f32, DistDot>),
+    DotProduct(Hnsw<'static, f32, DistUnitDot>),
+    Manhattan(Hnsw<'static, f32, DistL1>),
+    Hamming(Hnsw<'static, f32, DistBinaryHamming>),
+    Jaccard(Hnsw<'static, f32, DistSetJaccard>),
+    /// Any metric over int8 codes
+    Int8(Hnsw<'static, u8, DistInt8>),
+}
+
+impl HnswInstance {
+    fn build(
+        distance: Distance,
+        config: &HnswConfig,
+        quantizer: Option<&ScalarQuantizer8>,
+    ) -> Result<Self> {
+        let HnswConfig {
+            m,
+            ef_construction,
+            max_elements,
+            max_layer,
+        } = *config;
+
+        // Quantizers are only calibrated for stores whose metric built an f32 index
+        if let Some(quantizer) = quantizer {
+            return Ok(HnswInstance::Int8(Hnsw::<u8, DistInt8>::new(
+                m,
+                max_elements,
+                max_layer,
+                ef_construction,
+                DistInt8::new(distance, quantizer),
+            )));
+        }
+
+        match distance {
+            Distance::Cosine => Ok(HnswInstance::Cosine(Hnsw::<f32, DistCosine>::new(
+                m,
+                max_elements,
+                max_layer,
+                ef_construction,
+                DistCosine,
+            ))),
+            Distance::Euclidean => Ok(HnswInstance::Euclidean(Hnsw::<f32, DistL2>::new(
+                m,
+                max_elements,
+                max_layer,
+                ef_construction,
+                DistL2,
+            ))),
+            Distance::DotProduct => Ok(HnswInstance::DotProduct(Hnsw::<f32, DistUnitDot>::new(
+                m,
+                max_elements,
+                max_layer,
+                ef_construction,
+                DistUnitDot,
+            ))),
+            Distance::Manhattan => Ok(HnswInstance::Manhattan(Hnsw::<f32, DistL1>::new(
+                m,
+                max_elements,
+                max_layer,
+                ef_construction,
+                DistL1,
+            ))),
+            Distance::Hamming => Ok(HnswInstance::Hamming(Hnsw::<f32, DistBinaryHamming>::new(
+                m,
+                max_elements,
+                max_layer,
+                ef_construction,
+                DistBinaryHamming,
+            ))),
+            Distance::Jaccard => Ok(HnswInstance::Jaccard(Hnsw::<f32, DistSetJaccard>::new(
+                m,
+                max_elements,
+                max_layer,
+                ef_construction,
+                DistSetJaccard,
+            ))),
+            _ => Err(anyhow!(
+                "Distance metric {:?} is not yet supported by the HNSW backend. \
+                 Supported metrics: Cosine, Euclidean, DotProduct, Manhattan, Hamming, Jaccard. \
+                 See https://github.com/yourusername/vecstore/issues for updates.",
+                distance
+            )),
+        }
+    }
 }
 
 pub struct HnswBackend {
@@ -18,33 +177,17 @@ pub struct HnswBackend {
     next_idx: usize,
     dimension: usize,
     distance: Distance,
+    /// Parameters the graph was built with
+    config: HnswConfig,
+    /// Points inserted into the graph, including replaced and removed ones
+    inserted: usize,
+    /// Codebook for an int8 graph
+    quantizer: Option<ScalarQuantizer8>,
 }
 
 impl HnswBackend {
-    pub fn new(dimension: usize, distance: Distance) -> Result<Self> {
-        let hnsw = match distance {
-            Distance::Cosine => HnswInstance::Cosine(Hnsw::<f32, DistCosine>::new(
-                16,      // max_nb_connection
-                100_000, // max_elements
-                16,      // max_layer
-                200,     // ef_construction
-                DistCosine,
-            )),
-            Distance::Euclidean => {
-                HnswInstance::Euclidean(Hnsw::<f32, DistL2>::new(16, 100_000, 16, 200, DistL2))
-            }
-            Distance::DotProduct => {
-                HnswInstance::DotProduct(Hnsw::<f32, DistDot>::new(16, 100_000, 16, 200, DistDot))
-            }
-            _ => {
-                return Err(anyhow!(
-                    "Distance metric {:?} is not yet supported by the HNSW backend. \
-                     Supported metrics: Cosine, Euclidean, DotProduct. \
-                     See https://github.com/yourusername/vecstore/issues for updates.",
-                    distance
-                ))
-            }
-        };
+    pub fn new(dimension: usize, distance: Distance, config: &HnswConfig) -> Result<Self> {
+        let hnsw = HnswInstance::build(distance, config, None)?;
 
         Ok(Self {
             hnsw,
@@ -53,6 +196,9 @@ impl HnswBackend {
             next_idx: 0,
             dimension,
             distance,
+            config: *config,
+            inserted: 0,
+            quantizer: None,
         })
     }
 
@@ -78,10 +224,18 @@ impl HnswBackend {
             HnswInstance::Cosine(h) => h.insert((vector, idx)),
             HnswInstance::Euclidean(h) => h.insert((vector, idx)),
             HnswInstance::DotProduct(h) => h.insert((vector, idx)),
+            HnswInstance::Manhattan(h) => h.insert((vector, idx)),
+            HnswInstance::Hamming(h) => h.insert((vector, idx)),
+            HnswInstance::Jaccard(h) => h.insert((vector, idx)),
+            HnswInstance::Int8(h) => {
+                let code = encode(self.quantizer.as_ref(), vector)?;
+                h.insert((code.as_slice(), idx))
+            }
         }
 
         self.id_to_idx.insert(id.clone(), idx);
         self.idx_to_id.insert(idx, id);
+        self.inserted += 1;
 
         Ok(())
     }
@@ -102,9 +256,16 @@ impl HnswBackend {
         }
 
         let neighbors = match &self.hnsw {
-            HnswInstance::Cosine(h) => h.search(vector, k, 30),
-            HnswInstance::Euclidean(h) => h.search(vector, k, 30),
-            HnswInstance::DotProduct(h) => h.search(vector, k, 30),
+            HnswInstance::Cosine(h) => h.search(vector, k, DEFAULT_EF_SEARCH),
+            HnswInstance::Euclidean(h) => h.search(vector, k, DEFAULT_EF_SEARCH),
+            HnswInstance::DotProduct(h) => h.search(vector, k, DEFAULT_EF_SEARCH),
+            HnswInstance::Manhattan(h) => h.search(vector, k, DEFAULT_EF_SEARCH),
+            HnswInstance::Hamming(h) => h.search(vector, k, DEFAULT_EF_SEARCH),
+            HnswInstance::Jaccard(h) => h.search(vector, k, DEFAULT_EF_SEARCH),
+            HnswInstance::Int8(h) => match encode(self.quantizer.as_ref(), vector) {
+                Ok(code) => h.search(&code, k, DEFAULT_EF_SEARCH),
+                Err(_) => Vec::new(),
+            },
         };
 
         neighbors
@@ -112,17 +273,7 @@ impl HnswBackend {
             .filter_map(|neighbor| {
                 let idx = neighbor.d_id;
                 self.idx_to_id.get(&idx).map(|id| {
-                    let score = match self.distance {
-                        Distance::Cosine | Distance::DotProduct => neighbor.distance,
-                        Distance::Euclidean => {
-                            // For Euclidean, invert so higher score = closer
-                            1.0 / (1.0 + neighbor.distance)
-                        }
-                        _ => {
-                            // This should never happen since we validate distance metric in new()
-                            neighbor.distance
-                        }
-                    };
+                    let score = score(self.distance, neighbor.distance, vector.len());
                     (id.clone(), score)
                 })
             })
@@ -149,6 +300,18 @@ impl HnswBackend {
             HnswInstance::DotProduct(h) => {
                 h.file_dump(parent, file_name)?;
             }
+            HnswInstance::Manhattan(h) => {
+                h.file_dump(parent, file_name)?;
+            }
+            HnswInstance::Hamming(h) => {
+                h.file_dump(parent, file_name)?;
+            }
+            HnswInstance::Jaccard(h) => {
+                h.file_dump(parent, file_name)?;
+            }
+            HnswInstance::Int8(h) => {
+                h.file_dump(parent, file_name)?;
+            }
         }
 
         Ok(())
@@ -168,29 +331,12 @@ impl HnswBackend {
     pub fn restore(
         dimension: usize,
         distance: Distance,
+        config: &HnswConfig,
         id_to_idx: HashMap<Id, usize>,
         idx_to_id: HashMap<usize, Id>,
         next_idx: usize,
     ) -> Result<Self> {
-        let hnsw = match distance {
-            Distance::Cosine => HnswInstance::Cosine(Hnsw::<f32, DistCosine>::new(
-                16, 100_000, 16, 200, DistCosine,
-            )),
-            Distance::Euclidean => {
-                HnswInstance::Euclidean(Hnsw::<f32, DistL2>::new(16, 100_000, 16, 200, DistL2))
-            }
-            Distance::DotProduct => {
-                HnswInstance::DotProduct(Hnsw::<f32, DistDot>::new(16, 100_000, 16, 200, DistDot))
-            }
-            _ => {
-                return Err(anyhow!(
-                    "Distance metric {:?} is not yet supported by the HNSW backend. \
-                     Supported metrics: Cosine, Euclidean, DotProduct. \
-                     See https://github.com/yourusername/vecstore/issues for updates.",
-                    distance
-                ))
-            }
-        };
+        let hnsw = HnswInstance::build(distance, config, None)?;
 
         Ok(Self {
             hnsw,
@@ -199,9 +345,22 @@ impl HnswBackend {
             next_idx,
             dimension,
             distance,
+            config: *config,
+            inserted: 0,
+            quantizer: None,
         })
     }
 
+    /// `max_elements` the graph was built with
+    pub fn capacity(&self) -> usize {
+        self.config.max_elements
+    }
+
+    /// Number of points in the graph, counting stale entries left by updates and removals
+    pub fn inserted_count(&self) -> usize {
+        self.inserted
+    }
+
     pub fn get_next_idx(&self) -> usize {
         self.next_idx
     }
@@ -231,10 +390,33 @@ impl HnswBackend {
         Ok(())
     }
 
-    pub fn optimize(&mut self, _vectors: &[(Id, Vec<f32>)]) -> Result<usize> {
-        // HNSW doesn't need explicit optimization
-        // Return number of vectors in index
-        Ok(self.id_to_idx.len())
+    /// Rebuild the graph from `vectors`, dropping removed and replaced entries
+    ///
+    /// Returns the number of graph entries reclaimed.
+    pub fn optimize(&mut self, vectors: &[(Id, Vec<f32>)]) -> Result<usize> {
+        self.optimize_with_progress(vectors, &mut |_| {})
+    }
+
+    /// [`optimize`](Self::optimize), calling `progress` with the number of
+    /// vectors inserted so far
+    pub fn optimize_with_progress(
+        &mut self,
+        vectors: &[(Id, Vec<f32>)],
+        progress: &mut dyn FnMut(usize),
+    ) -> Result<usize> {
+        let reclaimed = self.inserted.saturating_sub(vectors.len());
+
+        self.hnsw = HnswInstance::build(self.distance, &self.config, self.quantizer.as_ref())?;
+        self.id_to_idx.clear();
+        self.idx_to_id.clear();
+        self.next_idx = 0;
+        self.inserted = 0;
+        for (done, (id, vector)) in vectors.iter().enumerate() {
+            self.insert(id.clone(), vector)?;
+            progress(done + 1);
+        }
+
+        Ok(reclaimed)
     }
 
     pub fn search_with_ef(
@@ -251,6 +433,12 @@ impl HnswBackend {
             HnswInstance::Cosine(h) => h.search(vector, k, ef_search),
             HnswInstance::Euclidean(h) => h.search(vector, k, ef_search),
             HnswInstance::DotProduct(h) => h.search(vector, k, ef_search),
+            HnswInstance::Manhattan(h) => h.search(vector, k, ef_search),
+            HnswInstance::Hamming(h) => h.search(vector, k, ef_search),
+            HnswInstance::Jaccard(h) => h.search(vector, k, ef_search),
+            HnswInstance::Int8(h) => {
+                h.search(&encode(self.quantizer.as_ref(), vector)?, k, ef_search)
+            }
         };
 
         Ok(neighbors
@@ -258,17 +446,7 @@ impl HnswBackend {
             .filter_map(|neighbor| {
                 let idx = neighbor.d_id;
                 self.idx_to_id.get(&idx).map(|id| {
-                    let score = match self.distance {
-                        Distance::Cosine | Distance::DotProduct => neighbor.distance,
-                        Distance::Euclidean => {
-                            // For Euclidean, invert so higher score = closer
-                            1.0 / (1.0 + neighbor.distance)
-                        }
-                        _ => {
-                            // This should never happen since we validate distance metric in new()
-                            neighbor.distance
-                        }
-                    };
+                    let score = score(self.distance, neighbor.distance, vector.len());
                     (id.clone(), score)
                 })
             })
@@ -294,4 +472,33 @@ impl HnswBackend {
     pub fn distance(&self) -> Distance {
         self.distance
     }
+
+    /// Switch the graph to int8 codes (or back to f32 with `None`)
+    ///
+    /// Replaces the graph with an empty one, so call it before inserting.
+    pub fn with_quantizer(mut self, quantizer: Option<ScalarQuantizer8>) -> Result<Self> {
+        self.hnsw = HnswInstance::build(self.distance, &self.config, quantizer.as_ref())?;
+        self.quantizer = quantizer;
+        Ok(self)
+    }
+
+    pub fn is_quantized(&self) -> bool {
+        self.quantizer.is_some()
+    }
+
+    /// Bytes of vector data held by the graph, counting stale entries
+    pub fn vector_bytes(&self) -> usize {
+        let element_size = if self.is_quantized() {
+            1
+        } else {
+            std::mem::size_of::<f32>()
+        };
+        self.inserted * self.dimension * element_size
+    }
+}
+
+fn encode(quantizer: Option<&ScalarQuantizer8>, vector: &[f32]) -> Result<Vec<u8>> {
+    quantizer
+        .ok_or_else(|| anyhow!("Index is not quantized"))?
+        .encode(vector)
 }
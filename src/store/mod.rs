@@ -1,6 +1,15 @@
 pub mod advanced_filters;
+#[cfg(not(target_arch = "wasm32"))]
+mod backup;
+mod checkpoint;
+mod concurrent;
+mod dedupe;
 mod disk;
 pub mod disk_hnsw;
+mod encryption;
+mod events;
+mod exact;
+mod field_index;
 mod filter_parser;
 pub mod filters; // Public for WASM module
 
@@ -24,18 +33,47 @@ pub type VectorBackend = hnsw_backend::HnswBackend;
 pub type VectorBackend = wasm_backend::WasmVectorBackend;
 
 pub mod hybrid;
+mod profile;
 pub mod quantization;
+mod quantized;
+mod recall;
 mod types;
+#[cfg(not(target_arch = "wasm32"))]
+mod validate;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use backup::{BackupCompression, BackupKind, BackupManifest, BackupSegment};
+pub use checkpoint::SaveSnapshot;
+pub use concurrent::{VecStoreConcurrent, DEFAULT_INGEST_CHUNK_SIZE};
+pub use dedupe::{DuplicateKeep, NearDuplicateGroup, DEDUPE_NEIGHBORS};
+#[cfg(feature = "encryption")]
+pub use encryption::EncryptionKey;
+pub use events::{EventHook, StoreEvent, SubscriptionId};
+pub use field_index::{FieldIndexStats, FieldIndexType};
 pub use filter_parser::{parse_filter, ParseError as FilterParseError};
 pub use hybrid::{HybridQuery, TextIndex};
+pub use profile::{
+    ProfiledResults, QueryCancel, QueryInterrupted, QueryProfile, SlowQuery,
+    SLOW_QUERY_LOG_CAPACITY,
+};
 pub use quantization::{PQConfig, PQVectorStore, ProductQuantizer};
+pub use recall::{EfRecall, RecallReport, DEFAULT_RECALL_K, DEFAULT_RECALL_SAMPLES};
 pub use types::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use validate::{CheckStatus, IntegrityCheck, IntegrityReport, RepairReport, MAX_CHECK_DETAILS};
 
+use crate::reranking::QueryReranker;
+use crate::wal::{LogEntry, WalSync, WriteAheadLog};
 use anyhow::{Context, Result};
 use chrono::Utc;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use encryption::Cipher;
+#[cfg(not(feature = "encryption"))]
+use encryption::EncryptionKey;
+use quantized::{full_vector, QuantizedVectors};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 pub struct VecStore {
     root: PathBuf,
@@ -45,12 +83,37 @@ pub struct VecStore {
     text_index: hybrid::TextIndex,
     compaction_config: CompactionConfig,
     config: Config,
+    /// Fraction of searched candidates found deleted (`f64` bits), used to size over-fetching
+    deleted_ratio: AtomicU64,
+    /// Applied to every `query` result set when registered
+    reranker: Option<Arc<dyn QueryReranker>>,
+    /// Int8 codebook (and codes of dropped originals) once calibrated
+    quantized: Option<QuantizedVectors>,
+    /// Log of upserts and removals since the last save, when enabled
+    ///
+    /// Behind a mutex so `save(&self)` can truncate it.
+    wal: Option<Mutex<WriteAheadLog>>,
+    /// Operations replayed from the log on open
+    recovered_operations: usize,
+    /// Operation sequence counter and removal tombstones for incremental backups
+    changes: disk::ChangeState,
+    /// `changes.op_seq` as of the last save, or the open
+    saved_seq: AtomicU64,
+    /// Seals the store files when the store is encrypted at rest
+    cipher: Option<Arc<Cipher>>,
+    /// Postings of the metadata fields listed in `config.field_indexes`
+    field_indexes: field_index::FieldIndexes,
+    /// Hooks called after each change
+    subscribers: events::Subscribers,
+    /// Queries slower than `config.slow_query_threshold_ms`
+    slow_queries: Mutex<profile::SlowQueryLog>,
 }
 
 /// Builder for VecStore with customizable configuration
 pub struct VecStoreBuilder {
     path: PathBuf,
     config: Config,
+    encryption: Option<EncryptionKey>,
 }
 
 impl VecStoreBuilder {
@@ -59,6 +122,7 @@ impl VecStoreBuilder {
         Self {
             path: path.into(),
             config: Config::default(),
+            encryption: None,
         }
     }
 
@@ -77,12 +141,21 @@ impl VecStoreBuilder {
         self
     }
 
+    /// Fix the vector dimension up front
+    ///
+    /// Without it the first insert decides; with it, even the first insert
+    /// is rejected if its length differs.
+    pub fn dimension(mut self, dimension: usize) -> Self {
+        self.config.dimension = Some(dimension);
+        self
+    }
+
     /// Set HNSW M parameter (number of connections per layer)
     ///
     /// Higher values = better recall, more memory usage
     /// Default: 16
     pub fn hnsw_m(mut self, m: usize) -> Self {
-        self.config.hnsw_m = m;
+        self.config.hnsw.m = m;
         self
     }
 
@@ -91,13 +164,136 @@ impl VecStoreBuilder {
     /// Higher values = better quality index, slower construction
     /// Default: 200
     pub fn hnsw_ef_construction(mut self, ef: usize) -> Self {
-        self.config.hnsw_ef_construction = ef;
+        self.config.hnsw.ef_construction = ef;
+        self
+    }
+
+    /// Set all HNSW construction parameters at once
+    ///
+    /// Only applies when the store is created. An existing store keeps the
+    /// parameters it was saved with; use [`VecStore::set_hnsw_config`] to
+    /// change them, which rebuilds the index.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{HnswConfig, VecStore};
+    /// let store = VecStore::builder("./data")
+    ///     .hnsw_config(HnswConfig {
+    ///         m: 32,
+    ///         max_elements: 2_000_000,
+    ///         ..Default::default()
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn hnsw_config(mut self, config: HnswConfig) -> Self {
+        self.config.hnsw = config;
+        self
+    }
+
+    /// Set the checks and normalization applied to vectors
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, VectorPolicy};
+    /// let store = VecStore::builder("./data")
+    ///     .vector_policy(VectorPolicy {
+    ///         normalize: true,
+    ///         reject_nan: true,
+    ///         ..Default::default()
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn vector_policy(mut self, policy: VectorPolicy) -> Self {
+        self.config.vector_policy = policy;
+        self
+    }
+
+    /// Choose how vectors are held in memory
+    ///
+    /// Only takes effect for a new store; an existing store keeps the mode it
+    /// was created with. Int8 quantization starts once
+    /// [`VecStore::calibrate_quantization`] has learned the codebook.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, Quantization};
+    /// let mut store = VecStore::builder("./data")
+    ///     .quantization(Quantization::Int8 { keep_originals: false })
+    ///     .build()?;
+    /// // ... load a representative set of vectors ...
+    /// store.calibrate_quantization()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn quantization(mut self, quantization: Quantization) -> Self {
+        self.config.quantization = quantization;
+        self
+    }
+
+    /// Log upserts and removals to a write-ahead log between saves
+    ///
+    /// Operations logged since the last [`save`](VecStore::save) are replayed
+    /// when the store is reopened after a crash; `save` checkpoints and
+    /// truncates the log. `sync` controls how often the log is fsynced.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, wal::WalSync};
+    /// let store = VecStore::builder("./data")
+    ///     .wal(WalSync::IntervalMs(100))
+    ///     .build()?;
+    /// println!("recovered {} operations", store.recovered_operations());
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn wal(mut self, sync: WalSync) -> Self {
+        self.config.wal = Some(sync);
+        self
+    }
+
+    /// Answer queries by exact scan while the store holds fewer than `threshold` records
+    ///
+    /// Set to 0 to always use the HNSW index unless a query asks for `exact`.
+    /// Default: 1000
+    pub fn exact_search_threshold(mut self, threshold: usize) -> Self {
+        self.config.exact_search_threshold = threshold;
+        self
+    }
+
+    /// Keep queries taking `threshold` or longer in the slow-query log
+    ///
+    /// See [`VecStore::slow_queries`].
+    pub fn slow_query_threshold(mut self, threshold: std::time::Duration) -> Self {
+        self.config.slow_query_threshold_ms = Some(threshold.as_millis() as u64);
+        self
+    }
+
+    /// Encrypt the store's files at rest under `key`
+    ///
+    /// A new store, or an existing unencrypted one, is encrypted from this
+    /// open on. An encrypted store must be opened with the key it was
+    /// encrypted with; without one, the key is read from the
+    /// `VECSTORE_ENCRYPTION_KEY` or `VECSTORE_ENCRYPTION_PASSPHRASE`
+    /// environment variable. The write-ahead log can't be encrypted, so the
+    /// two can't be combined.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{EncryptionKey, VecStore};
+    /// let store = VecStore::builder("./data")
+    ///     .encryption(EncryptionKey::from_env()?.expect("key not set"))
+    ///     .build()?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "encryption")]
+    pub fn encryption(mut self, key: EncryptionKey) -> Self {
+        self.encryption = Some(key);
         self
     }
 
     /// Build the VecStore with the configured settings
     pub fn build(self) -> Result<VecStore> {
-        VecStore::open_with_config(self.path, self.config)
+        VecStore::open_with_key(self.path, self.config, self.encryption.as_ref())
     }
 }
 
@@ -119,8 +315,21 @@ impl VecStore {
 
     /// Open VecStore with custom configuration
     pub fn open_with_config<P: Into<PathBuf>>(root: P, config: Config) -> Result<Self> {
+        Self::open_with_key(root, config, None)
+    }
+
+    fn open_with_key<P: Into<PathBuf>>(
+        root: P,
+        config: Config,
+        key: Option<&EncryptionKey>,
+    ) -> Result<Self> {
+        config.hnsw.validate()?;
+        if config.dimension == Some(0) {
+            return Err(anyhow::anyhow!("Configured dimension must be at least 1"));
+        }
         let root = root.into();
-        let layout = disk::DiskLayout::new(&root);
+        let cipher = encryption::load(&root, key)?.map(Arc::new);
+        let layout = disk::DiskLayout::new(&root).with_cipher(cipher.clone());
 
         // Validate distance metric configuration (Major Issue #5 partial fix)
         if config.distance != Distance::Cosine {
@@ -133,7 +342,7 @@ impl VecStore {
             );
         }
 
-        if layout.exists() {
+        let mut store = if layout.exists() {
             // Load existing store
             let (
                 records,
@@ -146,10 +355,21 @@ impl VecStore {
             ) = layout.load_all().context("Failed to load existing store")?;
 
             // Use loaded config if available, otherwise use provided config (Major Issue #7 fix)
-            let config = loaded_config.unwrap_or(config);
+            let requested_wal = config.wal;
+            let mut config = loaded_config.unwrap_or(config);
+            // A WAL requested at open applies to stores saved without one
+            config.wal = requested_wal.or(config.wal);
+            fit_capacity(&mut config.hnsw, records.len());
+            let quantized = layout.load_quantized()?;
+            // Stores saved before sequence tracking start counting from their records
+            let changes = layout.load_changes()?.unwrap_or_else(|| disk::ChangeState {
+                op_seq: records.values().map(|r| r.seq).max().unwrap_or(0),
+                ..Default::default()
+            });
 
             #[cfg(not(target_arch = "wasm32"))]
-            let mut backend = VectorBackend::new(dimension, config.distance)?;
+            let mut backend = VectorBackend::new(dimension, config.distance, &config.hnsw)?
+                .with_quantizer(quantized.as_ref().map(|q| q.quantizer().clone()))?;
             #[cfg(target_arch = "wasm32")]
             let mut backend = VectorBackend::new(dimension);
             backend.set_mappings(id_to_idx, idx_to_id, next_idx);
@@ -157,7 +377,10 @@ impl VecStore {
             // Rebuild HNSW index from vectors
             let vectors: Vec<(Id, Vec<f32>)> = records
                 .values()
-                .map(|r| (r.id.clone(), r.vector.clone()))
+                .map(|r| {
+                    let vector = full_vector(r, quantized.as_ref()).into_owned();
+                    (r.id.clone(), vector)
+                })
                 .collect();
             backend.rebuild_from_vectors(&vectors)?;
 
@@ -166,8 +389,10 @@ impl VecStore {
             if let Some(texts) = text_index_data {
                 text_index.import_texts(texts);
             }
+            let field_indexes =
+                field_index::FieldIndexes::build(&config.field_indexes, records.values());
 
-            Ok(Self {
+            Self {
                 root,
                 backend,
                 records,
@@ -175,17 +400,31 @@ impl VecStore {
                 text_index,
                 compaction_config: CompactionConfig::default(),
                 config,
-            })
+                deleted_ratio: AtomicU64::new(0),
+                reranker: None,
+                quantized,
+                wal: None,
+                recovered_operations: 0,
+                saved_seq: AtomicU64::new(changes.op_seq),
+                changes,
+                cipher,
+                field_indexes,
+                subscribers: events::Subscribers::default(),
+                slow_queries: Mutex::default(),
+            }
         } else {
             // Create new store - infer dimension from first insert
             layout.ensure_directory()?;
 
             #[cfg(not(target_arch = "wasm32"))]
-            let backend = VectorBackend::new(0, config.distance)?;
+            let backend = VectorBackend::new(0, config.distance, &config.hnsw)?;
             #[cfg(target_arch = "wasm32")]
             let backend = VectorBackend::new(0);
 
-            Ok(Self {
+            let field_indexes =
+                field_index::FieldIndexes::build(&config.field_indexes, std::iter::empty());
+
+            Self {
                 root,
                 backend, // Will be set on first insert
                 records: HashMap::new(),
@@ -193,8 +432,174 @@ impl VecStore {
                 text_index: hybrid::TextIndex::new(),
                 compaction_config: CompactionConfig::default(),
                 config,
-            })
+                deleted_ratio: AtomicU64::new(0),
+                reranker: None,
+                quantized: None,
+                wal: None,
+                recovered_operations: 0,
+                changes: disk::ChangeState::default(),
+                saved_seq: AtomicU64::new(0),
+                cipher,
+                field_indexes,
+                subscribers: events::Subscribers::default(),
+                slow_queries: Mutex::default(),
+            }
+        };
+
+        if (store.cipher.is_some() || key.is_some()) && store.config.wal.is_some() {
+            return Err(anyhow::anyhow!(
+                "The write-ahead log is not encrypted, so it can't be enabled on an encrypted store"
+            ));
+        }
+        if let (None, Some(key)) = (&store.cipher, key) {
+            // First open with a key: rewrite any plaintext files sealed
+            store.cipher = Some(Arc::new(encryption::create(&store.root, key)?));
+            if layout.exists() {
+                store.save()?;
+            }
+        }
+
+        store.recover_wal()?;
+        Ok(store)
+    }
+
+    /// Replay operations logged since the last save, then start logging
+    fn recover_wal(&mut self) -> Result<()> {
+        let sync = match self.config.wal {
+            Some(sync) => sync,
+            None => return Ok(()),
+        };
+
+        std::fs::create_dir_all(&self.root)
+            .with_context(|| format!("Failed to create store directory {:?}", self.root))?;
+        let path = disk::DiskLayout::new(&self.root).wal_path();
+        let mut wal = WriteAheadLog::open_with_sync(&path, sync)?;
+        for entry in wal.replay().context("Failed to replay write-ahead log")? {
+            match entry {
+                LogEntry::Upsert {
+                    id,
+                    vector,
+                    metadata,
+                    namespace,
+                } => {
+                    let metadata = serde_json::from_str(&metadata)
+                        .context("Corrupt metadata in write-ahead log")?;
+                    self.upsert_record(Record {
+                        namespace,
+                        ..make_record(id, vector, metadata)
+                    })?;
+                }
+                LogEntry::UpsertRecord {
+                    id,
+                    vector,
+                    metadata,
+                    namespace,
+                    text,
+                    parent_id,
+                    chunk_index,
+                    expires_at,
+                } => {
+                    let metadata = serde_json::from_str(&metadata)
+                        .context("Corrupt metadata in write-ahead log")?;
+                    self.upsert_record(Record {
+                        namespace,
+                        text,
+                        parent_id,
+                        chunk_index,
+                        expires_at,
+                        ..make_record(id, vector, metadata)
+                    })?;
+                }
+                // Removing a record that is already gone is a no-op on replay
+                LogEntry::Delete { id } => {
+                    let _ = self.remove(&id);
+                }
+                LogEntry::Metadata { id, metadata } => {
+                    let metadata = serde_json::from_str(&metadata)
+                        .context("Corrupt metadata in write-ahead log")?;
+                    self.replace_metadata(&id, metadata)?;
+                }
+                LogEntry::SoftDelete { id } => {
+                    self.soft_delete(&id)?;
+                }
+                LogEntry::Restore { id } => {
+                    self.restore(&id)?;
+                }
+                LogEntry::SetTtl { id, expires_at } => self.set_expires_at(&id, expires_at)?,
+                _ => continue,
+            }
+            self.recovered_operations += 1;
+        }
+
+        if self.recovered_operations > 0 {
+            tracing::info!(
+                operations = self.recovered_operations,
+                "Recovered operations from write-ahead log"
+            );
+        }
+        self.wal = Some(Mutex::new(wal));
+        Ok(())
+    }
+
+    /// Operations replayed from the write-ahead log when the store was opened
+    ///
+    /// Non-zero means the previous process exited without saving.
+    pub fn recovered_operations(&self) -> usize {
+        self.recovered_operations
+    }
+
+    /// Append `entry` to the write-ahead log, if enabled
+    fn log(&mut self, entry: LogEntry) -> Result<()> {
+        match &mut self.wal {
+            Some(wal) => {
+                let wal = wal
+                    .get_mut()
+                    .map_err(|_| anyhow::anyhow!("Write-ahead log lock poisoned"))?;
+                wal.append(entry).map(|_| ())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Log an upsert of `record` with the given vector
+    fn log_upsert(&mut self, record: &Record, vector: &[f32]) -> Result<()> {
+        if self.wal.is_none() {
+            return Ok(());
+        }
+        let id = record.id.clone();
+        let vector = vector.to_vec();
+        let metadata = serde_json::to_string(&record.metadata)?;
+        let namespace = record.namespace.clone();
+        if record.text.is_none() && record.parent_id.is_none() && record.expires_at.is_none() {
+            return self.log(LogEntry::Upsert {
+                id,
+                vector,
+                metadata,
+                namespace,
+            });
+        }
+        self.log(LogEntry::UpsertRecord {
+            id,
+            vector,
+            metadata,
+            namespace,
+            text: record.text.clone(),
+            parent_id: record.parent_id.clone(),
+            chunk_index: record.chunk_index,
+            expires_at: record.expires_at,
+        })
+    }
+
+    /// Log the metadata `id` now has
+    fn log_metadata(&mut self, id: &str) -> Result<()> {
+        if self.wal.is_none() {
+            return Ok(());
         }
+        let metadata = serde_json::to_string(&self.records[id].metadata)?;
+        self.log(LogEntry::Metadata {
+            id: id.to_string(),
+            metadata,
+        })
     }
 
     pub fn open<P: Into<PathBuf>>(root: P) -> Result<Self> {
@@ -211,2327 +616,7835 @@ impl VecStore {
         &self.config
     }
 
-    #[tracing::instrument(skip(self, vector, metadata), fields(dimension = vector.len()))]
-    pub fn upsert(&mut self, id: Id, vector: Vec<f32>, metadata: Metadata) -> Result<()> {
-        // Validate vector is non-empty (Critical Issue #20 fix)
-        if vector.is_empty() {
-            return Err(anyhow::anyhow!(
-                "Cannot insert zero-dimension vector. Vectors must have at least one dimension."
-            ));
-        }
+    /// Change the record count below which queries use exact search
+    ///
+    /// Persisted on the next [`save`](Self::save).
+    pub fn set_exact_search_threshold(&mut self, threshold: usize) {
+        self.config.exact_search_threshold = threshold;
+        self.settings_changed();
+    }
 
-        // Set dimension on first insert
-        if self.dimension == 0 {
-            self.dimension = vector.len();
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                self.backend = VectorBackend::new(self.dimension, self.config.distance)?;
-            }
-            #[cfg(target_arch = "wasm32")]
-            {
-                self.backend = VectorBackend::new(self.dimension);
-            }
-        }
+    /// `ef_search` used by index searches whose query doesn't set one
+    ///
+    /// The configured [`Config::default_ef_search`], or else the backend's
+    /// default (on wasm32, the number of candidates fetched, reported as 0).
+    pub fn default_ef_search(&self) -> usize {
+        #[cfg(not(target_arch = "wasm32"))]
+        let fallback = hnsw_backend::DEFAULT_EF_SEARCH;
+        #[cfg(target_arch = "wasm32")]
+        let fallback = 0;
+        self.config.default_ef_search.unwrap_or(fallback)
+    }
 
-        if vector.len() != self.dimension {
+    /// Change the `ef_search` of queries that don't set one (None restores
+    /// the backend's default)
+    ///
+    /// It is raised to the number of candidates a query fetches, as an
+    /// explicit `ef_search` is. Persisted on the next [`save`](Self::save).
+    pub fn set_default_ef_search(&mut self, ef_search: Option<usize>) -> Result<()> {
+        if ef_search == Some(0) {
             return Err(anyhow::anyhow!(
-                "Vector dimension mismatch: expected {}, got {}",
-                self.dimension,
-                vector.len()
+                "Invalid ef_search parameter: must be at least 1, got 0"
             ));
         }
-
-        let record = Record {
-            id: id.clone(),
-            vector: vector.clone(),
-            metadata,
-            created_at: Utc::now().timestamp(),
-            deleted: false,
-            deleted_at: None,
-            expires_at: None,
-        };
-
-        self.backend.insert(id.clone(), &vector)?;
-        self.records.insert(id, record);
-
+        self.config.default_ef_search = ef_search;
+        self.settings_changed();
         Ok(())
     }
 
-    pub fn remove(&mut self, id: &str) -> Result<()> {
-        self.backend.remove(id)?;
-        self.records
-            .remove(id)
-            .ok_or_else(|| anyhow::anyhow!("Record not found: {}", id))?;
+    /// Change the vector policy for future inserts and queries
+    ///
+    /// Existing vectors are not rewritten. Persisted on the next
+    /// [`save`](Self::save).
+    pub fn set_vector_policy(&mut self, policy: VectorPolicy) {
+        self.config.vector_policy = policy;
+        self.settings_changed();
+    }
 
-        // Clean up text index (Critical Issue #4 fix)
-        self.text_index.remove_document(id);
+    /// Rerank the results of every [`query`](Self::query) with `reranker`
+    ///
+    /// Not persisted; register it again after reopening the store.
+    pub fn set_reranker(&mut self, reranker: Arc<dyn QueryReranker>) {
+        self.reranker = Some(reranker);
+    }
 
-        Ok(())
+    /// Stop reranking query results
+    pub fn clear_reranker(&mut self) {
+        self.reranker = None;
     }
 
-    /// Batch insert multiple vectors using parallel processing
+    /// Call `hook` after every upsert, delete, compaction and save
     ///
-    /// This is significantly faster than calling upsert() in a loop when you have
-    /// many vectors to add at once. The HNSW index is built in parallel using rayon.
+    /// Hooks run on the writing thread once the change is applied, in the
+    /// order they were registered; keep them short or hand the event to a
+    /// channel. A hook that panics is logged and skipped. Not persisted.
     ///
     /// # Example
     /// ```no_run
-    /// # use vecstore::{VecStore, make_record};
-    /// # let mut store = VecStore::open("data").unwrap();
-    /// let records = vec![
-    ///     make_record("doc1", vec![0.1, 0.2, 0.3], vec![]),
-    ///     make_record("doc2", vec![0.4, 0.5, 0.6], vec![]),
-    /// ];
-    /// store.batch_upsert(records).unwrap();
+    /// # use vecstore::{VecStore, StoreEvent};
+    /// # let mut store = VecStore::open("data")?;
+    /// store.subscribe(Box::new(|event| {
+    ///     if let StoreEvent::Delete { id } = event {
+    ///         println!("invalidate {}", id);
+    ///     }
+    /// }));
+    /// # Ok::<(), anyhow::Error>(())
     /// ```
-    pub fn batch_upsert(&mut self, items: impl IntoIterator<Item = Record>) -> Result<()> {
-        use rayon::prelude::*;
+    pub fn subscribe(&mut self, hook: EventHook) -> SubscriptionId {
+        self.subscribers.add(hook)
+    }
 
-        let items: Vec<_> = items.into_iter().collect();
+    /// Remove a hook added with [`subscribe`](Self::subscribe)
+    ///
+    /// Returns false if it was already removed.
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        self.subscribers.remove(id)
+    }
 
-        if items.is_empty() {
+    fn emit(&self, event: StoreEvent) {
+        self.subscribers.emit(event);
+    }
+
+    fn emit_upsert(&self, id: &str) {
+        self.emit(StoreEvent::Upsert {
+            id: id.to_string(),
+            dimension: self.dimension,
+        });
+    }
+
+    /// Change the HNSW construction parameters and rebuild the index
+    ///
+    /// The new parameters are persisted on the next [`save`](Self::save).
+    /// Does nothing if `config` matches the current parameters.
+    pub fn set_hnsw_config(&mut self, config: HnswConfig) -> Result<()> {
+        config.validate()?;
+        if config == self.config.hnsw {
             return Ok(());
         }
 
-        // Set dimension from first record if needed
-        if self.dimension == 0 {
-            if let Some(first) = items.first() {
-                // Validate first vector is non-empty (Major Issue #21 fix)
-                if first.vector.is_empty() {
-                    return Err(anyhow::anyhow!(
-                        "Cannot insert zero-dimension vector. Vectors must have at least one dimension."
-                    ));
-                }
-                self.dimension = first.vector.len();
-                #[cfg(not(target_arch = "wasm32"))]
-                {
-                    self.backend = VectorBackend::new(self.dimension, self.config.distance)?;
-                }
-                #[cfg(target_arch = "wasm32")]
-                {
-                    self.backend = VectorBackend::new(self.dimension);
-                }
-            }
-        }
+        self.config.hnsw = config;
+        self.settings_changed();
+        self.rebuild_index()
+    }
 
-        // Validate all vectors in parallel (Major Issue #21 fix)
-        items.par_iter().try_for_each(|record| {
-            if record.vector.is_empty() {
-                return Err(anyhow::anyhow!(
-                    "Cannot insert zero-dimension vector for '{}'. Vectors must have at least one dimension.",
-                    record.id
-                ));
-            }
-            if record.vector.len() != self.dimension {
+    /// Learn the int8 codebook from the live records and quantize the store
+    ///
+    /// Requires a store created with [`Quantization::Int8`]. Per-dimension
+    /// min/max are learned from the current live vectors, the index is rebuilt
+    /// over int8 codes and, unless `keep_originals` is set, every record's f32
+    /// vector is replaced by its code. Later inserts reuse the codebook and
+    /// clamp values outside the calibrated range, so calibrate once a
+    /// representative sample is loaded. Persisted on the next
+    /// [`save`](Self::save).
+    pub fn calibrate_quantization(&mut self) -> Result<()> {
+        let keep_originals = match self.config.quantization {
+            Quantization::Int8 { keep_originals } => keep_originals,
+            Quantization::None => {
                 return Err(anyhow::anyhow!(
-                    "Vector dimension mismatch for {}: expected {}, got {}",
-                    record.id,
-                    self.dimension,
-                    record.vector.len()
-                ));
+                    "Store was not created with int8 quantization"
+                ))
             }
-            Ok(())
-        })?;
+        };
 
-        // Prepare data for batch insert
-        let batch_data: Vec<(Id, Vec<f32>)> = items
-            .iter()
-            .map(|r| (r.id.clone(), r.vector.clone()))
+        let now = Utc::now().timestamp();
+        let sample: Vec<Vec<f32>> = self
+            .records
+            .values()
+            .filter(|r| r.is_live(now))
+            .map(|r| full_vector(r, self.quantized.as_ref()).into_owned())
             .collect();
+        let mut quantized = QuantizedVectors::calibrate(&sample)?;
+
+        if !keep_originals {
+            // Decode through the old codebook before re-encoding with the new one
+            for (id, vector) in self.full_vectors() {
+                if let Some(record) = self.records.get_mut(&id) {
+                    record.vector = vector;
+                    quantized.absorb(record)?;
+                }
+            }
+        }
 
-        // Use parallel batch insert (much faster than sequential)
-        self.backend.batch_insert(batch_data)?;
+        self.quantized = Some(quantized);
+        self.settings_changed();
+        self.rebuild_index()
+    }
 
-        // Update records
-        for record in items {
-            self.records.insert(record.id.clone(), record);
-        }
+    /// Whether searches re-rank quantized candidates against f32 originals
+    fn reranks_originals(&self) -> bool {
+        let keep_originals = matches!(
+            self.config.quantization,
+            Quantization::Int8 {
+                keep_originals: true
+            }
+        );
+        keep_originals && self.quantized.is_some()
+    }
 
-        Ok(())
+    /// Replace `record`'s f32 vector by its int8 code when originals are not kept
+    fn drop_original(&mut self, record: &mut Record) -> Result<()> {
+        let drops_originals = matches!(
+            self.config.quantization,
+            Quantization::Int8 {
+                keep_originals: false
+            }
+        );
+        match &mut self.quantized {
+            Some(quantized) if drops_originals => quantized.absorb(record),
+            _ => Ok(()),
+        }
     }
 
-    /// Optimize the index by rebuilding to remove "ghost" entries from deletions
+    /// Index a metadata field so filtered queries can skip records that can't match
     ///
-    /// After many remove() operations, the HNSW index accumulates entries that
-    /// are no longer referenced. This method rebuilds the index from scratch to
-    /// reclaim memory and improve search performance.
-    ///
-    /// Returns the number of ghost entries removed.
+    /// Keyword indexes answer `Eq`, `In` and `AnyIn`; numeric indexes also
+    /// answer range comparisons. The index is built from the current records,
+    /// kept up to date on every write, and its definition is persisted on the
+    /// next `save()`.
     ///
     /// # Example
     /// ```no_run
-    /// # use vecstore::VecStore;
+    /// # use vecstore::{FieldIndexType, VecStore};
     /// # let mut store = VecStore::open("data").unwrap();
-    /// // After many deletions...
-    /// let removed = store.optimize().unwrap();
-    /// println!("Removed {} ghost entries", removed);
+    /// store.create_field_index("category", FieldIndexType::Keyword)?;
+    /// store.create_field_index("price", FieldIndexType::Numeric)?;
+    /// # Ok::<(), anyhow::Error>(())
     /// ```
-    pub fn optimize(&mut self) -> Result<usize> {
-        let vectors: Vec<(Id, Vec<f32>)> = self
-            .records
-            .values()
-            .map(|r| (r.id.clone(), r.vector.clone()))
-            .collect();
-
-        self.backend.optimize(&vectors)
+    pub fn create_field_index(&mut self, field: &str, index_type: FieldIndexType) -> Result<()> {
+        if field.is_empty() {
+            return Err(anyhow::anyhow!("Field name cannot be empty"));
+        }
+        if self.field_indexes.contains(field) {
+            return Err(anyhow::anyhow!("Field '{}' is already indexed", field));
+        }
+        self.field_indexes
+            .create(field, index_type, self.records.values());
+        self.config
+            .field_indexes
+            .insert(field.to_string(), index_type);
+        self.settings_changed();
+        Ok(())
     }
 
-    #[tracing::instrument(skip(self, q), fields(k = q.k, has_filter = q.filter.is_some(), dimension = q.vector.len()))]
-    pub fn query(&self, q: Query) -> Result<Vec<Neighbor>> {
-        if self.dimension == 0 {
-            return Ok(Vec::new());
+    /// Remove the index on a metadata field
+    pub fn drop_field_index(&mut self, field: &str) -> Result<()> {
+        if !self.field_indexes.drop_index(field) {
+            return Err(anyhow::anyhow!("No index on field '{}'", field));
         }
+        self.config.field_indexes.remove(field);
+        self.settings_changed();
+        Ok(())
+    }
 
-        if q.vector.len() != self.dimension {
-            return Err(anyhow::anyhow!(
-                "Query dimension mismatch: expected {}, got {}",
-                self.dimension,
-                q.vector.len()
-            ));
+    /// Bytes the store's files take in its directory
+    pub fn disk_bytes(&self) -> u64 {
+        self.layout_at(&self.root).data_size().unwrap_or(0)
+    }
+
+    /// Record counts, dimension and index parameters
+    pub fn stats(&self) -> StoreStats {
+        let deleted_records = self.deleted_count();
+        let layout = self.layout_at(&self.root);
+        StoreStats {
+            total_records: self.records.len(),
+            active_records: self.records.len() - deleted_records,
+            deleted_records,
+            dimension: self.dimension,
+            distance: self.config.distance,
+            hnsw: self.config.hnsw,
+            index_capacity: self.config.hnsw.max_elements,
+            index_utilization: self.index_len() as f64 / self.config.hnsw.max_elements as f64,
+            ghost_entries: self.ghost_entries(),
+            fragmentation_ratio: self.fragmentation_ratio(),
+            vector_policy: self.config.vector_policy,
+            quantization: self.config.quantization,
+            quantization_calibrated: self.quantized.is_some(),
+            vector_memory_bytes: self.vector_memory_bytes(),
+            full_precision_vector_bytes: self.full_precision_vector_bytes(),
+            index_link_bytes: self.index_link_bytes(),
+            metadata_bytes: self.metadata_bytes(),
+            disk_bytes: layout.data_size().unwrap_or(0),
+            last_saved_at: layout.last_saved_at(),
+            wal_bytes: self
+                .wal
+                .as_ref()
+                .map(|_| std::fs::metadata(layout.wal_path()).map_or(0, |m| m.len())),
+            namespace_counts: self.namespace_counts(),
+            field_indexes: self.field_indexes.stats(),
         }
+    }
 
-        // Determine fetch size for HNSW search
-        let fetch_size = if q.filter.is_some() {
-            // When filtering, we need to over-fetch to account for filtered-out results
-            // Fetch all records (up to k*10) to ensure we have enough candidates
-            let total_records = self.records.len();
-            if total_records <= q.k {
-                // If we have fewer records than k, fetch all
-                total_records
-            } else {
-                // Otherwise, over-fetch by 10x (capped at total records) - using saturating_mul to prevent overflow (Critical Issue #10 fix)
-                std::cmp::min(q.k.saturating_mul(10), total_records)
-            }
-        } else {
-            // No filter, just fetch k (or all records if fewer than k)
-            std::cmp::min(q.k, self.records.len())
-        };
+    /// Approximate bytes of the HNSW neighbour lists
+    ///
+    /// A node links to up to `2 × M` neighbours on the base layer and `M` on
+    /// each upper layer; with the usual level distribution it reaches
+    /// `1 / (M - 1)` upper layers on average.
+    fn index_link_bytes(&self) -> usize {
+        let m = self.config.hnsw.m.max(2) as f64;
+        let links_per_node = 2.0 * m + m / (m - 1.0);
+        (self.index_len() as f64 * links_per_node) as usize * std::mem::size_of::<usize>()
+    }
+
+    /// Bytes of all record metadata serialized as JSON
+    fn metadata_bytes(&self) -> usize {
+        self.records
+            .values()
+            .map(|r| serde_json::to_string(&r.metadata).map_or(0, |json| json.len()))
+            .sum()
+    }
+
+    /// Bytes held for vectors: record vectors, int8 codes and the index's copies
+    fn vector_memory_bytes(&self) -> usize {
+        let records: usize = self
+            .records
+            .values()
+            .map(|r| r.vector.len() * std::mem::size_of::<f32>())
+            .sum();
+        let codes = self.quantized.as_ref().map_or(0, |q| q.code_bytes());
         #[cfg(not(target_arch = "wasm32"))]
-        let candidates = self.backend.search(&q.vector, fetch_size);
+        let index = self.backend.vector_bytes();
         #[cfg(target_arch = "wasm32")]
-        let candidates = self.backend.search(&q.vector, fetch_size)?;
+        let index = self.index_len() * self.dimension * std::mem::size_of::<f32>();
+        records + codes + index
+    }
 
-        let mut results = Vec::new();
-        for (id, score) in candidates {
-            if let Some(record) = self.records.get(&id) {
-                // Skip soft-deleted records
-                if record.deleted {
-                    continue;
-                }
+    /// Bytes the records and index entries would take as f32 vectors
+    fn full_precision_vector_bytes(&self) -> usize {
+        (self.records.len() + self.index_len()) * self.dimension * std::mem::size_of::<f32>()
+    }
 
-                // Apply filter if present
-                if let Some(ref filter) = q.filter {
-                    if !filters::evaluate_filter(filter, &record.metadata) {
-                        continue;
-                    }
-                }
+    /// Number of points in the vector index, including stale entries
+    fn index_len(&self) -> usize {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.backend.inserted_count();
+        #[cfg(target_arch = "wasm32")]
+        return self.backend.len();
+    }
 
-                results.push(Neighbor {
-                    id: id.clone(),
-                    score,
-                    metadata: record.metadata.clone(),
-                });
+    /// Index entries left behind by removed or replaced vectors
+    pub fn ghost_entries(&self) -> usize {
+        self.index_len().saturating_sub(self.records.len())
+    }
 
-                if results.len() >= q.k {
-                    break;
-                }
-            }
+    /// Fraction of index entries that are ghosts (0.0 for an empty index)
+    pub fn fragmentation_ratio(&self) -> f64 {
+        match self.index_len() {
+            0 => 0.0,
+            len => self.ghost_entries() as f64 / len as f64,
         }
-
-        Ok(results)
     }
 
-    /// Query with detailed explanations of why each result was returned
+    /// Compare the index's id mappings with the records, and read up to
+    /// `sample` vectors for NaN, infinite and all-zero values
     ///
-    /// This is useful for debugging, understanding search results, and optimizing queries.
-    /// Returns the same results as `query()` but with additional explanation metadata.
-    pub fn query_explain(&self, q: Query) -> Result<Vec<ExplainedNeighbor>> {
-        if self.dimension == 0 {
-            return Ok(Vec::new());
-        }
-
-        if q.vector.len() != self.dimension {
-            return Err(anyhow::anyhow!(
-                "Query dimension mismatch: expected {}, got {}",
-                self.dimension,
-                q.vector.len()
-            ));
+    /// Every record's dimension is counted; the sampled records are spread
+    /// evenly over the store. Used by [`HealthChecker`](crate::health::HealthChecker).
+    pub fn scan_consistency(&self, sample: usize) -> ConsistencyScan {
+        let id_to_idx = self.backend.get_id_to_idx_map();
+        let mut slots: HashMap<&Id, usize> = HashMap::new();
+        for id in self.backend.get_idx_to_id_map().values() {
+            *slots.entry(id).or_default() += 1;
         }
 
-        // Determine fetch size for HNSW search
-        let fetch_size = if q.filter.is_some() {
-            let total_records = self.records.len();
-            if total_records <= q.k {
-                total_records
-            } else {
-                // Using saturating_mul to prevent overflow (Major Issue #10 fix)
-                std::cmp::min(q.k.saturating_mul(10), total_records)
-            }
-        } else {
-            std::cmp::min(q.k, self.records.len())
+        let mut scan = ConsistencyScan {
+            orphaned_ids: id_to_idx
+                .keys()
+                .filter(|id| !self.records.contains_key(*id))
+                .cloned()
+                .collect(),
+            unindexed_ids: self
+                .records
+                .keys()
+                .filter(|id| !id_to_idx.contains_key(*id))
+                .cloned()
+                .collect(),
+            duplicate_ids: slots
+                .into_iter()
+                .filter(|(_, count)| *count > 1)
+                .map(|(id, _)| id.clone())
+                .collect(),
+            ..Default::default()
         };
 
-        // Track stats for explanation
-        #[cfg(not(target_arch = "wasm32"))]
-        let candidates = self.backend.search(&q.vector, fetch_size);
-        #[cfg(target_arch = "wasm32")]
-        let candidates = self.backend.search(&q.vector, fetch_size)?;
-        let total_candidates = candidates.len();
-
-        let distance_metric = self.config.distance.name().to_string();
-        let has_filter = q.filter.is_some();
-
-        let mut results = Vec::new();
-        let mut rank = 1;
-        let mut filtered_out_count = 0;
-        let mut deleted_count = 0;
-
-        for (id, score) in candidates {
-            if let Some(record) = self.records.get(&id) {
-                // Track if soft-deleted
-                if record.deleted {
-                    deleted_count += 1;
-                    continue;
-                }
-
-                // Evaluate filter and collect details
-                let (filter_passed, filter_details) = if let Some(ref filter) = q.filter {
-                    let passed = filters::evaluate_filter(filter, &record.metadata);
-                    let details = FilterEvaluation {
-                        filter_expr: format!("{:?}", filter),
-                        matched_conditions: if passed {
-                            vec![format!("All conditions matched")]
-                        } else {
-                            vec![]
-                        },
-                        failed_conditions: if !passed {
-                            vec![format!("Filter did not match")]
-                        } else {
-                            vec![]
-                        },
-                        passed,
-                    };
-                    (passed, Some(details))
-                } else {
-                    (true, None)
-                };
-
-                if !filter_passed {
-                    filtered_out_count += 1;
-                    continue;
+        let mut records: Vec<&Record> = self.records.values().collect();
+        records.sort_by(|a, b| a.id.cmp(&b.id));
+        let step = records.len().div_ceil(sample.max(1)).max(1);
+        for (position, record) in records.into_iter().enumerate() {
+            let len = match &self.quantized {
+                Some(quantized) if record.vector.is_empty() => {
+                    quantized.code_len(&record.id).unwrap_or(0)
                 }
+                _ => record.vector.len(),
+            };
+            *scan.dimensions.entry(len).or_default() += 1;
 
-                // Build explanation text
-                let explanation_text = if has_filter {
-                    format!(
-                        "Ranked #{} with score {:.4} ({}). Passed filters. {} candidates evaluated, {} filtered out, {} deleted.",
-                        rank, score, distance_metric, total_candidates, filtered_out_count, deleted_count
-                    )
-                } else {
-                    format!(
-                        "Ranked #{} with score {:.4} ({}). {} candidates evaluated, {} deleted.",
-                        rank, score, distance_metric, total_candidates, deleted_count
-                    )
-                };
-
-                results.push(ExplainedNeighbor {
-                    id: id.clone(),
-                    score,
-                    metadata: record.metadata.clone(),
-                    explanation: QueryExplanation {
-                        raw_score: score,
-                        distance_metric: distance_metric.clone(),
-                        filter_passed,
-                        filter_details,
-                        graph_stats: Some(GraphTraversalStats {
-                            distance_calculations: total_candidates,
-                            nodes_visited: total_candidates,
-                            found_at_layer: None, // Would require HNSW backend changes
-                            hops_from_entry: None, // Would require HNSW backend changes
-                        }),
-                        rank,
-                        explanation_text,
-                    },
-                });
-
-                rank += 1;
-
-                if results.len() >= q.k {
-                    break;
-                }
+            if sample == 0 || position % step != 0 {
+                continue;
+            }
+            scan.sampled += 1;
+            let vector = full_vector(record, self.quantized.as_ref());
+            if vector.iter().any(|x| !x.is_finite()) {
+                scan.non_finite_ids.push(record.id.clone());
+            } else if !vector.is_empty() && vector.iter().all(|x| *x == 0.0) {
+                scan.zero_vector_ids.push(record.id.clone());
             }
         }
 
-        Ok(results)
+        scan.orphaned_ids.sort();
+        scan.unindexed_ids.sort();
+        scan.duplicate_ids.sort();
+        scan
     }
 
-    pub fn save(&self) -> Result<()> {
-        let layout = disk::DiskLayout::new(&self.root);
-
-        // Export text index if any texts are indexed (Major Issue #6 fix)
-        let text_index_data = if self.text_index.export_texts().is_empty() {
-            None
-        } else {
-            Some(self.text_index.export_texts())
-        };
+    /// Rebuild the index if fragmentation exceeds `max_fragmentation_ratio`
+    ///
+    /// A rebuild re-inserts every live vector, so writes don't trigger it;
+    /// call this periodically, or let [`maybe_compact`](Self::maybe_compact)
+    /// do so. An index without live records is left as is. Returns whether
+    /// the index was rebuilt.
+    pub fn defragment_if_needed(&mut self) -> Result<bool> {
+        if self.records.is_empty()
+            || self.fragmentation_ratio() <= self.compaction_config.max_fragmentation_ratio as f64
+        {
+            return Ok(false);
+        }
+        let reclaimed = self.optimize()?;
+        tracing::debug!(reclaimed, "Rebuilt fragmented HNSW index");
+        Ok(true)
+    }
 
-        layout.save_all(
-            &self.records,
-            self.backend.get_id_to_idx_map(),
-            self.backend.get_idx_to_id_map(),
-            // Use actual next_idx counter, not map length (Critical Issue #2 fix)
-            self.backend.get_next_idx(),
-            self.dimension,
-            &self.config,    // Major Issue #7 fix: persist config
-            text_index_data, // Major Issue #6 fix: persist text index
-        )?;
+    /// Recreate the vector index from `records` with the current config
+    fn rebuild_index(&mut self) -> Result<()> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let mut backend =
+            VectorBackend::new(self.dimension, self.config.distance, &self.config.hnsw)?
+                .with_quantizer(self.quantized.as_ref().map(|q| q.quantizer().clone()))?;
+        #[cfg(target_arch = "wasm32")]
+        let mut backend = VectorBackend::new(self.dimension);
 
-        // Save HNSW index
-        if self.dimension > 0 {
-            self.backend.save_index(&layout.hnsw_path())?;
-        }
+        backend.rebuild_from_vectors(&self.full_vectors())?;
 
+        self.backend = backend;
         Ok(())
     }
 
-    /// Get the number of active (non-deleted) records
-    pub fn count(&self) -> usize {
-        self.active_count()
+    /// Rebuild into a larger index if `additional` inserts would overflow it
+    ///
+    /// The new capacity is stored in the config, so it is persisted and used
+    /// when the store is reopened.
+    fn ensure_capacity(&mut self, additional: usize) -> Result<()> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let needed = self.backend.inserted_count() + additional;
+            if needed <= self.backend.capacity() {
+                return Ok(());
+            }
+
+            let old_capacity = self.config.hnsw.max_elements;
+            fit_capacity(&mut self.config.hnsw, self.records.len() + additional);
+            tracing::info!(
+                records = self.records.len(),
+                old_capacity,
+                new_capacity = self.config.hnsw.max_elements,
+                "HNSW index full, rebuilding"
+            );
+            self.rebuild_index()?;
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = additional;
+
+        Ok(())
     }
 
-    pub fn dimension(&self) -> usize {
-        self.dimension
+    #[tracing::instrument(skip(self, vector, metadata), fields(dimension = vector.len()))]
+    pub fn upsert(&mut self, id: Id, vector: Vec<f32>, metadata: Metadata) -> Result<()> {
+        self.upsert_record(make_record(id, vector, metadata))
     }
 
-    /// Query with a filter expression parsed from a SQL-like string
+    /// Upsert only if the record is still at `expected_version`
     ///
-    /// # Example
+    /// Pass the `version` last read from [`get`](Self::get) or a query
+    /// result, or 0 to insert only when no live record has this id. On a
+    /// mismatch nothing is written and the error is a [`VersionConflict`]
+    /// (`err.downcast_ref::<VersionConflict>()`).
     ///
-    /// ```
-    /// # use vecstore::{VecStore, Metadata};
+    /// Returns the record's new version.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, Metadata, VersionConflict};
     /// # use std::collections::HashMap;
-    /// # let temp_dir = tempfile::tempdir().unwrap();
-    /// # let mut store = VecStore::open(temp_dir.path()).unwrap();
-    /// # let mut meta = Metadata { fields: HashMap::new() };
-    /// # meta.fields.insert("age".into(), serde_json::json!(25));
-    /// # meta.fields.insert("role".into(), serde_json::json!("admin"));
-    /// # store.upsert("user1".into(), vec![1.0, 0.0, 0.0], meta).unwrap();
-    /// let results = store.query_with_filter(
-    ///     vec![1.0, 0.0, 0.0],
-    ///     10,
-    ///     "age > 18 AND role = 'admin'"
-    /// ).unwrap();
+    /// # let mut store = VecStore::open("data")?;
+    /// # let meta = Metadata { fields: HashMap::new() };
+    /// let current = store.get("doc1").map_or(0, |r| r.version);
+    /// match store.upsert_if_version("doc1".into(), current, vec![0.1, 0.2], meta) {
+    ///     Ok(version) => println!("now at version {}", version),
+    ///     Err(e) if e.downcast_ref::<VersionConflict>().is_some() => println!("lost the race"),
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
     /// ```
-    pub fn query_with_filter(
-        &self,
+    pub fn upsert_if_version(
+        &mut self,
+        id: Id,
+        expected_version: u64,
         vector: Vec<f32>,
-        k: usize,
-        filter_str: &str,
-    ) -> Result<Vec<Neighbor>> {
-        let filter = filter_parser::parse_filter(filter_str)?;
-        self.query(Query {
-            vector,
-            k,
-            filter: Some(filter),
-        })
+        metadata: Metadata,
+    ) -> Result<u64> {
+        self.upsert_record_if_version(make_record(id, vector, metadata), expected_version)
     }
 
-    /// Get the number of active (non-deleted) vectors in the store
-    pub fn len(&self) -> usize {
-        self.active_count()
+    /// [`upsert_record`](Self::upsert_record) only if the record is still at
+    /// `expected_version`, as for [`upsert_if_version`](Self::upsert_if_version)
+    pub fn upsert_record_if_version(
+        &mut self,
+        record: Record,
+        expected_version: u64,
+    ) -> Result<u64> {
+        let now = Utc::now().timestamp();
+        let actual = self
+            .records
+            .get(&record.id)
+            .filter(|r| r.is_live(now))
+            .map_or(0, |r| r.version);
+        if actual != expected_version {
+            return Err(VersionConflict {
+                id: record.id,
+                expected: expected_version,
+                actual,
+            }
+            .into());
+        }
+        let id = record.id.clone();
+        self.upsert_record(record)?;
+        Ok(self.records[&id].version)
     }
 
-    /// Check if the store has no active (non-deleted) records
-    pub fn is_empty(&self) -> bool {
-        self.active_count() == 0
+    /// Insert or update a record in `namespace`
+    ///
+    /// IDs are unique across the whole store: upserting an ID that belongs
+    /// to a different namespace (including the default one) fails rather
+    /// than moving the record. Query a namespace with
+    /// [`Query::with_namespace`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, Metadata, Query};
+    /// # use std::collections::HashMap;
+    /// # let mut store = VecStore::open("data")?;
+    /// # let meta = Metadata { fields: HashMap::new() };
+    /// store.upsert_in_namespace("tenant-a", "doc1".into(), vec![0.1, 0.2], meta)?;
+    /// let hits = store.query(Query::new(vec![0.1, 0.2]).with_namespace("tenant-a"))?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn upsert_in_namespace(
+        &mut self,
+        namespace: &str,
+        id: Id,
+        vector: Vec<f32>,
+        metadata: Metadata,
+    ) -> Result<()> {
+        self.upsert_record(make_record(id, vector, metadata).with_namespace(namespace))
     }
 
-    /// Create a named snapshot of the current store state
+    /// Reject an upsert of `id` into `namespace` when the ID belongs to another one
+    fn check_namespace_owner(&self, id: &str, namespace: Option<&str>) -> Result<()> {
+        match self.records.get(id) {
+            Some(existing) if existing.namespace.as_deref() != namespace => Err(anyhow::anyhow!(
+                "Record '{}' belongs to namespace {:?}, not {:?}",
+                id,
+                existing.namespace.as_deref().unwrap_or_default(),
+                namespace.unwrap_or_default()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Insert or update one record built with [`make_record`], keeping its
+    /// namespace, text, parent and expiry
     ///
-    /// # Example
+    /// Its creation time, sequence number and version are assigned by the
+    /// store, as for [`upsert`](Self::upsert).
     ///
-    /// ```
-    /// # use vecstore::VecStore;
-    /// # let temp_dir = tempfile::tempdir().unwrap();
-    /// # let store = VecStore::open(temp_dir.path()).unwrap();
-    /// store.create_snapshot("backup-2024-01-15")?;
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, Metadata, make_record};
+    /// # let mut store = VecStore::open("data")?;
+    /// # let meta = Metadata { fields: Default::default() };
+    /// let chunk = make_record("report#3", vec![0.1, 0.2], meta)
+    ///     .with_text("Revenue grew 12% year over year.")
+    ///     .with_parent("report", 3);
+    /// store.upsert_record(chunk)?;
     /// # Ok::<(), anyhow::Error>(())
     /// ```
-    pub fn create_snapshot(&self, name: &str) -> Result<()> {
-        let snapshot_dir = self.root.join("snapshots").join(name);
+    pub fn upsert_record(&mut self, record: Record) -> Result<()> {
+        let Record {
+            id,
+            mut vector,
+            metadata,
+            namespace,
+            text,
+            parent_id,
+            chunk_index,
+            expires_at,
+            ..
+        } = record;
+        self.check_namespace_owner(&id, namespace.as_deref())?;
 
-        if snapshot_dir.exists() {
+        // Validate vector is non-empty (Critical Issue #20 fix)
+        if vector.is_empty() {
             return Err(anyhow::anyhow!(
-                "Snapshot '{}' already exists. Use a different name or delete the old snapshot.",
-                name
+                "Cannot insert zero-dimension vector. Vectors must have at least one dimension."
             ));
         }
+        self.apply_vector_policy(&id, &mut vector)?;
 
-        std::fs::create_dir_all(&snapshot_dir)
-            .with_context(|| format!("Failed to create snapshot directory: {:?}", snapshot_dir))?;
+        // Set dimension on first insert, unless the config fixes it
+        if self.dimension == 0 {
+            self.dimension = self.config.dimension.unwrap_or(vector.len());
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                self.backend =
+                    VectorBackend::new(self.dimension, self.config.distance, &self.config.hnsw)?;
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                self.backend = VectorBackend::new(self.dimension);
+            }
+        }
 
-        // Save to snapshot directory
-        let layout = disk::DiskLayout::new(&snapshot_dir);
+        if vector.len() != self.dimension {
+            return Err(anyhow::anyhow!(
+                "Vector dimension mismatch: expected {}, got {}",
+                self.dimension,
+                vector.len()
+            ));
+        }
 
-        // Export text index if any texts are indexed (Major Issue #6 fix)
-        let text_index_data = if self.text_index.export_texts().is_empty() {
-            None
-        } else {
-            Some(self.text_index.export_texts())
+        let mut record = Record {
+            id: id.clone(),
+            vector: vector.clone(),
+            metadata,
+            created_at: Utc::now().timestamp(),
+            deleted: false,
+            deleted_at: None,
+            expires_at,
+            namespace,
+            seq: self.changes.next_seq(),
+            version: self.records.get(&id).map_or(1, |r| r.version + 1),
+            text,
+            parent_id,
+            chunk_index,
         };
 
-        layout.save_all(
-            &self.records,
-            self.backend.get_id_to_idx_map(),
-            self.backend.get_idx_to_id_map(),
-            // Use actual next_idx counter, not map length (Critical Issue #2 fix)
-            self.backend.get_next_idx(),
-            self.dimension,
-            &self.config,    // Major Issue #7 fix: persist config in snapshots
-            text_index_data, // Major Issue #6 fix: persist text index in snapshots
-        )?;
+        self.ensure_capacity(1)?;
+        self.backend.insert(id.clone(), &vector)?;
+        self.log_upsert(&record, &vector)?;
+        self.drop_original(&mut record)?;
+        self.changes.removed.remove(&id);
+        self.field_indexes.insert(&id, &record.metadata);
+        self.records.insert(id.clone(), record);
+        self.emit_upsert(&id);
 
-        // Save HNSW index
-        if self.dimension > 0 {
-            self.backend.save_index(&layout.hnsw_path())?;
-        }
+        Ok(())
+    }
 
-        // Write snapshot metadata
-        let metadata = serde_json::json!({
-            "name": name,
-            "created_at": chrono::Utc::now().to_rfc3339(),
-            "record_count": self.records.len(),
-            "dimension": self.dimension,
-        });
+    pub fn remove(&mut self, id: &str) -> Result<()> {
+        self.backend.remove(id)?;
+        self.records
+            .remove(id)
+            .ok_or_else(|| anyhow::anyhow!("Record not found: {}", id))?;
+        if let Some(quantized) = &mut self.quantized {
+            quantized.remove(id);
+        }
+        self.field_indexes.remove(id);
 
-        std::fs::write(
-            snapshot_dir.join("snapshot.json"),
-            serde_json::to_string_pretty(&metadata)?,
-        )?;
+        // Clean up text index (Critical Issue #4 fix)
+        self.text_index.remove_document(id);
+        self.changes.tombstone(id);
+        self.log(LogEntry::Delete { id: id.to_string() })?;
+        self.emit(StoreEvent::Delete { id: id.to_string() });
 
         Ok(())
     }
 
-    /// List all available snapshots
+    /// Batch insert multiple vectors using parallel processing
     ///
-    /// Returns a vector of (snapshot_name, created_at, record_count)
-    pub fn list_snapshots(&self) -> Result<Vec<(String, String, usize)>> {
-        let snapshots_dir = self.root.join("snapshots");
+    /// This is significantly faster than calling upsert() in a loop when you have
+    /// many vectors to add at once. The HNSW index is built in parallel using rayon.
+    ///
+    /// The whole batch is validated before anything is written: if any record
+    /// has an empty id, an empty or wrongly sized vector, NaN components under
+    /// [`VectorPolicy::reject_nan`], or an id owned by another namespace, the
+    /// call fails with a [`BatchValidationError`] listing every bad record
+    /// and the store is unchanged. Use
+    /// [`batch_upsert_partial`](Self::batch_upsert_partial) to write the valid
+    /// records anyway.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, Metadata, make_record, BatchValidationError};
+    /// # let mut store = VecStore::open("data").unwrap();
+    /// let metadata = Metadata { fields: Default::default() };
+    /// let records = vec![
+    ///     make_record("doc1", vec![0.1, 0.2, 0.3], metadata.clone()),
+    ///     make_record("doc2", vec![0.4, 0.5, 0.6], metadata),
+    /// ];
+    /// if let Err(e) = store.batch_upsert(records) {
+    ///     if let Some(invalid) = e.downcast_ref::<BatchValidationError>() {
+    ///         for item in &invalid.errors {
+    ///             eprintln!("{}", item);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn batch_upsert(&mut self, items: impl IntoIterator<Item = Record>) -> Result<()> {
+        let items: Vec<_> = items.into_iter().collect();
 
-        if !snapshots_dir.exists() {
-            return Ok(Vec::new());
+        let errors = self.validate_batch(&items);
+        if !errors.is_empty() {
+            return Err(BatchValidationError { errors }.into());
         }
+        self.write_batch(items)
+    }
 
-        let mut snapshots = Vec::new();
+    /// Batch insert the valid records and report the rest
+    ///
+    /// Validates like [`batch_upsert`](Self::batch_upsert), but instead of
+    /// rejecting the batch, writes the records that pass and returns the ones
+    /// that don't with their position and reason.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, Metadata, make_record};
+    /// # let mut store = VecStore::open("data").unwrap();
+    /// let metadata = Metadata { fields: Default::default() };
+    /// let records = vec![
+    ///     make_record("doc1", vec![0.1, 0.2, 0.3], metadata.clone()),
+    ///     make_record("doc2", vec![0.4, 0.5], metadata),
+    /// ];
+    /// let result = store.batch_upsert_partial(records).unwrap();
+    /// assert_eq!(result.inserted, 1);
+    /// assert_eq!(result.failed[0].id, "doc2");
+    /// ```
+    pub fn batch_upsert_partial(
+        &mut self,
+        items: impl IntoIterator<Item = Record>,
+    ) -> Result<BatchUpsertResult> {
+        let items: Vec<_> = items.into_iter().collect();
 
-        for entry in std::fs::read_dir(&snapshots_dir)? {
-            let entry = entry?;
-            let path = entry.path();
+        let failed = self.validate_batch(&items);
+        let mut rejected = failed.iter().map(|e| e.index).peekable();
+        let valid: Vec<Record> = items
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| rejected.next_if_eq(index).is_none())
+            .map(|(_, record)| record)
+            .collect();
 
-            if path.is_dir() {
-                let metadata_path = path.join("snapshot.json");
-                if metadata_path.exists() {
-                    let content = std::fs::read_to_string(&metadata_path)?;
-                    let metadata: serde_json::Value = serde_json::from_str(&content)?;
+        let inserted = valid.len();
+        self.write_batch(valid)?;
+        Ok(BatchUpsertResult { inserted, failed })
+    }
 
-                    let name = metadata["name"].as_str().unwrap_or("unknown").to_string();
-                    let created_at = metadata["created_at"]
-                        .as_str()
-                        .unwrap_or("unknown")
-                        .to_string();
-                    let count = metadata["record_count"].as_u64().unwrap_or(0) as usize;
+    /// The dimension a batch's vectors must have: the store's, the
+    /// configured one, or else the first non-empty vector's
+    fn batch_dimension(&self, items: &[Record]) -> Option<usize> {
+        (self.dimension > 0)
+            .then_some(self.dimension)
+            .or(self.config.dimension)
+            .or_else(|| items.iter().map(|r| r.vector.len()).find(|&len| len > 0))
+    }
 
-                    snapshots.push((name, created_at, count));
-                }
+    /// Check every record of a batch without writing it, returning one error
+    /// per invalid record in batch order
+    ///
+    /// These are the checks [`batch_upsert`](Self::batch_upsert) runs before
+    /// writing; an empty result means it would accept the batch.
+    pub fn validate_batch(&self, items: &[Record]) -> Vec<BatchItemError> {
+        use rayon::prelude::*;
+
+        let dimension = self.batch_dimension(items).unwrap_or(0);
+        let reject_nan = self.config.vector_policy.reject_nan;
+        items
+            .par_iter()
+            .enumerate()
+            .filter_map(|(index, record)| {
+                let reason = if record.id.trim().is_empty() {
+                    "empty id".to_string()
+                } else if record.vector.is_empty() {
+                    "zero-dimension vector; vectors must have at least one dimension".to_string()
+                } else if record.vector.len() != dimension {
+                    format!(
+                        "vector dimension mismatch: expected {}, got {}",
+                        dimension,
+                        record.vector.len()
+                    )
+                } else if reject_nan && record.vector.iter().any(|x| !x.is_finite()) {
+                    "vector contains NaN or infinite values".to_string()
+                } else if let Err(e) =
+                    self.check_namespace_owner(&record.id, record.namespace.as_deref())
+                {
+                    e.to_string()
+                } else {
+                    return None;
+                };
+                Some(BatchItemError {
+                    index,
+                    id: record.id.clone(),
+                    reason,
+                })
+            })
+            .collect()
+    }
+
+    /// Write a validated batch
+    fn write_batch(&mut self, mut items: Vec<Record>) -> Result<()> {
+        use rayon::prelude::*;
+
+        if items.is_empty() {
+            return Ok(());
+        }
+
+        // Set dimension from the batch if needed
+        if self.dimension == 0 {
+            self.dimension = self.config.dimension.unwrap_or(items[0].vector.len());
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                self.backend =
+                    VectorBackend::new(self.dimension, self.config.distance, &self.config.hnsw)?;
+            }
+            #[cfg(target_arch = "wasm32")]
+            {
+                self.backend = VectorBackend::new(self.dimension);
             }
         }
 
-        // Sort by creation time (most recent first)
-        snapshots.sort_by(|a, b| b.1.cmp(&a.1));
+        items
+            .par_iter_mut()
+            .try_for_each(|record| self.apply_vector_policy(&record.id, &mut record.vector))?;
 
-        Ok(snapshots)
+        // Prepare data for batch insert
+        let batch_data: Vec<(Id, Vec<f32>)> = items
+            .iter()
+            .map(|r| (r.id.clone(), r.vector.clone()))
+            .collect();
+
+        // Grow the index up front so a batch crossing the capacity boundary fits
+        self.ensure_capacity(batch_data.len())?;
+
+        // Use parallel batch insert (much faster than sequential)
+        self.backend.batch_insert(batch_data)?;
+
+        // Update records
+        for mut record in items {
+            record.seq = self.changes.next_seq();
+            record.version = self.records.get(&record.id).map_or(1, |r| r.version + 1);
+            self.changes.removed.remove(&record.id);
+            self.log_upsert(&record, &record.vector)?;
+            self.drop_original(&mut record)?;
+            self.field_indexes.insert(&record.id, &record.metadata);
+            let id = record.id.clone();
+            self.records.insert(id.clone(), record);
+            self.emit_upsert(&id);
+        }
+
+        Ok(())
     }
 
-    /// Restore from a named snapshot
+    /// Batch insert with control over ids that already exist
     ///
-    /// This will replace the current store with the snapshot data.
+    /// An id conflicts when it is already in the store or appears earlier in
+    /// the batch; `policy` decides what happens to the incoming record. The
+    /// batch is validated and every conflict resolved before anything is
+    /// written, so an error (including any conflict under
+    /// [`UpsertPolicy::ErrorOnConflict`]) leaves the store unchanged.
     ///
     /// # Example
-    ///
     /// ```no_run
-    /// # use vecstore::VecStore;
-    /// # let temp_dir = tempfile::tempdir().unwrap();
-    /// # let mut store = VecStore::open(temp_dir.path()).unwrap();
-    /// store.restore_snapshot("backup-2024-01-15")?;
-    /// # Ok::<(), anyhow::Error>(())
+    /// # use vecstore::{VecStore, Metadata, make_record, UpsertPolicy};
+    /// # let mut store = VecStore::open("data").unwrap();
+    /// let metadata = Metadata { fields: Default::default() };
+    /// let records = vec![make_record("doc1", vec![0.1, 0.2, 0.3], metadata)];
+    /// let summary = store
+    ///     .batch_upsert_with_policy(records, UpsertPolicy::Skip)
+    ///     .unwrap();
+    /// println!("{} inserted, {} skipped", summary.inserted, summary.skipped);
     /// ```
-    pub fn restore_snapshot(&mut self, name: &str) -> Result<()> {
-        let snapshot_dir = self.root.join("snapshots").join(name);
-
-        if !snapshot_dir.exists() {
+    pub fn batch_upsert_with_policy(
+        &mut self,
+        items: impl IntoIterator<Item = Record>,
+        policy: UpsertPolicy,
+    ) -> Result<UpsertSummary> {
+        let (records, summary) = self.plan_upsert(items, policy)?;
+        if policy == UpsertPolicy::ErrorOnConflict && summary.conflicts > 0 {
             return Err(anyhow::anyhow!(
-                "Snapshot '{}' not found. Use list_snapshots() to see available snapshots.",
-                name
+                "{} record(s) conflict with existing ids; no records written",
+                summary.conflicts
             ));
         }
+        self.write_batch(records)?;
+        Ok(summary)
+    }
 
-        // Load from snapshot directory
-        let layout = disk::DiskLayout::new(&snapshot_dir);
+    /// Report what [`batch_upsert_with_policy`](Self::batch_upsert_with_policy)
+    /// would do without changing the store
+    ///
+    /// Validates ids, dimensions and the vector policy the same way and
+    /// fails with the same [`BatchValidationError`]. Under [`UpsertPolicy::ErrorOnConflict`]
+    /// conflicts are counted rather than reported as an error.
+    pub fn dry_run_upsert(
+        &self,
+        items: impl IntoIterator<Item = Record>,
+        policy: UpsertPolicy,
+    ) -> Result<UpsertSummary> {
+        self.plan_upsert(items, policy).map(|(_, summary)| summary)
+    }
 
-        if !layout.manifest_path().exists() {
-            return Err(anyhow::anyhow!(
-                "Snapshot '{}' is corrupted (missing manifest)",
-                name
-            ));
+    /// Validate `items` and resolve their id conflicts under `policy`
+    ///
+    /// Returns the records to write, at most one per id, and the summary.
+    fn plan_upsert(
+        &self,
+        items: impl IntoIterator<Item = Record>,
+        policy: UpsertPolicy,
+    ) -> Result<(Vec<Record>, UpsertSummary)> {
+        let items: Vec<Record> = items.into_iter().collect();
+        let errors = self.validate_batch(&items);
+        if !errors.is_empty() {
+            return Err(BatchValidationError { errors }.into());
         }
 
-        let (records, id_to_idx, idx_to_id, next_idx, dimension, loaded_config, text_index_data) =
-            layout.load_all()?;
-
-        self.records = records;
-        self.dimension = dimension;
+        let mut summary = UpsertSummary::default();
+        let mut records: Vec<Record> = Vec::new();
+        let mut positions: HashMap<Id, usize> = HashMap::new();
 
-        // Update config if loaded (Major Issue #7 fix)
-        if let Some(config) = loaded_config {
-            self.config = config;
-        }
+        for mut record in items {
+            let position = positions.get(&record.id).copied();
+            let existing = match position {
+                Some(i) => Some(&records[i].metadata),
+                None => self.records.get(&record.id).map(|r| &r.metadata),
+            };
+            match (existing, policy) {
+                (None, _) => summary.inserted += 1,
+                (Some(_), UpsertPolicy::Overwrite) => {
+                    summary.conflicts += 1;
+                    summary.updated += 1;
+                }
+                (Some(_), UpsertPolicy::Skip) => {
+                    summary.conflicts += 1;
+                    summary.skipped += 1;
+                    continue;
+                }
+                (Some(_), UpsertPolicy::ErrorOnConflict) => {
+                    summary.conflicts += 1;
+                    continue;
+                }
+                (Some(metadata), UpsertPolicy::MergeMetadata) => {
+                    summary.conflicts += 1;
+                    summary.updated += 1;
+                    let mut merged = metadata.clone();
+                    merged.fields.extend(record.metadata.fields);
+                    record.metadata = merged;
+                }
+            }
 
-        // Restore text index if available (Major Issue #6 fix)
-        self.text_index = hybrid::TextIndex::new();
-        if let Some(texts) = text_index_data {
-            self.text_index.import_texts(texts);
+            match position {
+                Some(i) => records[i] = record,
+                None => {
+                    positions.insert(record.id.clone(), records.len());
+                    records.push(record);
+                }
+            }
         }
+        Ok((records, summary))
+    }
 
-        // Recreate backend
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            self.backend = hnsw_backend::HnswBackend::restore(
-                dimension,
-                self.config.distance,
-                id_to_idx,
-                idx_to_id,
-                next_idx,
-            )?;
-        }
+    /// Optimize the index by rebuilding to remove "ghost" entries from deletions
+    ///
+    /// After many remove() operations, the HNSW index accumulates entries that
+    /// are no longer referenced. This method rebuilds the index from scratch to
+    /// reclaim memory and improve search performance.
+    ///
+    /// Returns the number of ghost entries removed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::VecStore;
+    /// # let mut store = VecStore::open("data").unwrap();
+    /// // After many deletions...
+    /// let removed = store.optimize().unwrap();
+    /// println!("Removed {} ghost entries", removed);
+    /// ```
+    pub fn optimize(&mut self) -> Result<usize> {
+        let vectors = self.full_vectors();
+        self.backend.optimize(&vectors)
+    }
 
-        #[cfg(target_arch = "wasm32")]
-        {
-            // WASM backend doesn't support restore (no persistence in browser)
-            // Create a new backend and rebuild from records
-            let mut backend = VectorBackend::new(dimension);
-            backend.set_mappings(id_to_idx, idx_to_id, next_idx);
-            self.backend = backend;
+    /// [`optimize`](Self::optimize), calling `progress` with the number of
+    /// vectors re-inserted so far and the total as the index is rebuilt
+    pub fn optimize_with_progress(
+        &mut self,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<usize> {
+        let vectors = self.full_vectors();
+        let total = vectors.len();
+        self.backend
+            .optimize_with_progress(&vectors, &mut |done| progress(done, total))
+    }
+
+    /// Every record's vector at full precision, dequantized where the
+    /// original was dropped
+    fn full_vectors(&self) -> Vec<(Id, Vec<f32>)> {
+        self.records
+            .values()
+            .map(|r| {
+                let vector = full_vector(r, self.quantized.as_ref()).into_owned();
+                (r.id.clone(), vector)
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(skip(self, q), fields(k = q.k, has_filter = q.filter.is_some(), dimension = q.vector.len()))]
+    pub fn query(&self, q: Query) -> Result<Vec<Neighbor>> {
+        self.query_profiled(q).map(|r| r.neighbors)
+    }
+
+    /// Query, returning a [`QueryProfile`] with the results if `q.profile` is set
+    ///
+    /// The profile splits the query's time between index search, scoring,
+    /// filtering and materializing results, and counts the candidates and
+    /// over-fetch rounds it took.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, Query};
+    /// # let store = VecStore::open("data")?;
+    /// let results = store.query_profiled(Query::new(vec![0.1, 0.2]).with_profile())?;
+    /// if let Some(profile) = results.profile {
+    ///     println!("{:?} in the index, {:?} filtering", profile.index_search, profile.filtering);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn query_profiled(&self, q: Query) -> Result<ProfiledResults> {
+        self.run_query(q, self.reranker.as_deref())
+    }
+
+    /// Query with a one-off reranker, ignoring any registered one
+    ///
+    /// Fetches `q.rerank_fetch_k` candidates (default 10 × `k`), passes them to
+    /// `reranker` with the query text and vector, and keeps the first `k`.
+    pub fn query_with_reranker(
+        &self,
+        q: Query,
+        reranker: &dyn QueryReranker,
+    ) -> Result<Vec<Neighbor>> {
+        self.query_with_reranker_profiled(q, reranker)
+            .map(|r| r.neighbors)
+    }
+
+    /// [`query_with_reranker`](Self::query_with_reranker), returning a
+    /// [`QueryProfile`] if `q.profile` is set
+    pub fn query_with_reranker_profiled(
+        &self,
+        q: Query,
+        reranker: &dyn QueryReranker,
+    ) -> Result<ProfiledResults> {
+        self.run_query(q, Some(reranker))
+    }
+
+    /// Run a query, timing it and logging it if slow or timed out
+    fn run_query(&self, q: Query, reranker: Option<&dyn QueryReranker>) -> Result<ProfiledResults> {
+        let start = std::time::Instant::now();
+        let mut profile = QueryProfile::default();
+        let wants_profile = q.profile;
+        let threshold = self
+            .config
+            .slow_query_threshold_ms
+            .map(std::time::Duration::from_millis);
+        let logged = (threshold.is_some() || q.cancel.is_some()).then(|| {
+            (
+                q.k,
+                q.filter.as_ref().map(|f| f.to_string()),
+                q.namespace.clone(),
+            )
+        });
+        let log = |profile: &QueryProfile, timed_out: bool| {
+            let Some((k, filter, namespace)) = logged.clone() else {
+                return;
+            };
+            tracing::warn!(total = ?profile.total, k, filter = filter.as_deref(), timed_out, "Slow query");
+            if let Ok(mut log) = self.slow_queries.lock() {
+                log.record(SlowQuery {
+                    at: Utc::now().timestamp(),
+                    k,
+                    filter,
+                    namespace,
+                    profile: profile.clone(),
+                    timed_out,
+                });
+            }
+        };
+
+        let options = q.options.clone();
+        let results = match reranker {
+            Some(reranker) => self.rerank_query(q, reranker, &mut profile),
+            None => self.query_unranked(q, &mut profile),
+        };
+        let results = match results {
+            Ok(results) => results,
+            Err(e) => {
+                if e.downcast_ref::<QueryInterrupted>()
+                    .is_some_and(|stopped| !stopped.cancelled)
+                {
+                    profile.total = start.elapsed();
+                    log(&profile, true);
+                }
+                return Err(e);
+            }
+        };
+        let neighbors = QueryProfile::time(&mut profile.materialization, || {
+            self.shape_results(results, &options)
+        });
+        profile.results = neighbors.len();
+        profile.total = start.elapsed();
+
+        if threshold.is_some_and(|threshold| profile.total >= threshold) {
+            log(&profile, false);
         }
 
-        // Rebuild HNSW index from vectors
-        if dimension > 0 {
-            let vectors: Vec<(Id, Vec<f32>)> = self
-                .records
-                .iter()
-                .map(|(id, record)| (id.clone(), record.vector.clone()))
-                .collect();
-            self.backend.rebuild_from_vectors(&vectors)?;
+        Ok(ProfiledResults {
+            neighbors,
+            profile: wants_profile.then_some(profile),
+        })
+    }
+
+    /// Fetch `q.rerank_fetch_k` candidates and keep the reranker's first `k`
+    fn rerank_query(
+        &self,
+        mut q: Query,
+        reranker: &dyn QueryReranker,
+        profile: &mut QueryProfile,
+    ) -> Result<Vec<Neighbor>> {
+        let k = q.k;
+        let fetch_k = q
+            .rerank_fetch_k
+            .unwrap_or_else(|| k.saturating_mul(10))
+            .max(k);
+        q.k = fetch_k;
+        q.ef_search = q.ef_search.map(|ef| ef.max(fetch_k));
+
+        let text = q.text.take();
+        let vector = q.vector.clone();
+        let cancel = q.cancel.clone();
+        let candidates = self.query_unranked(q, profile)?;
+        if let Some(cancel) = cancel {
+            cancel.check()?;
         }
 
-        Ok(())
+        let mut reranked = QueryProfile::time(&mut profile.scoring, || {
+            reranker.rerank(text.as_deref(), &vector, candidates)
+        })?;
+        reranked.truncate(k);
+        Ok(reranked)
     }
 
-    /// Delete a named snapshot
-    pub fn delete_snapshot(&self, name: &str) -> Result<()> {
-        let snapshot_dir = self.root.join("snapshots").join(name);
+    /// Queries slower than the configured threshold, oldest first
+    ///
+    /// Holds the last [`SLOW_QUERY_LOG_CAPACITY`] queries that took at least
+    /// [`Config::slow_query_threshold_ms`], each with its filter and
+    /// [`QueryProfile`]. Empty unless a threshold is set with
+    /// [`VecStoreBuilder::slow_query_threshold`] or
+    /// [`set_slow_query_threshold`](Self::set_slow_query_threshold), apart
+    /// from queries stopped at their [`QueryCancel`] deadline, which are kept
+    /// with `timed_out` set.
+    pub fn slow_queries(&self) -> Vec<SlowQuery> {
+        self.slow_queries
+            .lock()
+            .map(|log| log.entries())
+            .unwrap_or_default()
+    }
 
-        if !snapshot_dir.exists() {
-            return Err(anyhow::anyhow!("Snapshot '{}' not found", name));
+    /// Number of slow queries seen since the store was opened, including
+    /// ones no longer in the log
+    pub fn slow_query_count(&self) -> u64 {
+        self.slow_queries.lock().map_or(0, |log| log.total())
+    }
+
+    /// Empty the slow-query log
+    pub fn clear_slow_queries(&self) {
+        if let Ok(mut log) = self.slow_queries.lock() {
+            log.clear();
         }
+    }
 
-        std::fs::remove_dir_all(&snapshot_dir)
-            .with_context(|| format!("Failed to delete snapshot: {}", name))?;
+    /// Change the slow-query threshold (None stops logging)
+    ///
+    /// Persisted on the next [`save`](Self::save).
+    pub fn set_slow_query_threshold(&mut self, threshold: Option<std::time::Duration>) {
+        self.config.slow_query_threshold_ms = threshold.map(|t| t.as_millis() as u64);
+        self.settings_changed();
+    }
 
-        Ok(())
+    /// Model the store's vectors were embedded with, if one was recorded
+    pub fn embedding_model(&self) -> Option<&EmbeddingModel> {
+        self.config.embedding_model.as_ref()
     }
 
-    /// Index text content for a document (enables hybrid search)
+    /// Check that text embedded with `model` can be stored or searched here
     ///
-    /// # Example
-    ///
-    /// ```
-    /// # use vecstore::VecStore;
-    /// # use std::collections::HashMap;
-    /// # let temp_dir = tempfile::tempdir().unwrap();
-    /// # let mut store = VecStore::open(temp_dir.path()).unwrap();
-    /// # let mut meta = vecstore::Metadata { fields: HashMap::new() };
-    /// # store.upsert("doc1".into(), vec![1.0, 0.0, 0.0], meta).unwrap();
-    /// store.index_text("doc1", "Rust is a systems programming language")?;
-    /// # Ok::<(), anyhow::Error>(())
-    /// ```
-    pub fn index_text(&mut self, id: &str, text: impl Into<String>) -> Result<()> {
-        if !self.records.contains_key(id) {
-            return Err(anyhow::anyhow!(
-                "Cannot index text for non-existent document: {}",
-                id
-            ));
+    /// Fails with an [`EmbeddingModelMismatch`] when the store records a
+    /// different model, and when it records none, if `model`'s dimension
+    /// differs from that of the vectors already stored.
+    pub fn check_embedding_model(&self, model: &EmbeddingModel) -> Result<()> {
+        match &self.config.embedding_model {
+            Some(stored) if stored != model => Err(EmbeddingModelMismatch {
+                stored: stored.clone(),
+                requested: model.clone(),
+            }
+            .into()),
+            Some(_) => Ok(()),
+            None if self.dimension != 0 && self.dimension != model.dimension => {
+                Err(anyhow::anyhow!(
+                    "Store holds {}-dimensional vectors but '{}' embeds to {} dimensions",
+                    self.dimension,
+                    model.name,
+                    model.dimension
+                ))
+            }
+            None => Ok(()),
         }
+    }
 
-        self.text_index.index_document(id.to_string(), text.into());
+    /// Record the model the store's vectors are embedded with
+    ///
+    /// Checked with [`check_embedding_model`](Self::check_embedding_model)
+    /// first, so a store never switches models. Persisted on the next
+    /// [`save`](Self::save).
+    pub fn set_embedding_model(&mut self, model: EmbeddingModel) -> Result<()> {
+        self.check_embedding_model(&model)?;
+        self.config.embedding_model = Some(model);
+        self.settings_changed();
         Ok(())
     }
 
-    /// Perform hybrid search combining vector similarity and keyword search
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use vecstore::{VecStore, HybridQuery};
-    /// # let temp_dir = tempfile::tempdir().unwrap();
-    /// # let store = VecStore::open(temp_dir.path()).unwrap();
-    /// let query = HybridQuery {
-    ///     vector: vec![0.1, 0.2, 0.3],
-    ///     keywords: "rust programming".into(),
-    ///     k: 10,
-    ///     filter: None,
-    ///     alpha: 0.7, // 70% vector, 30% keyword
-    /// };
-    ///
-    /// let results = store.hybrid_query(query)?;
-    /// # Ok::<(), anyhow::Error>(())
-    /// ```
-    #[tracing::instrument(skip(self, query), fields(k = query.k, has_keywords = !query.keywords.is_empty(), alpha = query.alpha))]
-    pub fn hybrid_query(&self, query: HybridQuery) -> Result<Vec<Neighbor>> {
+    /// Apply the query's result options: attach vectors and text, trim metadata
+    fn shape_results(&self, mut results: Vec<Neighbor>, options: &QueryOptions) -> Vec<Neighbor> {
+        for neighbor in &mut results {
+            if options.include_vector {
+                neighbor.vector = self
+                    .records
+                    .get(&neighbor.id)
+                    .map(|r| full_vector(r, self.quantized.as_ref()).into_owned());
+            }
+            if options.include_text {
+                neighbor.text = self.records.get(&neighbor.id).and_then(|r| r.text.clone());
+            }
+            if !options.include_metadata {
+                neighbor.metadata.fields.clear();
+            } else if let Some(paths) = &options.metadata_fields {
+                neighbor.metadata = neighbor.metadata.select(paths);
+            }
+        }
+        results
+    }
+
+    fn query_unranked(&self, mut q: Query, profile: &mut QueryProfile) -> Result<Vec<Neighbor>> {
         if self.dimension == 0 {
             return Ok(Vec::new());
         }
 
-        if query.vector.len() != self.dimension {
+        if q.vector.len() != self.dimension {
             return Err(anyhow::anyhow!(
                 "Query dimension mismatch: expected {}, got {}",
                 self.dimension,
-                query.vector.len()
+                q.vector.len()
             ));
         }
 
-        // Get vector similarity scores
-        let fetch_size = if query.filter.is_some() {
-            // Using saturating_mul to prevent overflow (Major Issue #10 fix)
-            std::cmp::min(query.k.saturating_mul(10), self.records.len())
-        } else {
-            // Using saturating_mul to prevent overflow (Major Issue #10 fix)
-            std::cmp::min(query.k.saturating_mul(2), self.records.len()) // Fetch more for ranking
-        };
+        validate_ef_search(&q)?;
+        self.apply_query_policy(&mut q.vector)?;
+        check_cancel(&q)?;
 
-        #[cfg(not(target_arch = "wasm32"))]
-        let vector_results = self.backend.search(&query.vector, fetch_size);
-        #[cfg(target_arch = "wasm32")]
-        let vector_results = self.backend.search(&query.vector, fetch_size)?;
+        let candidates = self.search_candidates(&q, profile)?;
 
-        // Get BM25 scores if keywords provided
-        let bm25_scores = if !query.keywords.is_empty() {
-            self.text_index.bm25_scores(&query.keywords)
-        } else {
-            HashMap::new()
-        };
+        let now = Utc::now().timestamp();
+        let accepted = QueryProfile::time(&mut profile.filtering, || {
+            candidates
+                .into_iter()
+                .filter_map(|(id, score)| {
+                    // Skip soft-deleted and expired records
+                    let record = self.records.get(&id).filter(|r| r.is_live(now))?;
+                    filters::admits(&q, record).then_some((score, record))
+                })
+                .take(q.k)
+                .collect::<Vec<_>>()
+        });
 
-        // Combine scores
-        let combined = hybrid::combine_scores(vector_results, bm25_scores, query.alpha);
+        let results = QueryProfile::time(&mut profile.materialization, || {
+            accepted
+                .into_iter()
+                .map(|(score, record)| Neighbor {
+                    id: record.id.clone(),
+                    score,
+                    metadata: record.metadata.clone(),
+                    vector: None,
+                    version: record.version,
+                    text: None,
+                })
+                .collect()
+        });
 
-        // Apply filter and build results
-        let mut results = Vec::new();
+        Ok(results)
+    }
 
-        for (id, score) in combined.into_iter().take(query.k * 2) {
-            if let Some(record) = self.records.get(&id) {
-                // Skip soft-deleted records (Major Issue #12 fix)
-                if record.deleted {
-                    continue;
-                }
+    /// Search with adaptive over-fetching until `q.k` candidates survive
+    ///
+    /// Removed and replaced records stay in the HNSW graph, soft-deleted ones
+    /// stay in the index, and a filter rejects more. The first search asks
+    /// for enough extra candidates to cover the deletion ratio seen on
+    /// earlier queries (and 10x more with a filter); each retry doubles the
+    /// fetch size until `q.k` live, filter-passing candidates are found or
+    /// the whole index has been searched. HNSW can miss points even then, so
+    /// a search that exhausts the index short of `q.k` candidates is
+    /// answered by an exact scan of the live records.
+    ///
+    /// A namespace restricts candidates like a filter. A namespace smaller
+    /// than `exact_search_threshold` is scanned exactly instead, since most
+    /// of the index belongs to other namespaces.
+    ///
+    /// When field indexes narrow the filter, the records they leave are
+    /// scanned exactly if there are few of them (under `exact_search_threshold`
+    /// or 1 in `INDEXED_SCAN_RATIO` records); otherwise their share of the
+    /// store sets how far to over-fetch.
+    fn search_candidates(&self, q: &Query, profile: &mut QueryProfile) -> Result<Vec<(Id, f32)>> {
+        let small_namespace = q
+            .namespace
+            .as_deref()
+            .is_some_and(|ns| self.namespace_len(ns) < self.config.exact_search_threshold);
+        if q.exact || small_namespace || self.records.len() < self.config.exact_search_threshold {
+            profile.exact = true;
+            profile.candidates = self.records.len();
+            return Ok(QueryProfile::time(&mut profile.scoring, || {
+                exact::search(
+                    self.records.values(),
+                    self.quantized.as_ref(),
+                    self.config.distance,
+                    q,
+                )
+            }));
+        }
 
-                // Apply filter if present
-                if let Some(ref filter) = query.filter {
-                    if !filters::evaluate_filter(filter, &record.metadata) {
-                        continue;
+        let indexed = q
+            .filter
+            .as_ref()
+            .and_then(|filter| self.field_indexes.candidates(filter));
+        if let Some(ids) = &indexed {
+            let scan_limit = self
+                .config
+                .exact_search_threshold
+                .max(self.records.len() / INDEXED_SCAN_RATIO);
+            if ids.len() < scan_limit {
+                profile.exact = true;
+                profile.candidates = ids.len();
+                return Ok(QueryProfile::time(&mut profile.scoring, || {
+                    exact::search(
+                        ids.iter().filter_map(|id| self.records.get(*id)),
+                        self.quantized.as_ref(),
+                        self.config.distance,
+                        q,
+                    )
+                }));
+            }
+        }
+
+        let index_len = self.index_len();
+        let deleted_ratio = f64::from_bits(self.deleted_ratio.load(Ordering::Relaxed));
+
+        let wanted = match &indexed {
+            Some(ids) => {
+                let share = ids.len().max(1) as f64 / self.records.len() as f64;
+                (q.k as f64 / share).ceil() as usize
+            }
+            // Using saturating_mul to prevent overflow (Critical Issue #10 fix)
+            None if q.filter.is_some() || q.namespace.is_some() => q.k.saturating_mul(10),
+            None => q.k,
+        };
+        let mut fetch_size =
+            ((wanted as f64 / (1.0 - deleted_ratio)).ceil() as usize).min(index_len);
+
+        let now = Utc::now().timestamp();
+        loop {
+            let candidates = self.search_with_fetch_size(q, fetch_size, profile)?;
+            profile.candidates += candidates.len();
+            profile.fetch_rounds += 1;
+
+            let (live, accepted) = QueryProfile::time(&mut profile.filtering, || {
+                let mut live = 0;
+                let mut accepted = 0;
+                for (id, _) in &candidates {
+                    if let Some(record) = self.records.get(id).filter(|r| r.is_live(now)) {
+                        live += 1;
+                        if filters::admits(q, record) {
+                            accepted += 1;
+                        }
                     }
                 }
+                (live, accepted)
+            });
 
-                results.push(Neighbor {
-                    id: id.clone(),
-                    score,
-                    metadata: record.metadata.clone(),
-                });
+            if fetch_size > 0 {
+                let ratio = (1.0 - live as f64 / fetch_size as f64).clamp(0.0, MAX_DELETED_RATIO);
+                self.deleted_ratio.store(ratio.to_bits(), Ordering::Relaxed);
+            }
 
-                if results.len() >= query.k {
-                    break;
-                }
+            if accepted >= q.k {
+                return Ok(candidates);
+            }
+            if fetch_size >= index_len {
+                profile.exact = true;
+                profile.candidates += self.records.len();
+                return Ok(QueryProfile::time(&mut profile.scoring, || {
+                    exact::search(
+                        self.records.values(),
+                        self.quantized.as_ref(),
+                        self.config.distance,
+                        q,
+                    )
+                }));
             }
+            check_cancel(q)?;
+            fetch_size = fetch_size.saturating_mul(2).max(1).min(index_len);
         }
-
-        Ok(results)
     }
 
-    /// Check if a document has indexed text
-    pub fn has_text(&self, id: &str) -> bool {
-        self.text_index.has_text(id)
+    /// Search the backend for `fetch_size` candidates, honouring `q.ef_search`
+    ///
+    /// A quantized index that kept its f32 originals is searched for
+    /// `RERANK_FACTOR` times as many candidates, which are re-ranked at full
+    /// precision.
+    fn search_with_fetch_size(
+        &self,
+        q: &Query,
+        fetch_size: usize,
+        profile: &mut QueryProfile,
+    ) -> Result<Vec<(Id, f32)>> {
+        if self.reranks_originals() {
+            let wide = fetch_size
+                .saturating_mul(quantized::RERANK_FACTOR)
+                .min(self.index_len());
+            let candidates = self.search_index(q, wide, profile)?;
+            return Ok(QueryProfile::time(&mut profile.scoring, || {
+                exact::rescore(
+                    &self.records,
+                    self.config.distance,
+                    &q.vector,
+                    candidates,
+                    fetch_size,
+                )
+            }));
+        }
+        self.search_index(q, fetch_size, profile)
     }
 
-    /// Get the indexed text for a document
-    pub fn get_text(&self, id: &str) -> Option<&str> {
-        self.text_index.get_text(id)
+    fn search_index(
+        &self,
+        q: &Query,
+        fetch_size: usize,
+        profile: &mut QueryProfile,
+    ) -> Result<Vec<(Id, f32)>> {
+        let requested = q.ef_search.or(self.config.default_ef_search);
+        // The candidate list must be able to hold every over-fetched result
+        #[cfg(not(target_arch = "wasm32"))]
+        let ef_search = requested
+            .unwrap_or(hnsw_backend::DEFAULT_EF_SEARCH)
+            .max(fetch_size);
+        #[cfg(target_arch = "wasm32")]
+        let ef_search = requested.unwrap_or(fetch_size).max(fetch_size);
+        profile.ef_search = profile.ef_search.max(Some(ef_search));
+
+        QueryProfile::time(&mut profile.index_search, || match requested {
+            Some(_) => self
+                .backend
+                .search_with_ef(&q.vector, fetch_size, ef_search),
+            #[cfg(not(target_arch = "wasm32"))]
+            None => Ok(self.backend.search(&q.vector, fetch_size)),
+            #[cfg(target_arch = "wasm32")]
+            None => self.backend.search(&q.vector, fetch_size),
+        })
     }
 
-    /// Get all records in the store
+    /// Measure the index's recall@`k` over a range of `ef_search` values
     ///
-    /// Returns a vector of all records, useful for iteration and export.
-    pub fn list_all(&self) -> Vec<Record> {
-        self.records.values().cloned().collect()
+    /// Uses up to `sample_queries` stored vectors, spread evenly over the
+    /// records, as queries. Each is answered by the HNSW index (whatever
+    /// `exact_search_threshold` says) and compared with an exact scan.
+    pub fn measure_recall(&self, sample_queries: usize, k: usize) -> Result<RecallReport> {
+        let queries = self.sample_vectors(sample_queries);
+        self.measure_recall_with_queries(&queries, k)
     }
 
-    /// Soft delete a record (mark as deleted without removing)
+    /// Measure the index's recall@`k` for the given query vectors
     ///
-    /// Soft deletes allow deferred cleanup and potential recovery.
-    /// Call `compact()` to permanently remove soft-deleted records.
-    ///
-    /// # Arguments
-    /// * `id` - ID of the record to soft delete
+    /// See [`measure_recall`](Self::measure_recall).
+    pub fn measure_recall_with_queries(
+        &self,
+        queries: &[Vec<f32>],
+        k: usize,
+    ) -> Result<RecallReport> {
+        self.measure_recall_over(queries, k, recall::ef_ladder(k))
+    }
+
+    /// Measure the index's recall@`k` at the given `ef_search` values
     ///
-    /// # Returns
-    /// * `Ok(true)` if record was soft deleted
-    /// * `Ok(false)` if record doesn't exist or was already deleted
-    pub fn soft_delete(&mut self, id: &str) -> Result<bool> {
-        if let Some(record) = self.records.get_mut(id) {
-            if !record.deleted {
-                record.deleted = true;
-                record.deleted_at = Some(Utc::now().timestamp());
-                return Ok(true);
-            }
-        }
-        Ok(false)
+    /// Like [`measure_recall`](Self::measure_recall), for `ef_search` values
+    /// chosen by the caller. Values below `k` are raised to `k`.
+    pub fn measure_recall_at(
+        &self,
+        sample_queries: usize,
+        k: usize,
+        ef_searches: &[usize],
+    ) -> Result<RecallReport> {
+        let mut ladder: Vec<usize> = ef_searches.iter().map(|&ef| ef.max(k)).collect();
+        ladder.sort_unstable();
+        ladder.dedup();
+        self.measure_recall_over(&self.sample_vectors(sample_queries), k, ladder)
     }
 
-    /// Restore a soft-deleted record
-    ///
-    /// # Arguments
-    /// * `id` - ID of the record to restore
-    ///
-    /// # Returns
-    /// * `Ok(true)` if record was restored
-    /// * `Ok(false)` if record doesn't exist or wasn't deleted
-    pub fn restore(&mut self, id: &str) -> Result<bool> {
-        if let Some(record) = self.records.get_mut(id) {
-            if record.deleted {
-                record.deleted = false;
-                record.deleted_at = None;
-                return Ok(true);
-            }
-        }
-        Ok(false)
+    fn measure_recall_over(
+        &self,
+        queries: &[Vec<f32>],
+        k: usize,
+        ladder: Vec<usize>,
+    ) -> Result<RecallReport> {
+        let truth = self.ground_truth(queries, k)?;
+        let points = ladder
+            .into_iter()
+            .map(|ef_search| self.recall_at(&truth, ef_search))
+            .collect::<Result<_>>()?;
+        Ok(RecallReport {
+            k,
+            queries: truth.queries.len(),
+            points,
+        })
     }
 
-    /// Permanently remove all soft-deleted records (compaction)
+    /// The smallest `ef_search` giving at least `target_recall` recall@10
     ///
-    /// This frees up memory and disk space by removing records marked
-    /// for deletion. Returns the number of records removed.
-    ///
-    /// # Returns
-    /// Number of records permanently removed
-    pub fn compact(&mut self) -> Result<usize> {
-        // Find all soft-deleted record IDs
-        let deleted_ids: Vec<String> = self
-            .records
-            .values()
-            .filter(|r| r.deleted)
-            .map(|r| r.id.clone())
-            .collect();
-
-        let count = deleted_ids.len();
-
-        // Permanently remove them
-        for id in deleted_ids {
-            self.backend.remove(&id)?;
-            self.records.remove(&id);
-
-            // Clean up text index (Critical Issue #4 fix)
-            self.text_index.remove_document(&id);
-        }
-
-        Ok(count)
-    }
-
-    /// Get count of soft-deleted records
-    pub fn deleted_count(&self) -> usize {
-        self.records.values().filter(|r| r.deleted).count()
-    }
-
-    /// Get count of active (non-deleted) records
-    pub fn active_count(&self) -> usize {
-        self.records.values().filter(|r| !r.deleted).count()
+    /// Binary-searches `ef_search` over `DEFAULT_RECALL_SAMPLES` sampled
+    /// stored vectors. Use [`suggest_ef_search_with`](Self::suggest_ef_search_with)
+    /// for another sample size or `k`.
+    pub fn suggest_ef_search(&self, target_recall: f64) -> Result<usize> {
+        self.suggest_ef_search_with(target_recall, DEFAULT_RECALL_SAMPLES, DEFAULT_RECALL_K)
     }
 
-    /// List all soft-deleted records
-    pub fn list_deleted(&self) -> Vec<Record> {
-        self.records
-            .values()
-            .filter(|r| r.deleted)
-            .cloned()
-            .collect()
-    }
+    /// The smallest `ef_search` giving at least `target_recall` recall@`k`
+    /// over up to `sample_queries` sampled stored vectors
+    ///
+    /// Fails if even an `ef_search` as large as the index falls short.
+    pub fn suggest_ef_search_with(
+        &self,
+        target_recall: f64,
+        sample_queries: usize,
+        k: usize,
+    ) -> Result<usize> {
+        if !(target_recall > 0.0 && target_recall <= 1.0) {
+            return Err(anyhow::anyhow!(
+                "Target recall must be in (0, 1], got {}",
+                target_recall
+            ));
+        }
+        let truth = self.ground_truth(&self.sample_vectors(sample_queries), k)?;
+        let reaches = |ef_search| -> Result<bool> {
+            Ok(self.recall_at(&truth, ef_search)?.recall >= target_recall)
+        };
 
-    /// List all active (non-deleted) records
-    pub fn list_active(&self) -> Vec<Record> {
-        self.records
-            .values()
-            .filter(|r| !r.deleted)
-            .cloned()
-            .collect()
+        // Recall grows with ef_search, so find the first value reaching the target
+        let mut low = k;
+        let mut high = self.index_len().max(k);
+        if reaches(low)? {
+            return Ok(low);
+        }
+        if !reaches(high)? {
+            return Err(anyhow::anyhow!(
+                "No ef_search up to {} reaches recall@{} of {}",
+                high,
+                k,
+                target_recall
+            ));
+        }
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if reaches(mid)? {
+                high = mid;
+            } else {
+                low = mid;
+            }
+        }
+        Ok(high)
     }
 
-    /// Execute a batch of mixed operations (upsert, delete, soft delete, restore, update metadata)
-    ///
-    /// This is significantly faster than executing operations individually and provides
-    /// transactional-like semantics with detailed error reporting for each failed operation.
+    /// Groups of live records whose vectors have a cosine similarity of at
+    /// least `threshold`
     ///
-    /// # Arguments
-    /// * `operations` - Vector of BatchOperation variants to execute
-    ///
-    /// # Returns
-    /// * `BatchResult` with success/failure counts, detailed errors, and duration
+    /// Each vector queries the index for its [`DEDUPE_NEIGHBORS`] nearest
+    /// neighbours, whatever the store's metric, and each candidate is kept if
+    /// its exact cosine similarity reaches `threshold`. The store is only
+    /// read, so removing the redundant records of one group can't hide the
+    /// candidates of another. Groups are ordered by their oldest record's id.
     ///
     /// # Example
     /// ```no_run
-    /// # use vecstore::{VecStore, BatchOperation, Metadata};
-    /// # use std::collections::HashMap;
-    /// # let mut store = VecStore::open("./data").unwrap();
-    /// let operations = vec![
-    ///     BatchOperation::Upsert {
-    ///         id: "doc1".into(),
-    ///         vector: vec![0.1, 0.2, 0.3],
-    ///         metadata: Metadata { fields: HashMap::new() },
-    ///     },
-    ///     BatchOperation::Delete { id: "doc2".into() },
-    ///     BatchOperation::SoftDelete { id: "doc3".into() },
-    /// ];
-    ///
-    /// let result = store.batch_execute(operations).unwrap();
-    /// println!("Succeeded: {}, Failed: {}", result.succeeded, result.failed);
+    /// # use vecstore::{DuplicateKeep, VecStore};
+    /// let mut store = VecStore::open("./data")?;
+    /// for group in store.near_duplicates(0.98)? {
+    ///     for id in group.redundant(DuplicateKeep::First) {
+    ///         store.remove(id)?;
+    ///     }
+    /// }
+    /// store.save()?;
+    /// # Ok::<(), anyhow::Error>(())
     /// ```
-    pub fn batch_execute(&mut self, operations: Vec<BatchOperation>) -> Result<BatchResult> {
-        let start = std::time::Instant::now();
-        let mut succeeded = 0;
-        let mut failed = 0;
-        let mut errors = Vec::new();
+    pub fn near_duplicates(&self, threshold: f32) -> Result<Vec<NearDuplicateGroup>> {
+        if !(threshold > 0.0 && threshold <= 1.0) {
+            return Err(anyhow::anyhow!(
+                "Duplicate threshold must be in (0, 1], got {}",
+                threshold
+            ));
+        }
 
-        for (index, op) in operations.into_iter().enumerate() {
-            let result = match &op {
-                BatchOperation::Upsert {
-                    id,
-                    vector,
-                    metadata,
-                } => self
-                    .upsert(id.clone(), vector.clone(), metadata.clone())
-                    .map_err(|e| (format!("upsert({})", id), e)),
-                BatchOperation::Delete { id } => {
-                    self.remove(id).map_err(|e| (format!("delete({})", id), e))
-                }
-                BatchOperation::SoftDelete { id } => self
-                    .soft_delete(id)
-                    .map(|_| ())
-                    .map_err(|e| (format!("soft_delete({})", id), e)),
-                BatchOperation::Restore { id } => self
-                    .restore(id)
-                    .map(|_| ())
-                    .map_err(|e| (format!("restore({})", id), e)),
-                BatchOperation::UpdateMetadata { id, metadata } => {
-                    if let Some(record) = self.records.get_mut(id) {
-                        record.metadata = metadata.clone();
-                        Ok(())
-                    } else {
-                        Err((
-                            format!("update_metadata({})", id),
-                            anyhow::anyhow!("Record not found: {}", id),
-                        ))
-                    }
-                }
-            };
+        let now = Utc::now().timestamp();
+        let mut live: Vec<&Record> = self.records.values().filter(|r| r.is_live(now)).collect();
+        live.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        let position: HashMap<&str, usize> = live
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.id.as_str(), i))
+            .collect();
+        let vectors: Vec<Vec<f32>> = live
+            .iter()
+            .map(|r| full_vector(r, self.quantized.as_ref()).into_owned())
+            .collect();
 
-            match result {
-                Ok(_) => succeeded += 1,
-                Err((operation, error)) => {
-                    failed += 1;
-                    errors.push(BatchError {
-                        index,
-                        operation,
-                        error: error.to_string(),
-                    });
+        #[cfg(not(target_arch = "wasm32"))]
+        let ef_search = hnsw_backend::DEFAULT_EF_SEARCH.max(DEDUPE_NEIGHBORS);
+        #[cfg(target_arch = "wasm32")]
+        let ef_search = DEDUPE_NEIGHBORS;
+        let mut groups = dedupe::DisjointSet::new(live.len());
+        let mut pairs = Vec::new();
+        for (i, vector) in vectors.iter().enumerate() {
+            let candidates =
+                self.backend
+                    .search_with_ef(vector, DEDUPE_NEIGHBORS + 1, ef_search)?;
+            for (id, _) in candidates {
+                // The index may find a pair from only one side, so every side asks
+                let Some(&j) = position.get(id.as_str()) else {
+                    continue;
+                };
+                if j == i || vectors[j].len() != vector.len() {
+                    continue;
+                }
+                let similarity = dedupe::similarity(vector, &vectors[j]);
+                if similarity >= threshold {
+                    groups.union(i, j);
+                    pairs.push((i.min(j), similarity));
                 }
             }
         }
 
-        Ok(BatchResult {
-            succeeded,
-            failed,
-            errors,
-            duration_ms: start.elapsed().as_secs_f64() * 1000.0,
-        })
+        let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for i in 0..live.len() {
+            members.entry(groups.find(i)).or_default().push(i);
+        }
+        let mut min_similarity: HashMap<usize, f32> = HashMap::new();
+        for (i, similarity) in pairs {
+            let root = groups.find(i);
+            let min = min_similarity.entry(root).or_insert(similarity);
+            *min = min.min(similarity);
+        }
+
+        let mut duplicates: Vec<NearDuplicateGroup> = members
+            .into_iter()
+            .filter(|(_, members)| members.len() > 1)
+            .map(|(root, members)| {
+                let mut records: Vec<&Record> = members.into_iter().map(|i| live[i]).collect();
+                records.sort_by(|a, b| {
+                    (a.created_at, a.seq, &a.id).cmp(&(b.created_at, b.seq, &b.id))
+                });
+                NearDuplicateGroup {
+                    ids: records.into_iter().map(|r| r.id.clone()).collect(),
+                    min_similarity: min_similarity[&root],
+                }
+            })
+            .collect();
+        duplicates.sort_by(|a, b| a.ids[0].cmp(&b.ids[0]));
+        Ok(duplicates)
     }
 
-    /// Convenience method: Delete a record (alias for remove)
-    ///
-    /// This provides consistency with BatchOperation::Delete naming
-    pub fn delete(&mut self, id: &str) -> Result<()> {
-        self.remove(id)
+    /// Up to `n` live stored vectors, evenly spaced through the records in id order
+    fn sample_vectors(&self, n: usize) -> Vec<Vec<f32>> {
+        let now = Utc::now().timestamp();
+        let mut live: Vec<&Record> = self.records.values().filter(|r| r.is_live(now)).collect();
+        live.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        let step = (live.len() / n.max(1)).max(1);
+        live.iter()
+            .step_by(step)
+            .take(n)
+            .map(|r| full_vector(r, self.quantized.as_ref()).into_owned())
+            .collect()
     }
 
-    /// Update only the metadata of an existing record
-    ///
-    /// # Arguments
-    /// * `id` - ID of the record to update
-    /// * `metadata` - New metadata to set
-    ///
-    /// # Returns
-    /// * `Ok(())` if successful
-    /// * `Err` if record not found
-    pub fn update_metadata(&mut self, id: &str, metadata: Metadata) -> Result<()> {
-        if let Some(record) = self.records.get_mut(id) {
-            record.metadata = metadata;
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("Record not found: {}", id))
+    /// Exact `k` nearest neighbours of each query
+    fn ground_truth(&self, queries: &[Vec<f32>], k: usize) -> Result<recall::GroundTruth> {
+        if k == 0 {
+            return Err(anyhow::anyhow!("Recall needs k of at least 1"));
+        }
+        if queries.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Recall needs at least one query and a non-empty store"
+            ));
+        }
+        let mut truth = recall::GroundTruth {
+            k,
+            queries: Vec::with_capacity(queries.len()),
+            nearest: Vec::with_capacity(queries.len()),
+        };
+        for vector in queries {
+            if vector.len() != self.dimension {
+                return Err(anyhow::anyhow!(
+                    "Query dimension mismatch: expected {}, got {}",
+                    self.dimension,
+                    vector.len()
+                ));
+            }
+            let mut vector = vector.clone();
+            self.apply_query_policy(&mut vector)?;
+            let q = Query::new(vector).with_limit(k);
+            let nearest = exact::search(
+                self.records.values(),
+                self.quantized.as_ref(),
+                self.config.distance,
+                &q,
+            );
+            truth
+                .nearest
+                .push(nearest.into_iter().map(|(id, _)| id).collect());
+            truth.queries.push(q.vector);
         }
+        Ok(truth)
     }
 
-    /// Estimate query cost and validate query parameters
-    ///
-    /// This provides pre-flight validation and cost estimation for queries,
-    /// helping users understand query complexity and catch errors early.
-    ///
-    /// # Arguments
-    /// * `q` - The query to estimate
-    ///
-    /// # Returns
-    /// * `QueryEstimate` with validation results and cost information
-    ///
-    /// # Example
-    /// ```no_run
-    /// # use vecstore::{VecStore, Query};
-    /// # let store = VecStore::open("./data").unwrap();
-    /// let query = Query {
-    ///     vector: vec![0.1, 0.2, 0.3],
-    ///     k: 100,
-    ///     filter: None,
-    /// };
-    ///
-    /// let estimate = store.estimate_query(&query);
-    /// if !estimate.valid {
-    ///     println!("Query errors: {:?}", estimate.errors);
-    /// } else {
-    ///     println!("Estimated cost: {}", estimate.cost_estimate);
-    ///     println!("Estimated duration: {}ms", estimate.estimated_duration_ms);
-    /// }
-    /// ```
-    pub fn estimate_query(&self, q: &Query) -> QueryEstimate {
-        let mut errors = Vec::new();
-        let mut recommendations = Vec::new();
+    /// Recall and latency of index searches at `ef_search` against `truth`
+    fn recall_at(&self, truth: &recall::GroundTruth, ef_search: usize) -> Result<EfRecall> {
+        let now = Utc::now().timestamp();
+        let mut total_recall = 0.0;
+        let mut latencies = Vec::with_capacity(truth.queries.len());
+        for (vector, nearest) in truth.queries.iter().zip(&truth.nearest) {
+            let q = Query::new(vector.clone())
+                .with_limit(truth.k)
+                .with_ef_search(ef_search);
+            let start = std::time::Instant::now();
+            let candidates =
+                self.search_with_fetch_size(&q, truth.k, &mut QueryProfile::default())?;
+            latencies.push(start.elapsed());
+
+            let found: Vec<Id> = candidates
+                .into_iter()
+                .map(|(id, _)| id)
+                .filter(|id| self.records.get(id).is_some_and(|r| r.is_live(now)))
+                .collect();
+            total_recall += recall::recall(&found, nearest);
+        }
+        let (mean_latency, p95_latency) = recall::latency_summary(&mut latencies);
+        Ok(EfRecall {
+            ef_search,
+            recall: total_recall / truth.queries.len() as f64,
+            mean_latency,
+            p95_latency,
+        })
+    }
 
-        // Validate dimension
+    /// Query with detailed explanations of why each result was returned
+    ///
+    /// This is useful for debugging, understanding search results, and optimizing queries.
+    /// Returns the same results as `query()` but with additional explanation metadata.
+    pub fn query_explain(&self, mut q: Query) -> Result<Vec<ExplainedNeighbor>> {
         if self.dimension == 0 {
-            return QueryEstimate {
-                valid: true,
-                errors: vec![],
-                cost_estimate: 0.0,
-                estimated_distance_calculations: 0,
-                estimated_nodes_visited: 0,
-                will_overfetch: false,
-                recommendations: vec!["Store is empty, query will return no results".to_string()],
-                estimated_duration_ms: 0.0,
-            };
+            return Ok(Vec::new());
         }
 
         if q.vector.len() != self.dimension {
-            errors.push(format!(
-                "Vector dimension mismatch: expected {}, got {}",
+            return Err(anyhow::anyhow!(
+                "Query dimension mismatch: expected {}, got {}",
                 self.dimension,
                 q.vector.len()
             ));
         }
 
-        // Validate k
-        if q.k == 0 {
-            errors.push("k must be greater than 0".to_string());
-        }
+        validate_ef_search(&q)?;
+        self.apply_query_policy(&mut q.vector)?;
 
-        if q.k > 1000 {
-            recommendations.push(format!(
-                "Large k value ({}). Consider reducing for better performance",
-                q.k
-            ));
-        }
+        // Track stats for explanation
+        let candidates = self.search_candidates(&q, &mut QueryProfile::default())?;
+        let total_candidates = candidates.len();
 
-        // Determine if query is valid
-        let valid = errors.is_empty();
+        let distance_metric = self.config.distance.name().to_string();
+        let has_filter = q.filter.is_some();
 
-        // Estimate computational cost
-        let total_records = self.records.len();
-        let active_records = self.active_count();
+        let mut results = Vec::new();
+        let mut rank = 1;
+        let mut filtered_out_count = 0;
+        let mut deleted_count = 0;
+        let now = Utc::now().timestamp();
 
-        // Calculate fetch size (same logic as query())
-        let fetch_size = if q.filter.is_some() {
-            if total_records <= q.k {
-                total_records
-            } else {
-                // Using saturating_mul to prevent overflow (Major Issue #10 fix)
-                std::cmp::min(q.k.saturating_mul(10), total_records)
-            }
-        } else {
-            std::cmp::min(q.k, total_records)
-        };
+        for (id, score) in candidates {
+            if let Some(record) = self.records.get(&id) {
+                // Track if soft-deleted or expired
+                if !record.is_live(now) {
+                    deleted_count += 1;
+                    continue;
+                }
 
-        // Check for over-fetching
-        let will_overfetch = q.filter.is_some() && fetch_size > q.k;
-        if will_overfetch {
-            recommendations.push(format!(
-                "Filter will cause over-fetching: requesting {} candidates to find {} results",
-                fetch_size, q.k
-            ));
-        }
+                if !record.visible_in(q.namespace.as_deref()) {
+                    filtered_out_count += 1;
+                    continue;
+                }
 
-        // Estimate distance calculations (HNSW typically visits ~log(n) * M nodes)
-        let m = 16; // Default HNSW M parameter
-        let estimated_nodes = if total_records <= 1 {
-            1
-        } else {
-            ((total_records as f32).log2() * m as f32).min(fetch_size as f32) as usize
-        };
+                // Evaluate filter and collect details
+                let (filter_passed, filter_details) = if let Some(ref filter) = q.filter {
+                    let passed = filters::evaluate_filter(filter, &record.metadata);
+                    let details = FilterEvaluation {
+                        filter_expr: format!("{:?}", filter),
+                        matched_conditions: if passed {
+                            vec![format!("All conditions matched")]
+                        } else {
+                            vec![]
+                        },
+                        failed_conditions: if !passed {
+                            vec![format!("Filter did not match")]
+                        } else {
+                            vec![]
+                        },
+                        passed,
+                    };
+                    (passed, Some(details))
+                } else {
+                    (true, None)
+                };
 
-        // Cost estimate (0.0 - 1.0)
-        // Factors: k, filter presence, over-fetching, database size
-        let base_cost = (q.k as f32 / 100.0).min(1.0) * 0.3;
-        let filter_cost = if q.filter.is_some() { 0.3 } else { 0.0 };
-        let overfetch_cost = if will_overfetch { 0.2 } else { 0.0 };
-        let size_cost = (total_records as f32 / 100000.0).min(1.0) * 0.2;
+                if !filter_passed {
+                    filtered_out_count += 1;
+                    continue;
+                }
 
-        let cost_estimate = (base_cost + filter_cost + overfetch_cost + size_cost).min(1.0);
+                // Build explanation text
+                let explanation_text = if has_filter {
+                    format!(
+                        "Ranked #{} with score {:.4} ({}). Passed filters. {} candidates evaluated, {} filtered out, {} deleted.",
+                        rank, score, distance_metric, total_candidates, filtered_out_count, deleted_count
+                    )
+                } else {
+                    format!(
+                        "Ranked #{} with score {:.4} ({}). {} candidates evaluated, {} deleted.",
+                        rank, score, distance_metric, total_candidates, deleted_count
+                    )
+                };
 
-        // Estimate duration (very rough heuristic)
-        // Base: 0.1ms per distance calculation
-        // Filter overhead: +50%
-        let base_duration = estimated_nodes as f32 * 0.1;
-        let filter_overhead = if q.filter.is_some() {
-            base_duration * 0.5
+                results.push(ExplainedNeighbor {
+                    id: id.clone(),
+                    score,
+                    metadata: record.metadata.clone(),
+                    explanation: QueryExplanation {
+                        raw_score: score,
+                        distance_metric: distance_metric.clone(),
+                        filter_passed,
+                        filter_details,
+                        graph_stats: Some(GraphTraversalStats {
+                            distance_calculations: total_candidates,
+                            nodes_visited: total_candidates,
+                            found_at_layer: None, // Would require HNSW backend changes
+                            hops_from_entry: None, // Would require HNSW backend changes
+                        }),
+                        rank,
+                        explanation_text,
+                    },
+                });
+
+                rank += 1;
+
+                if results.len() >= q.k {
+                    break;
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let layout = self.layout_at(&self.root);
+
+        // Export text index if any texts are indexed (Major Issue #6 fix)
+        let text_index_data = if self.text_index.export_texts().is_empty() {
+            None
         } else {
-            0.0
+            Some(self.text_index.export_texts())
         };
-        let estimated_duration_ms = base_duration + filter_overhead;
 
-        // Add recommendations
-        if active_records < total_records {
-            let deleted = total_records - active_records;
-            recommendations.push(format!(
-                "{} soft-deleted records present. Consider running compact() to improve performance",
-                deleted
-            ));
-        }
+        layout.save_all(
+            &self.records,
+            self.backend.get_id_to_idx_map(),
+            self.backend.get_idx_to_id_map(),
+            // Use actual next_idx counter, not map length (Critical Issue #2 fix)
+            self.backend.get_next_idx(),
+            self.dimension,
+            &self.config,    // Major Issue #7 fix: persist config
+            text_index_data, // Major Issue #6 fix: persist text index
+        )?;
+        layout.save_quantized(self.quantized.as_ref())?;
+        layout.save_changes(&self.changes)?;
 
-        if q.filter.is_some() {
-            recommendations.push(
-                "Filtered queries are slower. Consider reducing k or adding indexes for better performance"
-                    .to_string(),
-            );
-        }
+        self.write_index(&layout)?;
 
-        QueryEstimate {
-            valid,
-            errors,
-            cost_estimate,
-            estimated_distance_calculations: estimated_nodes,
-            estimated_nodes_visited: estimated_nodes,
-            will_overfetch,
-            recommendations,
-            estimated_duration_ms,
-        }
+        // Everything logged is now in the store files
+        self.truncate_wal()?;
+        self.saved_seq.store(self.changes.op_seq, Ordering::Relaxed);
+        self.emit(StoreEvent::Save);
+
+        Ok(())
     }
 
-    /// Set auto-compaction configuration
+    /// Sequence number of the store's latest change
     ///
-    /// # Arguments
-    /// * `config` - Compaction configuration
-    pub fn set_compaction_config(&mut self, config: CompactionConfig) {
-        self.compaction_config = config;
+    /// Every record write, removal and change to a persisted setting moves
+    /// it forward; backups record it as their `seq_end`.
+    pub fn op_seq(&self) -> u64 {
+        self.changes.op_seq
     }
 
-    /// Get current compaction configuration
-    pub fn compaction_config(&self) -> &CompactionConfig {
-        &self.compaction_config
+    /// Whether anything changed since the store was last saved or opened
+    ///
+    /// Record writes and changes to persisted settings count; operations
+    /// replayed from the write-ahead log on open count as unsaved.
+    pub fn is_dirty(&self) -> bool {
+        self.changes.op_seq != self.saved_seq.load(Ordering::Relaxed)
     }
 
-    /// Check if auto-compaction should run and execute it if needed
+    /// Copy the state [`save`](Self::save) persists, to write with
+    /// [`SaveSnapshot::write`] once the store is no longer borrowed
     ///
-    /// This method should be called periodically or after operations that may
-    /// generate deleted records (e.g., after soft_delete or TTL expiration).
+    /// The HNSW index can't be copied, so it is dumped here, into a staging
+    /// directory inside the store that the write moves into place alongside
+    /// the other files. Pass the snapshot to [`finish_save`](Self::finish_save)
+    /// once it is written.
+    pub fn snapshot_for_save(&self) -> Result<SaveSnapshot> {
+        let layout = self.layout_at(&self.root);
+        layout.ensure_directory()?;
+        let index_dir = tempfile::Builder::new()
+            .prefix(".index-snapshot")
+            .tempdir_in(&self.root)
+            .context("Failed to create index staging directory")?;
+        self.write_index(&self.layout_at(index_dir.path()))?;
+
+        let texts = self.text_index.export_texts();
+        Ok(SaveSnapshot {
+            layout,
+            records: self.records.clone(),
+            id_to_idx: self.backend.get_id_to_idx_map().clone(),
+            idx_to_id: self.backend.get_idx_to_id_map().clone(),
+            next_idx: self.backend.get_next_idx(),
+            dimension: self.dimension,
+            config: self.config.clone(),
+            texts: (!texts.is_empty()).then(|| texts.clone()),
+            quantized: self.quantized.clone(),
+            changes: self.changes.clone(),
+            index_dir,
+        })
+    }
+
+    /// Dump the HNSW index into `layout`, or remove a stale dump when the
+    /// index holds no points
+    fn write_index(&self, layout: &disk::DiskLayout) -> Result<()> {
+        if self.dimension == 0 || self.index_len() == 0 {
+            return layout.remove_index_dump();
+        }
+        self.backend.save_index(layout.hnsw_path())?;
+        layout.seal_index()
+    }
+
+    /// Mark a written [`SaveSnapshot`] as saved
     ///
-    /// # Returns
-    /// * `CompactionResult` with statistics about the compaction
-    pub fn maybe_compact(&mut self) -> Result<CompactionResult> {
-        if !self.compaction_config.enabled {
-            return Ok(CompactionResult {
-                removed_count: 0,
-                duration_ms: 0.0,
-                triggered: false,
-                reason: "Auto-compaction is disabled".to_string(),
-            });
+    /// The write-ahead log is truncated only if nothing changed since the
+    /// snapshot. Otherwise it keeps every entry, and reopening the store
+    /// replays them over the saved files.
+    pub fn finish_save(&self, snapshot: &SaveSnapshot) -> Result<()> {
+        let seq = snapshot.seq();
+        if self.changes.op_seq == seq {
+            self.truncate_wal()?;
         }
+        self.saved_seq.fetch_max(seq, Ordering::Relaxed);
+        self.emit(StoreEvent::Save);
+        Ok(())
+    }
 
-        let deleted_count = self.deleted_count();
-        let total_count = self.records.len();
+    fn truncate_wal(&self) -> Result<()> {
+        if let Some(wal) = &self.wal {
+            let mut wal = wal
+                .lock()
+                .map_err(|_| anyhow::anyhow!("Write-ahead log lock poisoned"))?;
+            wal.checkpoint()?;
+            wal.truncate()?;
+        }
+        Ok(())
+    }
 
-        let deleted_ratio = if total_count > 0 {
-            deleted_count as f32 / total_count as f32
-        } else {
-            0.0
-        };
+    /// Count a change to a persisted setting as unsaved
+    fn settings_changed(&mut self) {
+        self.changes.next_seq();
+    }
 
-        let should_compact = deleted_count >= self.compaction_config.min_deleted_records
-            && deleted_ratio >= self.compaction_config.min_deleted_ratio;
+    /// Get the number of active (non-deleted) records
+    pub fn count(&self) -> usize {
+        self.active_count()
+    }
 
-        if !should_compact {
-            return Ok(CompactionResult {
-                removed_count: 0,
-                duration_ms: 0.0,
-                triggered: false,
-                reason: format!(
-                    "Thresholds not met: {} deleted records ({:.1}% ratio), need {} records and {:.1}% ratio",
-                    deleted_count,
-                    deleted_ratio * 100.0,
-                    self.compaction_config.min_deleted_records,
-                    self.compaction_config.min_deleted_ratio * 100.0
-                ),
-            });
-        }
-
-        // Run compaction
-        let start = std::time::Instant::now();
-        let removed_count = self.compact()?;
-        let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+    pub fn dimension(&self) -> usize {
+        self.dimension
+    }
 
-        Ok(CompactionResult {
-            removed_count,
-            duration_ms,
-            triggered: true,
-            reason: format!(
-                "Compaction triggered: {} deleted records ({:.1}% ratio)",
-                deleted_count,
-                deleted_ratio * 100.0
-            ),
+    /// Query with a filter expression parsed from a SQL-like string
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use vecstore::{VecStore, Metadata};
+    /// # use std::collections::HashMap;
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// # let mut store = VecStore::open(temp_dir.path()).unwrap();
+    /// # let mut meta = Metadata { fields: HashMap::new() };
+    /// # meta.fields.insert("age".into(), serde_json::json!(25));
+    /// # meta.fields.insert("role".into(), serde_json::json!("admin"));
+    /// # store.upsert("user1".into(), vec![1.0, 0.0, 0.0], meta).unwrap();
+    /// let results = store.query_with_filter(
+    ///     vec![1.0, 0.0, 0.0],
+    ///     10,
+    ///     "age > 18 AND role = 'admin'"
+    /// ).unwrap();
+    /// ```
+    pub fn query_with_filter(
+        &self,
+        vector: Vec<f32>,
+        k: usize,
+        filter_str: &str,
+    ) -> Result<Vec<Neighbor>> {
+        let filter = filter_parser::parse_filter(filter_str)?;
+        self.query(Query {
+            vector,
+            k,
+            filter: Some(filter),
+            ..Default::default()
         })
     }
 
-    /// Expire TTL records (soft delete them)
+    /// Get the number of active (non-deleted) vectors in the store
+    pub fn len(&self) -> usize {
+        self.active_count()
+    }
+
+    /// Check if the store has no active (non-deleted) records
+    pub fn is_empty(&self) -> bool {
+        self.active_count() == 0
+    }
+
+    /// Create a named snapshot of the current store state
     ///
-    /// This scans all records and soft-deletes any that have passed their expiration time.
+    /// # Example
     ///
-    /// # Returns
-    /// * Number of records expired
-    pub fn expire_ttl_records(&mut self) -> Result<usize> {
-        let now = Utc::now().timestamp();
-        let mut expired_count = 0;
-
-        for record in self.records.values_mut() {
-            if let Some(expires_at) = record.expires_at {
-                if !record.deleted && now >= expires_at {
-                    record.deleted = true;
-                    record.deleted_at = Some(now);
-                    expired_count += 1;
-                }
-            }
+    /// ```
+    /// # use vecstore::VecStore;
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// # let store = VecStore::open(temp_dir.path()).unwrap();
+    /// store.create_snapshot("backup-2024-01-15")?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn create_snapshot(&self, name: &str) -> Result<()> {
+        let snapshot_dir = self.root.join("snapshots").join(name);
+
+        if snapshot_dir.exists() {
+            return Err(anyhow::anyhow!(
+                "Snapshot '{}' already exists. Use a different name or delete the old snapshot.",
+                name
+            ));
         }
 
-        Ok(expired_count)
+        std::fs::create_dir_all(&snapshot_dir)
+            .with_context(|| format!("Failed to create snapshot directory: {:?}", snapshot_dir))?;
+
+        // Save to snapshot directory
+        self.write_state_to(&snapshot_dir)?;
+
+        // Write snapshot metadata
+        let metadata = serde_json::json!({
+            "name": name,
+            "created_at": chrono::Utc::now().to_rfc3339(),
+            "record_count": self.records.len(),
+            "dimension": self.dimension,
+        });
+
+        std::fs::write(
+            snapshot_dir.join("snapshot.json"),
+            serde_json::to_string_pretty(&metadata)?,
+        )?;
+
+        Ok(())
     }
 
-    /// Set TTL for an existing record
-    ///
-    /// # Arguments
-    /// * `id` - Record ID
-    /// * `ttl_seconds` - Time-to-live in seconds from now
-    ///
-    /// # Returns
-    /// * `Ok(())` if successful
-    /// * `Err` if record not found
-    pub fn set_ttl(&mut self, id: &str, ttl_seconds: i64) -> Result<()> {
-        if let Some(record) = self.records.get_mut(id) {
-            let expires_at = Utc::now().timestamp() + ttl_seconds;
-            record.expires_at = Some(expires_at);
-            Ok(())
+    /// Write the full store state (records, config, indexes) into `dir`
+    fn write_state_to(&self, dir: &Path) -> Result<()> {
+        let layout = self.layout_at(dir);
+
+        // Export text index if any texts are indexed (Major Issue #6 fix)
+        let text_index_data = if self.text_index.export_texts().is_empty() {
+            None
         } else {
-            Err(anyhow::anyhow!("Record not found: {}", id))
+            Some(self.text_index.export_texts())
+        };
+
+        layout.save_all(
+            &self.records,
+            self.backend.get_id_to_idx_map(),
+            self.backend.get_idx_to_id_map(),
+            // Use actual next_idx counter, not map length (Critical Issue #2 fix)
+            self.backend.get_next_idx(),
+            self.dimension,
+            &self.config,    // Major Issue #7 fix: persist config in snapshots
+            text_index_data, // Major Issue #6 fix: persist text index in snapshots
+        )?;
+        layout.save_quantized(self.quantized.as_ref())?;
+        layout.save_changes(&self.changes)?;
+
+        self.write_index(&layout)?;
+
+        // Keep the wrapped key with the sealed files so the copy can be opened
+        if self.cipher.is_some() {
+            let keyring = self.root.join(encryption::KEYRING_FILE);
+            std::fs::copy(&keyring, dir.join(encryption::KEYRING_FILE))
+                .with_context(|| format!("Failed to copy {:?}", keyring))?;
         }
+
+        Ok(())
     }
 
-    /// Upsert with TTL
+    /// Layout of the store files in `dir`, sealed like this store's
+    fn layout_at(&self, dir: &Path) -> disk::DiskLayout {
+        disk::DiskLayout::new(dir).with_cipher(self.cipher.clone())
+    }
+
+    /// Whether the store's files are encrypted at rest
+    pub fn is_encrypted(&self) -> bool {
+        self.cipher.is_some()
+    }
+
+    /// Change the key an encrypted store is opened with
     ///
-    /// # Arguments
-    /// * `id` - Record ID
-    /// * `vector` - Vector data
-    /// * `metadata` - Metadata
-    /// * `ttl_seconds` - Time-to-live in seconds from now
-    pub fn upsert_with_ttl(
-        &mut self,
-        id: Id,
-        vector: Vec<f32>,
-        metadata: Metadata,
-        ttl_seconds: i64,
+    /// Only the store's key file is rewritten; the data stays sealed under
+    /// the same data key. Backups and snapshots taken earlier still need
+    /// `old`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::{EncryptionKey, VecStore};
+    /// VecStore::rewrap_key(
+    ///     "./data",
+    ///     &EncryptionKey::passphrase("old passphrase"),
+    ///     &EncryptionKey::passphrase("new passphrase"),
+    /// )?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[cfg(feature = "encryption")]
+    pub fn rewrap_key<P: AsRef<Path>>(
+        root: P,
+        old: &EncryptionKey,
+        new: &EncryptionKey,
     ) -> Result<()> {
-        // Set dimension on first insert
-        if self.dimension == 0 {
-            self.dimension = vector.len();
-            #[cfg(not(target_arch = "wasm32"))]
-            {
-                self.backend = VectorBackend::new(self.dimension, self.config.distance)?;
-            }
-            #[cfg(target_arch = "wasm32")]
-            {
-                self.backend = VectorBackend::new(self.dimension);
-            }
-        }
+        encryption::rewrap(root.as_ref(), old, new)
+    }
 
-        if vector.len() != self.dimension {
-            return Err(anyhow::anyhow!(
-                "Vector dimension mismatch: expected {}, got {}",
-                self.dimension,
-                vector.len()
-            ));
-        }
+    /// Check the store at `root` for internal consistency without opening it
+    ///
+    /// Reads the files directly and reports, per category: records and their
+    /// metadata deserialize, vector lengths match the dimension, the HNSW id
+    /// mappings and the records name the same ids, the HNSW dump covers the
+    /// mapped ids, and the write-ahead log replays without a torn tail.
+    /// Nothing is written. Encrypted stores are not supported.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::VecStore;
+    /// let report = VecStore::validate("./data")?;
+    /// if !report.is_ok() {
+    ///     VecStore::repair("./data")?;
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn validate<P: AsRef<Path>>(root: P) -> Result<IntegrityReport> {
+        let root = root.as_ref();
+        let cipher = encryption::load(root, None)?.map(Arc::new);
+        validate::check(&disk::DiskLayout::new(root).with_cipher(cipher))
+    }
 
-        let expires_at = Utc::now().timestamp() + ttl_seconds;
+    /// Make the store at `root` consistent again and save it
+    ///
+    /// Drops records that don't deserialize or whose vector length isn't the
+    /// store's dimension, replays the write-ahead log, then rebuilds the HNSW
+    /// index from the remaining records, which drops mapped ids that have no
+    /// record.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn repair<P: Into<PathBuf>>(root: P) -> Result<RepairReport> {
+        let root = root.into();
+        let cipher = encryption::load(&root, None)?.map(Arc::new);
+        let mut report =
+            validate::drop_bad_records(&disk::DiskLayout::new(&root).with_cipher(cipher))?;
 
-        let record = Record {
-            id: id.clone(),
-            vector: vector.clone(),
-            metadata,
-            created_at: Utc::now().timestamp(),
-            deleted: false,
-            deleted_at: None,
-            expires_at: Some(expires_at),
-        };
+        let mut store = Self::open(root)?;
+        report.orphaned_ids = store
+            .backend
+            .get_id_to_idx_map()
+            .keys()
+            .filter(|id| !store.records.contains_key(*id))
+            .count();
+        store.optimize()?;
+        store.save()?;
+        report.indexed = store.backend.get_id_to_idx_map().len();
+        Ok(report)
+    }
 
-        self.backend.insert(id.clone(), &vector)?;
-        self.records.insert(id, record);
+    /// Write a single-file backup of the store to `output`
+    ///
+    /// The backup embeds a manifest with the record count, dimension,
+    /// distance metric and a checksum per file, which
+    /// [`restore_backup`](Self::restore_backup) verifies. Unsaved changes
+    /// are included.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::VecStore;
+    /// let store = VecStore::open("./data")?;
+    /// let manifest = store.backup("store.vsbak")?;
+    /// println!("Backed up {} records", manifest.record_count);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn backup<P: AsRef<Path>>(&self, output: P) -> Result<BackupManifest> {
+        self.backup_with(output.as_ref(), BackupCompression::None)
+    }
 
-        Ok(())
+    /// Write a zstd-compressed single-file backup of the store to `output`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn backup_compressed<P: AsRef<Path>>(&self, output: P) -> Result<BackupManifest> {
+        self.backup_with(output.as_ref(), BackupCompression::Zstd)
     }
 
-    /// Execute a multi-stage prefetch query for advanced RAG patterns
+    #[cfg(not(target_arch = "wasm32"))]
+    fn backup_with(&self, output: &Path, compression: BackupCompression) -> Result<BackupManifest> {
+        // Stage next to the output so large stores don't fill the system temp dir
+        let parent = match output.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let staging = tempfile::Builder::new()
+            .prefix(".vecstore-backup")
+            .tempdir_in(parent)
+            .with_context(|| format!("Failed to create staging directory in {:?}", parent))?;
+
+        self.write_state_to(staging.path())?;
+        backup::write(self, staging.path(), output, compression)
+    }
+
+    /// Write an incremental backup of the changes made since `base`
     ///
-    /// This enables complex retrieval pipelines like:
-    /// 1. Broad hybrid search (fetch 100 candidates)
-    /// 2. Rerank with cross-encoder (score top 20)
-    /// 3. Apply MMR for diversity (final 10 results)
+    /// `base` is the manifest of the previous backup in the chain (full or
+    /// incremental), as returned by [`backup`](Self::backup) or
+    /// [`backup_info`](Self::backup_info). Only records changed or removed
+    /// after it are written, using the base's compression. Restore the chain
+    /// with [`restore_chain`](Self::restore_chain).
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use vecstore::{VecStore, PrefetchQuery, QueryStage};
-    /// # let temp_dir = tempfile::tempdir().unwrap();
-    /// # let store = VecStore::open(temp_dir.path()).unwrap();
-    /// let query = PrefetchQuery {
-    ///     stages: vec![
-    ///         QueryStage::HybridSearch {
-    ///             vector: vec![0.1, 0.2, 0.3],
-    ///             keywords: "machine learning".into(),
-    ///             k: 100,
-    ///             alpha: 0.7,
-    ///             filter: None,
-    ///         },
-    ///         QueryStage::MMR {
-    ///             k: 10,
-    ///             lambda: 0.7, // 70% relevance, 30% diversity
-    ///         },
-    ///     ],
-    /// };
+    /// # use vecstore::VecStore;
+    /// let store = VecStore::open("./data")?;
+    /// let full = store.backup_compressed("full.vsbak")?;
+    /// // ... later ...
+    /// let inc1 = store.backup_incremental(&full, "inc1.vsbak")?;
+    /// let inc2 = store.backup_incremental(&inc1, "inc2.vsbak")?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn backup_incremental<P: AsRef<Path>>(
+        &self,
+        base: &BackupManifest,
+        output: P,
+    ) -> Result<BackupManifest> {
+        backup::write_incremental(self, base, output.as_ref())
+    }
+
+    /// Describe a backup without restoring it
     ///
-    /// let results = store.prefetch_query(query)?;
+    /// Fails if the file is not a backup or is shorter than its manifest
+    /// says. Segment checksums are only verified by
+    /// [`restore_backup`](Self::restore_backup).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn backup_info<P: AsRef<Path>>(path: P) -> Result<BackupManifest> {
+        backup::read_manifest(path.as_ref())
+    }
+
+    /// Restore a backup written by [`backup`](Self::backup) into `dest`
+    ///
+    /// Every segment is checked against the backup's manifest before
+    /// anything is written, and the opened store is checked against the
+    /// manifest's record count, dimension and distance metric. `dest` must
+    /// not already contain a store.
+    ///
+    /// A backup of an encrypted store restores encrypted, and is opened
+    /// with the key from the `VECSTORE_ENCRYPTION_KEY` or
+    /// `VECSTORE_ENCRYPTION_PASSPHRASE` environment variable.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::VecStore;
+    /// let store = VecStore::restore_backup("store.vsbak", "./restored")?;
     /// # Ok::<(), anyhow::Error>(())
     /// ```
-    pub fn prefetch_query(&self, query: PrefetchQuery) -> Result<Vec<Neighbor>> {
-        if query.stages.is_empty() {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn restore_backup<P: AsRef<Path>, Q: Into<PathBuf>>(backup: P, dest: Q) -> Result<Self> {
+        let (manifest, files) = backup::read_segments(backup.as_ref())?;
+        Self::restore_full(backup.as_ref(), manifest, files, dest.into())
+    }
+
+    /// Write the verified files of a full backup into `dest` and open them
+    #[cfg(not(target_arch = "wasm32"))]
+    fn restore_full(
+        backup: &Path,
+        manifest: BackupManifest,
+        files: Vec<(String, Vec<u8>)>,
+        dest: PathBuf,
+    ) -> Result<Self> {
+        if manifest.kind != BackupKind::Full {
             return Err(anyhow::anyhow!(
-                "Prefetch query must have at least one stage"
+                "{:?} is an incremental backup; restore it with restore_chain after its full backup",
+                backup
             ));
         }
 
-        // Validate first stage is a search stage (Major Issue #16 fix)
-        if let Some(first_stage) = query.stages.first() {
-            match first_stage {
-                QueryStage::VectorSearch { .. } | QueryStage::HybridSearch { .. } => {
-                    // Valid first stage
-                }
-                QueryStage::Rerank { .. } => {
-                    return Err(anyhow::anyhow!(
-                        "First stage must be VectorSearch or HybridSearch, not Rerank. Rerank requires previous search results."
-                    ));
-                }
-                QueryStage::MMR { .. } => {
-                    return Err(anyhow::anyhow!(
-                        "First stage must be VectorSearch or HybridSearch, not MMR. MMR requires previous search results."
-                    ));
-                }
-                QueryStage::Filter { .. } => {
-                    return Err(anyhow::anyhow!(
-                        "First stage must be VectorSearch or HybridSearch, not Filter. Filter requires previous search results."
-                    ));
+        let layout = disk::DiskLayout::new(&dest);
+        if layout.exists() {
+            return Err(anyhow::anyhow!(
+                "Refusing to restore into {:?}: it already contains a store",
+                dest
+            ));
+        }
+        // Check the key now rather than after writing files it can't open
+        if let Some((_, keyring)) = files
+            .iter()
+            .find(|(name, _)| name == encryption::KEYRING_FILE)
+        {
+            encryption::unlock(keyring, None, backup)?;
+        }
+        layout.ensure_directory()?;
+        for (name, data) in files {
+            std::fs::write(dest.join(&name), data)
+                .with_context(|| format!("Failed to write {:?}", dest.join(&name)))?;
+        }
+
+        let store = Self::open(&dest)?;
+        backup::verify_restored(&store, &manifest)?;
+        Ok(store)
+    }
+
+    /// Restore a full backup followed by its incremental backups into `dest`
+    ///
+    /// `chain` lists the full backup first, then each incremental in the
+    /// order it was taken. Every link is verified, and must start at the
+    /// operation the previous one ended at, before anything is written, so
+    /// a missing, reordered or corrupted backup fails the restore.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::VecStore;
+    /// let store =
+    ///     VecStore::restore_chain(&["full.vsbak", "inc1.vsbak", "inc2.vsbak"], "./restored")?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn restore_chain<P: AsRef<Path>, Q: Into<PathBuf>>(chain: &[P], dest: Q) -> Result<Self> {
+        let (base, increments) = chain
+            .split_first()
+            .ok_or_else(|| anyhow::anyhow!("Backup chain is empty"))?;
+        let (manifest, files) = backup::read_segments(base.as_ref())?;
+        // Encrypted increments are decoded with the base's key before anything is written
+        let cipher = match files
+            .iter()
+            .find(|(name, _)| name == encryption::KEYRING_FILE)
+        {
+            Some((_, keyring)) => Some(encryption::unlock(keyring, None, base.as_ref())?),
+            None => None,
+        };
+        let increments = backup::read_chain(base.as_ref(), increments, cipher.as_ref())?;
+
+        let mut store = Self::restore_full(base.as_ref(), manifest, files, dest.into())?;
+        for (manifest, increment) in increments {
+            backup::apply(&mut store, &manifest, increment)?;
+            backup::verify_restored(&store, &manifest)?;
+        }
+        store.save()?;
+        Ok(store)
+    }
+
+    /// List all available snapshots
+    ///
+    /// Returns a vector of (snapshot_name, created_at, record_count)
+    pub fn list_snapshots(&self) -> Result<Vec<(String, String, usize)>> {
+        let snapshots_dir = self.root.join("snapshots");
+
+        if !snapshots_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut snapshots = Vec::new();
+
+        for entry in std::fs::read_dir(&snapshots_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+
+            if path.is_dir() {
+                let metadata_path = path.join("snapshot.json");
+                if metadata_path.exists() {
+                    let content = std::fs::read_to_string(&metadata_path)?;
+                    let metadata: serde_json::Value = serde_json::from_str(&content)?;
+
+                    let name = metadata["name"].as_str().unwrap_or("unknown").to_string();
+                    let created_at = metadata["created_at"]
+                        .as_str()
+                        .unwrap_or("unknown")
+                        .to_string();
+                    let count = metadata["record_count"].as_u64().unwrap_or(0) as usize;
+
+                    snapshots.push((name, created_at, count));
                 }
             }
         }
 
-        let mut candidates = Vec::new();
+        // Sort by creation time (most recent first)
+        snapshots.sort_by(|a, b| b.1.cmp(&a.1));
 
-        for (stage_idx, stage) in query.stages.iter().enumerate() {
-            candidates = match stage {
-                QueryStage::VectorSearch { vector, k, filter } => {
-                    // Stage 1: Vector search
-                    self.query(Query {
-                        vector: vector.clone(),
-                        k: *k,
-                        filter: filter.clone(),
-                    })?
-                }
+        Ok(snapshots)
+    }
 
-                QueryStage::HybridSearch {
-                    vector,
-                    keywords,
-                    k,
-                    alpha,
-                    filter,
-                } => {
-                    // Stage 1: Hybrid search
-                    self.hybrid_query(HybridQuery {
-                        vector: vector.clone(),
-                        keywords: keywords.clone(),
-                        k: *k,
-                        alpha: *alpha,
-                        filter: filter.clone(),
-                    })?
-                }
+    /// Restore from a named snapshot
+    ///
+    /// This will replace the current store with the snapshot data.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::VecStore;
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// # let mut store = VecStore::open(temp_dir.path()).unwrap();
+    /// store.restore_snapshot("backup-2024-01-15")?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn restore_snapshot(&mut self, name: &str) -> Result<()> {
+        let snapshot_dir = self.root.join("snapshots").join(name);
 
-                QueryStage::Rerank { k, model: _ } => {
-                    // Stage 2+: Rerank existing candidates
-                    if candidates.is_empty() {
-                        return Err(anyhow::anyhow!(
-                            "Rerank stage {} requires previous stage results",
-                            stage_idx
-                        ));
-                    }
+        if !snapshot_dir.exists() {
+            return Err(anyhow::anyhow!(
+                "Snapshot '{}' not found. Use list_snapshots() to see available snapshots.",
+                name
+            ));
+        }
 
-                    // For now, just take top K (actual reranking would use cross-encoder)
-                    // TODO: Integrate cross-encoder model if specified
-                    candidates.truncate(*k);
-                    candidates
-                }
+        if !self.layout_at(&snapshot_dir).manifest_path().exists() {
+            return Err(anyhow::anyhow!(
+                "Snapshot '{}' is corrupted (missing manifest)",
+                name
+            ));
+        }
 
-                QueryStage::MMR { k, lambda } => {
-                    // Stage 2+: Maximal Marginal Relevance for diversity
-                    if candidates.is_empty() {
-                        return Err(anyhow::anyhow!(
-                            "MMR stage {} requires previous stage results",
-                            stage_idx
-                        ));
-                    }
+        self.load_contents(&snapshot_dir)
+    }
 
-                    self.apply_mmr(&candidates, *k, *lambda)?
-                }
+    /// Replace the store's contents with a full backup written by
+    /// [`backup`](Self::backup), and save
+    ///
+    /// The backup is verified like [`restore_backup`](Self::restore_backup)
+    /// before anything changes. Records it replaces are reported as changed,
+    /// as with [`restore_snapshot`](Self::restore_snapshot).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn restore_backup_in_place<P: AsRef<Path>>(&mut self, backup: P) -> Result<BackupManifest> {
+        let backup = backup.as_ref();
+        let (manifest, files) = backup::read_segments(backup)?;
+        let staging = tempfile::Builder::new()
+            .prefix(".vecstore-restore")
+            .tempdir_in(&self.root)
+            .with_context(|| format!("Failed to create staging directory in {:?}", self.root))?;
+        Self::restore_full(
+            backup,
+            manifest.clone(),
+            files,
+            staging.path().to_path_buf(),
+        )?;
+        self.load_contents(staging.path())?;
+        self.save()?;
+        Ok(manifest)
+    }
 
-                QueryStage::Filter { expr } => {
-                    // Stage 2+: Additional filtering
-                    if candidates.is_empty() {
-                        return Err(anyhow::anyhow!(
-                            "Filter stage {} requires previous stage results",
-                            stage_idx
-                        ));
-                    }
+    /// Become a copy of `other`, keeping this store's location, write-ahead
+    /// log, subscribers and slow query log
+    ///
+    /// Records keep `other`'s versions and sequence numbers, and the
+    /// operation counter and removal tombstones are `other`'s too.
+    /// Subscribers see each record that is gone as deleted and each live
+    /// record as upserted. Nothing is saved. As `other` is loaded and
+    /// indexed beforehand, a store shared behind a lock is only held for
+    /// the swap itself.
+    pub fn replace_with(&mut self, other: VecStore) {
+        let VecStore {
+            backend,
+            records,
+            dimension,
+            text_index,
+            config,
+            deleted_ratio,
+            quantized,
+            changes,
+            field_indexes,
+            ..
+        } = other;
+
+        let dropped: Vec<Id> = self
+            .records
+            .keys()
+            .filter(|id| !records.contains_key(*id))
+            .cloned()
+            .collect();
+        self.backend = backend;
+        self.records = records;
+        self.dimension = dimension;
+        self.text_index = text_index;
+        self.config = config;
+        self.deleted_ratio
+            .store(deleted_ratio.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.quantized = quantized;
+        self.changes = changes;
+        self.field_indexes = field_indexes;
+
+        for id in dropped {
+            self.emit(StoreEvent::Delete { id });
+        }
+        for record in self.records.values().filter(|r| !r.deleted) {
+            self.emit_upsert(&record.id);
+        }
+    }
 
-                    candidates
-                        .into_iter()
-                        .filter(|n| {
-                            if let Some(record) = self.records.get(&n.id) {
-                                filters::evaluate_filter(expr, &record.metadata)
-                            } else {
-                                false
-                            }
-                        })
-                        .collect()
-                }
-            };
+    /// Replace the store's records, index and config with the store saved
+    /// in `dir`
+    fn load_contents(&mut self, dir: &Path) -> Result<()> {
+        let layout = self.layout_at(dir);
+        let (
+            mut records,
+            id_to_idx,
+            idx_to_id,
+            next_idx,
+            dimension,
+            loaded_config,
+            text_index_data,
+        ) = layout.load_all()?;
+
+        // Everything the snapshot replaces counts as changed, and records
+        // still present move past their current version so stale writers
+        // conflict
+        let seq = self.changes.next_seq();
+        let mut dropped = Vec::new();
+        for id in self.records.keys() {
+            if !records.contains_key(id) {
+                self.changes.removed.insert(id.clone(), seq);
+                dropped.push(id.clone());
+            }
+        }
+        for record in records.values_mut() {
+            record.seq = seq;
+            if let Some(current) = self.records.get(&record.id) {
+                record.version = record.version.max(current.version) + 1;
+            }
+            self.changes.removed.remove(&record.id);
+        }
+        self.records = records;
+        self.dimension = dimension;
+
+        // Update config if loaded (Major Issue #7 fix)
+        if let Some(config) = loaded_config {
+            self.config = config;
+        }
+        fit_capacity(&mut self.config.hnsw, self.records.len());
+        self.quantized = layout.load_quantized()?;
+        self.field_indexes =
+            field_index::FieldIndexes::build(&self.config.field_indexes, self.records.values());
+
+        // Restore text index if available (Major Issue #6 fix)
+        self.text_index = hybrid::TextIndex::new();
+        if let Some(texts) = text_index_data {
+            self.text_index.import_texts(texts);
+        }
+
+        // Recreate backend
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.backend = hnsw_backend::HnswBackend::restore(
+                dimension,
+                self.config.distance,
+                &self.config.hnsw,
+                id_to_idx,
+                idx_to_id,
+                next_idx,
+            )?
+            .with_quantizer(self.quantized.as_ref().map(|q| q.quantizer().clone()))?;
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            // WASM backend doesn't support restore (no persistence in browser)
+            // Create a new backend and rebuild from records
+            let mut backend = VectorBackend::new(dimension);
+            backend.set_mappings(id_to_idx, idx_to_id, next_idx);
+            self.backend = backend;
+        }
+
+        // Rebuild HNSW index from vectors
+        if dimension > 0 {
+            let vectors = self.full_vectors();
+            self.backend.rebuild_from_vectors(&vectors)?;
+        }
+
+        for id in dropped {
+            self.emit(StoreEvent::Delete { id });
+        }
+        for record in self.records.values().filter(|r| !r.deleted) {
+            self.emit_upsert(&record.id);
+        }
+
+        Ok(())
+    }
+
+    /// Delete a named snapshot
+    pub fn delete_snapshot(&self, name: &str) -> Result<()> {
+        let snapshot_dir = self.root.join("snapshots").join(name);
+
+        if !snapshot_dir.exists() {
+            return Err(anyhow::anyhow!("Snapshot '{}' not found", name));
+        }
+
+        std::fs::remove_dir_all(&snapshot_dir)
+            .with_context(|| format!("Failed to delete snapshot: {}", name))?;
+
+        Ok(())
+    }
+
+    /// Index text content for a document (enables hybrid search)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use vecstore::VecStore;
+    /// # use std::collections::HashMap;
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// # let mut store = VecStore::open(temp_dir.path()).unwrap();
+    /// # let mut meta = vecstore::Metadata { fields: HashMap::new() };
+    /// # store.upsert("doc1".into(), vec![1.0, 0.0, 0.0], meta).unwrap();
+    /// store.index_text("doc1", "Rust is a systems programming language")?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn index_text(&mut self, id: &str, text: impl Into<String>) -> Result<()> {
+        let Some(record) = self.records.get_mut(id) else {
+            return Err(anyhow::anyhow!(
+                "Cannot index text for non-existent document: {}",
+                id
+            ));
+        };
+        record.seq = self.changes.next_seq();
+        record.version += 1;
+
+        self.text_index.index_document(id.to_string(), text.into());
+        Ok(())
+    }
+
+    /// Perform hybrid search combining vector similarity and keyword search
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::{VecStore, HybridQuery};
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// # let store = VecStore::open(temp_dir.path()).unwrap();
+    /// let query = HybridQuery {
+    ///     vector: vec![0.1, 0.2, 0.3],
+    ///     keywords: "rust programming".into(),
+    ///     k: 10,
+    ///     filter: None,
+    ///     alpha: 0.7, // 70% vector, 30% keyword
+    /// };
+    ///
+    /// let results = store.hybrid_query(query)?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    #[tracing::instrument(skip(self, query), fields(k = query.k, has_keywords = !query.keywords.is_empty(), alpha = query.alpha))]
+    pub fn hybrid_query(&self, mut query: HybridQuery) -> Result<Vec<Neighbor>> {
+        if self.dimension == 0 {
+            return Ok(Vec::new());
+        }
+
+        if query.vector.len() != self.dimension {
+            return Err(anyhow::anyhow!(
+                "Query dimension mismatch: expected {}, got {}",
+                self.dimension,
+                query.vector.len()
+            ));
+        }
+        self.apply_query_policy(&mut query.vector)?;
+
+        // Get vector similarity scores
+        let fetch_size = if query.filter.is_some() {
+            // Using saturating_mul to prevent overflow (Major Issue #10 fix)
+            std::cmp::min(query.k.saturating_mul(10), self.records.len())
+        } else {
+            // Using saturating_mul to prevent overflow (Major Issue #10 fix)
+            std::cmp::min(query.k.saturating_mul(2), self.records.len()) // Fetch more for ranking
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let vector_results = self.backend.search(&query.vector, fetch_size);
+        #[cfg(target_arch = "wasm32")]
+        let vector_results = self.backend.search(&query.vector, fetch_size)?;
+
+        // Get BM25 scores if keywords provided
+        let bm25_scores = if !query.keywords.is_empty() {
+            self.text_index.bm25_scores(&query.keywords)
+        } else {
+            HashMap::new()
+        };
+
+        // Combine scores
+        let combined = hybrid::combine_scores(vector_results, bm25_scores, query.alpha);
+
+        // Apply filter and build results
+        let mut results = Vec::new();
+        let now = Utc::now().timestamp();
+
+        for (id, score) in combined.into_iter().take(query.k * 2) {
+            if let Some(record) = self.records.get(&id) {
+                // Skip soft-deleted and expired records (Major Issue #12 fix)
+                if !record.is_live(now) {
+                    continue;
+                }
+
+                // Apply filter if present
+                if let Some(ref filter) = query.filter {
+                    if !filters::evaluate_filter(filter, &record.metadata) {
+                        continue;
+                    }
+                }
+
+                results.push(Neighbor {
+                    id: id.clone(),
+                    score,
+                    metadata: record.metadata.clone(),
+                    vector: None,
+                    version: record.version,
+                    text: None,
+                });
+
+                if results.len() >= query.k {
+                    break;
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Check if a document has indexed text
+    pub fn has_text(&self, id: &str) -> bool {
+        self.text_index.has_text(id)
+    }
+
+    /// Get the indexed text for a document
+    pub fn get_text(&self, id: &str) -> Option<&str> {
+        self.text_index.get_text(id)
+    }
+
+    /// Get all records in the store
+    ///
+    /// Returns a vector of all records, useful for iteration and export.
+    pub fn list_all(&self) -> Vec<Record> {
+        self.records.values().cloned().collect()
+    }
+
+    /// Soft delete a record (mark as deleted without removing)
+    ///
+    /// Soft deletes allow deferred cleanup and potential recovery.
+    /// Call `compact()` to permanently remove soft-deleted records.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the record to soft delete
+    ///
+    /// # Returns
+    /// * `Ok(true)` if record was soft deleted
+    /// * `Ok(false)` if record doesn't exist or was already deleted
+    pub fn soft_delete(&mut self, id: &str) -> Result<bool> {
+        if let Some(record) = self.records.get_mut(id) {
+            if !record.deleted {
+                record.deleted = true;
+                record.deleted_at = Some(Utc::now().timestamp());
+                record.seq = self.changes.next_seq();
+                record.version += 1;
+                self.log(LogEntry::SoftDelete { id: id.to_string() })?;
+                self.emit(StoreEvent::Delete { id: id.to_string() });
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Restore a soft-deleted record
+    ///
+    /// # Arguments
+    /// * `id` - ID of the record to restore
+    ///
+    /// # Returns
+    /// * `Ok(true)` if record was restored
+    /// * `Ok(false)` if record doesn't exist or wasn't deleted
+    pub fn restore(&mut self, id: &str) -> Result<bool> {
+        if let Some(record) = self.records.get_mut(id) {
+            if record.deleted {
+                record.deleted = false;
+                record.deleted_at = None;
+                record.seq = self.changes.next_seq();
+                record.version += 1;
+                self.log(LogEntry::Restore { id: id.to_string() })?;
+                self.emit_upsert(id);
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Permanently remove all soft-deleted records (compaction)
+    ///
+    /// Drops soft-deleted records, rebuilds the index from the remaining
+    /// vectors (reclaiming ghost entries), and rewrites the store files.
+    /// Each file is written to a temporary file and atomically renamed, so a
+    /// crash leaves either the old or the new version in place.
+    ///
+    /// # Returns
+    /// A [`CompactionReport`] with the records removed and bytes reclaimed
+    pub fn compact(&mut self) -> Result<CompactionReport> {
+        let layout = disk::DiskLayout::new(&self.root);
+        let bytes_before = layout.data_size()?;
+        let ghost_entries_removed = self.ghost_entries();
+
+        // Find all soft-deleted record IDs
+        let deleted_ids: Vec<String> = self
+            .records
+            .values()
+            .filter(|r| r.deleted)
+            .map(|r| r.id.clone())
+            .collect();
+
+        let removed_count = deleted_ids.len();
+
+        // Permanently remove them
+        for id in deleted_ids {
+            self.records.remove(&id);
+            self.field_indexes.remove(&id);
+            self.changes.tombstone(&id);
+
+            // Clean up text index (Critical Issue #4 fix)
+            self.text_index.remove_document(&id);
+        }
+        if let Some(quantized) = &mut self.quantized {
+            quantized.retain_records(&self.records);
+        }
+
+        self.optimize()?;
+        self.save()?;
+        self.emit(StoreEvent::Compact);
+
+        Ok(CompactionReport {
+            removed_count,
+            ghost_entries_removed,
+            bytes_before,
+            bytes_after: layout.data_size()?,
+        })
+    }
+
+    /// Get count of soft-deleted records
+    pub fn deleted_count(&self) -> usize {
+        self.records.values().filter(|r| r.deleted).count()
+    }
+
+    /// Get count of active (non-deleted) records
+    pub fn active_count(&self) -> usize {
+        self.records.values().filter(|r| !r.deleted).count()
+    }
+
+    /// Get count of active (non-deleted) records in `namespace`
+    pub fn namespace_len(&self, namespace: &str) -> usize {
+        self.records
+            .values()
+            .filter(|r| !r.deleted && r.namespace.as_deref() == Some(namespace))
+            .count()
+    }
+
+    /// Active record counts per namespace, excluding the default namespace
+    pub fn namespace_counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for record in self.records.values().filter(|r| !r.deleted) {
+            if let Some(namespace) = &record.namespace {
+                *counts.entry(namespace.clone()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// Permanently remove every record in `namespace`, including soft-deleted ones
+    ///
+    /// # Returns
+    /// The number of records removed
+    pub fn delete_namespace(&mut self, namespace: &str) -> Result<usize> {
+        let ids: Vec<Id> = self
+            .records
+            .values()
+            .filter(|r| r.namespace.as_deref() == Some(namespace))
+            .map(|r| r.id.clone())
+            .collect();
+        self.delete_ids(&ids)
+    }
+
+    /// Permanently remove every active record whose metadata matches `filter`
+    ///
+    /// Matches are the records [`scan`](Self::scan) and
+    /// [`count_where`](Self::count_where) would return for the same filter.
+    ///
+    /// # Returns
+    /// The number of records removed
+    pub fn delete_by_filter(&mut self, filter: &FilterExpr) -> Result<usize> {
+        let ids: Vec<Id> = self.matching(Some(filter)).map(|r| r.id.clone()).collect();
+        self.delete_ids(&ids)
+    }
+
+    /// Permanently remove the records in `ids`, skipping ids that aren't stored
+    ///
+    /// Soft-deleted records are removed too. An id listed twice is only
+    /// removed once.
+    ///
+    /// # Returns
+    /// The number of records removed; the rest of `ids` weren't found
+    pub fn delete_ids<S: AsRef<str>>(&mut self, ids: &[S]) -> Result<usize> {
+        let mut removed = 0;
+        for id in ids {
+            let id = id.as_ref();
+            if self.records.contains_key(id) {
+                self.remove(id)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// List all soft-deleted records
+    pub fn list_deleted(&self) -> Vec<Record> {
+        self.records
+            .values()
+            .filter(|r| r.deleted)
+            .cloned()
+            .collect()
+    }
+
+    /// List all active (non-deleted) records
+    pub fn list_active(&self) -> Vec<Record> {
+        self.records
+            .values()
+            .filter(|r| !r.deleted)
+            .cloned()
+            .collect()
+    }
+
+    /// Fetch a record by ID
+    ///
+    /// Reads straight from the record map without touching the index.
+    /// Soft-deleted and expired records are treated as absent.
+    pub fn get(&self, id: &str) -> Option<Record> {
+        let now = Utc::now().timestamp();
+        self.records
+            .get(id)
+            .filter(|r| r.is_live(now))
+            .map(|r| self.with_full_vector(r))
+    }
+
+    /// Clone `record`, restoring a vector dropped by quantization
+    fn with_full_vector(&self, record: &Record) -> Record {
+        let mut record = record.clone();
+        if record.vector.is_empty() {
+            record.vector = full_vector(&record, self.quantized.as_ref()).into_owned();
+        }
+        record
+    }
+
+    /// Fetch several records by ID, preserving the order of `ids`
+    ///
+    /// Missing or soft-deleted IDs yield `None` in their slot.
+    pub fn get_many(&self, ids: &[Id]) -> Vec<Option<Record>> {
+        ids.iter().map(|id| self.get(id)).collect()
+    }
+
+    /// Fetch a chunk with up to `n` chunks either side of it from the same
+    /// parent document, in document order
+    ///
+    /// Siblings are the live records of the chunk's namespace sharing its
+    /// [`parent_id`](Record::parent_id), ordered by
+    /// [`chunk_index`](Record::chunk_index) and then ID. A record without a
+    /// parent comes back alone.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, Query, QueryOptions};
+    /// # let store = VecStore::open("data")?;
+    /// let options = QueryOptions { include_text: true, ..Default::default() };
+    /// let hit = &store.query(Query::new(vec![0.1, 0.2]).with_options(options))?[0];
+    /// let context: Vec<String> = store
+    ///     .fetch_parent_window(&hit.id, 2)?
+    ///     .into_iter()
+    ///     .filter_map(|chunk| chunk.text)
+    ///     .collect();
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn fetch_parent_window(&self, id: &str, n: usize) -> Result<Vec<Record>> {
+        let now = Utc::now().timestamp();
+        let record = self
+            .records
+            .get(id)
+            .filter(|r| r.is_live(now))
+            .ok_or_else(|| anyhow::anyhow!("Record not found: {}", id))?;
+        let Some(parent_id) = &record.parent_id else {
+            return Ok(vec![self.with_full_vector(record)]);
+        };
+
+        let mut siblings: Vec<&Record> = self
+            .records
+            .values()
+            .filter(|r| {
+                r.parent_id.as_ref() == Some(parent_id)
+                    && r.namespace == record.namespace
+                    && r.is_live(now)
+            })
+            .collect();
+        siblings.sort_by(|a, b| (a.chunk_index, &a.id).cmp(&(b.chunk_index, &b.id)));
+
+        let position = siblings
+            .iter()
+            .position(|r| r.id == record.id)
+            .unwrap_or_default();
+        let start = position.saturating_sub(n);
+        let end = (position + n + 1).min(siblings.len());
+        Ok(siblings[start..end]
+            .iter()
+            .map(|r| self.with_full_vector(r))
+            .collect())
+    }
+
+    /// Check whether a live (not deleted or expired) record exists for `id`
+    pub fn contains(&self, id: &str) -> bool {
+        let now = Utc::now().timestamp();
+        self.records.get(id).is_some_and(|r| r.is_live(now))
+    }
+
+    /// Iterate over all active records, ordered by ID
+    ///
+    /// Walks the record map directly, so unlike `query` it is not capped by `k`
+    /// and needs no query vector. Soft-deleted and expired records are skipped.
+    /// Records whose f32 vector was dropped by int8 quantization yield an
+    /// empty `vector`; [`get`](Self::get) and [`scan`](Self::scan) dequantize it.
+    pub fn iter(&self) -> impl Iterator<Item = &Record> + '_ {
+        let now = Utc::now().timestamp();
+        let mut records: Vec<&Record> = self.records.values().filter(|r| r.is_live(now)).collect();
+        records.sort_by(|a, b| a.id.cmp(&b.id));
+        records.into_iter()
+    }
+
+    /// Page through active records matching `filter`, ordered by ID
+    ///
+    /// Skips the first `offset` matches and returns at most `limit` records.
+    pub fn scan(&self, filter: Option<FilterExpr>, offset: usize, limit: usize) -> Vec<Record> {
+        self.iter()
+            .filter(|r| match &filter {
+                Some(f) => filters::evaluate_filter(f, &r.metadata),
+                None => true,
+            })
+            .skip(offset)
+            .take(limit)
+            .map(|r| self.with_full_vector(r))
+            .collect()
+    }
+
+    /// Cursor-style pagination: return up to `limit` active records whose ID
+    /// sorts after `last_id` (or from the start when `None`)
+    ///
+    /// Pass the ID of the last record from the previous page to fetch the next
+    /// one. Unlike offsets, cursors stay stable when records are inserted or
+    /// deleted between pages.
+    pub fn scan_after(&self, last_id: Option<&str>, limit: usize) -> Vec<Record> {
+        self.iter()
+            .skip_while(|r| last_id.is_some_and(|last| r.id.as_str() <= last))
+            .take(limit)
+            .map(|r| self.with_full_vector(r))
+            .collect()
+    }
+
+    /// Up to `n` active records matching `filter`, picked uniformly at random
+    ///
+    /// Reservoir sampling over [`iter`](Self::iter), so every match is equally
+    /// likely to be picked and only `n` records are held at a time. The same
+    /// `seed` picks the same records while the store is unchanged. Returned in
+    /// ID order.
+    pub fn sample(&self, n: usize, filter: Option<FilterExpr>, seed: u64) -> Vec<Record> {
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        let mut reservoir: Vec<&Record> = Vec::with_capacity(n);
+        let matches = self.iter().filter(|r| match &filter {
+            Some(f) => filters::evaluate_filter(f, &r.metadata),
+            None => true,
+        });
+        for (seen, record) in matches.enumerate() {
+            if reservoir.len() < n {
+                reservoir.push(record);
+            } else {
+                let slot = rng.gen_range(0..=seen);
+                if slot < n {
+                    reservoir[slot] = record;
+                }
+            }
+        }
+        reservoir.sort_by(|a, b| a.id.cmp(&b.id));
+        reservoir
+            .into_iter()
+            .map(|r| self.with_full_vector(r))
+            .collect()
+    }
+
+    /// Count active records matching `filter` (all active records when `None`)
+    ///
+    /// Scans the record map; the vector index is not consulted.
+    pub fn count_where(&self, filter: Option<FilterExpr>) -> usize {
+        self.matching(filter.as_ref()).count()
+    }
+
+    /// Count active records matching `filter`, grouped by the value of `group_by`
+    ///
+    /// String values are used as-is and other values by their JSON text, so
+    /// `2024` and `true` group as `"2024"` and `"true"`. Array fields count once
+    /// under each element. Records where the field is missing or null are skipped.
+    pub fn aggregate(&self, filter: Option<FilterExpr>, group_by: &str) -> HashMap<String, usize> {
+        let mut groups = HashMap::new();
+        for record in self.matching(filter.as_ref()) {
+            if let Some(value) = record.metadata.fields.get(group_by) {
+                for key in group_keys(value) {
+                    *groups.entry(key).or_insert(0) += 1;
+                }
+            }
+        }
+        groups
+    }
+
+    /// Distinct values of a metadata field across active records, sorted, at most `limit`
+    ///
+    /// Values are keyed the same way as [`aggregate`](Self::aggregate).
+    pub fn distinct_values(&self, field: &str, limit: usize) -> Vec<String> {
+        let values: std::collections::BTreeSet<String> = self
+            .matching(None)
+            .filter_map(|r| r.metadata.fields.get(field))
+            .flat_map(group_keys)
+            .collect();
+        values.into_iter().take(limit).collect()
+    }
+
+    /// Active records passing `filter`, in no particular order
+    fn matching<'a>(&'a self, filter: Option<&'a FilterExpr>) -> impl Iterator<Item = &'a Record> {
+        let now = Utc::now().timestamp();
+        self.records.values().filter(move |r| {
+            r.is_live(now)
+                && match filter {
+                    Some(f) => filters::evaluate_filter(f, &r.metadata),
+                    None => true,
+                }
+        })
+    }
+
+    /// Execute a batch of mixed operations (upsert, delete, soft delete, restore, update metadata)
+    ///
+    /// This is significantly faster than executing operations individually and provides
+    /// transactional-like semantics with detailed error reporting for each failed operation.
+    ///
+    /// # Arguments
+    /// * `operations` - Vector of BatchOperation variants to execute
+    ///
+    /// # Returns
+    /// * `BatchResult` with success/failure counts, detailed errors, and duration
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, BatchOperation, Metadata};
+    /// # use std::collections::HashMap;
+    /// # let mut store = VecStore::open("./data").unwrap();
+    /// let operations = vec![
+    ///     BatchOperation::Upsert {
+    ///         id: "doc1".into(),
+    ///         vector: vec![0.1, 0.2, 0.3],
+    ///         metadata: Metadata { fields: HashMap::new() },
+    ///     },
+    ///     BatchOperation::Delete { id: "doc2".into() },
+    ///     BatchOperation::SoftDelete { id: "doc3".into() },
+    /// ];
+    ///
+    /// let result = store.batch_execute(operations).unwrap();
+    /// println!("Succeeded: {}, Failed: {}", result.succeeded, result.failed);
+    /// ```
+    pub fn batch_execute(&mut self, operations: Vec<BatchOperation>) -> Result<BatchResult> {
+        let start = std::time::Instant::now();
+
+        if self.config.vector_policy.strict_dimension {
+            self.check_batch_dimensions(&operations)?;
+        }
+        let mut succeeded = 0;
+        let mut failed = 0;
+        let mut errors = Vec::new();
+
+        for (index, op) in operations.into_iter().enumerate() {
+            let result = match &op {
+                BatchOperation::Upsert {
+                    id,
+                    vector,
+                    metadata,
+                } => self
+                    .upsert(id.clone(), vector.clone(), metadata.clone())
+                    .map_err(|e| (format!("upsert({})", id), e)),
+                BatchOperation::UpsertIfVersion {
+                    id,
+                    expected_version,
+                    vector,
+                    metadata,
+                } => self
+                    .upsert_if_version(
+                        id.clone(),
+                        *expected_version,
+                        vector.clone(),
+                        metadata.clone(),
+                    )
+                    .map(|_| ())
+                    .map_err(|e| (format!("upsert({})", id), e)),
+                BatchOperation::Delete { id } => {
+                    self.remove(id).map_err(|e| (format!("delete({})", id), e))
+                }
+                BatchOperation::SoftDelete { id } => self
+                    .soft_delete(id)
+                    .map(|_| ())
+                    .map_err(|e| (format!("soft_delete({})", id), e)),
+                BatchOperation::Restore { id } => self
+                    .restore(id)
+                    .map(|_| ())
+                    .map_err(|e| (format!("restore({})", id), e)),
+                BatchOperation::UpdateMetadata { id, metadata } => self
+                    .replace_metadata(id, metadata.clone())
+                    .map_err(|e| (format!("update_metadata({})", id), e)),
+                BatchOperation::PatchMetadata { id, patch } => self
+                    .update_metadata(id, patch.clone())
+                    .map_err(|e| (format!("patch_metadata({})", id), e)),
+            };
+
+            match result {
+                Ok(_) => succeeded += 1,
+                Err((operation, error)) => {
+                    failed += 1;
+                    errors.push(BatchError {
+                        index,
+                        operation,
+                        error: error.to_string(),
+                        version_conflict: error.downcast_ref::<VersionConflict>().cloned(),
+                    });
+                }
+            }
+        }
+
+        Ok(BatchResult {
+            succeeded,
+            failed,
+            errors,
+            duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+        })
+    }
+
+    /// Convenience method: Delete a record (alias for remove)
+    ///
+    /// This provides consistency with BatchOperation::Delete naming
+    pub fn delete(&mut self, id: &str) -> Result<()> {
+        self.remove(id)
+    }
+
+    /// Merge fields into the metadata of an existing record
+    ///
+    /// Keys in `patch` overwrite existing fields; all other fields are kept.
+    /// Only the record store is touched, so the vector is not re-inserted into
+    /// the index. The change is persisted on the next `save()`.
+    ///
+    /// # Arguments
+    /// * `id` - ID of the record to update
+    /// * `patch` - Fields to add or overwrite
+    ///
+    /// # Returns
+    /// * `Ok(())` if successful
+    /// * `Err` if record not found (or soft-deleted)
+    pub fn update_metadata(
+        &mut self,
+        id: &str,
+        patch: HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        self.active_record_mut(id)?.metadata.fields.extend(patch);
+        self.field_indexes.insert(id, &self.records[id].metadata);
+        self.log_metadata(id)?;
+        self.emit_upsert(id);
+        Ok(())
+    }
+
+    /// Replace the metadata of an existing record wholesale
+    ///
+    /// Like [`update_metadata`](Self::update_metadata), this leaves the index untouched.
+    ///
+    /// # Returns
+    /// * `Ok(())` if successful
+    /// * `Err` if record not found (or soft-deleted)
+    pub fn replace_metadata(&mut self, id: &str, metadata: Metadata) -> Result<()> {
+        self.active_record_mut(id)?.metadata = metadata;
+        self.field_indexes.insert(id, &self.records[id].metadata);
+        self.log_metadata(id)?;
+        self.emit_upsert(id);
+        Ok(())
+    }
+
+    /// Merge metadata patches into many records at once
+    ///
+    /// Each patch is applied independently; unknown IDs are reported in the
+    /// returned `BatchResult` rather than aborting the batch.
+    pub fn batch_update_metadata(
+        &mut self,
+        patches: Vec<(Id, HashMap<String, serde_json::Value>)>,
+    ) -> Result<BatchResult> {
+        let operations = patches
+            .into_iter()
+            .map(|(id, patch)| BatchOperation::PatchMetadata { id, patch })
+            .collect();
+        self.batch_execute(operations)
+    }
+
+    /// Apply the vector policy to a vector about to be inserted
+    fn apply_vector_policy(&self, id: &str, vector: &mut [f32]) -> Result<()> {
+        let policy = self.config.vector_policy;
+        if policy.reject_nan && vector.iter().any(|x| !x.is_finite()) {
+            return Err(anyhow::anyhow!(
+                "Vector for '{}' contains NaN or infinite values",
+                id
+            ));
+        }
+        if policy.normalize {
+            l2_normalize(vector);
+        }
+        self.check_dot_product_norm(vector)
+            .with_context(|| format!("Vector for '{}' rejected", id))
+    }
+
+    /// Apply the vector policy to a query vector
+    fn apply_query_policy(&self, vector: &mut [f32]) -> Result<()> {
+        let policy = self.config.vector_policy;
+        if policy.reject_nan && vector.iter().any(|x| !x.is_finite()) {
+            return Err(anyhow::anyhow!(
+                "Query vector contains NaN or infinite values"
+            ));
+        }
+        if policy.normalize {
+            l2_normalize(vector);
+        }
+        self.check_dot_product_norm(vector)
+            .context("Query vector rejected")
+    }
+
+    /// Fail if a DotProduct store is given a vector longer than 1
+    ///
+    /// The index ranks by `1 - dot`, which must not go negative.
+    fn check_dot_product_norm(&self, vector: &[f32]) -> Result<()> {
+        if self.config.distance != Distance::DotProduct {
+            return Ok(());
+        }
+        let norm = crate::simd::magnitude_simd(vector);
+        if norm > MAX_DOT_PRODUCT_NORM {
+            return Err(anyhow::anyhow!(
+                "DotProduct needs vectors of length at most 1, got {:.4}; \
+                 normalize them or set `VectorPolicy::normalize`",
+                norm
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fail if any upsert in `operations` has the wrong dimension
+    fn check_batch_dimensions(&self, operations: &[BatchOperation]) -> Result<()> {
+        let mut expected = (self.dimension > 0).then_some(self.dimension);
+        for op in operations {
+            if let BatchOperation::Upsert { id, vector, .. }
+            | BatchOperation::UpsertIfVersion { id, vector, .. } = op
+            {
+                let want = *expected.get_or_insert(vector.len());
+                if vector.is_empty() || vector.len() != want {
+                    return Err(anyhow::anyhow!(
+                        "Vector dimension mismatch for {}: expected {}, got {}; no operations applied",
+                        id,
+                        want,
+                        vector.len()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Live record for an in-place change; it gets a new sequence number and
+    /// version
+    fn active_record_mut(&mut self, id: &str) -> Result<&mut Record> {
+        let now = Utc::now().timestamp();
+        let record = self
+            .records
+            .get_mut(id)
+            .filter(|r| r.is_live(now))
+            .ok_or_else(|| anyhow::anyhow!("Record not found: {}", id))?;
+        record.seq = self.changes.next_seq();
+        record.version += 1;
+        Ok(record)
+    }
+
+    /// Estimate query cost and validate query parameters
+    ///
+    /// This provides pre-flight validation and cost estimation for queries,
+    /// helping users understand query complexity and catch errors early.
+    ///
+    /// # Arguments
+    /// * `q` - The query to estimate
+    ///
+    /// # Returns
+    /// * `QueryEstimate` with validation results and cost information
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, Query};
+    /// # let store = VecStore::open("./data").unwrap();
+    /// let query = Query {
+    ///     vector: vec![0.1, 0.2, 0.3],
+    ///     k: 100,
+    ///     filter: None,
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let estimate = store.estimate_query(&query);
+    /// if !estimate.valid {
+    ///     println!("Query errors: {:?}", estimate.errors);
+    /// } else {
+    ///     println!("Estimated cost: {}", estimate.cost_estimate);
+    ///     println!("Estimated duration: {}ms", estimate.estimated_duration_ms);
+    /// }
+    /// ```
+    pub fn estimate_query(&self, q: &Query) -> QueryEstimate {
+        let mut errors = Vec::new();
+        let mut recommendations = Vec::new();
+
+        // Validate dimension
+        if self.dimension == 0 {
+            return QueryEstimate {
+                valid: true,
+                errors: vec![],
+                cost_estimate: 0.0,
+                estimated_distance_calculations: 0,
+                estimated_nodes_visited: 0,
+                will_overfetch: false,
+                recommendations: vec!["Store is empty, query will return no results".to_string()],
+                estimated_duration_ms: 0.0,
+            };
+        }
+
+        if q.vector.len() != self.dimension {
+            errors.push(format!(
+                "Vector dimension mismatch: expected {}, got {}",
+                self.dimension,
+                q.vector.len()
+            ));
+        }
+
+        // Validate k
+        if q.k == 0 {
+            errors.push("k must be greater than 0".to_string());
+        }
+
+        if q.k > 1000 {
+            recommendations.push(format!(
+                "Large k value ({}). Consider reducing for better performance",
+                q.k
+            ));
+        }
+
+        // Determine if query is valid
+        let valid = errors.is_empty();
+
+        // Estimate computational cost
+        let total_records = self.records.len();
+        let active_records = self.active_count();
+
+        // Calculate fetch size (same logic as query())
+        let fetch_size = if q.filter.is_some() {
+            if total_records <= q.k {
+                total_records
+            } else {
+                // Using saturating_mul to prevent overflow (Major Issue #10 fix)
+                std::cmp::min(q.k.saturating_mul(10), total_records)
+            }
+        } else {
+            std::cmp::min(q.k, total_records)
+        };
+
+        // Check for over-fetching
+        let will_overfetch = q.filter.is_some() && fetch_size > q.k;
+        if will_overfetch {
+            recommendations.push(format!(
+                "Filter will cause over-fetching: requesting {} candidates to find {} results",
+                fetch_size, q.k
+            ));
+        }
+
+        // Estimate distance calculations (HNSW typically visits ~log(n) * M nodes)
+        let m = 16; // Default HNSW M parameter
+        let estimated_nodes = if total_records <= 1 {
+            1
+        } else {
+            ((total_records as f32).log2() * m as f32).min(fetch_size as f32) as usize
+        };
+
+        // Cost estimate (0.0 - 1.0)
+        // Factors: k, filter presence, over-fetching, database size
+        let base_cost = (q.k as f32 / 100.0).min(1.0) * 0.3;
+        let filter_cost = if q.filter.is_some() { 0.3 } else { 0.0 };
+        let overfetch_cost = if will_overfetch { 0.2 } else { 0.0 };
+        let size_cost = (total_records as f32 / 100000.0).min(1.0) * 0.2;
+
+        let cost_estimate = (base_cost + filter_cost + overfetch_cost + size_cost).min(1.0);
+
+        // Estimate duration (very rough heuristic)
+        // Base: 0.1ms per distance calculation
+        // Filter overhead: +50%
+        let base_duration = estimated_nodes as f32 * 0.1;
+        let filter_overhead = if q.filter.is_some() {
+            base_duration * 0.5
+        } else {
+            0.0
+        };
+        let estimated_duration_ms = base_duration + filter_overhead;
+
+        // Add recommendations
+        if active_records < total_records {
+            let deleted = total_records - active_records;
+            recommendations.push(format!(
+                "{} soft-deleted records present. Consider running compact() to improve performance",
+                deleted
+            ));
+        }
+
+        if q.filter.is_some() {
+            recommendations.push(
+                "Filtered queries are slower. Consider reducing k or adding indexes for better performance"
+                    .to_string(),
+            );
+        }
+
+        QueryEstimate {
+            valid,
+            errors,
+            cost_estimate,
+            estimated_distance_calculations: estimated_nodes,
+            estimated_nodes_visited: estimated_nodes,
+            will_overfetch,
+            recommendations,
+            estimated_duration_ms,
+        }
+    }
+
+    /// Set auto-compaction configuration
+    ///
+    /// # Arguments
+    /// * `config` - Compaction configuration
+    pub fn set_compaction_config(&mut self, config: CompactionConfig) {
+        self.compaction_config = config;
+    }
+
+    /// Get current compaction configuration
+    pub fn compaction_config(&self) -> &CompactionConfig {
+        &self.compaction_config
+    }
+
+    /// Check if auto-compaction should run and execute it if needed
+    ///
+    /// This method should be called periodically or after operations that may
+    /// generate deleted records (e.g., after soft_delete or TTL expiration).
+    ///
+    /// # Returns
+    /// * `CompactionResult` with statistics about the compaction
+    pub fn maybe_compact(&mut self) -> Result<CompactionResult> {
+        self.defragment_if_needed()?;
+        if !self.compaction_config.enabled {
+            return Ok(CompactionResult {
+                removed_count: 0,
+                duration_ms: 0.0,
+                triggered: false,
+                reason: "Auto-compaction is disabled".to_string(),
+                bytes_reclaimed: 0,
+            });
+        }
+
+        let deleted_count = self.deleted_count();
+        let total_count = self.records.len();
+
+        let deleted_ratio = if total_count > 0 {
+            deleted_count as f32 / total_count as f32
+        } else {
+            0.0
+        };
+
+        let should_compact = deleted_count >= self.compaction_config.min_deleted_records
+            && deleted_ratio >= self.compaction_config.min_deleted_ratio;
+
+        if !should_compact {
+            return Ok(CompactionResult {
+                removed_count: 0,
+                duration_ms: 0.0,
+                triggered: false,
+                reason: format!(
+                    "Thresholds not met: {} deleted records ({:.1}% ratio), need {} records and {:.1}% ratio",
+                    deleted_count,
+                    deleted_ratio * 100.0,
+                    self.compaction_config.min_deleted_records,
+                    self.compaction_config.min_deleted_ratio * 100.0
+                ),
+                bytes_reclaimed: 0,
+            });
+        }
+
+        // Run compaction
+        let start = std::time::Instant::now();
+        let report = self.compact()?;
+        let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+        Ok(CompactionResult {
+            removed_count: report.removed_count,
+            duration_ms,
+            triggered: true,
+            reason: format!(
+                "Compaction triggered: {} deleted records ({:.1}% ratio)",
+                deleted_count,
+                deleted_ratio * 100.0
+            ),
+            bytes_reclaimed: report.bytes_reclaimed(),
+        })
+    }
+
+    /// Tombstone records whose TTL has passed
+    ///
+    /// Expired records are already hidden from queries and lookups; this
+    /// soft-deletes them so the next [`compact`](Self::compact) reclaims
+    /// their space.
+    ///
+    /// # Returns
+    /// * Number of records purged
+    pub fn purge_expired(&mut self) -> Result<usize> {
+        let now = Utc::now().timestamp();
+        let expired: Vec<Id> = self
+            .records
+            .values()
+            .filter(|r| !r.deleted && r.is_expired(now))
+            .map(|r| r.id.clone())
+            .collect();
+
+        for id in &expired {
+            self.soft_delete(id)?;
+        }
+        Ok(expired.len())
+    }
+
+    /// Expire TTL records (soft delete them)
+    ///
+    /// Same as [`purge_expired`](Self::purge_expired).
+    pub fn expire_ttl_records(&mut self) -> Result<usize> {
+        self.purge_expired()
+    }
+
+    /// Set TTL for an existing record
+    ///
+    /// # Arguments
+    /// * `id` - Record ID
+    /// * `ttl_seconds` - Time-to-live in seconds from now
+    ///
+    /// # Returns
+    /// * `Ok(())` if successful
+    /// * `Err` if record not found
+    pub fn set_ttl(&mut self, id: &str, ttl_seconds: i64) -> Result<()> {
+        self.set_expires_at(id, Some(Utc::now().timestamp() + ttl_seconds))
+    }
+
+    /// Set the expiry of an existing record, as logged by [`set_ttl`](Self::set_ttl)
+    fn set_expires_at(&mut self, id: &str, expires_at: Option<i64>) -> Result<()> {
+        let record = self
+            .records
+            .get_mut(id)
+            .ok_or_else(|| anyhow::anyhow!("Record not found: {}", id))?;
+        record.expires_at = expires_at;
+        record.seq = self.changes.next_seq();
+        record.version += 1;
+        self.log(LogEntry::SetTtl {
+            id: id.to_string(),
+            expires_at,
+        })
+    }
+
+    /// Upsert with TTL
+    ///
+    /// The record stops appearing in queries and lookups once `ttl_seconds`
+    /// have passed, and is tombstoned by [`purge_expired`](Self::purge_expired).
+    ///
+    /// # Arguments
+    /// * `id` - Record ID
+    /// * `vector` - Vector data
+    /// * `metadata` - Metadata
+    /// * `ttl_seconds` - Time-to-live in seconds from now
+    pub fn upsert_with_ttl(
+        &mut self,
+        id: Id,
+        vector: Vec<f32>,
+        metadata: Metadata,
+        ttl_seconds: i64,
+    ) -> Result<()> {
+        self.upsert_record(Record {
+            expires_at: Some(Utc::now().timestamp() + ttl_seconds),
+            ..make_record(id, vector, metadata)
+        })
+    }
+
+    /// Execute a multi-stage prefetch query for advanced RAG patterns
+    ///
+    /// This enables complex retrieval pipelines like:
+    /// 1. Broad hybrid search (fetch 100 candidates)
+    /// 2. Rerank with cross-encoder (score top 20)
+    /// 3. Apply MMR for diversity (final 10 results)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::{VecStore, PrefetchQuery, QueryStage};
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// # let store = VecStore::open(temp_dir.path()).unwrap();
+    /// let query = PrefetchQuery {
+    ///     stages: vec![
+    ///         QueryStage::HybridSearch {
+    ///             vector: vec![0.1, 0.2, 0.3],
+    ///             keywords: "machine learning".into(),
+    ///             k: 100,
+    ///             alpha: 0.7,
+    ///             filter: None,
+    ///         },
+    ///         QueryStage::MMR {
+    ///             k: 10,
+    ///             lambda: 0.7, // 70% relevance, 30% diversity
+    ///         },
+    ///     ],
+    /// };
+    ///
+    /// let results = store.prefetch_query(query)?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn prefetch_query(&self, query: PrefetchQuery) -> Result<Vec<Neighbor>> {
+        if query.stages.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Prefetch query must have at least one stage"
+            ));
+        }
+
+        // Validate first stage is a search stage (Major Issue #16 fix)
+        if let Some(first_stage) = query.stages.first() {
+            match first_stage {
+                QueryStage::VectorSearch { .. } | QueryStage::HybridSearch { .. } => {
+                    // Valid first stage
+                }
+                QueryStage::Rerank { .. } => {
+                    return Err(anyhow::anyhow!(
+                        "First stage must be VectorSearch or HybridSearch, not Rerank. Rerank requires previous search results."
+                    ));
+                }
+                QueryStage::MMR { .. } => {
+                    return Err(anyhow::anyhow!(
+                        "First stage must be VectorSearch or HybridSearch, not MMR. MMR requires previous search results."
+                    ));
+                }
+                QueryStage::Filter { .. } => {
+                    return Err(anyhow::anyhow!(
+                        "First stage must be VectorSearch or HybridSearch, not Filter. Filter requires previous search results."
+                    ));
+                }
+            }
+        }
+
+        let mut candidates = Vec::new();
+
+        for (stage_idx, stage) in query.stages.iter().enumerate() {
+            candidates = match stage {
+                QueryStage::VectorSearch { vector, k, filter } => {
+                    // Stage 1: Vector search
+                    self.query(Query {
+                        vector: vector.clone(),
+                        k: *k,
+                        filter: filter.clone(),
+                        ..Default::default()
+                    })?
+                }
+
+                QueryStage::HybridSearch {
+                    vector,
+                    keywords,
+                    k,
+                    alpha,
+                    filter,
+                } => {
+                    // Stage 1: Hybrid search
+                    self.hybrid_query(HybridQuery {
+                        vector: vector.clone(),
+                        keywords: keywords.clone(),
+                        k: *k,
+                        alpha: *alpha,
+                        filter: filter.clone(),
+                    })?
+                }
+
+                QueryStage::Rerank { k, model: _ } => {
+                    // Stage 2+: Rerank existing candidates
+                    if candidates.is_empty() {
+                        return Err(anyhow::anyhow!(
+                            "Rerank stage {} requires previous stage results",
+                            stage_idx
+                        ));
+                    }
+
+                    // For now, just take top K (actual reranking would use cross-encoder)
+                    // TODO: Integrate cross-encoder model if specified
+                    candidates.truncate(*k);
+                    candidates
+                }
+
+                QueryStage::MMR { k, lambda } => {
+                    // Stage 2+: Maximal Marginal Relevance for diversity
+                    if candidates.is_empty() {
+                        return Err(anyhow::anyhow!(
+                            "MMR stage {} requires previous stage results",
+                            stage_idx
+                        ));
+                    }
+
+                    self.apply_mmr(&candidates, *k, *lambda)?
+                }
+
+                QueryStage::Filter { expr } => {
+                    // Stage 2+: Additional filtering
+                    if candidates.is_empty() {
+                        return Err(anyhow::anyhow!(
+                            "Filter stage {} requires previous stage results",
+                            stage_idx
+                        ));
+                    }
+
+                    candidates
+                        .into_iter()
+                        .filter(|n| {
+                            if let Some(record) = self.records.get(&n.id) {
+                                filters::evaluate_filter(expr, &record.metadata)
+                            } else {
+                                false
+                            }
+                        })
+                        .collect()
+                }
+            };
+        }
+
+        Ok(candidates)
+    }
+
+    /// Apply Maximal Marginal Relevance for diversity
+    fn apply_mmr(&self, candidates: &[Neighbor], k: usize, lambda: f32) -> Result<Vec<Neighbor>> {
+        use crate::simd::cosine_similarity_simd;
+
+        if candidates.is_empty() || k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let k = std::cmp::min(k, candidates.len());
+        let mut selected = Vec::new();
+        let mut remaining: Vec<_> = candidates.iter().collect();
+
+        // Select first result (highest score)
+        selected.push(remaining.remove(0).clone());
+
+        // Iteratively select diverse results
+        while selected.len() < k && !remaining.is_empty() {
+            let mut best_idx = 0;
+            let mut best_mmr_score = f32::NEG_INFINITY;
+
+            for (idx, candidate) in remaining.iter().enumerate() {
+                let candidate_record = match self.records.get(&candidate.id) {
+                    Some(r) => r,
+                    None => continue,
+                };
+
+                // Relevance score (from original query)
+                let relevance = candidate.score;
+
+                // Max similarity to already selected results
+                let mut max_sim: f32 = 0.0;
+                for selected_neighbor in &selected {
+                    if let Some(selected_record) = self.records.get(&selected_neighbor.id) {
+                        let sim = cosine_similarity_simd(
+                            &full_vector(candidate_record, self.quantized.as_ref()),
+                            &full_vector(selected_record, self.quantized.as_ref()),
+                        );
+                        max_sim = max_sim.max(sim);
+                    }
+                }
+
+                // MMR score: lambda * relevance - (1 - lambda) * max_similarity
+                let mmr_score = lambda * relevance - (1.0 - lambda) * max_sim;
+
+                if mmr_score > best_mmr_score {
+                    best_mmr_score = mmr_score;
+                    best_idx = idx;
+                }
+            }
+
+            selected.push(remaining.remove(best_idx).clone());
+        }
+
+        Ok(selected)
+    }
+
+    /// Query with custom HNSW search parameters for performance tuning
+    ///
+    /// Allows per-query control over the speed/accuracy tradeoff.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::{VecStore, Query, HNSWSearchParams};
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// # let store = VecStore::open(temp_dir.path()).unwrap();
+    /// // Fast search (lower recall, faster)
+    /// let results = store.query_with_params(
+    ///     Query::new(vec![0.1, 0.2, 0.3]).with_limit(10),
+    ///     HNSWSearchParams::fast(),
+    /// )?;
+    ///
+    /// // High recall search (better accuracy, slower)
+    /// let results = store.query_with_params(
+    ///     Query::new(vec![0.1, 0.2, 0.3]).with_limit(10),
+    ///     HNSWSearchParams::high_recall(),
+    /// )?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn query_with_params(
+        &self,
+        mut q: Query,
+        params: HNSWSearchParams,
+    ) -> Result<Vec<Neighbor>> {
+        if self.dimension == 0 {
+            return Ok(Vec::new());
+        }
+
+        if q.vector.len() != self.dimension {
+            return Err(anyhow::anyhow!(
+                "Query dimension mismatch: expected {}, got {}",
+                self.dimension,
+                q.vector.len()
+            ));
+        }
+        self.apply_query_policy(&mut q.vector)?;
+
+        // Validate ef_search parameter (Major Issue #14 fix)
+        if params.ef_search == 0 {
+            return Err(anyhow::anyhow!(
+                "Invalid ef_search parameter: must be at least 1, got 0"
+            ));
+        }
+
+        // Use custom ef_search parameter
+        let fetch_size = if q.filter.is_some() || q.namespace.is_some() {
+            // Using saturating_mul to prevent overflow (Major Issue #10 fix)
+            std::cmp::min(q.k.saturating_mul(10), self.records.len())
+        } else {
+            // Ensure we fetch at least k neighbors (Major Issue #8 fix)
+            std::cmp::min(std::cmp::max(q.k, params.ef_search), self.records.len())
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let backend_results = self
+            .backend
+            .search_with_ef(&q.vector, fetch_size, params.ef_search)
+            .unwrap_or_else(|_| self.backend.search(&q.vector, fetch_size));
+
+        #[cfg(target_arch = "wasm32")]
+        let backend_results = self
+            .backend
+            .search_with_ef(&q.vector, fetch_size, params.ef_search)
+            .or_else(|_| self.backend.search(&q.vector, fetch_size))?;
+
+        // Convert (Id, f32) to Neighbor with metadata
+        let mut results: Vec<Neighbor> = backend_results
+            .into_iter()
+            .filter_map(|(id, score)| {
+                self.records.get(&id).map(|record| Neighbor {
+                    id,
+                    score,
+                    metadata: record.metadata.clone(),
+                    vector: None,
+                    version: record.version,
+                    text: None,
+                })
+            })
+            .collect();
+
+        // Apply namespace and filter
+        results.retain(|n| {
+            self.records
+                .get(&n.id)
+                .is_some_and(|record| filters::admits(&q, record))
+        });
+
+        // Limit to k results
+        results.truncate(q.k);
+
+        Ok(self.shape_results(results, &q.options))
+    }
+
+    /// Explain how a query will be executed and estimate its cost
+    ///
+    /// This is useful for:
+    /// - Understanding query performance
+    /// - Optimizing complex queries
+    /// - Debugging slow queries
+    /// - Capacity planning
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::{VecStore, Query};
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// # let store = VecStore::open(temp_dir.path()).unwrap();
+    /// let query = Query::new(vec![0.1, 0.2, 0.3])
+    ///     .with_limit(10)
+    ///     .with_filter("category = 'tech' AND score > 0.9");
+    ///
+    /// let plan = store.explain_query(query)?;
+    ///
+    /// println!("Query type: {}", plan.query_type);
+    /// println!("Estimated cost: {:.2}", plan.estimated_cost);
+    /// println!("Estimated duration: {:.2}ms", plan.estimated_duration_ms);
+    ///
+    /// for step in plan.steps {
+    ///     println!("  Step {}: {} (cost: {:.2})", step.step, step.description, step.cost);
+    /// }
+    ///
+/// for rec in plan.recommendations {
+///     println!("Hint: {}", rec);
+/// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn explain_query(&self, q: Query) -> Result<QueryPlan> {
+        // Validate query vector is not empty (Major Issue #15 fix)
+        if q.vector.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Query vector cannot be empty. Provide a valid embedding vector."
+            ));
+        }
+
+        // Validate dimension matches store dimension (if store is initialized)
+        if self.dimension > 0 && q.vector.len() != self.dimension {
+            return Err(anyhow::anyhow!(
+                "Query dimension mismatch: expected {}, got {}",
+                self.dimension,
+                q.vector.len()
+            ));
+        }
+
+        let total_records = self.records.len();
+        let has_filter = q.filter.is_some();
+
+        let mut steps = Vec::new();
+        let mut estimated_cost = 0.0;
+        let mut recommendations = Vec::new();
+
+        // Handle empty or near-empty stores (Major Issue #11 fix)
+        if total_records < 2 {
+            recommendations.push(
+                "Store has fewer than 2 records. Ingest more data for meaningful query planning."
+                    .to_string(),
+            );
+            return Ok(QueryPlan {
+                query_type: "vector".to_string(),
+                steps,
+                estimated_cost: 0.0,
+                estimated_duration_ms: 0.0,
+                recommendations,
+                is_optimal: false,
+            });
+        }
+
+        // Step 1: HNSW graph traversal
+        let hnsw_search_size = if has_filter {
+            // Using saturating_mul to prevent overflow (Major Issue #10 fix)
+            std::cmp::min(q.k.saturating_mul(10), total_records)
+        } else {
+            // Using saturating_mul to prevent overflow (Major Issue #10 fix)
+            std::cmp::min(q.k.saturating_mul(2), total_records)
+        };
+
+        let hnsw_cost = (hnsw_search_size as f32).ln() / (total_records as f32).ln();
+        estimated_cost += hnsw_cost;
+
+        steps.push(QueryStep {
+            step: 1,
+            description: format!(
+                "HNSW graph traversal (ef_search=50, fetch={})",
+                hnsw_search_size
+            ),
+            cost: hnsw_cost,
+            input_size: total_records,
+            output_size: hnsw_search_size,
+        });
+
+        // Step 2: Filter application (if present)
+        if let Some(ref filter) = q.filter {
+            let filter_selectivity = self.estimate_filter_selectivity(filter);
+            let filter_cost = 0.1 * (1.0 - filter_selectivity); // More selective = cheaper
+
+            estimated_cost += filter_cost;
+
+            let expected_pass_filter = (hnsw_search_size as f32 * filter_selectivity) as usize;
+
+            steps.push(QueryStep {
+                step: 2,
+                description: format!(
+                    "Apply filter (selectivity: {:.1}%)",
+                    filter_selectivity * 100.0
+                ),
+                cost: filter_cost,
+                input_size: hnsw_search_size,
+                output_size: expected_pass_filter,
+            });
+
+            // Recommendation: if selectivity is very low, warn about over-fetching
+            if filter_selectivity < 0.01 {
+                recommendations.push(
+                    "⚠️ Very low filter selectivity (<1%). Consider indexing filter fields or using a different filter strategy.".into()
+                );
+            }
+
+            // Recommendation: if we're fetching way more than k, suggest optimization
+            if hnsw_search_size > q.k * 20 {
+                recommendations.push(format!(
+                    "💡 Fetching {}x more candidates than needed. Consider using filtered HNSW traversal.",
+                    hnsw_search_size / q.k
+                ));
+            }
+        }
+
+        // Step 3: Top-K selection
+        let final_step = steps.len() + 1;
+        steps.push(QueryStep {
+            step: final_step,
+            description: format!("Select top-{} results", q.k),
+            cost: 0.05,
+            input_size: if has_filter {
+                steps
+                    .last()
+                    .map(|s| s.output_size)
+                    .unwrap_or(hnsw_search_size)
+            } else {
+                hnsw_search_size
+            },
+            output_size: q.k,
+        });
+
+        estimated_cost += 0.05;
+
+        // Estimate duration (very rough heuristic)
+        let estimated_duration_ms = if total_records < 1000 {
+            0.5 + estimated_cost * 2.0
+        } else if total_records < 100_000 {
+            1.0 + estimated_cost * 5.0
+        } else {
+            2.0 + estimated_cost * 10.0
+        };
+
+        // Is this query optimal?
+        let is_optimal = recommendations.is_empty();
+
+        // Additional recommendations
+        if q.k > 100 {
+            recommendations.push(
+                "💡 Large k value (>100). Consider using pagination or reducing k for better performance.".into()
+            );
+        }
+
+        if !is_optimal {
+            recommendations
+                .push("💡 Run EXPLAIN again after optimizations to see improvement.".into());
+        }
+
+        Ok(QueryPlan {
+            query_type: if has_filter {
+                "Filtered Vector Search".into()
+            } else {
+                "Vector Search".into()
+            },
+            steps,
+            estimated_cost,
+            estimated_duration_ms,
+            recommendations,
+            is_optimal,
+        })
+    }
+
+    /// Estimate the selectivity of a filter (what fraction of records pass)
+    fn estimate_filter_selectivity(&self, filter: &FilterExpr) -> f32 {
+        // Field indexes bound it from above; otherwise assume 10% of records pass
+        match self.field_indexes.candidates(filter) {
+            Some(ids) if !self.records.is_empty() => ids.len() as f32 / self.records.len() as f32,
+            _ => 0.1,
+        }
+    }
+
+    /// Create a graph visualizer for the HNSW index
+    ///
+    /// Allows exporting the HNSW graph structure to various visualization formats:
+    /// - DOT format for Graphviz rendering
+    /// - JSON format for D3.js interactive visualizations
+    /// - Cytoscape.js format for web-based graph visualization
+    ///
+    /// Note: Graph visualization is currently only supported for WASM builds.
+    /// The native build uses hnsw_rs which doesn't expose the graph structure.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore::VecStore;
+    /// # let temp_dir = tempfile::tempdir().unwrap();
+    /// let store = VecStore::open(temp_dir.path())?;
+    ///
+    /// // Export to Graphviz DOT format
+    /// let viz = store.visualizer()?;
+    /// let dot = viz.export_dot()?;
+    /// std::fs::write("graph.dot", dot)?;
+    ///
+    /// // Render with: dot -Tpng graph.dot -o graph.png
+    ///
+    /// // Or export to D3.js JSON
+    /// let json = viz.export_json()?;
+    /// std::fs::write("graph.json", json)?;
+    ///
+    /// // Get graph statistics
+    /// let stats = viz.statistics();
+    /// println!("Nodes: {}, Edges: {}, Layers: {}",
+    ///          stats.node_count, stats.edge_count, stats.layer_count);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn visualizer(&self) -> Result<crate::graph_viz::HnswVisualizer> {
+        self.backend.to_visualizer()
+    }
+}
+
+/// Upper bound on the tracked deleted ratio, so over-fetching stays at most 10x
+const MAX_DELETED_RATIO: f64 = 0.9;
+
+/// Field indexes leaving fewer than 1 in this many records send a query to
+/// an exact scan of those records
+const INDEXED_SCAN_RATIO: usize = 20;
+
+/// Longest vector a DotProduct store accepts: unit length plus rounding
+const MAX_DOT_PRODUCT_NORM: f32 = 1.0 + 1e-3;
+
+/// Double `max_elements` until it holds `needed` points
+fn fit_capacity(hnsw: &mut HnswConfig, needed: usize) {
+    while hnsw.max_elements < needed {
+        hnsw.max_elements = hnsw.max_elements.saturating_mul(2);
+    }
+}
+
+/// Scale `vector` to unit length in place, leaving zero vectors alone
+fn l2_normalize(vector: &mut [f32]) {
+    let norm = crate::simd::magnitude_simd(vector);
+    if norm > 0.0 && norm.is_finite() {
+        for x in vector.iter_mut() {
+            *x /= norm;
+        }
+    }
+}
+
+/// Stop with [`QueryInterrupted`] when the query's cancel says so
+fn check_cancel(q: &Query) -> Result<()> {
+    match &q.cancel {
+        Some(cancel) => Ok(cancel.check()?),
+        None => Ok(()),
+    }
+}
+
+/// Reject an `ef_search` smaller than the number of requested results
+fn validate_ef_search(q: &Query) -> Result<()> {
+    match q.ef_search {
+        Some(ef_search) if ef_search == 0 || ef_search < q.k => Err(anyhow::anyhow!(
+            "Invalid ef_search parameter: must be at least k ({}), got {}",
+            q.k.max(1),
+            ef_search
+        )),
+        _ => Ok(()),
+    }
+}
+
+pub fn make_record(id: impl Into<String>, vector: Vec<f32>, metadata: Metadata) -> Record {
+    Record {
+        id: id.into(),
+        vector,
+        metadata,
+        created_at: Utc::now().timestamp(),
+        deleted: false,
+        deleted_at: None,
+        expires_at: None,
+        namespace: None,
+        seq: 0,
+        version: types::initial_version(),
+        text: None,
+        parent_id: None,
+        chunk_index: None,
+    }
+}
+
+/// Grouping keys for a metadata value: strings as-is, arrays per element, null none
+fn group_keys(value: &serde_json::Value) -> Vec<String> {
+    match value {
+        serde_json::Value::Null => Vec::new(),
+        serde_json::Value::String(s) => vec![s.clone()],
+        serde_json::Value::Array(items) => items.iter().flat_map(group_keys).collect(),
+        other => vec![other.to_string()],
+    }
+}
+
+#[cfg(test)]
+mod soft_delete_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn create_test_store() -> (VecStore, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+
+        let mut meta1 = Metadata {
+            fields: std::collections::HashMap::new(),
+        };
+        meta1
+            .fields
+            .insert("title".into(), serde_json::json!("Document 1"));
+        store
+            .upsert("doc1".into(), vec![1.0, 2.0, 3.0], meta1)
+            .unwrap();
+
+        let mut meta2 = Metadata {
+            fields: std::collections::HashMap::new(),
+        };
+        meta2
+            .fields
+            .insert("title".into(), serde_json::json!("Document 2"));
+        store
+            .upsert("doc2".into(), vec![4.0, 5.0, 6.0], meta2)
+            .unwrap();
+
+        let mut meta3 = Metadata {
+            fields: std::collections::HashMap::new(),
+        };
+        meta3
+            .fields
+            .insert("title".into(), serde_json::json!("Document 3"));
+        store
+            .upsert("doc3".into(), vec![7.0, 8.0, 9.0], meta3)
+            .unwrap();
+
+        (store, temp_dir)
+    }
+
+    #[test]
+    fn test_get_and_contains_reflect_deletes() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        let rec = store.get("doc1").unwrap();
+        assert_eq!(rec.vector, vec![1.0, 2.0, 3.0]);
+        assert_eq!(
+            rec.metadata.fields["title"],
+            serde_json::json!("Document 1")
+        );
+        assert!(store.contains("doc1"));
+        assert!(store.get("missing").is_none());
+        assert!(!store.contains("missing"));
+
+        store.soft_delete("doc1").unwrap();
+        assert!(store.get("doc1").is_none());
+        assert!(!store.contains("doc1"));
+
+        store.remove("doc2").unwrap();
+        assert!(store.get("doc2").is_none());
+        assert!(!store.contains("doc2"));
+
+        let many = store.get_many(&["doc3".into(), "doc1".into(), "doc2".into()]);
+        assert_eq!(many.len(), 3);
+        assert_eq!(many[0].as_ref().unwrap().id, "doc3");
+        assert!(many[1].is_none());
+        assert!(many[2].is_none());
+    }
+
+    #[test]
+    fn test_expired_records_hidden_and_purged() {
+        let (mut store, temp_dir) = create_test_store();
+        let empty = || Metadata {
+            fields: HashMap::new(),
+        };
+        store
+            .upsert_with_ttl("stale".into(), vec![1.0, 2.0, 3.0], empty(), -1)
+            .unwrap();
+        store
+            .upsert_with_ttl("fresh".into(), vec![1.0, 2.0, 3.1], empty(), 3600)
+            .unwrap();
+        // A TTL upsert is versioned like any other upsert
+        assert_eq!(store.get("fresh").unwrap().version, 1);
+        store
+            .upsert_with_ttl("fresh".into(), vec![1.0, 2.0, 3.1], empty(), 3600)
+            .unwrap();
+        assert_eq!(store.get("fresh").unwrap().version, 2);
+
+        assert!(store.get("stale").is_none());
+        assert!(!store.contains("stale"));
+        assert!(store.contains("fresh"));
+        let ids: Vec<_> = store.scan(None, 0, 10).into_iter().map(|r| r.id).collect();
+        assert_eq!(ids, ["doc1", "doc2", "doc3", "fresh"]);
+        assert_eq!(store.count_where(None), 4);
+        let results = store
+            .query(Query::new(vec![1.0, 2.0, 3.0]).with_limit(10))
+            .unwrap();
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|n| n.id != "stale"));
+
+        // Expiry timestamps survive a reload
+        store.save().unwrap();
+        let mut store = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        assert!(store.get("fresh").unwrap().expires_at.is_some());
+        assert!(store.get("stale").is_none());
+
+        assert_eq!(store.purge_expired().unwrap(), 1);
+        assert_eq!(store.purge_expired().unwrap(), 0);
+        assert_eq!(store.deleted_count(), 1);
+        assert_eq!(store.compact().unwrap().removed_count, 1);
+        assert_eq!(store.count(), 4);
+    }
+
+    #[test]
+    fn test_scan_skips_deleted_and_pages_in_id_order() {
+        let (mut store, _temp_dir) = create_test_store();
+        for i in (0..5).rev() {
+            let mut meta = Metadata {
+                fields: std::collections::HashMap::new(),
+            };
+            meta.fields.insert("n".into(), serde_json::json!(i));
+            store
+                .upsert(format!("item{}", i), vec![i as f32, 0.0, 1.0], meta)
+                .unwrap();
+        }
+        store.soft_delete("doc2").unwrap();
+        store.remove("item3").unwrap();
+
+        let ids: Vec<&str> = store.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["doc1", "doc3", "item0", "item1", "item2", "item4"]
+        );
+
+        let page = store.scan(None, 2, 3);
+        let ids: Vec<&str> = page.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["item0", "item1", "item2"]);
+
+        let filter = parse_filter("n >= 1").unwrap();
+        let matched = store.scan(Some(filter), 0, usize::MAX);
+        let ids: Vec<&str> = matched.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["item1", "item2", "item4"]);
+
+        let mut cursor: Option<String> = None;
+        let mut seen = Vec::new();
+        loop {
+            let page = store.scan_after(cursor.as_deref(), 4);
+            if page.is_empty() {
+                break;
+            }
+            cursor = page.last().map(|r| r.id.clone());
+            seen.extend(page.into_iter().map(|r| r.id));
+        }
+        assert_eq!(
+            seen,
+            vec!["doc1", "doc3", "item0", "item1", "item2", "item4"]
+        );
+    }
+
+    #[test]
+    fn test_metadata_json_is_flat_and_reads_legacy_wrapper() {
+        let mut fields = HashMap::new();
+        fields.insert("lang".to_string(), serde_json::json!("en"));
+        let metadata = Metadata { fields };
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json, serde_json::json!({"lang": "en"}));
+
+        let legacy: Metadata = serde_json::from_str(r#"{"fields": {"lang": "en"}}"#).unwrap();
+        assert_eq!(legacy, metadata);
+        let null: Metadata = serde_json::from_str("null").unwrap();
+        assert!(null.fields.is_empty());
+
+        // A lone object field named "fields" survives a round trip
+        let mut fields = HashMap::new();
+        fields.insert("fields".to_string(), serde_json::json!({"x": 1}));
+        let tricky = Metadata { fields };
+        let json = serde_json::to_string(&tricky).unwrap();
+        assert_eq!(serde_json::from_str::<Metadata>(&json).unwrap(), tricky);
+
+        let record = make_record("a", vec![1.0], metadata.clone());
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(json["metadata"], serde_json::json!({"lang": "en"}));
+    }
+
+    #[test]
+    fn test_sample_is_seeded_uniform_and_skips_deleted() {
+        let (mut store, _temp_dir) = create_test_store();
+        for i in 0..9 {
+            let mut meta = Metadata {
+                fields: std::collections::HashMap::new(),
+            };
+            meta.fields.insert("n".into(), serde_json::json!(i));
+            store
+                .upsert(format!("item{}", i), vec![i as f32, 0.0, 1.0], meta)
+                .unwrap();
+        }
+        store.soft_delete("doc2").unwrap();
+        store.remove("item3").unwrap();
+
+        let ids =
+            |records: Vec<Record>| -> Vec<String> { records.into_iter().map(|r| r.id).collect() };
+        let picked = ids(store.sample(4, None, 7));
+        assert_eq!(picked.len(), 4);
+        assert!(picked.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(picked, ids(store.sample(4, None, 7)));
+        assert_eq!(store.sample(100, None, 7).len(), 10);
+        assert!(store.sample(0, None, 7).is_empty());
+
+        let filter = parse_filter("n >= 5").unwrap();
+        assert_eq!(
+            ids(store.sample(10, Some(filter), 1)),
+            ["item5", "item6", "item7", "item8"]
+        );
+
+        // 10 live records, 3 picked per draw: each expected 600 times in 2000
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for seed in 0..2000 {
+            for record in store.sample(3, None, seed) {
+                *counts.entry(record.id).or_default() += 1;
+            }
+        }
+        assert_eq!(counts.len(), 10);
+        assert!(!counts.contains_key("doc2") && !counts.contains_key("item3"));
+        assert!(
+            counts.values().all(|&c| (480..=720).contains(&c)),
+            "{:?}",
+            counts
+        );
+    }
+
+    #[test]
+    fn test_metadata_updates_leave_index_untouched() {
+        let (mut store, temp_dir) = create_test_store();
+        let indexed = store.index_len();
+
+        let mut patch = HashMap::new();
+        patch.insert("tag".to_string(), serde_json::json!("red"));
+        store.update_metadata("doc1", patch).unwrap();
+
+        let fields = store.get("doc1").unwrap().metadata.fields;
+        assert_eq!(fields["title"], serde_json::json!("Document 1"));
+        assert_eq!(fields["tag"], serde_json::json!("red"));
+
+        let mut fields = HashMap::new();
+        fields.insert("only".to_string(), serde_json::json!(1));
+        store.replace_metadata("doc2", Metadata { fields }).unwrap();
+        let fields = store.get("doc2").unwrap().metadata.fields;
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields["only"], serde_json::json!(1));
+
+        assert!(store.update_metadata("missing", HashMap::new()).is_err());
+        store.soft_delete("doc3").unwrap();
+        let empty = Metadata {
+            fields: HashMap::new(),
+        };
+        assert!(store.replace_metadata("doc3", empty).is_err());
+
+        let mut patch = HashMap::new();
+        patch.insert("tag".to_string(), serde_json::json!("blue"));
+        let result = store
+            .batch_update_metadata(vec![
+                ("doc1".into(), patch.clone()),
+                ("missing".into(), patch),
+            ])
+            .unwrap();
+        assert_eq!(result.succeeded, 1);
+        assert_eq!(result.failed, 1);
+
+        assert_eq!(store.index_len(), indexed);
+        assert_eq!(store.ghost_entries(), 0);
+
+        store.save().unwrap();
+        let reopened = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        let fields = reopened.get("doc1").unwrap().metadata.fields;
+        assert_eq!(fields["tag"], serde_json::json!("blue"));
+        assert_eq!(fields["title"], serde_json::json!("Document 1"));
+    }
+
+    #[test]
+    fn test_count_aggregate_and_distinct() {
+        use serde_json::json;
+
+        let (mut store, _temp_dir) = create_test_store();
+        let rows = [
+            ("a", "tech", json!("2024-01"), json!(["x", "y"])),
+            ("b", "tech", json!("2024-02"), json!(["y"])),
+            ("c", "tech", json!("2024-02"), json!(null)),
+            ("d", "food", json!(2024), json!("z")),
+        ];
+        for (id, category, month, tags) in rows {
+            let mut meta = Metadata {
+                fields: HashMap::new(),
+            };
+            meta.fields.insert("category".into(), json!(category));
+            meta.fields.insert("month".into(), month);
+            meta.fields.insert("tags".into(), tags);
+            store.upsert(id.into(), vec![0.5, 0.5, 0.5], meta).unwrap();
+        }
+        store.soft_delete("c").unwrap();
+
+        assert_eq!(store.count_where(None), store.count());
+        let tech = parse_filter("category = 'tech'").unwrap();
+        assert_eq!(store.count_where(Some(tech.clone())), 2);
+
+        let per_month = store.aggregate(Some(tech), "month");
+        assert_eq!(per_month.len(), 2);
+        assert_eq!(per_month["2024-01"], 1);
+        assert_eq!(per_month["2024-02"], 1);
+
+        let all_months = store.aggregate(None, "month");
+        assert_eq!(all_months["2024"], 1);
+
+        let tags = store.aggregate(None, "tags");
+        assert_eq!(tags["y"], 2);
+        assert_eq!(tags["x"], 1);
+        assert_eq!(tags["z"], 1);
+
+        assert_eq!(store.distinct_values("category", 10), vec!["food", "tech"]);
+        assert_eq!(store.distinct_values("tags", 2), vec!["x", "y"]);
+        assert!(store.distinct_values("missing", 10).is_empty());
+    }
+
+    #[test]
+    fn test_soft_delete_basic() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        assert_eq!(store.len(), 3);
+        assert_eq!(store.active_count(), 3);
+        assert_eq!(store.deleted_count(), 0);
+
+        // Soft delete a record
+        let result = store.soft_delete("doc1").unwrap();
+        assert!(result);
+
+        // Count should decrease
+        assert_eq!(store.active_count(), 2);
+        assert_eq!(store.deleted_count(), 1);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_soft_delete_nonexistent() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        // Try to delete non-existent record
+        let result = store.soft_delete("nonexistent").unwrap();
+        assert!(!result);
+
+        assert_eq!(store.active_count(), 3);
+        assert_eq!(store.deleted_count(), 0);
+    }
+
+    #[test]
+    fn test_soft_delete_twice() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        // Delete once
+        assert!(store.soft_delete("doc1").unwrap());
+        assert_eq!(store.deleted_count(), 1);
+
+        // Delete again - should return false
+        assert!(!store.soft_delete("doc1").unwrap());
+        assert_eq!(store.deleted_count(), 1); // Count unchanged
+    }
+
+    #[test]
+    fn test_restore_deleted() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        // Delete and restore
+        store.soft_delete("doc1").unwrap();
+        assert_eq!(store.active_count(), 2);
+        assert_eq!(store.deleted_count(), 1);
+
+        let result = store.restore("doc1").unwrap();
+        assert!(result);
+
+        assert_eq!(store.active_count(), 3);
+        assert_eq!(store.deleted_count(), 0);
+        assert_eq!(store.len(), 3);
+    }
+
+    #[test]
+    fn test_restore_nondeleted() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        // Try to restore a record that isn't deleted
+        let result = store.restore("doc1").unwrap();
+        assert!(!result);
+
+        assert_eq!(store.active_count(), 3);
+    }
+
+    #[test]
+    fn test_query_excludes_deleted() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        // Query before deletion
+        let query = Query {
+            vector: vec![1.0, 2.0, 3.0],
+            k: 10,
+            filter: None,
+            ..Default::default()
+        };
+        let results = store.query(query.clone()).unwrap();
+        assert_eq!(results.len(), 3);
+
+        // Soft delete a record
+        store.soft_delete("doc1").unwrap();
+
+        // Query should exclude deleted record
+        let results = store.query(query).unwrap();
+        assert_eq!(results.len(), 2);
+
+        // Ensure doc1 is not in results
+        assert!(!results.iter().any(|n| n.id == "doc1"));
+    }
+
+    #[test]
+    fn test_compact() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        // Soft delete two records
+        store.soft_delete("doc1").unwrap();
+        store.soft_delete("doc2").unwrap();
+
+        assert_eq!(store.active_count(), 1);
+        assert_eq!(store.deleted_count(), 2);
+
+        // Compact (permanently remove)
+        let report = store.compact().unwrap();
+        assert_eq!(report.removed_count, 2);
+
+        // Deleted records should be gone
+        assert_eq!(store.deleted_count(), 0);
+        assert_eq!(store.active_count(), 1);
+
+        // Total records should be 1
+        assert_eq!(store.records.len(), 1);
+    }
+
+    #[test]
+    fn test_compact_rebuilds_index_and_rewrites_files() {
+        let (mut store, temp_dir) = create_test_store();
+        store.save().unwrap();
+
+        store.soft_delete("doc1").unwrap();
+        store.soft_delete("doc2").unwrap();
+        let report = store.compact().unwrap();
+
+        assert_eq!(report.removed_count, 2);
+        assert!(report.bytes_reclaimed() > 0);
+        assert_eq!(store.stats().ghost_entries, 0);
+
+        // The compacted state is already on disk
+        let reopened = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        assert_eq!(reopened.records.len(), 1);
+    }
+
+    #[test]
+    fn test_defragment_rebuilds_fragmented_index() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        // Removals leave ghosts behind without rebuilding
+        store.remove("doc1").unwrap();
+        store.remove("doc2").unwrap();
+        assert_eq!(store.ghost_entries(), 2);
+        assert!(store.fragmentation_ratio() > 0.6);
+
+        store.set_compaction_config(CompactionConfig {
+            max_fragmentation_ratio: 1.0,
+            ..Default::default()
+        });
+        assert!(!store.defragment_if_needed().unwrap());
+        assert_eq!(store.ghost_entries(), 2);
+
+        store.set_compaction_config(CompactionConfig::default());
+        store.maybe_compact().unwrap();
+        assert_eq!(store.ghost_entries(), 0);
+        assert_eq!(store.fragmentation_ratio(), 0.0);
+    }
+
+    #[test]
+    fn test_remove_all_then_save_and_reopen() {
+        let (mut store, temp_dir) = create_test_store();
+        store.save().unwrap();
+
+        for id in ["doc1", "doc2", "doc3"] {
+            store.remove(id).unwrap();
+        }
+        assert!(!store.defragment_if_needed().unwrap());
+        store.save().unwrap();
+
+        let mut reopened = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        assert_eq!(reopened.len(), 0);
+
+        // The emptied store keeps working
+        reopened
+            .upsert(
+                "doc4".into(),
+                vec![1.0, 0.0, 0.0],
+                Metadata {
+                    fields: std::collections::HashMap::new(),
+                },
+            )
+            .unwrap();
+        reopened.save().unwrap();
+        let reopened = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        assert_eq!(reopened.len(), 1);
+    }
+
+    #[test]
+    fn test_compact_all_soft_deleted() {
+        let (mut store, temp_dir) = create_test_store();
+        for id in ["doc1", "doc2", "doc3"] {
+            store.soft_delete(id).unwrap();
+        }
+        let report = store.compact().unwrap();
+        assert_eq!(report.removed_count, 3);
+
+        let reopened = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        assert_eq!(reopened.len(), 0);
+    }
+
+    #[test]
+    fn test_list_deleted() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        store.soft_delete("doc1").unwrap();
+        store.soft_delete("doc2").unwrap();
+
+        let deleted = store.list_deleted();
+        assert_eq!(deleted.len(), 2);
+        assert!(deleted.iter().any(|r| r.id == "doc1"));
+        assert!(deleted.iter().any(|r| r.id == "doc2"));
+    }
+
+    #[test]
+    fn test_list_active() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        store.soft_delete("doc1").unwrap();
+
+        let active = store.list_active();
+        assert_eq!(active.len(), 2);
+        assert!(!active.iter().any(|r| r.id == "doc1"));
+        assert!(active.iter().any(|r| r.id == "doc2"));
+        assert!(active.iter().any(|r| r.id == "doc3"));
+    }
+
+    #[test]
+    fn test_deleted_at_timestamp() {
+        let (mut store, _temp_dir) = create_test_store();
+
+        store.soft_delete("doc1").unwrap();
+
+        let deleted = store.list_deleted();
+        assert_eq!(deleted.len(), 1);
+        assert!(deleted[0].deleted);
+        assert!(deleted[0].deleted_at.is_some());
+    }
+}
+
+#[cfg(test)]
+mod query_param_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn store_with_points(n: usize) -> (TempDir, VecStore) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(temp_dir.path()).unwrap();
+        for i in 0..n {
+            let meta = Metadata {
+                fields: HashMap::new(),
+            };
+            store
+                .upsert(format!("v{}", i), vec![1.0, i as f32 / n as f32], meta)
+                .unwrap();
+        }
+        (temp_dir, store)
+    }
+
+    #[test]
+    fn test_query_with_ef_search() {
+        let (_dir, store) = store_with_points(50);
+
+        let q = Query::new(vec![1.0, 0.0]).with_limit(5).with_ef_search(64);
+        assert_eq!(q.ef_search, Some(64));
+
+        let results = store.query(q).unwrap();
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].id, "v0");
+
+        let explained = store
+            .query_explain(Query::new(vec![1.0, 0.0]).with_limit(5).with_ef_search(64))
+            .unwrap();
+        assert_eq!(explained.len(), 5);
+    }
+
+    #[test]
+    fn test_ef_search_smaller_than_k_rejected() {
+        let (_dir, store) = store_with_points(20);
+
+        let err = store
+            .query(Query::new(vec![1.0, 0.0]).with_limit(10).with_ef_search(5))
+            .unwrap_err();
+        assert!(err.to_string().contains("ef_search"));
+
+        assert!(store
+            .query(Query::new(vec![1.0, 0.0]).with_limit(10).with_ef_search(10))
+            .is_ok());
+    }
+
+    fn indexed_store(dir: &TempDir, distance: Distance) -> VecStore {
+        // DotProduct only takes unit vectors
+        let policy = VectorPolicy {
+            normalize: distance == Distance::DotProduct,
+            ..Default::default()
+        };
+        let mut store = VecStore::builder(dir.path().join("test.db"))
+            .distance(distance)
+            .exact_search_threshold(0)
+            .vector_policy(policy)
+            .build()
+            .unwrap();
+        for i in 0..40 {
+            let mut meta = Metadata {
+                fields: HashMap::new(),
+            };
+            meta.fields
+                .insert("even".into(), serde_json::json!(i % 2 == 0));
+            let angle = i as f32 * 0.05;
+            store
+                .upsert(format!("v{}", i), vec![angle.cos(), angle.sin(), 0.5], meta)
+                .unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn test_limit_equal_to_record_count_returns_every_record() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = VecStore::builder(temp_dir.path())
+            .exact_search_threshold(10)
+            .build()
+            .unwrap();
+        for i in 0..325 {
+            let angle = i as f32 * 0.1;
+            let meta = Metadata {
+                fields: HashMap::new(),
+            };
+            store
+                .upsert(format!("v{}", i), vec![angle.cos(), angle.sin(), 1.0], meta)
+                .unwrap();
+        }
+
+        let q = Query::new(vec![1.0, 0.0, 1.0]).with_limit(325);
+        assert_eq!(store.query(q.clone()).unwrap().len(), 325);
+
+        // Ghost entries leave the index larger than the live set
+        store.remove("v0").unwrap();
+        store.remove("v1").unwrap();
+        assert_eq!(store.query(q).unwrap().len(), 323);
+    }
+
+    #[test]
+    fn test_exact_search_matches_index() {
+        for distance in [Distance::Cosine, Distance::Euclidean, Distance::DotProduct] {
+            let temp_dir = TempDir::new().unwrap();
+            let store = indexed_store(&temp_dir, distance);
+
+            let q = Query::new(vec![0.9, 0.3, 0.5]).with_limit(5);
+            let approx = store.query(q.clone()).unwrap();
+            let exact = store.query(q.with_exact(true)).unwrap();
+
+            assert_eq!(exact.len(), 5);
+            for (a, e) in approx.iter().zip(&exact) {
+                assert_eq!(a.id, e.id, "{:?}", distance);
+                assert!((a.score - e.score).abs() < 1e-5, "{:?}", distance);
+            }
+            assert!(exact.windows(2).all(|w| w[0].score >= w[1].score));
+        }
+    }
+
+    #[test]
+    fn test_scores_are_similarities() {
+        for distance in [Distance::Cosine, Distance::DotProduct] {
+            for threshold in [0, 1000] {
+                let temp_dir = TempDir::new().unwrap();
+                let mut store = VecStore::builder(temp_dir.path())
+                    .distance(distance)
+                    .exact_search_threshold(threshold)
+                    .build()
+                    .unwrap();
+                let meta = Metadata {
+                    fields: HashMap::new(),
+                };
+                for (id, vector) in [("pos", [1.0, 0.0]), ("neg", [-1.0, 0.0])] {
+                    store
+                        .upsert(id.into(), vector.to_vec(), meta.clone())
+                        .unwrap();
+                }
+
+                let results = store
+                    .query(Query::new(vec![1.0, 0.0]).with_limit(2))
+                    .unwrap();
+                let scores: Vec<_> = results.iter().map(|n| (n.id.as_str(), n.score)).collect();
+                assert_eq!(scores.len(), 2, "{:?}", distance);
+                assert_eq!(scores[0].0, "pos");
+                assert!((scores[0].1 - 1.0).abs() < 1e-5, "{:?}", scores);
+                assert!((scores[1].1 + 1.0).abs() < 1e-5, "{:?}", scores);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dot_product_rejects_vectors_longer_than_one() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = VecStore::builder(temp_dir.path())
+            .distance(Distance::DotProduct)
+            .build()
+            .unwrap();
+        let meta = Metadata {
+            fields: HashMap::new(),
+        };
+
+        let err = store
+            .upsert("long".into(), vec![3.0, 4.0], meta.clone())
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("length at most 1"));
+        store.upsert("unit".into(), vec![0.6, 0.8], meta).unwrap();
+
+        assert!(store.query(Query::new(vec![3.0, 4.0])).is_err());
+        let results = store.query(Query::new(vec![0.6, 0.8])).unwrap();
+        assert_eq!(results[0].id, "unit");
+    }
+
+    #[test]
+    fn test_additional_metrics_match_brute_force() {
+        // (vector, query) per metric; binary inputs for the set/bit metrics
+        let bits = |n: usize| -> Vec<f32> { (0..8).map(|b| ((n >> b) & 1) as f32).collect() };
+        let dense = |n: usize| -> Vec<f32> {
+            (0..8)
+                .map(|b| ((n * 7 + b * 3) % 11) as f32 * 0.1)
+                .collect()
+        };
+        let reference = |distance: Distance, a: &[f32], b: &[f32]| -> f32 {
+            match distance {
+                Distance::Manhattan => a.iter().zip(b).map(|(x, y)| (x - y).abs()).sum(),
+                Distance::Hamming => a
+                    .iter()
+                    .zip(b)
+                    .filter(|(x, y)| (**x > 0.5) != (**y > 0.5))
+                    .count() as f32,
+                _ => {
+                    let inter = a.iter().zip(b).filter(|(x, y)| **x > 0.0 && **y > 0.0);
+                    let union = a.iter().zip(b).filter(|(x, y)| **x > 0.0 || **y > 0.0);
+                    let union = union.count();
+                    if union == 0 {
+                        1.0
+                    } else {
+                        1.0 - inter.count() as f32 / union as f32
+                    }
+                }
+            }
+        };
+
+        for distance in [Distance::Manhattan, Distance::Hamming, Distance::Jaccard] {
+            let make = if distance == Distance::Manhattan {
+                dense
+            } else {
+                bits
+            };
+            let temp_dir = TempDir::new().unwrap();
+            let mut store = VecStore::builder(temp_dir.path().join("test.db"))
+                .distance(distance)
+                .exact_search_threshold(0)
+                .build()
+                .unwrap();
+            let mut vectors = HashMap::new();
+            for i in 0..40 {
+                let v = make(i * 37 % 256);
+                vectors.insert(format!("v{}", i), v.clone());
+                store
+                    .upsert(
+                        format!("v{}", i),
+                        v,
+                        Metadata {
+                            fields: HashMap::new(),
+                        },
+                    )
+                    .unwrap();
+            }
+
+            let query = make(0b1011_0010);
+            let mut expected: Vec<f32> = vectors
+                .values()
+                .map(|v| reference(distance, &query, v))
+                .collect();
+            expected.sort_by(|a, b| a.total_cmp(b));
+
+            let q = Query::new(query.clone()).with_limit(5);
+            for results in [
+                store.query(q.clone()).unwrap(),
+                store.query(q.clone().with_exact(true)).unwrap(),
+            ] {
+                assert_eq!(results.len(), 5, "{:?}", distance);
+                for (n, expected_d) in results.iter().zip(&expected) {
+                    let d = reference(distance, &query, &vectors[&n.id]);
+                    assert!((d - expected_d).abs() < 1e-5, "{:?}", distance);
+                }
+                // Higher score = closer
+                assert!(results.windows(2).all(|w| w[0].score >= w[1].score));
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_search_honors_filter_and_deletes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = indexed_store(&temp_dir, Distance::Euclidean);
+        store.soft_delete("v0").unwrap();
+
+        let results = store
+            .query(
+                Query::new(vec![1.0, 0.0, 0.5])
+                    .with_limit(3)
+                    .with_filter("even = true")
+                    .with_exact(true),
+            )
+            .unwrap();
+
+        let ids: Vec<_> = results.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["v2", "v4", "v6"]);
+        let d = crate::simd::euclidean_distance_simd(
+            &[1.0, 0.0, 0.5],
+            &[0.1f32.cos(), 0.1f32.sin(), 0.5],
+        );
+        assert!((results[0].score - 1.0 / (1.0 + d)).abs() < 1e-6);
+    }
+
+    /// Records the text and candidate count it was given, then reverses them
+    struct ReversingReranker {
+        seen: std::sync::Mutex<Option<(Option<String>, usize)>>,
+    }
+
+    impl QueryReranker for ReversingReranker {
+        fn rerank(
+            &self,
+            query_text: Option<&str>,
+            _query_vec: &[f32],
+            mut candidates: Vec<Neighbor>,
+        ) -> Result<Vec<Neighbor>> {
+            *self.seen.lock().unwrap() = Some((query_text.map(String::from), candidates.len()));
+            candidates.reverse();
+            Ok(candidates)
+        }
+    }
+
+    #[test]
+    fn test_reranker_sees_oversampled_candidates() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = indexed_store(&temp_dir, Distance::Euclidean);
+        let reranker = Arc::new(ReversingReranker {
+            seen: std::sync::Mutex::new(None),
+        });
+
+        let q = Query::new(vec![1.0, 0.0, 0.5]).with_limit(3);
+        let results = store
+            .query_with_reranker(q.clone().with_text("hello"), reranker.as_ref())
+            .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].id, "v29");
+        assert_eq!(
+            *reranker.seen.lock().unwrap(),
+            Some((Some("hello".to_string()), 30))
+        );
+
+        store.set_reranker(reranker.clone());
+        let results = store
+            .query(q.clone().with_rerank_fetch_k(5).with_ef_search(3))
+            .unwrap();
+        let ids: Vec<_> = results.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(ids, ["v4", "v3", "v2"]);
+        assert_eq!(*reranker.seen.lock().unwrap(), Some((None, 5)));
+
+        store.clear_reranker();
+        assert_eq!(store.query(q).unwrap()[0].id, "v0");
+    }
+
+    #[test]
+    fn test_exact_search_threshold_persisted() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = indexed_store(&temp_dir, Distance::Cosine);
+        assert_eq!(store.config().exact_search_threshold, 0);
+
+        store.set_exact_search_threshold(100);
+        store.save().unwrap();
+
+        let reopened = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        assert_eq!(reopened.config().exact_search_threshold, 100);
+        let results = reopened
+            .query(Query::new(vec![1.0, 0.0, 0.5]).with_limit(3))
+            .unwrap();
+        assert_eq!(results[0].id, "v0");
+    }
+
+    #[test]
+    fn test_default_ef_search_persisted_and_used() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = indexed_store(&temp_dir, Distance::Cosine);
+        assert_eq!(store.default_ef_search(), hnsw_backend::DEFAULT_EF_SEARCH);
+        assert!(store.set_default_ef_search(Some(0)).is_err());
+
+        store.set_default_ef_search(Some(77)).unwrap();
+        store.save().unwrap();
+
+        let reopened = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        assert_eq!(reopened.default_ef_search(), 77);
+        let profiled = |q: Query| {
+            reopened
+                .query_profiled(q.with_limit(3).with_profile())
+                .unwrap()
+                .profile
+                .unwrap()
+                .ef_search
+        };
+        assert_eq!(profiled(Query::new(vec![1.0, 0.0, 0.5])), Some(77));
+        assert_eq!(
+            profiled(Query::new(vec![1.0, 0.0, 0.5]).with_ef_search(40)),
+            Some(40)
+        );
+    }
+
+    #[test]
+    fn test_query_options_shape_results() {
+        let (_dir, mut store) = store_with_points(10);
+        let mut patch = HashMap::new();
+        patch.insert("title".to_string(), serde_json::json!("first"));
+        patch.insert(
+            "author".to_string(),
+            serde_json::json!({"name": "Ada", "contact": {"email": "ada@example.com"}}),
+        );
+        patch.insert("a.b".to_string(), serde_json::json!(1));
+        store.update_metadata("v0", patch).unwrap();
+        let q = || Query::new(vec![1.0, 0.0]).with_limit(1);
+
+        // Defaults keep the current shape: full metadata, no vector
+        let full = store.query(q()).unwrap();
+        assert_eq!(full[0].metadata.fields.len(), 3);
+        assert!(full[0].vector.is_none());
+
+        let ids_and_vectors = QueryOptions {
+            include_vector: true,
+            include_metadata: false,
+            metadata_fields: None,
+            include_text: false,
+        };
+        let results = store.query(q().with_options(ids_and_vectors)).unwrap();
+        assert_eq!(results[0].vector.as_deref(), Some(&[1.0, 0.0][..]));
+        assert!(results[0].metadata.fields.is_empty());
+
+        // Dotted paths keep their nesting; a literal dotted key wins
+        let selected = QueryOptions {
+            metadata_fields: Some(vec![
+                "author.contact.email".into(),
+                "a.b".into(),
+                "author.missing".into(),
+                "title.length".into(),
+            ]),
+            ..Default::default()
+        };
+        let expected = HashMap::from([
+            (
+                "author".to_string(),
+                serde_json::json!({"contact": {"email": "ada@example.com"}}),
+            ),
+            ("a.b".to_string(), serde_json::json!(1)),
+        ]);
+        let results = store.query(q().with_options(selected.clone())).unwrap();
+        assert_eq!(results[0].metadata.fields, expected);
+
+        // Rerankers still see the full metadata before the results are trimmed
+        let reranker = crate::reranking::MetadataBoostReranker::new().boost("title", "first", 2.0);
+        let results = store
+            .query_with_reranker(q().with_options(selected), &reranker)
+            .unwrap();
+        assert_eq!(results[0].id, "v0");
+        assert_eq!(results[0].metadata.fields, expected);
+    }
+}
+
+#[cfg(test)]
+mod index_growth_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn small_store(dir: &TempDir) -> VecStore {
+        VecStore::builder(dir.path().join("test.db"))
+            .hnsw_config(HnswConfig {
+                max_elements: 4,
+                ..Default::default()
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn empty() -> Metadata {
+        Metadata {
+            fields: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_upsert_grows_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = small_store(&temp_dir);
+
+        for i in 0..10 {
+            store
+                .upsert(format!("doc{}", i), vec![i as f32, 1.0], empty())
+                .unwrap();
+        }
+
+        let stats = store.stats();
+        assert_eq!(stats.index_capacity, 16);
+        assert!(stats.index_utilization > 0.0 && stats.index_utilization <= 1.0);
+
+        let results = store
+            .query(Query::new(vec![1.0, 0.1]).with_limit(10))
+            .unwrap();
+        assert_eq!(results.len(), 10);
+    }
+
+    #[test]
+    fn test_batch_crossing_capacity_keeps_all_vectors() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = small_store(&temp_dir);
+        store
+            .upsert("first".into(), vec![1.0, 0.0], empty())
+            .unwrap();
+
+        let batch: Vec<Record> = (0..7)
+            .map(|i| make_record(format!("batch{}", i), vec![i as f32, 1.0], empty()))
+            .collect();
+        store.batch_upsert(batch).unwrap();
+
+        assert!(store.stats().index_capacity >= 8);
+        let results = store
+            .query(Query::new(vec![1.0, 1.0]).with_limit(8))
+            .unwrap();
+        assert_eq!(results.len(), 8);
+    }
+
+    #[test]
+    fn test_grown_capacity_persisted() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = small_store(&temp_dir);
+        for i in 0..6 {
+            store
+                .upsert(format!("doc{}", i), vec![i as f32, 1.0], empty())
+                .unwrap();
+        }
+        store.save().unwrap();
+
+        let reopened = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        assert_eq!(reopened.config().hnsw.max_elements, 8);
+    }
+}
+
+#[cfg(test)]
+mod builder_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_builder_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VecStore::builder(temp_dir.path().join("test.db"))
+            .build()
+            .unwrap();
+
+        // Default should be Cosine
+        assert_eq!(store.distance_metric(), Distance::Cosine);
+        assert_eq!(store.config().hnsw.m, 16);
+        assert_eq!(store.config().hnsw.ef_construction, 200);
+    }
+
+    #[test]
+    fn test_builder_manhattan() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VecStore::builder(temp_dir.path().join("test.db"))
+            .distance(Distance::Manhattan)
+            .build()
+            .unwrap();
+
+        assert_eq!(store.distance_metric(), Distance::Manhattan);
+    }
+
+    #[test]
+    fn test_builder_hamming() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VecStore::builder(temp_dir.path().join("test.db"))
+            .distance(Distance::Hamming)
+            .build()
+            .unwrap();
+
+        assert_eq!(store.distance_metric(), Distance::Hamming);
+    }
+
+    #[test]
+    fn test_builder_jaccard() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VecStore::builder(temp_dir.path().join("test.db"))
+            .distance(Distance::Jaccard)
+            .build()
+            .unwrap();
+
+        assert_eq!(store.distance_metric(), Distance::Jaccard);
+    }
+
+    #[test]
+    fn test_builder_chebyshev() {
+        // Chebyshev is not yet supported by HNSW backend - test that it returns error
+        let temp_dir = TempDir::new().unwrap();
+        let result = VecStore::builder(temp_dir.path().join("test.db"))
+            .distance(Distance::Chebyshev)
+            .build();
+
+        assert!(result.is_err());
+        if let Err(e) = result {
+            assert!(e.to_string().contains("not yet supported"));
+        }
+    }
+
+    #[test]
+    fn test_builder_custom_hnsw_params() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VecStore::builder(temp_dir.path().join("test.db"))
+            .hnsw_m(32)
+            .hnsw_ef_construction(400)
+            .build()
+            .unwrap();
+
+        assert_eq!(store.config().hnsw.m, 32);
+        assert_eq!(store.config().hnsw.ef_construction, 400);
+    }
+
+    #[test]
+    fn test_builder_chained() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = VecStore::builder(temp_dir.path().join("test.db"))
+            .distance(Distance::Euclidean)
+            .hnsw_m(64)
+            .hnsw_ef_construction(500)
+            .build()
+            .unwrap();
+
+        assert_eq!(store.distance_metric(), Distance::Euclidean);
+        assert_eq!(store.config().hnsw.m, 64);
+        assert_eq!(store.config().hnsw.ef_construction, 500);
+    }
+
+    #[test]
+    fn test_builder_hnsw_config_persisted() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.db");
+        let hnsw = HnswConfig {
+            m: 24,
+            ef_construction: 100,
+            max_elements: 2_000_000,
+            max_layer: 16,
+        };
+
+        let mut store = VecStore::builder(&path).hnsw_config(hnsw).build().unwrap();
+        store
+            .upsert(
+                "a".into(),
+                vec![1.0, 0.0],
+                Metadata {
+                    fields: HashMap::new(),
+                },
+            )
+            .unwrap();
+        store.save().unwrap();
+        assert_eq!(store.stats().hnsw, hnsw);
+
+        // Reopening with defaults keeps the persisted parameters
+        let reopened = VecStore::open(&path).unwrap();
+        assert_eq!(reopened.config().hnsw, hnsw);
+        assert_eq!(reopened.stats().active_records, 1);
+    }
+
+    #[test]
+    fn test_set_hnsw_config_rebuilds_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        for i in 0..10 {
+            store
+                .upsert(
+                    format!("doc{}", i),
+                    vec![i as f32, 1.0],
+                    Metadata {
+                        fields: HashMap::new(),
+                    },
+                )
+                .unwrap();
+        }
+
+        let hnsw = HnswConfig {
+            m: 8,
+            ..Default::default()
+        };
+        store.set_hnsw_config(hnsw).unwrap();
+
+        assert_eq!(store.config().hnsw, hnsw);
+        let results = store
+            .query(Query::new(vec![9.0, 1.0]).with_limit(3))
+            .unwrap();
+        assert_eq!(results.len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_hnsw_config_rejected() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = VecStore::builder(temp_dir.path().join("test.db"))
+            .hnsw_config(HnswConfig {
+                max_layer: 32,
+                ..Default::default()
+            })
+            .build();
+        assert!(result.is_err());
+
+        // hnsw_rs can only save 16-layer indexes
+        let result = VecStore::builder(temp_dir.path().join("layers.db"))
+            .hnsw_config(HnswConfig {
+                max_layer: 12,
+                ..Default::default()
+            })
+            .build();
+        assert!(result.is_err());
+
+        let mut store = VecStore::open(temp_dir.path().join("other.db")).unwrap();
+        let zero_m = HnswConfig {
+            m: 0,
+            ..Default::default()
+        };
+        assert!(store.set_hnsw_config(zero_m).is_err());
+    }
+
+    #[test]
+    fn test_builder_backward_compatibility() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // Old way still works
+        let store_old = VecStore::open(temp_dir.path().join("old.db")).unwrap();
+        assert_eq!(store_old.distance_metric(), Distance::Cosine);
+
+        // New way also works
+        let store_new = VecStore::builder(temp_dir.path().join("new.db"))
+            .build()
+            .unwrap();
+        assert_eq!(store_new.distance_metric(), Distance::Cosine);
+
+        // Both should have the same default config
+        assert_eq!(store_old.config().distance, store_new.config().distance);
+    }
+
+    #[test]
+    fn test_distance_enum_from_str() {
+        assert_eq!(Distance::from_str("cosine").unwrap(), Distance::Cosine);
+        assert_eq!(
+            Distance::from_str("euclidean").unwrap(),
+            Distance::Euclidean
+        );
+        assert_eq!(Distance::from_str("l2").unwrap(), Distance::Euclidean);
+        assert_eq!(
+            Distance::from_str("manhattan").unwrap(),
+            Distance::Manhattan
+        );
+        assert_eq!(Distance::from_str("l1").unwrap(), Distance::Manhattan);
+        assert_eq!(Distance::from_str("hamming").unwrap(), Distance::Hamming);
+        assert_eq!(Distance::from_str("jaccard").unwrap(), Distance::Jaccard);
+        assert_eq!(Distance::from_str("dot").unwrap(), Distance::DotProduct);
+
+        // Case insensitive
+        assert_eq!(
+            Distance::from_str("MANHATTAN").unwrap(),
+            Distance::Manhattan
+        );
+        assert_eq!(Distance::from_str("Hamming").unwrap(), Distance::Hamming);
+
+        // Invalid
+        assert!(Distance::from_str("invalid").is_err());
+    }
+
+    #[test]
+    fn test_distance_enum_name_and_description() {
+        assert_eq!(Distance::Cosine.name(), "Cosine");
+        assert_eq!(Distance::Manhattan.name(), "Manhattan");
+        assert_eq!(Distance::Hamming.name(), "Hamming");
+        assert_eq!(Distance::Jaccard.name(), "Jaccard");
+
+        assert!(Distance::Cosine.description().contains("angle"));
+        assert!(Distance::Manhattan.description().contains("outliers"));
+        assert!(Distance::Hamming.description().contains("binary"));
+        assert!(Distance::Jaccard.description().contains("set"));
+    }
+
+    fn empty() -> Metadata {
+        Metadata {
+            fields: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_vector_policy_normalizes_and_persists() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.db");
+        let policy = VectorPolicy {
+            normalize: true,
+            ..Default::default()
+        };
+        let mut store = VecStore::builder(&path)
+            .vector_policy(policy)
+            .build()
+            .unwrap();
+
+        store.upsert("a".into(), vec![3.0, 4.0], empty()).unwrap();
+        store
+            .batch_upsert(vec![make_record("b", vec![0.0, 2.0], empty())])
+            .unwrap();
+        assert_eq!(store.get("a").unwrap().vector, vec![0.6, 0.8]);
+        assert_eq!(store.get("b").unwrap().vector, vec![0.0, 1.0]);
+
+        let results = store
+            .query(Query::new(vec![30.0, 40.0]).with_limit(1))
+            .unwrap();
+        assert_eq!(results[0].id, "a");
+        assert!((results[0].score - 1.0).abs() < 1e-6);
+        assert_eq!(store.stats().vector_policy, policy);
+
+        store.save().unwrap();
+        let reopened = VecStore::open(&path).unwrap();
+        assert_eq!(reopened.stats().vector_policy, policy);
+    }
+
+    #[test]
+    fn test_vector_policy_rejects_nan() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = VecStore::builder(temp_dir.path().join("test.db"))
+            .vector_policy(VectorPolicy {
+                reject_nan: true,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+
+        let err = store
+            .upsert("bad".into(), vec![f32::NAN, 1.0], empty())
+            .unwrap_err();
+        assert!(err.to_string().contains("NaN"));
+        assert_eq!(store.dimension(), 0);
+
+        let batch = vec![
+            make_record("ok", vec![1.0, 0.0], empty()),
+            make_record("inf", vec![f32::INFINITY, 0.0], empty()),
+        ];
+        assert!(store.batch_upsert(batch).is_err());
+        assert!(store.get("ok").is_none());
+
+        store.upsert("ok".into(), vec![1.0, 0.0], empty()).unwrap();
+        assert!(store.query(Query::new(vec![f32::NAN, 0.0])).is_err());
+    }
+
+    #[test]
+    fn test_strict_dimension_batch_is_atomic() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+        let ops = || {
+            vec![
+                BatchOperation::Upsert {
+                    id: "a".into(),
+                    vector: vec![1.0, 0.0],
+                    metadata: empty(),
+                },
+                BatchOperation::Upsert {
+                    id: "b".into(),
+                    vector: vec![1.0, 0.0, 0.0],
+                    metadata: empty(),
+                },
+            ]
+        };
+
+        let result = store.batch_execute(ops()).unwrap();
+        assert_eq!((result.succeeded, result.failed), (1, 1));
+        store.remove("a").unwrap();
+
+        store.set_vector_policy(VectorPolicy {
+            strict_dimension: true,
+            ..Default::default()
+        });
+        let err = store.batch_execute(ops()).unwrap_err();
+        assert!(err.to_string().contains("no operations applied"));
+        assert!(store.get("a").is_none());
+    }
+}
+
+#[cfg(test)]
+mod text_index_persistence_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Test that text index survives store reopen (Major Issue #6 fix)
+    #[test]
+    fn test_text_index_persists_across_reopen() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.db");
+
+        // Create store, index text, and save
+        {
+            let mut store = VecStore::open(&path).unwrap();
+
+            let mut meta = Metadata {
+                fields: std::collections::HashMap::new(),
+            };
+            meta.fields
+                .insert("title".into(), serde_json::json!("Test Doc"));
+
+            store
+                .upsert("doc1".into(), vec![1.0, 2.0, 3.0], meta)
+                .unwrap();
+            store
+                .index_text("doc1", "hello world test".to_string())
+                .unwrap();
+            store.save().unwrap();
+
+            // Verify text is indexed
+            assert!(store.has_text("doc1"));
+            assert_eq!(store.get_text("doc1"), Some("hello world test"));
+        }
+
+        // Reopen store and verify text survives
+        {
+            let store = VecStore::open(&path).unwrap();
+            assert!(
+                store.has_text("doc1"),
+                "Text index should survive store reopen"
+            );
+            assert_eq!(store.get_text("doc1"), Some("hello world test"));
+        }
+    }
+
+    /// Test that text index survives snapshot restore (Major Issue #6 fix)
+    #[test]
+    fn test_text_index_persists_in_snapshots() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.db");
+
+        let mut store = VecStore::open(&path).unwrap();
+
+        let mut meta = Metadata {
+            fields: std::collections::HashMap::new(),
+        };
+        meta.fields
+            .insert("title".into(), serde_json::json!("Snapshot Doc"));
+
+        store
+            .upsert("doc1".into(), vec![1.0, 2.0, 3.0], meta.clone())
+            .unwrap();
+        store
+            .index_text("doc1", "important data".to_string())
+            .unwrap();
+
+        // Create snapshot
+        store.create_snapshot("backup1").unwrap();
+
+        // Modify store
+        store
+            .upsert("doc2".into(), vec![4.0, 5.0, 6.0], meta)
+            .unwrap();
+        store.index_text("doc2", "new data".to_string()).unwrap();
+
+        // Restore snapshot
+        store.restore_snapshot("backup1").unwrap();
+
+        // Text index should be restored to snapshot state
+        assert!(store.has_text("doc1"));
+        assert_eq!(store.get_text("doc1"), Some("important data"));
+        assert!(
+            !store.has_text("doc2"),
+            "doc2 should not exist after restore"
+        );
+    }
+
+    /// Test that text index is properly cleared when store is new
+    #[test]
+    fn test_text_index_empty_on_new_store() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.db");
+
+        let store = VecStore::open(&path).unwrap();
+        assert!(!store.has_text("anything"));
+    }
+
+    /// Test that multiple documents can be indexed and persisted
+    #[test]
+    fn test_multiple_texts_persist() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.db");
+
+        {
+            let mut store = VecStore::open(&path).unwrap();
+
+            for i in 1..=5 {
+                let id = format!("doc{}", i);
+                let text = format!("Document number {}", i);
+                let mut meta = Metadata {
+                    fields: std::collections::HashMap::new(),
+                };
+                meta.fields.insert("num".into(), serde_json::json!(i));
+
+                store.upsert(id.clone(), vec![i as f32; 3], meta).unwrap();
+                store.index_text(&id, text).unwrap();
+            }
+            store.save().unwrap();
+        }
+
+        {
+            let store = VecStore::open(&path).unwrap();
+            for i in 1..=5 {
+                let id = format!("doc{}", i);
+                let expected_text = format!("Document number {}", i);
+                assert!(store.has_text(&id), "doc{} should have text", i);
+                assert_eq!(store.get_text(&id), Some(expected_text.as_str()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod quantization_tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use tempfile::TempDir;
+
+    const DIM: usize = 32;
+
+    fn random_vectors(n: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..n)
+            .map(|_| (0..DIM).map(|_| rng.gen_range(-1.0..1.0)).collect())
+            .collect()
+    }
+
+    fn filled_store(dir: &TempDir, quantization: Quantization, vectors: &[Vec<f32>]) -> VecStore {
+        let mut store = VecStore::builder(dir.path().join("test.db"))
+            .quantization(quantization)
+            .exact_search_threshold(0)
+            .hnsw_m(32)
+            .build()
+            .unwrap();
+        let records = vectors
+            .iter()
+            .enumerate()
+            .map(|(i, v)| make_record(format!("v{}", i), v.clone(), empty_metadata()));
+        store.batch_upsert(records).unwrap();
+        store
+    }
+
+    fn empty_metadata() -> Metadata {
+        Metadata {
+            fields: HashMap::new(),
         }
+    }
 
-        Ok(candidates)
+    fn ids(results: Vec<Neighbor>) -> Vec<Id> {
+        results.into_iter().map(|n| n.id).collect()
     }
 
-    /// Apply Maximal Marginal Relevance for diversity
-    fn apply_mmr(&self, candidates: &[Neighbor], k: usize, lambda: f32) -> Result<Vec<Neighbor>> {
-        use crate::simd::cosine_similarity_simd;
+    /// The HNSW graph is built with an OS-seeded RNG from hash-ordered
+    /// records, so its recall varies from run to run even on seeded data.
+    /// The codes are checked with an exact scan, which doesn't vary; the
+    /// index gets a dense graph, a wide `ef_search` and a looser bound.
+    #[test]
+    fn test_int8_recall_against_f32_baseline() {
+        let vectors = random_vectors(1_000, 7);
+        let queries = random_vectors(50, 8);
+
+        let baseline_dir = TempDir::new().unwrap();
+        let baseline = filled_store(&baseline_dir, Quantization::None, &vectors);
+
+        for keep_originals in [true, false] {
+            let dir = TempDir::new().unwrap();
+            let mut store = filled_store(&dir, Quantization::Int8 { keep_originals }, &vectors);
+            store.calibrate_quantization().unwrap();
+
+            let (mut hits, mut scanned_hits) = (0, 0);
+            for q in &queries {
+                let query = Query::new(q.clone()).with_limit(10).with_ef_search(200);
+                let truth = ids(baseline.query(query.clone().with_exact(true)).unwrap());
+                let found = ids(store.query(query.clone()).unwrap());
+                hits += found.iter().filter(|id| truth.contains(id)).count();
+                let scanned = ids(store.query(query.with_exact(true)).unwrap());
+                scanned_hits += scanned.iter().filter(|id| truth.contains(id)).count();
+            }
 
-        if candidates.is_empty() || k == 0 {
-            return Ok(Vec::new());
+            let total = (queries.len() * 10) as f64;
+            let (recall, scanned_recall) = (hits as f64 / total, scanned_hits as f64 / total);
+            assert!(
+                scanned_recall >= 0.98,
+                "keep_originals={keep_originals}: exact scan recall {scanned_recall}"
+            );
+            assert!(
+                recall >= 0.95,
+                "keep_originals={keep_originals}: recall {recall}"
+            );
         }
+    }
 
-        let k = std::cmp::min(k, candidates.len());
-        let mut selected = Vec::new();
-        let mut remaining: Vec<_> = candidates.iter().collect();
+    #[test]
+    fn test_int8_persisted_and_reported() {
+        let vectors = random_vectors(200, 11);
+        let dir = TempDir::new().unwrap();
+        {
+            let mut store = filled_store(
+                &dir,
+                Quantization::Int8 {
+                    keep_originals: false,
+                },
+                &vectors,
+            );
+            assert!(!store.stats().quantization_calibrated);
+            store.calibrate_quantization().unwrap();
+
+            let stats = store.stats();
+            assert!(stats.quantization_calibrated);
+            // One byte per dimension for the code and for the index copy
+            assert_eq!(
+                stats.full_precision_vector_bytes,
+                4 * stats.vector_memory_bytes
+            );
+            store.save().unwrap();
+        }
 
-        // Select first result (highest score)
-        selected.push(remaining.remove(0).clone());
+        let mut store = VecStore::open(dir.path().join("test.db")).unwrap();
+        let stats = store.stats();
+        assert_eq!(
+            stats.quantization,
+            Quantization::Int8 {
+                keep_originals: false
+            }
+        );
+        assert!(stats.quantization_calibrated);
 
-        // Iteratively select diverse results
-        while selected.len() < k && !remaining.is_empty() {
-            let mut best_idx = 0;
-            let mut best_mmr_score = f32::NEG_INFINITY;
+        // Reads return dequantized vectors close to the originals
+        let restored = store.get("v3").unwrap().vector;
+        assert_eq!(restored.len(), DIM);
+        for (a, b) in restored.iter().zip(&vectors[3]) {
+            assert!((a - b).abs() < 0.01);
+        }
 
-            for (idx, candidate) in remaining.iter().enumerate() {
-                let candidate_record = match self.records.get(&candidate.id) {
-                    Some(r) => r,
-                    None => continue,
-                };
+        // New inserts are quantized with the persisted codebook; an exact
+        // scan compares the codes without depending on the graph reaching
+        // the duplicate
+        store
+            .upsert("new".into(), vectors[5].clone(), empty_metadata())
+            .unwrap();
+        let results = store
+            .query(
+                Query::new(vectors[5].clone())
+                    .with_limit(2)
+                    .with_exact(true),
+            )
+            .unwrap();
+        let found = ids(results);
+        assert!(found.contains(&"new".to_string()) && found.contains(&"v5".to_string()));
+    }
 
-                // Relevance score (from original query)
-                let relevance = candidate.score;
+    #[test]
+    fn test_calibrate_requires_int8_store() {
+        let dir = TempDir::new().unwrap();
+        let mut store = filled_store(&dir, Quantization::None, &random_vectors(10, 1));
+        assert!(store.calibrate_quantization().is_err());
+    }
+}
 
-                // Max similarity to already selected results
-                let mut max_sim: f32 = 0.0;
-                for selected_neighbor in &selected {
-                    if let Some(selected_record) = self.records.get(&selected_neighbor.id) {
-                        let sim = cosine_similarity_simd(
-                            &candidate_record.vector,
-                            &selected_record.vector,
-                        );
-                        max_sim = max_sim.max(sim);
-                    }
-                }
+#[cfg(test)]
+mod wal_tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
 
-                // MMR score: lambda * relevance - (1 - lambda) * max_similarity
-                let mmr_score = lambda * relevance - (1.0 - lambda) * max_sim;
+    fn metadata(n: i64) -> Metadata {
+        let mut fields = HashMap::new();
+        fields.insert("n".to_string(), serde_json::json!(n));
+        Metadata { fields }
+    }
 
-                if mmr_score > best_mmr_score {
-                    best_mmr_score = mmr_score;
-                    best_idx = idx;
+    fn open(dir: &TempDir, sync: WalSync) -> VecStore {
+        VecStore::builder(dir.path().join("test.db"))
+            .wal(sync)
+            .build()
+            .unwrap()
+    }
+
+    /// Every record as (id, vector, metadata JSON), ordered by ID
+    fn snapshot(store: &VecStore) -> BTreeMap<Id, (Vec<f32>, serde_json::Value)> {
+        store
+            .iter()
+            .map(|r| {
+                let metadata = serde_json::to_value(&r.metadata).unwrap();
+                (r.id.clone(), (r.vector.clone(), metadata))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_replay_after_crash_restores_records() {
+        for sync in [WalSync::PerWrite, WalSync::IntervalMs(50), WalSync::Off] {
+            let dir = TempDir::new().unwrap();
+            let expected = {
+                let mut store = open(&dir, sync);
+                for i in 0..10 {
+                    store
+                        .upsert(format!("v{i}"), vec![i as f32, 1.0, 0.5], metadata(i))
+                        .unwrap();
                 }
-            }
+                store
+                    .batch_upsert(vec![
+                        make_record("b1", vec![0.0, 2.0, 1.0], metadata(100)),
+                        make_record("v3", vec![9.0, 9.0, 9.0], metadata(30)),
+                    ])
+                    .unwrap();
+                store.remove("v5").unwrap();
+                store
+                    .upsert("v7".into(), vec![7.0, 0.0, 0.0], metadata(70))
+                    .unwrap();
+                snapshot(&store)
+                // Dropped without save
+            };
 
-            selected.push(remaining.remove(best_idx).clone());
+            let store = open(&dir, sync);
+            assert_eq!(store.recovered_operations(), 14);
+            assert_eq!(snapshot(&store), expected);
+            assert_eq!(store.count(), 10);
         }
+    }
 
-        Ok(selected)
+    #[test]
+    fn test_replay_on_top_of_saved_state() {
+        let dir = TempDir::new().unwrap();
+        let expected = {
+            let mut store = open(&dir, WalSync::PerWrite);
+            store
+                .upsert("saved".into(), vec![1.0, 0.0], metadata(1))
+                .unwrap();
+            store
+                .upsert("gone".into(), vec![0.0, 1.0], metadata(2))
+                .unwrap();
+            store.save().unwrap();
+
+            store.remove("gone").unwrap();
+            store
+                .upsert("unsaved".into(), vec![1.0, 1.0], metadata(3))
+                .unwrap();
+            snapshot(&store)
+        };
+
+        // The WAL setting is persisted with the config
+        let store = VecStore::open(dir.path().join("test.db")).unwrap();
+        assert_eq!(store.recovered_operations(), 2);
+        assert_eq!(snapshot(&store), expected);
     }
 
-    /// Query with custom HNSW search parameters for performance tuning
-    ///
-    /// Allows per-query control over the speed/accuracy tradeoff.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use vecstore::{VecStore, Query, HNSWSearchParams};
-    /// # let temp_dir = tempfile::tempdir().unwrap();
-    /// # let store = VecStore::open(temp_dir.path()).unwrap();
-    /// // Fast search (lower recall, faster)
-    /// let results = store.query_with_params(
-    ///     Query::new(vec![0.1, 0.2, 0.3]).with_limit(10),
-    ///     HNSWSearchParams::fast(),
-    /// )?;
-    ///
-    /// // High recall search (better accuracy, slower)
-    /// let results = store.query_with_params(
-    ///     Query::new(vec![0.1, 0.2, 0.3]).with_limit(10),
-    ///     HNSWSearchParams::high_recall(),
-    /// )?;
-    /// # Ok::<(), anyhow::Error>(())
-    /// ```
-    pub fn query_with_params(&self, q: Query, params: HNSWSearchParams) -> Result<Vec<Neighbor>> {
-        if self.dimension == 0 {
-            return Ok(Vec::new());
-        }
+    #[test]
+    fn test_replay_restores_every_kind_of_write() {
+        let dir = TempDir::new().unwrap();
+        let (expected, expires_at) = {
+            let mut store = open(&dir, WalSync::PerWrite);
+            for i in 0..5 {
+                store
+                    .upsert(format!("v{i}"), vec![i as f32, 1.0], metadata(i))
+                    .unwrap();
+            }
+            store.save().unwrap();
 
-        if q.vector.len() != self.dimension {
-            return Err(anyhow::anyhow!(
-                "Query dimension mismatch: expected {}, got {}",
-                self.dimension,
-                q.vector.len()
-            ));
+            let mut patch = HashMap::new();
+            patch.insert("tag".to_string(), serde_json::json!("patched"));
+            store.update_metadata("v0", patch).unwrap();
+            store.replace_metadata("v1", metadata(10)).unwrap();
+            store.soft_delete("v2").unwrap();
+            store.soft_delete("v3").unwrap();
+            store.restore("v3").unwrap();
+            store.set_ttl("v4", 3600).unwrap();
+            store
+                .upsert_with_ttl("t".into(), vec![1.0, 1.0], metadata(5), 3600)
+                .unwrap();
+            store
+                .upsert_with_ttl("gone".into(), vec![1.0, 0.0], metadata(6), -1)
+                .unwrap();
+            assert_eq!(store.purge_expired().unwrap(), 1);
+
+            let expires_at = (
+                store.records["v4"].expires_at,
+                store.records["t"].expires_at,
+            );
+            (snapshot(&store), expires_at)
+        };
+
+        let store = open(&dir, WalSync::PerWrite);
+        assert_eq!(snapshot(&store), expected);
+        assert_eq!(store.records["v0"].metadata.fields["tag"], "patched");
+        assert!(store.records["v2"].deleted);
+        assert!(!store.records["v3"].deleted);
+        assert!(store.records["gone"].deleted);
+        assert_eq!(
+            (
+                store.records["v4"].expires_at,
+                store.records["t"].expires_at
+            ),
+            expires_at
+        );
+        assert!(expires_at.0.is_some() && expires_at.1.is_some());
+    }
+
+    #[test]
+    fn test_stats_report_sizes_and_last_save() {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(&dir, WalSync::PerWrite);
+        for i in 0..5 {
+            store
+                .upsert(format!("v{i}"), vec![i as f32, 1.0, 0.5], metadata(i))
+                .unwrap();
         }
+        store.soft_delete("v0").unwrap();
+
+        let stats = store.stats();
+        assert_eq!(stats.active_records, 4);
+        assert_eq!(stats.deleted_records, 1);
+        assert!(stats.index_link_bytes > 0);
+        let per_record = serde_json::to_string(&metadata(0)).unwrap().len();
+        assert_eq!(stats.metadata_bytes, 5 * per_record);
+        assert!(stats.wal_bytes.unwrap() > 0);
+        assert_eq!(stats.last_saved_at, None);
+
+        store.save().unwrap();
+        let stats = store.stats();
+        assert_eq!(stats.wal_bytes, Some(crate::wal::HEADER_LEN));
+        assert!(stats.last_saved_at.is_some());
+        assert!(stats.disk_bytes > 0);
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["deleted_records"], 1);
+    }
 
-        // Validate ef_search parameter (Major Issue #14 fix)
-        if params.ef_search == 0 {
-            return Err(anyhow::anyhow!(
-                "Invalid ef_search parameter: must be at least 1, got 0"
-            ));
+    #[test]
+    fn test_save_truncates_log() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("test.db").join("wal.log");
+        {
+            let mut store = open(&dir, WalSync::Off);
+            store
+                .upsert("a".into(), vec![1.0, 0.0], metadata(1))
+                .unwrap();
+            assert!(std::fs::metadata(&wal_path).unwrap().len() > 0);
+            store.save().unwrap();
+            assert_eq!(
+                std::fs::metadata(&wal_path).unwrap().len(),
+                crate::wal::HEADER_LEN
+            );
         }
 
-        // Use custom ef_search parameter
-        let fetch_size = if q.filter.is_some() {
-            // Using saturating_mul to prevent overflow (Major Issue #10 fix)
-            std::cmp::min(q.k.saturating_mul(10), self.records.len())
-        } else {
-            // Ensure we fetch at least k neighbors (Major Issue #8 fix)
-            std::cmp::min(std::cmp::max(q.k, params.ef_search), self.records.len())
-        };
+        let mut store = open(&dir, WalSync::Off);
+        assert_eq!(store.recovered_operations(), 0);
+        assert_eq!(store.count(), 1);
 
-        #[cfg(not(target_arch = "wasm32"))]
-        let backend_results = self
-            .backend
-            .search_with_ef(&q.vector, fetch_size, params.ef_search)
-            .unwrap_or_else(|_| self.backend.search(&q.vector, fetch_size));
+        // Replayed operations stay logged until the next save
+        store
+            .upsert("b".into(), vec![0.0, 1.0], metadata(2))
+            .unwrap();
+        drop(store);
+        let store = open(&dir, WalSync::Off);
+        assert_eq!(store.recovered_operations(), 1);
+        drop(store);
+        let store = open(&dir, WalSync::Off);
+        assert_eq!(store.recovered_operations(), 1);
+        assert_eq!(store.count(), 2);
+    }
 
-        #[cfg(target_arch = "wasm32")]
-        let backend_results = self
-            .backend
-            .search_with_ef(&q.vector, fetch_size, params.ef_search)
-            .or_else(|_| self.backend.search(&q.vector, fetch_size))?;
+    #[test]
+    fn test_torn_tail_is_dropped() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("test.db").join("wal.log");
+        {
+            let mut store = open(&dir, WalSync::PerWrite);
+            store
+                .upsert("a".into(), vec![1.0, 0.0], metadata(1))
+                .unwrap();
+            store
+                .upsert("b".into(), vec![0.0, 1.0], metadata(2))
+                .unwrap();
+        }
 
-        // Convert (Id, f32) to Neighbor with metadata
-        let mut results: Vec<Neighbor> = backend_results
-            .into_iter()
-            .filter_map(|(id, score)| {
-                self.records.get(&id).map(|record| Neighbor {
-                    id,
-                    score,
-                    metadata: record.metadata.clone(),
-                })
-            })
-            .collect();
+        // Cut the last record short, as a crash mid-write would
+        let len = std::fs::metadata(&wal_path).unwrap().len();
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(&wal_path)
+            .unwrap();
+        file.set_len(len - 3).unwrap();
+        drop(file);
 
-        // Apply filter if present
-        if let Some(ref filter) = q.filter {
-            results.retain(|n| {
-                if let Some(record) = self.records.get(&n.id) {
-                    filters::evaluate_filter(filter, &record.metadata)
-                } else {
-                    false
-                }
-            });
-        }
+        let mut store = open(&dir, WalSync::PerWrite);
+        assert_eq!(store.recovered_operations(), 1);
+        assert!(store.get("a").is_some());
+        assert!(store.get("b").is_none());
 
-        // Limit to k results
-        results.truncate(q.k);
+        // Appends continue after the last intact record
+        store
+            .upsert("c".into(), vec![1.0, 1.0], metadata(3))
+            .unwrap();
+        drop(store);
+        let store = open(&dir, WalSync::PerWrite);
+        assert_eq!(store.recovered_operations(), 2);
+        assert!(store.get("c").is_some());
+    }
+}
 
-        Ok(results)
+#[cfg(test)]
+mod namespace_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn tenant_metadata(tenant: &str) -> Metadata {
+        let mut fields = HashMap::new();
+        fields.insert("tenant".to_string(), serde_json::json!(tenant));
+        Metadata { fields }
     }
 
-    /// Explain how a query will be executed and estimate its cost
-    ///
-    /// This is useful for:
-    /// - Understanding query performance
-    /// - Optimizing complex queries
-    /// - Debugging slow queries
-    /// - Capacity planning
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use vecstore::{VecStore, Query};
-    /// # let temp_dir = tempfile::tempdir().unwrap();
-    /// # let store = VecStore::open(temp_dir.path()).unwrap();
-    /// let query = Query::new(vec![0.1, 0.2, 0.3])
-    ///     .with_limit(10)
-    ///     .with_filter("category = 'tech' AND score > 0.9");
-    ///
-    /// let plan = store.explain_query(query)?;
-    ///
-    /// println!("Query type: {}", plan.query_type);
-    /// println!("Estimated cost: {:.2}", plan.estimated_cost);
-    /// println!("Estimated duration: {:.2}ms", plan.estimated_duration_ms);
-    ///
-    /// for step in plan.steps {
-    ///     println!("  Step {}: {} (cost: {:.2})", step.step, step.description, step.cost);
-    /// }
-    ///
-/// for rec in plan.recommendations {
-///     println!("Hint: {}", rec);
-/// }
-    /// # Ok::<(), anyhow::Error>(())
-    /// ```
-    pub fn explain_query(&self, q: Query) -> Result<QueryPlan> {
-        // Validate query vector is not empty (Major Issue #15 fix)
-        if q.vector.is_empty() {
-            return Err(anyhow::anyhow!(
-                "Query vector cannot be empty. Provide a valid embedding vector."
-            ));
+    fn vector(i: usize) -> Vec<f32> {
+        (0..8)
+            .map(|d| ((i * 13 + d * 5) % 23) as f32 + 1.0)
+            .collect()
+    }
+
+    /// Tenant "big" with 300 records, "small" with 5, the default namespace with 20
+    fn tenant_store(dir: &TempDir) -> VecStore {
+        let mut store = VecStore::builder(dir.path().join("test.db"))
+            .exact_search_threshold(50)
+            .build()
+            .unwrap();
+        for i in 0..300 {
+            store
+                .upsert_in_namespace("big", format!("big{i}"), vector(i), tenant_metadata("big"))
+                .unwrap();
+        }
+        for i in 0..5 {
+            store
+                .upsert_in_namespace(
+                    "small",
+                    format!("small{i}"),
+                    vector(i),
+                    tenant_metadata("small"),
+                )
+                .unwrap();
         }
+        for i in 0..20 {
+            store
+                .upsert(format!("default{i}"), vector(i), tenant_metadata(""))
+                .unwrap();
+        }
+        store
+    }
 
-        // Validate dimension matches store dimension (if store is initialized)
-        if self.dimension > 0 && q.vector.len() != self.dimension {
-            return Err(anyhow::anyhow!(
-                "Query dimension mismatch: expected {}, got {}",
-                self.dimension,
-                q.vector.len()
-            ));
+    fn ids_in(store: &VecStore, q: Query) -> Vec<Id> {
+        store.query(q).unwrap().into_iter().map(|n| n.id).collect()
+    }
+
+    #[test]
+    fn test_query_never_leaves_namespace() {
+        let dir = TempDir::new().unwrap();
+        let store = tenant_store(&dir);
+
+        for (namespace, prefix, expected) in [("big", "big", 10), ("small", "small", 5)] {
+            for exact in [false, true] {
+                let q = Query::new(vector(1))
+                    .with_limit(10)
+                    .with_exact(exact)
+                    .with_namespace(namespace);
+                let ids = ids_in(&store, q);
+                assert_eq!(ids.len(), expected);
+                assert!(ids.iter().all(|id| id.starts_with(prefix)), "{ids:?}");
+            }
         }
 
-        let total_records = self.records.len();
-        let has_filter = q.filter.is_some();
+        // Unscoped queries see every namespace
+        let ids = ids_in(&store, Query::new(vector(1)).with_limit(400));
+        assert_eq!(ids.len(), 325);
 
-        let mut steps = Vec::new();
-        let mut estimated_cost = 0.0;
-        let mut recommendations = Vec::new();
+        // A limit covering the whole namespace returns all of it
+        let ids = ids_in(
+            &store,
+            Query::new(vector(1)).with_limit(300).with_namespace("big"),
+        );
+        assert_eq!(ids.len(), 300);
+        assert!(ids.iter().all(|id| id.starts_with("big")));
 
-        // Handle empty or near-empty stores (Major Issue #11 fix)
-        if total_records < 2 {
-            recommendations.push(
-                "Store has fewer than 2 records. Ingest more data for meaningful query planning."
-                    .to_string(),
+        assert!(ids_in(&store, Query::new(vector(1)).with_namespace("none")).is_empty());
+    }
+
+    #[test]
+    fn test_filter_cannot_widen_namespace() {
+        let dir = TempDir::new().unwrap();
+        let store = tenant_store(&dir);
+
+        let filters = [
+            "tenant = 'big' OR tenant = 'small'",
+            "NOT tenant = 'small'",
+            "tenant = 'big'",
+            "((( not a filter",
+        ];
+        for filter in filters {
+            let q = Query::new(vector(2))
+                .with_limit(50)
+                .with_namespace("small")
+                .with_filter(filter);
+            let ids = ids_in(&store, q.clone());
+            assert!(
+                ids.iter().all(|id| id.starts_with("small")),
+                "{filter}: {ids:?}"
             );
-            return Ok(QueryPlan {
-                query_type: "vector".to_string(),
-                steps,
-                estimated_cost: 0.0,
-                estimated_duration_ms: 0.0,
-                recommendations,
-                is_optimal: false,
-            });
+
+            let explained = store.query_explain(q).unwrap();
+            assert!(explained.iter().all(|n| n.id.starts_with("small")));
         }
 
-        // Step 1: HNSW graph traversal
-        let hnsw_search_size = if has_filter {
-            // Using saturating_mul to prevent overflow (Major Issue #10 fix)
-            std::cmp::min(q.k.saturating_mul(10), total_records)
-        } else {
-            // Using saturating_mul to prevent overflow (Major Issue #10 fix)
-            std::cmp::min(q.k.saturating_mul(2), total_records)
-        };
+        let q = Query::new(vector(2))
+            .with_namespace("small")
+            .with_filter("tenant = 'big'");
+        assert!(ids_in(&store, q).is_empty());
 
-        let hnsw_cost = (hnsw_search_size as f32).ln() / (total_records as f32).ln();
-        estimated_cost += hnsw_cost;
+        let q = Query::new(vector(2)).with_namespace("small");
+        let ids: Vec<Id> = store
+            .query_with_params(q, HNSWSearchParams::high_recall())
+            .unwrap()
+            .into_iter()
+            .map(|n| n.id)
+            .collect();
+        assert!(ids.iter().all(|id| id.starts_with("small")), "{ids:?}");
+    }
 
-        steps.push(QueryStep {
-            step: 1,
-            description: format!(
-                "HNSW graph traversal (ef_search=50, fetch={})",
-                hnsw_search_size
-            ),
-            cost: hnsw_cost,
-            input_size: total_records,
-            output_size: hnsw_search_size,
-        });
+    #[test]
+    fn test_ids_belong_to_one_namespace() {
+        let dir = TempDir::new().unwrap();
+        let mut store = tenant_store(&dir);
+
+        // Another tenant can't overwrite the record, nor can the default namespace
+        let err = store
+            .upsert_in_namespace("big", "small0".into(), vector(9), tenant_metadata("big"))
+            .unwrap_err();
+        assert!(err.to_string().contains("belongs to namespace"));
+        assert!(store
+            .upsert("small0".into(), vector(9), tenant_metadata(""))
+            .is_err());
+        assert!(store
+            .batch_upsert(vec![
+                make_record("small0", vector(9), tenant_metadata("")).with_namespace("big")
+            ])
+            .is_err());
+
+        // The owner can update it
+        store
+            .upsert_in_namespace(
+                "small",
+                "small0".into(),
+                vector(9),
+                tenant_metadata("small"),
+            )
+            .unwrap();
+        assert_eq!(
+            store.get("small0").unwrap().namespace.as_deref(),
+            Some("small")
+        );
+    }
 
-        // Step 2: Filter application (if present)
-        if let Some(ref filter) = q.filter {
-            let filter_selectivity = self.estimate_filter_selectivity(filter);
-            let filter_cost = 0.1 * (1.0 - filter_selectivity); // More selective = cheaper
+    #[test]
+    fn test_delete_namespace_and_counts() {
+        let dir = TempDir::new().unwrap();
+        let mut store = tenant_store(&dir);
+        store.soft_delete("small4").unwrap();
+
+        let counts = store.stats().namespace_counts;
+        assert_eq!(counts.get("big"), Some(&300));
+        assert_eq!(counts.get("small"), Some(&4));
+        assert_eq!(counts.len(), 2);
+
+        assert_eq!(store.delete_namespace("small").unwrap(), 5);
+        assert_eq!(store.delete_namespace("small").unwrap(), 0);
+        assert_eq!(store.namespace_len("small"), 0);
+        assert!(!store.stats().namespace_counts.contains_key("small"));
+        assert_eq!(store.len(), 320);
+        assert!(ids_in(&store, Query::new(vector(0)).with_namespace("small")).is_empty());
+    }
 
-            estimated_cost += filter_cost;
+    #[test]
+    fn test_namespace_persisted() {
+        let dir = TempDir::new().unwrap();
+        {
+            let mut store = VecStore::builder(dir.path().join("test.db"))
+                .wal(WalSync::Off)
+                .build()
+                .unwrap();
+            store
+                .upsert_in_namespace("a", "saved".into(), vector(0), tenant_metadata("a"))
+                .unwrap();
+            store.save().unwrap();
+            store
+                .upsert_in_namespace("a", "logged".into(), vector(1), tenant_metadata("a"))
+                .unwrap();
+        }
 
-            let expected_pass_filter = (hnsw_search_size as f32 * filter_selectivity) as usize;
+        let store = VecStore::open(dir.path().join("test.db")).unwrap();
+        assert_eq!(store.recovered_operations(), 1);
+        assert_eq!(store.namespace_len("a"), 2);
+        let ids = ids_in(&store, Query::new(vector(0)).with_namespace("a"));
+        assert_eq!(ids.len(), 2);
+    }
+}
 
-            steps.push(QueryStep {
-                step: 2,
-                description: format!(
-                    "Apply filter (selectivity: {:.1}%)",
-                    filter_selectivity * 100.0
-                ),
-                cost: filter_cost,
-                input_size: hnsw_search_size,
-                output_size: expected_pass_filter,
-            });
+#[cfg(test)]
+mod field_index_tests {
+    use super::*;
+    use tempfile::TempDir;
 
-            // Recommendation: if selectivity is very low, warn about over-fetching
-            if filter_selectivity < 0.01 {
-                recommendations.push(
-                    "⚠️ Very low filter selectivity (<1%). Consider indexing filter fields or using a different filter strategy.".into()
-                );
-            }
+    const CATEGORIES: [&str; 4] = ["news", "blog", "docs", "misc"];
 
-            // Recommendation: if we're fetching way more than k, suggest optimization
-            if hnsw_search_size > q.k * 20 {
-                recommendations.push(format!(
-                    "💡 Fetching {}x more candidates than needed. Consider using filtered HNSW traversal.",
-                    hnsw_search_size / q.k
-                ));
-            }
-        }
+    fn vector(i: usize) -> Vec<f32> {
+        (0..8)
+            .map(|d| ((i * 13 + d * 5) % 23) as f32 + 1.0)
+            .collect()
+    }
 
-        // Step 3: Top-K selection
-        let final_step = steps.len() + 1;
-        steps.push(QueryStep {
-            step: final_step,
-            description: format!("Select top-{} results", q.k),
-            cost: 0.05,
-            input_size: if has_filter {
-                steps
-                    .last()
-                    .map(|s| s.output_size)
-                    .unwrap_or(hnsw_search_size)
-            } else {
-                hnsw_search_size
-            },
-            output_size: q.k,
-        });
+    fn metadata(i: usize) -> Metadata {
+        let mut fields = HashMap::new();
+        fields.insert("category".to_string(), serde_json::json!(CATEGORIES[i % 4]));
+        fields.insert("price".to_string(), serde_json::json!(i));
+        Metadata { fields }
+    }
 
-        estimated_cost += 0.05;
+    fn store(dir: &TempDir) -> VecStore {
+        let mut store = VecStore::builder(dir.path().join("test.db"))
+            .exact_search_threshold(50)
+            .build()
+            .unwrap();
+        for i in 0..400 {
+            store
+                .upsert(format!("doc{i}"), vector(i), metadata(i))
+                .unwrap();
+        }
+        store
+    }
 
-        // Estimate duration (very rough heuristic)
-        let estimated_duration_ms = if total_records < 1000 {
-            0.5 + estimated_cost * 2.0
-        } else if total_records < 100_000 {
-            1.0 + estimated_cost * 5.0
-        } else {
-            2.0 + estimated_cost * 10.0
-        };
+    fn ids(store: &VecStore, filter: &str, exact: bool) -> Vec<Id> {
+        let q = Query::new(vector(3))
+            .with_limit(10)
+            .with_filter(filter)
+            .with_exact(exact);
+        store.query(q).unwrap().into_iter().map(|n| n.id).collect()
+    }
 
-        // Is this query optimal?
-        let is_optimal = recommendations.is_empty();
+    #[test]
+    fn test_indexed_queries_match_unindexed() {
+        let dir = TempDir::new().unwrap();
+        let mut store = store(&dir);
+        let filters = [
+            "price < 12",
+            "category = 'blog' AND price >= 390",
+            "category IN ('news', 'docs')",
+        ];
+        let expected: Vec<Vec<Id>> = filters.iter().map(|f| ids(&store, f, true)).collect();
 
-        // Additional recommendations
-        if q.k > 100 {
-            recommendations.push(
-                "💡 Large k value (>100). Consider using pagination or reducing k for better performance.".into()
-            );
-        }
+        store
+            .create_field_index("category", FieldIndexType::Keyword)
+            .unwrap();
+        store
+            .create_field_index("price", FieldIndexType::Numeric)
+            .unwrap();
+        assert!(store
+            .create_field_index("price", FieldIndexType::Keyword)
+            .is_err());
 
-        if !is_optimal {
-            recommendations
-                .push("💡 Run EXPLAIN again after optimizations to see improvement.".into());
+        for (filter, expected) in filters.iter().zip(&expected) {
+            assert_eq!(&ids(&store, filter, true), expected, "{filter}");
         }
+        // Few enough indexed records to be scanned exactly
+        assert_eq!(ids(&store, filters[0], false), expected[0]);
+        assert_eq!(ids(&store, filters[1], false), expected[1]);
+        // Half the store goes through the vector index, over-fetched by its share
+        let broad = ids(&store, filters[2], false);
+        assert_eq!(broad.len(), 10);
+        assert!(broad.iter().all(|id| {
+            let category = &store.get(id).unwrap().metadata.fields["category"];
+            category == "news" || category == "docs"
+        }));
+    }
 
-        Ok(QueryPlan {
-            query_type: if has_filter {
-                "Filtered Vector Search".into()
-            } else {
-                "Vector Search".into()
-            },
-            steps,
-            estimated_cost,
-            estimated_duration_ms,
-            recommendations,
-            is_optimal,
-        })
+    #[test]
+    fn test_index_follows_writes_and_persists() {
+        let dir = TempDir::new().unwrap();
+        let mut store = store(&dir);
+        store
+            .create_field_index("category", FieldIndexType::Keyword)
+            .unwrap();
+
+        let mut patch = HashMap::new();
+        patch.insert("category".to_string(), serde_json::json!("archive"));
+        store.update_metadata("doc0", patch).unwrap();
+        store.remove("doc1").unwrap();
+        assert_eq!(ids(&store, "category = 'archive'", false), vec!["doc0"]);
+
+        let stats = store.stats().field_indexes;
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].field, "category");
+        assert_eq!(stats[0].index_type, FieldIndexType::Keyword);
+        assert_eq!(stats[0].cardinality, 5);
+        assert_eq!(stats[0].indexed_records, 399);
+        store.save().unwrap();
+
+        let mut reopened = VecStore::open(dir.path().join("test.db")).unwrap();
+        assert_eq!(reopened.stats().field_indexes, stats);
+        assert_eq!(ids(&reopened, "category = 'archive'", false), vec!["doc0"]);
+
+        reopened.drop_field_index("category").unwrap();
+        assert!(reopened.drop_field_index("category").is_err());
+        assert!(reopened.stats().field_indexes.is_empty());
+        assert_eq!(ids(&reopened, "category = 'archive'", false), vec!["doc0"]);
     }
+}
 
-    /// Estimate the selectivity of a filter (what fraction of records pass)
-    fn estimate_filter_selectivity(&self, _filter: &FilterExpr) -> f32 {
-        // TODO: Implement actual selectivity estimation based on metadata statistics
-        // For now, return a conservative estimate
-        0.1 // Assume 10% of records pass filter
+#[cfg(test)]
+mod recall_tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use tempfile::TempDir;
+
+    const DIM: usize = 24;
+
+    fn random_vectors(n: usize, seed: u64) -> Vec<Vec<f32>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..n)
+            .map(|_| (0..DIM).map(|_| rng.gen_range(-1.0..1.0)).collect())
+            .collect()
     }
 
-    /// Create a graph visualizer for the HNSW index
-    ///
-    /// Allows exporting the HNSW graph structure to various visualization formats:
-    /// - DOT format for Graphviz rendering
-    /// - JSON format for D3.js interactive visualizations
-    /// - Cytoscape.js format for web-based graph visualization
-    ///
-    /// Note: Graph visualization is currently only supported for WASM builds.
-    /// The native build uses hnsw_rs which doesn't expose the graph structure.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use vecstore::VecStore;
-    /// # let temp_dir = tempfile::tempdir().unwrap();
-    /// let store = VecStore::open(temp_dir.path())?;
-    ///
-    /// // Export to Graphviz DOT format
-    /// let viz = store.visualizer()?;
-    /// let dot = viz.export_dot()?;
-    /// std::fs::write("graph.dot", dot)?;
-    ///
-    /// // Render with: dot -Tpng graph.dot -o graph.png
-    ///
-    /// // Or export to D3.js JSON
-    /// let json = viz.export_json()?;
-    /// std::fs::write("graph.json", json)?;
-    ///
-    /// // Get graph statistics
-    /// let stats = viz.statistics();
-    /// println!("Nodes: {}, Edges: {}, Layers: {}",
-    ///          stats.node_count, stats.edge_count, stats.layer_count);
-    /// # Ok::<(), anyhow::Error>(())
-    /// ```
-    pub fn visualizer(&self) -> Result<crate::graph_viz::HnswVisualizer> {
-        self.backend.to_visualizer()
+    fn filled_store(dir: &TempDir, n: usize) -> VecStore {
+        let mut store = VecStore::open(dir.path().join("test.db")).unwrap();
+        let records = random_vectors(n, 11).into_iter().enumerate().map(|(i, v)| {
+            make_record(
+                format!("v{}", i),
+                v,
+                Metadata {
+                    fields: HashMap::new(),
+                },
+            )
+        });
+        store.batch_upsert(records).unwrap();
+        store
     }
-}
 
-pub fn make_record(id: impl Into<String>, vector: Vec<f32>, metadata: Metadata) -> Record {
-    Record {
-        id: id.into(),
-        vector,
-        metadata,
-        created_at: Utc::now().timestamp(),
-        deleted: false,
-        deleted_at: None,
-        expires_at: None,
+    #[test]
+    fn test_measure_recall_over_ef_search() {
+        let dir = TempDir::new().unwrap();
+        // Under the exact search threshold, so queries would skip the index
+        let store = filled_store(&dir, 800);
+
+        let report = store.measure_recall(40, 10).unwrap();
+        assert_eq!(report.k, 10);
+        assert_eq!(report.queries, 40);
+        let efs: Vec<usize> = report.points.iter().map(|p| p.ef_search).collect();
+        assert_eq!(efs, vec![10, 16, 32, 64, 128, 256, 512, 1024]);
+        for point in &report.points {
+            assert!((0.0..=1.0).contains(&point.recall));
+            assert!(point.p95_latency >= point.mean_latency / 2);
+        }
+        let last = report.points.last().unwrap();
+        assert!(last.recall >= 0.95, "recall at ef 1024: {}", last.recall);
+        assert!(report.first_reaching(0.9).is_some());
+
+        let queries = random_vectors(5, 12);
+        let report = store.measure_recall_with_queries(&queries, 5).unwrap();
+        assert_eq!(report.queries, 5);
+        assert_eq!(report.points[0].ef_search, 5);
+
+        let report = store.measure_recall_at(10, 10, &[64, 3, 30, 64]).unwrap();
+        let efs: Vec<usize> = report.points.iter().map(|p| p.ef_search).collect();
+        assert_eq!(efs, vec![10, 30, 64]);
+    }
+
+    #[test]
+    fn test_suggest_ef_search_reaches_target() {
+        let dir = TempDir::new().unwrap();
+        let store = filled_store(&dir, 800);
+
+        let ef = store.suggest_ef_search(0.95).unwrap();
+        assert!(ef >= DEFAULT_RECALL_K);
+        let truth = store
+            .ground_truth(
+                &store.sample_vectors(DEFAULT_RECALL_SAMPLES),
+                DEFAULT_RECALL_K,
+            )
+            .unwrap();
+        assert!(store.recall_at(&truth, ef).unwrap().recall >= 0.95);
+        if ef > DEFAULT_RECALL_K {
+            assert!(store.recall_at(&truth, ef - 1).unwrap().recall < 0.95);
+        }
+
+        assert_eq!(store.suggest_ef_search_with(0.1, 20, 5).unwrap(), 5);
+        assert!(store.suggest_ef_search(0.0).is_err());
+        assert!(store.suggest_ef_search(1.5).is_err());
+    }
+
+    #[test]
+    fn test_measure_recall_rejects_bad_input() {
+        let dir = TempDir::new().unwrap();
+        let empty = VecStore::open(dir.path().join("empty.db")).unwrap();
+        assert!(empty.measure_recall(10, 10).is_err());
+
+        let store = filled_store(&dir, 50);
+        assert!(store.measure_recall(10, 0).is_err());
+        assert!(store
+            .measure_recall_with_queries(&[vec![0.0; DIM + 1]], 10)
+            .is_err());
     }
 }
 
 #[cfg(test)]
-mod soft_delete_tests {
+mod upsert_policy_tests {
     use super::*;
+    use serde_json::json;
     use tempfile::TempDir;
 
-    fn create_test_store() -> (VecStore, TempDir) {
-        let temp_dir = TempDir::new().unwrap();
-        let mut store = VecStore::open(temp_dir.path().join("test.db")).unwrap();
+    fn record(id: &str, vector: Vec<f32>, fields: &[(&str, serde_json::Value)]) -> Record {
+        let fields = fields
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+        make_record(id, vector, Metadata { fields })
+    }
 
-        let mut meta1 = Metadata {
-            fields: std::collections::HashMap::new(),
-        };
-        meta1
-            .fields
-            .insert("title".into(), serde_json::json!("Document 1"));
+    fn seeded_store(dir: &TempDir) -> VecStore {
+        let mut store = VecStore::open(dir.path().join("test.db")).unwrap();
         store
-            .upsert("doc1".into(), vec![1.0, 2.0, 3.0], meta1)
+            .batch_upsert(vec![
+                record(
+                    "a",
+                    vec![1.0, 0.0],
+                    &[("color", json!("red")), ("size", json!(1))],
+                ),
+                record("b", vec![0.0, 1.0], &[("color", json!("blue"))]),
+            ])
             .unwrap();
+        store
+    }
 
-        let mut meta2 = Metadata {
-            fields: std::collections::HashMap::new(),
+    #[test]
+    fn test_policies_resolve_conflicts() {
+        let dir = TempDir::new().unwrap();
+        let incoming = || {
+            vec![
+                record("a", vec![0.5, 0.5], &[("color", json!("green"))]),
+                record("c", vec![1.0, 1.0], &[]),
+            ]
         };
-        meta2
-            .fields
-            .insert("title".into(), serde_json::json!("Document 2"));
-        store
-            .upsert("doc2".into(), vec![4.0, 5.0, 6.0], meta2)
+
+        let mut store = seeded_store(&dir);
+        let summary = store
+            .batch_upsert_with_policy(incoming(), UpsertPolicy::Overwrite)
             .unwrap();
+        assert_eq!(
+            summary,
+            UpsertSummary {
+                inserted: 1,
+                updated: 1,
+                skipped: 0,
+                conflicts: 1
+            }
+        );
+        let a = store.get("a").unwrap();
+        assert_eq!(a.metadata.fields.get("size"), None);
+        assert_eq!(a.metadata.fields["color"], json!("green"));
 
-        let mut meta3 = Metadata {
-            fields: std::collections::HashMap::new(),
-        };
-        meta3
-            .fields
-            .insert("title".into(), serde_json::json!("Document 3"));
-        store
-            .upsert("doc3".into(), vec![7.0, 8.0, 9.0], meta3)
+        let mut store = seeded_store(&dir);
+        let summary = store
+            .batch_upsert_with_policy(incoming(), UpsertPolicy::Skip)
             .unwrap();
+        assert_eq!((summary.inserted, summary.skipped), (1, 1));
+        assert_eq!(
+            store.get("a").unwrap().metadata.fields["color"],
+            json!("red")
+        );
+        assert!(store.get("c").is_some());
 
-        (store, temp_dir)
+        let mut store = seeded_store(&dir);
+        let summary = store
+            .batch_upsert_with_policy(incoming(), UpsertPolicy::MergeMetadata)
+            .unwrap();
+        assert_eq!((summary.updated, summary.conflicts), (1, 1));
+        let a = store.get("a").unwrap();
+        assert_eq!(a.metadata.fields["color"], json!("green"));
+        assert_eq!(a.metadata.fields["size"], json!(1));
+        assert_eq!(store.get("a").unwrap().vector, vec![0.5, 0.5]);
     }
 
     #[test]
-    fn test_soft_delete_basic() {
-        let (mut store, _temp_dir) = create_test_store();
-
-        assert_eq!(store.len(), 3);
-        assert_eq!(store.active_count(), 3);
-        assert_eq!(store.deleted_count(), 0);
-
-        // Soft delete a record
-        let result = store.soft_delete("doc1").unwrap();
-        assert!(result);
-
-        // Count should decrease
-        assert_eq!(store.active_count(), 2);
-        assert_eq!(store.deleted_count(), 1);
+    fn test_error_on_conflict_writes_nothing() {
+        let dir = TempDir::new().unwrap();
+        let mut store = seeded_store(&dir);
+        let err = store
+            .batch_upsert_with_policy(
+                vec![
+                    record("c", vec![1.0, 1.0], &[]),
+                    record("b", vec![0.5, 0.5], &[]),
+                ],
+                UpsertPolicy::ErrorOnConflict,
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("1 record(s) conflict"));
         assert_eq!(store.len(), 2);
+        assert!(store.get("c").is_none());
     }
 
     #[test]
-    fn test_soft_delete_nonexistent() {
-        let (mut store, _temp_dir) = create_test_store();
-
-        // Try to delete non-existent record
-        let result = store.soft_delete("nonexistent").unwrap();
-        assert!(!result);
-
-        assert_eq!(store.active_count(), 3);
-        assert_eq!(store.deleted_count(), 0);
+    fn test_duplicates_within_batch_conflict() {
+        let dir = TempDir::new().unwrap();
+        let mut store = seeded_store(&dir);
+        let summary = store
+            .batch_upsert_with_policy(
+                vec![
+                    record("c", vec![1.0, 1.0], &[("x", json!(1))]),
+                    record("c", vec![2.0, 1.0], &[("y", json!(2))]),
+                ],
+                UpsertPolicy::MergeMetadata,
+            )
+            .unwrap();
+        assert_eq!(
+            summary,
+            UpsertSummary {
+                inserted: 1,
+                updated: 1,
+                skipped: 0,
+                conflicts: 1
+            }
+        );
+        let c = store.get("c").unwrap();
+        assert_eq!(c.metadata.fields.len(), 2);
+        assert_eq!(store.get("c").unwrap().vector, vec![2.0, 1.0]);
     }
 
     #[test]
-    fn test_soft_delete_twice() {
-        let (mut store, _temp_dir) = create_test_store();
+    fn test_dry_run_validates_without_writing() {
+        let dir = TempDir::new().unwrap();
+        let store = seeded_store(&dir);
+
+        let summary = store
+            .dry_run_upsert(
+                vec![
+                    record("a", vec![0.5, 0.5], &[]),
+                    record("d", vec![0.5, 0.5], &[]),
+                ],
+                UpsertPolicy::ErrorOnConflict,
+            )
+            .unwrap();
+        assert_eq!((summary.inserted, summary.conflicts), (1, 1));
+        assert!(store.get("d").is_none());
+
+        let bad_dimension = vec![record("d", vec![1.0, 2.0, 3.0], &[])];
+        assert!(store
+            .dry_run_upsert(bad_dimension, UpsertPolicy::Overwrite)
+            .is_err());
+        let empty_id = vec![record("", vec![1.0, 2.0], &[])];
+        assert!(store
+            .dry_run_upsert(empty_id, UpsertPolicy::Overwrite)
+            .is_err());
+
+        // An empty store takes its dimension from the first record
+        let empty = VecStore::open(dir.path().join("empty.db")).unwrap();
+        let mixed = vec![
+            record("x", vec![1.0, 2.0, 3.0], &[]),
+            record("y", vec![1.0, 2.0], &[]),
+        ];
+        assert!(empty
+            .dry_run_upsert(mixed, UpsertPolicy::Overwrite)
+            .is_err());
+    }
+}
 
-        // Delete once
-        assert!(store.soft_delete("doc1").unwrap());
-        assert_eq!(store.deleted_count(), 1);
+#[cfg(test)]
+mod batch_validation_tests {
+    use super::*;
+    use tempfile::TempDir;
 
-        // Delete again - should return false
-        assert!(!store.soft_delete("doc1").unwrap());
-        assert_eq!(store.deleted_count(), 1); // Count unchanged
+    fn metadata() -> Metadata {
+        Metadata {
+            fields: HashMap::new(),
+        }
     }
 
-    #[test]
-    fn test_restore_deleted() {
-        let (mut store, _temp_dir) = create_test_store();
+    fn record(id: &str, vector: Vec<f32>) -> Record {
+        make_record(id, vector, metadata())
+    }
 
-        // Delete and restore
-        store.soft_delete("doc1").unwrap();
-        assert_eq!(store.active_count(), 2);
-        assert_eq!(store.deleted_count(), 1);
+    fn mixed_batch() -> Vec<Record> {
+        vec![
+            record("a", vec![1.0, 0.0]),
+            record("b", vec![1.0, 0.0, 0.0]),
+            record("", vec![0.0, 1.0]),
+            record("c", vec![0.5, 0.5]),
+            record("d", vec![]),
+        ]
+    }
 
-        let result = store.restore("doc1").unwrap();
-        assert!(result);
+    #[test]
+    fn test_invalid_batch_is_rejected_whole() {
+        let dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(dir.path().join("test.db")).unwrap();
+
+        let err = store.batch_upsert(mixed_batch()).unwrap_err();
+        let invalid = err.downcast_ref::<BatchValidationError>().unwrap();
+        let failed: Vec<(usize, &str)> = invalid
+            .errors
+            .iter()
+            .map(|e| (e.index, e.id.as_str()))
+            .collect();
+        assert_eq!(failed, vec![(1, "b"), (2, ""), (4, "d")]);
+        assert!(invalid.errors[0].reason.contains("expected 2, got 3"));
+        assert!(err.to_string().contains("3 record(s) failed validation"));
 
-        assert_eq!(store.active_count(), 3);
-        assert_eq!(store.deleted_count(), 0);
-        assert_eq!(store.len(), 3);
+        // Nothing written, and the empty store didn't take a dimension
+        assert_eq!(store.len(), 0);
+        assert_eq!(store.dimension(), 0);
     }
 
     #[test]
-    fn test_restore_nondeleted() {
-        let (mut store, _temp_dir) = create_test_store();
+    fn test_partial_batch_writes_valid_records() {
+        let dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(dir.path().join("test.db")).unwrap();
+
+        let result = store.batch_upsert_partial(mixed_batch()).unwrap();
+        assert_eq!(result.inserted, 2);
+        let failed: Vec<usize> = result.failed.iter().map(|e| e.index).collect();
+        assert_eq!(failed, vec![1, 2, 4]);
+        assert!(store.get("a").is_some());
+        assert!(store.get("c").is_some());
+        assert!(store.get("b").is_none());
+    }
 
-        // Try to restore a record that isn't deleted
-        let result = store.restore("doc1").unwrap();
-        assert!(!result);
+    #[test]
+    fn test_validation_checks_nan_and_namespace() {
+        let dir = TempDir::new().unwrap();
+        let mut store = VecStore::builder(dir.path().join("test.db"))
+            .vector_policy(VectorPolicy {
+                reject_nan: true,
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+        store
+            .upsert_in_namespace("tenant", "owned".into(), vec![1.0, 0.0], metadata())
+            .unwrap();
 
-        assert_eq!(store.active_count(), 3);
+        let result = store
+            .batch_upsert_partial(vec![
+                record("nan", vec![f32::NAN, 0.0]),
+                record("owned", vec![0.0, 1.0]),
+                record("ok", vec![0.0, 1.0]),
+            ])
+            .unwrap();
+        assert_eq!(result.inserted, 1);
+        assert!(result.failed[0].reason.contains("NaN"));
+        assert!(result.failed[1].reason.contains("namespace"));
     }
+}
 
-    #[test]
-    fn test_query_excludes_deleted() {
-        let (mut store, _temp_dir) = create_test_store();
+#[cfg(test)]
+mod version_tests {
+    use super::*;
+    use tempfile::TempDir;
 
-        // Query before deletion
-        let query = Query {
-            vector: vec![1.0, 2.0, 3.0],
-            k: 10,
-            filter: None,
-        };
-        let results = store.query(query.clone()).unwrap();
-        assert_eq!(results.len(), 3);
+    fn meta(title: &str) -> Metadata {
+        let mut fields = HashMap::new();
+        fields.insert("title".to_string(), serde_json::json!(title));
+        Metadata { fields }
+    }
 
-        // Soft delete a record
-        store.soft_delete("doc1").unwrap();
+    fn version(store: &VecStore, id: &str) -> u64 {
+        store.get(id).unwrap().version
+    }
 
-        // Query should exclude deleted record
-        let results = store.query(query).unwrap();
-        assert_eq!(results.len(), 2);
+    #[test]
+    fn test_versions_count_writes() {
+        let dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(dir.path().join("test.db")).unwrap();
 
-        // Ensure doc1 is not in results
-        assert!(!results.iter().any(|n| n.id == "doc1"));
+        store
+            .upsert("a".into(), vec![1.0, 0.0], meta("one"))
+            .unwrap();
+        assert_eq!(version(&store, "a"), 1);
+        store
+            .upsert("a".into(), vec![0.0, 1.0], meta("two"))
+            .unwrap();
+        assert_eq!(version(&store, "a"), 2);
+        store
+            .update_metadata(
+                "a",
+                HashMap::from([("x".to_string(), serde_json::json!(1))]),
+            )
+            .unwrap();
+        assert_eq!(version(&store, "a"), 3);
+        store.soft_delete("a").unwrap();
+        store.restore("a").unwrap();
+        assert_eq!(version(&store, "a"), 5);
+
+        store
+            .batch_upsert(vec![
+                make_record("a", vec![1.0, 1.0], meta("three")),
+                make_record("b", vec![1.0, 1.0], meta("b")),
+            ])
+            .unwrap();
+        assert_eq!(version(&store, "a"), 6);
+        assert_eq!(version(&store, "b"), 1);
+
+        let hits = store
+            .query(Query::new(vec![1.0, 1.0]).with_limit(2))
+            .unwrap();
+        let a = hits.iter().find(|n| n.id == "a").unwrap();
+        assert_eq!(a.version, 6);
     }
 
     #[test]
-    fn test_compact() {
-        let (mut store, _temp_dir) = create_test_store();
+    fn test_versions_persist_through_compaction() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("test.db");
+        let mut store = VecStore::open(&path).unwrap();
+        for i in 0..3 {
+            store
+                .upsert("a".into(), vec![1.0, i as f32], meta("a"))
+                .unwrap();
+        }
+        store
+            .upsert("gone".into(), vec![0.0, 1.0], meta("gone"))
+            .unwrap();
+        store.soft_delete("gone").unwrap();
+        store.compact().unwrap();
+        assert_eq!(version(&store, "a"), 3);
 
-        // Soft delete two records
-        store.soft_delete("doc1").unwrap();
-        store.soft_delete("doc2").unwrap();
+        store.save().unwrap();
+        let reopened = VecStore::open(&path).unwrap();
+        assert_eq!(version(&reopened, "a"), 3);
+    }
 
-        assert_eq!(store.active_count(), 1);
-        assert_eq!(store.deleted_count(), 2);
+    #[test]
+    fn test_upsert_if_version() {
+        let dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(dir.path().join("test.db")).unwrap();
 
-        // Compact (permanently remove)
-        let removed = store.compact().unwrap();
-        assert_eq!(removed, 2);
+        assert_eq!(
+            store
+                .upsert_if_version("a".into(), 0, vec![1.0, 0.0], meta("one"))
+                .unwrap(),
+            1
+        );
+        let err = store
+            .upsert_if_version("a".into(), 0, vec![1.0, 0.0], meta("again"))
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<VersionConflict>(),
+            Some(&VersionConflict {
+                id: "a".into(),
+                expected: 0,
+                actual: 1,
+            })
+        );
 
-        // Deleted records should be gone
-        assert_eq!(store.deleted_count(), 0);
-        assert_eq!(store.active_count(), 1);
+        assert_eq!(
+            store
+                .upsert_if_version("a".into(), 1, vec![0.0, 1.0], meta("two"))
+                .unwrap(),
+            2
+        );
+        // The stale writer loses and nothing changes
+        assert!(store
+            .upsert_if_version("a".into(), 1, vec![1.0, 1.0], meta("stale"))
+            .is_err());
+        let a = store.get("a").unwrap();
+        assert_eq!(
+            (a.version, a.metadata.fields["title"].as_str()),
+            (2, Some("two"))
+        );
 
-        // Total records should be 1
-        assert_eq!(store.records.len(), 1);
+        // Soft-deleted records count as absent
+        store.soft_delete("a").unwrap();
+        let err = store
+            .upsert_if_version("a".into(), 3, vec![1.0, 0.0], meta("x"))
+            .unwrap_err();
+        assert_eq!(err.downcast_ref::<VersionConflict>().unwrap().actual, 0);
+        assert_eq!(
+            store
+                .upsert_if_version("a".into(), 0, vec![1.0, 0.0], meta("back"))
+                .unwrap(),
+            4
+        );
     }
 
     #[test]
-    fn test_list_deleted() {
-        let (mut store, _temp_dir) = create_test_store();
+    fn test_batch_reports_conflicts_per_item() {
+        let dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(dir.path().join("test.db")).unwrap();
+        store.upsert("a".into(), vec![1.0, 0.0], meta("a")).unwrap();
+
+        let result = store
+            .batch_execute(vec![
+                BatchOperation::UpsertIfVersion {
+                    id: "a".into(),
+                    expected_version: 7,
+                    vector: vec![0.0, 1.0],
+                    metadata: meta("stale"),
+                },
+                BatchOperation::UpsertIfVersion {
+                    id: "b".into(),
+                    expected_version: 0,
+                    vector: vec![0.0, 1.0],
+                    metadata: meta("b"),
+                },
+                BatchOperation::Delete {
+                    id: "missing".into(),
+                },
+            ])
+            .unwrap();
+        assert_eq!((result.succeeded, result.failed), (1, 2));
+        assert_eq!(result.errors[0].index, 0);
+        assert_eq!(
+            result.errors[0].version_conflict.as_ref().map(|c| c.actual),
+            Some(1)
+        );
+        assert!(result.errors[1].version_conflict.is_none());
+        assert_eq!(version(&store, "a"), 1);
+        assert_eq!(version(&store, "b"), 1);
+    }
+}
 
-        store.soft_delete("doc1").unwrap();
-        store.soft_delete("doc2").unwrap();
+#[cfg(test)]
+mod event_tests {
+    use super::*;
+    use tempfile::TempDir;
 
-        let deleted = store.list_deleted();
-        assert_eq!(deleted.len(), 2);
-        assert!(deleted.iter().any(|r| r.id == "doc1"));
-        assert!(deleted.iter().any(|r| r.id == "doc2"));
+    fn meta() -> Metadata {
+        Metadata {
+            fields: HashMap::new(),
+        }
+    }
+
+    fn recorder(store: &mut VecStore) -> (Arc<Mutex<Vec<StoreEvent>>>, SubscriptionId) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let id = store.subscribe(Box::new(move |event| sink.lock().unwrap().push(event)));
+        (events, id)
+    }
+
+    fn upsert(id: &str) -> StoreEvent {
+        StoreEvent::Upsert {
+            id: id.into(),
+            dimension: 2,
+        }
+    }
+
+    fn delete(id: &str) -> StoreEvent {
+        StoreEvent::Delete { id: id.into() }
     }
 
     #[test]
-    fn test_list_active() {
-        let (mut store, _temp_dir) = create_test_store();
+    fn test_events_follow_writes() {
+        let dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(dir.path()).unwrap();
+        let (events, subscription) = recorder(&mut store);
 
-        store.soft_delete("doc1").unwrap();
+        store.upsert("a".into(), vec![1.0, 0.0], meta()).unwrap();
+        store
+            .batch_upsert(vec![make_record("b", vec![0.0, 1.0], meta())])
+            .unwrap();
+        store.soft_delete("a").unwrap();
+        store.restore("a").unwrap();
+        store.soft_delete("b").unwrap();
+        store.remove("a").unwrap();
+        store.compact().unwrap();
+        // Rejected writes are not reported
+        assert!(store.upsert("c".into(), vec![1.0], meta()).is_err());
 
-        let active = store.list_active();
-        assert_eq!(active.len(), 2);
-        assert!(!active.iter().any(|r| r.id == "doc1"));
-        assert!(active.iter().any(|r| r.id == "doc2"));
-        assert!(active.iter().any(|r| r.id == "doc3"));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                upsert("a"),
+                upsert("b"),
+                delete("a"),
+                upsert("a"),
+                delete("b"),
+                delete("a"),
+                StoreEvent::Save,
+                StoreEvent::Compact,
+            ]
+        );
+
+        assert!(store.unsubscribe(subscription));
+        store.save().unwrap();
+        assert_eq!(events.lock().unwrap().len(), 8);
     }
 
     #[test]
-    fn test_deleted_at_timestamp() {
-        let (mut store, _temp_dir) = create_test_store();
+    fn test_panicking_hook_leaves_store_consistent() {
+        let dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(dir.path()).unwrap();
+        store.subscribe(Box::new(|_| panic!("subscriber bug")));
+        let (events, _) = recorder(&mut store);
 
-        store.soft_delete("doc1").unwrap();
+        store.upsert("a".into(), vec![1.0, 0.0], meta()).unwrap();
+        store.upsert("b".into(), vec![0.0, 1.0], meta()).unwrap();
+        store.save().unwrap();
 
-        let deleted = store.list_deleted();
-        assert_eq!(deleted.len(), 1);
-        assert!(deleted[0].deleted);
-        assert!(deleted[0].deleted_at.is_some());
+        assert_eq!(store.len(), 2);
+        let hits = store
+            .query(Query::new(vec![1.0, 0.0]).with_limit(1))
+            .unwrap();
+        assert_eq!(hits[0].id, "a");
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![upsert("a"), upsert("b"), StoreEvent::Save]
+        );
     }
 }
 
 #[cfg(test)]
-mod builder_tests {
+mod profile_tests {
     use super::*;
+    use std::time::Duration;
     use tempfile::TempDir;
 
-    #[test]
-    fn test_builder_default() {
-        let temp_dir = TempDir::new().unwrap();
-        let store = VecStore::builder(temp_dir.path().join("test.db"))
+    fn populated(dir: &TempDir, exact_search_threshold: usize) -> VecStore {
+        let mut store = VecStore::builder(dir.path())
+            .exact_search_threshold(exact_search_threshold)
             .build()
             .unwrap();
-
-        // Default should be Cosine
-        assert_eq!(store.distance_metric(), Distance::Cosine);
-        assert_eq!(store.config().hnsw_m, 16);
-        assert_eq!(store.config().hnsw_ef_construction, 200);
+        for i in 0..50 {
+            let mut fields = HashMap::new();
+            fields.insert("even".to_string(), serde_json::json!(i % 2 == 0));
+            store
+                .upsert(
+                    format!("v{i}"),
+                    vec![i as f32, 1.0, (i % 7) as f32],
+                    Metadata { fields },
+                )
+                .unwrap();
+        }
+        store
     }
 
     #[test]
-    fn test_builder_manhattan() {
-        // Manhattan is not yet supported by HNSW backend - test that it returns error
-        let temp_dir = TempDir::new().unwrap();
-        let result = VecStore::builder(temp_dir.path().join("test.db"))
-            .distance(Distance::Manhattan)
-            .build();
-
-        assert!(result.is_err());
-        if let Err(e) = result {
-            assert!(e.to_string().contains("not yet supported"));
-        }
+    fn test_profile_only_when_asked() {
+        let dir = TempDir::new().unwrap();
+        let store = populated(&dir, 1000);
+        let q = Query::new(vec![3.0, 1.0, 3.0]).with_limit(5);
+
+        assert!(store.query_profiled(q.clone()).unwrap().profile.is_none());
+
+        let results = store.query_profiled(q.with_profile()).unwrap();
+        let profile = results.profile.unwrap();
+        assert_eq!(results.neighbors.len(), 5);
+        assert!(profile.exact);
+        assert_eq!(profile.candidates, 50);
+        assert_eq!(profile.results, 5);
+        assert_eq!(profile.ef_search, None);
+        assert_eq!(profile.fetch_rounds, 0);
+        assert!(profile.total >= profile.scoring);
+    }
+
+    #[test]
+    fn test_profile_of_index_search() {
+        let dir = TempDir::new().unwrap();
+        let store = populated(&dir, 0);
+        let q = Query::new(vec![3.0, 1.0, 3.0])
+            .with_limit(5)
+            .with_filter("even = true")
+            .with_profile();
+
+        let results = store.query_profiled(q.clone()).unwrap();
+        let profile = results.profile.unwrap();
+        assert!(!profile.exact);
+        assert!(profile.fetch_rounds >= 1);
+        assert!(profile.candidates >= profile.results);
+        assert!(profile.ef_search.unwrap() >= 50);
+        assert_eq!(profile.results, 5);
+        // Same results as an unprofiled query
+        let ids =
+            |neighbors: &[Neighbor]| neighbors.iter().map(|n| n.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&results.neighbors), ids(&store.query(q).unwrap()));
     }
 
     #[test]
-    fn test_builder_hamming() {
-        // Hamming is not yet supported by HNSW backend - test that it returns error
-        let temp_dir = TempDir::new().unwrap();
-        let result = VecStore::builder(temp_dir.path().join("test.db"))
-            .distance(Distance::Hamming)
-            .build();
+    fn test_slow_query_log() {
+        let dir = TempDir::new().unwrap();
+        let mut store = populated(&dir, 1000);
+        let q = Query::new(vec![3.0, 1.0, 3.0])
+            .with_limit(3)
+            .with_filter("even = false");
+
+        store.query(q.clone()).unwrap();
+        assert!(store.slow_queries().is_empty());
+
+        // Every query takes at least 0 ms
+        store.set_slow_query_threshold(Some(Duration::ZERO));
+        store.query(q.clone()).unwrap();
+        store.query(q.clone().with_profile()).unwrap();
+        let slow = store.slow_queries();
+        assert_eq!(slow.len(), 2);
+        assert_eq!(slow[0].k, 3);
+        assert_eq!(slow[0].filter.as_deref(), Some("even = false"));
+        assert_eq!(slow[0].profile.results, 3);
+        assert_eq!(store.slow_query_count(), 2);
+
+        store.clear_slow_queries();
+        assert!(store.slow_queries().is_empty());
+        assert_eq!(store.slow_query_count(), 2);
+
+        store.set_slow_query_threshold(None);
+        store.query(q).unwrap();
+        assert!(store.slow_queries().is_empty());
+    }
 
-        assert!(result.is_err());
-        if let Err(e) = result {
-            assert!(e.to_string().contains("not yet supported"));
-        }
+    #[test]
+    fn test_queries_stop_when_cancelled_or_timed_out() {
+        let dir = TempDir::new().unwrap();
+        let store = populated(&dir, 0);
+        let q = Query::new(vec![3.0, 1.0, 3.0]).with_limit(3);
+
+        let cancel = QueryCancel::new();
+        cancel.cancel();
+        let error = store.query(q.clone().with_cancel(cancel)).unwrap_err();
+        assert!(error.downcast_ref::<QueryInterrupted>().unwrap().cancelled);
+        // Cancelled queries aren't logged
+        assert!(store.slow_queries().is_empty());
+
+        let error = store
+            .query(
+                q.clone()
+                    .with_cancel(QueryCancel::with_timeout(Duration::ZERO)),
+            )
+            .unwrap_err();
+        assert!(!error.downcast_ref::<QueryInterrupted>().unwrap().cancelled);
+        // Timed-out queries are, with no threshold set
+        let slow = store.slow_queries();
+        assert_eq!(slow.len(), 1);
+        assert!(slow[0].timed_out);
+
+        let generous = QueryCancel::with_timeout(Duration::from_secs(60));
+        assert_eq!(store.query(q.with_cancel(generous)).unwrap().len(), 3);
+        assert_eq!(store.slow_queries().len(), 1);
     }
 
     #[test]
-    fn test_builder_jaccard() {
-        // Jaccard is not yet supported by HNSW backend - test that it returns error
-        let temp_dir = TempDir::new().unwrap();
-        let result = VecStore::builder(temp_dir.path().join("test.db"))
-            .distance(Distance::Jaccard)
-            .build();
+    fn test_embedding_model_is_recorded_and_checked() {
+        let dir = TempDir::new().unwrap();
+        let mut store = populated(&dir, 1000);
+        let model = |name: &str, dimension| EmbeddingModel {
+            name: name.to_string(),
+            dimension,
+        };
 
-        assert!(result.is_err());
-        if let Err(e) = result {
-            assert!(e.to_string().contains("not yet supported"));
-        }
+        // Without a recorded model only the dimension is checked
+        assert!(store.check_embedding_model(&model("minilm", 384)).is_err());
+        store.set_embedding_model(model("hash", 3)).unwrap();
+        store.save().unwrap();
+
+        let mut store = VecStore::open(dir.path()).unwrap();
+        assert_eq!(store.embedding_model(), Some(&model("hash", 3)));
+        store.check_embedding_model(&model("hash", 3)).unwrap();
+        let err = store.set_embedding_model(model("other", 3)).unwrap_err();
+        let mismatch = err.downcast_ref::<EmbeddingModelMismatch>().unwrap();
+        assert_eq!(mismatch.stored.name, "hash");
+        assert_eq!(mismatch.requested.name, "other");
     }
 
     #[test]
-    fn test_builder_custom_hnsw_params() {
-        let temp_dir = TempDir::new().unwrap();
-        let store = VecStore::builder(temp_dir.path().join("test.db"))
-            .hnsw_m(32)
-            .hnsw_ef_construction(400)
-            .build()
+    fn test_optimize_with_progress() {
+        let dir = TempDir::new().unwrap();
+        let mut store = populated(&dir, 1000);
+        store.remove("v0").unwrap();
+
+        let mut seen = Vec::new();
+        store
+            .optimize_with_progress(&mut |done, total| seen.push((done, total)))
             .unwrap();
+        assert_eq!(seen.len(), 49);
+        assert_eq!(seen.last(), Some(&(49, 49)));
+    }
 
-        assert_eq!(store.config().hnsw_m, 32);
-        assert_eq!(store.config().hnsw_ef_construction, 400);
+    #[test]
+    fn test_delete_by_filter() {
+        let dir = TempDir::new().unwrap();
+        let mut store = populated(&dir, 1000);
+        let even = parse_filter("even = true").unwrap();
+
+        assert_eq!(store.delete_by_filter(&even).unwrap(), 25);
+        assert_eq!(store.delete_by_filter(&even).unwrap(), 0);
+        assert_eq!(store.len(), 25);
+        assert!(store.get("v0").is_none());
+        assert!(store.get("v1").is_some());
     }
 
     #[test]
-    fn test_builder_chained() {
-        let temp_dir = TempDir::new().unwrap();
-        let store = VecStore::builder(temp_dir.path().join("test.db"))
-            .distance(Distance::Euclidean)
-            .hnsw_m(64)
-            .hnsw_ef_construction(500)
-            .build()
-            .unwrap();
+    fn test_delete_ids_skips_unknown() {
+        let dir = TempDir::new().unwrap();
+        let mut store = populated(&dir, 1000);
+        store.soft_delete("v3").unwrap();
+
+        let ids = ["v1", "missing", "v2", "v1", "v3"];
+        assert_eq!(store.delete_ids(&ids).unwrap(), 3);
+        assert_eq!(store.len(), 47);
+        assert_eq!(store.deleted_count(), 0);
+        assert_eq!(store.delete_ids(&ids).unwrap(), 0);
+        assert_eq!(store.delete_ids::<String>(&[]).unwrap(), 0);
+    }
+}
 
-        assert_eq!(store.distance_metric(), Distance::Euclidean);
-        assert_eq!(store.config().hnsw_m, 64);
-        assert_eq!(store.config().hnsw_ef_construction, 500);
+#[cfg(test)]
+mod chunk_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn metadata() -> Metadata {
+        Metadata {
+            fields: HashMap::new(),
+        }
     }
 
-    #[test]
-    fn test_builder_backward_compatibility() {
-        let temp_dir = TempDir::new().unwrap();
+    fn chunk(parent: &str, index: usize) -> Record {
+        make_record(
+            format!("{parent}#{index}"),
+            vec![index as f32, 1.0],
+            metadata(),
+        )
+        .with_text(format!("{parent} part {index}"))
+        .with_parent(parent, index)
+    }
 
-        // Old way still works
-        let store_old = VecStore::open(temp_dir.path().join("old.db")).unwrap();
-        assert_eq!(store_old.distance_metric(), Distance::Cosine);
+    fn ids(records: &[Record]) -> Vec<&str> {
+        records.iter().map(|r| r.id.as_str()).collect()
+    }
 
-        // New way also works
-        let store_new = VecStore::builder(temp_dir.path().join("new.db"))
-            .build()
-            .unwrap();
-        assert_eq!(store_new.distance_metric(), Distance::Cosine);
+    #[test]
+    fn test_text_and_parent_persist() {
+        let dir = TempDir::new().unwrap();
+        {
+            let mut store = VecStore::open(dir.path()).unwrap();
+            store.upsert_record(chunk("doc", 0)).unwrap();
+            store
+                .upsert("plain".into(), vec![5.0, 5.0], metadata())
+                .unwrap();
+            store.save().unwrap();
+        }
 
-        // Both should have the same default config
-        assert_eq!(store_old.config().distance, store_new.config().distance);
+        let store = VecStore::open(dir.path()).unwrap();
+        let record = store.get("doc#0").unwrap();
+        assert_eq!(record.text.as_deref(), Some("doc part 0"));
+        assert_eq!(record.parent_id.as_deref(), Some("doc"));
+        assert_eq!(record.chunk_index, Some(0));
+        assert!(store.get("plain").unwrap().text.is_none());
+
+        let q = Query::new(vec![0.0, 1.0]).with_limit(1);
+        assert!(store.query(q.clone()).unwrap()[0].text.is_none());
+        let options = QueryOptions {
+            include_text: true,
+            ..Default::default()
+        };
+        let hit = &store.query(q.with_options(options)).unwrap()[0];
+        assert_eq!(hit.text.as_deref(), Some("doc part 0"));
+
+        // Records written before text existed still load
+        let old: Record = serde_json::from_str(
+            r#"{"id":"a","vector":[1.0],"metadata":{"fields":{}},"created_at":0}"#,
+        )
+        .unwrap();
+        assert!(old.text.is_none() && old.parent_id.is_none());
     }
 
     #[test]
-    fn test_distance_enum_from_str() {
-        assert_eq!(Distance::from_str("cosine").unwrap(), Distance::Cosine);
+    fn test_fetch_parent_window() {
+        let dir = TempDir::new().unwrap();
+        let mut store = VecStore::open(dir.path()).unwrap();
+        // Inserted out of order, with a chunk of another document in between
+        store
+            .batch_upsert(vec![
+                chunk("doc", 3),
+                chunk("doc", 0),
+                chunk("other", 1),
+                chunk("doc", 4),
+                chunk("doc", 1),
+                chunk("doc", 2),
+            ])
+            .unwrap();
+        store
+            .upsert("lone".into(), vec![1.0, 1.0], metadata())
+            .unwrap();
+
+        let window = store.fetch_parent_window("doc#2", 1).unwrap();
+        assert_eq!(ids(&window), ["doc#1", "doc#2", "doc#3"]);
+        assert_eq!(window[0].text.as_deref(), Some("doc part 1"));
         assert_eq!(
-            Distance::from_str("euclidean").unwrap(),
-            Distance::Euclidean
+            ids(&store.fetch_parent_window("doc#0", 2).unwrap()),
+            ["doc#0", "doc#1", "doc#2"]
         );
-        assert_eq!(Distance::from_str("l2").unwrap(), Distance::Euclidean);
         assert_eq!(
-            Distance::from_str("manhattan").unwrap(),
-            Distance::Manhattan
+            ids(&store.fetch_parent_window("doc#4", 0).unwrap()),
+            ["doc#4"]
         );
-        assert_eq!(Distance::from_str("l1").unwrap(), Distance::Manhattan);
-        assert_eq!(Distance::from_str("hamming").unwrap(), Distance::Hamming);
-        assert_eq!(Distance::from_str("jaccard").unwrap(), Distance::Jaccard);
-        assert_eq!(Distance::from_str("dot").unwrap(), Distance::DotProduct);
 
-        // Case insensitive
+        store.soft_delete("doc#3").unwrap();
         assert_eq!(
-            Distance::from_str("MANHATTAN").unwrap(),
-            Distance::Manhattan
+            ids(&store.fetch_parent_window("doc#2", 1).unwrap()),
+            ["doc#1", "doc#2", "doc#4"]
         );
-        assert_eq!(Distance::from_str("Hamming").unwrap(), Distance::Hamming);
 
-        // Invalid
-        assert!(Distance::from_str("invalid").is_err());
+        assert_eq!(
+            ids(&store.fetch_parent_window("lone", 3).unwrap()),
+            ["lone"]
+        );
+        assert!(store.fetch_parent_window("missing", 1).is_err());
     }
 
     #[test]
-    fn test_distance_enum_name_and_description() {
-        assert_eq!(Distance::Cosine.name(), "Cosine");
-        assert_eq!(Distance::Manhattan.name(), "Manhattan");
-        assert_eq!(Distance::Hamming.name(), "Hamming");
-        assert_eq!(Distance::Jaccard.name(), "Jaccard");
+    fn test_wal_replays_text_and_parent() {
+        let dir = TempDir::new().unwrap();
+        let open = || {
+            VecStore::builder(dir.path())
+                .wal(WalSync::Off)
+                .build()
+                .unwrap()
+        };
+        {
+            let mut store = open();
+            store.upsert_record(chunk("doc", 7)).unwrap();
+            store
+                .upsert("plain".into(), vec![1.0, 0.0], metadata())
+                .unwrap();
+        }
 
-        assert!(Distance::Cosine.description().contains("angle"));
-        assert!(Distance::Manhattan.description().contains("outliers"));
-        assert!(Distance::Hamming.description().contains("binary"));
-        assert!(Distance::Jaccard.description().contains("set"));
+        let store = open();
+        let record = store.get("doc#7").unwrap();
+        assert_eq!(record.text.as_deref(), Some("doc part 7"));
+        assert_eq!(record.parent_id.as_deref(), Some("doc"));
+        assert_eq!(record.chunk_index, Some(7));
+        assert!(store.contains("plain"));
     }
 }
 
 #[cfg(test)]
-mod text_index_persistence_tests {
+mod validate_tests {
     use super::*;
     use tempfile::TempDir;
 
-    /// Test that text index survives store reopen (Major Issue #6 fix)
-    #[test]
-    fn test_text_index_persists_across_reopen() {
-        let temp_dir = TempDir::new().unwrap();
-        let path = temp_dir.path().join("test.db");
-
-        // Create store, index text, and save
-        {
-            let mut store = VecStore::open(&path).unwrap();
-
-            let mut meta = Metadata {
-                fields: std::collections::HashMap::new(),
+    fn saved_store(path: &Path) {
+        let mut store = VecStore::open(path).unwrap();
+        for i in 0..5 {
+            let metadata = Metadata {
+                fields: HashMap::new(),
             };
-            meta.fields
-                .insert("title".into(), serde_json::json!("Test Doc"));
-
-            store
-                .upsert("doc1".into(), vec![1.0, 2.0, 3.0], meta)
-                .unwrap();
             store
-                .index_text("doc1", "hello world test".to_string())
+                .upsert(format!("doc{}", i), vec![i as f32, 1.0, 0.5], metadata)
                 .unwrap();
-            store.save().unwrap();
-
-            // Verify text is indexed
-            assert!(store.has_text("doc1"));
-            assert_eq!(store.get_text("doc1"), Some("hello world test"));
         }
+        store.save().unwrap();
+    }
 
-        // Reopen store and verify text survives
-        {
-            let store = VecStore::open(&path).unwrap();
-            assert!(
-                store.has_text("doc1"),
-                "Text index should survive store reopen"
-            );
-            assert_eq!(store.get_text("doc1"), Some("hello world test"));
-        }
+    fn status(report: &IntegrityReport, name: &str) -> CheckStatus {
+        report
+            .checks
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.status)
+            .unwrap()
     }
 
-    /// Test that text index survives snapshot restore (Major Issue #6 fix)
     #[test]
-    fn test_text_index_persists_in_snapshots() {
-        let temp_dir = TempDir::new().unwrap();
-        let path = temp_dir.path().join("test.db");
+    fn test_validate_consistent_store() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("store");
+        saved_store(&path);
+
+        let report = VecStore::validate(&path).unwrap();
+        assert!(report.is_ok(), "{:?}", report);
+        assert_eq!(report.manifest_records, 5);
+        assert_eq!(report.dimension, 3);
+        assert_eq!(status(&report, "id mappings"), CheckStatus::Pass);
+        assert_eq!(status(&report, "index dump"), CheckStatus::Pass);
+        assert_eq!(status(&report, "write-ahead log"), CheckStatus::Skipped);
+
+        assert!(VecStore::validate(dir.path().join("missing")).is_err());
+    }
 
-        let mut store = VecStore::open(&path).unwrap();
+    #[test]
+    fn test_validate_and_repair_corrupt_store() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("store");
+        saved_store(&path);
+        let layout = disk::DiskLayout::new(&path);
+
+        // An index entry with no record
+        let (mut id_to_idx, mut idx_to_id, next_idx): (
+            HashMap<Id, usize>,
+            HashMap<usize, Id>,
+            usize,
+        ) = bincode::deserialize(&std::fs::read(layout.meta_path()).unwrap()).unwrap();
+        id_to_idx.insert("ghost".into(), next_idx);
+        idx_to_id.insert(next_idx, "ghost".into());
+        let meta = bincode::serialize(&(id_to_idx, idx_to_id, next_idx + 1)).unwrap();
+        std::fs::write(layout.meta_path(), meta).unwrap();
+
+        // A record of the wrong dimension and one that doesn't deserialize
+        let mut records: Vec<serde_json::Value> =
+            serde_json::from_slice(&std::fs::read(layout.vectors_path()).unwrap()).unwrap();
+        let mut short = records[0].clone();
+        short["id"] = "short".into();
+        short["vector"] = serde_json::json!([1.0, 2.0]);
+        records.push(short);
+        records.push(serde_json::json!({"id": "broken"}));
+        std::fs::write(layout.vectors_path(), serde_json::to_vec(&records).unwrap()).unwrap();
+
+        let report = VecStore::validate(&path).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(status(&report, "records and metadata"), CheckStatus::Fail);
+        assert_eq!(status(&report, "vector dimensions"), CheckStatus::Fail);
+        // Five points in the graph for six mapped ids
+        assert_eq!(status(&report, "index dump"), CheckStatus::Fail);
+        let mappings = report
+            .checks
+            .iter()
+            .find(|c| c.name == "id mappings")
+            .unwrap();
+        assert_eq!(mappings.status, CheckStatus::Fail);
+        assert!(mappings.details.iter().any(|d| d.starts_with("ghost:")));
+        assert!(mappings.details.iter().any(|d| d.starts_with("short:")));
+
+        let repair = VecStore::repair(&path).unwrap();
+        assert_eq!(repair.undecodable_records, 1);
+        assert_eq!(repair.mismatched_records, vec!["short".to_string()]);
+        assert_eq!(repair.orphaned_ids, 1);
+        assert_eq!(repair.indexed, 5);
+
+        let report = VecStore::validate(&path).unwrap();
+        assert!(report.is_ok(), "{:?}", report);
+        let store = VecStore::open(&path).unwrap();
+        assert_eq!(store.len(), 5);
+    }
 
-        let mut meta = Metadata {
-            fields: std::collections::HashMap::new(),
+    #[test]
+    fn test_scan_consistency_of_an_open_store() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("store");
+        saved_store(&path);
+        let scan = VecStore::open(&path).unwrap().scan_consistency(2);
+        assert!(scan.orphaned_ids.is_empty() && scan.duplicate_ids.is_empty());
+        assert_eq!(scan.dimensions, BTreeMap::from([(3, 5)]));
+        assert_eq!(scan.sampled, 2);
+
+        // A mapped id with no record, and a second slot naming doc1
+        let layout = disk::DiskLayout::new(&path);
+        let (mut id_to_idx, mut idx_to_id, next_idx): (
+            HashMap<Id, usize>,
+            HashMap<usize, Id>,
+            usize,
+        ) = bincode::deserialize(&std::fs::read(layout.meta_path()).unwrap()).unwrap();
+        id_to_idx.insert("ghost".into(), next_idx);
+        idx_to_id.insert(next_idx, "ghost".into());
+        idx_to_id.insert(next_idx + 1, "doc1".into());
+        let meta = bincode::serialize(&(id_to_idx, idx_to_id, next_idx + 2)).unwrap();
+        std::fs::write(layout.meta_path(), meta).unwrap();
+
+        let scan = VecStore::open(&path).unwrap().scan_consistency(100);
+        assert_eq!(scan.orphaned_ids, vec!["ghost".to_string()]);
+        assert!(scan.unindexed_ids.is_empty());
+        assert_eq!(scan.duplicate_ids, vec!["doc1".to_string()]);
+        assert_eq!(scan.sampled, 5);
+        assert!(scan.non_finite_ids.is_empty() && scan.zero_vector_ids.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod dedupe_tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use tempfile::TempDir;
+
+    fn upsert(store: &mut VecStore, id: &str, vector: Vec<f32>) {
+        let metadata = Metadata {
+            fields: HashMap::new(),
         };
-        meta.fields
-            .insert("title".into(), serde_json::json!("Snapshot Doc"));
+        store.upsert(id.to_string(), vector, metadata).unwrap();
+    }
 
+    fn store_with_duplicates(dir: &TempDir) -> VecStore {
+        let mut store = VecStore::open(dir.path().join("test.db")).unwrap();
+        let mut rng = StdRng::seed_from_u64(5);
+        for i in 0..40 {
+            let vector: Vec<f32> = (0..16).map(|_| rng.gen_range(-1.0..1.0)).collect();
+            upsert(&mut store, &format!("v{:02}", i), vector);
+        }
+        // Written newest last: z-copy after a-copy after v03
+        let base = store.get("v03").unwrap().vector;
+        let mut nudged = base.clone();
+        nudged[0] += 0.01;
+        upsert(&mut store, "z-copy", base.clone());
+        upsert(&mut store, "a-copy", nudged);
+        let other = store.get("v17").unwrap().vector;
+        upsert(&mut store, "v17-copy", other);
         store
-            .upsert("doc1".into(), vec![1.0, 2.0, 3.0], meta.clone())
-            .unwrap();
-        store
-            .index_text("doc1", "important data".to_string())
-            .unwrap();
+    }
 
-        // Create snapshot
-        store.create_snapshot("backup1").unwrap();
+    #[test]
+    fn test_near_duplicates_groups_copies() {
+        let dir = TempDir::new().unwrap();
+        let store = store_with_duplicates(&dir);
+
+        let groups = store.near_duplicates(0.98).unwrap();
+        assert_eq!(groups.len(), 2, "{:?}", groups);
+        assert_eq!(groups[0].ids, ["v03", "z-copy", "a-copy"]);
+        assert_eq!(groups[1].ids, ["v17", "v17-copy"]);
+        assert!(groups[0].min_similarity >= 0.98 && groups[0].min_similarity < 1.0);
+        assert_eq!(groups[1].min_similarity, 1.0);
+
+        assert_eq!(groups[0].kept(DuplicateKeep::First), "v03");
+        assert_eq!(groups[0].kept(DuplicateKeep::Newest), "a-copy");
+
+        let exact = store.near_duplicates(1.0).unwrap();
+        assert_eq!(exact.len(), 2);
+        assert_eq!(exact[0].ids, ["v03", "z-copy"]);
+
+        assert!(store.near_duplicates(0.0).is_err());
+        assert!(store.near_duplicates(1.5).is_err());
+    }
+
+    #[test]
+    fn test_removing_redundant_records_leaves_no_duplicates() {
+        let dir = TempDir::new().unwrap();
+        let mut store = store_with_duplicates(&dir);
+
+        let groups = store.near_duplicates(0.98).unwrap();
+        for group in &groups {
+            for id in group.redundant(DuplicateKeep::Newest) {
+                store.remove(id).unwrap();
+            }
+        }
+
+        assert_eq!(store.count(), 40);
+        assert!(store.get("a-copy").is_some());
+        assert!(store.get("v17-copy").is_some());
+        assert!(store.get("v03").is_none());
+        assert!(store.near_duplicates(0.98).unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod checkpoint_tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn metadata() -> Metadata {
+        Metadata {
+            fields: HashMap::new(),
+        }
+    }
+
+    fn open(dir: &TempDir) -> VecStore {
+        VecStore::builder(dir.path())
+            .wal(WalSync::Off)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dirty_tracks_writes_and_settings() {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(&dir);
+        assert!(!store.is_dirty());
 
-        // Modify store
         store
-            .upsert("doc2".into(), vec![4.0, 5.0, 6.0], meta)
+            .upsert("a".into(), vec![1.0, 0.0], metadata())
             .unwrap();
-        store.index_text("doc2", "new data".to_string()).unwrap();
+        assert!(store.is_dirty());
+        store.save().unwrap();
+        assert!(!store.is_dirty());
 
-        // Restore snapshot
-        store.restore_snapshot("backup1").unwrap();
+        store.set_exact_search_threshold(10);
+        assert!(store.is_dirty());
+        store.save().unwrap();
 
-        // Text index should be restored to snapshot state
-        assert!(store.has_text("doc1"));
-        assert_eq!(store.get_text("doc1"), Some("important data"));
-        assert!(
-            !store.has_text("doc2"),
-            "doc2 should not exist after restore"
-        );
+        let store = open(&dir);
+        assert!(!store.is_dirty());
+        assert_eq!(store.config().exact_search_threshold, 10);
     }
 
-    /// Test that text index is properly cleared when store is new
     #[test]
-    fn test_text_index_empty_on_new_store() {
-        let temp_dir = TempDir::new().unwrap();
-        let path = temp_dir.path().join("test.db");
+    fn test_snapshot_index_installed_on_write() {
+        let dir = TempDir::new().unwrap();
+        let mut store = open(&dir);
+        store
+            .upsert("a".into(), vec![1.0, 0.0], metadata())
+            .unwrap();
+        store.save().unwrap();
+        let graph = disk::DiskLayout::new(dir.path()).hnsw_graph_path();
+        let saved = std::fs::read(&graph).unwrap();
 
-        let store = VecStore::open(&path).unwrap();
-        assert!(!store.has_text("anything"));
+        store
+            .upsert("b".into(), vec![0.0, 1.0], metadata())
+            .unwrap();
+        let snapshot = store.snapshot_for_save().unwrap();
+        // The saved dump still matches the saved records until the write
+        assert_eq!(std::fs::read(&graph).unwrap(), saved);
+
+        snapshot.write().unwrap();
+        store.finish_save(&snapshot).unwrap();
+        assert_ne!(std::fs::read(&graph).unwrap(), saved);
+        drop(snapshot);
+
+        let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
     }
 
-    /// Test that multiple documents can be indexed and persisted
     #[test]
-    fn test_multiple_texts_persist() {
-        let temp_dir = TempDir::new().unwrap();
-        let path = temp_dir.path().join("test.db");
-
+    fn test_snapshot_written_after_later_writes() {
+        let dir = TempDir::new().unwrap();
         {
-            let mut store = VecStore::open(&path).unwrap();
-
-            for i in 1..=5 {
-                let id = format!("doc{}", i);
-                let text = format!("Document number {}", i);
-                let mut meta = Metadata {
-                    fields: std::collections::HashMap::new(),
-                };
-                meta.fields.insert("num".into(), serde_json::json!(i));
+            let mut store = open(&dir);
+            store
+                .upsert("a".into(), vec![1.0, 0.0], metadata())
+                .unwrap();
+            let snapshot = store.snapshot_for_save().unwrap();
+            assert_eq!(snapshot.len(), 1);
 
-                store.upsert(id.clone(), vec![i as f32; 3], meta).unwrap();
-                store.index_text(&id, text).unwrap();
-            }
-            store.save().unwrap();
+            // Written after the snapshot, so only the log holds it
+            store
+                .upsert("b".into(), vec![0.0, 1.0], metadata())
+                .unwrap();
+            snapshot.write().unwrap();
+            store.finish_save(&snapshot).unwrap();
+            assert!(store.is_dirty());
         }
 
-        {
-            let store = VecStore::open(&path).unwrap();
-            for i in 1..=5 {
-                let id = format!("doc{}", i);
-                let expected_text = format!("Document number {}", i);
-                assert!(store.has_text(&id), "doc{} should have text", i);
-                assert_eq!(store.get_text(&id), Some(expected_text.as_str()));
-            }
-        }
+        let store = open(&dir);
+        assert!(store.contains("a"));
+        assert!(store.contains("b"));
+
+        let snapshot = store.snapshot_for_save().unwrap();
+        snapshot.write().unwrap();
+        store.finish_save(&snapshot).unwrap();
+        assert!(!store.is_dirty());
+        drop(store);
+
+        let store = open(&dir);
+        assert_eq!(store.count(), 2);
+        assert!(!store.is_dirty());
+        assert!(VecStore::validate(dir.path()).unwrap().is_ok());
     }
 }
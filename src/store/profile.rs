@@ -0,0 +1,240 @@
+//! Query timing breakdown and the slow-query log
+//!
+//! Every query is timed in stages: HNSW traversal, scoring done outside the
+//! index, filtering candidates, and copying record data into results. The
+//! breakdown is returned as a [`QueryProfile`] when the query sets
+//! `profile: true`, and kept in the store's slow-query log whenever the query
+//! takes longer than [`Config::slow_query_threshold_ms`](super::Config::slow_query_threshold_ms).
+//!
+//! A query given a [`QueryCancel`] stops with [`QueryInterrupted`] once its
+//! deadline passes or it is cancelled. Queries that time out are logged as
+//! slow with `timed_out` set, whatever the threshold.
+
+use super::types::Neighbor;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Slow queries kept by a store; older entries are dropped first
+pub const SLOW_QUERY_LOG_CAPACITY: usize = 100;
+
+/// Where the time of one query went
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryProfile {
+    /// Wall time of the whole query
+    pub total: Duration,
+    /// HNSW graph traversal, over every over-fetch round
+    pub index_search: Duration,
+    /// Distances computed outside the index: exact scans (which also apply
+    /// the filter), full-precision re-ranking of quantized candidates, and
+    /// the reranker
+    pub scoring: Duration,
+    /// Checking index candidates for deletion, expiry, namespace and filter
+    pub filtering: Duration,
+    /// Copying metadata and vectors into the results
+    pub materialization: Duration,
+    /// Records scanned, or candidates returned by the index summed over rounds
+    pub candidates: usize,
+    /// Results returned
+    pub results: usize,
+    /// Largest `ef_search` the index was searched with (None for exact scans)
+    pub ef_search: Option<usize>,
+    /// Index searches run; more than one means over-fetching had to retry
+    pub fetch_rounds: usize,
+    /// Answered by an exact scan instead of the HNSW index
+    pub exact: bool,
+}
+
+impl QueryProfile {
+    /// Add the time since `start` to `stage`, returning the stage's result
+    pub(super) fn time<T>(stage: &mut Duration, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        *stage += start.elapsed();
+        result
+    }
+}
+
+/// Results of a query with its profile
+#[derive(Debug, Clone)]
+pub struct ProfiledResults {
+    pub neighbors: Vec<Neighbor>,
+    /// Present when the query set `profile: true`
+    pub profile: Option<QueryProfile>,
+}
+
+/// A query that exceeded the slow-query threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlowQuery {
+    /// When the query finished, as Unix seconds
+    pub at: i64,
+    pub k: usize,
+    /// The filter in [`parse_filter`](super::parse_filter) syntax
+    pub filter: Option<String>,
+    pub namespace: Option<String>,
+    pub profile: QueryProfile,
+    /// Stopped at its deadline rather than finishing
+    #[serde(default)]
+    pub timed_out: bool,
+}
+
+/// Stops a query at a deadline, or when cancelled from another thread
+///
+/// Clones share the cancellation. The query checks it before searching and
+/// between over-fetch rounds, so it stops within one index search of the
+/// deadline.
+///
+/// # Example
+/// ```no_run
+/// # use vecstore::{VecStore, Query, QueryCancel, QueryInterrupted};
+/// # use std::time::Duration;
+/// # let store = VecStore::open("data")?;
+/// let cancel = QueryCancel::with_timeout(Duration::from_millis(50));
+/// match store.query(Query::new(vec![0.1, 0.2]).with_cancel(cancel)) {
+///     Err(e) if e.is::<QueryInterrupted>() => println!("{}", e),
+///     other => println!("{:?}", other?),
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryCancel {
+    started: Instant,
+    deadline: Option<Instant>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl Default for QueryCancel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QueryCancel {
+    /// No deadline; stops only when [`cancel`](Self::cancel)led
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            deadline: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Stop `timeout` from now
+    pub fn with_timeout(timeout: Duration) -> Self {
+        let mut cancel = Self::new();
+        cancel.deadline = cancel.started.checked_add(timeout);
+        cancel
+    }
+
+    /// Stop the query at its next check
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Time since the cancel was created
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Fail once cancelled or past the deadline; a query cancelled after
+    /// its deadline counts as timed out
+    pub fn check(&self) -> Result<(), QueryInterrupted> {
+        let timed_out = self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        if timed_out || self.is_cancelled() {
+            return Err(QueryInterrupted {
+                elapsed: self.elapsed(),
+                cancelled: !timed_out,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A query stopped by its [`QueryCancel`]
+///
+/// Returned inside an `anyhow::Error`; check for it with
+/// `err.downcast_ref::<QueryInterrupted>()`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub struct QueryInterrupted {
+    /// Time from the cancel's creation to the check that stopped the query
+    pub elapsed: Duration,
+    /// Cancelled rather than past its deadline
+    pub cancelled: bool,
+}
+
+impl std::fmt::Display for QueryInterrupted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let how = if self.cancelled {
+            "cancelled"
+        } else {
+            "timed out"
+        };
+        write!(f, "Query {} after {} ms", how, self.elapsed.as_millis())
+    }
+}
+
+/// The most recent slow queries and a count of all of them
+#[derive(Debug, Default)]
+pub(super) struct SlowQueryLog {
+    entries: VecDeque<SlowQuery>,
+    total: u64,
+}
+
+impl SlowQueryLog {
+    pub fn record(&mut self, query: SlowQuery) {
+        if self.entries.len() == SLOW_QUERY_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(query);
+        self.total += 1;
+    }
+
+    /// Oldest first
+    pub fn entries(&self) -> Vec<SlowQuery> {
+        self.entries.iter().cloned().collect()
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_keeps_most_recent() {
+        let mut log = SlowQueryLog::default();
+        for k in 0..SLOW_QUERY_LOG_CAPACITY + 5 {
+            log.record(SlowQuery {
+                at: 0,
+                k,
+                filter: None,
+                namespace: None,
+                profile: QueryProfile::default(),
+                timed_out: false,
+            });
+        }
+        let entries = log.entries();
+        assert_eq!(entries.len(), SLOW_QUERY_LOG_CAPACITY);
+        assert_eq!(entries[0].k, 5);
+        assert_eq!(log.total(), SLOW_QUERY_LOG_CAPACITY as u64 + 5);
+
+        log.clear();
+        assert!(log.entries().is_empty());
+        assert_eq!(log.total(), SLOW_QUERY_LOG_CAPACITY as u64 + 5);
+    }
+}
@@ -0,0 +1,92 @@
+//! Int8 scalar-quantized vector storage
+//!
+//! Holds the codebook learned by [`VecStore::calibrate_quantization`](crate::VecStore::calibrate_quantization)
+//! and, when the store drops its f32 originals, the per-record codes they are
+//! reconstructed from.
+
+use super::types::{Id, Record};
+use crate::quantization::ScalarQuantizer8;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// Candidates fetched per requested result when re-ranking quantized search
+/// results against the f32 originals
+pub(super) const RERANK_FACTOR: usize = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct QuantizedVectors {
+    quantizer: ScalarQuantizer8,
+    /// Codes for records whose f32 vector was dropped
+    codes: HashMap<Id, Vec<u8>>,
+}
+
+impl QuantizedVectors {
+    /// Learn per-dimension min/max from `vectors`
+    pub fn calibrate(vectors: &[Vec<f32>]) -> Result<Self> {
+        if vectors.is_empty() {
+            return Err(anyhow!("Cannot calibrate quantization on an empty store"));
+        }
+        Ok(Self {
+            quantizer: ScalarQuantizer8::train(vectors)?,
+            codes: HashMap::new(),
+        })
+    }
+
+    pub fn quantizer(&self) -> &ScalarQuantizer8 {
+        &self.quantizer
+    }
+
+    /// Quantize `record`'s vector into a code and drop the f32 copy
+    pub fn absorb(&mut self, record: &mut Record) -> Result<()> {
+        if record.vector.is_empty() {
+            return Ok(());
+        }
+        let code = self.quantizer.encode(&record.vector)?;
+        self.codes.insert(record.id.clone(), code);
+        record.vector = Vec::new();
+        Ok(())
+    }
+
+    /// Dequantized vector for `id`, if its original was dropped
+    pub fn decode(&self, id: &str) -> Option<Vec<f32>> {
+        self.codes
+            .get(id)
+            .and_then(|code| self.quantizer.decode(code).ok())
+    }
+
+    /// Length of `id`'s code, which is its vector's dimension
+    pub fn code_len(&self, id: &str) -> Option<usize> {
+        self.codes.get(id).map(Vec::len)
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        self.codes.remove(id);
+    }
+
+    /// Drop codes of records no longer in `records`
+    pub fn retain_records(&mut self, records: &HashMap<Id, Record>) {
+        self.codes.retain(|id, _| records.contains_key(id));
+    }
+
+    /// Bytes held by the stored codes
+    pub fn code_bytes(&self) -> usize {
+        self.codes.values().map(Vec::len).sum()
+    }
+}
+
+/// `record`'s vector at full precision, dequantized from its code when the
+/// f32 original was dropped
+pub(super) fn full_vector<'a>(
+    record: &'a Record,
+    quantized: Option<&QuantizedVectors>,
+) -> Cow<'a, [f32]> {
+    if !record.vector.is_empty() {
+        return Cow::Borrowed(&record.vector);
+    }
+    match quantized.and_then(|q| q.decode(&record.id)) {
+        Some(vector) => Cow::Owned(vector),
+        None => Cow::Borrowed(&record.vector),
+    }
+}
@@ -0,0 +1,112 @@
+//! Recall measurement for the HNSW index
+//!
+//! Recall@k is the share of the true `k` nearest neighbours (found by an
+//! exact scan) that the index returns. It depends on `ef_search`: a longer
+//! candidate list finds more of the true neighbours and costs more time per
+//! query. [`RecallReport`] records that trade-off over a range of `ef_search`
+//! values so one can be picked for a target recall.
+
+use super::types::Id;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Queries sampled by [`VecStore::suggest_ef_search`](super::VecStore::suggest_ef_search)
+pub const DEFAULT_RECALL_SAMPLES: usize = 100;
+
+/// Results per query measured by [`VecStore::suggest_ef_search`](super::VecStore::suggest_ef_search)
+pub const DEFAULT_RECALL_K: usize = 10;
+
+/// `ef_search` values measured by a recall report, raised to at least `k`
+const EF_LADDER: [usize; 7] = [16, 32, 64, 128, 256, 512, 1024];
+
+/// Recall and latency of the index at one `ef_search`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EfRecall {
+    pub ef_search: usize,
+    /// Mean recall@k over the sampled queries, from 0.0 to 1.0
+    pub recall: f64,
+    /// Mean time per index search
+    pub mean_latency: Duration,
+    /// 95th percentile time per index search
+    pub p95_latency: Duration,
+}
+
+/// Recall@k of the index for a range of `ef_search` values
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecallReport {
+    pub k: usize,
+    /// Queries measured at each `ef_search`
+    pub queries: usize,
+    /// Ordered by increasing `ef_search`
+    pub points: Vec<EfRecall>,
+}
+
+impl RecallReport {
+    /// The smallest measured `ef_search` reaching `target_recall`
+    pub fn first_reaching(&self, target_recall: f64) -> Option<&EfRecall> {
+        self.points.iter().find(|p| p.recall >= target_recall)
+    }
+}
+
+/// Sample queries with their exact nearest neighbours
+pub(super) struct GroundTruth {
+    pub k: usize,
+    pub queries: Vec<Vec<f32>>,
+    pub nearest: Vec<HashSet<Id>>,
+}
+
+/// The `ef_search` values a report for `k` results measures
+pub(super) fn ef_ladder(k: usize) -> Vec<usize> {
+    let mut ladder: Vec<usize> = std::iter::once(k)
+        .chain(EF_LADDER.iter().map(|&ef| ef.max(k)))
+        .collect();
+    ladder.dedup();
+    ladder
+}
+
+/// Share of `truth` present in `found`; 1.0 when there is nothing to find
+pub(super) fn recall(found: &[Id], truth: &HashSet<Id>) -> f64 {
+    if truth.is_empty() {
+        return 1.0;
+    }
+    let hits = found.iter().filter(|id| truth.contains(*id)).count();
+    hits as f64 / truth.len() as f64
+}
+
+/// Mean and 95th percentile of `latencies`
+pub(super) fn latency_summary(latencies: &mut [Duration]) -> (Duration, Duration) {
+    if latencies.is_empty() {
+        return (Duration::ZERO, Duration::ZERO);
+    }
+    latencies.sort_unstable();
+    let total: Duration = latencies.iter().sum();
+    let mean = total / latencies.len() as u32;
+    let p95 = latencies[(latencies.len() * 95).div_ceil(100) - 1];
+    (mean, p95)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ef_ladder_starts_at_k() {
+        assert_eq!(ef_ladder(10), vec![10, 16, 32, 64, 128, 256, 512, 1024]);
+        assert_eq!(ef_ladder(100), vec![100, 128, 256, 512, 1024]);
+        assert_eq!(ef_ladder(2000), vec![2000]);
+    }
+
+    #[test]
+    fn test_recall_and_latency_summary() {
+        let truth: HashSet<Id> = ["a", "b", "c", "d"].map(String::from).into();
+        let found = ["a", "c", "x"].map(String::from);
+        assert_eq!(recall(&found, &truth), 0.5);
+        assert_eq!(recall(&found, &HashSet::new()), 1.0);
+
+        let mut latencies: Vec<Duration> = (1..=20).rev().map(Duration::from_millis).collect();
+        let (mean, p95) = latency_summary(&mut latencies);
+        assert_eq!(mean, Duration::from_micros(10_500));
+        assert_eq!(p95, Duration::from_millis(19));
+    }
+}
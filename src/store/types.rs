@@ -1,6 +1,9 @@
+use super::field_index::{FieldIndexStats, FieldIndexType};
+use super::profile::QueryCancel;
+use crate::wal::WalSync;
 use anyhow;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub type Id = String;
 
@@ -103,33 +106,414 @@ impl Distance {
     }
 }
 
+/// HNSW index construction parameters
+///
+/// Stored with the store's config, so a reopened store rebuilds its index
+/// with the same parameters. Use [`VecStore::set_hnsw_config`](crate::VecStore::set_hnsw_config)
+/// to change them on an existing store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HnswConfig {
+    /// Number of connections per layer (default: 16)
+    #[serde(rename = "hnsw_m")]
+    pub m: usize,
+
+    /// Size of dynamic candidate list during construction (default: 200)
+    #[serde(rename = "hnsw_ef_construction")]
+    pub ef_construction: usize,
+
+    /// Initial index capacity (default: 100_000)
+    ///
+    /// The store doubles this and rebuilds the index when it fills up, so
+    /// setting it to the expected size only avoids those rebuilds.
+    #[serde(rename = "hnsw_max_elements")]
+    pub max_elements: usize,
+
+    /// Maximum number of layers; must be 16, the only value the HNSW
+    /// implementation can save
+    #[serde(rename = "hnsw_max_layer")]
+    pub max_layer: usize,
+}
+
+impl HnswConfig {
+    /// The `max_layer` the HNSW implementation supports; it can only dump
+    /// indexes built with exactly this many layers
+    pub const MAX_LAYER_LIMIT: usize = 16;
+
+    /// Check that every parameter is in range
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.m == 0 {
+            anyhow::bail!("Invalid HNSW config: m must be greater than 0");
+        }
+        if self.ef_construction == 0 {
+            anyhow::bail!("Invalid HNSW config: ef_construction must be greater than 0");
+        }
+        if self.max_elements == 0 {
+            anyhow::bail!("Invalid HNSW config: max_elements must be greater than 0");
+        }
+        if self.max_layer != Self::MAX_LAYER_LIMIT {
+            anyhow::bail!(
+                "Invalid HNSW config: max_layer must be {}, got {}",
+                Self::MAX_LAYER_LIMIT,
+                self.max_layer
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for HnswConfig {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            max_elements: 100_000,
+            max_layer: 16,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)] // Major Issue #7 fix: add serialization
 pub struct Config {
     /// Distance metric to use for similarity search
     pub distance: Distance,
 
-    /// HNSW parameter: number of connections per layer (default: 16)
-    pub hnsw_m: usize,
+    /// HNSW construction parameters
+    #[serde(flatten)]
+    pub hnsw: HnswConfig,
+
+    /// Stores with fewer records than this answer every query by exact
+    /// brute-force scan instead of the HNSW index (0 disables the fallback)
+    #[serde(default = "default_exact_search_threshold")]
+    pub exact_search_threshold: usize,
+
+    /// `ef_search` for index searches whose query doesn't set one (None =
+    /// the backend's default)
+    #[serde(default)]
+    pub default_ef_search: Option<usize>,
+
+    /// Checks and normalization applied to vectors on insert and query
+    #[serde(default)]
+    pub vector_policy: VectorPolicy,
+
+    /// In-memory vector representation, fixed when the store is created
+    #[serde(default)]
+    pub quantization: Quantization,
+
+    /// Log upserts and removals to `wal.log` between saves, fsyncing as given
+    /// (`None` disables the write-ahead log)
+    #[serde(default)]
+    pub wal: Option<WalSync>,
+
+    /// Vector dimension every record must have (None = set by the first insert)
+    #[serde(default)]
+    pub dimension: Option<usize>,
+
+    /// Metadata fields with a secondary index, and the kind of each
+    #[serde(default)]
+    pub field_indexes: BTreeMap<String, FieldIndexType>,
+
+    /// Queries taking at least this many milliseconds are kept in the
+    /// slow-query log (None disables the log)
+    #[serde(default)]
+    pub slow_query_threshold_ms: Option<u64>,
+
+    /// Model the store's vectors were embedded with, if they came from text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding_model: Option<EmbeddingModel>,
+}
+
+/// Text embedding model recorded with a store
+///
+/// Set by [`VecStore::set_embedding_model`](crate::VecStore::set_embedding_model)
+/// when text is first embedded into the store, so later text queries can
+/// check they embed with the same model.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmbeddingModel {
+    /// Identifies the model and its backend, e.g. `openai:text-embedding-3-small`
+    pub name: String,
+    pub dimension: usize,
+}
+
+/// Text was embedded with a different model than the store records
+///
+/// Returned (inside `anyhow::Error`) by
+/// [`check_embedding_model`](super::VecStore::check_embedding_model); recover
+/// it with `err.downcast_ref::<EmbeddingModelMismatch>()`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "Store was embedded with '{}' ({} dimensions), not '{}' ({} dimensions)",
+    stored.name, stored.dimension, requested.name, requested.dimension
+)]
+pub struct EmbeddingModelMismatch {
+    pub stored: EmbeddingModel,
+    pub requested: EmbeddingModel,
+}
+
+/// How a store holds vectors in memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Quantization {
+    /// Full-precision f32 vectors in both the records and the index
+    #[default]
+    None,
+
+    /// 8-bit scalar quantization with per-dimension min/max
+    ///
+    /// Takes effect once [`VecStore::calibrate_quantization`](crate::VecStore::calibrate_quantization)
+    /// has learned the codebook; the index then stores one byte per dimension.
+    /// With `keep_originals`, the f32 vectors stay in the records and re-rank
+    /// the top candidates; without, they are dropped and reads return
+    /// dequantized vectors.
+    Int8 { keep_originals: bool },
+}
+
+/// How a store treats incoming vectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VectorPolicy {
+    /// L2-normalize vectors on insert and query vectors before searching
+    ///
+    /// Makes Cosine and DotProduct rankings agree for callers that do not
+    /// normalize their embeddings themselves. Zero vectors are left as-is.
+    pub normalize: bool,
+
+    /// Reject a whole `batch_execute` call when any upsert in it has the
+    /// wrong dimension, instead of applying the valid operations
+    ///
+    /// `batch_upsert` always validates the full batch before inserting.
+    pub strict_dimension: bool,
+
+    /// Reject vectors containing NaN or infinite components
+    pub reject_nan: bool,
+}
 
-    /// HNSW parameter: size of dynamic candidate list during construction (default: 200)
-    pub hnsw_ef_construction: usize,
+fn default_exact_search_threshold() -> usize {
+    1_000
+}
+
+impl Config {
+    /// HNSW connections per layer
+    #[deprecated(note = "use `config.hnsw.m`")]
+    pub fn hnsw_m(&self) -> usize {
+        self.hnsw.m
+    }
+
+    /// HNSW candidate list size during construction
+    #[deprecated(note = "use `config.hnsw.ef_construction`")]
+    pub fn hnsw_ef_construction(&self) -> usize {
+        self.hnsw.ef_construction
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             distance: Distance::Cosine,
-            hnsw_m: 16,
-            hnsw_ef_construction: 200,
+            hnsw: HnswConfig::default(),
+            exact_search_threshold: default_exact_search_threshold(),
+            default_ef_search: None,
+            vector_policy: VectorPolicy::default(),
+            quantization: Quantization::None,
+            wal: None,
+            dimension: None,
+            field_indexes: BTreeMap::new(),
+            slow_query_threshold_ms: None,
+            embedding_model: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Summary of a store's contents and index configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoreStats {
+    /// Number of records, including soft-deleted ones
+    pub total_records: usize,
+
+    /// Number of active (non-deleted) records
+    pub active_records: usize,
+
+    /// Number of soft-deleted records, kept as tombstones until compaction
+    pub deleted_records: usize,
+
+    /// Vector dimension (0 until the first insert)
+    pub dimension: usize,
+
+    /// Distance metric
+    pub distance: Distance,
+
+    /// HNSW construction parameters in use
+    pub hnsw: HnswConfig,
+
+    /// Points the index holds before it is rebuilt larger (`hnsw.max_elements`)
+    pub index_capacity: usize,
+
+    /// Fraction of `index_capacity` in use, counting stale entries left by updates
+    pub index_utilization: f64,
+
+    /// Index entries not backing any record (removed or replaced vectors)
+    pub ghost_entries: usize,
+
+    /// `ghost_entries` as a fraction of all index entries
+    pub fragmentation_ratio: f64,
+
+    /// Vector checks and normalization in effect
+    pub vector_policy: VectorPolicy,
+
+    /// Configured vector representation
+    pub quantization: Quantization,
+
+    /// Whether the int8 codebook has been learned and the index is quantized
+    pub quantization_calibrated: bool,
+
+    /// Bytes held in memory for vectors (record vectors, int8 codes and index copies)
+    pub vector_memory_bytes: usize,
+
+    /// Bytes the same vectors would take at full precision
+    pub full_precision_vector_bytes: usize,
+
+    /// Approximate bytes of the HNSW graph's neighbour lists (index entries
+    /// × links per entry)
+    pub index_link_bytes: usize,
+
+    /// Bytes of record metadata, measured as serialized JSON
+    pub metadata_bytes: usize,
+
+    /// Bytes of the store's files on disk, snapshots excluded
+    pub disk_bytes: u64,
+
+    /// When the store was last saved, as a Unix timestamp (`None` if never)
+    pub last_saved_at: Option<i64>,
+
+    /// Size of the write-ahead log, when one is enabled
+    pub wal_bytes: Option<u64>,
+
+    /// Active records per namespace (the default namespace is not listed)
+    pub namespace_counts: BTreeMap<String, usize>,
+
+    /// Secondary indexes on metadata fields, by field name
+    pub field_indexes: Vec<FieldIndexStats>,
+}
+
+/// Result of [`VecStore::scan_consistency`](super::VecStore::scan_consistency):
+/// how the open store's index and records disagree, and what a sample of its
+/// vectors holds
+///
+/// Id lists are sorted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConsistencyScan {
+    /// Ids in the index's id map that have no record
+    pub orphaned_ids: Vec<Id>,
+
+    /// Records, soft-deleted ones included, that have no index entry
+    pub unindexed_ids: Vec<Id>,
+
+    /// Ids more than one index slot maps to
+    pub duplicate_ids: Vec<Id>,
+
+    /// Number of records per vector length, over every record
+    pub dimensions: BTreeMap<usize, usize>,
+
+    /// Records whose vectors were read
+    pub sampled: usize,
+
+    /// Sampled records with a NaN or infinite component
+    pub non_finite_ids: Vec<Id>,
+
+    /// Sampled records whose vector is all zeros
+    pub zero_vector_ids: Vec<Id>,
+}
+
+/// A record's metadata
+///
+/// Serialized as a plain JSON object of its fields, the shape records take in
+/// exports, backups, store files and the HTTP API alike. Metadata written
+/// before that was wrapped as `{"fields": {...}}`, and is unwrapped when read;
+/// metadata whose only field is an object named `fields` is written wrapped
+/// so that it reads back unchanged.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Metadata {
     pub fields: HashMap<String, serde_json::Value>,
 }
 
+impl Metadata {
+    /// Whether the fields read as the legacy `{"fields": {...}}` wrapper
+    fn looks_wrapped(fields: &HashMap<String, serde_json::Value>) -> bool {
+        fields.len() == 1 && fields.get("fields").is_some_and(|v| v.is_object())
+    }
+}
+
+impl Serialize for Metadata {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        if Self::looks_wrapped(&self.fields) {
+            let mut map = serializer.serialize_map(Some(1))?;
+            map.serialize_entry("fields", &self.fields)?;
+            map.end()
+        } else {
+            self.fields.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Metadata {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut fields: HashMap<String, serde_json::Value> =
+            Option::deserialize(deserializer)?.unwrap_or_default();
+        if Self::looks_wrapped(&fields) {
+            if let Some(serde_json::Value::Object(inner)) = fields.remove("fields") {
+                fields = inner.into_iter().collect();
+            }
+        }
+        Ok(Metadata { fields })
+    }
+}
+
+impl Metadata {
+    /// Keep only the fields at `paths`
+    ///
+    /// A path names a top-level field or, with dots, a field nested in objects
+    /// (`"author.name"`), which keeps its nesting in the result. A top-level
+    /// key that itself contains dots is matched first. Paths that don't
+    /// resolve are skipped.
+    pub fn select(&self, paths: &[String]) -> Metadata {
+        let mut fields = HashMap::new();
+        for path in paths {
+            if let Some(value) = self.fields.get(path) {
+                fields.insert(path.clone(), value.clone());
+                continue;
+            }
+            let mut keys = path.split('.');
+            let Some(head) = keys.next() else { continue };
+            let nested: Vec<&str> = keys.collect();
+            let mut found = self.fields.get(head);
+            for key in &nested {
+                found = found.and_then(|value| value.get(key));
+            }
+            if let Some(value) = found {
+                let slot = fields
+                    .entry(head.to_string())
+                    .or_insert_with(|| serde_json::json!({}));
+                insert_nested(slot, &nested, value.clone());
+            }
+        }
+        Metadata { fields }
+    }
+}
+
+/// Set `value` at `path` below `slot`, creating objects on the way
+fn insert_nested(slot: &mut serde_json::Value, path: &[&str], value: serde_json::Value) {
+    match path.split_first() {
+        None => *slot = value,
+        Some((key, rest)) => {
+            if let Some(object) = slot.as_object_mut() {
+                let child = object.entry(*key).or_insert_with(|| serde_json::json!({}));
+                insert_nested(child, rest, value);
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Record {
     pub id: Id,
@@ -150,6 +534,112 @@ pub struct Record {
     /// None means no expiration
     #[serde(default)]
     pub expires_at: Option<i64>,
+
+    /// Logical namespace (tenant) the record belongs to; None is the default
+    /// namespace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+
+    /// Store operation sequence number of the record's last change (0 for
+    /// records saved before sequence tracking); drives incremental backups
+    #[serde(default)]
+    pub seq: u64,
+
+    /// Number of writes to this record, starting at 1 when it is inserted;
+    /// checked by [`upsert_if_version`](super::VecStore::upsert_if_version)
+    #[serde(default = "initial_version")]
+    pub version: u64,
+
+    /// Source text the vector was embedded from, returned with query results
+    /// that set [`QueryOptions::include_text`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+
+    /// ID of the document this record is a chunk of (which need not itself
+    /// be a record in the store)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<Id>,
+
+    /// Position among the chunks of [`parent_id`](Self::parent_id); orders
+    /// the chunks [`fetch_parent_window`](super::VecStore::fetch_parent_window)
+    /// returns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+}
+
+/// Version of a newly inserted record, and of records saved before versioning
+pub(crate) fn initial_version() -> u64 {
+    1
+}
+
+impl Record {
+    /// Whether the record's TTL has run out at `now` (unix seconds)
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    /// Neither soft-deleted nor expired at `now`; only live records are
+    /// visible to queries and lookups
+    pub fn is_live(&self, now: i64) -> bool {
+        !self.deleted && !self.is_expired(now)
+    }
+
+    /// Place the record in `namespace`
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Keep the text the vector was embedded from
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Mark the record as chunk `chunk_index` of document `parent_id`
+    pub fn with_parent(mut self, parent_id: impl Into<Id>, chunk_index: usize) -> Self {
+        self.parent_id = Some(parent_id.into());
+        self.chunk_index = Some(chunk_index);
+        self
+    }
+
+    /// Whether the record is visible to a query scoped to `namespace`
+    ///
+    /// An unscoped query (`None`) sees every namespace.
+    pub fn visible_in(&self, namespace: Option<&str>) -> bool {
+        namespace.is_none_or(|ns| self.namespace.as_deref() == Some(ns))
+    }
+}
+
+/// What each query result carries besides its id and score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "server", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct QueryOptions {
+    /// Return the record's vector (dequantized if only its int8 code is kept)
+    pub include_vector: bool,
+
+    /// Return the record's metadata
+    pub include_metadata: bool,
+
+    /// Only return these metadata fields; dotted paths select nested fields
+    /// (None = all fields). Ignored when `include_metadata` is false.
+    pub metadata_fields: Option<Vec<String>>,
+
+    /// Return the record's [`text`](Record::text)
+    pub include_text: bool,
+}
+
+impl Default for QueryOptions {
+    /// Full metadata, no vector or text
+    fn default() -> Self {
+        Self {
+            include_vector: false,
+            include_metadata: true,
+            metadata_fields: None,
+            include_text: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -157,6 +647,47 @@ pub struct Query {
     pub vector: Vec<f32>,
     pub k: usize,
     pub filter: Option<FilterExpr>,
+
+    /// HNSW candidate list size for this query (None = backend default)
+    ///
+    /// Higher values trade latency for recall. Must be >= `k`.
+    pub ef_search: Option<usize>,
+
+    /// Scan every live vector instead of searching the HNSW index
+    ///
+    /// Exact results at linear cost; useful as a recall baseline. Stores smaller
+    /// than `Config::exact_search_threshold` search exactly regardless.
+    pub exact: bool,
+
+    /// Original query text, passed to the reranker
+    pub text: Option<String>,
+
+    /// Candidates fetched for the reranker before truncating to `k`
+    /// (None = 10 × `k`). Ignored when no reranker applies.
+    pub rerank_fetch_k: Option<usize>,
+
+    /// Only return records in this namespace (None = all namespaces)
+    ///
+    /// Enforced before `filter` is evaluated, so no filter expression,
+    /// malformed or otherwise, can admit another namespace's records.
+    pub namespace: Option<String>,
+
+    /// Which parts of each record the results carry
+    pub options: QueryOptions,
+
+    /// Return a [`QueryProfile`](super::QueryProfile) with the results from
+    /// [`VecStore::query_profiled`](super::VecStore::query_profiled)
+    pub profile: bool,
+
+    /// Stop the query at a deadline or when cancelled (None = run to the end)
+    pub cancel: Option<QueryCancel>,
+}
+
+impl Default for Query {
+    /// An empty query with k = 10, for use with struct update syntax
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
 }
 
 impl Query {
@@ -166,6 +697,14 @@ impl Query {
             vector,
             k: 10, // Default k
             filter: None,
+            ef_search: None,
+            exact: false,
+            text: None,
+            rerank_fetch_k: None,
+            namespace: None,
+            options: QueryOptions::default(),
+            profile: false,
+            cancel: None,
         }
     }
 
@@ -175,6 +714,64 @@ impl Query {
         self
     }
 
+    /// Set the HNSW `ef_search` parameter for this query
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use vecstore::{VecStore, Query};
+    /// # let store = VecStore::open("data")?;
+    /// // Wider candidate list: better recall, higher latency
+    /// let results = store.query(Query::new(vec![0.1, 0.2, 0.3]).with_limit(10).with_ef_search(200))?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn with_ef_search(mut self, ef_search: usize) -> Self {
+        self.ef_search = Some(ef_search);
+        self
+    }
+
+    /// Force an exact brute-force search, bypassing the HNSW index
+    pub fn with_exact(mut self, exact: bool) -> Self {
+        self.exact = exact;
+        self
+    }
+
+    /// Attach the query text for rerankers that score against it
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Set how many candidates the reranker sees before truncating to `k`
+    pub fn with_rerank_fetch_k(mut self, fetch_k: usize) -> Self {
+        self.rerank_fetch_k = Some(fetch_k);
+        self
+    }
+
+    /// Restrict results to records in `namespace`
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Choose what the results carry: vectors, metadata, or some metadata fields
+    pub fn with_options(mut self, options: QueryOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Ask for a timing breakdown with the results
+    pub fn with_profile(mut self) -> Self {
+        self.profile = true;
+        self
+    }
+
+    /// Stop the query with [`QueryInterrupted`](super::QueryInterrupted)
+    /// when `cancel` is cancelled or its deadline passes
+    pub fn with_cancel(mut self, cancel: QueryCancel) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
     /// Add a filter expression
     pub fn with_filter_expr(mut self, filter: FilterExpr) -> Self {
         self.filter = Some(filter);
@@ -222,6 +819,11 @@ pub enum FilterOp {
     In,         // Value is in array
     NotIn,      // Value not in array
     StartsWith, // String starts with prefix (Major Issue #13 fix)
+    Between,    // Inclusive range; value is [min, max]
+    Exists,     // Field is present and not null; value is ignored
+    IsNull,     // Field is missing or null; value is ignored
+    Matches,    // String matches a regex pattern
+    AnyIn,      // Array field shares at least one element with the value array
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -234,6 +836,14 @@ pub enum FilterExpr {
         op: FilterOp,
         value: serde_json::Value,
     },
+    /// Field holds a point, `{"lat": .., "lon": ..}` in degrees, within
+    /// `meters` of (`lat`, `lon`) by great-circle (haversine) distance
+    WithinRadius {
+        field: String,
+        lat: f64,
+        lon: f64,
+        meters: f64,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -241,6 +851,15 @@ pub struct Neighbor {
     pub id: Id,
     pub score: f32,
     pub metadata: Metadata,
+    /// The record's vector, when the query asked for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vector: Option<Vec<f32>>,
+    /// The record's [`version`](Record::version)
+    #[serde(default = "initial_version")]
+    pub version: u64,
+    /// The record's text, when the query asked for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
 }
 
 /// Detailed explanation of why a result was returned and how it was scored
@@ -319,14 +938,114 @@ pub enum BatchOperation {
         vector: Vec<f32>,
         metadata: Metadata,
     },
+    /// Upsert a vector if the record is still at `expected_version` (0: absent)
+    UpsertIfVersion {
+        id: Id,
+        expected_version: u64,
+        vector: Vec<f32>,
+        metadata: Metadata,
+    },
     /// Delete a vector
     Delete { id: Id },
     /// Soft delete a vector
     SoftDelete { id: Id },
     /// Restore a soft-deleted vector
     Restore { id: Id },
-    /// Update metadata only
+    /// Replace metadata only
     UpdateMetadata { id: Id, metadata: Metadata },
+    /// Merge fields into existing metadata
+    PatchMetadata {
+        id: Id,
+        patch: HashMap<String, serde_json::Value>,
+    },
+}
+
+/// What [`batch_upsert_with_policy`](super::VecStore::batch_upsert_with_policy)
+/// does with a record whose id is already in the store or earlier in the batch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpsertPolicy {
+    /// Replace the existing record (what `batch_upsert` does)
+    #[default]
+    Overwrite,
+    /// Keep the existing record and drop the incoming one
+    Skip,
+    /// Reject the whole batch
+    ErrorOnConflict,
+    /// Take the incoming vector and merge the incoming metadata fields into
+    /// the existing ones, incoming values winning
+    MergeMetadata,
+}
+
+/// Outcome of an upsert under an [`UpsertPolicy`], or of its dry run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpsertSummary {
+    /// Records with a new id
+    pub inserted: usize,
+    /// Existing records replaced or merged into
+    pub updated: usize,
+    /// Incoming records dropped under [`UpsertPolicy::Skip`]
+    pub skipped: usize,
+    /// Incoming records whose id already existed, however they were handled
+    pub conflicts: usize,
+}
+
+impl std::ops::AddAssign for UpsertSummary {
+    fn add_assign(&mut self, other: Self) {
+        self.inserted += other.inserted;
+        self.updated += other.updated;
+        self.skipped += other.skipped;
+        self.conflicts += other.conflicts;
+    }
+}
+
+/// Why one record of a batch upsert was rejected
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchItemError {
+    /// Position of the record in the batch, from 0
+    pub index: usize,
+    pub id: Id,
+    pub reason: String,
+}
+
+impl std::fmt::Display for BatchItemError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "record {} ('{}'): {}", self.index, self.id, self.reason)
+    }
+}
+
+/// A batch upsert had invalid records, so none of it was written
+///
+/// Returned (inside `anyhow::Error`) by
+/// [`batch_upsert`](super::VecStore::batch_upsert); recover it with
+/// `err.downcast_ref::<BatchValidationError>()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+pub struct BatchValidationError {
+    /// One entry per invalid record, in batch order
+    pub errors: Vec<BatchItemError>,
+}
+
+impl std::fmt::Display for BatchValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} record(s) failed validation; no records written",
+            self.errors.len()
+        )?;
+        if let Some(first) = self.errors.first() {
+            write!(f, " (first: {})", first)?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of [`batch_upsert_partial`](super::VecStore::batch_upsert_partial)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchUpsertResult {
+    /// Records written, new or replacing an existing one
+    pub inserted: usize,
+    /// Records rejected by validation, in batch order
+    pub failed: Vec<BatchItemError>,
 }
 
 /// Result of a batch operation
@@ -356,6 +1075,25 @@ pub struct BatchError {
 
     /// Error message
     pub error: String,
+
+    /// Set when the operation failed on a version check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_conflict: Option<VersionConflict>,
+}
+
+/// A conditional write found the record at a different version than expected
+///
+/// Returned (inside `anyhow::Error`) by
+/// [`upsert_if_version`](super::VecStore::upsert_if_version); recover it with
+/// `err.downcast_ref::<VersionConflict>()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[error("Version conflict for '{id}': expected version {expected}, found {actual}")]
+pub struct VersionConflict {
+    pub id: Id,
+    /// Version the writer expected, 0 meaning no record
+    pub expected: u64,
+    /// Version of the live record, 0 when there is none
+    pub actual: u64,
 }
 
 /// Query validation and cost estimation result
@@ -397,6 +1135,16 @@ pub struct CompactionConfig {
 
     /// Whether auto-compaction is enabled
     pub enabled: bool,
+
+    /// Rebuild the index once this fraction of its entries are ghosts
+    /// (left behind by `remove` and updates), checked by
+    /// `defragment_if_needed` and `maybe_compact`; 1.0 disables the rebuild
+    #[serde(default = "default_max_fragmentation_ratio")]
+    pub max_fragmentation_ratio: f32,
+}
+
+fn default_max_fragmentation_ratio() -> f32 {
+    0.5
 }
 
 impl Default for CompactionConfig {
@@ -405,6 +1153,7 @@ impl Default for CompactionConfig {
             min_deleted_records: 1000,
             min_deleted_ratio: 0.1, // 10% deleted
             enabled: false,
+            max_fragmentation_ratio: default_max_fragmentation_ratio(),
         }
     }
 }
@@ -423,6 +1172,33 @@ pub struct CompactionResult {
 
     /// Reason for compaction (or why it wasn't triggered)
     pub reason: String,
+
+    /// On-disk bytes freed by the compaction
+    #[serde(default)]
+    pub bytes_reclaimed: u64,
+}
+
+/// Outcome of [`VecStore::compact`](crate::VecStore::compact)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompactionReport {
+    /// Soft-deleted records permanently removed
+    pub removed_count: usize,
+
+    /// Ghost index entries dropped by the rebuild
+    pub ghost_entries_removed: usize,
+
+    /// Size of the store files before compaction
+    pub bytes_before: u64,
+
+    /// Size of the store files after compaction
+    pub bytes_after: u64,
+}
+
+impl CompactionReport {
+    /// On-disk bytes freed (0 if the files grew)
+    pub fn bytes_reclaimed(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
 }
 
 /// Multi-stage query for advanced RAG patterns (prefetch API)
@@ -0,0 +1,419 @@
+//! Integrity checks over a store's files
+//!
+//! Opening a store rebuilds the HNSW graph from the records and keeps the
+//! persisted id mappings as they are, so a store left inconsistent by a crash
+//! can open fine and still return ids it no longer has. These checks read the
+//! files directly instead, without changing them.
+
+use super::disk::{DiskLayout, Manifest};
+use super::quantized::{full_vector, QuantizedVectors};
+use super::types::{Id, Record};
+use crate::wal::{LogEntry, WriteAheadLog};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Problems listed per check; the rest are only counted
+pub const MAX_CHECK_DETAILS: usize = 10;
+
+/// Outcome of one integrity check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Nothing to check, e.g. no write-ahead log
+    Skipped,
+}
+
+/// One category of [`IntegrityReport`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    /// Items checked: records, mapped ids, log entries
+    pub checked: usize,
+    /// Items that failed the check
+    pub failed: usize,
+    /// The first [`MAX_CHECK_DETAILS`] problems found
+    pub details: Vec<String>,
+}
+
+impl IntegrityCheck {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Pass,
+            checked: 0,
+            failed: 0,
+            details: Vec::new(),
+        }
+    }
+
+    fn skipped(name: &str, reason: &str) -> Self {
+        Self {
+            status: CheckStatus::Skipped,
+            details: vec![reason.to_string()],
+            ..Self::new(name)
+        }
+    }
+
+    fn fail(&mut self, detail: String) {
+        self.status = CheckStatus::Fail;
+        self.failed += 1;
+        if self.details.len() < MAX_CHECK_DETAILS {
+            self.details.push(detail);
+        }
+    }
+}
+
+/// Result of [`VecStore::validate`](super::VecStore::validate)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Record count in the manifest
+    pub manifest_records: usize,
+    /// Configured vector dimension
+    pub dimension: usize,
+    pub checks: Vec<IntegrityCheck>,
+}
+
+impl IntegrityReport {
+    /// Whether no check failed
+    pub fn is_ok(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+}
+
+/// Result of [`VecStore::repair`](super::VecStore::repair)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RepairReport {
+    /// Records dropped because they didn't deserialize
+    pub undecodable_records: usize,
+    /// Records dropped because their vector length isn't the store's dimension
+    pub mismatched_records: Vec<Id>,
+    /// Mapped ids with no record, dropped from the index
+    pub orphaned_ids: usize,
+    /// Records in the rebuilt index
+    pub indexed: usize,
+}
+
+/// Records decoded from `vectors.bin`, and the entries that failed
+pub(super) struct DecodedRecords {
+    pub records: Vec<Record>,
+    /// Position in the file and error of each entry that didn't decode
+    pub errors: Vec<(usize, String)>,
+}
+
+/// Decode the records one by one, so a bad entry doesn't hide the rest
+pub(super) fn decode_records(layout: &DiskLayout) -> Result<DecodedRecords> {
+    let data = layout
+        .read(&layout.vectors_path())
+        .context("Failed to read vectors")?;
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_slice(&data).context("Failed to parse vectors.bin")?;
+
+    let mut decoded = DecodedRecords {
+        records: Vec::with_capacity(entries.len()),
+        errors: Vec::new(),
+    };
+    for (position, entry) in entries.into_iter().enumerate() {
+        match serde_json::from_value::<Record>(entry) {
+            Ok(record) => decoded.records.push(record),
+            Err(e) => decoded.errors.push((position, e.to_string())),
+        }
+    }
+    Ok(decoded)
+}
+
+/// Run every check against the files under `layout`
+pub(super) fn check(layout: &DiskLayout) -> Result<IntegrityReport> {
+    if !layout.exists() {
+        return Err(anyhow::anyhow!("Store does not exist at {:?}", layout.root));
+    }
+    let manifest = layout.load_manifest()?;
+    let mut checks = Vec::new();
+
+    let mut records_check = IntegrityCheck::new("records and metadata");
+    let records = match decode_records(layout) {
+        Ok(decoded) => {
+            records_check.checked = decoded.records.len() + decoded.errors.len();
+            for (position, error) in decoded.errors {
+                records_check.fail(format!("record #{}: {}", position, error));
+            }
+            if records_check.checked != manifest.record_count {
+                records_check.status = CheckStatus::Fail;
+                records_check.details.push(format!(
+                    "manifest counts {} records, vectors.bin holds {}",
+                    manifest.record_count, records_check.checked
+                ));
+            }
+            decoded.records
+        }
+        Err(e) => {
+            records_check.fail(format!("{:#}", e));
+            Vec::new()
+        }
+    };
+    checks.push(records_check);
+
+    let quantized = layout.load_quantized().ok().flatten();
+    checks.push(check_dimensions(&manifest, &records, quantized.as_ref()));
+
+    let mapping = layout
+        .read(&layout.meta_path())
+        .context("Failed to read id mappings")
+        .and_then(|data| {
+            bincode::deserialize::<(HashMap<Id, usize>, HashMap<usize, Id>, usize)>(&data)
+                .context("Failed to deserialize id mappings")
+        });
+    let mapped = match mapping {
+        Ok((id_to_idx, idx_to_id, next_idx)) => {
+            checks.push(check_mappings(&records, &id_to_idx, &idx_to_id, next_idx));
+            Some(id_to_idx.len())
+        }
+        Err(e) => {
+            let mut check = IntegrityCheck::new("id mappings");
+            check.fail(format!("{:#}", e));
+            checks.push(check);
+            None
+        }
+    };
+
+    checks.push(check_dump(layout, &manifest, mapped));
+    checks.push(check_wal(layout));
+
+    Ok(IntegrityReport {
+        manifest_records: manifest.record_count,
+        dimension: manifest.dimension,
+        checks,
+    })
+}
+
+fn check_dimensions(
+    manifest: &Manifest,
+    records: &[Record],
+    quantized: Option<&QuantizedVectors>,
+) -> IntegrityCheck {
+    let mut check = IntegrityCheck::new("vector dimensions");
+    let expected = manifest
+        .config
+        .as_ref()
+        .and_then(|c| c.dimension)
+        .unwrap_or(manifest.dimension);
+    if expected != manifest.dimension && manifest.dimension != 0 {
+        check.fail(format!(
+            "configured dimension {} but the manifest records {}",
+            expected, manifest.dimension
+        ));
+    }
+    for record in records {
+        check.checked += 1;
+        let len = full_vector(record, quantized).len();
+        if len != expected {
+            check.fail(format!(
+                "{}: {} dimensions, expected {}",
+                record.id, len, expected
+            ));
+        }
+    }
+    check
+}
+
+fn check_mappings(
+    records: &[Record],
+    id_to_idx: &HashMap<Id, usize>,
+    idx_to_id: &HashMap<usize, Id>,
+    next_idx: usize,
+) -> IntegrityCheck {
+    let mut check = IntegrityCheck::new("id mappings");
+    let ids: HashSet<&str> = records.iter().map(|r| r.id.as_str()).collect();
+
+    let mut orphans: Vec<&Id> = id_to_idx
+        .keys()
+        .filter(|id| !ids.contains(id.as_str()))
+        .collect();
+    orphans.sort();
+    let mut unindexed: Vec<&str> = ids
+        .iter()
+        .copied()
+        .filter(|id| !id_to_idx.contains_key(*id))
+        .collect();
+    unindexed.sort();
+
+    check.checked = id_to_idx.len().max(ids.len());
+    for id in orphans {
+        check.fail(format!("{}: in the index but has no record", id));
+    }
+    for id in unindexed {
+        check.fail(format!("{}: has a record but isn't in the index", id));
+    }
+    let mut entries: Vec<(&Id, &usize)> = id_to_idx.iter().collect();
+    entries.sort();
+    for (id, idx) in entries {
+        if idx_to_id.get(idx) != Some(id) {
+            check.fail(format!(
+                "{}: index slot {} maps back to another id",
+                id, idx
+            ));
+        } else if *idx >= next_idx {
+            check.fail(format!(
+                "{}: index slot {} is past the next free slot {}",
+                id, idx, next_idx
+            ));
+        }
+    }
+    if idx_to_id.len() != id_to_idx.len() {
+        check.fail(format!(
+            "{} slots map to ids but {} ids map to slots",
+            idx_to_id.len(),
+            id_to_idx.len()
+        ));
+    }
+    check
+}
+
+/// The dump holds every point inserted since the last rebuild, replaced
+/// ones included, so it can't hold fewer points than there are mapped ids
+fn check_dump(layout: &DiskLayout, manifest: &Manifest, mapped: Option<usize>) -> IntegrityCheck {
+    const NAME: &str = "index dump";
+    let path = layout.hnsw_graph_path();
+    if !path.exists() {
+        return IntegrityCheck::skipped(NAME, "no HNSW dump");
+    }
+
+    let mut check = IntegrityCheck::new(NAME);
+    let description = layout
+        .read(&path)
+        .context("Failed to read the HNSW dump")
+        .and_then(|data| {
+            hnsw_rs::hnswio::load_description(&mut data.as_slice())
+                .context("Failed to parse the HNSW dump")
+        });
+    let description = match description {
+        Ok(description) => description,
+        Err(e) => {
+            check.fail(format!("{:#}", e));
+            return check;
+        }
+    };
+
+    check.checked = description.nb_point;
+    if description.dimension != manifest.dimension {
+        check.fail(format!(
+            "dump has {} dimensions, the store {}",
+            description.dimension, manifest.dimension
+        ));
+    }
+    if let Some(mapped) = mapped {
+        if description.nb_point < mapped {
+            check.fail(format!(
+                "dump holds {} points for {} mapped ids",
+                description.nb_point, mapped
+            ));
+        }
+    }
+    check
+}
+
+fn check_wal(layout: &DiskLayout) -> IntegrityCheck {
+    const NAME: &str = "write-ahead log";
+    let path = layout.wal_path();
+    if !path.exists() {
+        return IntegrityCheck::skipped(NAME, "no write-ahead log");
+    }
+
+    let mut check = IntegrityCheck::new(NAME);
+    let inspection = match WriteAheadLog::inspect(&path) {
+        Ok(inspection) => inspection,
+        Err(e) => {
+            check.fail(format!("{:#}", e));
+            return check;
+        }
+    };
+
+    check.checked = inspection.pending.len();
+    for (position, entry) in inspection.pending.iter().enumerate() {
+        let metadata = match entry {
+            LogEntry::Upsert { id, metadata, .. }
+            | LogEntry::UpsertRecord { id, metadata, .. }
+            | LogEntry::Metadata { id, metadata } => Some((id, metadata)),
+            _ => None,
+        };
+        if let Some((id, metadata)) = metadata {
+            if let Err(e) = serde_json::from_str::<serde_json::Value>(metadata) {
+                check.fail(format!(
+                    "entry #{} ({}): corrupt metadata: {}",
+                    position, id, e
+                ));
+            }
+        }
+    }
+    if inspection.torn_bytes > 0 {
+        check.fail(format!(
+            "{} bytes after the last intact entry, dropped on replay",
+            inspection.torn_bytes
+        ));
+    }
+    check
+}
+
+/// Rewrite the store files without the records it can't load: those that
+/// don't deserialize and those whose vector length isn't the store's
+/// dimension
+///
+/// Mappings of the dropped records go with them; unreadable mappings are
+/// replaced by empty ones, which opening the store fills in from the records.
+pub(super) fn drop_bad_records(layout: &DiskLayout) -> Result<RepairReport> {
+    let manifest = layout.load_manifest()?;
+    let decoded = decode_records(layout)?;
+    let quantized = layout.load_quantized()?;
+    let dimension = manifest.dimension;
+    let (records, mismatched): (Vec<Record>, Vec<Record>) =
+        decoded.records.into_iter().partition(|record| {
+            dimension == 0 || full_vector(record, quantized.as_ref()).len() == dimension
+        });
+
+    let report = RepairReport {
+        undecodable_records: decoded.errors.len(),
+        mismatched_records: mismatched.into_iter().map(|r| r.id).collect(),
+        ..Default::default()
+    };
+    let mapping = layout.read(&layout.meta_path()).ok().and_then(|data| {
+        bincode::deserialize::<(HashMap<Id, usize>, HashMap<usize, Id>, usize)>(&data).ok()
+    });
+    if report.undecodable_records == 0 && report.mismatched_records.is_empty() && mapping.is_some()
+    {
+        return Ok(report);
+    }
+
+    let dropped: HashSet<&Id> = report.mismatched_records.iter().collect();
+    let (mut id_to_idx, mut idx_to_id, next_idx) = mapping.unwrap_or_default();
+    id_to_idx.retain(|id, _| !dropped.contains(id));
+    idx_to_id.retain(|_, id| !dropped.contains(id));
+
+    let texts = layout
+        .read(&layout.text_index_path())
+        .ok()
+        .and_then(|data| serde_json::from_slice::<HashMap<Id, String>>(&data).ok())
+        .map(|mut texts| {
+            texts.retain(|id, _| !dropped.contains(id));
+            texts
+        });
+    let records: HashMap<Id, Record> = records.into_iter().map(|r| (r.id.clone(), r)).collect();
+    layout.save_all(
+        &records,
+        &id_to_idx,
+        &idx_to_id,
+        next_idx,
+        dimension,
+        &manifest.config.unwrap_or_default(),
+        texts.as_ref(),
+    )?;
+
+    let mut changes = layout.load_changes()?.unwrap_or_default();
+    for id in &report.mismatched_records {
+        changes.tombstone(id);
+    }
+    layout.save_changes(&changes)?;
+    Ok(report)
+}
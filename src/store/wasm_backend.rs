@@ -78,6 +78,16 @@ impl WasmVectorBackend {
         Ok(self.hnsw.len())
     }
 
+    /// Same as [`optimize`](Self::optimize), reporting every vector as done
+    pub fn optimize_with_progress(
+        &mut self,
+        vectors: &[(Id, Vec<f32>)],
+        progress: &mut dyn FnMut(usize),
+    ) -> Result<usize> {
+        progress(vectors.len());
+        self.optimize(vectors)
+    }
+
     /// Delete a vector by ID
     pub fn remove(&mut self, id: &str) -> Result<()> {
         self.hnsw.remove(id)
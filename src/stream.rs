@@ -242,6 +242,9 @@ mod tests {
             metadata: Metadata {
                 fields: std::collections::HashMap::new(),
             },
+            vector: None,
+            version: 1,
+            text: None,
         }
     }
 
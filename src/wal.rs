@@ -7,10 +7,21 @@
 //! ## Features
 //!
 //! - Append-only log for fast writes
+//! - CRC32-checked records; a torn or corrupt tail is dropped on replay
 //! - Crash recovery via log replay
 //! - Checkpointing for log compaction
+//! - Configurable fsync behaviour ([`WalSync`])
 //! - Concurrent readers during write operations
 //!
+//! `VecStore` uses it when built with [`VecStoreBuilder::wal`](crate::VecStoreBuilder::wal).
+//!
+//! ## Format
+//!
+//! The file starts with an 8-byte header, `VWAL` and the format version as
+//! a `u32 LE`; a log of another version is rejected rather than misread.
+//! Each record that follows is `[len: u32 LE][crc32: u32 LE][bincode
+//! payload]`, where the CRC covers the payload.
+//!
 //! ## Usage
 //!
 //! ```no_run
@@ -38,11 +49,38 @@
 //! # }
 //! ```
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs::{File, OpenOptions};
 use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Magic bytes at the start of every log file
+const WAL_MAGIC: [u8; 4] = *b"VWAL";
+
+/// Version of the record layout, bumped whenever [`LogEntry`] changes
+pub const WAL_VERSION: u32 = 2;
+
+/// Length of the magic and version header
+pub(crate) const HEADER_LEN: u64 = 8;
+
+/// When appended records are fsynced to disk
+///
+/// Every append is flushed to the OS, so a process crash loses nothing;
+/// this controls what survives a power loss or kernel crash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum WalSync {
+    /// fsync after every append
+    #[default]
+    PerWrite,
+
+    /// fsync on an append once this many milliseconds have passed since the last fsync
+    IntervalMs(u64),
+
+    /// Never fsync; leave write-back to the OS
+    Off,
+}
 
 /// A single entry in the write-ahead log
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +105,40 @@ pub enum LogEntry {
 
     /// Checkpoint marker
     Checkpoint { sequence: u64 },
+
+    /// Insert or replace a record; `metadata` is the JSON-encoded
+    /// [`Metadata`](crate::Metadata), which bincode cannot encode directly
+    Upsert {
+        id: String,
+        vector: Vec<f32>,
+        metadata: String,
+        namespace: Option<String>,
+    },
+
+    /// [`Upsert`](Self::Upsert) of a record with text, a parent or an
+    /// expiry; logged only when one is set, to keep plain upserts small
+    UpsertRecord {
+        id: String,
+        vector: Vec<f32>,
+        metadata: String,
+        namespace: Option<String>,
+        text: Option<String>,
+        parent_id: Option<String>,
+        chunk_index: Option<usize>,
+        expires_at: Option<i64>,
+    },
+
+    /// Replace a record's metadata, JSON-encoded as for [`Upsert`](Self::Upsert)
+    Metadata { id: String, metadata: String },
+
+    /// Mark a record as deleted without removing it
+    SoftDelete { id: String },
+
+    /// Undo a [`SoftDelete`](Self::SoftDelete)
+    Restore { id: String },
+
+    /// Set a record's expiry (unix seconds), or clear it
+    SetTtl { id: String, expires_at: Option<i64> },
 }
 
 /// Write-Ahead Log implementation
@@ -76,12 +148,21 @@ pub struct WriteAheadLog {
     next_sequence: u64,
     last_checkpoint: u64,
     entry_count: u64,
+    sync: WalSync,
+    last_sync: Instant,
 }
 
 impl WriteAheadLog {
-    /// Open or create a WAL file
+    /// Open or create a WAL file that never fsyncs
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let file = OpenOptions::new()
+        Self::open_with_sync(path, WalSync::Off)
+    }
+
+    /// Open or create a WAL file with the given fsync behaviour
+    ///
+    /// Fails on a log written in another format version.
+    pub fn open_with_sync<P: AsRef<Path>>(path: P, sync: WalSync) -> Result<Self> {
+        let mut file = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
@@ -89,6 +170,16 @@ impl WriteAheadLog {
             .open(&path)
             .context("Failed to open WAL file")?;
 
+        // A file shorter than the header is a new log, or one whose creation
+        // was cut short before any record was written
+        if file.metadata()?.len() < HEADER_LEN {
+            file.set_len(0)?;
+            file.write_all(&header())?;
+            file.sync_all()?;
+        }
+        file.seek(SeekFrom::Start(0))?;
+        check_header(&mut file, path.as_ref())?;
+
         let writer_file = file.try_clone()?;
         let writer = BufWriter::new(writer_file);
 
@@ -102,6 +193,8 @@ impl WriteAheadLog {
             next_sequence,
             last_checkpoint: 0,
             entry_count,
+            sync,
+            last_sync: Instant::now(),
         })
     }
 
@@ -113,61 +206,84 @@ impl WriteAheadLog {
 
         // Serialize the entry with its sequence number
         let record = LogRecord { sequence, entry };
-        let serialized = bincode::serialize(&record).context("Failed to serialize log entry")?;
+        self.write_record(&record)?;
+
+        let due = match self.sync {
+            WalSync::PerWrite => true,
+            WalSync::IntervalMs(ms) => self.last_sync.elapsed() >= Duration::from_millis(ms),
+            WalSync::Off => false,
+        };
+        if due {
+            self.sync()?;
+        }
+
+        Ok(sequence)
+    }
+
+    /// fsync everything appended so far
+    pub fn sync(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        self.file.sync_data()?;
+        self.last_sync = Instant::now();
+        Ok(())
+    }
+
+    /// Frame, checksum and write `record`, flushing it to the OS
+    fn write_record(&mut self, record: &LogRecord) -> Result<()> {
+        let serialized = bincode::serialize(record).context("Failed to serialize log entry")?;
 
-        // Write length prefix (for easy recovery)
+        // Length and CRC prefix (for easy recovery and torn-write detection)
         let len = serialized.len() as u32;
         self.writer.write_all(&len.to_le_bytes())?;
-
-        // Write the serialized entry
+        self.writer
+            .write_all(&crc32fast::hash(&serialized).to_le_bytes())?;
         self.writer.write_all(&serialized)?;
 
-        // Flush to ensure durability
         self.writer.flush()?;
-
-        Ok(sequence)
+        Ok(())
     }
 
     /// Replay all log entries since the last checkpoint
+    ///
+    /// Stops at the first torn or corrupt record (a write cut short by a
+    /// crash) and truncates the file there, so later appends follow the last
+    /// intact record.
     pub fn replay(&mut self) -> Result<Vec<LogEntry>> {
         let mut reader = BufReader::new(self.file.try_clone()?);
-        reader.seek(SeekFrom::Start(0))?;
-
-        let mut entries = Vec::new();
-        let mut last_checkpoint_seq = 0;
-
-        loop {
-            // Read length prefix
-            let mut len_bytes = [0u8; 4];
-            match reader.read_exact(&mut len_bytes) {
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
-                Err(e) => return Err(e.into()),
-            }
-
-            let len = u32::from_le_bytes(len_bytes) as usize;
-
-            // Read the entry
-            let mut buffer = vec![0u8; len];
-            reader.read_exact(&mut buffer)?;
+        reader.seek(SeekFrom::Start(HEADER_LEN))?;
 
-            let record: LogRecord =
-                bincode::deserialize(&buffer).context("Failed to deserialize log entry")?;
+        let (entries, last_checkpoint_seq, valid_len) = read_since_checkpoint(&mut reader)?;
+        self.last_checkpoint = last_checkpoint_seq;
 
-            // Track checkpoints
-            if let LogEntry::Checkpoint { sequence } = record.entry {
-                last_checkpoint_seq = sequence;
-                entries.clear(); // Discard entries before checkpoint
-            } else {
-                entries.push(record.entry);
-            }
+        // Drop a torn tail and continue appending after the last intact record
+        self.writer.flush()?;
+        if self.file.metadata()?.len() > valid_len {
+            self.file.set_len(valid_len)?;
         }
-
-        self.last_checkpoint = last_checkpoint_seq;
+        self.file.seek(SeekFrom::Start(valid_len))?;
 
         Ok(entries)
     }
 
+    /// Read the log at `path` as [`replay`](Self::replay) would, without
+    /// creating it or truncating a torn tail
+    pub fn inspect<P: AsRef<Path>>(path: P) -> Result<WalInspection> {
+        let mut file = File::open(&path).context("Failed to open WAL file")?;
+        let len = file.metadata()?.len();
+        if len < HEADER_LEN {
+            return Ok(WalInspection {
+                pending: Vec::new(),
+                torn_bytes: 0,
+            });
+        }
+        check_header(&mut file, path.as_ref())?;
+        let (pending, _, valid_len) = read_since_checkpoint(&mut BufReader::new(file))?;
+        Ok(WalInspection {
+            pending,
+            torn_bytes: len - valid_len,
+        })
+    }
+
     /// Write a checkpoint marker and truncate log
     pub fn checkpoint(&mut self) -> Result<()> {
         if self.entry_count == 0 {
@@ -185,16 +301,12 @@ impl WriteAheadLog {
             },
         };
 
-        let serialized = bincode::serialize(&record).context("Failed to serialize checkpoint")?;
-
-        let len = serialized.len() as u32;
-        self.writer.write_all(&len.to_le_bytes())?;
-        self.writer.write_all(&serialized)?;
+        self.write_record(&record)?;
         self.entry_count += 1;
 
         // Flush everything
-        self.writer.flush()?;
         self.file.sync_all()?;
+        self.last_sync = Instant::now();
 
         self.last_checkpoint = checkpoint_seq;
 
@@ -207,9 +319,9 @@ impl WriteAheadLog {
         self.writer.flush()?;
         self.file.sync_all()?;
 
-        // Truncate to zero (start fresh)
-        self.file.set_len(0)?;
-        self.file.seek(SeekFrom::Start(0))?;
+        // Truncate to the header (start fresh)
+        self.file.set_len(HEADER_LEN)?;
+        self.file.seek(SeekFrom::Start(HEADER_LEN))?;
 
         self.next_sequence = 0;
         self.last_checkpoint = 0;
@@ -231,31 +343,109 @@ impl WriteAheadLog {
     /// Scan the log and return (entry_count, last_sequence)
     fn scan_log(file: &File) -> Result<(u64, u64)> {
         let mut reader = BufReader::new(file.try_clone()?);
-        reader.seek(SeekFrom::Start(0))?;
+        reader.seek(SeekFrom::Start(HEADER_LEN))?;
 
         let mut entry_count = 0u64;
         let mut last_seq = 0u64;
 
-        loop {
-            let mut len_bytes = [0u8; 4];
-            match reader.read_exact(&mut len_bytes) {
-                Ok(_) => {}
-                Err(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
-                Err(e) => return Err(e.into()),
-            }
+        while let Some((record, _)) = read_record(&mut reader)? {
+            last_seq = record.sequence;
+            entry_count += 1;
+        }
+
+        Ok((entry_count, last_seq))
+    }
+}
 
-            let len = u32::from_le_bytes(len_bytes) as usize;
-            let mut buffer = vec![0u8; len];
-            reader.read_exact(&mut buffer)?;
+/// The header of a log in the current format
+fn header() -> [u8; HEADER_LEN as usize] {
+    let mut header = [0u8; HEADER_LEN as usize];
+    header[..4].copy_from_slice(&WAL_MAGIC);
+    header[4..].copy_from_slice(&WAL_VERSION.to_le_bytes());
+    header
+}
 
-            if let Ok(record) = bincode::deserialize::<LogRecord>(&buffer) {
-                last_seq = record.sequence;
-                entry_count += 1;
-            }
+/// Read the header of the log at `path`, failing unless it is a log in the
+/// current format
+fn check_header(reader: &mut impl Read, path: &Path) -> Result<()> {
+    let mut found = [0u8; HEADER_LEN as usize];
+    reader
+        .read_exact(&mut found)
+        .with_context(|| format!("Failed to read WAL header of {:?}", path))?;
+    if found[..4] != WAL_MAGIC {
+        bail!(
+            "{:?} has no write-ahead log header; it was written by an older vecstore \
+             and must be replayed by that version",
+            path
+        );
+    }
+    let version = u32::from_le_bytes([found[4], found[5], found[6], found[7]]);
+    if version != WAL_VERSION {
+        bail!(
+            "{:?} is a version {} write-ahead log, this vecstore reads version {}",
+            path,
+            version,
+            WAL_VERSION
+        );
+    }
+    Ok(())
+}
+
+/// Read the entries after the last checkpoint from a reader positioned after
+/// the header, returning them with the checkpoint's sequence number and the
+/// length of the header and intact records
+fn read_since_checkpoint(reader: &mut impl Read) -> Result<(Vec<LogEntry>, u64, u64)> {
+    let mut entries = Vec::new();
+    let mut last_checkpoint_seq = 0;
+    let mut valid_len = HEADER_LEN;
+
+    while let Some((record, frame_len)) = read_record(reader)? {
+        valid_len += frame_len;
+
+        // Track checkpoints
+        if let LogEntry::Checkpoint { sequence } = record.entry {
+            last_checkpoint_seq = sequence;
+            entries.clear(); // Discard entries before checkpoint
+        } else {
+            entries.push(record.entry);
         }
+    }
 
-        Ok((entry_count, last_seq))
+    Ok((entries, last_checkpoint_seq, valid_len))
+}
+
+/// Read the next intact record and its on-disk size
+///
+/// Returns `None` at end of file or at a torn or corrupt record.
+fn read_record(reader: &mut impl Read) -> Result<Option<(LogRecord, u64)>> {
+    let mut header = [0u8; 8];
+    match reader.read_exact(&mut header) {
+        Ok(_) => {}
+        Err(ref e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+
+    let len = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let crc = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+
+    let mut buffer = Vec::new();
+    reader.take(len as u64).read_to_end(&mut buffer)?;
+    if buffer.len() < len || crc32fast::hash(&buffer) != crc {
+        return Ok(None);
     }
+
+    Ok(bincode::deserialize(&buffer)
+        .ok()
+        .map(|record| (record, (header.len() + len) as u64)))
+}
+
+/// What [`WriteAheadLog::inspect`] found in a log file
+#[derive(Debug)]
+pub struct WalInspection {
+    /// Entries after the last checkpoint, which a replay applies
+    pub pending: Vec<LogEntry>,
+    /// Bytes after the last intact record, which a replay drops
+    pub torn_bytes: u64,
 }
 
 /// Internal log record with sequence number
@@ -442,4 +632,86 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_rejects_log_without_header() {
+        let temp_file = NamedTempFile::new().unwrap();
+        // A version 1 log: records from the first byte
+        let record = bincode::serialize(&LogRecord {
+            sequence: 0,
+            entry: LogEntry::Delete {
+                id: "doc1".to_string(),
+            },
+        })
+        .unwrap();
+        let mut old = Vec::new();
+        old.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        old.extend_from_slice(&crc32fast::hash(&record).to_le_bytes());
+        old.extend_from_slice(&record);
+        std::fs::write(temp_file.path(), &old).unwrap();
+
+        assert!(WriteAheadLog::open(temp_file.path()).is_err());
+        assert!(WriteAheadLog::inspect(temp_file.path()).is_err());
+        assert_eq!(std::fs::read(temp_file.path()).unwrap(), old);
+    }
+
+    #[test]
+    fn test_rejects_other_version() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut header = header();
+        header[4..].copy_from_slice(&(WAL_VERSION + 1).to_le_bytes());
+        std::fs::write(temp_file.path(), header).unwrap();
+
+        let err = WriteAheadLog::open(temp_file.path()).err().unwrap();
+        assert!(err.to_string().contains("version"));
+    }
+
+    #[test]
+    fn test_truncate_keeps_header() {
+        let temp_file = NamedTempFile::new().unwrap();
+        {
+            let mut wal = WriteAheadLog::open(temp_file.path()).unwrap();
+            wal.append(LogEntry::Restore {
+                id: "doc1".to_string(),
+            })
+            .unwrap();
+            wal.truncate().unwrap();
+        }
+        assert_eq!(
+            std::fs::metadata(temp_file.path()).unwrap().len(),
+            HEADER_LEN
+        );
+
+        let mut wal = WriteAheadLog::open(temp_file.path()).unwrap();
+        assert!(wal.replay().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_inspect_leaves_torn_tail() {
+        let temp_file = NamedTempFile::new().unwrap();
+        {
+            let mut wal = WriteAheadLog::open(temp_file.path()).unwrap();
+            wal.append(LogEntry::Delete {
+                id: "doc1".to_string(),
+            })
+            .unwrap();
+            wal.checkpoint().unwrap();
+            wal.append(LogEntry::Delete {
+                id: "doc2".to_string(),
+            })
+            .unwrap();
+        }
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(temp_file.path())
+            .unwrap();
+        file.write_all(&[7, 0, 0]).unwrap();
+        let len = file.metadata().unwrap().len();
+
+        let inspection = WriteAheadLog::inspect(temp_file.path()).unwrap();
+        assert_eq!(inspection.pending.len(), 1);
+        assert!(matches!(&inspection.pending[0], LogEntry::Delete { id } if id == "doc2"));
+        assert_eq!(inspection.torn_bytes, 3);
+        assert_eq!(std::fs::metadata(temp_file.path()).unwrap().len(), len);
+    }
 }
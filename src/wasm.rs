@@ -121,6 +121,12 @@ impl WasmVecStore {
             deleted: false,
             deleted_at: None,
             expires_at: None,
+            namespace: None,
+            seq: 0,
+            version: self.records.get(&id).map_or(1, |r| r.version + 1),
+            text: None,
+            parent_id: None,
+            chunk_index: None,
         };
 
         self.backend
@@ -186,6 +192,7 @@ impl WasmVecStore {
             vector,
             k,
             filter: filter_expr.clone(),
+            ..Default::default()
         };
 
         // Get results from backend
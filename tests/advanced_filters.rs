@@ -72,6 +72,7 @@ fn test_complex_and_or_filter() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -116,6 +117,7 @@ fn test_nested_not_filters() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -162,6 +164,7 @@ fn test_multiple_or_conditions() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -192,6 +195,7 @@ fn test_range_filter() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -222,6 +226,7 @@ fn test_boolean_field_filter() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -252,6 +257,7 @@ fn test_contains_in_string() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -282,6 +288,7 @@ fn test_contains_in_array() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -341,6 +348,7 @@ fn test_deeply_nested_filters() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -369,6 +377,7 @@ fn test_filter_with_zero_results() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -397,6 +406,7 @@ fn test_filter_neq_multiple() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -432,6 +442,7 @@ fn test_numeric_string_coercion() {
         vector: vec![1.0, 0.0, 0.0],
         k: 1,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -450,6 +461,7 @@ fn test_empty_and_filter() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -469,6 +481,7 @@ fn test_empty_or_filter() {
         vector: vec![10.0, 0.0, 0.0],
         k: 20,
         filter: Some(filter),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -25,6 +25,7 @@ fn test_basic_operations() {
         vector: vec![1.0, 0.0, 0.0],
         k: 1,
         filter: None,
+        ..Default::default()
     };
     let results = store.query(query).unwrap();
     assert_eq!(results.len(), 1);
@@ -82,6 +83,7 @@ fn test_query_with_k() {
         vector: vec![5.0, 0.0, 0.0],
         k: 3,
         filter: None,
+        ..Default::default()
     };
     let results = store.query(query).unwrap();
     assert!(results.len() <= 3);
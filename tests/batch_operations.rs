@@ -94,6 +94,7 @@ fn test_batch_upsert_large() {
         vector: vec![500.0, 1000.0, 1500.0],
         k: 5,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -128,6 +129,7 @@ fn test_batch_upsert_with_duplicates() {
         vector: vec![1.0, 1.0, 0.0],
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
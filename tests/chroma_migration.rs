@@ -0,0 +1,132 @@
+// ChromaDB migration from the mini-Chroma directory in tests/fixtures/chroma
+//
+// Run with: cargo test --features migrate --test chroma_migration
+
+#![cfg(feature = "migrate")]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use vecstore::migration::ChromaDirectory;
+use vecstore::{Distance, VecDatabase, VecStore};
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/chroma")
+}
+
+#[test]
+fn test_reads_collections_with_distance_and_dimension() {
+    let chroma = ChromaDirectory::open(fixture()).unwrap();
+    let collections = chroma.collections().unwrap();
+
+    let summary: Vec<_> = collections
+        .iter()
+        .map(|c| (c.name.as_str(), c.dimension, c.distance))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("articles", Some(3), Distance::Cosine),
+            ("empty", None, Distance::Euclidean),
+            ("notes", Some(2), Distance::DotProduct),
+        ]
+    );
+
+    let error = chroma.collection("missing").unwrap_err();
+    assert!(error.to_string().contains("articles, empty, notes"));
+}
+
+#[test]
+fn test_migrates_every_collection() {
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("db");
+    let chroma = ChromaDirectory::open(fixture()).unwrap();
+    {
+        let mut db = VecDatabase::open(&dest).unwrap();
+        for collection in chroma.collections().unwrap() {
+            let report = chroma
+                .migrate_into(&collection, &mut db, &mut |_, _| {})
+                .unwrap();
+            assert_eq!(report.verified(), Some(true), "{}", collection.name);
+        }
+
+        let mut names = db.list_collections().unwrap();
+        names.sort();
+        assert_eq!(names, vec!["articles", "empty", "notes"]);
+        let articles = db.get_collection("articles").unwrap().unwrap();
+        assert_eq!(articles.distance_metric(), Distance::Cosine);
+        assert_eq!(articles.dimension(), Some(3));
+        assert_eq!(articles.count().unwrap(), 3);
+    }
+
+    let articles = VecStore::open(dest.join("articles")).unwrap();
+    let a1 = articles.get("a1").unwrap();
+    assert_eq!(a1.text.as_deref(), Some("Rust is fast"));
+    assert_eq!(a1.metadata.fields["lang"], serde_json::json!("en"));
+    assert_eq!(a1.metadata.fields["year"], serde_json::json!(2021));
+    assert_eq!(a1.metadata.fields["score"], serde_json::json!(0.5));
+    assert_eq!(a1.metadata.fields["draft"], serde_json::json!(false));
+    assert!(!a1.metadata.fields.contains_key("chroma:document"));
+
+    // Updates replace the vector; deleted records are gone
+    assert_eq!(articles.get("a2").unwrap().vector, vec![0.0, 0.6, 0.8]);
+    assert!(articles.get("a3").is_none());
+    assert_eq!(articles.get("a4").unwrap().text, None);
+
+    let notes = VecStore::open(dest.join("notes")).unwrap();
+    assert_eq!(notes.distance_metric(), Distance::DotProduct);
+    assert_eq!(notes.get("n1").unwrap().text.as_deref(), Some("hello"));
+}
+
+#[test]
+fn test_purged_vectors_fail_with_the_record_id() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("chroma");
+    std::fs::create_dir(&source).unwrap();
+    std::fs::copy(
+        fixture().join("chroma.sqlite3"),
+        source.join("chroma.sqlite3"),
+    )
+    .unwrap();
+    rusqlite::Connection::open(source.join("chroma.sqlite3"))
+        .unwrap()
+        .execute("DELETE FROM embeddings_queue WHERE id = 'a1'", [])
+        .unwrap();
+
+    let chroma = ChromaDirectory::open(&source).unwrap();
+    let articles = chroma.collection("articles").unwrap();
+    let mut db = VecDatabase::open(dir.path().join("db")).unwrap();
+    let error = chroma
+        .migrate_into(&articles, &mut db, &mut |_, _| {})
+        .unwrap_err();
+    assert!(error.to_string().contains("No vector for 'a1'"));
+}
+
+#[test]
+fn test_cli_migrates_one_collection() {
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("db");
+    let output = Command::new(env!("CARGO_BIN_EXE_vecstore"))
+        .args(["migrate", "--source", "chromadb", "--source-path"])
+        .arg(fixture())
+        .arg("--dest")
+        .arg(&dest)
+        .args(["--collection", "notes"])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let db = VecDatabase::open(&dest).unwrap();
+    assert_eq!(db.list_collections().unwrap(), vec!["notes"]);
+    assert_eq!(
+        db.get_collection("notes")
+            .unwrap()
+            .unwrap()
+            .count()
+            .unwrap(),
+        1
+    );
+}
@@ -3,6 +3,7 @@
 // Run with: `cargo build && cargo test --test cli_integration`
 
 use std::fs;
+use std::io::Write;
 use std::process::Command;
 use tempfile::TempDir;
 
@@ -190,3 +191,939 @@ fn test_cli_binary_exists() {
     let bin_path = vecstore_bin();
     assert!(bin_path.ends_with("vecstore"));
 }
+
+// Write `count` records with nested metadata and ingest them into `data_path`
+fn ingest_sample_records(temp_dir: &TempDir, data_path: &std::path::Path, count: usize) {
+    let mut lines = String::new();
+    for i in 0..count {
+        let record = serde_json::json!({
+            "id": format!("doc{:03}", i),
+            "vector": [i as f32, 1.0, -0.5],
+            "metadata": {"fields": {
+                "category": if i % 2 == 0 { "even" } else { "odd" },
+                "rank": i,
+                "source": {"name": "wiki", "page": i * 10},
+            }},
+            "created_at": 0,
+            "text": format!("chunk, \"number\" {}", i),
+        });
+        lines.push_str(&record.to_string());
+        lines.push('\n');
+    }
+    let input = temp_dir.path().join("input.jsonl");
+    fs::write(&input, lines).unwrap();
+
+    let output = Command::new(vecstore_bin())
+        .args(["ingest-batch", "--dir"])
+        .arg(data_path)
+        .arg("--jsonl")
+        .arg(&input)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+}
+
+fn get_json(data_path: &std::path::Path, id: &str) -> serde_json::Value {
+    let output = Command::new(vecstore_bin())
+        .args(["get", "--json-out", "--dir"])
+        .arg(data_path)
+        .args(["--id", id])
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn test_cli_export_jsonl_roundtrip() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    ingest_sample_records(&temp_dir, &source, 25);
+
+    let exported = temp_dir.path().join("export.jsonl");
+    let output = Command::new(vecstore_bin())
+        .args(["export", "--dir"])
+        .arg(&source)
+        .arg("--output")
+        .arg(&exported)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Exported 25 records"), "{}", stdout);
+    assert_eq!(fs::read_to_string(&exported).unwrap().lines().count(), 25);
+
+    // Import into a fresh store
+    let copy = temp_dir.path().join("copy");
+    let output = Command::new(vecstore_bin())
+        .args(["ingest-batch", "--dir"])
+        .arg(&copy)
+        .arg("--jsonl")
+        .arg(&exported)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+
+    let output = Command::new(vecstore_bin())
+        .args(["stats", "--json-out", "--dir"])
+        .arg(&copy)
+        .output()
+        .expect("Failed to execute vecstore");
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["active_records"], 25);
+
+    let original = &get_json(&source, "doc007")[0];
+    let copied = &get_json(&copy, "doc007")[0];
+    for field in ["vector", "metadata", "text"] {
+        assert_eq!(original[field], copied[field], "{} differs", field);
+    }
+}
+
+#[test]
+fn test_cli_export_filter_and_limit() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    ingest_sample_records(&temp_dir, &data_path, 20);
+
+    let exported = temp_dir.path().join("even.jsonl");
+    let output = Command::new(vecstore_bin())
+        .args(["export", "--dir"])
+        .arg(&data_path)
+        .arg("--output")
+        .arg(&exported)
+        .args(["--filter", "category = 'even'", "--limit", "4"])
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+
+    let ids: Vec<String> = fs::read_to_string(&exported)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            record["id"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(ids, ["doc000", "doc002", "doc004", "doc006"]);
+}
+
+#[test]
+fn test_cli_export_csv() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    ingest_sample_records(&temp_dir, &data_path, 12);
+
+    let exported = temp_dir.path().join("export.csv");
+    let output = Command::new(vecstore_bin())
+        .args(["export", "--format", "csv", "--dir"])
+        .arg(&data_path)
+        .arg("--output")
+        .arg(&exported)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Exported 12 records"), "{}", stdout);
+
+    let content = fs::read_to_string(&exported).unwrap();
+    let mut lines = content.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "id,vector,text,parent_id,chunk_index,metadata.category,metadata.rank,\
+         metadata.source.name,metadata.source.page"
+    );
+    let rows: Vec<&str> = lines.collect();
+    assert_eq!(rows.len(), 12);
+    assert_eq!(
+        rows[3],
+        "\"doc003\",\"3;1;-0.5\",\"chunk, \"\"number\"\" 3\",,,\"odd\",\"3\",\"wiki\",\"30\""
+    );
+}
+
+#[test]
+fn test_cli_import_csv_roundtrip() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    ingest_sample_records(&temp_dir, &source, 15);
+
+    let exported = temp_dir.path().join("export.csv");
+    let output = Command::new(vecstore_bin())
+        .args(["export", "--format", "csv", "--dir"])
+        .arg(&source)
+        .arg("--output")
+        .arg(&exported)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+
+    // The format is picked from the extension
+    let copy = temp_dir.path().join("copy");
+    let output = Command::new(vecstore_bin())
+        .args(["import", "--dir"])
+        .arg(&copy)
+        .arg("--input")
+        .arg(&exported)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Imported 15 rows"), "{}", stdout);
+
+    let original = &get_json(&source, "doc011")[0];
+    let copied = &get_json(&copy, "doc011")[0];
+    for field in ["vector", "metadata", "text"] {
+        assert_eq!(original[field], copied[field], "{} differs", field);
+    }
+}
+
+#[test]
+fn test_cli_import_dimension_mismatch() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    let input = temp_dir.path().join("records.jsonl");
+    fs::write(
+        &input,
+        "{\"id\":\"a\",\"vector\":[1.0,0.0],\"metadata\":{}}\n\
+         {\"id\":\"b\",\"vector\":[1.0,0.0,0.0],\"metadata\":{}}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(vecstore_bin())
+        .args(["import", "--partial", "--dir"])
+        .arg(&data_path)
+        .arg("--input")
+        .arg(&input)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("on line 2 ('b')"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_delete_by_filter() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    ingest_sample_records(&temp_dir, &data_path, 10);
+
+    let delete = || {
+        let mut command = Command::new(vecstore_bin());
+        command
+            .args(["delete", "--dir"])
+            .arg(&data_path)
+            .args(["--filter", "category = 'even'"]);
+        command
+    };
+
+    // Declining the prompt deletes nothing
+    let mut child = delete()
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute vecstore");
+    std::io::Write::write_all(child.stdin.as_mut().unwrap(), b"n\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("Delete 5 vectors matching filter?"),
+        "{}",
+        stderr
+    );
+
+    let output = delete().arg("--yes").output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Deleted 5 vectors"), "{}", stdout);
+
+    // Only the odd records are left
+    let exported = temp_dir.path().join("left.jsonl");
+    let output = Command::new(vecstore_bin())
+        .args(["export", "--dir"])
+        .arg(&data_path)
+        .arg("--output")
+        .arg(&exported)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    let ids: Vec<String> = fs::read_to_string(&exported)
+        .unwrap()
+        .lines()
+        .map(|line| {
+            let record: serde_json::Value = serde_json::from_str(line).unwrap();
+            record["id"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(ids, ["doc001", "doc003", "doc005", "doc007", "doc009"]);
+}
+
+#[test]
+fn test_cli_config_profiles() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let vecstore = |args: &[&str]| {
+        let mut command = Command::new(vecstore_bin());
+        command
+            .args(args)
+            .current_dir(temp_dir.path())
+            .env("XDG_CONFIG_HOME", temp_dir.path().join("xdg"))
+            .env_remove("VECSTORE_CONFIG")
+            .env_remove("VECSTORE_PROFILE")
+            .env_remove("VECSTORE_DIR")
+            .env_remove("VECSTORE_OUTPUT");
+        command
+    };
+
+    let output = vecstore(&["config", "init"]).output().unwrap();
+    assert!(output.status.success());
+    assert!(temp_dir.path().join("vecstore.toml").exists());
+    let output = vecstore(&["--profile", "prod", "config", "set", "dir", "./prod"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // The profile's dir is the default for init
+    let output = vecstore(&["--profile", "prod", "init"]).output().unwrap();
+    assert!(output.status.success());
+    assert!(temp_dir.path().join("prod").exists());
+    assert!(!temp_dir.path().join("data").exists());
+
+    // An env var beats the profile, and a flag beats both
+    let output = vecstore(&["--profile", "prod", "config", "show"])
+        .env("VECSTORE_DIR", "./from-env")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("./from-env  (VECSTORE_DIR)"), "{}", stdout);
+    let output = vecstore(&["--profile", "prod", "init", "--dir", "./from-flag"])
+        .env("VECSTORE_DIR", "./from-env")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(temp_dir.path().join("from-flag").exists());
+    assert!(!temp_dir.path().join("from-env").exists());
+
+    // Unknown profiles and bad values are errors naming the file and key
+    let output = vecstore(&["--profile", "nope", "stats"]).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("No profile 'nope'"), "{}", stderr);
+
+    fs::write(
+        temp_dir.path().join("vecstore.toml"),
+        "[profiles.default]\noutput = \"yaml\"\n",
+    )
+    .unwrap();
+    let output = vecstore(&["stats"]).output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("vecstore.toml: profiles.default.output"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_cli_completions_and_manpage() {
+    skip_if_no_binary!();
+
+    for shell in ["bash", "zsh", "fish", "powershell"] {
+        let output = Command::new(vecstore_bin())
+            .args(["completions", shell])
+            .output()
+            .expect("Failed to execute vecstore");
+        assert!(output.status.success(), "{}", shell);
+        let script = String::from_utf8_lossy(&output.stdout);
+        assert!(!script.is_empty(), "{}", shell);
+        for subcommand in [
+            "init", "ingest", "query", "export", "import", "migrate", "config",
+        ] {
+            assert!(script.contains(subcommand), "{}: {}", shell, subcommand);
+        }
+        // Enum-valued flags complete their values; clap_complete's
+        // PowerShell script only completes flags and subcommands
+        if shell != "powershell" {
+            assert!(script.contains("parquet"), "{}", shell);
+            assert!(script.contains("braycurtis"), "{}", shell);
+        }
+
+        let output = Command::new(vecstore_bin())
+            .args(["completions", shell, "--server"])
+            .output()
+            .expect("Failed to execute vecstore");
+        assert!(output.status.success(), "{}", shell);
+        let script = String::from_utf8_lossy(&output.stdout);
+        assert!(script.contains("vecstore-server"), "{}", shell);
+        assert!(script.contains("grpc-port"), "{}", shell);
+    }
+
+    let output = Command::new(vecstore_bin())
+        .arg("manpage")
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    let page = String::from_utf8_lossy(&output.stdout);
+    assert!(page.starts_with(".ie"), "{}", page);
+    assert!(page.contains(".TH vecstore 1"), "{}", page);
+
+    let output = Command::new(vecstore_bin())
+        .args(["manpage", "--server"])
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(".TH vecstore-server 1"));
+}
+
+#[test]
+fn test_cli_validate_and_repair() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    ingest_sample_records(&temp_dir, &data_path, 4);
+
+    let validate = |args: &[&str]| {
+        Command::new(vecstore_bin())
+            .args(["validate", "--dir"])
+            .arg(&data_path)
+            .args(args)
+            // Keep log lines out of the JSON report
+            .env("RUST_LOG", "warn")
+            .output()
+            .expect("Failed to execute vecstore")
+    };
+
+    let output = validate(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ id mappings"), "{}", stdout);
+    assert!(stdout.contains("Store is consistent"), "{}", stdout);
+
+    // A record of the wrong dimension
+    let vectors = data_path.join("vectors.bin");
+    let mut records: Vec<serde_json::Value> =
+        serde_json::from_slice(&fs::read(&vectors).unwrap()).unwrap();
+    let mut short = records[0].clone();
+    short["id"] = "short".into();
+    short["vector"] = serde_json::json!([1.0]);
+    records.push(short);
+    fs::write(&vectors, serde_json::to_vec(&records).unwrap()).unwrap();
+
+    let output = validate(&["--json-out"]);
+    assert!(!output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let failed: Vec<&str> = json["report"]["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|c| c["status"] == "fail")
+        .map(|c| c["name"].as_str().unwrap())
+        .collect();
+    assert!(failed.contains(&"vector dimensions"), "{:?}", failed);
+    assert!(failed.contains(&"id mappings"), "{:?}", failed);
+
+    let output = validate(&["--repair"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Dropped 0 undecodable and 1 mismatched"),
+        "{}",
+        stdout
+    );
+    assert!(
+        stdout.contains("Rebuilt the index with 4 records"),
+        "{}",
+        stdout
+    );
+    assert!(validate(&[]).status.success());
+}
+
+#[test]
+fn test_cli_health_exit_code_follows_worst_check() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    ingest_sample_records(&temp_dir, &data_path, 4);
+
+    let health = |args: &[&str]| {
+        Command::new(vecstore_bin())
+            .args(["health", "--dir"])
+            .arg(&data_path)
+            .args(args)
+            .env("RUST_LOG", "warn")
+            .output()
+            .expect("Failed to execute vecstore")
+    };
+
+    let output = health(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("index_consistency"), "{}", stdout);
+
+    // An all-zero vector is a warning
+    let vectors = data_path.join("vectors.bin");
+    let mut records: Vec<serde_json::Value> =
+        serde_json::from_slice(&fs::read(&vectors).unwrap()).unwrap();
+    let mut zero = records[0].clone();
+    zero["id"] = "zero".into();
+    zero["vector"] = serde_json::json!([0.0, 0.0, 0.0]);
+    records.push(zero);
+    fs::write(&vectors, serde_json::to_vec(&records).unwrap()).unwrap();
+
+    let output = health(&["--json"]);
+    assert_eq!(output.status.code(), Some(1));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let zeros = json["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["code"] == "zero_vectors")
+        .unwrap();
+    assert_eq!(zeros["severity"], "warn");
+    assert_eq!(json["status"], "Degraded");
+}
+
+#[test]
+fn test_cli_optimize_analyze_applies_compaction() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    ingest_sample_records(&temp_dir, &data_path, 10);
+
+    // Soft-delete three of the ten records
+    let vectors = data_path.join("vectors.bin");
+    let mut records: Vec<serde_json::Value> =
+        serde_json::from_slice(&fs::read(&vectors).unwrap()).unwrap();
+    for record in &mut records[..3] {
+        record["deleted"] = true.into();
+    }
+    fs::write(&vectors, serde_json::to_vec(&records).unwrap()).unwrap();
+
+    let optimize = |args: &[&str]| {
+        let output = Command::new(vecstore_bin())
+            .args(["optimize", "--analyze", "--json", "--dir"])
+            .arg(&data_path)
+            .args(args)
+            .env("RUST_LOG", "warn")
+            .output()
+            .expect("Failed to execute vecstore");
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let json = optimize(&[]);
+    let recommendations = json["recommendations"].as_array().unwrap();
+    assert_eq!(recommendations.len(), 1, "{}", json);
+    assert_eq!(recommendations[0]["kind"], "compaction");
+    assert_eq!(json["applied"].as_array().unwrap().len(), 0);
+
+    let json = optimize(&["--apply"]);
+    assert_eq!(json["applied"][0]["kind"], "compaction");
+
+    let json = optimize(&[]);
+    assert!(json["recommendations"].as_array().unwrap().is_empty());
+}
+
+#[test]
+fn test_cli_diff() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let old_path = temp_dir.path().join("old");
+    ingest_sample_records(&temp_dir, &old_path, 6);
+    let backup = temp_dir.path().join("old.jsonl");
+    let output = Command::new(vecstore_bin())
+        .args(["export", "--dir"])
+        .arg(&old_path)
+        .arg("--output")
+        .arg(&backup)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+
+    // doc000 removed, doc001 re-ranked, doc002 moved, doc009 added
+    let mut lines = String::new();
+    for (id, vector, rank) in [
+        ("doc001", [1.0, 1.0, -0.5], 99),
+        ("doc002", [-2.0, 1.0, 3.0], 2),
+        ("doc003", [3.0, 1.0, -0.5], 3),
+        ("doc004", [4.0, 1.0, -0.5], 4),
+        ("doc005", [5.0, 1.0, -0.5], 5),
+        ("doc009", [9.0, 1.0, -0.5], 9),
+    ] {
+        let i: usize = id[3..].parse().unwrap();
+        let record = serde_json::json!({
+            "id": id,
+            "vector": vector,
+            "metadata": {"fields": {
+                "category": if i % 2 == 0 { "even" } else { "odd" },
+                "rank": rank,
+                "source": {"name": "wiki", "page": i * 10},
+            }},
+            "created_at": 0,
+            "text": format!("chunk, \"number\" {}", i),
+        });
+        lines.push_str(&record.to_string());
+        lines.push('\n');
+    }
+    let input = temp_dir.path().join("new.jsonl");
+    fs::write(&input, lines).unwrap();
+    let new_path = temp_dir.path().join("new");
+    let output = Command::new(vecstore_bin())
+        .args(["ingest-batch", "--dir"])
+        .arg(&new_path)
+        .arg("--jsonl")
+        .arg(&input)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+
+    // The old side as a store and as its export
+    for old in [&old_path, &backup] {
+        let output = Command::new(vecstore_bin())
+            .arg("diff")
+            .arg("--a")
+            .arg(old)
+            .arg("--b")
+            .arg(&new_path)
+            .arg("--json-out")
+            .env("RUST_LOG", "warn")
+            .output()
+            .expect("Failed to execute vecstore");
+        assert!(output.status.success());
+        let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report["added_ids"], serde_json::json!(["doc009"]));
+        assert_eq!(report["removed_ids"], serde_json::json!(["doc000"]));
+        assert_eq!(report["common"], 5);
+        assert_eq!(report["metadata_changed"], 1);
+        assert_eq!(report["metadata_changes"][0]["id"], "doc001");
+        assert_eq!(
+            report["metadata_changes"][0]["fields"],
+            serde_json::json!(["rank"])
+        );
+        assert_eq!(report["drifted"], 1);
+        assert_eq!(report["drifts"][0]["id"], "doc002");
+    }
+
+    let output = Command::new(vecstore_bin())
+        .arg("diff")
+        .arg("--a")
+        .arg(&old_path)
+        .arg("--b")
+        .arg(&new_path)
+        .args(["--sample", "2"])
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("compared a sample of 2"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_dedupe() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    // dup-b and dup-c repeat orig-a; orig-d and orig-e are far apart
+    let mut lines = String::new();
+    for (id, vector, created_at) in [
+        ("orig-a", [1.0, 0.0, 0.0], 100),
+        ("dup-c", [1.0, 0.0, 0.0], 300),
+        ("dup-b", [1.0, 0.01, 0.0], 200),
+        ("orig-d", [0.0, 1.0, 0.0], 100),
+        ("orig-e", [0.0, 0.0, 1.0], 100),
+    ] {
+        let record = serde_json::json!({
+            "id": id,
+            "vector": vector,
+            "metadata": {"fields": {"source": format!("{}.md", id)}},
+            "created_at": created_at,
+        });
+        lines.push_str(&record.to_string());
+        lines.push('\n');
+    }
+    let input = temp_dir.path().join("input.jsonl");
+    fs::write(&input, lines).unwrap();
+    let output = Command::new(vecstore_bin())
+        .args(["ingest-batch", "--dir"])
+        .arg(&data_path)
+        .arg("--jsonl")
+        .arg(&input)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+
+    let dedupe = |args: &[&str]| {
+        let output = Command::new(vecstore_bin())
+            .args(["dedupe", "--dir"])
+            .arg(&data_path)
+            .args(args)
+            .env("RUST_LOG", "warn")
+            .output()
+            .expect("Failed to execute vecstore");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let stdout = dedupe(&[]);
+    assert!(stdout.contains("1 groups"), "{}", stdout);
+    assert!(stdout.contains("dup-b.md"), "{}", stdout);
+
+    let report: serde_json::Value =
+        serde_json::from_str(&dedupe(&["--delete", "keep-newest", "--json-out"])).unwrap();
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(report["removed"], 0);
+    let group = &report["groups"][0];
+    assert_eq!(
+        group["ids"],
+        serde_json::json!(["orig-a", "dup-b", "dup-c"])
+    );
+    assert_eq!(group["kept"], "dup-c");
+    assert_eq!(group["records"][1]["source"], "dup-b.md");
+
+    let report: serde_json::Value = serde_json::from_str(&dedupe(&[
+        "--delete",
+        "keep-first",
+        "--dry-run=false",
+        "--json-out",
+    ]))
+    .unwrap();
+    assert_eq!(report["dry_run"], false);
+    assert_eq!(report["removed"], 2);
+
+    let report: serde_json::Value = serde_json::from_str(&dedupe(&["--json-out"])).unwrap();
+    assert_eq!(report["groups"], serde_json::json!([]));
+    let output = Command::new(vecstore_bin())
+        .args(["get", "--dir"])
+        .arg(&data_path)
+        .args(["--id", "orig-a", "--id", "dup-b"])
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ orig-a"), "{}", stdout);
+    assert!(stdout.contains("✗ dup-b not found"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_sample_head_tail() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    ingest_sample_records(&temp_dir, &data_path, 25);
+
+    let run = |args: &[&str]| {
+        let output = Command::new(vecstore_bin())
+            .arg(args[0])
+            .arg("--dir")
+            .arg(&data_path)
+            .args(&args[1..])
+            .env("RUST_LOG", "warn")
+            .output()
+            .expect("Failed to execute vecstore");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    let ids = |stdout: String| -> Vec<String> {
+        let records: Vec<serde_json::Value> = serde_json::from_str(&stdout).unwrap();
+        records
+            .iter()
+            .map(|r| r["id"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let head: Vec<serde_json::Value> =
+        serde_json::from_str(&run(&["head", "--n", "3", "--json-out"])).unwrap();
+    assert_eq!(head.len(), 3);
+    assert_eq!(head[0]["id"], "doc000");
+    assert!(head[0].get("vector").is_none());
+    assert_eq!(
+        ids(run(&["tail", "--n", "2", "--json-out"])),
+        ["doc023", "doc024"]
+    );
+    assert_eq!(
+        ids(run(&[
+            "tail",
+            "--n",
+            "2",
+            "--filter",
+            "rank < 10",
+            "--json-out"
+        ])),
+        ["doc008", "doc009"]
+    );
+
+    let stdout = run(&["head", "--n", "1", "--show-vectors"]);
+    assert!(stdout.contains("vector: [0.0, 1.0, -0.5]"), "{}", stdout);
+
+    let first = ids(run(&["sample", "--n", "5", "--seed", "9", "--json-out"]));
+    assert_eq!(first.len(), 5);
+    assert_eq!(
+        first,
+        ids(run(&["sample", "--n", "5", "--seed", "9", "--json-out"]))
+    );
+    let even = ids(run(&[
+        "sample",
+        "--n",
+        "50",
+        "--filter",
+        "category = 'even'",
+        "--json-out",
+    ]));
+    assert_eq!(even.len(), 13);
+
+    let stdout = run(&["sample", "--n", "2"]);
+    assert!(stdout.contains("2 random records (seed "), "{}", stdout);
+}
+
+#[test]
+fn test_cli_delete_ids_file() {
+    skip_if_no_binary!();
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    ingest_sample_records(&temp_dir, &data_path, 25);
+
+    let ids_file = temp_dir.path().join("stale.txt");
+    fs::write(
+        &ids_file,
+        "doc001\ndoc002\n\nmissing\n doc003 \ndoc004\ndoc005\ngone\n",
+    )
+    .unwrap();
+    let output = Command::new(vecstore_bin())
+        .args(["delete", "--dir"])
+        .arg(&data_path)
+        .arg("--ids-file")
+        .arg(&ids_file)
+        .env("RUST_LOG", "warn")
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Deleted 5 vectors (2 IDs not found)"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("vecstore compact"), "{}", stdout);
+
+    let mut child = Command::new(vecstore_bin())
+        .args(["delete", "--ids-file", "-", "--dir"])
+        .arg(&data_path)
+        .env("RUST_LOG", "warn")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("Failed to execute vecstore");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"doc001\ndoc010\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("Deleted 1 vectors (1 IDs not found)"),
+        "{}",
+        stdout
+    );
+
+    let output = Command::new(vecstore_bin())
+        .args(["head", "--n", "100", "--json-out", "--dir"])
+        .arg(&data_path)
+        .env("RUST_LOG", "warn")
+        .output()
+        .expect("Failed to execute vecstore");
+    let records: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(records.len(), 19);
+
+    let output = Command::new(vecstore_bin())
+        .args(["delete", "--id", "doc000", "--ids-file", "-", "--dir"])
+        .arg(&data_path)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_cli_export_writes_flat_metadata() {
+    skip_if_no_binary!();
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    ingest_sample_records(&temp_dir, &data_path, 3);
+
+    let export = temp_dir.path().join("export.jsonl");
+    let output = Command::new(vecstore_bin())
+        .args(["export", "--dir"])
+        .arg(&data_path)
+        .arg("--output")
+        .arg(&export)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+
+    let contents = fs::read_to_string(&export).unwrap();
+    let first: serde_json::Value = serde_json::from_str(contents.lines().next().unwrap()).unwrap();
+    assert_eq!(first["id"], "doc000");
+    assert_eq!(first["metadata"]["category"], "even");
+    assert_eq!(first["metadata"]["source"]["page"], 0);
+    assert!(first["metadata"].get("fields").is_none());
+}
+
+#[test]
+fn test_cli_deprecated_spellings_still_work() {
+    skip_if_no_binary!();
+    let temp_dir = TempDir::new().unwrap();
+    let data_path = temp_dir.path().join("data");
+    ingest_sample_records(&temp_dir, &data_path, 3);
+
+    let output = Command::new(vecstore_bin())
+        .args(["info", "--json-out", "--database"])
+        .arg(&data_path)
+        .env("RUST_LOG", "warn")
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(stats["active_records"], 3);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("`info` is deprecated"), "{}", stderr);
+    assert!(stderr.contains("`--database` is deprecated"), "{}", stderr);
+
+    let output = Command::new(vecstore_bin())
+        .args(["list", "--show-vectors", "--dir"])
+        .arg(&data_path)
+        .output()
+        .expect("Failed to execute vecstore");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("vector: [0.0, 1.0, -0.5]"));
+}
@@ -23,6 +23,9 @@ mod cross_encoder_tests {
             id: id.to_string(),
             score,
             metadata,
+            vector: None,
+            version: 1,
+            text: None,
         }
     }
 
@@ -264,6 +267,9 @@ mod cross_encoder_tests {
             id: "doc1".to_string(),
             score: 0.5,
             metadata,
+            vector: None,
+            version: 1,
+            text: None,
         }];
 
         // Should not panic, should handle gracefully
@@ -23,6 +23,7 @@ fn test_cosine_distance_identical_vectors() {
         vector: vector.clone(),
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -54,6 +55,7 @@ fn test_cosine_distance_orthogonal_vectors() {
         vector: vec![1.0, 0.0, 0.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -90,6 +92,7 @@ fn test_cosine_distance_opposite_vectors() {
         vector: vec![1.0, 0.0, 0.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -126,6 +129,7 @@ fn test_cosine_distance_magnitude_invariant() {
         vector: vec![1.0, 2.0, 3.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -153,6 +157,7 @@ fn test_euclidean_distance_identical_vectors() {
         vector: vector.clone(),
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -187,6 +192,7 @@ fn test_euclidean_distance_ordering() {
         vector: vec![1.0, 0.0, 0.0],
         k: 3,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -219,6 +225,7 @@ fn test_euclidean_distance_pythagorean() {
         vector: vec![0.0, 0.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -253,6 +260,7 @@ fn test_dot_product_distance() {
         vector: vec![1.0, 0.0, 0.0],
         k: 3,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -290,6 +298,7 @@ fn test_dot_product_magnitude_sensitive() {
         vector: vec![1.0, 0.0, 0.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -328,6 +337,7 @@ fn test_manhattan_distance() {
         vector: vec![0.0, 0.0],
         k: 3,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -362,6 +372,7 @@ fn test_manhattan_distance_calculation() {
         vector: vec![0.0, 0.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -398,6 +409,7 @@ fn test_hamming_distance_binary_vectors() {
         vector: vec![0.0, 0.0, 0.0, 0.0],
         k: 4,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -439,6 +451,7 @@ fn test_hamming_distance_ordering() {
         vector: vec![1.0, 0.0, 1.0],
         k: 4,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -474,6 +487,7 @@ fn test_jaccard_distance_sets() {
         vector: vec![1.0, 1.0, 0.0, 0.0],
         k: 3,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -501,6 +515,7 @@ fn test_jaccard_distance_identical_sets() {
         vector: vector.clone(),
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -532,6 +547,7 @@ fn test_jaccard_distance_disjoint_sets() {
         vector: vec![1.0, 1.0, 0.0, 0.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -568,6 +584,7 @@ fn test_distance_metric_comparison() {
             vector: vec![1.0, 0.0],
             k: 2,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -593,6 +610,7 @@ fn test_distance_metric_comparison() {
             vector: vec![1.0, 0.0],
             k: 2,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -655,6 +673,7 @@ fn test_distance_metric_negative_values() {
             vector: vec![1.0, 2.0, 3.0],
             k: 2,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -687,6 +706,7 @@ fn test_distance_metric_high_dimensions() {
             vector,
             k: 1,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -716,6 +736,7 @@ fn test_distance_metric_normalized_scores() {
         vector: vec![5.0, 0.0, 0.0],
         k: 10,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
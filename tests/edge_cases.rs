@@ -10,6 +10,7 @@ fn test_empty_store_query() {
         vector: vec![1.0, 0.0, 0.0],
         k: 5,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -33,6 +34,7 @@ fn test_single_vector() {
         vector: vec![1.0, 0.0, 0.0],
         k: 5,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -59,6 +61,7 @@ fn test_k_larger_than_store_size() {
         vector: vec![1.0, 0.0, 0.0],
         k: 10, // More than we have
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -82,6 +85,7 @@ fn test_zero_k() {
         vector: vec![1.0, 0.0, 0.0],
         k: 0,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -110,6 +114,7 @@ fn test_high_dimensional_vectors() {
         vector: vec1,
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -148,6 +153,7 @@ fn test_duplicate_id_upsert() {
         vector: vec![0.0, 1.0, 0.0],
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -193,6 +199,7 @@ fn test_all_vectors_filtered_out() {
             op: FilterOp::Eq,
             value: serde_json::json!("B"), // None match
         }),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -228,6 +235,7 @@ fn test_very_large_metadata() {
         vector: vec![1.0, 0.0, 0.0],
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -252,6 +260,7 @@ fn test_zero_vectors() {
         vector: vec![0.0, 0.0, 0.0],
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -278,6 +287,7 @@ fn test_negative_values_in_vectors() {
         vector: vec![-1.0, -2.0, -3.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -332,6 +342,7 @@ fn test_unicode_in_metadata() {
             op: FilterOp::Contains,
             value: serde_json::json!("🚀"),
         }),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -355,9 +366,87 @@ fn test_empty_metadata() {
         vector: vec![1.0, 0.0, 0.0],
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
     assert_eq!(results.len(), 1);
     assert!(results[0].metadata.fields.is_empty());
 }
+
+/// 100 unit vectors fanning out from [1, 0], so lower ids are nearer the query
+fn fan_store(dir: &std::path::Path) -> VecStore {
+    let mut store = VecStore::open(dir).unwrap();
+    for i in 0..100 {
+        let angle = i as f32 * 0.01;
+        let mut fields = HashMap::new();
+        fields.insert("even".to_string(), serde_json::json!(i % 2 == 0));
+        store
+            .upsert(
+                format!("doc{}", i),
+                vec![angle.cos(), angle.sin()],
+                Metadata { fields },
+            )
+            .unwrap();
+    }
+    store
+}
+
+#[test]
+fn test_half_removed_still_returns_k() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut store = fan_store(temp_dir.path());
+
+    // Remove the 50 records nearest the query
+    for i in 0..50 {
+        store.remove(&format!("doc{}", i)).unwrap();
+    }
+
+    let results = store
+        .query(Query::new(vec![1.0, 0.0]).with_limit(10))
+        .unwrap();
+    assert_eq!(results.len(), 10);
+    assert!(results.iter().all(|n| n.id != "doc0"));
+}
+
+#[test]
+fn test_half_soft_deleted_still_returns_k() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut store = fan_store(temp_dir.path());
+
+    for i in 0..50 {
+        store.soft_delete(&format!("doc{}", i)).unwrap();
+    }
+
+    // Repeat so the second query starts from the tracked deleted ratio
+    for _ in 0..2 {
+        let results = store
+            .query(Query::new(vec![1.0, 0.0]).with_limit(10))
+            .unwrap();
+        assert_eq!(results.len(), 10);
+    }
+}
+
+#[test]
+fn test_selective_filter_after_deletes_returns_k() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut store = fan_store(temp_dir.path());
+
+    for i in 0..50 {
+        store.remove(&format!("doc{}", i)).unwrap();
+    }
+
+    let filter = FilterExpr::Cmp {
+        field: "even".into(),
+        op: FilterOp::Eq,
+        value: serde_json::json!(true),
+    };
+    let results = store
+        .query(
+            Query::new(vec![1.0, 0.0])
+                .with_limit(10)
+                .with_filter_expr(filter),
+        )
+        .unwrap();
+    assert_eq!(results.len(), 10);
+}
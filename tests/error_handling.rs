@@ -41,6 +41,7 @@ fn test_dimension_mismatch_on_query() {
         vector: vec![1.0, 0.0], // Wrong dimension
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let result = store.query(query);
@@ -111,6 +112,7 @@ fn test_query_after_remove_all() {
         vector: vec![1.0, 0.0, 0.0],
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -183,6 +185,7 @@ fn test_invalid_filter_field() {
             op: FilterOp::Eq,
             value: serde_json::json!("value"),
         }),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -213,6 +216,7 @@ fn test_type_mismatch_in_filter() {
             op: FilterOp::Eq,
             value: serde_json::json!("10"), // String instead of number
         }),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
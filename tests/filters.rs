@@ -54,6 +54,7 @@ fn test_eq_filter() {
             op: FilterOp::Eq,
             value: serde_json::json!("rust"),
         }),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -76,6 +77,7 @@ fn test_neq_filter() {
             op: FilterOp::Neq,
             value: serde_json::json!("rust"),
         }),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -95,6 +97,7 @@ fn test_gt_filter() {
             op: FilterOp::Gt,
             value: serde_json::json!(7),
         }),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -113,6 +116,7 @@ fn test_lte_filter() {
             op: FilterOp::Lte,
             value: serde_json::json!(8),
         }),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -152,6 +156,7 @@ fn test_and_filter() {
                 value: serde_json::json!(7),
             },
         ])),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -178,6 +183,7 @@ fn test_or_filter() {
                 value: serde_json::json!(10),
             },
         ])),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -196,6 +202,7 @@ fn test_not_filter() {
             op: FilterOp::Eq,
             value: serde_json::json!("python"),
         }))),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -224,6 +231,7 @@ fn test_contains_filter() {
             op: FilterOp::Contains,
             value: serde_json::json!("world"),
         }),
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
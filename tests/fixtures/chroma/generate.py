@@ -0,0 +1,140 @@
+"""Regenerate chroma.sqlite3, a minimal persistent ChromaDB directory.
+
+The tables and columns match what Chroma 0.5 writes, trimmed to the ones
+the migration reads. Run from this directory: python3 generate.py
+"""
+
+import json
+import os
+import sqlite3
+import struct
+
+ARTICLES = "6f0c1c2e-5d0b-4f43-9d7a-2b1f4c8e9a01"
+NOTES = "a3e9b7c4-1f2d-4e6a-8b5c-7d9e0f1a2b3c"
+EMPTY = "0d4b8e2f-9c7a-4b1e-a6d3-5f8c2e9b7a40"
+
+SCHEMA = """
+CREATE TABLE collections (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    dimension INTEGER,
+    database_id TEXT NOT NULL,
+    config_json_str TEXT,
+    UNIQUE (name, database_id)
+);
+CREATE TABLE collection_metadata (
+    collection_id TEXT REFERENCES collections(id) ON DELETE CASCADE,
+    key TEXT NOT NULL,
+    str_value TEXT,
+    int_value INTEGER,
+    float_value REAL,
+    bool_value INTEGER,
+    PRIMARY KEY (collection_id, key)
+);
+CREATE TABLE segments (
+    id TEXT PRIMARY KEY,
+    type TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    collection TEXT REFERENCES collections(id) NOT NULL
+);
+CREATE TABLE embeddings (
+    id INTEGER PRIMARY KEY,
+    segment_id TEXT NOT NULL,
+    embedding_id TEXT NOT NULL,
+    seq_id BLOB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (segment_id, embedding_id)
+);
+CREATE TABLE embedding_metadata (
+    id INTEGER REFERENCES embeddings(id),
+    key TEXT NOT NULL,
+    string_value TEXT,
+    int_value INTEGER,
+    float_value REAL,
+    bool_value INTEGER,
+    PRIMARY KEY (id, key)
+);
+CREATE TABLE embeddings_queue (
+    seq_id INTEGER PRIMARY KEY,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    operation INTEGER NOT NULL,
+    topic TEXT NOT NULL,
+    id TEXT NOT NULL,
+    vector BLOB,
+    encoding TEXT,
+    metadata TEXT
+);
+"""
+
+ADD, UPDATE, UPSERT, DELETE = 0, 1, 2, 3
+
+
+def main():
+    if os.path.exists("chroma.sqlite3"):
+        os.remove("chroma.sqlite3")
+    db = sqlite3.connect("chroma.sqlite3")
+    db.executescript(SCHEMA)
+
+    database = "00000000-0000-0000-0000-000000000000"
+    db.execute("INSERT INTO collections VALUES (?, 'articles', 3, ?, NULL)", (ARTICLES, database))
+    db.execute(
+        "INSERT INTO collection_metadata (collection_id, key, str_value) VALUES (?, 'hnsw:space', 'cosine')",
+        (ARTICLES,),
+    )
+    config = json.dumps({"hnsw_configuration": {"space": "ip"}})
+    db.execute("INSERT INTO collections VALUES (?, 'notes', 2, ?, ?)", (NOTES, database, config))
+    db.execute("INSERT INTO collections VALUES (?, 'empty', NULL, ?, NULL)", (EMPTY, database))
+
+    for collection in (ARTICLES, NOTES, EMPTY):
+        db.execute(
+            "INSERT INTO segments VALUES (?, 'urn:chroma:segment/metadata/sqlite', 'METADATA', ?)",
+            ("meta-" + collection, collection),
+        )
+        db.execute(
+            "INSERT INTO segments VALUES (?, 'urn:chroma:segment/vector/hnsw-local-persisted', 'VECTOR', ?)",
+            ("vec-" + collection, collection),
+        )
+
+    seq = 0
+
+    def log(collection, operation, id, vector=None):
+        nonlocal seq
+        seq += 1
+        blob = struct.pack("<%df" % len(vector), *vector) if vector else None
+        db.execute(
+            "INSERT INTO embeddings_queue (seq_id, operation, topic, id, vector, encoding) VALUES (?, ?, ?, ?, ?, ?)",
+            (seq, operation, "persistent://default/default/" + collection, id, blob, "FLOAT32" if blob else None),
+        )
+
+    def record(collection, id, metadata):
+        cursor = db.execute(
+            "INSERT INTO embeddings (segment_id, embedding_id, seq_id) VALUES (?, ?, ?)",
+            ("meta-" + collection, id, struct.pack(">Q", seq)),
+        )
+        for key, value in metadata.items():
+            column = {str: "string_value", bool: "bool_value", int: "int_value", float: "float_value"}[type(value)]
+            db.execute(
+                "INSERT INTO embedding_metadata (id, key, %s) VALUES (?, ?, ?)" % column,
+                (cursor.lastrowid, key, value),
+            )
+
+    log(ARTICLES, ADD, "a1", [1.0, 0.0, 0.0])
+    record(ARTICLES, "a1", {"chroma:document": "Rust is fast", "lang": "en", "year": 2021, "score": 0.5, "draft": False})
+    log(ARTICLES, ADD, "a2", [0.0, 1.0, 0.0])
+    record(ARTICLES, "a2", {"chroma:document": "Vectors everywhere", "lang": "en"})
+    log(ARTICLES, ADD, "a3", [0.0, 0.0, 1.0])
+    log(ARTICLES, DELETE, "a3")
+    log(ARTICLES, UPDATE, "a2", [0.0, 0.6, 0.8])
+    log(ARTICLES, UPSERT, "a4", [0.5, 0.5, 0.0])
+    record(ARTICLES, "a4", {"lang": "de"})
+
+    log(NOTES, ADD, "n1", [0.5, 0.5])
+    record(NOTES, "n1", {"chroma:document": "hello"})
+
+    db.commit()
+    db.execute("VACUUM")
+    db.close()
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,144 @@
+"""Regenerate model.onnx and tokenizer.json, a tiny stand-in for MiniLM.
+
+The model has MiniLM's inputs (input_ids, attention_mask, token_type_ids)
+and output (last_hidden_state), but each token's hidden state is just its
+row of a 4-dimensional embedding table. Words about cats and dogs share
+directions, so pooled embeddings still rank sensibly. The ONNX protobuf is
+written by hand to avoid depending on the onnx package.
+Run from this directory: python3 generate.py
+"""
+
+import json
+import struct
+
+DIMENSION = 4
+
+# Token, embedding; ids follow this order
+VOCAB = [
+    ("[PAD]", [0.0, 0.0, 0.0, 9.0]),
+    ("[UNK]", [0.1, 0.1, 0.1, 0.1]),
+    ("cat", [1.0, 0.0, 0.0, 0.0]),
+    ("kitten", [0.9, 0.1, 0.0, 0.0]),
+    ("dog", [0.0, 1.0, 0.0, 0.0]),
+    ("puppy", [0.1, 0.9, 0.0, 0.0]),
+    ("car", [0.0, 0.0, 1.0, 0.0]),
+    ("the", [0.2, 0.2, 0.2, 0.0]),
+]
+
+FLOAT = 1
+INT64 = 7
+
+
+def varint(n):
+    out = bytearray()
+    while True:
+        byte = n & 0x7F
+        n >>= 7
+        if n:
+            out.append(byte | 0x80)
+        else:
+            out.append(byte)
+            return bytes(out)
+
+
+def key(field, wire):
+    return varint(field << 3 | wire)
+
+
+def int_field(field, value):
+    return key(field, 0) + varint(value)
+
+
+def bytes_field(field, value):
+    if isinstance(value, str):
+        value = value.encode()
+    return key(field, 2) + varint(len(value)) + value
+
+
+def tensor_type(elem_type, dims):
+    shape = b"".join(
+        bytes_field(1, bytes_field(2, d) if isinstance(d, str) else int_field(1, d))
+        for d in dims
+    )
+    tensor = int_field(1, elem_type) + bytes_field(2, shape)
+    return bytes_field(1, tensor)
+
+
+def value_info(name, elem_type, dims):
+    return bytes_field(1, name) + bytes_field(2, tensor_type(elem_type, dims))
+
+
+def model():
+    table = b"".join(struct.pack("<f", x) for _, row in VOCAB for x in row)
+    initializer = (
+        int_field(1, len(VOCAB))
+        + int_field(1, DIMENSION)
+        + int_field(2, FLOAT)
+        + bytes_field(8, "embeddings")
+        + bytes_field(9, table)
+    )
+    axis = bytes_field(1, "axis") + int_field(3, 0) + int_field(20, 2)
+    gather = (
+        bytes_field(1, "embeddings")
+        + bytes_field(1, "input_ids")
+        + bytes_field(2, "last_hidden_state")
+        + bytes_field(3, "lookup")
+        + bytes_field(4, "Gather")
+        + bytes_field(5, axis)
+    )
+    graph = (
+        bytes_field(1, gather)
+        + bytes_field(2, "tiny-embedder")
+        + bytes_field(5, initializer)
+        + bytes_field(11, value_info("input_ids", INT64, ["batch", "sequence"]))
+        + bytes_field(11, value_info("attention_mask", INT64, ["batch", "sequence"]))
+        + bytes_field(11, value_info("token_type_ids", INT64, ["batch", "sequence"]))
+        + bytes_field(
+            12,
+            value_info("last_hidden_state", FLOAT, ["batch", "sequence", DIMENSION]),
+        )
+    )
+    opset = bytes_field(1, "") + int_field(2, 13)
+    return (
+        int_field(1, 8)
+        + bytes_field(2, "vecstore-tests")
+        + bytes_field(7, graph)
+        + bytes_field(8, opset)
+    )
+
+
+def special(token_id, content):
+    return {
+        "id": token_id,
+        "content": content,
+        "single_word": False,
+        "lstrip": False,
+        "rstrip": False,
+        "normalized": False,
+        "special": True,
+    }
+
+
+def tokenizer():
+    return {
+        "version": "1.0",
+        "truncation": None,
+        "padding": None,
+        "added_tokens": [special(0, "[PAD]"), special(1, "[UNK]")],
+        "normalizer": {"type": "Lowercase"},
+        "pre_tokenizer": {"type": "Whitespace"},
+        "post_processor": None,
+        "decoder": None,
+        "model": {
+            "type": "WordLevel",
+            "vocab": {token: i for i, (token, _) in enumerate(VOCAB)},
+            "unk_token": "[UNK]",
+        },
+    }
+
+
+with open("model.onnx", "wb") as f:
+    f.write(model())
+with open("tokenizer.json", "w") as f:
+    json.dump(tokenizer(), f, indent=2)
+    f.write("\n")
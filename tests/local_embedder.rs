@@ -0,0 +1,76 @@
+// LocalEmbedder against the tiny ONNX model in tests/fixtures/tiny-embedder
+//
+// Run with: cargo test --features embeddings --test local_embedder
+
+#![cfg(feature = "embeddings")]
+
+use std::path::{Path, PathBuf};
+use vecstore::embeddings::{LocalEmbedder, TextEmbedder};
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tiny-embedder")
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+#[test]
+fn test_embeddings_are_pooled_and_normalized() {
+    let embedder = LocalEmbedder::from_dir(fixture()).unwrap();
+    assert_eq!(embedder.dimension(), 4);
+    assert_eq!(TextEmbedder::dimension(&embedder).unwrap(), 4);
+
+    let cat = embedder.embed("cat").unwrap();
+    let kitten = embedder.embed("Kitten").unwrap();
+    let car = embedder.embed("car").unwrap();
+    for vector in [&cat, &kitten, &car] {
+        assert_eq!(vector.len(), 4);
+        assert!((dot(vector, vector) - 1.0).abs() < 1e-5);
+    }
+    assert!(dot(&cat, &kitten) > dot(&cat, &car));
+
+    // "the cat" averages two token rows
+    let the_cat = embedder.embed("the cat").unwrap();
+    let expected = [0.6f32, 0.1, 0.1, 0.0];
+    let norm = dot(&expected, &expected).sqrt();
+    for (got, want) in the_cat.iter().zip(expected) {
+        assert!((got - want / norm).abs() < 1e-5, "{:?}", the_cat);
+    }
+}
+
+#[test]
+fn test_batches_match_single_texts() {
+    // Batches of two, and texts of different lengths padded in each batch
+    let embedder = LocalEmbedder::from_dir(fixture())
+        .unwrap()
+        .with_batch_size(2);
+    let texts = ["cat", "cat car", "the puppy and the dog", "kitten"];
+    let batched = embedder.embed_batch(&texts).unwrap();
+    assert_eq!(batched.len(), texts.len());
+    for (text, vector) in texts.iter().zip(&batched) {
+        let single = embedder.embed(text).unwrap();
+        for (a, b) in vector.iter().zip(&single) {
+            assert!(
+                (a - b).abs() < 1e-5,
+                "{}: {:?} vs {:?}",
+                text,
+                vector,
+                single
+            );
+        }
+    }
+    assert!(embedder.embed_batch(&[]).unwrap().is_empty());
+}
+
+#[cfg(feature = "server")]
+#[test]
+fn test_serves_as_server_embedder() {
+    use vecstore::server::ServerEmbedder;
+
+    let embedder = LocalEmbedder::from_dir(fixture()).unwrap();
+    let server: &dyn ServerEmbedder = &embedder;
+    assert_eq!(server.model(), fixture().display().to_string());
+    assert_eq!(server.dimension(), 4);
+    assert_eq!(server.embed_batch(&["dog", "puppy"]).unwrap().len(), 2);
+}
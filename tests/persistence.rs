@@ -45,6 +45,7 @@ fn test_save_and_reload() {
             vector: vec![1.0, 0.0, 0.0],
             k: 1,
             filter: None,
+            ..Default::default()
         };
         let results = store.query(query).unwrap();
         assert_eq!(results.len(), 1);
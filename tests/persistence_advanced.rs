@@ -36,6 +36,7 @@ fn test_persistence_with_filters() {
                 op: FilterOp::Gte,
                 value: serde_json::json!(5),
             }),
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -171,6 +172,7 @@ fn test_persistence_large_dataset() {
             vector: vec![500.0, 1000.0, 1500.0],
             k: 5,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -219,6 +221,7 @@ fn test_persistence_after_remove() {
             vector: vec![0.0, 1.0, 0.0],
             k: 3,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -295,6 +298,7 @@ fn test_persistence_complex_metadata() {
             vector: vec![1.0, 0.0, 0.0],
             k: 1,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -429,6 +433,7 @@ fn test_timestamp_persistence() {
             vector: vec![1.0, 0.0, 0.0],
             k: 1,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -0,0 +1,145 @@
+// Pinecone migration against a mocked REST API
+//
+// Run with: cargo test --features migrate --test pinecone_migration
+
+#![cfg(feature = "migrate")]
+
+use std::time::Duration;
+use vecstore::migration::{MigrationRun, PineconeClient, PineconeSource};
+use vecstore::{Distance, VecStore};
+use wiremock::matchers::{header, method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn mock_index(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path("/indexes/products"))
+        .and(header("Api-Key", "test-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "name": "products",
+            "dimension": 2,
+            "metric": "dotproduct",
+            "host": server.uri(),
+        })))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/vectors/list"))
+        .and(query_param("paginationToken", "page-2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "vectors": [{"id": "c"}],
+            "namespace": "shop",
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vectors/list"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "vectors": [{"id": "a"}, {"id": "b"}],
+            "pagination": {"next": "page-2"},
+            "namespace": "shop",
+        })))
+        .mount(server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/vectors/fetch"))
+        .and(query_param("ids", "c"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "vectors": {"c": {"id": "c", "values": [0.0, 1.0]}},
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/vectors/fetch"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "vectors": {
+                "a": {"id": "a", "values": [1.0, 0.0], "metadata": {"color": "red", "tags": ["x"]}},
+                "b": {"id": "b", "values": [0.5, 0.5], "metadata": {"price": 3}},
+            },
+        })))
+        .mount(server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/describe_index_stats"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "dimension": 2,
+            "namespaces": {"shop": {"vectorCount": 3}, "other": {"vectorCount": 10}},
+            "totalVectorCount": 13,
+        })))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_migrates_index_with_metric_and_metadata() {
+    let server = MockServer::start().await;
+    // The first list request is rate limited
+    Mock::given(method("GET"))
+        .and(path("/vectors/list"))
+        .respond_with(ResponseTemplate::new(429))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mock_index(&server).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("store");
+    let checkpoint = dir.path().join("checkpoint.json");
+    let uri = server.uri();
+    let (report, dest) = tokio::task::spawn_blocking(move || {
+        let client = PineconeClient::new("test-key")
+            .unwrap()
+            .with_control_url(uri)
+            .with_retries(3, Duration::from_millis(10));
+        let mut source = PineconeSource::connect(client, "products", Some("shop")).unwrap();
+        let report = MigrationRun::new()
+            .with_checkpoint(&checkpoint)
+            .with_save_every(1)
+            .run(&mut source, &dest)
+            .unwrap();
+        assert!(!checkpoint.exists());
+        (report, dest)
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(report.migrated, 3);
+    assert_eq!(report.source_count, Some(3));
+    assert_eq!(report.verified(), Some(true));
+
+    let store = VecStore::open(&dest).unwrap();
+    assert_eq!(store.distance_metric(), Distance::DotProduct);
+    assert_eq!(store.dimension(), 2);
+    let a = store.get("a").unwrap();
+    assert_eq!(a.metadata.fields["color"], serde_json::json!("red"));
+    assert_eq!(a.metadata.fields["tags"], serde_json::json!(["x"]));
+    assert_eq!(store.get("c").unwrap().vector, vec![0.0, 1.0]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_gives_up_after_retries() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/indexes/products"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let uri = server.uri();
+    let error = tokio::task::spawn_blocking(move || {
+        let client = PineconeClient::new("test-key")
+            .unwrap()
+            .with_control_url(uri)
+            .with_retries(2, Duration::from_millis(1));
+        PineconeSource::connect(client, "products", None)
+            .err()
+            .unwrap()
+    })
+    .await
+    .unwrap();
+    assert!(format!("{:#}", error).contains("503"));
+}
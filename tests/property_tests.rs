@@ -85,6 +85,7 @@ proptest! {
             vector: vec![0.0, 0.0, 0.0],
             k,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -117,6 +118,7 @@ proptest! {
             vector,
             k: num_docs,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -196,6 +198,7 @@ proptest! {
             vector: vector.clone(),
             k: 1,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -245,6 +248,7 @@ proptest! {
             vector,
             k,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -331,6 +335,7 @@ proptest! {
             vector,
             k: num_docs,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query).unwrap();
@@ -379,6 +384,7 @@ proptest! {
             vector,
             k: 0,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query);
@@ -415,6 +421,7 @@ proptest! {
             vector: normalized,
             k: 1,
             filter: None,
+            ..Default::default()
         };
 
         let results = store.query(query);
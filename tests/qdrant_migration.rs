@@ -0,0 +1,101 @@
+// Qdrant migration against a mocked REST API
+//
+// Run with: cargo test --features migrate --test qdrant_migration
+
+#![cfg(feature = "migrate")]
+
+use std::time::Duration;
+use vecstore::migration::{MigrationRun, QdrantClient, QdrantSource};
+use vecstore::{Distance, VecStore};
+use wiremock::matchers::{body_partial_json, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_scrolls_named_vector_with_payload_mapping() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/collections/docs"))
+        .and(header("api-key", "secret"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "result": {"config": {"params": {"vectors": {
+                "text": {"size": 2, "distance": "Dot"},
+                "image": {"size": 3, "distance": "Euclid"},
+            }}}},
+            "status": "ok",
+        })))
+        .mount(&server)
+        .await;
+
+    // The second page starts at the offset the first returned, a UUID
+    let uuid = "5c56c793-69f3-4fbf-87e6-c4bf54c28c26";
+    Mock::given(method("POST"))
+        .and(path("/collections/docs/points/scroll"))
+        .and(body_partial_json(serde_json::json!({"offset": uuid})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "result": {
+                "points": [{"id": uuid, "vector": {"image": [0.0, 0.0, 1.0]}, "payload": {}}],
+                "next_page_offset": null,
+            },
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/collections/docs/points/scroll"))
+        .and(body_partial_json(
+            serde_json::json!({"with_vector": ["image"]}),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "result": {
+                "points": [
+                    {"id": 1, "vector": {"image": [1.0, 0.0, 0.0]},
+                     "payload": {"lang": "en", "author": {"name": "Ada"}}},
+                    {"id": 2, "vector": {"image": [0.0, 1.0, 0.0]},
+                     "payload": {"location": {"lat": 52.5, "lon": 13.4}}},
+                ],
+                "next_page_offset": uuid,
+            },
+        })))
+        .mount(&server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/collections/docs/points/count"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(serde_json::json!({"result": {"count": 3}})),
+        )
+        .mount(&server)
+        .await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let dest = dir.path().join("store");
+    let uri = server.uri();
+    let (report, mapping, dest) = tokio::task::spawn_blocking(move || {
+        let client = QdrantClient::new(uri, Some("secret".to_string()))
+            .unwrap()
+            .with_retries(0, Duration::from_millis(1));
+        let mut source = QdrantSource::connect(client, "docs", Some("image")).unwrap();
+        let report = MigrationRun::new().run(&mut source, &dest).unwrap();
+        (report, source.payload_mapping().clone(), dest)
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(report.migrated, 3);
+    assert_eq!(report.verified(), Some(true));
+    assert_eq!(mapping.stringified.get("author"), Some(&1));
+    assert_eq!(mapping.stringified.len(), 1);
+
+    let store = VecStore::open(&dest).unwrap();
+    assert_eq!(store.distance_metric(), Distance::Euclidean);
+    let first = store.get("1").unwrap();
+    assert_eq!(first.metadata.fields["lang"], serde_json::json!("en"));
+    assert_eq!(
+        first.metadata.fields["author"],
+        serde_json::json!(r#"{"name":"Ada"}"#)
+    );
+    assert_eq!(
+        store.get("2").unwrap().metadata.fields["location"],
+        serde_json::json!({"lat": 52.5, "lon": 13.4})
+    );
+    assert_eq!(store.get(uuid).unwrap().vector, vec![0.0, 0.0, 1.0]);
+}
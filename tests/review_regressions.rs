@@ -144,7 +144,7 @@ fn vecstore_should_require_minimum_dimension() {
 #[test]
 fn graph_visualizer_native_backend_should_error() {
     let temp_dir = tempdir().expect("tempdir");
-    let store = VecStore::open(temp_dir.path()).expect("store");
+    let mut store = VecStore::open(temp_dir.path()).expect("store");
 
     store
         .upsert("doc".to_string(), vec![1.0, 0.0], empty_metadata())
@@ -251,11 +251,11 @@ fn store_configuration_should_persist_across_reopen() {
         "Store distance configuration was not persisted across reopen."
     );
     assert_eq!(
-        config.hnsw_m, 42,
+        config.hnsw.m, 42,
         "HNSW 'M' parameter reverted to default after reopen."
     );
     assert_eq!(
-        config.hnsw_ef_construction, 77,
+        config.hnsw.ef_construction, 77,
         "HNSW 'ef_construction' parameter reverted to default after reopen."
     );
 }
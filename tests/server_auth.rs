@@ -0,0 +1,207 @@
+//! API key authentication over both server protocols
+
+#![cfg(feature = "server")]
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tonic::Code;
+use tower::ServiceExt;
+use vecstore::server::auth::ApiKeyEntry;
+use vecstore::server::types::pb::vec_store_service_client::VecStoreServiceClient;
+use vecstore::server::types::pb::vec_store_service_server::VecStoreServiceServer;
+use vecstore::server::types::pb::{HealthCheckRequest, StatsRequest, UpsertRequest};
+use vecstore::server::{AdminHttpServer, ApiKeys, Scope, VecStoreGrpcServer, VecStoreHttpServer};
+use vecstore::{NamespaceManager, VecStore};
+
+fn keys() -> Arc<ApiKeys> {
+    let entry = |name: &str, scopes: Vec<Scope>, namespace: Option<&str>| ApiKeyEntry {
+        name: name.to_string(),
+        hash: ApiKeys::hash_key(&format!("{}-key", name)),
+        scopes,
+        namespace: namespace.map(str::to_string),
+    };
+    let keys = ApiKeys::from_entries(vec![
+        entry("reader", vec![Scope::Read], None),
+        entry("writer", vec![Scope::Write], None),
+        entry("acme", vec![Scope::Admin], Some("acme")),
+    ])
+    .unwrap();
+    Arc::new(keys.with_public_health(true))
+}
+
+async fn send(router: &axum::Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn upsert(key_header: Option<(&str, &str)>) -> Request<Body> {
+    let mut request = Request::post("/v1/upsert").header("content-type", "application/json");
+    if let Some((name, value)) = key_header {
+        request = request.header(name, value);
+    }
+    let body = json!({"id": "a", "vector": [1.0, 0.0], "metadata": {}});
+    request.body(Body::from(body.to_string())).unwrap()
+}
+
+#[tokio::test]
+async fn test_http_keys_and_scopes() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = VecStore::open(dir.path()).unwrap();
+    let router = VecStoreHttpServer::new(store).with_auth(keys()).router();
+
+    let (status, body) = send(&router, upsert(None)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "missing_key");
+
+    let (status, body) = send(&router, upsert(Some(("x-api-key", "wrong")))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], "invalid_key");
+
+    let (status, body) = send(&router, upsert(Some(("x-api-key", "reader-key")))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "insufficient_scope");
+
+    let (status, _) = send(
+        &router,
+        upsert(Some(("authorization", "Bearer writer-key"))),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let stats = Request::get("/v1/stats")
+        .header("x-api-key", "reader-key")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(send(&router, stats).await.0, StatusCode::OK);
+
+    let snapshot = Request::post("/v1/snapshots")
+        .header("x-api-key", "writer-key")
+        .header("content-type", "application/json")
+        .body(Body::from(json!({"name": "s"}).to_string()))
+        .unwrap();
+    assert_eq!(send(&router, snapshot).await.0, StatusCode::FORBIDDEN);
+
+    // A tenant key reaches nothing in single-tenant mode
+    let stats = Request::get("/v1/stats")
+        .header("x-api-key", "acme-key")
+        .body(Body::empty())
+        .unwrap();
+    let (status, body) = send(&router, stats).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "namespace_forbidden");
+
+    let health = Request::get("/health").body(Body::empty()).unwrap();
+    assert_eq!(send(&router, health).await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_admin_http_namespace_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = NamespaceManager::new(dir.path()).unwrap();
+    for id in ["acme", "globex"] {
+        manager
+            .create_namespace(id.to_string(), id.to_string(), None)
+            .unwrap();
+    }
+    let router = AdminHttpServer::new(Arc::new(RwLock::new(manager)))
+        .with_auth(keys())
+        .router();
+    let get = |path: &str| {
+        Request::get(path)
+            .header("x-api-key", "acme-key")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    assert_eq!(
+        send(&router, get("/admin/namespaces/acme")).await.0,
+        StatusCode::OK
+    );
+    assert_eq!(
+        send(&router, get("/admin/namespaces/acme/stats")).await.0,
+        StatusCode::OK
+    );
+
+    let (status, body) = send(&router, get("/admin/namespaces/globex")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "namespace_forbidden");
+
+    let (status, body) = send(&router, get("/admin/namespaces")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "namespace_forbidden");
+}
+
+#[tokio::test]
+async fn test_grpc_keys_and_scopes() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = VecStore::open(dir.path()).unwrap();
+    let keys = keys();
+    let service = VecStoreGrpcServer::new(store).with_auth(keys.clone());
+
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(VecStoreServiceServer::with_interceptor(
+                service,
+                keys.interceptor(),
+            ))
+            .serve(addr),
+    );
+    let mut client = None;
+    for _ in 0..50 {
+        match VecStoreServiceClient::connect(format!("http://{}", addr)).await {
+            Ok(connected) => {
+                client = Some(connected);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+    let mut client = client.expect("gRPC server did not start");
+
+    let with_key = |key: Option<&str>| {
+        let mut request = tonic::Request::new(UpsertRequest {
+            id: "a".to_string(),
+            vector: vec![1.0, 0.0],
+            ..Default::default()
+        });
+        if let Some(key) = key {
+            request
+                .metadata_mut()
+                .insert("x-api-key", key.parse().unwrap());
+        }
+        request
+    };
+
+    let status = client.upsert(with_key(None)).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+    let status = client.upsert(with_key(Some("wrong"))).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+    let status = client
+        .upsert(with_key(Some("reader-key")))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    assert!(client.upsert(with_key(Some("writer-key"))).await.is_ok());
+
+    let mut stats = tonic::Request::new(StatsRequest::default());
+    stats
+        .metadata_mut()
+        .insert("authorization", "Bearer acme-key".parse().unwrap());
+    let status = client.get_stats(stats).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+
+    // Public health check
+    assert!(client.health_check(HealthCheckRequest {}).await.is_ok());
+}
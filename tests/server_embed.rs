@@ -0,0 +1,136 @@
+//! Server-side embedding on `/v1/embed` and `/v1/query/text`
+
+#![cfg(feature = "server")]
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tower::ServiceExt;
+use vecstore::server::{Embedding, ServerEmbedder, VecStoreHttpServer};
+use vecstore::VecStore;
+
+/// Embeds a text by whether it mentions cats or dogs
+struct Pets;
+
+impl ServerEmbedder for Pets {
+    fn model(&self) -> &str {
+        "pets"
+    }
+
+    fn dimension(&self) -> usize {
+        2
+    }
+
+    fn embed_batch(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+        Ok(texts
+            .iter()
+            .map(|t| {
+                let cat = if t.contains("cat") { 1.0 } else { 0.0 };
+                let dog = if t.contains("dog") { 1.0 } else { 0.0 };
+                vec![cat, dog]
+            })
+            .collect())
+    }
+}
+
+async fn send(router: &axum::Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn post(path: &str, body: Value) -> Request<Body> {
+    Request::post(path)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_embed_and_query_by_text() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = VecStore::open(dir.path()).unwrap();
+    let router = VecStoreHttpServer::new(store)
+        .with_embedder(Embedding::new(Arc::new(Pets)).with_max_batch(2))
+        .router();
+
+    let texts = json!({"texts": ["a cat", "a dog"]});
+    let (status, body) = send(&router, post("/v1/embed", texts)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["model"], "pets");
+    assert_eq!(body["dimension"], 2);
+    assert_eq!(body["embeddings"], json!([[1.0, 0.0], [0.0, 1.0]]));
+
+    for (id, vector) in [("cat", [1.0, 0.0]), ("dog", [0.0, 1.0])] {
+        let upsert = post(
+            "/v1/upsert",
+            json!({"id": id, "vector": vector, "metadata": {}}),
+        );
+        assert_eq!(send(&router, upsert).await.0, StatusCode::OK);
+    }
+    let query = json!({"text": "where is my dog", "limit": 1});
+    let (status, body) = send(&router, post("/v1/query/text", query)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["results"][0]["id"], "dog");
+
+    let too_many = json!({"texts": ["a", "b", "c"]});
+    let (status, body) = send(&router, post("/v1/embed", too_many)).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["code"], "batch_too_large");
+
+    let request = Request::get("/metrics").body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let scrape = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let scrape = String::from_utf8(scrape.to_vec()).unwrap();
+    assert!(scrape.contains("vecstore_embedding_duration_seconds_count{model=\"pets\"} 2"));
+}
+
+#[tokio::test]
+async fn test_embedding_routes_without_an_embedder() {
+    let dir = tempfile::tempdir().unwrap();
+    let router = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap()).router();
+
+    let (status, body) = send(&router, post("/v1/embed", json!({"texts": ["a"]}))).await;
+    assert_eq!(status, StatusCode::NOT_IMPLEMENTED);
+    assert_eq!(body["code"], "unimplemented");
+}
+
+#[cfg(feature = "server-openai")]
+#[tokio::test]
+async fn test_openai_compatible_backend() {
+    use vecstore::server::EmbedderConfig;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .and(header("authorization", "Bearer secret"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": [{"embedding": [0.5, 0.5, 0.0], "index": 0}]
+        })))
+        .mount(&server)
+        .await;
+
+    let config = EmbedderConfig::OpenAi {
+        base_url: format!("{}/v1", server.uri()),
+        model: "tiny".to_string(),
+        api_key: Some("secret".to_string()),
+    };
+    let embedder = tokio::task::spawn_blocking(move || config.build())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(embedder.dimension(), 3);
+    assert!(vecstore::server::embed::check_dimension(embedder.as_ref(), 384).is_err());
+
+    let embedding = Embedding::new(embedder);
+    let vectors = embedding.embed(vec!["hello".to_string()]).await.unwrap();
+    assert_eq!(vectors, vec![vec![0.5, 0.5, 0.0]]);
+}
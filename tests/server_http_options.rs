@@ -0,0 +1,186 @@
+//! CORS, response compression and request body limits of the HTTP servers
+
+#![cfg(feature = "server")]
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower::ServiceExt;
+use vecstore::server::{AdminHttpServer, Cors, HttpOptions, VecStoreHttpServer};
+use vecstore::{NamespaceManager, VecStore};
+
+fn http_router(options: HttpOptions) -> (tempfile::TempDir, axum::Router) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = VecStore::open(dir.path()).unwrap();
+    let router = VecStoreHttpServer::new(store)
+        .with_http_options(options)
+        .router();
+    (dir, router)
+}
+
+fn preflight(origin: &str) -> Request<Body> {
+    Request::options("/v1/query")
+        .header(header::ORIGIN, origin)
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+        .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn upsert(id: &str, padding: usize) -> String {
+    json!({"id": id, "vector": [1.0, 0.0], "metadata": {"pad": "x".repeat(padding)}}).to_string()
+}
+
+#[tokio::test]
+async fn test_cors_allows_localhost_by_default() {
+    let (_dir, router) = http_router(HttpOptions::default());
+
+    let response = router
+        .clone()
+        .oneshot(preflight("http://localhost:5173"))
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "http://localhost:5173"
+    );
+
+    let response = router
+        .clone()
+        .oneshot(preflight("https://demo.example.com"))
+        .await
+        .unwrap();
+    assert!(response
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .is_none());
+
+    let options = HttpOptions {
+        cors: Cors::parse(&["https://demo.example.com"], &["POST"], &["content-type"]).unwrap(),
+        ..HttpOptions::default()
+    };
+    let (_dir, router) = http_router(options);
+    let response = router
+        .oneshot(preflight("https://demo.example.com"))
+        .await
+        .unwrap();
+    assert_eq!(
+        response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://demo.example.com"
+    );
+}
+
+#[tokio::test]
+async fn test_responses_are_compressed_when_accepted() {
+    let (_dir, router) = http_router(HttpOptions::default());
+    for id in 0..50 {
+        let request = Request::post("/v1/upsert")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(upsert(&id.to_string(), 100)))
+            .unwrap();
+        assert_eq!(
+            router.clone().oneshot(request).await.unwrap().status(),
+            StatusCode::OK
+        );
+    }
+    let query = || {
+        Request::post("/v1/query")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(
+                json!({"vector": [1.0, 0.0], "limit": 50}).to_string(),
+            ))
+            .unwrap()
+    };
+
+    for encoding in ["gzip", "br"] {
+        let mut request = query();
+        request
+            .headers_mut()
+            .insert(header::ACCEPT_ENCODING, encoding.parse().unwrap());
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], encoding);
+    }
+
+    let response = router.clone().oneshot(query()).await.unwrap();
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+
+    let (_dir, uncompressed) = http_router(HttpOptions {
+        compression: false,
+        ..HttpOptions::default()
+    });
+    let mut request = query();
+    request
+        .headers_mut()
+        .insert(header::ACCEPT_ENCODING, "gzip".parse().unwrap());
+    let response = uncompressed.oneshot(request).await.unwrap();
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+}
+
+#[tokio::test]
+async fn test_oversized_bodies_get_413() {
+    let options = HttpOptions {
+        max_body_bytes: 1024,
+        ..HttpOptions::default()
+    };
+    let (_dir, router) = http_router(options.clone());
+
+    let small = Request::post("/v1/upsert")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(upsert("a", 10)))
+        .unwrap();
+    assert_eq!(
+        router.clone().oneshot(small).await.unwrap().status(),
+        StatusCode::OK
+    );
+
+    // Declared length, and a chunked body with none
+    let declared = Request::post("/v1/upsert")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(upsert("b", 4096)))
+        .unwrap();
+    let chunks = vec![Ok::<_, std::io::Error>(upsert("c", 4096))];
+    let chunked = Request::post("/v1/upsert")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from_stream(futures::stream::iter(chunks)))
+        .unwrap();
+    for request in [declared, chunked] {
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "payload_too_large");
+        assert_eq!(body["details"]["max_bytes"], 1024);
+    }
+
+    // Imports keep their own, larger limit
+    let rows: String = (0..10)
+        .map(|i| upsert(&i.to_string(), 200) + "\n")
+        .collect();
+    let import = Request::post("/v1/import").body(Body::from(rows)).unwrap();
+    assert_eq!(
+        router.oneshot(import).await.unwrap().status(),
+        StatusCode::OK
+    );
+
+    let dir = tempfile::tempdir().unwrap();
+    let manager = NamespaceManager::new(dir.path()).unwrap();
+    let admin = AdminHttpServer::new(Arc::new(RwLock::new(manager)))
+        .with_http_options(options)
+        .router();
+    let create = Request::post("/admin/namespaces")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(
+            json!({"id": "acme", "name": "x".repeat(4096)}).to_string(),
+        ))
+        .unwrap();
+    assert_eq!(
+        admin.oneshot(create).await.unwrap().status(),
+        StatusCode::PAYLOAD_TOO_LARGE
+    );
+}
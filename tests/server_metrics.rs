@@ -0,0 +1,237 @@
+//! The `/metrics` scrape after traffic over both server protocols
+
+#![cfg(feature = "server")]
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tower::ServiceExt;
+use vecstore::server::metrics::GrpcMetricsLayer;
+use vecstore::server::types::pb::vec_store_service_client::VecStoreServiceClient;
+use vecstore::server::types::pb::vec_store_service_server::VecStoreServiceServer;
+use vecstore::server::types::pb::{QueryRequest, UpsertRequest};
+use vecstore::server::{AdminHttpServer, VecStoreGrpcServer, VecStoreHttpServer};
+use vecstore::{NamespaceManager, VecStore};
+
+async fn call(router: &axum::Router, request: Request<Body>) -> (StatusCode, String) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+fn post(path: &str, body: serde_json::Value) -> Request<Body> {
+    Request::post(path)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// Whether the scrape has a sample of `name` carrying every label in `labels`
+fn has_series(scrape: &str, name: &str, labels: &[&str]) -> bool {
+    scrape.lines().any(|line| {
+        line.strip_prefix(name)
+            .is_some_and(|rest| rest.starts_with('{') || rest.starts_with(' '))
+            && labels.iter().all(|label| line.contains(label))
+    })
+}
+
+#[tokio::test]
+async fn test_scrape_after_http_and_grpc_traffic() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = VecStore::open(dir.path()).unwrap();
+    store.set_slow_query_threshold(Some(Duration::ZERO));
+    let store = Arc::new(RwLock::new(store));
+    let router = VecStoreHttpServer::with_store(store.clone()).router();
+
+    for id in ["a", "b", "c"] {
+        let upsert = post(
+            "/v1/upsert",
+            json!({"id": id, "vector": [1.0, 0.0], "metadata": {}}),
+        );
+        assert_eq!(call(&router, upsert).await.0, StatusCode::OK);
+    }
+    let query = post("/v1/query", json!({"vector": [1.0, 0.0], "limit": 2}));
+    assert_eq!(call(&router, query).await.0, StatusCode::OK);
+    let soft_delete = post("/v1/soft-delete/c", json!({}));
+    assert_eq!(call(&router, soft_delete).await.0, StatusCode::OK);
+    let bad_query = post(
+        "/v1/query",
+        json!({"vector": [1.0, 0.0], "limit": 2, "filter": "(("}),
+    );
+    let (status, _) = call(&router, bad_query).await;
+    assert!(status.is_client_error() || status.is_server_error());
+
+    // gRPC through the same layer the server binary adds
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let service = VecStoreGrpcServer::with_store(store.clone());
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .layer(GrpcMetricsLayer)
+            .add_service(VecStoreServiceServer::new(service))
+            .serve(addr),
+    );
+    let mut client = None;
+    for _ in 0..50 {
+        match VecStoreServiceClient::connect(format!("http://{}", addr)).await {
+            Ok(connected) => {
+                client = Some(connected);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+    let mut client = client.expect("gRPC server did not start");
+    client
+        .upsert(UpsertRequest {
+            id: "d".to_string(),
+            vector: vec![0.0, 1.0],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    client
+        .query(QueryRequest {
+            vector: vec![0.0, 1.0],
+            limit: 1,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let invalid = client
+        .query(QueryRequest {
+            vector: vec![0.0, 1.0],
+            limit: 1,
+            filter: Some("((".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(invalid.code(), tonic::Code::InvalidArgument);
+
+    let metrics = Request::get("/metrics").body(Body::empty()).unwrap();
+    let (status, scrape) = call(&router, metrics).await;
+    assert_eq!(status, StatusCode::OK);
+
+    for operation in ["query", "upsert", "delete"] {
+        let label = format!("operation=\"{}\"", operation);
+        assert!(
+            has_series(
+                &scrape,
+                "vecstore_operation_duration_seconds_bucket",
+                &[&label, "protocol=\"http\"", "le=\""],
+            ),
+            "no {} latency histogram in:\n{}",
+            operation,
+            scrape
+        );
+    }
+    for operation in ["query", "upsert"] {
+        let label = format!("operation=\"{}\"", operation);
+        assert!(has_series(
+            &scrape,
+            "vecstore_operation_duration_seconds_count",
+            &[&label, "protocol=\"grpc\""],
+        ));
+    }
+
+    assert!(has_series(
+        &scrape,
+        "vecstore_requests_total",
+        &[
+            "protocol=\"http\"",
+            "endpoint=\"/v1/upsert\"",
+            "status=\"200\""
+        ],
+    ));
+    assert!(has_series(
+        &scrape,
+        "vecstore_requests_total",
+        &["protocol=\"http\"", "endpoint=\"/v1/soft-delete/{id}\""],
+    ));
+    assert!(has_series(
+        &scrape,
+        "vecstore_requests_total",
+        &[
+            "protocol=\"grpc\"",
+            "endpoint=\"/vecstore.VecStoreService/Query\"",
+            "status=\"Ok\"",
+        ],
+    ));
+    assert!(has_series(
+        &scrape,
+        "vecstore_requests_total",
+        &[
+            "protocol=\"grpc\"",
+            "endpoint=\"/vecstore.VecStoreService/Query\"",
+            "status=\"InvalidArgument\"",
+        ],
+    ));
+
+    assert!(scrape.contains("vecstore_vectors_active 3\n"));
+    assert!(scrape.contains("vecstore_vectors_deleted 1\n"));
+    assert!(scrape.contains("vecstore_tombstone_ratio 0.25\n"));
+    for gauge in ["vecstore_memory_bytes", "vecstore_wal_bytes"] {
+        assert!(
+            has_series(&scrape, gauge, &[]),
+            "no {} in:\n{}",
+            gauge,
+            scrape
+        );
+    }
+    assert!(has_series(
+        &scrape,
+        "vecstore_last_slow_query_seconds",
+        &["at=\""]
+    ));
+}
+
+#[tokio::test]
+async fn test_scrape_counts_records_per_namespace() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = NamespaceManager::new(dir.path()).unwrap();
+    for id in ["acme", "globex"] {
+        manager
+            .create_namespace(id.to_string(), id.to_string(), None)
+            .unwrap();
+    }
+    for id in ["a", "b"] {
+        manager
+            .upsert(
+                &"acme".to_string(),
+                id.to_string(),
+                vec![1.0, 0.0],
+                vecstore::Metadata {
+                    fields: Default::default(),
+                },
+            )
+            .unwrap();
+    }
+    let router = AdminHttpServer::new(Arc::new(RwLock::new(manager))).router();
+
+    let stats = Request::get("/admin/stats").body(Body::empty()).unwrap();
+    assert_eq!(call(&router, stats).await.0, StatusCode::OK);
+    let metrics = Request::get("/metrics").body(Body::empty()).unwrap();
+    let (status, scrape) = call(&router, metrics).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert!(scrape.contains("vecstore_namespace_records{namespace=\"acme\"} 2\n"));
+    assert!(scrape.contains("vecstore_namespace_records{namespace=\"globex\"} 0\n"));
+    assert!(has_series(
+        &scrape,
+        "vecstore_requests_total",
+        &[
+            "endpoint=\"/admin/stats\"",
+            "method=\"GET\"",
+            "status=\"200\""
+        ],
+    ));
+}
@@ -0,0 +1,269 @@
+//! Vector operations on namespaces in multi-tenant mode
+
+#![cfg(feature = "server")]
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tonic::Code;
+use tower::ServiceExt;
+use vecstore::server::auth::ApiKeyEntry;
+use vecstore::server::types::pb::vec_store_service_client::VecStoreServiceClient;
+use vecstore::server::types::pb::vec_store_service_server::VecStoreServiceServer;
+use vecstore::server::types::pb::{DeleteRequest, QueryRequest, UpsertRequest};
+use vecstore::server::{AdminHttpServer, ApiKeys, NamespaceGrpcServer, Scope};
+use vecstore::NamespaceManager;
+
+fn manager() -> (tempfile::TempDir, Arc<RwLock<NamespaceManager>>) {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = NamespaceManager::new(dir.path()).unwrap();
+    for id in ["acme", "globex"] {
+        manager
+            .create_namespace(id.to_string(), id.to_string(), None)
+            .unwrap();
+    }
+    (dir, Arc::new(RwLock::new(manager)))
+}
+
+async fn send(router: &axum::Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn post(path: &str, key: Option<&str>, body: Value) -> Request<Body> {
+    let mut request = Request::post(path).header("content-type", "application/json");
+    if let Some(key) = key {
+        request = request.header("x-api-key", key);
+    }
+    request.body(Body::from(body.to_string())).unwrap()
+}
+
+#[tokio::test]
+async fn test_http_routes_stay_in_their_namespace() {
+    let (_dir, manager) = manager();
+    let router = AdminHttpServer::new(manager).router();
+
+    for (id, vector) in [("a", [1.0, 0.0]), ("b", [0.0, 1.0])] {
+        let upsert = post(
+            "/v1/ns/acme/upsert",
+            None,
+            json!({"id": id, "vector": vector, "metadata": {"n": 1}}),
+        );
+        let (status, body) = send(&router, upsert).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        assert_eq!(body["version"], 1);
+    }
+
+    let query = json!({"vector": [1.0, 0.0], "limit": 5});
+    let (status, body) = send(&router, post("/v1/ns/acme/query", None, query.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["results"].as_array().unwrap().len(), 2);
+    assert_eq!(body["results"][0]["id"], "a");
+    let (_, body) = send(&router, post("/v1/ns/globex/query", None, query.clone())).await;
+    assert!(body["results"].as_array().unwrap().is_empty());
+
+    let (status, _) = send(&router, post("/v1/ns/missing/query", None, query.clone())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let stale = post(
+        "/v1/ns/acme/upsert",
+        None,
+        json!({"id": "a", "vector": [1.0, 0.0], "metadata": {}, "expected_version": 0}),
+    );
+    assert_eq!(
+        send(&router, stale).await.0,
+        StatusCode::PRECONDITION_FAILED
+    );
+
+    let delete = Request::delete("/v1/ns/acme/delete/a")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(send(&router, delete).await.0, StatusCode::OK);
+    let (_, body) = send(&router, post("/v1/ns/acme/query", None, query)).await;
+    assert_eq!(body["results"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_http_namespace_key_scopes() {
+    let entry = |name: &str, scopes: Vec<Scope>, namespace: Option<&str>| ApiKeyEntry {
+        name: name.to_string(),
+        hash: ApiKeys::hash_key(&format!("{}-key", name)),
+        scopes,
+        namespace: namespace.map(str::to_string),
+    };
+    let keys = ApiKeys::from_entries(vec![
+        entry("acme-reader", vec![Scope::Read], Some("acme")),
+        entry("acme-writer", vec![Scope::Write], Some("acme")),
+    ])
+    .unwrap();
+    let (_dir, manager) = manager();
+    let router = AdminHttpServer::new(manager)
+        .with_auth(Arc::new(keys))
+        .router();
+    let upsert = |path: &str, key: &str| {
+        post(
+            path,
+            Some(key),
+            json!({"id": "a", "vector": [1.0, 0.0], "metadata": {}}),
+        )
+    };
+
+    assert_eq!(
+        send(&router, upsert("/v1/ns/acme/upsert", "acme-writer-key"))
+            .await
+            .0,
+        StatusCode::OK
+    );
+    let (status, body) = send(&router, upsert("/v1/ns/acme/upsert", "acme-reader-key")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "insufficient_scope");
+    let (status, body) = send(&router, upsert("/v1/ns/globex/upsert", "acme-writer-key")).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "namespace_forbidden");
+
+    let query = post(
+        "/v1/ns/acme/query",
+        Some("acme-reader-key"),
+        json!({"vector": [1.0, 0.0], "limit": 1}),
+    );
+    let (status, body) = send(&router, query).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["results"][0]["id"], "a");
+}
+
+#[tokio::test]
+async fn test_vector_quota_rejects_only_its_namespace() {
+    let (_dir, manager) = manager();
+    let router = AdminHttpServer::new(manager).router();
+    let put_quotas = |max_vectors: u64| {
+        Request::put("/admin/namespaces/acme/quotas")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"max_vectors": max_vectors}).to_string()))
+            .unwrap()
+    };
+    let upsert = |ns: &str, id: &str| {
+        post(
+            &format!("/v1/ns/{}/upsert", ns),
+            None,
+            json!({"id": id, "vector": [1.0, 0.0], "metadata": {}}),
+        )
+    };
+
+    assert_eq!(send(&router, put_quotas(1)).await.0, StatusCode::OK);
+    assert_eq!(send(&router, upsert("acme", "a")).await.0, StatusCode::OK);
+    let (status, body) = send(&router, upsert("acme", "b")).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(body["code"], "quota_exceeded");
+    assert_eq!(body["details"]["quota"], "max_vectors");
+    assert_eq!(body["details"]["limit"], 1);
+
+    // Overwrites, queries and other tenants are unaffected
+    assert_eq!(send(&router, upsert("acme", "a")).await.0, StatusCode::OK);
+    let query = json!({"vector": [1.0, 0.0], "limit": 5});
+    assert_eq!(
+        send(&router, post("/v1/ns/acme/query", None, query))
+            .await
+            .0,
+        StatusCode::OK
+    );
+    for id in ["a", "b"] {
+        assert_eq!(send(&router, upsert("globex", id)).await.0, StatusCode::OK);
+    }
+
+    let list = Request::get("/admin/namespaces")
+        .body(Body::empty())
+        .unwrap();
+    let (_, body) = send(&router, list).await;
+    let acme = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|ns| ns["id"] == "acme")
+        .unwrap();
+    assert_eq!(acme["usage"]["vector_count"], 1);
+    assert!(acme["usage"]["storage_bytes"].as_u64().unwrap() > 0);
+    assert_eq!(acme["quotas"]["max_vectors"], 1);
+
+    // Raising the quota applies to the next request
+    assert_eq!(send(&router, put_quotas(2)).await.0, StatusCode::OK);
+    assert_eq!(send(&router, upsert("acme", "b")).await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_grpc_routes_by_field_or_header() {
+    let (_dir, manager) = manager();
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(VecStoreServiceServer::new(NamespaceGrpcServer::new(
+                manager,
+            )))
+            .serve(addr),
+    );
+    let mut client = None;
+    for _ in 0..50 {
+        match VecStoreServiceClient::connect(format!("http://{}", addr)).await {
+            Ok(connected) => {
+                client = Some(connected);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+    let mut client = client.expect("gRPC server did not start");
+
+    for id in ["a", "b"] {
+        client
+            .upsert(UpsertRequest {
+                id: id.to_string(),
+                vector: vec![1.0, 0.0],
+                namespace: Some("acme".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+    }
+
+    let query = |namespace: Option<&str>| QueryRequest {
+        vector: vec![1.0, 0.0],
+        limit: 5,
+        namespace: namespace.map(str::to_string),
+        ..Default::default()
+    };
+    let found = client.query(query(Some("acme"))).await.unwrap();
+    assert_eq!(found.into_inner().results.len(), 2);
+    let found = client.query(query(Some("globex"))).await.unwrap();
+    assert!(found.into_inner().results.is_empty());
+
+    let mut by_header = tonic::Request::new(query(None));
+    by_header
+        .metadata_mut()
+        .insert("x-vecstore-namespace", "acme".parse().unwrap());
+    let found = client.query(by_header).await.unwrap();
+    assert_eq!(found.into_inner().results.len(), 2);
+
+    let status = client.query(query(None)).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    let status = client.query(query(Some("missing"))).await.unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    client
+        .delete(DeleteRequest {
+            id: "a".to_string(),
+            namespace: Some("acme".to_string()),
+        })
+        .await
+        .unwrap();
+    let found = client.query(query(Some("acme"))).await.unwrap();
+    assert_eq!(found.into_inner().results.len(), 1);
+}
@@ -0,0 +1,127 @@
+//! OpenAPI documents and the error envelope of the HTTP servers
+//!
+//! The documents are compared with `tests/snapshots/`. After changing a
+//! route or one of its types, regenerate them with
+//! `UPDATE_OPENAPI_SNAPSHOTS=1 cargo test --features server --test server_openapi`.
+
+#![cfg(feature = "server")]
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use tower::ServiceExt;
+use utoipa::OpenApi;
+use vecstore::server::auth::ApiKeyEntry;
+use vecstore::server::{AdminApi, ApiKeys, HttpApi, HttpOptions, Scope, VecStoreHttpServer};
+use vecstore::VecStore;
+
+fn check_snapshot(name: &str, spec: utoipa::openapi::OpenApi) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name);
+    let actual = spec.to_pretty_json().unwrap() + "\n";
+    if std::env::var_os("UPDATE_OPENAPI_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("reading {}: {}", path.display(), e));
+    assert!(
+        actual == expected,
+        "{} is out of date; regenerate it with UPDATE_OPENAPI_SNAPSHOTS=1",
+        path.display()
+    );
+}
+
+async fn send(router: &axum::Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn get(path: &str) -> Request<Body> {
+    Request::get(path).body(Body::empty()).unwrap()
+}
+
+#[test]
+fn test_http_api_matches_snapshot() {
+    check_snapshot("openapi.json", HttpApi::openapi());
+}
+
+#[test]
+fn test_admin_api_matches_snapshot() {
+    check_snapshot("openapi-admin.json", AdminApi::openapi());
+}
+
+#[tokio::test]
+async fn test_spec_is_served_without_a_key() {
+    let keys = ApiKeys::from_entries(vec![ApiKeyEntry {
+        name: "reader".to_string(),
+        hash: ApiKeys::hash_key("reader-key"),
+        scopes: vec![Scope::Read],
+        namespace: None,
+    }])
+    .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let router = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap())
+        .with_auth(Arc::new(keys))
+        .router();
+
+    let (status, spec) = send(&router, get("/openapi.json")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(spec["paths"]["/v1/query"]["post"].is_object());
+    assert_eq!(
+        spec["paths"]["/v1/query"]["post"]["responses"]["default"]["content"]["application/json"]
+            ["schema"]["$ref"],
+        "#/components/schemas/ErrorResponse"
+    );
+
+    // Swagger UI is opt-in
+    assert_eq!(send(&router, get("/docs")).await.0, StatusCode::NOT_FOUND);
+    let dir = tempfile::tempdir().unwrap();
+    let router = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap())
+        .with_http_options(HttpOptions {
+            swagger_ui: true,
+            ..HttpOptions::default()
+        })
+        .router();
+    let response = router.oneshot(get("/docs")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE]
+        .to_str()
+        .unwrap()
+        .starts_with("text/html"));
+
+    // Pinned, integrity-checked assets and a spec URL that survives a path prefix
+    let page = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let page = String::from_utf8(page.to_vec()).unwrap();
+    assert!(!page.contains("swagger-ui-dist@5/"));
+    assert_eq!(page.matches("integrity=\"sha384-").count(), 2);
+    assert!(page.contains("url: \"./openapi.json\""));
+}
+
+#[tokio::test]
+async fn test_framework_errors_use_the_envelope() {
+    let dir = tempfile::tempdir().unwrap();
+    let router = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap()).router();
+
+    let (status, body) = send(&router, get("/v1/no-such-route")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["code"], "not_found");
+
+    let malformed = Request::post("/v1/upsert")
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from("{not json"))
+        .unwrap();
+    let (status, body) = send(&router, malformed).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], "invalid_argument");
+    assert!(body["message"].as_str().unwrap().contains("JSON"));
+}
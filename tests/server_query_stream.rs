@@ -0,0 +1,270 @@
+//! The `/ws/query-stream` protocol over a real socket
+
+#![cfg(feature = "server")]
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use vecstore::server::query_stream::{CLOSE_IDLE, CLOSE_SLOW_CONSUMER};
+use vecstore::server::{EventStream, StreamLimits, VecStoreHttpServer};
+use vecstore::{Metadata, VecStore};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+fn metadata() -> Metadata {
+    Metadata {
+        fields: Default::default(),
+    }
+}
+
+/// Serve `server` on a local port and connect to its query stream
+async fn connect(server: VecStoreHttpServer) -> Client {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = server.router();
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    let url = format!("ws://{}/ws/query-stream", addr);
+    tokio_tungstenite::connect_async(url).await.unwrap().0
+}
+
+async fn send(client: &mut Client, message: Value) {
+    client
+        .send(Message::text(message.to_string()))
+        .await
+        .unwrap();
+}
+
+/// The next text message, skipping pings
+async fn receive(client: &mut Client) -> Value {
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(10), client.next())
+            .await
+            .expect("no message within 10s")
+            .unwrap()
+            .unwrap();
+        match message {
+            Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+            Message::Ping(_) | Message::Pong(_) => {}
+            other => panic!("expected a text message, got {:?}", other),
+        }
+    }
+}
+
+/// Read until the server closes the connection and return its close code
+async fn close_code(client: &mut Client) -> u16 {
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(10), client.next())
+            .await
+            .expect("not closed within 10s")
+            .expect("closed without a close frame")
+            .unwrap();
+        if let Message::Close(frame) = message {
+            return frame.expect("close frame without a code").code.into();
+        }
+    }
+}
+
+fn store_with(dir: &std::path::Path, records: &[(&str, [f32; 2])]) -> VecStore {
+    let mut store = VecStore::open(dir).unwrap();
+    for (id, vector) in records {
+        store
+            .upsert(id.to_string(), vector.to_vec(), metadata())
+            .unwrap();
+    }
+    store
+}
+
+#[tokio::test]
+async fn test_queries_and_errors_name_their_id() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = store_with(
+        dir.path(),
+        &[
+            ("east", [1.0, 0.0]),
+            ("north", [0.0, 1.0]),
+            ("west", [-1.0, 0.0]),
+        ],
+    );
+    let mut client = connect(VecStoreHttpServer::new(store)).await;
+
+    send(
+        &mut client,
+        json!({"type": "query", "id": "q1", "vector": [0.9, 0.1], "limit": 2}),
+    )
+    .await;
+    let first = receive(&mut client).await;
+    assert_eq!(first["type"], "result");
+    assert_eq!(first["id"], "q1");
+    assert_eq!(first["result"]["id"], "east");
+    assert_eq!(receive(&mut client).await["type"], "result");
+    let complete = receive(&mut client).await;
+    assert_eq!(complete["type"], "complete");
+    assert_eq!(complete["stats"]["total_candidates"], 2);
+
+    client.send(Message::text("not json")).await.unwrap();
+    let invalid = receive(&mut client).await;
+    assert_eq!(invalid["type"], "error");
+    assert_eq!(invalid["code"], "invalid_argument");
+    assert!(invalid.get("id").is_none());
+
+    send(&mut client, json!({"type": "cancel", "id": "nope"})).await;
+    let unknown = receive(&mut client).await;
+    assert_eq!(unknown["code"], "not_found");
+    assert_eq!(unknown["id"], "nope");
+
+    // Subscriptions read change events, which this server doesn't publish
+    send(
+        &mut client,
+        json!({"type": "subscribe", "id": "s1", "vector": [1.0, 0.0], "limit": 2}),
+    )
+    .await;
+    assert_eq!(receive(&mut client).await["code"], "unimplemented");
+
+    send(&mut client, json!({"type": "ping"})).await;
+    assert_eq!(receive(&mut client).await, json!({"type": "pong"}));
+}
+
+#[tokio::test]
+async fn test_subscription_reports_upserts_entering_its_top_k() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = store_with(dir.path(), &[("east", [1.0, 0.0]), ("north", [0.0, 1.0])]);
+    let events = EventStream::attach(&mut store);
+    let server = VecStoreHttpServer::new(store)
+        .with_events(events)
+        .with_stream_limits(StreamLimits {
+            max_subscriptions: 1,
+            ..StreamLimits::default()
+        });
+    let shared = server.store();
+    let mut client = connect(server).await;
+
+    send(
+        &mut client,
+        json!({"type": "subscribe", "id": "s1", "vector": [1.0, 0.0], "limit": 2}),
+    )
+    .await;
+    let subscribed = receive(&mut client).await;
+    assert_eq!(subscribed["type"], "subscribed");
+    assert_eq!(subscribed["results"].as_array().unwrap().len(), 2);
+
+    send(
+        &mut client,
+        json!({"type": "subscribe", "id": "s2", "vector": [0.0, 1.0], "limit": 2}),
+    )
+    .await;
+    let refused = receive(&mut client).await;
+    assert_eq!(refused["code"], "too_many_queries");
+    assert_eq!(refused["details"]["limit"], 1);
+
+    shared
+        .write()
+        .await
+        .upsert("northeast".into(), vec![0.9, 0.1], metadata())
+        .unwrap();
+    let matched = receive(&mut client).await;
+    assert_eq!(matched["type"], "match");
+    assert_eq!(matched["id"], "s1");
+    assert_eq!(matched["result"]["id"], "northeast");
+    assert!(matched["rank"].as_u64().unwrap() <= 2);
+
+    // Outside the top 2, so nothing to report before the pong
+    shared
+        .write()
+        .await
+        .upsert("west".into(), vec![-1.0, 0.0], metadata())
+        .unwrap();
+    send(&mut client, json!({"type": "ping"})).await;
+    assert_eq!(receive(&mut client).await["type"], "pong");
+
+    send(&mut client, json!({"type": "cancel", "id": "s1"})).await;
+    assert_eq!(
+        receive(&mut client).await,
+        json!({"type": "cancelled", "id": "s1"})
+    );
+    shared
+        .write()
+        .await
+        .upsert("due-east".into(), vec![1.0, 0.01], metadata())
+        .unwrap();
+    send(&mut client, json!({"type": "ping"})).await;
+    assert_eq!(receive(&mut client).await["type"], "pong");
+}
+
+#[tokio::test]
+async fn test_pongs_keep_a_connection_open_past_the_idle_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let limits = StreamLimits {
+        ping_interval: Duration::from_millis(50),
+        idle_timeout: Duration::from_millis(200),
+        ..StreamLimits::default()
+    };
+    let server = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap());
+    let mut client = connect(server.with_stream_limits(limits)).await;
+
+    // Reading answers the server's pings
+    let mut pings = 0;
+    let reading = tokio::time::sleep(Duration::from_millis(500));
+    tokio::pin!(reading);
+    loop {
+        tokio::select! {
+            message = client.next() => match message.unwrap().unwrap() {
+                Message::Ping(_) => pings += 1,
+                other => panic!("expected a ping, got {:?}", other),
+            },
+            _ = &mut reading => break,
+        }
+    }
+    assert!(pings >= 5, "{} pings", pings);
+    send(&mut client, json!({"type": "ping"})).await;
+    assert_eq!(receive(&mut client).await["type"], "pong");
+}
+
+#[tokio::test]
+async fn test_quiet_connection_is_closed_after_idle_timeout() {
+    let dir = tempfile::tempdir().unwrap();
+    let limits = StreamLimits {
+        idle_timeout: Duration::from_millis(200),
+        ..StreamLimits::default()
+    };
+    let server = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap());
+    let mut client = connect(server.with_stream_limits(limits)).await;
+
+    tokio::time::sleep(Duration::from_millis(400)).await;
+    assert_eq!(close_code(&mut client).await, CLOSE_IDLE);
+}
+
+#[tokio::test]
+async fn test_slow_consumer_is_closed_instead_of_buffered() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut store = store_with(dir.path(), &[("east", [1.0, 0.0])]);
+    let events = EventStream::attach(&mut store);
+    let server = VecStoreHttpServer::new(store)
+        .with_events(events)
+        .with_stream_limits(StreamLimits {
+            send_buffer: 1,
+            ..StreamLimits::default()
+        });
+    let shared = server.store();
+    let mut client = connect(server).await;
+
+    send(
+        &mut client,
+        json!({"type": "subscribe", "id": "s1", "vector": [1.0, 0.0], "limit": 10}),
+    )
+    .await;
+    assert_eq!(receive(&mut client).await["type"], "subscribed");
+
+    // Five matches at once overflow a one-message queue
+    {
+        let mut store = shared.write().await;
+        for i in 0..5 {
+            store
+                .upsert(format!("doc{}", i), vec![1.0, i as f32 / 10.0], metadata())
+                .unwrap();
+        }
+    }
+    assert_eq!(close_code(&mut client).await, CLOSE_SLOW_CONSUMER);
+}
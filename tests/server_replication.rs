@@ -0,0 +1,179 @@
+//! Read-only replicas polling a primary's `/admin/replication` endpoints
+
+#![cfg(feature = "server")]
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower::ServiceExt;
+use vecstore::server::auth::ApiKeyEntry;
+use vecstore::server::types::pb::vec_store_service_server::VecStoreService;
+use vecstore::server::types::pb::UpsertRequest;
+use vecstore::server::{ApiKeys, Replica, Scope, VecStoreGrpcServer, VecStoreHttpServer};
+use vecstore::{Metadata, VecStore};
+
+async fn send(router: &axum::Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn post(path: &str, body: Value) -> Request<Body> {
+    Request::post(path)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn upsert(id: &str, vector: [f32; 2]) -> Request<Body> {
+    post(
+        "/v1/upsert",
+        json!({"id": id, "vector": vector, "metadata": {}}),
+    )
+}
+
+/// Serve `router` on a local port and return its URL
+async fn serve(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    format!("http://{}", addr)
+}
+
+fn open_shared(path: std::path::PathBuf) -> Arc<RwLock<VecStore>> {
+    Arc::new(RwLock::new(VecStore::open(path).unwrap()))
+}
+
+#[tokio::test]
+async fn test_replica_follows_primary_and_refuses_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let primary = VecStoreHttpServer::new(VecStore::open(dir.path().join("primary")).unwrap());
+    let primary_store = primary.store();
+    let primary_router = primary.router();
+    for (id, vector) in [("cat", [1.0, 0.0]), ("dog", [0.0, 1.0])] {
+        assert_eq!(
+            send(&primary_router, upsert(id, vector)).await.0,
+            StatusCode::OK
+        );
+    }
+    let url = serve(primary_router.clone()).await;
+
+    let store = open_shared(dir.path().join("replica"));
+    store
+        .write()
+        .await
+        .upsert(
+            "stale".into(),
+            vec![1.0, 1.0],
+            Metadata {
+                fields: Default::default(),
+            },
+        )
+        .unwrap();
+    let replica = Arc::new(Replica::new(url.clone(), store.clone()));
+    assert!(replica.sync().await.unwrap());
+    assert!(!replica.sync().await.unwrap(), "nothing changed");
+
+    let router = VecStoreHttpServer::with_store(store.clone())
+        .with_replica(replica.clone())
+        .router();
+    let query = json!({"vector": [0.1, 0.9], "limit": 5});
+    let (status, body) = send(&router, post("/v1/query", query.clone())).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let ids: Vec<&str> = body["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, ["dog", "cat"]);
+
+    let (status, body) = send(&router, upsert("bird", [0.5, 0.5])).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["code"], "read_only_replica");
+    assert_eq!(body["details"]["primary"], url.as_str());
+    assert!(body["message"].as_str().unwrap().contains(&url));
+
+    let seq = primary_store.read().await.op_seq();
+    let request = Request::get("/health").body(Body::empty()).unwrap();
+    let (status, body) = send(&router, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["replication"]["applied_seq"], seq);
+    assert_eq!(body["replication"]["lag_operations"], 0);
+
+    // A write on the primary shows up after the next poll
+    assert_eq!(
+        send(&primary_router, upsert("bird", [0.5, 0.5])).await.0,
+        StatusCode::OK
+    );
+    assert!(replica.sync().await.unwrap());
+    let (_, body) = send(&router, post("/v1/query", query)).await;
+    assert_eq!(body["results"].as_array().unwrap().len(), 3);
+    assert_eq!(
+        replica.status().applied_seq,
+        primary_store.read().await.op_seq()
+    );
+
+    let request = Request::get("/metrics").body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let scrape = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let scrape = String::from_utf8(scrape.to_vec()).unwrap();
+    assert!(scrape.contains("vecstore_replication_lag_operations 0"));
+    assert!(scrape.contains("vecstore_replication_syncs_total{outcome=\"applied\"}"));
+}
+
+#[tokio::test]
+async fn test_replica_refuses_grpc_writes() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open_shared(dir.path().to_path_buf());
+    let replica = Arc::new(Replica::new("http://primary:8080", store.clone()));
+    let service = VecStoreGrpcServer::with_store(store).with_replica(replica);
+
+    let status = service
+        .upsert(tonic::Request::new(UpsertRequest {
+            id: "a".to_string(),
+            vector: vec![1.0, 0.0],
+            ..Default::default()
+        }))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+    assert!(status.message().contains("http://primary:8080"));
+    assert_eq!(
+        status.metadata().get("x-vecstore-error-code").unwrap(),
+        "read_only_replica"
+    );
+}
+
+#[tokio::test]
+async fn test_replica_presents_an_admin_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let keys = ApiKeys::from_entries(vec![ApiKeyEntry {
+        name: "replicator".to_string(),
+        hash: ApiKeys::hash_key("replicator-key"),
+        scopes: vec![Scope::Admin],
+        namespace: None,
+    }])
+    .unwrap();
+    let primary = VecStoreHttpServer::new(VecStore::open(dir.path().join("primary")).unwrap())
+        .with_auth(Arc::new(keys));
+    let url = serve(primary.router()).await;
+
+    let store = open_shared(dir.path().join("replica"));
+    let replica = Replica::new(url.clone(), store.clone());
+    assert!(replica.sync().await.is_err());
+    let status = replica.status();
+    assert!(status.last_error.unwrap().contains("401"));
+    assert!(status.last_applied_at.is_none());
+
+    let replica = Replica::new(url, store).with_api_key(Some("replicator-key".to_string()));
+    assert!(replica.sync().await.unwrap());
+    assert!(replica.status().last_error.is_none());
+}
@@ -0,0 +1,96 @@
+//! Request ids in response headers and error bodies
+
+#![cfg(feature = "server")]
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tower::ServiceExt;
+use vecstore::server::request_id::REQUEST_ID_HEADER;
+use vecstore::server::{AdminHttpServer, VecStoreHttpServer};
+use vecstore::{NamespaceManager, VecStore};
+
+async fn send(router: &axum::Router, request: Request<Body>) -> (StatusCode, String, Value) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let id = response.headers()[REQUEST_ID_HEADER]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (
+        status,
+        id,
+        serde_json::from_slice(&body).unwrap_or(Value::Null),
+    )
+}
+
+#[tokio::test]
+async fn test_request_id_round_trips_into_error_bodies() {
+    let dir = tempfile::tempdir().unwrap();
+    let router = VecStoreHttpServer::new(VecStore::open(dir.path()).unwrap()).router();
+
+    let request = Request::get("/v1/stats")
+        .header(REQUEST_ID_HEADER, "abc-123")
+        .body(Body::empty())
+        .unwrap();
+    let (status, id, body) = send(&router, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(id, "abc-123");
+    assert!(body.get("request_id").is_none());
+
+    // An error from a handler
+    let request = Request::post("/v1/query")
+        .header("content-type", "application/json")
+        .header(REQUEST_ID_HEADER, "abc-124")
+        .body(Body::from(
+            r#"{"vector": [1.0], "limit": 1, "filter": "tag = "}"#,
+        ))
+        .unwrap();
+    let (status, id, body) = send(&router, request).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(id, "abc-124");
+    assert_eq!(body["code"], "invalid_argument");
+    assert_eq!(body["request_id"], "abc-124");
+
+    // A plain-text rejection rewritten into the envelope
+    let request = Request::post("/v1/query")
+        .header("content-type", "application/json")
+        .header(REQUEST_ID_HEADER, "abc-125")
+        .body(Body::from("{not json"))
+        .unwrap();
+    let (status, _, body) = send(&router, request).await;
+    assert!(status.is_client_error());
+    assert_eq!(body["code"], "invalid_argument");
+    assert_eq!(body["request_id"], "abc-125");
+
+    // Unknown routes too, with an id the server picked
+    let request = Request::get("/nowhere").body(Body::empty()).unwrap();
+    let (status, id, body) = send(&router, request).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(id.len(), 32);
+    assert_eq!(body["request_id"], id.as_str());
+}
+
+#[tokio::test]
+async fn test_traceparent_names_the_request() {
+    let dir = tempfile::tempdir().unwrap();
+    let manager = NamespaceManager::new(dir.path()).unwrap();
+    let router = AdminHttpServer::new(Arc::new(RwLock::new(manager))).router();
+
+    let request = Request::get("/admin/namespaces/absent")
+        .header(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        )
+        .body(Body::empty())
+        .unwrap();
+    let (status, id, body) = send(&router, request).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(id, "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(body["request_id"], id.as_str());
+}
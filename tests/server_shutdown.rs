@@ -0,0 +1,59 @@
+//! Graceful shutdown: requests in flight finish and the store is saved
+
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+use vecstore::server::{serve_until_shutdown, Shutdown, VecStoreHttpServer};
+use vecstore::VecStore;
+
+#[tokio::test]
+async fn test_in_flight_request_finishes_and_store_reopens() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(RwLock::new(VecStore::open(dir.path()).unwrap()));
+    let router = VecStoreHttpServer::with_store(store.clone()).router();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let shutdown = Shutdown::new();
+    let stopped = shutdown.signalled();
+    let server = tokio::spawn(async move {
+        axum::serve(listener, router)
+            .with_graceful_shutdown(stopped)
+            .await?;
+        Ok(())
+    });
+
+    // Hold the write lock so the upsert is still in flight when shutdown starts
+    let guard = store.write().await;
+    let mut client = TcpStream::connect(addr).await.unwrap();
+    let body = r#"{"id": "a", "vector": [1.0, 0.0], "metadata": {}}"#;
+    let request = format!(
+        "POST /v1/upsert HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    client.write_all(request.as_bytes()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    shutdown.trigger();
+    drop(guard);
+
+    let mut response = String::new();
+    client.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+
+    serve_until_shutdown(vec![server], &shutdown, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert!(TcpStream::connect(addr).await.is_err());
+
+    // What vecstore-server does once the servers have drained
+    store.write().await.save().unwrap();
+    drop(store);
+
+    let reopened = VecStore::open(dir.path()).unwrap();
+    assert_eq!(reopened.get("a").unwrap().vector, vec![1.0, 0.0]);
+}
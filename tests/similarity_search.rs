@@ -17,6 +17,7 @@ fn test_exact_match_similarity() {
         vector: vec,
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -53,6 +54,7 @@ fn test_similarity_ordering() {
         vector: vec![1.0, 0.0, 0.0],
         k: 4,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -90,6 +92,7 @@ fn test_opposite_vectors() {
         vector: vec![1.0, 0.0, 0.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -128,6 +131,7 @@ fn test_orthogonal_vectors() {
         vector: vec![1.0, 0.0, 0.0],
         k: 3,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -173,6 +177,7 @@ fn test_normalized_vs_unnormalized() {
         vector: vec![1.0, 0.0, 0.0],
         k: 2,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -205,6 +210,7 @@ fn test_similarity_with_many_vectors() {
         vector: vec![1.0, 0.0, 0.0],
         k: 10,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -238,6 +244,7 @@ fn test_query_with_k_equals_one() {
         vector: vec![5.0, 0.0, 0.0],
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -272,6 +279,7 @@ fn test_incremental_similarity() {
         vector: query_vec,
         k: 10,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -301,6 +309,7 @@ fn test_similarity_after_updates() {
         vector: vec![1.0, 0.0, 0.0],
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results1 = store.query(query.clone()).unwrap();
@@ -363,6 +372,7 @@ fn test_three_dimensional_similarity() {
         vector: vec![0.5, 0.5, 0.5],
         k: 8,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
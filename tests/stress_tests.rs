@@ -58,6 +58,7 @@ fn test_large_dataset_query_performance() {
         vector: vec![0.5, 0.5, 0.5],
         k: 100,
         filter: None,
+        ..Default::default()
     };
 
     let start = std::time::Instant::now();
@@ -101,6 +102,7 @@ fn test_concurrent_reads() {
                     vector: vec![thread_id as f32, 0.0, 0.0],
                     k: 10,
                     filter: None,
+                    ..Default::default()
                 };
 
                 let store = store_clone.lock().unwrap();
@@ -214,6 +216,7 @@ fn test_concurrent_mixed_operations() {
                     vector: vec![thread_id as f32, 0.0, 0.0],
                     k: 10,
                     filter: None,
+                    ..Default::default()
                 };
                 let _ = store.query(query);
             }
@@ -285,6 +288,7 @@ fn test_high_dimensional_vectors() {
         vector: query_vec,
         k: 10,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -358,6 +362,7 @@ fn test_query_with_large_k() {
         vector: vec![50.0, 0.0, 0.0],
         k: 1000,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -394,6 +399,7 @@ fn test_extreme_vector_values() {
         vector: vec![1.0, 1.0, 1.0],
         k: 3,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query);
@@ -473,6 +479,7 @@ fn test_memory_efficiency_many_vectors() {
         vector: vec![50.0],
         k: 10,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -518,6 +525,7 @@ fn test_very_long_ids() {
         vector: vec![1.0, 2.0, 3.0],
         k: 1,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -547,6 +555,7 @@ fn test_stress_test_mixed_workload() {
             vector: vec![50.0, 0.0, 0.0],
             k: 10,
             filter: None,
+            ..Default::default()
         };
         store.query(query).unwrap();
     }
@@ -572,6 +581,7 @@ fn test_stress_test_mixed_workload() {
         vector: vec![25.0, 0.0, 0.0],
         k: 20,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
@@ -624,6 +634,7 @@ fn test_identical_vectors_different_ids() {
         vector: same_vector,
         k: 10,
         filter: None,
+        ..Default::default()
     };
 
     let results = store.query(query).unwrap();
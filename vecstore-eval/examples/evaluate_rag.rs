@@ -116,6 +116,7 @@ fn main() -> Result<()> {
              has gained popularity due to its performance and safety guarantees."
                 .to_string(),
         ),
+        ..Default::default()
     };
 
     let report = evaluator.evaluate(&test_case)?;
@@ -144,6 +145,7 @@ fn main() -> Result<()> {
                  manage memory safely."
                     .to_string(),
             ),
+            ..Default::default()
         },
         EvaluationInput {
             query: "What are Rust's key features?".to_string(),
@@ -158,6 +160,7 @@ fn main() -> Result<()> {
                  and fearless concurrency."
                     .to_string(),
             ),
+            ..Default::default()
         },
         EvaluationInput {
             query: "Is Rust suitable for web development?".to_string(),
@@ -174,6 +177,7 @@ fn main() -> Result<()> {
                  Actix and Rocket."
                     .to_string(),
             ),
+            ..Default::default()
         },
     ];
 
@@ -0,0 +1,291 @@
+//! Answer Relevance via Question Regeneration
+//!
+//! The RAGAS answer-relevancy approach: if an answer really addresses the
+//! query, questions reverse-engineered from the answer should be close to
+//! the original query in embedding space.
+
+use crate::faithfulness::parse_claims;
+use crate::metrics::{parse_relevance, AnswerCorrectness, Embedder, GenerationParams, LLM};
+use crate::prompt::PromptTemplate;
+use crate::types::{EvaluationInput, Metric, MetricResult};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// Default prompt for generating questions from an answer
+///
+/// Placeholders: `{n}`, `{answer}`, `{context}`.
+pub const DEFAULT_QUESTION_PROMPT: &str = "Answer: {answer}\n\n\
+Write {n} different questions that this answer would be a good response to. \
+Write one question per line with no numbering or commentary.";
+
+/// Default prompt for detecting evasive answers
+///
+/// Placeholders: `{query}`, `{answer}`.
+pub const DEFAULT_NONCOMMITTAL_PROMPT: &str = "Question: {query}\n\nAnswer: {answer}\n\n\
+Is the answer non-committal, evasive or a refusal (for example \"I don't know\" or \"I cannot answer that\")? \
+Answer only 'Yes' or 'No'.";
+
+const QUESTION_PLACEHOLDERS: &[&str] = &["n", "answer", "context"];
+const NONCOMMITTAL_PLACEHOLDERS: &[&str] = &["query", "answer"];
+
+/// Mean similarity between the query and questions generated from the answer
+///
+/// Requires both an [`LLM`] (to generate questions and detect non-committal
+/// answers) and an [`Embedder`]. Non-committal answers score 0.0. Details
+/// contain `generated_questions`, their `similarities` and `noncommittal`.
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{AnswerRelevance, EvaluationInput, Metric};
+/// # struct MyLLM;
+/// # impl vecstore_eval::LLM for MyLLM {
+/// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok("What is Rust?".to_string()) }
+/// # }
+/// # struct MyEmbedder;
+/// # impl vecstore_eval::Embedder for MyEmbedder {
+/// #     fn embed(&self, _: &str) -> anyhow::Result<Vec<f32>> { Ok(vec![1.0, 0.0]) }
+/// # }
+///
+/// let metric = AnswerRelevance::new(Box::new(MyLLM), Box::new(MyEmbedder)).with_num_questions(5);
+///
+/// let input = EvaluationInput {
+///     query: "What is Rust?".to_string(),
+///     answer: Some("Rust is a systems programming language.".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let result = metric.evaluate(&input)?;
+/// println!("{}", result.details["generated_questions"]);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct AnswerRelevance {
+    llm: Box<dyn LLM>,
+    embedder: Box<dyn Embedder>,
+    num_questions: usize,
+    question_prompt: PromptTemplate,
+    noncommittal_prompt: PromptTemplate,
+    params: GenerationParams,
+}
+
+impl AnswerRelevance {
+    /// Create the metric, generating 3 questions per answer
+    pub fn new(llm: Box<dyn LLM>, embedder: Box<dyn Embedder>) -> Self {
+        Self {
+            llm,
+            embedder,
+            num_questions: 3,
+            question_prompt: PromptTemplate::new(DEFAULT_QUESTION_PROMPT),
+            noncommittal_prompt: PromptTemplate::new(DEFAULT_NONCOMMITTAL_PROMPT),
+            params: GenerationParams::deterministic(),
+        }
+    }
+
+    /// Set how many questions to generate (minimum 1)
+    pub fn with_num_questions(mut self, n: usize) -> Self {
+        self.num_questions = n.max(1);
+        self
+    }
+
+    /// Override the question generation prompt (`{n}`, `{answer}`, `{context}`)
+    pub fn with_question_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("question generation", QUESTION_PLACEHOLDERS)?;
+        self.question_prompt = template;
+        Ok(self)
+    }
+
+    /// Override the non-committal detection prompt (`{query}`, `{answer}`)
+    pub fn with_noncommittal_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("non-committal detection", NONCOMMITTAL_PLACEHOLDERS)?;
+        self.noncommittal_prompt = template;
+        Ok(self)
+    }
+
+    /// Override the sampling params (default: temperature 0)
+    pub fn with_generation_params(mut self, params: GenerationParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    fn is_noncommittal(&self, query: &str, answer: &str) -> Result<bool> {
+        let prompt = self
+            .noncommittal_prompt
+            .render(&[("query", query), ("answer", answer)]);
+
+        Ok(parse_relevance(
+            &self.llm.generate_with_params(&prompt, &self.params)?,
+        ))
+    }
+
+    fn generate_questions(&self, answer: &str, context: &str) -> Result<Vec<String>> {
+        let prompt = self.question_prompt.render(&[
+            ("n", &self.num_questions.to_string()),
+            ("answer", answer),
+            ("context", context),
+        ]);
+
+        let mut questions = parse_claims(&self.llm.generate_with_params(&prompt, &self.params)?);
+        questions.truncate(self.num_questions);
+        Ok(questions)
+    }
+}
+
+impl Metric for AnswerRelevance {
+    fn name(&self) -> &str {
+        "answer_relevance"
+    }
+
+    fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let answer = input
+            .answer
+            .as_ref()
+            .ok_or_else(|| anyhow!("Answer required for answer relevance metric"))?;
+
+        let mut details = HashMap::new();
+
+        if self.is_noncommittal(&input.query, answer)? {
+            details.insert("noncommittal".to_string(), serde_json::json!(true));
+            details.insert(
+                "generated_questions".to_string(),
+                serde_json::json!(Vec::<String>::new()),
+            );
+            return Ok(self.params.record(MetricResult {
+                metric_name: self.name().to_string(),
+                score: 0.0,
+                details,
+                ..Default::default()
+            }));
+        }
+
+        let questions = self.generate_questions(answer, &input.contexts.join("\n\n"))?;
+        if questions.is_empty() {
+            return Err(anyhow!("LLM generated no questions for answer relevance"));
+        }
+
+        let query_embedding = self.embedder.embed(&input.query)?;
+        let similarities = questions
+            .iter()
+            .map(|question| {
+                let embedding = self.embedder.embed(question)?;
+                Ok(AnswerCorrectness::cosine_similarity(
+                    &query_embedding,
+                    &embedding,
+                ))
+            })
+            .collect::<Result<Vec<f32>>>()?;
+
+        let mean = similarities.iter().sum::<f32>() / similarities.len() as f32;
+        let score = mean.clamp(0.0, 1.0);
+
+        details.insert("noncommittal".to_string(), serde_json::json!(false));
+        details.insert(
+            "generated_questions".to_string(),
+            serde_json::json!(questions),
+        );
+        details.insert("similarities".to_string(), serde_json::json!(similarities));
+
+        Ok(self.params.record(MetricResult {
+            metric_name: self.name().to_string(),
+            score,
+            details,
+            ..Default::default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct QuestionLLM {
+        noncommittal: bool,
+    }
+
+    impl LLM for QuestionLLM {
+        fn generate(&self, prompt: &str) -> Result<String> {
+            if prompt.contains("non-committal") {
+                let verdict = if self.noncommittal { "Yes" } else { "No" };
+                return Ok(verdict.to_string());
+            }
+            Ok("1. What is Rust?\n2. What kind of language is Rust?\n3. Who uses Go?\n4. Extra?"
+                .to_string())
+        }
+    }
+
+    /// Embeds questions mentioning Rust along one axis, everything else along another
+    struct TopicEmbedder;
+
+    impl Embedder for TopicEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            if text.contains("Rust") {
+                Ok(vec![1.0, 0.0])
+            } else {
+                Ok(vec![0.0, 1.0])
+            }
+        }
+    }
+
+    fn input(answer: &str) -> EvaluationInput {
+        EvaluationInput {
+            query: "What is Rust?".to_string(),
+            answer: Some(answer.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_answer_relevance_mean_similarity() {
+        let metric = AnswerRelevance::new(
+            Box::new(QuestionLLM { noncommittal: false }),
+            Box::new(TopicEmbedder),
+        );
+
+        let result = metric.evaluate(&input("Rust is a systems language.")).unwrap();
+
+        // Two of the three questions match the query's topic
+        assert!((result.score - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(result.details["generated_questions"].as_array().unwrap().len(), 3);
+        assert_eq!(result.details["generated_questions"][0], "What is Rust?");
+        assert_eq!(result.details["noncommittal"], false);
+    }
+
+    #[test]
+    fn test_num_questions() {
+        let metric = AnswerRelevance::new(
+            Box::new(QuestionLLM { noncommittal: false }),
+            Box::new(TopicEmbedder),
+        )
+        .with_num_questions(2);
+
+        let result = metric.evaluate(&input("Rust is a systems language.")).unwrap();
+        assert_eq!(result.score, 1.0);
+        assert_eq!(result.details["similarities"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_noncommittal_scores_zero() {
+        let metric = AnswerRelevance::new(
+            Box::new(QuestionLLM { noncommittal: true }),
+            Box::new(TopicEmbedder),
+        );
+
+        let result = metric.evaluate(&input("I cannot answer that.")).unwrap();
+        assert_eq!(result.score, 0.0);
+        assert_eq!(result.details["noncommittal"], true);
+    }
+
+    #[test]
+    fn test_requires_answer() {
+        let metric = AnswerRelevance::new(
+            Box::new(QuestionLLM { noncommittal: false }),
+            Box::new(TopicEmbedder),
+        );
+        let input = EvaluationInput {
+            query: "q".to_string(),
+            ..Default::default()
+        };
+        assert!(metric.evaluate(&input).is_err());
+    }
+}
@@ -0,0 +1,566 @@
+//! Async evaluation (feature `async`)
+//!
+//! Real judges and embedding models are remote HTTP services, so the async
+//! path lets [`AsyncContextRelevance`] judge contexts concurrently and lets
+//! [`AsyncEvaluator`] run independent metrics in parallel.
+//!
+//! Existing synchronous [`LLM`], [`Embedder`] and [`Metric`] implementations
+//! plug in through [`BlockingLLM`], [`BlockingEmbedder`] and [`BlockingMetric`],
+//! which run the sync call on tokio's blocking thread pool.
+
+use crate::evaluator::{build_report, evaluate_with_usage};
+use crate::metrics::{
+    correctness_result, faithfulness_result, parse_faithfulness_score, parse_relevance,
+    relevance_result, unparseable_score_error, Embedder, DEFAULT_FAITHFULNESS_PROMPT,
+    DEFAULT_RELEVANCE_PROMPT, DEFAULT_SCORE_RETRIES, FAITHFULNESS_PLACEHOLDERS, LLM,
+    RELEVANCE_PLACEHOLDERS, SCORE_RETRY_SUFFIX,
+};
+use crate::prompt::PromptTemplate;
+use crate::types::{EvaluationInput, EvaluationReport, Metric, MetricResult};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Default number of concurrent judge calls per metric
+const DEFAULT_CONCURRENCY: usize = 4;
+
+// ============================================================================
+// Trait Definitions
+// ============================================================================
+
+/// Async counterpart of [`LLM`]
+#[async_trait]
+pub trait AsyncLLM: Send + Sync {
+    /// Generate text from a prompt
+    async fn generate(&self, prompt: &str) -> Result<String>;
+}
+
+/// Async counterpart of [`Embedder`]
+#[async_trait]
+pub trait AsyncEmbedder: Send + Sync {
+    /// Embed text into a vector
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// Async counterpart of [`Metric`]
+#[async_trait]
+pub trait AsyncMetric: Send + Sync {
+    /// Name of this metric
+    fn name(&self) -> &str;
+
+    /// Evaluate the metric on the given input
+    async fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult>;
+}
+
+// ============================================================================
+// Sync Adapters
+// ============================================================================
+
+/// Runs a synchronous [`LLM`] on the blocking thread pool
+pub struct BlockingLLM {
+    inner: Arc<dyn LLM>,
+}
+
+impl BlockingLLM {
+    /// Wrap a synchronous LLM
+    pub fn new(llm: impl LLM + 'static) -> Self {
+        Self {
+            inner: Arc::new(llm),
+        }
+    }
+
+    /// Wrap a shared synchronous LLM
+    pub fn from_arc(llm: Arc<dyn LLM>) -> Self {
+        Self { inner: llm }
+    }
+}
+
+#[async_trait]
+impl AsyncLLM for BlockingLLM {
+    async fn generate(&self, prompt: &str) -> Result<String> {
+        let inner = Arc::clone(&self.inner);
+        let prompt = prompt.to_string();
+        tokio::task::spawn_blocking(move || inner.generate(&prompt))
+            .await
+            .map_err(|e| anyhow!("LLM task failed: {}", e))?
+    }
+}
+
+/// Runs a synchronous [`Embedder`] on the blocking thread pool
+pub struct BlockingEmbedder {
+    inner: Arc<dyn Embedder>,
+}
+
+impl BlockingEmbedder {
+    /// Wrap a synchronous embedder
+    pub fn new(embedder: impl Embedder + 'static) -> Self {
+        Self {
+            inner: Arc::new(embedder),
+        }
+    }
+
+    /// Wrap a shared synchronous embedder
+    pub fn from_arc(embedder: Arc<dyn Embedder>) -> Self {
+        Self { inner: embedder }
+    }
+}
+
+#[async_trait]
+impl AsyncEmbedder for BlockingEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let inner = Arc::clone(&self.inner);
+        let text = text.to_string();
+        tokio::task::spawn_blocking(move || inner.embed(&text))
+            .await
+            .map_err(|e| anyhow!("Embedder task failed: {}", e))?
+    }
+}
+
+/// Runs a synchronous [`Metric`] on the blocking thread pool
+pub struct BlockingMetric {
+    inner: Arc<dyn Metric>,
+    name: String,
+}
+
+impl BlockingMetric {
+    /// Wrap a synchronous metric
+    pub fn new(metric: impl Metric + 'static) -> Self {
+        Self::from_arc(Arc::new(metric))
+    }
+
+    /// Wrap a shared synchronous metric
+    pub fn from_arc(metric: Arc<dyn Metric>) -> Self {
+        let name = metric.name().to_string();
+        Self {
+            inner: metric,
+            name,
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncMetric for BlockingMetric {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let inner = Arc::clone(&self.inner);
+        let input = input.clone();
+        tokio::task::spawn_blocking(move || evaluate_with_usage(inner.as_ref(), &input))
+            .await
+            .map_err(|e| anyhow!("Metric task failed: {}", e))?
+    }
+}
+
+// ============================================================================
+// Async Metrics
+// ============================================================================
+
+/// Async [`ContextRelevance`](crate::ContextRelevance) that judges contexts concurrently
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{AsyncContextRelevance, AsyncMetric, BlockingLLM, EvaluationInput};
+/// # struct MyLLM;
+/// # impl vecstore_eval::LLM for MyLLM {
+/// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok("Yes".to_string()) }
+/// # }
+/// # async fn run() -> anyhow::Result<()> {
+/// let metric = AsyncContextRelevance::new(Box::new(BlockingLLM::new(MyLLM)))
+///     .with_concurrency(8);
+///
+/// let input = EvaluationInput {
+///     query: "What is Rust?".to_string(),
+///     contexts: vec!["Rust is a systems programming language.".to_string()],
+///     ..Default::default()
+/// };
+///
+/// let result = metric.evaluate(&input).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncContextRelevance {
+    llm: Box<dyn AsyncLLM>,
+    concurrency: usize,
+    prompt: PromptTemplate,
+}
+
+impl AsyncContextRelevance {
+    /// Create a new async context relevance metric
+    pub fn new(llm: Box<dyn AsyncLLM>) -> Self {
+        Self {
+            llm,
+            concurrency: DEFAULT_CONCURRENCY,
+            prompt: PromptTemplate::new(DEFAULT_RELEVANCE_PROMPT),
+        }
+    }
+
+    /// Replace the judge prompt (`{query}`, `{context}`)
+    pub fn with_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("context_relevance", RELEVANCE_PLACEHOLDERS)?;
+        self.prompt = template;
+        Ok(self)
+    }
+
+    /// Set the maximum number of contexts judged at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    async fn is_relevant(&self, query: &str, context: &str) -> Result<bool> {
+        let prompt = self.prompt.render(&[("query", query), ("context", context)]);
+        let response = self.llm.generate(&prompt).await?;
+        Ok(parse_relevance(&response))
+    }
+}
+
+#[async_trait]
+impl AsyncMetric for AsyncContextRelevance {
+    fn name(&self) -> &str {
+        "context_relevance"
+    }
+
+    async fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let judgments = input
+            .contexts
+            .iter()
+            .map(|context| self.is_relevant(&input.query, context))
+            .collect::<Vec<_>>();
+
+        // `buffered` keeps verdicts in context order
+        let judgments: Vec<bool> = stream::iter(judgments)
+            .buffered(self.concurrency)
+            .try_collect()
+            .await?;
+
+        Ok(relevance_result(self.name(), &judgments))
+    }
+}
+
+/// Async [`AnswerFaithfulness`](crate::AnswerFaithfulness)
+pub struct AsyncAnswerFaithfulness {
+    llm: Box<dyn AsyncLLM>,
+    prompt: PromptTemplate,
+    max_retries: usize,
+}
+
+impl AsyncAnswerFaithfulness {
+    /// Create a new async answer faithfulness metric
+    pub fn new(llm: Box<dyn AsyncLLM>) -> Self {
+        Self {
+            llm,
+            prompt: PromptTemplate::new(DEFAULT_FAITHFULNESS_PROMPT),
+            max_retries: DEFAULT_SCORE_RETRIES,
+        }
+    }
+
+    /// Set how many times to re-prompt when the reply has no score (default: 2)
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Replace the judge prompt (`{query}`, `{context}`, `{answer}`)
+    pub fn with_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("answer_faithfulness", FAITHFULNESS_PLACEHOLDERS)?;
+        self.prompt = template;
+        Ok(self)
+    }
+}
+
+#[async_trait]
+impl AsyncMetric for AsyncAnswerFaithfulness {
+    fn name(&self) -> &str {
+        "answer_faithfulness"
+    }
+
+    async fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let answer = input
+            .answer
+            .as_ref()
+            .ok_or_else(|| anyhow!("Answer required for faithfulness metric"))?;
+
+        if input.contexts.is_empty() {
+            return Ok(MetricResult {
+                metric_name: self.name().to_string(),
+                score: 0.0,
+                details: HashMap::new(),
+                ..Default::default()
+            });
+        }
+
+        let prompt = self.prompt.render(&[
+            ("query", &input.query),
+            ("context", &input.contexts.join("\n\n")),
+            ("answer", answer),
+        ]);
+
+        let mut responses = Vec::new();
+        for attempt in 0..=self.max_retries {
+            let response = if attempt == 0 {
+                self.llm.generate(&prompt).await?
+            } else {
+                self.llm
+                    .generate(&format!("{}{}", prompt, SCORE_RETRY_SUFFIX))
+                    .await?
+            };
+            let score = parse_faithfulness_score(&response);
+            responses.push(response);
+
+            if let Some(score) = score {
+                return Ok(faithfulness_result(self.name(), score, responses));
+            }
+        }
+
+        Err(unparseable_score_error(&responses))
+    }
+}
+
+/// Async [`AnswerCorrectness`](crate::AnswerCorrectness) that embeds both texts concurrently
+pub struct AsyncAnswerCorrectness {
+    embedder: Box<dyn AsyncEmbedder>,
+}
+
+impl AsyncAnswerCorrectness {
+    /// Create a new async answer correctness metric
+    pub fn new(embedder: Box<dyn AsyncEmbedder>) -> Self {
+        Self { embedder }
+    }
+}
+
+#[async_trait]
+impl AsyncMetric for AsyncAnswerCorrectness {
+    fn name(&self) -> &str {
+        "answer_correctness"
+    }
+
+    async fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let answer = input
+            .answer
+            .as_ref()
+            .ok_or_else(|| anyhow!("Answer required for correctness metric"))?;
+
+        let ground_truth = input
+            .ground_truth
+            .as_ref()
+            .ok_or_else(|| anyhow!("Ground truth required for correctness metric"))?;
+
+        let (answer_embedding, truth_embedding) = futures::try_join!(
+            self.embedder.embed(answer),
+            self.embedder.embed(ground_truth)
+        )?;
+
+        Ok(correctness_result(
+            self.name(),
+            answer,
+            ground_truth,
+            &answer_embedding,
+            &truth_embedding,
+        ))
+    }
+}
+
+// ============================================================================
+// Async Evaluator
+// ============================================================================
+
+/// Async counterpart of [`Evaluator`](crate::Evaluator)
+///
+/// All metrics for a test case run concurrently; results are reported in the
+/// order metrics were added.
+pub struct AsyncEvaluator {
+    metrics: Vec<Box<dyn AsyncMetric>>,
+    weights: Vec<f32>,
+    thresholds: HashMap<String, f32>,
+}
+
+impl AsyncEvaluator {
+    /// Create a new async evaluator with no metrics
+    pub fn new() -> Self {
+        Self {
+            metrics: Vec::new(),
+            weights: Vec::new(),
+            thresholds: HashMap::new(),
+        }
+    }
+
+    /// Add a metric to the evaluator (weight 1.0)
+    pub fn add_metric(&mut self, metric: Box<dyn AsyncMetric>) {
+        self.add_metric_weighted(metric, 1.0);
+    }
+
+    /// Add a metric that counts `weight` times toward the overall score
+    pub fn add_metric_weighted(&mut self, metric: Box<dyn AsyncMetric>, weight: f32) {
+        self.metrics.push(metric);
+        self.weights.push(weight.max(0.0));
+    }
+
+    /// Fail the report when `metric_name` scores below `min_score`
+    pub fn set_threshold(&mut self, metric_name: impl Into<String>, min_score: f32) {
+        self.thresholds.insert(metric_name.into(), min_score);
+    }
+
+    /// Evaluate a single input with all metrics in parallel
+    pub async fn evaluate(&self, input: &EvaluationInput) -> Result<EvaluationReport> {
+        let results = futures::future::try_join_all(self.metrics.iter().map(|metric| async move {
+            let start = Instant::now();
+            let mut result = metric.evaluate(input).await?;
+            result.duration_ms = Some(start.elapsed().as_millis() as u64);
+            Ok::<_, anyhow::Error>(result)
+        }))
+        .await?;
+
+        let mut report = build_report(results, &self.weights, &self.thresholds);
+        report.case_id = input.case_id.clone();
+        Ok(report)
+    }
+
+    /// Evaluate multiple inputs in batch, one test case at a time
+    pub async fn evaluate_batch(&self, inputs: &[EvaluationInput]) -> Result<Vec<EvaluationReport>> {
+        let mut reports = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            reports.push(self.evaluate(input).await?);
+        }
+        Ok(reports)
+    }
+
+    /// Get the number of metrics in this evaluator
+    pub fn metric_count(&self) -> usize {
+        self.metrics.len()
+    }
+
+    /// Get the names of all metrics in this evaluator
+    pub fn metric_names(&self) -> Vec<String> {
+        self.metrics.iter().map(|m| m.name().to_string()).collect()
+    }
+}
+
+impl Default for AsyncEvaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::ContextRelevance;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    /// Async LLM that sleeps and tracks peak concurrency
+    struct SlowLLM {
+        in_flight: AtomicUsize,
+        peak: AtomicUsize,
+    }
+
+    impl SlowLLM {
+        fn new() -> Self {
+            Self {
+                in_flight: AtomicUsize::new(0),
+                peak: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl AsyncLLM for Arc<SlowLLM> {
+        async fn generate(&self, prompt: &str) -> Result<String> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            if prompt.contains("Rate the faithfulness") {
+                return Ok("0.9".to_string());
+            }
+            Ok(if prompt.contains("Context: Rust") { "Yes" } else { "No" }.to_string())
+        }
+    }
+
+    struct SyncYes;
+    impl LLM for SyncYes {
+        fn generate(&self, _prompt: &str) -> Result<String> {
+            Ok("Yes".to_string())
+        }
+    }
+
+    struct SyncEmbedder;
+    impl Embedder for SyncEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            Ok(vec![text.len() as f32, 1.0])
+        }
+    }
+
+    fn input(contexts: &[&str]) -> EvaluationInput {
+        EvaluationInput {
+            query: "What is Rust?".to_string(),
+            contexts: contexts.iter().map(|c| c.to_string()).collect(),
+            answer: Some("Rust is a language.".to_string()),
+            ground_truth: Some("Rust is a systems language.".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_contexts_judged_concurrently_in_order() {
+        let llm = Arc::new(SlowLLM::new());
+        let metric = AsyncContextRelevance::new(Box::new(Arc::clone(&llm))).with_concurrency(3);
+
+        let contexts = ["Rust a", "Python", "Rust b", "Go", "Rust c", "Java"];
+        let result = metric.evaluate(&input(&contexts)).await.unwrap();
+
+        assert_eq!(result.score, 0.5);
+        assert_eq!(
+            result.details["context_relevance"],
+            serde_json::json!([[0, true], [1, false], [2, true], [3, false], [4, true], [5, false]])
+        );
+        assert_eq!(llm.peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_adapters_match_sync_metrics() {
+        let data = input(&["Rust is fast."]);
+
+        let sync_result = ContextRelevance::new(Box::new(SyncYes)).evaluate(&data).unwrap();
+        let async_result = AsyncContextRelevance::new(Box::new(BlockingLLM::new(SyncYes)))
+            .evaluate(&data)
+            .await
+            .unwrap();
+        assert_eq!(sync_result.score, async_result.score);
+
+        let correctness = AsyncAnswerCorrectness::new(Box::new(BlockingEmbedder::new(SyncEmbedder)))
+            .evaluate(&data)
+            .await
+            .unwrap();
+        assert!(correctness.score > 0.0 && correctness.score <= 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_evaluator_runs_metrics_in_parallel() {
+        let llm = Arc::new(SlowLLM::new());
+
+        let mut evaluator = AsyncEvaluator::new();
+        evaluator.add_metric(Box::new(AsyncContextRelevance::new(Box::new(Arc::clone(&llm)))));
+        evaluator.add_metric(Box::new(AsyncAnswerFaithfulness::new(Box::new(Arc::clone(&llm)))));
+        evaluator.add_metric(Box::new(BlockingMetric::new(ContextRelevance::new(Box::new(SyncYes)))));
+
+        let start = Instant::now();
+        let report = evaluator.evaluate(&input(&["Rust"])).await.unwrap();
+
+        // Two 50ms LLM metrics running in parallel finish well under 100ms
+        assert!(start.elapsed() < Duration::from_millis(95));
+        assert_eq!(report.results.len(), 3);
+        assert_eq!(report.results[0].metric_name, "context_relevance");
+        assert_eq!(report.results[1].metric_name, "answer_faithfulness");
+        assert_eq!(evaluator.metric_names().len(), 3);
+    }
+}
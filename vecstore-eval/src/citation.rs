@@ -0,0 +1,283 @@
+//! Sentence-Level Citation Mapping
+//!
+//! Faithfulness scores say how much of an answer is grounded, not where.
+//! [`CitationMapping`] splits the answer into sentences and asks the judge
+//! which retrieved context supports each one, producing an attribution table
+//! a UI can use to highlight unsupported sentences.
+
+use crate::context::split_statements;
+use crate::metrics::{GenerationParams, LLM};
+use crate::prompt::PromptTemplate;
+use crate::types::{EvaluationInput, Metric, MetricResult};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Default prompt for attributing one answer sentence
+///
+/// Placeholders: `{query}`, `{context}` (contexts numbered `[1]`, `[2]`, ...),
+/// `{sentence}`.
+pub const DEFAULT_CITATION_PROMPT: &str = "Contexts:\n{context}\n\nSentence: {sentence}\n\n\
+Which context supports this sentence? \
+Respond with only the context number, or NONE if no context supports it.";
+
+const CITATION_PLACEHOLDERS: &[&str] = &["query", "context", "sentence"];
+
+/// One answer sentence and the context that supports it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SentenceAttribution {
+    /// Sentence text as split from the answer
+    pub sentence: String,
+
+    /// Zero-based index into `contexts`, or `None` if unsupported
+    pub context_index: Option<usize>,
+}
+
+/// Maps each answer sentence to its supporting context
+///
+/// Score: fraction of sentences attributed to some context (coverage).
+/// Details contain `attributions` (every sentence with its zero-based
+/// `context_index` or `null`), `unsupported_sentences`, `total_sentences`
+/// and `attributed_count`. An empty answer scores 1.0.
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{CitationMapping, EvaluationInput, Metric};
+/// # struct MyLLM;
+/// # impl vecstore_eval::LLM for MyLLM {
+/// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok("1".to_string()) }
+/// # }
+///
+/// let metric = CitationMapping::new(Box::new(MyLLM));
+///
+/// let input = EvaluationInput {
+///     query: "What is Rust?".to_string(),
+///     contexts: vec!["Rust is a systems programming language.".to_string()],
+///     answer: Some("Rust is a systems language. It was released in 1995.".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let result = metric.evaluate(&input)?;
+/// for sentence in result.details["unsupported_sentences"].as_array().unwrap() {
+///     println!("unsupported: {}", sentence);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct CitationMapping {
+    llm: Box<dyn LLM>,
+    prompt: PromptTemplate,
+    params: GenerationParams,
+}
+
+impl CitationMapping {
+    /// Create the metric with the default prompt
+    pub fn new(llm: Box<dyn LLM>) -> Self {
+        Self {
+            llm,
+            prompt: PromptTemplate::new(DEFAULT_CITATION_PROMPT),
+            params: GenerationParams::deterministic(),
+        }
+    }
+
+    /// Override the attribution prompt (`{query}`, `{context}`, `{sentence}`)
+    pub fn with_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("citation mapping", CITATION_PLACEHOLDERS)?;
+        self.prompt = template;
+        Ok(self)
+    }
+
+    /// Override the sampling params (default: temperature 0)
+    pub fn with_generation_params(mut self, params: GenerationParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Ask the judge which of `contexts` supports `sentence`
+    pub fn attribute(
+        &self,
+        query: &str,
+        contexts: &[String],
+        sentence: &str,
+    ) -> Result<Option<usize>> {
+        if contexts.is_empty() {
+            return Ok(None);
+        }
+
+        let numbered = contexts
+            .iter()
+            .enumerate()
+            .map(|(i, context)| format!("[{}] {}", i + 1, context))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let prompt = self.prompt.render(&[
+            ("query", query),
+            ("context", &numbered),
+            ("sentence", sentence),
+        ]);
+
+        let response = self.llm.generate_with_params(&prompt, &self.params)?;
+        Ok(parse_citation(&response, contexts.len()))
+    }
+}
+
+/// Read a 1-based context number from the judge's reply
+///
+/// Returns the zero-based index, or `None` for "NONE", no number, or a
+/// number outside `1..=num_contexts`.
+fn parse_citation(response: &str, num_contexts: usize) -> Option<usize> {
+    let number: usize = response
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|part| !part.is_empty())?
+        .parse()
+        .ok()?;
+
+    (1..=num_contexts).contains(&number).then(|| number - 1)
+}
+
+impl Metric for CitationMapping {
+    fn name(&self) -> &str {
+        "citation_mapping"
+    }
+
+    fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let answer = input
+            .answer
+            .as_ref()
+            .ok_or_else(|| anyhow!("Answer required for citation mapping metric"))?;
+
+        let attributions = split_statements(answer)
+            .into_iter()
+            .map(|sentence| {
+                let context_index = self.attribute(&input.query, &input.contexts, &sentence)?;
+                Ok(SentenceAttribution {
+                    sentence,
+                    context_index,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let unsupported: Vec<&str> = attributions
+            .iter()
+            .filter(|a| a.context_index.is_none())
+            .map(|a| a.sentence.as_str())
+            .collect();
+        let attributed_count = attributions.len() - unsupported.len();
+
+        let score = if attributions.is_empty() {
+            1.0
+        } else {
+            attributed_count as f32 / attributions.len() as f32
+        };
+
+        let mut details = HashMap::new();
+        details.insert("attributions".to_string(), serde_json::json!(attributions));
+        details.insert(
+            "unsupported_sentences".to_string(),
+            serde_json::json!(unsupported),
+        );
+        details.insert(
+            "total_sentences".to_string(),
+            serde_json::json!(attributions.len()),
+        );
+        details.insert(
+            "attributed_count".to_string(),
+            serde_json::json!(attributed_count),
+        );
+
+        Ok(self.params.record(MetricResult {
+            metric_name: self.name().to_string(),
+            score,
+            details,
+            ..Default::default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cites the context that shares the sentence's first word
+    struct KeywordLLM;
+
+    impl LLM for KeywordLLM {
+        fn generate(&self, prompt: &str) -> Result<String> {
+            let sentence = prompt.split("Sentence: ").nth(1).unwrap();
+            let first_word = sentence.split_whitespace().next().unwrap();
+            let contexts = prompt.split("Sentence: ").next().unwrap();
+
+            Ok(contexts
+                .lines()
+                .find(|line| line.starts_with('[') && line.contains(first_word))
+                .and_then(|line| line.get(1..2))
+                .map(|n| format!("Context {}.", n))
+                .unwrap_or_else(|| "NONE".to_string()))
+        }
+    }
+
+    fn input(answer: &str) -> EvaluationInput {
+        EvaluationInput {
+            query: "Tell me about Rust.".to_string(),
+            contexts: vec![
+                "Rust is a systems language.".to_string(),
+                "Cargo is Rust's package manager.".to_string(),
+            ],
+            answer: Some(answer.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_citation() {
+        assert_eq!(parse_citation("2", 3), Some(1));
+        assert_eq!(parse_citation("[1]", 3), Some(0));
+        assert_eq!(parse_citation("Context 3 supports it.", 3), Some(2));
+        assert_eq!(parse_citation("NONE", 3), None);
+        assert_eq!(parse_citation("4", 3), None);
+        assert_eq!(parse_citation("0", 3), None);
+    }
+
+    #[test]
+    fn test_attribution_table() {
+        let metric = CitationMapping::new(Box::new(KeywordLLM));
+        let result = metric
+            .evaluate(&input(
+                "Rust is fast, e.g. in benchmarks. Cargo builds it. Python is slower.",
+            ))
+            .unwrap();
+
+        assert!((result.score - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(result.details["total_sentences"], 3);
+        assert_eq!(
+            result.details["attributions"][0]["sentence"],
+            "Rust is fast, e.g. in benchmarks."
+        );
+        assert_eq!(result.details["attributions"][0]["context_index"], 0);
+        assert_eq!(result.details["attributions"][1]["context_index"], 1);
+        assert!(result.details["attributions"][2]["context_index"].is_null());
+        assert_eq!(
+            result.details["unsupported_sentences"],
+            serde_json::json!(["Python is slower."])
+        );
+    }
+
+    #[test]
+    fn test_no_contexts_means_unsupported() {
+        let metric = CitationMapping::new(Box::new(KeywordLLM));
+        let mut input = input("Rust is great.");
+        input.contexts.clear();
+
+        let result = metric.evaluate(&input).unwrap();
+        assert_eq!(result.score, 0.0);
+    }
+
+    #[test]
+    fn test_requires_answer() {
+        let metric = CitationMapping::new(Box::new(KeywordLLM));
+        let input = EvaluationInput::default();
+        assert!(metric.evaluate(&input).is_err());
+    }
+}
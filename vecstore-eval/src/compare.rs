@@ -0,0 +1,385 @@
+//! Comparing two evaluation runs
+//!
+//! [`compare_runs`] pairs the cases of a baseline and a candidate run,
+//! computes per-metric deltas and applies a two-sided sign test so a handful
+//! of lucky cases are not mistaken for a real improvement.
+
+use crate::types::EvaluationReport;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write as _;
+
+/// p-value below which a change is flagged as significant
+pub const SIGNIFICANCE_LEVEL: f64 = 0.05;
+
+/// Score changes smaller than this count as unchanged
+const DELTA_EPSILON: f32 = 1e-6;
+
+/// Name used for the overall score in [`ComparisonReport::overall`]
+pub const OVERALL: &str = "overall";
+
+/// Overall conclusion of a comparison
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// The overall score went up significantly
+    Improved,
+    /// The overall score went down significantly
+    Regressed,
+    /// No significant change in the overall score
+    NoSignificantChange,
+}
+
+/// Baseline vs. candidate statistics for one metric
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricComparison {
+    /// Metric name (or [`OVERALL`])
+    pub metric_name: String,
+
+    /// Number of paired cases that had this metric in both runs
+    pub cases: usize,
+
+    /// Mean baseline score over the paired cases
+    pub baseline_mean: f32,
+
+    /// Mean candidate score over the paired cases
+    pub candidate_mean: f32,
+
+    /// Mean of the per-case deltas (candidate - baseline)
+    pub mean_delta: f32,
+
+    /// Cases where the candidate scored higher
+    pub improved: usize,
+
+    /// Cases where the candidate scored lower
+    pub regressed: usize,
+
+    /// Cases with no change
+    pub unchanged: usize,
+
+    /// Two-sided sign test p-value
+    pub p_value: f64,
+
+    /// `p_value` < [`SIGNIFICANCE_LEVEL`]
+    pub significant: bool,
+}
+
+/// Result of [`compare_runs`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonReport {
+    /// Number of cases present in both runs
+    pub matched_cases: usize,
+
+    /// Baseline cases with no candidate counterpart
+    pub unmatched_baseline: usize,
+
+    /// Candidate cases with no baseline counterpart
+    pub unmatched_candidate: usize,
+
+    /// Whether cases were paired by `case_id` (otherwise by index)
+    pub matched_by_id: bool,
+
+    /// Comparison of `overall_score`
+    pub overall: MetricComparison,
+
+    /// Per-metric comparisons, sorted by metric name
+    pub metrics: Vec<MetricComparison>,
+
+    /// Conclusion based on the overall score
+    pub verdict: Verdict,
+}
+
+/// Compare a candidate run against a baseline
+///
+/// Cases are paired by `case_id` when every report in both runs has one,
+/// otherwise by position.
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{compare_runs, read_reports_json};
+///
+/// let baseline = read_reports_json("eval/main.json")?;
+/// let candidate = read_reports_json("eval/chunk-512.json")?;
+///
+/// let comparison = compare_runs(&baseline, &candidate);
+/// println!("{}", comparison.to_markdown());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn compare_runs(baseline: &[EvaluationReport], candidate: &[EvaluationReport]) -> ComparisonReport {
+    let has_ids = |reports: &[EvaluationReport]| {
+        !reports.is_empty() && reports.iter().all(|r| r.case_id.is_some())
+    };
+    let matched_by_id = has_ids(baseline) && has_ids(candidate);
+
+    let pairs: Vec<(&EvaluationReport, &EvaluationReport)> = if matched_by_id {
+        let by_id: HashMap<&str, &EvaluationReport> = candidate
+            .iter()
+            .filter_map(|r| r.case_id.as_deref().map(|id| (id, r)))
+            .collect();
+        baseline
+            .iter()
+            .filter_map(|b| {
+                b.case_id
+                    .as_deref()
+                    .and_then(|id| by_id.get(id))
+                    .map(|c| (b, *c))
+            })
+            .collect()
+    } else {
+        baseline.iter().zip(candidate.iter()).collect()
+    };
+
+    let overall = compare_scores(
+        OVERALL,
+        pairs
+            .iter()
+            .map(|(b, c)| (b.overall_score, c.overall_score))
+            .collect(),
+    );
+
+    let metric_names: BTreeSet<&str> = pairs
+        .iter()
+        .flat_map(|(b, _)| b.metric_scores.keys().map(String::as_str))
+        .collect();
+
+    let metrics = metric_names
+        .into_iter()
+        .map(|name| {
+            let scores = pairs
+                .iter()
+                .filter_map(|(b, c)| Some((*b.metric_scores.get(name)?, *c.metric_scores.get(name)?)))
+                .collect();
+            compare_scores(name, scores)
+        })
+        .collect();
+
+    let verdict = if !overall.significant {
+        Verdict::NoSignificantChange
+    } else if overall.mean_delta > 0.0 {
+        Verdict::Improved
+    } else {
+        Verdict::Regressed
+    };
+
+    ComparisonReport {
+        matched_cases: pairs.len(),
+        unmatched_baseline: baseline.len() - pairs.len(),
+        unmatched_candidate: candidate.len() - pairs.len(),
+        matched_by_id,
+        overall,
+        metrics,
+        verdict,
+    }
+}
+
+fn compare_scores(metric_name: &str, scores: Vec<(f32, f32)>) -> MetricComparison {
+    let cases = scores.len();
+    let mean = |values: &mut dyn Iterator<Item = f32>| {
+        if cases == 0 {
+            0.0
+        } else {
+            values.sum::<f32>() / cases as f32
+        }
+    };
+
+    let baseline_mean = mean(&mut scores.iter().map(|(b, _)| *b));
+    let candidate_mean = mean(&mut scores.iter().map(|(_, c)| *c));
+    let mean_delta = mean(&mut scores.iter().map(|(b, c)| c - b));
+
+    let improved = scores.iter().filter(|(b, c)| c - b > DELTA_EPSILON).count();
+    let regressed = scores.iter().filter(|(b, c)| b - c > DELTA_EPSILON).count();
+    let p_value = sign_test(improved, regressed);
+
+    MetricComparison {
+        metric_name: metric_name.to_string(),
+        cases,
+        baseline_mean,
+        candidate_mean,
+        mean_delta,
+        improved,
+        regressed,
+        unchanged: cases - improved - regressed,
+        p_value,
+        significant: p_value < SIGNIFICANCE_LEVEL,
+    }
+}
+
+/// Exact two-sided sign test p-value (ties already excluded)
+pub(crate) fn sign_test(positive: usize, negative: usize) -> f64 {
+    let n = positive + negative;
+    if n == 0 {
+        return 1.0;
+    }
+
+    // Sum binomial(n, 0.5) probabilities of outcomes at least as extreme,
+    // in log space so large n does not underflow
+    let k = positive.min(negative);
+    let ln_half_n = n as f64 * 0.5f64.ln();
+    let mut ln_choose = 0.0;
+    let mut tail = 0.0;
+
+    for i in 0..=k {
+        if i > 0 {
+            ln_choose += ((n - i + 1) as f64).ln() - (i as f64).ln();
+        }
+        tail += (ln_choose + ln_half_n).exp();
+    }
+
+    (2.0 * tail).min(1.0)
+}
+
+impl ComparisonReport {
+    /// Render a Markdown summary for pasting into a PR description
+    pub fn to_markdown(&self) -> String {
+        let headline = match self.verdict {
+            Verdict::Improved => "▲ Improved",
+            Verdict::Regressed => "▼ Regressed",
+            Verdict::NoSignificantChange => "No significant change",
+        };
+
+        let mut out = String::new();
+        let _ = writeln!(out, "### Evaluation comparison: {}\n", headline);
+        out.push_str("| Metric | Baseline | Candidate | Δ | ▲ | ▼ | p-value |\n");
+        out.push_str("|---|---|---|---|---|---|---|\n");
+
+        for m in self.metrics.iter().chain(std::iter::once(&self.overall)) {
+            let arrow = if m.mean_delta > DELTA_EPSILON {
+                "▲"
+            } else if m.mean_delta < -DELTA_EPSILON {
+                "▼"
+            } else {
+                "="
+            };
+            let name = if m.metric_name == OVERALL {
+                format!("**{}**", m.metric_name)
+            } else {
+                m.metric_name.clone()
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {:.3} | {:.3} | {} {:+.3} | {} | {} | {:.3}{} |",
+                name,
+                m.baseline_mean,
+                m.candidate_mean,
+                arrow,
+                m.mean_delta,
+                m.improved,
+                m.regressed,
+                m.p_value,
+                if m.significant { " *" } else { "" }
+            );
+        }
+
+        let _ = write!(
+            out,
+            "\n{} cases compared (matched by {})",
+            self.matched_cases,
+            if self.matched_by_id { "case_id" } else { "index" }
+        );
+        if self.unmatched_baseline + self.unmatched_candidate > 0 {
+            let _ = write!(
+                out,
+                "; {} baseline / {} candidate cases unmatched",
+                self.unmatched_baseline, self.unmatched_candidate
+            );
+        }
+        let _ = writeln!(out, ". * p < {}", SIGNIFICANCE_LEVEL);
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(id: Option<&str>, faithfulness: f32, correctness: f32) -> EvaluationReport {
+        EvaluationReport {
+            overall_score: (faithfulness + correctness) / 2.0,
+            metric_scores: [
+                ("faithfulness".to_string(), faithfulness),
+                ("correctness".to_string(), correctness),
+            ]
+            .into_iter()
+            .collect(),
+            results: vec![],
+            timestamp: 0,
+            passed: true,
+            violations: vec![],
+            case_id: id.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_sign_test() {
+        assert_eq!(sign_test(0, 0), 1.0);
+        // 10 of 10 improved: 2 * 0.5^10
+        assert!((sign_test(10, 0) - 2.0 / 1024.0).abs() < 1e-12);
+        assert!((sign_test(1, 1) - 1.0).abs() < 1e-12);
+        // Large n does not underflow to zero or NaN
+        let p = sign_test(2000, 1900);
+        assert!(p > 0.0 && p < 1.0);
+    }
+
+    #[test]
+    fn test_improvement_is_significant() {
+        let baseline: Vec<_> = (0..10).map(|_| report(None, 0.6, 0.7)).collect();
+        let candidate: Vec<_> = (0..10).map(|_| report(None, 0.8, 0.7)).collect();
+
+        let comparison = compare_runs(&baseline, &candidate);
+
+        assert_eq!(comparison.matched_cases, 10);
+        assert!(!comparison.matched_by_id);
+        assert_eq!(comparison.verdict, Verdict::Improved);
+
+        let faithfulness = &comparison.metrics[1];
+        assert_eq!(faithfulness.metric_name, "faithfulness");
+        assert!((faithfulness.mean_delta - 0.2).abs() < 1e-5);
+        assert_eq!(faithfulness.improved, 10);
+        assert!(faithfulness.significant);
+
+        let correctness = &comparison.metrics[0];
+        assert_eq!(correctness.unchanged, 10);
+        assert!(!correctness.significant);
+    }
+
+    #[test]
+    fn test_small_noisy_change_not_significant() {
+        let baseline = vec![report(None, 0.5, 0.5), report(None, 0.5, 0.5), report(None, 0.5, 0.5)];
+        let candidate = vec![report(None, 0.9, 0.5), report(None, 0.4, 0.5), report(None, 0.6, 0.5)];
+
+        let comparison = compare_runs(&baseline, &candidate);
+        assert_eq!(comparison.verdict, Verdict::NoSignificantChange);
+        assert_eq!(comparison.overall.improved, 2);
+        assert_eq!(comparison.overall.regressed, 1);
+    }
+
+    #[test]
+    fn test_match_by_case_id() {
+        let baseline = vec![report(Some("a"), 0.9, 0.9), report(Some("b"), 0.1, 0.1)];
+        let candidate = vec![
+            report(Some("b"), 0.1, 0.1),
+            report(Some("c"), 0.5, 0.5),
+            report(Some("a"), 0.9, 0.9),
+        ];
+
+        let comparison = compare_runs(&baseline, &candidate);
+        assert!(comparison.matched_by_id);
+        assert_eq!(comparison.matched_cases, 2);
+        assert_eq!(comparison.unmatched_candidate, 1);
+        assert_eq!(comparison.overall.unchanged, 2);
+    }
+
+    #[test]
+    fn test_markdown() {
+        let baseline: Vec<_> = (0..8).map(|_| report(None, 0.9, 0.8)).collect();
+        let candidate: Vec<_> = (0..8).map(|_| report(None, 0.7, 0.8)).collect();
+
+        let markdown = compare_runs(&baseline, &candidate).to_markdown();
+        assert!(markdown.starts_with("### Evaluation comparison: ▼ Regressed"));
+        assert!(markdown.contains("| faithfulness | 0.900 | 0.700 | ▼ -0.200 | 0 | 8 |"));
+        assert!(markdown.contains("| correctness | 0.800 | 0.800 | = +0.000 |"));
+        assert!(markdown.contains("8 cases compared (matched by index)"));
+    }
+}
@@ -0,0 +1,505 @@
+//! Rank-Aware Context Metrics
+//!
+//! [`ContextRelevance`](crate::ContextRelevance) ignores ordering. These
+//! metrics follow RAGAS:
+//!
+//! - [`ContextPrecision`]: were the useful contexts ranked early?
+//! - [`ContextRecall`]: is the ground truth covered by the retrieved contexts?
+
+use crate::metrics::{parse_relevance, GenerationParams, LLM};
+use crate::prompt::PromptTemplate;
+use crate::types::{EvaluationInput, Metric, MetricResult};
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// Default prompt for judging one context
+///
+/// Placeholders: `{query}`, `{ground_truth}`, `{context}`. `{ground_truth}`
+/// is empty when the input has none.
+pub const DEFAULT_PRECISION_PROMPT: &str = "Question: {query}\n\nReference answer: {ground_truth}\n\n\
+Context: {context}\n\n\
+Is this context useful for answering the question (and arriving at the reference answer, if given)? \
+Answer only 'Yes' or 'No'.";
+
+/// Default prompt for attributing one ground-truth statement
+///
+/// Placeholders: `{query}`, `{context}`, `{statement}`.
+pub const DEFAULT_RECALL_PROMPT: &str = "Context:\n{context}\n\nStatement: {statement}\n\n\
+Can this statement be attributed to the context? Answer only 'Yes' or 'No'.";
+
+const PRECISION_PLACEHOLDERS: &[&str] = &["query", "ground_truth", "context"];
+const RECALL_PLACEHOLDERS: &[&str] = &["query", "context", "statement"];
+
+// ============================================================================
+// Context Precision
+// ============================================================================
+
+/// Rank-weighted precision of the retrieved contexts
+///
+/// Each context is judged useful or not. The score is the mean of
+/// precision@k over the ranks k that hold a useful context, so useful
+/// contexts at the top count for more. Details include `first_relevant_rank`
+/// (1-based), the per-rank `verdicts` and `precision_at_rank`.
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{ContextPrecision, EvaluationInput, Metric};
+/// # struct MyLLM;
+/// # impl vecstore_eval::LLM for MyLLM {
+/// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok("Yes".to_string()) }
+/// # }
+///
+/// let metric = ContextPrecision::new(Box::new(MyLLM));
+///
+/// let input = EvaluationInput {
+///     query: "What is Rust?".to_string(),
+///     contexts: vec![
+///         "Python is an interpreted language.".to_string(),
+///         "Rust is a systems programming language.".to_string(),
+///     ],
+///     ground_truth: Some("Rust is a systems programming language.".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let result = metric.evaluate(&input)?;
+/// println!("first relevant at {}", result.details["first_relevant_rank"]);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct ContextPrecision {
+    llm: Box<dyn LLM>,
+    prompt: PromptTemplate,
+    params: GenerationParams,
+}
+
+impl ContextPrecision {
+    /// Create the metric with the default prompt
+    pub fn new(llm: Box<dyn LLM>) -> Self {
+        Self {
+            llm,
+            prompt: PromptTemplate::new(DEFAULT_PRECISION_PROMPT),
+            params: GenerationParams::deterministic(),
+        }
+    }
+
+    /// Override the judge prompt (`{query}`, `{ground_truth}`, `{context}`)
+    pub fn with_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("context_precision", PRECISION_PLACEHOLDERS)?;
+        self.prompt = template;
+        Ok(self)
+    }
+
+    /// Override the sampling params (default: temperature 0)
+    pub fn with_generation_params(mut self, params: GenerationParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Judge whether one context is useful
+    fn is_useful(&self, query: &str, ground_truth: &str, context: &str) -> Result<bool> {
+        let prompt = self.prompt.render(&[
+            ("query", query),
+            ("ground_truth", ground_truth),
+            ("context", context),
+        ]);
+
+        Ok(parse_relevance(
+            &self.llm.generate_with_params(&prompt, &self.params)?,
+        ))
+    }
+}
+
+impl Metric for ContextPrecision {
+    fn name(&self) -> &str {
+        "context_precision"
+    }
+
+    fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let ground_truth = input.ground_truth.as_deref().unwrap_or("");
+
+        let verdicts = input
+            .contexts
+            .iter()
+            .map(|context| self.is_useful(&input.query, ground_truth, context))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(self.params.record(precision_result(self.name(), &verdicts)))
+    }
+}
+
+/// Compute RAGAS context precision from per-rank verdicts
+pub(crate) fn precision_result(metric_name: &str, verdicts: &[bool]) -> MetricResult {
+    let mut hits = 0;
+    let mut weighted = 0.0;
+    let mut precision_at_rank = Vec::with_capacity(verdicts.len());
+
+    for (i, &useful) in verdicts.iter().enumerate() {
+        if useful {
+            hits += 1;
+        }
+        let precision = hits as f32 / (i + 1) as f32;
+        precision_at_rank.push(precision);
+        if useful {
+            weighted += precision;
+        }
+    }
+
+    let score = if hits == 0 { 0.0 } else { weighted / hits as f32 };
+    let first_relevant_rank = verdicts.iter().position(|&useful| useful).map(|i| i + 1);
+
+    let mut details = HashMap::new();
+    details.insert("verdicts".to_string(), serde_json::json!(verdicts));
+    details.insert(
+        "precision_at_rank".to_string(),
+        serde_json::json!(precision_at_rank),
+    );
+    details.insert(
+        "first_relevant_rank".to_string(),
+        serde_json::json!(first_relevant_rank),
+    );
+    details.insert("relevant_count".to_string(), serde_json::json!(hits));
+    details.insert(
+        "total_contexts".to_string(),
+        serde_json::json!(verdicts.len()),
+    );
+
+    MetricResult {
+        metric_name: metric_name.to_string(),
+        score,
+        details,
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// Context Recall
+// ============================================================================
+
+/// Fraction of ground-truth statements attributable to the contexts
+///
+/// The ground truth is split into sentences and the judge decides whether
+/// each one is backed by the retrieved contexts. Requires `ground_truth`.
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{ContextRecall, EvaluationInput, Metric};
+/// # struct MyLLM;
+/// # impl vecstore_eval::LLM for MyLLM {
+/// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok("Yes".to_string()) }
+/// # }
+///
+/// let metric = ContextRecall::new(Box::new(MyLLM));
+///
+/// let input = EvaluationInput {
+///     query: "What is Rust?".to_string(),
+///     contexts: vec!["Rust is a systems programming language.".to_string()],
+///     ground_truth: Some("Rust is a systems language. It has no garbage collector.".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let result = metric.evaluate(&input)?;
+/// assert!(result.score >= 0.0 && result.score <= 1.0);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct ContextRecall {
+    llm: Box<dyn LLM>,
+    prompt: PromptTemplate,
+    params: GenerationParams,
+}
+
+impl ContextRecall {
+    /// Create the metric with the default prompt
+    pub fn new(llm: Box<dyn LLM>) -> Self {
+        Self {
+            llm,
+            prompt: PromptTemplate::new(DEFAULT_RECALL_PROMPT),
+            params: GenerationParams::deterministic(),
+        }
+    }
+
+    /// Override the judge prompt (`{query}`, `{context}`, `{statement}`)
+    pub fn with_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("context_recall", RECALL_PLACEHOLDERS)?;
+        self.prompt = template;
+        Ok(self)
+    }
+
+    /// Override the sampling params (default: temperature 0)
+    pub fn with_generation_params(mut self, params: GenerationParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Judge whether one statement is backed by the context
+    fn is_attributed(&self, query: &str, context: &str, statement: &str) -> Result<bool> {
+        let prompt = self.prompt.render(&[
+            ("query", query),
+            ("context", context),
+            ("statement", statement),
+        ]);
+
+        Ok(parse_relevance(
+            &self.llm.generate_with_params(&prompt, &self.params)?,
+        ))
+    }
+}
+
+impl Metric for ContextRecall {
+    fn name(&self) -> &str {
+        "context_recall"
+    }
+
+    fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let ground_truth = input
+            .ground_truth
+            .as_ref()
+            .ok_or_else(|| anyhow!("Ground truth required for context recall metric"))?;
+
+        let statements = split_statements(ground_truth);
+        if statements.is_empty() {
+            return Err(anyhow!("Ground truth contains no statements"));
+        }
+
+        let context = input.contexts.join("\n\n");
+        let attributed = statements
+            .iter()
+            .map(|statement| {
+                if input.contexts.is_empty() {
+                    Ok(false)
+                } else {
+                    self.is_attributed(&input.query, &context, statement)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let attributed_count = attributed.iter().filter(|&&a| a).count();
+        let score = attributed_count as f32 / statements.len() as f32;
+
+        let per_statement: Vec<_> = statements
+            .iter()
+            .zip(&attributed)
+            .map(|(statement, attributed)| {
+                serde_json::json!({ "statement": statement, "attributed": attributed })
+            })
+            .collect();
+
+        let mut details = HashMap::new();
+        details.insert("statements".to_string(), serde_json::json!(per_statement));
+        details.insert(
+            "attributed_count".to_string(),
+            serde_json::json!(attributed_count),
+        );
+        details.insert(
+            "total_statements".to_string(),
+            serde_json::json!(statements.len()),
+        );
+
+        Ok(self.params.record(MetricResult {
+            metric_name: self.name().to_string(),
+            score,
+            details,
+            ..Default::default()
+        }))
+    }
+}
+
+/// Words that end in a period without ending the sentence
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "cf", "al",
+    "approx", "inc", "ltd", "co", "corp", "fig", "no", "vol", "jan", "feb", "mar", "apr", "jun",
+    "jul", "aug", "sep", "sept", "oct", "nov", "dec", "u.s", "u.k",
+];
+
+/// Split text into sentences on `.`, `!`, `?` and newlines
+///
+/// A period does not end a sentence after a known abbreviation ("Dr.",
+/// "e.g."), after a single capital letter (initials such as "J. R. R."),
+/// inside a number ("3.14") or when the next word starts in lowercase.
+/// Closing quotes and brackets stay with the sentence they end.
+pub(crate) fn split_statements(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+
+        if c == '\n' {
+            push_statement(&mut statements, &mut current);
+            continue;
+        }
+
+        current.push(c);
+        if !matches!(c, '.' | '!' | '?') {
+            continue;
+        }
+
+        // Keep runs like "?!" or "..." and closing quotes/brackets together
+        while i < chars.len() && matches!(chars[i], '.' | '!' | '?' | '"' | '\'' | ')' | ']' | '”' | '’') {
+            current.push(chars[i]);
+            i += 1;
+        }
+
+        // Only break when the terminator ends a word (keeps "3.14" intact)
+        if i < chars.len() && !chars[i].is_whitespace() {
+            continue;
+        }
+
+        if c == '.' && !ends_sentence(&current, &chars[i..]) {
+            continue;
+        }
+
+        push_statement(&mut statements, &mut current);
+    }
+    push_statement(&mut statements, &mut current);
+
+    statements
+}
+
+/// Whether the period ending `current` closes a sentence, given what follows
+fn ends_sentence(current: &str, rest: &[char]) -> bool {
+    let word = current
+        .trim_end_matches(['.', '"', '\'', ')', ']', '”', '’'])
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or("")
+        .trim_start_matches(['"', '\'', '(', '[', '“', '‘']);
+
+    let is_initial = word.chars().count() == 1 && word.chars().all(char::is_uppercase);
+    if is_initial || ABBREVIATIONS.contains(&word.to_lowercase().as_str()) {
+        return false;
+    }
+
+    // "approx. five": a lowercase continuation is not a new sentence
+    let next = rest.iter().find(|c| !c.is_whitespace());
+    !next.is_some_and(|c| c.is_lowercase())
+}
+
+fn push_statement(statements: &mut Vec<String>, current: &mut String) {
+    let statement = current.trim();
+    if !statement.is_empty() {
+        statements.push(statement.to_string());
+    }
+    current.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Answers "Yes" when the prompt contains any of the given needles
+    struct KeywordLLM(Vec<&'static str>);
+
+    impl LLM for KeywordLLM {
+        fn generate(&self, prompt: &str) -> Result<String> {
+            if self.0.iter().any(|needle| prompt.contains(needle)) {
+                Ok("Yes".to_string())
+            } else {
+                Ok("No".to_string())
+            }
+        }
+    }
+
+    fn contexts(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_precision_rank_weighting() {
+        let metric = ContextPrecision::new(Box::new(KeywordLLM(vec!["Context: good"])));
+
+        let early = EvaluationInput {
+            query: "q".to_string(),
+            contexts: contexts(&["good 1", "bad", "good 2"]),
+            ..Default::default()
+        };
+        let late = EvaluationInput {
+            query: "q".to_string(),
+            contexts: contexts(&["bad", "good 1", "good 2"]),
+            ..Default::default()
+        };
+
+        let early = metric.evaluate(&early).unwrap();
+        let late = metric.evaluate(&late).unwrap();
+
+        // (1/1 + 2/3) / 2 and (1/2 + 2/3) / 2
+        assert!((early.score - 5.0 / 6.0).abs() < 1e-6);
+        assert!((late.score - 7.0 / 12.0).abs() < 1e-6);
+        assert_eq!(early.details["first_relevant_rank"], 1);
+        assert_eq!(late.details["first_relevant_rank"], 2);
+        assert_eq!(late.details["verdicts"], serde_json::json!([false, true, true]));
+    }
+
+    #[test]
+    fn test_precision_no_relevant() {
+        let result = precision_result("context_precision", &[false, false]);
+        assert_eq!(result.score, 0.0);
+        assert!(result.details["first_relevant_rank"].is_null());
+
+        let result = precision_result("context_precision", &[]);
+        assert_eq!(result.score, 0.0);
+    }
+
+    #[test]
+    fn test_recall_statements() {
+        let metric = ContextRecall::new(Box::new(KeywordLLM(vec!["Statement: Rust is fast."])));
+        let input = EvaluationInput {
+            query: "q".to_string(),
+            contexts: contexts(&["Rust is fast."]),
+            ground_truth: Some("Rust is fast. Rust is 30 years old!".to_string()),
+            ..Default::default()
+        };
+
+        let result = metric.evaluate(&input).unwrap();
+        assert_eq!(result.score, 0.5);
+        assert_eq!(result.details["total_statements"], 2);
+        assert_eq!(result.details["statements"][0]["attributed"], true);
+        assert_eq!(result.details["statements"][1]["statement"], "Rust is 30 years old!");
+    }
+
+    #[test]
+    fn test_recall_requires_ground_truth() {
+        let metric = ContextRecall::new(Box::new(KeywordLLM(vec![])));
+        let input = EvaluationInput {
+            query: "q".to_string(),
+            contexts: contexts(&["c"]),
+            ..Default::default()
+        };
+
+        let err = metric.evaluate(&input).unwrap_err();
+        assert!(err.to_string().contains("Ground truth required"));
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            split_statements("Pi is 3.14. Is it?\nYes  "),
+            vec!["Pi is 3.14.", "Is it?", "Yes"]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_abbreviations() {
+        assert_eq!(
+            split_statements("Dr. Smith met Mrs. Jones at 5 p.m. yesterday. They talked."),
+            vec!["Dr. Smith met Mrs. Jones at 5 p.m. yesterday.", "They talked."]
+        );
+        assert_eq!(
+            split_statements("Use a library, e.g. serde. It works. J. R. R. Tolkien agreed."),
+            vec!["Use a library, e.g. serde.", "It works.", "J. R. R. Tolkien agreed."]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_quotes_and_runs() {
+        assert_eq!(
+            split_statements("He said \"stop.\" Then he left... Really?! Yes (mostly.) Done"),
+            vec!["He said \"stop.\"", "Then he left...", "Really?!", "Yes (mostly.)", "Done"]
+        );
+    }
+}
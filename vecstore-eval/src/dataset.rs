@@ -0,0 +1,247 @@
+//! Loading evaluation test cases from disk
+//!
+//! Two formats are supported:
+//!
+//! - **JSONL**: one [`EvaluationInput`] object per line (blank lines and
+//!   lines starting with `#` or `//` are skipped)
+//! - **CSV**: a header row naming `query`, `contexts`, `answer`,
+//!   `ground_truth` and optionally `retrieved_ids` / `relevant_ids` /
+//!   `case_id`
+//!
+//! Errors name the file and line that failed to parse.
+
+use crate::types::EvaluationInput;
+use anyhow::{anyhow, Context, Result};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Reads and writes evaluation test cases
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{EvaluationDataset, Evaluator};
+///
+/// let cases = EvaluationDataset::from_jsonl("tests/golden.jsonl")?;
+/// # let evaluator = Evaluator::new();
+/// let reports = evaluator.evaluate_batch(&cases)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct EvaluationDataset;
+
+impl EvaluationDataset {
+    /// Load test cases from a JSONL file
+    pub fn from_jsonl(path: impl AsRef<Path>) -> Result<Vec<EvaluationInput>> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open dataset {}", path.display()))?;
+
+        let mut cases = Vec::new();
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line_no = i + 1;
+            let line =
+                line.with_context(|| format!("{}:{}: failed to read line", path.display(), line_no))?;
+            let trimmed = line.trim();
+
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("//") {
+                continue;
+            }
+
+            let case: EvaluationInput = serde_json::from_str(trimmed).map_err(|e| {
+                anyhow!("{}:{}: invalid test case: {}", path.display(), line_no, e)
+            })?;
+            cases.push(case);
+        }
+
+        Ok(cases)
+    }
+
+    /// Load test cases from a CSV file with a header row
+    ///
+    /// `query` is required. List columns (`contexts`, `retrieved_ids`,
+    /// `relevant_ids`) hold either a JSON array of strings or values
+    /// separated by `|`. Empty `answer`/`ground_truth` cells become `None`.
+    pub fn from_csv(path: impl AsRef<Path>) -> Result<Vec<EvaluationInput>> {
+        let path = path.as_ref();
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_path(path)
+            .with_context(|| format!("Failed to open dataset {}", path.display()))?;
+
+        let headers = reader
+            .headers()
+            .with_context(|| format!("{}:1: failed to read header row", path.display()))?
+            .clone();
+
+        let column = |name: &str| headers.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+        let query_col = column("query")
+            .ok_or_else(|| anyhow!("{}:1: missing required 'query' column", path.display()))?;
+        let contexts_col = column("contexts");
+        let answer_col = column("answer");
+        let ground_truth_col = column("ground_truth");
+        let retrieved_col = column("retrieved_ids");
+        let relevant_col = column("relevant_ids");
+        let case_id_col = column("case_id");
+
+        let mut cases = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|e| {
+                let line = e.position().map(|p| p.line()).unwrap_or(0);
+                anyhow!("{}:{}: {}", path.display(), line, e)
+            })?;
+            let line_no = record.position().map(|p| p.line()).unwrap_or(0);
+
+            let cell = |col: Option<usize>| {
+                col.and_then(|c| record.get(c))
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+            };
+            let list = |col: Option<usize>, name: &str| -> Result<Vec<String>> {
+                cell(col)
+                    .map(parse_list)
+                    .transpose()
+                    .map_err(|e| anyhow!("{}:{}: invalid {}: {}", path.display(), line_no, name, e))
+                    .map(Option::unwrap_or_default)
+            };
+
+            let query = cell(Some(query_col))
+                .ok_or_else(|| anyhow!("{}:{}: empty query", path.display(), line_no))?;
+
+            cases.push(EvaluationInput {
+                query: query.to_string(),
+                contexts: list(contexts_col, "contexts")?,
+                answer: cell(answer_col).map(str::to_string),
+                ground_truth: cell(ground_truth_col).map(str::to_string),
+                retrieved_ids: list(retrieved_col, "retrieved_ids")?,
+                relevant_ids: list(relevant_col, "relevant_ids")?,
+                case_id: cell(case_id_col).map(str::to_string),
+            });
+        }
+
+        Ok(cases)
+    }
+
+    /// Write test cases as JSONL (readable by [`from_jsonl`](Self::from_jsonl))
+    pub fn to_jsonl(cases: &[EvaluationInput], path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        for case in cases {
+            serde_json::to_writer(&mut writer, case)?;
+            writer.write_all(b"\n")?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Parse a JSON array of strings, or `|`-separated values
+fn parse_list(value: &str) -> Result<Vec<String>> {
+    if value.starts_with('[') {
+        return Ok(serde_json::from_str(value)?);
+    }
+
+    Ok(value
+        .split('|')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, content: &str) -> std::path::PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_from_jsonl() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "cases.jsonl",
+            "# golden set\n\
+             {\"query\": \"What is Rust?\", \"contexts\": [\"Rust is fast.\"], \"answer\": null, \"ground_truth\": \"A language.\"}\n\
+             \n\
+             {\"query\": \"q2\", \"contexts\": [], \"answer\": \"a\", \"ground_truth\": null, \"relevant_ids\": [\"d1\"]}\n",
+        );
+
+        let cases = EvaluationDataset::from_jsonl(&path).unwrap();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[0].ground_truth.as_deref(), Some("A language."));
+        assert_eq!(cases[1].relevant_ids, vec!["d1"]);
+    }
+
+    #[test]
+    fn test_jsonl_error_has_line_number() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "bad.jsonl",
+            "{\"query\": \"ok\", \"contexts\": [], \"answer\": null, \"ground_truth\": null}\n{\"query\": 1}\n",
+        );
+
+        let err = EvaluationDataset::from_jsonl(&path).unwrap_err().to_string();
+        assert!(err.contains("bad.jsonl:2:"), "{}", err);
+    }
+
+    #[test]
+    fn test_from_csv() {
+        let dir = TempDir::new().unwrap();
+        let path = write(
+            &dir,
+            "cases.csv",
+            "query,contexts,answer,ground_truth,relevant_ids\n\
+             What is Rust?,\"[\"\"Rust is fast.\"\", \"\"Rust, safely\"\"]\",Fast.,,d1|d2\n\
+             Second,one | two,,truth,\n",
+        );
+
+        let cases = EvaluationDataset::from_csv(&path).unwrap();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(cases[0].contexts, vec!["Rust is fast.", "Rust, safely"]);
+        assert_eq!(cases[0].answer.as_deref(), Some("Fast."));
+        assert_eq!(cases[0].ground_truth, None);
+        assert_eq!(cases[0].relevant_ids, vec!["d1", "d2"]);
+        assert_eq!(cases[1].contexts, vec!["one", "two"]);
+        assert_eq!(cases[1].ground_truth.as_deref(), Some("truth"));
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        let dir = TempDir::new().unwrap();
+
+        let path = write(&dir, "noquery.csv", "question,answer\nq,a\n");
+        let err = EvaluationDataset::from_csv(&path).unwrap_err().to_string();
+        assert!(err.contains("missing required 'query' column"), "{}", err);
+
+        let path = write(&dir, "badlist.csv", "query,contexts\nok,a\nq,[not json\n");
+        let err = EvaluationDataset::from_csv(&path).unwrap_err().to_string();
+        assert!(err.contains("badlist.csv:3: invalid contexts"), "{}", err);
+    }
+
+    #[test]
+    fn test_jsonl_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cases.jsonl");
+        let cases = vec![EvaluationInput {
+            query: "q".to_string(),
+            contexts: vec!["c".to_string()],
+            answer: Some("a".to_string()),
+            retrieved_ids: vec!["d1".to_string()],
+            ..Default::default()
+        }];
+
+        EvaluationDataset::to_jsonl(&cases, &path).unwrap();
+        assert_eq!(EvaluationDataset::from_jsonl(&path).unwrap(), cases);
+    }
+}
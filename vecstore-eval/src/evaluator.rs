@@ -1,9 +1,16 @@
 //! Evaluation suite orchestrator
 
-use crate::types::{EvaluationInput, EvaluationReport, Metric, MetricResult};
-use anyhow::Result;
+use crate::compare::OVERALL;
+use crate::stats::{histogram, BootstrapConfig, HistogramBucket, ScoreSummary};
+use crate::types::{EvaluationInput, EvaluationReport, Metric, MetricResult, ThresholdViolation};
+use crate::usage::{merge_usage, TokenUsage, UsageRecorder, UsageSnapshot};
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 /// Orchestrates evaluation across multiple metrics
 ///
@@ -29,6 +36,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 ///     contexts: vec!["Rust is a systems programming language.".to_string()],
 ///     answer: Some("Rust is a systems language.".to_string()),
 ///     ground_truth: Some("Rust is a memory-safe systems language.".to_string()),
+///     ..Default::default()
 /// };
 ///
 /// let report = evaluator.evaluate(&input)?;
@@ -37,6 +45,10 @@ use std::time::{SystemTime, UNIX_EPOCH};
 /// ```
 pub struct Evaluator {
     metrics: Vec<Box<dyn Metric>>,
+    weights: Vec<f32>,
+    thresholds: HashMap<String, f32>,
+    usage: Option<Arc<TokenUsage>>,
+    bootstrap: BootstrapConfig,
 }
 
 impl Evaluator {
@@ -44,47 +56,70 @@ impl Evaluator {
     pub fn new() -> Self {
         Self {
             metrics: Vec::new(),
+            weights: Vec::new(),
+            thresholds: HashMap::new(),
+            usage: None,
+            bootstrap: BootstrapConfig::default(),
         }
     }
 
-    /// Add a metric to the evaluator
+    /// Add a metric to the evaluator (weight 1.0)
     pub fn add_metric(&mut self, metric: Box<dyn Metric>) {
+        self.add_metric_weighted(metric, 1.0);
+    }
+
+    /// Add a metric that counts `weight` times toward the overall score
+    ///
+    /// Negative weights are treated as 0.0.
+    pub fn add_metric_weighted(&mut self, metric: Box<dyn Metric>, weight: f32) {
         self.metrics.push(metric);
+        self.weights.push(weight.max(0.0));
+    }
+
+    /// Fail the report when `metric_name` scores below `min_score`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore_eval::{AnswerFaithfulness, Evaluator, EvaluationInput};
+    /// # struct MyLLM;
+    /// # impl vecstore_eval::LLM for MyLLM {
+    /// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok("0.9".to_string()) }
+    /// # }
+    /// # let input = EvaluationInput::default();
+    /// let mut evaluator = Evaluator::new();
+    /// evaluator.add_metric_weighted(Box::new(AnswerFaithfulness::new(Box::new(MyLLM))), 2.0);
+    /// evaluator.set_threshold("answer_faithfulness", 0.8);
+    ///
+    /// let report = evaluator.evaluate(&input)?;
+    /// if !report.passed {
+    ///     eprintln!("violations: {:?}", report.violations);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn set_threshold(&mut self, metric_name: impl Into<String>, min_score: f32) {
+        self.thresholds.insert(metric_name.into(), min_score);
     }
 
     /// Evaluate a single input with all metrics
+    ///
+    /// Each result carries the metric's `duration_ms` and the usage its
+    /// LLM/embedder calls reported through [`UsageRecorder`].
     pub fn evaluate(&self, input: &EvaluationInput) -> Result<EvaluationReport> {
-        let mut results = Vec::new();
-        let mut metric_scores = HashMap::new();
-        let mut total_score = 0.0;
-        let mut count = 0;
-
-        for metric in &self.metrics {
-            let result = metric.evaluate(input)?;
-            total_score += result.score;
-            count += 1;
-
-            metric_scores.insert(result.metric_name.clone(), result.score);
-            results.push(result);
-        }
-
-        let overall_score = if count > 0 {
-            total_score / count as f32
-        } else {
-            0.0
-        };
-
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        Ok(EvaluationReport {
-            overall_score,
-            metric_scores,
-            results,
-            timestamp,
-        })
+        let results = self
+            .metrics
+            .iter()
+            .map(|metric| {
+                let start = Instant::now();
+                let mut result = evaluate_with_usage(metric.as_ref(), input)?;
+                result.duration_ms = Some(start.elapsed().as_millis() as u64);
+                Ok(result)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut report = build_report(results, &self.weights, &self.thresholds);
+        report.case_id = input.case_id.clone();
+        Ok(report)
     }
 
     /// Evaluate multiple inputs in batch
@@ -102,6 +137,7 @@ impl Evaluator {
     ///         contexts: vec!["Rust is a systems programming language.".to_string()],
     ///         answer: Some("Rust is a systems language.".to_string()),
     ///         ground_truth: Some("Rust is a memory-safe systems language.".to_string()),
+    ///         ..Default::default()
     ///     },
     ///     // More test cases...
     /// ];
@@ -116,10 +152,274 @@ impl Evaluator {
         inputs.iter().map(|input| self.evaluate(input)).collect()
     }
 
+    /// Evaluate multiple inputs on `concurrency` worker threads
+    ///
+    /// Each case gets its own `Result`, so one failing case does not abort
+    /// the batch. Results are returned in input order.
+    pub fn evaluate_batch_parallel(
+        &self,
+        inputs: &[EvaluationInput],
+        concurrency: usize,
+    ) -> Vec<Result<EvaluationReport>> {
+        self.evaluate_batch_parallel_with_progress(inputs, concurrency, |_, _| {})
+    }
+
+    /// Like [`evaluate_batch_parallel`](Self::evaluate_batch_parallel), calling
+    /// `progress(done, total)` after each case finishes
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use vecstore_eval::{Evaluator, EvaluationInput};
+    /// # let evaluator = Evaluator::new();
+    /// # let test_cases: Vec<EvaluationInput> = vec![];
+    /// let results = evaluator.evaluate_batch_parallel_with_progress(&test_cases, 8, |done, total| {
+    ///     eprint!("\r{}/{}", done, total);
+    /// });
+    ///
+    /// for (i, result) in results.iter().enumerate() {
+    ///     match result {
+    ///         Ok(report) => println!("case {}: {:.2}", i, report.overall_score),
+    ///         Err(e) => println!("case {} failed: {}", i, e),
+    ///     }
+    /// }
+    /// ```
+    pub fn evaluate_batch_parallel_with_progress<F>(
+        &self,
+        inputs: &[EvaluationInput],
+        concurrency: usize,
+        progress: F,
+    ) -> Vec<Result<EvaluationReport>>
+    where
+        F: Fn(usize, usize) + Sync,
+    {
+        let total = inputs.len();
+        let done = AtomicUsize::new(0);
+
+        let run = || {
+            inputs
+                .par_iter()
+                .map(|input| {
+                    let result = self.evaluate(input);
+                    progress(done.fetch_add(1, Ordering::SeqCst) + 1, total);
+                    result
+                })
+                .collect()
+        };
+
+        match rayon::ThreadPoolBuilder::new()
+            .num_threads(concurrency.max(1))
+            .build()
+        {
+            Ok(pool) => pool.install(run),
+            Err(e) => inputs
+                .iter()
+                .map(|_| Err(anyhow!("Failed to create thread pool: {}", e)))
+                .collect(),
+        }
+    }
+
     /// Calculate aggregate statistics across multiple reports
     ///
-    /// Returns average scores for each metric plus overall average.
+    /// Returns average scores for each metric plus overall average, with
+    /// spread and confidence intervals computed using the evaluator's
+    /// [`BootstrapConfig`].
     pub fn aggregate_reports(&self, reports: &[EvaluationReport]) -> AggregateStats {
+        AggregateStats::from_reports_with_bootstrap(reports, &self.bootstrap)
+    }
+
+    /// Set the resample count, confidence level and seed used for confidence intervals
+    pub fn set_bootstrap(&mut self, config: BootstrapConfig) {
+        self.bootstrap = config;
+    }
+
+    /// Get the number of metrics in this evaluator
+    pub fn metric_count(&self) -> usize {
+        self.metrics.len()
+    }
+
+    /// Get the names of all metrics in this evaluator
+    pub fn metric_names(&self) -> Vec<String> {
+        self.metrics.iter().map(|m| m.name().to_string()).collect()
+    }
+
+    /// Attach the token counter shared with this evaluator's LLM/embedder clients
+    pub fn track_usage(&mut self, usage: Arc<TokenUsage>) {
+        self.usage = Some(usage);
+    }
+
+    /// Token usage recorded so far, if a counter is attached
+    pub fn usage(&self) -> Option<UsageSnapshot> {
+        self.usage.as_ref().map(|u| u.snapshot())
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Run a metric inside a [`UsageRecorder`] scope and attach what it recorded
+pub(crate) fn evaluate_with_usage(
+    metric: &dyn Metric,
+    input: &EvaluationInput,
+) -> Result<MetricResult> {
+    let (result, usage) = UsageRecorder::capture(|| metric.evaluate(input));
+    let mut result = result?;
+    merge_usage(&mut result.usage, &usage);
+    Ok(result)
+}
+
+/// Assemble a report from metric results
+///
+/// `weights[i]` applies to `results[i]` (missing weights count as 1.0).
+/// Metrics without a threshold always pass.
+pub(crate) fn build_report(
+    results: Vec<MetricResult>,
+    weights: &[f32],
+    thresholds: &HashMap<String, f32>,
+) -> EvaluationReport {
+    let mut metric_scores = HashMap::new();
+    let mut violations = Vec::new();
+    let mut weighted_total = 0.0;
+    let mut total_weight = 0.0;
+
+    for (i, result) in results.iter().enumerate() {
+        let weight = weights.get(i).copied().unwrap_or(1.0);
+        weighted_total += result.score * weight;
+        total_weight += weight;
+        metric_scores.insert(result.metric_name.clone(), result.score);
+
+        if let Some(&threshold) = thresholds.get(&result.metric_name) {
+            if result.score < threshold {
+                violations.push(ThresholdViolation {
+                    metric_name: result.metric_name.clone(),
+                    score: result.score,
+                    threshold,
+                });
+            }
+        }
+    }
+
+    let overall_score = if total_weight > 0.0 {
+        weighted_total / total_weight
+    } else {
+        0.0
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    EvaluationReport {
+        overall_score,
+        metric_scores,
+        results,
+        timestamp,
+        passed: violations.is_empty(),
+        violations,
+        case_id: None,
+    }
+}
+
+/// Aggregate statistics across multiple evaluation reports
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateStats {
+    /// Number of reports aggregated
+    pub count: usize,
+
+    /// Average overall score across all reports
+    pub average_overall_score: f32,
+
+    /// Average score for each metric
+    pub average_metric_scores: HashMap<String, f32>,
+
+    /// Minimum overall score
+    pub min_score: f32,
+
+    /// Maximum overall score
+    pub max_score: f32,
+
+    /// Number of reports with no threshold violations
+    pub passed_count: usize,
+
+    /// Fraction of reports with no threshold violations
+    pub pass_rate: f32,
+
+    /// Latency and usage totals for each metric that reported any
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metric_costs: HashMap<String, MetricCost>,
+
+    /// Spread and confidence interval for each metric
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub metric_summaries: HashMap<String, ScoreSummary>,
+
+    /// Spread and confidence interval for the overall score
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overall_summary: Option<ScoreSummary>,
+
+    /// Per-case scores for each metric and for `"overall"`, in report order
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub scores: HashMap<String, Vec<f32>>,
+}
+
+/// Latency and usage of one metric across a run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricCost {
+    /// Number of results for this metric that reported a duration or usage
+    pub cases: usize,
+
+    /// Sum of `duration_ms` over all results
+    pub total_duration_ms: u64,
+
+    /// `total_duration_ms / cases`
+    pub mean_duration_ms: f32,
+
+    /// Sum of each usage counter over all results
+    pub usage: HashMap<String, u64>,
+}
+
+impl MetricCost {
+    /// Average of a usage counter per case (e.g. `"prompt_tokens"`)
+    pub fn mean_usage(&self, key: &str) -> f32 {
+        if self.cases == 0 {
+            return 0.0;
+        }
+        self.usage.get(key).copied().unwrap_or(0) as f32 / self.cases as f32
+    }
+}
+
+impl AggregateStats {
+    /// Calculate aggregate statistics across multiple reports
+    ///
+    /// Confidence intervals use [`BootstrapConfig::default`].
+    pub fn from_reports(reports: &[EvaluationReport]) -> Self {
+        Self::from_reports_with_bootstrap(reports, &BootstrapConfig::default())
+    }
+
+    /// Calculate aggregate statistics with explicit bootstrap settings
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use vecstore_eval::{AggregateStats, BootstrapConfig};
+    /// # let reports: Vec<vecstore_eval::EvaluationReport> = vec![];
+    /// let config = BootstrapConfig { resamples: 5000, seed: 7, ..Default::default() };
+    /// let stats = AggregateStats::from_reports_with_bootstrap(&reports, &config);
+    ///
+    /// if let Some(overall) = &stats.overall_summary {
+    ///     println!("{:.3} [{:.3}, {:.3}]", overall.mean, overall.ci_lower, overall.ci_upper);
+    /// }
+    /// for bucket in stats.score_distribution("overall", 10) {
+    ///     println!("{:.1}-{:.1}: {}", bucket.lower, bucket.upper, bucket.count);
+    /// }
+    /// ```
+    pub fn from_reports_with_bootstrap(
+        reports: &[EvaluationReport],
+        bootstrap: &BootstrapConfig,
+    ) -> Self {
         if reports.is_empty() {
             return AggregateStats {
                 count: 0,
@@ -127,11 +427,19 @@ impl Evaluator {
                 average_metric_scores: HashMap::new(),
                 min_score: 0.0,
                 max_score: 0.0,
+                passed_count: 0,
+                pass_rate: 0.0,
+                metric_costs: HashMap::new(),
+                metric_summaries: HashMap::new(),
+                overall_summary: None,
+                scores: HashMap::new(),
             };
         }
 
         let mut total_overall = 0.0;
         let mut metric_totals: HashMap<String, f32> = HashMap::new();
+        let mut metric_costs: HashMap<String, MetricCost> = HashMap::new();
+        let mut scores: HashMap<String, Vec<f32>> = HashMap::new();
         let mut min_score = f32::MAX;
         let mut max_score = f32::MIN;
 
@@ -142,10 +450,27 @@ impl Evaluator {
 
             for (name, score) in &report.metric_scores {
                 *metric_totals.entry(name.clone()).or_insert(0.0) += score;
+                scores.entry(name.clone()).or_default().push(*score);
+            }
+
+            // Reports saved before timing existed carry no cost information
+            for result in &report.results {
+                if result.duration_ms.is_none() && result.usage.is_empty() {
+                    continue;
+                }
+                let cost = metric_costs.entry(result.metric_name.clone()).or_default();
+                cost.cases += 1;
+                cost.total_duration_ms += result.duration_ms.unwrap_or(0);
+                merge_usage(&mut cost.usage, &result.usage);
             }
         }
 
+        for cost in metric_costs.values_mut() {
+            cost.mean_duration_ms = cost.total_duration_ms as f32 / cost.cases as f32;
+        }
+
         let count = reports.len();
+        let passed_count = reports.iter().filter(|r| r.passed).count();
         let average_overall_score = total_overall / count as f32;
 
         let average_metric_scores = metric_totals
@@ -153,51 +478,44 @@ impl Evaluator {
             .map(|(name, total)| (name, total / count as f32))
             .collect();
 
+        let metric_summaries = scores
+            .iter()
+            .filter_map(|(name, values)| {
+                ScoreSummary::from_scores(values, bootstrap).map(|s| (name.clone(), s))
+            })
+            .collect();
+
+        let overall: Vec<f32> = reports.iter().map(|r| r.overall_score).collect();
+        let overall_summary = ScoreSummary::from_scores(&overall, bootstrap);
+        scores.insert(OVERALL.to_string(), overall);
+
         AggregateStats {
             count,
             average_overall_score,
             average_metric_scores,
             min_score,
             max_score,
+            passed_count,
+            pass_rate: passed_count as f32 / count as f32,
+            metric_costs,
+            metric_summaries,
+            overall_summary,
+            scores,
         }
     }
 
-    /// Get the number of metrics in this evaluator
-    pub fn metric_count(&self) -> usize {
-        self.metrics.len()
-    }
-
-    /// Get the names of all metrics in this evaluator
-    pub fn metric_names(&self) -> Vec<String> {
-        self.metrics.iter().map(|m| m.name().to_string()).collect()
-    }
-}
-
-impl Default for Evaluator {
-    fn default() -> Self {
-        Self::new()
+    /// Histogram of a metric's per-case scores over 0.0-1.0
+    ///
+    /// Pass [`OVERALL`] (`"overall"`) for the overall score. Returns no
+    /// buckets for an unknown metric.
+    pub fn score_distribution(&self, metric_name: &str, n_buckets: usize) -> Vec<HistogramBucket> {
+        match self.scores.get(metric_name) {
+            Some(values) => histogram(values, n_buckets),
+            None => Vec::new(),
+        }
     }
 }
 
-/// Aggregate statistics across multiple evaluation reports
-#[derive(Debug, Clone)]
-pub struct AggregateStats {
-    /// Number of reports aggregated
-    pub count: usize,
-
-    /// Average overall score across all reports
-    pub average_overall_score: f32,
-
-    /// Average score for each metric
-    pub average_metric_scores: HashMap<String, f32>,
-
-    /// Minimum overall score
-    pub min_score: f32,
-
-    /// Maximum overall score
-    pub max_score: f32,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,6 +560,7 @@ mod tests {
             contexts: vec!["Rust is a systems programming language.".to_string()],
             answer: Some("Rust is a systems language.".to_string()),
             ground_truth: Some("Rust is a memory-safe systems language.".to_string()),
+            ..Default::default()
         };
 
         let report = evaluator.evaluate(&input).unwrap();
@@ -261,12 +580,14 @@ mod tests {
                 contexts: vec!["Context 1".to_string()],
                 answer: None,
                 ground_truth: None,
+                ..Default::default()
             },
             EvaluationInput {
                 query: "Query 2".to_string(),
                 contexts: vec!["Context 2".to_string()],
                 answer: None,
                 ground_truth: None,
+                ..Default::default()
             },
         ];
 
@@ -274,6 +595,81 @@ mod tests {
         assert_eq!(reports.len(), 2);
     }
 
+    struct SlowLLM;
+    impl LLM for SlowLLM {
+        fn generate(&self, prompt: &str) -> Result<String> {
+            std::thread::sleep(std::time::Duration::from_millis(40));
+            if prompt.contains("Query: fail") {
+                Err(anyhow!("judge unavailable"))
+            } else {
+                Ok("Yes".to_string())
+            }
+        }
+    }
+
+    fn slow_inputs(n: usize) -> Vec<EvaluationInput> {
+        (0..n)
+            .map(|i| EvaluationInput {
+                query: format!("Query {}", i),
+                contexts: vec![format!("Context {}", i)],
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batch_parallel_is_faster() {
+        let mut evaluator = Evaluator::new();
+        evaluator.add_metric(Box::new(ContextRelevance::new(Box::new(SlowLLM))));
+        let inputs = slow_inputs(8);
+
+        let start = std::time::Instant::now();
+        evaluator.evaluate_batch(&inputs).unwrap();
+        let serial = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let results = evaluator.evaluate_batch_parallel(&inputs, 8);
+        let parallel = start.elapsed();
+
+        assert_eq!(results.len(), 8);
+        assert!(results.iter().all(|r| r.is_ok()));
+        assert!(
+            parallel * 2 < serial,
+            "parallel {:?} vs serial {:?}",
+            parallel,
+            serial
+        );
+    }
+
+    #[test]
+    fn test_batch_parallel_isolates_errors_and_keeps_order() {
+        let mut evaluator = Evaluator::new();
+        evaluator.add_metric(Box::new(ContextRelevance::new(Box::new(SlowLLM))));
+        evaluator.add_metric(Box::new(AnswerCorrectness::new(Box::new(MockEmbedder))));
+
+        let mut inputs = slow_inputs(4);
+        for input in &mut inputs {
+            input.answer = Some(input.query.clone());
+            input.ground_truth = Some("x".repeat(input.query.len()));
+        }
+        inputs[2].query = "fail".to_string();
+
+        let calls = AtomicUsize::new(0);
+        let results = evaluator.evaluate_batch_parallel_with_progress(&inputs, 3, |done, total| {
+            assert_eq!(total, 4);
+            assert!(done <= total);
+            calls.fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert!(results[2].is_err());
+        for i in [0, 1, 3] {
+            let report = results[i].as_ref().unwrap();
+            let details = &report.results[1].details;
+            assert_eq!(details["answer_length"], inputs[i].query.len());
+        }
+    }
+
     #[test]
     fn test_aggregate_reports() {
         let reports = vec![
@@ -282,12 +678,18 @@ mod tests {
                 metric_scores: [("metric1".to_string(), 0.8)].iter().cloned().collect(),
                 results: vec![],
                 timestamp: 0,
+                passed: true,
+                violations: vec![],
+                case_id: None,
             },
             EvaluationReport {
                 overall_score: 0.6,
                 metric_scores: [("metric1".to_string(), 0.6)].iter().cloned().collect(),
                 results: vec![],
                 timestamp: 0,
+                passed: false,
+                violations: vec![],
+                case_id: None,
             },
         ];
 
@@ -298,6 +700,106 @@ mod tests {
         assert!((stats.average_overall_score - 0.7).abs() < 0.001); // Floating point tolerance
         assert_eq!(stats.min_score, 0.6);
         assert_eq!(stats.max_score, 0.8);
+        assert_eq!(stats.passed_count, 1);
+        assert_eq!(stats.pass_rate, 0.5);
+    }
+
+    struct FixedMetric(&'static str, f32);
+    impl Metric for FixedMetric {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn evaluate(&self, _input: &EvaluationInput) -> Result<MetricResult> {
+            Ok(MetricResult {
+                metric_name: self.0.to_string(),
+                score: self.1,
+                details: HashMap::new(),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_weighted_overall_score() {
+        let mut evaluator = Evaluator::new();
+        evaluator.add_metric_weighted(Box::new(FixedMetric("faithfulness", 1.0)), 2.0);
+        evaluator.add_metric(Box::new(FixedMetric("correctness", 0.4)));
+
+        let report = evaluator.evaluate(&EvaluationInput::default()).unwrap();
+        assert!((report.overall_score - 0.8).abs() < 1e-6);
+        assert!(report.passed);
+        assert!(report.violations.is_empty());
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let mut evaluator = Evaluator::new();
+        evaluator.add_metric(Box::new(FixedMetric("faithfulness", 0.6)));
+        evaluator.add_metric(Box::new(FixedMetric("correctness", 0.9)));
+        evaluator.set_threshold("faithfulness", 0.7);
+        evaluator.set_threshold("correctness", 0.9);
+
+        let report = evaluator.evaluate(&EvaluationInput::default()).unwrap();
+        assert!(!report.passed);
+        assert_eq!(
+            report.violations,
+            vec![ThresholdViolation {
+                metric_name: "faithfulness".to_string(),
+                score: 0.6,
+                threshold: 0.7,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_report_without_verdict_deserializes_as_passed() {
+        let report: EvaluationReport = serde_json::from_str(
+            r#"{"overall_score": 0.5, "metric_scores": {}, "results": [], "timestamp": 0}"#,
+        )
+        .unwrap();
+        assert!(report.passed);
+    }
+
+    #[test]
+    fn test_aggregate_distributions() {
+        let reports: Vec<EvaluationReport> = [0.2, 0.4, 0.6, 0.8, 1.0]
+            .iter()
+            .map(|&score| {
+                let result = MetricResult {
+                    metric_name: "faithfulness".to_string(),
+                    score,
+                    ..Default::default()
+                };
+                build_report(vec![result], &[], &HashMap::new())
+            })
+            .collect();
+
+        let mut evaluator = Evaluator::new();
+        let stats = evaluator.aggregate_reports(&reports);
+
+        let summary = &stats.metric_summaries["faithfulness"];
+        assert!((summary.median - 0.6).abs() < 1e-6);
+        assert!(summary.ci_lower < 0.6 && summary.ci_upper > 0.6);
+        assert_eq!(stats.overall_summary.as_ref().unwrap(), summary);
+
+        let buckets = stats.score_distribution("faithfulness", 5);
+        let counts: Vec<usize> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![0, 1, 1, 1, 2]);
+        assert_eq!(stats.score_distribution("overall", 5), buckets);
+        assert!(stats.score_distribution("missing", 5).is_empty());
+
+        // Same seed, same interval; a different seed moves it
+        assert_eq!(evaluator.aggregate_reports(&reports), stats);
+        evaluator.set_bootstrap(BootstrapConfig {
+            seed: 1,
+            ..Default::default()
+        });
+        let reseeded = evaluator.aggregate_reports(&reports);
+        assert_ne!(
+            reseeded.metric_summaries["faithfulness"].ci_lower,
+            summary.ci_lower
+        );
     }
 
     #[test]
@@ -308,6 +810,70 @@ mod tests {
         assert_eq!(stats.average_overall_score, 0.0);
     }
 
+    /// Reports one 100-token call per prompt
+    struct MeteredLLM;
+    impl LLM for MeteredLLM {
+        fn generate(&self, _prompt: &str) -> Result<String> {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+            UsageRecorder::record_llm_call(100, 1);
+            Ok("Yes".to_string())
+        }
+    }
+
+    #[test]
+    fn test_per_metric_latency_and_usage() {
+        let mut evaluator = Evaluator::new();
+        evaluator.add_metric(Box::new(ContextRelevance::new(Box::new(MeteredLLM))));
+        evaluator.add_metric(Box::new(FixedMetric("fixed", 1.0)));
+
+        let inputs: Vec<EvaluationInput> = (0..2)
+            .map(|i| EvaluationInput {
+                query: format!("Query {}", i),
+                contexts: vec!["a".to_string(), "b".to_string()],
+                ..Default::default()
+            })
+            .collect();
+        let reports = evaluator.evaluate_batch(&inputs).unwrap();
+
+        let relevance = &reports[0].results[0];
+        assert!(relevance.duration_ms.unwrap() >= 10);
+        assert_eq!(relevance.usage["llm_calls"], 2);
+        assert_eq!(relevance.usage["prompt_tokens"], 200);
+        assert!(reports[0].results[1].usage.is_empty());
+        assert!(reports[0].results[1].duration_ms.is_some());
+
+        let stats = evaluator.aggregate_reports(&reports);
+        let cost = &stats.metric_costs["context_relevance"];
+        assert_eq!(cost.cases, 2);
+        assert_eq!(cost.usage["llm_calls"], 4);
+        assert_eq!(cost.mean_usage("prompt_tokens"), 200.0);
+        assert!(cost.mean_duration_ms >= 10.0);
+        assert_eq!(stats.metric_costs["fixed"].cases, 2);
+
+        // Saved reports without timing produce no cost entries
+        let mut untimed = reports.clone();
+        for result in untimed.iter_mut().flat_map(|r| r.results.iter_mut()) {
+            result.duration_ms = None;
+            result.usage.clear();
+        }
+        assert!(evaluator.aggregate_reports(&untimed).metric_costs.is_empty());
+    }
+
+    #[test]
+    fn test_track_usage() {
+        let mut evaluator = Evaluator::new();
+        assert!(evaluator.usage().is_none());
+
+        let usage = Arc::new(TokenUsage::new());
+        evaluator.track_usage(Arc::clone(&usage));
+        usage.record(10, 2);
+        usage.record(5, 1);
+
+        let snapshot = evaluator.usage().unwrap();
+        assert_eq!(snapshot.total_tokens(), 18);
+        assert_eq!(snapshot.requests, 2);
+    }
+
     #[test]
     fn test_metric_names() {
         let mut evaluator = Evaluator::new();
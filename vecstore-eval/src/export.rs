@@ -0,0 +1,385 @@
+//! Exporting evaluation results
+//!
+//! [`ReportExport`] is implemented for a single [`EvaluationReport`], a batch
+//! of reports (`[EvaluationReport]`, so `Vec` works too) and
+//! [`AggregateStats`]. JSON exports load back with [`read_reports_json`] and
+//! [`AggregateStats::from_json_file`].
+
+use crate::evaluator::AggregateStats;
+use crate::stats::ScoreSummary;
+use crate::types::EvaluationReport;
+use crate::usage::{COMPLETION_TOKENS, EMBED_CALLS, LLM_CALLS, PROMPT_TOKENS};
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// Write evaluation results as JSON, CSV or Markdown
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{Evaluator, EvaluationInput, ReportExport};
+/// # let evaluator = Evaluator::new();
+/// # let cases: Vec<EvaluationInput> = vec![];
+/// let reports = evaluator.evaluate_batch(&cases)?;
+/// reports.to_json_file("eval/reports.json")?;
+/// reports.to_csv_file("eval/scores.csv")?;
+///
+/// let stats = evaluator.aggregate_reports(&reports);
+/// println!("{}", stats.to_markdown());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub trait ReportExport {
+    /// Write as pretty-printed JSON
+    fn to_json_file(&self, path: impl AsRef<Path>) -> Result<()>;
+
+    /// Write as CSV
+    fn to_csv_file(&self, path: impl AsRef<Path>) -> Result<()>;
+
+    /// Render a Markdown summary table
+    fn to_markdown(&self) -> String;
+}
+
+/// Load reports written by [`ReportExport::to_json_file`]
+///
+/// Accepts both a single report and an array of reports.
+pub fn read_reports_json(path: impl AsRef<Path>) -> Result<Vec<EvaluationReport>> {
+    let path = path.as_ref();
+    let file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_reader(std::io::BufReader::new(file))
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    if value.is_array() {
+        Ok(serde_json::from_value(value)?)
+    } else {
+        Ok(vec![serde_json::from_value(value)?])
+    }
+}
+
+impl AggregateStats {
+    /// Load stats written by [`ReportExport::to_json_file`]
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        serde_json::from_reader(std::io::BufReader::new(file))
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+fn write_json<T: serde::Serialize + ?Sized>(value: &T, path: &Path) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    serde_json::to_writer_pretty(BufWriter::new(file), value)?;
+    Ok(())
+}
+
+/// Write one row per case per metric
+fn write_reports_csv(reports: &[EvaluationReport], path: &Path) -> Result<()> {
+    let mut writer = csv::Writer::from_path(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+
+    writer.write_record(["case", "metric", "score", "overall_score", "passed", "timestamp"])?;
+    for (case, report) in reports.iter().enumerate() {
+        for result in &report.results {
+            writer.write_record([
+                case.to_string(),
+                result.metric_name.clone(),
+                result.score.to_string(),
+                report.overall_score.to_string(),
+                report.passed.to_string(),
+                report.timestamp.to_string(),
+            ])?;
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+impl ReportExport for EvaluationReport {
+    fn to_json_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_json(self, path.as_ref())
+    }
+
+    fn to_csv_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_reports_csv(std::slice::from_ref(self), path.as_ref())
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        out.push_str("| Metric | Score |\n|---|---|\n");
+        for result in &self.results {
+            let _ = writeln!(out, "| {} | {:.3} |", result.metric_name, result.score);
+        }
+        let _ = writeln!(out, "| **overall** | **{:.3}** |", self.overall_score);
+
+        if !self.violations.is_empty() {
+            out.push('\n');
+            for v in &self.violations {
+                let _ = writeln!(
+                    out,
+                    "- ❌ `{}` scored {:.3} (threshold {:.3})",
+                    v.metric_name, v.score, v.threshold
+                );
+            }
+        }
+
+        out
+    }
+}
+
+impl ReportExport for [EvaluationReport] {
+    fn to_json_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_json(self, path.as_ref())
+    }
+
+    fn to_csv_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_reports_csv(self, path.as_ref())
+    }
+
+    fn to_markdown(&self) -> String {
+        let metrics: BTreeSet<&str> = self
+            .iter()
+            .flat_map(|r| r.metric_scores.keys().map(String::as_str))
+            .collect();
+
+        let mut out = String::from("| Case | Overall |");
+        for metric in &metrics {
+            let _ = write!(out, " {} |", metric);
+        }
+        out.push_str(" Passed |\n|---|---|");
+        for _ in &metrics {
+            out.push_str("---|");
+        }
+        out.push_str("---|\n");
+
+        for (case, report) in self.iter().enumerate() {
+            let _ = write!(out, "| {} | {:.3} |", case, report.overall_score);
+            for metric in &metrics {
+                match report.metric_scores.get(*metric) {
+                    Some(score) => {
+                        let _ = write!(out, " {:.3} |", score);
+                    }
+                    None => out.push_str(" - |"),
+                }
+            }
+            out.push_str(if report.passed { " ✅ |\n" } else { " ❌ |\n" });
+        }
+
+        out
+    }
+}
+
+impl ReportExport for AggregateStats {
+    fn to_json_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        write_json(self, path.as_ref())
+    }
+
+    fn to_csv_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut writer = csv::Writer::from_path(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+
+        writer.write_record([
+            "metric",
+            "average_score",
+            "std_dev",
+            "median",
+            "p25",
+            "p75",
+            "ci_lower",
+            "ci_upper",
+        ])?;
+        let summary_fields = |summary: Option<&ScoreSummary>| -> Vec<String> {
+            match summary {
+                Some(s) => [s.std_dev, s.median, s.p25, s.p75, s.ci_lower, s.ci_upper]
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect(),
+                None => vec![String::new(); 6],
+            }
+        };
+        let metrics: BTreeSet<&String> = self.average_metric_scores.keys().collect();
+        for metric in metrics {
+            let mut record = vec![
+                metric.clone(),
+                self.average_metric_scores[metric].to_string(),
+            ];
+            record.extend(summary_fields(self.metric_summaries.get(metric)));
+            writer.write_record(&record)?;
+        }
+        let mut record = vec!["overall".to_string(), self.average_overall_score.to_string()];
+        record.extend(summary_fields(self.overall_summary.as_ref()));
+        writer.write_record(&record)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    fn to_markdown(&self) -> String {
+        let confidence = self
+            .overall_summary
+            .as_ref()
+            .map_or(0.95, |summary| summary.confidence);
+        let mut out = format!(
+            "| Metric | Average | Std dev | {:.0}% CI |\n|---|---|---|---|\n",
+            confidence * 100.0
+        );
+        let spread = |summary: Option<&ScoreSummary>| match summary {
+            Some(s) => format!("{:.3} | {:.3}–{:.3}", s.std_dev, s.ci_lower, s.ci_upper),
+            None => "- | -".to_string(),
+        };
+        let metrics: BTreeSet<&String> = self.average_metric_scores.keys().collect();
+        for metric in metrics {
+            let _ = writeln!(
+                out,
+                "| {} | {:.3} | {} |",
+                metric,
+                self.average_metric_scores[metric],
+                spread(self.metric_summaries.get(metric))
+            );
+        }
+        let _ = writeln!(
+            out,
+            "| **overall** | **{:.3}** | {} |",
+            self.average_overall_score,
+            spread(self.overall_summary.as_ref())
+        );
+        let _ = write!(
+            out,
+            "\n{} cases · range {:.3}–{:.3} · pass rate {:.1}%\n",
+            self.count,
+            self.min_score,
+            self.max_score,
+            self.pass_rate * 100.0
+        );
+
+        if !self.metric_costs.is_empty() {
+            out.push_str(
+                "\n| Metric | Mean latency (ms) | Prompt tokens | Completion tokens | LLM calls | Embed calls |\n\
+                 |---|---|---|---|---|---|\n",
+            );
+            let metrics: BTreeSet<&String> = self.metric_costs.keys().collect();
+            for metric in metrics {
+                let cost = &self.metric_costs[metric];
+                let total = |key: &str| cost.usage.get(key).copied().unwrap_or(0);
+                let _ = writeln!(
+                    out,
+                    "| {} | {:.1} | {} | {} | {} | {} |",
+                    metric,
+                    cost.mean_duration_ms,
+                    total(PROMPT_TOKENS),
+                    total(COMPLETION_TOKENS),
+                    total(LLM_CALLS),
+                    total(EMBED_CALLS)
+                );
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{MetricResult, ThresholdViolation};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn report(faithfulness: f32, correctness: f32, passed: bool) -> EvaluationReport {
+        let results = vec![
+            MetricResult {
+                metric_name: "faithfulness".to_string(),
+                score: faithfulness,
+                details: [("llm_response".to_string(), serde_json::json!("0.9"))]
+                    .into_iter()
+                    .collect(),
+                ..Default::default()
+            },
+            MetricResult {
+                metric_name: "correctness".to_string(),
+                score: correctness,
+                details: HashMap::new(),
+                ..Default::default()
+            },
+        ];
+
+        EvaluationReport {
+            overall_score: (faithfulness + correctness) / 2.0,
+            metric_scores: results
+                .iter()
+                .map(|r| (r.metric_name.clone(), r.score))
+                .collect(),
+            results,
+            timestamp: 1_700_000_000,
+            passed,
+            violations: if passed {
+                vec![]
+            } else {
+                vec![ThresholdViolation {
+                    metric_name: "faithfulness".to_string(),
+                    score: faithfulness,
+                    threshold: 0.5,
+                }]
+            },
+            case_id: None,
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let reports = vec![report(0.9, 0.7, true), report(0.3, 0.8, false)];
+
+        let path = dir.path().join("reports.json");
+        reports.to_json_file(&path).unwrap();
+        assert_eq!(read_reports_json(&path).unwrap(), reports);
+
+        let single = dir.path().join("single.json");
+        reports[1].to_json_file(&single).unwrap();
+        assert_eq!(read_reports_json(&single).unwrap(), vec![reports[1].clone()]);
+
+        let stats = AggregateStats::from_reports(&reports);
+        let stats_path = dir.path().join("stats.json");
+        stats.to_json_file(&stats_path).unwrap();
+        assert_eq!(AggregateStats::from_json_file(&stats_path).unwrap(), stats);
+    }
+
+    #[test]
+    fn test_csv_one_row_per_case_per_metric() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("scores.csv");
+        [report(0.9, 0.7, true), report(0.3, 0.8, false)]
+            .to_csv_file(&path)
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "case,metric,score,overall_score,passed,timestamp");
+        assert!(lines[3].starts_with("1,faithfulness,0.3,"));
+        assert!(lines[3].contains(",false,"));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let reports = vec![report(0.9, 0.7, true), report(0.3, 0.8, false)];
+
+        let table = reports.to_markdown();
+        assert!(table.starts_with("| Case | Overall | correctness | faithfulness | Passed |"));
+        assert!(table.contains("| 1 | 0.550 | 0.800 | 0.300 | ❌ |"));
+
+        let single = reports[1].to_markdown();
+        assert!(single.contains("| faithfulness | 0.300 |"));
+        assert!(single.contains("threshold 0.500"));
+
+        let stats = AggregateStats::from_reports(&reports).to_markdown();
+        assert!(stats.contains("| faithfulness | 0.600 |"));
+        assert!(stats.contains("pass rate 50.0%"));
+    }
+}
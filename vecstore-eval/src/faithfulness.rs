@@ -0,0 +1,371 @@
+//! Claim-Level Faithfulness
+//!
+//! [`AnswerFaithfulness`](crate::AnswerFaithfulness) asks the judge for one
+//! number per answer. [`FaithfulnessDetailed`] instead breaks the answer into
+//! atomic claims and checks each claim against the context, so every point
+//! lost can be traced to a specific sentence.
+
+use crate::metrics::{GenerationParams, LLM};
+use crate::prompt::PromptTemplate;
+use crate::types::{EvaluationInput, Metric, MetricResult};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Default prompt for splitting an answer into claims
+///
+/// Placeholders: `{query}`, `{answer}`.
+pub const DEFAULT_DECOMPOSE_PROMPT: &str = "Question: {query}\n\nAnswer: {answer}\n\n\
+Break the answer into short, self-contained factual claims. \
+Write one claim per line with no numbering or commentary. \
+If the answer makes no factual claims (for example it declines to answer), respond with NONE.";
+
+/// Default prompt for verifying one claim
+///
+/// Placeholders: `{query}`, `{context}`, `{claim}`.
+pub const DEFAULT_VERIFY_PROMPT: &str = "Context:\n{context}\n\nClaim: {claim}\n\n\
+Is the claim supported by the context? \
+Respond with exactly one of: SUPPORTED, NOT SUPPORTED, CONTRADICTED.";
+
+const DECOMPOSE_PLACEHOLDERS: &[&str] = &["query", "answer"];
+const VERIFY_PLACEHOLDERS: &[&str] = &["query", "context", "claim"];
+
+/// Verdict for a single claim
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimVerdict {
+    /// The context backs the claim
+    Supported,
+    /// The context neither backs nor refutes the claim
+    NotSupported,
+    /// The context says the opposite
+    Contradicted,
+}
+
+impl ClaimVerdict {
+    /// Interpret the judge's verdict, defaulting to `NotSupported`
+    pub fn parse(response: &str) -> Self {
+        let response = response.trim().to_lowercase();
+
+        if response.contains("contradict") {
+            ClaimVerdict::Contradicted
+        } else if response.contains("not supported")
+            || response.contains("not_supported")
+            || response.contains("unsupported")
+        {
+            ClaimVerdict::NotSupported
+        } else if response.contains("supported") {
+            ClaimVerdict::Supported
+        } else {
+            ClaimVerdict::NotSupported
+        }
+    }
+}
+
+/// A claim extracted from the answer and its verdict
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClaimJudgment {
+    /// Claim text as produced by the judge
+    pub claim: String,
+
+    /// Whether the context supports the claim
+    pub verdict: ClaimVerdict,
+}
+
+/// Faithfulness scored as supported claims / total claims
+///
+/// Details contain `claims` (each claim with its verdict), per-verdict counts
+/// and `abstained`. An answer with no claims (e.g. "I don't know") scores 1.0
+/// with `abstained: true`.
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{EvaluationInput, FaithfulnessDetailed, Metric};
+/// # struct MyLLM;
+/// # impl vecstore_eval::LLM for MyLLM {
+/// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok("SUPPORTED".to_string()) }
+/// # }
+///
+/// let metric = FaithfulnessDetailed::new(Box::new(MyLLM));
+///
+/// let input = EvaluationInput {
+///     query: "What is Rust?".to_string(),
+///     contexts: vec!["Rust is a systems programming language.".to_string()],
+///     answer: Some("Rust is a systems language. It was created at Mozilla.".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let result = metric.evaluate(&input)?;
+/// println!("{}", result.details["claims"]);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct FaithfulnessDetailed {
+    llm: Box<dyn LLM>,
+    decompose_prompt: PromptTemplate,
+    verify_prompt: PromptTemplate,
+    params: GenerationParams,
+}
+
+impl FaithfulnessDetailed {
+    /// Create the metric with the default prompts
+    pub fn new(llm: Box<dyn LLM>) -> Self {
+        Self {
+            llm,
+            decompose_prompt: PromptTemplate::new(DEFAULT_DECOMPOSE_PROMPT),
+            verify_prompt: PromptTemplate::new(DEFAULT_VERIFY_PROMPT),
+            params: GenerationParams::deterministic(),
+        }
+    }
+
+    /// Override the claim decomposition prompt (`{query}`, `{answer}`)
+    pub fn with_decompose_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("claim decomposition", DECOMPOSE_PLACEHOLDERS)?;
+        self.decompose_prompt = template;
+        Ok(self)
+    }
+
+    /// Override the claim verification prompt (`{query}`, `{context}`, `{claim}`)
+    pub fn with_verify_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("claim verification", VERIFY_PLACEHOLDERS)?;
+        self.verify_prompt = template;
+        Ok(self)
+    }
+
+    /// Override the sampling params (default: temperature 0)
+    pub fn with_generation_params(mut self, params: GenerationParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Ask the judge to split the answer into claims
+    pub fn decompose(&self, query: &str, answer: &str) -> Result<Vec<String>> {
+        let prompt = self
+            .decompose_prompt
+            .render(&[("query", query), ("answer", answer)]);
+
+        let response = self.llm.generate_with_params(&prompt, &self.params)?;
+        Ok(parse_claims(&response))
+    }
+
+    /// Ask the judge whether the context supports a claim
+    pub fn verify(&self, query: &str, context: &str, claim: &str) -> Result<ClaimVerdict> {
+        let prompt = self
+            .verify_prompt
+            .render(&[("query", query), ("context", context), ("claim", claim)]);
+
+        let response = self.llm.generate_with_params(&prompt, &self.params)?;
+        Ok(ClaimVerdict::parse(&response))
+    }
+}
+
+impl Metric for FaithfulnessDetailed {
+    fn name(&self) -> &str {
+        "faithfulness_detailed"
+    }
+
+    fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let answer = input
+            .answer
+            .as_ref()
+            .ok_or_else(|| anyhow!("Answer required for faithfulness metric"))?;
+
+        let claims = self.decompose(&input.query, answer)?;
+        let context = input.contexts.join("\n\n");
+
+        let judgments = claims
+            .into_iter()
+            .map(|claim| {
+                // Nothing can be supported by an empty context
+                let verdict = if input.contexts.is_empty() {
+                    ClaimVerdict::NotSupported
+                } else {
+                    self.verify(&input.query, &context, &claim)?
+                };
+                Ok(ClaimJudgment { claim, verdict })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(self.params.record(claims_result(self.name(), &judgments)))
+    }
+}
+
+/// Split the judge's response into claims, one per line
+pub(crate) fn parse_claims(response: &str) -> Vec<String> {
+    response
+        .lines()
+        .map(strip_list_marker)
+        .filter(|line| !line.is_empty() && !line.eq_ignore_ascii_case("none"))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Remove leading bullets ("-", "*", "•") and numbering ("1.", "2)")
+fn strip_list_marker(line: &str) -> &str {
+    let line = line.trim();
+    let line = line.trim_start_matches(['-', '*', '•']).trim_start();
+
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(['.', ')']) {
+            return rest.trim_start();
+        }
+    }
+
+    line
+}
+
+/// Score claims as supported / total, treating no claims as an abstention
+pub(crate) fn claims_result(metric_name: &str, judgments: &[ClaimJudgment]) -> MetricResult {
+    let count = |verdict: ClaimVerdict| judgments.iter().filter(|j| j.verdict == verdict).count();
+    let supported = count(ClaimVerdict::Supported);
+    let abstained = judgments.is_empty();
+
+    let score = if abstained {
+        1.0
+    } else {
+        supported as f32 / judgments.len() as f32
+    };
+
+    let mut details = HashMap::new();
+    details.insert("claims".to_string(), serde_json::json!(judgments));
+    details.insert("total_claims".to_string(), serde_json::json!(judgments.len()));
+    details.insert("supported_count".to_string(), serde_json::json!(supported));
+    details.insert(
+        "not_supported_count".to_string(),
+        serde_json::json!(count(ClaimVerdict::NotSupported)),
+    );
+    details.insert(
+        "contradicted_count".to_string(),
+        serde_json::json!(count(ClaimVerdict::Contradicted)),
+    );
+    details.insert("abstained".to_string(), serde_json::json!(abstained));
+
+    MetricResult {
+        metric_name: metric_name.to_string(),
+        score,
+        details,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Decomposes into fixed claims and judges by keyword
+    struct ClaimLLM {
+        claims: &'static str,
+        prompts: Mutex<Vec<String>>,
+    }
+
+    impl ClaimLLM {
+        fn new(claims: &'static str) -> Self {
+            Self {
+                claims,
+                prompts: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl LLM for ClaimLLM {
+        fn generate(&self, prompt: &str) -> Result<String> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+
+            if prompt.contains("Break the answer") || prompt.starts_with("SPLIT") {
+                return Ok(self.claims.to_string());
+            }
+            if prompt.contains("Claim: Rust is a systems language") {
+                Ok("SUPPORTED".to_string())
+            } else if prompt.contains("Claim: Rust has a garbage collector") {
+                Ok("Contradicted".to_string())
+            } else {
+                Ok("NOT SUPPORTED".to_string())
+            }
+        }
+    }
+
+    fn input(answer: &str) -> EvaluationInput {
+        EvaluationInput {
+            query: "What is Rust?".to_string(),
+            contexts: vec!["Rust is a systems programming language without a GC.".to_string()],
+            answer: Some(answer.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_claim_verdicts_and_score() {
+        let llm = ClaimLLM::new(
+            "1. Rust is a systems language\n2. Rust has a garbage collector\n- Rust was released in 1990",
+        );
+        let metric = FaithfulnessDetailed::new(Box::new(llm));
+
+        let result = metric.evaluate(&input("...")).unwrap();
+
+        assert!((result.score - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(result.details["total_claims"], 3);
+        assert_eq!(result.details["contradicted_count"], 1);
+        assert_eq!(result.details["not_supported_count"], 1);
+        assert_eq!(result.details["abstained"], false);
+        assert_eq!(result.details["claims"][0]["claim"], "Rust is a systems language");
+        assert_eq!(result.details["claims"][0]["verdict"], "supported");
+        assert_eq!(result.details["claims"][1]["verdict"], "contradicted");
+    }
+
+    #[test]
+    fn test_abstention_scores_one() {
+        let metric = FaithfulnessDetailed::new(Box::new(ClaimLLM::new("NONE")));
+        let result = metric.evaluate(&input("I don't know.")).unwrap();
+
+        assert_eq!(result.score, 1.0);
+        assert_eq!(result.details["abstained"], true);
+        assert_eq!(result.details["total_claims"], 0);
+    }
+
+    #[test]
+    fn test_custom_prompts() {
+        let metric = FaithfulnessDetailed::new(Box::new(ClaimLLM::new("Rust is a systems language")))
+            .with_decompose_prompt("SPLIT {answer}")
+            .unwrap()
+            .with_verify_prompt("CHECK {claim}\nClaim: {claim}\nAGAINST {context}")
+            .unwrap();
+
+        let result = metric.evaluate(&input("Rust is a systems language.")).unwrap();
+        assert_eq!(result.score, 1.0);
+    }
+
+    #[test]
+    fn test_unknown_placeholder_rejected() {
+        let metric = FaithfulnessDetailed::new(Box::new(ClaimLLM::new("NONE")));
+        let err = metric.with_verify_prompt("{claim} {answer}").err().unwrap();
+        assert!(err.to_string().contains("{answer}"));
+    }
+
+    #[test]
+    fn test_missing_answer() {
+        let metric = FaithfulnessDetailed::new(Box::new(ClaimLLM::new("NONE")));
+        let input = EvaluationInput {
+            query: "q".to_string(),
+            ..Default::default()
+        };
+        assert!(metric.evaluate(&input).is_err());
+    }
+
+    #[test]
+    fn test_parse_claims_and_verdicts() {
+        assert_eq!(
+            parse_claims("* First claim\n\n2) Second claim\n  • Third  "),
+            vec!["First claim", "Second claim", "Third"]
+        );
+        assert!(parse_claims("none").is_empty());
+
+        assert_eq!(ClaimVerdict::parse("Supported."), ClaimVerdict::Supported);
+        assert_eq!(ClaimVerdict::parse("not supported"), ClaimVerdict::NotSupported);
+        assert_eq!(ClaimVerdict::parse("CONTRADICTED"), ClaimVerdict::Contradicted);
+        assert_eq!(ClaimVerdict::parse("unclear"), ClaimVerdict::NotSupported);
+    }
+}
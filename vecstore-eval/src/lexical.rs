@@ -0,0 +1,488 @@
+//! Lexical Correctness Metrics
+//!
+//! Overlap-based answer scoring that needs no LLM or embedder, only
+//! `answer` and `ground_truth`:
+//!
+//! - **ROUGE-1 / ROUGE-2**: unigram / bigram overlap F-measure
+//! - **ROUGE-L**: longest-common-subsequence F-measure
+//! - **BLEU**: up to 4-gram precision with brevity penalty and add-one
+//!   smoothing for n > 1 (Lin & Och, 2004)
+//! - **Token F1**: bag-of-tokens F1 as in the SQuAD evaluation script
+//!
+//! Text is lowercased, punctuation is stripped and tokens are split on
+//! whitespace. Token F1 additionally drops the articles "a", "an", "the".
+
+use crate::types::{EvaluationInput, Metric, MetricResult};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Highest n-gram order used by BLEU
+const BLEU_MAX_ORDER: usize = 4;
+
+/// A lexical sub-metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LexicalScore {
+    /// Unigram overlap F-measure
+    Rouge1,
+    /// Bigram overlap F-measure
+    Rouge2,
+    /// Longest-common-subsequence F-measure
+    RougeL,
+    /// Smoothed sentence BLEU
+    Bleu,
+    /// SQuAD-style token F1
+    TokenF1,
+}
+
+impl LexicalScore {
+    /// Every sub-metric
+    pub const ALL: [LexicalScore; 5] = [
+        LexicalScore::Rouge1,
+        LexicalScore::Rouge2,
+        LexicalScore::RougeL,
+        LexicalScore::Bleu,
+        LexicalScore::TokenF1,
+    ];
+
+    /// Key used in [`MetricResult::details`]
+    pub fn key(&self) -> &'static str {
+        match self {
+            LexicalScore::Rouge1 => "rouge_1",
+            LexicalScore::Rouge2 => "rouge_2",
+            LexicalScore::RougeL => "rouge_l",
+            LexicalScore::Bleu => "bleu",
+            LexicalScore::TokenF1 => "token_f1",
+        }
+    }
+}
+
+/// Answer vs. ground truth overlap without an embedding model
+///
+/// All selected sub-metrics are reported in `details` under their
+/// [`LexicalScore::key`]. The headline `score` defaults to ROUGE-L.
+///
+/// # Example
+///
+/// ```
+/// use vecstore_eval::{EvaluationInput, LexicalCorrectness, LexicalScore, Metric};
+///
+/// let metric = LexicalCorrectness::new().with_headline(LexicalScore::TokenF1);
+///
+/// let input = EvaluationInput {
+///     query: "What is Rust?".to_string(),
+///     answer: Some("Rust is a systems language.".to_string()),
+///     ground_truth: Some("Rust is a memory-safe systems language.".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let result = metric.evaluate(&input)?;
+/// assert!(result.details.contains_key("bleu"));
+/// assert!(result.score > 0.5);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct LexicalCorrectness {
+    scores: Vec<LexicalScore>,
+    headline: LexicalScore,
+    stemming: bool,
+}
+
+impl LexicalCorrectness {
+    /// Compute every sub-metric, with ROUGE-L as the headline score
+    pub fn new() -> Self {
+        Self {
+            scores: LexicalScore::ALL.to_vec(),
+            headline: LexicalScore::RougeL,
+            stemming: false,
+        }
+    }
+
+    /// Choose which sub-metrics to compute (the headline is always included)
+    pub fn with_scores(mut self, scores: Vec<LexicalScore>) -> Self {
+        self.scores = scores;
+        self
+    }
+
+    /// Choose the sub-metric reported as `score`
+    pub fn with_headline(mut self, headline: LexicalScore) -> Self {
+        self.headline = headline;
+        self
+    }
+
+    /// Strip common English suffixes ("-ing", "-ed", "-s", ...) before comparing
+    pub fn with_stemming(mut self, stemming: bool) -> Self {
+        self.stemming = stemming;
+        self
+    }
+
+    /// Lowercase, strip punctuation, split on whitespace and optionally stem
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        text.to_lowercase()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+            .collect::<String>()
+            .split_whitespace()
+            .map(|token| {
+                if self.stemming {
+                    stem(token)
+                } else {
+                    token.to_string()
+                }
+            })
+            .collect()
+    }
+
+    /// Compute one sub-metric on pre-tokenized text
+    pub fn compute(score: LexicalScore, candidate: &[String], reference: &[String]) -> f32 {
+        match score {
+            LexicalScore::Rouge1 => rouge_n(candidate, reference, 1),
+            LexicalScore::Rouge2 => rouge_n(candidate, reference, 2),
+            LexicalScore::RougeL => rouge_l(candidate, reference),
+            LexicalScore::Bleu => bleu(candidate, reference),
+            LexicalScore::TokenF1 => token_f1(candidate, reference),
+        }
+    }
+}
+
+impl Default for LexicalCorrectness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metric for LexicalCorrectness {
+    fn name(&self) -> &str {
+        "lexical_correctness"
+    }
+
+    fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let answer = input
+            .answer
+            .as_ref()
+            .ok_or_else(|| anyhow!("Answer required for lexical correctness metric"))?;
+
+        let ground_truth = input
+            .ground_truth
+            .as_ref()
+            .ok_or_else(|| anyhow!("Ground truth required for lexical correctness metric"))?;
+
+        let candidate = self.tokenize(answer);
+        let reference = self.tokenize(ground_truth);
+
+        let mut details = HashMap::new();
+        for score in self.scores.iter().chain(std::iter::once(&self.headline)) {
+            details.entry(score.key().to_string()).or_insert_with(|| {
+                serde_json::json!(Self::compute(*score, &candidate, &reference))
+            });
+        }
+        details.insert(
+            "answer_tokens".to_string(),
+            serde_json::json!(candidate.len()),
+        );
+        details.insert(
+            "ground_truth_tokens".to_string(),
+            serde_json::json!(reference.len()),
+        );
+
+        Ok(MetricResult {
+            metric_name: self.name().to_string(),
+            score: Self::compute(self.headline, &candidate, &reference),
+            details,
+            ..Default::default()
+        })
+    }
+}
+
+/// Harmonic mean of precision and recall
+fn f_measure(precision: f32, recall: f32) -> f32 {
+    if precision + recall == 0.0 {
+        0.0
+    } else {
+        2.0 * precision * recall / (precision + recall)
+    }
+}
+
+/// Counts of each n-gram in `tokens`
+fn ngram_counts(tokens: &[String], n: usize) -> HashMap<&[String], usize> {
+    let mut counts = HashMap::new();
+    if n > 0 && tokens.len() >= n {
+        for gram in tokens.windows(n) {
+            *counts.entry(gram).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+/// Clipped n-gram matches and the candidate's n-gram total
+fn ngram_overlap(candidate: &[String], reference: &[String], n: usize) -> (usize, usize) {
+    let candidate_counts = ngram_counts(candidate, n);
+    let reference_counts = ngram_counts(reference, n);
+
+    let matches = candidate_counts
+        .iter()
+        .map(|(gram, &count)| count.min(reference_counts.get(gram).copied().unwrap_or(0)))
+        .sum();
+    let total = candidate.len().saturating_sub(n - 1);
+
+    (matches, total)
+}
+
+fn rouge_n(candidate: &[String], reference: &[String], n: usize) -> f32 {
+    let (matches, candidate_total) = ngram_overlap(candidate, reference, n);
+    let reference_total = reference.len().saturating_sub(n - 1);
+
+    if candidate_total == 0 || reference_total == 0 {
+        return if candidate_total == reference_total {
+            1.0
+        } else {
+            0.0
+        };
+    }
+
+    f_measure(
+        matches as f32 / candidate_total as f32,
+        matches as f32 / reference_total as f32,
+    )
+}
+
+fn rouge_l(candidate: &[String], reference: &[String]) -> f32 {
+    if candidate.is_empty() || reference.is_empty() {
+        return if candidate.len() == reference.len() {
+            1.0
+        } else {
+            0.0
+        };
+    }
+
+    // Rolling single-row LCS table
+    let mut row = vec![0usize; reference.len() + 1];
+    for c in candidate {
+        let mut diagonal = 0;
+        for (j, r) in reference.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if c == r {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    let lcs = row[reference.len()] as f32;
+
+    f_measure(lcs / candidate.len() as f32, lcs / reference.len() as f32)
+}
+
+fn bleu(candidate: &[String], reference: &[String]) -> f32 {
+    if candidate.is_empty() {
+        return 0.0;
+    }
+
+    let mut log_precision_sum = 0.0f32;
+    for n in 1..=BLEU_MAX_ORDER {
+        let (matches, total) = ngram_overlap(candidate, reference, n);
+        let precision = if n == 1 {
+            if matches == 0 {
+                return 0.0;
+            }
+            matches as f32 / total as f32
+        } else {
+            (matches as f32 + 1.0) / (total as f32 + 1.0)
+        };
+        log_precision_sum += precision.ln();
+    }
+
+    let brevity_penalty = if candidate.len() >= reference.len() {
+        1.0
+    } else {
+        (1.0 - reference.len() as f32 / candidate.len() as f32).exp()
+    };
+
+    brevity_penalty * (log_precision_sum / BLEU_MAX_ORDER as f32).exp()
+}
+
+fn token_f1(candidate: &[String], reference: &[String]) -> f32 {
+    let is_article = |t: &&String| matches!(t.as_str(), "a" | "an" | "the");
+    let candidate: Vec<String> = candidate
+        .iter()
+        .filter(|t| !is_article(t))
+        .cloned()
+        .collect();
+    let reference: Vec<String> = reference
+        .iter()
+        .filter(|t| !is_article(t))
+        .cloned()
+        .collect();
+
+    if candidate.is_empty() || reference.is_empty() {
+        return if candidate.len() == reference.len() {
+            1.0
+        } else {
+            0.0
+        };
+    }
+
+    let (common, _) = ngram_overlap(&candidate, &reference, 1);
+    if common == 0 {
+        return 0.0;
+    }
+
+    f_measure(
+        common as f32 / candidate.len() as f32,
+        common as f32 / reference.len() as f32,
+    )
+}
+
+/// Light suffix-stripping stemmer for English
+///
+/// Loosely follows Porter's first steps: plurals, "-ed"/"-ing" (undoubling a
+/// trailing consonant, so "running" becomes "run") and a few derivational
+/// endings.
+fn stem(token: &str) -> String {
+    const SUFFIXES: [(&str, &str); 8] = [
+        ("ational", "ate"),
+        ("ization", "ize"),
+        ("iness", "y"),
+        ("ness", ""),
+        ("ing", ""),
+        ("ies", "y"),
+        ("ed", ""),
+        ("s", ""),
+    ];
+
+    for (suffix, replacement) in SUFFIXES {
+        if let Some(stem) = token.strip_suffix(suffix) {
+            // Keep short words ("is", "bed", "sing") and "-ss" words intact
+            if stem.chars().count() < 3 || stem.ends_with('s') {
+                continue;
+            }
+
+            let mut stem = stem.to_string();
+            if matches!(suffix, "ing" | "ed") {
+                let mut tail = stem.chars().rev();
+                if let (Some(last), Some(prev)) = (tail.next(), tail.next()) {
+                    if last == prev && !"aeioulsz".contains(last) {
+                        stem.pop();
+                    }
+                }
+            }
+            stem.push_str(replacement);
+            return stem;
+        }
+    }
+
+    token.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn tokens(text: &str) -> Vec<String> {
+        LexicalCorrectness::new().tokenize(text)
+    }
+
+    fn input(answer: Option<&str>, ground_truth: Option<&str>) -> EvaluationInput {
+        EvaluationInput {
+            query: "q".to_string(),
+            answer: answer.map(str::to_string),
+            ground_truth: ground_truth.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokens("Rust's memory-safe, FAST!"),
+            vec!["rust", "s", "memory", "safe", "fast"]
+        );
+
+        let stemmed = LexicalCorrectness::new().with_stemming(true);
+        assert_eq!(
+            stemmed.tokenize("running languages is"),
+            vec!["run", "language", "is"]
+        );
+    }
+
+    #[test]
+    fn test_identical_texts_score_one() {
+        let text = "the quick brown fox jumps over the lazy dog";
+        let t = tokens(text);
+        for score in LexicalScore::ALL {
+            assert_relative_eq!(
+                LexicalCorrectness::compute(score, &t, &t),
+                1.0,
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn test_rouge() {
+        let candidate = tokens("the cat sat on the mat");
+        let reference = tokens("the cat is on the mat");
+
+        // 5 of 6 unigrams match in both directions
+        assert_relative_eq!(
+            rouge_n(&candidate, &reference, 1),
+            5.0 / 6.0,
+            epsilon = 1e-6
+        );
+        // Bigrams: "the cat", "on the", "the mat" of 5
+        assert_relative_eq!(
+            rouge_n(&candidate, &reference, 2),
+            3.0 / 5.0,
+            epsilon = 1e-6
+        );
+        // LCS: the cat on the mat
+        assert_relative_eq!(rouge_l(&candidate, &reference), 5.0 / 6.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_bleu() {
+        let reference = tokens("the cat is on the mat");
+        assert_eq!(bleu(&tokens("dog"), &reference), 0.0);
+
+        // Short candidates are penalized
+        let short = bleu(&tokens("the cat"), &reference);
+        let long = bleu(&tokens("the cat is on a mat"), &reference);
+        assert!(short < long, "{} vs {}", short, long);
+        assert!(long < 1.0);
+    }
+
+    #[test]
+    fn test_token_f1_squad_style() {
+        // Articles are ignored: "cat sat" vs "cat sat down"
+        let f1 = token_f1(&tokens("The cat sat"), &tokens("A cat sat down"));
+        assert_relative_eq!(f1, 0.8, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_metric_details_and_headline() {
+        let metric = LexicalCorrectness::new()
+            .with_scores(vec![LexicalScore::Rouge1])
+            .with_headline(LexicalScore::TokenF1);
+
+        let result = metric
+            .evaluate(&input(Some("The cat sat"), Some("A cat sat down")))
+            .unwrap();
+
+        assert_relative_eq!(result.score, 0.8, epsilon = 1e-6);
+        assert!(result.details.contains_key("rouge_1"));
+        assert!(result.details.contains_key("token_f1"));
+        assert!(!result.details.contains_key("bleu"));
+    }
+
+    #[test]
+    fn test_missing_fields() {
+        let metric = LexicalCorrectness::new();
+
+        let err = metric.evaluate(&input(None, Some("x"))).unwrap_err();
+        assert!(err.to_string().contains("Answer required"));
+
+        let err = metric.evaluate(&input(Some("x"), None)).unwrap_err();
+        assert!(err.to_string().contains("Ground truth required"));
+    }
+}
@@ -8,7 +8,12 @@
 //!
 //! - **Context Relevance**: Are retrieved documents relevant to the query?
 //! - **Answer Faithfulness**: Is the answer supported by the retrieved context?
+//! - **Detailed Faithfulness**: Per-claim verdicts for the answer
+//! - **Context Precision / Recall**: Were useful contexts ranked early, and is the ground truth covered?
+//! - **Answer Relevance**: Does the answer address the query?
 //! - **Answer Correctness**: How similar is the answer to ground truth?
+//! - **Lexical Correctness**: ROUGE, BLEU and token F1 against ground truth
+//! - **Retrieval Metrics**: Precision@k, recall@k, MRR, hit rate and NDCG from labeled IDs
 //!
 //! ## Quick Start
 //!
@@ -26,6 +31,7 @@
 //!     contexts: vec!["Rust is a systems programming language...".to_string()],
 //!     answer: Some("Rust is a fast, safe systems language.".to_string()),
 //!     ground_truth: Some("Rust is a memory-safe systems programming language.".to_string()),
+//!     ..Default::default()
 //! };
 //!
 //! let report = evaluator.evaluate(&input)?;
@@ -44,11 +50,147 @@
 //! Measures whether the answer is supported by the retrieved context (no hallucination).
 //! Uses an LLM to judge faithfulness. Score: 0.0-1.0.
 //!
+//! ### Detailed Faithfulness (Claim Decomposition)
+//!
+//! Splits the answer into atomic claims and verifies each against the
+//! context. Score: supported claims / total claims, with every verdict listed.
+//!
+//! ### Citation Mapping (LLM-as-Judge)
+//!
+//! Attributes each answer sentence to the context that supports it, or to
+//! none. Score: fraction of sentences attributed, with the unsupported
+//! sentences listed for highlighting.
+//!
+//! ### Context Precision and Recall (LLM-as-Judge)
+//!
+//! Context precision weights each useful context by its rank, as in RAGAS.
+//! Context recall checks which ground-truth statements are attributable to
+//! the retrieved contexts.
+//!
+//! ### Answer Relevance (LLM + Embeddings)
+//!
+//! Generates questions the answer would respond to and compares them with
+//! the original query by cosine similarity. Non-committal answers score 0.0.
+//!
 //! ### Answer Correctness (Embedding Similarity)
 //!
 //! Measures semantic similarity between generated answer and ground truth.
 //! Uses embeddings to calculate similarity. Score: 0.0-1.0.
 //!
+//! ### Lexical Correctness (Token Overlap)
+//!
+//! ROUGE-1/2/L, smoothed BLEU and SQuAD-style token F1 between the answer and
+//! ground truth. No LLM or embedder required.
+//!
+//! ### Retrieval Metrics (Labeled IDs)
+//!
+//! Compares `retrieved_ids` against `relevant_ids` with classic IR measures
+//! (precision@k, recall@k, hit rate, MRR, NDCG@k). No LLM required.
+//!
+//! ## End-to-End RAG Runs
+//!
+//! With the `vecstore` feature, [`RagHarness`] drives retrieval against a
+//! live `VecStore`, optionally generates answers with an [`LLM`], and
+//! evaluates the result. [`RagHarness::sweep`] produces one
+//! [`AggregateStats`] per [`RagConfig`] for tuning `k` and `ef_search`.
+//!
+//! ## Datasets and Export
+//!
+//! [`EvaluationDataset`] loads test cases from JSONL or CSV. Reports and
+//! [`AggregateStats`] implement [`ReportExport`] for JSON, CSV and Markdown
+//! output.
+//!
+//! ## Custom Prompts
+//!
+//! Every LLM-as-judge metric has a `with_prompt` style builder taking a
+//! [`PromptTemplate`] with named placeholders (`{query}`, `{context}`,
+//! `{answer}`, ...). Unknown placeholders are rejected when the template is
+//! set. The built-in English prompts are the defaults.
+//!
+//! ## Structured Output
+//!
+//! [`LLM::generate_structured`] asks for a JSON reply and parses it,
+//! stripping markdown fences; override it to use a provider's JSON mode.
+//! [`ContextRelevance`] and [`AnswerFaithfulness`] request JSON verdicts by
+//! default and still read plain-text replies when a model ignores the
+//! instruction.
+//!
+//! ## Reproducible Judging
+//!
+//! Judge metrics call [`LLM::generate_with_params`] with temperature 0 by
+//! default; each has a `with_generation_params` builder for other
+//! [`GenerationParams`] (temperature, seed, max tokens). The params used are
+//! recorded under `generation_params` in the result details.
+//!
+//! ## Pairwise Comparison
+//!
+//! [`PairwiseComparator`] asks the judge which of two answers is better,
+//! runs every case a second time with the answers swapped to cancel position
+//! bias, and reports win rates in a [`PairwiseReport`]. Cases where the two
+//! orderings disagree are counted as inconsistent.
+//!
+//! ## Noise Robustness
+//!
+//! [`RobustnessHarness`] re-evaluates each case with distractor contexts
+//! injected at configurable [`DistractorPosition`]s, optionally regenerating
+//! the answer through your pipeline, and reports how far scores drop
+//! compared to the clean baseline.
+//!
+//! ## Score Distributions
+//!
+//! [`AggregateStats`] reports each metric's standard deviation, median,
+//! quartiles and a bootstrap confidence interval for the mean, plus a
+//! [`AggregateStats::score_distribution`] histogram. Resampling is seeded
+//! through [`BootstrapConfig`], so the same run always yields the same
+//! interval.
+//!
+//! ## Comparing Runs
+//!
+//! [`compare_runs`] pairs the cases of two runs (by `case_id` or index),
+//! reports per-metric deltas and flags significant changes with a sign test.
+//! [`ComparisonReport::to_markdown`] renders the diff for a PR description.
+//!
+//! ## Parallel Batches
+//!
+//! [`Evaluator::evaluate_batch_parallel`] spreads test cases over a thread
+//! pool, keeps input order, and returns a `Result` per case so one failure
+//! does not abort the batch.
+//!
+//! ## Weights and Thresholds
+//!
+//! [`Evaluator::add_metric_weighted`] makes a metric count more toward
+//! `overall_score`, and [`Evaluator::set_threshold`] marks a report as failed
+//! (`passed == false`) when a metric drops below a floor, which is handy for
+//! gating CI.
+//!
+//! ## Latency and Cost
+//!
+//! The [`Evaluator`] records each metric's `duration_ms`. LLM and embedder
+//! implementations report calls and tokens through [`UsageRecorder`] (the
+//! OpenAI clients do this already); the counts land in each metric result's
+//! `usage` map. [`AggregateStats::metric_costs`] sums both per metric across
+//! a run.
+//!
+//! ## Async Evaluation
+//!
+//! With the `async` feature, [`AsyncEvaluator`] runs metrics concurrently and
+//! [`AsyncContextRelevance`] judges contexts in parallel. Existing sync
+//! implementations plug in via [`BlockingLLM`], [`BlockingEmbedder`] and
+//! [`BlockingMetric`].
+//!
+//! ## OpenAI-Compatible Judges
+//!
+//! With the `openai` feature, [`OpenAiLLM`] and [`OpenAiEmbedder`] talk to any
+//! OpenAI-compatible endpoint (OpenAI, Azure, OpenRouter, Ollama's `/v1`).
+//! Share a [`TokenUsage`] counter with [`Evaluator::track_usage`] to see what
+//! an evaluation run cost.
+//!
+//! ## Local Embeddings
+//!
+//! With the `embeddings` feature, vecstore's `LocalEmbedder` implements
+//! [`Embedder`], so [`AnswerCorrectness`] can score answers with
+//! all-MiniLM-L6-v2 run in-process instead of an API.
+//!
 //! ## Architecture
 //!
 //! ```text
@@ -80,10 +222,64 @@
 pub mod metrics;
 pub mod types;
 pub mod evaluator;
+pub mod retrieval;
+pub mod faithfulness;
+pub mod context;
+pub mod citation;
+pub mod answer_relevance;
+pub mod lexical;
+pub mod pairwise;
+pub mod usage;
+pub mod dataset;
+pub mod export;
+pub mod compare;
+pub mod stats;
+pub mod robustness;
+pub mod prompt;
+#[cfg(feature = "async")]
+pub mod async_eval;
+#[cfg(feature = "openai")]
+pub mod openai;
+#[cfg(feature = "vecstore")]
+pub mod rag;
 
-pub use types::{EvaluationInput, EvaluationReport, Metric};
-pub use evaluator::Evaluator;
+pub use types::{EvaluationInput, EvaluationReport, Metric, ThresholdViolation};
+pub use evaluator::{AggregateStats, Evaluator, MetricCost};
 pub use metrics::{ContextRelevance, AnswerFaithfulness, AnswerCorrectness};
+pub use retrieval::{RetrievalMetrics, RetrievalScore};
+pub use answer_relevance::AnswerRelevance;
+pub use lexical::{LexicalCorrectness, LexicalScore};
+pub use pairwise::{
+    PairwiseCase, PairwiseChoice, PairwiseComparator, PairwiseJudgment, PairwiseReport,
+    PairwiseVerdict,
+};
+pub use context::{ContextPrecision, ContextRecall};
+pub use citation::{CitationMapping, SentenceAttribution};
+pub use faithfulness::{ClaimJudgment, ClaimVerdict, FaithfulnessDetailed};
+pub use usage::{TokenUsage, UsageRecorder, UsageSnapshot};
+pub use dataset::EvaluationDataset;
+pub use export::{read_reports_json, ReportExport};
+pub use prompt::PromptTemplate;
+pub use robustness::{
+    AnswerGenerator, CaseRobustness, DistractorPosition, PerturbedRun, RobustnessCase,
+    RobustnessHarness, RobustnessReport,
+};
+pub use stats::{BootstrapConfig, HistogramBucket, ScoreSummary};
+pub use compare::{compare_runs, ComparisonReport, MetricComparison, Verdict};
 
 // Re-export for convenience
-pub use metrics::{LLM, Embedder};
+pub use metrics::{
+    parse_json_response, Embedder, GenerationParams, StructuredOutputError, LLM,
+};
+
+#[cfg(feature = "async")]
+pub use async_eval::{
+    AsyncAnswerCorrectness, AsyncAnswerFaithfulness, AsyncContextRelevance, AsyncEmbedder,
+    AsyncEvaluator, AsyncLLM, AsyncMetric, BlockingEmbedder, BlockingLLM, BlockingMetric,
+};
+
+#[cfg(feature = "openai")]
+pub use openai::{OpenAiEmbedder, OpenAiLLM};
+
+#[cfg(feature = "vecstore")]
+pub use rag::{RagConfig, RagHarness, RagRun};
@@ -6,9 +6,44 @@
 //! 2. **Answer Faithfulness**: Is the answer grounded in the context?
 //! 3. **Answer Correctness**: How close is the answer to ground truth?
 
+use crate::prompt::PromptTemplate;
 use crate::types::{EvaluationInput, Metric, MetricResult};
 use anyhow::{anyhow, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Default [`ContextRelevance`] prompt (`{query}`, `{context}`)
+pub const DEFAULT_RELEVANCE_PROMPT: &str = "Query: {query}\n\nContext: {context}\n\n\
+Is this context relevant for answering the query? \
+Answer only 'Yes' or 'No'.";
+
+/// Placeholders available to relevance prompts
+pub(crate) const RELEVANCE_PLACEHOLDERS: &[&str] = &["query", "context"];
+
+/// Default [`AnswerFaithfulness`] prompt (`{query}`, `{context}`, `{answer}`)
+pub const DEFAULT_FAITHFULNESS_PROMPT: &str = "Context:\n{context}\n\nAnswer:\n{answer}\n\n\
+Is the answer fully supported by the context? \
+Rate the faithfulness from 0.0 (completely unfaithful/hallucinated) \
+to 1.0 (fully faithful/grounded). \
+Respond with only a number between 0.0 and 1.0.";
+
+/// Placeholders available to faithfulness prompts
+pub(crate) const FAITHFULNESS_PLACEHOLDERS: &[&str] = &["query", "context", "answer"];
+
+/// JSON shape requested from relevance judges
+pub(crate) const RELEVANCE_SCHEMA: &str = r#"{"relevant": true | false}"#;
+
+/// JSON shape requested from faithfulness judges
+pub(crate) const FAITHFULNESS_SCHEMA: &str = r#"{"score": <number from 0.0 to 1.0>}"#;
+
+/// Appended to the faithfulness prompt when the judge's reply had no score
+pub(crate) const SCORE_RETRY_SUFFIX: &str =
+    "\n\nRespond with only a number between 0.0 and 1.0.";
+
+/// Default number of re-prompts when the judge's reply has no score
+pub(crate) const DEFAULT_SCORE_RETRIES: usize = 2;
 
 // ============================================================================
 // Trait Definitions
@@ -21,6 +56,171 @@ use std::collections::HashMap;
 pub trait LLM: Send + Sync {
     /// Generate text from a prompt
     fn generate(&self, prompt: &str) -> Result<String>;
+
+    /// Generate text with sampling controls
+    ///
+    /// The default ignores `params` and calls [`generate`](LLM::generate).
+    /// Override it to pass temperature, seed and token limits to the model;
+    /// judge metrics call this with temperature 0 unless configured otherwise.
+    fn generate_with_params(&self, prompt: &str, params: &GenerationParams) -> Result<String> {
+        let _ = params;
+        self.generate(prompt)
+    }
+
+    /// Generate a JSON value described by `schema_hint`
+    ///
+    /// Calls [`generate_structured_with_params`](LLM::generate_structured_with_params)
+    /// with default params.
+    fn generate_structured(&self, prompt: &str, schema_hint: &str) -> Result<serde_json::Value> {
+        self.generate_structured_with_params(prompt, schema_hint, &GenerationParams::default())
+    }
+
+    /// Generate a JSON value described by `schema_hint`, with sampling controls
+    ///
+    /// The default appends "Respond with JSON matching ..." to the prompt and
+    /// parses the reply with [`parse_json_response`]. Override it to use a
+    /// provider's native JSON mode or function calling. A reply that is not
+    /// JSON fails with a [`StructuredOutputError`] carrying the raw text, so
+    /// callers can fall back to parsing it as plain text.
+    fn generate_structured_with_params(
+        &self,
+        prompt: &str,
+        schema_hint: &str,
+        params: &GenerationParams,
+    ) -> Result<serde_json::Value> {
+        let response = self.generate_with_params(&structured_prompt(prompt, schema_hint), params)?;
+        Ok(parse_json_response(&response)?)
+    }
+}
+
+/// Sampling controls passed to [`LLM::generate_with_params`]
+///
+/// `None` leaves a setting at the provider's default. Judge metrics use
+/// [`GenerationParams::deterministic`] unless given other params, and record
+/// the params they used under `generation_params` in their result details.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerationParams {
+    /// Sampling temperature (0.0 for greedy decoding)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Sampling seed, for providers that support reproducible sampling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+
+    /// Maximum number of tokens to generate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+}
+
+impl GenerationParams {
+    /// Temperature 0, the default for judge metrics
+    pub fn deterministic() -> Self {
+        Self {
+            temperature: Some(0.0),
+            ..Default::default()
+        }
+    }
+
+    /// Set the sampling temperature
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the sampling seed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Set the maximum number of generated tokens
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Record these params under `generation_params` in a result's details
+    pub(crate) fn record(&self, mut result: MetricResult) -> MetricResult {
+        result
+            .details
+            .insert("generation_params".to_string(), serde_json::json!(self));
+        result
+    }
+}
+
+/// A judge reply that should have been JSON but was not
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("LLM response is not valid JSON ({reason}): {response:?}")]
+pub struct StructuredOutputError {
+    /// The raw reply
+    pub response: String,
+
+    /// Why it could not be parsed
+    pub reason: String,
+}
+
+/// Append the JSON instruction used by [`LLM::generate_structured`]
+pub(crate) fn structured_prompt(prompt: &str, schema_hint: &str) -> String {
+    format!(
+        "{}\n\nRespond with JSON matching this schema and nothing else: {}",
+        prompt, schema_hint
+    )
+}
+
+/// Parse a JSON reply, tolerating markdown fences and surrounding prose
+///
+/// Tries the reply as-is (minus any ```` ```json ```` fence), then the span
+/// from the first `{` or `[` to the last `}` or `]`.
+pub fn parse_json_response(response: &str) -> std::result::Result<serde_json::Value, StructuredOutputError> {
+    let trimmed = response.trim();
+    let unfenced = match trimmed.strip_prefix("```") {
+        Some(rest) => {
+            // Drop the info string ("json") on the opening fence line
+            let body = rest.split_once('\n').map_or("", |(_, body)| body);
+            body.trim_end().trim_end_matches("```").trim()
+        }
+        None => trimmed,
+    };
+
+    let err = match serde_json::from_str(unfenced) {
+        Ok(value) => return Ok(value),
+        Err(err) => err,
+    };
+
+    let start = unfenced.find(['{', '[']);
+    let end = unfenced.rfind(['}', ']']);
+    if let (Some(start), Some(end)) = (start, end) {
+        if start < end {
+            if let Ok(value) = serde_json::from_str(&unfenced[start..=end]) {
+                return Ok(value);
+            }
+        }
+    }
+
+    Err(StructuredOutputError {
+        response: response.to_string(),
+        reason: err.to_string(),
+    })
+}
+
+/// Get a judge's JSON reply, or its raw text if it was not JSON
+///
+/// Errors other than a [`StructuredOutputError`] (e.g. transport failures)
+/// are propagated.
+pub(crate) fn structured_or_text(
+    llm: &dyn LLM,
+    prompt: &str,
+    schema_hint: &str,
+    params: &GenerationParams,
+) -> Result<std::result::Result<serde_json::Value, String>> {
+    match llm.generate_structured_with_params(prompt, schema_hint, params) {
+        Ok(value) => Ok(Ok(value)),
+        Err(err) => match err.downcast::<StructuredOutputError>() {
+            Ok(invalid) => Ok(Err(invalid.response)),
+            Err(err) => Err(err),
+        },
+    }
 }
 
 /// Trait for embedding models
@@ -31,6 +231,15 @@ pub trait Embedder: Send + Sync {
     fn embed(&self, text: &str) -> Result<Vec<f32>>;
 }
 
+/// all-MiniLM-L6-v2 run locally, e.g.
+/// `AnswerCorrectness::new(Box::new(LocalEmbedder::minilm()?))`
+#[cfg(feature = "embeddings")]
+impl Embedder for vecstore::embeddings::LocalEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        vecstore::embeddings::LocalEmbedder::embed(self, text)
+    }
+}
+
 // ============================================================================
 // Context Relevance Metric (LLM-as-Judge)
 // ============================================================================
@@ -60,6 +269,7 @@ pub trait Embedder: Send + Sync {
 ///     ],
 ///     answer: None,
 ///     ground_truth: None,
+///     ..Default::default()
 /// };
 ///
 /// let result = metric.evaluate(&input)?;
@@ -68,76 +278,131 @@ pub trait Embedder: Send + Sync {
 /// ```
 pub struct ContextRelevance {
     llm: Box<dyn LLM>,
+    prompt: PromptTemplate,
+    structured: bool,
+    params: GenerationParams,
 }
 
 impl ContextRelevance {
     /// Create a new context relevance metric
     pub fn new(llm: Box<dyn LLM>) -> Self {
-        Self { llm }
+        Self {
+            llm,
+            prompt: PromptTemplate::new(DEFAULT_RELEVANCE_PROMPT),
+            structured: true,
+            params: GenerationParams::deterministic(),
+        }
+    }
+
+    /// Replace the judge prompt (`{query}`, `{context}`)
+    ///
+    /// Fails if the template uses any other placeholder.
+    pub fn with_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("context_relevance", RELEVANCE_PLACEHOLDERS)?;
+        self.prompt = template;
+        Ok(self)
+    }
+
+    /// Ask for a JSON verdict via [`LLM::generate_structured`] (default: true)
+    ///
+    /// Replies that are not JSON are still read as Yes/No text. Disable to
+    /// send the prompt exactly as rendered.
+    pub fn with_structured_output(mut self, enabled: bool) -> Self {
+        self.structured = enabled;
+        self
+    }
+
+    /// Override the sampling params (default: temperature 0)
+    pub fn with_generation_params(mut self, params: GenerationParams) -> Self {
+        self.params = params;
+        self
     }
 
     /// Judge whether a single context is relevant
     fn is_relevant(&self, query: &str, context: &str) -> Result<bool> {
-        let prompt = format!(
-            "Query: {}\n\nContext: {}\n\n\
-             Is this context relevant for answering the query? \
-             Answer only 'Yes' or 'No'.",
-            query, context
-        );
+        let prompt = self.prompt.render(&[("query", query), ("context", context)]);
+        if !self.structured {
+            return Ok(parse_relevance(
+                &self.llm.generate_with_params(&prompt, &self.params)?,
+            ));
+        }
 
-        let response = self.llm.generate(&prompt)?;
-        let normalized = response.trim().to_lowercase();
+        Ok(
+            match structured_or_text(self.llm.as_ref(), &prompt, RELEVANCE_SCHEMA, &self.params)? {
+                Ok(value) => parse_relevance_json(&value),
+                Err(text) => parse_relevance(&text),
+            },
+        )
+    }
+}
 
-        Ok(normalized.contains("yes"))
+/// Interpret a Yes/No relevance verdict
+pub(crate) fn parse_relevance(response: &str) -> bool {
+    response.trim().to_lowercase().contains("yes")
+}
+
+/// Interpret a `{"relevant": ...}` verdict, accepting Yes/No strings too
+fn parse_relevance_json(value: &serde_json::Value) -> bool {
+    match value.get("relevant").unwrap_or(value) {
+        serde_json::Value::Bool(relevant) => *relevant,
+        serde_json::Value::String(text) => parse_relevance(text),
+        other => parse_relevance(&other.to_string()),
     }
 }
 
-impl Metric for ContextRelevance {
-    fn name(&self) -> &str {
-        "context_relevance"
+/// Build the context relevance result from per-context verdicts
+pub(crate) fn relevance_result(metric_name: &str, judgments: &[bool]) -> MetricResult {
+    if judgments.is_empty() {
+        return MetricResult {
+            metric_name: metric_name.to_string(),
+            score: 0.0,
+            details: HashMap::new(),
+            ..Default::default()
+        };
     }
 
-    fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
-        if input.contexts.is_empty() {
-            return Ok(MetricResult {
-                metric_name: self.name().to_string(),
-                score: 0.0,
-                details: HashMap::new(),
-            });
-        }
+    let relevant_count = judgments.iter().filter(|&&relevant| relevant).count();
+    let context_relevance: Vec<(usize, bool)> = judgments.iter().copied().enumerate().collect();
+    let score = relevant_count as f32 / judgments.len() as f32;
 
-        let mut relevant_count = 0;
-        let mut context_relevance = Vec::new();
+    let mut details = HashMap::new();
+    details.insert(
+        "relevant_count".to_string(),
+        serde_json::json!(relevant_count),
+    );
+    details.insert(
+        "total_contexts".to_string(),
+        serde_json::json!(judgments.len()),
+    );
+    details.insert(
+        "context_relevance".to_string(),
+        serde_json::json!(context_relevance),
+    );
 
-        for (i, context) in input.contexts.iter().enumerate() {
-            let is_relevant = self.is_relevant(&input.query, context)?;
-            if is_relevant {
-                relevant_count += 1;
-            }
-            context_relevance.push((i, is_relevant));
-        }
+    MetricResult {
+        metric_name: metric_name.to_string(),
+        score,
+        details,
+        ..Default::default()
+    }
+}
 
-        let score = relevant_count as f32 / input.contexts.len() as f32;
+impl Metric for ContextRelevance {
+    fn name(&self) -> &str {
+        "context_relevance"
+    }
 
-        let mut details = HashMap::new();
-        details.insert(
-            "relevant_count".to_string(),
-            serde_json::json!(relevant_count),
-        );
-        details.insert(
-            "total_contexts".to_string(),
-            serde_json::json!(input.contexts.len()),
-        );
-        details.insert(
-            "context_relevance".to_string(),
-            serde_json::json!(context_relevance),
-        );
+    fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        let judgments = input
+            .contexts
+            .iter()
+            .map(|context| self.is_relevant(&input.query, context))
+            .collect::<Result<Vec<_>>>()?;
 
-        Ok(MetricResult {
-            metric_name: self.name().to_string(),
-            score,
-            details,
-        })
+        Ok(self
+            .params
+            .record(relevance_result(self.name(), &judgments)))
     }
 }
 
@@ -167,6 +432,7 @@ impl Metric for ContextRelevance {
 ///     contexts: vec!["Rust is a systems programming language.".to_string()],
 ///     answer: Some("Rust is a systems language.".to_string()),
 ///     ground_truth: None,
+///     ..Default::default()
 /// };
 ///
 /// let result = metric.evaluate(&input)?;
@@ -175,12 +441,57 @@ impl Metric for ContextRelevance {
 /// ```
 pub struct AnswerFaithfulness {
     llm: Box<dyn LLM>,
+    prompt: PromptTemplate,
+    max_retries: usize,
+    structured: bool,
+    params: GenerationParams,
 }
 
 impl AnswerFaithfulness {
     /// Create a new answer faithfulness metric
     pub fn new(llm: Box<dyn LLM>) -> Self {
-        Self { llm }
+        Self {
+            llm,
+            prompt: PromptTemplate::new(DEFAULT_FAITHFULNESS_PROMPT),
+            max_retries: DEFAULT_SCORE_RETRIES,
+            structured: true,
+            params: GenerationParams::deterministic(),
+        }
+    }
+
+    /// Ask for a JSON score via [`LLM::generate_structured`] (default: true)
+    ///
+    /// Only the first attempt is structured; a reply that is not JSON is
+    /// parsed as text, and retries use the plain-text prompt. The result's
+    /// `structured_output` detail records whether the score came from JSON.
+    pub fn with_structured_output(mut self, enabled: bool) -> Self {
+        self.structured = enabled;
+        self
+    }
+
+    /// Override the sampling params (default: temperature 0)
+    pub fn with_generation_params(mut self, params: GenerationParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Set how many times to re-prompt when the reply has no score (default: 2)
+    ///
+    /// Each retry appends "Respond with only a number" to the prompt. When
+    /// every attempt fails, `evaluate` returns an error rather than 0.0.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Replace the judge prompt (`{query}`, `{context}`, `{answer}`)
+    ///
+    /// Fails if the template uses any other placeholder.
+    pub fn with_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("answer_faithfulness", FAITHFULNESS_PLACEHOLDERS)?;
+        self.prompt = template;
+        Ok(self)
     }
 }
 
@@ -196,46 +507,137 @@ impl Metric for AnswerFaithfulness {
             .ok_or_else(|| anyhow!("Answer required for faithfulness metric"))?;
 
         if input.contexts.is_empty() {
-            return Ok(MetricResult {
+            return Ok(self.params.record(MetricResult {
                 metric_name: self.name().to_string(),
                 score: 0.0,
                 details: HashMap::new(),
-            });
+                ..Default::default()
+            }));
         }
 
-        let context = input.contexts.join("\n\n");
+        let prompt = self.prompt.render(&[
+            ("query", &input.query),
+            ("context", &input.contexts.join("\n\n")),
+            ("answer", answer),
+        ]);
 
-        let prompt = format!(
-            "Context:\n{}\n\nAnswer:\n{}\n\n\
-             Is the answer fully supported by the context? \
-             Rate the faithfulness from 0.0 (completely unfaithful/hallucinated) \
-             to 1.0 (fully faithful/grounded). \
-             Respond with only a number between 0.0 and 1.0.",
-            context, answer
-        );
+        let mut responses = Vec::new();
+        let mut structured = false;
+        for attempt in 0..=self.max_retries {
+            let (response, score) = if attempt == 0 && self.structured {
+                match structured_or_text(
+                    self.llm.as_ref(),
+                    &prompt,
+                    FAITHFULNESS_SCHEMA,
+                    &self.params,
+                )? {
+                    Ok(value) => {
+                        let score = parse_faithfulness_json(&value);
+                        structured = score.is_some();
+                        (value.to_string(), score)
+                    }
+                    Err(text) => {
+                        let score = parse_faithfulness_score(&text);
+                        (text, score)
+                    }
+                }
+            } else {
+                let response = if attempt == 0 {
+                    self.llm.generate_with_params(&prompt, &self.params)?
+                } else {
+                    self.llm.generate_with_params(
+                        &format!("{}{}", prompt, SCORE_RETRY_SUFFIX),
+                        &self.params,
+                    )?
+                };
+                let score = parse_faithfulness_score(&response);
+                (response, score)
+            };
+            responses.push(response);
+
+            if let Some(score) = score {
+                let mut result = faithfulness_result(self.name(), score, responses);
+                result
+                    .details
+                    .insert("structured_output".to_string(), serde_json::json!(structured));
+                return Ok(self.params.record(result));
+            }
+        }
 
-        let response = self.llm.generate(&prompt)?;
+        Err(unparseable_score_error(&responses))
+    }
+}
 
-        // Parse score from response
-        let score = response
-            .trim()
-            .split_whitespace()
-            .next()
-            .and_then(|s| s.parse::<f32>().ok())
-            .unwrap_or(0.0)
-            .clamp(0.0, 1.0);
+/// Read a `{"score": ...}` reply, clamped to 0.0-1.0
+fn parse_faithfulness_json(value: &serde_json::Value) -> Option<f32> {
+    match value.get("score").unwrap_or(value) {
+        serde_json::Value::Number(score) => Some((score.as_f64()? as f32).clamp(0.0, 1.0)),
+        serde_json::Value::String(text) => parse_faithfulness_score(text),
+        other => parse_faithfulness_score(&other.to_string()),
+    }
+}
 
-        let mut details = HashMap::new();
-        details.insert("llm_response".to_string(), serde_json::json!(response));
+/// Build the faithfulness result, recording every raw judge reply
+pub(crate) fn faithfulness_result(
+    metric_name: &str,
+    score: f32,
+    responses: Vec<String>,
+) -> MetricResult {
+    let mut details = HashMap::new();
+    details.insert(
+        "llm_response".to_string(),
+        serde_json::json!(responses.last()),
+    );
+    details.insert("retries".to_string(), serde_json::json!(responses.len() - 1));
+    details.insert("raw_responses".to_string(), serde_json::json!(responses));
 
-        Ok(MetricResult {
-            metric_name: self.name().to_string(),
-            score,
-            details,
-        })
+    MetricResult {
+        metric_name: metric_name.to_string(),
+        score,
+        details,
+        ..Default::default()
     }
 }
 
+/// Error for a judge that never produced a usable score
+pub(crate) fn unparseable_score_error(responses: &[String]) -> anyhow::Error {
+    anyhow!(
+        "Judge returned no parseable faithfulness score after {} attempt(s); last response: {:?}",
+        responses.len(),
+        responses.last().map(String::as_str).unwrap_or("")
+    )
+}
+
+/// Parse the judge's faithfulness rating, clamped to 0.0-1.0
+///
+/// Takes the first number anywhere in the reply. Fractions ("8/10",
+/// "0.8 out of 1") are divided out and percentages ("80%") scaled.
+/// Returns `None` when the reply contains no number.
+pub(crate) fn parse_faithfulness_score(response: &str) -> Option<f32> {
+    static SCORE: OnceLock<Regex> = OnceLock::new();
+    let re = SCORE.get_or_init(|| {
+        Regex::new(r"(?i)(\d+(?:\.\d+)?|\.\d+)\s*(?:(?:/|out\s+of)\s*(\d+(?:\.\d+)?)|(%))?")
+            .unwrap()
+    });
+
+    let caps = re.captures(response)?;
+    let value: f32 = caps[1].parse().ok()?;
+
+    let score = if let Some(denominator) = caps.get(2) {
+        let denominator: f32 = denominator.as_str().parse().ok()?;
+        if denominator == 0.0 {
+            return None;
+        }
+        value / denominator
+    } else if caps.get(3).is_some() {
+        value / 100.0
+    } else {
+        value
+    };
+
+    Some(score.clamp(0.0, 1.0))
+}
+
 // ============================================================================
 // Answer Correctness Metric (Embedding Similarity)
 // ============================================================================
@@ -262,6 +664,7 @@ impl Metric for AnswerFaithfulness {
 ///     contexts: vec![],
 ///     answer: Some("Rust is a systems programming language.".to_string()),
 ///     ground_truth: Some("Rust is a memory-safe systems language.".to_string()),
+///     ..Default::default()
 /// };
 ///
 /// let result = metric.evaluate(&input)?;
@@ -279,7 +682,7 @@ impl AnswerCorrectness {
     }
 
     /// Calculate cosine similarity between two vectors
-    fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
         if a.len() != b.len() {
             return 0.0;
         }
@@ -316,28 +719,46 @@ impl Metric for AnswerCorrectness {
         let answer_embedding = self.embedder.embed(answer)?;
         let truth_embedding = self.embedder.embed(ground_truth)?;
 
-        // Calculate cosine similarity
-        let similarity = Self::cosine_similarity(&answer_embedding, &truth_embedding);
+        Ok(correctness_result(
+            self.name(),
+            answer,
+            ground_truth,
+            &answer_embedding,
+            &truth_embedding,
+        ))
+    }
+}
 
-        // Normalize to 0-1 range (cosine similarity is -1 to 1)
-        let score = ((similarity + 1.0) / 2.0).clamp(0.0, 1.0);
+/// Build the answer correctness result from the two embeddings
+pub(crate) fn correctness_result(
+    metric_name: &str,
+    answer: &str,
+    ground_truth: &str,
+    answer_embedding: &[f32],
+    truth_embedding: &[f32],
+) -> MetricResult {
+    // Calculate cosine similarity
+    let similarity = AnswerCorrectness::cosine_similarity(answer_embedding, truth_embedding);
 
-        let mut details = HashMap::new();
-        details.insert("cosine_similarity".to_string(), serde_json::json!(similarity));
-        details.insert(
-            "answer_length".to_string(),
-            serde_json::json!(answer.len()),
-        );
-        details.insert(
-            "ground_truth_length".to_string(),
-            serde_json::json!(ground_truth.len()),
-        );
+    // Normalize to 0-1 range (cosine similarity is -1 to 1)
+    let score = ((similarity + 1.0) / 2.0).clamp(0.0, 1.0);
 
-        Ok(MetricResult {
-            metric_name: self.name().to_string(),
-            score,
-            details,
-        })
+    let mut details = HashMap::new();
+    details.insert("cosine_similarity".to_string(), serde_json::json!(similarity));
+    details.insert(
+        "answer_length".to_string(),
+        serde_json::json!(answer.len()),
+    );
+    details.insert(
+        "ground_truth_length".to_string(),
+        serde_json::json!(ground_truth.len()),
+    );
+
+    MetricResult {
+        metric_name: metric_name.to_string(),
+        score,
+        details,
+        ..Default::default()
     }
 }
 
@@ -382,6 +803,7 @@ mod tests {
             ],
             answer: None,
             ground_truth: None,
+            ..Default::default()
         };
 
         let result = metric.evaluate(&input).unwrap();
@@ -397,6 +819,7 @@ mod tests {
             contexts: vec![],
             answer: None,
             ground_truth: None,
+            ..Default::default()
         };
 
         let result = metric.evaluate(&input).unwrap();
@@ -411,6 +834,7 @@ mod tests {
             contexts: vec!["Rust is a systems programming language.".to_string()],
             answer: Some("Rust is a systems language.".to_string()),
             ground_truth: None,
+            ..Default::default()
         };
 
         let result = metric.evaluate(&input).unwrap();
@@ -418,6 +842,66 @@ mod tests {
         assert_eq!(result.metric_name, "answer_faithfulness");
     }
 
+    #[test]
+    fn test_parse_faithfulness_score() {
+        assert_eq!(parse_faithfulness_score("0.8"), Some(0.8));
+        assert_eq!(parse_faithfulness_score("I would rate this 0.8 out of 1"), Some(0.8));
+        assert_eq!(parse_faithfulness_score("Score: 8/10."), Some(0.8));
+        assert_eq!(parse_faithfulness_score("About 75% faithful"), Some(0.75));
+        assert_eq!(parse_faithfulness_score("Faithfulness: .5"), Some(0.5));
+        assert_eq!(parse_faithfulness_score("1.7"), Some(1.0));
+        assert_eq!(parse_faithfulness_score("Fully supported."), None);
+        assert_eq!(parse_faithfulness_score("3/0"), None);
+    }
+
+    // Mock LLM that replays a script of responses
+    struct ScriptedLLM(std::sync::Mutex<Vec<&'static str>>);
+    impl LLM for ScriptedLLM {
+        fn generate(&self, prompt: &str) -> Result<String> {
+            let mut script = self.0.lock().unwrap();
+            let next = script.remove(0);
+            // Retries must carry the stricter instruction
+            if script.len() < 2 && !next.starts_with("first") {
+                assert!(prompt.ends_with("Respond with only a number between 0.0 and 1.0."));
+            }
+            Ok(next.to_string())
+        }
+    }
+
+    fn faithfulness_input() -> EvaluationInput {
+        EvaluationInput {
+            query: "What is Rust?".to_string(),
+            contexts: vec!["Rust is a systems programming language.".to_string()],
+            answer: Some("Rust is a systems language.".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_answer_faithfulness_retries() {
+        let llm = ScriptedLLM(std::sync::Mutex::new(vec![
+            "first: it looks well supported",
+            "0.9",
+        ]));
+        let metric = AnswerFaithfulness::new(Box::new(llm));
+
+        let result = metric.evaluate(&faithfulness_input()).unwrap();
+        assert_eq!(result.score, 0.9);
+        assert_eq!(result.details["retries"], 1);
+        assert_eq!(result.details["raw_responses"].as_array().unwrap().len(), 2);
+        assert_eq!(result.details["llm_response"], "0.9");
+    }
+
+    #[test]
+    fn test_answer_faithfulness_errors_instead_of_zero() {
+        let llm = ScriptedLLM(std::sync::Mutex::new(vec!["first: unsure", "no idea", "still no"]));
+        let metric = AnswerFaithfulness::new(Box::new(llm));
+
+        let err = metric.evaluate(&faithfulness_input()).unwrap_err().to_string();
+        assert!(err.contains("after 3 attempt(s)"), "{}", err);
+        assert!(err.contains("still no"), "{}", err);
+    }
+
     #[test]
     fn test_answer_correctness() {
         let metric = AnswerCorrectness::new(Box::new(MockEmbedder));
@@ -426,6 +910,7 @@ mod tests {
             contexts: vec![],
             answer: Some("Rust is a systems programming language.".to_string()),
             ground_truth: Some("Rust is a memory-safe systems language.".to_string()),
+            ..Default::default()
         };
 
         let result = metric.evaluate(&input).unwrap();
@@ -433,6 +918,175 @@ mod tests {
         assert_eq!(result.metric_name, "answer_correctness");
     }
 
+    // Mock LLM that records the last prompt
+    struct RecordingLLM(std::sync::Arc<std::sync::Mutex<String>>);
+    impl LLM for RecordingLLM {
+        fn generate(&self, prompt: &str) -> Result<String> {
+            *self.0.lock().unwrap() = prompt.to_string();
+            Ok("Yes".to_string())
+        }
+    }
+
+    #[test]
+    fn test_default_prompts_unchanged() {
+        let relevance = PromptTemplate::new(DEFAULT_RELEVANCE_PROMPT)
+            .render(&[("query", "Q"), ("context", "C")]);
+        assert_eq!(
+            relevance,
+            "Query: Q\n\nContext: C\n\nIs this context relevant for answering the query? \
+             Answer only 'Yes' or 'No'."
+        );
+    }
+
+    #[test]
+    fn test_custom_prompt() {
+        let last_prompt = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+        let metric = ContextRelevance::new(Box::new(RecordingLLM(last_prompt.clone())))
+            .with_prompt("{context} -> {query}? Answer strictly Yes or No")
+            .unwrap()
+            .with_structured_output(false);
+        let input = EvaluationInput {
+            query: "What is Rust?".to_string(),
+            contexts: vec!["Rust docs".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(metric.evaluate(&input).unwrap().score, 1.0);
+        assert_eq!(
+            *last_prompt.lock().unwrap(),
+            "Rust docs -> What is Rust?? Answer strictly Yes or No"
+        );
+    }
+
+    #[test]
+    fn test_unknown_placeholder_rejected() {
+        assert!(ContextRelevance::new(Box::new(MockLLMYes))
+            .with_prompt("{query} {answer}")
+            .is_err());
+        assert!(AnswerFaithfulness::new(Box::new(MockLLMYes))
+            .with_prompt("{query} {context} {answer}")
+            .is_ok());
+        assert!(AnswerFaithfulness::new(Box::new(MockLLMYes))
+            .with_prompt("{ground_truth}")
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_json_response() {
+        let expected = serde_json::json!({ "relevant": true });
+        assert_eq!(parse_json_response(r#"{"relevant": true}"#).unwrap(), expected);
+        assert_eq!(
+            parse_json_response("```json\n{\"relevant\": true}\n```").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_json_response("Sure! Here it is: {\"relevant\": true} Hope that helps.").unwrap(),
+            expected
+        );
+
+        let err = parse_json_response("Yes, it is relevant.").unwrap_err();
+        assert_eq!(err.response, "Yes, it is relevant.");
+    }
+
+    // Mock LLM that answers structured prompts with fenced JSON
+    struct JsonLLM(&'static str);
+    impl LLM for JsonLLM {
+        fn generate(&self, prompt: &str) -> Result<String> {
+            assert!(prompt.contains("Respond with JSON"));
+            Ok(format!("```json\n{}\n```", self.0))
+        }
+    }
+
+    #[test]
+    fn test_structured_relevance() {
+        let input = EvaluationInput {
+            query: "What is Rust?".to_string(),
+            contexts: vec!["Rust docs".to_string()],
+            ..Default::default()
+        };
+
+        let relevant = ContextRelevance::new(Box::new(JsonLLM(r#"{"relevant": true}"#)));
+        assert_eq!(relevant.evaluate(&input).unwrap().score, 1.0);
+
+        let irrelevant = ContextRelevance::new(Box::new(JsonLLM(r#"{"relevant": false}"#)));
+        assert_eq!(irrelevant.evaluate(&input).unwrap().score, 0.0);
+    }
+
+    #[test]
+    fn test_structured_faithfulness_and_text_fallback() {
+        let metric = AnswerFaithfulness::new(Box::new(JsonLLM(r#"{"score": 0.7}"#)));
+        let result = metric.evaluate(&faithfulness_input()).unwrap();
+        assert_eq!(result.score, 0.7);
+        assert_eq!(result.details["structured_output"], true);
+
+        // Plain-text replies are still scored without another call
+        let llm = ScriptedLLM(std::sync::Mutex::new(vec!["first: I'd say 0.6"]));
+        let result = AnswerFaithfulness::new(Box::new(llm))
+            .evaluate(&faithfulness_input())
+            .unwrap();
+        assert_eq!(result.score, 0.6);
+        assert_eq!(result.details["retries"], 0);
+        assert_eq!(result.details["structured_output"], false);
+    }
+
+    // Mock LLM whose transport always fails
+    struct FailingLLM;
+    impl LLM for FailingLLM {
+        fn generate(&self, _prompt: &str) -> Result<String> {
+            Err(anyhow!("connection refused"))
+        }
+    }
+
+    #[test]
+    fn test_structured_transport_errors_propagate() {
+        let err = AnswerFaithfulness::new(Box::new(FailingLLM))
+            .evaluate(&faithfulness_input())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "connection refused");
+    }
+
+    // Mock LLM that records the params it was called with
+    struct ParamsLLM(std::sync::Arc<std::sync::Mutex<Vec<GenerationParams>>>);
+    impl LLM for ParamsLLM {
+        fn generate(&self, _prompt: &str) -> Result<String> {
+            unreachable!("judges must call generate_with_params")
+        }
+
+        fn generate_with_params(&self, _prompt: &str, params: &GenerationParams) -> Result<String> {
+            self.0.lock().unwrap().push(*params);
+            Ok(r#"{"relevant": true, "score": 1.0}"#.to_string())
+        }
+    }
+
+    #[test]
+    fn test_judges_default_to_temperature_zero() {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let metric = ContextRelevance::new(Box::new(ParamsLLM(seen.clone())));
+
+        let result = metric.evaluate(&faithfulness_input()).unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![GenerationParams::deterministic()]);
+        assert_eq!(
+            result.details["generation_params"],
+            serde_json::json!({ "temperature": 0.0 })
+        );
+    }
+
+    #[test]
+    fn test_generation_params_override() {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let params = GenerationParams::default()
+            .with_temperature(0.3)
+            .with_seed(42)
+            .with_max_tokens(16);
+        let metric = AnswerFaithfulness::new(Box::new(ParamsLLM(seen.clone())))
+            .with_generation_params(params);
+
+        let result = metric.evaluate(&faithfulness_input()).unwrap();
+        assert_eq!(*seen.lock().unwrap(), vec![params]);
+        assert_eq!(result.details["generation_params"]["seed"], 42);
+        assert_eq!(result.details["generation_params"]["max_tokens"], 16);
+    }
+
     #[test]
     fn test_cosine_similarity() {
         let a = vec![1.0, 0.0, 0.0];
@@ -0,0 +1,540 @@
+//! OpenAI-compatible LLM and embedding clients (feature `openai`)
+//!
+//! Works with any endpoint that speaks the OpenAI `/chat/completions` and
+//! `/embeddings` protocol: OpenAI itself, Azure OpenAI, OpenRouter, or
+//! Ollama's `/v1` compatibility layer. Token usage from every response is
+//! added to a shared [`TokenUsage`] counter and reported to the active
+//! [`UsageRecorder`] scope.
+
+use crate::metrics::{parse_json_response, structured_prompt, Embedder, GenerationParams, LLM};
+use crate::usage::{TokenUsage, UsageRecorder};
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default API base URL
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+// ============================================================================
+// Shared HTTP Client
+// ============================================================================
+
+/// Connection settings shared by [`OpenAiLLM`] and [`OpenAiEmbedder`]
+struct OpenAiClient {
+    client: reqwest::blocking::Client,
+    api_key: String,
+    base_url: String,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+    max_retries: usize,
+    retry_backoff: Duration,
+    usage: Arc<TokenUsage>,
+}
+
+#[derive(Deserialize, Default)]
+struct ApiUsage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
+}
+
+impl OpenAiClient {
+    fn new(api_key: String) -> Result<Self> {
+        let timeout = Duration::from_secs(60);
+        Ok(Self {
+            client: build_client(timeout)?,
+            api_key,
+            base_url: OPENAI_BASE_URL.to_string(),
+            headers: Vec::new(),
+            timeout,
+            max_retries: 3,
+            retry_backoff: Duration::from_secs(1),
+            usage: Arc::new(TokenUsage::new()),
+        })
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> Result<()> {
+        self.client = build_client(timeout)?;
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    /// POST a JSON body, retrying on 429/5xx and transport errors
+    ///
+    /// Returns the response and the token usage it reported.
+    fn post(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<(serde_json::Value, ApiUsage)> {
+        let url = format!("{}/{}", self.base_url.trim_end_matches('/'), path);
+        let mut retries = 0;
+
+        loop {
+            let mut request = self.client.post(&url).json(body);
+            // Local servers (e.g. Ollama) need no key
+            if !self.api_key.is_empty() {
+                request = request.bearer_auth(&self.api_key);
+            }
+            for (name, value) in &self.headers {
+                request = request.header(name, value);
+            }
+
+            match request.send() {
+                Ok(resp) if resp.status().is_success() => {
+                    let value: serde_json::Value =
+                        resp.json().context("Failed to parse OpenAI response")?;
+
+                    let usage: ApiUsage = value
+                        .get("usage")
+                        .cloned()
+                        .and_then(|u| serde_json::from_value(u).ok())
+                        .unwrap_or_default();
+                    self.usage.record(usage.prompt_tokens, usage.completion_tokens);
+
+                    return Ok((value, usage));
+                }
+                Ok(resp)
+                    if (resp.status().as_u16() == 429 || resp.status().is_server_error())
+                        && retries < self.max_retries =>
+                {
+                    retries += 1;
+                    let wait_time = retry_after(&resp)
+                        .unwrap_or_else(|| self.retry_backoff * 2_u32.pow(retries as u32 - 1));
+                    std::thread::sleep(wait_time);
+                    continue;
+                }
+                Ok(resp) => {
+                    let status = resp.status();
+                    let body = resp
+                        .text()
+                        .unwrap_or_else(|_| String::from("(no body)"));
+                    return Err(anyhow!("OpenAI API error {}: {}", status, body));
+                }
+                Err(_e) if retries < self.max_retries => {
+                    retries += 1;
+                    std::thread::sleep(self.retry_backoff * 2_u32.pow(retries as u32 - 1));
+                    continue;
+                }
+                Err(e) => {
+                    return Err(anyhow!("Failed to call OpenAI API: {}", e));
+                }
+            }
+        }
+    }
+}
+
+fn build_client(timeout: Duration) -> Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+/// Honour a `Retry-After: <seconds>` header
+fn retry_after(resp: &reqwest::blocking::Response) -> Option<Duration> {
+    resp.headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .map(Duration::from_secs_f64)
+}
+
+/// Builder methods shared by both clients
+macro_rules! client_builders {
+    ($ty:ty) => {
+        impl $ty {
+            /// Use a different OpenAI-compatible endpoint (e.g. `http://localhost:11434/v1`)
+            pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+                self.client.base_url = base_url.into();
+                self
+            }
+
+            /// Send an extra header with every request (e.g. Azure's `api-key`)
+            pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+                self.client.headers.push((name.into(), value.into()));
+                self
+            }
+
+            /// Set the per-request timeout (default: 60s)
+            pub fn with_timeout(mut self, timeout: Duration) -> Result<Self> {
+                self.client.set_timeout(timeout)?;
+                Ok(self)
+            }
+
+            /// Set how many times 429/5xx responses are retried (default: 3)
+            pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+                self.client.max_retries = max_retries;
+                self
+            }
+
+            /// Set the initial backoff between retries, doubled each attempt (default: 1s)
+            pub fn with_retry_backoff(mut self, backoff: Duration) -> Self {
+                self.client.retry_backoff = backoff;
+                self
+            }
+
+            /// Record token usage into a shared counter
+            pub fn with_usage(mut self, usage: Arc<TokenUsage>) -> Self {
+                self.client.usage = usage;
+                self
+            }
+
+            /// Token usage counter for this client
+            pub fn usage(&self) -> Arc<TokenUsage> {
+                Arc::clone(&self.client.usage)
+            }
+
+            /// Configured request timeout
+            pub fn timeout(&self) -> Duration {
+                self.client.timeout
+            }
+        }
+    };
+}
+
+// ============================================================================
+// Chat Completions LLM
+// ============================================================================
+
+/// [`LLM`] backed by an OpenAI-compatible `/chat/completions` endpoint
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use vecstore_eval::{ContextRelevance, Evaluator, OpenAiLLM, TokenUsage};
+///
+/// let usage = Arc::new(TokenUsage::new());
+/// let llm = OpenAiLLM::new(std::env::var("OPENAI_API_KEY")?, "gpt-4o-mini")?
+///     .with_usage(Arc::clone(&usage));
+///
+/// let mut evaluator = Evaluator::new();
+/// evaluator.track_usage(usage);
+/// evaluator.add_metric(Box::new(ContextRelevance::new(Box::new(llm))));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct OpenAiLLM {
+    client: OpenAiClient,
+    model: String,
+    system_prompt: Option<String>,
+}
+
+impl OpenAiLLM {
+    /// Create a client for `model` (an empty key skips the Authorization header)
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            client: OpenAiClient::new(api_key.into())?,
+            model: model.into(),
+            system_prompt: None,
+        })
+    }
+
+    /// Prepend a system message to every request
+    pub fn with_system_prompt(mut self, system_prompt: impl Into<String>) -> Self {
+        self.system_prompt = Some(system_prompt.into());
+        self
+    }
+
+    /// Model name sent with each request
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Send one chat completion request, with an optional `response_format`
+    fn chat(
+        &self,
+        prompt: &str,
+        params: &GenerationParams,
+        response_format: Option<serde_json::Value>,
+    ) -> Result<String> {
+        let mut messages = Vec::new();
+        if let Some(ref system) = self.system_prompt {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.push(json!({ "role": "user", "content": prompt }));
+
+        let mut body = json!({
+            "model": self.model,
+            "messages": messages,
+        });
+        if let Some(temperature) = params.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if let Some(seed) = params.seed {
+            body["seed"] = json!(seed);
+        }
+        if let Some(max_tokens) = params.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+        if let Some(format) = response_format {
+            body["response_format"] = format;
+        }
+
+        let (response, usage) = self.client.post("chat/completions", &body)?;
+        UsageRecorder::record_llm_call(usage.prompt_tokens, usage.completion_tokens);
+
+        response
+            .pointer("/choices/0/message/content")
+            .and_then(|c| c.as_str())
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("No completion returned"))
+    }
+}
+
+client_builders!(OpenAiLLM);
+
+impl LLM for OpenAiLLM {
+    fn generate(&self, prompt: &str) -> Result<String> {
+        self.chat(prompt, &GenerationParams::default(), None)
+    }
+
+    /// Sends `temperature`, `seed` and `max_tokens` when set
+    fn generate_with_params(&self, prompt: &str, params: &GenerationParams) -> Result<String> {
+        self.chat(prompt, params, None)
+    }
+
+    /// Uses JSON mode (`response_format: json_object`)
+    fn generate_structured_with_params(
+        &self,
+        prompt: &str,
+        schema_hint: &str,
+        params: &GenerationParams,
+    ) -> Result<serde_json::Value> {
+        let response = self.chat(
+            &structured_prompt(prompt, schema_hint),
+            params,
+            Some(json!({ "type": "json_object" })),
+        )?;
+        Ok(parse_json_response(&response)?)
+    }
+}
+
+// ============================================================================
+// Embeddings
+// ============================================================================
+
+/// [`Embedder`] backed by an OpenAI-compatible `/embeddings` endpoint
+pub struct OpenAiEmbedder {
+    client: OpenAiClient,
+    model: String,
+}
+
+impl OpenAiEmbedder {
+    /// Create a client for `model` (an empty key skips the Authorization header)
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Result<Self> {
+        Ok(Self {
+            client: OpenAiClient::new(api_key.into())?,
+            model: model.into(),
+        })
+    }
+
+    /// Model name sent with each request
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+}
+
+client_builders!(OpenAiEmbedder);
+
+impl Embedder for OpenAiEmbedder {
+    fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let body = json!({
+            "model": self.model,
+            "input": text,
+        });
+
+        let (response, usage) = self.client.post("embeddings", &body)?;
+        UsageRecorder::record_embed_call(usage.prompt_tokens);
+
+        let embedding = response
+            .pointer("/data/0/embedding")
+            .and_then(|e| e.as_array())
+            .ok_or_else(|| anyhow!("No embedding returned"))?;
+
+        embedding
+            .iter()
+            .map(|v| {
+                v.as_f64()
+                    .map(|f| f as f32)
+                    .ok_or_else(|| anyhow!("Non-numeric embedding value"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn chat_response(content: &str) -> serde_json::Value {
+        json!({
+            "choices": [{ "message": { "role": "assistant", "content": content } }],
+            "usage": { "prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15 }
+        })
+    }
+
+    // The blocking client must not run on the async test runtime's threads
+    async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+        tokio::task::spawn_blocking(f).await.unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_chat_completion_and_usage() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(header("authorization", "Bearer sk-test"))
+            .and(body_partial_json(json!({ "model": "gpt-test" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chat_response("Yes")))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let base_url = format!("{}/v1", server.uri());
+        let ((answer, usage), recorded) = blocking(move || {
+            UsageRecorder::capture(|| {
+                let llm = OpenAiLLM::new("sk-test", "gpt-test").unwrap().with_base_url(base_url);
+                let answer = llm.generate("Is this relevant?").unwrap();
+                llm.generate("Again?").unwrap();
+                (answer, llm.usage().snapshot())
+            })
+        })
+        .await;
+
+        assert_eq!(answer, "Yes");
+        assert_eq!(recorded["llm_calls"], 2);
+        assert_eq!(recorded["prompt_tokens"], 24);
+        assert_eq!(usage.prompt_tokens, 24);
+        assert_eq!(usage.completion_tokens, 6);
+        assert_eq!(usage.requests, 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_structured_uses_json_mode() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(json!({
+                "response_format": { "type": "json_object" },
+                "temperature": 0.0,
+                "seed": 42
+            })))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(chat_response(r#"{"relevant": true}"#)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let base_url = server.uri();
+        let value = blocking(move || {
+            OpenAiLLM::new("", "local")
+                .unwrap()
+                .with_base_url(base_url)
+                .generate_structured_with_params(
+                    "Is this relevant?",
+                    r#"{"relevant": bool}"#,
+                    &GenerationParams::deterministic().with_seed(42),
+                )
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(value, json!({ "relevant": true }));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_retries_on_rate_limit() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(429))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(chat_response("0.9")))
+            .mount(&server)
+            .await;
+
+        let base_url = server.uri();
+        let answer = blocking(move || {
+            OpenAiLLM::new("", "local")
+                .unwrap()
+                .with_base_url(base_url)
+                .with_retry_backoff(Duration::from_millis(5))
+                .generate("Rate it")
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(answer, "0.9");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_gives_up_after_max_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).set_body_string("slow down"))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let base_url = server.uri();
+        let err = blocking(move || {
+            OpenAiLLM::new("k", "m")
+                .unwrap()
+                .with_base_url(base_url)
+                .with_max_retries(1)
+                .with_retry_backoff(Duration::from_millis(1))
+                .generate("x")
+        })
+        .await
+        .unwrap_err();
+
+        assert!(err.to_string().contains("429"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_embeddings_share_usage_counter() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/embeddings"))
+            .and(header("api-key", "azure-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": [{ "embedding": [0.5, -0.25, 1.0], "index": 0 }],
+                "usage": { "prompt_tokens": 4, "total_tokens": 4 }
+            })))
+            .mount(&server)
+            .await;
+
+        let base_url = server.uri();
+        let usage = Arc::new(TokenUsage::new());
+        let shared = Arc::clone(&usage);
+        let embedding = blocking(move || {
+            OpenAiEmbedder::new("", "text-embedding-3-small")
+                .unwrap()
+                .with_base_url(base_url)
+                .with_header("api-key", "azure-key")
+                .with_usage(shared)
+                .embed("hello")
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(embedding, vec![0.5, -0.25, 1.0]);
+        assert_eq!(usage.snapshot().prompt_tokens, 4);
+        assert_eq!(usage.snapshot().total_tokens(), 4);
+    }
+}
@@ -0,0 +1,413 @@
+//! Pairwise A/B Answer Comparison
+//!
+//! Absolute LLM scores drift between prompts and judges. When choosing
+//! between two candidate answers it is more reliable to ask the judge which
+//! one is better. Judges also tend to favour whichever answer is shown
+//! first, so [`PairwiseComparator`] asks twice with the answers swapped and
+//! only accepts a verdict both runs agree on.
+
+use crate::metrics::{GenerationParams, LLM};
+use crate::prompt::PromptTemplate;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+/// Default prompt for comparing two answers
+///
+/// Placeholders: `{query}`, `{context}`, `{answer_a}`, `{answer_b}`.
+pub const DEFAULT_PAIRWISE_PROMPT: &str = "Question: {query}\n\nContext:\n{context}\n\n\
+Answer A:\n{answer_a}\n\nAnswer B:\n{answer_b}\n\n\
+Which answer is more accurate, complete and faithful to the context? \
+Respond with only 'A', 'B' or 'tie'.";
+
+const PAIRWISE_PLACEHOLDERS: &[&str] = &["query", "context", "answer_a", "answer_b"];
+
+/// Two candidate answers for one query
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PairwiseCase {
+    /// Stable identifier used to line up verdicts across runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_id: Option<String>,
+
+    /// User query
+    pub query: String,
+
+    /// Retrieved contexts shown to the judge
+    #[serde(default)]
+    pub contexts: Vec<String>,
+
+    /// First candidate answer
+    pub answer_a: String,
+
+    /// Second candidate answer
+    pub answer_b: String,
+}
+
+/// What the judge picked in a single run, in terms of the original A/B labels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PairwiseChoice {
+    /// Answer A is better
+    A,
+    /// Answer B is better
+    B,
+    /// Neither answer is better
+    Tie,
+}
+
+impl PairwiseChoice {
+    /// Interpret the judge's reply, or `None` if it names no choice
+    ///
+    /// In longer replies only uppercase `A`/`B` count, so the article "a"
+    /// in "it's a tie" is not read as a vote.
+    pub fn parse(response: &str) -> Option<Self> {
+        let words: Vec<&str> = response
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        if words.iter().any(|word| word.eq_ignore_ascii_case("tie")) {
+            return Some(PairwiseChoice::Tie);
+        }
+
+        let single = words.len() == 1;
+        words.iter().find_map(|&word| match word {
+            "A" => Some(PairwiseChoice::A),
+            "B" => Some(PairwiseChoice::B),
+            "a" if single => Some(PairwiseChoice::A),
+            "b" if single => Some(PairwiseChoice::B),
+            _ => None,
+        })
+    }
+
+    fn swapped(self) -> Self {
+        match self {
+            PairwiseChoice::A => PairwiseChoice::B,
+            PairwiseChoice::B => PairwiseChoice::A,
+            PairwiseChoice::Tie => PairwiseChoice::Tie,
+        }
+    }
+}
+
+/// Final verdict for a case after both orderings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PairwiseVerdict {
+    /// Both runs preferred answer A
+    A,
+    /// Both runs preferred answer B
+    B,
+    /// Both runs called it a tie
+    Tie,
+    /// The two orderings disagreed
+    Inconsistent,
+}
+
+/// Verdict for one case, with the choice from each ordering
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairwiseJudgment {
+    /// Case identifier, if the case had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_id: Option<String>,
+
+    /// Combined verdict
+    pub verdict: PairwiseVerdict,
+
+    /// Choice with answer A shown first
+    pub original_order: PairwiseChoice,
+
+    /// Choice with answer B shown first, mapped back to the original labels
+    pub swapped_order: PairwiseChoice,
+}
+
+impl PairwiseJudgment {
+    fn new(case_id: Option<String>, original: PairwiseChoice, swapped: PairwiseChoice) -> Self {
+        let verdict = match (original, swapped) {
+            (PairwiseChoice::A, PairwiseChoice::A) => PairwiseVerdict::A,
+            (PairwiseChoice::B, PairwiseChoice::B) => PairwiseVerdict::B,
+            (PairwiseChoice::Tie, PairwiseChoice::Tie) => PairwiseVerdict::Tie,
+            _ => PairwiseVerdict::Inconsistent,
+        };
+
+        Self {
+            case_id,
+            verdict,
+            original_order: original,
+            swapped_order: swapped,
+        }
+    }
+}
+
+/// Win rates across a batch of pairwise comparisons
+///
+/// Rates are fractions of all cases, inconsistent ones included, so the four
+/// rates sum to 1.0 for a non-empty batch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PairwiseReport {
+    /// Per-case verdicts in input order
+    pub judgments: Vec<PairwiseJudgment>,
+
+    /// Cases where A won in both orderings
+    pub a_wins: usize,
+
+    /// Cases where B won in both orderings
+    pub b_wins: usize,
+
+    /// Cases judged a tie in both orderings
+    pub ties: usize,
+
+    /// Cases where the orderings disagreed
+    pub inconsistent: usize,
+
+    /// `a_wins / cases`
+    pub a_win_rate: f32,
+
+    /// `b_wins / cases`
+    pub b_win_rate: f32,
+
+    /// `ties / cases`
+    pub tie_rate: f32,
+
+    /// `inconsistent / cases`
+    pub inconsistent_rate: f32,
+}
+
+impl PairwiseReport {
+    /// Tally verdicts into a report
+    pub fn from_judgments(judgments: Vec<PairwiseJudgment>) -> Self {
+        let count = |verdict: PairwiseVerdict| {
+            judgments
+                .iter()
+                .filter(|judgment| judgment.verdict == verdict)
+                .count()
+        };
+        let a_wins = count(PairwiseVerdict::A);
+        let b_wins = count(PairwiseVerdict::B);
+        let ties = count(PairwiseVerdict::Tie);
+        let inconsistent = count(PairwiseVerdict::Inconsistent);
+
+        let total = judgments.len();
+        let rate = |n: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                n as f32 / total as f32
+            }
+        };
+
+        Self {
+            a_win_rate: rate(a_wins),
+            b_win_rate: rate(b_wins),
+            tie_rate: rate(ties),
+            inconsistent_rate: rate(inconsistent),
+            a_wins,
+            b_wins,
+            ties,
+            inconsistent,
+            judgments,
+        }
+    }
+}
+
+/// LLM judge that picks the better of two answers
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{PairwiseCase, PairwiseComparator};
+/// # struct MyLLM;
+/// # impl vecstore_eval::LLM for MyLLM {
+/// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok("A".to_string()) }
+/// # }
+///
+/// let comparator = PairwiseComparator::new(Box::new(MyLLM));
+///
+/// let cases = vec![PairwiseCase {
+///     query: "What is Rust?".to_string(),
+///     contexts: vec!["Rust is a systems programming language.".to_string()],
+///     answer_a: "Rust is a systems programming language.".to_string(),
+///     answer_b: "Rust is a kind of corrosion.".to_string(),
+///     ..Default::default()
+/// }];
+///
+/// let report = comparator.compare_batch(&cases)?;
+/// println!("A wins {:.0}% of cases", report.a_win_rate * 100.0);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct PairwiseComparator {
+    llm: Box<dyn LLM>,
+    prompt: PromptTemplate,
+    params: GenerationParams,
+}
+
+impl PairwiseComparator {
+    /// Create a comparator with the default prompt
+    pub fn new(llm: Box<dyn LLM>) -> Self {
+        Self {
+            llm,
+            prompt: PromptTemplate::new(DEFAULT_PAIRWISE_PROMPT),
+            params: GenerationParams::deterministic(),
+        }
+    }
+
+    /// Override the comparison prompt (`{query}`, `{context}`, `{answer_a}`, `{answer_b}`)
+    pub fn with_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("pairwise comparison", PAIRWISE_PLACEHOLDERS)?;
+        self.prompt = template;
+        Ok(self)
+    }
+
+    /// Override the sampling params (default: temperature 0)
+    pub fn with_generation_params(mut self, params: GenerationParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    fn judge(
+        &self,
+        query: &str,
+        context: &str,
+        first: &str,
+        second: &str,
+    ) -> Result<PairwiseChoice> {
+        let prompt = self.prompt.render(&[
+            ("query", query),
+            ("context", context),
+            ("answer_a", first),
+            ("answer_b", second),
+        ]);
+
+        let response = self.llm.generate_with_params(&prompt, &self.params)?;
+        PairwiseChoice::parse(&response).ok_or_else(|| {
+            anyhow!(
+                "Could not parse a pairwise verdict (A, B or tie) from LLM response: {:?}",
+                response
+            )
+        })
+    }
+
+    /// Compare one case in both orderings
+    pub fn compare(&self, case: &PairwiseCase) -> Result<PairwiseJudgment> {
+        let context = case.contexts.join("\n\n");
+
+        let original = self.judge(&case.query, &context, &case.answer_a, &case.answer_b)?;
+        let swapped = self
+            .judge(&case.query, &context, &case.answer_b, &case.answer_a)?
+            .swapped();
+
+        Ok(PairwiseJudgment::new(
+            case.case_id.clone(),
+            original,
+            swapped,
+        ))
+    }
+
+    /// Compare every case and tally win rates
+    pub fn compare_batch(&self, cases: &[PairwiseCase]) -> Result<PairwiseReport> {
+        let judgments = cases
+            .iter()
+            .map(|case| self.compare(case))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(PairwiseReport::from_judgments(judgments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Prefers whichever answer mentions "good", or the first one if neither does
+    struct KeywordLLM;
+
+    impl LLM for KeywordLLM {
+        fn generate(&self, prompt: &str) -> Result<String> {
+            let a = prompt.split("Answer A:").nth(1).unwrap();
+            let (a, b) = a.split_once("Answer B:").unwrap();
+            Ok(match (a.contains("good"), b.contains("good")) {
+                (true, false) => "A",
+                (false, true) => "Answer B is better.",
+                (true, true) => "Tie",
+                (false, false) => "A",
+            }
+            .to_string())
+        }
+    }
+
+    fn case(id: &str, a: &str, b: &str) -> PairwiseCase {
+        PairwiseCase {
+            case_id: Some(id.to_string()),
+            query: "q".to_string(),
+            answer_a: a.to_string(),
+            answer_b: b.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(PairwiseChoice::parse("A"), Some(PairwiseChoice::A));
+        assert_eq!(PairwiseChoice::parse(" b."), Some(PairwiseChoice::B));
+        assert_eq!(
+            PairwiseChoice::parse("It's a TIE"),
+            Some(PairwiseChoice::Tie)
+        );
+        assert_eq!(PairwiseChoice::parse("Answer B"), Some(PairwiseChoice::B));
+        assert_eq!(PairwiseChoice::parse("Neither is a clear winner"), None);
+    }
+
+    #[test]
+    fn test_swapped_runs_cancel_position_bias() {
+        let comparator = PairwiseComparator::new(Box::new(KeywordLLM));
+
+        let report = comparator
+            .compare_batch(&[
+                case("1", "good answer", "bad answer"),
+                case("2", "bad answer", "good answer"),
+                case("3", "good", "also good"),
+                // Judge always picks the first position here
+                case("4", "meh", "meh too"),
+            ])
+            .unwrap();
+
+        let verdicts: Vec<_> = report.judgments.iter().map(|j| j.verdict).collect();
+        assert_eq!(
+            verdicts,
+            vec![
+                PairwiseVerdict::A,
+                PairwiseVerdict::B,
+                PairwiseVerdict::Tie,
+                PairwiseVerdict::Inconsistent
+            ]
+        );
+        assert_eq!(report.judgments[3].original_order, PairwiseChoice::A);
+        assert_eq!(report.judgments[3].swapped_order, PairwiseChoice::B);
+        assert_eq!(report.judgments[1].case_id.as_deref(), Some("2"));
+
+        assert_eq!(report.a_wins, 1);
+        assert_eq!(report.inconsistent, 1);
+        assert_eq!(report.a_win_rate, 0.25);
+        assert_eq!(report.inconsistent_rate, 0.25);
+    }
+
+    #[test]
+    fn test_unparseable_verdict_errors() {
+        struct VagueLLM;
+        impl LLM for VagueLLM {
+            fn generate(&self, _: &str) -> Result<String> {
+                Ok("Both have merits.".to_string())
+            }
+        }
+
+        let comparator = PairwiseComparator::new(Box::new(VagueLLM));
+        let err = comparator.compare(&case("1", "x", "y")).unwrap_err();
+        assert!(err.to_string().contains("Both have merits"));
+    }
+
+    #[test]
+    fn test_with_prompt_validates() {
+        assert!(PairwiseComparator::new(Box::new(KeywordLLM))
+            .with_prompt("{query} {answer_c}")
+            .is_err());
+    }
+}
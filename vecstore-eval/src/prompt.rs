@@ -0,0 +1,239 @@
+//! Prompt templates for LLM-as-judge metrics
+//!
+//! Templates use `{name}` placeholders (e.g. `{query}`, `{context}`,
+//! `{answer}`). Write `{{` and `}}` for literal braces. Braces that do not
+//! wrap an identifier, such as a JSON example, are kept as-is.
+//!
+//! Each metric checks a custom template against the placeholders it can
+//! fill when the template is set, so typos fail at construction time rather
+//! than in the middle of an evaluation run.
+
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder(String),
+}
+
+/// A prompt with named `{placeholder}` slots
+///
+/// # Example
+///
+/// ```
+/// use vecstore_eval::{ContextRelevance, PromptTemplate};
+/// # struct MyLLM;
+/// # impl vecstore_eval::LLM for MyLLM {
+/// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok("Ja".to_string()) }
+/// # }
+///
+/// let template = PromptTemplate::new(
+///     "Frage: {query}\n\nKontext: {context}\n\nIst der Kontext relevant? Antworte nur mit 'Yes' oder 'No'.",
+/// );
+/// let metric = ContextRelevance::new(Box::new(MyLLM)).with_prompt(template)?;
+///
+/// // Unknown placeholders are rejected up front
+/// let typo = PromptTemplate::new("Query: {qurey}");
+/// assert!(ContextRelevance::new(Box::new(MyLLM)).with_prompt(typo).is_err());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+impl PromptTemplate {
+    /// Parse a template string
+    pub fn new(template: impl Into<String>) -> Self {
+        let source = template.into();
+        let segments = parse(&source);
+        Self { source, segments }
+    }
+
+    /// The original template text
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Placeholder names in order of first appearance
+    pub fn placeholders(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for segment in &self.segments {
+            if let Segment::Placeholder(name) = segment {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
+    /// Check that every placeholder is one of `allowed`
+    ///
+    /// `label` names the prompt in the error message.
+    pub fn validate(&self, label: &str, allowed: &[&str]) -> Result<()> {
+        let unknown: Vec<&str> = self
+            .placeholders()
+            .into_iter()
+            .filter(|name| !allowed.contains(name))
+            .collect();
+
+        if unknown.is_empty() {
+            return Ok(());
+        }
+
+        let fmt_names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|n| format!("{{{}}}", n))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        Err(anyhow!(
+            "Unknown placeholder(s) {} in {} prompt; supported placeholders are {}",
+            fmt_names(&unknown),
+            label,
+            fmt_names(allowed)
+        ))
+    }
+
+    /// Fill placeholders from `(name, value)` pairs
+    ///
+    /// Values are inserted verbatim and never re-scanned, so a context that
+    /// happens to contain `{answer}` is left alone. Placeholders without a
+    /// value are rendered unchanged.
+    pub fn render(&self, values: &[(&str, &str)]) -> String {
+        let mut out = String::with_capacity(self.source.len());
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Placeholder(name) => {
+                    match values.iter().find(|(key, _)| key == name) {
+                        Some((_, value)) => out.push_str(value),
+                        None => {
+                            out.push('{');
+                            out.push_str(name);
+                            out.push('}');
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl From<&str> for PromptTemplate {
+    fn from(template: &str) -> Self {
+        Self::new(template)
+    }
+}
+
+impl From<String> for PromptTemplate {
+    fn from(template: String) -> Self {
+        Self::new(template)
+    }
+}
+
+impl fmt::Display for PromptTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn parse(source: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut rest = source;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        literal.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            literal.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+
+        if tail.starts_with('{') {
+            if let Some(end) = tail.find('}') {
+                let name = &tail[1..end];
+                if is_identifier(name) {
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Placeholder(name.to_string()));
+                    rest = &tail[end + 1..];
+                    continue;
+                }
+            }
+        }
+
+        // Stray brace: keep it literally
+        literal.push_str(&tail[..1]);
+        rest = &tail[1..];
+    }
+
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let template = PromptTemplate::new("Q: {query}\nA: {answer}\nQ again: {query}");
+        assert_eq!(template.placeholders(), vec!["query", "answer"]);
+        assert_eq!(
+            template.render(&[("query", "What?"), ("answer", "That.")]),
+            "Q: What?\nA: That.\nQ again: What?"
+        );
+    }
+
+    #[test]
+    fn test_values_are_not_rescanned() {
+        let template = PromptTemplate::new("{context} / {answer}");
+        assert_eq!(
+            template.render(&[("context", "uses {answer} literally"), ("answer", "x")]),
+            "uses {answer} literally / x"
+        );
+    }
+
+    #[test]
+    fn test_escapes_and_literal_braces() {
+        let template = PromptTemplate::new("Reply as {\"score\": 0.5} or {{query}} for {query}");
+        assert_eq!(template.placeholders(), vec!["query"]);
+        assert_eq!(
+            template.render(&[("query", "q")]),
+            "Reply as {\"score\": 0.5} or {query} for q"
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let template = PromptTemplate::new("{query} {contxt}");
+        assert!(template.validate("test", &["query", "contxt"]).is_ok());
+
+        let err = template
+            .validate("context_relevance", &["query", "context"])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("{contxt}"), "{}", err);
+        assert!(err.contains("context_relevance"), "{}", err);
+    }
+}
@@ -0,0 +1,332 @@
+//! End-to-end RAG evaluation against a live VecStore (feature `vecstore`)
+//!
+//! The rest of the crate scores contexts that were retrieved elsewhere.
+//! [`RagHarness`] runs retrieval itself: for every test case it embeds the
+//! query, searches the store, fills `contexts` and `retrieved_ids` from the
+//! hits, optionally generates an answer, and hands the result to an
+//! [`Evaluator`]. [`RagHarness::sweep`] repeats that for several retrieval
+//! settings so `k` and `ef_search` can be tuned on quality rather than guessed.
+
+use crate::evaluator::{AggregateStats, Evaluator};
+use crate::metrics::{Embedder, LLM};
+use crate::prompt::PromptTemplate;
+use crate::types::{EvaluationInput, EvaluationReport};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use vecstore::{HNSWSearchParams, Neighbor, Query, VecStore};
+
+/// Default prompt for generating an answer from retrieved contexts
+///
+/// Placeholders: `{query}`, `{context}`.
+pub const DEFAULT_ANSWER_PROMPT: &str = "Answer the question using only the context below. \
+If the context does not contain the answer, say that you don't know.\n\n\
+Context:\n{context}\n\nQuestion: {query}\n\nAnswer:";
+
+const ANSWER_PLACEHOLDERS: &[&str] = &["query", "context"];
+
+/// Metadata field holding a record's text, unless overridden
+pub const DEFAULT_TEXT_FIELD: &str = "text";
+
+/// Retrieval settings for one harness run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RagConfig {
+    /// Number of contexts to retrieve
+    pub k: usize,
+
+    /// HNSW candidate list size (`None` uses the store default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ef_search: Option<usize>,
+}
+
+impl RagConfig {
+    /// Retrieve `k` contexts with the store's default search parameters
+    pub fn new(k: usize) -> Self {
+        Self { k, ef_search: None }
+    }
+
+    /// Set the HNSW `ef_search` parameter
+    pub fn with_ef_search(mut self, ef_search: usize) -> Self {
+        self.ef_search = Some(ef_search);
+        self
+    }
+
+    /// One configuration per `k`, e.g. `RagConfig::sweep_k(&[3, 5, 10])`
+    pub fn sweep_k(ks: &[usize]) -> Vec<Self> {
+        ks.iter().map(|&k| Self::new(k)).collect()
+    }
+}
+
+/// Reports and aggregate statistics for one configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RagRun {
+    /// Retrieval settings used
+    pub config: RagConfig,
+
+    /// One report per test case, in input order
+    pub reports: Vec<EvaluationReport>,
+
+    /// Aggregate over `reports`
+    pub stats: AggregateStats,
+}
+
+/// Drives VecStore retrieval and evaluates the results
+///
+/// Test cases are ordinary [`EvaluationInput`]s (e.g. loaded with
+/// [`EvaluationDataset`](crate::EvaluationDataset)); only `query`,
+/// `ground_truth`, `relevant_ids` and `case_id` need to be set. Retrieved
+/// contexts are read from the [`DEFAULT_TEXT_FIELD`] metadata field.
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore::VecStore;
+/// use vecstore_eval::{
+///     AnswerCorrectness, EvaluationDataset, Evaluator, RagConfig, RagHarness, RetrievalMetrics,
+/// };
+/// # struct MyEmbedder;
+/// # impl vecstore_eval::Embedder for MyEmbedder {
+/// #     fn embed(&self, _: &str) -> anyhow::Result<Vec<f32>> { Ok(vec![1.0]) }
+/// # }
+/// # struct MyLLM;
+/// # impl vecstore_eval::LLM for MyLLM {
+/// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok(String::new()) }
+/// # }
+///
+/// let store = VecStore::open("data")?;
+/// let cases = EvaluationDataset::from_jsonl("golden.jsonl")?;
+///
+/// let mut evaluator = Evaluator::new();
+/// evaluator.add_metric(Box::new(RetrievalMetrics::new()));
+/// evaluator.add_metric(Box::new(AnswerCorrectness::new(Box::new(MyEmbedder))));
+///
+/// let harness = RagHarness::new(&store, Box::new(MyEmbedder), evaluator).with_llm(Box::new(MyLLM));
+///
+/// for run in harness.sweep(&cases, &RagConfig::sweep_k(&[3, 5, 10]))? {
+///     println!("k={}: {:.3}", run.config.k, run.stats.average_overall_score);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct RagHarness<'a> {
+    store: &'a VecStore,
+    embedder: Box<dyn Embedder>,
+    llm: Option<Box<dyn LLM>>,
+    evaluator: Evaluator,
+    text_field: String,
+    answer_prompt: PromptTemplate,
+}
+
+impl<'a> RagHarness<'a> {
+    /// Create a harness over `store`, embedding queries with `embedder`
+    pub fn new(store: &'a VecStore, embedder: Box<dyn Embedder>, evaluator: Evaluator) -> Self {
+        Self {
+            store,
+            embedder,
+            llm: None,
+            evaluator,
+            text_field: DEFAULT_TEXT_FIELD.to_string(),
+            answer_prompt: PromptTemplate::new(DEFAULT_ANSWER_PROMPT),
+        }
+    }
+
+    /// Generate answers from the retrieved contexts with `llm`
+    ///
+    /// Without an LLM, each case keeps the `answer` it was loaded with.
+    pub fn with_llm(mut self, llm: Box<dyn LLM>) -> Self {
+        self.llm = Some(llm);
+        self
+    }
+
+    /// Read context text from a different metadata field
+    pub fn with_text_field(mut self, field: impl Into<String>) -> Self {
+        self.text_field = field.into();
+        self
+    }
+
+    /// Override the answer generation prompt (`{query}`, `{context}`)
+    pub fn with_answer_prompt(mut self, template: impl Into<PromptTemplate>) -> Result<Self> {
+        let template = template.into();
+        template.validate("answer generation", ANSWER_PLACEHOLDERS)?;
+        self.answer_prompt = template;
+        Ok(self)
+    }
+
+    /// Embed `query` and search the store
+    pub fn retrieve(&self, query: &str, config: &RagConfig) -> Result<Vec<Neighbor>> {
+        let vector = self.embedder.embed(query)?;
+        let q = Query::new(vector).with_limit(config.k);
+
+        match config.ef_search {
+            Some(ef_search) => self
+                .store
+                .query_with_params(q, HNSWSearchParams { ef_search }),
+            None => self.store.query(q),
+        }
+    }
+
+    /// Fill in contexts, retrieved IDs and (with an LLM) the answer for one case
+    pub fn prepare(&self, case: &EvaluationInput, config: &RagConfig) -> Result<EvaluationInput> {
+        let neighbors = self.retrieve(&case.query, config)?;
+
+        let contexts = neighbors
+            .iter()
+            .map(|neighbor| {
+                neighbor
+                    .metadata
+                    .fields
+                    .get(&self.text_field)
+                    .and_then(|value| value.as_str())
+                    .map(str::to_string)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Record '{}' has no string '{}' metadata field",
+                            neighbor.id,
+                            self.text_field
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut input = EvaluationInput {
+            contexts,
+            retrieved_ids: neighbors.into_iter().map(|neighbor| neighbor.id).collect(),
+            ..case.clone()
+        };
+
+        if let Some(llm) = &self.llm {
+            let prompt = self.answer_prompt.render(&[
+                ("query", &input.query),
+                ("context", &input.contexts.join("\n\n")),
+            ]);
+            input.answer = Some(llm.generate(&prompt)?);
+        }
+
+        Ok(input)
+    }
+
+    /// Retrieve, generate and evaluate every case with one configuration
+    pub fn run(&self, cases: &[EvaluationInput], config: &RagConfig) -> Result<RagRun> {
+        let reports = cases
+            .iter()
+            .map(|case| {
+                let input = self.prepare(case, config)?;
+                self.evaluator.evaluate(&input)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RagRun {
+            config: *config,
+            stats: self.evaluator.aggregate_reports(&reports),
+            reports,
+        })
+    }
+
+    /// Run every configuration, returning one [`RagRun`] each in order
+    pub fn sweep(&self, cases: &[EvaluationInput], configs: &[RagConfig]) -> Result<Vec<RagRun>> {
+        configs
+            .iter()
+            .map(|config| self.run(cases, config))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retrieval::RetrievalMetrics;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+    use vecstore::Metadata;
+
+    /// Two-dimensional "topic" embedding: Rust along x, Python along y
+    struct TopicEmbedder;
+    impl Embedder for TopicEmbedder {
+        fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            if text.contains("Rust") {
+                Ok(vec![1.0, 0.1])
+            } else {
+                Ok(vec![0.1, 1.0])
+            }
+        }
+    }
+
+    struct EchoLLM;
+    impl LLM for EchoLLM {
+        fn generate(&self, prompt: &str) -> Result<String> {
+            Ok(prompt.lines().nth(3).unwrap_or_default().to_string())
+        }
+    }
+
+    fn store(dir: &TempDir) -> VecStore {
+        let mut store = VecStore::open(dir.path()).unwrap();
+        for (id, text) in [
+            ("rust", "Rust is a systems language."),
+            ("python", "Python is a scripting language."),
+        ] {
+            let fields: HashMap<String, serde_json::Value> =
+                [("text".to_string(), serde_json::json!(text))]
+                    .into_iter()
+                    .collect();
+            store
+                .upsert(
+                    id.to_string(),
+                    TopicEmbedder.embed(text).unwrap(),
+                    Metadata { fields },
+                )
+                .unwrap();
+        }
+        store
+    }
+
+    fn cases() -> Vec<EvaluationInput> {
+        vec![EvaluationInput {
+            query: "What is Rust?".to_string(),
+            relevant_ids: vec!["rust".to_string()],
+            case_id: Some("rust".to_string()),
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn test_prepare_fills_contexts_and_answer() {
+        let dir = TempDir::new().unwrap();
+        let store = store(&dir);
+        let harness = RagHarness::new(&store, Box::new(TopicEmbedder), Evaluator::new())
+            .with_llm(Box::new(EchoLLM));
+
+        let input = harness.prepare(&cases()[0], &RagConfig::new(1)).unwrap();
+        assert_eq!(input.retrieved_ids, vec!["rust"]);
+        assert_eq!(input.contexts, vec!["Rust is a systems language."]);
+        assert_eq!(input.answer.as_deref(), Some("Rust is a systems language."));
+        assert_eq!(input.case_id.as_deref(), Some("rust"));
+    }
+
+    #[test]
+    fn test_sweep_one_run_per_config() {
+        let dir = TempDir::new().unwrap();
+        let store = store(&dir);
+        let mut evaluator = Evaluator::new();
+        evaluator.add_metric(Box::new(RetrievalMetrics::new()));
+        let harness = RagHarness::new(&store, Box::new(TopicEmbedder), evaluator);
+
+        let configs = vec![RagConfig::new(1), RagConfig::new(2).with_ef_search(64)];
+        let runs = harness.sweep(&cases(), &configs).unwrap();
+
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[1].config, configs[1]);
+        assert_eq!(runs[0].reports[0].case_id.as_deref(), Some("rust"));
+        assert_eq!(runs[1].stats.count, 1);
+    }
+
+    #[test]
+    fn test_missing_text_field() {
+        let dir = TempDir::new().unwrap();
+        let store = store(&dir);
+        let harness = RagHarness::new(&store, Box::new(TopicEmbedder), Evaluator::new())
+            .with_text_field("body");
+
+        let err = harness
+            .prepare(&cases()[0], &RagConfig::new(1))
+            .unwrap_err();
+        assert!(err.to_string().contains("'body'"));
+    }
+}
@@ -0,0 +1,336 @@
+//! Retrieval Quality Metrics
+//!
+//! Classic information-retrieval metrics computed from labeled document IDs.
+//! No LLM or embedder is needed: the metric compares
+//! [`EvaluationInput::retrieved_ids`] against [`EvaluationInput::relevant_ids`].
+//!
+//! - **Precision@k**: fraction of the top-k results that are relevant
+//! - **Recall@k**: fraction of relevant documents found in the top-k
+//! - **Hit rate@k**: 1.0 if any relevant document is in the top-k
+//! - **MRR**: reciprocal rank of the first relevant result
+//! - **NDCG@k**: rank-discounted gain normalized by the ideal ordering
+
+use crate::types::{EvaluationInput, Metric, MetricResult};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Which value [`RetrievalMetrics`] reports as its headline `score`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RetrievalScore {
+    /// Precision at the headline k
+    Precision,
+    /// Recall at the headline k
+    Recall,
+    /// Hit rate at the headline k
+    HitRate,
+    /// Mean reciprocal rank (k-independent)
+    Mrr,
+    /// NDCG at the headline k
+    Ndcg,
+}
+
+/// Precision@k, recall@k, hit rate@k, MRR and NDCG@k from labeled IDs
+///
+/// Every measure is reported in [`MetricResult::details`] for every
+/// configured k (e.g. `"precision@5"`, `"ndcg@10"`), plus `"mrr"` and
+/// `"first_relevant_rank"`. The headline `score` defaults to NDCG at the
+/// largest k.
+///
+/// # Example
+///
+/// ```
+/// use vecstore_eval::{EvaluationInput, Metric, RetrievalMetrics};
+///
+/// let metric = RetrievalMetrics::new().with_k_values(vec![1, 3]);
+///
+/// let input = EvaluationInput {
+///     query: "What is Rust?".to_string(),
+///     retrieved_ids: vec!["doc-7".into(), "doc-2".into(), "doc-9".into()],
+///     relevant_ids: vec!["doc-2".into()],
+///     ..Default::default()
+/// };
+///
+/// let result = metric.evaluate(&input)?;
+/// assert_eq!(result.details["mrr"], 0.5);
+/// assert_eq!(result.details["recall@3"], 1.0);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct RetrievalMetrics {
+    k_values: Vec<usize>,
+    headline: RetrievalScore,
+    headline_k: Option<usize>,
+}
+
+impl RetrievalMetrics {
+    /// Create retrieval metrics with k = 1, 3, 5, 10 and NDCG@10 as headline
+    pub fn new() -> Self {
+        Self {
+            k_values: vec![1, 3, 5, 10],
+            headline: RetrievalScore::Ndcg,
+            headline_k: None,
+        }
+    }
+
+    /// Set the cutoffs to compute measures at (zero values are ignored)
+    pub fn with_k_values(mut self, mut k_values: Vec<usize>) -> Self {
+        k_values.retain(|&k| k > 0);
+        k_values.sort_unstable();
+        k_values.dedup();
+        self.k_values = k_values;
+        self
+    }
+
+    /// Choose the headline score (at the largest k unless overridden)
+    pub fn with_headline(mut self, headline: RetrievalScore) -> Self {
+        self.headline = headline;
+        self
+    }
+
+    /// Choose the cutoff used for the headline score
+    pub fn with_headline_k(mut self, k: usize) -> Self {
+        self.headline_k = Some(k.max(1));
+        self
+    }
+
+    /// Fraction of the top-k results that are relevant
+    pub fn precision_at_k(retrieved: &[String], relevant: &HashSet<&str>, k: usize) -> f32 {
+        if k == 0 {
+            return 0.0;
+        }
+        hits_at_k(retrieved, relevant, k) as f32 / k as f32
+    }
+
+    /// Fraction of relevant documents found in the top-k results
+    pub fn recall_at_k(retrieved: &[String], relevant: &HashSet<&str>, k: usize) -> f32 {
+        if relevant.is_empty() {
+            return 0.0;
+        }
+        hits_at_k(retrieved, relevant, k) as f32 / relevant.len() as f32
+    }
+
+    /// Reciprocal rank of the first relevant result (0.0 if none)
+    pub fn reciprocal_rank(retrieved: &[String], relevant: &HashSet<&str>) -> f32 {
+        first_relevant_rank(retrieved, relevant)
+            .map(|rank| 1.0 / rank as f32)
+            .unwrap_or(0.0)
+    }
+
+    /// Normalized discounted cumulative gain with binary relevance
+    pub fn ndcg_at_k(retrieved: &[String], relevant: &HashSet<&str>, k: usize) -> f32 {
+        let mut seen = HashSet::new();
+        let dcg: f32 = retrieved
+            .iter()
+            .take(k)
+            .enumerate()
+            .filter(|(_, id)| relevant.contains(id.as_str()) && seen.insert(id.as_str()))
+            .map(|(i, _)| discount(i))
+            .sum();
+
+        let ideal_hits = relevant.len().min(k);
+        let idcg: f32 = (0..ideal_hits).map(discount).sum();
+
+        if idcg == 0.0 {
+            0.0
+        } else {
+            dcg / idcg
+        }
+    }
+}
+
+impl Default for RetrievalMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metric for RetrievalMetrics {
+    fn name(&self) -> &str {
+        "retrieval"
+    }
+
+    fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+        if input.relevant_ids.is_empty() {
+            return Err(anyhow!("relevant_ids required for retrieval metrics"));
+        }
+
+        let retrieved = &input.retrieved_ids;
+        let relevant: HashSet<&str> = input.relevant_ids.iter().map(String::as_str).collect();
+
+        let mut details = HashMap::new();
+
+        for &k in &self.k_values {
+            details.insert(
+                format!("precision@{}", k),
+                serde_json::json!(Self::precision_at_k(retrieved, &relevant, k)),
+            );
+            details.insert(
+                format!("recall@{}", k),
+                serde_json::json!(Self::recall_at_k(retrieved, &relevant, k)),
+            );
+            details.insert(
+                format!("hit_rate@{}", k),
+                serde_json::json!(hit_rate(retrieved, &relevant, k)),
+            );
+            details.insert(
+                format!("ndcg@{}", k),
+                serde_json::json!(Self::ndcg_at_k(retrieved, &relevant, k)),
+            );
+        }
+
+        let mrr = Self::reciprocal_rank(retrieved, &relevant);
+        details.insert("mrr".to_string(), serde_json::json!(mrr));
+        details.insert(
+            "first_relevant_rank".to_string(),
+            serde_json::json!(first_relevant_rank(retrieved, &relevant)),
+        );
+        details.insert("retrieved_count".to_string(), serde_json::json!(retrieved.len()));
+        details.insert("relevant_count".to_string(), serde_json::json!(relevant.len()));
+
+        let k = self
+            .headline_k
+            .or_else(|| self.k_values.last().copied())
+            .unwrap_or(retrieved.len().max(1));
+
+        let score = match self.headline {
+            RetrievalScore::Precision => Self::precision_at_k(retrieved, &relevant, k),
+            RetrievalScore::Recall => Self::recall_at_k(retrieved, &relevant, k),
+            RetrievalScore::HitRate => hit_rate(retrieved, &relevant, k),
+            RetrievalScore::Mrr => mrr,
+            RetrievalScore::Ndcg => Self::ndcg_at_k(retrieved, &relevant, k),
+        };
+
+        Ok(MetricResult {
+            metric_name: self.name().to_string(),
+            score,
+            details,
+            ..Default::default()
+        })
+    }
+}
+
+/// Number of distinct relevant documents in the top-k
+fn hits_at_k(retrieved: &[String], relevant: &HashSet<&str>, k: usize) -> usize {
+    retrieved
+        .iter()
+        .take(k)
+        .map(String::as_str)
+        .filter(|id| relevant.contains(id))
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+fn hit_rate(retrieved: &[String], relevant: &HashSet<&str>, k: usize) -> f32 {
+    if hits_at_k(retrieved, relevant, k) > 0 {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// 1-based rank of the first relevant result
+fn first_relevant_rank(retrieved: &[String], relevant: &HashSet<&str>) -> Option<usize> {
+    retrieved
+        .iter()
+        .position(|id| relevant.contains(id.as_str()))
+        .map(|i| i + 1)
+}
+
+/// DCG discount for a 0-based position
+fn discount(position: usize) -> f32 {
+    1.0 / ((position + 2) as f32).log2()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn ids(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn input(retrieved: &[&str], relevant: &[&str]) -> EvaluationInput {
+        EvaluationInput {
+            query: "q".to_string(),
+            retrieved_ids: ids(retrieved),
+            relevant_ids: ids(relevant),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_precision_recall_hit_rate() {
+        let metric = RetrievalMetrics::new().with_k_values(vec![1, 2, 4]);
+        let result = metric
+            .evaluate(&input(&["a", "x", "b", "y"], &["a", "b", "c"]))
+            .unwrap();
+
+        assert_relative_eq!(result.details["precision@1"].as_f64().unwrap(), 1.0);
+        assert_relative_eq!(result.details["precision@2"].as_f64().unwrap(), 0.5);
+        assert_relative_eq!(result.details["precision@4"].as_f64().unwrap(), 0.5);
+        assert_relative_eq!(result.details["recall@4"].as_f64().unwrap(), 2.0 / 3.0, epsilon = 1e-6);
+        assert_relative_eq!(result.details["hit_rate@1"].as_f64().unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_mrr_and_first_rank() {
+        let result = RetrievalMetrics::new()
+            .evaluate(&input(&["x", "y", "b"], &["b"]))
+            .unwrap();
+
+        assert_relative_eq!(result.details["mrr"].as_f64().unwrap(), 1.0 / 3.0, epsilon = 1e-6);
+        assert_eq!(result.details["first_relevant_rank"], 3);
+        assert_eq!(result.details["hit_rate@1"], 0.0);
+    }
+
+    #[test]
+    fn test_ndcg() {
+        let relevant: HashSet<&str> = ["a", "b"].into_iter().collect();
+
+        // Perfect ordering
+        assert_relative_eq!(RetrievalMetrics::ndcg_at_k(&ids(&["a", "b", "x"]), &relevant, 3), 1.0);
+
+        // Relevant docs at ranks 2 and 3
+        let expected = (1.0 / 3f32.log2() + 1.0 / 4f32.log2()) / (1.0 + 1.0 / 3f32.log2());
+        assert_relative_eq!(
+            RetrievalMetrics::ndcg_at_k(&ids(&["x", "a", "b"]), &relevant, 3),
+            expected,
+            epsilon = 1e-6
+        );
+
+        // Duplicates are not double counted
+        assert_relative_eq!(RetrievalMetrics::ndcg_at_k(&ids(&["a", "a"]), &relevant, 2), 1.0 / (1.0 + 1.0 / 3f32.log2()));
+    }
+
+    #[test]
+    fn test_headline_score() {
+        let data = input(&["x", "a"], &["a"]);
+
+        let mrr = RetrievalMetrics::new().with_headline(RetrievalScore::Mrr);
+        assert_relative_eq!(mrr.evaluate(&data).unwrap().score, 0.5);
+
+        let p1 = RetrievalMetrics::new()
+            .with_headline(RetrievalScore::Precision)
+            .with_headline_k(1);
+        assert_eq!(p1.evaluate(&data).unwrap().score, 0.0);
+    }
+
+    #[test]
+    fn test_no_results_and_missing_labels() {
+        let result = RetrievalMetrics::new().evaluate(&input(&[], &["a"])).unwrap();
+        assert_eq!(result.score, 0.0);
+
+        assert!(RetrievalMetrics::new().evaluate(&input(&["a"], &[])).is_err());
+    }
+
+    #[test]
+    fn test_input_ids_default_when_absent() {
+        let input: EvaluationInput = serde_json::from_str(
+            r#"{"query": "q", "contexts": [], "answer": null, "ground_truth": null}"#,
+        )
+        .unwrap();
+        assert!(input.retrieved_ids.is_empty());
+        assert!(input.relevant_ids.is_empty());
+    }
+}
@@ -0,0 +1,426 @@
+//! Noise Robustness
+//!
+//! Real retrievers return irrelevant passages. [`RobustnessHarness`] measures
+//! how much a RAG pipeline suffers from them: every case is evaluated once as
+//! given and once per [`DistractorPosition`] with distractor contexts mixed
+//! into the retrieval set, and the score drop is reported per case and
+//! across the run.
+//!
+//! Metrics that only look at contexts (e.g. context precision) react to the
+//! distractors directly. For faithfulness and correctness to change, the
+//! answer has to be regenerated from the noisy contexts, so plug the pipeline
+//! in with [`RobustnessHarness::with_generator`].
+
+use crate::evaluator::Evaluator;
+use crate::stats::SplitMix64;
+use crate::types::{EvaluationInput, EvaluationReport};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Produces an answer from a query and its contexts
+pub type AnswerGenerator = Box<dyn Fn(&str, &[String]) -> Result<String> + Send + Sync>;
+
+/// Where distractors are placed among the original contexts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DistractorPosition {
+    /// Before all original contexts (outranking them)
+    Front,
+    /// After all original contexts
+    Back,
+    /// Alternating original and distractor, starting with an original
+    Interleaved,
+    /// Randomly mixed with a fixed seed
+    Shuffled {
+        /// Seed for the shuffle
+        seed: u64,
+    },
+}
+
+impl DistractorPosition {
+    /// Combine `contexts` and `distractors` according to this position
+    pub fn apply(&self, contexts: &[String], distractors: &[String]) -> Vec<String> {
+        match self {
+            DistractorPosition::Front => distractors.iter().chain(contexts).cloned().collect(),
+            DistractorPosition::Back => contexts.iter().chain(distractors).cloned().collect(),
+            DistractorPosition::Interleaved => {
+                let mut mixed = Vec::with_capacity(contexts.len() + distractors.len());
+                let mut originals = contexts.iter();
+                let mut noise = distractors.iter();
+                loop {
+                    match (originals.next(), noise.next()) {
+                        (None, None) => break,
+                        (original, distractor) => {
+                            mixed.extend(original.cloned());
+                            mixed.extend(distractor.cloned());
+                        }
+                    }
+                }
+                mixed
+            }
+            DistractorPosition::Shuffled { seed } => {
+                let mut mixed: Vec<String> = contexts.iter().chain(distractors).cloned().collect();
+                let mut rng = SplitMix64::new(*seed);
+                for i in (1..mixed.len()).rev() {
+                    let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+                    mixed.swap(i, j);
+                }
+                mixed
+            }
+        }
+    }
+}
+
+/// An evaluation case plus the distractor contexts to inject
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RobustnessCase {
+    /// The clean case
+    pub input: EvaluationInput,
+
+    /// Irrelevant or misleading contexts
+    pub distractors: Vec<String>,
+}
+
+/// One evaluation with distractors injected
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PerturbedRun {
+    /// Where the distractors were placed
+    pub position: DistractorPosition,
+
+    /// Report for the perturbed input
+    pub report: EvaluationReport,
+
+    /// Baseline overall score minus perturbed overall score (positive = worse)
+    pub degradation: f32,
+
+    /// Per-metric baseline score minus perturbed score
+    pub metric_degradation: HashMap<String, f32>,
+}
+
+/// Baseline and perturbed results for one case
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseRobustness {
+    /// `case_id` of the input, if it had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_id: Option<String>,
+
+    /// Report without distractors
+    pub baseline: EvaluationReport,
+
+    /// One run per configured position
+    pub perturbed: Vec<PerturbedRun>,
+}
+
+impl CaseRobustness {
+    /// Largest overall score drop across positions
+    pub fn worst_degradation(&self) -> f32 {
+        self.perturbed
+            .iter()
+            .map(|run| run.degradation)
+            .fold(0.0, f32::max)
+    }
+}
+
+/// Noise robustness of a whole run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RobustnessReport {
+    /// Per-case results in input order
+    pub cases: Vec<CaseRobustness>,
+
+    /// Mean overall score drop over all perturbed runs
+    pub mean_degradation: f32,
+
+    /// Mean score drop per metric over all perturbed runs
+    pub mean_metric_degradation: HashMap<String, f32>,
+
+    /// Mean overall score drop for each position, in configured order
+    pub degradation_by_position: Vec<(DistractorPosition, f32)>,
+}
+
+impl RobustnessReport {
+    fn from_cases(cases: Vec<CaseRobustness>, positions: &[DistractorPosition]) -> Self {
+        let runs: Vec<&PerturbedRun> = cases.iter().flat_map(|c| &c.perturbed).collect();
+        let mean = |values: &[f32]| {
+            if values.is_empty() {
+                0.0
+            } else {
+                values.iter().sum::<f32>() / values.len() as f32
+            }
+        };
+
+        let overall: Vec<f32> = runs.iter().map(|run| run.degradation).collect();
+
+        let mut per_metric: HashMap<String, Vec<f32>> = HashMap::new();
+        for run in &runs {
+            for (name, drop) in &run.metric_degradation {
+                per_metric.entry(name.clone()).or_default().push(*drop);
+            }
+        }
+
+        let degradation_by_position = positions
+            .iter()
+            .map(|position| {
+                let drops: Vec<f32> = runs
+                    .iter()
+                    .filter(|run| run.position == *position)
+                    .map(|run| run.degradation)
+                    .collect();
+                (*position, mean(&drops))
+            })
+            .collect();
+
+        Self {
+            mean_degradation: mean(&overall),
+            mean_metric_degradation: per_metric
+                .into_iter()
+                .map(|(name, drops)| (name, mean(&drops)))
+                .collect(),
+            degradation_by_position,
+            cases,
+        }
+    }
+}
+
+/// Evaluates cases with and without injected distractor contexts
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_eval::{
+///     AnswerFaithfulness, DistractorPosition, EvaluationInput, Evaluator, RobustnessCase,
+///     RobustnessHarness,
+/// };
+/// # struct MyLLM;
+/// # impl vecstore_eval::LLM for MyLLM {
+/// #     fn generate(&self, _: &str) -> anyhow::Result<String> { Ok("0.9".to_string()) }
+/// # }
+/// # fn my_rag_answer(_: &str, _: &[String]) -> anyhow::Result<String> { Ok(String::new()) }
+///
+/// let mut evaluator = Evaluator::new();
+/// evaluator.add_metric(Box::new(AnswerFaithfulness::new(Box::new(MyLLM))));
+///
+/// let harness = RobustnessHarness::new(evaluator)
+///     .with_positions(vec![DistractorPosition::Front, DistractorPosition::Interleaved])
+///     .with_generator(my_rag_answer);
+///
+/// let cases = vec![RobustnessCase {
+///     input: EvaluationInput {
+///         query: "What is Rust?".to_string(),
+///         contexts: vec!["Rust is a systems programming language.".to_string()],
+///         ..Default::default()
+///     },
+///     distractors: vec!["Rust is a reddish-brown iron oxide.".to_string()],
+/// }];
+///
+/// let report = harness.evaluate(&cases)?;
+/// println!("mean score drop: {:.3}", report.mean_degradation);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct RobustnessHarness {
+    evaluator: Evaluator,
+    positions: Vec<DistractorPosition>,
+    generator: Option<AnswerGenerator>,
+}
+
+impl RobustnessHarness {
+    /// Wrap an evaluator, injecting distractors at the front and the back
+    pub fn new(evaluator: Evaluator) -> Self {
+        Self {
+            evaluator,
+            positions: vec![DistractorPosition::Front, DistractorPosition::Back],
+            generator: None,
+        }
+    }
+
+    /// Set where distractors are injected (one perturbed run per position)
+    pub fn with_positions(mut self, positions: Vec<DistractorPosition>) -> Self {
+        self.positions = positions;
+        self
+    }
+
+    /// Regenerate the answer from each context set before evaluating
+    ///
+    /// Without a generator the case's `answer` is reused unchanged.
+    pub fn with_generator<F>(mut self, generator: F) -> Self
+    where
+        F: Fn(&str, &[String]) -> Result<String> + Send + Sync + 'static,
+    {
+        self.generator = Some(Box::new(generator));
+        self
+    }
+
+    fn evaluate_contexts(
+        &self,
+        input: &EvaluationInput,
+        contexts: Vec<String>,
+    ) -> Result<EvaluationReport> {
+        let mut input = EvaluationInput {
+            contexts,
+            ..input.clone()
+        };
+        if let Some(generator) = &self.generator {
+            input.answer = Some(generator(&input.query, &input.contexts)?);
+        }
+        self.evaluator.evaluate(&input)
+    }
+
+    /// Evaluate one case at baseline and at every position
+    pub fn evaluate_case(&self, case: &RobustnessCase) -> Result<CaseRobustness> {
+        let baseline = self.evaluate_contexts(&case.input, case.input.contexts.clone())?;
+
+        let perturbed = self
+            .positions
+            .iter()
+            .map(|position| {
+                let contexts = position.apply(&case.input.contexts, &case.distractors);
+                let report = self.evaluate_contexts(&case.input, contexts)?;
+
+                let metric_degradation = baseline
+                    .metric_scores
+                    .iter()
+                    .filter_map(|(name, before)| {
+                        report
+                            .metric_scores
+                            .get(name)
+                            .map(|after| (name.clone(), before - after))
+                    })
+                    .collect();
+
+                Ok(PerturbedRun {
+                    position: *position,
+                    degradation: baseline.overall_score - report.overall_score,
+                    metric_degradation,
+                    report,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(CaseRobustness {
+            case_id: case.input.case_id.clone(),
+            baseline,
+            perturbed,
+        })
+    }
+
+    /// Evaluate every case and summarize the score drop
+    pub fn evaluate(&self, cases: &[RobustnessCase]) -> Result<RobustnessReport> {
+        let results = cases
+            .iter()
+            .map(|case| self.evaluate_case(case))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RobustnessReport::from_cases(results, &self.positions))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Metric, MetricResult};
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Scores the answer 1.0 unless it mentions oxide
+    struct NoOxide;
+    impl Metric for NoOxide {
+        fn name(&self) -> &str {
+            "no_oxide"
+        }
+
+        fn evaluate(&self, input: &EvaluationInput) -> Result<MetricResult> {
+            let misled = input.answer.as_deref().unwrap_or("").contains("oxide");
+            Ok(MetricResult {
+                metric_name: self.name().to_string(),
+                score: if misled { 0.0 } else { 1.0 },
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn test_positions() {
+        let contexts = strings(&["c1", "c2", "c3"]);
+        let distractors = strings(&["d1", "d2"]);
+
+        assert_eq!(
+            DistractorPosition::Front.apply(&contexts, &distractors),
+            strings(&["d1", "d2", "c1", "c2", "c3"])
+        );
+        assert_eq!(
+            DistractorPosition::Back.apply(&contexts, &distractors),
+            strings(&["c1", "c2", "c3", "d1", "d2"])
+        );
+        assert_eq!(
+            DistractorPosition::Interleaved.apply(&contexts, &distractors),
+            strings(&["c1", "d1", "c2", "d2", "c3"])
+        );
+
+        let shuffled = DistractorPosition::Shuffled { seed: 3 };
+        let mut once = shuffled.apply(&contexts, &distractors);
+        assert_eq!(once, shuffled.apply(&contexts, &distractors));
+        once.sort();
+        assert_eq!(once, strings(&["c1", "c2", "c3", "d1", "d2"]));
+    }
+
+    #[test]
+    fn test_degradation_with_generator() {
+        let mut evaluator = Evaluator::new();
+        evaluator.add_metric(Box::new(NoOxide));
+
+        // A pipeline that trusts whatever context is ranked first
+        let harness = RobustnessHarness::new(evaluator)
+            .with_generator(|_, contexts| Ok(contexts.first().cloned().unwrap_or_default()));
+
+        let case = RobustnessCase {
+            input: EvaluationInput {
+                query: "What is Rust?".to_string(),
+                contexts: strings(&["Rust is a programming language."]),
+                case_id: Some("rust".to_string()),
+                ..Default::default()
+            },
+            distractors: strings(&["Rust is iron oxide."]),
+        };
+
+        let report = harness.evaluate(&[case]).unwrap();
+        let case = &report.cases[0];
+
+        assert_eq!(case.case_id.as_deref(), Some("rust"));
+        assert_eq!(case.baseline.overall_score, 1.0);
+        assert_eq!(case.perturbed[0].position, DistractorPosition::Front);
+        assert_eq!(case.perturbed[0].degradation, 1.0);
+        assert_eq!(case.perturbed[1].degradation, 0.0);
+        assert_eq!(case.worst_degradation(), 1.0);
+
+        assert_eq!(report.mean_degradation, 0.5);
+        assert_eq!(report.mean_metric_degradation["no_oxide"], 0.5);
+        assert_eq!(
+            report.degradation_by_position,
+            vec![
+                (DistractorPosition::Front, 1.0),
+                (DistractorPosition::Back, 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_generator_answer_is_reused() {
+        let mut evaluator = Evaluator::new();
+        evaluator.add_metric(Box::new(NoOxide));
+        let harness = RobustnessHarness::new(evaluator);
+
+        let case = RobustnessCase {
+            input: EvaluationInput {
+                query: "q".to_string(),
+                answer: Some("Rust is a language.".to_string()),
+                ..Default::default()
+            },
+            distractors: strings(&["iron oxide"]),
+        };
+
+        let report = harness.evaluate(&[case]).unwrap();
+        assert_eq!(report.mean_degradation, 0.0);
+    }
+}
@@ -0,0 +1,252 @@
+//! Score distributions and bootstrap confidence intervals
+//!
+//! A mean alone cannot tell a real 0.02 improvement from noise. The
+//! summaries here add spread (standard deviation, quartiles) and a
+//! percentile-bootstrap confidence interval for the mean. Resampling uses a
+//! small seeded generator, so the same reports and [`BootstrapConfig`] always
+//! produce the same interval, which keeps CI output reproducible.
+
+use serde::{Deserialize, Serialize};
+
+/// Settings for bootstrap confidence intervals
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BootstrapConfig {
+    /// Number of resampled means (0 collapses the interval to the mean)
+    pub resamples: usize,
+
+    /// Confidence level, e.g. 0.95 for a 95% interval
+    pub confidence: f32,
+
+    /// Seed for the resampling generator
+    pub seed: u64,
+}
+
+impl Default for BootstrapConfig {
+    fn default() -> Self {
+        Self {
+            resamples: 1000,
+            confidence: 0.95,
+            seed: 42,
+        }
+    }
+}
+
+/// Spread of one metric's scores across a run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreSummary {
+    /// Number of scores
+    pub count: usize,
+
+    /// Arithmetic mean
+    pub mean: f32,
+
+    /// Sample standard deviation (0.0 for fewer than two scores)
+    pub std_dev: f32,
+
+    /// 50th percentile
+    pub median: f32,
+
+    /// 25th percentile
+    pub p25: f32,
+
+    /// 75th percentile
+    pub p75: f32,
+
+    /// Lower bound of the bootstrap confidence interval for the mean
+    pub ci_lower: f32,
+
+    /// Upper bound of the bootstrap confidence interval for the mean
+    pub ci_upper: f32,
+
+    /// Confidence level of `ci_lower`..`ci_upper`
+    pub confidence: f32,
+}
+
+impl ScoreSummary {
+    /// Summarize `scores`, or `None` if there are none
+    pub fn from_scores(scores: &[f32], config: &BootstrapConfig) -> Option<Self> {
+        if scores.is_empty() {
+            return None;
+        }
+
+        let values: Vec<f64> = scores.iter().map(|&s| s as f64).collect();
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let std_dev = if values.len() < 2 {
+            0.0
+        } else {
+            (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        };
+
+        let mut sorted = values.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        let (ci_lower, ci_upper) = bootstrap_mean_interval(&values, config).unwrap_or((mean, mean));
+
+        Some(Self {
+            count: values.len(),
+            mean: mean as f32,
+            std_dev: std_dev as f32,
+            median: percentile(&sorted, 0.5) as f32,
+            p25: percentile(&sorted, 0.25) as f32,
+            p75: percentile(&sorted, 0.75) as f32,
+            ci_lower: ci_lower as f32,
+            ci_upper: ci_upper as f32,
+            confidence: config.confidence,
+        })
+    }
+}
+
+/// One bar of a score histogram
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramBucket {
+    /// Inclusive lower edge
+    pub lower: f32,
+
+    /// Upper edge (exclusive, except for the last bucket)
+    pub upper: f32,
+
+    /// Number of scores in the bucket
+    pub count: usize,
+}
+
+/// Bucket scores into `n_buckets` equal-width bins over 0.0-1.0
+///
+/// Scores outside that range land in the first or last bucket.
+pub fn histogram(scores: &[f32], n_buckets: usize) -> Vec<HistogramBucket> {
+    if n_buckets == 0 {
+        return Vec::new();
+    }
+
+    let width = 1.0 / n_buckets as f32;
+    let mut buckets: Vec<HistogramBucket> = (0..n_buckets)
+        .map(|i| HistogramBucket {
+            lower: i as f32 * width,
+            upper: if i + 1 == n_buckets {
+                1.0
+            } else {
+                (i + 1) as f32 * width
+            },
+            count: 0,
+        })
+        .collect();
+
+    for &score in scores {
+        let index = ((score * n_buckets as f32).floor().max(0.0) as usize).min(n_buckets - 1);
+        buckets[index].count += 1;
+    }
+
+    buckets
+}
+
+/// Linearly interpolated percentile of sorted values (`q` in 0.0-1.0)
+fn percentile(sorted: &[f64], q: f64) -> f64 {
+    let rank = q * (sorted.len() - 1) as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64)
+}
+
+/// Percentile-bootstrap interval for the mean of `values`
+fn bootstrap_mean_interval(values: &[f64], config: &BootstrapConfig) -> Option<(f64, f64)> {
+    if config.resamples == 0 {
+        return None;
+    }
+
+    let n = values.len();
+    let mut rng = SplitMix64::new(config.seed);
+    let mut means: Vec<f64> = (0..config.resamples)
+        .map(|_| {
+            let total: f64 = (0..n)
+                .map(|_| values[(rng.next_u64() % n as u64) as usize])
+                .sum();
+            total / n as f64
+        })
+        .collect();
+    means.sort_by(|a, b| a.total_cmp(b));
+
+    let alpha = (1.0 - config.confidence.clamp(0.0, 1.0) as f64) / 2.0;
+    Some((percentile(&means, alpha), percentile(&means, 1.0 - alpha)))
+}
+
+/// SplitMix64: tiny, fast and good enough for resampling indices
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_summary() {
+        let scores = [0.2, 0.4, 0.6, 0.8, 1.0];
+        let summary = ScoreSummary::from_scores(&scores, &BootstrapConfig::default()).unwrap();
+
+        assert_eq!(summary.count, 5);
+        assert_relative_eq!(summary.mean, 0.6, epsilon = 1e-6);
+        assert_relative_eq!(summary.median, 0.6, epsilon = 1e-6);
+        assert_relative_eq!(summary.p25, 0.4, epsilon = 1e-6);
+        assert_relative_eq!(summary.p75, 0.8, epsilon = 1e-6);
+        assert_relative_eq!(summary.std_dev, 0.1f32.sqrt(), epsilon = 1e-6);
+
+        assert!(summary.ci_lower < summary.mean && summary.mean < summary.ci_upper);
+        assert!(summary.ci_lower >= 0.2 && summary.ci_upper <= 1.0);
+    }
+
+    #[test]
+    fn test_bootstrap_is_deterministic_per_seed() {
+        let scores: Vec<f32> = (0..50).map(|i| (i % 7) as f32 / 7.0).collect();
+        let config = BootstrapConfig::default();
+
+        let a = ScoreSummary::from_scores(&scores, &config).unwrap();
+        let b = ScoreSummary::from_scores(&scores, &config).unwrap();
+        assert_eq!(a, b);
+
+        let other_seed = BootstrapConfig { seed: 7, ..config };
+        let c = ScoreSummary::from_scores(&scores, &other_seed).unwrap();
+        assert_ne!((a.ci_lower, a.ci_upper), (c.ci_lower, c.ci_upper));
+    }
+
+    #[test]
+    fn test_degenerate_inputs() {
+        assert!(ScoreSummary::from_scores(&[], &BootstrapConfig::default()).is_none());
+
+        let single = ScoreSummary::from_scores(&[0.7], &BootstrapConfig::default()).unwrap();
+        assert_eq!(single.std_dev, 0.0);
+        assert_eq!((single.ci_lower, single.ci_upper), (0.7, 0.7));
+
+        let no_resamples = BootstrapConfig {
+            resamples: 0,
+            ..Default::default()
+        };
+        let summary = ScoreSummary::from_scores(&[0.0, 1.0], &no_resamples).unwrap();
+        assert_eq!((summary.ci_lower, summary.ci_upper), (0.5, 0.5));
+    }
+
+    #[test]
+    fn test_histogram() {
+        let buckets = histogram(&[0.0, 0.1, 0.5, 0.99, 1.0, 1.5, -0.2], 4);
+
+        assert_eq!(buckets.len(), 4);
+        assert_eq!(buckets[0].lower, 0.0);
+        assert_eq!(buckets[3].upper, 1.0);
+        let counts: Vec<usize> = buckets.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![3, 0, 1, 3]);
+
+        assert!(histogram(&[0.5], 0).is_empty());
+    }
+}
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Input data for RAG evaluation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct EvaluationInput {
     /// The user's query/question
     pub query: String,
@@ -17,10 +17,22 @@ pub struct EvaluationInput {
 
     /// Ground truth answer (optional, required for correctness)
     pub ground_truth: Option<String>,
+
+    /// IDs of the retrieved documents, in rank order (required for retrieval metrics)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retrieved_ids: Vec<String>,
+
+    /// IDs of the documents labeled relevant for the query (required for retrieval metrics)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub relevant_ids: Vec<String>,
+
+    /// Stable identifier used to match this case across evaluation runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_id: Option<String>,
 }
 
 /// Result of evaluating a single metric
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct MetricResult {
     /// Name of the metric
     pub metric_name: String,
@@ -29,14 +41,38 @@ pub struct MetricResult {
     pub score: f32,
 
     /// Additional details/explanations
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub details: HashMap<String, serde_json::Value>,
+
+    /// Wall-clock time the metric took, filled in by the evaluator
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+
+    /// Calls and tokens the metric consumed (`prompt_tokens`,
+    /// `completion_tokens`, `llm_calls`, `embed_calls`)
+    ///
+    /// See [`UsageRecorder`](crate::UsageRecorder) for how clients report usage.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub usage: HashMap<String, u64>,
+}
+
+/// A metric that scored below its configured threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdViolation {
+    /// Name of the metric
+    pub metric_name: String,
+
+    /// Score the metric produced
+    pub score: f32,
+
+    /// Minimum score that was required
+    pub threshold: f32,
 }
 
 /// Complete evaluation report
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EvaluationReport {
-    /// Overall score (average of all metrics)
+    /// Overall score (weighted mean of all metrics)
     pub overall_score: f32,
 
     /// Individual metric scores
@@ -47,6 +83,22 @@ pub struct EvaluationReport {
 
     /// Timestamp of evaluation (Unix timestamp)
     pub timestamp: u64,
+
+    /// True when no metric fell below its threshold
+    #[serde(default = "default_passed")]
+    pub passed: bool,
+
+    /// Metrics that fell below their threshold
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<ThresholdViolation>,
+
+    /// `case_id` of the evaluated input, if it had one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_id: Option<String>,
+}
+
+fn default_passed() -> bool {
+    true
 }
 
 /// Trait for evaluation metrics
@@ -0,0 +1,201 @@
+//! Token usage accounting shared between judge clients and the evaluator
+//!
+//! [`TokenUsage`] is a run-wide counter. [`UsageRecorder`] attributes calls
+//! to the metric that made them: the [`Evaluator`](crate::Evaluator) opens a
+//! recording scope around each metric, and LLM/embedder implementations
+//! report into whatever scope is active on the current thread.
+
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Usage key for tokens sent to a model
+pub const PROMPT_TOKENS: &str = "prompt_tokens";
+
+/// Usage key for tokens generated by a model
+pub const COMPLETION_TOKENS: &str = "completion_tokens";
+
+/// Usage key for [`LLM::generate`](crate::LLM::generate) calls
+pub const LLM_CALLS: &str = "llm_calls";
+
+/// Usage key for [`Embedder::embed`](crate::Embedder::embed) calls
+pub const EMBED_CALLS: &str = "embed_calls";
+
+/// Thread-safe token counter
+///
+/// Share one `Arc<TokenUsage>` between LLM/embedder clients and the
+/// [`Evaluator`](crate::Evaluator) to see what a run cost.
+#[derive(Debug, Default)]
+pub struct TokenUsage {
+    prompt_tokens: AtomicU64,
+    completion_tokens: AtomicU64,
+    requests: AtomicU64,
+}
+
+/// Point-in-time copy of a [`TokenUsage`] counter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageSnapshot {
+    /// Tokens sent to the model
+    pub prompt_tokens: u64,
+
+    /// Tokens generated by the model
+    pub completion_tokens: u64,
+
+    /// Number of successful API requests
+    pub requests: u64,
+}
+
+impl UsageSnapshot {
+    /// Prompt plus completion tokens
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+impl TokenUsage {
+    /// Create a zeroed counter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record one request and its token counts
+    pub fn record(&self, prompt_tokens: u64, completion_tokens: u64) {
+        self.prompt_tokens.fetch_add(prompt_tokens, Ordering::Relaxed);
+        self.completion_tokens
+            .fetch_add(completion_tokens, Ordering::Relaxed);
+        self.requests.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Read the current totals
+    pub fn snapshot(&self) -> UsageSnapshot {
+        UsageSnapshot {
+            prompt_tokens: self.prompt_tokens.load(Ordering::Relaxed),
+            completion_tokens: self.completion_tokens.load(Ordering::Relaxed),
+            requests: self.requests.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reset all counters to zero
+    pub fn reset(&self) {
+        self.prompt_tokens.store(0, Ordering::Relaxed);
+        self.completion_tokens.store(0, Ordering::Relaxed);
+        self.requests.store(0, Ordering::Relaxed);
+    }
+}
+
+thread_local! {
+    static ACTIVE: RefCell<Option<HashMap<String, u64>>> = const { RefCell::new(None) };
+}
+
+/// Per-thread usage attribution for metric results
+///
+/// Outside a [`capture`](Self::capture) scope the `record_*` functions are
+/// no-ops, so clients can call them unconditionally.
+///
+/// Usage is tracked per thread: calls made from threads a metric spawns
+/// itself are not attributed to it.
+///
+/// # Example
+///
+/// ```
+/// use vecstore_eval::UsageRecorder;
+///
+/// struct CountingLLM;
+///
+/// impl vecstore_eval::LLM for CountingLLM {
+///     fn generate(&self, prompt: &str) -> anyhow::Result<String> {
+///         UsageRecorder::record_llm_call(prompt.len() as u64 / 4, 1);
+///         Ok("Yes".to_string())
+///     }
+/// }
+///
+/// let (_, usage) = UsageRecorder::capture(|| {
+///     use vecstore_eval::LLM;
+///     CountingLLM.generate("twelve chars")
+/// });
+/// assert_eq!(usage["llm_calls"], 1);
+/// assert_eq!(usage["prompt_tokens"], 3);
+/// ```
+pub struct UsageRecorder;
+
+impl UsageRecorder {
+    /// Run `f` and return the usage it recorded on this thread
+    ///
+    /// Scopes nest: usage recorded in an inner scope also counts toward the
+    /// enclosing one.
+    pub fn capture<T>(f: impl FnOnce() -> T) -> (T, HashMap<String, u64>) {
+        let outer = ACTIVE.with(|active| active.borrow_mut().replace(HashMap::new()));
+        let value = f();
+        let captured = ACTIVE
+            .with(|active| std::mem::replace(&mut *active.borrow_mut(), outer))
+            .unwrap_or_default();
+
+        if !captured.is_empty() {
+            ACTIVE.with(|active| {
+                if let Some(outer) = active.borrow_mut().as_mut() {
+                    merge_usage(outer, &captured);
+                }
+            });
+        }
+
+        (value, captured)
+    }
+
+    /// Add `amount` to `key` in the active scope
+    pub fn record(key: &str, amount: u64) {
+        ACTIVE.with(|active| {
+            if let Some(usage) = active.borrow_mut().as_mut() {
+                *usage.entry(key.to_string()).or_insert(0) += amount;
+            }
+        });
+    }
+
+    /// Record one LLM call and its token counts
+    pub fn record_llm_call(prompt_tokens: u64, completion_tokens: u64) {
+        Self::record(LLM_CALLS, 1);
+        Self::record(PROMPT_TOKENS, prompt_tokens);
+        Self::record(COMPLETION_TOKENS, completion_tokens);
+    }
+
+    /// Record one embedding call and the tokens it consumed
+    pub fn record_embed_call(prompt_tokens: u64) {
+        Self::record(EMBED_CALLS, 1);
+        Self::record(PROMPT_TOKENS, prompt_tokens);
+    }
+}
+
+/// Add every counter in `other` to `into`
+pub(crate) fn merge_usage(into: &mut HashMap<String, u64>, other: &HashMap<String, u64>) {
+    for (key, amount) in other {
+        *into.entry(key.clone()).or_insert(0) += amount;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_outside_scope_is_noop() {
+        UsageRecorder::record_llm_call(10, 2);
+        let (_, usage) = UsageRecorder::capture(|| ());
+        assert!(usage.is_empty());
+    }
+
+    #[test]
+    fn test_nested_scopes_roll_up() {
+        let ((_, inner), outer) = UsageRecorder::capture(|| {
+            UsageRecorder::record_embed_call(4);
+            UsageRecorder::capture(|| UsageRecorder::record_llm_call(10, 2))
+        });
+
+        assert_eq!(inner[LLM_CALLS], 1);
+        assert!(!inner.contains_key(EMBED_CALLS));
+
+        assert_eq!(outer[LLM_CALLS], 1);
+        assert_eq!(outer[EMBED_CALLS], 1);
+        assert_eq!(outer[PROMPT_TOKENS], 14);
+        assert_eq!(outer[COMPLETION_TOKENS], 2);
+    }
+}
@@ -0,0 +1,532 @@
+//! iCalendar (.ics) document loader
+//!
+//! Parses RFC 5545 calendar files and produces one document per `VEVENT`.
+
+use crate::{Document, DocumentLoader, LoaderError, LoaderOptions, Result};
+use std::fs;
+use std::path::Path;
+
+/// Loader for iCalendar (.ics) files
+///
+/// Each `VEVENT` becomes its own [`Document`] whose content is the event's
+/// SUMMARY, DESCRIPTION and LOCATION. Start/end times, organizer, attendee
+/// count and recurrence rule are recorded as metadata. Recurring events are
+/// loaded once with their `RRULE` rather than expanded.
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_loaders::IcsLoader;
+///
+/// let loader = IcsLoader::new().with_date_range("2024-01-01", "2024-12-31");
+/// let events = loader.load_events("calendar.ics")?;
+/// println!("Loaded {} events", events.len());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct IcsLoader {
+    /// Inclusive lower bound on event dates, normalized to `YYYYMMDD`
+    range_start: Option<String>,
+
+    /// Inclusive upper bound on event dates, normalized to `YYYYMMDD`
+    range_end: Option<String>,
+}
+
+/// A single parsed `VEVENT`
+#[derive(Debug, Default)]
+struct IcsEvent {
+    uid: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    dtstart: Option<String>,
+    dtend: Option<String>,
+    organizer: Option<String>,
+    rrule: Option<String>,
+    attendees: usize,
+}
+
+impl IcsLoader {
+    /// Create a new ICS loader that keeps every event
+    pub fn new() -> Self {
+        Self {
+            range_start: None,
+            range_end: None,
+        }
+    }
+
+    /// Only keep events that fall within `[start, end]`
+    ///
+    /// Dates may be given as `YYYY-MM-DD` or `YYYYMMDD`. Recurring events are
+    /// kept when their series overlaps the range (start before `end` and no
+    /// `UNTIL` earlier than `start`).
+    pub fn with_date_range(mut self, start: &str, end: &str) -> Self {
+        self.range_start = Some(date_part(start));
+        self.range_end = Some(date_part(end));
+        self
+    }
+
+    /// Load every event in the calendar as a separate document
+    pub fn load_events(&self, source: &str) -> Result<Vec<Document>> {
+        let path = Path::new(source);
+
+        if !path.exists() {
+            return Err(LoaderError::InvalidPath(format!(
+                "File not found: {}",
+                source
+            )));
+        }
+
+        let raw = fs::read_to_string(path)?;
+        self.parse_events(&raw, source)
+    }
+
+    /// Parse calendar text into one document per event
+    pub fn parse_events(&self, ics: &str, source: &str) -> Result<Vec<Document>> {
+        let lines = Self::unfold_lines(ics);
+
+        if !lines
+            .iter()
+            .any(|l| l.eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+        {
+            return Err(LoaderError::ParseError(
+                "Missing BEGIN:VCALENDAR".to_string(),
+            ));
+        }
+
+        let mut documents = Vec::new();
+        let mut current: Option<IcsEvent> = None;
+        // Depth of components nested inside the current VEVENT (e.g. VALARM)
+        let mut nested = 0usize;
+
+        for line in &lines {
+            let Some((name, params, value)) = split_content_line(line) else {
+                continue;
+            };
+
+            match name.as_str() {
+                "BEGIN" if value.eq_ignore_ascii_case("VEVENT") && current.is_none() => {
+                    current = Some(IcsEvent::default());
+                    nested = 0;
+                }
+                "BEGIN" if current.is_some() => nested += 1,
+                "END" if value.eq_ignore_ascii_case("VEVENT") && nested == 0 => {
+                    if let Some(event) = current.take() {
+                        if self.in_range(&event) {
+                            documents.push(event.into_document(source, documents.len()));
+                        }
+                    }
+                }
+                "END" if current.is_some() => nested = nested.saturating_sub(1),
+                _ => {
+                    if nested > 0 {
+                        continue;
+                    }
+                    if let Some(event) = current.as_mut() {
+                        event.apply(&name, params, value);
+                    }
+                }
+            }
+        }
+
+        if current.is_some() {
+            return Err(LoaderError::ParseError(
+                "Unterminated VEVENT (missing END:VEVENT)".to_string(),
+            ));
+        }
+
+        Ok(documents)
+    }
+
+    /// Undo RFC 5545 line folding: a line starting with a space or tab
+    /// continues the previous line, with that single whitespace removed.
+    pub fn unfold_lines(ics: &str) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+
+        for raw in ics.split('\n') {
+            let line = raw.strip_suffix('\r').unwrap_or(raw);
+
+            if let Some(rest) = line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')) {
+                if let Some(last) = lines.last_mut() {
+                    last.push_str(rest);
+                    continue;
+                }
+            }
+
+            if !line.is_empty() {
+                lines.push(line.to_string());
+            }
+        }
+
+        lines
+    }
+
+    /// Decode TEXT value escapes (`\\`, `\;`, `\,`, `\n`, `\N`)
+    pub fn unescape_text(value: &str) -> String {
+        let mut result = String::with_capacity(value.len());
+        let mut chars = value.chars();
+
+        while let Some(ch) = chars.next() {
+            if ch != '\\' {
+                result.push(ch);
+                continue;
+            }
+
+            match chars.next() {
+                Some('n') | Some('N') => result.push('\n'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        }
+
+        result
+    }
+
+    fn in_range(&self, event: &IcsEvent) -> bool {
+        if self.range_start.is_none() && self.range_end.is_none() {
+            return true;
+        }
+
+        // Events without a start date cannot be placed; drop them when filtering
+        let Some(start) = event.dtstart.as_deref().map(date_part) else {
+            return false;
+        };
+
+        if let Some(ref end_bound) = self.range_end {
+            if start.as_str() > end_bound.as_str() {
+                return false;
+            }
+        }
+
+        if let Some(ref start_bound) = self.range_start {
+            let last = match event.rrule {
+                // Open-ended series overlap any range that starts after them
+                Some(ref rule) => match rrule_until(rule) {
+                    Some(until) => until,
+                    None => return true,
+                },
+                None => event
+                    .dtend
+                    .as_deref()
+                    .map(date_part)
+                    .unwrap_or_else(|| start.clone()),
+            };
+
+            if last.as_str() < start_bound.as_str() {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl Default for IcsLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IcsEvent {
+    fn apply(&mut self, name: &str, params: &str, value: &str) {
+        match name {
+            "UID" => self.uid = Some(value.to_string()),
+            "SUMMARY" => self.summary = Some(IcsLoader::unescape_text(value)),
+            "DESCRIPTION" => self.description = Some(IcsLoader::unescape_text(value)),
+            "LOCATION" => self.location = Some(IcsLoader::unescape_text(value)),
+            "DTSTART" => self.dtstart = Some(value.to_string()),
+            "DTEND" => self.dtend = Some(value.to_string()),
+            "RRULE" => self.rrule = Some(value.to_string()),
+            "ORGANIZER" => {
+                // Prefer the common name, fall back to the calendar address
+                let organizer =
+                    param_value(params, "CN").unwrap_or_else(|| strip_mailto(value).to_string());
+                self.organizer = Some(organizer);
+            }
+            "ATTENDEE" => self.attendees += 1,
+            _ => {}
+        }
+    }
+
+    fn into_document(self, source: &str, index: usize) -> Document {
+        let content = [&self.summary, &self.description, &self.location]
+            .iter()
+            .filter_map(|field| field.as_deref())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let mut document = Document::new(content, source.to_string());
+        document.add_metadata("format", "ics");
+        document.add_metadata("loader", "IcsLoader");
+        document.add_metadata("event_index", index.to_string());
+        document.add_metadata("attendee_count", self.attendees.to_string());
+
+        let optional = [
+            ("uid", self.uid),
+            ("title", self.summary),
+            ("dtstart", self.dtstart),
+            ("dtend", self.dtend),
+            ("organizer", self.organizer),
+            ("rrule", self.rrule),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                document.add_metadata(key, value);
+            }
+        }
+
+        if document.metadata.contains_key("rrule") {
+            document.add_metadata("recurring", "true");
+        }
+
+        document
+    }
+}
+
+impl DocumentLoader for IcsLoader {
+    /// Load the whole calendar as a single document (events separated by
+    /// blank lines). Use [`IcsLoader::load_events`] for one document per event.
+    fn load(&self, source: &str) -> Result<Document> {
+        let events = self.load_events(source)?;
+
+        let content = events
+            .iter()
+            .map(|event| event.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n---\n\n");
+
+        let mut document = Document::new(content, source.to_string());
+        document.add_metadata("format", "ics");
+        document.add_metadata("loader", "IcsLoader");
+        document.add_metadata("event_count", events.len().to_string());
+
+        Ok(document)
+    }
+
+    fn load_with_options(&self, source: &str, options: &LoaderOptions) -> Result<Document> {
+        if let Some(max_size) = options.max_size {
+            let file_size = fs::metadata(source)?.len() as usize;
+            if file_size > max_size {
+                return Err(LoaderError::FileTooLarge(file_size, max_size));
+            }
+        }
+
+        self.load(source)
+    }
+
+    fn name(&self) -> &str {
+        "IcsLoader"
+    }
+
+    fn supported_extensions(&self) -> &[&str] {
+        &["ics", "ical", "ifb", "icalendar"]
+    }
+}
+
+/// Split a content line into (uppercased name, raw params, value).
+///
+/// The name ends at the first `;` or `:`; the value starts after the first
+/// `:` that is not inside a quoted parameter value.
+fn split_content_line(line: &str) -> Option<(String, &str, &str)> {
+    let mut in_quotes = false;
+    let mut name_end = None;
+
+    for (i, ch) in line.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes && name_end.is_none() => name_end = Some(i),
+            ':' if !in_quotes => {
+                let name_end = name_end.unwrap_or(i);
+                let name = line[..name_end].trim().to_ascii_uppercase();
+                let params = if name_end < i {
+                    &line[name_end + 1..i]
+                } else {
+                    ""
+                };
+                return Some((name, params, &line[i + 1..]));
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Look up a parameter (e.g. `CN`) in a `;`-separated parameter list
+fn param_value(params: &str, key: &str) -> Option<String> {
+    params.split(';').find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        if name.trim().eq_ignore_ascii_case(key) {
+            Some(value.trim().trim_matches('"').to_string())
+        } else {
+            None
+        }
+    })
+}
+
+fn strip_mailto(value: &str) -> &str {
+    if value
+        .get(..7)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("mailto:"))
+    {
+        &value[7..]
+    } else {
+        value
+    }
+}
+
+/// Extract `YYYYMMDD` from a DATE or DATE-TIME value
+fn date_part(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_digit())
+        .take(8)
+        .collect()
+}
+
+/// Extract the `UNTIL` date from an RRULE, if any
+fn rrule_until(rule: &str) -> Option<String> {
+    rule.split(';').find_map(|part| {
+        let (key, value) = part.split_once('=')?;
+        if key.eq_ignore_ascii_case("UNTIL") {
+            Some(date_part(value))
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\n\
+VERSION:2.0\r\n\
+BEGIN:VEVENT\r\n\
+UID:1@example.com\r\n\
+DTSTART:20240115T090000Z\r\n\
+DTEND:20240115T100000Z\r\n\
+SUMMARY:Quarterly planning\\, Q1\r\n\
+DESCRIPTION:Agenda:\\n1. Budget\\n2. Hiring and a very long line that is \r\n folded across two lines\r\n\
+LOCATION:Room 4\\; Building B\r\n\
+ORGANIZER;CN=\"Alice: PM\":mailto:alice@example.com\r\n\
+ATTENDEE:mailto:bob@example.com\r\n\
+ATTENDEE:mailto:carol@example.com\r\n\
+BEGIN:VALARM\r\n\
+DESCRIPTION:Reminder\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:2@example.com\r\n\
+DTSTART;VALUE=DATE:20100301\r\n\
+SUMMARY:Weekly standup\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+UID:3@example.com\r\n\
+DTSTART:19990101T120000\r\n\
+SUMMARY:Old party\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn test_one_document_per_event() {
+        let docs = IcsLoader::new().parse_events(CALENDAR, "cal.ics").unwrap();
+        assert_eq!(docs.len(), 3);
+
+        let first = &docs[0];
+        assert!(first.content.contains("Quarterly planning, Q1"));
+        assert!(first
+            .content
+            .contains("Hiring and a very long line that is folded"));
+        assert!(first.content.contains("Room 4; Building B"));
+        assert!(!first.content.contains("Reminder"));
+        assert_eq!(first.metadata.get("dtstart").unwrap(), "20240115T090000Z");
+        assert_eq!(first.metadata.get("dtend").unwrap(), "20240115T100000Z");
+        assert_eq!(first.metadata.get("organizer").unwrap(), "Alice: PM");
+        assert_eq!(first.metadata.get("attendee_count").unwrap(), "2");
+    }
+
+    #[test]
+    fn test_recurring_event_not_expanded() {
+        let docs = IcsLoader::new().parse_events(CALENDAR, "cal.ics").unwrap();
+        let standup = &docs[1];
+
+        assert_eq!(
+            standup.metadata.get("rrule").unwrap(),
+            "FREQ=WEEKLY;BYDAY=MO"
+        );
+        assert_eq!(standup.metadata.get("recurring").unwrap(), "true");
+        assert_eq!(
+            docs.iter()
+                .filter(|d| d.content == "Weekly standup")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_date_range_filter() {
+        let docs = IcsLoader::new()
+            .with_date_range("2024-01-01", "2024-12-31")
+            .parse_events(CALENDAR, "cal.ics")
+            .unwrap();
+
+        // The 2024 meeting and the open-ended weekly series, but not 1999
+        let titles: Vec<_> = docs.iter().map(|d| d.metadata["title"].as_str()).collect();
+        assert_eq!(titles, vec!["Quarterly planning, Q1", "Weekly standup"]);
+    }
+
+    #[test]
+    fn test_unescape_text() {
+        assert_eq!(IcsLoader::unescape_text(r"a\,b\;c\\d\Ne"), "a,b;c\\d\ne");
+    }
+
+    #[test]
+    fn test_load_events_from_file() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "{}", CALENDAR).unwrap();
+
+        let loader = IcsLoader::new();
+        let path = temp_file.path().to_str().unwrap();
+
+        assert_eq!(loader.load_events(path).unwrap().len(), 3);
+
+        let document = loader.load(path).unwrap();
+        assert_eq!(document.metadata.get("event_count").unwrap(), "3");
+    }
+
+    #[test]
+    fn test_non_ascii_organizer_and_attendees() {
+        // Byte 7 of "José Ñúñez" falls inside the 'Ñ'
+        let calendar = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Revisión\r\n\
+ORGANIZER:José Ñúñez\r\n\
+ATTENDEE;CN=Jürgen Müller:MAILTO:jurgen@example.com\r\n\
+ATTENDEE:Zoë\r\n\
+END:VEVENT\r\n\
+BEGIN:VEVENT\r\n\
+SUMMARY:Réunion\r\n\
+ORGANIZER:MAILTO:andré@example.com\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+        let docs = IcsLoader::new().parse_events(calendar, "cal.ics").unwrap();
+        assert_eq!(docs[0].metadata.get("organizer").unwrap(), "José Ñúñez");
+        assert_eq!(docs[0].metadata.get("attendee_count").unwrap(), "2");
+        assert_eq!(
+            docs[1].metadata.get("organizer").unwrap(),
+            "andré@example.com"
+        );
+    }
+
+    #[test]
+    fn test_missing_calendar_header() {
+        let result = IcsLoader::new().parse_events("BEGIN:VEVENT\nEND:VEVENT\n", "x.ics");
+        assert!(matches!(result, Err(LoaderError::ParseError(_))));
+    }
+}
@@ -9,6 +9,9 @@
 //! - Web pages
 //! - JSON/CSV data
 //! - Source code (syntax-aware)
+//! - iCalendar (.ics) events
+//! - Application logs (timestamp-aware chunking)
+//! - OpenAPI/Swagger specs (one document per operation)
 //!
 //! ## Philosophy
 //!
@@ -38,6 +41,9 @@
 //! - `json` - JSON loader (enabled by default)
 //! - `csv` - CSV loader (enabled by default)
 //! - `code` - Syntax-aware code loader with tree-sitter
+//! - `ics` - iCalendar loader (one document per event)
+//! - `logs` - Log loader with timestamp chunking and gzip support
+//! - `openapi` - OpenAPI/Swagger loader with local `$ref` resolution
 //! - `all` - Enable all loaders
 
 use std::collections::HashMap;
@@ -96,6 +102,21 @@ mod epub_loader;
 #[cfg(feature = "epub")]
 pub use epub_loader::EpubLoader;
 
+#[cfg(feature = "ics")]
+mod ics_loader;
+#[cfg(feature = "ics")]
+pub use ics_loader::IcsLoader;
+
+#[cfg(feature = "logs")]
+mod log_loader;
+#[cfg(feature = "logs")]
+pub use log_loader::{LogLoader, TimestampPattern};
+
+#[cfg(feature = "openapi")]
+mod openapi_loader;
+#[cfg(feature = "openapi")]
+pub use openapi_loader::OpenApiLoader;
+
 // Extended loaders
 #[cfg(feature = "extended")]
 mod extended_loaders;
@@ -0,0 +1,566 @@
+//! Log file loader with timestamp-aware chunking
+//!
+//! Streams application logs line by line and groups entries into documents
+//! by time window and/or line count, keeping multi-line entries (stack
+//! traces, wrapped messages) attached to the timestamped line they belong to.
+
+use crate::{Document, DocumentLoader, LoaderError, LoaderOptions, Result};
+use flate2::read::MultiGzDecoder;
+use regex::{Captures, Regex};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::time::Duration;
+
+/// Gzip magic bytes
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// A regex that recognizes a timestamp at the start of a log line
+///
+/// Patterns are anchored by the caller's regex (use `^`). The following named
+/// groups are understood:
+///
+/// - `ts` - the timestamp text recorded in metadata (defaults to the whole match)
+/// - `year`, `month`, `day`, `hour`, `minute`, `second` - used to place the
+///   line in time for window-based grouping. `month` may be numeric or a
+///   three-letter English abbreviation. A missing `year` is treated as 1970,
+///   which is fine for windowing but not for absolute dates.
+///
+/// Patterns without the date/time groups still split entries, but only
+/// `max_lines` grouping applies to them.
+#[derive(Debug, Clone)]
+pub struct TimestampPattern {
+    /// Human-readable name (e.g. "iso8601")
+    pub name: String,
+
+    /// Compiled regex
+    pub regex: Regex,
+}
+
+impl TimestampPattern {
+    /// Compile a custom timestamp pattern
+    pub fn new(name: impl Into<String>, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(pattern)
+            .map_err(|e| LoaderError::ParseError(format!("Invalid timestamp regex: {}", e)))?;
+        Ok(Self {
+            name: name.into(),
+            regex,
+        })
+    }
+
+    /// `2024-01-15T10:00:00Z`, `2024-01-15 10:00:00,123`, `[2024-01-15 10:00:00]`
+    pub fn iso8601() -> Self {
+        Self::new(
+            "iso8601",
+            r"^\[?(?P<ts>(?P<year>\d{4})-(?P<month>\d{2})-(?P<day>\d{2})[T ](?P<hour>\d{2}):(?P<minute>\d{2}):(?P<second>\d{2})(?:[.,]\d+)?(?:Z|[+-]\d{2}:?\d{2})?)",
+        )
+        .expect("built-in pattern is valid")
+    }
+
+    /// `Jan 15 10:00:00` (BSD syslog, no year)
+    pub fn syslog() -> Self {
+        Self::new(
+            "syslog",
+            r"^(?P<ts>(?P<month>Jan|Feb|Mar|Apr|May|Jun|Jul|Aug|Sep|Oct|Nov|Dec) +(?P<day>\d{1,2}) (?P<hour>\d{2}):(?P<minute>\d{2}):(?P<second>\d{2}))",
+        )
+        .expect("built-in pattern is valid")
+    }
+
+    /// nginx/Apache combined log format: `127.0.0.1 - - [10/Oct/2000:13:55:36 -0700] ...`
+    pub fn nginx() -> Self {
+        Self::new(
+            "nginx",
+            r"^\S+ \S+ \S+ \[(?P<ts>(?P<day>\d{2})/(?P<month>[A-Za-z]{3})/(?P<year>\d{4}):(?P<hour>\d{2}):(?P<minute>\d{2}):(?P<second>\d{2})(?: [+-]\d{4})?)\]",
+        )
+        .expect("built-in pattern is valid")
+    }
+
+    /// All built-in patterns, in the order they are tried
+    pub fn builtins() -> Vec<Self> {
+        vec![Self::iso8601(), Self::syslog(), Self::nginx()]
+    }
+
+    /// Match a line, returning the timestamp text and (if parseable) seconds
+    fn detect(&self, line: &str) -> Option<(String, Option<i64>)> {
+        let caps = self.regex.captures(line)?;
+        let text = caps
+            .name("ts")
+            .or_else(|| caps.get(0))
+            .map(|m| m.as_str().to_string())
+            .unwrap_or_default();
+        Some((text, seconds_from_captures(&caps)))
+    }
+}
+
+/// Loader for application log files
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use vecstore_loaders::LogLoader;
+///
+/// let loader = LogLoader::new()
+///     .with_window(Duration::from_secs(300))
+///     .with_max_lines(500);
+///
+/// for chunk in loader.load_chunks("app.log.gz")? {
+///     println!("{} .. {}", chunk.metadata["start_ts"], chunk.metadata["end_ts"]);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct LogLoader {
+    /// Timestamp patterns, tried in order
+    patterns: Vec<TimestampPattern>,
+
+    /// Maximum time span covered by one chunk (None = unlimited)
+    window: Option<Duration>,
+
+    /// Maximum lines per chunk (None = unlimited). Entries are never split,
+    /// so a single long stack trace may exceed this.
+    max_lines: Option<usize>,
+}
+
+impl LogLoader {
+    /// Create a log loader with the built-in patterns, 5 minute windows and
+    /// at most 1000 lines per chunk
+    pub fn new() -> Self {
+        Self {
+            patterns: TimestampPattern::builtins(),
+            window: Some(Duration::from_secs(300)),
+            max_lines: Some(1000),
+        }
+    }
+
+    /// Replace the timestamp patterns
+    pub fn with_patterns(mut self, patterns: Vec<TimestampPattern>) -> Self {
+        self.patterns = patterns;
+        self
+    }
+
+    /// Add a timestamp pattern, tried before the existing ones
+    pub fn with_pattern(mut self, pattern: TimestampPattern) -> Self {
+        self.patterns.insert(0, pattern);
+        self
+    }
+
+    /// Set the time window per chunk
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    /// Disable time-window grouping
+    pub fn without_window(mut self) -> Self {
+        self.window = None;
+        self
+    }
+
+    /// Set the maximum number of lines per chunk
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines.max(1));
+        self
+    }
+
+    /// Disable line-count grouping
+    pub fn without_max_lines(mut self) -> Self {
+        self.max_lines = None;
+        self
+    }
+
+    /// Load the log as a list of chunk documents
+    pub fn load_chunks(&self, source: &str) -> Result<Vec<Document>> {
+        let mut documents = Vec::new();
+        self.for_each_chunk(source, |doc| {
+            documents.push(doc);
+            Ok(())
+        })?;
+        Ok(documents)
+    }
+
+    /// Stream the log, invoking `f` for each chunk as soon as it is complete
+    ///
+    /// Only one chunk is held in memory at a time, so this is the method to
+    /// use for multi-gigabyte logs. Gzip input is detected by magic bytes.
+    pub fn for_each_chunk<F>(&self, source: &str, f: F) -> Result<()>
+    where
+        F: FnMut(Document) -> Result<()>,
+    {
+        let path = Path::new(source);
+
+        if !path.exists() {
+            return Err(LoaderError::InvalidPath(format!(
+                "File not found: {}",
+                source
+            )));
+        }
+
+        let mut reader = BufReader::new(File::open(path)?);
+        let is_gzip = reader.fill_buf()?.starts_with(&GZIP_MAGIC);
+
+        if is_gzip {
+            let decoder = BufReader::new(MultiGzDecoder::new(reader));
+            self.chunk_reader(decoder, source, f)
+        } else {
+            self.chunk_reader(reader, source, f)
+        }
+    }
+
+    /// Chunk any buffered reader of log lines
+    pub fn chunk_reader<R, F>(&self, mut reader: R, source: &str, mut f: F) -> Result<()>
+    where
+        R: BufRead,
+        F: FnMut(Document) -> Result<()>,
+    {
+        let mut chunk = Chunk::default();
+        let mut chunk_index = 0usize;
+        let mut line_no = 0usize;
+        let mut buf = Vec::new();
+        // Index of the pattern that matched most recently; tried first
+        let mut last_pattern = 0usize;
+
+        loop {
+            buf.clear();
+            if reader.read_until(b'\n', &mut buf)? == 0 {
+                break;
+            }
+            line_no += 1;
+
+            let line = String::from_utf8_lossy(&buf);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            match self.detect(line, &mut last_pattern) {
+                Some((ts_text, seconds)) => {
+                    if self.starts_new_chunk(&chunk, seconds) {
+                        f(chunk.take(source, chunk_index))?;
+                        chunk_index += 1;
+                    }
+                    chunk.push_entry(line, line_no, ts_text, seconds);
+                }
+                // Continuation lines stay with the entry above them
+                None => chunk.push_line(line, line_no),
+            }
+        }
+
+        if !chunk.is_empty() {
+            f(chunk.take(source, chunk_index))?;
+        }
+
+        Ok(())
+    }
+
+    fn detect(&self, line: &str, last_pattern: &mut usize) -> Option<(String, Option<i64>)> {
+        if let Some(found) = self
+            .patterns
+            .get(*last_pattern)
+            .and_then(|p| p.detect(line))
+        {
+            return Some(found);
+        }
+
+        for (i, pattern) in self.patterns.iter().enumerate() {
+            if i == *last_pattern {
+                continue;
+            }
+            if let Some(found) = pattern.detect(line) {
+                *last_pattern = i;
+                return Some(found);
+            }
+        }
+
+        None
+    }
+
+    /// Decide whether an entry starting at `seconds` belongs in a new chunk
+    fn starts_new_chunk(&self, chunk: &Chunk, seconds: Option<i64>) -> bool {
+        if chunk.entries == 0 {
+            return false;
+        }
+
+        if let Some(max_lines) = self.max_lines {
+            if chunk.lines.len() >= max_lines {
+                return true;
+            }
+        }
+
+        if let (Some(window), Some(start), Some(now)) = (self.window, chunk.start_seconds, seconds)
+        {
+            let elapsed = now - start;
+            // Going backwards (e.g. syslog year rollover) also closes the chunk
+            if elapsed < 0 || elapsed as u64 >= window.as_secs().max(1) {
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl Default for LogLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocumentLoader for LogLoader {
+    /// Load the whole log as one document. Prefer [`LogLoader::load_chunks`]
+    /// or [`LogLoader::for_each_chunk`] for anything but small files.
+    fn load(&self, source: &str) -> Result<Document> {
+        let chunks = self.load_chunks(source)?;
+
+        let content = chunks
+            .iter()
+            .map(|chunk| chunk.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut document = Document::new(content, source.to_string());
+        document.add_metadata("format", "log");
+        document.add_metadata("loader", "LogLoader");
+        document.add_metadata("chunk_count", chunks.len().to_string());
+
+        if let Some(first) = chunks.first().and_then(|c| c.metadata.get("start_ts")) {
+            document.add_metadata("start_ts", first.clone());
+        }
+        if let Some(last) = chunks.last().and_then(|c| c.metadata.get("end_ts")) {
+            document.add_metadata("end_ts", last.clone());
+        }
+
+        Ok(document)
+    }
+
+    fn load_with_options(&self, source: &str, options: &LoaderOptions) -> Result<Document> {
+        if let Some(max_size) = options.max_size {
+            let file_size = fs::metadata(source)?.len() as usize;
+            if file_size > max_size {
+                return Err(LoaderError::FileTooLarge(file_size, max_size));
+            }
+        }
+
+        self.load(source)
+    }
+
+    fn name(&self) -> &str {
+        "LogLoader"
+    }
+
+    fn supported_extensions(&self) -> &[&str] {
+        &["log", "gz"]
+    }
+}
+
+/// Lines accumulated for the chunk currently being built
+#[derive(Default)]
+struct Chunk {
+    lines: Vec<String>,
+    first_line: usize,
+    last_line: usize,
+    entries: usize,
+    start_ts: Option<String>,
+    end_ts: Option<String>,
+    start_seconds: Option<i64>,
+}
+
+impl Chunk {
+    fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    fn push_line(&mut self, line: &str, line_no: usize) {
+        if self.lines.is_empty() {
+            self.first_line = line_no;
+        }
+        self.lines.push(line.to_string());
+        self.last_line = line_no;
+    }
+
+    fn push_entry(&mut self, line: &str, line_no: usize, ts: String, seconds: Option<i64>) {
+        if self.start_ts.is_none() {
+            self.start_ts = Some(ts.clone());
+        }
+        if self.start_seconds.is_none() {
+            self.start_seconds = seconds;
+        }
+        self.end_ts = Some(ts);
+        self.entries += 1;
+        self.push_line(line, line_no);
+    }
+
+    fn take(&mut self, source: &str, index: usize) -> Document {
+        let chunk = std::mem::take(self);
+
+        let mut document = Document::new(chunk.lines.join("\n"), source.to_string());
+        document.add_metadata("format", "log");
+        document.add_metadata("loader", "LogLoader");
+        document.add_metadata("chunk_index", index.to_string());
+        document.add_metadata(
+            "line_range",
+            format!("{}-{}", chunk.first_line, chunk.last_line),
+        );
+        document.add_metadata("line_count", chunk.lines.len().to_string());
+        document.add_metadata("entry_count", chunk.entries.to_string());
+
+        if let Some(start_ts) = chunk.start_ts {
+            document.add_metadata("start_ts", start_ts);
+        }
+        if let Some(end_ts) = chunk.end_ts {
+            document.add_metadata("end_ts", end_ts);
+        }
+
+        document
+    }
+}
+
+/// Convert date/time capture groups to seconds since the Unix epoch
+fn seconds_from_captures(caps: &Captures) -> Option<i64> {
+    let num = |name: &str| caps.name(name).and_then(|m| m.as_str().parse::<i64>().ok());
+
+    let month = caps.name("month").and_then(|m| {
+        let text = m.as_str();
+        text.parse::<i64>().ok().or_else(|| month_from_name(text))
+    })?;
+    let day = num("day")?;
+    let hour = num("hour")?;
+    let minute = num("minute")?;
+    let second = num("second")?;
+    let year = num("year").unwrap_or(1970);
+
+    Some(days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+fn month_from_name(name: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let lower = name.to_ascii_lowercase();
+    MONTHS
+        .iter()
+        .position(|m| *m == lower)
+        .map(|i| i as i64 + 1)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const LOG: &str = "\
+2024-01-15T10:00:00Z INFO starting
+2024-01-15T10:01:00Z ERROR request failed
+java.lang.RuntimeException: boom
+    at com.example.Foo.bar(Foo.java:10)
+    at com.example.Main.main(Main.java:3)
+2024-01-15T10:04:59Z INFO recovered
+2024-01-15T10:05:00Z INFO next window
+2024-01-15T10:20:00Z INFO much later
+";
+
+    fn chunks(loader: &LogLoader, text: &str) -> Vec<Document> {
+        let mut docs = Vec::new();
+        loader
+            .chunk_reader(text.as_bytes(), "test.log", |d| {
+                docs.push(d);
+                Ok(())
+            })
+            .unwrap();
+        docs
+    }
+
+    #[test]
+    fn test_time_window_grouping() {
+        let docs = chunks(&LogLoader::new(), LOG);
+        assert_eq!(docs.len(), 3);
+
+        assert_eq!(docs[0].metadata["start_ts"], "2024-01-15T10:00:00Z");
+        assert_eq!(docs[0].metadata["end_ts"], "2024-01-15T10:04:59Z");
+        assert_eq!(docs[0].metadata["line_range"], "1-6");
+        assert_eq!(docs[1].metadata["line_range"], "7-7");
+        assert_eq!(docs[2].metadata["start_ts"], "2024-01-15T10:20:00Z");
+    }
+
+    #[test]
+    fn test_stack_trace_stays_attached() {
+        let loader = LogLoader::new().without_window().with_max_lines(2);
+        let docs = chunks(&loader, LOG);
+
+        let error_chunk = docs
+            .iter()
+            .find(|d| d.content.contains("request failed"))
+            .unwrap();
+        assert!(error_chunk.content.contains("Main.java:3"));
+        assert!(docs.iter().all(|d| !d.content.starts_with("    at")));
+    }
+
+    #[test]
+    fn test_max_lines_grouping() {
+        let loader = LogLoader::new().without_window().with_max_lines(1);
+        let docs = chunks(&loader, LOG);
+
+        // One chunk per timestamped entry
+        assert_eq!(docs.len(), 5);
+        assert_eq!(docs[1].metadata["line_range"], "2-5");
+    }
+
+    #[test]
+    fn test_builtin_syslog_and_nginx() {
+        let syslog = "Jan 15 10:00:00 host sshd[1]: accepted\nJan 15 10:10:00 host cron[2]: run\n";
+        let docs = chunks(&LogLoader::new(), syslog);
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].metadata["start_ts"], "Jan 15 10:00:00");
+
+        let nginx = "127.0.0.1 - - [10/Oct/2000:13:55:36 -0700] \"GET / HTTP/1.1\" 200 2326\n";
+        let docs = chunks(&LogLoader::new(), nginx);
+        assert_eq!(docs[0].metadata["start_ts"], "10/Oct/2000:13:55:36 -0700");
+    }
+
+    #[test]
+    fn test_custom_pattern() {
+        let pattern = TimestampPattern::new("epoch", r"^(?P<ts>\d{10}) ").unwrap();
+        let loader = LogLoader::new()
+            .with_patterns(vec![pattern])
+            .with_max_lines(1);
+        let docs = chunks(&loader, "1700000000 a\ncontinued\n1700000001 b\n");
+
+        assert_eq!(docs.len(), 2);
+        assert_eq!(docs[0].metadata["line_count"], "2");
+        assert!(TimestampPattern::new("bad", "(").is_err());
+    }
+
+    #[test]
+    fn test_gzip_transparent() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(LOG.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut temp_file = tempfile::Builder::new()
+            .suffix(".log.gz")
+            .tempfile()
+            .unwrap();
+        temp_file.write_all(&compressed).unwrap();
+
+        let docs = LogLoader::new()
+            .load_chunks(temp_file.path().to_str().unwrap())
+            .unwrap();
+        assert_eq!(docs.len(), 3);
+        assert!(docs[0].content.contains("java.lang.RuntimeException"));
+    }
+
+    #[test]
+    fn test_days_from_civil() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+    }
+}
@@ -0,0 +1,547 @@
+//! OpenAPI / Swagger specification loader
+//!
+//! Emits one document per API operation so retrieval lands on the endpoint
+//! that answers a question, rather than on a wall of raw YAML.
+
+use crate::{Document, DocumentLoader, LoaderError, LoaderOptions, Result};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// HTTP methods recognized as operations under a path item
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Guard against reference cycles (`A -> B -> A`)
+const MAX_REF_DEPTH: usize = 16;
+
+/// Loader for OpenAPI 3.x and Swagger 2.0 specifications (YAML or JSON)
+///
+/// Each operation (`GET /users/{id}`) becomes a [`Document`] whose content is
+/// built from its summary, description, parameters, request body and
+/// responses. Metadata includes `method`, `path`, `operation_id` and `tags`.
+/// Local `$ref`s (`#/components/...`) are resolved; external references are
+/// left unresolved and listed under the `unresolved_refs` metadata key.
+///
+/// # Example
+///
+/// ```no_run
+/// use vecstore_loaders::OpenApiLoader;
+///
+/// let loader = OpenApiLoader::new();
+/// for op in loader.load_operations("openapi.yaml")? {
+///     println!("{} {}", op.metadata["method"], op.metadata["path"]);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct OpenApiLoader {
+    /// Whether to include deprecated operations
+    include_deprecated: bool,
+}
+
+impl OpenApiLoader {
+    /// Create a new OpenAPI loader
+    pub fn new() -> Self {
+        Self {
+            include_deprecated: true,
+        }
+    }
+
+    /// Skip operations marked `deprecated: true`
+    pub fn without_deprecated(mut self) -> Self {
+        self.include_deprecated = false;
+        self
+    }
+
+    /// Load every operation in the spec as a separate document
+    pub fn load_operations(&self, source: &str) -> Result<Vec<Document>> {
+        let path = Path::new(source);
+
+        if !path.exists() {
+            return Err(LoaderError::InvalidPath(format!(
+                "File not found: {}",
+                source
+            )));
+        }
+
+        let raw = fs::read_to_string(path)?;
+        let is_json = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+
+        let spec: Value = if is_json {
+            serde_json::from_str(&raw)?
+        } else {
+            serde_yaml::from_str(&raw)
+                .map_err(|e| LoaderError::ParseError(format!("Invalid YAML: {}", e)))?
+        };
+
+        self.parse_operations(&spec, source)
+    }
+
+    /// Extract operations from an already-parsed spec
+    pub fn parse_operations(&self, spec: &Value, source: &str) -> Result<Vec<Document>> {
+        if spec.get("openapi").is_none() && spec.get("swagger").is_none() {
+            return Err(LoaderError::ParseError(
+                "Not an OpenAPI/Swagger document (missing `openapi` or `swagger` field)"
+                    .to_string(),
+            ));
+        }
+
+        let paths = spec
+            .get("paths")
+            .and_then(Value::as_object)
+            .ok_or_else(|| LoaderError::ParseError("Spec has no `paths` object".to_string()))?;
+
+        let api_title = spec.pointer("/info/title").and_then(Value::as_str);
+        let api_version = spec.pointer("/info/version").and_then(Value::as_str);
+
+        let mut documents = Vec::new();
+
+        for (path, item) in paths {
+            let mut resolver = RefResolver::new(spec);
+            let item = resolver.resolve(item);
+
+            let shared_params: Vec<Value> = item
+                .get("parameters")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+
+            for method in METHODS {
+                let Some(operation) = item.get(method) else {
+                    continue;
+                };
+
+                let mut resolver = RefResolver::new(spec);
+                let operation = resolver.resolve(operation);
+
+                let deprecated = operation
+                    .get("deprecated")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                if deprecated && !self.include_deprecated {
+                    continue;
+                }
+
+                let content =
+                    render_operation(method, path, &operation, &shared_params, &mut resolver);
+
+                let mut document = Document::new(content, source.to_string());
+                document.add_metadata("format", "openapi");
+                document.add_metadata("loader", "OpenApiLoader");
+                document.add_metadata("method", method.to_uppercase());
+                document.add_metadata("path", path.clone());
+
+                if let Some(operation_id) = operation.get("operationId").and_then(Value::as_str) {
+                    document.add_metadata("operation_id", operation_id);
+                }
+
+                let tags = string_list(operation.get("tags"));
+                if !tags.is_empty() {
+                    document.add_metadata("tags", tags.join(","));
+                }
+
+                if let Some(title) = api_title {
+                    document.add_metadata("api_title", title);
+                }
+                if let Some(version) = api_version {
+                    document.add_metadata("api_version", version);
+                }
+                if deprecated {
+                    document.add_metadata("deprecated", "true");
+                }
+                if !resolver.unresolved.is_empty() {
+                    let refs: Vec<_> = resolver.unresolved.into_iter().collect();
+                    document.add_metadata("unresolved_refs", refs.join(","));
+                }
+
+                documents.push(document);
+            }
+        }
+
+        Ok(documents)
+    }
+}
+
+impl Default for OpenApiLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocumentLoader for OpenApiLoader {
+    /// Load the whole spec as a single document (operations separated by
+    /// blank lines). Use [`OpenApiLoader::load_operations`] for one document
+    /// per operation.
+    fn load(&self, source: &str) -> Result<Document> {
+        let operations = self.load_operations(source)?;
+
+        let content = operations
+            .iter()
+            .map(|op| op.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n---\n\n");
+
+        let mut document = Document::new(content, source.to_string());
+        document.add_metadata("format", "openapi");
+        document.add_metadata("loader", "OpenApiLoader");
+        document.add_metadata("operation_count", operations.len().to_string());
+
+        Ok(document)
+    }
+
+    fn load_with_options(&self, source: &str, options: &LoaderOptions) -> Result<Document> {
+        if let Some(max_size) = options.max_size {
+            let file_size = fs::metadata(source)?.len() as usize;
+            if file_size > max_size {
+                return Err(LoaderError::FileTooLarge(file_size, max_size));
+            }
+        }
+
+        self.load(source)
+    }
+
+    fn name(&self) -> &str {
+        "OpenApiLoader"
+    }
+
+    fn supported_extensions(&self) -> &[&str] {
+        &["yaml", "yml", "json"]
+    }
+}
+
+/// Resolves `$ref` objects against the root of the same document
+struct RefResolver<'a> {
+    root: &'a Value,
+    /// External or dangling references encountered while resolving
+    unresolved: BTreeSet<String>,
+}
+
+impl<'a> RefResolver<'a> {
+    fn new(root: &'a Value) -> Self {
+        Self {
+            root,
+            unresolved: BTreeSet::new(),
+        }
+    }
+
+    /// Return a copy of `value` with every resolvable `$ref` inlined
+    fn resolve(&mut self, value: &Value) -> Value {
+        self.resolve_depth(value, 0)
+    }
+
+    fn resolve_depth(&mut self, value: &Value, depth: usize) -> Value {
+        match value {
+            Value::Object(map) => {
+                if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+                    return self
+                        .follow(reference, depth)
+                        .unwrap_or_else(|| value.clone());
+                }
+                Value::Object(
+                    map.iter()
+                        .map(|(k, v)| (k.clone(), self.resolve_depth(v, depth)))
+                        .collect(),
+                )
+            }
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|v| self.resolve_depth(v, depth)).collect())
+            }
+            other => other.clone(),
+        }
+    }
+
+    fn follow(&mut self, reference: &str, depth: usize) -> Option<Value> {
+        let pointer = match reference.strip_prefix('#') {
+            Some(pointer) if depth < MAX_REF_DEPTH => pointer,
+            _ => {
+                self.unresolved.insert(reference.to_string());
+                return None;
+            }
+        };
+
+        match self.root.pointer(pointer) {
+            Some(target) => Some(self.resolve_depth(target, depth + 1)),
+            None => {
+                self.unresolved.insert(reference.to_string());
+                None
+            }
+        }
+    }
+}
+
+/// Build the embeddable text for one operation
+fn render_operation(
+    method: &str,
+    path: &str,
+    operation: &Value,
+    shared_params: &[Value],
+    resolver: &mut RefResolver,
+) -> String {
+    let mut sections = vec![format!("{} {}", method.to_uppercase(), path)];
+
+    for key in ["summary", "description"] {
+        if let Some(text) = operation.get(key).and_then(Value::as_str) {
+            let text = text.trim();
+            if !text.is_empty() {
+                sections.push(text.to_string());
+            }
+        }
+    }
+
+    // Operation-level parameters override path-level ones with the same name+location
+    let own_params: Vec<Value> = operation
+        .get("parameters")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let mut params: Vec<Value> = shared_params
+        .iter()
+        .map(|p| resolver.resolve(p))
+        .filter(|p| !own_params.iter().any(|o| same_param(o, p)))
+        .collect();
+    params.extend(own_params);
+
+    let param_lines: Vec<String> = params.iter().filter_map(describe_param).collect();
+    if !param_lines.is_empty() {
+        sections.push(format!("Parameters:\n{}", param_lines.join("\n")));
+    }
+
+    if let Some(body) = operation.get("requestBody") {
+        let mut line = "Request body".to_string();
+        if body
+            .get("required")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+        {
+            line.push_str(" (required)");
+        }
+        if let Some(desc) = body.get("description").and_then(Value::as_str) {
+            line.push_str(": ");
+            line.push_str(desc.trim());
+        }
+        if let Some(content) = body.get("content").and_then(Value::as_object) {
+            let types: Vec<&str> = content.keys().map(String::as_str).collect();
+            if !types.is_empty() {
+                line.push_str(&format!(" [{}]", types.join(", ")));
+            }
+        }
+        sections.push(line);
+    }
+
+    if let Some(responses) = operation.get("responses").and_then(Value::as_object) {
+        let lines: Vec<String> = responses
+            .iter()
+            .map(
+                |(status, response)| match response.get("description").and_then(Value::as_str) {
+                    Some(desc) => format!("- {}: {}", status, desc.trim()),
+                    None => format!("- {}", status),
+                },
+            )
+            .collect();
+        if !lines.is_empty() {
+            sections.push(format!("Responses:\n{}", lines.join("\n")));
+        }
+    }
+
+    sections.join("\n\n")
+}
+
+fn describe_param(param: &Value) -> Option<String> {
+    let name = param.get("name").and_then(Value::as_str)?;
+    let location = param.get("in").and_then(Value::as_str).unwrap_or("query");
+    let required = param
+        .get("required")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let mut line = format!(
+        "- {} ({}{})",
+        name,
+        location,
+        if required { ", required" } else { "" }
+    );
+    if let Some(desc) = param.get("description").and_then(Value::as_str) {
+        line.push_str(": ");
+        line.push_str(desc.trim());
+    }
+    Some(line)
+}
+
+fn same_param(a: &Value, b: &Value) -> bool {
+    a.get("name").is_some() && a.get("name") == b.get("name") && a.get("in") == b.get("in")
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const SPEC: &str = r##"
+openapi: 3.0.0
+info:
+  title: Users API
+  version: "1.2"
+paths:
+  /users:
+    post:
+      operationId: createUser
+      tags: [users]
+      summary: Create a user
+      description: Registers a new user account.
+      requestBody:
+        required: true
+        description: The user to create
+        content:
+          application/json: {}
+      responses:
+        "201":
+          description: User created
+  /users/{id}:
+    parameters:
+      - $ref: "#/components/parameters/UserId"
+    get:
+      operationId: getUser
+      tags: [users, read]
+      summary: Fetch a user by id
+      parameters:
+        - name: expand
+          in: query
+          description: Related objects to include
+      responses:
+        "200":
+          $ref: "#/components/responses/UserResponse"
+        "404":
+          $ref: "other.yaml#/components/responses/NotFound"
+    delete:
+      deprecated: true
+      summary: Delete a user
+      responses:
+        "204":
+          description: Deleted
+components:
+  parameters:
+    UserId:
+      name: id
+      in: path
+      required: true
+      description: The user identifier
+  responses:
+    UserResponse:
+      description: The requested user
+"##;
+
+    fn parse(loader: &OpenApiLoader) -> Vec<Document> {
+        let spec: Value = serde_yaml::from_str(SPEC).unwrap();
+        loader.parse_operations(&spec, "openapi.yaml").unwrap()
+    }
+
+    #[test]
+    fn test_one_document_per_operation() {
+        let docs = parse(&OpenApiLoader::new());
+        assert_eq!(docs.len(), 3);
+
+        let create = &docs[0];
+        assert_eq!(create.metadata["method"], "POST");
+        assert_eq!(create.metadata["path"], "/users");
+        assert_eq!(create.metadata["operation_id"], "createUser");
+        assert_eq!(create.metadata["tags"], "users");
+        assert!(create.content.contains("Create a user"));
+        assert!(create
+            .content
+            .contains("Request body (required): The user to create"));
+        assert!(create.content.contains("- 201: User created"));
+    }
+
+    #[test]
+    fn test_local_refs_resolved() {
+        let docs = parse(&OpenApiLoader::new());
+        let get = docs.iter().find(|d| d.metadata["method"] == "GET").unwrap();
+
+        assert!(get.content.starts_with("GET /users/{id}"));
+        assert!(get
+            .content
+            .contains("- id (path, required): The user identifier"));
+        assert!(get
+            .content
+            .contains("- expand (query): Related objects to include"));
+        assert!(get.content.contains("- 200: The requested user"));
+        assert_eq!(get.metadata["tags"], "users,read");
+    }
+
+    #[test]
+    fn test_external_refs_noted() {
+        let docs = parse(&OpenApiLoader::new());
+        let get = docs.iter().find(|d| d.metadata["method"] == "GET").unwrap();
+
+        assert_eq!(
+            get.metadata["unresolved_refs"],
+            "other.yaml#/components/responses/NotFound"
+        );
+        assert!(get.content.contains("- 404"));
+    }
+
+    #[test]
+    fn test_without_deprecated() {
+        let docs = parse(&OpenApiLoader::new().without_deprecated());
+        assert_eq!(docs.len(), 2);
+        assert!(docs.iter().all(|d| d.metadata["method"] != "DELETE"));
+    }
+
+    #[test]
+    fn test_ref_cycle_terminates() {
+        let spec = serde_json::json!({
+            "openapi": "3.0.0",
+            "paths": { "/a": { "get": { "$ref": "#/paths/~1a/get" } } }
+        });
+        let docs = OpenApiLoader::new()
+            .parse_operations(&spec, "spec.json")
+            .unwrap();
+        assert_eq!(docs.len(), 1);
+        assert!(docs[0].metadata.contains_key("unresolved_refs"));
+    }
+
+    #[test]
+    fn test_load_json_file() {
+        let spec = serde_json::json!({
+            "swagger": "2.0",
+            "paths": { "/ping": { "get": { "summary": "Health check", "responses": { "200": { "description": "pong" } } } } }
+        });
+        let mut temp_file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        write!(temp_file, "{}", spec).unwrap();
+
+        let loader = OpenApiLoader::new();
+        let path = temp_file.path().to_str().unwrap();
+        let docs = loader.load_operations(path).unwrap();
+
+        assert_eq!(docs.len(), 1);
+        assert!(docs[0].content.contains("Health check"));
+        assert_eq!(loader.load(path).unwrap().metadata["operation_count"], "1");
+    }
+
+    #[test]
+    fn test_rejects_non_openapi() {
+        let spec = serde_json::json!({ "paths": {} });
+        assert!(OpenApiLoader::new()
+            .parse_operations(&spec, "x.json")
+            .is_err());
+    }
+}